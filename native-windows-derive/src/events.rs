@@ -6,10 +6,13 @@ use quote::{ToTokens};
 use std::collections::HashMap;
 
 
-/// A callback function definition
-struct CallbackFunction {
-    path: syn::Path,
-    args: Option<Punctuated<syn::Ident, Token![,]>>
+/// A callback function definition. Either a path with optional arguments (`TestApp::callback(SELF, 10)`) or an inline closure (`|app| app.callback()`)
+enum CallbackFunction {
+    Path {
+        path: syn::Path,
+        args: Option<Punctuated<syn::Expr, Token![,]>>
+    },
+    Closure(syn::ExprClosure)
 }
 
 impl Parse for CallbackFunction {
@@ -20,13 +23,17 @@ impl Parse for CallbackFunction {
             Ok(content)
         }
 
+        if input.peek(Token![|]) || input.peek(Token![||]) || input.peek(Token![move]) {
+            return Ok(CallbackFunction::Closure(input.parse()?));
+        }
+
         let path = input.parse()?;
         let args = match maybe_parse_parens(input) {
-            Ok(parse_buffer) => Some(parse_buffer.parse_terminated(syn::Ident::parse)?),
+            Ok(parse_buffer) => Some(parse_buffer.parse_terminated(syn::Expr::parse)?),
             Err(_) => None,
         };
 
-        Ok(CallbackFunction {
+        Ok(CallbackFunction::Path {
             path,
             args
         })
@@ -65,6 +72,14 @@ impl Parse for CallbackDef {
         };
 
         let _sep: Token![:] =  input.parse()?;
+
+        // A single callback can be written without the brackets. Ex: `OnButtonClick: |app| app.counter.increment()`
+        if !input.peek(syn::token::Bracket) {
+            let mut callbacks = Punctuated::new();
+            callbacks.push(input.parse()?);
+            return Ok(CallbackDef { field_name, callback_id, callbacks });
+        }
+
         let _bracket_token = bracketed!(content in input);
 
         Ok(CallbackDef {
@@ -90,11 +105,11 @@ impl Parse for CallbackDefinitions {
     }
 }
 
-/// Parsed callbacks for a event type
+/// Parsed callbacks for a event type. `function` is a path, or a closure in parentheses.
 #[derive(Debug)]
 struct EventCallback {
     member: syn::Expr,
-    path: syn::Path,
+    function: syn::Expr,
     args: Punctuated<syn::Expr, Token![,]>
 }
 
@@ -105,6 +120,9 @@ pub struct ControlEvents {
     callbacks: HashMap<syn::Pat, Vec<EventCallback>>,
     partials_callbacks: Vec<pm2::TokenStream>,
     callback_args_cache: HashMap<usize, syn::Expr>,
+
+    /// Errors in the callbacks, emitted as `compile_error!` in the generated code
+    errors: Vec<pm2::TokenStream>,
 }
 
 impl ControlEvents {
@@ -121,7 +139,8 @@ impl ControlEvents {
             handles: Vec::with_capacity(1),
             callbacks: HashMap::with_capacity(cap),
             partials_callbacks: Vec::with_capacity(6),
-            callback_args_cache: cache
+            callback_args_cache: cache,
+            errors: Vec::new(),
         }
     }
 
@@ -164,10 +183,29 @@ impl ControlEvents {
                 .or_insert(Vec::with_capacity(3));
 
             for cb_fn in callback_def.callbacks.iter() {
+                let (function, args) = match cb_fn {
+                    CallbackFunction::Path { path, args } => (
+                        syn::parse2(quote! { #path }).expect("Failed to generate event callback code"),
+                        map_callback_args(member, args, &self.callback_args_cache)
+                    ),
+                    CallbackFunction::Closure(closure) => {
+                        let args = match map_closure_args(member, closure, &self.callback_args_cache) {
+                            Ok(args) => args,
+                            Err(e) => {
+                                self.errors.push(e.to_compile_error());
+                                continue;
+                            }
+                        };
+
+                        let closure = annotate_closure(closure);
+                        (syn::parse2(quote! { (#closure) }).expect("Failed to generate event callback code"), args)
+                    },
+                };
+
                 let callback = EventCallback {
                     member: Self::parse_member(&callback_def.field_name, &member),
-                    path: cb_fn.path.clone(),
-                    args: map_callback_args(&member, &cb_fn.args, &self.callback_args_cache)
+                    function,
+                    args
                 };
 
                 evt_callbacks.push(callback);
@@ -190,6 +228,7 @@ impl ToTokens for ControlEvents {
 
     fn to_tokens(&self, tokens: &mut pm2::TokenStream) {
         let handles = &self.handles;
+        let errors = &self.errors;
 
         let mut pats: Vec<&syn::Pat> = Vec::with_capacity(self.callbacks.len());
         let partial_callbacks = &self.partials_callbacks;
@@ -202,6 +241,8 @@ impl ToTokens for ControlEvents {
         let events_tk = if self.partial {
             // There's no need to bind events handler in a partials
             quote! {
+                #(#errors)*
+
                 let evt_ui = self;
 
                 #( #partial_callbacks );*
@@ -213,6 +254,8 @@ impl ToTokens for ControlEvents {
             }
         } else {
             quote! {
+                #(#errors)*
+
                 let window_handles: &[&ControlHandle] = &[#(&ui.#handles.handle),*];
                 for handle in window_handles.iter() {
                     let evt_ui = Rc::downgrade(&inner);
@@ -250,17 +293,17 @@ impl<'a> ToTokens for EventCallbackCol<'a> {
             0 => quote!{ {} },
             1 => {
                 let member = &cb[0].member;
-                let path = &cb[0].path;
+                let function = &cb[0].function;
                 let args = &cb[0].args;
-                quote!{ if &_handle == &#member { #path(#args) } }
+                quote!{ if &_handle == &#member { #function(#args); } }
             }
             _ => {
                 
                 // Group callbacks by members
-                let mut members_callbacks: HashMap<&syn::Expr, Vec<(&syn::Path, &Args)>> = HashMap::new();
+                let mut members_callbacks: HashMap<&syn::Expr, Vec<(&syn::Expr, &Args)>> = HashMap::new();
                 for c in cb.iter() {
                     let mc = members_callbacks.entry(&c.member).or_insert(Vec::new());
-                    mc.push((&c.path, &c.args));
+                    mc.push((&c.function, &c.args));
                 }
 
                 let members: Vec<&&syn::Expr> = members_callbacks.keys().collect();
@@ -284,7 +327,7 @@ impl<'a> ToTokens for EventCallbackCol<'a> {
 
 
 type Args = Punctuated<syn::Expr, Token![,]>;
-struct PathArgs<'a> (&'a [(&'a syn::Path, &'a Args)]);
+struct PathArgs<'a> (&'a [(&'a syn::Expr, &'a Args)]);
 
 impl<'a> ToTokens for PathArgs<'a> {
 
@@ -316,7 +359,7 @@ fn find_events_attr(attrs: &[syn::Attribute]) -> Option<&syn::Attribute> {
 
 
 fn top_level_window(field: &syn::Field) -> bool {
-    static TOP_LEVEL: &'static [&'static str] = &["Window", "FancyWindow", "MessageWindow"];
+    static TOP_LEVEL: &'static [&'static str] = &["Window", "FancyWindow", "MessageWindow", "LayeredWindow"];
 
     match &field.ty {
         syn::Type::Path(p) => {
@@ -330,7 +373,7 @@ fn top_level_window(field: &syn::Field) -> bool {
 }
 
 
-fn map_callback_args(member: &syn::Ident, args: &Option<Punctuated<syn::Ident, Token![,]>>, cache: &HashMap<usize, syn::Expr>) -> Punctuated<syn::Expr, Token![,]> {
+fn map_callback_args(member: &syn::Ident, args: &Option<Punctuated<syn::Expr, Token![,]>>, cache: &HashMap<usize, syn::Expr>) -> Punctuated<syn::Expr, Token![,]> {
     let mut p = Punctuated::new();
     if args.is_none() {
         p.push(cache[&0].clone());
@@ -338,7 +381,16 @@ fn map_callback_args(member: &syn::Ident, args: &Option<Punctuated<syn::Ident, T
     }
 
     let values = ["SELF", "CTRL", "HANDLE", "EVT", "EVT_DATA", "RC_SELF"];
-    for a in args.as_ref().unwrap().iter() {
+    for arg in args.as_ref().unwrap().iter() {
+        // Any other expression than a single identifier (ex: a literal) is passed as is to the callback
+        let a = match arg {
+            syn::Expr::Path(path) if path.qself.is_none() && path.path.get_ident().is_some() => path.path.get_ident().unwrap(),
+            _ => {
+                p.push(arg.clone());
+                continue;
+            }
+        };
+
         let pos = values.iter().position(|v| &a == &v );
         match pos {
             Some(0) | Some(5) => { p.push(cache[&0].clone()); },
@@ -358,6 +410,49 @@ fn map_callback_args(member: &syn::Ident, args: &Option<Punctuated<syn::Ident, T
 }
 
 
+/// Closures receive the ui struct (`SELF`), and the event data (`EVT_DATA`) if they have a second parameter
+/// Returns an error pointing at the parameters of the closure if it has more than two parameters
+fn map_closure_args(member: &syn::Ident, closure: &syn::ExprClosure, cache: &HashMap<usize, syn::Expr>) -> syn::Result<Punctuated<syn::Expr, Token![,]>> {
+    let mut p = Punctuated::new();
+    match closure.inputs.len() {
+        0 => {},
+        1 => { p.push(cache[&0].clone()); },
+        2 => {
+            p.push(cache[&0].clone());
+            p.push(cache[&4].clone());
+        },
+        n => {
+            let msg = format!("Event closure for #{} has {} parameters. Closures accept the ui struct and, optionally, the event data.", member, n);
+            return Err(syn::Error::new_spanned(&closure.inputs, msg));
+        }
+    }
+
+    Ok(p)
+}
+
+
+/// The parameters of the closures must be typed, otherwise the closure body cannot be checked before the call
+fn annotate_closure(closure: &syn::ExprClosure) -> syn::ExprClosure {
+    let types: [syn::Type; 2] = [parse_quote!(&Self), parse_quote!(&EventData)];
+
+    let mut closure = closure.clone();
+    for (input, ty) in closure.inputs.iter_mut().zip(types.iter()) {
+        if let syn::Pat::Type(_) = input {
+            continue;
+        }
+
+        *input = syn::Pat::Type(syn::PatType {
+            attrs: Vec::new(),
+            pat: Box::new(input.clone()),
+            colon_token: Default::default(),
+            ty: Box::new(ty.clone())
+        });
+    }
+
+    closure
+}
+
+
 fn map_event_enum(ident: &syn::Ident) -> syn::Pat {
     let evt = ident.to_string();
     let pat = match &evt as &str {
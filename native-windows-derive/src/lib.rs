@@ -58,6 +58,11 @@ fn parse_base_names(d: &DeriveInput) -> BaseNames {
     }
 }
 
+/// Returns `true` if the struct has the `nwg_profile` attribute
+fn has_profile_attr(d: &DeriveInput) -> bool {
+    d.attrs.iter().any(|attr| attr.path.get_ident().map(|id| id == "nwg_profile").unwrap_or(false))
+}
+
 fn parse_ui_data(d: &DeriveInput) -> Option<&syn::DataStruct> {
     match &d.data {
         syn::Data::Struct(ds) => Some(ds),
@@ -152,6 +157,15 @@ nwg::TextInput::builder()
     .build(&mut data.text_edit);
 ```
 
+Controls that are rarely used can be created the first time they are accessed by using the `lazy` parameter
+on a `nwg::LazyControl` field. The parent is resolved when the UI is built, but the other parameters are evaluated
+when the control is created, so they must not borrow the UI struct.
+
+```
+#[nwg_control(lazy, text: "Advanced")]
+advanced_button: nwg::LazyControl<nwg::Button>,
+```
+
 ## Resources
 
 Use the `nwg_resource` to generate a resource from a struct field. It works the exact same way as `nwg_controls`. 
@@ -185,7 +199,25 @@ That's very limiting. For example, if the same callback is used by two different
  - **EVT**: Sends the event that was triggered. `&Event`
  - **EVT_DATA**: Sends the data of the event that was triggered. `&EventData`
 
-It's also possible to not use any parameters, ex: `TestApp::callback1()`. 
+It's also possible to not use any parameters, ex: `TestApp::callback1()`.
+
+Other expressions than these identifiers (ex: a literal or a constant path) are passed as is to the callback, ex: `TestApp::zoom(SELF, 2)`.
+
+## Inline closures
+
+Trivial callbacks can be written as closures instead of methods. A closure receives the ui struct `&UiStruct`
+and, if it has a second parameter, the data of the event `&EventData`. Callbacks that are alone can be written without the brackets.
+
+```
+struct TestApp {
+    #[nwg_control]
+    #[nwg_events(
+        OnButtonClick: |app| app.counter.increment(),
+        OnKeyPress: [|app, data| app.key_pressed(data.on_key()), TestApp::refresh]
+    )]
+    button: nwg::Button
+}
+```
 
 Different event types:
 
@@ -241,8 +273,24 @@ struct Ui {
 }
 ```
 
+## Profiling
+
+Add the `nwg_profile` attribute to the struct to measure the time spent building each control, resource and partial with `nwg::BuildProfile`.
+Without the attribute, no measure is generated. The timings are only recorded when native-windows-gui is built with the `build-profiling` feature.
+
+`nwg_profile` requires a version of native-windows-gui that defines `BuildProfile`.
+
+```
+#[derive(Default, NwgUi)]
+#[nwg_profile]
+pub struct SlowApp {
+    #[nwg_control]
+    window: nwg::Window,
+}
+```
+
 */
-#[proc_macro_derive(NwgUi, attributes(nwg_control, nwg_resource, nwg_events, nwg_layout, nwg_layout_item, nwg_partial))]
+#[proc_macro_derive(NwgUi, attributes(nwg_control, nwg_resource, nwg_events, nwg_layout, nwg_layout_item, nwg_partial, nwg_profile))]
 pub fn derive_ui(input: pm::TokenStream) -> pm::TokenStream {
     let base = parse_macro_input!(input as DeriveInput);
     let names = parse_base_names(&base);
@@ -261,7 +309,11 @@ pub fn derive_ui(input: pm::TokenStream) -> pm::TokenStream {
     let generics = quote! { #lt #generic_params #gt }; // <'a: 'b, T: Trait1, const C>
     let generic_names = quote! { #lt #generic_names #gt }; // <'a, T, C>
 
-    let ui = NwgUi::build(&ui_data, false);
+    let ui = NwgUi::build(&ui_data, false, has_profile_attr(&base));
+    let ui_profile = match has_profile_attr(&base) {
+        true => quote! { let _profile = BuildProfile::start(stringify!(#struct_name), "NativeUi"); },
+        false => quote! {}
+    };
     let controls = ui.controls();
     let resources = ui.resources();
     let partials = ui.partials();
@@ -293,6 +345,8 @@ pub fn derive_ui(input: pm::TokenStream) -> pm::TokenStream {
 
             impl #generics NativeUi<#ui_struct_name #generic_names> for #struct_name #generic_names #where_clause {
                 fn build_ui(mut data: Self) -> Result<#ui_struct_name #generic_names, NwgError> {
+                    #ui_profile
+
                     #resources
                     #controls
                     #partials
@@ -370,7 +424,7 @@ pub struct MyApp {
 ```
 
 */
-#[proc_macro_derive(NwgPartial, attributes(nwg_control, nwg_resource, nwg_events, nwg_layout, nwg_layout_item, nwg_partial))]
+#[proc_macro_derive(NwgPartial, attributes(nwg_control, nwg_resource, nwg_events, nwg_layout, nwg_layout_item, nwg_partial, nwg_profile))]
 pub fn derive_partial(input: pm::TokenStream) -> pm::TokenStream {
     let base = parse_macro_input!(input as DeriveInput);
 
@@ -389,7 +443,7 @@ pub fn derive_partial(input: pm::TokenStream) -> pm::TokenStream {
     let generic_names = quote! { #lt #generic_names #gt }; // <'a, T, C>
 
     let ui_data = parse_ui_data(&base).expect("NWG derive can only be implemented on structs");
-    let ui = NwgUi::build(&ui_data, true);
+    let ui = NwgUi::build(&ui_data, true, has_profile_attr(&base));
     let controls = ui.controls();
     let resources = ui.resources();
     let partials = ui.partials();
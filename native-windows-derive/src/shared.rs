@@ -2,20 +2,24 @@ use syn::parse::{Parse, ParseStream, ParseBuffer};
 use syn::punctuated::Punctuated;
 
 
+/// A `name: value` parameter. A parameter without value (ex: `lazy`) is parsed as `name: true`.
 #[derive(Debug)]
 pub struct Param {
     pub ident: syn::Ident,
-    pub sep: Token![:],
+    pub sep: Option<Token![:]>,
     pub e: syn::Expr,
 }
 
 impl Parse for Param {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        Ok(Param {
-            ident: input.parse()?,
-            sep: input.parse()?,
-            e: input.parse()?,
-        })
+        let ident = input.parse()?;
+        let sep: Option<Token![:]> = input.parse()?;
+        let e = match sep.is_some() {
+            true => input.parse()?,
+            false => syn::parse_quote!(true)
+        };
+
+        Ok(Param { ident, sep, e })
     }
 }
 
@@ -4,11 +4,11 @@ use crate::events::ControlEvents;
 use crate::shared::Parameters;
 
 const TOP_LEVEL: &'static [&'static str] = &[
-    "Window", "MessageWindow", "ExternCanvas"
+    "Window", "MessageWindow", "ExternCanvas", "LayeredWindow"
 ];
 
 const AUTO_PARENT: &'static [&'static str] = &[
-    "Window", "TabsContainer", "Tab", "MessageWindow", "ExternCanvas"
+    "Window", "TabsContainer", "Tab", "MessageWindow", "ExternCanvas", "ScrollPanel", "MdiChild"
 ];
 
 
@@ -26,6 +26,9 @@ struct NwgControl<'a> {
 
     // First value if the parent order, second value is the insert order
     weight: [u16; 2],
+
+    // If the control is wrapped in a `LazyControl`
+    lazy: bool,
 }
 
 impl<'a> NwgControl<'a> {
@@ -38,8 +41,7 @@ impl<'a> NwgControl<'a> {
         )
     }
 
-    fn parse_type(field: &syn::Field) -> syn::Ident {
-        // Check for `ty` in nwg_control
+    fn parse_params(field: &syn::Field) -> Parameters {
         let nwg_control = |attr: &&syn::Attribute| {
             attr.path.get_ident()
               .map(|id| id == "nwg_control" )
@@ -51,11 +53,15 @@ impl<'a> NwgControl<'a> {
             None => unreachable!()
         };
 
-        let params: Parameters = match syn::parse2(attr.tokens.clone()) {
+        match syn::parse2(attr.tokens.clone()) {
             Ok(p) => p,
             Err(e) => panic!("Failed to parse field #{}: {}", field.ident.as_ref().unwrap(), e)
-        };
+        }
+    }
 
+    fn parse_type(field: &syn::Field) -> syn::Ident {
+        // Check for `ty` in nwg_control
+        let params = NwgControl::parse_params(field);
         match params.params.iter().find(|p| p.ident == "ty").map(|p| &p.e) {
             Some(syn::Expr::Path(p)) => match p.path.segments.last().map(|seg| seg.ident.clone()) {
                 Some(ty) => { return ty; }
@@ -64,13 +70,34 @@ impl<'a> NwgControl<'a> {
             _ => {}
         }
         
-        // Use field type
-        match &field.ty {
-            syn::Type::Path(p) => match p.path.segments.last() {
-                Some(seg) => seg.ident.clone(),
-                None => panic!("Impossible to parse type for field {:?}. Try specifying it in the nwg_control attribute.", field.ident)
+        // Use field type. For lazy controls, use the type wrapped in `LazyControl<T>`
+        let ty = match &field.ty {
+            syn::Type::Path(p) => p.path.segments.last(),
+            _ => None
+        };
+
+        match ty {
+            Some(seg) if seg.ident == "LazyControl" => match &seg.arguments {
+                syn::PathArguments::AngleBracketed(args) => match args.args.first() {
+                    Some(syn::GenericArgument::Type(syn::Type::Path(p))) => match p.path.segments.last() {
+                        Some(seg) => seg.ident.clone(),
+                        None => panic!("Impossible to parse type for field {:?}. Try specifying it in the nwg_control attribute.", field.ident)
+                    },
+                    _ => panic!("Impossible to parse type for field {:?}. Try specifying it in the nwg_control attribute.", field.ident)
+                },
+                _ => panic!("Impossible to parse type for field {:?}. Try specifying it in the nwg_control attribute.", field.ident)
             },
-            _ => panic!("Impossible to parse type for field {:?}. Try specifying it in the nwg_control attribute.", field.ident)
+            Some(seg) => seg.ident.clone(),
+            None => panic!("Impossible to parse type for field {:?}. Try specifying it in the nwg_control attribute.", field.ident)
+        }
+    }
+
+    fn parse_lazy(field: &syn::Field) -> bool {
+        let params = NwgControl::parse_params(field);
+        match params.params.iter().find(|p| p.ident == "lazy").map(|p| &p.e) {
+            Some(syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Bool(b), .. })) => b.value,
+            Some(_) => panic!("The lazy parameter of field {:?} must be a boolean", field.ident),
+            None => false
         }
     }
 
@@ -264,6 +291,17 @@ impl<'a> NwgPartial<'a> {
 }
 
 
+/// Measures the build of a control, a resource or a partial when the struct has the `nwg_profile` attribute
+fn build_profile(profile: bool, member: &syn::Ident, ty: &syn::Ident) -> pm2::TokenStream {
+    if !profile {
+        return pm2::TokenStream::new();
+    }
+
+    let name = member.to_string();
+    let kind = ty.to_string();
+    quote! { let _profile = BuildProfile::start(#name, #kind); }
+}
+
 pub struct NwgUiControls<'a>(&'a NwgUi<'a>);
 
 impl<'a> ToTokens for NwgUiControls<'a> {
@@ -271,6 +309,7 @@ impl<'a> ToTokens for NwgUiControls<'a> {
     fn to_tokens(&self, tokens: &mut pm2::TokenStream) {
 
         struct ControlGen<'b> {
+            profile: bool,
             item: &'b NwgControl<'b>
         }
 
@@ -281,10 +320,52 @@ impl<'a> ToTokens for NwgUiControls<'a> {
                 let member = item.id;
                 let names = &item.names;
                 let values = &item.values;
+                let profile = build_profile(self.profile, member, ty);
+
+                if item.lazy {
+                    // The parent is converted to a handle now because the builder function cannot borrow `data`
+                    let parent_index = names.iter().position(|n| n == "parent");
+                    let (names, values): (Vec<_>, Vec<_>) = names.iter().zip(values.iter())
+                        .filter(|(n, _)| *n != "parent")
+                        .unzip();
+
+                    let control_tk = match parent_index {
+                        Some(i) => {
+                            let parent = &item.values[i];
+                            quote! {
+                                {
+                                    let lazy_parent: ControlHandle = (#parent).into();
+                                    data.#member.set_builder(move |out| {
+                                        #profile
+                                        #ty::builder()
+                                            #(.#names(#values))*
+                                            .parent(lazy_parent)
+                                            .build(out)
+                                    });
+                                }
+                            }
+                        },
+                        None => quote! {
+                            data.#member.set_builder(move |out| {
+                                #profile
+                                #ty::builder()
+                                    #(.#names(#values))*
+                                    .build(out)
+                            });
+                        }
+                    };
+
+                    control_tk.to_tokens(tokens);
+                    return;
+                }
+
                 let control_tk = quote! {
-                    #ty::builder()
-                        #(.#names(#values))*
-                        .build(&mut data.#member)?;
+                    {
+                        #profile
+                        #ty::builder()
+                            #(.#names(#values))*
+                            .build(&mut data.#member)?;
+                    }
                 };
 
                 control_tk.to_tokens(tokens);
@@ -293,7 +374,7 @@ impl<'a> ToTokens for NwgUiControls<'a> {
 
         let ui = &self.0;
         let controls: Vec<ControlGen> = ui.controls.iter()
-            .map(|item| ControlGen { item })
+            .map(|item| ControlGen { profile: ui.profile, item })
             .collect();
 
         let controls_tk = quote! {
@@ -312,6 +393,7 @@ impl<'a> ToTokens for NwgUiResources<'a> {
     fn to_tokens(&self, tokens: &mut pm2::TokenStream) {
         
         struct ResourceGen<'b> {
+            profile: bool,
             item: &'b NwgResource<'b>
         }
 
@@ -322,10 +404,14 @@ impl<'a> ToTokens for NwgUiResources<'a> {
                 let member = item.id;
                 let names = &item.names;
                 let values = &item.values;
+                let profile = build_profile(self.profile, member, ty);
                 let resource_tk = quote! {
-                    #ty::builder()
-                        #(.#names(#values))*
-                        .build(&mut data.#member)?;
+                    {
+                        #profile
+                        #ty::builder()
+                            #(.#names(#values))*
+                            .build(&mut data.#member)?;
+                    }
                 };
 
                 resource_tk.to_tokens(tokens);
@@ -334,7 +420,7 @@ impl<'a> ToTokens for NwgUiResources<'a> {
 
         let ui = &self.0;
         let resources: Vec<ResourceGen> = ui.resources.iter()
-            .map(|item| ResourceGen { item })
+            .map(|item| ResourceGen { profile: ui.profile, item })
             .collect();
 
         let resources_tk = quote! {
@@ -441,6 +527,7 @@ impl<'a> ToTokens for NwgUiPartials<'a> {
     fn to_tokens(&self, tokens: &mut pm2::TokenStream) {
 
         struct PartialGen<'b> {
+            profile: bool,
             item: &'b NwgPartial<'b>
         }
 
@@ -450,14 +537,21 @@ impl<'a> ToTokens for NwgUiPartials<'a> {
                 let ty = &i.ty;
                 let id = &i.id;
                 let parent = &i.parent;
+                let profile = build_profile(self.profile, id, ty);
 
                 let partial_tk = if parent.is_none() {
                     quote! {
-                        #ty::build_partial::<&Window>(&mut data.#id, None)?;
+                        {
+                            #profile
+                            #ty::build_partial::<&Window>(&mut data.#id, None)?;
+                        }
                     }
                 } else {
                     quote! {
-                        #ty::build_partial(&mut data.#id, Some(&data.#parent))?;
+                        {
+                            #profile
+                            #ty::build_partial(&mut data.#id, Some(&data.#parent))?;
+                        }
                     }
                 };
                 
@@ -467,7 +561,7 @@ impl<'a> ToTokens for NwgUiPartials<'a> {
 
         let ui = &self.0;
         let partials: Vec<PartialGen> = ui.partials.iter()
-            .map(|item| PartialGen { item })
+            .map(|item| PartialGen { profile: ui.profile, item })
             .collect();
 
         let partials_tk = quote! {
@@ -486,11 +580,12 @@ pub struct NwgUi<'a> {
     layouts: Vec<NwgLayout<'a>>,
     partials: Vec<NwgPartial<'a>>,
     events: ControlEvents,
+    profile: bool,
 }
 
 impl<'a> NwgUi<'a> {
 
-    pub fn build(data: &'a syn::DataStruct, partial: bool) -> NwgUi<'a> {
+    pub fn build(data: &'a syn::DataStruct, partial: bool, profile: bool) -> NwgUi<'a> {
         let named_fields = match &data.fields {
             syn::Fields::Named(n) => &n.named,
             _ => panic!("Ui structure must have named fields")
@@ -510,8 +605,13 @@ impl<'a> NwgUi<'a> {
             if NwgControl::valid(field) {
                 let id = field.ident.as_ref().unwrap();
                 let ty = NwgControl::parse_type(field);
+                let lazy = NwgControl::parse_lazy(field);
                 let (names, values) = crate::controls::parameters(field, "nwg_control");
 
+                if lazy && TOP_LEVEL.iter().any(|top| ty == *top) {
+                    panic!("Top level window {} cannot be lazy", id);
+                }
+
                 let f = NwgControl {
                     id,
                     parent_id: None,
@@ -521,6 +621,7 @@ impl<'a> NwgUi<'a> {
                     names,
                     values,
                     weight: [0, field_pos as u16],
+                    lazy,
                 };
 
                 events.add_top_level_handle(field);
@@ -658,7 +759,7 @@ impl<'a> NwgUi<'a> {
             a.cmp(&b)
         });
 
-        NwgUi { controls, resources, layouts, partials, events }
+        NwgUi { controls, resources, layouts, partials, events, profile }
     }
 
     pub fn controls(&self) -> NwgUiControls {
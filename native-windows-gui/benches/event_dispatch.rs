@@ -0,0 +1,128 @@
+/*!
+    Measures the cost of the events dispatcher and counts the heap allocations it makes.
+
+    Run with `cargo bench --bench event_dispatch`. The benchmark fails (exit code 1) if dispatching
+    one of the common events allocates memory.
+*/
+use native_windows_gui as nwg;
+use winapi::shared::minwindef::{MAKELONG, WPARAM, LPARAM, UINT};
+use winapi::shared::windef::HWND;
+use winapi::um::winuser::{SendMessageW, NMHDR, WM_MOUSEMOVE, WM_PAINT, WM_TIMER, WM_COMMAND, WM_NOTIFY, WM_KEYDOWN, BN_CLICKED};
+use winapi::um::commctrl::NM_CUSTOMDRAW;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use std::cell::Cell;
+use std::rc::Rc;
+
+const ITERATIONS: usize = 100_000;
+
+
+/// Wraps the system allocator to count the allocations
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+
+struct Scenario {
+    name: &'static str,
+    hwnd: HWND,
+    msg: UINT,
+    w: WPARAM,
+    l: LPARAM,
+}
+
+/// Sends the message of the scenario `ITERATIONS` times and returns the time per message (in ns) and the number of allocations
+fn run(scenario: &Scenario) -> (f64, usize) {
+    // Warm up
+    for _ in 0..100 {
+        unsafe { SendMessageW(scenario.hwnd, scenario.msg, scenario.w, scenario.l); }
+    }
+
+    let allocations = ALLOCATIONS.load(Ordering::SeqCst);
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        unsafe { SendMessageW(scenario.hwnd, scenario.msg, scenario.w, scenario.l); }
+    }
+
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::SeqCst) - allocations;
+
+    (elapsed.as_nanos() as f64 / ITERATIONS as f64, allocations)
+}
+
+fn main() {
+    nwg::init().expect("Failed to init Native Windows GUI");
+
+    let mut window = nwg::Window::default();
+    nwg::Window::builder()
+        .flags(nwg::WindowFlags::WINDOW)
+        .title("Event dispatch benchmark")
+        .build(&mut window)
+        .unwrap();
+
+    let mut button = nwg::Button::default();
+    nwg::Button::builder()
+        .text("Button")
+        .parent(&window)
+        .build(&mut button)
+        .unwrap();
+
+    let count = Rc::new(Cell::new(0usize));
+    let handler_count = count.clone();
+    let handler = nwg::full_bind_event_handler(&window.handle, move |evt, _data, _handle| {
+        match evt {
+            nwg::Event::Unknown => {},
+            _ => handler_count.set(handler_count.get() + 1),
+        }
+    });
+
+    let window_hwnd = window.handle.hwnd().unwrap();
+    let button_hwnd = button.handle.hwnd().unwrap();
+    let mut notify = NMHDR { hwndFrom: button_hwnd, idFrom: 0, code: NM_CUSTOMDRAW };
+
+    let scenarios = [
+        Scenario { name: "WM_MOUSEMOVE", hwnd: window_hwnd, msg: WM_MOUSEMOVE, w: 0, l: MAKELONG(10, 10) as LPARAM },
+        Scenario { name: "WM_PAINT", hwnd: window_hwnd, msg: WM_PAINT, w: 0, l: 0 },
+        Scenario { name: "WM_TIMER", hwnd: window_hwnd, msg: WM_TIMER, w: 1, l: 0 },
+        Scenario { name: "WM_KEYDOWN", hwnd: window_hwnd, msg: WM_KEYDOWN, w: 0x41, l: 0 },
+        Scenario { name: "WM_COMMAND", hwnd: window_hwnd, msg: WM_COMMAND, w: MAKELONG(0, BN_CLICKED as u16) as u32 as WPARAM, l: button_hwnd as LPARAM },
+        Scenario { name: "WM_NOTIFY", hwnd: window_hwnd, msg: WM_NOTIFY, w: 0, l: &mut notify as *mut NMHDR as LPARAM },
+    ];
+
+    let mut failed = false;
+
+    println!("{:<14} {:>12} {:>16}", "message", "ns/event", "allocations");
+    for scenario in scenarios.iter() {
+        let (ns, allocations) = run(scenario);
+        println!("{:<14} {:>12.1} {:>16}", scenario.name, ns, allocations);
+
+        if allocations > 0 {
+            failed = true;
+        }
+    }
+
+    println!("{} events handled", count.get());
+
+    nwg::unbind_event_handler(&handler);
+
+    if failed {
+        eprintln!("The events dispatcher allocated memory while dispatching a common event");
+        std::process::exit(1);
+    }
+}
@@ -57,9 +57,9 @@ impl PartialDemo {
         self.frame3.set_visible(false);
 
         let layout = &self.layout;
-        if layout.has_child(&self.frame1) { layout.remove_child(&self.frame1); }
-        if layout.has_child(&self.frame2) { layout.remove_child(&self.frame2); }
-        if layout.has_child(&self.frame3) { layout.remove_child(&self.frame3); }
+        if layout.has_child(&self.frame1) { layout.remove_child(&self.frame1).unwrap(); }
+        if layout.has_child(&self.frame2) { layout.remove_child(&self.frame2).unwrap(); }
+        if layout.has_child(&self.frame3) { layout.remove_child(&self.frame3).unwrap(); }
 
         use nwg::stretch::{geometry::Size, style::{Style, Dimension as D}};
         let mut style = Style::default();
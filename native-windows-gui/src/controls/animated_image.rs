@@ -0,0 +1,606 @@
+use winapi::shared::windef::{HWND, HBITMAP, RECT};
+use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_CHILD, WS_CLIPSIBLINGS};
+use winapi::um::wingdi::DeleteObject;
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
+use crate::win32::base_helper::check_hwnd;
+use crate::win32::image_decoder::{self as img, Animation};
+use crate::{NwgError, RawEventHandler, ImageSource, unbind_raw_event_handler};
+use super::{ControlBase, ControlHandle};
+use std::{mem, ptr, rc::Rc, cell::RefCell};
+
+const NOT_BOUND: &'static str = "AnimatedImage is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: AnimatedImage handle is not HWND!";
+
+const FRAME_TIMER_ID: u32 = 0x61F0;
+
+
+bitflags! {
+    /**
+        The animated image flags
+
+        * NONE:     No flags. Equivalent to a invisible control.
+        * VISIBLE:  The control is immediatly visible after creation
+        * DISABLED: The control is disabled
+    */
+    pub struct AnimatedImageFlags: u32 {
+        const NONE = 0;
+        const VISIBLE = WS_VISIBLE;
+        const DISABLED = WS_DISABLED;
+    }
+}
+
+/// Internal state shared with the animated image event handler
+struct AnimatedImageState {
+    frames: Vec<(HBITMAP, u32)>,
+    image_size: (i32, i32),
+
+    /// Loop count read from the image file
+    file_loop_count: u32,
+
+    /// Loop count set by the user. Overrides `file_loop_count`.
+    loop_count: Option<u32>,
+
+    current: usize,
+    loops_done: u32,
+    playing: bool,
+    background_color: Option<[u8; 3]>,
+}
+
+impl AnimatedImageState {
+
+    fn effective_loop_count(&self) -> u32 {
+        self.loop_count.unwrap_or(self.file_loop_count)
+    }
+
+    fn clear(&mut self) {
+        for &(bitmap, _) in self.frames.iter() {
+            unsafe { DeleteObject(bitmap as _); }
+        }
+
+        self.frames.clear();
+        self.image_size = (0, 0);
+        self.file_loop_count = 1;
+        self.current = 0;
+        self.loops_done = 0;
+    }
+
+}
+
+impl Default for AnimatedImageState {
+    fn default() -> AnimatedImageState {
+        AnimatedImageState {
+            frames: Vec::new(),
+            image_size: (0, 0),
+            file_loop_count: 1,
+            loop_count: None,
+            current: 0,
+            loops_done: 0,
+            playing: false,
+            background_color: None,
+        }
+    }
+}
+
+impl Drop for AnimatedImageState {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+/**
+An animated image displays the frames of a multi-frame image (such as an animated GIF) with the delays stored in the file.
+
+The frames are decoded with WIC and composed (frame offsets, transparency and disposal methods) when the image is loaded.
+The animation is played as many times as specified in the file (the `NETSCAPE2.0` extension of GIFs). This can be
+overridden with `set_loop_count`. When the playback ends, the last frame stays displayed.
+
+Formats whose WIC codec exposes a single frame (such as PNG, including animated PNG) are displayed as a still image.
+
+The image is centered in the control and scaled down (keeping its aspect ratio) if it does not fit.
+
+Requires the `animated-image` feature.
+
+**Builder parameters:**
+  * `parent`:           **Required.** The animated image parent container.
+  * `size`:             The animated image size.
+  * `position`:         The animated image position.
+  * `flags`:            A combination of the AnimatedImageFlags values.
+  * `source_file`:      The image to load if it is a file.
+  * `source_bin`:       The image to load if it is a binary blob. For example using `include_bytes!("loading.gif")`.
+  * `loop_count`:       Overrides the number of times the animation is played. `Some(0)` loops forever.
+  * `autoplay`:         If the animation starts playing when the control is built. Defaults to `true`.
+  * `background_color`: The background color of the control. Defaults to the system button face color.
+
+**Control events:**
+  * `MousePress(_)`: Generic mouse press events on the control
+  * `OnMouseMove`: Generic mouse mouse event
+
+```rust
+use native_windows_gui as nwg;
+fn build_spinner(image: &mut nwg::AnimatedImage, window: &nwg::Window) {
+    nwg::AnimatedImage::builder()
+        .size((32, 32))
+        .source_file(Some("loading.gif"))
+        .loop_count(Some(0))
+        .parent(window)
+        .build(image);
+}
+```
+*/
+#[derive(Default)]
+pub struct AnimatedImage {
+    pub handle: ControlHandle,
+    state: Rc<RefCell<AnimatedImageState>>,
+    handler0: RefCell<Option<RawEventHandler>>,
+}
+
+impl AnimatedImage {
+
+    pub fn builder<'a>() -> AnimatedImageBuilder<'a> {
+        AnimatedImageBuilder {
+            size: (100, 100).into(),
+            position: (0, 0).into(),
+            flags: None,
+            source_file: None,
+            source_bin: None,
+            loop_count: None,
+            autoplay: true,
+            background_color: None,
+            parent: None,
+        }
+    }
+
+    /// Loads an image file, replacing the current image. The playback is stopped on the first frame.
+    pub fn load_file(&self, path: &str) -> Result<(), NwgError> {
+        let animation = unsafe { img::animation_from_file(path)? };
+        self.set_animation(animation);
+        Ok(())
+    }
+
+    /// Loads an image from memory, replacing the current image. The playback is stopped on the first frame.
+    pub fn load_bin(&self, data: &[u8]) -> Result<(), NwgError> {
+        let animation = unsafe { img::animation_from_memory(data)? };
+        self.set_animation(animation);
+        Ok(())
+    }
+
+    /// Loads the frames of an image source created by an `ImageDecoder`, replacing the current image.
+    /// The playback is stopped on the first frame.
+    pub fn set_source(&self, source: &ImageSource) -> Result<(), NwgError> {
+        let animation = unsafe { img::decode_animation(&*source.decoder)? };
+        self.set_animation(animation);
+        Ok(())
+    }
+
+    /// Removes the image from the control
+    pub fn clear(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::kill_timer(handle, FRAME_TIMER_ID);
+
+        let mut state = self.state.borrow_mut();
+        state.clear();
+        state.playing = false;
+        redraw(handle);
+    }
+
+    /// Starts or resumes the animation. If the playback had ended, the animation restarts from the first frame.
+    /// Does nothing if the image has less than 2 frames.
+    pub fn play(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        if state.frames.len() < 2 || state.playing {
+            return;
+        }
+
+        let loop_count = state.effective_loop_count();
+        if loop_count != 0 && state.loops_done >= loop_count {
+            state.current = 0;
+            state.loops_done = 0;
+            redraw(handle);
+        }
+
+        state.playing = true;
+        wh::start_timer(handle, FRAME_TIMER_ID, state.frames[state.current].1);
+    }
+
+    /// Pauses the animation on the current frame
+    pub fn pause(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::kill_timer(handle, FRAME_TIMER_ID);
+        self.state.borrow_mut().playing = false;
+    }
+
+    /// Pauses the animation and goes back to the first frame
+    pub fn stop(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::kill_timer(handle, FRAME_TIMER_ID);
+
+        let mut state = self.state.borrow_mut();
+        state.playing = false;
+        state.current = 0;
+        state.loops_done = 0;
+        redraw(handle);
+    }
+
+    /// Returns `true` if the animation is playing
+    pub fn playing(&self) -> bool {
+        self.state.borrow().playing
+    }
+
+    /// Returns the number of frames of the image. `0` if the control is empty.
+    pub fn frame_count(&self) -> usize {
+        self.state.borrow().frames.len()
+    }
+
+    /// Returns the index of the displayed frame
+    pub fn frame(&self) -> usize {
+        self.state.borrow().current
+    }
+
+    /// Displays the frame at `index`. If the animation is playing, it continues from this frame.
+    /// Panics if `index` is out of bounds.
+    pub fn set_frame(&self, index: usize) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        if index >= state.frames.len() {
+            panic!("Frame index {} is out of bounds", index);
+        }
+
+        state.current = index;
+        if state.playing {
+            wh::start_timer(handle, FRAME_TIMER_ID, state.frames[index].1);
+        }
+
+        redraw(handle);
+    }
+
+    /// Returns the number of times the animation is played. `0` loops forever.
+    pub fn loop_count(&self) -> u32 {
+        self.state.borrow().effective_loop_count()
+    }
+
+    /// Overrides the number of times the animation is played. `Some(0)` loops forever.
+    /// `None` uses the value stored in the image file.
+    pub fn set_loop_count(&self, count: Option<u32>) {
+        let mut state = self.state.borrow_mut();
+        state.loop_count = count;
+        state.loops_done = 0;
+    }
+
+    /// Returns the size (in pixels) of the image, or `None` if the control is empty
+    pub fn image_size(&self) -> Option<(u32, u32)> {
+        let state = self.state.borrow();
+        match state.frames.is_empty() {
+            true => None,
+            false => Some((state.image_size.0 as u32, state.image_size.1 as u32))
+        }
+    }
+
+    /// Returns true if the control user can interact with the control, return false otherwise
+    pub fn enabled(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_enabled(handle) }
+    }
+
+    /// Enable or disable the control
+    pub fn set_enabled(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_enabled(handle, v) }
+    }
+
+    /// Returns true if the control is visible to the user. Will return true even if the
+    /// control is outside of the parent client view (ex: at the position (10000, 10000))
+    pub fn visible(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_visibility(handle) }
+    }
+
+    /// Show or hide the control to the user
+    pub fn set_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_visibility(handle, v) }
+    }
+
+    /// Returns the size of the animated image in the parent window
+    pub fn size(&self) -> (u32, u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_size(handle) }
+    }
+
+    /// Sets the size of the animated image in the parent window
+    pub fn set_size(&self, x: u32, y: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_size(handle, x, y, false) }
+    }
+
+    /// Returns the position of the animated image in the parent window
+    pub fn position(&self) -> (i32, i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_position(handle) }
+    }
+
+    /// Sets the position of the animated image in the parent window
+    pub fn set_position(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_position(handle, x, y) }
+    }
+
+    /// Winapi class name used during control creation
+    pub fn class_name(&self) -> &'static str {
+        "NWG_ANIMATED_IMAGE"
+    }
+
+    /// Winapi base flags used during window creation
+    pub fn flags(&self) -> u32 {
+        WS_VISIBLE
+    }
+
+    /// Winapi flags required by the control
+    pub fn forced_flags(&self) -> u32 {
+        WS_CHILD | WS_CLIPSIBLINGS
+    }
+
+    fn set_animation(&self, animation: Animation) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::kill_timer(handle, FRAME_TIMER_ID);
+
+        let mut state = self.state.borrow_mut();
+        state.clear();
+        state.frames = animation.frames;
+        state.image_size = (animation.size.0 as i32, animation.size.1 as i32);
+        state.file_loop_count = animation.loop_count;
+        state.playing = false;
+        redraw(handle);
+    }
+
+    /// Handles the painting and the frame timer
+    fn hook_events(&self) {
+        use crate::bind_raw_event_handler_inner;
+        use winapi::um::winuser::{WM_PAINT, WM_ERASEBKGND, WM_SIZE, WM_TIMER};
+
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let state = self.state.clone();
+
+        let handler = bind_raw_event_handler_inner(&self.handle, 0, move |hwnd, msg, w, _l| {
+            match msg {
+                WM_PAINT => {
+                    paint(hwnd, &state.borrow());
+                    return Some(0);
+                },
+                WM_ERASEBKGND => {
+                    // Everything is drawn in WM_PAINT
+                    return Some(1);
+                },
+                WM_SIZE => {
+                    redraw(hwnd);
+                },
+                WM_TIMER if w == FRAME_TIMER_ID as usize => {
+                    next_frame(hwnd, &mut state.borrow_mut());
+                    return Some(0);
+                },
+                _ => {}
+            }
+
+            None
+        });
+
+        *self.handler0.borrow_mut() = Some(handler.unwrap());
+    }
+
+}
+
+impl Drop for AnimatedImage {
+    fn drop(&mut self) {
+        let handler = self.handler0.borrow();
+        if let Some(h) = handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
+        self.handle.destroy();
+    }
+}
+
+impl PartialEq for AnimatedImage {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for AnimatedImage {}
+
+pub struct AnimatedImageBuilder<'a> {
+    size: Extent,
+    position: Extent,
+    flags: Option<AnimatedImageFlags>,
+    source_file: Option<&'a str>,
+    source_bin: Option<&'a [u8]>,
+    loop_count: Option<u32>,
+    autoplay: bool,
+    background_color: Option<[u8; 3]>,
+    parent: Option<ControlHandle>
+}
+
+impl<'a> AnimatedImageBuilder<'a> {
+
+    pub fn flags(mut self, flags: AnimatedImageFlags) -> AnimatedImageBuilder<'a> {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> AnimatedImageBuilder<'a> {
+        self.size = size.into();
+        self
+    }
+
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> AnimatedImageBuilder<'a> {
+        self.position = pos.into();
+        self
+    }
+
+    pub fn source_file(mut self, t: Option<&'a str>) -> AnimatedImageBuilder<'a> {
+        self.source_file = t;
+        self
+    }
+
+    pub fn source_bin(mut self, t: Option<&'a [u8]>) -> AnimatedImageBuilder<'a> {
+        self.source_bin = t;
+        self
+    }
+
+    pub fn loop_count(mut self, count: Option<u32>) -> AnimatedImageBuilder<'a> {
+        self.loop_count = count;
+        self
+    }
+
+    pub fn autoplay(mut self, autoplay: bool) -> AnimatedImageBuilder<'a> {
+        self.autoplay = autoplay;
+        self
+    }
+
+    pub fn background_color(mut self, color: Option<[u8;3]>) -> AnimatedImageBuilder<'a> {
+        self.background_color = color;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> AnimatedImageBuilder<'a> {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut AnimatedImage) -> Result<(), NwgError> {
+        let flags = self.flags.map(|f| f.bits()).unwrap_or(out.flags());
+
+        let parent = match self.parent {
+            Some(p) => Ok(p),
+            None => Err(NwgError::no_parent("AnimatedImage"))
+        }?;
+
+        *out = Default::default();
+
+        out.handle = ControlBase::build_hwnd()
+            .class_name(out.class_name())
+            .forced_flags(out.forced_flags())
+            .flags(flags)
+            .size(self.size)
+            .position(self.position)
+            .parent(Some(parent))
+            .build()?;
+
+        {
+            let mut state = out.state.borrow_mut();
+            state.loop_count = self.loop_count;
+            state.background_color = self.background_color;
+        }
+
+        out.hook_events();
+
+        if let Some(path) = self.source_file {
+            out.load_file(path)?;
+        } else if let Some(data) = self.source_bin {
+            out.load_bin(data)?;
+        }
+
+        if self.autoplay {
+            out.play();
+        }
+
+        Ok(())
+    }
+
+}
+
+/// Returns the size of the client area of the control in physical pixels
+fn client_size(handle: HWND) -> (i32, i32) {
+    use winapi::um::winuser::GetClientRect;
+
+    unsafe {
+        let mut rect: RECT = mem::zeroed();
+        GetClientRect(handle, &mut rect);
+        (rect.right - rect.left, rect.bottom - rect.top)
+    }
+}
+
+fn redraw(handle: HWND) {
+    use winapi::um::winuser::InvalidateRect;
+    unsafe { InvalidateRect(handle, ptr::null(), 0); }
+}
+
+/// Advances the animation to the next frame and schedules the following one
+fn next_frame(handle: HWND, state: &mut AnimatedImageState) {
+    if !state.playing || state.frames.len() < 2 {
+        wh::kill_timer(handle, FRAME_TIMER_ID);
+        return;
+    }
+
+    state.current += 1;
+    if state.current == state.frames.len() {
+        state.loops_done += 1;
+
+        let loop_count = state.effective_loop_count();
+        if loop_count != 0 && state.loops_done >= loop_count {
+            state.current = state.frames.len() - 1;
+            state.playing = false;
+            wh::kill_timer(handle, FRAME_TIMER_ID);
+            return;
+        }
+
+        state.current = 0;
+    }
+
+    wh::start_timer(handle, FRAME_TIMER_ID, state.frames[state.current].1);
+    redraw(handle);
+}
+
+/// Paints the current frame in a memory bitmap and copies the result on the screen
+fn paint(handle: HWND, state: &AnimatedImageState) {
+    use winapi::um::winuser::{BeginPaint, EndPaint, FillRect, GetSysColorBrush, PAINTSTRUCT, COLOR_BTNFACE};
+    use winapi::um::wingdi::{CreateCompatibleDC, CreateCompatibleBitmap, CreateSolidBrush, SelectObject, DeleteDC, BitBlt,
+        AlphaBlend, RGB, BLENDFUNCTION, AC_SRC_OVER, AC_SRC_ALPHA, SRCCOPY};
+
+    unsafe {
+        let mut ps: PAINTSTRUCT = mem::zeroed();
+        let hdc = BeginPaint(handle, &mut ps);
+        let (width, height) = client_size(handle);
+        let rect = RECT { left: 0, top: 0, right: width, bottom: height };
+
+        let buffer_dc = CreateCompatibleDC(hdc);
+        let buffer = CreateCompatibleBitmap(hdc, width.max(1), height.max(1));
+        let old_buffer = SelectObject(buffer_dc, buffer as _);
+
+        match state.background_color {
+            Some([r, g, b]) => {
+                let brush = CreateSolidBrush(RGB(r, g, b));
+                FillRect(buffer_dc, &rect, brush);
+                DeleteObject(brush as _);
+            },
+            None => {
+                FillRect(buffer_dc, &rect, GetSysColorBrush(COLOR_BTNFACE));
+            }
+        }
+
+        if let Some(&(bitmap, _)) = state.frames.get(state.current) {
+            let (iw, ih) = state.image_size;
+            let scale = (width as f64 / iw as f64).min(height as f64 / ih as f64).min(1.0);
+            let (sw, sh) = (((iw as f64) * scale).round().max(1.0) as i32, ((ih as f64) * scale).round().max(1.0) as i32);
+            let (x, y) = ((width - sw) / 2, (height - sh) / 2);
+
+            let image_dc = CreateCompatibleDC(hdc);
+            let old_image = SelectObject(image_dc, bitmap as _);
+
+            let blend = BLENDFUNCTION { BlendOp: AC_SRC_OVER, BlendFlags: 0, SourceConstantAlpha: 255, AlphaFormat: AC_SRC_ALPHA };
+            AlphaBlend(buffer_dc, x, y, sw, sh, image_dc, 0, 0, iw, ih, blend);
+
+            SelectObject(image_dc, old_image);
+            DeleteDC(image_dc);
+        }
+
+        BitBlt(hdc, 0, 0, width, height, buffer_dc, 0, 0, SRCCOPY);
+
+        SelectObject(buffer_dc, old_buffer);
+        DeleteObject(buffer as _);
+        DeleteDC(buffer_dc);
+
+        EndPaint(handle, &ps);
+    }
+}
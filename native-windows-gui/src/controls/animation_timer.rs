@@ -1,30 +1,41 @@
 use crate::controls::ControlHandle;
 use crate::NwgError;
 use crate::win32::window_helper as wh;
-use std::{thread, time::{Duration, Instant}, sync::{Mutex, Arc}};
+use std::{ptr, thread, time::{Duration, Instant}, sync::{Mutex, Arc}};
 
 use winapi::um::winuser::SendNotifyMessageW;
-use winapi::shared::minwindef::{WPARAM, LPARAM};
+use winapi::um::winnt::HANDLE;
+use winapi::shared::minwindef::{DWORD, WPARAM, LPARAM};
 use winapi::shared::windef::HWND;
 
 const NOT_BOUND: &'static str = "AnimationTimer is not yet bound to a winapi object";
 const BAD_HANDLE: &'static str = "INTERNAL ERROR: AnimationTimer handle is not Timer!";
 
+/// Not declared by winapi. Available since Windows 10 1803.
+const CREATE_WAITABLE_TIMER_HIGH_RESOLUTION: DWORD = 0x00000002;
+
+/// The frame duration used by the vsync timers when the desktop composition is not available
+const FALLBACK_FRAME: Duration = Duration::from_micros(16_667);
+
 lazy_static! {
     
     static ref THREAD_STATE: Arc<Mutex<AnimationThread>> = {
+        let waiter = FrameWaiter::new();
+
         let state = AnimationThread {
             timers: Vec::new(),
+            wake: waiter.wake as usize,
         };
 
         let state = Arc::new(Mutex::new(state));
         let shared_state = state.clone();
         
         thread::spawn(move || {
-            let sleep_time = Duration::from_millis(1);
-
             loop {
                 let mut state = shared_state.lock().unwrap();
+                let now = Instant::now();
+                let mut vsync = false;
+                let mut next_wake: Option<Duration> = None;
 
                 for (id, timer) in state.timers.iter_mut().enumerate() {
                     let timer = match timer.as_mut() {
@@ -35,11 +46,17 @@ lazy_static! {
                         None => { continue; }
                     };
 
-                    if timer.last_tick.elapsed() > timer.interval {
-                        AnimationThread::timer_tick(id as u32, timer.hwnd);
-                        timer.last_tick = Instant::now();
+                    let elapsed = now.duration_since(timer.last_tick);
+                    if timer.vsync || elapsed >= timer.interval {
+                        AnimationThread::timer_tick(id as u32, timer.hwnd, elapsed);
                         timer.current_tick += 1;
 
+                        // Keep the cadence of the interval, unless the timer is late by more than one tick
+                        timer.last_tick = match timer.vsync || elapsed >= timer.interval * 2 {
+                            true => now,
+                            false => timer.last_tick + timer.interval
+                        };
+
                         if Some(timer.current_tick) == timer.max_tick {
                             AnimationThread::timer_stop(id as u32, timer.hwnd);
                             timer.active = false;
@@ -50,15 +67,27 @@ lazy_static! {
                     }
 
                     if let Some(lf) = timer.lifetime {
-                        if timer.birthtime.elapsed() > lf {
+                        let age = now.duration_since(timer.birthtime);
+                        if age > lf {
                             AnimationThread::timer_stop(id as u32, timer.hwnd);
                             timer.active = false;
+                            continue;
+                        }
+
+                        next_wake = min_duration(next_wake, lf - age);
+                    }
+
+                    match timer.vsync {
+                        true => { vsync = true; },
+                        false => {
+                            let next_tick = (timer.last_tick + timer.interval).saturating_duration_since(now);
+                            next_wake = min_duration(next_wake, next_tick);
                         }
                     }
                 }
 
                 drop(state);
-                thread::sleep(sleep_time);
+                waiter.wait(vsync, next_wake);
             }
         });
 
@@ -75,17 +104,21 @@ struct InnerTimer {
     max_tick: Option<u64>,
     current_tick: u64,
     active: bool,
+    vsync: bool,
     hwnd: usize,
 }
 
 struct AnimationThread {
     timers: Vec<Option<InnerTimer>>,
+    /// Event that wakes the animation thread when the timers are changed
+    wake: usize,
 }
 
 impl AnimationThread {
 
     fn add_timer(inner: InnerTimer) -> u32 {
         let mut state = THREAD_STATE.lock().unwrap();
+        state.wake();
         
         let empty = state.timers
             .iter_mut()
@@ -106,15 +139,20 @@ impl AnimationThread {
 
     fn reset_timer(id: u32) {
         let mut state = THREAD_STATE.lock().unwrap();
+        state.wake();
+
         if let Some(Some(t)) = state.timers.get_mut(id as usize) {
             t.active = true;
             t.birthtime = Instant::now();
+            t.last_tick = Instant::now();
             t.current_tick = 0;
         }
     }
 
     fn update_timer(id: u32, interval: Option<Duration>, lifetime: Option<Option<Duration>>, max_tick: Option<Option<u64>>) {
         let mut state = THREAD_STATE.lock().unwrap();
+        state.wake();
+
         if let Some(Some(t)) = state.timers.get_mut(id as usize) {
             if let Some(v) = interval {
                 t.interval = v;
@@ -144,9 +182,17 @@ impl AnimationThread {
         }
     }
 
-    pub fn timer_tick(id: u32, hwnd: usize) {
+    fn wake(&self) {
+        use winapi::um::synchapi::SetEvent;
+        unsafe { SetEvent(self.wake as HANDLE); }
+    }
+
+    pub fn timer_tick(id: u32, hwnd: usize, elapsed: Duration) {
+        // The elapsed time is sent in microseconds. See `EventData::OnTimerTick`.
+        let micros = elapsed.as_micros().min(u32::max_value() as u128) as u32;
+
         unsafe {
-            SendNotifyMessageW(hwnd as HWND, wh::NWG_TIMER_TICK, id as WPARAM, hwnd as LPARAM);
+            SendNotifyMessageW(hwnd as HWND, wh::NWG_TIMER_TICK, id as WPARAM, micros as LPARAM);
         }
     }
 
@@ -158,6 +204,86 @@ impl AnimationThread {
 
 }
 
+/**
+    Puts the animation thread to sleep until the next tick. A high resolution waitable timer is used because
+    `thread::sleep` has the resolution of the system clock (about 15 ms by default).
+*/
+struct FrameWaiter {
+    timer: HANDLE,
+    wake: HANDLE,
+}
+
+unsafe impl Send for FrameWaiter {}
+
+impl FrameWaiter {
+
+    fn new() -> FrameWaiter {
+        use winapi::um::synchapi::{CreateWaitableTimerExW, CreateEventW};
+        use winapi::um::timeapi::timeBeginPeriod;
+        use winapi::um::winnt::TIMER_ALL_ACCESS;
+
+        unsafe {
+            let mut timer = CreateWaitableTimerExW(ptr::null_mut(), ptr::null(), CREATE_WAITABLE_TIMER_HIGH_RESOLUTION, TIMER_ALL_ACCESS);
+            if timer.is_null() {
+                // Before Windows 10 1803, the resolution of the waitable timers follows the resolution of the system clock
+                timer = CreateWaitableTimerExW(ptr::null_mut(), ptr::null(), 0, TIMER_ALL_ACCESS);
+                timeBeginPeriod(1);
+            }
+
+            let wake = CreateEventW(ptr::null_mut(), 0, 0, ptr::null());
+
+            FrameWaiter { timer, wake }
+        }
+    }
+
+    /**
+        Waits for the next composition frame if `vsync` is set, otherwise waits for `timeout`.
+        Returns early if the timers are changed. Without timeout, waits until the timers are changed.
+    */
+    fn wait(&self, vsync: bool, timeout: Option<Duration>) {
+        use winapi::um::dwmapi::DwmFlush;
+        use winapi::um::synchapi::{WaitForSingleObject, WaitForMultipleObjects, SetWaitableTimer};
+        use winapi::um::winbase::INFINITE;
+        use winapi::shared::winerror::S_OK;
+        use winapi::shared::ntdef::LARGE_INTEGER;
+
+        unsafe {
+            // `DwmFlush` returns after the next composition of the desktop, at the refresh rate of the monitor
+            if vsync && DwmFlush() == S_OK {
+                return;
+            }
+
+            let timeout = match (vsync, timeout) {
+                (true, t) => min_duration(t, FALLBACK_FRAME),
+                (false, t) => t,
+            };
+
+            match timeout {
+                Some(timeout) => {
+                    // A negative due time is relative, in units of 100 nanoseconds
+                    let mut due: LARGE_INTEGER = std::mem::zeroed();
+                    *due.QuadPart_mut() = -((timeout.as_nanos() / 100).max(1) as i64);
+                    SetWaitableTimer(self.timer, &due, 0, None, ptr::null_mut(), 0);
+
+                    let handles = [self.timer, self.wake];
+                    WaitForMultipleObjects(2, handles.as_ptr(), 0, INFINITE);
+                },
+                None => {
+                    WaitForSingleObject(self.wake, INFINITE);
+                }
+            }
+        }
+    }
+
+}
+
+fn min_duration(a: Option<Duration>, b: Duration) -> Option<Duration> {
+    match a {
+        Some(a) => Some(a.min(b)),
+        None => Some(b)
+    }
+}
+
 
 /**
 A timer is an invisible UI component that trigger the `OnTimerTick` event at the specified interval.
@@ -168,6 +294,10 @@ AnimationTimer is controlled from a singletion running in another thread. All in
 A timer still requires a top level window parent. If the top level window parent is destroyed, the timer becomes invalid.
 
 AnimationTimer replaces the default winapi timer. Please, for the love of god, do not use the default timer.
+The animation thread waits on a high resolution timer, so the ticks do not have the ~15 ms jitter of `WM_TIMER`.
+Each `OnTimerTick` event carries the time elapsed since the previous tick (`EventData::on_timer_tick`), which should be used to advance the animations.
+
+With `vsync`, the timer ticks once per frame of the display (at the refresh rate of the monitor) instead of following `interval`.
 
 **Builder parameters:**
     * `parent`:     **Required.** The timer parent container that will receive the timer event. Should be a top level window
@@ -175,9 +305,10 @@ AnimationTimer replaces the default winapi timer. Please, for the love of god, d
     * `lifetime`:   The timer should automatically stop after the selected Duration. Defaults to `None`.
     * `max_tick`:   The timer should automatically stop after sending X amount of OnTImerTick events. Defaults to `None`.
     * `active`:     If the timer should start right away. Default to `false`
+    * `vsync`:      If the timer should tick on each frame of the display instead of following the interval. Default to `false`
 
 **Control events:**
-    * `OnTimerTick`: When the timer ticks
+    * `OnTimerTick`: When the timer ticks. The event data is the time elapsed since the previous tick.
     * `OnTimerStop`: When the timer stops itself (due to max_tick_count or lifetime being reached, not user actions)
 
 ```
@@ -209,6 +340,7 @@ impl AnimationTimer {
             max_tick: None,
             lifetime: None,
             active: false,
+            vsync: false,
         }
     }
 
@@ -280,7 +412,8 @@ pub struct AnimationTimerBuilder {
     interval: Duration,
     max_tick: Option<u64>,
     lifetime: Option<Duration>,
-    active: bool
+    active: bool,
+    vsync: bool,
 }
 
 impl AnimationTimerBuilder {
@@ -310,6 +443,11 @@ impl AnimationTimerBuilder {
         self
     }
 
+    pub fn vsync(mut self, vsync: bool) -> AnimationTimerBuilder {
+        self.vsync = vsync;
+        self
+    }
+
     pub fn build(self, out: &mut AnimationTimer) -> Result<(), NwgError> {
         let parent = match self.parent {
             Some(p) => match p.hwnd() {
@@ -331,6 +469,7 @@ impl AnimationTimerBuilder {
             max_tick: self.max_tick,
             current_tick: 0,
             active: self.active,
+            vsync: self.vsync,
             hwnd: parent as usize,
         };
 
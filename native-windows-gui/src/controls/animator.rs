@@ -0,0 +1,169 @@
+use crate::controls::ControlHandle;
+use crate::win32::{animation, window::build_timer, window_helper as wh};
+use crate::{Easing, Property, NwgError};
+use winapi::um::winuser::PostMessageW;
+use winapi::shared::minwindef::{WPARAM, LPARAM};
+use std::{rc::Rc, cell::RefCell, time::Duration};
+
+const NOT_BOUND: &'static str = "Animator is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: Animator handle is not Timer!";
+
+
+/**
+An invisible component that animates the position, the size, or the opacity of controls with an easing curve.
+
+Each call to `animate` starts a new animation and returns its id. The property is updated about 60 times per second
+until it reaches its target value, then the animator raises a `OnAnimationEnd` event on its parent. Starting an
+animation on a property that is already animated replaces the previous animation.
+
+The frames of all the animations of a thread are scheduled by the same timer, so many controls can be animated
+at the same time without drifting apart. Stopped animations keep their current value and do not raise `OnAnimationEnd`.
+
+Requires the `animator` feature.
+
+**Builder parameters:**
+  * `parent`:   **Required.** The animator parent container that receives the `OnAnimationEnd` events. Should be a top level window
+
+**Control events:**
+  * `OnAnimationEnd`: When an animation reaches its target value. The event data is the id of the animation.
+
+```rust
+use native_windows_gui as nwg;
+use std::time::Duration;
+
+fn slide_in(animator: &nwg::Animator, panel: &nwg::Frame) -> u32 {
+    let (_, y) = panel.position();
+    animator.animate(panel, nwg::Property::Y, y + 200, y, Duration::from_millis(250), nwg::Easing::OutCubic)
+}
+
+fn build_animator(animator: &mut nwg::Animator, window: &nwg::Window) {
+    nwg::Animator::builder()
+        .parent(window)
+        .build(animator);
+}
+```
+*/
+#[derive(Default)]
+pub struct Animator {
+    pub handle: ControlHandle,
+    animations: Rc<RefCell<Vec<u32>>>,
+}
+
+impl Animator {
+
+    pub fn builder() -> AnimatorBuilder {
+        AnimatorBuilder {
+            parent: None
+        }
+    }
+
+    /**
+        Animates `property` of `control` from `from` to `to` in `duration`. The property is set to `from` right away.
+        Returns the id of the animation. The same id is sent with the `OnAnimationEnd` event.
+
+        Panics if `control` is not a window-like control.
+    */
+    pub fn animate<C: Into<ControlHandle>>(&self, control: C, property: Property, from: i32, to: i32, duration: Duration, easing: Easing) -> u32 {
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let (parent, animator_id) = self.handle.timer().expect(BAD_HANDLE);
+        let target = control.into().hwnd().expect("Control to be a window-like control");
+
+        let animations = self.animations.clone();
+        let on_end = move |id: u32| {
+            animations.borrow_mut().retain(|&a| a != id);
+            if wh::window_valid(parent) {
+                unsafe { PostMessageW(parent, wh::NWG_ANIMATION_END, animator_id as WPARAM, id as LPARAM); }
+            }
+        };
+
+        let id = animation::animate(target, property, from, to, duration, easing, Some(Box::new(on_end)))
+            .expect("Failed to create the animation scheduler");
+
+        self.animations.borrow_mut().push(id);
+
+        id
+    }
+
+    /// Stops an animation started by this animator. The property keeps its current value.
+    pub fn stop(&self, id: u32) {
+        let mut animations = self.animations.borrow_mut();
+        if animations.contains(&id) {
+            animation::stop(id);
+            animations.retain(|&a| a != id);
+        }
+    }
+
+    /// Stops all the animations started by this animator
+    pub fn stop_all(&self) {
+        for id in self.animations.borrow_mut().drain(..) {
+            animation::stop(id);
+        }
+    }
+
+    /**
+        Stops all the animations of `control`, including the animations started by other animators.
+
+        Panics if `control` is not a window-like control.
+    */
+    pub fn stop_control<C: Into<ControlHandle>>(&self, control: C) {
+        let target = control.into().hwnd().expect("Control to be a window-like control");
+        animation::stop_window(target);
+        self.animations.borrow_mut().retain(|&a| animation::running(a));
+    }
+
+    /// Returns `true` if the animation is running
+    pub fn running(&self, id: u32) -> bool {
+        self.animations.borrow().contains(&id) && animation::running(id)
+    }
+
+    /// Returns the number of running animations started by this animator
+    pub fn running_count(&self) -> usize {
+        self.animations.borrow().len()
+    }
+
+}
+
+impl Drop for Animator {
+    fn drop(&mut self) {
+        self.stop_all();
+    }
+}
+
+impl PartialEq for Animator {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for Animator {}
+
+pub struct AnimatorBuilder {
+    parent: Option<ControlHandle>,
+}
+
+impl AnimatorBuilder {
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> AnimatorBuilder {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut Animator) -> Result<(), NwgError> {
+        let parent = match self.parent {
+            Some(p) => match p.hwnd() {
+                Some(handle) => Ok(handle),
+                None => Err(NwgError::control_create("Wrong parent type"))
+            },
+            None => Err(NwgError::no_parent("Animator"))
+        }?;
+
+        // Creates the scheduler window now, so that `animate` cannot fail
+        animation::init()?;
+
+        *out = Default::default();
+        out.handle = unsafe { build_timer(parent, 0, true) };
+
+        Ok(())
+    }
+
+}
@@ -4,6 +4,7 @@ use crate::win32::{
     window_helper as wh,
     resources_helper as rh
 };
+use crate::win32::high_dpi::Extent;
 use crate::{NwgError, Font, Bitmap, Icon};
 use super::{ControlBase, ControlHandle};
 
@@ -83,8 +84,8 @@ impl Button {
     pub fn builder<'a>() -> ButtonBuilder<'a> {
         ButtonBuilder {
             text: "Button",
-            size: (100, 25),
-            position: (0, 0),
+            size: (100, 25).into(),
+            position: (0, 0).into(),
             enabled: true,
             flags: None,
             ex_flags: 0,
@@ -267,8 +268,8 @@ impl Drop for Button {
 
 pub struct ButtonBuilder<'a> {
     text: &'a str,
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     enabled: bool,
     flags: Option<ButtonFlags>,
     ex_flags: u32,
@@ -296,13 +297,13 @@ impl<'a> ButtonBuilder<'a> {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> ButtonBuilder<'a> {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> ButtonBuilder<'a> {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> ButtonBuilder<'a> {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> ButtonBuilder<'a> {
+        self.position = pos.into();
         self
     }
 
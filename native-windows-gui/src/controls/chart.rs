@@ -0,0 +1,1113 @@
+use winapi::shared::windef::HWND;
+use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_TABSTOP, WS_BORDER, WS_CHILD, WS_CLIPSIBLINGS};
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
+use crate::win32::base_helper::check_hwnd;
+use crate::win32::chart_renderer::{ChartRenderer, Rect, TextStyle, TextAlign, NMCHART, CHART_POINT_HOVER, CHART_POINT_LEAVE};
+use crate::{NwgError, RawEventHandler, unbind_raw_event_handler};
+use super::{ControlBase, ControlHandle};
+use std::{rc::Rc, cell::RefCell};
+
+const NOT_BOUND: &'static str = "Chart is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: Chart handle is not HWND!";
+
+/// Maximum distance (in DIPs) between the mouse and a line or scatter point for the point to be hovered
+const HIT_DISTANCE: f32 = 6.0;
+
+/// Fraction of the space between two x values taken by a group of bars
+const BAR_GROUP_WIDTH: f64 = 0.8;
+
+const BACKGROUND_COLOR: [u8; 3] = [255, 255, 255];
+const GRID_COLOR: [u8; 3] = [230, 230, 230];
+const AXIS_COLOR: [u8; 3] = [110, 110, 110];
+const TEXT_COLOR: [u8; 3] = [40, 40, 40];
+
+const PADDING: f32 = 8.0;
+
+
+bitflags! {
+    /**
+        The chart flags
+
+        * NONE:     No flags. Equivalent to a invisible chart.
+        * VISIBLE:  The chart is immediatly visible after creation
+        * DISABLED: The chart does not react to the mouse
+        * TAB_STOP: The chart can be selected using tab navigation
+        * BORDER:   The chart has a thin border
+    */
+    pub struct ChartFlags: u32 {
+        const NONE = 0;
+        const VISIBLE = WS_VISIBLE;
+        const DISABLED = WS_DISABLED;
+        const TAB_STOP = WS_TABSTOP;
+        const BORDER = WS_BORDER;
+    }
+}
+
+/// How the points of a chart series are drawn
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChartSeriesKind {
+    /// The points are joined by a line
+    Line,
+
+    /// Each point is a vertical bar starting at `y = 0`. The bars of the different bar series that share the same x value are drawn side by side.
+    Bar,
+
+    /// Each point is drawn as a dot
+    Scatter,
+}
+
+/// A named list of points displayed by a `Chart`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChartSeries {
+    /// The name of the series, displayed in the legend
+    pub name: String,
+    pub kind: ChartSeriesKind,
+    pub color: [u8; 3],
+    /// The `(x, y)` values of the series. Points with a value that is not finite are not drawn.
+    pub points: Vec<(f64, f64)>,
+}
+
+impl ChartSeries {
+
+    pub fn new<S: Into<String>>(name: S, kind: ChartSeriesKind, color: [u8; 3], points: Vec<(f64, f64)>) -> ChartSeries {
+        ChartSeries { name: name.into(), kind, color, points }
+    }
+
+    /// Creates a line series
+    pub fn line<S: Into<String>>(name: S, color: [u8; 3], points: Vec<(f64, f64)>) -> ChartSeries {
+        ChartSeries::new(name, ChartSeriesKind::Line, color, points)
+    }
+
+    /// Creates a bar series
+    pub fn bar<S: Into<String>>(name: S, color: [u8; 3], points: Vec<(f64, f64)>) -> ChartSeries {
+        ChartSeries::new(name, ChartSeriesKind::Bar, color, points)
+    }
+
+    /// Creates a scatter series
+    pub fn scatter<S: Into<String>>(name: S, color: [u8; 3], points: Vec<(f64, f64)>) -> ChartSeries {
+        ChartSeries::new(name, ChartSeriesKind::Scatter, color, points)
+    }
+
+}
+
+/// A data point of a chart. Returned by `Chart::hit_test` and sent with the `OnChartPointHover` event.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ChartPoint {
+    /// The index of the series in the chart
+    pub series: usize,
+    /// The index of the point in the series
+    pub index: usize,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Mapping between the chart values and the control area. Computed when the chart is painted.
+#[derive(Copy, Clone)]
+struct ChartLayout {
+    plot: Rect,
+    x_range: (f64, f64),
+    y_range: (f64, f64),
+    /// Width of a single bar in chart units
+    bar_width: f64,
+    /// Number of bar series
+    bar_count: usize,
+}
+
+impl ChartLayout {
+
+    fn to_screen(&self, x: f64, y: f64) -> (f32, f32) {
+        let [left, top, right, bottom] = self.plot;
+        let (x0, x1) = self.x_range;
+        let (y0, y1) = self.y_range;
+        let sx = left as f64 + (x - x0) / (x1 - x0) * (right - left) as f64;
+        let sy = bottom as f64 - (y - y0) / (y1 - y0) * (bottom - top) as f64;
+        (sx as f32, sy as f32)
+    }
+
+    /// Returns the rectangle of the bar of the point `(x, y)` of the `bar_index`-th bar series
+    fn bar_rect(&self, bar_index: usize, x: f64, y: f64) -> Rect {
+        let group = self.bar_width * self.bar_count as f64;
+        let start = x - group / 2.0 + self.bar_width * bar_index as f64;
+        let base = 0.0f64.max(self.y_range.0).min(self.y_range.1);
+
+        let (x0, y0) = self.to_screen(start, base);
+        let (x1, y1) = self.to_screen(start + self.bar_width, y);
+        [x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)]
+    }
+
+}
+
+struct ChartState {
+    renderer: Option<ChartRenderer>,
+    series: Vec<ChartSeries>,
+    x_range: Option<(f64, f64)>,
+    y_range: Option<(f64, f64)>,
+    title: String,
+    x_label: String,
+    y_label: String,
+    legend: bool,
+    layout: Option<ChartLayout>,
+    hover: Option<ChartPoint>,
+    tracking_mouse: bool,
+}
+
+impl Default for ChartState {
+    fn default() -> ChartState {
+        ChartState {
+            renderer: None,
+            series: Vec::new(),
+            x_range: None,
+            y_range: None,
+            title: String::new(),
+            x_label: String::new(),
+            y_label: String::new(),
+            legend: true,
+            layout: None,
+            hover: None,
+            tracking_mouse: false,
+        }
+    }
+}
+
+/**
+A chart displays one or more series of values as lines, bars or dots, with axes, a grid and a legend.
+
+The ranges of the axes are computed from the values of the series unless they are set with `set_x_range` and `set_y_range`.
+The ticks of the axes are placed on "round" values (1, 2 or 5 times a power of ten). Series can be updated at any time,
+for example with `push_point` to display the last values of a measure.
+
+Hovering a data point with the mouse highlights it, displays its value and raises a `OnChartPointHover` event.
+Positions (for example in `hit_test`) are in logical pixels relative to the top left corner of the chart.
+
+Chart is implemented as a custom control drawn with Direct2D.
+
+Requires the `chart` feature.
+
+**Builder parameters:**
+  * `parent`:   **Required.** The chart parent container.
+  * `size`:     The chart size.
+  * `position`: The chart position.
+  * `title`:    The title displayed above the chart.
+  * `x_label`:  The label of the horizontal axis.
+  * `y_label`:  The label of the vertical axis.
+  * `x_range`:  The range of the horizontal axis. `None` (the default) computes the range from the values.
+  * `y_range`:  The range of the vertical axis. `None` (the default) computes the range from the values.
+  * `legend`:   If the legend is displayed. Defaults to `true`.
+  * `series`:   The initial series of the chart.
+  * `flags`:    A combination of the ChartFlags values.
+
+**Control events:**
+  * `OnChartPointHover`: When the mouse moves over a data point. The event data is a `EventData::OnChartPoint`.
+  * `OnChartPointLeave`: When the mouse leaves the data point that was hovered
+  * `MousePress(_)`: Generic mouse press events on the chart
+  * `OnMouseMove`: Generic mouse mouse event
+  * `OnMouseWheel`: Generic mouse wheel event
+
+```rust
+use native_windows_gui as nwg;
+fn build_chart(chart: &mut nwg::Chart, window: &nwg::Window) {
+    let cpu = (0..60).map(|i| (i as f64, 20.0 + (i as f64 / 5.0).sin() * 10.0)).collect();
+
+    nwg::Chart::builder()
+        .size((400, 300))
+        .title("CPU usage")
+        .x_label("Time (s)")
+        .y_label("%")
+        .y_range(Some((0.0, 100.0)))
+        .series(vec![nwg::ChartSeries::line("CPU", [31, 119, 180], cpu)])
+        .parent(window)
+        .build(chart);
+}
+```
+*/
+#[derive(Default)]
+pub struct Chart {
+    pub handle: ControlHandle,
+    state: Rc<RefCell<ChartState>>,
+    handler0: RefCell<Option<RawEventHandler>>,
+}
+
+impl Chart {
+
+    pub fn builder<'a>() -> ChartBuilder<'a> {
+        ChartBuilder {
+            size: (300, 200).into(),
+            position: (0, 0).into(),
+            title: "",
+            x_label: "",
+            y_label: "",
+            x_range: None,
+            y_range: None,
+            legend: true,
+            series: Vec::new(),
+            flags: None,
+            parent: None,
+        }
+    }
+
+    /// Adds a series to the chart and returns its index
+    pub fn add_series(&self, series: ChartSeries) -> usize {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        state.series.push(series);
+        state.hover = None;
+        redraw(handle);
+
+        state.series.len() - 1
+    }
+
+    /// Returns a copy of the series at `index`, or `None` if the index is out of bounds
+    pub fn series(&self, index: usize) -> Option<ChartSeries> {
+        self.state.borrow().series.get(index).cloned()
+    }
+
+    /// Returns the number of series in the chart
+    pub fn series_count(&self) -> usize {
+        self.state.borrow().series.len()
+    }
+
+    /// Replaces the points of the series at `index`.
+    /// Panics if `index` is out of bounds.
+    pub fn set_points(&self, index: usize, points: Vec<(f64, f64)>) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        state.series[index].points = points;
+        state.hover = None;
+        redraw(handle);
+    }
+
+    /// Appends a point to the series at `index`. If `limit` is set, the oldest points are removed
+    /// so that the series keeps at most `limit` points.
+    /// Panics if `index` is out of bounds.
+    pub fn push_point(&self, index: usize, point: (f64, f64), limit: Option<usize>) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+
+        let points = &mut state.series[index].points;
+        points.push(point);
+        if let Some(limit) = limit {
+            if points.len() > limit {
+                let excess = points.len() - limit;
+                points.drain(0..excess);
+            }
+        }
+
+        state.hover = None;
+        redraw(handle);
+    }
+
+    /// Removes the series at `index` and returns it.
+    /// Panics if `index` is out of bounds.
+    pub fn remove_series(&self, index: usize) -> ChartSeries {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        let series = state.series.remove(index);
+        state.hover = None;
+        redraw(handle);
+
+        series
+    }
+
+    /// Removes all the series of the chart
+    pub fn clear(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        state.series.clear();
+        state.hover = None;
+        redraw(handle);
+    }
+
+    /// Sets the range of the horizontal axis. `None` computes the range from the values of the series.
+    pub fn set_x_range(&self, range: Option<(f64, f64)>) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow_mut().x_range = range;
+        redraw(handle);
+    }
+
+    /// Returns the range of the horizontal axis set by `set_x_range`
+    pub fn x_range(&self) -> Option<(f64, f64)> {
+        self.state.borrow().x_range
+    }
+
+    /// Sets the range of the vertical axis. `None` computes the range from the values of the series.
+    pub fn set_y_range(&self, range: Option<(f64, f64)>) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow_mut().y_range = range;
+        redraw(handle);
+    }
+
+    /// Returns the range of the vertical axis set by `set_y_range`
+    pub fn y_range(&self) -> Option<(f64, f64)> {
+        self.state.borrow().y_range
+    }
+
+    /// Returns the title of the chart
+    pub fn title(&self) -> String {
+        self.state.borrow().title.clone()
+    }
+
+    /// Sets the title of the chart. An empty title is not displayed.
+    pub fn set_title<'a>(&self, title: &'a str) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow_mut().title = title.to_string();
+        redraw(handle);
+    }
+
+    /// Sets the label of the horizontal axis. An empty label is not displayed.
+    pub fn set_x_label<'a>(&self, label: &'a str) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow_mut().x_label = label.to_string();
+        redraw(handle);
+    }
+
+    /// Sets the label of the vertical axis. An empty label is not displayed.
+    pub fn set_y_label<'a>(&self, label: &'a str) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow_mut().y_label = label.to_string();
+        redraw(handle);
+    }
+
+    /// Returns `true` if the legend is displayed
+    pub fn legend_visible(&self) -> bool {
+        self.state.borrow().legend
+    }
+
+    /// Shows or hides the legend
+    pub fn set_legend_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow_mut().legend = v;
+        redraw(handle);
+    }
+
+    /// Returns the data point at the position `(x, y)` of the chart, or `None` if there is no point there.
+    /// Always returns `None` before the chart is painted for the first time.
+    pub fn hit_test(&self, x: i32, y: i32) -> Option<ChartPoint> {
+        let state = self.state.borrow();
+        hit_test(&state, (x as f32, y as f32))
+    }
+
+    /// Returns the data point under the mouse, if any
+    pub fn hovered_point(&self) -> Option<ChartPoint> {
+        self.state.borrow().hover
+    }
+
+    /// Returns true if the control currently has the keyboard focus
+    pub fn focus(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_focus(handle) }
+    }
+
+    /// Sets the keyboard focus on the chart
+    pub fn set_focus(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_focus(handle); }
+    }
+
+    /// Returns true if the control user can interact with the control, return false otherwise
+    pub fn enabled(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_enabled(handle) }
+    }
+
+    /// Enable or disable the control
+    pub fn set_enabled(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_enabled(handle, v) }
+    }
+
+    /// Returns true if the control is visible to the user. Will return true even if the
+    /// control is outside of the parent client view (ex: at the position (10000, 10000))
+    pub fn visible(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_visibility(handle) }
+    }
+
+    /// Show or hide the control to the user
+    pub fn set_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_visibility(handle, v) }
+    }
+
+    /// Returns the size of the chart in the parent window
+    pub fn size(&self) -> (u32, u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_size(handle) }
+    }
+
+    /// Sets the size of the chart in the parent window
+    pub fn set_size(&self, x: u32, y: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_size(handle, x, y, false) }
+    }
+
+    /// Returns the position of the chart in the parent window
+    pub fn position(&self) -> (i32, i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_position(handle) }
+    }
+
+    /// Sets the position of the chart in the parent window
+    pub fn set_position(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_position(handle, x, y) }
+    }
+
+    /// Winapi class name used during control creation
+    pub fn class_name(&self) -> &'static str {
+        "NWG_CHART"
+    }
+
+    /// Winapi base flags used during window creation
+    pub fn flags(&self) -> u32 {
+        WS_VISIBLE
+    }
+
+    /// Winapi flags required by the control
+    pub fn forced_flags(&self) -> u32 {
+        WS_CHILD | WS_CLIPSIBLINGS
+    }
+
+    /// Handles the painting and the hit-testing of the chart
+    fn hook_events(&self) {
+        use crate::bind_raw_event_handler_inner;
+        use winapi::um::winuser::{WM_PAINT, WM_ERASEBKGND, WM_SIZE, WM_MOUSEMOVE, WM_MOUSELEAVE, TME_LEAVE, TRACKMOUSEEVENT, PAINTSTRUCT};
+        use winapi::um::winuser::{TrackMouseEvent, BeginPaint, EndPaint};
+        use winapi::shared::minwindef::{LOWORD, HIWORD};
+        use std::mem;
+
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let state = self.state.clone();
+
+        let handler = bind_raw_event_handler_inner(&self.handle, 0, move |hwnd, msg, _w, l| {
+            match msg {
+                WM_PAINT => {
+                    unsafe {
+                        let mut ps: PAINTSTRUCT = mem::zeroed();
+                        BeginPaint(hwnd, &mut ps);
+                        paint(hwnd, &mut state.borrow_mut());
+                        EndPaint(hwnd, &ps);
+                    }
+                    return Some(0);
+                },
+                WM_ERASEBKGND => {
+                    // Everything is drawn in WM_PAINT
+                    return Some(1);
+                },
+                WM_SIZE => {
+                    if let Ok(mut state) = state.try_borrow_mut() {
+                        if let Some(renderer) = state.renderer.as_mut() {
+                            renderer.resize(hwnd);
+                        }
+                        redraw(hwnd);
+                    }
+                },
+                WM_MOUSEMOVE => {
+                    // Mouse coordinates are signed
+                    let (x, y) = (LOWORD(l as u32) as i16 as i32, HIWORD(l as u32) as i16 as i32);
+
+                    let changed = {
+                        let mut state = state.borrow_mut();
+
+                        if !state.tracking_mouse {
+                            let mut track = TRACKMOUSEEVENT {
+                                cbSize: mem::size_of::<TRACKMOUSEEVENT>() as u32,
+                                dwFlags: TME_LEAVE,
+                                hwndTrack: hwnd,
+                                dwHoverTime: 0,
+                            };
+                            state.tracking_mouse = unsafe { TrackMouseEvent(&mut track) != 0 };
+                        }
+
+                        let point = match state.renderer.as_ref() {
+                            Some(renderer) => renderer.to_dips(x, y),
+                            None => (x as f32, y as f32)
+                        };
+
+                        let hover = hit_test(&state, point);
+                        match hover != state.hover {
+                            true => Some((state.hover.is_some(), hover)),
+                            false => None
+                        }
+                    };
+
+                    // The state must not be borrowed when the events are sent because the user handler may access the chart
+                    if let Some((had_hover, hover)) = changed {
+                        state.borrow_mut().hover = hover;
+                        redraw(hwnd);
+
+                        if had_hover {
+                            notify(hwnd, CHART_POINT_LEAVE, None);
+                        }
+
+                        if hover.is_some() {
+                            notify(hwnd, CHART_POINT_HOVER, hover);
+                        }
+                    }
+                },
+                WM_MOUSELEAVE => {
+                    let hover = {
+                        let mut state = state.borrow_mut();
+                        state.tracking_mouse = false;
+                        state.hover.take()
+                    };
+
+                    if hover.is_some() {
+                        redraw(hwnd);
+                        notify(hwnd, CHART_POINT_LEAVE, None);
+                    }
+                },
+                _ => {}
+            }
+
+            None
+        });
+
+        *self.handler0.borrow_mut() = Some(handler.unwrap());
+    }
+
+}
+
+impl Drop for Chart {
+    fn drop(&mut self) {
+        let handler = self.handler0.borrow();
+        if let Some(h) = handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
+        self.handle.destroy();
+    }
+}
+
+impl PartialEq for Chart {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for Chart {}
+
+pub struct ChartBuilder<'a> {
+    size: Extent,
+    position: Extent,
+    title: &'a str,
+    x_label: &'a str,
+    y_label: &'a str,
+    x_range: Option<(f64, f64)>,
+    y_range: Option<(f64, f64)>,
+    legend: bool,
+    series: Vec<ChartSeries>,
+    flags: Option<ChartFlags>,
+    parent: Option<ControlHandle>
+}
+
+impl<'a> ChartBuilder<'a> {
+
+    pub fn flags(mut self, flags: ChartFlags) -> ChartBuilder<'a> {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> ChartBuilder<'a> {
+        self.size = size.into();
+        self
+    }
+
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> ChartBuilder<'a> {
+        self.position = pos.into();
+        self
+    }
+
+    pub fn title(mut self, title: &'a str) -> ChartBuilder<'a> {
+        self.title = title;
+        self
+    }
+
+    pub fn x_label(mut self, label: &'a str) -> ChartBuilder<'a> {
+        self.x_label = label;
+        self
+    }
+
+    pub fn y_label(mut self, label: &'a str) -> ChartBuilder<'a> {
+        self.y_label = label;
+        self
+    }
+
+    pub fn x_range(mut self, range: Option<(f64, f64)>) -> ChartBuilder<'a> {
+        self.x_range = range;
+        self
+    }
+
+    pub fn y_range(mut self, range: Option<(f64, f64)>) -> ChartBuilder<'a> {
+        self.y_range = range;
+        self
+    }
+
+    pub fn legend(mut self, legend: bool) -> ChartBuilder<'a> {
+        self.legend = legend;
+        self
+    }
+
+    pub fn series(mut self, series: Vec<ChartSeries>) -> ChartBuilder<'a> {
+        self.series = series;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> ChartBuilder<'a> {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut Chart) -> Result<(), NwgError> {
+        let flags = self.flags.map(|f| f.bits()).unwrap_or(out.flags());
+
+        let parent = match self.parent {
+            Some(p) => Ok(p),
+            None => Err(NwgError::no_parent("Chart"))
+        }?;
+
+        let renderer = ChartRenderer::new()?;
+
+        *out = Default::default();
+
+        out.handle = ControlBase::build_hwnd()
+            .class_name(out.class_name())
+            .forced_flags(out.forced_flags())
+            .flags(flags)
+            .size(self.size)
+            .position(self.position)
+            .parent(Some(parent))
+            .build()?;
+
+        {
+            let mut state = out.state.borrow_mut();
+            state.renderer = Some(renderer);
+            state.series = self.series;
+            state.x_range = self.x_range;
+            state.y_range = self.y_range;
+            state.title = self.title.to_string();
+            state.x_label = self.x_label.to_string();
+            state.y_label = self.y_label.to_string();
+            state.legend = self.legend;
+        }
+
+        out.hook_events();
+
+        Ok(())
+    }
+
+}
+
+fn redraw(handle: HWND) {
+    use winapi::um::winuser::InvalidateRect;
+    unsafe { InvalidateRect(handle, std::ptr::null(), 0); }
+}
+
+/// Sends a `NMCHART` notification to the parent of the chart
+fn notify(handle: HWND, code: u32, point: Option<ChartPoint>) {
+    use winapi::um::winuser::{NMHDR, WM_NOTIFY};
+
+    let point = point.unwrap_or(ChartPoint { series: 0, index: 0, x: 0.0, y: 0.0 });
+    let notification = NMCHART {
+        hdr: NMHDR { hwndFrom: handle, idFrom: 0, code },
+        series: point.series,
+        index: point.index,
+        x: point.x,
+        y: point.y,
+    };
+
+    let parent = wh::get_window_parent(handle);
+    wh::send_message(parent, WM_NOTIFY, 0, &notification as *const NMCHART as _);
+}
+
+/// Returns the smallest and the largest finite x and y values of the series
+fn data_bounds(series: &[ChartSeries]) -> Option<((f64, f64), (f64, f64))> {
+    let mut bounds: Option<((f64, f64), (f64, f64))> = None;
+
+    let points = series.iter()
+        .flat_map(|s| s.points.iter())
+        .filter(|(x, y)| x.is_finite() && y.is_finite());
+
+    for &(x, y) in points {
+        bounds = Some(match bounds {
+            None => ((x, x), (y, y)),
+            Some(((x0, x1), (y0, y1))) => ((x0.min(x), x1.max(x)), (y0.min(y), y1.max(y)))
+        });
+    }
+
+    bounds
+}
+
+/// Returns the smallest distance between two distinct x values of the bar series
+fn bar_spacing(series: &[ChartSeries]) -> f64 {
+    let mut xs: Vec<f64> = series.iter()
+        .filter(|s| s.kind == ChartSeriesKind::Bar)
+        .flat_map(|s| s.points.iter().map(|p| p.0))
+        .filter(|x| x.is_finite())
+        .collect();
+
+    xs.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    xs.dedup();
+
+    xs.windows(2)
+        .map(|w| w[1] - w[0])
+        .fold(None, |min: Option<f64>, d| Some(min.map(|m| m.min(d)).unwrap_or(d)))
+        .unwrap_or(1.0)
+}
+
+/// Returns a "round" step (1, 2 or 5 times a power of ten) that splits `span` in about `target` parts
+fn nice_step(span: f64, target: usize) -> f64 {
+    let raw = span / target.max(1) as f64;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let normalized = raw / magnitude;
+
+    let nice = if normalized < 1.5 {
+        1.0
+    } else if normalized < 3.0 {
+        2.0
+    } else if normalized < 7.0 {
+        5.0
+    } else {
+        10.0
+    };
+
+    nice * magnitude
+}
+
+/// Returns the ticks of an axis of range `range` and the step between the ticks
+fn ticks(range: (f64, f64), target: usize) -> (Vec<f64>, f64) {
+    let step = nice_step(range.1 - range.0, target);
+    let first = (range.0 / step).ceil() as i64;
+    let last = (range.1 / step).floor() as i64;
+
+    let ticks = (first..=last)
+        .take(100)
+        .map(|i| i as f64 * step)
+        .collect();
+
+    (ticks, step)
+}
+
+fn format_tick(value: f64, step: f64) -> String {
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+
+    // Avoid displaying "-0"
+    let value = if value.abs() < step * 1e-9 { 0.0 } else { value };
+
+    format!("{:.*}", decimals, value)
+}
+
+fn format_value(value: f64) -> String {
+    let text = format!("{:.4}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    text.to_string()
+}
+
+/// Makes sure a range is not empty nor reversed
+fn valid_range(range: (f64, f64)) -> (f64, f64) {
+    let (min, max) = (range.0.min(range.1), range.0.max(range.1));
+    match max - min > f64::EPSILON * max.abs().max(1.0) {
+        true => (min, max),
+        false => (min - 1.0, max + 1.0)
+    }
+}
+
+/// Computes the ranges of the axes and the width of the bars
+fn compute_ranges(state: &ChartState) -> ((f64, f64), (f64, f64), f64) {
+    let bar_count = state.series.iter().filter(|s| s.kind == ChartSeriesKind::Bar).count();
+    let spacing = bar_spacing(&state.series);
+    let bar_width = match bar_count {
+        0 => 0.0,
+        n => spacing * BAR_GROUP_WIDTH / n as f64
+    };
+
+    let bounds = data_bounds(&state.series);
+
+    let x_range = match (state.x_range, bounds) {
+        (Some(range), _) => valid_range(range),
+        (None, Some((x, _))) if bar_count > 0 => valid_range((x.0 - spacing / 2.0, x.1 + spacing / 2.0)),
+        (None, Some((x, _))) => valid_range(x),
+        (None, None) => (0.0, 1.0),
+    };
+
+    let y_range = match (state.y_range, bounds) {
+        (Some(range), _) => valid_range(range),
+        (None, Some((_, y))) => {
+            let y = match bar_count > 0 {
+                true => (y.0.min(0.0), y.1.max(0.0)),
+                false => y
+            };
+
+            let (min, max) = valid_range(y);
+            let pad = (max - min) * 0.05;
+            let (min, max) = (
+                if min == 0.0 && bar_count > 0 { 0.0 } else { min - pad },
+                if max == 0.0 && bar_count > 0 { 0.0 } else { max + pad },
+            );
+
+            // Extend the range to the closest ticks
+            let step = nice_step(max - min, 5);
+            ((min / step).floor() * step, (max / step).ceil() * step)
+        },
+        (None, None) => (0.0, 1.0),
+    };
+
+    (x_range, y_range, bar_width)
+}
+
+/// Returns the data point at `position` (in DIPs)
+fn hit_test(state: &ChartState, position: (f32, f32)) -> Option<ChartPoint> {
+    let layout = state.layout?;
+    let [left, top, right, bottom] = layout.plot;
+    let (px, py) = position;
+
+    if px < left || px > right || py < top || py > bottom {
+        return None;
+    }
+
+    let mut best: Option<(f32, ChartPoint)> = None;
+    let mut bar_index = 0;
+
+    for (series_index, series) in state.series.iter().enumerate() {
+        for (index, &(x, y)) in series.points.iter().enumerate() {
+            if !(x.is_finite() && y.is_finite()) {
+                continue;
+            }
+
+            let distance = match series.kind {
+                ChartSeriesKind::Bar => {
+                    let [l, t, r, b] = layout.bar_rect(bar_index, x, y);
+                    match px >= l && px <= r && py >= t && py <= b {
+                        true => 0.0,
+                        false => continue
+                    }
+                },
+                ChartSeriesKind::Line | ChartSeriesKind::Scatter => {
+                    let (sx, sy) = layout.to_screen(x, y);
+                    let distance = ((sx - px).powi(2) + (sy - py).powi(2)).sqrt();
+                    match distance <= HIT_DISTANCE {
+                        true => distance,
+                        false => continue
+                    }
+                }
+            };
+
+            if best.map(|(d, _)| distance < d).unwrap_or(true) {
+                best = Some((distance, ChartPoint { series: series_index, index, x, y }));
+            }
+        }
+
+        if series.kind == ChartSeriesKind::Bar {
+            bar_index += 1;
+        }
+    }
+
+    best.map(|(_, point)| point)
+}
+
+/// Paints the chart and updates the layout used for the hit-testing
+fn paint(handle: HWND, state: &mut ChartState) {
+    let mut renderer = match state.renderer.take() {
+        Some(r) => r,
+        None => { return; }
+    };
+
+    if renderer.begin_draw(handle) {
+        state.layout = Some(draw_chart(&renderer, state));
+        renderer.end_draw();
+    }
+
+    state.renderer = Some(renderer);
+}
+
+fn draw_chart(r: &ChartRenderer, state: &ChartState) -> ChartLayout {
+    let (width, height) = r.size();
+    let (x_range, y_range, bar_width) = compute_ranges(state);
+    let bar_count = state.series.iter().filter(|s| s.kind == ChartSeriesKind::Bar).count();
+    let text_height = r.measure_text("0", TextStyle::Normal).1;
+
+    r.clear(BACKGROUND_COLOR);
+
+    // Vertical layout
+    let mut top = PADDING;
+    if !state.title.is_empty() {
+        let title_height = r.measure_text(&state.title, TextStyle::Title).1;
+        r.text(&state.title, [0.0, top, width, top + title_height], TextStyle::Title, TextAlign::Center, TEXT_COLOR);
+        top += title_height + PADDING;
+    }
+
+    let mut bottom = height - PADDING;
+    if !state.x_label.is_empty() {
+        bottom -= text_height + 4.0;
+    }
+    let x_label_bottom = bottom;
+    bottom -= text_height + 4.0;
+
+    // Y ticks are computed first because their labels define the left margin
+    let (y_ticks, y_step) = ticks(y_range, ((bottom - top) / 40.0).max(2.0) as usize);
+    let y_labels: Vec<String> = y_ticks.iter().map(|&v| format_tick(v, y_step)).collect();
+    let y_labels_width = y_labels.iter()
+        .map(|label| r.measure_text(label, TextStyle::Normal).0)
+        .fold(0.0f32, f32::max);
+
+    let mut left = PADDING;
+    if !state.y_label.is_empty() {
+        r.vertical_text(&state.y_label, [left, top, left + text_height, bottom], TextStyle::Normal, TEXT_COLOR);
+        left += text_height + 4.0;
+    }
+    left += y_labels_width + 6.0;
+
+    // Keep some space for the last x tick label
+    let right = (width - PADDING * 2.0).max(left + 1.0);
+    let bottom = bottom.max(top + 1.0);
+
+    let layout = ChartLayout { plot: [left, top, right, bottom], x_range, y_range, bar_width, bar_count };
+
+    // Grid and ticks of the vertical axis
+    for (&value, label) in y_ticks.iter().zip(y_labels.iter()) {
+        let (_, y) = layout.to_screen(x_range.0, value);
+        r.line((left, y), (right, y), GRID_COLOR, 1.0);
+        let label_rect = [PADDING, y - text_height / 2.0, left - 6.0, y + text_height / 2.0];
+        r.text(label, label_rect, TextStyle::Normal, TextAlign::Right, TEXT_COLOR);
+    }
+
+    // Grid and ticks of the horizontal axis
+    let (x_ticks, x_step) = ticks(x_range, ((right - left) / 80.0).max(2.0) as usize);
+    for &value in x_ticks.iter() {
+        let (x, _) = layout.to_screen(value, y_range.0);
+        let label = format_tick(value, x_step);
+        let label_width = r.measure_text(&label, TextStyle::Normal).0 + 4.0;
+
+        r.line((x, top), (x, bottom), GRID_COLOR, 1.0);
+        r.line((x, bottom), (x, bottom + 4.0), AXIS_COLOR, 1.0);
+        let label_rect = [x - label_width / 2.0, bottom + 4.0, x + label_width / 2.0, bottom + 4.0 + text_height];
+        r.text(&label, label_rect, TextStyle::Normal, TextAlign::Center, TEXT_COLOR);
+    }
+
+    if !state.x_label.is_empty() {
+        let label_rect = [left, x_label_bottom, right, x_label_bottom + text_height];
+        r.text(&state.x_label, label_rect, TextStyle::Normal, TextAlign::Center, TEXT_COLOR);
+    }
+
+    // Series
+    r.push_clip(layout.plot);
+    draw_series(r, state, &layout);
+    r.pop_clip();
+
+    // Axes
+    r.line((left, top), (left, bottom), AXIS_COLOR, 1.0);
+    r.line((left, bottom), (right, bottom), AXIS_COLOR, 1.0);
+
+    if state.legend {
+        draw_legend(r, state, &layout, text_height);
+    }
+
+    if let Some(point) = state.hover {
+        draw_hover(r, state, &layout, point, text_height);
+    }
+
+    layout
+}
+
+fn draw_series(r: &ChartRenderer, state: &ChartState, layout: &ChartLayout) {
+    let mut bar_index = 0;
+
+    for series in state.series.iter() {
+        let points = series.points.iter().filter(|(x, y)| x.is_finite() && y.is_finite());
+
+        match series.kind {
+            ChartSeriesKind::Line => {
+                let mut last: Option<(f32, f32)> = None;
+                for &(x, y) in points {
+                    let point = layout.to_screen(x, y);
+                    if let Some(last) = last {
+                        r.line(last, point, series.color, 2.0);
+                    }
+                    last = Some(point);
+                }
+            },
+            ChartSeriesKind::Bar => {
+                for &(x, y) in points {
+                    r.fill_rect(layout.bar_rect(bar_index, x, y), series.color, 1.0);
+                }
+                bar_index += 1;
+            },
+            ChartSeriesKind::Scatter => {
+                for &(x, y) in points {
+                    r.fill_circle(layout.to_screen(x, y), 3.5, series.color);
+                }
+            },
+        }
+    }
+}
+
+fn draw_legend(r: &ChartRenderer, state: &ChartState, layout: &ChartLayout, text_height: f32) {
+    const SWATCH: f32 = 16.0;
+
+    if state.series.is_empty() {
+        return;
+    }
+
+    let [_, top, right, _] = layout.plot;
+    let names_width = state.series.iter()
+        .map(|s| r.measure_text(&s.name, TextStyle::Normal).0)
+        .fold(0.0f32, f32::max);
+
+    let line_height = text_height + 2.0;
+    let width = SWATCH + names_width + 18.0;
+    let height = line_height * state.series.len() as f32 + 8.0;
+    let (left, top) = (right - width - PADDING, top + PADDING);
+
+    r.fill_rect([left, top, left + width, top + height], BACKGROUND_COLOR, 0.85);
+    r.draw_rect([left, top, left + width, top + height], GRID_COLOR, 1.0);
+
+    for (i, series) in state.series.iter().enumerate() {
+        let y = top + 4.0 + line_height * i as f32;
+        let middle = y + line_height / 2.0;
+        let swatch_left = left + 6.0;
+
+        match series.kind {
+            ChartSeriesKind::Line => r.line((swatch_left, middle), (swatch_left + SWATCH, middle), series.color, 2.0),
+            ChartSeriesKind::Bar => r.fill_rect([swatch_left + 3.0, middle - 5.0, swatch_left + SWATCH - 3.0, middle + 5.0], series.color, 1.0),
+            ChartSeriesKind::Scatter => r.fill_circle((swatch_left + SWATCH / 2.0, middle), 3.5, series.color),
+        }
+
+        let text_left = swatch_left + SWATCH + 6.0;
+        r.text(&series.name, [text_left, y, left + width, y + line_height], TextStyle::Normal, TextAlign::Left, TEXT_COLOR);
+    }
+}
+
+fn draw_hover(r: &ChartRenderer, state: &ChartState, layout: &ChartLayout, point: ChartPoint, text_height: f32) {
+    let series = match state.series.get(point.series) {
+        Some(s) => s,
+        None => { return; }
+    };
+
+    let anchor = match series.kind {
+        ChartSeriesKind::Bar => {
+            let bar_index = state.series[..point.series].iter().filter(|s| s.kind == ChartSeriesKind::Bar).count();
+            let rect = layout.bar_rect(bar_index, point.x, point.y);
+            r.draw_rect(rect, AXIS_COLOR, 2.0);
+            ((rect[0] + rect[2]) / 2.0, rect[1])
+        },
+        ChartSeriesKind::Line | ChartSeriesKind::Scatter => {
+            let center = layout.to_screen(point.x, point.y);
+            r.fill_circle(center, 5.0, series.color);
+            r.draw_circle(center, 6.0, BACKGROUND_COLOR, 2.0);
+            center
+        }
+    };
+
+    // Value label, kept inside the plot
+    let text = format!("{}: ({}, {})", series.name, format_value(point.x), format_value(point.y));
+    let text_width = r.measure_text(&text, TextStyle::Normal).0 + 10.0;
+    let [left, top, right, _] = layout.plot;
+
+    let x = (anchor.0 - text_width / 2.0).min(right - text_width).max(left);
+    let y = (anchor.1 - text_height - 14.0).max(top);
+    let rect = [x, y, x + text_width, y + text_height + 6.0];
+
+    r.fill_rect(rect, BACKGROUND_COLOR, 0.95);
+    r.draw_rect(rect, AXIS_COLOR, 1.0);
+    r.text(&text, rect, TextStyle::Normal, TextAlign::Center, TEXT_COLOR);
+}
@@ -4,7 +4,9 @@ use winapi::um::{
 };
 use winapi::shared::windef::HBRUSH;
 use crate::win32::{base_helper::check_hwnd, window_helper as wh};
+use crate::win32::high_dpi::Extent;
 use crate::{Font, NwgError, RawEventHandler};
+use crate::win32::resource_tracker::{self as rt, ResourceKind};
 use super::{ControlBase, ControlHandle};
 use std::cell::RefCell;
 
@@ -94,8 +96,8 @@ impl CheckBox {
     pub fn builder<'a>() -> CheckBoxBuilder<'a> {
         CheckBoxBuilder {
             text: "A checkbox",
-            size: (100, 25),
-            position: (0, 0),
+            size: (100, 25).into(),
+            position: (0, 0).into(),
             enabled: true,
             focus: false,
             background_color: None,
@@ -280,6 +282,7 @@ impl CheckBox {
         let parent_handle = ControlHandle::Hwnd(wh::get_window_parent(handle));
         
         let brush = unsafe { CreateSolidBrush(RGB(c[0], c[1], c[2])) };
+        rt::track(ResourceKind::Brush, brush);
         self.background_brush = Some(brush);
         
         let handler = bind_raw_event_handler_inner(&parent_handle, handle as UINT_PTR, move |_hwnd, msg, _w, l| {
@@ -311,6 +314,7 @@ impl Drop for CheckBox {
         }
 
         if let Some(bg) = self.background_brush {
+            rt::untrack(bg);
             unsafe { DeleteObject(bg as _); }
         }
 
@@ -320,8 +324,8 @@ impl Drop for CheckBox {
 
 pub struct CheckBoxBuilder<'a> {
     text: &'a str,
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     enabled: bool,
     focus: bool,
     background_color: Option<[u8; 3]>,
@@ -349,13 +353,13 @@ impl<'a> CheckBoxBuilder<'a> {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> CheckBoxBuilder<'a> {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> CheckBoxBuilder<'a> {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> CheckBoxBuilder<'a> {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> CheckBoxBuilder<'a> {
+        self.position = pos.into();
         self
     }
 
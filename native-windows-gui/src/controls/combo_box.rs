@@ -1,9 +1,11 @@
 use winapi::shared::windef::HWND;
 use winapi::shared::minwindef::{LPARAM, WPARAM};
 use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_TABSTOP};
-use crate::win32::base_helper::{check_hwnd, to_utf16, from_utf16};
+use crate::win32::base_helper::{check_hwnd, to_utf16, with_utf16, from_utf16};
+use crate::win32::high_dpi::Extent;
 use crate::win32::window_helper as wh;
 use crate::{Font, NwgError, VTextAlign, RawEventHandler, unbind_raw_event_handler};
+use crate::win32::resource_tracker::{self as rt, ResourceKind};
 use super::{ControlHandle, ControlBase};
 use std::cell::{Ref, RefMut, RefCell};
 use std::fmt::Display;
@@ -82,8 +84,8 @@ impl<D: Display+Default> ComboBox<D> {
 
     pub fn builder<'a>() -> ComboBoxBuilder<'a, D> {
         ComboBoxBuilder {
-            size: (100, 25),
-            position: (0, 0),
+            size: (100, 25).into(),
+            position: (0, 0).into(),
             enabled: true,
             focus: false,
             flags: None,
@@ -126,8 +128,9 @@ impl<D: Display+Default> ComboBox<D> {
 
         for item in col.iter() {
             let display = format!("{}", item);
-            let display_os = to_utf16(&display);
-            wh::send_message(handle, CB_ADDSTRING, 0, display_os.as_ptr() as LPARAM);
+            with_utf16(&display, |display_os| {
+                wh::send_message(handle, CB_ADDSTRING, 0, display_os.as_ptr() as LPARAM);
+            });
         }
     }
 
@@ -212,9 +215,9 @@ impl<D: Display+Default> ComboBox<D> {
         let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
 
         let display = format!("{}", item);
-        let display_os = to_utf16(&display);
-
-        wh::send_message(handle, CB_ADDSTRING, 0, display_os.as_ptr() as LPARAM);
+        with_utf16(&display, |display_os| {
+            wh::send_message(handle, CB_ADDSTRING, 0, display_os.as_ptr() as LPARAM);
+        });
 
         self.collection.borrow_mut().push(item);
     }
@@ -252,9 +255,9 @@ impl<D: Display+Default> ComboBox<D> {
 
         for item in self.collection.borrow().iter() {
             let display = format!("{}", item);
-            let display_os = to_utf16(&display);
-            
-            wh::send_message(handle, CB_ADDSTRING, 0, display_os.as_ptr() as LPARAM);
+            with_utf16(&display, |display_os| {
+                wh::send_message(handle, CB_ADDSTRING, 0, display_os.as_ptr() as LPARAM);
+            });
         }
     }
 
@@ -268,8 +271,9 @@ impl<D: Display+Default> ComboBox<D> {
 
         for item in col.iter() {
             let display = format!("{}", item);
-            let display_os = to_utf16(&display);
-            wh::send_message(handle, CB_ADDSTRING, 0, display_os.as_ptr() as LPARAM);
+            with_utf16(&display, |display_os| {
+                wh::send_message(handle, CB_ADDSTRING, 0, display_os.as_ptr() as LPARAM);
+            });
         }
 
         let mut col_ref = self.collection.borrow_mut();
@@ -418,7 +422,11 @@ impl<D: Display+Default> ComboBox<D> {
 
         if self.handle.blank() { panic!("{}", NOT_BOUND); }
         let brush = match bg {
-            Some(c) => unsafe { CreateSolidBrush(RGB(c[0], c[1], c[2])) },
+            Some(c) => {
+                let b = unsafe { CreateSolidBrush(RGB(c[0], c[1], c[2])) };
+                rt::track(ResourceKind::Brush, b);
+                b
+            },
             None => COLOR_WINDOW as HBRUSH
         };
 
@@ -526,8 +534,8 @@ impl<D: Display+Default> Drop for ComboBox<D> {
 }
 
 pub struct ComboBoxBuilder<'a, D: Display+Default> {
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     enabled: bool,
     focus: bool,
     flags: Option<ComboBoxFlags>,
@@ -550,13 +558,13 @@ impl<'a, D: Display+Default> ComboBoxBuilder<'a, D> {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> ComboBoxBuilder<'a, D> {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> ComboBoxBuilder<'a, D> {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> ComboBoxBuilder<'a, D> {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> ComboBoxBuilder<'a, D> {
+        self.position = pos.into();
         self
     }
 
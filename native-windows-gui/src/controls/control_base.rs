@@ -2,7 +2,9 @@ use winapi::shared::minwindef::DWORD;
 use winapi::shared::windef::{HWND};
 use super::ControlHandle;
 use crate::win32::window::{build_hwnd_control, build_timer, build_notice};
+use crate::win32::high_dpi::Extent;
 use crate::{NwgError};
+use std::ptr;
 
 #[cfg(feature = "menu")] use crate::win32::menu::build_hmenu_control;
 #[cfg(feature = "menu")] use winapi::shared::windef::{HMENU};
@@ -81,8 +83,8 @@ impl ControlBase {
 pub struct HwndBuilder {
     class_name: String,
     text: Option<String>,
-    size: Option<(i32, i32)>,
-    pos: Option<(i32, i32)>,
+    size: Option<Extent>,
+    pos: Option<Extent>,
     forced_flags: DWORD,
     flags: Option<DWORD>,
     ex_flags: Option<DWORD>,
@@ -101,13 +103,13 @@ impl HwndBuilder {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> HwndBuilder {
-        self.size = Some(size);
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> HwndBuilder {
+        self.size = Some(size.into());
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> HwndBuilder {
-        self.pos = Some(pos);
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> HwndBuilder {
+        self.pos = Some(pos.into());
         self
     }
 
@@ -135,11 +137,16 @@ impl HwndBuilder {
     }
 
     pub fn build(self) -> Result<ControlHandle, NwgError> {
+        // DIPs are converted at the DPI of the parent (or of the screen for top level windows)
+        let dpi_handle = self.parent.unwrap_or(ptr::null_mut());
+        let size = self.size.map(|s| unsafe { s.to_units(dpi_handle) });
+        let pos = self.pos.map(|p| unsafe { p.to_units(dpi_handle) });
+
         let handle = unsafe { build_hwnd_control(
             &self.class_name,
             self.text.as_ref().map(|v| v as &str),
-            self.size,
-            self.pos,
+            size,
+            pos,
             self.flags,
             self.ex_flags,
             self.forced_flags,
@@ -1,5 +1,7 @@
 use winapi::shared::windef::{HWND, HMENU};
 use crate::win32::window_helper as wh;
+use crate::win32::window::{EventHandler, EventHandlerGuard, bind_event_handler};
+use crate::{Event, EventData, NwgError};
 
 
 /**
@@ -102,6 +104,83 @@ impl ControlHandle {
         }
     }
 
+    /**
+        Binds a callback to a single event of the control. The callback receives the data of the event.
+        Returns a `EventHandler` that can be passed to `unbind_event_handler` to remove the callback.
+
+        Unlike `bind_event_handler`, the window that receives the events of the control is found automatically:
+        the parent of a child control, the window of a menu, timer or notice, or the window itself.
+
+        Panics if the control was not created.
+
+        ```rust
+        use native_windows_gui as nwg;
+
+        fn bind(button: &nwg::Button) -> nwg::EventHandler {
+            button.on(nwg::Event::OnButtonClick, |_data| {
+                nwg::simple_message("Hello", "Hello!");
+            })
+        }
+        ```
+    */
+    pub fn on<F>(&self, event: Event, f: F) -> EventHandler
+        where F: Fn(EventData) + 'static
+    {
+        let control = *self;
+        let (hooked, parent) = self.event_windows();
+
+        bind_event_handler(&ControlHandle::Hwnd(hooked), &ControlHandle::Hwnd(parent), move |evt, data, handle| {
+            if evt == event && handle == control {
+                f(data);
+            }
+        })
+    }
+
+    /// Same as `on`, but returns a `EventHandlerGuard` that removes the callback when it is dropped
+    pub fn on_guarded<F>(&self, event: Event, f: F) -> EventHandlerGuard
+        where F: Fn(EventData) + 'static
+    {
+        self.on(event, f).guard()
+    }
+
+    /**
+        Returns a `NwgError::WrongThread` error if the control is owned by another thread than the calling thread.
+        The methods of the controls panic in that case, so call this function first to handle the error.
+
+        Menus and handles that were not built always return `Ok`.
+    */
+    pub fn check_thread(&self) -> Result<(), NwgError> {
+        match self {
+            &ControlHandle::Hwnd(h) | &ControlHandle::PopMenu(h, _) | &ControlHandle::Notice(h, _) |
+            &ControlHandle::Timer(h, _) | &ControlHandle::SystemTray(h) => crate::win32::ui_thread::check_thread(h),
+            _ => Ok(())
+        }
+    }
+
+    /// Returns the window of the control and the window that receives its events
+    pub(crate) fn event_windows(&self) -> (HWND, HWND) {
+        use winapi::um::winuser::WS_CHILD;
+
+        let window = match self {
+            &ControlHandle::Hwnd(h) => match wh::get_style(h) & WS_CHILD == WS_CHILD {
+                true => { return (h, wh::get_window_parent(h)); },
+                false => h
+            },
+            #[cfg(feature = "menu")]
+            &ControlHandle::Menu(_, m) | &ControlHandle::MenuItem(m, _) => unsafe { crate::win32::menu::menu_window(m) },
+            #[cfg(not(feature = "menu"))]
+            &ControlHandle::Menu(..) | &ControlHandle::MenuItem(..) => ::std::ptr::null_mut(),
+            &ControlHandle::PopMenu(h, _) | &ControlHandle::Notice(h, _) | &ControlHandle::Timer(h, _) | &ControlHandle::SystemTray(h) => h,
+            &ControlHandle::NoHandle => panic!("Cannot bind events to a control that was not created"),
+        };
+
+        if window.is_null() {
+            panic!("Cannot find the window that receives the events of the control");
+        }
+
+        (window, window)
+    }
+
 }
 
 
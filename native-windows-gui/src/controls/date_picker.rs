@@ -1,8 +1,12 @@
 use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_TABSTOP};
+use winapi::um::commctrl::{DTS_UPDOWN, DTS_LONGDATEFORMAT, DTS_RIGHTALIGN};
+use winapi::um::minwinbase::SYSTEMTIME;
 use crate::win32::window_helper as wh;
-use crate::win32::base_helper::{to_utf16, check_hwnd};
-use crate::{Font, NwgError};
+use crate::win32::high_dpi::Extent;
+use crate::win32::base_helper::{to_utf16, from_utf16, check_hwnd};
+use crate::{Font, NwgError, RawEventHandler, unbind_raw_event_handler};
 use super::{ControlBase, ControlHandle};
+use std::{mem, rc::Rc, cell::RefCell};
 
 const NOT_BOUND: &'static str = "DatePicker is not yet bound to a winapi object";
 const BAD_HANDLE: &'static str = "INTERNAL ERROR: DatePicker handle is not HWND!";
@@ -17,11 +21,17 @@ bitflags! {
         * VISIBLE:  The date picker is immediatly visible after creation
         * DISABLED: The date picker cannot be interacted with by the user. It also has a grayed out look.
         * TAB_STOP: The control can be selected using tab navigation
+        * UP_DOWN:  Use an up-down control instead of the calendar dropdown to edit the date
+        * LONG_DATE: Use the long date format of the system locale (ex: "Friday, April 19, 1996")
+        * RIGHT_ALIGN: The calendar dropdown is aligned on the right side of the control
     */
     pub struct DatePickerFlags: u32 {
         const VISIBLE = WS_VISIBLE;
         const DISABLED = WS_DISABLED;
         const TAB_STOP = WS_TABSTOP;
+        const UP_DOWN = DTS_UPDOWN;
+        const LONG_DATE = DTS_LONGDATEFORMAT;
+        const RIGHT_ALIGN = DTS_RIGHTALIGN;
     }
 }
 
@@ -36,6 +46,9 @@ pub struct DatePickerValue {
     pub day: u16
 }
 
+/// The callback used to fill the callback fields (`X`) of a date picker format. See `DatePicker::set_format_callback`
+type FormatCallback = dyn Fn(&str, DatePickerValue) -> String;
+
 
 
 /**
@@ -54,6 +67,7 @@ Requires the `datetime-picker` feature.
   * `font`:     The font used for the dtp text
   * `date`:     The default date as a `DatePickerValue` value
   * `format`:   The format of the date. See the `set_format` method.
+  * `format_callback`: The callback that fills the callback fields of the format. See the `set_format_callback` method.
   * `range`:    The accepted range of dates. The value is inclusive.
   * `focus`:    The control receive focus after being created
 
@@ -83,17 +97,18 @@ fn build_dtp(date: &mut nwg::DatePicker, window: &nwg::Window) {
 }
 ```
 */
-#[derive(Default, PartialEq, Eq)]
+#[derive(Default)]
 pub struct DatePicker {
-    pub handle: ControlHandle
+    pub handle: ControlHandle,
+    handler0: RefCell<Option<RawEventHandler>>,
 }
 
 impl DatePicker {
 
     pub fn builder<'a>() -> DatePickerBuilder<'a> {
         DatePickerBuilder {
-            size: (100, 25),
-            position: (0, 0),
+            size: (100, 25).into(),
+            position: (0, 0).into(),
             focus: false,
             flags: None,
             ex_flags: 0,
@@ -101,6 +116,7 @@ impl DatePicker {
             parent: None,
             date: None,
             format: None,
+            format_callback: None,
             range: None
         }
     }
@@ -131,6 +147,10 @@ impl DatePicker {
         
         Furthermore, any string enclosed in `'` can be used in the format to display text.  
         For example, to display the current date with the format `'Today is: Tuesday Mar 23, 1996`, the format string is `'Today is: 'dddd MMM dd', 'yyyy`. 
+
+        Finally, a sequence of one or more `X` is a callback field. The text of a callback field is computed by the
+        callback set with `set_format_callback`. Different callback fields can be used in the same format
+        by using a different number of `X` (ex: `X`, `XX`, `XXX`).
     
         If `format` is set to `None`, use the default system format.
     */
@@ -151,6 +171,97 @@ impl DatePicker {
         wh::send_message(handle, DTM_SETFORMATW, 0, format_ptr);
     }
 
+    /**
+        Sets the callback that computes the text of the callback fields (`X`, `XX`, ...) of the format string.
+        The callback receives the callback field (ex: `"XX"`) and the date displayed by the control.
+        The returned text is truncated to 63 characters.
+
+        The control uses the text of the current date to compute the width of the callback field, so
+        the callback should return values of similar length.
+
+        Calling this method a second time replaces the old callback.
+
+        ```rust
+        use native_windows_gui as nwg;
+        fn quarter_format(date: &nwg::DatePicker) {
+            date.set_format(Some("yyyy' - 'X"));
+            date.set_format_callback(|_field, value| format!("Q{}", ((value.month - 1) / 3) + 1));
+        }
+        ```
+    */
+    pub fn set_format_callback<F>(&self, cb: F) 
+        where F: Fn(&str, DatePickerValue) -> String + 'static
+    {
+        use crate::bind_raw_event_handler_inner;
+        use winapi::um::winuser::{WM_NOTIFY, NMHDR, GetDC, ReleaseDC};
+        use winapi::um::wingdi::{GetTextExtentPoint32W, SelectObject};
+        use winapi::um::commctrl::{DTN_FORMATW, DTN_FORMATQUERYW, NMDATETIMEFORMATW, NMDATETIMEFORMATQUERYW, DTM_GETSYSTEMTIME};
+        use winapi::shared::{basetsd::UINT_PTR, minwindef::LPARAM};
+        use std::ptr;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.unbind_format_callback();
+
+        let callback: Rc<FormatCallback> = Rc::new(cb);
+        let parent_handle = ControlHandle::Hwnd(wh::get_window_parent(handle));
+
+        let handler = bind_raw_event_handler_inner(&parent_handle, handle as UINT_PTR, move |_hwnd, msg, _w, l| {
+            if msg != WM_NOTIFY {
+                return None;
+            }
+
+            let nmhdr = unsafe { &*(l as *const NMHDR) };
+            if nmhdr.hwndFrom != handle {
+                return None;
+            }
+
+            match nmhdr.code {
+                DTN_FORMATW => unsafe {
+                    let info = &mut *(l as *mut NMDATETIMEFORMATW);
+                    let field = from_wide_ptr(info.pszFormat);
+                    let text = to_utf16(&callback(&field, from_system_time(&info.st)));
+
+                    let len = usize::min(text.len() - 1, info.szDisplay.len() - 1);
+                    ptr::copy_nonoverlapping(text.as_ptr(), info.szDisplay.as_mut_ptr(), len);
+                    info.szDisplay[len] = 0;
+                    info.pszDisplay = info.szDisplay.as_ptr();
+
+                    Some(0)
+                },
+                DTN_FORMATQUERYW => unsafe {
+                    let info = &mut *(l as *mut NMDATETIMEFORMATQUERYW);
+                    let field = from_wide_ptr(info.pszFormat);
+
+                    let mut syst: SYSTEMTIME = mem::zeroed();
+                    wh::send_message(handle, DTM_GETSYSTEMTIME, 0, &mut syst as *mut SYSTEMTIME as LPARAM);
+                    let text = to_utf16(&callback(&field, from_system_time(&syst)));
+
+                    let dc = GetDC(handle);
+                    let old_font = SelectObject(dc, wh::get_window_font(handle) as _);
+                    GetTextExtentPoint32W(dc, text.as_ptr(), (text.len() - 1) as _, &mut info.szMax);
+                    SelectObject(dc, old_font);
+                    ReleaseDC(handle, dc);
+
+                    Some(0)
+                },
+                _ => None
+            }
+        });
+
+        *self.handler0.borrow_mut() = Some(handler.unwrap());
+
+        // Force the control to query the callback fields again
+        unsafe { ::winapi::um::winuser::InvalidateRect(handle, ptr::null(), 1); }
+    }
+
+    /// Removes the callback set by `set_format_callback`. Does nothing if there is no callback.
+    pub fn unbind_format_callback(&self) {
+        let handler = self.handler0.borrow_mut().take();
+        if let Some(h) = handler {
+            drop(unbind_raw_event_handler(&h));
+        }
+    }
+
     /**
         Return the check state of the checkbox of the control.  
         If the date time picker is not optional, return false.
@@ -170,6 +281,33 @@ impl DatePicker {
         }
     }
 
+    /**
+        Sets the check state of the checkbox of the control.  
+        Unchecking the checkbox is the same as `set_value(None)`. Checking the checkbox keeps the last date of the control.
+    */
+    pub fn set_checked(&self, checked: bool) {
+        use winapi::um::commctrl::{DTM_GETSYSTEMTIME, DTM_SETSYSTEMTIME, GDT_VALID, GDT_NONE};
+        use winapi::shared::minwindef::{WPARAM, LPARAM};
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        match checked {
+            true => {
+                // When the checkbox is unchecked, the control still keeps the last date in memory
+                let mut syst: SYSTEMTIME = unsafe { mem::zeroed() };
+                wh::send_message(handle, DTM_GETSYSTEMTIME, 0, &mut syst as *mut SYSTEMTIME as LPARAM);
+                if syst.wYear == 0 {
+                    unsafe { ::winapi::um::sysinfoapi::GetLocalTime(&mut syst); }
+                }
+
+                wh::send_message(handle, DTM_SETSYSTEMTIME, GDT_VALID as WPARAM, &syst as *const SYSTEMTIME as LPARAM);
+            },
+            false => {
+                wh::send_message(handle, DTM_SETSYSTEMTIME, GDT_NONE as WPARAM, 0);
+            }
+        }
+    }
+
     /// Close the calendar popup if it is open. Note that there is no way to force the calendar to drop down
     pub fn close_calendar(&self) {
         use winapi::um::commctrl::DTM_CLOSEMONTHCAL;
@@ -184,8 +322,6 @@ impl DatePicker {
     */
     pub fn value(&self) -> Option<DatePickerValue> {
         use winapi::um::commctrl::{GDT_VALID, DTM_GETSYSTEMTIME};
-        use winapi::um::minwinbase::SYSTEMTIME;
-        use std::mem;
 
         let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
 
@@ -209,7 +345,6 @@ impl DatePicker {
     pub fn set_value(&self, date: Option<DatePickerValue>) {
         use winapi::um::commctrl::{DTM_SETSYSTEMTIME, GDT_VALID, GDT_NONE};
         use winapi::shared::minwindef::{WPARAM, LPARAM};
-        use winapi::um::minwinbase::SYSTEMTIME;
 
         let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
 
@@ -233,9 +368,7 @@ impl DatePicker {
     /// Gets the current minimum and maximum allowable system times for a date and time picker control.
     pub fn range(&self) -> [DatePickerValue; 2] {
         use winapi::um::commctrl::DTM_GETRANGE;
-        use winapi::um::minwinbase::SYSTEMTIME;
         use winapi::shared::minwindef::{LPARAM};
-        use std::mem;
 
         let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
 
@@ -252,19 +385,71 @@ impl DatePicker {
     
     /// Sets the minimum and maximum allowable system times for a date and time picker control. 
     pub fn set_range(&self, r: &[DatePickerValue; 2]) {
-        use winapi::um::commctrl::DTM_SETRANGE;
-        use winapi::um::commctrl::{GDTR_MIN, GDTR_MAX};
-        use winapi::um::minwinbase::SYSTEMTIME;
-        use winapi::shared::minwindef::{LPARAM};
+        self.set_range_limits(&[Some(r[0]), Some(r[1])]);
+    }
+
+    /**
+        Return the minimum and maximum allowable dates of the control.  
+        Unlike `range`, a limit that is not set is returned as `None`.
+    */
+    pub fn range_limits(&self) -> [Option<DatePickerValue>; 2] {
+        use winapi::um::commctrl::{DTM_GETRANGE, GDTR_MIN, GDTR_MAX};
+        use winapi::shared::minwindef::{WPARAM, LPARAM};
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let mut tr: [SYSTEMTIME; 2] = unsafe { mem::zeroed() };
+        let limits = wh::send_message(handle, DTM_GETRANGE, 0, &mut tr as *mut [SYSTEMTIME; 2] as LPARAM) as WPARAM;
+
+        let min = match limits & GDTR_MIN == GDTR_MIN {
+            true => Some(from_system_time(&tr[0])),
+            false => None
+        };
+
+        let max = match limits & GDTR_MAX == GDTR_MAX {
+            true => Some(from_system_time(&tr[1])),
+            false => None
+        };
+
+        [min, max]
+    }
+
+    /**
+        Sets the minimum and maximum allowable dates of the control.  
+        Use `None` to remove a limit. For example, `[Some(date), None]` only prevents the user from selecting a date before `date`.
+    */
+    pub fn set_range_limits(&self, r: &[Option<DatePickerValue>; 2]) {
+        use winapi::um::commctrl::{DTM_SETRANGE, GDTR_MIN, GDTR_MAX};
+        use winapi::shared::minwindef::{WPARAM, LPARAM};
 
         let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
 
-        let values = [
-            SYSTEMTIME { wYear: r[0].year , wMonth: r[0].month, wDayOfWeek: 0, wDay: r[0].day, wHour: 0, wMinute: 0, wSecond: 0, wMilliseconds: 0 },
-            SYSTEMTIME { wYear: r[1].year , wMonth: r[1].month, wDayOfWeek: 0, wDay: r[1].day, wHour: 0, wMinute: 0, wSecond: 0, wMilliseconds: 0 }
-        ];
+        let mut limits: WPARAM = 0;
+        let mut values: [SYSTEMTIME; 2] = unsafe { mem::zeroed() };
+
+        if let Some(min) = r[0].as_ref() {
+            limits |= GDTR_MIN;
+            values[0] = to_system_time(min);
+        }
+
+        if let Some(max) = r[1].as_ref() {
+            limits |= GDTR_MAX;
+            values[1] = to_system_time(max);
+        }
+
+        wh::send_message(handle, DTM_SETRANGE, limits, &values as *const [SYSTEMTIME; 2] as LPARAM);
+    }
+
+    /// Sets the minimum allowable date of the control. Use `None` to remove the limit.
+    pub fn set_min_date(&self, date: Option<DatePickerValue>) {
+        let [_, max] = self.range_limits();
+        self.set_range_limits(&[date, max]);
+    }
 
-        wh::send_message(handle, DTM_SETRANGE, GDTR_MIN | GDTR_MAX, &values as *const [SYSTEMTIME; 2] as LPARAM);
+    /// Sets the maximum allowable date of the control. Use `None` to remove the limit.
+    pub fn set_max_date(&self, date: Option<DatePickerValue>) {
+        let [min, _] = self.range_limits();
+        self.set_range_limits(&[min, date]);
     }
 
     /// Return the font of the control
@@ -368,13 +553,22 @@ impl DatePicker {
 
 impl Drop for DatePicker {
     fn drop(&mut self) {
+        self.unbind_format_callback();
         self.handle.destroy();
     }
 }
 
+impl PartialEq for DatePicker {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for DatePicker {}
+
 pub struct DatePickerBuilder<'a> {
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     flags: Option<DatePickerFlags>,
     ex_flags: u32,
     font: Option<&'a Font>,
@@ -382,6 +576,7 @@ pub struct DatePickerBuilder<'a> {
     parent: Option<ControlHandle>,
     date: Option<DatePickerValue>,
     format: Option<&'a str>,
+    format_callback: Option<Box<FormatCallback>>,
     range: Option<[DatePickerValue; 2]>
 }
 
@@ -397,13 +592,13 @@ impl<'a> DatePickerBuilder<'a> {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> DatePickerBuilder<'a> {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> DatePickerBuilder<'a> {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> DatePickerBuilder<'a> {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> DatePickerBuilder<'a> {
+        self.position = pos.into();
         self
     }
 
@@ -427,6 +622,13 @@ impl<'a> DatePickerBuilder<'a> {
         self
     }
 
+    pub fn format_callback<F>(mut self, cb: F) -> DatePickerBuilder<'a> 
+        where F: Fn(&str, DatePickerValue) -> String + 'static
+    {
+        self.format_callback = Some(Box::new(cb));
+        self
+    }
+
     pub fn range(mut self, range: Option<[DatePickerValue; 2]>) -> DatePickerBuilder<'a> {
         self.range = range;
         self
@@ -471,6 +673,10 @@ impl<'a> DatePickerBuilder<'a> {
             out.set_range(&self.range.unwrap());
         }
 
+        if let Some(cb) = self.format_callback {
+            out.set_format_callback(cb);
+        }
+
         if self.format.is_some() {
             out.set_format(self.format);
         }
@@ -491,7 +697,6 @@ use winapi::shared::windef::HWND;
 unsafe fn get_dtp_info(handle: HWND) -> DATETIMEPICKERINFO {
     use winapi::um::commctrl::DTM_GETDATETIMEPICKERINFO;
     use winapi::shared::minwindef::DWORD;
-    use std::mem;
 
     let mut dtp_info: DATETIMEPICKERINFO = mem::zeroed();
     dtp_info.cbSize = mem::size_of::<DATETIMEPICKERINFO>() as DWORD;
@@ -499,3 +704,16 @@ unsafe fn get_dtp_info(handle: HWND) -> DATETIMEPICKERINFO {
 
     dtp_info
 }
+
+fn from_system_time(st: &SYSTEMTIME) -> DatePickerValue {
+    DatePickerValue { year: st.wYear, month: st.wMonth, day: st.wDay }
+}
+
+fn to_system_time(v: &DatePickerValue) -> SYSTEMTIME {
+    SYSTEMTIME { wYear: v.year, wMonth: v.month, wDayOfWeek: 0, wDay: v.day, wHour: 0, wMinute: 0, wSecond: 0, wMilliseconds: 0 }
+}
+
+unsafe fn from_wide_ptr(ptr: *const u16) -> String {
+    let len = (0..).take_while(|&i| *ptr.offset(i) != 0).count();
+    from_utf16(std::slice::from_raw_parts(ptr, len))
+}
@@ -0,0 +1,915 @@
+use winapi::shared::windef::{HWND, HFONT, HDC, RECT};
+use winapi::shared::minwindef::WPARAM;
+use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_TABSTOP, WS_BORDER, WS_CHILD, WS_CLIPSIBLINGS, WS_VSCROLL, WS_HSCROLL, SB_VERT, SB_HORZ};
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
+use crate::win32::base_helper::check_hwnd;
+use crate::win32::paint_helper as ph;
+use crate::{Font, NwgError, RawEventHandler, unbind_raw_event_handler};
+use super::{ControlBase, ControlHandle};
+use std::{mem, ptr, rc::Rc, cell::RefCell};
+
+const NOT_BOUND: &'static str = "DiffView is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: DiffView handle is not HWND!";
+
+/// Margin around the line numbers and the text, in logical pixels
+const MARGIN: i32 = 4;
+
+/// Width of the line between the two panes, in logical pixels
+const DIVIDER: i32 = 2;
+
+/// Lines scrolled by one notch of the mouse wheel
+const WHEEL_LINES: i64 = 3;
+
+/// Tabs are expanded to spaces so that the columns of the two panes match
+const TAB_WIDTH: usize = 4;
+
+const DELETED_COLOR: [u8; 3] = [255, 228, 228];
+const DELETED_TEXT_COLOR: [u8; 3] = [255, 180, 180];
+const INSERTED_COLOR: [u8; 3] = [226, 250, 226];
+const INSERTED_TEXT_COLOR: [u8; 3] = [170, 235, 170];
+const FILLER_COLOR: [u8; 3] = [242, 242, 242];
+
+
+bitflags! {
+    /**
+        The diff view flags
+
+        * NONE:     No flags. Equivalent to a invisible diff view.
+        * VISIBLE:  The diff view is immediatly visible after creation
+        * DISABLED: The diff view cannot be focused by the user
+        * TAB_STOP: The diff view can be selected using tab navigation
+        * BORDER:   The diff view has a thin border
+    */
+    pub struct DiffViewFlags: u32 {
+        const NONE = 0;
+        const VISIBLE = WS_VISIBLE;
+        const DISABLED = WS_DISABLED;
+        const TAB_STOP = WS_TABSTOP;
+        const BORDER = WS_BORDER;
+    }
+}
+
+/**
+    An operation of an edit script, in lines. The script transforms the old text into the new text
+    when the operations are applied in order.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DiffOp {
+    /// Lines present in both texts
+    Equal(usize),
+
+    /// Lines of the old text removed from the new text
+    Delete(usize),
+
+    /// Lines of the new text that are not in the old text
+    Insert(usize),
+
+    /// Lines of the old text (first value) replaced by lines of the new text (second value)
+    Replace(usize, usize),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum RowKind {
+    Equal,
+    Delete,
+    Insert,
+    Replace,
+}
+
+/// A line of the view. A side is `None` when the line only exists in the other text.
+#[derive(Clone, Debug)]
+struct DiffRow {
+    kind: RowKind,
+    left: Option<usize>,
+    right: Option<usize>,
+    /// The range of the characters that changed in a replaced line, for each side
+    left_change: Option<(usize, usize)>,
+    right_change: Option<(usize, usize)>,
+}
+
+struct DiffViewState {
+    old_lines: Vec<Vec<char>>,
+    new_lines: Vec<Vec<char>>,
+    rows: Vec<DiffRow>,
+    /// Index of the first row of each hunk
+    hunks: Vec<usize>,
+    current_hunk: Option<usize>,
+    max_columns: usize,
+
+    top_row: usize,
+    left_column: usize,
+
+    font: HFONT,
+    default_font: HFONT,
+    char_size: (i32, i32),
+}
+
+impl Default for DiffViewState {
+    fn default() -> DiffViewState {
+        DiffViewState {
+            old_lines: Vec::new(),
+            new_lines: Vec::new(),
+            rows: Vec::new(),
+            hunks: Vec::new(),
+            current_hunk: None,
+            max_columns: 0,
+            top_row: 0,
+            left_column: 0,
+            font: ptr::null_mut(),
+            default_font: ptr::null_mut(),
+            char_size: (8, 16),
+        }
+    }
+}
+
+impl Drop for DiffViewState {
+    fn drop(&mut self) {
+        ph::delete_font(self.default_font);
+    }
+}
+
+/**
+A diff view displays two versions of a text side by side: the old text on the left and the new text on the right.
+The differences are described by an edit script (a list of `DiffOp`) computed by the application. The removed lines
+are highlighted in red, the added lines in green, and in the replaced lines the characters that changed are highlighted.
+Empty rows are inserted in one of the panes so that the unchanged lines stay aligned, and both panes scroll together.
+
+A group of consecutive changed lines is a hunk. The user moves to the next hunk with F7 and to the previous hunk with Shift+F7.
+
+The script must cover all the lines of the two texts. The lines that are not covered by the script are displayed as removed
+or added lines at the end of the view.
+
+The control uses the "Consolas" font unless a font is set; the font must be monospaced.
+
+Requires the `diff-view` feature.
+
+**Builder parameters:**
+  * `parent`:   **Required.** The diff view parent container.
+  * `size`:     The diff view size.
+  * `position`: The diff view position.
+  * `old_text`: The text displayed in the left pane.
+  * `new_text`: The text displayed in the right pane.
+  * `script`:   The edit script from the old text to the new text.
+  * `font`:     A monospaced font.
+  * `flags`:    A combination of the DiffViewFlags values.
+
+**Control events:**
+  * `MousePress(_)`: Generic mouse press events on the diff view
+  * `OnKeyPress`: Generic key press event
+
+```rust
+use native_windows_gui as nwg;
+use nwg::DiffOp;
+
+fn show_changes(view: &nwg::DiffView) {
+    let old = "fn main() {\n    println!(\"Hello\");\n}\n";
+    let new = "fn main() {\n    println!(\"Hello world\");\n    run();\n}\n";
+    view.set_diff(old, new, &[DiffOp::Equal(1), DiffOp::Replace(1, 2), DiffOp::Equal(1)]);
+    view.next_hunk();
+}
+```
+*/
+#[derive(Default)]
+pub struct DiffView {
+    pub handle: ControlHandle,
+    state: Rc<RefCell<DiffViewState>>,
+    handler0: RefCell<Option<RawEventHandler>>,
+}
+
+impl DiffView {
+
+    pub fn builder<'a>() -> DiffViewBuilder<'a> {
+        DiffViewBuilder {
+            size: (600, 400).into(),
+            position: (0, 0).into(),
+            old_text: "",
+            new_text: "",
+            script: &[],
+            font: None,
+            flags: None,
+            parent: None,
+        }
+    }
+
+    /// Displays the differences between two texts. `script` is the edit script from `old` to `new`, in lines.
+    pub fn set_diff(&self, old: &str, new: &str, script: &[DiffOp]) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+
+        state.old_lines = split_lines(old);
+        state.new_lines = split_lines(new);
+        build_rows(&mut state, script);
+        state.top_row = 0;
+        state.left_column = 0;
+
+        update_scroll(handle, &mut state);
+        ph::redraw(handle);
+    }
+
+    /// Removes the texts from the view
+    pub fn clear(&self) {
+        self.set_diff("", "", &[]);
+    }
+
+    /// Returns the number of hunks (groups of consecutive changed lines)
+    pub fn hunk_count(&self) -> usize {
+        self.state.borrow().hunks.len()
+    }
+
+    /// Returns the index of the hunk selected with the navigation functions
+    pub fn current_hunk(&self) -> Option<usize> {
+        self.state.borrow().current_hunk
+    }
+
+    /// Selects a hunk and scrolls the view to show it. Does nothing if the index is out of bounds.
+    pub fn set_current_hunk(&self, index: usize) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        select_hunk(handle, &mut self.state.borrow_mut(), index);
+    }
+
+    /// Selects the next hunk. Returns `false` if the current hunk is the last one.
+    pub fn next_hunk(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        move_hunk(handle, &mut self.state.borrow_mut(), true)
+    }
+
+    /// Selects the previous hunk. Returns `false` if the current hunk is the first one.
+    pub fn previous_hunk(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        move_hunk(handle, &mut self.state.borrow_mut(), false)
+    }
+
+    /// Returns the font of the control
+    pub fn font(&self) -> Option<Font> {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let font_handle = wh::get_window_font(handle);
+        if font_handle.is_null() {
+            None
+        } else {
+            Some(Font { handle: font_handle })
+        }
+    }
+
+    /// Sets the font of the control. The font must be monospaced. `None` restores the default font.
+    pub fn set_font(&self, font: Option<&Font>) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_font(handle, font.map(|f| f.handle), true); }
+    }
+
+    /// Returns true if the control currently has the keyboard focus
+    pub fn focus(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_focus(handle) }
+    }
+
+    /// Sets the keyboard focus on the diff view
+    pub fn set_focus(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_focus(handle); }
+    }
+
+    /// Returns true if the control user can interact with the control, return false otherwise
+    pub fn enabled(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_enabled(handle) }
+    }
+
+    /// Enable or disable the control
+    pub fn set_enabled(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_enabled(handle, v) }
+    }
+
+    /// Returns true if the control is visible to the user. Will return true even if the
+    /// control is outside of the parent client view (ex: at the position (10000, 10000))
+    pub fn visible(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_visibility(handle) }
+    }
+
+    /// Show or hide the control to the user
+    pub fn set_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_visibility(handle, v) }
+    }
+
+    /// Returns the size of the diff view in the parent window
+    pub fn size(&self) -> (u32, u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_size(handle) }
+    }
+
+    /// Sets the size of the diff view in the parent window
+    pub fn set_size(&self, x: u32, y: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_size(handle, x, y, false) }
+    }
+
+    /// Returns the position of the diff view in the parent window
+    pub fn position(&self) -> (i32, i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_position(handle) }
+    }
+
+    /// Sets the position of the diff view in the parent window
+    pub fn set_position(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_position(handle, x, y) }
+    }
+
+    /// Winapi class name used during control creation
+    pub fn class_name(&self) -> &'static str {
+        "NWG_DIFF_VIEW"
+    }
+
+    /// Winapi base flags used during window creation
+    pub fn flags(&self) -> u32 {
+        WS_VISIBLE | WS_TABSTOP | WS_BORDER
+    }
+
+    /// Winapi flags required by the control
+    pub fn forced_flags(&self) -> u32 {
+        WS_CHILD | WS_CLIPSIBLINGS | WS_VSCROLL | WS_HSCROLL
+    }
+
+    /// Handles the painting, the scrolling and the navigation
+    fn hook_events(&self) {
+        use crate::bind_raw_event_handler_inner;
+        use winapi::um::winuser::{WM_PAINT, WM_ERASEBKGND, WM_SIZE, WM_SETFONT, WM_GETFONT, WM_VSCROLL, WM_HSCROLL, WM_MOUSEWHEEL,
+            WM_LBUTTONDOWN, WM_SETFOCUS, WM_KILLFOCUS, WM_GETDLGCODE, WM_KEYDOWN, DLGC_WANTARROWS, GET_WHEEL_DELTA_WPARAM, WHEEL_DELTA};
+        use winapi::um::winuser::SetFocus;
+
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let state = self.state.clone();
+
+        let handler = bind_raw_event_handler_inner(&self.handle, 0, move |hwnd, msg, w, l| {
+            match msg {
+                WM_PAINT => {
+                    paint(hwnd, &state.borrow());
+                    return Some(0);
+                },
+                WM_ERASEBKGND => {
+                    // Everything is drawn in WM_PAINT
+                    return Some(1);
+                },
+                WM_SIZE => {
+                    // Showing or hiding a scrollbar resizes the control
+                    if let Ok(mut state) = state.try_borrow_mut() {
+                        update_scroll(hwnd, &mut state);
+                        ph::redraw(hwnd);
+                    }
+                },
+                WM_SETFONT => {
+                    let mut state = state.borrow_mut();
+                    state.font = w as HFONT;
+                    state.char_size = char_size(hwnd, ph::font_or(state.font, state.default_font));
+                    update_scroll(hwnd, &mut state);
+                    if l != 0 {
+                        ph::redraw(hwnd);
+                    }
+                    return Some(0);
+                },
+                WM_GETFONT => {
+                    return Some(state.borrow().font as _);
+                },
+                WM_GETDLGCODE => {
+                    return Some(DLGC_WANTARROWS);
+                },
+                WM_SETFOCUS | WM_KILLFOCUS => {
+                    ph::redraw(hwnd);
+                },
+                WM_LBUTTONDOWN => {
+                    unsafe { SetFocus(hwnd); }
+                },
+                WM_VSCROLL => {
+                    let mut state = state.borrow_mut();
+                    let page = visible_size(hwnd, &state).1 as i64;
+                    let top = scroll_request(hwnd, SB_VERT, w, state.top_row as i64, page);
+                    set_top_row(hwnd, &mut state, top);
+                    return Some(0);
+                },
+                WM_HSCROLL => {
+                    let mut state = state.borrow_mut();
+                    let page = visible_size(hwnd, &state).0 as i64;
+                    let left = scroll_request(hwnd, SB_HORZ, w, state.left_column as i64, page);
+                    set_left_column(hwnd, &mut state, left);
+                    return Some(0);
+                },
+                WM_MOUSEWHEEL => {
+                    let notches = GET_WHEEL_DELTA_WPARAM(w) as i64 / WHEEL_DELTA as i64;
+                    let mut state = state.borrow_mut();
+                    let top = state.top_row as i64 - notches * WHEEL_LINES;
+                    set_top_row(hwnd, &mut state, top);
+                    return Some(0);
+                },
+                WM_KEYDOWN => {
+                    key_down(hwnd, &mut state.borrow_mut(), w);
+                    return Some(0);
+                },
+                _ => {}
+            }
+
+            None
+        });
+
+        *self.handler0.borrow_mut() = Some(handler.unwrap());
+    }
+
+}
+
+impl Drop for DiffView {
+    fn drop(&mut self) {
+        let handler = self.handler0.borrow();
+        if let Some(h) = handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
+        self.handle.destroy();
+    }
+}
+
+impl PartialEq for DiffView {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for DiffView {}
+
+pub struct DiffViewBuilder<'a> {
+    size: Extent,
+    position: Extent,
+    old_text: &'a str,
+    new_text: &'a str,
+    script: &'a [DiffOp],
+    font: Option<&'a Font>,
+    flags: Option<DiffViewFlags>,
+    parent: Option<ControlHandle>
+}
+
+impl<'a> DiffViewBuilder<'a> {
+
+    pub fn flags(mut self, flags: DiffViewFlags) -> DiffViewBuilder<'a> {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> DiffViewBuilder<'a> {
+        self.size = size.into();
+        self
+    }
+
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> DiffViewBuilder<'a> {
+        self.position = pos.into();
+        self
+    }
+
+    pub fn old_text(mut self, text: &'a str) -> DiffViewBuilder<'a> {
+        self.old_text = text;
+        self
+    }
+
+    pub fn new_text(mut self, text: &'a str) -> DiffViewBuilder<'a> {
+        self.new_text = text;
+        self
+    }
+
+    pub fn script(mut self, script: &'a [DiffOp]) -> DiffViewBuilder<'a> {
+        self.script = script;
+        self
+    }
+
+    pub fn font(mut self, font: Option<&'a Font>) -> DiffViewBuilder<'a> {
+        self.font = font;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> DiffViewBuilder<'a> {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut DiffView) -> Result<(), NwgError> {
+        let flags = self.flags.map(|f| f.bits()).unwrap_or(out.flags());
+
+        let parent = match self.parent {
+            Some(p) => Ok(p),
+            None => Err(NwgError::no_parent("DiffView"))
+        }?;
+
+        *out = Default::default();
+
+        out.handle = ControlBase::build_hwnd()
+            .class_name(out.class_name())
+            .forced_flags(out.forced_flags())
+            .flags(flags)
+            .size(self.size)
+            .position(self.position)
+            .parent(Some(parent))
+            .build()?;
+
+        out.state.borrow_mut().default_font = ph::monospace_font();
+
+        out.hook_events();
+        out.set_font(self.font);
+        out.set_diff(self.old_text, self.new_text, self.script);
+
+        Ok(())
+    }
+
+}
+
+/// Returns the width and the height of a character of a monospaced font
+fn char_size(handle: HWND, font: HFONT) -> (i32, i32) {
+    use winapi::um::winuser::{GetDC, ReleaseDC};
+    use winapi::um::wingdi::{SelectObject, GetTextMetricsW, TEXTMETRICW};
+
+    unsafe {
+        let dc = GetDC(handle);
+        let old_font = SelectObject(dc, font as _);
+
+        let mut metrics: TEXTMETRICW = mem::zeroed();
+        GetTextMetricsW(dc, &mut metrics);
+
+        SelectObject(dc, old_font);
+        ReleaseDC(handle, dc);
+
+        (metrics.tmAveCharWidth.max(1), (metrics.tmHeight + metrics.tmExternalLeading).max(1))
+    }
+}
+
+fn split_lines(text: &str) -> Vec<Vec<char>> {
+    text.lines()
+        .map(|line| {
+            let mut chars = Vec::with_capacity(line.len());
+            for c in line.chars() {
+                match c {
+                    '\t' => {
+                        let spaces = TAB_WIDTH - chars.len() % TAB_WIDTH;
+                        chars.extend(std::iter::repeat(' ').take(spaces));
+                    },
+                    c => chars.push(c)
+                }
+            }
+            chars
+        })
+        .collect()
+}
+
+/// Returns the range of characters that differ between two lines, after the common prefix and before the common suffix
+fn changed_ranges(old: &[char], new: &[char]) -> ((usize, usize), (usize, usize)) {
+    let prefix = old.iter().zip(new.iter()).take_while(|(a, b)| a == b).count();
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let suffix = old.iter().rev().zip(new.iter().rev()).take(max_suffix).take_while(|(a, b)| a == b).count();
+
+    ((prefix, old.len() - suffix), (prefix, new.len() - suffix))
+}
+
+/// Aligns the lines of the two texts following the edit script
+fn build_rows(state: &mut DiffViewState, script: &[DiffOp]) {
+    let (old_count, new_count) = (state.old_lines.len(), state.new_lines.len());
+    let mut rows = Vec::new();
+    let (mut old, mut new) = (0, 0);
+
+    let row = |kind, left, right| DiffRow { kind, left, right, left_change: None, right_change: None };
+
+    // Lines not covered by the script are added at the end
+    let tail = [DiffOp::Delete(usize::max_value()), DiffOp::Insert(usize::max_value())];
+
+    for op in script.iter().chain(tail.iter()) {
+        match *op {
+            DiffOp::Equal(count) => {
+                let count = count.min(old_count - old).min(new_count - new);
+                for i in 0..count {
+                    rows.push(row(RowKind::Equal, Some(old + i), Some(new + i)));
+                }
+                old += count;
+                new += count;
+            },
+            DiffOp::Delete(count) => {
+                let count = count.min(old_count - old);
+                for i in 0..count {
+                    rows.push(row(RowKind::Delete, Some(old + i), None));
+                }
+                old += count;
+            },
+            DiffOp::Insert(count) => {
+                let count = count.min(new_count - new);
+                for i in 0..count {
+                    rows.push(row(RowKind::Insert, None, Some(new + i)));
+                }
+                new += count;
+            },
+            DiffOp::Replace(old_lines, new_lines) => {
+                let old_lines = old_lines.min(old_count - old);
+                let new_lines = new_lines.min(new_count - new);
+                for i in 0..old_lines.max(new_lines) {
+                    let left = match i < old_lines { true => Some(old + i), false => None };
+                    let right = match i < new_lines { true => Some(new + i), false => None };
+                    let mut r = row(RowKind::Replace, left, right);
+
+                    if let (Some(l), Some(r2)) = (left, right) {
+                        let (left_change, right_change) = changed_ranges(&state.old_lines[l], &state.new_lines[r2]);
+                        r.left_change = Some(left_change);
+                        r.right_change = Some(right_change);
+                    }
+
+                    rows.push(r);
+                }
+                old += old_lines;
+                new += new_lines;
+            },
+        }
+    }
+
+    let mut hunks = Vec::new();
+    for (i, r) in rows.iter().enumerate() {
+        let changed = r.kind != RowKind::Equal;
+        let previous_changed = i > 0 && rows[i - 1].kind != RowKind::Equal;
+        if changed && !previous_changed {
+            hunks.push(i);
+        }
+    }
+
+    state.max_columns = state.old_lines.iter().chain(state.new_lines.iter()).map(|l| l.len()).max().unwrap_or(0);
+    state.rows = rows;
+    state.hunks = hunks;
+    state.current_hunk = None;
+}
+
+/// Number of digits of the line numbers
+fn number_digits(state: &DiffViewState) -> usize {
+    state.old_lines.len().max(state.new_lines.len()).max(1).to_string().len()
+}
+
+/// Returns the width of a pane and the width of its line numbers column, in pixels
+fn pane_layout(handle: HWND, state: &DiffViewState) -> (i32, i32) {
+    let (width, _) = ph::client_size(handle);
+    let pane = ((width - ph::px(DIVIDER)) / 2).max(0);
+    let gutter = number_digits(state) as i32 * state.char_size.0 + ph::px(MARGIN) * 2;
+    (pane, gutter)
+}
+
+/// Number of columns of text and rows fully visible in a pane
+fn visible_size(handle: HWND, state: &DiffViewState) -> (usize, usize) {
+    let (_, height) = ph::client_size(handle);
+    let (pane, gutter) = pane_layout(handle, state);
+    let (cw, line) = state.char_size;
+    (((pane - gutter - ph::px(MARGIN)) / cw).max(1) as usize, (height / line).max(1) as usize)
+}
+
+fn max_top_row(handle: HWND, state: &DiffViewState) -> usize {
+    state.rows.len().saturating_sub(visible_size(handle, state).1)
+}
+
+fn max_left_column(handle: HWND, state: &DiffViewState) -> usize {
+    (state.max_columns + 1).saturating_sub(visible_size(handle, state).0)
+}
+
+fn update_scroll(handle: HWND, state: &mut DiffViewState) {
+    use winapi::um::winuser::{SetScrollInfo, SCROLLINFO, SIF_RANGE, SIF_PAGE, SIF_POS};
+
+    state.top_row = state.top_row.min(max_top_row(handle, state));
+    state.left_column = state.left_column.min(max_left_column(handle, state));
+
+    let (columns, rows) = visible_size(handle, state);
+    let bars = [
+        (SB_VERT, state.rows.len(), rows, state.top_row),
+        (SB_HORZ, state.max_columns + 1, columns, state.left_column),
+    ];
+
+    for &(bar, count, page, pos) in bars.iter() {
+        let info = SCROLLINFO {
+            cbSize: mem::size_of::<SCROLLINFO>() as u32,
+            fMask: SIF_RANGE | SIF_PAGE | SIF_POS,
+            nMin: 0,
+            nMax: count.max(1).min(i32::max_value() as usize) as i32 - 1,
+            nPage: page as u32,
+            nPos: pos.min(i32::max_value() as usize) as i32,
+            nTrackPos: 0,
+        };
+
+        unsafe { SetScrollInfo(handle, bar, &info, 1); }
+    }
+}
+
+fn set_top_row(handle: HWND, state: &mut DiffViewState, top: i64) {
+    let top = (top.max(0) as usize).min(max_top_row(handle, state));
+    if top != state.top_row {
+        state.top_row = top;
+        update_scroll(handle, state);
+        ph::redraw(handle);
+    }
+}
+
+fn set_left_column(handle: HWND, state: &mut DiffViewState, left: i64) {
+    let left = (left.max(0) as usize).min(max_left_column(handle, state));
+    if left != state.left_column {
+        state.left_column = left;
+        update_scroll(handle, state);
+        ph::redraw(handle);
+    }
+}
+
+/// Computes the new position of a scrollbar from a WM_VSCROLL or WM_HSCROLL message
+fn scroll_request(handle: HWND, bar: i32, w: WPARAM, current: i64, page: i64) -> i64 {
+    use winapi::um::winuser::{GetScrollInfo, SCROLLINFO, SIF_TRACKPOS, SB_LINEUP, SB_LINEDOWN, SB_PAGEUP, SB_PAGEDOWN,
+        SB_THUMBTRACK, SB_THUMBPOSITION, SB_TOP, SB_BOTTOM};
+    use winapi::shared::minwindef::LOWORD;
+
+    // SB_LINELEFT, SB_PAGELEFT, ... have the same values as the vertical codes
+    match LOWORD(w as u32) as isize {
+        SB_LINEUP => current - 1,
+        SB_LINEDOWN => current + 1,
+        SB_PAGEUP => current - page,
+        SB_PAGEDOWN => current + page,
+        SB_TOP => 0,
+        SB_BOTTOM => i64::max_value(),
+        SB_THUMBTRACK | SB_THUMBPOSITION => unsafe {
+            // The position in the message is limited to 16 bits
+            let mut info: SCROLLINFO = mem::zeroed();
+            info.cbSize = mem::size_of::<SCROLLINFO>() as u32;
+            info.fMask = SIF_TRACKPOS;
+            GetScrollInfo(handle, bar, &mut info);
+            info.nTrackPos as i64
+        },
+        _ => current
+    }
+}
+
+/// Selects a hunk and scrolls the view so that the hunk is near the top of the view
+fn select_hunk(handle: HWND, state: &mut DiffViewState, index: usize) {
+    let first_row = match state.hunks.get(index) {
+        Some(&row) => row,
+        None => { return; }
+    };
+
+    state.current_hunk = Some(index);
+
+    // Keep a few lines of context above the hunk
+    let (_, rows) = visible_size(handle, state);
+    state.top_row = first_row.saturating_sub(rows / 4);
+    update_scroll(handle, state);
+    ph::redraw(handle);
+}
+
+fn move_hunk(handle: HWND, state: &mut DiffViewState, forward: bool) -> bool {
+    if state.hunks.is_empty() {
+        return false;
+    }
+
+    // Without a current hunk, the navigation starts from the top of the view
+    let target = match (state.current_hunk, forward) {
+        (Some(i), true) if i + 1 < state.hunks.len() => i + 1,
+        (Some(i), false) if i > 0 => i - 1,
+        (Some(_), _) => { return false; },
+        (None, true) => match state.hunks.iter().position(|&row| row >= state.top_row) {
+            Some(i) => i,
+            None => { return false; }
+        },
+        (None, false) => match state.hunks.iter().rposition(|&row| row < state.top_row) {
+            Some(i) => i,
+            None => { return false; }
+        },
+    };
+
+    select_hunk(handle, state, target);
+    true
+}
+
+fn key_down(handle: HWND, state: &mut DiffViewState, key: WPARAM) {
+    use winapi::um::winuser::{GetKeyState, VK_UP, VK_DOWN, VK_LEFT, VK_RIGHT, VK_PRIOR, VK_NEXT, VK_HOME, VK_END, VK_F7, VK_SHIFT};
+
+    let (_, page) = visible_size(handle, state);
+    let (top, left) = (state.top_row as i64, state.left_column as i64);
+
+    match key as i32 {
+        VK_UP => set_top_row(handle, state, top - 1),
+        VK_DOWN => set_top_row(handle, state, top + 1),
+        VK_PRIOR => set_top_row(handle, state, top - page as i64),
+        VK_NEXT => set_top_row(handle, state, top + page as i64),
+        VK_HOME => set_top_row(handle, state, 0),
+        VK_END => set_top_row(handle, state, i64::max_value()),
+        VK_LEFT => set_left_column(handle, state, left - 1),
+        VK_RIGHT => set_left_column(handle, state, left + 1),
+        VK_F7 => {
+            let shift = unsafe { GetKeyState(VK_SHIFT) < 0 };
+            move_hunk(handle, state, !shift);
+        },
+        _ => {}
+    }
+}
+
+/// Draws characters of a line, clipped to `clip`
+unsafe fn draw_chars(dc: HDC, x: i32, y: i32, chars: &[char], clip: &RECT) {
+    use winapi::um::wingdi::{ExtTextOutW, ETO_CLIPPED};
+
+    let text: Vec<u16> = chars.iter().collect::<String>().encode_utf16().collect();
+    ExtTextOutW(dc, x, y, ETO_CLIPPED, clip, text.as_ptr(), text.len() as u32, ptr::null());
+}
+
+/// Paints one side of a row
+unsafe fn paint_side(dc: HDC, state: &DiffViewState, row: &DiffRow, left_side: bool, layout: (i32, i32), pane_x: i32, y: i32) {
+    use winapi::um::winuser::{GetSysColor, COLOR_WINDOW, COLOR_WINDOWTEXT, COLOR_GRAYTEXT};
+    use winapi::um::wingdi::SetTextColor;
+
+    let (cw, line_height) = state.char_size;
+    let (pane, gutter) = layout;
+    let rect = RECT { left: pane_x, top: y, right: pane_x + pane, bottom: y + line_height };
+
+    let (line, change, lines) = match left_side {
+        true => (row.left, row.left_change, &state.old_lines),
+        false => (row.right, row.right_change, &state.new_lines)
+    };
+
+    let (background, highlight) = match (row.kind, left_side) {
+        (RowKind::Equal, _) => (GetSysColor(COLOR_WINDOW), None),
+        (RowKind::Delete, _) | (RowKind::Replace, true) => (ph::rgb(DELETED_COLOR), Some(ph::rgb(DELETED_TEXT_COLOR))),
+        (RowKind::Insert, _) | (RowKind::Replace, false) => (ph::rgb(INSERTED_COLOR), Some(ph::rgb(INSERTED_TEXT_COLOR))),
+    };
+
+    let index = match line {
+        Some(i) => i,
+        None => {
+            // The line only exists in the other pane
+            ph::fill(dc, &rect, ph::rgb(FILLER_COLOR));
+            return;
+        }
+    };
+
+    ph::fill(dc, &rect, background);
+
+    let text_clip = RECT { left: pane_x + gutter, ..rect };
+    let text_x = text_clip.left - state.left_column as i32 * cw;
+
+    if let (Some((start, end)), Some(color)) = (change, highlight) {
+        if end > start {
+            let changed = RECT {
+                left: (text_x + start as i32 * cw).max(text_clip.left),
+                right: (text_x + end as i32 * cw).min(text_clip.right),
+                ..rect
+            };
+
+            if changed.right > changed.left {
+                ph::fill(dc, &changed, color);
+            }
+        }
+    }
+
+    let number = format!("{:>width$}", index + 1, width = number_digits(state));
+    let number: Vec<char> = number.chars().collect();
+    SetTextColor(dc, GetSysColor(COLOR_GRAYTEXT));
+    draw_chars(dc, pane_x + ph::px(MARGIN), y, &number, &rect);
+
+    SetTextColor(dc, GetSysColor(COLOR_WINDOWTEXT));
+    draw_chars(dc, text_x, y, &lines[index], &text_clip);
+}
+
+/// Paints the view in a memory bitmap and copies the result on the screen
+fn paint(handle: HWND, state: &DiffViewState) {
+    use winapi::um::winuser::{GetSysColor, COLOR_WINDOW, COLOR_BTNSHADOW, COLOR_HIGHLIGHT};
+    use winapi::um::wingdi::{SetBkMode, TRANSPARENT};
+
+    ph::paint_buffered(handle, ph::font_or(state.font, state.default_font), |buffer_dc, width, height| unsafe {
+        let (_, line_height) = state.char_size;
+        let (_, rows) = visible_size(handle, state);
+        let (pane, gutter) = pane_layout(handle, state);
+
+        ph::fill(buffer_dc, &RECT { left: 0, top: 0, right: width, bottom: height }, GetSysColor(COLOR_WINDOW));
+        SetBkMode(buffer_dc, TRANSPARENT as i32);
+
+        let right_x = pane + ph::px(DIVIDER);
+        let current = state.current_hunk.map(|i| {
+            let start = state.hunks[i];
+            let end = state.rows[start..].iter().position(|r| r.kind == RowKind::Equal).map(|len| start + len).unwrap_or(state.rows.len());
+            (start, end)
+        });
+
+        let end_row = (state.top_row + rows + 1).min(state.rows.len());
+        for index in state.top_row..end_row {
+            let row = &state.rows[index];
+            let y = (index - state.top_row) as i32 * line_height;
+
+            paint_side(buffer_dc, state, row, true, (pane, gutter), 0, y);
+            paint_side(buffer_dc, state, row, false, (pane, gutter), right_x, y);
+
+            // Marks the rows of the hunk selected with the navigation
+            if let Some((start, end)) = current {
+                if index >= start && index < end {
+                    let marker = RECT { left: 0, top: y, right: ph::px(3), bottom: y + line_height };
+                    ph::fill(buffer_dc, &marker, GetSysColor(COLOR_HIGHLIGHT));
+                    ph::fill(buffer_dc, &RECT { left: right_x, right: right_x + ph::px(3), ..marker }, GetSysColor(COLOR_HIGHLIGHT));
+                }
+            }
+        }
+
+        // Separators between the line numbers and the text, and between the panes
+        let shadow = GetSysColor(COLOR_BTNSHADOW);
+        ph::fill(buffer_dc, &RECT { left: pane, top: 0, right: right_x, bottom: height }, shadow);
+        for &x in [gutter - ph::px(MARGIN) / 2, right_x + gutter - ph::px(MARGIN) / 2].iter() {
+            ph::fill(buffer_dc, &RECT { left: x, top: 0, right: x + 1, bottom: height }, shadow);
+        }
+    });
+}
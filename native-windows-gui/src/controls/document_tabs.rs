@@ -0,0 +1,1161 @@
+use winapi::shared::windef::{HWND, HFONT, RECT};
+use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_TABSTOP, WS_BORDER, WS_CHILD, WS_CLIPSIBLINGS};
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
+use crate::win32::base_helper::{check_hwnd, to_utf16};
+use crate::win32::high_dpi::logical_to_physical;
+use crate::win32::paint_helper as ph;
+use crate::win32::document_tabs::{NMDOCUMENTTAB, NO_TAB, DOCUMENT_TAB_SELECT, DOCUMENT_TAB_CLOSE, DOCUMENT_TAB_REORDER, DOCUMENT_TAB_CONTEXT_MENU};
+use crate::{Font, NwgError, RawEventHandler, unbind_raw_event_handler};
+use super::{ControlBase, ControlHandle};
+use std::{mem, ptr, rc::Rc, cell::RefCell};
+
+const NOT_BOUND: &'static str = "DocumentTabs is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: DocumentTabs handle is not HWND!";
+
+// Sizes in logical pixels
+const TAB_MIN_WIDTH: i32 = 80;
+const TAB_MAX_WIDTH: i32 = 220;
+const TAB_PADDING: i32 = 10;
+const CLOSE_SIZE: i32 = 16;
+const CLOSE_MARGIN: i32 = 6;
+const DIRTY_DOT_SIZE: i32 = 8;
+const ACCENT_HEIGHT: i32 = 2;
+const OVERFLOW_WIDTH: i32 = 22;
+
+
+bitflags! {
+    /**
+        The document tabs flags
+
+        * NONE:     No flags. Equivalent to a invisible control.
+        * VISIBLE:  The tabs are immediatly visible after creation
+        * DISABLED: The tabs cannot be selected or closed by the user
+        * TAB_STOP: The control can be selected using tab navigation
+        * BORDER:   The control has a thin border
+    */
+    pub struct DocumentTabsFlags: u32 {
+        const NONE = 0;
+        const VISIBLE = WS_VISIBLE;
+        const DISABLED = WS_DISABLED;
+        const TAB_STOP = WS_TABSTOP;
+        const BORDER = WS_BORDER;
+    }
+}
+
+/// The part of the control under the mouse
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Hit {
+    Tab(usize),
+    Close(usize),
+    Overflow,
+}
+
+impl Hit {
+    fn tab(&self) -> Option<usize> {
+        match *self {
+            Hit::Tab(i) | Hit::Close(i) => Some(i),
+            Hit::Overflow => None
+        }
+    }
+}
+
+struct DocumentTab {
+    title: String,
+    dirty: bool,
+    control: Option<HWND>,
+}
+
+#[derive(Copy, Clone)]
+struct DragState {
+    index: usize,
+    start_x: i32,
+    moved: bool,
+}
+
+struct DocumentTabsState {
+    tabs: Vec<DocumentTab>,
+    selected: Option<usize>,
+    first_visible: usize,
+    font: HFONT,
+    close_buttons: bool,
+    reorderable: bool,
+    /// The visible tabs and their rectangle in physical pixels. Computed by `update_layout`.
+    layout: Vec<(usize, RECT)>,
+    overflow: Option<RECT>,
+    hover: Option<Hit>,
+    pressed_close: Option<usize>,
+    drag: Option<DragState>,
+    tracking_mouse: bool,
+}
+
+impl Default for DocumentTabsState {
+    fn default() -> DocumentTabsState {
+        DocumentTabsState {
+            tabs: Vec::new(),
+            selected: None,
+            first_visible: 0,
+            font: ptr::null_mut(),
+            close_buttons: true,
+            reorderable: true,
+            layout: Vec::new(),
+            overflow: None,
+            hover: None,
+            pressed_close: None,
+            drag: None,
+            tracking_mouse: false,
+        }
+    }
+}
+
+/**
+A strip of document tabs, as found in the text editors and the IDEs. Unlike `TabsContainer`, the control only displays
+the tabs: the application decides what to show when a tab is selected, and when a tab is closed.
+
+Each tab has a title, a close button, and a "dirty" marker (a dot drawn in place of the close button until the tab
+is hovered) to indicate that the document has unsaved changes. The user can:
+  * select a tab with a left click,
+  * close a tab with its close button or a middle click,
+  * move a tab by dragging it (if the control is `reorderable`),
+  * open a context menu with a right click.
+
+When the tabs do not fit in the control, the strip scrolls to keep the selected tab visible and a button at the right
+of the strip opens a menu listing all the tabs.
+
+A control can be associated with each tab with `set_tab_control`. When a tab is selected, its control is shown and the
+controls of the other tabs are hidden. If the control is a `MdiChild`, it is activated instead. To keep the tabs in sync
+when the user activates a MDI child in another way, call `set_selected` with the index returned by `find_tab` in `OnMdiChildActivate`.
+
+The control never removes a tab by itself: `OnDocumentTabClose` is raised and the application calls `remove_tab`
+(for example after asking the user to save the document). `set_selected`, `add_tab` and the other methods do not raise events.
+
+Positions (for example in `hit_test`) are in logical pixels relative to the top left corner of the control.
+
+Requires the `document-tabs` feature.
+
+**Builder parameters:**
+  * `parent`:        **Required.** The control parent container.
+  * `size`:          The control size.
+  * `position`:      The control position.
+  * `tabs`:          The titles of the initial tabs.
+  * `selected`:      The index of the selected tab. Defaults to the first tab.
+  * `close_buttons`: If the tabs have a close button. Defaults to `true`.
+  * `reorderable`:   If the user can move the tabs by dragging them. Defaults to `true`.
+  * `font`:          The font of the tab titles.
+  * `flags`:         A combination of the DocumentTabsFlags values.
+
+**Control events:**
+  * `OnDocumentTabSelect`: When the user selects a tab
+  * `OnDocumentTabClose`: When the user clicks the close button of a tab or middle-clicks it
+  * `OnDocumentTabReorder`: When the user moves a tab
+  * `OnDocumentTabContextMenu`: When the user right-clicks a tab
+  * `MousePress(_)`: Generic mouse press events on the control
+  * `OnMouseMove`: Generic mouse mouse event
+
+```rust
+use native_windows_gui as nwg;
+
+fn build_tabs(tabs: &mut nwg::DocumentTabs, window: &nwg::Window) {
+    nwg::DocumentTabs::builder()
+        .size((600, 30))
+        .tabs(&["main.rs", "lib.rs"])
+        .parent(window)
+        .build(tabs);
+}
+
+fn on_tab_close(tabs: &nwg::DocumentTabs, data: &nwg::EventData) {
+    let (index, _) = data.on_document_tab();
+    tabs.remove_tab(index);
+}
+```
+*/
+#[derive(Default)]
+pub struct DocumentTabs {
+    pub handle: ControlHandle,
+    state: Rc<RefCell<DocumentTabsState>>,
+    handler0: RefCell<Option<RawEventHandler>>,
+}
+
+impl DocumentTabs {
+
+    pub fn builder<'a>() -> DocumentTabsBuilder<'a> {
+        DocumentTabsBuilder {
+            size: (400, 30).into(),
+            position: (0, 0).into(),
+            tabs: &[],
+            selected: None,
+            close_buttons: true,
+            reorderable: true,
+            font: None,
+            flags: None,
+            parent: None,
+        }
+    }
+
+    /// Adds a tab at the end of the strip and returns its index.
+    /// If the strip was empty, the new tab is selected.
+    pub fn add_tab<'a>(&self, title: &'a str) -> usize {
+        let index = self.state.borrow().tabs.len();
+        self.insert_tab(index, title);
+        index
+    }
+
+    /// Inserts a tab at `index`. If the strip was empty, the new tab is selected.
+    /// Panics if `index` is greater than the number of tabs.
+    pub fn insert_tab<'a>(&self, index: usize, title: &'a str) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let controls = {
+            let mut state = self.state.borrow_mut();
+            state.tabs.insert(index, DocumentTab { title: title.to_string(), dirty: false, control: None });
+            state.selected = match state.selected {
+                Some(s) if s >= index => Some(s + 1),
+                Some(s) => Some(s),
+                None => Some(index)
+            };
+
+            reset_mouse(&mut state);
+            update_layout(handle, &mut state);
+            tab_controls(&state)
+        };
+
+        show_selected_control(&controls, self.selected());
+        ph::redraw(handle);
+    }
+
+    /// Removes the tab at `index`. If the tab was selected, the next tab (or the previous one if it was the last tab) is selected.
+    /// The control associated with the tab is not hidden nor destroyed.
+    /// Panics if `index` is out of bounds.
+    pub fn remove_tab(&self, index: usize) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let controls = {
+            let mut state = self.state.borrow_mut();
+            state.tabs.remove(index);
+
+            let count = state.tabs.len();
+            state.selected = match state.selected {
+                _ if count == 0 => None,
+                Some(s) if s > index => Some(s - 1),
+                Some(s) if s == index => Some(index.min(count - 1)),
+                s => s
+            };
+
+            reset_mouse(&mut state);
+            update_layout(handle, &mut state);
+            tab_controls(&state)
+        };
+
+        show_selected_control(&controls, self.selected());
+        ph::redraw(handle);
+    }
+
+    /// Removes all the tabs
+    pub fn clear(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        state.tabs.clear();
+        state.selected = None;
+        reset_mouse(&mut state);
+        update_layout(handle, &mut state);
+        ph::redraw(handle);
+    }
+
+    /// Returns the number of tabs
+    pub fn tab_count(&self) -> usize {
+        self.state.borrow().tabs.len()
+    }
+
+    /// Returns the title of the tab at `index`, or `None` if the index is out of bounds
+    pub fn tab_title(&self, index: usize) -> Option<String> {
+        self.state.borrow().tabs.get(index).map(|t| t.title.clone())
+    }
+
+    /// Sets the title of the tab at `index`.
+    /// Panics if `index` is out of bounds.
+    pub fn set_tab_title<'a>(&self, index: usize, title: &'a str) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        state.tabs[index].title = title.to_string();
+        update_layout(handle, &mut state);
+        ph::redraw(handle);
+    }
+
+    /// Returns `true` if the tab at `index` is marked as having unsaved changes
+    pub fn tab_dirty(&self, index: usize) -> bool {
+        self.state.borrow().tabs.get(index).map(|t| t.dirty).unwrap_or(false)
+    }
+
+    /// Marks the tab at `index` as having unsaved changes (or not). A dirty tab displays a dot in place of its close button.
+    /// Panics if `index` is out of bounds.
+    pub fn set_tab_dirty(&self, index: usize, dirty: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow_mut().tabs[index].dirty = dirty;
+        ph::redraw(handle);
+    }
+
+    /// Returns the control associated with the tab at `index`
+    pub fn tab_control(&self, index: usize) -> Option<ControlHandle> {
+        self.state.borrow().tabs.get(index)
+            .and_then(|t| t.control)
+            .map(|hwnd| ControlHandle::Hwnd(hwnd))
+    }
+
+    /// Associates a control with the tab at `index`. The control is shown when the tab is selected and hidden otherwise.
+    /// If the control is a `MdiChild`, it is activated when the tab is selected.
+    /// Panics if `index` is out of bounds or if the control is not a window-like control.
+    pub fn set_tab_control<C: Into<ControlHandle>>(&self, index: usize, control: C) {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let hwnd = control.into().hwnd().expect("Tab control to be a window-like control");
+
+        let controls = {
+            let mut state = self.state.borrow_mut();
+            state.tabs[index].control = Some(hwnd);
+            tab_controls(&state)
+        };
+
+        show_selected_control(&controls, self.selected());
+    }
+
+    /// Removes the control associated with the tab at `index`. The control is not hidden.
+    /// Panics if `index` is out of bounds.
+    pub fn remove_tab_control(&self, index: usize) {
+        self.state.borrow_mut().tabs[index].control = None;
+    }
+
+    /// Returns the index of the tab associated with `control`, if any
+    pub fn find_tab<C: Into<ControlHandle>>(&self, control: C) -> Option<usize> {
+        let hwnd = control.into().hwnd()?;
+        self.state.borrow().tabs.iter().position(|t| t.control == Some(hwnd))
+    }
+
+    /// Returns the index of the selected tab, or `None` if there are no tabs
+    pub fn selected(&self) -> Option<usize> {
+        self.state.borrow().selected
+    }
+
+    /// Selects the tab at `index`, and scrolls the strip to show it. Does not raise `OnDocumentTabSelect`.
+    /// Panics if `index` is out of bounds.
+    pub fn set_selected(&self, index: usize) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let controls = {
+            let mut state = self.state.borrow_mut();
+            assert!(index < state.tabs.len(), "Tab index out of bounds");
+            state.selected = Some(index);
+            update_layout(handle, &mut state);
+            tab_controls(&state)
+        };
+
+        show_selected_control(&controls, Some(index));
+        ph::redraw(handle);
+    }
+
+    /// Moves the tab at `from` to the index `to`. The selected tab stays selected. Does not raise `OnDocumentTabReorder`.
+    /// Panics if `from` or `to` are out of bounds.
+    pub fn move_tab(&self, from: usize, to: usize) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        assert!(to < state.tabs.len(), "Tab index out of bounds");
+        move_tab(&mut state, from, to);
+        reset_mouse(&mut state);
+        update_layout(handle, &mut state);
+        ph::redraw(handle);
+    }
+
+    /// Returns the index of the tab at the position `(x, y)`, or `None` if there is no visible tab there
+    pub fn hit_test(&self, x: i32, y: i32) -> Option<usize> {
+        let (x, y) = unsafe { logical_to_physical(x, y) };
+        hit_test(&self.state.borrow(), x, y).and_then(|hit| hit.tab())
+    }
+
+    /// Returns `true` if the tabs have a close button
+    pub fn close_buttons(&self) -> bool {
+        self.state.borrow().close_buttons
+    }
+
+    /// Shows or hides the close buttons of the tabs. Middle clicks still raise `OnDocumentTabClose`.
+    pub fn set_close_buttons(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow_mut().close_buttons = v;
+        ph::redraw(handle);
+    }
+
+    /// Returns `true` if the user can move the tabs
+    pub fn reorderable(&self) -> bool {
+        self.state.borrow().reorderable
+    }
+
+    /// Allows or forbids the user to move the tabs by dragging them
+    pub fn set_reorderable(&self, v: bool) {
+        self.state.borrow_mut().reorderable = v;
+    }
+
+    /// Returns the font of the control
+    pub fn font(&self) -> Option<Font> {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let font_handle = wh::get_window_font(handle);
+        if font_handle.is_null() {
+            None
+        } else {
+            Some(Font { handle: font_handle })
+        }
+    }
+
+    /// Sets the font of the control
+    pub fn set_font(&self, font: Option<&Font>) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_font(handle, font.map(|f| f.handle), true); }
+    }
+
+    /// Returns true if the control currently has the keyboard focus
+    pub fn focus(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_focus(handle) }
+    }
+
+    /// Sets the keyboard focus on the control
+    pub fn set_focus(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_focus(handle); }
+    }
+
+    /// Returns true if the control user can interact with the control, return false otherwise
+    pub fn enabled(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_enabled(handle) }
+    }
+
+    /// Enable or disable the control
+    pub fn set_enabled(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_enabled(handle, v) }
+    }
+
+    /// Returns true if the control is visible to the user. Will return true even if the
+    /// control is outside of the parent client view (ex: at the position (10000, 10000))
+    pub fn visible(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_visibility(handle) }
+    }
+
+    /// Show or hide the control to the user
+    pub fn set_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_visibility(handle, v) }
+    }
+
+    /// Returns the size of the control in the parent window
+    pub fn size(&self) -> (u32, u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_size(handle) }
+    }
+
+    /// Sets the size of the control in the parent window
+    pub fn set_size(&self, x: u32, y: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_size(handle, x, y, false) }
+    }
+
+    /// Returns the position of the control in the parent window
+    pub fn position(&self) -> (i32, i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_position(handle) }
+    }
+
+    /// Sets the position of the control in the parent window
+    pub fn set_position(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_position(handle, x, y) }
+    }
+
+    /// Winapi class name used during control creation
+    pub fn class_name(&self) -> &'static str {
+        "NWG_DOCUMENT_TABS"
+    }
+
+    /// Winapi base flags used during window creation
+    pub fn flags(&self) -> u32 {
+        WS_VISIBLE
+    }
+
+    /// Winapi flags required by the control
+    pub fn forced_flags(&self) -> u32 {
+        WS_CHILD | WS_CLIPSIBLINGS
+    }
+
+    /// Handles the painting and the mouse input of the tabs
+    fn hook_events(&self) {
+        use crate::bind_raw_event_handler_inner;
+        use winapi::um::winuser::{WM_PAINT, WM_ERASEBKGND, WM_SIZE, WM_SETFONT, WM_GETFONT, WM_ENABLE, WM_MOUSEMOVE, WM_MOUSELEAVE,
+            WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MBUTTONUP, WM_RBUTTONUP, WM_CAPTURECHANGED, SM_CXDRAG, TME_LEAVE, TRACKMOUSEEVENT};
+        use winapi::um::winuser::{TrackMouseEvent, SetCapture, ReleaseCapture, GetCapture, GetSystemMetrics};
+        use winapi::shared::minwindef::{LOWORD, HIWORD};
+
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let state = self.state.clone();
+
+        let handler = bind_raw_event_handler_inner(&self.handle, 0, move |hwnd, msg, w, l| {
+            // Mouse coordinates are signed
+            let (x, y) = (LOWORD(l as u32) as i16 as i32, HIWORD(l as u32) as i16 as i32);
+
+            match msg {
+                WM_PAINT => {
+                    paint(hwnd, &state.borrow());
+                    return Some(0);
+                },
+                WM_ERASEBKGND => {
+                    // Everything is drawn in WM_PAINT
+                    return Some(1);
+                },
+                WM_SIZE => {
+                    if let Ok(mut state) = state.try_borrow_mut() {
+                        update_layout(hwnd, &mut state);
+                        ph::redraw(hwnd);
+                    }
+                },
+                WM_SETFONT => {
+                    let mut state = state.borrow_mut();
+                    state.font = w as HFONT;
+                    update_layout(hwnd, &mut state);
+                    if l != 0 {
+                        ph::redraw(hwnd);
+                    }
+                    return Some(0);
+                },
+                WM_GETFONT => {
+                    return Some(state.borrow().font as _);
+                },
+                WM_ENABLE => {
+                    ph::redraw(hwnd);
+                },
+                WM_MOUSEMOVE => {
+                    let moved = {
+                        let mut state = state.borrow_mut();
+
+                        if !state.tracking_mouse {
+                            let mut track = TRACKMOUSEEVENT {
+                                cbSize: mem::size_of::<TRACKMOUSEEVENT>() as u32,
+                                dwFlags: TME_LEAVE,
+                                hwndTrack: hwnd,
+                                dwHoverTime: 0,
+                            };
+                            state.tracking_mouse = unsafe { TrackMouseEvent(&mut track) != 0 };
+                        }
+
+                        let mut moved = None;
+                        if let Some(mut drag) = state.drag {
+                            let threshold = unsafe { GetSystemMetrics(SM_CXDRAG) };
+                            drag.moved |= (x - drag.start_x).abs() > threshold;
+
+                            if drag.moved {
+                                if let Some(target) = drop_index(&state, drag.index, x) {
+                                    move_tab(&mut state, drag.index, target);
+                                    update_layout(hwnd, &mut state);
+                                    moved = Some((target, drag.index));
+                                    drag.index = target;
+                                }
+                            }
+
+                            state.drag = Some(drag);
+                        }
+
+                        let hover = hit_test(&state, x, y);
+                        if hover != state.hover || moved.is_some() {
+                            state.hover = hover;
+                            ph::redraw(hwnd);
+                        }
+
+                        moved
+                    };
+
+                    // The state must not be borrowed when the events are sent because the user handler may access the tabs
+                    if let Some((index, previous)) = moved {
+                        notify(hwnd, DOCUMENT_TAB_REORDER, index, previous);
+                    }
+                },
+                WM_MOUSELEAVE => {
+                    let mut state = state.borrow_mut();
+                    state.tracking_mouse = false;
+                    if state.hover.take().is_some() {
+                        ph::redraw(hwnd);
+                    }
+                },
+                WM_LBUTTONDOWN => {
+                    let hit = {
+                        let mut state = state.borrow_mut();
+                        let hit = hit_test(&state, x, y);
+                        match hit {
+                            Some(Hit::Close(i)) => {
+                                state.pressed_close = Some(i);
+                                unsafe { SetCapture(hwnd); }
+                            },
+                            Some(Hit::Tab(i)) if state.reorderable => {
+                                state.drag = Some(DragState { index: i, start_x: x, moved: false });
+                                unsafe { SetCapture(hwnd); }
+                            },
+                            _ => {}
+                        }
+                        hit
+                    };
+
+                    match hit {
+                        Some(Hit::Tab(i)) => select_tab(hwnd, &state, i),
+                        Some(Hit::Overflow) => {
+                            if let Some(i) = overflow_menu(hwnd, &state) {
+                                select_tab(hwnd, &state, i);
+                            }
+                        },
+                        _ => {}
+                    }
+                },
+                WM_LBUTTONUP => {
+                    let closed = {
+                        let mut state = state.borrow_mut();
+                        let pressed = state.pressed_close.take();
+                        state.drag = None;
+                        match hit_test(&state, x, y) {
+                            Some(Hit::Close(i)) if pressed == Some(i) => Some(i),
+                            _ => None
+                        }
+                    };
+
+                    unsafe {
+                        if GetCapture() == hwnd {
+                            ReleaseCapture();
+                        }
+                    }
+
+                    if let Some(index) = closed {
+                        notify(hwnd, DOCUMENT_TAB_CLOSE, index, NO_TAB);
+                    }
+                },
+                WM_CAPTURECHANGED => {
+                    let mut state = state.borrow_mut();
+                    state.pressed_close = None;
+                    state.drag = None;
+                },
+                WM_MBUTTONUP => {
+                    let hit = hit_test(&state.borrow(), x, y);
+                    if let Some(index) = hit.and_then(|h| h.tab()) {
+                        notify(hwnd, DOCUMENT_TAB_CLOSE, index, NO_TAB);
+                    }
+                },
+                WM_RBUTTONUP => {
+                    let hit = hit_test(&state.borrow(), x, y);
+                    if let Some(index) = hit.and_then(|h| h.tab()) {
+                        notify(hwnd, DOCUMENT_TAB_CONTEXT_MENU, index, NO_TAB);
+                    }
+                },
+                _ => {}
+            }
+
+            None
+        });
+
+        *self.handler0.borrow_mut() = Some(handler.unwrap());
+    }
+
+}
+
+impl Drop for DocumentTabs {
+    fn drop(&mut self) {
+        let handler = self.handler0.borrow();
+        if let Some(h) = handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
+        self.handle.destroy();
+    }
+}
+
+impl PartialEq for DocumentTabs {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for DocumentTabs {}
+
+pub struct DocumentTabsBuilder<'a> {
+    size: Extent,
+    position: Extent,
+    tabs: &'a [&'a str],
+    selected: Option<usize>,
+    close_buttons: bool,
+    reorderable: bool,
+    font: Option<&'a Font>,
+    flags: Option<DocumentTabsFlags>,
+    parent: Option<ControlHandle>
+}
+
+impl<'a> DocumentTabsBuilder<'a> {
+
+    pub fn flags(mut self, flags: DocumentTabsFlags) -> DocumentTabsBuilder<'a> {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> DocumentTabsBuilder<'a> {
+        self.size = size.into();
+        self
+    }
+
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> DocumentTabsBuilder<'a> {
+        self.position = pos.into();
+        self
+    }
+
+    pub fn tabs(mut self, tabs: &'a [&'a str]) -> DocumentTabsBuilder<'a> {
+        self.tabs = tabs;
+        self
+    }
+
+    pub fn selected(mut self, selected: Option<usize>) -> DocumentTabsBuilder<'a> {
+        self.selected = selected;
+        self
+    }
+
+    pub fn close_buttons(mut self, close_buttons: bool) -> DocumentTabsBuilder<'a> {
+        self.close_buttons = close_buttons;
+        self
+    }
+
+    pub fn reorderable(mut self, reorderable: bool) -> DocumentTabsBuilder<'a> {
+        self.reorderable = reorderable;
+        self
+    }
+
+    pub fn font(mut self, font: Option<&'a Font>) -> DocumentTabsBuilder<'a> {
+        self.font = font;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> DocumentTabsBuilder<'a> {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut DocumentTabs) -> Result<(), NwgError> {
+        let flags = self.flags.map(|f| f.bits()).unwrap_or(out.flags());
+
+        let parent = match self.parent {
+            Some(p) => Ok(p),
+            None => Err(NwgError::no_parent("DocumentTabs"))
+        }?;
+
+        *out = Default::default();
+
+        out.handle = ControlBase::build_hwnd()
+            .class_name(out.class_name())
+            .forced_flags(out.forced_flags())
+            .flags(flags)
+            .size(self.size)
+            .position(self.position)
+            .parent(Some(parent))
+            .build()?;
+
+        {
+            let mut state = out.state.borrow_mut();
+            state.tabs = self.tabs.iter()
+                .map(|title| DocumentTab { title: title.to_string(), dirty: false, control: None })
+                .collect();
+            state.selected = match state.tabs.is_empty() {
+                true => None,
+                false => Some(self.selected.unwrap_or(0).min(state.tabs.len() - 1))
+            };
+            state.close_buttons = self.close_buttons;
+            state.reorderable = self.reorderable;
+        }
+
+        out.hook_events();
+
+        if self.font.is_some() {
+            out.set_font(self.font);
+        } else {
+            out.set_font(Font::global_default().as_ref());
+        }
+
+        Ok(())
+    }
+
+}
+
+/// Sends a `NMDOCUMENTTAB` notification to the parent of the tabs
+fn notify(handle: HWND, code: u32, index: usize, previous: usize) {
+    use winapi::um::winuser::{NMHDR, WM_NOTIFY};
+
+    let notification = NMDOCUMENTTAB {
+        hdr: NMHDR { hwndFrom: handle, idFrom: 0, code },
+        index,
+        previous,
+    };
+
+    let parent = wh::get_window_parent(handle);
+    wh::send_message(parent, WM_NOTIFY, 0, &notification as *const NMDOCUMENTTAB as _);
+}
+
+/// Selects a tab clicked by the user and raises `OnDocumentTabSelect`
+fn select_tab(handle: HWND, state: &RefCell<DocumentTabsState>, index: usize) {
+    let changed = {
+        let mut state = state.borrow_mut();
+        let previous = state.selected;
+        match previous == Some(index) {
+            true => None,
+            false => {
+                state.selected = Some(index);
+                update_layout(handle, &mut state);
+                Some((previous, tab_controls(&state)))
+            }
+        }
+    };
+
+    if let Some((previous, controls)) = changed {
+        ph::redraw(handle);
+        show_selected_control(&controls, Some(index));
+        notify(handle, DOCUMENT_TAB_SELECT, index, previous.unwrap_or(NO_TAB));
+    }
+}
+
+/// Opens the menu listing all the tabs under the overflow button. Returns the index of the chosen tab.
+fn overflow_menu(handle: HWND, state: &RefCell<DocumentTabsState>) -> Option<usize> {
+    use winapi::um::winuser::{CreatePopupMenu, AppendMenuW, TrackPopupMenu, DestroyMenu, ClientToScreen, MF_STRING, MF_CHECKED,
+        TPM_RETURNCMD, TPM_RIGHTALIGN, TPM_TOPALIGN};
+    use winapi::shared::windef::POINT;
+
+    let (menu, mut anchor) = {
+        let state = state.borrow();
+        let button = state.overflow?;
+
+        let menu = unsafe { CreatePopupMenu() };
+        for (i, tab) in state.tabs.iter().enumerate() {
+            let mut flags = MF_STRING;
+            if state.selected == Some(i) {
+                flags |= MF_CHECKED;
+            }
+
+            let title = match tab.dirty {
+                true => format!("{} \u{25CF}", tab.title.replace('&', "&&")),
+                false => tab.title.replace('&', "&&")
+            };
+
+            let title = to_utf16(&title);
+            unsafe { AppendMenuW(menu, flags, i + 1, title.as_ptr()); }
+        }
+
+        (menu, POINT { x: button.right, y: button.bottom })
+    };
+
+    // The menu runs a modal loop: the state must not be borrowed
+    let command = unsafe {
+        ClientToScreen(handle, &mut anchor);
+        let command = TrackPopupMenu(menu, TPM_RETURNCMD | TPM_RIGHTALIGN | TPM_TOPALIGN, anchor.x, anchor.y, 0, handle, ptr::null());
+        DestroyMenu(menu);
+        command
+    };
+
+    match command {
+        0 => None,
+        c => Some(c as usize - 1)
+    }
+}
+
+/// Shows the control of the selected tab and hides the controls of the other tabs. MDI children are activated instead.
+fn show_selected_control(controls: &[Option<HWND>], selected: Option<usize>) {
+    use winapi::um::winuser::{WM_MDIACTIVATE, WS_EX_MDICHILD};
+
+    let is_mdi = |hwnd: HWND| wh::get_ex_style(hwnd) & WS_EX_MDICHILD == WS_EX_MDICHILD;
+    let valid = controls.iter().enumerate()
+        .filter_map(|(i, c)| c.map(|hwnd| (i, hwnd)))
+        .filter(|&(_, hwnd)| wh::window_valid(hwnd));
+
+    // Hide the other controls first to avoid drawing two documents at the same place
+    for (_, hwnd) in valid.clone().filter(|&(i, hwnd)| Some(i) != selected && !is_mdi(hwnd)) {
+        unsafe { wh::set_window_visibility(hwnd, false); }
+    }
+
+    for (_, hwnd) in valid.filter(|&(i, _)| Some(i) == selected) {
+        match is_mdi(hwnd) {
+            true => { wh::send_message(wh::get_window_parent(hwnd), WM_MDIACTIVATE, hwnd as _, 0); },
+            false => unsafe { wh::set_window_visibility(hwnd, true); }
+        }
+    }
+}
+
+fn tab_controls(state: &DocumentTabsState) -> Vec<Option<HWND>> {
+    state.tabs.iter().map(|t| t.control).collect()
+}
+
+/// Forgets the mouse interactions in progress. Called when the tabs are modified by the application.
+fn reset_mouse(state: &mut DocumentTabsState) {
+    state.hover = None;
+    state.pressed_close = None;
+    state.drag = None;
+}
+
+/// Moves a tab and keeps the same tab selected
+fn move_tab(state: &mut DocumentTabsState, from: usize, to: usize) {
+    let tab = state.tabs.remove(from);
+    state.tabs.insert(to, tab);
+
+    state.selected = state.selected.map(|s| match s {
+        s if s == from => to,
+        s if from < s && s <= to => s - 1,
+        s if to <= s && s < from => s + 1,
+        s => s
+    });
+}
+
+/// Returns the index where the dragged tab must be moved when the mouse is at `x`, or `None` if the tab must not move.
+/// A tab only swaps with its neighbour when the mouse is far enough in it, so that tabs of different widths do not swap back and forth.
+fn drop_index(state: &DocumentTabsState, dragged: usize, x: i32) -> Option<usize> {
+    let &(_, dragged_rect) = state.layout.iter().find(|&&(i, _)| i == dragged)?;
+    let dragged_width = dragged_rect.right - dragged_rect.left;
+
+    let target = state.layout.iter()
+        .find(|&&(_, r)| x >= r.left && x < r.right)
+        .or_else(|| match x < dragged_rect.left {
+            true => state.layout.first(),
+            false => state.layout.last()
+        })?;
+
+    let (index, rect) = *target;
+    let far_enough = match index > dragged {
+        true => x > rect.right - dragged_width,
+        false => x < rect.left + dragged_width
+    };
+
+    match index != dragged && far_enough {
+        true => Some(index),
+        false => None
+    }
+}
+
+fn hit_test(state: &DocumentTabsState, x: i32, y: i32) -> Option<Hit> {
+    let inside = |r: &RECT| x >= r.left && x < r.right && y >= r.top && y < r.bottom;
+
+    if let Some(button) = state.overflow.as_ref() {
+        if inside(button) {
+            return Some(Hit::Overflow);
+        }
+    }
+
+    let &(index, rect) = state.layout.iter().find(|(_, r)| inside(r))?;
+    match state.close_buttons && inside(&close_rect(&rect)) {
+        true => Some(Hit::Close(index)),
+        false => Some(Hit::Tab(index))
+    }
+}
+
+/// Returns the width of each tab in physical pixels
+fn tab_widths(handle: HWND, state: &DocumentTabsState) -> Vec<i32> {
+    use winapi::um::winuser::{GetDC, ReleaseDC};
+    use winapi::um::wingdi::{SelectObject, GetTextExtentPoint32W};
+    use winapi::shared::windef::SIZE;
+
+    unsafe {
+        let dc = GetDC(handle);
+        let old_font = SelectObject(dc, ph::font_or(state.font, ph::gui_font()) as _);
+
+        let widths = state.tabs.iter().map(|tab| {
+            let text: Vec<u16> = tab.title.encode_utf16().collect();
+            let mut size = SIZE { cx: 0, cy: 0 };
+            GetTextExtentPoint32W(dc, text.as_ptr(), text.len() as i32, &mut size);
+
+            let width = ph::px(TAB_PADDING) + size.cx + ph::px(CLOSE_MARGIN) + ph::px(CLOSE_SIZE) + ph::px(CLOSE_MARGIN);
+            width.max(ph::px(TAB_MIN_WIDTH)).min(ph::px(TAB_MAX_WIDTH))
+        }).collect();
+
+        SelectObject(dc, old_font);
+        ReleaseDC(handle, dc);
+
+        widths
+    }
+}
+
+/// Computes the rectangles of the visible tabs. If the tabs do not fit, the strip is scrolled to show the selected tab
+/// and the overflow button is displayed.
+fn update_layout(handle: HWND, state: &mut DocumentTabsState) {
+    let (width, height) = ph::client_size(handle);
+    let widths = tab_widths(handle, state);
+    let count = widths.len();
+
+    state.layout.clear();
+    state.overflow = None;
+
+    if count == 0 {
+        state.first_visible = 0;
+        return;
+    }
+
+    let total: i32 = widths.iter().sum();
+    let available = match total <= width {
+        true => width,
+        false => {
+            let button_width = ph::px(OVERFLOW_WIDTH);
+            state.overflow = Some(RECT { left: width - button_width, top: 0, right: width, bottom: height });
+            width - button_width
+        }
+    };
+
+    state.first_visible = state.first_visible.min(count - 1);
+    if let Some(selected) = state.selected {
+        if selected < state.first_visible {
+            state.first_visible = selected;
+        }
+
+        while state.first_visible < selected && widths[state.first_visible..=selected].iter().sum::<i32>() > available {
+            state.first_visible += 1;
+        }
+    }
+
+    // Scroll back when there is free space at the end of the strip (ex: after closing the last tabs)
+    while state.first_visible > 0 && widths[state.first_visible - 1..].iter().sum::<i32>() <= available {
+        state.first_visible -= 1;
+    }
+
+    let mut x = 0;
+    for i in state.first_visible..count {
+        if x + widths[i] > available && i != state.first_visible {
+            break;
+        }
+
+        let right = (x + widths[i]).min(available);
+        state.layout.push((i, RECT { left: x, top: 0, right, bottom: height }));
+        x = right;
+    }
+}
+
+/// Returns the rectangle of the close button of a tab
+fn close_rect(tab: &RECT) -> RECT {
+    let size = ph::px(CLOSE_SIZE);
+    let right = tab.right - ph::px(CLOSE_MARGIN);
+    let top = tab.top + (tab.bottom - tab.top - size) / 2;
+    RECT { left: right - size, top, right, bottom: top + size }
+}
+
+/// Paints the tabs in a memory bitmap and copies the result on the screen
+fn paint(handle: HWND, state: &DocumentTabsState) {
+    use winapi::um::winuser::{FillRect, GetSysColorBrush, GetSysColor, DrawTextW,
+        COLOR_BTNFACE, COLOR_WINDOW, COLOR_3DLIGHT, COLOR_BTNSHADOW, COLOR_HIGHLIGHT, COLOR_BTNTEXT, COLOR_GRAYTEXT,
+        DT_LEFT, DT_SINGLELINE, DT_VCENTER, DT_END_ELLIPSIS, DT_NOPREFIX};
+    use winapi::um::wingdi::{SetBkMode, SetTextColor, TRANSPARENT};
+
+    ph::paint_buffered(handle, ph::font_or(state.font, ph::gui_font()), |buffer_dc, width, height| unsafe {
+        let enabled = wh::get_window_enabled(handle);
+
+        FillRect(buffer_dc, &RECT { left: 0, top: 0, right: width, bottom: height }, GetSysColorBrush(COLOR_BTNFACE));
+
+        SetBkMode(buffer_dc, TRANSPARENT as i32);
+        SetTextColor(buffer_dc, GetSysColor(if enabled { COLOR_BTNTEXT } else { COLOR_GRAYTEXT }));
+
+        for &(index, rect) in state.layout.iter() {
+            let tab = &state.tabs[index];
+            let selected = state.selected == Some(index);
+            let hovered = state.hover.and_then(|h| h.tab()) == Some(index);
+
+            if selected {
+                FillRect(buffer_dc, &rect, GetSysColorBrush(COLOR_WINDOW));
+                let accent = RECT { bottom: rect.top + ph::px(ACCENT_HEIGHT), ..rect };
+                FillRect(buffer_dc, &accent, GetSysColorBrush(COLOR_HIGHLIGHT));
+            } else {
+                if hovered {
+                    FillRect(buffer_dc, &rect, GetSysColorBrush(COLOR_3DLIGHT));
+                }
+
+                let margin = (rect.bottom - rect.top) / 4;
+                let separator = RECT { left: rect.right - 1, top: rect.top + margin, right: rect.right, bottom: rect.bottom - margin };
+                FillRect(buffer_dc, &separator, GetSysColorBrush(COLOR_BTNSHADOW));
+            }
+
+            let close = close_rect(&rect);
+            let mut text_rect = RECT { left: rect.left + ph::px(TAB_PADDING), right: close.left - ph::px(CLOSE_MARGIN), ..rect };
+            let title = to_utf16(&tab.title);
+            DrawTextW(buffer_dc, title.as_ptr(), -1, &mut text_rect, DT_LEFT | DT_SINGLELINE | DT_VCENTER | DT_END_ELLIPSIS | DT_NOPREFIX);
+
+            if tab.dirty && !hovered {
+                draw_dirty_dot(buffer_dc, &close);
+            } else if state.close_buttons && (selected || hovered) {
+                let close_hovered = state.hover == Some(Hit::Close(index));
+                draw_close_button(buffer_dc, &close, close_hovered);
+            }
+        }
+
+        if let Some(button) = state.overflow {
+            if state.hover == Some(Hit::Overflow) {
+                FillRect(buffer_dc, &button, GetSysColorBrush(COLOR_3DLIGHT));
+            }
+            draw_overflow_arrow(buffer_dc, &button);
+        }
+    });
+}
+
+unsafe fn draw_dirty_dot(dc: winapi::shared::windef::HDC, close: &RECT) {
+    use winapi::um::winuser::{GetSysColor, COLOR_BTNTEXT};
+    use winapi::um::wingdi::{CreateSolidBrush, GetStockObject, SelectObject, DeleteObject, Ellipse, NULL_PEN};
+
+    let size = ph::px(DIRTY_DOT_SIZE);
+    let x = close.left + (close.right - close.left - size) / 2;
+    let y = close.top + (close.bottom - close.top - size) / 2;
+
+    let brush = CreateSolidBrush(GetSysColor(COLOR_BTNTEXT));
+    let old_brush = SelectObject(dc, brush as _);
+    let old_pen = SelectObject(dc, GetStockObject(NULL_PEN as _));
+
+    Ellipse(dc, x, y, x + size + 1, y + size + 1);
+
+    SelectObject(dc, old_pen);
+    SelectObject(dc, old_brush);
+    DeleteObject(brush as _);
+}
+
+/// Draws the "x" of a close button. The background is highlighted when the button is hovered.
+unsafe fn draw_close_button(dc: winapi::shared::windef::HDC, close: &RECT, hovered: bool) {
+    use winapi::um::winuser::{FillRect, GetSysColor, GetSysColorBrush, COLOR_BTNTEXT, COLOR_BTNSHADOW};
+    use winapi::um::wingdi::{CreatePen, SelectObject, DeleteObject, MoveToEx, LineTo, PS_SOLID};
+
+    if hovered {
+        FillRect(dc, close, GetSysColorBrush(COLOR_BTNSHADOW));
+    }
+
+    let inset = ph::px(4);
+    let (left, top, right, bottom) = (close.left + inset, close.top + inset, close.right - inset, close.bottom - inset);
+
+    let pen = CreatePen(PS_SOLID as _, ph::px(1), GetSysColor(COLOR_BTNTEXT));
+    let old_pen = SelectObject(dc, pen as _);
+
+    // LineTo does not draw the last pixel
+    MoveToEx(dc, left, top, ptr::null_mut());
+    LineTo(dc, right, bottom);
+    MoveToEx(dc, right - 1, top, ptr::null_mut());
+    LineTo(dc, left - 1, bottom);
+
+    SelectObject(dc, old_pen);
+    DeleteObject(pen as _);
+}
+
+unsafe fn draw_overflow_arrow(dc: winapi::shared::windef::HDC, button: &RECT) {
+    use winapi::um::winuser::{GetSysColor, COLOR_BTNTEXT};
+    use winapi::um::wingdi::{CreateSolidBrush, GetStockObject, SelectObject, DeleteObject, Polygon, NULL_PEN};
+    use winapi::shared::windef::POINT;
+
+    let half = ph::px(4);
+    let cx = (button.left + button.right) / 2;
+    let cy = (button.top + button.bottom) / 2;
+    let points = [
+        POINT { x: cx - half, y: cy - half / 2 },
+        POINT { x: cx + half + 1, y: cy - half / 2 },
+        POINT { x: cx, y: cy + half / 2 + 1 },
+    ];
+
+    let brush = CreateSolidBrush(GetSysColor(COLOR_BTNTEXT));
+    let old_brush = SelectObject(dc, brush as _);
+    let old_pen = SelectObject(dc, GetStockObject(NULL_PEN as _));
+
+    Polygon(dc, points.as_ptr(), points.len() as i32);
+
+    SelectObject(dc, old_pen);
+    SelectObject(dc, old_brush);
+    DeleteObject(brush as _);
+}
@@ -2,6 +2,7 @@ use winapi::um::winuser::{WS_OVERLAPPEDWINDOW, WS_VISIBLE, WS_DISABLED, WS_MAXIM
 WS_MINIMIZEBOX, WS_MAXIMIZEBOX, WS_SYSMENU, WS_THICKFRAME, WS_CLIPCHILDREN, WS_CLIPSIBLINGS };
 
 use crate::win32::base_helper::check_hwnd;
+use crate::win32::high_dpi::Extent;
 use crate::win32::window_helper as wh;
 use crate::{NwgError, Icon};
 use super::{ControlBase, ControlHandle};
@@ -81,6 +82,7 @@ bitflags! {
       * `OnWindowMinimize`: When the window is minimized
       * `OnMove`: When the window is moved by the user
       * `OnMinMaxInfo`: When the size or position of the window is about to change and the size of the windows must be restricted
+      * `OnPointerDown`, `OnPointerUpdate`, `OnPointerUp`: When a pen or a finger is used on the canvas, with the pressure and the tilt of the pen (requires the `pointer` feature)
 
 */
 #[derive(Default)]
@@ -93,8 +95,8 @@ impl ExternCanvas {
     pub fn builder<'a>() -> ExternCanvasBuilder<'a> {
         ExternCanvasBuilder {
             title: "New Canvas",
-            size: (500, 500),
-            position: (300, 300),
+            size: (500, 500).into(),
+            position: (300, 300).into(),
             flags: None,
             ex_flags: 0,
             icon: None,
@@ -267,8 +269,8 @@ unsafe impl HasRawWindowHandle for ExternCanvas {
 
 pub struct ExternCanvasBuilder<'a> {
     title: &'a str,
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     flags: Option<ExternCanvasFlags>,
     ex_flags: u32,
     icon: Option<&'a Icon>,
@@ -292,13 +294,13 @@ impl<'a> ExternCanvasBuilder<'a> {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> ExternCanvasBuilder<'a> {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> ExternCanvasBuilder<'a> {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> ExternCanvasBuilder<'a> {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> ExternCanvasBuilder<'a> {
+        self.position = pos.into();
         self
     }
 
@@ -1,5 +1,6 @@
 use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_BORDER, WS_CHILD, WS_CLIPCHILDREN, WS_EX_CONTROLPARENT};
 use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
 use crate::win32::base_helper::check_hwnd;
 use crate::NwgError;
 use super::{ControlBase, ControlHandle};
@@ -52,8 +53,8 @@ impl Frame {
 
     pub fn builder() -> FrameBuilder {
         FrameBuilder {
-            size: (100, 25),
-            position: (0, 0),
+            size: (100, 25).into(),
+            position: (0, 0).into(),
             enabled: true,
             flags: None,
             ex_flags: 0,
@@ -145,8 +146,8 @@ impl Drop for Frame {
     }
 }
 pub struct FrameBuilder {
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     enabled: bool,
     flags: Option<FrameFlags>,
     ex_flags: u32,
@@ -165,13 +166,13 @@ impl FrameBuilder {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> FrameBuilder {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> FrameBuilder {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> FrameBuilder {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> FrameBuilder {
+        self.position = pos.into();
         self
     }
 
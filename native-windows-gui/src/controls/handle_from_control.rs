@@ -29,6 +29,23 @@ macro_rules! handles {
                 *self == other.handle
             }
         }
+
+        #[allow(deprecated)]
+        impl $control {
+            /// Binds a callback to a single event of the control. See `ControlHandle::on`.
+            pub fn on<F>(&self, event: crate::Event, f: F) -> crate::EventHandler
+                where F: Fn(crate::EventData) + 'static
+            {
+                self.handle.on(event, f)
+            }
+
+            /// Binds a callback to a single event of the control. See `ControlHandle::on_guarded`.
+            pub fn on_guarded<F>(&self, event: crate::Event, f: F) -> crate::EventHandlerGuard
+                where F: Fn(crate::EventData) + 'static
+            {
+                self.handle.on_guarded(event, f)
+            }
+        }
     };
 }
 
@@ -109,7 +126,7 @@ use super::TrackBar;
 handles!(TrackBar);
 
 #[cfg(feature = "menu")]
-use super::{Menu, MenuItem, MenuSeparator};
+use super::{Menu, MenuItem, MenuSeparator, MenuRadioGroup};
 
 #[cfg(feature = "menu")]
 handles!(Menu);
@@ -117,6 +134,8 @@ handles!(Menu);
 handles!(MenuItem);
 #[cfg(feature = "menu")]
 handles!(MenuSeparator);
+#[cfg(feature = "menu")]
+handles!(MenuRadioGroup);
 
 #[cfg(feature = "combobox")]
 use super::ComboBox;
@@ -178,6 +197,118 @@ use super::{DatePicker};
 #[cfg(feature = "datetime-picker")]
 handles!(DatePicker);
 
+#[cfg(feature = "month-calendar")]
+use super::MonthCalendar;
+
+#[cfg(feature = "month-calendar")]
+handles!(MonthCalendar);
+
+#[cfg(feature = "hotkey")]
+use super::HotKey;
+
+#[cfg(feature = "hotkey")]
+handles!(HotKey);
+
+#[cfg(feature = "toolbar")]
+use super::Toolbar;
+
+#[cfg(feature = "toolbar")]
+handles!(Toolbar);
+
+#[cfg(feature = "splitter")]
+use super::Splitter;
+
+#[cfg(feature = "splitter")]
+handles!(Splitter);
+
+#[cfg(feature = "scroll-panel")]
+use super::ScrollPanel;
+
+/// Children of a scroll panel are created in its content window
+#[cfg(feature = "scroll-panel")]
+impl From<&ScrollPanel> for ControlHandle {
+    fn from(control: &ScrollPanel) -> Self { control.content() }
+}
+
+#[cfg(feature = "scroll-panel")]
+impl From<&mut ScrollPanel> for ControlHandle {
+    fn from(control: &mut ScrollPanel) -> Self { control.content() }
+}
+
+#[cfg(feature = "scroll-panel")]
+impl PartialEq<ControlHandle> for ScrollPanel {
+    fn eq(&self, other: &ControlHandle) -> bool {
+        self.handle == *other
+    }
+}
+
+#[cfg(feature = "scroll-panel")]
+impl PartialEq<ScrollPanel> for ControlHandle {
+    fn eq(&self, other: &ScrollPanel) -> bool {
+        *self == other.handle
+    }
+}
+
+#[cfg(feature = "image-viewer")]
+use super::ImageViewer;
+
+#[cfg(feature = "image-viewer")]
+handles!(ImageViewer);
+
+#[cfg(feature = "animated-image")]
+use super::AnimatedImage;
+
+#[cfg(feature = "animated-image")]
+handles!(AnimatedImage);
+
+#[cfg(feature = "mdi")]
+use super::MdiChild;
+
+#[cfg(feature = "mdi")]
+handles!(MdiChild);
+
+#[cfg(feature = "chart")]
+use super::Chart;
+
+#[cfg(feature = "chart")]
+handles!(Chart);
+
+#[cfg(feature = "layered-window")]
+use super::LayeredWindow;
+
+#[cfg(feature = "layered-window")]
+handles!(LayeredWindow);
+
+#[cfg(feature = "document-tabs")]
+use super::DocumentTabs;
+
+#[cfg(feature = "document-tabs")]
+handles!(DocumentTabs);
+
+#[cfg(feature = "hex-view")]
+use super::HexView;
+
+#[cfg(feature = "hex-view")]
+handles!(HexView);
+
+#[cfg(feature = "terminal-view")]
+use super::TerminalView;
+
+#[cfg(feature = "terminal-view")]
+handles!(TerminalView);
+
+#[cfg(feature = "diff-view")]
+use super::DiffView;
+
+#[cfg(feature = "diff-view")]
+handles!(DiffView);
+
+#[cfg(feature = "markdown-view")]
+use super::MarkdownView;
+
+#[cfg(feature = "markdown-view")]
+handles!(MarkdownView);
+
 #[cfg(feature = "progress-bar")]
 use super::ProgressBar;
 
@@ -215,6 +346,12 @@ use super::AnimationTimer;
 #[cfg(feature = "animation-timer")]
 handles!(AnimationTimer);
 
+#[cfg(feature = "animator")]
+use super::Animator;
+
+#[cfg(feature = "animator")]
+handles!(Animator);
+
 #[cfg(feature = "notice")]
 use super::Notice;
 
@@ -0,0 +1,1086 @@
+use winapi::shared::windef::{HWND, HFONT, HDC, RECT};
+use winapi::shared::minwindef::WPARAM;
+use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_TABSTOP, WS_BORDER, WS_CHILD, WS_CLIPSIBLINGS, WS_VSCROLL, SB_VERT};
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
+use crate::win32::base_helper::check_hwnd;
+use crate::win32::paint_helper as ph;
+use crate::win32::hex_view::{NMHEXVIEW, HEX_VIEW_SELECTION_CHANGED, HEX_VIEW_EDIT};
+use crate::{Font, Clipboard, NwgError, RawEventHandler, unbind_raw_event_handler};
+use super::{ControlBase, ControlHandle};
+use std::{mem, ptr, rc::Rc, cell::RefCell};
+
+const NOT_BOUND: &'static str = "HexView is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: HexView handle is not HWND!";
+
+/// Margin at the left of the offsets, in logical pixels
+const MARGIN: i32 = 4;
+
+/// Rows scrolled by one notch of the mouse wheel
+const WHEEL_ROWS: i64 = 3;
+
+/// Largest selection copied to the clipboard
+const MAX_COPY: u64 = 16 * 1024 * 1024;
+
+const MAX_BYTES_PER_ROW: u32 = 64;
+
+
+bitflags! {
+    /**
+        The hex view flags
+
+        * NONE:     No flags. Equivalent to a invisible hex view.
+        * VISIBLE:  The hex view is immediatly visible after creation
+        * DISABLED: The hex view cannot be focused or edited by the user
+        * TAB_STOP: The hex view can be selected using tab navigation
+        * BORDER:   The hex view has a thin border
+    */
+    pub struct HexViewFlags: u32 {
+        const NONE = 0;
+        const VISIBLE = WS_VISIBLE;
+        const DISABLED = WS_DISABLED;
+        const TAB_STOP = WS_TABSTOP;
+        const BORDER = WS_BORDER;
+    }
+}
+
+/**
+    The data displayed by a `HexView`. The hex view only reads the bytes of the visible rows, so a provider
+    can expose a large file or the memory of a device without loading it.
+
+    `Vec<u8>` implements this trait.
+*/
+pub trait HexDataProvider {
+    /// Returns the size of the data in bytes
+    fn len(&self) -> u64;
+
+    /// Returns `true` if there is no data
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Copies the bytes starting at `offset` in `buffer`. Returns the number of bytes copied.
+    fn read(&self, offset: u64, buffer: &mut [u8]) -> usize;
+
+    /// Writes `data` at `offset`. Returns `false` if the data cannot be modified. The default implementation is read only.
+    fn write(&mut self, _offset: u64, _data: &[u8]) -> bool {
+        false
+    }
+}
+
+impl HexDataProvider for Vec<u8> {
+
+    fn len(&self) -> u64 {
+        Vec::len(self) as u64
+    }
+
+    fn read(&self, offset: u64, buffer: &mut [u8]) -> usize {
+        let start = (offset.min(Vec::len(self) as u64)) as usize;
+        let end = (start + buffer.len()).min(Vec::len(self));
+        buffer[..end - start].copy_from_slice(&self[start..end]);
+        end - start
+    }
+
+    fn write(&mut self, offset: u64, data: &[u8]) -> bool {
+        let start = offset as usize;
+        match offset + data.len() as u64 <= Vec::len(self) as u64 {
+            true => {
+                self[start..start + data.len()].copy_from_slice(data);
+                true
+            },
+            false => false
+        }
+    }
+
+}
+
+/// The column that receives the keyboard input
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum HexColumn {
+    Hex,
+    Ascii,
+}
+
+struct HexViewState {
+    data: Box<dyn HexDataProvider>,
+    bytes_per_row: u32,
+    read_only: bool,
+    top_row: u64,
+    anchor: u64,
+    caret: u64,
+    column: HexColumn,
+    /// If the next hex digit typed replaces the low nibble of the caret byte
+    low_nibble: bool,
+    selecting: bool,
+    font: HFONT,
+    default_font: HFONT,
+    char_size: (i32, i32),
+}
+
+impl Default for HexViewState {
+    fn default() -> HexViewState {
+        HexViewState {
+            data: Box::new(Vec::new()),
+            bytes_per_row: 16,
+            read_only: false,
+            top_row: 0,
+            anchor: 0,
+            caret: 0,
+            column: HexColumn::Hex,
+            low_nibble: false,
+            selecting: false,
+            font: ptr::null_mut(),
+            default_font: ptr::null_mut(),
+            char_size: (8, 16),
+        }
+    }
+}
+
+impl Drop for HexViewState {
+    fn drop(&mut self) {
+        ph::delete_font(self.default_font);
+    }
+}
+
+/**
+A hex view displays binary data in three columns: the offset of each row, the bytes in hexadecimal, and the bytes as ASCII
+characters (non printable bytes are displayed as `.`). The data is read from a `HexDataProvider`, one screen at a time,
+so the control can display data of any size.
+
+The user selects bytes with the mouse or with the keyboard (arrows, page up/down, home/end, with shift to extend the
+selection) and copies them with ctrl+C. Unless the view is read only, typing hex digits in the hex column or characters
+in the ASCII column overwrites the bytes at the caret. The size of the data never changes.
+
+Offsets are expressed in bytes. The control uses the "Consolas" font unless a font is set; the font must be monospaced.
+
+Requires the `hex-view` feature.
+
+**Builder parameters:**
+  * `parent`:        **Required.** The hex view parent container.
+  * `size`:          The hex view size.
+  * `position`:      The hex view position.
+  * `data`:          The data provider. Defaults to an empty `Vec<u8>`.
+  * `bytes_per_row`: The number of bytes displayed on each row, from 1 to 64. Defaults to 16.
+  * `read_only`:     If the user cannot modify the bytes. Defaults to `false`.
+  * `font`:          A monospaced font.
+  * `flags`:         A combination of the HexViewFlags values.
+
+**Control events:**
+  * `OnHexViewSelectionChanged`: When the user changes the selection. The event data is a `EventData::OnHexView`.
+  * `OnHexViewEdit`: When the user modifies a byte. The event data is a `EventData::OnHexView`.
+  * `MousePress(_)`: Generic mouse press events on the hex view
+  * `OnKeyPress`: Generic key press event
+
+```rust
+use native_windows_gui as nwg;
+
+fn build_hex_view(view: &mut nwg::HexView, window: &nwg::Window) {
+    let firmware: Vec<u8> = std::fs::read("firmware.bin").unwrap_or_default();
+
+    nwg::HexView::builder()
+        .size((600, 400))
+        .data(firmware)
+        .read_only(true)
+        .parent(window)
+        .build(view);
+}
+```
+*/
+#[derive(Default)]
+pub struct HexView {
+    pub handle: ControlHandle,
+    state: Rc<RefCell<HexViewState>>,
+    handler0: RefCell<Option<RawEventHandler>>,
+}
+
+impl HexView {
+
+    pub fn builder<'a>() -> HexViewBuilder<'a> {
+        HexViewBuilder {
+            size: (500, 300).into(),
+            position: (0, 0).into(),
+            data: None,
+            bytes_per_row: 16,
+            read_only: false,
+            font: None,
+            flags: None,
+            parent: None,
+        }
+    }
+
+    /// Replaces the data displayed by the view. The selection is reset and the view scrolls to the start of the data.
+    pub fn set_data<P: HexDataProvider + 'static>(&self, data: P) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        state.data = Box::new(data);
+        state.top_row = 0;
+        state.anchor = 0;
+        state.caret = 0;
+        state.low_nibble = false;
+        update_scroll(handle, &mut state);
+        ph::redraw(handle);
+    }
+
+    /// Redraws the view after the data of the provider was modified by the application.
+    /// The selection is kept inside the data if its size changed.
+    pub fn refresh(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        let last = state.data.len().saturating_sub(1);
+        state.anchor = state.anchor.min(last);
+        state.caret = state.caret.min(last);
+        update_scroll(handle, &mut state);
+        ph::redraw(handle);
+    }
+
+    /// Returns the size of the data in bytes
+    pub fn data_len(&self) -> u64 {
+        self.state.borrow().data.len()
+    }
+
+    /// Reads `length` bytes at `offset`. The returned vector is shorter if the range goes past the end of the data.
+    pub fn read(&self, offset: u64, length: usize) -> Vec<u8> {
+        let state = self.state.borrow();
+        let mut buffer = vec![0; length];
+        let count = state.data.read(offset, &mut buffer);
+        buffer.truncate(count);
+        buffer
+    }
+
+    /// Writes bytes at `offset`, even if the view is read only. Returns `false` if the provider cannot modify the data.
+    /// Does not raise `OnHexViewEdit`.
+    pub fn write(&self, offset: u64, data: &[u8]) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let written = self.state.borrow_mut().data.write(offset, data);
+        ph::redraw(handle);
+        written
+    }
+
+    /// Returns the selection as `(offset, length)`. The length is 0 if there is no data.
+    pub fn selection(&self) -> (u64, u64) {
+        selection(&self.state.borrow())
+    }
+
+    /// Selects `length` bytes starting at `offset` and scrolls the view to show the start of the selection.
+    /// The range is clamped to the data. Does not raise `OnHexViewSelectionChanged`.
+    pub fn set_selection(&self, offset: u64, length: u64) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        let last = state.data.len().saturating_sub(1);
+        state.anchor = offset.min(last);
+        state.caret = offset.saturating_add(length.max(1) - 1).min(last);
+        state.low_nibble = false;
+
+        let anchor = state.anchor;
+        scroll_to_offset(handle, &mut state, anchor);
+        ph::redraw(handle);
+    }
+
+    /// Scrolls the view so that the byte at `offset` is visible
+    pub fn scroll_to(&self, offset: u64) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        scroll_to_offset(handle, &mut self.state.borrow_mut(), offset);
+        ph::redraw(handle);
+    }
+
+    /// Returns the number of bytes displayed on each row
+    pub fn bytes_per_row(&self) -> u32 {
+        self.state.borrow().bytes_per_row
+    }
+
+    /// Sets the number of bytes displayed on each row, from 1 to 64
+    pub fn set_bytes_per_row(&self, count: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        state.bytes_per_row = count.max(1).min(MAX_BYTES_PER_ROW);
+
+        let caret = state.caret;
+        scroll_to_offset(handle, &mut state, caret);
+        ph::redraw(handle);
+    }
+
+    /// Returns `true` if the user cannot modify the bytes
+    pub fn read_only(&self) -> bool {
+        self.state.borrow().read_only
+    }
+
+    /// Sets if the user can modify the bytes
+    pub fn set_read_only(&self, v: bool) {
+        self.state.borrow_mut().read_only = v;
+    }
+
+    /// Returns the font of the control
+    pub fn font(&self) -> Option<Font> {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let font_handle = wh::get_window_font(handle);
+        if font_handle.is_null() {
+            None
+        } else {
+            Some(Font { handle: font_handle })
+        }
+    }
+
+    /// Sets the font of the control. The font must be monospaced. `None` restores the default font.
+    pub fn set_font(&self, font: Option<&Font>) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_font(handle, font.map(|f| f.handle), true); }
+    }
+
+    /// Returns true if the control currently has the keyboard focus
+    pub fn focus(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_focus(handle) }
+    }
+
+    /// Sets the keyboard focus on the hex view
+    pub fn set_focus(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_focus(handle); }
+    }
+
+    /// Returns true if the control user can interact with the control, return false otherwise
+    pub fn enabled(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_enabled(handle) }
+    }
+
+    /// Enable or disable the control
+    pub fn set_enabled(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_enabled(handle, v) }
+    }
+
+    /// Returns true if the control is visible to the user. Will return true even if the
+    /// control is outside of the parent client view (ex: at the position (10000, 10000))
+    pub fn visible(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_visibility(handle) }
+    }
+
+    /// Show or hide the control to the user
+    pub fn set_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_visibility(handle, v) }
+    }
+
+    /// Returns the size of the hex view in the parent window
+    pub fn size(&self) -> (u32, u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_size(handle) }
+    }
+
+    /// Sets the size of the hex view in the parent window
+    pub fn set_size(&self, x: u32, y: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_size(handle, x, y, false) }
+    }
+
+    /// Returns the position of the hex view in the parent window
+    pub fn position(&self) -> (i32, i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_position(handle) }
+    }
+
+    /// Sets the position of the hex view in the parent window
+    pub fn set_position(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_position(handle, x, y) }
+    }
+
+    /// Winapi class name used during control creation
+    pub fn class_name(&self) -> &'static str {
+        "NWG_HEX_VIEW"
+    }
+
+    /// Winapi base flags used during window creation
+    pub fn flags(&self) -> u32 {
+        WS_VISIBLE | WS_TABSTOP | WS_BORDER
+    }
+
+    /// Winapi flags required by the control
+    pub fn forced_flags(&self) -> u32 {
+        WS_CHILD | WS_CLIPSIBLINGS | WS_VSCROLL
+    }
+
+    /// Handles the painting, the scrolling, the selection and the edition
+    fn hook_events(&self) {
+        use crate::bind_raw_event_handler_inner;
+        use winapi::um::winuser::{WM_PAINT, WM_ERASEBKGND, WM_SIZE, WM_SETFONT, WM_GETFONT, WM_VSCROLL, WM_MOUSEWHEEL, WM_SETFOCUS,
+            WM_KILLFOCUS, WM_GETDLGCODE, WM_LBUTTONDOWN, WM_MOUSEMOVE, WM_LBUTTONUP, WM_CAPTURECHANGED, WM_KEYDOWN, WM_CHAR,
+            DLGC_WANTARROWS, DLGC_WANTCHARS, VK_SHIFT, GET_WHEEL_DELTA_WPARAM, WHEEL_DELTA};
+        use winapi::um::winuser::{SetFocus, SetCapture, ReleaseCapture, GetCapture, GetKeyState};
+        use winapi::shared::minwindef::{LOWORD, HIWORD};
+
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let state = self.state.clone();
+
+        let handler = bind_raw_event_handler_inner(&self.handle, 0, move |hwnd, msg, w, l| {
+            // Mouse coordinates are signed
+            let (x, y) = (LOWORD(l as u32) as i16 as i32, HIWORD(l as u32) as i16 as i32);
+
+            match msg {
+                WM_PAINT => {
+                    paint(hwnd, &state.borrow());
+                    return Some(0);
+                },
+                WM_ERASEBKGND => {
+                    // Everything is drawn in WM_PAINT
+                    return Some(1);
+                },
+                WM_SIZE => {
+                    // Showing or hiding the scrollbar resizes the control
+                    if let Ok(mut state) = state.try_borrow_mut() {
+                        update_scroll(hwnd, &mut state);
+                        ph::redraw(hwnd);
+                    }
+                },
+                WM_SETFONT => {
+                    let mut state = state.borrow_mut();
+                    state.font = w as HFONT;
+                    state.char_size = char_size(hwnd, ph::font_or(state.font, state.default_font));
+                    update_scroll(hwnd, &mut state);
+                    if l != 0 {
+                        ph::redraw(hwnd);
+                    }
+                    return Some(0);
+                },
+                WM_GETFONT => {
+                    return Some(state.borrow().font as _);
+                },
+                WM_GETDLGCODE => {
+                    return Some(DLGC_WANTARROWS | DLGC_WANTCHARS);
+                },
+                WM_SETFOCUS | WM_KILLFOCUS => {
+                    ph::redraw(hwnd);
+                },
+                WM_VSCROLL => {
+                    let mut state = state.borrow_mut();
+                    let top = scroll_request(hwnd, &state, w);
+                    set_top_row(hwnd, &mut state, top);
+                    return Some(0);
+                },
+                WM_MOUSEWHEEL => {
+                    let notches = GET_WHEEL_DELTA_WPARAM(w) as i64 / WHEEL_DELTA as i64;
+                    let mut state = state.borrow_mut();
+                    let top = state.top_row as i64 - notches * WHEEL_ROWS;
+                    set_top_row(hwnd, &mut state, top);
+                    return Some(0);
+                },
+                WM_LBUTTONDOWN => {
+                    unsafe { SetFocus(hwnd); }
+
+                    let extend = unsafe { GetKeyState(VK_SHIFT) < 0 };
+                    let changed = {
+                        let mut state = state.borrow_mut();
+                        match hit_test(&state, x, y, false) {
+                            Some((offset, column)) => {
+                                let before = selection(&state);
+                                state.caret = offset;
+                                if !extend {
+                                    state.anchor = offset;
+                                }
+                                state.column = column;
+                                state.low_nibble = false;
+                                state.selecting = true;
+                                unsafe { SetCapture(hwnd); }
+                                ph::redraw(hwnd);
+                                selection_changed(&state, before)
+                            },
+                            None => None
+                        }
+                    };
+
+                    // The state must not be borrowed when the events are sent because the user handler may access the view
+                    if let Some((offset, length)) = changed {
+                        notify(hwnd, HEX_VIEW_SELECTION_CHANGED, offset, length);
+                    }
+                },
+                WM_MOUSEMOVE => {
+                    let changed = {
+                        let mut state = state.borrow_mut();
+                        match state.selecting {
+                            true => match hit_test(&state, x, y, true) {
+                                Some((offset, _)) if offset != state.caret => {
+                                    let before = selection(&state);
+                                    state.caret = offset;
+                                    scroll_to_offset(hwnd, &mut state, offset);
+                                    ph::redraw(hwnd);
+                                    selection_changed(&state, before)
+                                },
+                                _ => None
+                            },
+                            false => None
+                        }
+                    };
+
+                    if let Some((offset, length)) = changed {
+                        notify(hwnd, HEX_VIEW_SELECTION_CHANGED, offset, length);
+                    }
+                },
+                WM_LBUTTONUP => {
+                    state.borrow_mut().selecting = false;
+                    unsafe {
+                        if GetCapture() == hwnd {
+                            ReleaseCapture();
+                        }
+                    }
+                },
+                WM_CAPTURECHANGED => {
+                    state.borrow_mut().selecting = false;
+                },
+                WM_KEYDOWN => {
+                    let changed = key_down(hwnd, &mut state.borrow_mut(), w);
+                    if let Some((offset, length)) = changed {
+                        notify(hwnd, HEX_VIEW_SELECTION_CHANGED, offset, length);
+                    }
+                    return Some(0);
+                },
+                WM_CHAR => {
+                    let edited = type_char(hwnd, &mut state.borrow_mut(), w);
+                    if let Some(offset) = edited {
+                        notify(hwnd, HEX_VIEW_EDIT, offset, 1);
+                    }
+                    return Some(0);
+                },
+                _ => {}
+            }
+
+            None
+        });
+
+        *self.handler0.borrow_mut() = Some(handler.unwrap());
+    }
+
+}
+
+impl Drop for HexView {
+    fn drop(&mut self) {
+        let handler = self.handler0.borrow();
+        if let Some(h) = handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
+        self.handle.destroy();
+    }
+}
+
+impl PartialEq for HexView {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for HexView {}
+
+pub struct HexViewBuilder<'a> {
+    size: Extent,
+    position: Extent,
+    data: Option<Box<dyn HexDataProvider>>,
+    bytes_per_row: u32,
+    read_only: bool,
+    font: Option<&'a Font>,
+    flags: Option<HexViewFlags>,
+    parent: Option<ControlHandle>
+}
+
+impl<'a> HexViewBuilder<'a> {
+
+    pub fn flags(mut self, flags: HexViewFlags) -> HexViewBuilder<'a> {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> HexViewBuilder<'a> {
+        self.size = size.into();
+        self
+    }
+
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> HexViewBuilder<'a> {
+        self.position = pos.into();
+        self
+    }
+
+    pub fn data<P: HexDataProvider + 'static>(mut self, data: P) -> HexViewBuilder<'a> {
+        self.data = Some(Box::new(data));
+        self
+    }
+
+    pub fn bytes_per_row(mut self, count: u32) -> HexViewBuilder<'a> {
+        self.bytes_per_row = count;
+        self
+    }
+
+    pub fn read_only(mut self, read_only: bool) -> HexViewBuilder<'a> {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn font(mut self, font: Option<&'a Font>) -> HexViewBuilder<'a> {
+        self.font = font;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> HexViewBuilder<'a> {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut HexView) -> Result<(), NwgError> {
+        let flags = self.flags.map(|f| f.bits()).unwrap_or(out.flags());
+
+        let parent = match self.parent {
+            Some(p) => Ok(p),
+            None => Err(NwgError::no_parent("HexView"))
+        }?;
+
+        *out = Default::default();
+
+        out.handle = ControlBase::build_hwnd()
+            .class_name(out.class_name())
+            .forced_flags(out.forced_flags())
+            .flags(flags)
+            .size(self.size)
+            .position(self.position)
+            .parent(Some(parent))
+            .build()?;
+
+        {
+            let mut state = out.state.borrow_mut();
+            if let Some(data) = self.data {
+                state.data = data;
+            }
+            state.bytes_per_row = self.bytes_per_row.max(1).min(MAX_BYTES_PER_ROW);
+            state.read_only = self.read_only;
+            state.default_font = ph::monospace_font();
+        }
+
+        out.hook_events();
+        out.set_font(self.font);
+
+        Ok(())
+    }
+
+}
+
+/// Sends a `NMHEXVIEW` notification to the parent of the view
+fn notify(handle: HWND, code: u32, offset: u64, length: u64) {
+    use winapi::um::winuser::{NMHDR, WM_NOTIFY};
+
+    let notification = NMHEXVIEW {
+        hdr: NMHDR { hwndFrom: handle, idFrom: 0, code },
+        offset,
+        length,
+    };
+
+    let parent = wh::get_window_parent(handle);
+    wh::send_message(parent, WM_NOTIFY, 0, &notification as *const NMHEXVIEW as _);
+}
+
+/// Returns the width and the height of a character of a monospaced font
+fn char_size(handle: HWND, font: HFONT) -> (i32, i32) {
+    use winapi::um::winuser::{GetDC, ReleaseDC};
+    use winapi::um::wingdi::{SelectObject, GetTextMetricsW, TEXTMETRICW};
+
+    unsafe {
+        let dc = GetDC(handle);
+        let old_font = SelectObject(dc, font as _);
+
+        let mut metrics: TEXTMETRICW = mem::zeroed();
+        GetTextMetricsW(dc, &mut metrics);
+
+        SelectObject(dc, old_font);
+        ReleaseDC(handle, dc);
+
+        (metrics.tmAveCharWidth.max(1), (metrics.tmHeight + metrics.tmExternalLeading).max(1))
+    }
+}
+
+fn row_count(state: &HexViewState) -> u64 {
+    let bpr = state.bytes_per_row as u64;
+    (state.data.len() + bpr - 1) / bpr
+}
+
+/// Number of rows fully visible below the header
+fn visible_rows(handle: HWND, state: &HexViewState) -> u64 {
+    let (_, height) = ph::client_size(handle);
+    let line = state.char_size.1;
+    ((height - line) / line).max(1) as u64
+}
+
+fn max_top_row(handle: HWND, state: &HexViewState) -> u64 {
+    row_count(state).saturating_sub(visible_rows(handle, state))
+}
+
+/// Returns `(offset, length)` of the selection
+fn selection(state: &HexViewState) -> (u64, u64) {
+    if state.data.is_empty() {
+        return (0, 0);
+    }
+
+    let start = state.anchor.min(state.caret);
+    let end = state.anchor.max(state.caret);
+    (start, end - start + 1)
+}
+
+/// Returns the new selection if it is different from `before`
+fn selection_changed(state: &HexViewState, before: (u64, u64)) -> Option<(u64, u64)> {
+    let after = selection(state);
+    match after != before {
+        true => Some(after),
+        false => None
+    }
+}
+
+/// Number of hex digits used to display the offsets
+fn offset_digits(state: &HexViewState) -> usize {
+    match state.data.len() > 0xFFFF_FFFF {
+        true => 16,
+        false => 8
+    }
+}
+
+/// Returns the x position of the hex digits of the byte `i` of a row
+fn hex_x(state: &HexViewState, i: u32) -> i32 {
+    let cw = state.char_size.0;
+    let start = ph::px(MARGIN) + (offset_digits(state) as i32 + 2) * cw;
+
+    // An extra space separates each group of 8 bytes
+    start + (i as i32 * 3 + i as i32 / 8) * cw
+}
+
+/// Returns the x position of the character of the byte `i` of a row
+fn ascii_x(state: &HexViewState, i: u32) -> i32 {
+    let cw = state.char_size.0;
+    let bpr = state.bytes_per_row;
+    hex_x(state, bpr - 1) + 4 * cw + i as i32 * cw
+}
+
+/// Returns the byte and the column at a position. With `clamp`, a position outside of the rows
+/// returns the nearest byte (used when the user extends the selection with the mouse).
+fn hit_test(state: &HexViewState, x: i32, y: i32, clamp: bool) -> Option<(u64, HexColumn)> {
+    let len = state.data.len();
+    if len == 0 {
+        return None;
+    }
+
+    let (cw, line) = state.char_size;
+    if y < line && !clamp {
+        return None;
+    }
+
+    let row = match y < line {
+        true => state.top_row.saturating_sub(1),
+        false => state.top_row + ((y - line) / line) as u64
+    };
+
+    let bpr = state.bytes_per_row;
+    let (index, column) = match x >= ascii_x(state, 0) - cw {
+        true => (((x - ascii_x(state, 0)) / cw).max(0).min(bpr as i32 - 1) as u32, HexColumn::Ascii),
+        false => ((0..bpr).rev().find(|&i| x >= hex_x(state, i) - cw / 2).unwrap_or(0), HexColumn::Hex)
+    };
+
+    let offset = row * bpr as u64 + index as u64;
+    match offset < len || clamp {
+        true => Some((offset.min(len - 1), column)),
+        false => None
+    }
+}
+
+/// Divider applied to the rows in the scrollbar, which is limited to 32 bits
+fn scroll_scale(state: &HexViewState) -> u64 {
+    row_count(state) / (i32::max_value() as u64) + 1
+}
+
+fn update_scroll(handle: HWND, state: &mut HexViewState) {
+    use winapi::um::winuser::{SetScrollInfo, SCROLLINFO, SIF_RANGE, SIF_PAGE, SIF_POS};
+
+    state.top_row = state.top_row.min(max_top_row(handle, state));
+
+    let scale = scroll_scale(state);
+    let info = SCROLLINFO {
+        cbSize: mem::size_of::<SCROLLINFO>() as u32,
+        fMask: SIF_RANGE | SIF_PAGE | SIF_POS,
+        nMin: 0,
+        nMax: (row_count(state) / scale).max(1) as i32 - 1,
+        nPage: (visible_rows(handle, state) / scale).max(1) as u32,
+        nPos: (state.top_row / scale) as i32,
+        nTrackPos: 0,
+    };
+
+    unsafe { SetScrollInfo(handle, SB_VERT as i32, &info, 1); }
+}
+
+fn set_top_row(handle: HWND, state: &mut HexViewState, top: i64) {
+    let top = (top.max(0) as u64).min(max_top_row(handle, state));
+    if top != state.top_row {
+        state.top_row = top;
+        update_scroll(handle, state);
+        ph::redraw(handle);
+    }
+}
+
+/// Scrolls the view so that the row of `offset` is visible
+fn scroll_to_offset(handle: HWND, state: &mut HexViewState, offset: u64) {
+    let row = offset / state.bytes_per_row as u64;
+    let visible = visible_rows(handle, state);
+
+    if row < state.top_row {
+        state.top_row = row;
+    } else if row >= state.top_row + visible {
+        state.top_row = row + 1 - visible;
+    }
+
+    update_scroll(handle, state);
+}
+
+/// Computes the new first row from a WM_VSCROLL message
+fn scroll_request(handle: HWND, state: &HexViewState, w: WPARAM) -> i64 {
+    use winapi::um::winuser::{GetScrollInfo, SCROLLINFO, SIF_TRACKPOS, SB_LINEUP, SB_LINEDOWN, SB_PAGEUP, SB_PAGEDOWN,
+        SB_THUMBTRACK, SB_THUMBPOSITION, SB_TOP, SB_BOTTOM};
+    use winapi::shared::minwindef::LOWORD;
+
+    let current = state.top_row as i64;
+    let page = visible_rows(handle, state) as i64;
+
+    match LOWORD(w as u32) as isize {
+        SB_LINEUP => current - 1,
+        SB_LINEDOWN => current + 1,
+        SB_PAGEUP => current - page,
+        SB_PAGEDOWN => current + page,
+        SB_TOP => 0,
+        SB_BOTTOM => i64::max_value(),
+        SB_THUMBTRACK | SB_THUMBPOSITION => unsafe {
+            // The position in the message is limited to 16 bits
+            let mut info: SCROLLINFO = mem::zeroed();
+            info.cbSize = mem::size_of::<SCROLLINFO>() as u32;
+            info.fMask = SIF_TRACKPOS;
+            GetScrollInfo(handle, SB_VERT as i32, &mut info);
+            info.nTrackPos as i64 * scroll_scale(state) as i64
+        },
+        _ => current
+    }
+}
+
+/// Moves the caret with the keyboard. Returns the new selection if it changed.
+fn key_down(handle: HWND, state: &mut HexViewState, key: WPARAM) -> Option<(u64, u64)> {
+    use winapi::um::winuser::{GetKeyState, VK_LEFT, VK_RIGHT, VK_UP, VK_DOWN, VK_PRIOR, VK_NEXT, VK_HOME, VK_END, VK_SHIFT, VK_CONTROL};
+
+    let len = state.data.len();
+    if len == 0 {
+        return None;
+    }
+
+    let ctrl = unsafe { GetKeyState(VK_CONTROL) < 0 };
+    let shift = unsafe { GetKeyState(VK_SHIFT) < 0 };
+    let bpr = state.bytes_per_row as u64;
+    let page = visible_rows(handle, state) * bpr;
+    let caret = state.caret;
+    let last = len - 1;
+
+    let target = match key as i32 {
+        VK_LEFT => caret.saturating_sub(1),
+        VK_RIGHT => (caret + 1).min(last),
+        VK_UP => caret.saturating_sub(bpr),
+        VK_DOWN => (caret + bpr).min(last),
+        VK_PRIOR => caret.saturating_sub(page),
+        VK_NEXT => (caret + page).min(last),
+        VK_HOME if ctrl => 0,
+        VK_END if ctrl => last,
+        VK_HOME => caret - caret % bpr,
+        VK_END => (caret - caret % bpr + bpr - 1).min(last),
+        0x41 if ctrl => {
+            // Ctrl+A
+            let before = selection(state);
+            state.anchor = 0;
+            state.caret = last;
+            ph::redraw(handle);
+            return selection_changed(state, before);
+        },
+        0x43 if ctrl => {
+            // Ctrl+C
+            copy_selection(handle, state);
+            return None;
+        },
+        _ => return None
+    };
+
+    let before = selection(state);
+    state.caret = target;
+    if !shift {
+        state.anchor = target;
+    }
+    state.low_nibble = false;
+
+    scroll_to_offset(handle, state, target);
+    ph::redraw(handle);
+    selection_changed(state, before)
+}
+
+/// Overwrites the byte at the caret with a typed character. Returns the offset of the modified byte.
+fn type_char(handle: HWND, state: &mut HexViewState, c: WPARAM) -> Option<u64> {
+    let len = state.data.len();
+    if state.read_only || len == 0 || c < 0x20 || c > 0x7E {
+        return None;
+    }
+
+    let offset = state.caret;
+    let mut byte = [0u8];
+    state.data.read(offset, &mut byte);
+
+    let value = match state.column {
+        HexColumn::Ascii => c as u8,
+        HexColumn::Hex => {
+            let digit = (c as u8 as char).to_digit(16)? as u8;
+            match state.low_nibble {
+                true => (byte[0] & 0xF0) | digit,
+                false => (byte[0] & 0x0F) | (digit << 4)
+            }
+        }
+    };
+
+    if !state.data.write(offset, &[value]) {
+        return None;
+    }
+
+    // The caret moves to the next byte once the byte is complete
+    let complete = state.column == HexColumn::Ascii || state.low_nibble;
+    state.low_nibble = !complete;
+    if complete && offset + 1 < len {
+        state.caret = offset + 1;
+    }
+    state.anchor = state.caret;
+
+    let caret = state.caret;
+    scroll_to_offset(handle, state, caret);
+    ph::redraw(handle);
+
+    Some(offset)
+}
+
+/// Copies the selection to the clipboard, as hex digits or as text depending on the active column
+fn copy_selection(handle: HWND, state: &HexViewState) {
+    let (offset, length) = selection(state);
+    if length == 0 {
+        return;
+    }
+
+    let mut bytes = vec![0u8; length.min(MAX_COPY) as usize];
+    let count = state.data.read(offset, &mut bytes);
+    bytes.truncate(count);
+
+    let text = match state.column {
+        HexColumn::Hex => bytes.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" "),
+        HexColumn::Ascii => bytes.iter().map(|&b| printable(b)).collect(),
+    };
+
+    Clipboard::set_data_text(ControlHandle::Hwnd(handle), &text);
+}
+
+fn printable(b: u8) -> char {
+    match b {
+        0x20..=0x7E => b as char,
+        _ => '.'
+    }
+}
+
+unsafe fn draw_text(dc: HDC, x: i32, y: i32, text: &str) {
+    use winapi::um::wingdi::TextOutW;
+
+    let text: Vec<u16> = text.encode_utf16().collect();
+    TextOutW(dc, x, y, text.as_ptr(), text.len() as i32);
+}
+
+/// Paints the view in a memory bitmap and copies the result on the screen
+fn paint(handle: HWND, state: &HexViewState) {
+    use winapi::um::winuser::{FillRect, FrameRect, GetSysColorBrush, GetSysColor,
+        COLOR_WINDOW, COLOR_WINDOWTEXT, COLOR_BTNFACE, COLOR_GRAYTEXT, COLOR_HIGHLIGHT, COLOR_HIGHLIGHTTEXT, COLOR_3DLIGHT};
+    use winapi::um::wingdi::{SetBkMode, SetTextColor, TRANSPARENT};
+
+    ph::paint_buffered(handle, ph::font_or(state.font, state.default_font), |buffer_dc, width, height| unsafe {
+        let focused = wh::get_focus(handle);
+        let (cw, line) = state.char_size;
+        let bpr = state.bytes_per_row;
+        let digits = offset_digits(state);
+
+        FillRect(buffer_dc, &RECT { left: 0, top: 0, right: width, bottom: height }, GetSysColorBrush(COLOR_WINDOW));
+        SetBkMode(buffer_dc, TRANSPARENT as i32);
+
+        // Header with the index of the bytes in a row
+        FillRect(buffer_dc, &RECT { left: 0, top: 0, right: width, bottom: line }, GetSysColorBrush(COLOR_BTNFACE));
+        SetTextColor(buffer_dc, GetSysColor(COLOR_GRAYTEXT));
+        draw_text(buffer_dc, ph::px(MARGIN), 0, "Offset");
+        for i in 0..bpr {
+            draw_text(buffer_dc, hex_x(state, i), 0, &format!("{:02X}", i));
+        }
+
+        let (sel_start, sel_length) = selection(state);
+        let sel_end = sel_start + sel_length;
+        let selection_brush = match focused {
+            true => GetSysColorBrush(COLOR_HIGHLIGHT),
+            false => GetSysColorBrush(COLOR_3DLIGHT)
+        };
+
+        let mut bytes = vec![0u8; bpr as usize];
+        let rows = visible_rows(handle, state) + 1;
+        for r in 0..rows {
+            let row = state.top_row + r;
+            let row_offset = row * bpr as u64;
+            if row_offset >= state.data.len() {
+                break;
+            }
+
+            let count = state.data.read(row_offset, &mut bytes) as u32;
+            let y = line * (r as i32 + 1);
+
+            SetTextColor(buffer_dc, GetSysColor(COLOR_GRAYTEXT));
+            draw_text(buffer_dc, ph::px(MARGIN), y, &format!("{:0width$X}", row_offset, width = digits));
+
+            for i in 0..count {
+                let offset = row_offset + i as u64;
+                let selected = offset >= sel_start && offset < sel_end;
+                let hex = RECT { left: hex_x(state, i), top: y, right: hex_x(state, i) + 2 * cw, bottom: y + line };
+                let ascii = RECT { left: ascii_x(state, i), top: y, right: ascii_x(state, i) + cw, bottom: y + line };
+
+                if selected {
+                    // The space between two selected bytes is also highlighted
+                    let joined = offset + 1 < sel_end && i + 1 < count;
+                    let hex_fill = match joined {
+                        true => RECT { right: hex_x(state, i + 1), ..hex },
+                        false => hex
+                    };
+
+                    FillRect(buffer_dc, &hex_fill, selection_brush);
+                    FillRect(buffer_dc, &ascii, selection_brush);
+                }
+
+                let text_color = match selected && focused {
+                    true => COLOR_HIGHLIGHTTEXT,
+                    false => COLOR_WINDOWTEXT
+                };
+                SetTextColor(buffer_dc, GetSysColor(text_color));
+
+                let b = bytes[i as usize];
+                draw_text(buffer_dc, hex.left, y, &format!("{:02X}", b));
+                draw_text(buffer_dc, ascii.left, y, &printable(b).to_string());
+
+                if focused && offset == state.caret {
+                    // A bar before the active nibble or character, and a frame around the byte in the other column
+                    let (active, other) = match state.column {
+                        HexColumn::Hex => (hex, ascii),
+                        HexColumn::Ascii => (ascii, hex)
+                    };
+
+                    let bar_x = match state.column == HexColumn::Hex && state.low_nibble {
+                        true => active.left + cw,
+                        false => active.left
+                    };
+
+                    let bar = RECT { left: bar_x, top: y, right: bar_x + ph::px(2), bottom: y + line };
+                    FillRect(buffer_dc, &bar, GetSysColorBrush(COLOR_WINDOWTEXT));
+                    FrameRect(buffer_dc, &other, GetSysColorBrush(COLOR_GRAYTEXT));
+                }
+            }
+        }
+    });
+}
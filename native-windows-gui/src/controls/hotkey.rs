@@ -0,0 +1,440 @@
+use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_TABSTOP};
+use winapi::um::commctrl::{HOTKEYF_SHIFT, HOTKEYF_CONTROL, HOTKEYF_ALT, HOTKEYF_EXT,
+ HKCOMB_NONE, HKCOMB_S, HKCOMB_C, HKCOMB_A, HKCOMB_SC, HKCOMB_SA, HKCOMB_CA, HKCOMB_SCA};
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
+use crate::win32::base_helper::{check_hwnd, from_utf16};
+use crate::{Font, NwgError};
+use super::{ControlBase, ControlHandle};
+use std::fmt;
+
+const NOT_BOUND: &'static str = "HotKey is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: HotKey handle is not HWND!";
+
+
+bitflags! {
+
+    /**
+        The HotKey flags
+
+        * NONE:     No flags. Equivalent to a invisible hotkey control.
+        * VISIBLE:  The hotkey control is immediatly visible after creation
+        * DISABLED: The hotkey control cannot be interacted with by the user. It also has a grayed out look.
+        * TAB_STOP: The control can be selected using tab navigation
+    */
+    pub struct HotKeyFlags: u32 {
+        const NONE = 0;
+        const VISIBLE = WS_VISIBLE;
+        const DISABLED = WS_DISABLED;
+        const TAB_STOP = WS_TABSTOP;
+    }
+}
+
+bitflags! {
+
+    /**
+        The modifier keys of a key chord
+
+        * SHIFT:   The Shift key
+        * CONTROL: The Ctrl key
+        * ALT:     The Alt key
+        * EXT:     The key is an extended key (ex: the arrow keys of the numeric keypad)
+    */
+    pub struct HotKeyModifiers: u8 {
+        const NONE = 0;
+        const SHIFT = HOTKEYF_SHIFT;
+        const CONTROL = HOTKEYF_CONTROL;
+        const ALT = HOTKEYF_ALT;
+        const EXT = HOTKEYF_EXT;
+    }
+}
+
+bitflags! {
+
+    /**
+        Modifier combinations that can be rejected by a hotkey control. See `HotKey::set_rules`.
+
+        * UNMODIFIED: A key without any modifier
+        * SHIFT: Shift+key
+        * CONTROL: Ctrl+key
+        * ALT: Alt+key
+        * SHIFT_CONTROL: Shift+Ctrl+key
+        * SHIFT_ALT: Shift+Alt+key
+        * CONTROL_ALT: Ctrl+Alt+key
+        * SHIFT_CONTROL_ALT: Shift+Ctrl+Alt+key
+    */
+    pub struct HotKeyRules: u32 {
+        const NONE = 0;
+        const UNMODIFIED = HKCOMB_NONE as u32;
+        const SHIFT = HKCOMB_S as u32;
+        const CONTROL = HKCOMB_C as u32;
+        const ALT = HKCOMB_A as u32;
+        const SHIFT_CONTROL = HKCOMB_SC as u32;
+        const SHIFT_ALT = HKCOMB_SA as u32;
+        const CONTROL_ALT = HKCOMB_CA as u32;
+        const SHIFT_CONTROL_ALT = HKCOMB_SCA as u32;
+    }
+}
+
+/**
+    A virtual key code combined with modifier keys (ex: Ctrl+S).
+
+    `KeyChord` implements `Display` using the key names of the current keyboard layout.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    /// The virtual key code of the key (see `nwg::keys`)
+    pub key: u32,
+    pub modifiers: HotKeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(key: u32, modifiers: HotKeyModifiers) -> KeyChord {
+        KeyChord { key, modifiers }
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(HotKeyModifiers::CONTROL) {
+            write!(f, "{}+", key_name(0x11, false))?;
+        }
+        if self.modifiers.contains(HotKeyModifiers::SHIFT) {
+            write!(f, "{}+", key_name(0x10, false))?;
+        }
+        if self.modifiers.contains(HotKeyModifiers::ALT) {
+            write!(f, "{}+", key_name(0x12, false))?;
+        }
+
+        write!(f, "{}", key_name(self.key, self.modifiers.contains(HotKeyModifiers::EXT)))
+    }
+}
+
+/**
+A hot key control is a window that enables the user to enter a combination of keystrokes to be used as a keyboard shortcut.
+The control displays the key chord as text (ex: "Ctrl + Shift + S").
+
+Requires the `hotkey` feature.
+
+**Builder parameters:**
+  * `parent`:   **Required.** The hotkey control parent container.
+  * `size`:     The hotkey control size.
+  * `position`: The hotkey control position.
+  * `enabled`:  If the hotkey control can be used by the user. It also has a grayed out look if disabled.
+  * `flags`:    A combination of the HotKeyFlags values.
+  * `ex_flags`: A combination of win32 window extended flags. Unlike `flags`, ex_flags must be used straight from winapi
+  * `font`:     The font used for the hotkey control text
+  * `chord`:    The initial key chord of the control
+  * `rules`:    The rejected modifier combinations and the modifiers used to replace them
+  * `focus`:    The control receive focus after being created
+
+**Control events:**
+  * `OnHotKeyChanged`: When the user changes the key chord of the control
+  * `MousePress(_)`: Generic mouse press events on the control
+  * `OnMouseMove`: Generic mouse mouse event
+  * `OnMouseWheel`: Generic mouse wheel event
+
+```rust
+use native_windows_gui as nwg;
+fn build_hotkey(hotkey: &mut nwg::HotKey, window: &nwg::Window) {
+    nwg::HotKey::builder()
+        .chord(Some(nwg::KeyChord::new(nwg::keys::_S, nwg::HotKeyModifiers::CONTROL)))
+        .rules(nwg::HotKeyRules::UNMODIFIED | nwg::HotKeyRules::SHIFT, nwg::HotKeyModifiers::CONTROL)
+        .parent(window)
+        .build(hotkey);
+}
+```
+*/
+#[derive(Default, PartialEq, Eq)]
+pub struct HotKey {
+    pub handle: ControlHandle
+}
+
+impl HotKey {
+
+    pub fn builder<'a>() -> HotKeyBuilder<'a> {
+        HotKeyBuilder {
+            size: (150, 25).into(),
+            position: (0, 0).into(),
+            enabled: true,
+            flags: None,
+            ex_flags: 0,
+            font: None,
+            chord: None,
+            rules: None,
+            focus: false,
+            parent: None
+        }
+    }
+
+    /// Return the key chord in the control or `None` if the control is empty
+    pub fn chord(&self) -> Option<KeyChord> {
+        use winapi::um::commctrl::HKM_GETHOTKEY;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let value = wh::send_message(handle, HKM_GETHOTKEY, 0, 0) as u16;
+
+        let key = (value & 0xFF) as u32;
+        let modifiers = HotKeyModifiers::from_bits_truncate((value >> 8) as u8);
+        match key {
+            0 => None,
+            key => Some(KeyChord { key, modifiers })
+        }
+    }
+
+    /// Set the key chord in the control. `None` clears the control.
+    pub fn set_chord(&self, chord: Option<KeyChord>) {
+        use winapi::um::commctrl::HKM_SETHOTKEY;
+        use winapi::shared::minwindef::WPARAM;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let value = match chord {
+            Some(c) => ((c.modifiers.bits() as WPARAM) << 8) | (c.key as WPARAM & 0xFF),
+            None => 0
+        };
+
+        wh::send_message(handle, HKM_SETHOTKEY, value, 0);
+    }
+
+    /// Set the modifier combinations rejected by the control. When the user enters a rejected
+    /// combination, the modifiers are replaced by `replacement`.
+    pub fn set_rules(&self, invalid: HotKeyRules, replacement: HotKeyModifiers) {
+        use winapi::um::commctrl::HKM_SETRULES;
+        use winapi::shared::minwindef::{WPARAM, LPARAM};
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::send_message(handle, HKM_SETRULES, invalid.bits() as WPARAM, replacement.bits() as LPARAM);
+    }
+
+    /// Return the font of the control
+    pub fn font(&self) -> Option<Font> {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let font_handle = wh::get_window_font(handle);
+        if font_handle.is_null() {
+            None
+        } else {
+            Some(Font { handle: font_handle })
+        }
+    }
+
+    /// Sets the font of the control
+    pub fn set_font(&self, font: Option<&Font>) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_font(handle, font.map(|f| f.handle), true); }
+    }
+
+    /// Return true if the control currently has the keyboard focus
+    pub fn focus(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_focus(handle) }
+    }
+
+    /// Sets the keyboard focus on the control.
+    pub fn set_focus(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_focus(handle); }
+    }
+
+    /// Return true if the control user can interact with the control, return false otherwise
+    pub fn enabled(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_enabled(handle) }
+    }
+
+    /// Enable or disable the control
+    pub fn set_enabled(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_enabled(handle, v) }
+    }
+
+    /// Return true if the control is visible to the user. Will return true even if the
+    /// control is outside of the parent client view (ex: at the position (10000, 10000))
+    pub fn visible(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_visibility(handle) }
+    }
+
+    /// Show or hide the control to the user
+    pub fn set_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_visibility(handle, v) }
+    }
+
+    /// Return the size of the control in the parent window
+    pub fn size(&self) -> (u32, u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_size(handle) }
+    }
+
+    /// Set the size of the control in the parent window
+    pub fn set_size(&self, x: u32, y: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_size(handle, x, y, false) }
+    }
+
+    /// Return the position of the control in the parent window
+    pub fn position(&self) -> (i32, i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_position(handle) }
+    }
+
+    /// Set the position of the control in the parent window
+    pub fn set_position(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_position(handle, x, y) }
+    }
+
+    /// Winapi class name used during control creation
+    pub fn class_name(&self) -> &'static str {
+        "msctls_hotkey32"
+    }
+
+    /// Winapi base flags used during window creation
+    pub fn flags(&self) -> u32 {
+        WS_VISIBLE | WS_TABSTOP
+    }
+
+    /// Winapi flags required by the control
+    pub fn forced_flags(&self) -> u32 {
+        use winapi::um::winuser::{WS_CHILD, WS_BORDER};
+
+        WS_CHILD | WS_BORDER
+    }
+
+}
+
+impl Drop for HotKey {
+    fn drop(&mut self) {
+        self.handle.destroy();
+    }
+}
+
+pub struct HotKeyBuilder<'a> {
+    size: Extent,
+    position: Extent,
+    enabled: bool,
+    flags: Option<HotKeyFlags>,
+    ex_flags: u32,
+    font: Option<&'a Font>,
+    chord: Option<KeyChord>,
+    rules: Option<(HotKeyRules, HotKeyModifiers)>,
+    focus: bool,
+    parent: Option<ControlHandle>
+}
+
+impl<'a> HotKeyBuilder<'a> {
+
+    pub fn flags(mut self, flags: HotKeyFlags) -> HotKeyBuilder<'a> {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn ex_flags(mut self, flags: u32) -> HotKeyBuilder<'a> {
+        self.ex_flags = flags;
+        self
+    }
+
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> HotKeyBuilder<'a> {
+        self.size = size.into();
+        self
+    }
+
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> HotKeyBuilder<'a> {
+        self.position = pos.into();
+        self
+    }
+
+    pub fn enabled(mut self, e: bool) -> HotKeyBuilder<'a> {
+        self.enabled = e;
+        self
+    }
+
+    pub fn font(mut self, font: Option<&'a Font>) -> HotKeyBuilder<'a> {
+        self.font = font;
+        self
+    }
+
+    pub fn chord(mut self, chord: Option<KeyChord>) -> HotKeyBuilder<'a> {
+        self.chord = chord;
+        self
+    }
+
+    pub fn rules(mut self, invalid: HotKeyRules, replacement: HotKeyModifiers) -> HotKeyBuilder<'a> {
+        self.rules = Some((invalid, replacement));
+        self
+    }
+
+    pub fn focus(mut self, focus: bool) -> HotKeyBuilder<'a> {
+        self.focus = focus;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> HotKeyBuilder<'a> {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut HotKey) -> Result<(), NwgError> {
+        let mut flags = self.flags.map(|f| f.bits()).unwrap_or(out.flags());
+        if !self.enabled {
+            flags |= WS_DISABLED;
+        }
+
+        let parent = match self.parent {
+            Some(p) => Ok(p),
+            None => Err(NwgError::no_parent("HotKey"))
+        }?;
+
+        *out = Default::default();
+
+        out.handle = ControlBase::build_hwnd()
+            .class_name(out.class_name())
+            .forced_flags(out.forced_flags())
+            .flags(flags)
+            .ex_flags(self.ex_flags)
+            .size(self.size)
+            .position(self.position)
+            .parent(Some(parent))
+            .build()?;
+
+        if self.font.is_some() {
+            out.set_font(self.font);
+        } else {
+            out.set_font(Font::global_default().as_ref());
+        }
+
+        if let Some((invalid, replacement)) = self.rules {
+            out.set_rules(invalid, replacement);
+        }
+
+        if self.chord.is_some() {
+            out.set_chord(self.chord);
+        }
+
+        if self.focus {
+            out.set_focus();
+        }
+
+        Ok(())
+    }
+
+}
+
+/// Return the name of a virtual key in the current keyboard layout
+fn key_name(key: u32, extended: bool) -> String {
+    use winapi::um::winuser::{MapVirtualKeyW, GetKeyNameTextW, MAPVK_VK_TO_VSC};
+
+    let mut buffer = [0u16; 64];
+    unsafe {
+        let scan_code = MapVirtualKeyW(key, MAPVK_VK_TO_VSC);
+        let mut param = (scan_code << 16) as i32;
+        if extended {
+            param |= 1 << 24;
+        }
+
+        match GetKeyNameTextW(param, buffer.as_mut_ptr(), buffer.len() as i32) {
+            0 => format!("0x{:02X}", key),
+            _ => from_utf16(&buffer)
+        }
+    }
+}
@@ -6,6 +6,8 @@ use crate::win32::{
     window_helper as wh,
     resources_helper as rh
 };
+use crate::win32::high_dpi::Extent;
+use crate::win32::resource_tracker::{self as rt, ResourceKind};
 use super::{ControlBase, ControlHandle};
 use crate::{Bitmap, Icon, NwgError, RawEventHandler, unbind_raw_event_handler};
 use std::cell::RefCell;
@@ -63,8 +65,8 @@ impl ImageFrame {
 
     pub fn builder<'a>() -> ImageFrameBuilder<'a> {
         ImageFrameBuilder {
-            size: (100, 100),
-            position: (0, 0),
+            size: (100, 100).into(),
+            position: (0, 0).into(),
             flags: None,
             ex_flags: 0,
             bitmap: None,
@@ -206,6 +208,7 @@ impl ImageFrame {
 
         let parent_handle = ControlHandle::Hwnd(wh::get_window_parent(handle));
         let brush = unsafe { CreateSolidBrush(RGB(c[0], c[1], c[2])) };
+        rt::track(ResourceKind::Brush, brush);
         self.background_brush = Some(brush);
         
         let handler = bind_raw_event_handler_inner(&parent_handle, handle as UINT_PTR, move |_hwnd, msg, _w, l| {
@@ -235,6 +238,7 @@ impl Drop for ImageFrame {
         }
 
         if let Some(bg) = self.background_brush {
+            rt::untrack(bg);
             unsafe { DeleteObject(bg as _); }
         }
 
@@ -243,8 +247,8 @@ impl Drop for ImageFrame {
 }
 
 pub struct ImageFrameBuilder<'a> {
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     flags: Option<ImageFrameFlags>,
     ex_flags: u32,
     bitmap: Option<&'a Bitmap>,
@@ -265,13 +269,13 @@ impl<'a> ImageFrameBuilder<'a> {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> ImageFrameBuilder<'a> {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> ImageFrameBuilder<'a> {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> ImageFrameBuilder<'a> {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> ImageFrameBuilder<'a> {
+        self.position = pos.into();
         self
     }
 
@@ -0,0 +1,704 @@
+use winapi::shared::windef::{HWND, HBITMAP, RECT};
+use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_TABSTOP, WS_BORDER, WS_CHILD, WS_CLIPSIBLINGS};
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
+use crate::win32::base_helper::check_hwnd;
+use crate::win32::high_dpi::{logical_to_physical, physical_to_logical};
+use crate::{NwgError, RawEventHandler, Bitmap, unbind_raw_event_handler};
+use super::{ControlBase, ControlHandle};
+use std::{mem, ptr, rc::Rc, cell::RefCell};
+
+const NOT_BOUND: &'static str = "ImageViewer is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: ImageViewer handle is not HWND!";
+
+/// Zoom factor applied by one notch of the mouse wheel or by the `+` and `-` keys
+const ZOOM_STEP: f64 = 1.25;
+const MIN_ZOOM: f64 = 0.01;
+const MAX_ZOOM: f64 = 64.0;
+
+/// Distance (in physical pixels) the image is moved by the arrow keys
+const PAN_STEP: f64 = 32.0;
+
+/// Size of a square of the checkerboard in physical pixels
+const CHECKER_SIZE: usize = 8;
+
+
+bitflags! {
+    /**
+        The image viewer flags
+
+        * NONE:     No flags. Equivalent to a invisible viewer.
+        * VISIBLE:  The viewer is immediatly visible after creation
+        * DISABLED: The viewer does not react to the mouse and the keyboard
+        * TAB_STOP: The viewer can be selected using tab navigation
+        * BORDER:   The viewer has a thin border
+    */
+    pub struct ImageViewerFlags: u32 {
+        const NONE = 0;
+        const VISIBLE = WS_VISIBLE;
+        const DISABLED = WS_DISABLED;
+        const TAB_STOP = WS_TABSTOP;
+        const BORDER = WS_BORDER;
+    }
+}
+
+/// Internal state shared with the image viewer event handler. All values are in physical pixels.
+struct ImageViewerState {
+    bitmap: HBITMAP,
+    image_size: (i32, i32),
+    alpha: bool,
+    zoom: f64,
+    fit: bool,
+    checkerboard: bool,
+
+    /// Position of the top left corner of the image in the client area of the viewer
+    offset: (f64, f64),
+
+    /// When dragging: the position of the mouse and the offset of the image when the drag started
+    drag: Option<((i32, i32), (f64, f64))>,
+}
+
+impl Default for ImageViewerState {
+    fn default() -> ImageViewerState {
+        ImageViewerState {
+            bitmap: ptr::null_mut(),
+            image_size: (0, 0),
+            alpha: false,
+            zoom: 1.0,
+            fit: true,
+            checkerboard: true,
+            offset: (0.0, 0.0),
+            drag: None,
+        }
+    }
+}
+
+/**
+An image viewer displays a bitmap that the user can zoom and pan.
+
+Interactions:
+  * Mouse wheel: zoom in or out around the mouse cursor
+  * Left mouse button drag: pan the image
+  * `+` and `-`: zoom in or out around the center of the viewer
+  * `0`: display the image at its actual size (1:1)
+  * `F`: fit the image in the viewer
+  * Arrow keys: pan the image
+
+In "fit to window" mode, the image is scaled to fill the viewer while keeping its aspect ratio, and it is scaled again when the viewer is resized.
+Zooming or panning the image leaves this mode. A zoom of `1.0` displays one pixel of the image per physical pixel of the screen.
+
+Transparent (32 bits premultiplied) bitmaps, such as those returned by `ImageDecoder`, are drawn over a checkerboard.
+The image is rendered with double buffering to avoid flickering.
+
+The viewer does not copy the bitmap. The bitmap must be kept alive as long as it is displayed.
+
+ImageViewer is implemented as a custom control.
+
+Requires the `image-viewer` feature.
+
+**Builder parameters:**
+  * `parent`:       **Required.** The image viewer parent container.
+  * `size`:         The image viewer size.
+  * `position`:     The image viewer position.
+  * `image`:        The bitmap to display
+  * `zoom`:         The initial zoom. `None` (the default) fits the image in the viewer.
+  * `checkerboard`: If the checkerboard is drawn behind the image. Defaults to `true`.
+  * `flags`:        A combination of the ImageViewerFlags values.
+
+**Control events:**
+  * `MousePress(_)`: Generic mouse press events on the viewer
+  * `OnMouseMove`: Generic mouse mouse event
+  * `OnMouseWheel`: Generic mouse wheel event
+
+```rust
+use native_windows_gui as nwg;
+fn build_viewer(viewer: &mut nwg::ImageViewer, window: &nwg::Window, image: &nwg::Bitmap) {
+    nwg::ImageViewer::builder()
+        .size((400, 300))
+        .image(Some(image))
+        .parent(window)
+        .build(viewer);
+}
+```
+*/
+#[derive(Default)]
+pub struct ImageViewer {
+    pub handle: ControlHandle,
+    state: Rc<RefCell<ImageViewerState>>,
+    handler0: RefCell<Option<RawEventHandler>>,
+}
+
+impl ImageViewer {
+
+    pub fn builder<'a>() -> ImageViewerBuilder<'a> {
+        ImageViewerBuilder {
+            size: (200, 200).into(),
+            position: (0, 0).into(),
+            image: None,
+            zoom: None,
+            checkerboard: true,
+            flags: None,
+            parent: None,
+        }
+    }
+
+    /// Sets the bitmap displayed by the viewer. `None` clears the viewer.
+    /// The zoom is kept unless the viewer is in "fit to window" mode.
+    pub fn set_image<'a>(&self, image: Option<&'a Bitmap>) {
+        use winapi::um::wingdi::{GetObjectW, BITMAP};
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+
+        state.bitmap = ptr::null_mut();
+        state.image_size = (0, 0);
+        state.alpha = false;
+
+        if let Some(image) = image {
+            unsafe {
+                let mut info: BITMAP = mem::zeroed();
+                let size = mem::size_of::<BITMAP>() as i32;
+                if GetObjectW(image.handle, size, &mut info as *mut BITMAP as _) == size {
+                    state.bitmap = image.handle as HBITMAP;
+                    state.image_size = (info.bmWidth, info.bmHeight.abs());
+                    state.alpha = info.bmBitsPixel == 32;
+                }
+            }
+        }
+
+        state.offset = (0.0, 0.0);
+        update_layout(handle, &mut state);
+        redraw(handle);
+    }
+
+    /// Returns the size (in pixels) of the displayed image, or `None` if the viewer is empty
+    pub fn image_size(&self) -> Option<(u32, u32)> {
+        let state = self.state.borrow();
+        match state.bitmap.is_null() {
+            true => None,
+            false => Some((state.image_size.0 as u32, state.image_size.1 as u32))
+        }
+    }
+
+    /// Returns the current zoom. `1.0` is the actual size of the image.
+    pub fn zoom(&self) -> f64 {
+        self.state.borrow().zoom
+    }
+
+    /// Sets the zoom, keeping the center of the viewer in place. Leaves the "fit to window" mode.
+    pub fn set_zoom(&self, zoom: f64) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        let (w, h) = client_size(handle);
+        zoom_at(handle, &mut state, zoom, (w as f64 / 2.0, h as f64 / 2.0));
+        redraw(handle);
+    }
+
+    /// Zooms in by one step, keeping the center of the viewer in place
+    pub fn zoom_in(&self) {
+        let zoom = self.zoom() * ZOOM_STEP;
+        self.set_zoom(zoom);
+    }
+
+    /// Zooms out by one step, keeping the center of the viewer in place
+    pub fn zoom_out(&self) {
+        let zoom = self.zoom() / ZOOM_STEP;
+        self.set_zoom(zoom);
+    }
+
+    /// Scales the image to fit in the viewer. The image is scaled again when the viewer is resized.
+    pub fn fit_to_window(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        state.fit = true;
+        update_layout(handle, &mut state);
+        redraw(handle);
+    }
+
+    /// Displays the image at its actual size (a zoom of `1.0`)
+    pub fn actual_size(&self) {
+        self.set_zoom(1.0);
+    }
+
+    /// Returns `true` if the viewer is in "fit to window" mode
+    pub fn is_fit_to_window(&self) -> bool {
+        self.state.borrow().fit
+    }
+
+    /// Returns the position of the top left corner of the image in the viewer
+    pub fn offset(&self) -> (i32, i32) {
+        let (x, y) = self.state.borrow().offset;
+        unsafe { physical_to_logical(x.round() as i32, y.round() as i32) }
+    }
+
+    /// Moves the image so that its top left corner is at `(x, y)` in the viewer.
+    /// The image is kept inside the viewer. Leaves the "fit to window" mode.
+    pub fn set_offset(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        let (x, y) = unsafe { logical_to_physical(x, y) };
+        state.fit = false;
+        state.offset = (x as f64, y as f64);
+        update_layout(handle, &mut state);
+        redraw(handle);
+    }
+
+    /// Returns `true` if the checkerboard is drawn behind the image
+    pub fn checkerboard(&self) -> bool {
+        self.state.borrow().checkerboard
+    }
+
+    /// Draws a checkerboard (`true`) or the window background color (`false`) behind the image
+    pub fn set_checkerboard(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow_mut().checkerboard = v;
+        redraw(handle);
+    }
+
+    /// Returns true if the control currently has the keyboard focus
+    pub fn focus(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_focus(handle) }
+    }
+
+    /// Sets the keyboard focus on the image viewer
+    pub fn set_focus(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_focus(handle); }
+    }
+
+    /// Returns true if the control user can interact with the control, return false otherwise
+    pub fn enabled(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_enabled(handle) }
+    }
+
+    /// Enable or disable the control
+    pub fn set_enabled(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_enabled(handle, v) }
+    }
+
+    /// Returns true if the control is visible to the user. Will return true even if the
+    /// control is outside of the parent client view (ex: at the position (10000, 10000))
+    pub fn visible(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_visibility(handle) }
+    }
+
+    /// Show or hide the control to the user
+    pub fn set_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_visibility(handle, v) }
+    }
+
+    /// Returns the size of the image viewer in the parent window
+    pub fn size(&self) -> (u32, u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_size(handle) }
+    }
+
+    /// Sets the size of the image viewer in the parent window
+    pub fn set_size(&self, x: u32, y: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_size(handle, x, y, false) }
+    }
+
+    /// Returns the position of the image viewer in the parent window
+    pub fn position(&self) -> (i32, i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_position(handle) }
+    }
+
+    /// Sets the position of the image viewer in the parent window
+    pub fn set_position(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_position(handle, x, y) }
+    }
+
+    /// Winapi class name used during control creation
+    pub fn class_name(&self) -> &'static str {
+        "NWG_IMAGE_VIEWER"
+    }
+
+    /// Winapi base flags used during window creation
+    pub fn flags(&self) -> u32 {
+        WS_VISIBLE | WS_TABSTOP
+    }
+
+    /// Winapi flags required by the control
+    pub fn forced_flags(&self) -> u32 {
+        WS_CHILD | WS_CLIPSIBLINGS
+    }
+
+    /// Handles the painting, the zoom and the panning of the image
+    fn hook_events(&self) {
+        use crate::bind_raw_event_handler_inner;
+        use winapi::um::winuser::{WM_PAINT, WM_ERASEBKGND, WM_SIZE, WM_MOUSEWHEEL, WM_KEYDOWN, WM_GETDLGCODE, WM_SETCURSOR,
+            WM_LBUTTONDOWN, WM_MOUSEMOVE, WM_LBUTTONUP, WM_CAPTURECHANGED, DLGC_WANTARROWS, DLGC_WANTCHARS, HTCLIENT, IDC_SIZEALL,
+            VK_ADD, VK_OEM_PLUS, VK_SUBTRACT, VK_OEM_MINUS, VK_NUMPAD0, VK_LEFT, VK_RIGHT, VK_UP, VK_DOWN, GET_WHEEL_DELTA_WPARAM};
+        use winapi::um::winuser::{SetCursor, LoadCursorW, SetCapture, ReleaseCapture, SetFocus, ScreenToClient};
+        use winapi::shared::windef::POINT;
+        use winapi::shared::minwindef::{LOWORD, HIWORD};
+
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let state = self.state.clone();
+
+        let handler = bind_raw_event_handler_inner(&self.handle, 0, move |hwnd, msg, w, l| {
+            // Mouse coordinates are signed
+            let mouse = || (LOWORD(l as u32) as i16 as i32, HIWORD(l as u32) as i16 as i32);
+
+            match msg {
+                WM_PAINT => {
+                    paint(hwnd, &state.borrow());
+                    return Some(0);
+                },
+                WM_ERASEBKGND => {
+                    // Everything is drawn in WM_PAINT
+                    return Some(1);
+                },
+                WM_SIZE => {
+                    // `try_borrow_mut` because the viewer can be resized while its state is borrowed
+                    if let Ok(mut state) = state.try_borrow_mut() {
+                        update_layout(hwnd, &mut state);
+                        redraw(hwnd);
+                    }
+                },
+                WM_GETDLGCODE => {
+                    return Some(DLGC_WANTARROWS | DLGC_WANTCHARS);
+                },
+                WM_SETCURSOR => {
+                    let can_pan = {
+                        let state = state.borrow();
+                        let (w, h) = client_size(hwnd);
+                        let (sw, sh) = scaled_size(&state);
+                        sw > w as f64 || sh > h as f64
+                    };
+
+                    if can_pan && LOWORD(l as u32) as isize == HTCLIENT {
+                        unsafe { SetCursor(LoadCursorW(ptr::null_mut(), IDC_SIZEALL)); }
+                        return Some(1);
+                    }
+                },
+                WM_MOUSEWHEEL => {
+                    let delta = GET_WHEEL_DELTA_WPARAM(w) as f64 / 120.0;
+                    let mut point = { let (x, y) = mouse(); POINT { x, y } };
+                    unsafe { ScreenToClient(hwnd, &mut point); }
+
+                    let mut state = state.borrow_mut();
+                    let zoom = state.zoom * ZOOM_STEP.powf(delta);
+                    zoom_at(hwnd, &mut state, zoom, (point.x as f64, point.y as f64));
+                    redraw(hwnd);
+                    return Some(0);
+                },
+                WM_KEYDOWN => {
+                    let mut state = state.borrow_mut();
+                    let (width, height) = client_size(hwnd);
+                    let center = (width as f64 / 2.0, height as f64 / 2.0);
+
+                    match w as i32 {
+                        VK_ADD | VK_OEM_PLUS => { let zoom = state.zoom * ZOOM_STEP; zoom_at(hwnd, &mut state, zoom, center); },
+                        VK_SUBTRACT | VK_OEM_MINUS => { let zoom = state.zoom / ZOOM_STEP; zoom_at(hwnd, &mut state, zoom, center); },
+                        0x30 | VK_NUMPAD0 => zoom_at(hwnd, &mut state, 1.0, center),
+                        0x46 => { state.fit = true; update_layout(hwnd, &mut state); },
+                        VK_LEFT => pan_by(hwnd, &mut state, PAN_STEP, 0.0),
+                        VK_RIGHT => pan_by(hwnd, &mut state, -PAN_STEP, 0.0),
+                        VK_UP => pan_by(hwnd, &mut state, 0.0, PAN_STEP),
+                        VK_DOWN => pan_by(hwnd, &mut state, 0.0, -PAN_STEP),
+                        _ => { return None; }
+                    }
+
+                    redraw(hwnd);
+                },
+                WM_LBUTTONDOWN => {
+                    {
+                        let mut state = state.borrow_mut();
+                        state.drag = Some((mouse(), state.offset));
+                    }
+
+                    unsafe {
+                        SetFocus(hwnd);
+                        SetCapture(hwnd);
+                    }
+                },
+                WM_MOUSEMOVE => {
+                    let mut state = state.borrow_mut();
+                    if let Some((start, offset)) = state.drag {
+                        let (x, y) = mouse();
+                        state.fit = false;
+                        state.offset = (offset.0 + (x - start.0) as f64, offset.1 + (y - start.1) as f64);
+                        update_layout(hwnd, &mut state);
+                        redraw(hwnd);
+                    }
+                },
+                WM_LBUTTONUP => {
+                    // ReleaseCapture sends WM_CAPTURECHANGED, so the state must not be borrowed
+                    let drag = state.borrow_mut().drag.take();
+                    if drag.is_some() {
+                        unsafe { ReleaseCapture(); }
+                    }
+                },
+                WM_CAPTURECHANGED => {
+                    state.borrow_mut().drag = None;
+                },
+                _ => {}
+            }
+
+            None
+        });
+
+        *self.handler0.borrow_mut() = Some(handler.unwrap());
+    }
+
+}
+
+impl Drop for ImageViewer {
+    fn drop(&mut self) {
+        let handler = self.handler0.borrow();
+        if let Some(h) = handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
+        self.handle.destroy();
+    }
+}
+
+impl PartialEq for ImageViewer {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for ImageViewer {}
+
+pub struct ImageViewerBuilder<'a> {
+    size: Extent,
+    position: Extent,
+    image: Option<&'a Bitmap>,
+    zoom: Option<f64>,
+    checkerboard: bool,
+    flags: Option<ImageViewerFlags>,
+    parent: Option<ControlHandle>
+}
+
+impl<'a> ImageViewerBuilder<'a> {
+
+    pub fn flags(mut self, flags: ImageViewerFlags) -> ImageViewerBuilder<'a> {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> ImageViewerBuilder<'a> {
+        self.size = size.into();
+        self
+    }
+
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> ImageViewerBuilder<'a> {
+        self.position = pos.into();
+        self
+    }
+
+    pub fn image(mut self, image: Option<&'a Bitmap>) -> ImageViewerBuilder<'a> {
+        self.image = image;
+        self
+    }
+
+    pub fn zoom(mut self, zoom: Option<f64>) -> ImageViewerBuilder<'a> {
+        self.zoom = zoom;
+        self
+    }
+
+    pub fn checkerboard(mut self, checkerboard: bool) -> ImageViewerBuilder<'a> {
+        self.checkerboard = checkerboard;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> ImageViewerBuilder<'a> {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut ImageViewer) -> Result<(), NwgError> {
+        let flags = self.flags.map(|f| f.bits()).unwrap_or(out.flags());
+
+        let parent = match self.parent {
+            Some(p) => Ok(p),
+            None => Err(NwgError::no_parent("ImageViewer"))
+        }?;
+
+        *out = Default::default();
+
+        out.handle = ControlBase::build_hwnd()
+            .class_name(out.class_name())
+            .forced_flags(out.forced_flags())
+            .flags(flags)
+            .size(self.size)
+            .position(self.position)
+            .parent(Some(parent))
+            .build()?;
+
+        out.state.borrow_mut().checkerboard = self.checkerboard;
+        out.hook_events();
+        out.set_image(self.image);
+
+        if let Some(zoom) = self.zoom {
+            out.set_zoom(zoom);
+        }
+
+        Ok(())
+    }
+
+}
+
+/// Returns the size of the client area of the viewer in physical pixels
+fn client_size(handle: HWND) -> (i32, i32) {
+    use winapi::um::winuser::GetClientRect;
+
+    unsafe {
+        let mut rect: RECT = mem::zeroed();
+        GetClientRect(handle, &mut rect);
+        (rect.right - rect.left, rect.bottom - rect.top)
+    }
+}
+
+/// Returns the size of the image with the current zoom
+fn scaled_size(state: &ImageViewerState) -> (f64, f64) {
+    (state.image_size.0 as f64 * state.zoom, state.image_size.1 as f64 * state.zoom)
+}
+
+/// Computes the zoom in "fit to window" mode and keeps the image inside the viewer.
+/// An image smaller than the viewer is centered.
+fn update_layout(handle: HWND, state: &mut ImageViewerState) {
+    let (width, height) = client_size(handle);
+    let (iw, ih) = state.image_size;
+
+    if state.fit {
+        state.zoom = match iw > 0 && ih > 0 && width > 0 && height > 0 {
+            true => (width as f64 / iw as f64).min(height as f64 / ih as f64).max(MIN_ZOOM).min(MAX_ZOOM),
+            false => 1.0
+        };
+    }
+
+    let (sw, sh) = scaled_size(state);
+    let clamp = |offset: f64, scaled: f64, view: f64| match scaled <= view {
+        true => ((view - scaled) / 2.0).floor(),
+        false => offset.min(0.0).max(view - scaled),
+    };
+
+    state.offset = (
+        clamp(state.offset.0, sw, width as f64),
+        clamp(state.offset.1, sh, height as f64),
+    );
+}
+
+/// Changes the zoom, keeping the point `anchor` of the viewer over the same pixel of the image
+fn zoom_at(handle: HWND, state: &mut ImageViewerState, zoom: f64, anchor: (f64, f64)) {
+    let zoom = zoom.max(MIN_ZOOM).min(MAX_ZOOM);
+    let ratio = zoom / state.zoom;
+
+    state.offset = (
+        anchor.0 - (anchor.0 - state.offset.0) * ratio,
+        anchor.1 - (anchor.1 - state.offset.1) * ratio,
+    );
+
+    state.zoom = zoom;
+    state.fit = false;
+    update_layout(handle, state);
+}
+
+fn pan_by(handle: HWND, state: &mut ImageViewerState, x: f64, y: f64) {
+    state.offset = (state.offset.0 + x, state.offset.1 + y);
+    state.fit = false;
+    update_layout(handle, state);
+}
+
+fn redraw(handle: HWND) {
+    use winapi::um::winuser::InvalidateRect;
+    unsafe { InvalidateRect(handle, ptr::null(), 0); }
+}
+
+/// Paints the viewer in a memory bitmap and copies the result on the screen
+fn paint(handle: HWND, state: &ImageViewerState) {
+    use winapi::um::winuser::{BeginPaint, EndPaint, FillRect, GetSysColorBrush, PAINTSTRUCT, COLOR_WINDOW};
+    use winapi::um::wingdi::{CreateCompatibleDC, CreateCompatibleBitmap, SelectObject, DeleteDC, DeleteObject, BitBlt, StretchBlt,
+        AlphaBlend, SetStretchBltMode, SetBrushOrgEx, BLENDFUNCTION, AC_SRC_OVER, AC_SRC_ALPHA, HALFTONE, SRCCOPY};
+
+    unsafe {
+        let mut ps: PAINTSTRUCT = mem::zeroed();
+        let hdc = BeginPaint(handle, &mut ps);
+        let (width, height) = client_size(handle);
+        let rect = RECT { left: 0, top: 0, right: width, bottom: height };
+
+        let buffer_dc = CreateCompatibleDC(hdc);
+        let buffer = CreateCompatibleBitmap(hdc, width.max(1), height.max(1));
+        let old_buffer = SelectObject(buffer_dc, buffer as _);
+
+        match state.checkerboard {
+            true => {
+                let (brush, pattern) = checkerboard_brush();
+                FillRect(buffer_dc, &rect, brush);
+                DeleteObject(brush as _);
+                DeleteObject(pattern as _);
+            },
+            false => {
+                FillRect(buffer_dc, &rect, GetSysColorBrush(COLOR_WINDOW));
+            }
+        }
+
+        if !state.bitmap.is_null() {
+            let (iw, ih) = state.image_size;
+            let (sw, sh) = scaled_size(state);
+            let (x, y) = (state.offset.0.round() as i32, state.offset.1.round() as i32);
+            let (sw, sh) = (sw.round().max(1.0) as i32, sh.round().max(1.0) as i32);
+
+            let image_dc = CreateCompatibleDC(hdc);
+            let old_image = SelectObject(image_dc, state.bitmap as _);
+
+            SetStretchBltMode(buffer_dc, HALFTONE);
+            SetBrushOrgEx(buffer_dc, 0, 0, ptr::null_mut());
+
+            match state.alpha {
+                true => {
+                    let blend = BLENDFUNCTION { BlendOp: AC_SRC_OVER, BlendFlags: 0, SourceConstantAlpha: 255, AlphaFormat: AC_SRC_ALPHA };
+                    AlphaBlend(buffer_dc, x, y, sw, sh, image_dc, 0, 0, iw, ih, blend);
+                },
+                false => {
+                    StretchBlt(buffer_dc, x, y, sw, sh, image_dc, 0, 0, iw, ih, SRCCOPY);
+                }
+            }
+
+            SelectObject(image_dc, old_image);
+            DeleteDC(image_dc);
+        }
+
+        BitBlt(hdc, 0, 0, width, height, buffer_dc, 0, 0, SRCCOPY);
+
+        SelectObject(buffer_dc, old_buffer);
+        DeleteObject(buffer as _);
+        DeleteDC(buffer_dc);
+
+        EndPaint(handle, &ps);
+    }
+}
+
+/// Creates the pattern brush used to draw the checkerboard. The brush and its bitmap must be deleted by the caller.
+fn checkerboard_brush() -> (winapi::shared::windef::HBRUSH, HBITMAP) {
+    use winapi::um::wingdi::{CreateBitmap, CreatePatternBrush};
+
+    const LIGHT: u32 = 0x00FFFFFF;
+    const DARK: u32 = 0x00CCCCCC;
+    const SIZE: usize = CHECKER_SIZE * 2;
+
+    let mut pixels = [0u32; SIZE * SIZE];
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let light = (x / CHECKER_SIZE) == (y / CHECKER_SIZE);
+            pixels[y * SIZE + x] = if light { LIGHT } else { DARK };
+        }
+    }
+
+    unsafe {
+        let pattern = CreateBitmap(SIZE as i32, SIZE as i32, 1, 32, pixels.as_ptr() as _);
+        (CreatePatternBrush(pattern), pattern)
+    }
+}
@@ -5,8 +5,10 @@ use winapi::um::{
 
 use winapi::shared::windef::HBRUSH;
 use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
 use crate::win32::base_helper::check_hwnd;
 use crate::{Font, NwgError, HTextAlign, VTextAlign, RawEventHandler, unbind_raw_event_handler};
+use crate::win32::resource_tracker::{self as rt, ResourceKind};
 use super::{ControlBase, ControlHandle};
 use std::cell::RefCell;
 
@@ -83,8 +85,8 @@ impl Label {
     pub fn builder<'a>() -> LabelBuilder<'a> {
         LabelBuilder {
             text: "A label",
-            size: (130, 25),
-            position: (0, 0),
+            size: (130, 25).into(),
+            position: (0, 0).into(),
             flags: None,
             ex_flags: 0,
             font: None,
@@ -224,6 +226,7 @@ impl Label {
         let brush = match bg {
             Some(c) => {
                 let b = unsafe { CreateSolidBrush(RGB(c[0], c[1], c[2])) };
+                rt::track(ResourceKind::Brush, b);
                 self.background_brush = Some(b);
                 b
             },
@@ -380,6 +383,7 @@ impl Drop for Label {
         }
 
         if let Some(bg) = self.background_brush {
+            rt::untrack(bg);
             unsafe { DeleteObject(bg as _); }
         }
 
@@ -389,8 +393,8 @@ impl Drop for Label {
 
 pub struct LabelBuilder<'a> {
     text: &'a str,
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     background_color: Option<[u8; 3]>,
     flags: Option<LabelFlags>,
     ex_flags: u32,
@@ -417,13 +421,13 @@ impl<'a> LabelBuilder<'a> {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> LabelBuilder<'a> {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> LabelBuilder<'a> {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> LabelBuilder<'a> {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> LabelBuilder<'a> {
+        self.position = pos.into();
         self
     }
 
@@ -0,0 +1,482 @@
+/*!
+    A top level window drawn from a 32 bits bitmap with per-pixel alpha. The transparent pixels of the bitmap are not drawn,
+    and do not receive the mouse input. Use it for splash screens, on-screen displays and custom shaped windows.
+
+    The window has no frame and no title bar. The content of the window is the bitmap passed to `set_bitmap`:
+    the window takes the size of the bitmap, and children controls are not drawn. The bitmap must be a 32 bits
+    bitmap with a premultiplied alpha channel (the bitmaps created by `ImageDecoder` from a PNG file).
+
+    Without a bitmap, the window is a plain popup window drawn by the application (`OnPaint`) and children controls
+    are drawn as usual, with a constant opacity.
+
+    Requires the `layered-window` feature.
+
+    **Builder parameters:**
+      * `size`:          The size of the window. Replaced by the size of the bitmap.
+      * `position`:      The position of the window in the desktop
+      * `bitmap`:        The content of the window
+      * `opacity`:       The opacity of the whole window, from 0 (invisible) to 255 (opaque). Defaults to 255.
+      * `click_through`: If the mouse input goes through the window to the windows below. Defaults to `false`.
+      * `topmost`:       If the window should always be on top of other system window. Defaults to `false`.
+      * `tool_window`:   If the window is hidden from the taskbar and the alt-tab list. Defaults to `true`.
+      * `center`:        Center the window in the current monitor based on its size. If `true`, this overrides `position`
+      * `visible`:       If the window is visible after creation. Defaults to `true`.
+
+    **Control events:**
+      * `OnInit`: The window was created
+      * `OnPaint`: The window must be painted. Only sent if there is no bitmap.
+      * `MousePress(_)`: Generic mouse press events on the window
+      * `OnMouseMove`: Generic mouse mouse event
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn splash_screen(window: &mut nwg::LayeredWindow, image: &nwg::Bitmap) {
+        nwg::LayeredWindow::builder()
+            .bitmap(Some(image))
+            .opacity(0)
+            .topmost(true)
+            .center(true)
+            .build(window)
+            .expect("Failed to build the splash screen");
+
+        window.fade(255, 300);
+    }
+    ```
+*/
+use winapi::shared::windef::{HWND, POINT, SIZE};
+use winapi::um::winuser::{WS_POPUP, WS_CLIPCHILDREN, WS_EX_LAYERED, WS_EX_TRANSPARENT, WS_EX_TOPMOST, WS_EX_TOOLWINDOW, GWL_EXSTYLE};
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
+use crate::win32::base_helper::check_hwnd;
+use crate::{NwgError, Bitmap, RawEventHandler, unbind_raw_event_handler};
+use super::{ControlBase, ControlHandle};
+use std::{rc::Rc, cell::RefCell, ptr, mem};
+use std::time::{Duration, Instant};
+
+const NOT_BOUND: &'static str = "LayeredWindow is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: LayeredWindow handle is not HWND!";
+
+/// Id of the timer that animates the opacity
+const FADE_TIMER_ID: u32 = 0xFADE;
+
+/// Interval of the opacity animation, in milliseconds
+const FADE_INTERVAL: u32 = 15;
+
+
+/// A running opacity animation
+struct Fade {
+    from: u8,
+    to: u8,
+    start: Instant,
+    duration: Duration,
+}
+
+#[derive(Default)]
+struct LayeredWindowState {
+    opacity: u8,
+    /// `true` once `UpdateLayeredWindow` was used. `SetLayeredWindowAttributes` cannot be used afterwards.
+    has_bitmap: bool,
+    fade: Option<Fade>,
+}
+
+/**
+    A window with per-pixel alpha. See the module documentation.
+*/
+#[derive(Default)]
+pub struct LayeredWindow {
+    pub handle: ControlHandle,
+    state: Rc<RefCell<LayeredWindowState>>,
+    handler0: RefCell<Option<RawEventHandler>>,
+}
+
+impl LayeredWindow {
+
+    pub fn builder<'a>() -> LayeredWindowBuilder<'a> {
+        LayeredWindowBuilder {
+            size: (300, 300).into(),
+            position: (300, 300).into(),
+            bitmap: None,
+            opacity: 255,
+            click_through: false,
+            topmost: false,
+            tool_window: true,
+            center: false,
+            visible: true,
+        }
+    }
+
+    /**
+        Replaces the content of the window. The window is resized to the size of the bitmap.
+        Returns an error if the bitmap is not a 32 bits bitmap.
+    */
+    pub fn set_bitmap(&self, bitmap: &Bitmap) -> Result<(), NwgError> {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let opacity = self.state.borrow().opacity;
+        unsafe { update_layered_window(handle, Some(bitmap), opacity)?; }
+
+        self.state.borrow_mut().has_bitmap = true;
+
+        Ok(())
+    }
+
+    /// Returns the opacity of the window, from 0 (invisible) to 255 (opaque)
+    pub fn opacity(&self) -> u8 {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow().opacity
+    }
+
+    /// Sets the opacity of the window, from 0 (invisible) to 255 (opaque). Stops the running fade animation.
+    pub fn set_opacity(&self, opacity: u8) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        self.stop_fade();
+        apply_opacity(handle, &mut self.state.borrow_mut(), opacity);
+    }
+
+    /**
+        Animates the opacity of the window to `opacity` in `duration` milliseconds.
+        The window is shown when the animation starts, and hidden when it ends if `opacity` is 0.
+    */
+    pub fn fade(&self, opacity: u8, duration: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        {
+            let mut state = self.state.borrow_mut();
+            state.fade = Some(Fade {
+                from: state.opacity,
+                to: opacity,
+                start: Instant::now(),
+                duration: Duration::from_millis(duration as u64),
+            });
+        }
+
+        unsafe { wh::set_window_visibility(handle, true); }
+        wh::start_timer(handle, FADE_TIMER_ID, FADE_INTERVAL);
+    }
+
+    /// Returns `true` if a fade animation is running
+    pub fn fading(&self) -> bool {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow().fade.is_some()
+    }
+
+    /// Stops the running fade animation. The window keeps its current opacity.
+    pub fn stop_fade(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        if self.state.borrow_mut().fade.take().is_some() {
+            wh::kill_timer(handle, FADE_TIMER_ID);
+        }
+    }
+
+    /// Returns `true` if the mouse input goes through the window
+    pub fn click_through(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::get_window_long(handle, GWL_EXSTYLE) as u32 & WS_EX_TRANSPARENT == WS_EX_TRANSPARENT
+    }
+
+    /// Sets if the mouse input goes through the window to the windows below
+    pub fn set_click_through(&self, click_through: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let ex_style = wh::get_window_long(handle, GWL_EXSTYLE) as u32;
+        let ex_style = match click_through {
+            true => ex_style | WS_EX_TRANSPARENT,
+            false => ex_style & !WS_EX_TRANSPARENT
+        };
+
+        wh::set_window_long(handle, GWL_EXSTYLE, ex_style as usize);
+    }
+
+    /// Returns true if the window is visible
+    pub fn visible(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_visibility(handle) }
+    }
+
+    /// Shows or hides the window
+    pub fn set_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_visibility(handle, v) }
+    }
+
+    /// Returns the size of the window
+    pub fn size(&self) -> (u32, u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_size(handle) }
+    }
+
+    /// Sets the size of the window. Has no effect if the window has a bitmap.
+    pub fn set_size(&self, x: u32, y: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_size(handle, x, y, false) }
+    }
+
+    /// Returns the position of the window in the desktop
+    pub fn position(&self) -> (i32, i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_position(handle) }
+    }
+
+    /// Sets the position of the window in the desktop
+    pub fn set_position(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_position(handle, x, y) }
+    }
+
+    /// Winapi class name used during control creation
+    pub fn class_name(&self) -> &'static str {
+        "NativeWindowsGuiWindow"
+    }
+
+    // Winapi base flags used during window creation. The window is shown once its content is set.
+    pub fn flags(&self) -> u32 {
+        0
+    }
+
+    /// Winapi flags required by the control
+    pub fn forced_flags(&self) -> u32 {
+        WS_POPUP | WS_CLIPCHILDREN
+    }
+
+    /// Animates the opacity
+    fn hook_fade(&self) {
+        use crate::bind_raw_event_handler_inner;
+        use winapi::um::winuser::WM_TIMER;
+
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let state = self.state.clone();
+
+        let handler = bind_raw_event_handler_inner(&self.handle, 0, move |hwnd, msg, w, _l| {
+            if msg != WM_TIMER || w != FADE_TIMER_ID as usize {
+                return None;
+            }
+
+            let mut state = state.borrow_mut();
+            let (opacity, done) = match state.fade.as_ref() {
+                Some(fade) => {
+                    let elapsed = fade.start.elapsed();
+                    match elapsed >= fade.duration {
+                        true => (fade.to, true),
+                        false => {
+                            let progress = elapsed.as_secs_f64() / fade.duration.as_secs_f64();
+                            let opacity = fade.from as f64 + (fade.to as f64 - fade.from as f64) * progress;
+                            (opacity.round() as u8, false)
+                        }
+                    }
+                },
+                None => (state.opacity, true)
+            };
+
+            apply_opacity(hwnd, &mut state, opacity);
+
+            if done {
+                state.fade = None;
+                wh::kill_timer(hwnd, FADE_TIMER_ID);
+
+                if opacity == 0 {
+                    unsafe { wh::set_window_visibility(hwnd, false); }
+                }
+            }
+
+            Some(0)
+        });
+
+        *self.handler0.borrow_mut() = Some(handler.unwrap());
+    }
+
+}
+
+impl Drop for LayeredWindow {
+    fn drop(&mut self) {
+        let handler = self.handler0.borrow();
+        if let Some(h) = handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
+        self.handle.destroy();
+    }
+}
+
+impl PartialEq for LayeredWindow {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for LayeredWindow {}
+
+pub struct LayeredWindowBuilder<'a> {
+    size: Extent,
+    position: Extent,
+    bitmap: Option<&'a Bitmap>,
+    opacity: u8,
+    click_through: bool,
+    topmost: bool,
+    tool_window: bool,
+    center: bool,
+    visible: bool,
+}
+
+impl<'a> LayeredWindowBuilder<'a> {
+
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> LayeredWindowBuilder<'a> {
+        self.size = size.into();
+        self
+    }
+
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> LayeredWindowBuilder<'a> {
+        self.position = pos.into();
+        self
+    }
+
+    pub fn bitmap(mut self, bitmap: Option<&'a Bitmap>) -> LayeredWindowBuilder<'a> {
+        self.bitmap = bitmap;
+        self
+    }
+
+    pub fn opacity(mut self, opacity: u8) -> LayeredWindowBuilder<'a> {
+        self.opacity = opacity;
+        self
+    }
+
+    pub fn click_through(mut self, click_through: bool) -> LayeredWindowBuilder<'a> {
+        self.click_through = click_through;
+        self
+    }
+
+    pub fn topmost(mut self, topmost: bool) -> LayeredWindowBuilder<'a> {
+        self.topmost = topmost;
+        self
+    }
+
+    pub fn tool_window(mut self, tool_window: bool) -> LayeredWindowBuilder<'a> {
+        self.tool_window = tool_window;
+        self
+    }
+
+    pub fn center(mut self, center: bool) -> LayeredWindowBuilder<'a> {
+        self.center = center;
+        self
+    }
+
+    pub fn visible(mut self, visible: bool) -> LayeredWindowBuilder<'a> {
+        self.visible = visible;
+        self
+    }
+
+    pub fn build(self, out: &mut LayeredWindow) -> Result<(), NwgError> {
+        use crate::win32::high_dpi::physical_to_logical;
+
+        let mut ex_flags = WS_EX_LAYERED;
+        if self.click_through { ex_flags |= WS_EX_TRANSPARENT; }
+        if self.topmost { ex_flags |= WS_EX_TOPMOST; }
+        if self.tool_window { ex_flags |= WS_EX_TOOLWINDOW; }
+
+        *out = Default::default();
+
+        out.handle = ControlBase::build_hwnd()
+            .class_name(out.class_name())
+            .forced_flags(out.forced_flags())
+            .ex_flags(ex_flags)
+            .flags(out.flags())
+            .size(self.size)
+            .position(self.position)
+            .text("")
+            .build()?;
+
+        let handle = out.handle.hwnd().unwrap();
+
+        {
+            let mut state = out.state.borrow_mut();
+            match self.bitmap {
+                Some(bitmap) => {
+                    unsafe { update_layered_window(handle, Some(bitmap), self.opacity)?; }
+                    state.opacity = self.opacity;
+                    state.has_bitmap = true;
+                },
+                None => apply_opacity(handle, &mut state, self.opacity)
+            }
+        }
+
+        out.hook_fade();
+
+        if self.center {
+            let [left, top, right, bottom] = crate::Monitor::monitor_rect_from_window(out.handle);
+            let (m_width, m_height) = unsafe { physical_to_logical(right-left, bottom-top) };
+            let (width, height) = out.size();
+
+            let x = left + ((m_width-width as i32)/2);
+            let y = top + ((m_height-height as i32)/2);
+
+            out.set_position(x, y);
+        }
+
+        if self.visible {
+            out.set_visible(true);
+        }
+
+        Ok(())
+    }
+
+}
+
+/// Sets the opacity of a window, with or without a bitmap
+fn apply_opacity(handle: HWND, state: &mut LayeredWindowState, opacity: u8) {
+    use winapi::um::winuser::{SetLayeredWindowAttributes, LWA_ALPHA};
+
+    state.opacity = opacity;
+
+    unsafe {
+        match state.has_bitmap {
+            true => { update_layered_window(handle, None, opacity).ok(); },
+            false => { SetLayeredWindowAttributes(handle, 0, opacity, LWA_ALPHA); }
+        }
+    }
+}
+
+/// Updates the content and the opacity of a layered window. Without bitmap, only the opacity changes.
+unsafe fn update_layered_window(handle: HWND, bitmap: Option<&Bitmap>, opacity: u8) -> Result<(), NwgError> {
+    use winapi::um::winuser::{UpdateLayeredWindow, GetDC, ReleaseDC, ULW_ALPHA};
+    use winapi::um::wingdi::{CreateCompatibleDC, DeleteDC, SelectObject, GetObjectW, BITMAP, BLENDFUNCTION, AC_SRC_OVER, AC_SRC_ALPHA};
+
+    let mut blend = BLENDFUNCTION {
+        BlendOp: AC_SRC_OVER,
+        BlendFlags: 0,
+        SourceConstantAlpha: opacity,
+        AlphaFormat: AC_SRC_ALPHA,
+    };
+
+    let bitmap = match bitmap {
+        Some(b) => b,
+        None => {
+            // The content of the window is kept
+            UpdateLayeredWindow(handle, ptr::null_mut(), ptr::null_mut(), ptr::null_mut(), ptr::null_mut(), ptr::null_mut(), 0, &mut blend, ULW_ALPHA);
+            return Ok(());
+        }
+    };
+
+    let mut info: BITMAP = mem::zeroed();
+    GetObjectW(bitmap.handle, mem::size_of::<BITMAP>() as i32, &mut info as *mut BITMAP as _);
+    if info.bmBitsPixel != 32 {
+        return Err(NwgError::resource_create("The content of a LayeredWindow must be a 32 bits bitmap"));
+    }
+
+    let screen_dc = GetDC(ptr::null_mut());
+    let memory_dc = CreateCompatibleDC(screen_dc);
+    let old_bitmap = SelectObject(memory_dc, bitmap.handle as _);
+
+    let mut size = SIZE { cx: info.bmWidth, cy: info.bmHeight.abs() };
+    let mut source = POINT { x: 0, y: 0 };
+    let result = UpdateLayeredWindow(handle, screen_dc, ptr::null_mut(), &mut size, memory_dc, &mut source, 0, &mut blend, ULW_ALPHA);
+
+    SelectObject(memory_dc, old_bitmap);
+    DeleteDC(memory_dc);
+    ReleaseDC(ptr::null_mut(), screen_dc);
+
+    match result {
+        0 => Err(NwgError::control_create("UpdateLayeredWindow failed")),
+        _ => Ok(())
+    }
+}
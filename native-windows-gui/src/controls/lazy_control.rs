@@ -0,0 +1,212 @@
+use crate::NwgError;
+use crate::win32::window::bind_late_child;
+use super::ControlHandle;
+use std::cell::{Cell, Ref, RefCell, RefMut};
+use std::fmt;
+
+
+type LazyBuilder<T> = Box<dyn FnMut(&mut T) -> Result<(), NwgError>>;
+
+/**
+A control that is only created the first time it is accessed.
+
+`LazyControl` stores a function that builds the control and calls it the first time `get`, `get_mut` or `build` is called.
+Windows with many rarely used tabs or dialogs can wrap those controls in a `LazyControl` to reduce their startup time.
+
+Because the build function is called later, it cannot borrow the other members of the UI. Convert the parent into a `ControlHandle`
+before creating the function and copy or clone the other parameters.
+
+The control is created when it is accessed, not when it is shown. To create a control when a tab or a dialog is shown, access it
+in the handler of the event that shows it (ex: `OnTabsContainerChanged` or `OnWindowShow`).
+
+Events are only sent to the control after it was built. When the control is built, it is bound to the event handlers
+that `full_bind_event_handler` bound to its parent (the default handler of native-windows-derive), so it receives its own
+events (ex: `OnKeyPress`, `OnResize`) like the controls created before the handler was bound.
+Comparing a control handle to a `LazyControl` that was not built always returns `false`.
+
+If the build function fails, it is kept and called again the next time the control is accessed.
+
+When using native-windows-derive, add the `lazy` parameter to `nwg_control`. The `parent` parameter is handled automatically,
+but the other parameters must not borrow the UI struct.
+
+```rust
+use native_windows_gui as nwg;
+
+fn lazy_button(window: &nwg::Window) -> nwg::LazyControl<nwg::Button> {
+    let parent: nwg::ControlHandle = window.into();
+    nwg::LazyControl::new(move |out| {
+        nwg::Button::builder()
+            .text("Rarely used")
+            .parent(parent)
+            .build(out)
+    })
+}
+
+fn show_button(button: &nwg::LazyControl<nwg::Button>) {
+    // The button is created here
+    button.get().set_visible(true);
+}
+```
+
+```rust
+use native_windows_derive as nwd;
+use native_windows_gui as nwg;
+
+#[derive(Default, nwd::NwgUi)]
+pub struct LazyApp {
+    #[nwg_control]
+    window: nwg::Window,
+
+    #[nwg_control(lazy, text: "Advanced")]
+    advanced: nwg::LazyControl<nwg::Button>,
+}
+```
+*/
+pub struct LazyControl<T> {
+    control: RefCell<T>,
+    builder: RefCell<Option<LazyBuilder<T>>>,
+    built: Cell<bool>,
+}
+
+impl<T: Default> LazyControl<T>
+    where for<'b> ControlHandle: From<&'b T>
+{
+
+    /// Create a new lazy control using `builder` to create the control
+    pub fn new<F>(builder: F) -> LazyControl<T>
+        where F: FnMut(&mut T) -> Result<(), NwgError> + 'static
+    {
+        LazyControl {
+            control: RefCell::new(Default::default()),
+            builder: RefCell::new(Some(Box::new(builder))),
+            built: Cell::new(false),
+        }
+    }
+
+    /// Replace the function used to create the control. Does nothing if the control was already built.
+    pub fn set_builder<F>(&self, builder: F)
+        where F: FnMut(&mut T) -> Result<(), NwgError> + 'static
+    {
+        if !self.is_built() {
+            *self.builder.borrow_mut() = Some(Box::new(builder));
+        }
+    }
+
+    /**
+        Create the control if it was not already created.
+        Returns an error if the lazy control has no build function or if the build function fails.
+    */
+    pub fn build(&self) -> Result<(), NwgError> {
+        if self.is_built() {
+            return Ok(());
+        }
+
+        let builder = self.builder.borrow_mut().take();
+        match builder {
+            Some(mut builder) => {
+                // The control is built outside of the cell because the events sent during the
+                // creation of the control may compare their handle with this lazy control
+                let mut control = T::default();
+                if let Err(e) = builder(&mut control) {
+                    // Keep the build function so that the control can be built later
+                    *self.builder.borrow_mut() = Some(builder);
+                    return Err(e);
+                }
+
+                if let Some(hwnd) = ControlHandle::from(&control).hwnd() {
+                    bind_late_child(hwnd);
+                }
+
+                *self.control.borrow_mut() = control;
+                self.built.set(true);
+                Ok(())
+            },
+            None => Err(NwgError::control_create("LazyControl has no build function"))
+        }
+    }
+
+    /// Return a reference to the control, creating it if needed
+    ///
+    /// Panics if the control could not be created. Use `build` to handle the error.
+    pub fn get(&self) -> Ref<T> {
+        if let Err(e) = self.build() {
+            panic!("Failed to build lazy control: {}", e);
+        }
+
+        self.control.borrow()
+    }
+
+    /// Return a mutable reference to the control, creating it if needed
+    ///
+    /// Panics if the control could not be created. Use `build` to handle the error.
+    pub fn get_mut(&self) -> RefMut<T> {
+        if let Err(e) = self.build() {
+            panic!("Failed to build lazy control: {}", e);
+        }
+
+        self.control.borrow_mut()
+    }
+
+}
+
+impl<T> LazyControl<T> {
+
+    /// Return `true` if the control was created
+    pub fn is_built(&self) -> bool {
+        self.built.get()
+    }
+
+    /// Return a reference to the control if it was already created. Never creates the control.
+    pub fn try_get(&self) -> Option<Ref<T>> {
+        match self.is_built() {
+            true => Some(self.control.borrow()),
+            false => None
+        }
+    }
+
+}
+
+impl<T: Default> Default for LazyControl<T> {
+    fn default() -> LazyControl<T> {
+        LazyControl {
+            control: RefCell::new(Default::default()),
+            builder: RefCell::new(None),
+            built: Cell::new(false),
+        }
+    }
+}
+
+impl<T> fmt::Debug for LazyControl<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "LazyControl {{ built: {} }}", self.built.get())
+    }
+}
+
+/// Using a lazy control as a parent creates the control
+impl<'a, T: Default> From<&'a LazyControl<T>> for ControlHandle
+    where for<'b> ControlHandle: From<&'b T>
+{
+    fn from(control: &'a LazyControl<T>) -> ControlHandle {
+        let control = control.get();
+        ControlHandle::from(&*control)
+    }
+}
+
+impl<T: Default> PartialEq<LazyControl<T>> for ControlHandle
+    where ControlHandle: PartialEq<T>
+{
+    fn eq(&self, other: &LazyControl<T>) -> bool {
+        match other.try_get() {
+            Some(control) => *self == *control,
+            None => false
+        }
+    }
+}
+
+impl<T: Default> PartialEq<ControlHandle> for LazyControl<T>
+    where ControlHandle: PartialEq<T>
+{
+    fn eq(&self, other: &ControlHandle) -> bool {
+        *other == *self
+    }
+}
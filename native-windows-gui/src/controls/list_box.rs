@@ -2,7 +2,8 @@ use winapi::shared::windef::HWND;
 use winapi::shared::minwindef::{WPARAM, LPARAM};
 use winapi::um::winuser::{LBS_MULTIPLESEL, LBS_NOSEL, WS_VISIBLE, WS_DISABLED, WS_TABSTOP};
 use crate::win32::window_helper as wh;
-use crate::win32::base_helper::{to_utf16, from_utf16, check_hwnd};
+use crate::win32::high_dpi::Extent;
+use crate::win32::base_helper::{to_utf16, with_utf16, from_utf16, check_hwnd};
 use crate::{Font, NwgError};
 use super::{ControlBase, ControlHandle};
 use std::cell::{Ref, RefMut, RefCell};
@@ -84,8 +85,8 @@ impl<D: Display+Default> ListBox<D> {
 
     pub fn builder<'a>() -> ListBoxBuilder<'a, D> {
         ListBoxBuilder {
-            size: (100, 300),
-            position: (0, 0),
+            size: (100, 300).into(),
+            position: (0, 0).into(),
             enabled: true,
             focus: false,
             flags: None,
@@ -104,11 +105,9 @@ impl<D: Display+Default> ListBox<D> {
 
         let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
         let display = format!("{}", item);
-        let display_os = to_utf16(&display);
-
-        unsafe {
-            wh::send_message(handle, LB_ADDSTRING, 0, mem::transmute(display_os.as_ptr()));
-        }
+        with_utf16(&display, |display_os| {
+            wh::send_message(handle, LB_ADDSTRING, 0, display_os.as_ptr() as LPARAM);
+        });
 
         self.collection.borrow_mut().push(item);
     }
@@ -325,11 +324,9 @@ impl<D: Display+Default> ListBox<D> {
 
         for item in self.collection.borrow().iter() {
             let display = format!("{}", item);
-            let display_os = to_utf16(&display);
-            
-            unsafe {
-                wh::send_message(handle, LB_ADDSTRING, 0, mem::transmute(display_os.as_ptr()));
-            }
+            with_utf16(&display, |display_os| {
+                wh::send_message(handle, LB_ADDSTRING, 0, display_os.as_ptr() as LPARAM);
+            });
         }
     }
 
@@ -343,11 +340,9 @@ impl<D: Display+Default> ListBox<D> {
 
         for item in col.iter() {
             let display = format!("{}", item);
-            let display_os = to_utf16(&display);
-            
-            unsafe {
-                wh::send_message(handle, LB_ADDSTRING, 0, mem::transmute(display_os.as_ptr()));
-            }
+            with_utf16(&display, |display_os| {
+                wh::send_message(handle, LB_ADDSTRING, 0, display_os.as_ptr() as LPARAM);
+            });
         }
 
         let mut col_ref = self.collection.borrow_mut();
@@ -498,8 +493,8 @@ impl<D: Display+Default> Drop for ListBox<D> {
 }
 
 pub struct ListBoxBuilder<'a, D: Display+Default> {
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     enabled: bool,
     focus: bool,
     flags: Option<ListBoxFlags>,
@@ -523,13 +518,13 @@ impl<'a, D: Display+Default> ListBoxBuilder<'a, D> {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> ListBoxBuilder<'a, D> {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> ListBoxBuilder<'a, D> {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> ListBoxBuilder<'a, D> {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> ListBoxBuilder<'a, D> {
+        self.position = pos.into();
         self
     }
 
@@ -4,8 +4,10 @@ use winapi::um::commctrl::{
     LVS_ICON, LVS_SMALLICON, LVS_LIST, LVS_REPORT, LVS_NOCOLUMNHEADER, LVCOLUMNW, LVCFMT_LEFT, LVCFMT_RIGHT, LVCFMT_CENTER, LVCFMT_JUSTIFYMASK,
     LVCFMT_IMAGE, LVCFMT_BITMAP_ON_RIGHT, LVCFMT_COL_HAS_IMAGES, LVITEMW, LVIF_TEXT, LVCF_WIDTH, LVCF_TEXT, LVS_EX_GRIDLINES, LVS_EX_BORDERSELECT,
     LVS_EX_AUTOSIZECOLUMNS, LVM_SETEXTENDEDLISTVIEWSTYLE, LVS_EX_FULLROWSELECT, LVS_SINGLESEL, LVCF_FMT, LVIF_IMAGE, LVS_SHOWSELALWAYS,
-    LVS_EX_HEADERDRAGDROP, LVS_EX_HEADERINALLVIEWS, LVM_GETHEADER, HDITEMW, HDI_FORMAT, HDM_GETITEMW, HDF_SORTUP, HDF_SORTDOWN, HDM_SETITEMW
+    LVS_SHAREIMAGELISTS, LVS_EX_HEADERDRAGDROP, LVS_EX_HEADERINALLVIEWS, LVM_GETHEADER, HDITEMW, HDI_FORMAT, HDM_GETITEMW, HDF_SORTUP, HDF_SORTDOWN, HDM_SETITEMW
 };
+use crate::win32::resource_tracker::{self as rt, ResourceKind};
+use crate::win32::high_dpi::Extent;
 use super::{ControlBase, ControlHandle};
 use crate::win32::window_helper as wh;
 use crate::win32::base_helper::{to_utf16, from_utf16, check_hwnd};
@@ -30,6 +32,7 @@ bitflags! {
         * NO_HEADER: Remove the headers in Detailed view (ON by default, use `ListView::set_headers_enabled` to enable headers)
         * SINGLE_SELECTION: Only one item can be selected
         * ALWAYS_SHOW_SELECTION: Shows the selected list view item when the control is not in focus
+        * SHARE_IMAGE_LISTS: The image lists are not destroyed with the list view. Required to share an image list between list views
     */
     pub struct ListViewFlags: u32 {
         const VISIBLE = WS_VISIBLE;
@@ -40,6 +43,8 @@ bitflags! {
 
         const ALWAYS_SHOW_SELECTION = LVS_SHOWSELALWAYS;
 
+        const SHARE_IMAGE_LISTS = LVS_SHAREIMAGELISTS;
+
         // Remove the headers in Detailed view (ON by default due to backward compatibility)
         // TODO: OFF by default in next major releases
         const NO_HEADER = LVS_NOCOLUMNHEADER;
@@ -273,6 +278,7 @@ Builder parameters:
   * `OnListViewItemChanged`: When an item is selected/unselected in the listview
   * `OnListViewFocus`: When the list view has received focus
   * `OnListViewFocusLost`: When the list view has lost focus
+  * `OnListViewBeginDrag`: When the user starts to drag an item with the left mouse button
 
 */
 #[derive(Default)]
@@ -286,8 +292,8 @@ impl ListView {
 
     pub fn builder() -> ListViewBuilder {
         ListViewBuilder {
-            size: (300, 300),
-            position: (0, 0),
+            size: (300, 300).into(),
+            position: (0, 0).into(),
             background_color: None,
             double_buffer: true,
             text_color: None,
@@ -998,6 +1004,7 @@ impl ListView {
             size: [0, 0],
             bg: unsafe { CreateSolidBrush(RGB(255, 255, 255)) },
         };
+        rt::track(ResourceKind::Brush, double_buffer.bg);
 
         let rc_double_buffer = Rc::new(RefCell::new(double_buffer));
         let callback_double_buffer = rc_double_buffer.clone();
@@ -1082,6 +1089,7 @@ impl Drop for ListView {
             let double_buffer = backbuffer.borrow();
             unsafe {
                 DeleteObject(double_buffer.buffer as _);
+                rt::untrack(double_buffer.bg);
                 DeleteObject(double_buffer.bg as _);
             }
         }
@@ -1095,8 +1103,8 @@ impl Drop for ListView {
 }
 
 pub struct ListViewBuilder {
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     background_color: Option<[u8; 3]>,
     text_color: Option<[u8; 3]>,
     double_buffer: bool,
@@ -1132,13 +1140,13 @@ impl ListViewBuilder {
     }
 
 
-    pub fn size(mut self, size: (i32, i32)) -> ListViewBuilder {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> ListViewBuilder {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, position: (i32, i32)) -> ListViewBuilder {
-        self.position = position;
+    pub fn position<P: Into<Extent>>(mut self, position: P) -> ListViewBuilder {
+        self.position = position.into();
         self
     }
 
@@ -0,0 +1,1002 @@
+use winapi::shared::windef::HWND;
+use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_TABSTOP, WS_BORDER, WS_CHILD, WS_CLIPSIBLINGS, WS_VSCROLL};
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
+use crate::win32::base_helper::check_hwnd;
+use crate::win32::high_dpi::scale_factor;
+use crate::win32::markdown::{self as md, Block, BlockKind, NMMARKDOWNLINK, MARKDOWN_LINK_CLICK};
+use crate::win32::markdown_renderer::{MarkdownRenderer, TextLayout, Rect};
+use crate::{ImageData, NwgError, RawEventHandler, unbind_raw_event_handler};
+use super::{ControlBase, ControlHandle};
+use std::{mem, ptr, rc::Rc, cell::RefCell};
+
+const NOT_BOUND: &'static str = "MarkdownView is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: MarkdownView handle is not HWND!";
+
+/// The default size of the body text, in DIPs
+const DEFAULT_FONT_SIZE: f32 = 14.0;
+
+/// Space around the document, in DIPs
+const PADDING: f32 = 12.0;
+
+/// Size of the headings relative to the body text
+const HEADING_SCALE: [f32; 6] = [2.0, 1.5, 1.25, 1.0, 0.9, 0.85];
+
+/// Lines scrolled by one notch of the mouse wheel
+const WHEEL_LINES: f32 = 3.0;
+
+const BACKGROUND_COLOR: [u8; 3] = [255, 255, 255];
+const TEXT_COLOR: [u8; 3] = [36, 41, 47];
+const LINK_COLOR: [u8; 3] = [9, 105, 218];
+const CODE_BACKGROUND_COLOR: [u8; 3] = [240, 242, 245];
+const QUOTE_BAR_COLOR: [u8; 3] = [208, 215, 222];
+const QUOTE_TEXT_COLOR: [u8; 3] = [87, 96, 106];
+const RULE_COLOR: [u8; 3] = [216, 222, 228];
+
+
+bitflags! {
+    /**
+        The markdown view flags
+
+        * NONE:     No flags. Equivalent to a invisible markdown view.
+        * VISIBLE:  The markdown view is immediatly visible after creation
+        * DISABLED: The markdown view cannot be focused by the user
+        * TAB_STOP: The markdown view can be selected using tab navigation
+        * BORDER:   The markdown view has a thin border
+    */
+    pub struct MarkdownViewFlags: u32 {
+        const NONE = 0;
+        const VISIBLE = WS_VISIBLE;
+        const DISABLED = WS_DISABLED;
+        const TAB_STOP = WS_TABSTOP;
+        const BORDER = WS_BORDER;
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum LaidKind {
+    Text,
+    Heading(u8),
+    Code,
+    Rule,
+    Image(String),
+    /// An image that was not registered with `set_image`. The alternate text is displayed in a frame.
+    MissingImage,
+}
+
+/// A block of the document placed in the view. The positions are in DIPs from the top of the document.
+struct LaidBlock {
+    kind: LaidKind,
+    rect: Rect,
+    quote: usize,
+    /// The bottom of the quote bars, that continue in the space between two quoted blocks
+    quote_bottom: f32,
+    text: Option<(TextLayout, (f32, f32))>,
+    marker: Option<(TextLayout, (f32, f32))>,
+    /// The links of the text: first UTF-16 unit, length, url
+    links: Vec<(u32, u32, String)>,
+    /// A link that covers the whole block (image inside a link)
+    block_link: Option<String>,
+    code_ranges: Vec<(u32, u32)>,
+    anchor: Option<String>,
+}
+
+struct MarkdownViewState {
+    renderer: Option<MarkdownRenderer>,
+    markdown: String,
+    blocks: Vec<Block>,
+    laid: Vec<LaidBlock>,
+    /// The width used for the layout, in DIPs. Negative if the layout must be recomputed.
+    layout_width: f32,
+    content_height: f32,
+    /// The vertical scroll position, in DIPs
+    scroll: f32,
+    pressed_link: Option<String>,
+}
+
+impl Default for MarkdownViewState {
+    fn default() -> MarkdownViewState {
+        MarkdownViewState {
+            renderer: None,
+            markdown: String::new(),
+            blocks: Vec::new(),
+            laid: Vec::new(),
+            layout_width: -1.0,
+            content_height: 0.0,
+            scroll: 0.0,
+            pressed_link: None,
+        }
+    }
+}
+
+/**
+A markdown view displays a read-only Markdown document: headings, paragraphs with bold, italic, strikethrough and code text,
+ordered and unordered lists, block quotes, code blocks, horizontal rules, links and images. It is meant for help panes,
+release notes or "About" pages, without embedding a web view.
+
+When the user clicks a link, the control raises a `OnMarkdownLinkClick` event with the url of the link. The control does not
+open the links itself. Links that start with `#` are anchors: clicking them scrolls the view to the heading with the matching
+anchor (ex: `[Install](#getting-started)` goes to the heading "Getting started") and no event is raised.
+
+Images are not loaded by the control. The application decodes them (for example with an `ImageDecoder`) and registers them
+with `set_image` under the name used in the document (`![logo](logo.png)` uses the image registered as "logo.png").
+Images that are not registered are replaced by their alternate text. Images are displayed at 1 pixel per logical pixel,
+reduced to the width of the view if needed.
+
+MarkdownView is implemented as a custom control drawn with Direct2D and DirectWrite.
+
+Requires the `markdown-view` feature.
+
+**Builder parameters:**
+  * `parent`:    **Required.** The markdown view parent container.
+  * `size`:      The markdown view size.
+  * `position`:  The markdown view position.
+  * `markdown`:  The markdown document to display.
+  * `font_size`: The size of the body text in logical pixels. Defaults to 14. The headings are scaled from this size.
+  * `flags`:     A combination of the MarkdownViewFlags values.
+
+**Control events:**
+  * `OnMarkdownLinkClick`: When the user clicks a link. The event data is a `EventData::OnMarkdownLink` with the url.
+  * `MousePress(_)`: Generic mouse press events on the markdown view
+  * `OnMouseMove`: Generic mouse mouse event
+
+```rust
+use native_windows_gui as nwg;
+
+fn build_about(view: &mut nwg::MarkdownView, window: &nwg::Window) {
+    nwg::MarkdownView::builder()
+        .size((400, 300))
+        .markdown("# My app\n\nVersion **1.2**. See the [website](https://example.com).")
+        .parent(window)
+        .build(view);
+}
+
+fn link_clicked(data: &nwg::EventData) {
+    let url = data.on_markdown_link();
+    // Open the url with the default browser
+}
+```
+*/
+#[derive(Default)]
+pub struct MarkdownView {
+    pub handle: ControlHandle,
+    state: Rc<RefCell<MarkdownViewState>>,
+    handler0: RefCell<Option<RawEventHandler>>,
+}
+
+impl MarkdownView {
+
+    pub fn builder<'a>() -> MarkdownViewBuilder<'a> {
+        MarkdownViewBuilder {
+            size: (400, 300).into(),
+            position: (0, 0).into(),
+            markdown: "",
+            font_size: DEFAULT_FONT_SIZE,
+            flags: None,
+            parent: None,
+        }
+    }
+
+    /// Returns the markdown document displayed by the view
+    pub fn markdown(&self) -> String {
+        self.state.borrow().markdown.clone()
+    }
+
+    /// Displays a new markdown document and scrolls to the top of the document
+    pub fn set_markdown<'a>(&self, markdown: &'a str) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        state.markdown = markdown.to_string();
+        state.blocks = md::parse(markdown);
+        state.scroll = 0.0;
+        invalidate_layout(handle, &mut state);
+    }
+
+    /// Registers an image under the name used by the document (ex: "logo.png" for `![logo](logo.png)`).
+    /// Replaces the image previously registered under this name.
+    pub fn set_image<'a>(&self, name: &'a str, image: &ImageData) -> Result<(), NwgError> {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        if let Some(renderer) = state.renderer.as_mut() {
+            renderer.set_image(name, image)?;
+        }
+
+        invalidate_layout(handle, &mut state);
+        Ok(())
+    }
+
+    /// Removes a registered image. Returns `false` if there was no image with this name.
+    pub fn remove_image<'a>(&self, name: &'a str) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        let removed = state.renderer.as_mut().map(|r| r.remove_image(name)).unwrap_or(false);
+        invalidate_layout(handle, &mut state);
+        removed
+    }
+
+    /// Removes all the registered images
+    pub fn clear_images(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        if let Some(renderer) = state.renderer.as_mut() {
+            renderer.clear_images();
+        }
+
+        invalidate_layout(handle, &mut state);
+    }
+
+    /// Returns the size of the body text in logical pixels
+    pub fn font_size(&self) -> f32 {
+        self.state.borrow().renderer.as_ref().map(|r| r.font_size()).unwrap_or(DEFAULT_FONT_SIZE)
+    }
+
+    /// Sets the size of the body text in logical pixels. The headings are scaled from this size.
+    pub fn set_font_size(&self, size: f32) -> Result<(), NwgError> {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        if let Some(renderer) = state.renderer.as_mut() {
+            renderer.set_font_size(size.max(1.0))?;
+        }
+
+        invalidate_layout(handle, &mut state);
+        Ok(())
+    }
+
+    /// Returns the url of the link at a position in logical pixels relative to the top left corner of the view
+    pub fn link_at(&self, x: i32, y: i32) -> Option<String> {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        update_layout(handle, &mut state);
+        link_at(&state, (x as f32, y as f32))
+    }
+
+    /// Scrolls the view to the heading with the given anchor (ex: "getting-started" or "#getting-started").
+    /// Returns `false` if there is no such heading.
+    pub fn scroll_to_anchor<'a>(&self, anchor: &'a str) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        scroll_to_anchor(handle, &mut self.state.borrow_mut(), anchor)
+    }
+
+    /// Returns the scroll position in logical pixels from the top of the document
+    pub fn scroll_position(&self) -> u32 {
+        self.state.borrow().scroll as u32
+    }
+
+    /// Sets the scroll position in logical pixels from the top of the document
+    pub fn set_scroll_position(&self, position: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        update_layout(handle, &mut state);
+        set_scroll(handle, &mut state, position as f32);
+    }
+
+    /// Returns true if the control currently has the keyboard focus
+    pub fn focus(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_focus(handle) }
+    }
+
+    /// Sets the keyboard focus on the markdown view
+    pub fn set_focus(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_focus(handle); }
+    }
+
+    /// Returns true if the control user can interact with the control, return false otherwise
+    pub fn enabled(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_enabled(handle) }
+    }
+
+    /// Enable or disable the control
+    pub fn set_enabled(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_enabled(handle, v) }
+    }
+
+    /// Returns true if the control is visible to the user. Will return true even if the
+    /// control is outside of the parent client view (ex: at the position (10000, 10000))
+    pub fn visible(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_visibility(handle) }
+    }
+
+    /// Show or hide the control to the user
+    pub fn set_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_visibility(handle, v) }
+    }
+
+    /// Returns the size of the markdown view in the parent window
+    pub fn size(&self) -> (u32, u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_size(handle) }
+    }
+
+    /// Sets the size of the markdown view in the parent window
+    pub fn set_size(&self, x: u32, y: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_size(handle, x, y, false) }
+    }
+
+    /// Returns the position of the markdown view in the parent window
+    pub fn position(&self) -> (i32, i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_position(handle) }
+    }
+
+    /// Sets the position of the markdown view in the parent window
+    pub fn set_position(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_position(handle, x, y) }
+    }
+
+    /// Winapi class name used during control creation
+    pub fn class_name(&self) -> &'static str {
+        "NWG_MARKDOWN_VIEW"
+    }
+
+    /// Winapi base flags used during window creation
+    pub fn flags(&self) -> u32 {
+        WS_VISIBLE | WS_TABSTOP
+    }
+
+    /// Winapi flags required by the control
+    pub fn forced_flags(&self) -> u32 {
+        WS_CHILD | WS_CLIPSIBLINGS | WS_VSCROLL
+    }
+
+    /// Handles the painting, the scrolling and the links
+    fn hook_events(&self) {
+        use crate::bind_raw_event_handler_inner;
+        use winapi::um::winuser::{WM_PAINT, WM_ERASEBKGND, WM_SIZE, WM_VSCROLL, WM_MOUSEWHEEL, WM_KEYDOWN, WM_GETDLGCODE, WM_SETCURSOR,
+            WM_LBUTTONDOWN, WM_LBUTTONUP, DLGC_WANTARROWS, HTCLIENT, IDC_HAND, GET_WHEEL_DELTA_WPARAM, WHEEL_DELTA, PAINTSTRUCT};
+        use winapi::um::winuser::{SetCursor, LoadCursorW, SetFocus, GetCursorPos, ScreenToClient, BeginPaint, EndPaint};
+        use winapi::shared::windef::POINT;
+        use winapi::shared::minwindef::{LOWORD, HIWORD};
+
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let state = self.state.clone();
+
+        let handler = bind_raw_event_handler_inner(&self.handle, 0, move |hwnd, msg, w, l| {
+            // Mouse coordinates are signed
+            let mouse = || to_dips((LOWORD(l as u32) as i16 as i32, HIWORD(l as u32) as i16 as i32));
+
+            match msg {
+                WM_PAINT => {
+                    unsafe {
+                        let mut ps: PAINTSTRUCT = mem::zeroed();
+                        BeginPaint(hwnd, &mut ps);
+                        paint(hwnd, &mut state.borrow_mut());
+                        EndPaint(hwnd, &ps);
+                    }
+                    return Some(0);
+                },
+                WM_ERASEBKGND => {
+                    // Everything is drawn in WM_PAINT
+                    return Some(1);
+                },
+                WM_SIZE => {
+                    // Showing or hiding the scrollbar resizes the view while the state is borrowed
+                    if let Ok(mut state) = state.try_borrow_mut() {
+                        if let Some(renderer) = state.renderer.as_mut() {
+                            renderer.resize(hwnd);
+                        }
+                        update_layout(hwnd, &mut state);
+                        redraw(hwnd);
+                    }
+                },
+                WM_GETDLGCODE => {
+                    return Some(DLGC_WANTARROWS);
+                },
+                WM_SETCURSOR => {
+                    if LOWORD(l as u32) as isize == HTCLIENT {
+                        let over_link = unsafe {
+                            let mut point = POINT { x: 0, y: 0 };
+                            GetCursorPos(&mut point);
+                            ScreenToClient(hwnd, &mut point);
+                            link_at(&state.borrow(), to_dips((point.x, point.y))).is_some()
+                        };
+
+                        if over_link {
+                            unsafe { SetCursor(LoadCursorW(ptr::null_mut(), IDC_HAND)); }
+                            return Some(1);
+                        }
+                    }
+                },
+                WM_VSCROLL => {
+                    let mut state = state.borrow_mut();
+                    let scroll = scroll_request(hwnd, &state, w);
+                    set_scroll(hwnd, &mut state, scroll);
+                    return Some(0);
+                },
+                WM_MOUSEWHEEL => {
+                    let notches = GET_WHEEL_DELTA_WPARAM(w) as f32 / WHEEL_DELTA as f32;
+                    let mut state = state.borrow_mut();
+                    let scroll = state.scroll - notches * WHEEL_LINES * line_height(&state);
+                    set_scroll(hwnd, &mut state, scroll);
+                    return Some(0);
+                },
+                WM_KEYDOWN => {
+                    let mut state = state.borrow_mut();
+                    match key_scroll(hwnd, &state, w as i32) {
+                        Some(scroll) => set_scroll(hwnd, &mut state, scroll),
+                        None => { return None; }
+                    }
+                    return Some(0);
+                },
+                WM_LBUTTONDOWN => {
+                    unsafe { SetFocus(hwnd); }
+                    let mut state = state.borrow_mut();
+                    state.pressed_link = link_at(&state, mouse());
+                },
+                WM_LBUTTONUP => {
+                    // A link is clicked if the mouse is pressed and released over the same link
+                    let clicked = {
+                        let mut state = state.borrow_mut();
+                        let pressed = state.pressed_link.take();
+                        match pressed.is_some() && pressed == link_at(&state, mouse()) {
+                            true => pressed,
+                            false => None
+                        }
+                    };
+
+                    if let Some(url) = clicked {
+                        if url.starts_with('#') {
+                            scroll_to_anchor(hwnd, &mut state.borrow_mut(), &url);
+                        } else {
+                            // The state must not be borrowed because the user handler may access the view
+                            notify_link(hwnd, &url);
+                        }
+                    }
+                },
+                _ => {}
+            }
+
+            None
+        });
+
+        *self.handler0.borrow_mut() = Some(handler.unwrap());
+    }
+
+}
+
+impl Drop for MarkdownView {
+    fn drop(&mut self) {
+        let handler = self.handler0.borrow();
+        if let Some(h) = handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
+        self.handle.destroy();
+    }
+}
+
+impl PartialEq for MarkdownView {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for MarkdownView {}
+
+pub struct MarkdownViewBuilder<'a> {
+    size: Extent,
+    position: Extent,
+    markdown: &'a str,
+    font_size: f32,
+    flags: Option<MarkdownViewFlags>,
+    parent: Option<ControlHandle>
+}
+
+impl<'a> MarkdownViewBuilder<'a> {
+
+    pub fn flags(mut self, flags: MarkdownViewFlags) -> MarkdownViewBuilder<'a> {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> MarkdownViewBuilder<'a> {
+        self.size = size.into();
+        self
+    }
+
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> MarkdownViewBuilder<'a> {
+        self.position = pos.into();
+        self
+    }
+
+    pub fn markdown(mut self, markdown: &'a str) -> MarkdownViewBuilder<'a> {
+        self.markdown = markdown;
+        self
+    }
+
+    pub fn font_size(mut self, size: f32) -> MarkdownViewBuilder<'a> {
+        self.font_size = size;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> MarkdownViewBuilder<'a> {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut MarkdownView) -> Result<(), NwgError> {
+        let flags = self.flags.map(|f| f.bits()).unwrap_or(out.flags());
+
+        let parent = match self.parent {
+            Some(p) => Ok(p),
+            None => Err(NwgError::no_parent("MarkdownView"))
+        }?;
+
+        let renderer = MarkdownRenderer::new(self.font_size.max(1.0))?;
+
+        *out = Default::default();
+
+        out.handle = ControlBase::build_hwnd()
+            .class_name(out.class_name())
+            .forced_flags(out.forced_flags())
+            .flags(flags)
+            .size(self.size)
+            .position(self.position)
+            .parent(Some(parent))
+            .build()?;
+
+        out.state.borrow_mut().renderer = Some(renderer);
+
+        out.hook_events();
+        out.set_markdown(self.markdown);
+
+        Ok(())
+    }
+
+}
+
+fn redraw(handle: HWND) {
+    use winapi::um::winuser::InvalidateRect;
+    unsafe { InvalidateRect(handle, ptr::null(), 0); }
+}
+
+/// Converts a position in physical pixels into DIPs
+fn to_dips(point: (i32, i32)) -> (f32, f32) {
+    let scale = scale_factor() as f32;
+    (point.0 as f32 / scale, point.1 as f32 / scale)
+}
+
+/// Returns the size of the client area in DIPs
+fn client_size(handle: HWND) -> (f32, f32) {
+    use winapi::um::winuser::GetClientRect;
+    use winapi::shared::windef::RECT;
+
+    unsafe {
+        let mut rect: RECT = mem::zeroed();
+        GetClientRect(handle, &mut rect);
+        to_dips((rect.right - rect.left, rect.bottom - rect.top))
+    }
+}
+
+/// Sends a `NMMARKDOWNLINK` notification to the parent of the view
+fn notify_link(handle: HWND, url: &str) {
+    use winapi::um::winuser::{NMHDR, WM_NOTIFY};
+
+    let notification = NMMARKDOWNLINK {
+        hdr: NMHDR { hwndFrom: handle, idFrom: 0, code: MARKDOWN_LINK_CLICK },
+        url: url.as_ptr(),
+        url_len: url.len(),
+    };
+
+    let parent = wh::get_window_parent(handle);
+    wh::send_message(parent, WM_NOTIFY, 0, &notification as *const NMMARKDOWNLINK as _);
+}
+
+fn line_height(state: &MarkdownViewState) -> f32 {
+    state.renderer.as_ref().map(|r| r.font_size()).unwrap_or(DEFAULT_FONT_SIZE) * 1.4
+}
+
+/// Recomputes the layout on the next update
+fn invalidate_layout(handle: HWND, state: &mut MarkdownViewState) {
+    state.layout_width = -1.0;
+    update_layout(handle, state);
+    redraw(handle);
+}
+
+/// Lays out the document for the width of the view if the width changed, and updates the scrollbar
+fn update_layout(handle: HWND, state: &mut MarkdownViewState) {
+    // Showing or hiding the scrollbar changes the width of the view
+    for _ in 0..2 {
+        let (width, _) = client_size(handle);
+        if width == state.layout_width {
+            break;
+        }
+
+        layout(state, width);
+        update_scroll(handle, state);
+    }
+}
+
+fn update_scroll(handle: HWND, state: &mut MarkdownViewState) {
+    use winapi::um::winuser::{SetScrollInfo, SCROLLINFO, SIF_RANGE, SIF_PAGE, SIF_POS, SB_VERT};
+
+    let (_, height) = client_size(handle);
+    let max_scroll = (state.content_height - height).max(0.0);
+    state.scroll = state.scroll.min(max_scroll).max(0.0).round();
+
+    let info = SCROLLINFO {
+        cbSize: mem::size_of::<SCROLLINFO>() as u32,
+        fMask: SIF_RANGE | SIF_PAGE | SIF_POS,
+        nMin: 0,
+        nMax: state.content_height.ceil() as i32 - 1,
+        nPage: height.max(0.0) as u32,
+        nPos: state.scroll as i32,
+        nTrackPos: 0,
+    };
+
+    unsafe { SetScrollInfo(handle, SB_VERT, &info, 1); }
+}
+
+fn set_scroll(handle: HWND, state: &mut MarkdownViewState, scroll: f32) {
+    let previous = state.scroll;
+    state.scroll = scroll;
+    update_scroll(handle, state);
+
+    if state.scroll != previous {
+        redraw(handle);
+    }
+}
+
+/// Computes the new scroll position from a WM_VSCROLL message
+fn scroll_request(handle: HWND, state: &MarkdownViewState, w: usize) -> f32 {
+    use winapi::um::winuser::{GetScrollInfo, SCROLLINFO, SIF_TRACKPOS, SB_VERT, SB_LINEUP, SB_LINEDOWN, SB_PAGEUP, SB_PAGEDOWN,
+        SB_THUMBTRACK, SB_THUMBPOSITION, SB_TOP, SB_BOTTOM};
+    use winapi::shared::minwindef::LOWORD;
+
+    let (_, page) = client_size(handle);
+    let line = line_height(state);
+
+    match LOWORD(w as u32) as isize {
+        SB_LINEUP => state.scroll - line,
+        SB_LINEDOWN => state.scroll + line,
+        SB_PAGEUP => state.scroll - page,
+        SB_PAGEDOWN => state.scroll + page,
+        SB_TOP => 0.0,
+        SB_BOTTOM => state.content_height,
+        SB_THUMBTRACK | SB_THUMBPOSITION => unsafe {
+            // The position in the message is limited to 16 bits
+            let mut info: SCROLLINFO = mem::zeroed();
+            info.cbSize = mem::size_of::<SCROLLINFO>() as u32;
+            info.fMask = SIF_TRACKPOS;
+            GetScrollInfo(handle, SB_VERT, &mut info);
+            info.nTrackPos as f32
+        },
+        _ => state.scroll
+    }
+}
+
+fn key_scroll(handle: HWND, state: &MarkdownViewState, key: i32) -> Option<f32> {
+    use winapi::um::winuser::{VK_UP, VK_DOWN, VK_PRIOR, VK_NEXT, VK_HOME, VK_END, VK_SPACE};
+
+    let (_, page) = client_size(handle);
+    let line = line_height(state);
+
+    match key {
+        VK_UP => Some(state.scroll - line),
+        VK_DOWN => Some(state.scroll + line),
+        VK_PRIOR => Some(state.scroll - page),
+        VK_NEXT | VK_SPACE => Some(state.scroll + page),
+        VK_HOME => Some(0.0),
+        VK_END => Some(state.content_height),
+        _ => None
+    }
+}
+
+fn scroll_to_anchor(handle: HWND, state: &mut MarkdownViewState, anchor: &str) -> bool {
+    let anchor = md::anchor(anchor.trim_start_matches('#'));
+    update_layout(handle, state);
+
+    let top = state.laid.iter()
+        .find(|b| b.anchor.as_ref() == Some(&anchor))
+        .map(|b| b.rect[1]);
+
+    match top {
+        Some(top) => {
+            set_scroll(handle, state, top - PADDING);
+            true
+        },
+        None => false
+    }
+}
+
+/// Returns the link at a position in DIPs relative to the view
+fn link_at(state: &MarkdownViewState, point: (f32, f32)) -> Option<String> {
+    let (x, y) = (point.0, point.1 + state.scroll);
+
+    let block = state.laid.iter().find(|b| x >= b.rect[0] && x < b.rect[2] && y >= b.rect[1] && y < b.rect[3])?;
+    if block.block_link.is_some() {
+        return block.block_link.clone();
+    }
+
+    let (layout, origin) = block.text.as_ref()?;
+    let position = layout.hit_test(x - origin.0, y - origin.1)?;
+
+    block.links.iter()
+        .find(|(start, length, _)| position >= *start && position < start + length)
+        .map(|(_, _, url)| url.clone())
+}
+
+/// Creates the text layout of a block and applies the styles of its spans
+fn text_layout(renderer: &MarkdownRenderer, block: &Block, width: f32) -> Option<(TextLayout, Vec<(u32, u32, String)>, Vec<(u32, u32)>)> {
+    use winapi::um::dwrite::DWRITE_FONT_WEIGHT_BOLD;
+
+    let text = block.plain_text();
+    let mut layout = renderer.create_layout(&text, block.kind == BlockKind::Code, width)?;
+    let mut links = Vec::new();
+    let mut code_ranges = Vec::new();
+
+    let mut start = 0u32;
+    for span in block.spans.iter() {
+        let length = span.text.encode_utf16().count() as u32;
+        let style = &span.style;
+
+        if style.bold { layout.set_weight(DWRITE_FONT_WEIGHT_BOLD, start, length); }
+        if style.italic { layout.set_italic(start, length); }
+        if style.strike { layout.set_strikethrough(start, length); }
+
+        if style.code && block.kind != BlockKind::Code {
+            layout.set_code(start, length);
+            code_ranges.push((start, length));
+        }
+
+        if let Some(url) = style.link.as_ref() {
+            layout.set_link(start, length);
+            links.push((start, length, url.clone()));
+        }
+
+        start += length;
+    }
+
+    Some((layout, links, code_ranges))
+}
+
+/// Places the blocks of the document for a view of `width` DIPs
+fn layout(state: &mut MarkdownViewState, width: f32) {
+    use winapi::um::dwrite::DWRITE_FONT_WEIGHT_SEMI_BOLD;
+
+    state.layout_width = width;
+    state.laid.clear();
+
+    let renderer = match state.renderer.as_ref() {
+        Some(r) => r,
+        None => { return; }
+    };
+
+    let font_size = renderer.font_size();
+    let quote_indent = font_size * 1.2;
+    let list_indent = font_size * 1.6;
+    let right = (width - PADDING).max(PADDING + 1.0);
+    let mut y = PADDING;
+
+    for (index, block) in state.blocks.iter().enumerate() {
+        let next = state.blocks.get(index + 1);
+        let left = PADDING + block.quote as f32 * quote_indent;
+        let available = (right - left).max(1.0);
+
+        // Space between this block and the next one
+        let mut spacing = match (&block.kind, next.map(|n| &n.kind)) {
+            (BlockKind::ListItem { .. }, Some(BlockKind::ListItem { .. })) => font_size * 0.3,
+            _ => font_size * 0.75
+        };
+
+        let mut laid = LaidBlock {
+            kind: LaidKind::Text,
+            rect: [left, y, right, y],
+            quote: block.quote,
+            quote_bottom: y,
+            text: None,
+            marker: None,
+            links: Vec::new(),
+            block_link: None,
+            code_ranges: Vec::new(),
+            anchor: None,
+        };
+
+        match &block.kind {
+            BlockKind::Heading(level) => {
+                if index > 0 {
+                    y += font_size * 0.5;
+                }
+
+                let level = (*level).max(1).min(6);
+                if let Some((layout, links, code_ranges)) = text_layout(renderer, block, available) {
+                    let length = block.plain_text().encode_utf16().count() as u32;
+                    layout.set_font_size(font_size * HEADING_SCALE[level as usize - 1], 0, length);
+                    layout.set_weight(DWRITE_FONT_WEIGHT_SEMI_BOLD, 0, length);
+
+                    let (_, height) = layout.size();
+
+                    // The largest headings are underlined by a rule
+                    let rule = match level <= 2 { true => font_size * 0.4, false => 0.0 };
+                    laid.rect = [left, y, right, y + height + rule];
+                    laid.text = Some((layout, (left, y)));
+                    laid.links = links;
+                    laid.code_ranges = code_ranges;
+                }
+
+                laid.kind = LaidKind::Heading(level);
+                laid.anchor = Some(md::anchor(&block.plain_text()));
+                spacing = font_size * 0.6;
+            },
+            BlockKind::Paragraph => {
+                if let Some((layout, links, code_ranges)) = text_layout(renderer, block, available) {
+                    let (_, height) = layout.size();
+                    laid.rect = [left, y, right, y + height];
+                    laid.text = Some((layout, (left, y)));
+                    laid.links = links;
+                    laid.code_ranges = code_ranges;
+                }
+            },
+            BlockKind::ListItem { marker, depth } => {
+                let text_left = left + (*depth + 1) as f32 * list_indent;
+                let available = (right - text_left).max(1.0);
+
+                if let Some((layout, links, code_ranges)) = text_layout(renderer, block, available) {
+                    let (_, height) = layout.size();
+                    laid.rect = [left, y, right, y + height];
+                    laid.text = Some((layout, (text_left, y)));
+                    laid.links = links;
+                    laid.code_ranges = code_ranges;
+                }
+
+                if !marker.is_empty() {
+                    if let Some(marker_layout) = renderer.create_layout(marker, false, list_indent) {
+                        let (marker_width, _) = marker_layout.size();
+                        let marker_x = text_left - marker_width - font_size * 0.4;
+                        laid.marker = Some((marker_layout, (marker_x, y)));
+                    }
+                }
+            },
+            BlockKind::Code => {
+                let inner = font_size * 0.6;
+                if let Some((layout, _, _)) = text_layout(renderer, block, f32::MAX / 2.0) {
+                    layout.set_no_wrap();
+                    let (_, height) = layout.size();
+                    laid.rect = [left, y, right, y + height + inner * 2.0];
+                    laid.text = Some((layout, (left + inner, y + inner)));
+                }
+
+                laid.kind = LaidKind::Code;
+            },
+            BlockKind::Rule => {
+                laid.rect = [left, y, right, y + font_size * 0.5];
+                laid.kind = LaidKind::Rule;
+            },
+            BlockKind::Image { src, alt, link } => {
+                laid.block_link = link.clone();
+
+                match renderer.image_size(src) {
+                    Some((image_width, image_height)) => {
+                        let (mut w, mut h) = (image_width as f32, image_height as f32);
+                        if w > available {
+                            h *= available / w;
+                            w = available;
+                        }
+
+                        laid.rect = [left, y, left + w, y + h];
+                        laid.kind = LaidKind::Image(src.clone());
+                    },
+                    None => {
+                        let inner = font_size * 0.4;
+                        let text = match alt.is_empty() { true => src, false => alt };
+                        if let Some(layout) = renderer.create_layout(text, false, (available - inner * 2.0).max(1.0)) {
+                            layout.set_italic(0, text.encode_utf16().count() as u32);
+                            let (text_width, text_height) = layout.size();
+                            laid.rect = [left, y, left + text_width + inner * 2.0, y + text_height + inner * 2.0];
+                            laid.text = Some((layout, (left + inner, y + inner)));
+                        }
+
+                        laid.kind = LaidKind::MissingImage;
+                    }
+                }
+            },
+        }
+
+        y = laid.rect[3];
+
+        // The quote bars are continuous between two blocks of the same quote
+        let next_quote = next.map(|n| n.quote).unwrap_or(0);
+        laid.quote_bottom = match next_quote >= block.quote && block.quote > 0 {
+            true => y + spacing,
+            false => y
+        };
+
+        if next.is_some() {
+            y += spacing;
+        }
+
+        state.laid.push(laid);
+    }
+
+    state.content_height = y + PADDING;
+}
+
+/// Paints the visible blocks of the document
+fn paint(handle: HWND, state: &mut MarkdownViewState) {
+    update_layout(handle, state);
+
+    let mut renderer = match state.renderer.take() {
+        Some(r) => r,
+        None => { return; }
+    };
+
+    if renderer.begin_draw(handle) {
+        draw_document(&mut renderer, state, client_size(handle).1);
+        renderer.end_draw();
+    }
+
+    state.renderer = Some(renderer);
+}
+
+fn draw_document(r: &mut MarkdownRenderer, state: &MarkdownViewState, height: f32) {
+    let font_size = r.font_size();
+    let quote_indent = font_size * 1.2;
+    let scroll = state.scroll;
+    let offset = |rect: Rect| [rect[0], rect[1] - scroll, rect[2], rect[3] - scroll];
+
+    r.clear(BACKGROUND_COLOR);
+
+    for block in state.laid.iter() {
+        if block.quote_bottom.max(block.rect[3]) < scroll || block.rect[1] > scroll + height {
+            continue;
+        }
+
+        let rect = offset(block.rect);
+
+        for level in 0..block.quote {
+            let x = PADDING + level as f32 * quote_indent;
+            r.fill_rect([x, rect[1], x + 3.0, block.quote_bottom - scroll], QUOTE_BAR_COLOR);
+        }
+
+        match &block.kind {
+            LaidKind::Code => r.fill_rounded_rect(rect, 4.0, CODE_BACKGROUND_COLOR),
+            LaidKind::Rule => {
+                let middle = ((rect[1] + rect[3]) / 2.0).floor();
+                r.fill_rect([rect[0], middle, rect[2], middle + 1.0], RULE_COLOR);
+            },
+            LaidKind::Heading(level) if *level <= 2 => {
+                r.fill_rect([rect[0], rect[3] - 1.0, rect[2], rect[3]], RULE_COLOR);
+            },
+            LaidKind::Image(name) => r.draw_image(name, rect),
+            LaidKind::MissingImage => r.draw_rect([rect[0] + 0.5, rect[1] + 0.5, rect[2] - 0.5, rect[3] - 0.5], RULE_COLOR, 1.0),
+            _ => {}
+        }
+
+        let color = match (&block.kind, block.quote > 0) {
+            (LaidKind::MissingImage, _) | (_, true) => QUOTE_TEXT_COLOR,
+            _ => TEXT_COLOR
+        };
+
+        if let Some((marker, (x, y))) = block.marker.as_ref() {
+            r.draw_layout(marker, (*x, y - scroll), color, LINK_COLOR);
+        }
+
+        if let Some((layout, (x, y))) = block.text.as_ref() {
+            let origin = (*x, y - scroll);
+
+            for &(start, length) in block.code_ranges.iter() {
+                for range in layout.range_rects(start, length) {
+                    let background = [origin.0 + range[0] - 2.0, origin.1 + range[1], origin.0 + range[2] + 2.0, origin.1 + range[3]];
+                    r.fill_rounded_rect(background, 3.0, CODE_BACKGROUND_COLOR);
+                }
+            }
+
+            // The code blocks do not wrap: the long lines are cut at the edge of the block
+            match block.kind == LaidKind::Code {
+                true => {
+                    r.push_clip(rect);
+                    r.draw_layout(layout, origin, color, LINK_COLOR);
+                    r.pop_clip();
+                },
+                false => r.draw_layout(layout, origin, color, LINK_COLOR)
+            }
+        }
+    }
+}
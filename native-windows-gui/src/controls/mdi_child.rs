@@ -0,0 +1,368 @@
+use winapi::um::winuser::{WS_OVERLAPPEDWINDOW, WS_CLIPCHILDREN, WS_VISIBLE, WS_DISABLED, WS_MAXIMIZE, WS_MINIMIZE, WS_CAPTION,
+WS_MINIMIZEBOX, WS_MAXIMIZEBOX, WS_SYSMENU, WS_THICKFRAME};
+
+use crate::win32::window_helper as wh;
+use crate::win32::base_helper::check_hwnd;
+use crate::win32::mdi;
+use crate::win32::high_dpi::Extent;
+use crate::{NwgError, Icon};
+use super::ControlHandle;
+
+const NOT_BOUND: &'static str = "MdiChild is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: MdiChild handle is not HWND!";
+
+
+bitflags! {
+
+    /**
+        The MDI child flags.
+
+        * MAIN_WINDOW: A title, a system menu, a resizable frame, and the close, minimize, maximize buttons
+        * WINDOW:  A window with a title, a system menu, a close button, and a non resizable border.
+        * MINIMIZE_BOX: Includes a minimize button
+        * MAXIMIZE_BOX: Includes a maximize button
+        * SYS_MENU: Includes a system menu when the user right click the window header
+        * MAXIMIZED: Create the window as maximized
+        * MINIMIZED: Create the window as minimized
+        * RESIZABLE: Add a resizable border
+        * VISIBLE: Show the window right away
+    */
+    pub struct MdiChildFlags: u32 {
+        const MAIN_WINDOW = WS_CAPTION | WS_SYSMENU | WS_MINIMIZEBOX | WS_THICKFRAME | WS_MAXIMIZEBOX;
+        const WINDOW = WS_CAPTION | WS_SYSMENU;
+        const MINIMIZE_BOX = WS_MINIMIZEBOX;
+        const MAXIMIZE_BOX = WS_MAXIMIZEBOX;
+        const SYS_MENU = WS_SYSMENU;
+        const VISIBLE = WS_VISIBLE;
+        const DISABLED = WS_DISABLED;
+        const MAXIMIZED = WS_MAXIMIZE;
+        const MINIMIZED = WS_MINIMIZE;
+        const RESIZABLE = WS_THICKFRAME | WS_MAXIMIZEBOX;
+    }
+}
+
+
+/**
+    A document window inside a MDI frame. The frame is a `Window` built with `mdi_frame(true)`.
+
+    MDI children are moved, resized, minimized and maximized inside the frame. Only one child is active at a time.
+    A MDI child can be used as the parent of other controls.
+
+    Like `Window`, closing a MDI child (with the X button or CTRL+F4) hides it. The child is destroyed when the `MdiChild` is dropped.
+    To destroy the child when it is closed, remove it from the application in the `OnWindowClose` event.
+
+    Children created after the event handler of the frame was bound do not send their events to this handler.
+    Use `bind_event_handler` with the handle of the child to receive its events.
+
+    Requires the `mdi` feature.
+
+    **Builder parameters:**
+      * `parent`:      **Required.** The MDI frame window
+      * `flags`:       The window flags. See `MdiChildFlags`
+      * `title`:       The text in the window title bar
+      * `size`:        The default size of the window
+      * `position`:    The position of the window in the frame. If not set, the system cascades the new children.
+      * `icon`:        The window icon
+
+    **Control events:**
+      * `OnInit`: The window was created
+      * `OnMdiChildActivate`: The child became the active child of the frame
+      * `OnMdiChildDeactivate`: The child is no longer the active child of the frame
+      * `OnWindowClose`: When the user closes the child
+      * `OnResize`: When the window is resized
+      * `OnWindowMaximize`: When the window is maximized
+      * `OnWindowMinimize`: When the window is minimized
+      * `OnMove`: When the window is moved by the user
+      * `MousePress(_)`: Generic mouse press events on the window
+      * `OnMouseMove`: Generic mouse mouse event
+      * `OnPaint`: Generic on paint event
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn new_document(frame: &nwg::Window, count: usize) -> Result<nwg::MdiChild, nwg::NwgError> {
+        let mut doc = nwg::MdiChild::default();
+        nwg::MdiChild::builder()
+            .title(&format!("Document {}", count))
+            .size((400, 300))
+            .parent(frame)
+            .build(&mut doc)?;
+
+        Ok(doc)
+    }
+    ```
+*/
+#[derive(Default, PartialEq, Eq)]
+pub struct MdiChild {
+    pub handle: ControlHandle
+}
+
+impl MdiChild {
+
+    pub fn builder<'a>() -> MdiChildBuilder<'a> {
+        MdiChildBuilder {
+            title: "New Document",
+            size: (400, 300).into(),
+            position: None,
+            flags: None,
+            icon: None,
+            parent: None
+        }
+    }
+
+    /// Make the child the active child of the frame
+    pub fn activate(&self) {
+        use winapi::um::winuser::WM_MDIACTIVATE;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::send_message(wh::get_window_parent(handle), WM_MDIACTIVATE, handle as _, 0);
+    }
+
+    /// Return true if the child is the active child of the frame
+    pub fn is_active(&self) -> bool {
+        use winapi::um::winuser::WM_MDIGETACTIVE;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::send_message(wh::get_window_parent(handle), WM_MDIGETACTIVE, 0, 0) as usize == handle as usize
+    }
+
+    /// Maximize the child. The other children are maximized when they are activated.
+    pub fn maximize(&self) {
+        use winapi::um::winuser::WM_MDIMAXIMIZE;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::send_message(wh::get_window_parent(handle), WM_MDIMAXIMIZE, handle as _, 0);
+    }
+
+    /// Minimize the child
+    pub fn minimize(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::minimize_window(handle);
+    }
+
+    /// Restore a minimized/maximized child
+    pub fn restore(&self) {
+        use winapi::um::winuser::WM_MDIRESTORE;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::send_message(wh::get_window_parent(handle), WM_MDIRESTORE, handle as _, 0);
+    }
+
+    /// Close the child as if the user clicked the X button.
+    pub fn close(&self) {
+        use winapi::um::winuser::WM_CLOSE;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::post_message(handle, WM_CLOSE, 0, 0);
+    }
+
+    /// Return the icon of the child
+    pub fn icon(&self) -> Option<Icon> {
+        use winapi::um::winuser::WM_GETICON;
+        use winapi::um::winnt::HANDLE;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let handle = wh::send_message(handle, WM_GETICON, 0, 0);
+        if handle == 0 {
+            None
+        } else {
+            Some(Icon { handle: handle as HANDLE, owned: false })
+        }
+    }
+
+    /// Set the icon of the child
+    /// - icon: The new icon. If None, the icon is removed
+    pub fn set_icon(&self, icon: Option<&Icon>) {
+        use winapi::um::winuser::WM_SETICON;
+        use std::{mem, ptr};
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let image_handle = icon.map(|i| i.handle).unwrap_or(ptr::null_mut());
+        unsafe {
+            wh::send_message(handle, WM_SETICON, 0, mem::transmute(image_handle));
+        }
+    }
+
+    /// Return true if the control currently has the keyboard focus
+    pub fn focus(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_focus(handle) }
+    }
+
+    /// Set the keyboard focus on the child
+    pub fn set_focus(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_focus(handle); }
+    }
+
+    /// Return true if the control user can interact with the control, return false otherwise
+    pub fn enabled(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_enabled(handle) }
+    }
+
+    /// Enable or disable the control
+    pub fn set_enabled(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_enabled(handle, v) }
+    }
+
+    /// Return true if the control is visible to the user.
+    pub fn visible(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_visibility(handle) }
+    }
+
+    /// Show or hide the control to the user
+    pub fn set_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_visibility(handle, v) }
+    }
+
+    /// Return the size of the child in the frame
+    pub fn size(&self) -> (u32, u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_size(handle) }
+    }
+
+    /// Set the size of the child in the frame
+    pub fn set_size(&self, x: u32, y: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_size(handle, x, y, true) }
+    }
+
+    /// Return the position of the child in the frame
+    pub fn position(&self) -> (i32, i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_position(handle) }
+    }
+
+    /// Set the position of the child in the frame
+    pub fn set_position(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_position(handle, x, y) }
+    }
+
+    /// Return the child title
+    pub fn text(&self) -> String {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_text(handle) }
+    }
+
+    /// Set the child title
+    pub fn set_text<'a>(&self, v: &'a str) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_text(handle, v) }
+    }
+
+    /// Winapi class name used during control creation
+    pub fn class_name(&self) -> &'static str {
+        "NWG_MDI_CHILD"
+    }
+
+    // Winapi base flags used during window creation
+    pub fn flags(&self) -> u32 {
+        WS_OVERLAPPEDWINDOW | WS_VISIBLE
+    }
+
+    /// Winapi flags required by the control
+    pub fn forced_flags(&self) -> u32 {
+        WS_CLIPCHILDREN
+    }
+}
+
+impl Drop for MdiChild {
+    fn drop(&mut self) {
+        use winapi::um::winuser::WM_MDIDESTROY;
+        use crate::win32::resource_tracker;
+
+        // MDI children must be destroyed by their MDI client. If the frame was already destroyed, the child was
+        // moved out of the client and it can be destroyed normally.
+        if let Some(handle) = self.handle.hwnd() {
+            let client = wh::get_window_parent(handle);
+            if !client.is_null() && mdi::is_mdi_client(client) {
+                resource_tracker::untrack(handle);
+                wh::send_message(client, WM_MDIDESTROY, handle as _, 0);
+                self.handle = ControlHandle::NoHandle;
+                return;
+            }
+        }
+
+        self.handle.destroy();
+    }
+}
+
+pub struct MdiChildBuilder<'a> {
+    title: &'a str,
+    size: Extent,
+    position: Option<Extent>,
+    flags: Option<MdiChildFlags>,
+    icon: Option<&'a Icon>,
+    parent: Option<ControlHandle>
+}
+
+impl<'a> MdiChildBuilder<'a> {
+
+    pub fn flags(mut self, flags: MdiChildFlags) -> MdiChildBuilder<'a> {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn title(mut self, text: &'a str) -> MdiChildBuilder<'a> {
+        self.title = text;
+        self
+    }
+
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> MdiChildBuilder<'a> {
+        self.size = size.into();
+        self
+    }
+
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> MdiChildBuilder<'a> {
+        self.position = Some(pos.into());
+        self
+    }
+
+    pub fn icon(mut self, ico: Option<&'a Icon>) -> MdiChildBuilder<'a> {
+        self.icon = ico;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> MdiChildBuilder<'a> {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut MdiChild) -> Result<(), NwgError> {
+        let flags = self.flags.map(|f| f.bits()).unwrap_or(out.flags()) | out.forced_flags();
+
+        let parent = match self.parent.and_then(|p| p.hwnd()) {
+            Some(p) => Ok(p),
+            None => Err(NwgError::no_parent("MdiChild"))
+        }?;
+
+        // The parent can be the frame or its MDI client
+        let client = match mdi::mdi_client(parent) {
+            Some(client) => client,
+            None if mdi::is_mdi_client(parent) => parent,
+            None => { return Err(NwgError::control_create("The parent of a MdiChild must be a MDI frame")); }
+        };
+
+        *out = Default::default();
+
+        let handle = unsafe {
+            let size = self.size.to_units(client);
+            let position = self.position.map(|p| p.to_units(client));
+            mdi::build_mdi_child(client, self.title, size, position, flags)?
+        };
+        out.handle = ControlHandle::Hwnd(handle);
+
+        if self.icon.is_some() {
+            out.set_icon(self.icon);
+        }
+
+        Ok(())
+    }
+
+}
@@ -1,5 +1,6 @@
 use crate::win32::menu as mh;
-use crate::NwgError;
+use crate::win32::window_helper as wh;
+use crate::{NwgError, Bitmap, Icon, Font};
 use super::{ControlBase, ControlHandle};
 use std::ptr;
 
@@ -230,6 +231,13 @@ impl<'a> MenuBuilder<'a> {
       - text: The text of the menu, including access key and shortcut label
       - disabled: If the item can be selected by the user
       - check: If the item should have a check mark next to it.
+      - bitmap: A bitmap displayed next to the text of the item
+      - check_bitmap: A bitmap replacing the check mark when the item is checked
+      - uncheck_bitmap: A bitmap displayed in the check mark column when the item is not checked
+      - icon: An icon displayed next to the text of the item. Makes the item owner drawn.
+      - font: The font of the text of the item. Makes the item owner drawn.
+      - text_color: The color of the text of the item. Makes the item owner drawn.
+      - background_color: The background color of the item. Makes the item owner drawn.
       - parent: A top level window or a menu. With a top level window, the menu item is added to the menu bar.
 
    **Control events:**
@@ -268,6 +276,28 @@ impl<'a> MenuBuilder<'a> {
 
     **note:** This will only add a text label to the menu item, the keyboard handling must be done through other means.
 
+    **Owner drawn items**
+
+    Items with an icon, a font or colors are drawn by nwg when the window owning the menu receives `WM_MEASUREITEM`
+    and `WM_DRAWITEM`. The owner window must have an event handler bound (the derive macro always binds one).
+    Access keys and shortcut labels work the same way as for the other items.
+
+    The resources (bitmaps, icon, font) are not copied and must live as long as the menu item.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn save_item(item: &mut nwg::MenuItem, menu: &nwg::Menu, icon: &nwg::Icon, bold: &nwg::Font) -> Result<(), nwg::NwgError> {
+        nwg::MenuItem::builder()
+            .text("&Save\tCtrl+S")
+            .icon(Some(icon))
+            .font(Some(bold))
+            .text_color(Some([0, 80, 160]))
+            .parent(menu)
+            .build(item)
+    }
+    ```
+
     ```rust
     use native_windows_gui as nwg;
 
@@ -292,6 +322,13 @@ impl MenuItem {
             text: "Menu Item",
             disabled: false,
             check: false,
+            bitmap: None,
+            check_bitmap: None,
+            uncheck_bitmap: None,
+            icon: None,
+            font: None,
+            text_color: None,
+            background_color: None,
             parent: None
         }
     }
@@ -332,6 +369,10 @@ impl MenuItem {
 
 impl Drop for MenuItem {
     fn drop(&mut self) {
+        if let Some((parent_handle, id)) = self.handle.hmenu_item() {
+            unsafe { mh::free_menu_item_style(parent_handle, id); }
+        }
+
         self.handle.destroy();
     }
 }
@@ -340,6 +381,13 @@ pub struct MenuItemBuilder<'a> {
     text: &'a str,
     disabled: bool,
     check: bool,
+    bitmap: Option<&'a Bitmap>,
+    check_bitmap: Option<&'a Bitmap>,
+    uncheck_bitmap: Option<&'a Bitmap>,
+    icon: Option<&'a Icon>,
+    font: Option<&'a Font>,
+    text_color: Option<[u8; 3]>,
+    background_color: Option<[u8; 3]>,
     parent: Option<ControlHandle>
 }
 
@@ -360,6 +408,41 @@ impl<'a> MenuItemBuilder<'a> {
         self
     }
 
+    pub fn bitmap(mut self, bitmap: Option<&'a Bitmap>) -> MenuItemBuilder<'a> {
+        self.bitmap = bitmap;
+        self
+    }
+
+    pub fn check_bitmap(mut self, bitmap: Option<&'a Bitmap>) -> MenuItemBuilder<'a> {
+        self.check_bitmap = bitmap;
+        self
+    }
+
+    pub fn uncheck_bitmap(mut self, bitmap: Option<&'a Bitmap>) -> MenuItemBuilder<'a> {
+        self.uncheck_bitmap = bitmap;
+        self
+    }
+
+    pub fn icon(mut self, icon: Option<&'a Icon>) -> MenuItemBuilder<'a> {
+        self.icon = icon;
+        self
+    }
+
+    pub fn font(mut self, font: Option<&'a Font>) -> MenuItemBuilder<'a> {
+        self.font = font;
+        self
+    }
+
+    pub fn text_color(mut self, color: Option<[u8; 3]>) -> MenuItemBuilder<'a> {
+        self.text_color = color;
+        self
+    }
+
+    pub fn background_color(mut self, color: Option<[u8; 3]>) -> MenuItemBuilder<'a> {
+        self.background_color = color;
+        self
+    }
+
     pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> MenuItemBuilder<'a> {
         self.parent = Some(p.into());
         self
@@ -370,12 +453,38 @@ impl<'a> MenuItemBuilder<'a> {
             return Err(NwgError::no_parent_menu());
         }
 
+        let parent = self.parent.unwrap();
         item.handle = ControlBase::build_hmenu()
             .text(self.text)
             .item(true)
-            .parent(self.parent.unwrap())
+            .parent(parent)
             .build()?;
 
+        let (parent_handle, id) = item.handle.hmenu_item().expect(BAD_HANDLE);
+
+        if let Some(bitmap) = self.bitmap {
+            unsafe { mh::set_menu_item_bitmap(parent_handle, id, bitmap.handle as _); }
+        }
+
+        let check_bitmap = self.check_bitmap.map(|b| b.handle as _).unwrap_or(ptr::null_mut());
+        let uncheck_bitmap = self.uncheck_bitmap.map(|b| b.handle as _).unwrap_or(ptr::null_mut());
+        if !check_bitmap.is_null() || !uncheck_bitmap.is_null() {
+            unsafe { mh::set_menu_item_checkmarks(parent_handle, id, check_bitmap, uncheck_bitmap); }
+        }
+
+        let owner_drawn = self.icon.is_some() || self.font.is_some() || self.text_color.is_some() || self.background_color.is_some();
+        if owner_drawn {
+            let mut style = mh::MenuItemStyle::new(self.text, parent.hwnd().is_some());
+            style.icon = self.icon.map(|i| i.handle as _).unwrap_or(ptr::null_mut());
+            style.check_bitmap = check_bitmap;
+            style.uncheck_bitmap = uncheck_bitmap;
+            style.font = self.font.map(|f| f.handle).unwrap_or(ptr::null_mut());
+            style.text_color = self.text_color;
+            style.background_color = self.background_color;
+
+            unsafe { mh::set_menu_item_style(parent_handle, id, style); }
+        }
+
         if self.disabled {
             item.set_enabled(false);
         }
@@ -454,3 +563,156 @@ impl Drop for MenuSeparator {
         self.handle.destroy();
     }
 }
+
+/**
+    A group of menu items of which exactly one is checked. The items are displayed with a radio bullet.
+    Selecting an item checks it and unchecks the other items of the group.
+
+    Requires the `menu` feature.
+
+    **Builder parameters:**
+      - items: The text of the items. Same syntax as the text of a `MenuItem` (access key and shortcut label).
+      - selected: The index of the checked item. Defaults to the first item.
+      - parent: A menu or a popup menu. Radio groups cannot be added to the menu bar.
+
+    **Control events:**
+      - OnMenuRadioSelectionChanged: When the user checks another item of the group. The event data is a `EventData::OnMenuRadioSelection` with the index of the item.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn zoom_group(group: &mut nwg::MenuRadioGroup, menu: &nwg::Menu) -> Result<(), nwg::NwgError> {
+        nwg::MenuRadioGroup::builder()
+            .items(&["50%", "100%", "200%"])
+            .selected(1)
+            .parent(menu)
+            .build(group)
+    }
+    ```
+*/
+#[derive(Default, Debug, PartialEq, Eq)]
+pub struct MenuRadioGroup {
+    pub handle: ControlHandle
+}
+
+impl MenuRadioGroup {
+
+    pub fn builder<'a>() -> MenuRadioGroupBuilder<'a> {
+        MenuRadioGroupBuilder {
+            items: &[],
+            selected: 0,
+            parent: None
+        }
+    }
+
+    /// Returns the number of items in the group
+    pub fn item_count(&self) -> usize {
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let (parent_handle, first_id) = self.handle.hmenu_item().expect(BAD_HANDLE);
+
+        unsafe { mh::radio_group_len(parent_handle, first_id) as usize }
+    }
+
+    /// Returns the index of the checked item
+    pub fn selected(&self) -> usize {
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let (parent_handle, first_id) = self.handle.hmenu_item().expect(BAD_HANDLE);
+
+        unsafe { mh::radio_group_selected(parent_handle, first_id).unwrap_or(0) as usize }
+    }
+
+    /// Checks the item at `index` and unchecks the other items. Does nothing if the index is out of bounds.
+    /// Does not raise `OnMenuRadioSelectionChanged`.
+    pub fn set_selected(&self, index: usize) {
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let (parent_handle, first_id) = self.handle.hmenu_item().expect(BAD_HANDLE);
+
+        unsafe { mh::radio_group_select(parent_handle, first_id, index as u32); }
+    }
+
+    /// Returns true if the user can select the item at `index`
+    pub fn item_enabled(&self, index: usize) -> bool {
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let (parent_handle, first_id) = self.handle.hmenu_item().expect(BAD_HANDLE);
+
+        unsafe { mh::is_menuitem_enabled(parent_handle, None, Some(first_id + index as u32)) }
+    }
+
+    /// Enable or disable the item at `index`
+    pub fn set_item_enabled(&self, index: usize, v: bool) {
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let (parent_handle, first_id) = self.handle.hmenu_item().expect(BAD_HANDLE);
+
+        unsafe { mh::enable_menuitem(parent_handle, None, Some(first_id + index as u32), v); }
+    }
+
+    /// Returns true if the user can select the items of the group
+    pub fn enabled(&self) -> bool {
+        (0..self.item_count()).any(|i| self.item_enabled(i))
+    }
+
+    /// Enable or disable all the items of the group
+    pub fn set_enabled(&self, v: bool) {
+        for i in 0..self.item_count() {
+            self.set_item_enabled(i, v);
+        }
+    }
+
+}
+
+impl Drop for MenuRadioGroup {
+    fn drop(&mut self) {
+        if let Some((parent_handle, first_id)) = self.handle.hmenu_item() {
+            let count = unsafe { mh::radio_group_len(parent_handle, first_id) };
+            for id in (first_id..first_id + count).rev() {
+                wh::destroy_menu_item(parent_handle, id);
+            }
+
+            self.handle = ControlHandle::NoHandle;
+        }
+    }
+}
+
+pub struct MenuRadioGroupBuilder<'a> {
+    items: &'a [&'a str],
+    selected: usize,
+    parent: Option<ControlHandle>
+}
+
+impl<'a> MenuRadioGroupBuilder<'a> {
+
+    pub fn items(mut self, items: &'a [&'a str]) -> MenuRadioGroupBuilder<'a> {
+        self.items = items;
+        self
+    }
+
+    pub fn selected(mut self, index: usize) -> MenuRadioGroupBuilder<'a> {
+        self.selected = index;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> MenuRadioGroupBuilder<'a> {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, group: &mut MenuRadioGroup) -> Result<(), NwgError> {
+        let menu = match self.parent {
+            Some(ControlHandle::Menu(_, menu)) | Some(ControlHandle::PopMenu(_, menu)) => menu,
+            Some(_) => { return Err(NwgError::menu_create("The parent of a MenuRadioGroup must be a menu")); },
+            None => { return Err(NwgError::no_parent_menu()); }
+        };
+
+        if self.items.is_empty() {
+            return Err(NwgError::menu_create("MenuRadioGroup without items"));
+        }
+
+        *group = Default::default();
+
+        let first_id = unsafe { mh::build_radio_group(menu, self.items) };
+        group.handle = ControlHandle::MenuItem(menu, first_id);
+        group.set_selected(self.selected.min(self.items.len() - 1));
+
+        Ok(())
+    }
+}
@@ -2,55 +2,121 @@
     A message-only window enables you to send and receive messages. It is not visible, has no z-order, cannot be enumerated, and does not
     receive broadcast messages. The window simply dispatches messages.
 
-    A MessageWindow do not have any builder parameter, but still provides the API for the derive macro.
+    A message window can be the parent of the non visual components (`Timer`, `AnimationTimer`, `Notice`, `TrayNotification`, ...).
+    This lets background components run without creating a hidden top level window.
 
-    Requires the `message-window` feature. 
+    Requires the `message-window` feature.
+
+    **Builder parameters:**
+      * `title`: The name of the window. Other processes can find a named message window with `FindWindowExW(HWND_MESSAGE, ...)`.
+      * `device_notifications`: If the window should receive the arrival and the removal of the devices. Defaults to `false`.
+
+    **Control events:**
+      * `OnInit`: The window was created
+      * `OnTimerTick`: A timer of the window ticked
+      * `OnTimerStop`: A timer of the window stopped
+      * `OnNotice`: A notice of the window was triggered
+      * `OnDeviceChange`: A device was added or removed. Requires `device_notifications(true)`.
+      * `OnAppExit`: The events loop of the thread exited
+      * Tray notification events if the window is the parent of a `TrayNotification`
 
     ## Example
     ```
     use native_windows_gui as nwg;
 
     let mut window = Default::default();
-    nwg::MessageWindow::builder().build(&mut window);
+    nwg::MessageWindow::builder()
+        .title("MyApp.Service")
+        .device_notifications(true)
+        .build(&mut window);
     ```
 
     When making a system-tray application (with TrayNotification), this is the recommended top level window type.
 */
+use winapi::um::winuser::HDEVNOTIFY;
 use super::ControlHandle;
-use crate::win32::window::create_message_window;
+use crate::win32::window::{create_message_window, register_device_notifications};
 use crate::NwgError;
 
 /**
     A message only top level window. At least one top level window is required to make a NWG application.
     See the module documentation
 */
-#[derive(Default, PartialEq, Eq)]
+#[derive(Default)]
 pub struct MessageWindow {
-    pub handle: ControlHandle
+    pub handle: ControlHandle,
+    device_notify: Option<DeviceNotify>,
 }
 
 impl MessageWindow {
 
-    pub fn builder() -> MessageWindowBuilder {
-        MessageWindowBuilder {}
+    pub fn builder<'a>() -> MessageWindowBuilder<'a> {
+        MessageWindowBuilder {
+            title: "",
+            device_notifications: false,
+        }
+    }
+
+    /// Returns `true` if the window receives the arrival and the removal of the devices
+    pub fn device_notifications(&self) -> bool {
+        self.device_notify.is_some()
     }
 
 }
 
 impl Drop for MessageWindow {
     fn drop(&mut self) {
+        // The notifications must be unregistered before the window is destroyed
+        self.device_notify = None;
         self.handle.destroy();
     }
 }
-pub struct MessageWindowBuilder {
+
+impl PartialEq for MessageWindow {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for MessageWindow {}
+
+pub struct MessageWindowBuilder<'a> {
+    title: &'a str,
+    device_notifications: bool,
 }
 
-impl MessageWindowBuilder {
+impl<'a> MessageWindowBuilder<'a> {
+
+    pub fn title(mut self, title: &'a str) -> MessageWindowBuilder<'a> {
+        self.title = title;
+        self
+    }
+
+    pub fn device_notifications(mut self, device_notifications: bool) -> MessageWindowBuilder<'a> {
+        self.device_notifications = device_notifications;
+        self
+    }
 
     pub fn build(self, out: &mut MessageWindow) -> Result<(), NwgError> {
         *out = Default::default();
-        out.handle = create_message_window()?;
+        out.handle = create_message_window(self.title)?;
+
+        if self.device_notifications {
+            let notify = register_device_notifications(out.handle.hwnd().unwrap())?;
+            out.device_notify = Some(DeviceNotify(notify));
+        }
+
         Ok(())
     }
 
 }
+
+/// Unregisters the device notifications when dropped
+struct DeviceNotify(HDEVNOTIFY);
+
+impl Drop for DeviceNotify {
+    fn drop(&mut self) {
+        use winapi::um::winuser::UnregisterDeviceNotification;
+        unsafe { UnregisterDeviceNotification(self.0); }
+    }
+}
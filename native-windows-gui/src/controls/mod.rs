@@ -7,6 +7,7 @@ mod radio_button;
 mod text_input;
 mod label;
 mod image_frame;
+mod lazy_control;
 
 #[cfg(feature = "textbox")]
 mod text_box;
@@ -29,12 +30,18 @@ mod track_bar;
 #[cfg(feature = "menu")]
 mod menu;
 
+#[cfg(feature = "menu")]
+mod popup_menu;
+
 #[cfg(feature = "timer")]
 mod timer;
 
 #[cfg(feature = "animation-timer")]
 mod animation_timer;
 
+#[cfg(feature = "animator")]
+mod animator;
+
 #[cfg(feature = "notice")]
 mod notice;
 
@@ -47,6 +54,60 @@ mod list_box;
 #[cfg(feature = "datetime-picker")]
 mod date_picker;
 
+#[cfg(feature = "month-calendar")]
+mod month_calendar;
+
+#[cfg(feature = "hotkey")]
+mod hotkey;
+
+#[cfg(feature = "toolbar")]
+mod toolbar;
+
+#[cfg(feature = "splitter")]
+mod splitter;
+
+#[cfg(feature = "scroll-panel")]
+mod scroll_panel;
+
+#[cfg(feature = "image-viewer")]
+mod image_viewer;
+
+#[cfg(feature = "mdi")]
+mod mdi_child;
+
+#[cfg(feature = "chart")]
+mod chart;
+
+#[cfg(feature = "title-bar")]
+mod title_bar;
+
+#[cfg(feature = "layered-window")]
+mod layered_window;
+
+#[cfg(feature = "document-tabs")]
+mod document_tabs;
+
+#[cfg(feature = "hex-view")]
+mod hex_view;
+
+#[cfg(feature = "terminal-view")]
+mod terminal_view;
+
+#[cfg(feature = "diff-view")]
+mod diff_view;
+
+#[cfg(feature = "markdown-view")]
+mod markdown_view;
+
+#[cfg(feature = "number-slider")]
+mod number_slider;
+
+#[cfg(feature = "panel-stack")]
+mod panel_stack;
+
+#[cfg(feature = "animated-image")]
+mod animated_image;
+
 #[cfg(feature = "progress-bar")]
 mod progress_bar;
 
@@ -87,13 +148,14 @@ mod handle_from_control;
 
 pub use control_handle::ControlHandle;
 pub use control_base::{ControlBase, HwndBuilder, TimerBuilder as BaseTimerBuilder, OtherBuilder};
-pub use window::{Window, WindowBuilder, WindowFlags};
+pub use window::{Window, WindowBuilder, WindowFlags, WindowExFlags};
 pub use button::{Button, ButtonBuilder, ButtonFlags};
 pub use check_box::{CheckBox, CheckBoxBuilder, CheckBoxState, CheckBoxFlags};
 pub use radio_button::{RadioButton, RadioButtonBuilder, RadioButtonState, RadioButtonFlags};
 pub use text_input::{TextInput, TextInputBuilder, TextInputFlags};
 pub use label::{Label, LabelBuilder, LabelFlags};
 pub use image_frame::{ImageFrame, ImageFrameBuilder, ImageFrameFlags};
+pub use lazy_control::LazyControl;
 
 #[cfg(feature = "textbox")]
 pub use text_box::{TextBox, TextBoxBuilder, TextBoxFlags};
@@ -105,7 +167,7 @@ pub use rich_text_box::*;
 pub use rich_label::*;
 
 #[cfg(feature = "status-bar")]
-pub use status_bar::{StatusBar, StatusBarBuilder};
+pub use status_bar::{StatusBar, StatusBarBuilder, StatusBarDrawItem, StatusBarDrawCallback, JobReporter};
 
 #[cfg(feature = "tooltip")]
 pub use tooltip::{Tooltip, TooltipBuilder, TooltipIcon};
@@ -114,7 +176,10 @@ pub use tooltip::{Tooltip, TooltipBuilder, TooltipIcon};
 pub use track_bar::{TrackBar, TrackBarBuilder, TrackBarFlags};
 
 #[cfg(feature = "menu")]
-pub use menu::{Menu, MenuBuilder, MenuItem, MenuSeparator, MenuItemBuilder, PopupMenuFlags};
+pub use menu::{Menu, MenuBuilder, MenuItem, MenuSeparator, MenuItemBuilder, MenuRadioGroup, MenuRadioGroupBuilder, PopupMenuFlags};
+
+#[cfg(feature = "menu")]
+pub use popup_menu::{PopupMenu, MenuEntry};
 
 #[cfg(feature = "menu")]
 pub use control_base::HmenuBuilder;
@@ -127,6 +192,9 @@ pub use timer::{Timer, TimerBuilder};
 #[allow(deprecated)]
 pub use animation_timer::{AnimationTimer, AnimationTimerBuilder};
 
+#[cfg(feature = "animator")]
+pub use animator::{Animator, AnimatorBuilder};
+
 #[cfg(feature = "notice")]
 pub use notice::{Notice, NoticeSender, NoticeBuilder};
 
@@ -139,6 +207,60 @@ pub use list_box::{ListBox, ListBoxFlags, ListBoxBuilder};
 #[cfg(feature = "datetime-picker")]
 pub use date_picker::{DatePicker, DatePickerValue, DatePickerFlags, DatePickerBuilder};
 
+#[cfg(feature = "month-calendar")]
+pub use month_calendar::{MonthCalendar, MonthCalendarFlags, MonthCalendarBuilder};
+
+#[cfg(feature = "hotkey")]
+pub use hotkey::{HotKey, HotKeyFlags, HotKeyModifiers, HotKeyRules, KeyChord, HotKeyBuilder};
+
+#[cfg(feature = "toolbar")]
+pub use toolbar::{Toolbar, ToolbarFlags, ToolbarButton, ToolbarButtonStyle, ToolbarBuilder};
+
+#[cfg(feature = "splitter")]
+pub use splitter::{Splitter, SplitterBuilder, SplitterFlags, SplitterOrientation};
+
+#[cfg(feature = "scroll-panel")]
+pub use scroll_panel::{ScrollPanel, ScrollPanelBuilder, ScrollPanelFlags};
+
+#[cfg(feature = "image-viewer")]
+pub use image_viewer::{ImageViewer, ImageViewerBuilder, ImageViewerFlags};
+
+#[cfg(feature = "mdi")]
+pub use mdi_child::{MdiChild, MdiChildFlags, MdiChildBuilder};
+
+#[cfg(feature = "chart")]
+pub use chart::{Chart, ChartBuilder, ChartFlags, ChartSeries, ChartSeriesKind, ChartPoint};
+
+#[cfg(feature = "title-bar")]
+pub use title_bar::{TitleBar, TitleBarBuilder, TitleBarHit};
+
+#[cfg(feature = "layered-window")]
+pub use layered_window::{LayeredWindow, LayeredWindowBuilder};
+
+#[cfg(feature = "document-tabs")]
+pub use document_tabs::{DocumentTabs, DocumentTabsBuilder, DocumentTabsFlags};
+
+#[cfg(feature = "hex-view")]
+pub use hex_view::{HexView, HexViewBuilder, HexViewFlags, HexDataProvider};
+
+#[cfg(feature = "terminal-view")]
+pub use terminal_view::{TerminalView, TerminalViewBuilder, TerminalViewFlags};
+
+#[cfg(feature = "diff-view")]
+pub use diff_view::{DiffView, DiffViewBuilder, DiffViewFlags, DiffOp};
+
+#[cfg(feature = "markdown-view")]
+pub use markdown_view::{MarkdownView, MarkdownViewBuilder, MarkdownViewFlags};
+
+#[cfg(feature = "number-slider")]
+pub use number_slider::{NumberSlider, NumberSliderBuilder, NumberSliderFlags};
+
+#[cfg(feature = "panel-stack")]
+pub use panel_stack::{PanelStack, PanelStackBuilder, PanelStackFlags};
+
+#[cfg(feature = "animated-image")]
+pub use animated_image::{AnimatedImage, AnimatedImageBuilder, AnimatedImageFlags};
+
 #[cfg(feature = "progress-bar")]
 pub use progress_bar::{ProgressBar, ProgressBarState, ProgressBarFlags, ProgressBarBuilder};
 
@@ -152,7 +274,7 @@ pub use treeview::{TreeView, TreeViewBuilder, TreeItem, TreeInsert, TreeItemActi
 pub use treeview_iterator::TreeViewIterator;
 
 #[cfg(feature = "tray-notification")]
-pub use tray_notification::{TrayNotificationFlags, TrayNotification, TrayNotificationBuilder};
+pub use tray_notification::{TrayNotificationFlags, TrayNotification, TrayNotificationBuilder, TrayBalloon};
 
 #[cfg(feature = "message-window")]
 pub use message_window::{MessageWindow, MessageWindowBuilder};
@@ -0,0 +1,621 @@
+use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_TABSTOP};
+use winapi::um::commctrl::{MCS_MULTISELECT, MCS_WEEKNUMBERS, MCS_NOTODAYCIRCLE, MCS_NOTODAY, MCS_NOTRAILINGDATES, MCS_SHORTDAYSOFWEEK};
+use winapi::um::minwinbase::SYSTEMTIME;
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
+use crate::win32::base_helper::check_hwnd;
+use crate::{Font, NwgError, DatePickerValue, RawEventHandler, unbind_raw_event_handler};
+use super::{ControlBase, ControlHandle};
+use std::{mem, ptr, rc::Rc, cell::RefCell};
+
+const NOT_BOUND: &'static str = "MonthCalendar is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: MonthCalendar handle is not HWND!";
+
+
+bitflags! {
+
+    /**
+        The MonthCalendarFlags flags
+
+        * NONE:              No flags. Equivalent to a invisible month calendar.
+        * VISIBLE:           The month calendar is immediatly visible after creation
+        * DISABLED:          The month calendar cannot be interacted with by the user. It also has a grayed out look.
+        * TAB_STOP:          The control can be selected using tab navigation
+        * MULTI_SELECT:      The user can select a range of dates instead of a single date
+        * WEEK_NUMBERS:      Display the week numbers to the left of each row of days
+        * NO_TODAY_CIRCLE:   Do not circle the "today" date
+        * NO_TODAY:          Do not display the "today" date at the bottom of the control
+        * NO_TRAILING_DATES: Do not display the dates from the previous and the next month
+        * SHORT_DAYS:        Use the short version of the days of the week in the header
+    */
+    pub struct MonthCalendarFlags: u32 {
+        const VISIBLE = WS_VISIBLE;
+        const DISABLED = WS_DISABLED;
+        const TAB_STOP = WS_TABSTOP;
+        const MULTI_SELECT = MCS_MULTISELECT;
+        const WEEK_NUMBERS = MCS_WEEKNUMBERS;
+        const NO_TODAY_CIRCLE = MCS_NOTODAYCIRCLE;
+        const NO_TODAY = MCS_NOTODAY;
+        const NO_TRAILING_DATES = MCS_NOTRAILINGDATES;
+        const SHORT_DAYS = MCS_SHORTDAYSOFWEEK;
+    }
+}
+
+
+/**
+A month calendar control implements a calendar-like user interface. This provides the user with a very intuitive
+and recognizable method of entering or selecting a date or a range of dates.
+
+Dates are exchanged with the control using the `DatePickerValue` structure.
+
+Requires the `month-calendar` feature.
+
+**Builder parameters:**
+  * `parent`:              **Required.** The calendar parent container.
+  * `size`:                The calendar size.
+  * `position`:            The calendar position.
+  * `flags`:               A combination of the MonthCalendarFlags values.
+  * `ex_flags`:            A combination of win32 window extended flags. Unlike `flags`, ex_flags must be used straight from winapi
+  * `font`:                The font used for the calendar text
+  * `date`:                The default selected date
+  * `selection`:           The default selected range. Requires the `MULTI_SELECT` flag.
+  * `max_selection_count`: The maximum number of days that can be selected. Requires the `MULTI_SELECT` flag.
+  * `range`:               The accepted range of dates. The value is inclusive.
+  * `bold_dates`:          The dates that should be displayed in bold.
+  * `focus`:               The control receive focus after being created
+
+**Control events:**
+  * `OnMonthCalendarSelectionChanged`: When the selected date (or range of date) changes. Includes the user scrolling the months.
+  * `OnMonthCalendarSelect`: When the user explicitly selects a date
+  * `MousePress(_)`: Generic mouse press events on the calendar
+  * `OnMouseMove`: Generic mouse mouse event
+  * `OnMouseWheel`: Generic mouse wheel event
+
+Both selection events generate a `EventData::OnMonthCalendarSelection` that contains the start and the end of the selection.
+
+```rust
+use native_windows_gui as nwg;
+fn build_calendar(cal: &mut nwg::MonthCalendar, window: &nwg::Window) {
+    let start = nwg::DatePickerValue { year: 2020, month: 1, day: 1 };
+    let end = nwg::DatePickerValue { year: 2020, month: 12, day: 31 };
+    let holiday = nwg::DatePickerValue { year: 2020, month: 12, day: 25 };
+
+    nwg::MonthCalendar::builder()
+        .flags(nwg::MonthCalendarFlags::VISIBLE | nwg::MonthCalendarFlags::MULTI_SELECT)
+        .range(Some([start, end]))
+        .bold_dates(&[holiday])
+        .max_selection_count(14)
+        .parent(window)
+        .build(cal);
+}
+```
+*/
+#[derive(Default)]
+pub struct MonthCalendar {
+    pub handle: ControlHandle,
+    bold_dates: Rc<RefCell<Vec<DatePickerValue>>>,
+    handler0: RefCell<Option<RawEventHandler>>,
+}
+
+impl MonthCalendar {
+
+    pub fn builder<'a>() -> MonthCalendarBuilder<'a> {
+        MonthCalendarBuilder {
+            size: (230, 170).into(),
+            position: (0, 0).into(),
+            focus: false,
+            flags: None,
+            ex_flags: 0,
+            font: None,
+            parent: None,
+            date: None,
+            selection: None,
+            max_selection_count: None,
+            range: None,
+            bold_dates: &[],
+        }
+    }
+
+    /**
+        Return the selected date of the control.
+        If the control has the `MULTI_SELECT` flag, this returns the first day of the selection.
+    */
+    pub fn value(&self) -> DatePickerValue {
+        self.selection()[0]
+    }
+
+    /**
+        Sets the selected date of the control.
+        If the control has the `MULTI_SELECT` flag, this selects a range of a single day.
+    */
+    pub fn set_value(&self, date: DatePickerValue) {
+        self.set_selection(&[date, date]);
+    }
+
+    /**
+        Return the selected range of dates of the control as `[start, end]`. The range is inclusive.
+        If the control does not have the `MULTI_SELECT` flag, `start` and `end` will be the same date.
+    */
+    pub fn selection(&self) -> [DatePickerValue; 2] {
+        use winapi::um::commctrl::{MCM_GETCURSEL, MCM_GETSELRANGE};
+        use winapi::shared::minwindef::LPARAM;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let mut range: [SYSTEMTIME; 2] = unsafe { mem::zeroed() };
+        if self.multi_select() {
+            wh::send_message(handle, MCM_GETSELRANGE, 0, &mut range as *mut [SYSTEMTIME; 2] as LPARAM);
+        } else {
+            wh::send_message(handle, MCM_GETCURSEL, 0, &mut range[0] as *mut SYSTEMTIME as LPARAM);
+            range[1] = range[0];
+        }
+
+        [from_system_time(&range[0]), from_system_time(&range[1])]
+    }
+
+    /**
+        Sets the selected range of dates of the control. The range is inclusive.
+        If the control does not have the `MULTI_SELECT` flag, only the first date is used.
+
+        If the range is bigger than `max_selection_count` or if the dates are outside of the control range,
+        the selection is not changed.
+    */
+    pub fn set_selection(&self, r: &[DatePickerValue; 2]) {
+        use winapi::um::commctrl::{MCM_SETCURSEL, MCM_SETSELRANGE};
+        use winapi::shared::minwindef::LPARAM;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let values = [to_system_time(&r[0]), to_system_time(&r[1])];
+        if self.multi_select() {
+            wh::send_message(handle, MCM_SETSELRANGE, 0, &values as *const [SYSTEMTIME; 2] as LPARAM);
+        } else {
+            wh::send_message(handle, MCM_SETCURSEL, 0, &values[0] as *const SYSTEMTIME as LPARAM);
+        }
+    }
+
+    /// Return the maximum number of days that can be selected in a control with the `MULTI_SELECT` flag.
+    pub fn max_selection_count(&self) -> u32 {
+        use winapi::um::commctrl::MCM_GETMAXSELCOUNT;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::send_message(handle, MCM_GETMAXSELCOUNT, 0, 0) as u32
+    }
+
+    /// Sets the maximum number of days that can be selected in a control with the `MULTI_SELECT` flag.
+    /// The default value is 7 days.
+    pub fn set_max_selection_count(&self, count: u32) {
+        use winapi::um::commctrl::MCM_SETMAXSELCOUNT;
+        use winapi::shared::minwindef::WPARAM;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::send_message(handle, MCM_SETMAXSELCOUNT, count as WPARAM, 0);
+    }
+
+    /**
+        Return the minimum and the maximum allowable dates of the calendar.
+        If a limit is not set, the value is `None`.
+    */
+    pub fn range(&self) -> [Option<DatePickerValue>; 2] {
+        use winapi::um::commctrl::{MCM_GETRANGE, GDTR_MIN, GDTR_MAX};
+        use winapi::shared::minwindef::{LPARAM, WPARAM};
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let mut tr: [SYSTEMTIME; 2] = unsafe { mem::zeroed() };
+        let limits = wh::send_message(handle, MCM_GETRANGE, 0, &mut tr as *mut [SYSTEMTIME; 2] as LPARAM) as WPARAM;
+
+        let min = match limits & GDTR_MIN == GDTR_MIN {
+            true => Some(from_system_time(&tr[0])),
+            false => None
+        };
+
+        let max = match limits & GDTR_MAX == GDTR_MAX {
+            true => Some(from_system_time(&tr[1])),
+            false => None
+        };
+
+        [min, max]
+    }
+
+    /**
+        Sets the minimum and the maximum allowable dates of the calendar.
+        Use `None` to remove a limit.
+    */
+    pub fn set_range(&self, r: &[Option<DatePickerValue>; 2]) {
+        use winapi::um::commctrl::{MCM_SETRANGE, GDTR_MIN, GDTR_MAX};
+        use winapi::shared::minwindef::{LPARAM, WPARAM};
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let mut limits: WPARAM = 0;
+        let mut values: [SYSTEMTIME; 2] = unsafe { mem::zeroed() };
+
+        if let Some(min) = r[0].as_ref() {
+            limits |= GDTR_MIN;
+            values[0] = to_system_time(min);
+        }
+
+        if let Some(max) = r[1].as_ref() {
+            limits |= GDTR_MAX;
+            values[1] = to_system_time(max);
+        }
+
+        wh::send_message(handle, MCM_SETRANGE, limits, &values as *const [SYSTEMTIME; 2] as LPARAM);
+    }
+
+    /// Return the dates that are displayed in bold in the calendar
+    pub fn bold_dates(&self) -> Vec<DatePickerValue> {
+        self.bold_dates.borrow().clone()
+    }
+
+    /**
+        Sets the dates that should be displayed in bold in the calendar. This replaces the old bold dates.
+        The dates remain bold when the user scrolls to another month.
+    */
+    pub fn set_bold_dates(&self, dates: &[DatePickerValue]) {
+        use winapi::um::commctrl::{MCM_GETMONTHRANGE, MCM_SETDAYSTATE, GMR_DAYSTATE, MONTHDAYSTATE};
+        use winapi::shared::minwindef::{WPARAM, LPARAM};
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        *self.bold_dates.borrow_mut() = dates.to_vec();
+
+        // Update the months that are currently visible. The other months are handled in the `MCN_GETDAYSTATE` hook.
+        let mut visible: [SYSTEMTIME; 2] = unsafe { mem::zeroed() };
+        let count = wh::send_message(handle, MCM_GETMONTHRANGE, GMR_DAYSTATE as WPARAM, &mut visible as *mut [SYSTEMTIME; 2] as LPARAM);
+        if count <= 0 {
+            return;
+        }
+
+        let state: Vec<MONTHDAYSTATE> = month_day_state(&self.bold_dates.borrow(), &visible[0], count as usize);
+        wh::send_message(handle, MCM_SETDAYSTATE, count as WPARAM, state.as_ptr() as LPARAM);
+    }
+
+    /// Return `true` if the "today" date is circled in the calendar
+    pub fn today_circle(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::get_style(handle) & MCS_NOTODAYCIRCLE != MCS_NOTODAYCIRCLE
+    }
+
+    /// Show or hide the circle around the "today" date
+    pub fn set_today_circle(&self, v: bool) {
+        use winapi::um::winuser::InvalidateRect;
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let style = wh::get_style(handle);
+        match v {
+            true => wh::set_style(handle, style & !MCS_NOTODAYCIRCLE),
+            false => wh::set_style(handle, style | MCS_NOTODAYCIRCLE),
+        }
+
+        unsafe { InvalidateRect(handle, ptr::null(), 1); }
+    }
+
+    /// Return `true` if the control has the `MULTI_SELECT` flag.
+    pub fn multi_select(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::get_style(handle) & MCS_MULTISELECT == MCS_MULTISELECT
+    }
+
+    /// Return the minimum size required to display a full month in the control
+    pub fn min_size(&self) -> (u32, u32) {
+        use winapi::um::commctrl::MCM_GETMINREQRECT;
+        use winapi::shared::{windef::RECT, minwindef::LPARAM};
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let mut r: RECT = unsafe { mem::zeroed() };
+        wh::send_message(handle, MCM_GETMINREQRECT, 0, &mut r as *mut RECT as LPARAM);
+
+        let (w, h) = unsafe { crate::win32::high_dpi::physical_to_logical(r.right - r.left, r.bottom - r.top) };
+        (w as u32, h as u32)
+    }
+
+    /// Return the font of the control
+    pub fn font(&self) -> Option<Font> {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let font_handle = wh::get_window_font(handle);
+        if font_handle.is_null() {
+            None
+        } else {
+            Some(Font { handle: font_handle })
+        }
+    }
+
+    /// Sets the font of the control
+    pub fn set_font(&self, font: Option<&Font>) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_font(handle, font.map(|f| f.handle), true); }
+    }
+
+    /// Return true if the control currently has the keyboard focus
+    pub fn focus(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_focus(handle) }
+    }
+
+    /// Sets the keyboard focus on the calendar.
+    pub fn set_focus(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_focus(handle); }
+    }
+
+    /// Return true if the control user can interact with the control, return false otherwise
+    pub fn enabled(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_enabled(handle) }
+    }
+
+    /// Enable or disable the control
+    pub fn set_enabled(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_enabled(handle, v) }
+    }
+
+    /// Return true if the control is visible to the user. Will return true even if the
+    /// control is outside of the parent client view (ex: at the position (10000, 10000))
+    pub fn visible(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_visibility(handle) }
+    }
+
+    /// Show or hide the control to the user
+    pub fn set_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_visibility(handle, v) }
+    }
+
+    /// Return the size of the calendar in the parent window
+    pub fn size(&self) -> (u32, u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_size(handle) }
+    }
+
+    /// Set the size of the calendar in the parent window
+    pub fn set_size(&self, x: u32, y: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_size(handle, x, y, false) }
+    }
+
+    /// Return the position of the calendar in the parent window
+    pub fn position(&self) -> (i32, i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_position(handle) }
+    }
+
+    /// Set the position of the calendar in the parent window
+    pub fn set_position(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_position(handle, x, y) }
+    }
+
+    /// Winapi class name used during control creation
+    pub fn class_name(&self) -> &'static str {
+        "SysMonthCal32"
+    }
+
+    /// Winapi base flags used during window creation
+    pub fn flags(&self) -> u32 {
+        WS_VISIBLE | WS_TABSTOP
+    }
+
+    /// Winapi flags required by the control
+    pub fn forced_flags(&self) -> u32 {
+        use winapi::um::winuser::WS_CHILD;
+        use winapi::um::commctrl::MCS_DAYSTATE;
+
+        WS_CHILD | MCS_DAYSTATE
+    }
+
+    /// Answer the `MCN_GETDAYSTATE` requests of the control using the bold dates
+    fn hook_day_state(&self) {
+        use crate::bind_raw_event_handler_inner;
+        use winapi::um::winuser::{WM_NOTIFY, NMHDR};
+        use winapi::um::commctrl::{MCN_GETDAYSTATE, NMDAYSTATE};
+        use winapi::shared::basetsd::UINT_PTR;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let parent_handle = ControlHandle::Hwnd(wh::get_window_parent(handle));
+        let bold_dates = self.bold_dates.clone();
+
+        let handler = bind_raw_event_handler_inner(&parent_handle, handle as UINT_PTR, move |_hwnd, msg, _w, l| {
+            if msg == WM_NOTIFY {
+                let nmhdr = unsafe { &*(l as *const NMHDR) };
+                if nmhdr.hwndFrom == handle && nmhdr.code == MCN_GETDAYSTATE {
+                    let info = unsafe { &mut *(l as *mut NMDAYSTATE) };
+                    let count = info.cDayState as usize;
+                    let state = month_day_state(&bold_dates.borrow(), &info.stStart, count);
+                    unsafe { ptr::copy_nonoverlapping(state.as_ptr(), info.prgDayState, count); }
+                    return Some(0);
+                }
+            }
+
+            None
+        });
+
+        *self.handler0.borrow_mut() = Some(handler.unwrap());
+    }
+
+}
+
+impl Drop for MonthCalendar {
+    fn drop(&mut self) {
+        let handler = self.handler0.borrow();
+        if let Some(h) = handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
+        self.handle.destroy();
+    }
+}
+
+impl PartialEq for MonthCalendar {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for MonthCalendar {}
+
+pub struct MonthCalendarBuilder<'a> {
+    size: Extent,
+    position: Extent,
+    flags: Option<MonthCalendarFlags>,
+    ex_flags: u32,
+    font: Option<&'a Font>,
+    focus: bool,
+    parent: Option<ControlHandle>,
+    date: Option<DatePickerValue>,
+    selection: Option<[DatePickerValue; 2]>,
+    max_selection_count: Option<u32>,
+    range: Option<[DatePickerValue; 2]>,
+    bold_dates: &'a [DatePickerValue],
+}
+
+impl<'a> MonthCalendarBuilder<'a> {
+
+    pub fn flags(mut self, flags: MonthCalendarFlags) -> MonthCalendarBuilder<'a> {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn ex_flags(mut self, flags: u32) -> MonthCalendarBuilder<'a> {
+        self.ex_flags = flags;
+        self
+    }
+
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> MonthCalendarBuilder<'a> {
+        self.size = size.into();
+        self
+    }
+
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> MonthCalendarBuilder<'a> {
+        self.position = pos.into();
+        self
+    }
+
+    pub fn font(mut self, font: Option<&'a Font>) -> MonthCalendarBuilder<'a> {
+        self.font = font;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> MonthCalendarBuilder<'a> {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn date(mut self, date: Option<DatePickerValue>) -> MonthCalendarBuilder<'a> {
+        self.date = date;
+        self
+    }
+
+    pub fn selection(mut self, selection: Option<[DatePickerValue; 2]>) -> MonthCalendarBuilder<'a> {
+        self.selection = selection;
+        self
+    }
+
+    pub fn max_selection_count(mut self, count: u32) -> MonthCalendarBuilder<'a> {
+        self.max_selection_count = Some(count);
+        self
+    }
+
+    pub fn range(mut self, range: Option<[DatePickerValue; 2]>) -> MonthCalendarBuilder<'a> {
+        self.range = range;
+        self
+    }
+
+    pub fn bold_dates(mut self, dates: &'a [DatePickerValue]) -> MonthCalendarBuilder<'a> {
+        self.bold_dates = dates;
+        self
+    }
+
+    pub fn focus(mut self, focus: bool) -> MonthCalendarBuilder<'a> {
+        self.focus = focus;
+        self
+    }
+
+    pub fn build(self, out: &mut MonthCalendar) -> Result<(), NwgError> {
+        let flags = self.flags.map(|f| f.bits()).unwrap_or(out.flags());
+
+        let parent = match self.parent {
+            Some(p) => Ok(p),
+            None => Err(NwgError::no_parent("MonthCalendar"))
+        }?;
+
+        *out = Default::default();
+
+        out.handle = ControlBase::build_hwnd()
+            .class_name(out.class_name())
+            .forced_flags(out.forced_flags())
+            .flags(flags)
+            .ex_flags(self.ex_flags)
+            .size(self.size)
+            .position(self.position)
+            .parent(Some(parent))
+            .build()?;
+
+        out.hook_day_state();
+
+        if self.font.is_some() {
+            out.set_font(self.font);
+        } else {
+            out.set_font(Font::global_default().as_ref());
+        }
+
+        if let Some(range) = self.range {
+            out.set_range(&[Some(range[0]), Some(range[1])]);
+        }
+
+        if let Some(count) = self.max_selection_count {
+            out.set_max_selection_count(count);
+        }
+
+        if let Some(date) = self.date {
+            out.set_value(date);
+        }
+
+        if let Some(selection) = self.selection.as_ref() {
+            out.set_selection(selection);
+        }
+
+        if self.bold_dates.len() > 0 {
+            out.set_bold_dates(self.bold_dates);
+        }
+
+        if self.focus {
+            out.set_focus();
+        }
+
+        Ok(())
+    }
+
+}
+
+
+fn from_system_time(st: &SYSTEMTIME) -> DatePickerValue {
+    DatePickerValue { year: st.wYear, month: st.wMonth, day: st.wDay }
+}
+
+fn to_system_time(v: &DatePickerValue) -> SYSTEMTIME {
+    SYSTEMTIME { wYear: v.year, wMonth: v.month, wDayOfWeek: 0, wDay: v.day, wHour: 0, wMinute: 0, wSecond: 0, wMilliseconds: 0 }
+}
+
+/// Build the `MONTHDAYSTATE` array of `count` months starting at the month of `start`.
+/// Each bit of a `MONTHDAYSTATE` maps to a day of the month. A bit set to 1 means the day is bold.
+fn month_day_state(dates: &[DatePickerValue], start: &SYSTEMTIME, count: usize) -> Vec<u32> {
+    let mut state = vec![0u32; count];
+    let first_month = (start.wYear as i32 * 12) + (start.wMonth as i32 - 1);
+
+    for date in dates {
+        if date.day < 1 || date.day > 31 {
+            continue;
+        }
+
+        let index = (date.year as i32 * 12) + (date.month as i32 - 1) - first_month;
+        if index >= 0 && (index as usize) < count {
+            state[index as usize] |= 1 << (date.day - 1);
+        }
+    }
+
+    state
+}
@@ -3,6 +3,7 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
 use crate::win32::base_helper::check_hwnd;
 use crate::{NwgError, Font, RawEventHandler, bind_raw_event_handler_inner, unbind_raw_event_handler};
 use super::{ControlBase, ControlHandle, TextInput, Button, ButtonFlags, TextInputFlags};
@@ -127,8 +128,8 @@ impl NumberSelect {
 
     pub fn builder<'a>() -> NumberSelectBuilder<'a> {
         NumberSelectBuilder {
-            size: (100, 25),
-            position: (0, 0),
+            size: (100, 25).into(),
+            position: (0, 0).into(),
             data: NumberSelectData::default(),
             enabled: true,
             flags: None,
@@ -259,8 +260,8 @@ impl Drop for NumberSelect {
 }
 
 pub struct NumberSelectBuilder<'a> {
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     data: NumberSelectData,
     enabled: bool,
     flags: Option<NumberSelectFlags>,
@@ -275,13 +276,13 @@ impl<'a> NumberSelectBuilder<'a> {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> NumberSelectBuilder<'a> {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> NumberSelectBuilder<'a> {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> NumberSelectBuilder<'a> {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> NumberSelectBuilder<'a> {
+        self.position = pos.into();
         self
     }
 
@@ -389,7 +390,7 @@ impl<'a> NumberSelectBuilder<'a> {
 
         *out = Default::default();
 
-        let (w, h) = self.size;
+        let (w, h) = unsafe { self.size.to_units(parent.hwnd().unwrap_or(std::ptr::null_mut())) };
 
         if out.handler.is_some() {
             unbind_raw_event_handler(out.handler.as_ref().unwrap())?;
@@ -403,7 +404,7 @@ impl<'a> NumberSelectBuilder<'a> {
             .forced_flags(out.forced_flags())
             .ex_flags(WS_EX_CONTROLPARENT)
             .flags(flags)
-            .size(self.size)
+            .size((w, h))
             .position(self.position)
             .parent(Some(parent))
             .build()?;
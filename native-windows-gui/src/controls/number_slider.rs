@@ -0,0 +1,682 @@
+use winapi::shared::windef::HWND;
+use winapi::shared::minwindef::LPARAM;
+use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_TABSTOP, WS_EX_CONTROLPARENT};
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
+use crate::win32::base_helper::check_hwnd;
+use crate::win32::number_slider::{NMNUMBERSLIDER, NUMBER_SLIDER_VALUE_CHANGED};
+use crate::{NwgError, Font, RawEventHandler, bind_raw_event_handler_inner, unbind_raw_event_handler};
+use super::{ControlBase, ControlHandle, TextInput, TextInputFlags, TrackBar, TrackBarFlags};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const NOT_BOUND: &'static str = "NumberSlider is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: NumberSlider handle is not HWND!";
+
+/// Number of trackbar positions of a continuous or logarithmic slider
+const RESOLUTION: usize = 1000;
+
+/// A slider with more steps than this is continuous
+const MAX_STEPS: usize = 10000;
+
+/// Space between the trackbar and the text input, in logical pixels
+const SPACING: i32 = 4;
+
+
+bitflags! {
+    /**
+        The NumberSlider flags
+
+        * NONE:     No flags. Equivalent to a invisible blank NumberSlider.
+        * VISIBLE:  The NumberSlider is immediatly visible after creation
+        * DISABLED: The NumberSlider cannot be interacted with by the user. It also has a grayed out look.
+        * TAB_STOP: The trackbar and the text input can be selected using tab navigation.
+    */
+    pub struct NumberSliderFlags: u32 {
+        const NONE = 0;
+        const VISIBLE = WS_VISIBLE;
+        const DISABLED = WS_DISABLED;
+        const TAB_STOP = WS_TABSTOP;
+    }
+}
+
+/// The value of a number slider and the way it maps to the trackbar positions
+#[derive(Copy, Clone, Debug)]
+struct SliderData {
+    value: f64,
+    min: f64,
+    max: f64,
+    step: f64,
+    decimals: u8,
+    logarithmic: bool,
+}
+
+impl SliderData {
+
+    /// Clamps `v` in the range, snaps it to the step and rounds it to the decimals
+    fn normalize(&self, v: f64) -> f64 {
+        let mut v = v.max(self.min).min(self.max);
+        if self.step > 0.0 {
+            v = self.min + ((v - self.min) / self.step).round() * self.step;
+        }
+
+        let scale = 10f64.powi(self.decimals as i32);
+        ((v * scale).round() / scale).max(self.min).min(self.max)
+    }
+
+    /// The number of positions of the trackbar. A linear slider has one position per step.
+    fn positions(&self) -> usize {
+        if !self.logarithmic && self.step > 0.0 {
+            let steps = ((self.max - self.min) / self.step).round();
+            if steps <= MAX_STEPS as f64 {
+                return usize::max(steps as usize, 1);
+            }
+        }
+
+        RESOLUTION
+    }
+
+    fn to_pos(&self, v: f64) -> usize {
+        let t = match self.logarithmic {
+            true => (v / self.min).ln() / (self.max / self.min).ln(),
+            false => (v - self.min) / (self.max - self.min),
+        };
+
+        (t * self.positions() as f64).round() as usize
+    }
+
+    fn from_pos(&self, pos: usize) -> f64 {
+        let t = pos as f64 / self.positions() as f64;
+        let v = match self.logarithmic {
+            true => self.min * (self.max / self.min).powf(t),
+            false => self.min + t * (self.max - self.min),
+        };
+
+        self.normalize(v)
+    }
+
+    fn formatted_value(&self) -> String {
+        format!("{:.*}", self.decimals as usize, self.value)
+    }
+
+    fn validate(&self) -> Result<(), NwgError> {
+        if !(self.min < self.max) {
+            return Err(NwgError::control_create("NumberSlider minimum must be lower than its maximum"));
+        }
+
+        if self.logarithmic && self.min <= 0.0 {
+            return Err(NwgError::control_create("A logarithmic NumberSlider must have a positive minimum"));
+        }
+
+        Ok(())
+    }
+
+}
+
+impl Default for SliderData {
+    fn default() -> SliderData {
+        SliderData {
+            value: 0.0,
+            min: 0.0,
+            max: 100.0,
+            step: 1.0,
+            decimals: 0,
+            logarithmic: false,
+        }
+    }
+}
+
+/**
+A NumberSlider pairs a trackbar with a text input that display the same value. Moving the trackbar updates the text,
+and the value typed in the text input moves the trackbar when the user presses Enter or when the input loses the focus.
+The up and down arrows in the text input move the value by one step.
+
+The value is clamped to a range, snapped to a step and rounded to a number of decimals. With a logarithmic scale,
+the trackbar positions are spread evenly between the powers of the range (ex: 20Hz to 20kHz). A logarithmic slider must have
+a positive minimum.
+
+Changing the value with `set_value` does not raise any event.
+
+Requires the `number-slider` feature.
+
+**Builder parameters:**
+  * `parent`:      **Required.** The number slider parent container.
+  * `value`:       The initial value of the slider
+  * `range`:       The minimum and the maximum value. Default to (0.0, 100.0)
+  * `step`:        The increment of the value. 0.0 for a continuous slider. Default to 1.0
+  * `decimals`:    The number of decimals displayed and kept in the value. Default to 0
+  * `logarithmic`: If the trackbar uses a logarithmic scale
+  * `edit_width`:  The width of the text input. Default to 60
+  * `size`:        The number slider size.
+  * `position`:    The number slider position.
+  * `enabled`:     If the number slider can be used by the user. It also has a grayed out look if disabled.
+  * `flags`:       A combination of the NumberSliderFlags values.
+  * `font`:        The font used for the number slider text
+
+**Control events:**
+  * `OnValueChanged`: When the user changes the value with the trackbar or with the text input
+
+```rust
+use native_windows_gui as nwg;
+fn build_number_slider(slider: &mut nwg::NumberSlider, window: &nwg::Window) {
+    nwg::NumberSlider::builder()
+        .range(20.0, 20000.0)
+        .step(0.0)
+        .logarithmic(true)
+        .value(440.0)
+        .parent(window)
+        .build(slider);
+}
+
+fn on_value_changed(evt_data: &nwg::EventData) {
+    println!("Frequency: {}Hz", evt_data.on_value_changed());
+}
+```
+*/
+#[derive(Default)]
+pub struct NumberSlider {
+    pub handle: ControlHandle,
+    data: Rc<RefCell<SliderData>>,
+    track: TrackBar,
+    edit: TextInput,
+    handler: Option<RawEventHandler>,
+    edit_handler: Option<RawEventHandler>,
+}
+
+impl NumberSlider {
+
+    pub fn builder<'a>() -> NumberSliderBuilder<'a> {
+        NumberSliderBuilder {
+            size: (200, 25).into(),
+            position: (0, 0).into(),
+            data: SliderData::default(),
+            edit_width: 60,
+            enabled: true,
+            flags: None,
+            font: None,
+            parent: None
+        }
+    }
+
+    /// Returns the value of the slider
+    pub fn value(&self) -> f64 {
+        self.data.borrow().value
+    }
+
+    /// Sets the value of the slider. The value is clamped, snapped to the step and rounded. Does not raise `OnValueChanged`.
+    pub fn set_value(&self, v: f64) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.update(handle, v);
+    }
+
+    /// Returns the minimum and the maximum value of the slider
+    pub fn range(&self) -> (f64, f64) {
+        let data = self.data.borrow();
+        (data.min, data.max)
+    }
+
+    /// Sets the minimum and the maximum value of the slider. The value is clamped in the new range.
+    /// Panics if the range is empty, or if the slider is logarithmic and `min` is not positive.
+    pub fn set_range(&self, min: f64, max: f64) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.reconfigure(handle, |data| { data.min = min; data.max = max; });
+    }
+
+    /// Returns the increment of the value. 0.0 if the slider is continuous.
+    pub fn step(&self) -> f64 {
+        self.data.borrow().step
+    }
+
+    /// Sets the increment of the value. 0.0 for a continuous slider.
+    pub fn set_step(&self, step: f64) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.reconfigure(handle, |data| { data.step = step.max(0.0); });
+    }
+
+    /// Returns the number of decimals of the value
+    pub fn decimals(&self) -> u8 {
+        self.data.borrow().decimals
+    }
+
+    /// Sets the number of decimals of the value
+    pub fn set_decimals(&self, decimals: u8) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.reconfigure(handle, |data| { data.decimals = decimals; });
+    }
+
+    /// Returns true if the trackbar uses a logarithmic scale
+    pub fn logarithmic(&self) -> bool {
+        self.data.borrow().logarithmic
+    }
+
+    /// Sets if the trackbar uses a logarithmic scale. Panics if the minimum of the range is not positive.
+    pub fn set_logarithmic(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.reconfigure(handle, |data| { data.logarithmic = v; });
+    }
+
+    /// Returns the font of the control
+    pub fn font(&self) -> Option<Font> {
+        let handle = check_hwnd(&self.edit.handle, NOT_BOUND, BAD_HANDLE);
+        let font_handle = wh::get_window_font(handle);
+        if font_handle.is_null() {
+            None
+        } else {
+            Some(Font { handle: font_handle })
+        }
+    }
+
+    /// Sets the font of the control
+    pub fn set_font(&self, font: Option<&Font>) {
+        self.edit.set_font(font);
+    }
+
+    /// Returns true if the trackbar or the text input currently has the keyboard focus
+    pub fn focus(&self) -> bool {
+        self.track.focus() || self.edit.focus()
+    }
+
+    /// Sets the keyboard focus on the trackbar.
+    pub fn set_focus(&self) {
+        self.track.set_focus();
+    }
+
+    /// Returns true if the control user can interact with the control, return false otherwise
+    pub fn enabled(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_enabled(handle) }
+    }
+
+    /// Enable or disable the control
+    pub fn set_enabled(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_enabled(handle, v) }
+        self.track.set_enabled(v);
+        self.edit.set_enabled(v);
+    }
+
+    /// Returns true if the control is visible to the user. Will return true even if the
+    /// control is outside of the parent client view (ex: at the position (10000, 10000))
+    pub fn visible(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_visibility(handle) }
+    }
+
+    /// Show or hide the control to the user
+    pub fn set_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_visibility(handle, v) }
+    }
+
+    /// Returns the size of the control in the parent window
+    pub fn size(&self) -> (u32, u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_size(handle) }
+    }
+
+    /// Sets the size of the control in the parent window
+    pub fn set_size(&self, x: u32, y: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_size(handle, x, y, false) }
+    }
+
+    /// Returns the position of the control in the parent window
+    pub fn position(&self) -> (i32, i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_position(handle) }
+    }
+
+    /// Sets the position of the control in the parent window
+    pub fn set_position(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_position(handle, x, y) }
+    }
+
+    /// Winapi class name used during control creation
+    pub fn class_name(&self) -> &'static str {
+        "NWG_NUMBER_SLIDER"
+    }
+
+    /// Winapi base flags used during window creation
+    pub fn flags(&self) -> u32 {
+        WS_VISIBLE | WS_TABSTOP
+    }
+
+    /// Winapi flags required by the control
+    pub fn forced_flags(&self) -> u32 {
+        use winapi::um::winuser::{WS_CHILD, WS_CLIPCHILDREN};
+        WS_CHILD | WS_CLIPCHILDREN
+    }
+
+    fn update(&self, handle: HWND, v: f64) {
+        let track = self.track.handle.hwnd().expect(BAD_HANDLE);
+        let edit = self.edit.handle.hwnd().expect(BAD_HANDLE);
+        update_value(handle, &self.data, v, track, edit, false);
+    }
+
+    /// Changes the way the value maps to the trackbar and refresh the trackbar range and the value
+    fn reconfigure<F: FnOnce(&mut SliderData)>(&self, handle: HWND, f: F) {
+        let value = {
+            let mut data = self.data.borrow_mut();
+            let mut new_data = *data;
+            f(&mut new_data);
+            new_data.validate().expect("Invalid NumberSlider configuration");
+            *data = new_data;
+            data.value
+        };
+
+        let track = self.track.handle.hwnd().expect(BAD_HANDLE);
+        set_track_range(track, self.data.borrow().positions());
+        self.update(handle, value);
+    }
+
+}
+
+impl Drop for NumberSlider {
+
+    fn drop(&mut self) {
+        if let Some(h) = self.edit_handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
+        if let Some(h) = self.handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
+        self.handle.destroy();
+    }
+
+}
+
+impl PartialEq for NumberSlider {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+pub struct NumberSliderBuilder<'a> {
+    size: Extent,
+    position: Extent,
+    data: SliderData,
+    edit_width: i32,
+    enabled: bool,
+    flags: Option<NumberSliderFlags>,
+    font: Option<&'a Font>,
+    parent: Option<ControlHandle>
+}
+
+impl<'a> NumberSliderBuilder<'a> {
+
+    pub fn flags(mut self, flags: NumberSliderFlags) -> NumberSliderBuilder<'a> {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> NumberSliderBuilder<'a> {
+        self.size = size.into();
+        self
+    }
+
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> NumberSliderBuilder<'a> {
+        self.position = pos.into();
+        self
+    }
+
+    pub fn value(mut self, value: f64) -> NumberSliderBuilder<'a> {
+        self.data.value = value;
+        self
+    }
+
+    pub fn range(mut self, min: f64, max: f64) -> NumberSliderBuilder<'a> {
+        self.data.min = min;
+        self.data.max = max;
+        self
+    }
+
+    pub fn step(mut self, step: f64) -> NumberSliderBuilder<'a> {
+        self.data.step = step.max(0.0);
+        self
+    }
+
+    pub fn decimals(mut self, decimals: u8) -> NumberSliderBuilder<'a> {
+        self.data.decimals = decimals;
+        self
+    }
+
+    pub fn logarithmic(mut self, logarithmic: bool) -> NumberSliderBuilder<'a> {
+        self.data.logarithmic = logarithmic;
+        self
+    }
+
+    pub fn edit_width(mut self, width: i32) -> NumberSliderBuilder<'a> {
+        self.edit_width = width;
+        self
+    }
+
+    pub fn enabled(mut self, e: bool) -> NumberSliderBuilder<'a> {
+        self.enabled = e;
+        self
+    }
+
+    pub fn font(mut self, font: Option<&'a Font>) -> NumberSliderBuilder<'a> {
+        self.font = font;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> NumberSliderBuilder<'a> {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut NumberSlider) -> Result<(), NwgError> {
+        let flags = self.flags.map(|f| f.bits()).unwrap_or(out.flags());
+        let (track_flags, text_flags) = if flags & WS_TABSTOP == WS_TABSTOP {
+            (TrackBarFlags::VISIBLE | TrackBarFlags::HORIZONTAL | TrackBarFlags::NO_TICK | TrackBarFlags::TAB_STOP, TextInputFlags::VISIBLE | TextInputFlags::TAB_STOP)
+        } else {
+            (TrackBarFlags::VISIBLE | TrackBarFlags::HORIZONTAL | TrackBarFlags::NO_TICK, TextInputFlags::VISIBLE)
+        };
+
+        let parent = match self.parent {
+            Some(p) => Ok(p),
+            None => Err(NwgError::no_parent("NumberSlider"))
+        }?;
+
+        self.data.validate()?;
+
+        *out = NumberSlider::default();
+
+        let mut data = self.data;
+        data.value = data.normalize(data.value);
+        *out.data.borrow_mut() = data;
+
+        let (w, h) = unsafe { self.size.to_units(parent.hwnd().unwrap_or(std::ptr::null_mut())) };
+        let edit_width = i32::min(self.edit_width, w);
+
+        out.handle = ControlBase::build_hwnd()
+            .class_name(out.class_name())
+            .forced_flags(out.forced_flags())
+            .ex_flags(WS_EX_CONTROLPARENT)
+            .flags(flags & !WS_TABSTOP)
+            .size((w, h))
+            .position(self.position)
+            .parent(Some(parent))
+            .build()?;
+
+        TrackBar::builder()
+            .size((i32::max(w - edit_width - SPACING, 0), h))
+            .range(Some(0..data.positions()))
+            .pos(Some(data.to_pos(data.value)))
+            .flags(track_flags)
+            .parent(&out.handle)
+            .build(&mut out.track)?;
+
+        TextInput::builder()
+            .text(&data.formatted_value())
+            .size((edit_width, h))
+            .position((w - edit_width, 0))
+            .flags(text_flags)
+            .parent(&out.handle)
+            .build(&mut out.edit)?;
+
+        if self.font.is_some() {
+            out.edit.set_font(self.font);
+        } else {
+            out.edit.set_font(Font::global_default().as_ref());
+        }
+
+        let handle = out.handle.hwnd().unwrap();
+        let track = out.track.handle.hwnd().unwrap();
+        let edit = out.edit.handle.hwnd().unwrap();
+
+        let handler_data = out.data.clone();
+        let handler = bind_raw_event_handler_inner(&out.handle, 0x5D1E, move |hwnd, msg, w, l| {
+            use winapi::um::winuser::{WM_HSCROLL, WM_COMMAND, WM_SIZE, EN_KILLFOCUS};
+            use winapi::um::commctrl::{TBM_GETPOS, TBM_SETPOS, TB_ENDTRACK};
+            use winapi::shared::minwindef::{HIWORD, LOWORD};
+
+            match msg {
+                WM_HSCROLL if l as HWND == track => {
+                    if LOWORD(w as u32) as usize == TB_ENDTRACK {
+                        let data = handler_data.borrow();
+                        wh::send_message(track, TBM_SETPOS, 1, data.to_pos(data.value) as LPARAM);
+                    } else {
+                        let pos = wh::send_message(track, TBM_GETPOS, 0, 0) as usize;
+                        let value = handler_data.borrow().from_pos(pos);
+                        update_edit(hwnd, &handler_data, value, edit);
+                    }
+                },
+                WM_COMMAND if l as HWND == edit && HIWORD(w as u32) == EN_KILLFOCUS => {
+                    commit_text(hwnd, &handler_data, track, edit);
+                },
+                WM_SIZE => {
+                    layout(track, edit, LOWORD(l as u32) as i32, HIWORD(l as u32) as i32);
+                },
+                _ => {}
+            }
+
+            None
+        });
+
+        let edit_data = out.data.clone();
+        let edit_handler = bind_raw_event_handler_inner(&out.edit.handle, 0x5D1F, move |_hwnd, msg, w, _l| {
+            use winapi::um::winuser::{WM_KEYDOWN, WM_CHAR, VK_RETURN, VK_UP, VK_DOWN};
+
+            match (msg, w as i32) {
+                (WM_KEYDOWN, VK_RETURN) => {
+                    commit_text(handle, &edit_data, track, edit);
+                    Some(0)
+                },
+                (WM_KEYDOWN, VK_UP) | (WM_KEYDOWN, VK_DOWN) => {
+                    commit_text(handle, &edit_data, track, edit);
+                    let value = {
+                        let data = edit_data.borrow();
+                        let pos = data.to_pos(data.value);
+                        let pos = if w as i32 == VK_UP { usize::min(pos + 1, data.positions()) } else { pos.saturating_sub(1) };
+                        data.from_pos(pos)
+                    };
+                    update_value(handle, &edit_data, value, track, edit, true);
+                    Some(0)
+                },
+                // Swallow the carriage return to stop the edit from beeping
+                (WM_CHAR, 0x0D) => Some(0),
+                _ => None
+            }
+        });
+
+        out.handler = Some(handler?);
+        out.edit_handler = Some(edit_handler?);
+
+        if !self.enabled {
+            out.set_enabled(self.enabled);
+        }
+
+        Ok(())
+    }
+
+}
+
+/// Sets the value of the slider, moves the trackbar and updates the text. Notifies the parent if `notify` is true and the value changed.
+fn update_value(handle: HWND, data: &RefCell<SliderData>, value: f64, track: HWND, edit: HWND, notify: bool) {
+    use winapi::um::commctrl::TBM_SETPOS;
+
+    let (changed, value, pos, text) = {
+        let mut data = data.borrow_mut();
+        let value = data.normalize(value);
+        let changed = value != data.value;
+        data.value = value;
+        (changed, value, data.to_pos(value), data.formatted_value())
+    };
+
+    wh::send_message(track, TBM_SETPOS, 1, pos as LPARAM);
+    unsafe { wh::set_window_text(edit, &text); }
+
+    if changed && notify {
+        notify_value(handle, value);
+    }
+}
+
+/// Sets the value picked with the trackbar. The trackbar is left alone while the user drags the thumb.
+fn update_edit(handle: HWND, data: &RefCell<SliderData>, value: f64, edit: HWND) {
+    let text = {
+        let mut data = data.borrow_mut();
+        if value == data.value {
+            return;
+        }
+
+        data.value = value;
+        data.formatted_value()
+    };
+
+    unsafe { wh::set_window_text(edit, &text); }
+    notify_value(handle, value);
+}
+
+/// Parses the text of the input and applies it. An invalid text is replaced by the current value.
+fn commit_text(handle: HWND, data: &RefCell<SliderData>, track: HWND, edit: HWND) {
+    let text = unsafe { wh::get_window_text(edit) };
+    let value = match text.trim().parse::<f64>() {
+        Ok(v) if v.is_finite() => v,
+        _ => data.borrow().value
+    };
+
+    update_value(handle, data, value, track, edit, true);
+}
+
+fn set_track_range(track: HWND, positions: usize) {
+    use winapi::um::commctrl::{TBM_SETRANGEMIN, TBM_SETRANGEMAX};
+
+    wh::send_message(track, TBM_SETRANGEMIN, 0, 0);
+    wh::send_message(track, TBM_SETRANGEMAX, 1, positions as LPARAM);
+}
+
+/// Resizes the trackbar and the text input to fill the control. The text input keeps its width.
+fn layout(track: HWND, edit: HWND, width: i32, height: i32) {
+    use winapi::um::winuser::{GetWindowRect, SetWindowPos, SWP_NOZORDER, SWP_NOACTIVATE};
+    use winapi::shared::windef::RECT;
+    use crate::win32::high_dpi::logical_to_physical;
+
+    unsafe {
+        let mut rect: RECT = std::mem::zeroed();
+        GetWindowRect(edit, &mut rect);
+
+        let edit_width = i32::min(rect.right - rect.left, width);
+        let spacing = logical_to_physical(SPACING, 0).0;
+        let track_width = i32::max(width - edit_width - spacing, 0);
+
+        SetWindowPos(track, std::ptr::null_mut(), 0, 0, track_width, height, SWP_NOZORDER | SWP_NOACTIVATE);
+        SetWindowPos(edit, std::ptr::null_mut(), width - edit_width, 0, edit_width, height, SWP_NOZORDER | SWP_NOACTIVATE);
+    }
+}
+
+/// Sends a `NMNUMBERSLIDER` notification to the parent of the slider
+fn notify_value(handle: HWND, value: f64) {
+    use winapi::um::winuser::{NMHDR, WM_NOTIFY};
+
+    let notification = NMNUMBERSLIDER {
+        hdr: NMHDR { hwndFrom: handle, idFrom: 0, code: NUMBER_SLIDER_VALUE_CHANGED },
+        value,
+    };
+
+    let parent = wh::get_window_parent(handle);
+    wh::send_message(parent, WM_NOTIFY, 0, &notification as *const NMNUMBERSLIDER as _);
+}
@@ -0,0 +1,897 @@
+use winapi::shared::windef::{HWND, HFONT, RECT};
+use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_BORDER, WS_CHILD, WS_CLIPCHILDREN, WS_CLIPSIBLINGS};
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
+use crate::win32::base_helper::{check_hwnd, to_utf16};
+use crate::win32::high_dpi::physical_to_logical;
+use crate::win32::paint_helper as ph;
+use crate::win32::panel_stack::{NMPANELSTACK, PANEL_STACK_REORDER};
+use crate::{Font, NwgError, RawEventHandler, unbind_raw_event_handler};
+use super::{ControlBase, ControlHandle};
+use std::{mem, ptr, rc::Rc, cell::RefCell, time::{Duration, Instant}};
+
+#[cfg(feature = "settings")]
+use crate::{RegistrySettings, SettingValue};
+
+const NOT_BOUND: &'static str = "PanelStack is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: PanelStack handle is not HWND!";
+
+// Sizes in logical pixels
+const HEADER_HEIGHT: i32 = 24;
+const SPACING: i32 = 4;
+const PADDING: i32 = 8;
+const GRIP_WIDTH: i32 = 10;
+
+/// Id of the timer that moves the panels to their new place
+const SLIDE_TIMER_ID: u32 = 0x57AC;
+
+/// Interval between two frames of the slide animation, in milliseconds
+const SLIDE_INTERVAL: u32 = 15;
+
+/// Duration of the slide animation
+const SLIDE_DURATION: Duration = Duration::from_millis(150);
+
+
+bitflags! {
+    /**
+        The panel stack flags
+
+        * NONE:     No flags. Equivalent to a invisible control.
+        * VISIBLE:  The stack is immediatly visible after creation
+        * DISABLED: The panels cannot be moved by the user
+        * BORDER:   The control has a thin border
+    */
+    pub struct PanelStackFlags: u32 {
+        const NONE = 0;
+        const VISIBLE = WS_VISIBLE;
+        const DISABLED = WS_DISABLED;
+        const BORDER = WS_BORDER;
+    }
+}
+
+/// A move of a panel to its new place, in physical pixels
+#[derive(Copy, Clone)]
+struct Slide {
+    from: i32,
+    to: i32,
+    start: Instant,
+}
+
+struct Panel {
+    key: String,
+    title: String,
+    control: HWND,
+    /// Height of the control in physical pixels
+    height: i32,
+    /// Top of the header in physical pixels
+    top: i32,
+    slide: Option<Slide>,
+}
+
+#[derive(Copy, Clone)]
+struct DragState {
+    index: usize,
+    start_index: usize,
+    /// Distance between the mouse and the top of the dragged header
+    grab: i32,
+    start_y: i32,
+    moved: bool,
+}
+
+struct PanelStackState {
+    panels: Vec<Panel>,
+    font: HFONT,
+    animated: bool,
+    hover: Option<usize>,
+    drag: Option<DragState>,
+    tracking_mouse: bool,
+}
+
+impl Default for PanelStackState {
+    fn default() -> PanelStackState {
+        PanelStackState {
+            panels: Vec::new(),
+            font: ptr::null_mut(),
+            animated: true,
+            hover: None,
+            drag: None,
+            tracking_mouse: false,
+        }
+    }
+}
+
+/**
+A vertical stack of panels, each with a header bar. The user reorders the panels by dragging their header:
+the other panels slide out of the way while the panel is moved, as in the dashboards.
+
+A panel is a window-like control (for example a `Frame`) built with the stack as its parent, and added to the stack with a key
+and a title. The stack places the control under its header and stretches it to the width of the stack. The control keeps its height.
+The panels are not scrolled: put the stack in a `ScrollPanel` sized with `content_height` if the panels do not fit.
+
+The order of the panels is identified by their keys. Save `order` when the application closes and give it back to `set_order`
+on the next launch to restore the dashboard of the user (`save_order` and `load_order` do it with a `RegistrySettings`).
+
+Requires the `panel-stack` feature.
+
+**Builder parameters:**
+  * `parent`:   **Required.** The control parent container.
+  * `size`:     The control size.
+  * `position`: The control position.
+  * `animated`: If the panels slide to their new place. Defaults to `true`.
+  * `font`:     The font of the panel titles.
+  * `flags`:    A combination of the PanelStackFlags values.
+
+**Control events:**
+  * `OnPanelReorder`: When the user drops a panel at a new place
+  * `MousePress(_)`: Generic mouse press events on the headers
+  * `OnMouseMove`: Generic mouse mouse event
+
+```rust
+use native_windows_gui as nwg;
+
+fn build_dashboard(stack: &mut nwg::PanelStack, sales: &mut nwg::Frame, news: &mut nwg::Frame, window: &nwg::Window) {
+    nwg::PanelStack::builder()
+        .size((400, 600))
+        .parent(window)
+        .build(stack);
+
+    nwg::Frame::builder().size((400, 200)).parent(&stack.handle).build(sales);
+    nwg::Frame::builder().size((400, 150)).parent(&stack.handle).build(news);
+
+    stack.add_panel("sales", "Sales", sales);
+    stack.add_panel("news", "News", news);
+    stack.set_order(&["news", "sales"]);
+}
+```
+*/
+#[derive(Default)]
+pub struct PanelStack {
+    pub handle: ControlHandle,
+    state: Rc<RefCell<PanelStackState>>,
+    handler0: RefCell<Option<RawEventHandler>>,
+}
+
+impl PanelStack {
+
+    pub fn builder<'a>() -> PanelStackBuilder<'a> {
+        PanelStackBuilder {
+            size: (300, 400).into(),
+            position: (0, 0).into(),
+            animated: true,
+            font: None,
+            flags: None,
+            parent: None,
+        }
+    }
+
+    /// Adds a panel at the bottom of the stack. The control must be a window-like control whose parent is the stack.
+    /// Panics if `key` is already used by another panel, or if the control is not a window-like control.
+    pub fn add_panel<'a, C: Into<ControlHandle>>(&self, key: &'a str, title: &'a str, control: C) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let control = control.into().hwnd().expect("Panel control to be a window-like control");
+
+        {
+            let mut state = self.state.borrow_mut();
+            if state.panels.iter().any(|p| p.key == key) {
+                panic!("Panel key {:?} is already used", key);
+            }
+
+            let (_, height) = window_size(control);
+            let top = slot_tops(&state).last().copied().unwrap_or(0);
+            state.panels.push(Panel { key: key.to_string(), title: title.to_string(), control, height, top, slide: None });
+            cancel_drag(&mut state);
+            update_layout(handle, &mut state, false);
+        }
+
+        self.refresh(handle);
+    }
+
+    /// Removes a panel from the stack. The control is not hidden nor destroyed. Returns `false` if there is no panel with this key.
+    pub fn remove_panel<'a>(&self, key: &'a str) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        {
+            let mut state = self.state.borrow_mut();
+            let index = match state.panels.iter().position(|p| p.key == key) {
+                Some(i) => i,
+                None => { return false; }
+            };
+
+            state.panels.remove(index);
+            state.hover = None;
+            cancel_drag(&mut state);
+
+            let animated = state.animated;
+            update_layout(handle, &mut state, animated);
+        }
+
+        self.refresh(handle);
+        true
+    }
+
+    /// Returns the number of panels
+    pub fn panel_count(&self) -> usize {
+        self.state.borrow().panels.len()
+    }
+
+    /// Returns the control of a panel
+    pub fn panel_control<'a>(&self, key: &'a str) -> Option<ControlHandle> {
+        self.state.borrow().panels.iter()
+            .find(|p| p.key == key)
+            .map(|p| ControlHandle::Hwnd(p.control))
+    }
+
+    /// Returns the title of a panel
+    pub fn panel_title<'a>(&self, key: &'a str) -> Option<String> {
+        self.state.borrow().panels.iter()
+            .find(|p| p.key == key)
+            .map(|p| p.title.clone())
+    }
+
+    /// Sets the title of a panel. Does nothing if there is no panel with this key.
+    pub fn set_panel_title<'a>(&self, key: &'a str, title: &'a str) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        if let Some(panel) = self.state.borrow_mut().panels.iter_mut().find(|p| p.key == key) {
+            panel.title = title.to_string();
+        }
+
+        ph::redraw(handle);
+    }
+
+    /// Sets the height of the control of a panel, in logical pixels. The panels below slide to make room.
+    /// Does nothing if there is no panel with this key.
+    pub fn set_panel_height<'a>(&self, key: &'a str, height: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        {
+            let mut state = self.state.borrow_mut();
+            match state.panels.iter_mut().find(|p| p.key == key) {
+                Some(panel) => { panel.height = ph::px(height as i32); },
+                None => { return; }
+            }
+
+            let animated = state.animated;
+            update_layout(handle, &mut state, animated);
+        }
+
+        self.refresh(handle);
+    }
+
+    /// Returns the keys of the panels, from the top to the bottom of the stack
+    pub fn order(&self) -> Vec<String> {
+        self.state.borrow().panels.iter().map(|p| p.key.clone()).collect()
+    }
+
+    /// Sorts the panels in the order of `keys`. The keys without a panel are ignored,
+    /// and the panels missing from `keys` keep their relative order after the other panels. Does not raise `OnPanelReorder`.
+    pub fn set_order<S: AsRef<str>>(&self, keys: &[S]) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        {
+            let mut state = self.state.borrow_mut();
+            let rank = |key: &str| keys.iter().position(|k| k.as_ref() == key).unwrap_or(keys.len());
+            state.panels.sort_by_key(|p| rank(&p.key));
+            cancel_drag(&mut state);
+
+            let animated = state.animated;
+            update_layout(handle, &mut state, animated);
+        }
+
+        self.refresh(handle);
+    }
+
+    /// Saves the order of the panels in the settings store, under `name`
+    #[cfg(feature = "settings")]
+    pub fn save_order<'a>(&self, store: &RegistrySettings, name: &'a str) -> Result<(), NwgError> {
+        store.write(name, &SettingValue::Text(self.order().join("\n")))
+    }
+
+    /// Restores the order of the panels saved with `save_order`. Does nothing if there is no saved order.
+    #[cfg(feature = "settings")]
+    pub fn load_order<'a>(&self, store: &RegistrySettings, name: &'a str) {
+        if let Some(SettingValue::Text(order)) = store.read(name) {
+            let keys: Vec<&str> = order.lines().collect();
+            self.set_order(&keys);
+        }
+    }
+
+    /// Returns the height needed to display all the panels, in logical pixels
+    pub fn content_height(&self) -> u32 {
+        let state = self.state.borrow();
+        let height = slot_tops(&state).last().map(|&h| h - ph::px(SPACING)).unwrap_or(0);
+        unsafe { physical_to_logical(0, height.max(0)).1 as u32 }
+    }
+
+    /// Returns `true` if the panels slide to their new place
+    pub fn animated(&self) -> bool {
+        self.state.borrow().animated
+    }
+
+    /// Enables or disables the slide animation of the panels
+    pub fn set_animated(&self, v: bool) {
+        self.state.borrow_mut().animated = v;
+    }
+
+    /// Returns the font of the control
+    pub fn font(&self) -> Option<Font> {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let font_handle = wh::get_window_font(handle);
+        if font_handle.is_null() {
+            None
+        } else {
+            Some(Font { handle: font_handle })
+        }
+    }
+
+    /// Sets the font of the control
+    pub fn set_font(&self, font: Option<&Font>) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_font(handle, font.map(|f| f.handle), true); }
+    }
+
+    /// Returns true if the control user can interact with the control, return false otherwise
+    pub fn enabled(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_enabled(handle) }
+    }
+
+    /// Enable or disable the control
+    pub fn set_enabled(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_enabled(handle, v) }
+    }
+
+    /// Returns true if the control is visible to the user. Will return true even if the
+    /// control is outside of the parent client view (ex: at the position (10000, 10000))
+    pub fn visible(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_visibility(handle) }
+    }
+
+    /// Show or hide the control to the user
+    pub fn set_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_visibility(handle, v) }
+    }
+
+    /// Returns the size of the control in the parent window
+    pub fn size(&self) -> (u32, u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_size(handle) }
+    }
+
+    /// Sets the size of the control in the parent window
+    pub fn set_size(&self, x: u32, y: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_size(handle, x, y, false) }
+    }
+
+    /// Returns the position of the control in the parent window
+    pub fn position(&self) -> (i32, i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_position(handle) }
+    }
+
+    /// Sets the position of the control in the parent window
+    pub fn set_position(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_position(handle, x, y) }
+    }
+
+    /// Winapi class name used during control creation
+    pub fn class_name(&self) -> &'static str {
+        "NWG_PANEL_STACK"
+    }
+
+    /// Winapi base flags used during window creation
+    pub fn flags(&self) -> u32 {
+        WS_VISIBLE
+    }
+
+    /// Winapi flags required by the control
+    pub fn forced_flags(&self) -> u32 {
+        WS_CHILD | WS_CLIPCHILDREN | WS_CLIPSIBLINGS
+    }
+
+    /// Moves the controls to the place of their panel and redraws the headers
+    fn refresh(&self, handle: HWND) {
+        move_controls(handle, &self.state.borrow());
+        ph::redraw(handle);
+    }
+
+    /// Handles the painting of the headers, the drag and drop of the panels and the slide animation
+    fn hook_events(&self) {
+        use crate::bind_raw_event_handler_inner;
+        use winapi::um::winuser::{WM_PAINT, WM_ERASEBKGND, WM_SIZE, WM_SETFONT, WM_GETFONT, WM_ENABLE, WM_MOUSEMOVE, WM_MOUSELEAVE,
+            WM_LBUTTONDOWN, WM_LBUTTONUP, WM_CAPTURECHANGED, WM_TIMER, WM_SETCURSOR, SM_CYDRAG, TME_LEAVE, HTCLIENT, IDC_SIZENS, TRACKMOUSEEVENT};
+        use winapi::um::winuser::{TrackMouseEvent, SetCapture, ReleaseCapture, GetCapture, GetSystemMetrics, GetCursorPos, ScreenToClient,
+            SetCursor, LoadCursorW};
+        use winapi::shared::minwindef::{LOWORD, HIWORD};
+        use winapi::shared::windef::POINT;
+
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let state = self.state.clone();
+
+        let handler = bind_raw_event_handler_inner(&self.handle, 0, move |hwnd, msg, w, l| {
+            // Mouse coordinates are signed
+            let (x, y) = (LOWORD(l as u32) as i16 as i32, HIWORD(l as u32) as i16 as i32);
+
+            match msg {
+                WM_PAINT => {
+                    paint(hwnd, &state.borrow());
+                    return Some(0);
+                },
+                WM_ERASEBKGND => {
+                    // Everything is drawn in WM_PAINT
+                    return Some(1);
+                },
+                WM_SIZE => {
+                    if let Ok(mut state) = state.try_borrow_mut() {
+                        update_layout(hwnd, &mut state, false);
+                    }
+
+                    refresh(hwnd, &state);
+                },
+                WM_SETFONT => {
+                    state.borrow_mut().font = w as HFONT;
+                    if l != 0 {
+                        ph::redraw(hwnd);
+                    }
+                    return Some(0);
+                },
+                WM_GETFONT => {
+                    return Some(state.borrow().font as _);
+                },
+                WM_ENABLE => {
+                    ph::redraw(hwnd);
+                },
+                WM_TIMER if w == SLIDE_TIMER_ID as usize => {
+                    next_frame(hwnd, &mut state.borrow_mut());
+                    refresh(hwnd, &state);
+                    return Some(0);
+                },
+                WM_SETCURSOR if LOWORD(l as u32) as isize == HTCLIENT => {
+                    let over_header = unsafe {
+                        let mut point = POINT { x: 0, y: 0 };
+                        GetCursorPos(&mut point);
+                        ScreenToClient(hwnd, &mut point);
+                        header_hit_test(&state.borrow(), point.y).is_some()
+                    };
+
+                    if over_header && unsafe { wh::get_window_enabled(hwnd) } {
+                        unsafe { SetCursor(LoadCursorW(ptr::null_mut(), IDC_SIZENS)); }
+                        return Some(1);
+                    }
+                },
+                WM_MOUSEMOVE => {
+                    let mut state_mut = state.borrow_mut();
+                    let state_ref = &mut *state_mut;
+
+                    if !state_ref.tracking_mouse {
+                        let mut track = TRACKMOUSEEVENT {
+                            cbSize: mem::size_of::<TRACKMOUSEEVENT>() as u32,
+                            dwFlags: TME_LEAVE,
+                            hwndTrack: hwnd,
+                            dwHoverTime: 0,
+                        };
+                        state_ref.tracking_mouse = unsafe { TrackMouseEvent(&mut track) != 0 };
+                    }
+
+                    if let Some(mut drag) = state_ref.drag {
+                        let threshold = unsafe { GetSystemMetrics(SM_CYDRAG) };
+                        drag.moved |= (y - drag.start_y).abs() > threshold;
+                        state_ref.drag = Some(drag);
+
+                        if drag.moved {
+                            drag_panel(hwnd, state_ref, y);
+                            drop(state_mut);
+                            refresh(hwnd, &state);
+                        }
+                    } else {
+                        let hover = header_hit_test(state_ref, y);
+                        if hover != state_ref.hover {
+                            state_ref.hover = hover;
+                            ph::redraw(hwnd);
+                        }
+                    }
+                },
+                WM_MOUSELEAVE => {
+                    let mut state = state.borrow_mut();
+                    state.tracking_mouse = false;
+                    if state.hover.take().is_some() {
+                        ph::redraw(hwnd);
+                    }
+                },
+                WM_LBUTTONDOWN => {
+                    let mut state = state.borrow_mut();
+                    if let Some(index) = header_hit_test(&state, y) {
+                        let grab = y - state.panels[index].top;
+                        state.drag = Some(DragState { index, start_index: index, grab, start_y: y, moved: false });
+                        unsafe { SetCapture(hwnd); }
+                    }
+                },
+                WM_LBUTTONUP => {
+                    let reorder = drop_panel(hwnd, &mut state.borrow_mut());
+                    refresh(hwnd, &state);
+
+                    unsafe {
+                        if GetCapture() == hwnd {
+                            ReleaseCapture();
+                        }
+                    }
+
+                    // The state must not be borrowed when the events are sent because the user handler may access the panels
+                    if let Some((index, previous)) = reorder {
+                        notify(hwnd, PANEL_STACK_REORDER, index, previous);
+                    }
+                },
+                WM_CAPTURECHANGED => {
+                    // The capture is also released by the methods of the stack, while the state is borrowed
+                    let reorder = match state.try_borrow_mut() {
+                        Ok(mut state) => drop_panel(hwnd, &mut state),
+                        Err(_) => None
+                    };
+
+                    if let Some((index, previous)) = reorder {
+                        refresh(hwnd, &state);
+                        notify(hwnd, PANEL_STACK_REORDER, index, previous);
+                    }
+                },
+                _ => {}
+            }
+
+            None
+        });
+
+        *self.handler0.borrow_mut() = Some(handler.unwrap());
+    }
+
+}
+
+impl Drop for PanelStack {
+    fn drop(&mut self) {
+        let handler = self.handler0.borrow();
+        if let Some(h) = handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
+        self.handle.destroy();
+    }
+}
+
+impl PartialEq for PanelStack {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for PanelStack {}
+
+pub struct PanelStackBuilder<'a> {
+    size: Extent,
+    position: Extent,
+    animated: bool,
+    font: Option<&'a Font>,
+    flags: Option<PanelStackFlags>,
+    parent: Option<ControlHandle>
+}
+
+impl<'a> PanelStackBuilder<'a> {
+
+    pub fn flags(mut self, flags: PanelStackFlags) -> PanelStackBuilder<'a> {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> PanelStackBuilder<'a> {
+        self.size = size.into();
+        self
+    }
+
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> PanelStackBuilder<'a> {
+        self.position = pos.into();
+        self
+    }
+
+    pub fn animated(mut self, animated: bool) -> PanelStackBuilder<'a> {
+        self.animated = animated;
+        self
+    }
+
+    pub fn font(mut self, font: Option<&'a Font>) -> PanelStackBuilder<'a> {
+        self.font = font;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> PanelStackBuilder<'a> {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut PanelStack) -> Result<(), NwgError> {
+        let flags = self.flags.map(|f| f.bits()).unwrap_or(out.flags());
+
+        let parent = match self.parent {
+            Some(p) => Ok(p),
+            None => Err(NwgError::no_parent("PanelStack"))
+        }?;
+
+        *out = Default::default();
+
+        out.handle = ControlBase::build_hwnd()
+            .class_name(out.class_name())
+            .forced_flags(out.forced_flags())
+            .flags(flags)
+            .size(self.size)
+            .position(self.position)
+            .parent(Some(parent))
+            .build()?;
+
+        out.state.borrow_mut().animated = self.animated;
+        out.hook_events();
+
+        if self.font.is_some() {
+            out.set_font(self.font);
+        } else {
+            out.set_font(Font::global_default().as_ref());
+        }
+
+        Ok(())
+    }
+
+}
+
+/**
+    Moves the controls to the place of their panel and redraws the headers. Moving a control sends messages to its
+    event handlers, so the state is only borrowed to read it. Does nothing if the state is being modified.
+*/
+fn refresh(handle: HWND, state: &RefCell<PanelStackState>) {
+    if let Ok(state) = state.try_borrow() {
+        move_controls(handle, &state);
+        ph::redraw(handle);
+    }
+}
+
+/// Sends a `NMPANELSTACK` notification to the parent of the stack
+fn notify(handle: HWND, code: u32, index: usize, previous: usize) {
+    use winapi::um::winuser::{NMHDR, WM_NOTIFY};
+
+    let notification = NMPANELSTACK {
+        hdr: NMHDR { hwndFrom: handle, idFrom: 0, code },
+        index,
+        previous,
+    };
+
+    let parent = wh::get_window_parent(handle);
+    wh::send_message(parent, WM_NOTIFY, 0, &notification as *const NMPANELSTACK as _);
+}
+
+/// Returns the size of a window in physical pixels
+fn window_size(handle: HWND) -> (i32, i32) {
+    use winapi::um::winuser::GetWindowRect;
+
+    unsafe {
+        let mut rect: RECT = mem::zeroed();
+        GetWindowRect(handle, &mut rect);
+        (rect.right - rect.left, rect.bottom - rect.top)
+    }
+}
+
+/// Height of a panel and its header in physical pixels
+fn extent(panel: &Panel) -> i32 {
+    ph::px(HEADER_HEIGHT) + panel.height
+}
+
+/// Returns the top of each panel in the stack order, followed by the bottom of the last panel plus the spacing
+fn slot_tops(state: &PanelStackState) -> Vec<i32> {
+    let mut tops = Vec::with_capacity(state.panels.len() + 1);
+    let mut top = 0;
+    tops.push(top);
+
+    for panel in state.panels.iter() {
+        top += extent(panel) + ph::px(SPACING);
+        tops.push(top);
+    }
+
+    tops
+}
+
+/// Returns the index of the panel whose header is at `y`. The dragged panel is over the others.
+fn header_hit_test(state: &PanelStackState, y: i32) -> Option<usize> {
+    let in_header = |i: &usize| {
+        let top = state.panels[*i].top;
+        y >= top && y < top + ph::px(HEADER_HEIGHT)
+    };
+
+    if let Some(drag) = state.drag {
+        if in_header(&drag.index) {
+            return Some(drag.index);
+        }
+    }
+
+    (0..state.panels.len()).find(in_header)
+}
+
+fn cancel_drag(state: &mut PanelStackState) {
+    use winapi::um::winuser::ReleaseCapture;
+
+    if state.drag.take().is_some() {
+        unsafe { ReleaseCapture(); }
+    }
+}
+
+/**
+    Moves the panels to their slot. If `animated` is true, the panels slide from their current place.
+    The dragged panel stays under the mouse. The controls are moved by `refresh`.
+*/
+fn update_layout(handle: HWND, state: &mut PanelStackState, animated: bool) {
+    let tops = slot_tops(state);
+    let dragged = state.drag.filter(|d| d.moved).map(|d| d.index);
+    let now = Instant::now();
+
+    for (i, panel) in state.panels.iter_mut().enumerate() {
+        if Some(i) == dragged {
+            continue;
+        }
+
+        let target = tops[i];
+        match animated {
+            true if panel.slide.map(|s| s.to) != Some(target) && panel.top != target => {
+                panel.slide = Some(Slide { from: panel.top, to: target, start: now });
+            },
+            true => {},
+            false => {
+                panel.top = target;
+                panel.slide = None;
+            }
+        }
+    }
+
+    if state.panels.iter().any(|p| p.slide.is_some()) {
+        wh::start_timer(handle, SLIDE_TIMER_ID, SLIDE_INTERVAL);
+    }
+}
+
+/// Places the controls of the panels under their header
+fn move_controls(handle: HWND, state: &PanelStackState) {
+    use winapi::um::winuser::{SetWindowPos, HWND_TOP, SWP_NOZORDER, SWP_NOACTIVATE};
+
+    let (width, _) = ph::client_size(handle);
+    let dragged = state.drag.filter(|d| d.moved).map(|d| d.index);
+
+    for (i, panel) in state.panels.iter().enumerate() {
+        let flags = match Some(i) == dragged {
+            true => SWP_NOACTIVATE,
+            false => SWP_NOZORDER | SWP_NOACTIVATE,
+        };
+
+        unsafe { SetWindowPos(panel.control, HWND_TOP, 0, panel.top + ph::px(HEADER_HEIGHT), width, panel.height, flags); }
+    }
+}
+
+/// Advances the slide animation of the panels
+fn next_frame(handle: HWND, state: &mut PanelStackState) {
+    let duration = SLIDE_DURATION.as_secs_f64();
+
+    for panel in state.panels.iter_mut() {
+        if let Some(slide) = panel.slide {
+            let t = slide.start.elapsed().as_secs_f64() / duration;
+            if t >= 1.0 {
+                panel.top = slide.to;
+                panel.slide = None;
+            } else {
+                let progress = 1.0 - (1.0 - t).powi(3);
+                panel.top = slide.from + ((slide.to - slide.from) as f64 * progress).round() as i32;
+            }
+        }
+    }
+
+    if state.panels.iter().all(|p| p.slide.is_none()) {
+        wh::kill_timer(handle, SLIDE_TIMER_ID);
+    }
+}
+
+/// Moves the dragged panel under the mouse and swaps it with its neighbours when its middle passes theirs
+fn drag_panel(handle: HWND, state: &mut PanelStackState, y: i32) {
+    let mut drag = match state.drag {
+        Some(drag) => drag,
+        None => { return; }
+    };
+
+    let tops = slot_tops(state);
+    let bottom = tops.last().map(|&b| b - ph::px(SPACING)).unwrap_or(0);
+    let height = extent(&state.panels[drag.index]);
+
+    let top = (y - drag.grab).min(bottom - height).max(0);
+    let middle = top + height / 2;
+    state.panels[drag.index].top = top;
+    state.panels[drag.index].slide = None;
+
+    let neighbour_middle = |state: &PanelStackState, i: usize| slot_tops(state)[i] + extent(&state.panels[i]) / 2;
+    loop {
+        let i = drag.index;
+        if i > 0 && middle < neighbour_middle(state, i - 1) {
+            state.panels.swap(i, i - 1);
+            drag.index = i - 1;
+        } else if i + 1 < state.panels.len() && middle > neighbour_middle(state, i + 1) {
+            state.panels.swap(i, i + 1);
+            drag.index = i + 1;
+        } else {
+            break;
+        }
+    }
+
+    state.drag = Some(drag);
+    let animated = state.animated;
+    update_layout(handle, state, animated);
+}
+
+/// Ends the drag of a panel. Returns the new index and the previous index of the panel if it was moved.
+fn drop_panel(handle: HWND, state: &mut PanelStackState) -> Option<(usize, usize)> {
+    let drag = state.drag.take()?;
+
+    let animated = state.animated;
+    update_layout(handle, state, animated);
+
+    match drag.moved && drag.index != drag.start_index {
+        true => Some((drag.index, drag.start_index)),
+        false => None
+    }
+}
+
+/// Paints the headers in a memory bitmap and copies the result on the screen
+fn paint(handle: HWND, state: &PanelStackState) {
+    use winapi::um::winuser::{FillRect, GetSysColorBrush, GetSysColor, DrawTextW,
+        COLOR_BTNFACE, COLOR_3DLIGHT, COLOR_BTNSHADOW, COLOR_HIGHLIGHT, COLOR_BTNTEXT, COLOR_GRAYTEXT,
+        DT_LEFT, DT_SINGLELINE, DT_VCENTER, DT_END_ELLIPSIS, DT_NOPREFIX};
+    use winapi::um::wingdi::{SetBkMode, SetTextColor, TRANSPARENT};
+
+    ph::paint_buffered(handle, ph::font_or(state.font, ph::gui_font()), |buffer_dc, width, height| unsafe {
+        let enabled = wh::get_window_enabled(handle);
+
+        FillRect(buffer_dc, &RECT { left: 0, top: 0, right: width, bottom: height }, GetSysColorBrush(COLOR_BTNFACE));
+
+        SetBkMode(buffer_dc, TRANSPARENT as i32);
+        SetTextColor(buffer_dc, GetSysColor(if enabled { COLOR_BTNTEXT } else { COLOR_GRAYTEXT }));
+
+        // The dragged header is painted last to stay over the others
+        let dragged = state.drag.filter(|d| d.moved).map(|d| d.index);
+        let order = (0..state.panels.len()).filter(|&i| Some(i) != dragged).chain(dragged);
+
+        for index in order {
+            let panel = &state.panels[index];
+            let header = RECT { left: 0, top: panel.top, right: width, bottom: panel.top + ph::px(HEADER_HEIGHT) };
+
+            FillRect(buffer_dc, &header, GetSysColorBrush(COLOR_3DLIGHT));
+
+            if Some(index) == dragged || (state.hover == Some(index) && enabled) {
+                let accent = RECT { right: ph::px(2), ..header };
+                FillRect(buffer_dc, &accent, GetSysColorBrush(COLOR_HIGHLIGHT));
+            }
+
+            let separator = RECT { top: header.bottom - 1, ..header };
+            FillRect(buffer_dc, &separator, GetSysColorBrush(COLOR_BTNSHADOW));
+
+            draw_grip(buffer_dc, &header);
+
+            let mut text_rect = RECT { left: ph::px(PADDING + GRIP_WIDTH + PADDING), right: width - ph::px(PADDING), ..header };
+            let title = to_utf16(&panel.title);
+            DrawTextW(buffer_dc, title.as_ptr(), -1, &mut text_rect, DT_LEFT | DT_SINGLELINE | DT_VCENTER | DT_END_ELLIPSIS | DT_NOPREFIX);
+        }
+    });
+}
+
+/// Draws the three lines of the grip at the left of a header
+unsafe fn draw_grip(dc: winapi::shared::windef::HDC, header: &RECT) {
+    use winapi::um::winuser::{FillRect, GetSysColorBrush, COLOR_BTNSHADOW};
+
+    let middle = (header.top + header.bottom) / 2;
+    let gap = ph::px(3);
+
+    for i in -1..=1 {
+        let y = middle + i * gap;
+        let line = RECT { left: ph::px(PADDING), top: y, right: ph::px(PADDING + GRIP_WIDTH), bottom: y + ph::px(1).max(1) };
+        FillRect(dc, &line, GetSysColorBrush(COLOR_BTNSHADOW));
+    }
+}
@@ -1,5 +1,6 @@
 use winapi::um::winuser::{WS_CHILD, WS_VISIBLE, WS_CLIPCHILDREN, WS_CLIPSIBLINGS};
 use crate::win32::base_helper::check_hwnd;
+use crate::win32::high_dpi::Extent;
 use crate::win32::window_helper as wh;
 use crate::NwgError;
 use super::{ControlBase, ControlHandle};
@@ -73,8 +74,8 @@ impl Plotters {
 
     pub fn builder() -> PlottersBuilder {
         PlottersBuilder {
-            size: (500, 500),
-            position: (0, 0),
+            size: (500, 500).into(),
+            position: (0, 0).into(),
             ex_flags: 0,
             parent: None,
         }
@@ -180,8 +181,8 @@ impl PartialEq for Plotters {
 
 pub struct PlottersBuilder {
     parent: Option<ControlHandle>,
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     ex_flags: u32,
 }
 
@@ -192,13 +193,13 @@ impl PlottersBuilder {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> PlottersBuilder {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> PlottersBuilder {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> PlottersBuilder {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> PlottersBuilder {
+        self.position = pos.into();
         self
     }
 
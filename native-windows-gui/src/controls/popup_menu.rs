@@ -0,0 +1,231 @@
+use winapi::shared::windef::{HMENU, HWND};
+use crate::win32::menu as mh;
+use crate::win32::base_helper::to_utf16;
+use crate::{NwgError, Icon};
+use super::{ControlHandle, PopupMenuFlags};
+use std::ptr;
+
+
+#[derive(Clone, Debug)]
+enum MenuEntryKind<'a> {
+    Item { id: u32 },
+    Separator,
+    Submenu { entries: Vec<MenuEntry<'a>> },
+}
+
+/**
+    An entry of a `PopupMenu`: an item, a separator or a submenu. Entries are created with `item`, `separator` and `submenu`,
+    then configured with the chained setters.
+
+    The `id` of an item is chosen by the application and returned by `PopupMenu::popup` when the user selects the item.
+*/
+#[derive(Clone, Debug)]
+pub struct MenuEntry<'a> {
+    kind: MenuEntryKind<'a>,
+    text: &'a str,
+    shortcut: Option<&'a str>,
+    icon: Option<&'a Icon>,
+    enabled: bool,
+    checked: bool,
+}
+
+impl<'a> MenuEntry<'a> {
+
+    /// An item identified by `id`. The text can contain an access key (ex: "&Copy").
+    pub fn item(id: u32, text: &'a str) -> MenuEntry<'a> {
+        MenuEntry { kind: MenuEntryKind::Item { id }, text, shortcut: None, icon: None, enabled: true, checked: false }
+    }
+
+    /// A separator line
+    pub fn separator() -> MenuEntry<'a> {
+        MenuEntry { kind: MenuEntryKind::Separator, text: "", shortcut: None, icon: None, enabled: true, checked: false }
+    }
+
+    /// A submenu containing `entries`
+    pub fn submenu(text: &'a str, entries: Vec<MenuEntry<'a>>) -> MenuEntry<'a> {
+        MenuEntry { kind: MenuEntryKind::Submenu { entries }, text, shortcut: None, icon: None, enabled: true, checked: false }
+    }
+
+    /// A shortcut label displayed at the right of the item (ex: "Ctrl+C"). The keyboard handling must be done through other means.
+    pub fn shortcut(mut self, shortcut: &'a str) -> MenuEntry<'a> {
+        self.shortcut = Some(shortcut);
+        self
+    }
+
+    /// An icon displayed next to the text of the item. Ignored for the submenus.
+    pub fn icon(mut self, icon: Option<&'a Icon>) -> MenuEntry<'a> {
+        self.icon = icon;
+        self
+    }
+
+    /// If the user can select the entry
+    pub fn enabled(mut self, enabled: bool) -> MenuEntry<'a> {
+        self.enabled = enabled;
+        self
+    }
+
+    /// If the item has a check mark next to it
+    pub fn checked(mut self, checked: bool) -> MenuEntry<'a> {
+        self.checked = checked;
+        self
+    }
+
+    fn full_text(&self) -> String {
+        match self.shortcut {
+            Some(shortcut) => format!("{}\t{}", self.text, shortcut),
+            None => self.text.to_string()
+        }
+    }
+
+}
+
+/**
+    A context menu built from a list of `MenuEntry`. Unlike a `Menu` built with `popup(true)`, the selection does not raise an event:
+    `popup` blocks until the menu is closed and returns the id of the selected item.
+
+    Items with an icon are owner drawn: the parent window passed to `popup` must have an event handler bound.
+    The icons must live as long as the popup menu.
+
+    Requires the `menu` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+    use nwg::MenuEntry;
+
+    const CUT: u32 = 1;
+    const COPY: u32 = 2;
+    const PASTE: u32 = 3;
+    const PASTE_TEXT: u32 = 4;
+
+    fn context_menu(window: &nwg::Window, can_paste: bool) {
+        let menu = nwg::PopupMenu::from_items(&[
+            MenuEntry::item(CUT, "Cu&t").shortcut("Ctrl+X"),
+            MenuEntry::item(COPY, "&Copy").shortcut("Ctrl+C"),
+            MenuEntry::separator(),
+            MenuEntry::submenu("&Paste", vec![
+                MenuEntry::item(PASTE, "&Paste").shortcut("Ctrl+V"),
+                MenuEntry::item(PASTE_TEXT, "Paste as &text"),
+            ]).enabled(can_paste),
+        ]).expect("Failed to build the menu");
+
+        let (x, y) = nwg::GlobalCursor::position();
+        match menu.popup(window, x, y) {
+            Some(COPY) => { /* copy */ },
+            Some(_) => { /* ... */ },
+            None => { /* the menu was closed without a selection */ }
+        }
+    }
+    ```
+*/
+pub struct PopupMenu {
+    handle: HMENU,
+    /// The id of the application for each item. The id of the item in the menu is the index + 1, because 0 means "no selection".
+    ids: Vec<u32>,
+}
+
+impl PopupMenu {
+
+    /// Builds a popup menu from a list of entries
+    pub fn from_items(entries: &[MenuEntry]) -> Result<PopupMenu, NwgError> {
+        use winapi::um::winuser::CreatePopupMenu;
+
+        let handle = unsafe { CreatePopupMenu() };
+        if handle.is_null() {
+            return Err(NwgError::menu_create("Popup menu creation failed"));
+        }
+
+        let mut menu = PopupMenu { handle, ids: Vec::new() };
+        menu.append_entries(handle, entries)?;
+
+        Ok(menu)
+    }
+
+    /// Shows the menu at a position in screen coordinates. Returns the id of the selected item, or `None` if the menu was closed without selection.
+    pub fn popup<C: Into<ControlHandle>>(&self, parent: C, x: i32, y: i32) -> Option<u32> {
+        self.popup_with_flags(parent, x, y, PopupMenuFlags::empty())
+    }
+
+    /// Shows the menu at a position in screen coordinates using custom flags. Returns the id of the selected item, or `None` if the menu was closed without selection.
+    pub fn popup_with_flags<C: Into<ControlHandle>>(&self, parent: C, x: i32, y: i32, flags: PopupMenuFlags) -> Option<u32> {
+        use winapi::um::winuser::{TrackPopupMenu, SetForegroundWindow, PostMessageW, TPM_RETURNCMD, TPM_NONOTIFY, WM_NULL};
+
+        let parent: HWND = parent.into().hwnd().expect("Popup menu parent must be a window-like control");
+
+        let command = unsafe {
+            // The menu is not closed when the user clicks outside of it if the window is not in the foreground
+            SetForegroundWindow(parent);
+            let command = TrackPopupMenu(self.handle, flags.bits() | TPM_RETURNCMD | TPM_NONOTIFY, x, y, 0, parent, ptr::null());
+            PostMessageW(parent, WM_NULL, 0, 0);
+            command
+        };
+
+        match command {
+            0 => None,
+            c => self.ids.get(c as usize - 1).cloned()
+        }
+    }
+
+    fn append_entries(&mut self, menu: HMENU, entries: &[MenuEntry]) -> Result<(), NwgError> {
+        use winapi::um::winuser::{AppendMenuW, CreatePopupMenu, MF_STRING, MF_POPUP, MF_SEPARATOR, MF_GRAYED, MF_CHECKED};
+
+        for entry in entries {
+            let text = to_utf16(&entry.full_text());
+            let mut flags = MF_STRING;
+            if !entry.enabled { flags |= MF_GRAYED; }
+            if entry.checked { flags |= MF_CHECKED; }
+
+            match &entry.kind {
+                MenuEntryKind::Separator => unsafe {
+                    AppendMenuW(menu, MF_SEPARATOR, 0, ptr::null());
+                },
+                MenuEntryKind::Submenu { entries } => {
+                    let submenu = unsafe { CreatePopupMenu() };
+                    if submenu.is_null() {
+                        return Err(NwgError::menu_create("Popup menu creation failed"));
+                    }
+
+                    // The submenu is destroyed with its parent
+                    unsafe { AppendMenuW(menu, flags | MF_POPUP, submenu as usize, text.as_ptr()); }
+                    self.append_entries(submenu, entries)?;
+                },
+                MenuEntryKind::Item { id } => {
+                    self.ids.push(*id);
+                    let command = self.ids.len() as u32;
+                    unsafe { AppendMenuW(menu, flags, command as usize, text.as_ptr()); }
+
+                    if let Some(icon) = entry.icon {
+                        let mut style = mh::MenuItemStyle::new(&entry.full_text(), false);
+                        style.icon = icon.handle as _;
+                        unsafe { mh::set_menu_item_style(menu, command, style); }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn free_styles(menu: HMENU) {
+        use winapi::um::winuser::{GetMenuItemCount, GetMenuItemID, GetSubMenu};
+
+        unsafe {
+            for i in 0..GetMenuItemCount(menu).max(0) {
+                let submenu = GetSubMenu(menu, i);
+                match submenu.is_null() {
+                    true => mh::free_menu_item_style(menu, GetMenuItemID(menu, i)),
+                    false => Self::free_styles(submenu)
+                }
+            }
+        }
+    }
+
+}
+
+impl Drop for PopupMenu {
+    fn drop(&mut self) {
+        use winapi::um::winuser::DestroyMenu;
+
+        Self::free_styles(self.handle);
+        unsafe { DestroyMenu(self.handle); }
+    }
+}
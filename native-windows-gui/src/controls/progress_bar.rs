@@ -6,6 +6,7 @@ that indicates what the button does when the user selects it.
 use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED};
 use winapi::um::commctrl::{PBS_MARQUEE, PBS_VERTICAL};
 use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
 use crate::win32::base_helper::check_hwnd;
 use crate::NwgError;
 use super::{ControlHandle, ControlBase};
@@ -78,8 +79,8 @@ impl ProgressBar {
 
     pub fn builder() -> ProgressBarBuilder {
         ProgressBarBuilder {
-            size: (100, 40),
-            position: (0, 0),
+            size: (100, 40).into(),
+            position: (0, 0).into(),
             flags: None,
             ex_flags: 0,
             state: ProgressBarState::Normal,
@@ -311,8 +312,8 @@ impl Drop for ProgressBar {
 }
 
 pub struct ProgressBarBuilder {
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     flags: Option<ProgressBarFlags>,
     ex_flags: u32,
     state: ProgressBarState,
@@ -336,13 +337,13 @@ impl ProgressBarBuilder {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> ProgressBarBuilder {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> ProgressBarBuilder {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> ProgressBarBuilder {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> ProgressBarBuilder {
+        self.position = pos.into();
         self
     }
 
@@ -4,8 +4,10 @@ use winapi::um::{
 };
 use winapi::shared::windef::HBRUSH;
 use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
 use crate::win32::base_helper::check_hwnd;
 use crate::{Font, NwgError, RawEventHandler, unbind_raw_event_handler};
+use crate::win32::resource_tracker::{self as rt, ResourceKind};
 use super::{ControlBase, ControlHandle};
 use std::cell::RefCell;
 
@@ -120,8 +122,8 @@ impl RadioButton {
     pub fn builder<'a>() -> RadioButtonBuilder<'a> {
         RadioButtonBuilder {
             text: "A radio button",
-            size: (100, 25),
-            position: (0, 0),
+            size: (100, 25).into(),
+            position: (0, 0).into(),
             focus: false,
             background_color: None,
             check_state: RadioButtonState::Unchecked,
@@ -280,6 +282,7 @@ impl RadioButton {
 
         let parent_handle = ControlHandle::Hwnd(wh::get_window_parent(handle));
         let brush = unsafe { CreateSolidBrush(RGB(c[0], c[1], c[2])) };
+        rt::track(ResourceKind::Brush, brush);
         self.background_brush = Some(brush);
         
         let handler = bind_raw_event_handler_inner(&parent_handle, handle as UINT_PTR, move |_hwnd, msg, _w, l| {
@@ -309,6 +312,7 @@ impl Drop for RadioButton {
         }
 
         if let Some(bg) = self.background_brush {
+            rt::untrack(bg);
             unsafe { DeleteObject(bg as _); }
         }
 
@@ -318,8 +322,8 @@ impl Drop for RadioButton {
 
 pub struct RadioButtonBuilder<'a> {
     text: &'a str,
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     focus: bool,
     background_color: Option<[u8; 3]>,
     check_state: RadioButtonState,
@@ -346,13 +350,13 @@ impl<'a> RadioButtonBuilder<'a> {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> RadioButtonBuilder<'a> {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> RadioButtonBuilder<'a> {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> RadioButtonBuilder<'a> {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> RadioButtonBuilder<'a> {
+        self.position = pos.into();
         self
     }
 
@@ -1,5 +1,6 @@
 use winapi::um::winuser::{WS_VISIBLE, ES_MULTILINE, WS_DISABLED, EM_SETSEL};
 use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
 use crate::win32::base_helper::check_hwnd;
 use crate::win32::richedit as rich;
 use crate::{Font, NwgError, RawEventHandler, HTextAlign, unbind_raw_event_handler};
@@ -86,8 +87,8 @@ impl RichLabel {
     pub fn builder<'a>() -> RichLabelBuilder<'a> {
         RichLabelBuilder {
             text: "A rich label",
-            size: (130, 25),
-            position: (0, 0),
+            size: (130, 25).into(),
+            position: (0, 0).into(),
             flags: None,
             ex_flags: 0,
             font: None,
@@ -369,8 +370,8 @@ impl Drop for RichLabel {
 
 pub struct RichLabelBuilder<'a> {
     text: &'a str,
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     flags: Option<RichLabelFlags>,
     ex_flags: u32,
     font: Option<&'a Font>,
@@ -387,13 +388,13 @@ impl<'a> RichLabelBuilder<'a> {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> RichLabelBuilder<'a> {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> RichLabelBuilder<'a> {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> RichLabelBuilder<'a> {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> RichLabelBuilder<'a> {
+        self.position = pos.into();
         self
     }
 
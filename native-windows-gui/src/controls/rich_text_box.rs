@@ -1,6 +1,7 @@
 use winapi::shared::minwindef::{WPARAM, LPARAM};
 use winapi::um::winuser::{ES_AUTOVSCROLL, ES_AUTOHSCROLL, WS_VISIBLE, WS_DISABLED, WS_TABSTOP, WS_VSCROLL, WS_HSCROLL};
 use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
 use crate::win32::base_helper::check_hwnd;
 use crate::win32::richedit as rich;
 use crate::{Font, NwgError};
@@ -267,8 +268,8 @@ impl RichTextBox {
     pub fn builder<'a>() -> RichTextBoxBuilder<'a> {
         RichTextBoxBuilder {
             text: "",
-            size: (100, 25),
-            position: (0, 0),
+            size: (100, 25).into(),
+            position: (0, 0).into(),
             flags: None,
             ex_flags: 0,
             limit: 0,
@@ -554,8 +555,8 @@ impl Drop for RichTextBox {
 }
 pub struct RichTextBoxBuilder<'a> {
     text: &'a str,
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     flags: Option<RichTextBoxFlags>,
     ex_flags: u32,
     limit: usize,
@@ -582,13 +583,13 @@ impl<'a> RichTextBoxBuilder<'a> {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> RichTextBoxBuilder<'a> {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> RichTextBoxBuilder<'a> {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> RichTextBoxBuilder<'a> {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> RichTextBoxBuilder<'a> {
+        self.position = pos.into();
         self
     }
 
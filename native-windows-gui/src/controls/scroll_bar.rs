@@ -1,5 +1,6 @@
 use winapi::um::winuser::{WS_DISABLED, WS_VISIBLE, WS_TABSTOP, WS_CHILD, SBS_HORZ, SBS_VERT};
 use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
 use crate::win32::base_helper::check_hwnd;
 use crate::{NwgError, RawEventHandler};
 use super::{ControlBase, ControlHandle};
@@ -76,8 +77,8 @@ impl ScrollBar {
 
     pub fn builder<'a>() -> ScrollBarBuilder {
         ScrollBarBuilder {
-            size: (25, 100),
-            position: (0, 0),
+            size: (25, 100).into(),
+            position: (0, 0).into(),
             enabled: true,
             flags: None,
             ex_flags: 0,
@@ -324,8 +325,8 @@ impl Drop for ScrollBar {
 }
 
 pub struct ScrollBarBuilder {
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     enabled: bool,
     flags: Option<ScrollBarFlags>,
     ex_flags: u32,
@@ -347,13 +348,13 @@ impl ScrollBarBuilder {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> ScrollBarBuilder {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> ScrollBarBuilder {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> ScrollBarBuilder {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> ScrollBarBuilder {
+        self.position = pos.into();
         self
     }
 
@@ -0,0 +1,540 @@
+use winapi::shared::windef::{HWND, RECT, POINT};
+use winapi::shared::minwindef::{WPARAM, LOWORD};
+use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_BORDER, WS_CHILD, WS_CLIPCHILDREN, WS_VSCROLL, WS_HSCROLL, WS_EX_CONTROLPARENT, SB_HORZ, SB_VERT};
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
+use crate::win32::base_helper::check_hwnd;
+use crate::win32::high_dpi::{logical_to_physical, physical_to_logical};
+use crate::{NwgError, RawEventHandler, unbind_raw_event_handler};
+use super::{ControlBase, ControlHandle};
+use std::{mem, ptr, rc::Rc, cell::RefCell};
+
+const NOT_BOUND: &'static str = "ScrollPanel is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: ScrollPanel handle is not HWND!";
+
+/// Number of logical pixels scrolled by the scrollbar arrows and by one line of the mouse wheel
+const LINE_SIZE: i32 = 20;
+
+
+bitflags! {
+    /**
+        The scroll panel flags
+
+        * NONE:     No flags. Equivalent to a invisible panel without borders.
+        * VISIBLE:  The panel is immediatly visible after creation
+        * DISABLED: The panel chidlren cannot be interacted with by the user.
+        * BORDER:   The panel has a thin black border
+    */
+    pub struct ScrollPanelFlags: u32 {
+        const NONE = 0;
+        const VISIBLE = WS_VISIBLE;
+        const DISABLED = WS_DISABLED;
+        const BORDER = WS_BORDER;
+    }
+}
+
+/// Internal state shared with the panel event handler. All values are in physical pixels.
+struct ScrollPanelState {
+    content: HWND,
+    virtual_size: (i32, i32),
+    scroll: (i32, i32),
+}
+
+impl Default for ScrollPanelState {
+    fn default() -> ScrollPanelState {
+        ScrollPanelState {
+            content: ptr::null_mut(),
+            virtual_size: (0, 0),
+            scroll: (0, 0),
+        }
+    }
+}
+
+/**
+A scroll panel is a container whose virtual area can be larger than its visible area. Scrollbars are shown automatically
+when the virtual area does not fit in the panel. The panel can be scrolled with the scrollbars and the mouse wheel.
+
+The children of the panel are placed in an inner content window that has the size of the virtual area (or the size of the
+panel if it is larger). Using the panel as a parent (in a builder or in a layout) uses the content window, so layouts placed
+in a scroll panel fill the virtual area. Use `fit_virtual_size` to compute the virtual area from the children of the panel.
+
+ScrollPanel is implemented as a custom control.
+
+Requires the `scroll-panel` feature.
+
+**Builder parameters:**
+  * `parent`:       **Required.** The panel parent container.
+  * `size`:         The panel size.
+  * `position`:     The panel position.
+  * `virtual_size`: The size of the scrollable area.
+  * `enabled`:      If the panel children can be used by the user.
+  * `flags`:        A combination of the ScrollPanelFlags values.
+  * `ex_flags`:     A combination of win32 window extended flags. Unlike `flags`, ex_flags must be used straight from winapi
+
+**Control events:**
+  * `MousePress(_)`: Generic mouse press events on the panel
+  * `OnMouseMove`: Generic mouse mouse event
+  * `OnMouseWheel`: Generic mouse wheel event
+
+```rust
+use native_windows_gui as nwg;
+fn build_panel(panel: &mut nwg::ScrollPanel, window: &nwg::Window, name: &mut nwg::TextInput) {
+    nwg::ScrollPanel::builder()
+        .size((300, 200))
+        .virtual_size((300, 1000))
+        .parent(window)
+        .build(panel);
+
+    nwg::TextInput::builder()
+        .position((10, 900))
+        .parent(&*panel)
+        .build(name);
+
+    panel.scroll_to_control(&*name);
+}
+```
+*/
+#[derive(Default)]
+pub struct ScrollPanel {
+    pub handle: ControlHandle,
+    content: ControlHandle,
+    state: Rc<RefCell<ScrollPanelState>>,
+    handler0: RefCell<Option<RawEventHandler>>,
+}
+
+impl ScrollPanel {
+
+    pub fn builder() -> ScrollPanelBuilder {
+        ScrollPanelBuilder {
+            size: (100, 100).into(),
+            position: (0, 0).into(),
+            virtual_size: None,
+            enabled: true,
+            flags: None,
+            ex_flags: 0,
+            parent: None,
+        }
+    }
+
+    /// Returns the handle of the inner window that contains the children of the panel
+    pub fn content(&self) -> ControlHandle {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.content
+    }
+
+    /// Returns the size of the scrollable area
+    pub fn virtual_size(&self) -> (u32, u32) {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let (w, h) = self.state.borrow().virtual_size;
+        let (w, h) = unsafe { physical_to_logical(w, h) };
+        (w as u32, h as u32)
+    }
+
+    /// Sets the size of the scrollable area
+    pub fn set_virtual_size(&self, w: u32, h: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow_mut().virtual_size = unsafe { logical_to_physical(w as i32, h as i32) };
+        update_scroll(handle, &mut self.state.borrow_mut());
+    }
+
+    /// Sets the size of the scrollable area to fit all the children of the panel
+    pub fn fit_virtual_size(&self) {
+        use winapi::um::winuser::{GetWindow, GW_CHILD, GW_HWNDNEXT};
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let content = self.state.borrow().content;
+        let (mut w, mut h) = (0, 0);
+
+        unsafe {
+            let mut child = GetWindow(content, GW_CHILD);
+            while !child.is_null() {
+                let r = content_rect(content, child);
+                w = w.max(r.right);
+                h = h.max(r.bottom);
+                child = GetWindow(child, GW_HWNDNEXT);
+            }
+        }
+
+        self.state.borrow_mut().virtual_size = (w, h);
+        update_scroll(handle, &mut self.state.borrow_mut());
+    }
+
+    /// Returns the scroll position (the point of the virtual area shown at the top left of the panel)
+    pub fn scroll_position(&self) -> (i32, i32) {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let (x, y) = self.state.borrow().scroll;
+        unsafe { physical_to_logical(x, y) }
+    }
+
+    /// Scrolls the panel. The position is clamped to the virtual area.
+    pub fn set_scroll_position(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow_mut().scroll = unsafe { logical_to_physical(x, y) };
+        update_scroll(handle, &mut self.state.borrow_mut());
+    }
+
+    /// Scrolls the panel the least possible so that `control` is fully visible.
+    /// If the control is larger than the panel, its top left corner is shown.
+    pub fn scroll_to_control<C: Into<ControlHandle>>(&self, control: C) {
+        use winapi::um::winuser::GetClientRect;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let control = match control.into().hwnd() {
+            Some(c) => c,
+            None => { return; }
+        };
+
+        let mut state = self.state.borrow_mut();
+        let r = content_rect(state.content, control);
+
+        let mut client: RECT = unsafe { mem::zeroed() };
+        unsafe { GetClientRect(handle, &mut client); }
+
+        let fit = |scroll: i32, start: i32, end: i32, visible: i32| {
+            if end > scroll + visible { (end - visible).min(start) }
+            else if start < scroll { start }
+            else { scroll }
+        };
+
+        state.scroll = (
+            fit(state.scroll.0, r.left, r.right, client.right),
+            fit(state.scroll.1, r.top, r.bottom, client.bottom),
+        );
+
+        update_scroll(handle, &mut state);
+    }
+
+    /// Returns true if the control user can interact with the control, return false otherwise
+    pub fn enabled(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_enabled(handle) }
+    }
+
+    /// Enable or disable the control
+    pub fn set_enabled(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_enabled(handle, v) }
+    }
+
+    /// Returns true if the control is visible to the user. Will return true even if the
+    /// control is outside of the parent client view (ex: at the position (10000, 10000))
+    pub fn visible(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_visibility(handle) }
+    }
+
+    /// Show or hide the control to the user
+    pub fn set_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_visibility(handle, v) }
+    }
+
+    /// Returns the size of the panel in the parent window
+    pub fn size(&self) -> (u32, u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_size(handle) }
+    }
+
+    /// Sets the size of the panel in the parent window
+    pub fn set_size(&self, x: u32, y: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_size(handle, x, y, false) }
+    }
+
+    /// Returns the position of the panel in the parent window
+    pub fn position(&self) -> (i32, i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_position(handle) }
+    }
+
+    /// Sets the position of the panel in the parent window
+    pub fn set_position(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_position(handle, x, y) }
+    }
+
+    /// Winapi class name used during control creation
+    pub fn class_name(&self) -> &'static str {
+        "NWG_SCROLL_PANEL"
+    }
+
+    /// Winapi base flags used during window creation
+    pub fn flags(&self) -> u32 {
+        WS_VISIBLE
+    }
+
+    /// Winapi flags required by the control
+    pub fn forced_flags(&self) -> u32 {
+        WS_CHILD | WS_CLIPCHILDREN | WS_VSCROLL | WS_HSCROLL
+    }
+
+    /// Handles the scrollbars, the mouse wheel, the kinetic scrolling and the resizing of the panel
+    fn hook_scroll(&self) {
+        use crate::bind_raw_event_handler_inner;
+        use winapi::um::winuser::{WM_SIZE, WM_VSCROLL, WM_HSCROLL, WM_MOUSEWHEEL, WM_MOUSEHWHEEL, GetClientRect, GET_WHEEL_DELTA_WPARAM, WHEEL_DELTA};
+
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let state = self.state.clone();
+
+        let handler = bind_raw_event_handler_inner(&self.handle, 0, move |hwnd, msg, w, l| {
+            match msg {
+                WM_SIZE => {
+                    // The panel is also resized when `update_scroll` shows or hides a scrollbar
+                    if let Ok(mut state) = state.try_borrow_mut() {
+                        update_scroll(hwnd, &mut state);
+                    }
+                },
+                WM_VSCROLL | WM_HSCROLL => {
+                    let mut client: RECT = unsafe { mem::zeroed() };
+                    unsafe { GetClientRect(hwnd, &mut client); }
+
+                    let mut state = state.borrow_mut();
+                    let vertical = msg == WM_VSCROLL;
+                    let (current, page, max) = match vertical {
+                        true => (state.scroll.1, client.bottom, state.virtual_size.1),
+                        false => (state.scroll.0, client.right, state.virtual_size.0),
+                    };
+
+                    let pos = scroll_request(hwnd, w, vertical, current, page, max);
+                    match vertical {
+                        true => { state.scroll.1 = pos; },
+                        false => { state.scroll.0 = pos; }
+                    }
+
+                    update_scroll(hwnd, &mut state);
+                    return Some(0);
+                },
+                WM_MOUSEWHEEL | WM_MOUSEHWHEEL => {
+                    let line = unsafe { logical_to_physical(LINE_SIZE, 0).0 };
+                    let delta = GET_WHEEL_DELTA_WPARAM(w) as i32 * line * 3 / WHEEL_DELTA as i32;
+
+                    let mut state = state.borrow_mut();
+                    match msg == WM_MOUSEWHEEL {
+                        true => { state.scroll.1 -= delta; },
+                        false => { state.scroll.0 += delta; }
+                    }
+
+                    update_scroll(hwnd, &mut state);
+                    return Some(0);
+                },
+                wh::NWG_SCROLL_BY => {
+                    // Sent by the kinetic scrolling. wParam and lParam are signed distances in physical pixels.
+                    let mut state = state.borrow_mut();
+                    state.scroll.0 += w as isize as i32;
+                    state.scroll.1 += l as i32;
+
+                    update_scroll(hwnd, &mut state);
+                    return Some(0);
+                },
+                _ => {}
+            }
+
+            None
+        });
+
+        *self.handler0.borrow_mut() = Some(handler.unwrap());
+    }
+
+}
+
+impl Drop for ScrollPanel {
+    fn drop(&mut self) {
+        let handler = self.handler0.borrow();
+        if let Some(h) = handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
+        self.content.destroy();
+        self.handle.destroy();
+    }
+}
+
+impl PartialEq for ScrollPanel {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for ScrollPanel {}
+
+pub struct ScrollPanelBuilder {
+    size: Extent,
+    position: Extent,
+    virtual_size: Option<(u32, u32)>,
+    enabled: bool,
+    flags: Option<ScrollPanelFlags>,
+    ex_flags: u32,
+    parent: Option<ControlHandle>
+}
+
+impl ScrollPanelBuilder {
+
+    pub fn flags(mut self, flags: ScrollPanelFlags) -> ScrollPanelBuilder {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn ex_flags(mut self, flags: u32) -> ScrollPanelBuilder {
+        self.ex_flags = flags;
+        self
+    }
+
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> ScrollPanelBuilder {
+        self.size = size.into();
+        self
+    }
+
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> ScrollPanelBuilder {
+        self.position = pos.into();
+        self
+    }
+
+    pub fn virtual_size(mut self, size: (u32, u32)) -> ScrollPanelBuilder {
+        self.virtual_size = Some(size);
+        self
+    }
+
+    pub fn enabled(mut self, e: bool) -> ScrollPanelBuilder {
+        self.enabled = e;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> ScrollPanelBuilder {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut ScrollPanel) -> Result<(), NwgError> {
+        let flags = self.flags.map(|f| f.bits()).unwrap_or(out.flags());
+
+        let parent = match self.parent {
+            Some(p) => Ok(p),
+            None => Err(NwgError::no_parent("ScrollPanel"))
+        }?;
+
+        *out = Default::default();
+
+        out.handle = ControlBase::build_hwnd()
+            .class_name(out.class_name())
+            .forced_flags(out.forced_flags())
+            .flags(flags)
+            .ex_flags(WS_EX_CONTROLPARENT | self.ex_flags)
+            .size(self.size)
+            .position(self.position)
+            .parent(Some(parent))
+            .build()?;
+
+        out.content = ControlBase::build_hwnd()
+            .class_name(out.class_name())
+            .forced_flags(WS_CHILD | WS_CLIPCHILDREN)
+            .flags(WS_VISIBLE)
+            .ex_flags(WS_EX_CONTROLPARENT)
+            .size(self.size)
+            .parent(Some(out.handle))
+            .build()?;
+
+        out.state.borrow_mut().content = out.content.hwnd().expect(BAD_HANDLE);
+        out.hook_scroll();
+
+        match self.virtual_size {
+            Some((w, h)) => out.set_virtual_size(w, h),
+            None => {
+                let (w, h) = unsafe { wh::get_window_size(out.content.hwnd().expect(BAD_HANDLE)) };
+                out.set_virtual_size(w, h);
+            },
+        }
+
+        out.set_enabled(self.enabled);
+
+        Ok(())
+    }
+
+}
+
+/// Returns the rectangle of a child of the content window in the virtual area (in physical pixels)
+fn content_rect(content: HWND, child: HWND) -> RECT {
+    use winapi::um::winuser::{GetWindowRect, MapWindowPoints};
+
+    unsafe {
+        let mut rect: RECT = mem::zeroed();
+        GetWindowRect(child, &mut rect);
+
+        let mut points = [POINT { x: rect.left, y: rect.top }, POINT { x: rect.right, y: rect.bottom }];
+        MapWindowPoints(ptr::null_mut(), content, points.as_mut_ptr(), 2);
+
+        RECT { left: points[0].x, top: points[0].y, right: points[1].x, bottom: points[1].y }
+    }
+}
+
+/// Computes the new scroll position from a WM_VSCROLL or a WM_HSCROLL message
+fn scroll_request(handle: HWND, w: WPARAM, vertical: bool, current: i32, page: i32, max: i32) -> i32 {
+    use winapi::um::winuser::{GetScrollInfo, SCROLLINFO, SIF_TRACKPOS, SB_LINEUP, SB_LINEDOWN, SB_PAGEUP, SB_PAGEDOWN,
+        SB_THUMBTRACK, SB_THUMBPOSITION, SB_TOP, SB_BOTTOM};
+
+    let line = unsafe { logical_to_physical(LINE_SIZE, 0).0 };
+
+    match LOWORD(w as u32) as isize {
+        SB_LINEUP => current - line,
+        SB_LINEDOWN => current + line,
+        SB_PAGEUP => current - page,
+        SB_PAGEDOWN => current + page,
+        SB_TOP => 0,
+        SB_BOTTOM => max,
+        SB_THUMBTRACK | SB_THUMBPOSITION => unsafe {
+            // The position in the message is limited to 16 bits
+            let bar = match vertical {
+                true => SB_VERT,
+                false => SB_HORZ
+            };
+
+            let mut info: SCROLLINFO = mem::zeroed();
+            info.cbSize = mem::size_of::<SCROLLINFO>() as u32;
+            info.fMask = SIF_TRACKPOS;
+            GetScrollInfo(handle, bar as i32, &mut info);
+            info.nTrackPos
+        },
+        _ => current
+    }
+}
+
+/// Updates the scrollbars, clamps the scroll position and moves the content window
+fn update_scroll(handle: HWND, state: &mut ScrollPanelState) {
+    use winapi::um::winuser::{GetClientRect, SetScrollInfo, SetWindowPos, SCROLLINFO, SIF_RANGE, SIF_PAGE, SIF_POS, SWP_NOZORDER, SWP_NOACTIVATE};
+
+    unsafe {
+        let set_bar = |bar: i32, max: i32, page: i32, pos: i32| {
+            let info = SCROLLINFO {
+                cbSize: mem::size_of::<SCROLLINFO>() as u32,
+                fMask: SIF_RANGE | SIF_PAGE | SIF_POS,
+                nMin: 0,
+                nMax: (max - 1).max(0),
+                nPage: page.max(0) as u32,
+                nPos: pos,
+                nTrackPos: 0,
+            };
+
+            SetScrollInfo(handle, bar, &info, 1);
+        };
+
+        // Showing or hiding a scrollbar changes the client size, so the client size is read after updating the scrollbars
+        let mut client: RECT = mem::zeroed();
+        GetClientRect(handle, &mut client);
+        set_bar(SB_HORZ as i32, state.virtual_size.0, client.right, state.scroll.0);
+        set_bar(SB_VERT as i32, state.virtual_size.1, client.bottom, state.scroll.1);
+        GetClientRect(handle, &mut client);
+
+        let (vw, vh) = state.virtual_size;
+        let max_x = (vw - client.right).max(0);
+        let max_y = (vh - client.bottom).max(0);
+        state.scroll = (state.scroll.0.min(max_x).max(0), state.scroll.1.min(max_y).max(0));
+
+        set_bar(SB_HORZ as i32, vw, client.right, state.scroll.0);
+        set_bar(SB_VERT as i32, vh, client.bottom, state.scroll.1);
+
+        if !state.content.is_null() {
+            let (x, y) = (-state.scroll.0, -state.scroll.1);
+            let (w, h) = (vw.max(client.right), vh.max(client.bottom));
+            SetWindowPos(state.content, ptr::null_mut(), x, y, w, h, SWP_NOZORDER | SWP_NOACTIVATE);
+        }
+    }
+}
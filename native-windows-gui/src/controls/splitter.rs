@@ -0,0 +1,558 @@
+use winapi::shared::windef::{HWND, RECT, POINT};
+use winapi::shared::minwindef::{WPARAM, LPARAM};
+use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_CHILD, WS_CLIPSIBLINGS};
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
+use crate::win32::base_helper::check_hwnd;
+use crate::win32::high_dpi::{logical_to_physical, physical_to_logical};
+use crate::{NwgError, RawEventHandler, unbind_raw_event_handler};
+use super::{ControlBase, ControlHandle};
+use std::{mem, ptr, rc::Rc, cell::RefCell};
+
+const NOT_BOUND: &'static str = "Splitter is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: Splitter handle is not HWND!";
+
+/// Notification code sent to the parent in a WM_COMMAND message when the splitter was moved
+pub(crate) const SPLITTER_MOVED: u16 = 1;
+
+
+bitflags! {
+    /**
+        The splitter flags
+
+        * NONE:     No flags. Equivalent to a invisible splitter.
+        * VISIBLE:  The splitter is immediatly visible after creation
+        * DISABLED: The splitter cannot be dragged by the user
+    */
+    pub struct SplitterFlags: u32 {
+        const NONE = 0;
+        const VISIBLE = WS_VISIBLE;
+        const DISABLED = WS_DISABLED;
+    }
+}
+
+/// The orientation of a splitter bar
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SplitterOrientation {
+    /// A vertical bar between a left pane and a right pane. The splitter moves horizontally.
+    Vertical,
+
+    /// A horizontal bar between a top pane and a bottom pane. The splitter moves vertically.
+    Horizontal,
+}
+
+/// Internal state shared with the splitter event handler. All values are in physical pixels.
+struct SplitterState {
+    orientation: SplitterOrientation,
+    first: Option<HWND>,
+    second: Option<HWND>,
+    min_first: i32,
+    min_second: i32,
+    max_first: Option<i32>,
+
+    /// When dragging: the offset of the mouse in the splitter and the position of the drag ghost
+    drag: Option<(i32, i32)>,
+}
+
+impl Default for SplitterState {
+    fn default() -> SplitterState {
+        SplitterState {
+            orientation: SplitterOrientation::Vertical,
+            first: None,
+            second: None,
+            min_first: 0,
+            min_second: 0,
+            max_first: None,
+            drag: None,
+        }
+    }
+}
+
+/**
+A splitter is a bar placed between two sibling controls (the panes) that the user can drag to resize them.
+
+While the user drags the splitter, a ghost of the bar follows the mouse. The panes are only resized when the mouse button is released.
+The splitter position is always kept between the minimum sizes of the two panes (and the maximum size of the first pane if there is one).
+
+The first pane is the control on the left (or on top) of the splitter and the second pane is the control on the right (or under) the splitter.
+When the splitter is moved, the first pane keeps its left (or top) edge and the second pane keeps its right (or bottom) edge.
+
+Splitter is implemented as a custom control.
+
+Requires the `splitter` feature.
+
+**Builder parameters:**
+  * `parent`:      **Required.** The splitter parent container. Must be the same as the parent of the panes.
+  * `orientation`: The orientation of the splitter bar. Defaults to `Vertical`.
+  * `size`:        The splitter size. The width (or height if horizontal) is the thickness of the bar.
+  * `position`:    The splitter position.
+  * `panes`:       The two controls resized by the splitter
+  * `min_size`:    The minimum sizes of the first and the second pane
+  * `max_size`:    The maximum size of the first pane
+  * `flags`:       A combination of the SplitterFlags values.
+
+**Control events:**
+  * `OnSplitterMoved`: When the user has moved the splitter. The panes are already resized.
+
+```rust
+use native_windows_gui as nwg;
+fn build_splitter(splitter: &mut nwg::Splitter, window: &nwg::Window, tree: &nwg::TreeView, list: &nwg::ListView) {
+    nwg::Splitter::builder()
+        .orientation(nwg::SplitterOrientation::Vertical)
+        .position((200, 0))
+        .size((5, 400))
+        .panes(tree, list)
+        .min_size((100, 150))
+        .parent(window)
+        .build(splitter);
+}
+```
+*/
+#[derive(Default)]
+pub struct Splitter {
+    pub handle: ControlHandle,
+    state: Rc<RefCell<SplitterState>>,
+    handler0: RefCell<Option<RawEventHandler>>,
+}
+
+impl Splitter {
+
+    pub fn builder() -> SplitterBuilder {
+        SplitterBuilder {
+            orientation: SplitterOrientation::Vertical,
+            size: (5, 100).into(),
+            position: (0, 0).into(),
+            panes: None,
+            min_size: (0, 0),
+            max_size: None,
+            flags: None,
+            parent: None,
+        }
+    }
+
+    /// Returns the orientation of the splitter
+    pub fn orientation(&self) -> SplitterOrientation {
+        self.state.borrow().orientation
+    }
+
+    /// Sets the two controls resized by the splitter. The controls must be siblings of the splitter.
+    pub fn set_panes<C1: Into<ControlHandle>, C2: Into<ControlHandle>>(&self, first: C1, second: C2) {
+        let mut state = self.state.borrow_mut();
+        state.first = first.into().hwnd();
+        state.second = second.into().hwnd();
+    }
+
+    /// Sets the minimum size (in logical pixels) of the first and the second pane
+    pub fn set_min_size(&self, first: i32, second: i32) {
+        let mut state = self.state.borrow_mut();
+        unsafe {
+            state.min_first = logical_to_physical(first, 0).0;
+            state.min_second = logical_to_physical(second, 0).0;
+        }
+    }
+
+    /// Sets the maximum size (in logical pixels) of the first pane. `None` removes the limit.
+    pub fn set_max_size(&self, first: Option<i32>) {
+        self.state.borrow_mut().max_first = first.map(|v| unsafe { logical_to_physical(v, 0).0 });
+    }
+
+    /// Returns the position of the splitter bar in its parent. This is the x coordinate for a
+    /// vertical splitter and the y coordinate for a horizontal splitter.
+    pub fn split_position(&self) -> i32 {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let state = self.state.borrow();
+        let rect = child_rect(handle);
+        let (x, y) = unsafe { physical_to_logical(rect.left, rect.top) };
+        match state.orientation {
+            SplitterOrientation::Vertical => x,
+            SplitterOrientation::Horizontal => y,
+        }
+    }
+
+    /// Moves the splitter bar and resizes the panes. The position is clamped to the pane sizes limits.
+    /// Does not raise `OnSplitterMoved`.
+    pub fn set_split_position(&self, pos: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let state = self.state.borrow();
+        let pos = unsafe { logical_to_physical(pos, pos) };
+        let pos = match state.orientation {
+            SplitterOrientation::Vertical => pos.0,
+            SplitterOrientation::Horizontal => pos.1,
+        };
+
+        move_splitter(handle, &state, pos);
+    }
+
+    /// Returns true if the control user can interact with the control, return false otherwise
+    pub fn enabled(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_enabled(handle) }
+    }
+
+    /// Enable or disable the control
+    pub fn set_enabled(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_enabled(handle, v) }
+    }
+
+    /// Returns true if the control is visible to the user. Will return true even if the
+    /// control is outside of the parent client view (ex: at the position (10000, 10000))
+    pub fn visible(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_visibility(handle) }
+    }
+
+    /// Show or hide the control to the user
+    pub fn set_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_visibility(handle, v) }
+    }
+
+    /// Returns the size of the splitter in the parent window
+    pub fn size(&self) -> (u32, u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_size(handle) }
+    }
+
+    /// Sets the size of the splitter in the parent window
+    pub fn set_size(&self, x: u32, y: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_size(handle, x, y, false) }
+    }
+
+    /// Returns the position of the splitter in the parent window
+    pub fn position(&self) -> (i32, i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_position(handle) }
+    }
+
+    /// Sets the position of the splitter in the parent window. Unlike `set_split_position`, the panes are not resized.
+    pub fn set_position(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_position(handle, x, y) }
+    }
+
+    /// Winapi class name used during control creation
+    pub fn class_name(&self) -> &'static str {
+        "NWG_SPLITTER"
+    }
+
+    /// Winapi base flags used during window creation
+    pub fn flags(&self) -> u32 {
+        WS_VISIBLE
+    }
+
+    /// Winapi flags required by the control
+    pub fn forced_flags(&self) -> u32 {
+        WS_CHILD | WS_CLIPSIBLINGS
+    }
+
+    /// Handles the mouse dragging and the cursor of the splitter
+    fn hook_drag(&self) {
+        use crate::bind_raw_event_handler_inner;
+        use winapi::um::winuser::{WM_SETCURSOR, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MOUSEMOVE, WM_CAPTURECHANGED, WM_COMMAND, IDC_SIZEWE, IDC_SIZENS};
+        use winapi::um::winuser::{SetCursor, LoadCursorW, SetCapture, ReleaseCapture};
+        use winapi::shared::minwindef::{LOWORD, HIWORD, MAKELONG};
+
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let handle = self.handle.hwnd().expect(BAD_HANDLE);
+        let state = self.state.clone();
+
+        let handler = bind_raw_event_handler_inner(&self.handle, 0, move |hwnd, msg, _w, l| {
+            // Mouse coordinates are signed
+            let mouse = |state: &SplitterState| match state.orientation {
+                SplitterOrientation::Vertical => LOWORD(l as u32) as i16 as i32,
+                SplitterOrientation::Horizontal => HIWORD(l as u32) as i16 as i32,
+            };
+
+            match msg {
+                WM_SETCURSOR => {
+                    let cursor = match state.borrow().orientation {
+                        SplitterOrientation::Vertical => IDC_SIZEWE,
+                        SplitterOrientation::Horizontal => IDC_SIZENS,
+                    };
+
+                    unsafe { SetCursor(LoadCursorW(ptr::null_mut(), cursor)); }
+                    return Some(1);
+                },
+                WM_LBUTTONDOWN => {
+                    let pos = {
+                        let mut state = state.borrow_mut();
+                        let pos = bar_position(hwnd, &state);
+                        state.drag = Some((mouse(&state), pos));
+                        pos
+                    };
+
+                    unsafe { SetCapture(hwnd); }
+                    invert_ghost(hwnd, &state.borrow(), pos);
+                    return Some(0);
+                },
+                WM_MOUSEMOVE => {
+                    let mut state = state.borrow_mut();
+                    if let Some((offset, ghost)) = state.drag {
+                        let pos = clamp_position(hwnd, &state, bar_position(hwnd, &state) + mouse(&state) - offset);
+                        if pos != ghost {
+                            invert_ghost(hwnd, &state, ghost);
+                            invert_ghost(hwnd, &state, pos);
+                            state.drag = Some((offset, pos));
+                        }
+                    }
+                },
+                WM_LBUTTONUP => {
+                    let drag = state.borrow_mut().drag.take();
+                    if let Some((_, ghost)) = drag {
+                        invert_ghost(hwnd, &state.borrow(), ghost);
+                        unsafe { ReleaseCapture(); }
+
+                        if ghost != bar_position(hwnd, &state.borrow()) {
+                            move_splitter(hwnd, &state.borrow(), ghost);
+                            let command = MAKELONG(0, SPLITTER_MOVED) as u32 as WPARAM;
+                            wh::send_message(wh::get_window_parent(hwnd), WM_COMMAND, command, handle as LPARAM);
+                        }
+                    }
+                },
+                WM_CAPTURECHANGED => {
+                    // The capture was taken by another window while dragging. Cancel the drag.
+                    let drag = state.borrow_mut().drag.take();
+                    if let Some((_, ghost)) = drag {
+                        invert_ghost(hwnd, &state.borrow(), ghost);
+                    }
+                },
+                _ => {}
+            }
+
+            None
+        });
+
+        *self.handler0.borrow_mut() = Some(handler.unwrap());
+    }
+
+}
+
+impl Drop for Splitter {
+    fn drop(&mut self) {
+        let handler = self.handler0.borrow();
+        if let Some(h) = handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
+        self.handle.destroy();
+    }
+}
+
+impl PartialEq for Splitter {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for Splitter {}
+
+pub struct SplitterBuilder {
+    orientation: SplitterOrientation,
+    size: Extent,
+    position: Extent,
+    panes: Option<(ControlHandle, ControlHandle)>,
+    min_size: (i32, i32),
+    max_size: Option<i32>,
+    flags: Option<SplitterFlags>,
+    parent: Option<ControlHandle>
+}
+
+impl SplitterBuilder {
+
+    pub fn flags(mut self, flags: SplitterFlags) -> SplitterBuilder {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn orientation(mut self, orientation: SplitterOrientation) -> SplitterBuilder {
+        self.orientation = orientation;
+        self
+    }
+
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> SplitterBuilder {
+        self.size = size.into();
+        self
+    }
+
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> SplitterBuilder {
+        self.position = pos.into();
+        self
+    }
+
+    pub fn panes<C1: Into<ControlHandle>, C2: Into<ControlHandle>>(mut self, first: C1, second: C2) -> SplitterBuilder {
+        self.panes = Some((first.into(), second.into()));
+        self
+    }
+
+    pub fn min_size(mut self, min_size: (i32, i32)) -> SplitterBuilder {
+        self.min_size = min_size;
+        self
+    }
+
+    pub fn max_size(mut self, max_size: Option<i32>) -> SplitterBuilder {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> SplitterBuilder {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut Splitter) -> Result<(), NwgError> {
+        let flags = self.flags.map(|f| f.bits()).unwrap_or(out.flags());
+
+        let parent = match self.parent {
+            Some(p) => Ok(p),
+            None => Err(NwgError::no_parent("Splitter"))
+        }?;
+
+        *out = Default::default();
+
+        out.handle = ControlBase::build_hwnd()
+            .class_name(out.class_name())
+            .forced_flags(out.forced_flags())
+            .flags(flags)
+            .size(self.size)
+            .position(self.position)
+            .parent(Some(parent))
+            .build()?;
+
+        out.state.borrow_mut().orientation = self.orientation;
+        out.set_min_size(self.min_size.0, self.min_size.1);
+        out.set_max_size(self.max_size);
+
+        if let Some((first, second)) = self.panes {
+            out.set_panes(first, second);
+        }
+
+        out.hook_drag();
+
+        Ok(())
+    }
+
+}
+
+/// Returns the rectangle of a control in the client area of its parent in physical pixels
+fn child_rect(handle: HWND) -> RECT {
+    use winapi::um::winuser::{GetWindowRect, MapWindowPoints};
+
+    unsafe {
+        let mut rect: RECT = mem::zeroed();
+        GetWindowRect(handle, &mut rect);
+
+        let mut points = [POINT { x: rect.left, y: rect.top }, POINT { x: rect.right, y: rect.bottom }];
+        MapWindowPoints(ptr::null_mut(), wh::get_window_parent(handle), points.as_mut_ptr(), 2);
+
+        RECT { left: points[0].x, top: points[0].y, right: points[1].x, bottom: points[1].y }
+    }
+}
+
+/// Returns the left (or top) edge of the splitter bar in its parent
+fn bar_position(handle: HWND, state: &SplitterState) -> i32 {
+    let rect = child_rect(handle);
+    match state.orientation {
+        SplitterOrientation::Vertical => rect.left,
+        SplitterOrientation::Horizontal => rect.top,
+    }
+}
+
+/// Keeps the position of the splitter bar between the limits of the panes
+fn clamp_position(handle: HWND, state: &SplitterState, pos: i32) -> i32 {
+    use winapi::um::winuser::GetClientRect;
+
+    let vertical = state.orientation == SplitterOrientation::Vertical;
+    let bar = child_rect(handle);
+    let thickness = match vertical {
+        true => bar.right - bar.left,
+        false => bar.bottom - bar.top,
+    };
+
+    let mut parent_rect: RECT = unsafe { mem::zeroed() };
+    unsafe { GetClientRect(wh::get_window_parent(handle), &mut parent_rect); }
+
+    let start = state.first.map(child_rect).unwrap_or(parent_rect);
+    let end = state.second.map(child_rect).unwrap_or(parent_rect);
+    let (start, end) = match vertical {
+        true => (start.left, end.right),
+        false => (start.top, end.bottom),
+    };
+
+    let low = start + state.min_first;
+    let mut high = end - state.min_second - thickness;
+    if let Some(max) = state.max_first {
+        high = high.min(start + max);
+    }
+
+    pos.min(high).max(low)
+}
+
+/// Moves the splitter bar to `pos` (clamped) and resizes the panes
+fn move_splitter(handle: HWND, state: &SplitterState, pos: i32) {
+    use winapi::um::winuser::{SetWindowPos, SWP_NOZORDER, SWP_NOACTIVATE, SWP_NOSIZE};
+
+    let pos = clamp_position(handle, state, pos);
+    let bar = child_rect(handle);
+    let flags = SWP_NOZORDER | SWP_NOACTIVATE;
+
+    unsafe {
+        match state.orientation {
+            SplitterOrientation::Vertical => {
+                let thickness = bar.right - bar.left;
+                SetWindowPos(handle, ptr::null_mut(), pos, bar.top, 0, 0, flags | SWP_NOSIZE);
+
+                if let Some(first) = state.first {
+                    let r = child_rect(first);
+                    SetWindowPos(first, ptr::null_mut(), r.left, r.top, (pos - r.left).max(0), r.bottom - r.top, flags);
+                }
+
+                if let Some(second) = state.second {
+                    let r = child_rect(second);
+                    let left = pos + thickness;
+                    SetWindowPos(second, ptr::null_mut(), left, r.top, (r.right - left).max(0), r.bottom - r.top, flags);
+                }
+            },
+            SplitterOrientation::Horizontal => {
+                let thickness = bar.bottom - bar.top;
+                SetWindowPos(handle, ptr::null_mut(), bar.left, pos, 0, 0, flags | SWP_NOSIZE);
+
+                if let Some(first) = state.first {
+                    let r = child_rect(first);
+                    SetWindowPos(first, ptr::null_mut(), r.left, r.top, r.right - r.left, (pos - r.top).max(0), flags);
+                }
+
+                if let Some(second) = state.second {
+                    let r = child_rect(second);
+                    let top = pos + thickness;
+                    SetWindowPos(second, ptr::null_mut(), r.left, top, r.right - r.left, (r.bottom - top).max(0), flags);
+                }
+            },
+        }
+    }
+}
+
+/// Draws (or erases, if it was already drawn) the drag ghost of the splitter bar over its parent and the panes
+fn invert_ghost(handle: HWND, state: &SplitterState, pos: i32) {
+    use winapi::um::winuser::{GetDCEx, ReleaseDC, DCX_CACHE, DCX_LOCKWINDOWUPDATE};
+    use winapi::um::wingdi::{PatBlt, DSTINVERT};
+
+    let parent = wh::get_window_parent(handle);
+    let bar = child_rect(handle);
+    let (x, y) = match state.orientation {
+        SplitterOrientation::Vertical => (pos, bar.top),
+        SplitterOrientation::Horizontal => (bar.left, pos),
+    };
+
+    unsafe {
+        // Without DCX_CLIPCHILDREN the ghost is also drawn over the panes
+        let hdc = GetDCEx(parent, ptr::null_mut(), DCX_CACHE | DCX_LOCKWINDOWUPDATE);
+        if hdc.is_null() {
+            return;
+        }
+
+        PatBlt(hdc, x, y, bar.right - bar.left, bar.bottom - bar.top, DSTINVERT);
+        ReleaseDC(parent, hdc);
+    }
+}
@@ -1,23 +1,125 @@
 use winapi::shared::minwindef::{WPARAM, LPARAM};
+use winapi::shared::windef::{HWND, HDC, RECT};
 use crate::win32::window_helper as wh;
 use crate::win32::base_helper::check_hwnd;
-use crate::{Font, NwgError, RawEventHandler, unbind_raw_event_handler};
+use crate::{Font, Icon, NwgError, RawEventHandler, unbind_raw_event_handler};
 use super::{ControlHandle, ControlBase};
-use std::cell::RefCell;
+use std::{mem, ptr, rc::Rc, cell::RefCell};
+use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
 
 const NOT_BOUND: &'static str = "StatusBar is not yet bound to a winapi object";
 const BAD_HANDLE: &'static str = "INTERNAL ERROR: StatusBar handle is not HWND!";
+const NO_JOB_INDICATOR: &'static str = "StatusBar::with_progress must be called before starting a job";
+
+/// Range of the progress bar of the job indicator
+const JOB_RANGE: u64 = 10000;
+
+/// Values sent instead of a position by `JobReporter`
+const JOB_INDETERMINATE: isize = -1;
+const JOB_FINISHED: isize = -2;
+
+/// Callback used to paint an owner drawn status bar part
+pub type StatusBarDrawCallback = dyn Fn(&StatusBarDrawItem);
+
+/// The parameters of an owner drawn status bar part. See `StatusBar::set_owner_draw`.
+pub struct StatusBarDrawItem {
+    /// The index of the part to draw
+    pub index: u8,
+
+    /// The device context of the status bar
+    pub hdc: HDC,
+
+    /// The area of the part in the status bar (left, top, right, bottom)
+    pub rect: [i32; 4],
+}
+
+/// Internal state shared with the parent hook
+#[derive(Default)]
+struct StatusBarState {
+    /// Controls embedded in a part: (part index, control)
+    embedded: Vec<(u8, HWND)>,
+
+    /// Owner draw callbacks: (part index, callback)
+    draw: Vec<(u8, Rc<StatusBarDrawCallback>)>,
+
+    /// The background job indicator created by `with_progress`
+    job: Option<JobIndicator>,
+}
+
+struct JobIndicator {
+    progress: HWND,
+    cancel: HWND,
+    /// Id of the last job started. Reports of the previous jobs are ignored.
+    id: usize,
+    running: bool,
+    cancelled: Arc<AtomicBool>,
+}
+
+/**
+    Reports the progress of a background job to the job indicator of a status bar. See `StatusBar::with_progress`.
+
+    A reporter can be sent to other threads and cloned. The reports are posted to the status bar, so they
+    never block the worker. The reports of a job are ignored once another job is started.
+*/
+#[derive(Clone)]
+pub struct JobReporter {
+    hwnd: usize,
+    id: usize,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl JobReporter {
+
+    /// Sets the progress of the job. `value` is clamped to `total`.
+    pub fn progress(&self, value: u64, total: u64) {
+        let position = match total {
+            0 => 0,
+            total => (value.min(total) as u128 * JOB_RANGE as u128 / total as u128) as isize
+        };
+
+        self.post(position);
+    }
+
+    /// Shows a progress bar without a value, for the parts of the job of unknown length
+    pub fn indeterminate(&self) {
+        self.post(JOB_INDETERMINATE);
+    }
+
+    /// Hides the job indicator. Must be called when the job is over, even if it was cancelled.
+    pub fn finish(&self) {
+        self.post(JOB_FINISHED);
+    }
+
+    /// Returns `true` if the user clicked on the cancel button, or if `StatusBar::cancel_job` was called
+    pub fn cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    fn post(&self, position: isize) {
+        use winapi::um::winuser::PostMessageW;
+        unsafe { PostMessageW(self.hwnd as HWND, wh::NWG_JOB_PROGRESS, self.id as WPARAM, position as LPARAM); }
+    }
+
+}
 
 /**
 A status bar is a horizontal window at the bottom of a parent window in which an application can display various kinds of status information.
 Status bar cannot stack, so there must be only one per window.
 
+The status bar can be split in multiple parts using `set_parts`. Each part can have a text, an icon, a tooltip,
+a custom drawing callback (`set_owner_draw`) or an embedded control (`embed_control`). Embedded controls are moved
+and resized with their part when the parent is resized.
+
+The status bar can also show the progress of a background job with a progress bar and a cancel button.
+See `with_progress`.
+
 Requires the `status-bar` feature. 
 
 **Builder parameters:**
   * `parent`:   **Required.** The status bar parent container.
   * `text`:     The status bar text.
   * `font`:     The font used for the status bar text
+  * `parts`:    The width of the status bar parts. See `StatusBar::set_parts`
 
 **Control events:**
   * `MousePress(_)`: Generic mouse press events on the status bar
@@ -33,13 +135,30 @@ fn build_status(status: &mut nwg::StatusBar, window: &nwg::Window, font: &nwg::F
         .parent(window)
         .build(status);
 }
+
+fn build_status_parts(status: &mut nwg::StatusBar, progress: &mut nwg::ProgressBar, window: &nwg::Window) {
+    nwg::StatusBar::builder()
+        .parts(&[200, 150, -1])
+        .parent(window)
+        .build(status);
+
+    nwg::ProgressBar::builder()
+        .parent(&*status)
+        .build(progress);
+
+    status.embed_control(1, &*progress);
+    status.set_text(2, "Ready");
+    status.set_tooltip(2, "The application status");
+}
 ```
 
 */
 #[derive(Default)]
 pub struct StatusBar {
     pub handle: ControlHandle,
+    state: Rc<RefCell<StatusBarState>>,
     handler0: RefCell<Option<RawEventHandler>>,
+    handler1: RefCell<Option<RawEventHandler>>,
 }
 
 
@@ -49,10 +168,245 @@ impl StatusBar {
         StatusBarBuilder {
             text: "",
             font: None,
+            parts: None,
             parent: None
         }
     }
 
+    /**
+        Split the status bar in parts. `widths` is the width of each part (in logical pixels).
+        A width of `-1` makes the part extend to the right edge of the status bar and should only be used for the last part.
+    */
+    pub fn set_parts(&self, widths: &[i32]) {
+        use winapi::um::commctrl::SB_SETPARTS;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let mut right = 0;
+        let edges: Vec<i32> = widths.iter()
+            .map(|&w| match w < 0 {
+                true => -1,
+                false => {
+                    right += unsafe { crate::win32::high_dpi::logical_to_physical(w, 0).0 };
+                    right
+                }
+            })
+            .collect();
+
+        wh::send_message(handle, SB_SETPARTS, edges.len() as WPARAM, edges.as_ptr() as LPARAM);
+        update_embedded(handle, &self.state.borrow());
+    }
+
+    /// Return the number of parts in the status bar
+    pub fn parts(&self) -> usize {
+        use winapi::um::commctrl::SB_GETPARTS;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::send_message(handle, SB_GETPARTS, 0, 0) as usize
+    }
+
+    /// Return the area of a part in the status bar (left, top, right, bottom) in physical pixels
+    pub fn part_rect(&self, index: u8) -> [i32; 4] {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let r = part_rect(handle, index);
+        [r.left, r.top, r.right, r.bottom]
+    }
+
+    /// Set the icon displayed in a part of the status bar. `None` removes the icon.
+    /// The icon must outlive its use by the status bar.
+    pub fn set_icon(&self, index: u8, icon: Option<&Icon>) {
+        use winapi::um::commctrl::SB_SETICON;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let icon_handle = icon.map(|i| i.handle).unwrap_or(ptr::null_mut());
+        wh::send_message(handle, SB_SETICON, index as WPARAM, icon_handle as LPARAM);
+    }
+
+    /// Set the tooltip of a part of the status bar. The tooltip is only shown if the part
+    /// only displays an icon or if its text is truncated.
+    pub fn set_tooltip<'a>(&self, index: u8, text: &'a str) {
+        use winapi::um::commctrl::SB_SETTIPTEXTW;
+        use crate::win32::base_helper::with_utf16;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        with_utf16(text, |text| {
+            wh::send_message(handle, SB_SETTIPTEXTW, index as WPARAM, text.as_ptr() as LPARAM);
+        });
+    }
+
+    /// Paint a part of the status bar using a callback. The callback is called each time the part needs to be redrawn.
+    /// This replaces the text of the part. Use `set_text` to remove the callback.
+    pub fn set_owner_draw<F>(&self, index: u8, callback: F)
+        where F: Fn(&StatusBarDrawItem) + 'static
+    {
+        use winapi::um::commctrl::{SB_SETTEXTW, SBT_OWNERDRAW};
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        {
+            let mut state = self.state.borrow_mut();
+            state.draw.retain(|(i, _)| *i != index);
+            state.draw.push((index, Rc::new(callback)));
+        }
+
+        wh::send_message(handle, SB_SETTEXTW, (index as WPARAM) | SBT_OWNERDRAW, 0);
+    }
+
+    /**
+        Embed a control in a part of the status bar. The control is moved over the part and
+        resized with it when the status bar is resized.
+
+        The control should be created with the status bar as parent.
+    */
+    pub fn embed_control<C: Into<ControlHandle>>(&self, index: u8, control: C) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let control = control.into().hwnd().expect("Only window controls can be embedded in a status bar");
+
+        {
+            let mut state = self.state.borrow_mut();
+            state.embedded.retain(|(_, c)| *c != control);
+            state.embedded.push((index, control));
+        }
+
+        update_embedded(handle, &self.state.borrow());
+    }
+
+    /// Stop moving a control embedded with `embed_control`. The control is not destroyed.
+    pub fn remove_embedded_control<C: Into<ControlHandle>>(&self, control: C) {
+        if let Some(control) = control.into().hwnd() {
+            self.state.borrow_mut().embedded.retain(|(_, c)| *c != control);
+        }
+    }
+
+    /**
+        Embeds a job indicator in the status bar: a progress bar in the part `progress_part` and a cancel button
+        in the part `cancel_part`. Both controls are hidden until a job is started with `start_job`.
+
+        The job indicator is created once. Calling this function again moves the controls to the new parts.
+
+        ```rust
+        use native_windows_gui as nwg;
+        use std::thread;
+
+        fn export(status: &nwg::StatusBar) {
+            status.with_progress(1, 2).expect("Failed to create the job indicator");
+
+            let reporter = status.start_job();
+            thread::spawn(move || {
+                for page in 0..100 {
+                    if reporter.cancelled() { break; }
+                    // export the page...
+                    reporter.progress(page + 1, 100);
+                }
+
+                reporter.finish();
+            });
+        }
+        ```
+    */
+    pub fn with_progress(&self, progress_part: u8, cancel_part: u8) -> Result<(), NwgError> {
+        use winapi::um::winuser::{WS_CHILD, WS_TABSTOP, BS_PUSHBUTTON};
+        use winapi::um::commctrl::{PBS_SMOOTH, PBM_SETRANGE32};
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let existing = self.state.borrow().job.as_ref().map(|j| (j.progress, j.cancel));
+        let (progress, cancel) = match existing {
+            Some(controls) => controls,
+            None => {
+                let progress = ControlBase::build_hwnd()
+                    .class_name("msctls_progress32")
+                    .forced_flags(WS_CHILD | PBS_SMOOTH)
+                    .parent(Some(self.handle))
+                    .build()?;
+
+                let cancel = ControlBase::build_hwnd()
+                    .class_name("BUTTON")
+                    .text("Cancel")
+                    .forced_flags(WS_CHILD | WS_TABSTOP | BS_PUSHBUTTON)
+                    .parent(Some(self.handle))
+                    .build()?;
+
+                let (progress, cancel) = (progress.hwnd().expect(BAD_HANDLE), cancel.hwnd().expect(BAD_HANDLE));
+                wh::send_message(progress, PBM_SETRANGE32, 0, JOB_RANGE as LPARAM);
+                unsafe { wh::set_window_font(cancel, Some(wh::get_window_font(handle)), false); }
+
+                self.hook_job_indicator(cancel)?;
+
+                self.state.borrow_mut().job = Some(JobIndicator {
+                    progress,
+                    cancel,
+                    id: 0,
+                    running: false,
+                    cancelled: Arc::new(AtomicBool::new(false)),
+                });
+
+                (progress, cancel)
+            }
+        };
+
+        self.embed_control(progress_part, ControlHandle::Hwnd(progress));
+        self.embed_control(cancel_part, ControlHandle::Hwnd(cancel));
+
+        Ok(())
+    }
+
+    /**
+        Shows the job indicator and returns the reporter of the new job. Send the reporter to the worker that runs the job.
+        Starting a job cancels the previous one.
+
+        Panics if `with_progress` was not called.
+    */
+    pub fn start_job(&self) -> JobReporter {
+        use winapi::um::commctrl::PBM_SETPOS;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let (progress, cancel, reporter) = {
+            let mut state = self.state.borrow_mut();
+            let job = state.job.as_mut().expect(NO_JOB_INDICATOR);
+
+            job.cancelled.store(true, Ordering::SeqCst);
+            job.cancelled = Arc::new(AtomicBool::new(false));
+            job.id += 1;
+            job.running = true;
+
+            let reporter = JobReporter { hwnd: handle as usize, id: job.id, cancelled: job.cancelled.clone() };
+            (job.progress, job.cancel, reporter)
+        };
+
+        set_marquee(progress, false);
+        wh::send_message(progress, PBM_SETPOS, 0, 0);
+
+        unsafe {
+            wh::set_window_enabled(cancel, true);
+            wh::set_window_visibility(progress, true);
+            wh::set_window_visibility(cancel, true);
+        }
+
+        reporter
+    }
+
+    /// Returns `true` if a job started with `start_job` is not finished
+    pub fn job_running(&self) -> bool {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow().job.as_ref().map(|j| j.running).unwrap_or(false)
+    }
+
+    /// Returns `true` if the running job was cancelled
+    pub fn job_cancelled(&self) -> bool {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow().job.as_ref().map(|j| j.running && j.cancelled.load(Ordering::SeqCst)).unwrap_or(false)
+    }
+
+    /// Cancels the running job, like a click on the cancel button. The job indicator stays visible until the worker calls `JobReporter::finish`.
+    pub fn cancel_job(&self) {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        if let Some(job) = self.state.borrow().job.as_ref() {
+            cancel_job(job);
+        }
+    }
+
     /// Set the minimum height of the statusbar (in pixels)
     pub fn set_min_height(&self, height: u32) {
         use winapi::um::commctrl::SB_SETMINHEIGHT;
@@ -100,11 +454,23 @@ impl StatusBar {
 
     /// Set the text in one of the region of the status bar
     pub fn set_text<'a>(&self, index: u8, text: &'a str) {
+        use crate::win32::base_helper::with_utf16;
+        with_utf16(text, |text| self.set_text_wide(index, text));
+    }
+
+    /// Set the text in one of the region of the status bar from a null terminated utf16 string.
+    /// Use with the `wstr!` macro to set a static text without encoding it at runtime.
+    ///
+    /// Panics if `text` is not null terminated.
+    pub fn set_text_wide(&self, index: u8, text: &[u16]) {
         use winapi::um::commctrl::SB_SETTEXTW;
-        use crate::win32::base_helper::to_utf16;
 
         let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
-        let text = to_utf16(text);
+        if text.last() != Some(&0) {
+            panic!("StatusBar::set_text_wide requires a null terminated string");
+        }
+
+        self.state.borrow_mut().draw.retain(|(i, _)| *i != index);
         wh::send_message(handle, SB_SETTEXTW, index as WPARAM, text.as_ptr() as LPARAM);
     }
 
@@ -120,24 +486,43 @@ impl StatusBar {
 
     /// Winapi flags required by the control
     pub fn forced_flags(&self) -> u32 {
-        use winapi::um::winuser::{WS_CHILD};
+        use winapi::um::winuser::{WS_CHILD, WS_CLIPCHILDREN};
+        use winapi::um::commctrl::SBARS_TOOLTIPS;
 
-        WS_CHILD
+        WS_CHILD | WS_CLIPCHILDREN | SBARS_TOOLTIPS
     }
 
     /// Status bar do not resize automatically. Instead, a resize message must be
     /// manually sent by the parent window to trigger the resize action.
+    /// The hook also paints the owner drawn parts and moves the embedded controls.
     pub fn hook_parent_resize(&self) {
-        use winapi::um::winuser::WM_SIZE;
+        use winapi::um::winuser::{WM_SIZE, WM_DRAWITEM, DRAWITEMSTRUCT};
         use crate::bind_raw_event_handler_inner;
 
         if self.handle.blank() { panic!("{}", NOT_BOUND); }
         let handle = self.handle.hwnd().expect(BAD_HANDLE);
+        let state = self.state.clone();
 
         let parent_handle = ControlHandle::Hwnd(wh::get_window_parent(handle));
-        let handler = bind_raw_event_handler_inner(&parent_handle, handle as usize, move |_hwnd, msg, _w, _l| {
-            if msg == WM_SIZE {
-                wh::send_message(handle, WM_SIZE, 0, 0);
+        let handler = bind_raw_event_handler_inner(&parent_handle, handle as usize, move |_hwnd, msg, _w, l| {
+            match msg {
+                WM_SIZE => {
+                    wh::send_message(handle, WM_SIZE, 0, 0);
+                    update_embedded(handle, &state.borrow());
+                },
+                WM_DRAWITEM => {
+                    let item = unsafe { &*(l as *const DRAWITEMSTRUCT) };
+                    if item.hwndItem == handle {
+                        let index = item.itemID as u8;
+                        let callback = state.borrow().draw.iter().find(|(i, _)| *i == index).map(|(_, cb)| cb.clone());
+                        if let Some(callback) = callback {
+                            let r = item.rcItem;
+                            callback(&StatusBarDrawItem { index, hdc: item.hDC, rect: [r.left, r.top, r.right, r.bottom] });
+                            return Some(1);
+                        }
+                    }
+                },
+                _ => {}
             }
 
             None
@@ -146,6 +531,59 @@ impl StatusBar {
         *self.handler0.borrow_mut() = Some(handler.unwrap());
     }
 
+    /// Handles the clicks on the cancel button and the reports of the jobs
+    fn hook_job_indicator(&self, cancel: HWND) -> Result<(), NwgError> {
+        use winapi::um::winuser::{WM_COMMAND, BN_CLICKED};
+        use winapi::um::commctrl::PBM_SETPOS;
+        use winapi::shared::minwindef::HIWORD;
+        use crate::bind_raw_event_handler_inner;
+
+        let state = self.state.clone();
+        let handler = bind_raw_event_handler_inner(&self.handle, 0, move |_hwnd, msg, w, l| {
+            match msg {
+                WM_COMMAND if l as HWND == cancel && HIWORD(w as u32) == BN_CLICKED => {
+                    if let Some(job) = state.borrow().job.as_ref() {
+                        cancel_job(job);
+                    }
+                },
+                wh::NWG_JOB_PROGRESS => {
+                    let mut state = state.borrow_mut();
+                    let job = match state.job.as_mut() {
+                        Some(job) if job.id == w && job.running => job,
+                        _ => { return Some(0); }
+                    };
+
+                    match l {
+                        JOB_FINISHED => {
+                            job.running = false;
+                            set_marquee(job.progress, false);
+                            unsafe {
+                                wh::set_window_visibility(job.progress, false);
+                                wh::set_window_visibility(job.cancel, false);
+                            }
+                        },
+                        JOB_INDETERMINATE => {
+                            set_marquee(job.progress, true);
+                        },
+                        position => {
+                            set_marquee(job.progress, false);
+                            wh::send_message(job.progress, PBM_SETPOS, position as WPARAM, 0);
+                        }
+                    }
+
+                    return Some(0);
+                },
+                _ => {}
+            }
+
+            None
+        })?;
+
+        *self.handler1.borrow_mut() = Some(handler);
+
+        Ok(())
+    }
+
 }
 
 impl Drop for StatusBar {
@@ -154,6 +592,17 @@ impl Drop for StatusBar {
         if let Some(h) = handler.as_ref() {
             drop(unbind_raw_event_handler(h));
         }
+
+        // Tells the worker of a running job to stop
+        if let Some(job) = self.state.borrow().job.as_ref() {
+            job.cancelled.store(true, Ordering::SeqCst);
+        }
+
+        let handler = self.handler1.borrow();
+        if let Some(h) = handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
         self.handle.destroy();
     }
 }
@@ -161,6 +610,7 @@ impl Drop for StatusBar {
 pub struct StatusBarBuilder<'a> {
     text: &'a str,
     font: Option<&'a Font>,
+    parts: Option<&'a [i32]>,
     parent: Option<ControlHandle>
 }
 
@@ -176,6 +626,11 @@ impl<'a> StatusBarBuilder<'a> {
         self
     }
 
+    pub fn parts(mut self, parts: &'a [i32]) -> StatusBarBuilder<'a> {
+        self.parts = Some(parts);
+        self
+    }
+
     pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> StatusBarBuilder<'a> {
         self.parent = Some(p.into());
         self
@@ -202,6 +657,10 @@ impl<'a> StatusBarBuilder<'a> {
             out.set_font(Font::global_default().as_ref());
         }
 
+        if let Some(parts) = self.parts {
+            out.set_parts(parts);
+        }
+
         out.set_text(0, self.text);
         out.hook_parent_resize();
 
@@ -215,3 +674,48 @@ impl PartialEq for StatusBar {
         self.handle == other.handle
     }
 }
+
+fn part_rect(handle: HWND, index: u8) -> RECT {
+    use winapi::um::commctrl::SB_GETRECT;
+
+    let mut rect: RECT = unsafe { mem::zeroed() };
+    wh::send_message(handle, SB_GETRECT, index as WPARAM, &mut rect as *mut RECT as LPARAM);
+    rect
+}
+
+/// Move the embedded controls over their part
+fn update_embedded(handle: HWND, state: &StatusBarState) {
+    use winapi::um::winuser::{SetWindowPos, SWP_NOZORDER, SWP_NOACTIVATE, SWP_NOOWNERZORDER};
+
+    for &(index, control) in state.embedded.iter() {
+        let r = part_rect(handle, index);
+        unsafe {
+            SetWindowPos(control, ptr::null_mut(), r.left + 1, r.top + 1, r.right - r.left - 2, r.bottom - r.top - 2, SWP_NOZORDER|SWP_NOACTIVATE|SWP_NOOWNERZORDER);
+        }
+    }
+}
+
+/// Cancels a job and disables the cancel button, so that the user knows the click was received
+fn cancel_job(job: &JobIndicator) {
+    if job.running {
+        job.cancelled.store(true, Ordering::SeqCst);
+        unsafe { wh::set_window_enabled(job.cancel, false); }
+    }
+}
+
+/// Switches the progress bar of the job indicator between a value and a marquee
+fn set_marquee(progress: HWND, enable: bool) {
+    use winapi::um::commctrl::{PBS_MARQUEE, PBM_SETMARQUEE};
+
+    let style = wh::get_style(progress);
+    if (style & PBS_MARQUEE != 0) == enable {
+        return;
+    }
+
+    match enable {
+        true => wh::set_style(progress, style | PBS_MARQUEE),
+        false => wh::set_style(progress, style & !PBS_MARQUEE),
+    }
+
+    wh::send_message(progress, PBM_SETMARQUEE, enable as WPARAM, 30);
+}
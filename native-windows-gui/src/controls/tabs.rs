@@ -3,6 +3,7 @@ use winapi::shared::windef::HWND;
 use winapi::um::winnt::LPWSTR;
 use winapi::um::winuser::{EnumChildWindows, WS_VISIBLE, WS_DISABLED, WS_EX_CONTROLPARENT};
 use crate::win32::{base_helper::{to_utf16, check_hwnd}, window_helper as wh};
+use crate::win32::high_dpi::Extent;
 use crate::{NwgError, Font, RawEventHandler, unbind_raw_event_handler};
 use super::{ControlBase, ControlHandle};
 use std::{mem, cell::RefCell};
@@ -60,8 +61,8 @@ impl TabsContainer {
 
     pub fn builder<'a>() -> TabsContainerBuilder<'a> {
         TabsContainerBuilder {
-            size: (300, 300),
-            position: (0, 0),
+            size: (300, 300).into(),
+            position: (0, 0).into(),
             parent: None,
             font: None,
             flags: None,
@@ -355,8 +356,8 @@ impl PartialEq for TabsContainer {
 
 
 pub struct TabsContainerBuilder<'a> {
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     parent: Option<ControlHandle>,
     font: Option<&'a Font>,
     flags: Option<TabsContainerFlags>,
@@ -378,13 +379,13 @@ impl<'a> TabsContainerBuilder<'a> {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> TabsContainerBuilder<'a> {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> TabsContainerBuilder<'a> {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> TabsContainerBuilder<'a> {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> TabsContainerBuilder<'a> {
+        self.position = pos.into();
         self
     }
 
@@ -0,0 +1,1249 @@
+use winapi::shared::windef::{HWND, HFONT, RECT};
+use winapi::shared::minwindef::WPARAM;
+use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_TABSTOP, WS_BORDER, WS_CHILD, WS_CLIPSIBLINGS, WS_VSCROLL, WS_HSCROLL, SB_VERT, SB_HORZ};
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
+use crate::win32::base_helper::check_hwnd;
+use crate::win32::paint_helper as ph;
+use crate::win32::resource_tracker::{self as rt, ResourceKind};
+use crate::{Font, Clipboard, NwgError, RawEventHandler, unbind_raw_event_handler};
+use super::{ControlBase, ControlHandle};
+use std::{mem, ptr, rc::Rc, cell::RefCell, collections::VecDeque};
+
+const NOT_BOUND: &'static str = "TerminalView is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: TerminalView handle is not HWND!";
+
+/// Margin at the left of the text, in logical pixels
+const MARGIN: i32 = 4;
+
+/// Lines scrolled by one notch of the mouse wheel
+const WHEEL_LINES: i64 = 3;
+
+const TAB_WIDTH: usize = 8;
+
+const DEFAULT_SCROLLBACK: usize = 10_000;
+
+/// The colors of the Windows console, in the ANSI order (black, red, green, yellow, blue, magenta, cyan, white, then the bright variants)
+const DEFAULT_PALETTE: [[u8; 3]; 16] = [
+    [12, 12, 12], [197, 15, 31], [19, 161, 14], [193, 156, 0], [0, 55, 218], [136, 23, 152], [58, 150, 221], [204, 204, 204],
+    [118, 118, 118], [231, 72, 86], [22, 198, 12], [249, 241, 165], [59, 120, 255], [180, 0, 158], [97, 214, 214], [242, 242, 242],
+];
+
+
+bitflags! {
+    /**
+        The terminal view flags
+
+        * NONE:     No flags. Equivalent to a invisible terminal view.
+        * VISIBLE:  The terminal view is immediatly visible after creation
+        * DISABLED: The terminal view cannot be focused by the user
+        * TAB_STOP: The terminal view can be selected using tab navigation
+        * BORDER:   The terminal view has a thin border
+    */
+    pub struct TerminalViewFlags: u32 {
+        const NONE = 0;
+        const VISIBLE = WS_VISIBLE;
+        const DISABLED = WS_DISABLED;
+        const TAB_STOP = WS_TABSTOP;
+        const BORDER = WS_BORDER;
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum TermColor {
+    Default,
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct CellStyle {
+    foreground: TermColor,
+    background: TermColor,
+    bold: bool,
+    underline: bool,
+    inverse: bool,
+}
+
+impl Default for CellStyle {
+    fn default() -> CellStyle {
+        CellStyle { foreground: TermColor::Default, background: TermColor::Default, bold: false, underline: false, inverse: false }
+    }
+}
+
+#[derive(Copy, Clone)]
+struct Cell {
+    ch: char,
+    style: CellStyle,
+}
+
+/// A position in the text. The line is counted from the first line ever written, so that it stays valid
+/// when the old lines are removed from the scrollback.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct TextPos {
+    line: u64,
+    column: usize,
+}
+
+/// State of the escape sequence parser. Sequences can be split between two writes.
+enum ParseState {
+    Text,
+    Escape,
+    Csi(String),
+    Osc,
+    OscEscape,
+}
+
+struct TerminalViewState {
+    lines: VecDeque<Vec<Cell>>,
+    /// Number of lines removed from the scrollback since the creation of the view
+    first_line: u64,
+    scrollback: usize,
+    cursor: usize,
+    style: CellStyle,
+    parse: ParseState,
+    /// Incomplete UTF-8 sequence at the end of the last `write_bytes`
+    pending_bytes: Vec<u8>,
+    /// Length of the longest line, used by the horizontal scrollbar
+    max_columns: usize,
+
+    top_line: usize,
+    left_column: usize,
+    anchor: Option<TextPos>,
+    caret: TextPos,
+    selecting: bool,
+
+    palette: [[u8; 3]; 16],
+    text_color: [u8; 3],
+    background_color: [u8; 3],
+    font: HFONT,
+    default_font: HFONT,
+    bold_font: HFONT,
+    char_size: (i32, i32),
+}
+
+impl Default for TerminalViewState {
+    fn default() -> TerminalViewState {
+        let mut lines = VecDeque::new();
+        lines.push_back(Vec::new());
+
+        TerminalViewState {
+            lines,
+            first_line: 0,
+            scrollback: DEFAULT_SCROLLBACK,
+            cursor: 0,
+            style: Default::default(),
+            parse: ParseState::Text,
+            pending_bytes: Vec::new(),
+            max_columns: 0,
+            top_line: 0,
+            left_column: 0,
+            anchor: None,
+            caret: TextPos { line: 0, column: 0 },
+            selecting: false,
+            palette: DEFAULT_PALETTE,
+            text_color: DEFAULT_PALETTE[7],
+            background_color: DEFAULT_PALETTE[0],
+            font: ptr::null_mut(),
+            default_font: ptr::null_mut(),
+            bold_font: ptr::null_mut(),
+            char_size: (8, 16),
+        }
+    }
+}
+
+impl Drop for TerminalViewState {
+    fn drop(&mut self) {
+        ph::delete_font(self.default_font);
+        ph::delete_font(self.bold_font);
+    }
+}
+
+/**
+A terminal view displays text written by a process, like the output of a compiler, with the colors of the ANSI escape
+sequences. The view keeps the last lines up to a scrollback limit and follows the new text when it is scrolled to the end.
+
+Supported sequences:
+  * SGR (`ESC [ ... m`): bold, underline, inverse, the 16 colors, the 256 colors palette and the 24 bits colors.
+  * `ESC [ K` to erase a line and `ESC [ G`, `ESC [ C`, `ESC [ D` to move the cursor on the line.
+  * Carriage return, backspace and tabs, so that progress bars redrawn on the same line are displayed correctly.
+
+The other sequences (including the OSC sequences, like the window title) are ignored. Lines are not wrapped.
+
+The user can select text with the mouse and copy it with ctrl+C. Ctrl+A selects the whole text.
+
+The control uses the "Consolas" font unless a font is set; the font must be monospaced.
+
+Requires the `terminal-view` feature.
+
+**Builder parameters:**
+  * `parent`:           **Required.** The terminal view parent container.
+  * `size`:             The terminal view size.
+  * `position`:         The terminal view position.
+  * `text`:             The initial text. Can contain escape sequences.
+  * `scrollback`:       The maximum number of lines kept in the view. Defaults to 10000.
+  * `text_color`:       The default text color.
+  * `background_color`: The default background color.
+  * `palette`:          The 16 ANSI colors.
+  * `font`:             A monospaced font.
+  * `flags`:            A combination of the TerminalViewFlags values.
+
+**Control events:**
+  * `MousePress(_)`: Generic mouse press events on the terminal view
+  * `OnKeyPress`: Generic key press event
+
+```rust
+use native_windows_gui as nwg;
+
+fn show_build_output(view: &nwg::TerminalView, output: &[u8]) {
+    view.write_bytes(output);
+    view.write("\x1b[1;32mFinished\x1b[0m\r\n");
+}
+```
+*/
+#[derive(Default)]
+pub struct TerminalView {
+    pub handle: ControlHandle,
+    state: Rc<RefCell<TerminalViewState>>,
+    handler0: RefCell<Option<RawEventHandler>>,
+}
+
+impl TerminalView {
+
+    pub fn builder<'a>() -> TerminalViewBuilder<'a> {
+        TerminalViewBuilder {
+            size: (500, 300).into(),
+            position: (0, 0).into(),
+            text: "",
+            scrollback: DEFAULT_SCROLLBACK,
+            text_color: None,
+            background_color: None,
+            palette: None,
+            font: None,
+            flags: None,
+            parent: None,
+        }
+    }
+
+    /// Appends text at the cursor. The text can contain escape sequences.
+    pub fn write(&self, text: &str) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        write_text(handle, &mut state, text);
+    }
+
+    /// Appends UTF-8 data at the cursor. A character split between two calls is decoded when the end of the character is written.
+    /// Invalid sequences are replaced by `U+FFFD`.
+    pub fn write_bytes(&self, data: &[u8]) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+
+        let mut bytes = mem::replace(&mut state.pending_bytes, Vec::new());
+        bytes.extend_from_slice(data);
+
+        let mut text = String::with_capacity(bytes.len());
+        let mut rest = &bytes[..];
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    break;
+                },
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    text.push_str(unsafe { std::str::from_utf8_unchecked(valid) });
+
+                    match e.error_len() {
+                        Some(len) => {
+                            text.push('\u{FFFD}');
+                            rest = &after[len..];
+                        },
+                        None => {
+                            // The end of the character is in the next write
+                            state.pending_bytes = after.to_vec();
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        write_text(handle, &mut state, &text);
+    }
+
+    /// Removes all the text and resets the colors and the cursor
+    pub fn clear(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+
+        state.first_line += state.lines.len() as u64;
+        state.lines.clear();
+        state.lines.push_back(Vec::new());
+        state.cursor = 0;
+        state.style = Default::default();
+        state.parse = ParseState::Text;
+        state.pending_bytes.clear();
+        state.max_columns = 0;
+        state.top_line = 0;
+        state.left_column = 0;
+        state.anchor = None;
+
+        update_scroll(handle, &mut state);
+        ph::redraw(handle);
+    }
+
+    /// Returns the text of the view, without the colors. Lines are separated by `\r\n`.
+    pub fn text(&self) -> String {
+        let state = self.state.borrow();
+        let last = state.lines.len() - 1;
+        let end = TextPos { line: state.first_line + last as u64, column: state.lines[last].len() };
+        text_range(&state, TextPos { line: state.first_line, column: 0 }, end)
+    }
+
+    /// Returns the number of lines in the view
+    pub fn line_count(&self) -> usize {
+        self.state.borrow().lines.len()
+    }
+
+    /// Returns the maximum number of lines kept in the view
+    pub fn scrollback(&self) -> usize {
+        self.state.borrow().scrollback
+    }
+
+    /// Sets the maximum number of lines kept in the view. The oldest lines are removed if there are too many lines.
+    pub fn set_scrollback(&self, lines: usize) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        state.scrollback = lines.max(1);
+        trim_scrollback(&mut state);
+        update_scroll(handle, &mut state);
+        ph::redraw(handle);
+    }
+
+    /// Returns the selected text, or an empty string if there is no selection
+    pub fn selected_text(&self) -> String {
+        let state = self.state.borrow();
+        match selection(&state) {
+            Some((start, end)) => text_range(&state, start, end),
+            None => String::new()
+        }
+    }
+
+    /// Selects the whole text
+    pub fn select_all(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        select_all(&mut self.state.borrow_mut());
+        ph::redraw(handle);
+    }
+
+    /// Removes the selection
+    pub fn clear_selection(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow_mut().anchor = None;
+        ph::redraw(handle);
+    }
+
+    /// Copies the selected text to the clipboard. Does nothing if there is no selection.
+    pub fn copy(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        copy_selection(handle, &self.state.borrow());
+    }
+
+    /// Scrolls the view to the last line. The view then follows the new text.
+    pub fn scroll_to_end(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut state = self.state.borrow_mut();
+        state.top_line = max_top_line(handle, &state);
+        update_scroll(handle, &mut state);
+        ph::redraw(handle);
+    }
+
+    /// Returns the default text color
+    pub fn text_color(&self) -> [u8; 3] {
+        self.state.borrow().text_color
+    }
+
+    /// Sets the default text color
+    pub fn set_text_color(&self, color: [u8; 3]) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow_mut().text_color = color;
+        ph::redraw(handle);
+    }
+
+    /// Returns the default background color
+    pub fn background_color(&self) -> [u8; 3] {
+        self.state.borrow().background_color
+    }
+
+    /// Sets the default background color
+    pub fn set_background_color(&self, color: [u8; 3]) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow_mut().background_color = color;
+        ph::redraw(handle);
+    }
+
+    /// Sets the 16 ANSI colors, in the ANSI order (black, red, green, yellow, blue, magenta, cyan, white, then the bright variants)
+    pub fn set_palette(&self, palette: [[u8; 3]; 16]) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow_mut().palette = palette;
+        ph::redraw(handle);
+    }
+
+    /// Returns the font of the control
+    pub fn font(&self) -> Option<Font> {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let font_handle = wh::get_window_font(handle);
+        if font_handle.is_null() {
+            None
+        } else {
+            Some(Font { handle: font_handle })
+        }
+    }
+
+    /// Sets the font of the control. The font must be monospaced. `None` restores the default font.
+    pub fn set_font(&self, font: Option<&Font>) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_font(handle, font.map(|f| f.handle), true); }
+    }
+
+    /// Returns true if the control currently has the keyboard focus
+    pub fn focus(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_focus(handle) }
+    }
+
+    /// Sets the keyboard focus on the terminal view
+    pub fn set_focus(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_focus(handle); }
+    }
+
+    /// Returns true if the control user can interact with the control, return false otherwise
+    pub fn enabled(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_enabled(handle) }
+    }
+
+    /// Enable or disable the control
+    pub fn set_enabled(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_enabled(handle, v) }
+    }
+
+    /// Returns true if the control is visible to the user. Will return true even if the
+    /// control is outside of the parent client view (ex: at the position (10000, 10000))
+    pub fn visible(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_visibility(handle) }
+    }
+
+    /// Show or hide the control to the user
+    pub fn set_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_visibility(handle, v) }
+    }
+
+    /// Returns the size of the terminal view in the parent window
+    pub fn size(&self) -> (u32, u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_size(handle) }
+    }
+
+    /// Sets the size of the terminal view in the parent window
+    pub fn set_size(&self, x: u32, y: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_size(handle, x, y, false) }
+    }
+
+    /// Returns the position of the terminal view in the parent window
+    pub fn position(&self) -> (i32, i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_position(handle) }
+    }
+
+    /// Sets the position of the terminal view in the parent window
+    pub fn set_position(&self, x: i32, y: i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_position(handle, x, y) }
+    }
+
+    /// Winapi class name used during control creation
+    pub fn class_name(&self) -> &'static str {
+        "NWG_TERMINAL_VIEW"
+    }
+
+    /// Winapi base flags used during window creation
+    pub fn flags(&self) -> u32 {
+        WS_VISIBLE | WS_TABSTOP | WS_BORDER
+    }
+
+    /// Winapi flags required by the control
+    pub fn forced_flags(&self) -> u32 {
+        WS_CHILD | WS_CLIPSIBLINGS | WS_VSCROLL | WS_HSCROLL
+    }
+
+    /// Handles the painting, the scrolling and the selection
+    fn hook_events(&self) {
+        use crate::bind_raw_event_handler_inner;
+        use winapi::um::winuser::{WM_PAINT, WM_ERASEBKGND, WM_SIZE, WM_SETFONT, WM_GETFONT, WM_VSCROLL, WM_HSCROLL, WM_MOUSEWHEEL,
+            WM_SETFOCUS, WM_KILLFOCUS, WM_GETDLGCODE, WM_LBUTTONDOWN, WM_MOUSEMOVE, WM_LBUTTONUP, WM_CAPTURECHANGED, WM_KEYDOWN,
+            DLGC_WANTARROWS, VK_SHIFT, GET_WHEEL_DELTA_WPARAM, WHEEL_DELTA};
+        use winapi::um::winuser::{SetFocus, SetCapture, ReleaseCapture, GetCapture, GetKeyState};
+        use winapi::shared::minwindef::{LOWORD, HIWORD};
+
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let state = self.state.clone();
+
+        let handler = bind_raw_event_handler_inner(&self.handle, 0, move |hwnd, msg, w, l| {
+            // Mouse coordinates are signed
+            let (x, y) = (LOWORD(l as u32) as i16 as i32, HIWORD(l as u32) as i16 as i32);
+
+            match msg {
+                WM_PAINT => {
+                    paint(hwnd, &state.borrow());
+                    return Some(0);
+                },
+                WM_ERASEBKGND => {
+                    // Everything is drawn in WM_PAINT
+                    return Some(1);
+                },
+                WM_SIZE => {
+                    // Showing or hiding a scrollbar resizes the control
+                    if let Ok(mut state) = state.try_borrow_mut() {
+                        update_scroll(hwnd, &mut state);
+                        ph::redraw(hwnd);
+                    }
+                },
+                WM_SETFONT => {
+                    let mut state = state.borrow_mut();
+                    state.font = w as HFONT;
+                    update_fonts(hwnd, &mut state);
+                    update_scroll(hwnd, &mut state);
+                    if l != 0 {
+                        ph::redraw(hwnd);
+                    }
+                    return Some(0);
+                },
+                WM_GETFONT => {
+                    return Some(state.borrow().font as _);
+                },
+                WM_GETDLGCODE => {
+                    return Some(DLGC_WANTARROWS);
+                },
+                WM_SETFOCUS | WM_KILLFOCUS => {
+                    ph::redraw(hwnd);
+                },
+                WM_VSCROLL => {
+                    let mut state = state.borrow_mut();
+                    let page = visible_size(hwnd, &state).1 as i64;
+                    let top = scroll_request(hwnd, SB_VERT as i32, w, state.top_line as i64, page);
+                    set_top_line(hwnd, &mut state, top);
+                    return Some(0);
+                },
+                WM_HSCROLL => {
+                    let mut state = state.borrow_mut();
+                    let page = visible_size(hwnd, &state).0 as i64;
+                    let left = scroll_request(hwnd, SB_HORZ as i32, w, state.left_column as i64, page);
+                    set_left_column(hwnd, &mut state, left);
+                    return Some(0);
+                },
+                WM_MOUSEWHEEL => {
+                    let notches = GET_WHEEL_DELTA_WPARAM(w) as i64 / WHEEL_DELTA as i64;
+                    let mut state = state.borrow_mut();
+                    let top = state.top_line as i64 - notches * WHEEL_LINES;
+                    set_top_line(hwnd, &mut state, top);
+                    return Some(0);
+                },
+                WM_LBUTTONDOWN => {
+                    unsafe { SetFocus(hwnd); }
+
+                    let extend = unsafe { GetKeyState(VK_SHIFT) < 0 };
+                    let mut state = state.borrow_mut();
+                    let pos = hit_test(&state, x, y);
+                    if !extend || state.anchor.is_none() {
+                        state.anchor = Some(pos);
+                    }
+                    state.caret = pos;
+                    state.selecting = true;
+
+                    unsafe { SetCapture(hwnd); }
+                    ph::redraw(hwnd);
+                },
+                WM_MOUSEMOVE => {
+                    let mut state = state.borrow_mut();
+                    if state.selecting {
+                        let pos = hit_test(&state, x, y);
+                        if pos != state.caret {
+                            state.caret = pos;
+                            scroll_to_pos(hwnd, &mut state, pos);
+                            ph::redraw(hwnd);
+                        }
+                    }
+                },
+                WM_LBUTTONUP => {
+                    state.borrow_mut().selecting = false;
+                    unsafe {
+                        if GetCapture() == hwnd {
+                            ReleaseCapture();
+                        }
+                    }
+                },
+                WM_CAPTURECHANGED => {
+                    state.borrow_mut().selecting = false;
+                },
+                WM_KEYDOWN => {
+                    key_down(hwnd, &mut state.borrow_mut(), w);
+                    return Some(0);
+                },
+                _ => {}
+            }
+
+            None
+        });
+
+        *self.handler0.borrow_mut() = Some(handler.unwrap());
+    }
+
+}
+
+impl Drop for TerminalView {
+    fn drop(&mut self) {
+        let handler = self.handler0.borrow();
+        if let Some(h) = handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
+        self.handle.destroy();
+    }
+}
+
+impl PartialEq for TerminalView {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for TerminalView {}
+
+pub struct TerminalViewBuilder<'a> {
+    size: Extent,
+    position: Extent,
+    text: &'a str,
+    scrollback: usize,
+    text_color: Option<[u8; 3]>,
+    background_color: Option<[u8; 3]>,
+    palette: Option<[[u8; 3]; 16]>,
+    font: Option<&'a Font>,
+    flags: Option<TerminalViewFlags>,
+    parent: Option<ControlHandle>
+}
+
+impl<'a> TerminalViewBuilder<'a> {
+
+    pub fn flags(mut self, flags: TerminalViewFlags) -> TerminalViewBuilder<'a> {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> TerminalViewBuilder<'a> {
+        self.size = size.into();
+        self
+    }
+
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> TerminalViewBuilder<'a> {
+        self.position = pos.into();
+        self
+    }
+
+    pub fn text(mut self, text: &'a str) -> TerminalViewBuilder<'a> {
+        self.text = text;
+        self
+    }
+
+    pub fn scrollback(mut self, lines: usize) -> TerminalViewBuilder<'a> {
+        self.scrollback = lines;
+        self
+    }
+
+    pub fn text_color(mut self, color: Option<[u8; 3]>) -> TerminalViewBuilder<'a> {
+        self.text_color = color;
+        self
+    }
+
+    pub fn background_color(mut self, color: Option<[u8; 3]>) -> TerminalViewBuilder<'a> {
+        self.background_color = color;
+        self
+    }
+
+    pub fn palette(mut self, palette: Option<[[u8; 3]; 16]>) -> TerminalViewBuilder<'a> {
+        self.palette = palette;
+        self
+    }
+
+    pub fn font(mut self, font: Option<&'a Font>) -> TerminalViewBuilder<'a> {
+        self.font = font;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> TerminalViewBuilder<'a> {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut TerminalView) -> Result<(), NwgError> {
+        let flags = self.flags.map(|f| f.bits()).unwrap_or(out.flags());
+
+        let parent = match self.parent {
+            Some(p) => Ok(p),
+            None => Err(NwgError::no_parent("TerminalView"))
+        }?;
+
+        *out = Default::default();
+
+        out.handle = ControlBase::build_hwnd()
+            .class_name(out.class_name())
+            .forced_flags(out.forced_flags())
+            .flags(flags)
+            .size(self.size)
+            .position(self.position)
+            .parent(Some(parent))
+            .build()?;
+
+        {
+            let mut state = out.state.borrow_mut();
+            state.scrollback = self.scrollback.max(1);
+            if let Some(palette) = self.palette {
+                state.palette = palette;
+                state.text_color = palette[7];
+                state.background_color = palette[0];
+            }
+            if let Some(color) = self.text_color {
+                state.text_color = color;
+            }
+            if let Some(color) = self.background_color {
+                state.background_color = color;
+            }
+            state.default_font = ph::monospace_font();
+        }
+
+        out.hook_events();
+        out.set_font(self.font);
+
+        if !self.text.is_empty() {
+            out.write(self.text);
+        }
+
+        Ok(())
+    }
+
+}
+
+/// Creates the bold variant of the current font and computes the size of a character
+fn update_fonts(handle: HWND, state: &mut TerminalViewState) {
+    use winapi::um::winuser::{GetDC, ReleaseDC};
+    use winapi::um::wingdi::{SelectObject, GetObjectW, CreateFontIndirectW, GetTextMetricsW, TEXTMETRICW, LOGFONTW, FW_BOLD};
+
+    let font = ph::font_or(state.font, state.default_font);
+
+    unsafe {
+        ph::delete_font(state.bold_font);
+
+        let mut info: LOGFONTW = mem::zeroed();
+        GetObjectW(font as _, mem::size_of::<LOGFONTW>() as i32, &mut info as *mut LOGFONTW as _);
+        info.lfWeight = FW_BOLD;
+        state.bold_font = CreateFontIndirectW(&info);
+        rt::track(ResourceKind::Font, state.bold_font);
+
+        let dc = GetDC(handle);
+        let old_font = SelectObject(dc, font as _);
+
+        let mut metrics: TEXTMETRICW = mem::zeroed();
+        GetTextMetricsW(dc, &mut metrics);
+
+        SelectObject(dc, old_font);
+        ReleaseDC(handle, dc);
+
+        state.char_size = (metrics.tmAveCharWidth.max(1), (metrics.tmHeight + metrics.tmExternalLeading).max(1));
+    }
+}
+
+/// Number of columns and lines fully visible in the view
+fn visible_size(handle: HWND, state: &TerminalViewState) -> (usize, usize) {
+    let (width, height) = ph::client_size(handle);
+    let (cw, line) = state.char_size;
+    (((width - ph::px(MARGIN)) / cw).max(1) as usize, (height / line).max(1) as usize)
+}
+
+fn max_top_line(handle: HWND, state: &TerminalViewState) -> usize {
+    state.lines.len().saturating_sub(visible_size(handle, state).1)
+}
+
+fn max_left_column(handle: HWND, state: &TerminalViewState) -> usize {
+    (state.max_columns + 1).saturating_sub(visible_size(handle, state).0)
+}
+
+fn update_scroll(handle: HWND, state: &mut TerminalViewState) {
+    use winapi::um::winuser::{SetScrollInfo, SCROLLINFO, SIF_RANGE, SIF_PAGE, SIF_POS};
+
+    state.top_line = state.top_line.min(max_top_line(handle, state));
+    state.left_column = state.left_column.min(max_left_column(handle, state));
+
+    let (columns, lines) = visible_size(handle, state);
+    let bars = [
+        (SB_VERT, state.lines.len(), lines, state.top_line),
+        (SB_HORZ, state.max_columns + 1, columns, state.left_column),
+    ];
+
+    for &(bar, count, page, pos) in bars.iter() {
+        let info = SCROLLINFO {
+            cbSize: mem::size_of::<SCROLLINFO>() as u32,
+            fMask: SIF_RANGE | SIF_PAGE | SIF_POS,
+            nMin: 0,
+            nMax: count.max(1).min(i32::max_value() as usize) as i32 - 1,
+            nPage: page as u32,
+            nPos: pos.min(i32::max_value() as usize) as i32,
+            nTrackPos: 0,
+        };
+
+        unsafe { SetScrollInfo(handle, bar as i32, &info, 1); }
+    }
+}
+
+fn set_top_line(handle: HWND, state: &mut TerminalViewState, top: i64) {
+    let top = (top.max(0) as usize).min(max_top_line(handle, state));
+    if top != state.top_line {
+        state.top_line = top;
+        update_scroll(handle, state);
+        ph::redraw(handle);
+    }
+}
+
+fn set_left_column(handle: HWND, state: &mut TerminalViewState, left: i64) {
+    let left = (left.max(0) as usize).min(max_left_column(handle, state));
+    if left != state.left_column {
+        state.left_column = left;
+        update_scroll(handle, state);
+        ph::redraw(handle);
+    }
+}
+
+/// Computes the new position of a scrollbar from a WM_VSCROLL or WM_HSCROLL message
+fn scroll_request(handle: HWND, bar: i32, w: WPARAM, current: i64, page: i64) -> i64 {
+    use winapi::um::winuser::{GetScrollInfo, SCROLLINFO, SIF_TRACKPOS, SB_LINEUP, SB_LINEDOWN, SB_PAGEUP, SB_PAGEDOWN,
+        SB_THUMBTRACK, SB_THUMBPOSITION, SB_TOP, SB_BOTTOM};
+    use winapi::shared::minwindef::LOWORD;
+
+    // SB_LINELEFT, SB_PAGELEFT, ... have the same values as the vertical codes
+    match LOWORD(w as u32) as isize {
+        SB_LINEUP => current - 1,
+        SB_LINEDOWN => current + 1,
+        SB_PAGEUP => current - page,
+        SB_PAGEDOWN => current + page,
+        SB_TOP => 0,
+        SB_BOTTOM => i64::max_value(),
+        SB_THUMBTRACK | SB_THUMBPOSITION => unsafe {
+            // The position in the message is limited to 16 bits
+            let mut info: SCROLLINFO = mem::zeroed();
+            info.cbSize = mem::size_of::<SCROLLINFO>() as u32;
+            info.fMask = SIF_TRACKPOS;
+            GetScrollInfo(handle, bar, &mut info);
+            info.nTrackPos as i64
+        },
+        _ => current
+    }
+}
+
+/// Scrolls the view so that `pos` is visible
+fn scroll_to_pos(handle: HWND, state: &mut TerminalViewState, pos: TextPos) {
+    let (columns, lines) = visible_size(handle, state);
+    let line = pos.line.saturating_sub(state.first_line) as usize;
+
+    if line < state.top_line {
+        state.top_line = line;
+    } else if line >= state.top_line + lines {
+        state.top_line = line + 1 - lines;
+    }
+
+    if pos.column < state.left_column {
+        state.left_column = pos.column;
+    } else if pos.column > state.left_column + columns {
+        state.left_column = pos.column - columns;
+    }
+
+    update_scroll(handle, state);
+}
+
+/// Returns the position of the character boundary nearest to a point
+fn hit_test(state: &TerminalViewState, x: i32, y: i32) -> TextPos {
+    let (cw, line_height) = state.char_size;
+    let line = (state.top_line as i64 + (y as i64).div_euclid(line_height as i64)).max(0) as usize;
+    let line = line.min(state.lines.len() - 1);
+
+    let column = (state.left_column as i64 + ((x - ph::px(MARGIN)) as i64 + (cw / 2) as i64).div_euclid(cw as i64)).max(0) as usize;
+    let column = column.min(state.lines[line].len());
+
+    TextPos { line: state.first_line + line as u64, column }
+}
+
+/// Returns the start and the end (exclusive) of the selection, clamped to the lines in the scrollback
+fn selection(state: &TerminalViewState) -> Option<(TextPos, TextPos)> {
+    let anchor = state.anchor?;
+    let (start, end) = match anchor <= state.caret {
+        true => (anchor, state.caret),
+        false => (state.caret, anchor)
+    };
+
+    let first = TextPos { line: state.first_line, column: 0 };
+    let start = start.max(first);
+    match start < end {
+        true => Some((start, end)),
+        false => None
+    }
+}
+
+fn select_all(state: &mut TerminalViewState) {
+    let last = state.lines.len() - 1;
+    state.anchor = Some(TextPos { line: state.first_line, column: 0 });
+    state.caret = TextPos { line: state.first_line + last as u64, column: state.lines[last].len() };
+}
+
+/// Returns the text between two positions. The trailing spaces of the lines are removed.
+fn text_range(state: &TerminalViewState, start: TextPos, end: TextPos) -> String {
+    let mut text = String::new();
+    let first = start.line.saturating_sub(state.first_line) as usize;
+    let last = (end.line.saturating_sub(state.first_line) as usize).min(state.lines.len() - 1);
+
+    for index in first..=last {
+        let cells = &state.lines[index];
+        let from = match index == first {
+            true => start.column.min(cells.len()),
+            false => 0
+        };
+        let to = match index == last {
+            true => end.column.min(cells.len()),
+            false => cells.len()
+        };
+
+        let line: String = cells[from..to.max(from)].iter().map(|c| c.ch).collect();
+        text.push_str(line.trim_end_matches(' '));
+        if index != last {
+            text.push_str("\r\n");
+        }
+    }
+
+    text
+}
+
+fn copy_selection(handle: HWND, state: &TerminalViewState) {
+    if let Some((start, end)) = selection(state) {
+        let text = text_range(state, start, end);
+        Clipboard::set_data_text(ControlHandle::Hwnd(handle), &text);
+    }
+}
+
+fn key_down(handle: HWND, state: &mut TerminalViewState, key: WPARAM) {
+    use winapi::um::winuser::{GetKeyState, VK_UP, VK_DOWN, VK_LEFT, VK_RIGHT, VK_PRIOR, VK_NEXT, VK_HOME, VK_END, VK_CONTROL};
+
+    let ctrl = unsafe { GetKeyState(VK_CONTROL) < 0 };
+    let (_, page) = visible_size(handle, state);
+    let (top, left) = (state.top_line as i64, state.left_column as i64);
+
+    match key as i32 {
+        VK_UP => set_top_line(handle, state, top - 1),
+        VK_DOWN => set_top_line(handle, state, top + 1),
+        VK_PRIOR => set_top_line(handle, state, top - page as i64),
+        VK_NEXT => set_top_line(handle, state, top + page as i64),
+        VK_HOME => set_top_line(handle, state, 0),
+        VK_END => set_top_line(handle, state, i64::max_value()),
+        VK_LEFT => set_left_column(handle, state, left - 1),
+        VK_RIGHT => set_left_column(handle, state, left + 1),
+        0x41 if ctrl => {
+            // Ctrl+A
+            select_all(state);
+            ph::redraw(handle);
+        },
+        0x43 if ctrl => {
+            // Ctrl+C
+            copy_selection(handle, state);
+        },
+        _ => {}
+    }
+}
+
+/// Parses `text` and updates the scroll position. The view follows the text if it was scrolled to the end.
+fn write_text(handle: HWND, state: &mut TerminalViewState, text: &str) {
+    let follow = state.top_line >= max_top_line(handle, state);
+    let first_line = state.first_line;
+
+    for c in text.chars() {
+        parse_char(state, c);
+    }
+
+    // Keep the same text in the view when old lines are removed
+    let removed = (state.first_line - first_line) as usize;
+    state.top_line = match follow {
+        true => max_top_line(handle, state),
+        false => state.top_line.saturating_sub(removed)
+    };
+
+    update_scroll(handle, state);
+    ph::redraw(handle);
+}
+
+fn parse_char(state: &mut TerminalViewState, c: char) {
+    let parse = mem::replace(&mut state.parse, ParseState::Text);
+    state.parse = match parse {
+        ParseState::Text => match c {
+            '\x1b' => ParseState::Escape,
+            '\n' => { new_line(state); ParseState::Text },
+            '\r' => { state.cursor = 0; ParseState::Text },
+            '\x08' => { state.cursor = state.cursor.saturating_sub(1); ParseState::Text },
+            '\t' => {
+                let spaces = TAB_WIDTH - state.cursor % TAB_WIDTH;
+                for _ in 0..spaces {
+                    put_char(state, ' ');
+                }
+                ParseState::Text
+            },
+            c if (c as u32) < 0x20 || c == '\x7f' => ParseState::Text,
+            c => { put_char(state, c); ParseState::Text },
+        },
+        ParseState::Escape => match c {
+            '[' => ParseState::Csi(String::new()),
+            ']' => ParseState::Osc,
+            _ => ParseState::Text,
+        },
+        ParseState::Csi(mut params) => match c {
+            '\x20'..='\x3f' => {
+                params.push(c);
+                ParseState::Csi(params)
+            },
+            '\x40'..='\x7e' => {
+                execute_csi(state, c, &params);
+                ParseState::Text
+            },
+            // Invalid sequence
+            _ => ParseState::Text,
+        },
+        ParseState::Osc => match c {
+            '\x07' => ParseState::Text,
+            '\x1b' => ParseState::OscEscape,
+            _ => ParseState::Osc,
+        },
+        ParseState::OscEscape => ParseState::Text,
+    };
+}
+
+fn put_char(state: &mut TerminalViewState, ch: char) {
+    let cursor = state.cursor;
+    let style = state.style;
+    let line = state.lines.back_mut().unwrap();
+
+    if cursor < line.len() {
+        line[cursor] = Cell { ch, style };
+    } else {
+        while line.len() < cursor {
+            line.push(Cell { ch: ' ', style: Default::default() });
+        }
+        line.push(Cell { ch, style });
+    }
+
+    state.cursor += 1;
+    state.max_columns = state.max_columns.max(state.cursor);
+}
+
+fn new_line(state: &mut TerminalViewState) {
+    state.lines.push_back(Vec::new());
+    state.cursor = 0;
+    trim_scrollback(state);
+}
+
+fn trim_scrollback(state: &mut TerminalViewState) {
+    while state.lines.len() > state.scrollback {
+        state.lines.pop_front();
+        state.first_line += 1;
+    }
+}
+
+fn execute_csi(state: &mut TerminalViewState, command: char, params: &str) {
+    // Private sequences (ex: `ESC [ ? 25 l`) are not supported
+    if params.starts_with(|c| c == '?' || c == '>' || c == '<' || c == '=') {
+        return;
+    }
+
+    let values: Vec<u32> = params.split(|c| c == ';' || c == ':').map(|v| v.parse().unwrap_or(0)).collect();
+    let first = values.first().cloned().unwrap_or(0);
+    let count = first.max(1) as usize;
+
+    match command {
+        'm' => select_graphic_rendition(state, &values),
+        'K' => {
+            let cursor = state.cursor;
+            let line = state.lines.back_mut().unwrap();
+            match first {
+                0 => line.truncate(cursor),
+                1 => {
+                    for cell in line.iter_mut().take(cursor + 1) {
+                        *cell = Cell { ch: ' ', style: Default::default() };
+                    }
+                },
+                _ => line.clear(),
+            }
+        },
+        'G' => { state.cursor = count - 1; },
+        'C' => { state.cursor += count; },
+        'D' => { state.cursor = state.cursor.saturating_sub(count); },
+        _ => {}
+    }
+}
+
+fn select_graphic_rendition(state: &mut TerminalViewState, values: &[u32]) {
+    let mut i = 0;
+    while i < values.len() {
+        match values[i] {
+            0 => { state.style = Default::default(); },
+            1 => { state.style.bold = true; },
+            4 => { state.style.underline = true; },
+            7 => { state.style.inverse = true; },
+            22 => { state.style.bold = false; },
+            24 => { state.style.underline = false; },
+            27 => { state.style.inverse = false; },
+            v @ 30..=37 => { state.style.foreground = TermColor::Indexed((v - 30) as u8); },
+            v @ 90..=97 => { state.style.foreground = TermColor::Indexed((v - 90 + 8) as u8); },
+            39 => { state.style.foreground = TermColor::Default; },
+            v @ 40..=47 => { state.style.background = TermColor::Indexed((v - 40) as u8); },
+            v @ 100..=107 => { state.style.background = TermColor::Indexed((v - 100 + 8) as u8); },
+            49 => { state.style.background = TermColor::Default; },
+            v @ 38 | v @ 48 => {
+                let (color, used) = extended_color(&values[i+1..]);
+                if let Some(color) = color {
+                    match v {
+                        38 => { state.style.foreground = color; },
+                        _ => { state.style.background = color; },
+                    }
+                }
+                i += used;
+            },
+            _ => {}
+        }
+
+        i += 1;
+    }
+}
+
+/// Parses the parameters of a 256 colors (`5;n`) or 24 bits (`2;r;g;b`) color. Returns the color and the number of parameters used.
+fn extended_color(values: &[u32]) -> (Option<TermColor>, usize) {
+    match values {
+        [5, n, ..] => (Some(TermColor::Indexed(*n as u8)), 2),
+        [2, r, g, b, ..] => (Some(TermColor::Rgb(*r as u8, *g as u8, *b as u8)), 4),
+        _ => (None, values.len()),
+    }
+}
+
+/// Converts a color of the 256 colors palette
+fn indexed_color(state: &TerminalViewState, index: u8) -> [u8; 3] {
+    match index {
+        0..=15 => state.palette[index as usize],
+        16..=231 => {
+            let i = index - 16;
+            let level = |v: u8| match v { 0 => 0, v => 55 + v * 40 };
+            [level(i / 36), level((i / 6) % 6), level(i % 6)]
+        },
+        _ => {
+            let gray = 8 + (index - 232) * 10;
+            [gray, gray, gray]
+        }
+    }
+}
+
+fn resolve_color(state: &TerminalViewState, color: TermColor, default: [u8; 3]) -> [u8; 3] {
+    match color {
+        TermColor::Default => default,
+        TermColor::Indexed(i) => indexed_color(state, i),
+        TermColor::Rgb(r, g, b) => [r, g, b],
+    }
+}
+
+/// Paints the view in a memory bitmap and copies the result on the screen
+fn paint(handle: HWND, state: &TerminalViewState) {
+    use winapi::um::winuser::{GetSysColor, COLOR_HIGHLIGHT, COLOR_HIGHLIGHTTEXT};
+    use winapi::um::wingdi::{SelectObject, SetBkMode, SetTextColor, TextOutW, TRANSPARENT};
+
+    ph::paint_buffered(handle, ph::font_or(state.font, state.default_font), |buffer_dc, width, height| unsafe {
+        let (cw, line_height) = state.char_size;
+        let (columns, lines) = visible_size(handle, state);
+
+        let background = ph::rgb(state.background_color);
+        ph::fill(buffer_dc, &RECT { left: 0, top: 0, right: width, bottom: height }, background);
+        SetBkMode(buffer_dc, TRANSPARENT as i32);
+
+        let selection = selection(state);
+        let selected = |line: u64, column: usize| match selection {
+            Some((start, end)) => {
+                let pos = TextPos { line, column };
+                pos >= start && pos < end
+            },
+            None => false
+        };
+
+        let (highlight, highlight_text) = (GetSysColor(COLOR_HIGHLIGHT), GetSysColor(COLOR_HIGHLIGHTTEXT));
+        let end_line = (state.top_line + lines + 1).min(state.lines.len());
+
+        for index in state.top_line..end_line {
+            let cells = &state.lines[index];
+            let line = state.first_line + index as u64;
+            let y = (index - state.top_line) as i32 * line_height;
+            let end_column = (state.left_column + columns + 1).min(cells.len());
+
+            // Draw the consecutive cells with the same style together
+            let mut column = state.left_column;
+            while column < end_column {
+                let style = cells[column].style;
+                let is_selected = selected(line, column);
+                let mut next = column + 1;
+                while next < end_column && cells[next].style == style && selected(line, next) == is_selected {
+                    next += 1;
+                }
+
+                let (mut fg, mut bg) = (
+                    ph::rgb(resolve_color(state, style.foreground, state.text_color)),
+                    ph::rgb(resolve_color(state, style.background, state.background_color))
+                );
+                if style.inverse {
+                    mem::swap(&mut fg, &mut bg);
+                }
+                if is_selected {
+                    fg = highlight_text;
+                    bg = highlight;
+                }
+
+                let x = ph::px(MARGIN) + (column - state.left_column) as i32 * cw;
+                let right = x + (next - column) as i32 * cw;
+                if bg != background {
+                    ph::fill(buffer_dc, &RECT { left: x, top: y, right, bottom: y + line_height }, bg);
+                }
+
+                let text: Vec<u16> = cells[column..next].iter().flat_map(|c| {
+                    let mut buffer = [0u16; 2];
+                    c.ch.encode_utf16(&mut buffer).to_vec()
+                }).collect();
+
+                let font = match style.bold && !state.bold_font.is_null() {
+                    true => state.bold_font,
+                    false => ph::font_or(state.font, state.default_font)
+                };
+
+                SelectObject(buffer_dc, font as _);
+                SetTextColor(buffer_dc, fg);
+                TextOutW(buffer_dc, x, y, text.as_ptr(), text.len() as i32);
+
+                if style.underline {
+                    ph::fill(buffer_dc, &RECT { left: x, top: y + line_height - 1, right, bottom: y + line_height }, fg);
+                }
+
+                column = next;
+            }
+        }
+    });
+}
@@ -1,6 +1,7 @@
 use winapi::shared::minwindef::{WPARAM, LPARAM};
 use winapi::um::winuser::{WS_VSCROLL, WS_HSCROLL, ES_AUTOVSCROLL, ES_AUTOHSCROLL, WS_VISIBLE, WS_DISABLED, WS_TABSTOP};
 use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
 use crate::{Font, NwgError};
 use super::{ControlBase, ControlHandle};
 use std::ops::Range;
@@ -84,8 +85,8 @@ impl TextBox {
     pub fn builder<'a>() -> TextBoxBuilder<'a> {
         TextBoxBuilder {
             text: "",
-            size: (100, 25),
-            position: (0, 0),
+            size: (100, 25).into(),
+            position: (0, 0).into(),
             flags: None,
             ex_flags: 0,
             limit: 0,
@@ -379,8 +380,8 @@ impl Drop for TextBox {
 }
 pub struct TextBoxBuilder<'a> {
     text: &'a str,
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     flags: Option<TextBoxFlags>,
     ex_flags: u32,
     limit: usize,
@@ -407,13 +408,13 @@ impl<'a> TextBoxBuilder<'a> {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> TextBoxBuilder<'a> {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> TextBoxBuilder<'a> {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> TextBoxBuilder<'a> {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> TextBoxBuilder<'a> {
+        self.position = pos.into();
         self
     }
 
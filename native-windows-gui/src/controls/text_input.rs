@@ -7,8 +7,10 @@ use winapi::um::{
     wingdi::DeleteObject,
 };
 use crate::win32::window_helper as wh; 
+use crate::win32::high_dpi::Extent;
 use crate::win32::base_helper::{check_hwnd, to_utf16};
 use crate::{Font, NwgError, HTextAlign, RawEventHandler};
+use crate::win32::resource_tracker::{self as rt, ResourceKind};
 use super::{ControlBase, ControlHandle};
 use std::cell::RefCell;
 use std::ops::Range;
@@ -90,8 +92,8 @@ impl TextInput {
         TextInputBuilder {
             text: "",
             placeholder_text: None,
-            size: (100, 25),
-            position: (0, 0),
+            size: (100, 25).into(),
+            position: (0, 0).into(),
             flags: None,
             ex_flags: 0,
             limit: 0,
@@ -370,6 +372,7 @@ impl TextInput {
         let brush = match bg {
             Some(c) => {
                 let b = unsafe { CreateSolidBrush(RGB(c[0], c[1], c[2])) };
+                rt::track(ResourceKind::Brush, b);
                 self.background_brush = Some(b);
                 b
             },
@@ -470,6 +473,7 @@ impl Drop for TextInput {
         }
         
         if let Some(bg) = self.background_brush {
+            rt::untrack(bg);
             unsafe { DeleteObject(bg as _); }
         }
         
@@ -480,8 +484,8 @@ impl Drop for TextInput {
 pub struct TextInputBuilder<'a> {
     text: &'a str,
     placeholder_text: Option<&'a str>,
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     flags: Option<TextInputFlags>,
     ex_flags: u32,
     limit: usize,
@@ -516,13 +520,13 @@ impl<'a> TextInputBuilder<'a> {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> TextInputBuilder<'a> {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> TextInputBuilder<'a> {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> TextInputBuilder<'a> {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> TextInputBuilder<'a> {
+        self.position = pos.into();
         self
     }
 
@@ -0,0 +1,492 @@
+/*!
+    A title bar drawn by the application. The caption of the window is removed and its client area is extended up to the top
+    border of the window, so that the application can paint the caption strip itself and host any control in it (a search box,
+    tabs, a menu button, ...). The system keeps the window borders, the shadow and the resizing.
+
+    The strip at the top of the window (`height` logical pixels) behaves like the caption: the user can drag the window with it,
+    and double click it to maximize the window. The controls placed in the strip receive the mouse input as usual. A `Label` is
+    transparent to the hit testing: dragging a label in the strip also moves the window.
+
+    The caption buttons are drawn by the application. Their position is registered with `add_region`, so that the system
+    can handle them like the real caption buttons (ex: the snap layouts flyout of Windows 11 on the maximize button). The buttons
+    are painted in the `OnPaint` event of the window, using `hovered` and `pressed` to draw their state. With `dwm_buttons(true)`,
+    the system draws the caption buttons instead, in the top right corner of the strip.
+
+    Requires the `title-bar` feature.
+
+    **Builder parameters:**
+      * `parent`:      **Required.** The top level window.
+      * `height`:      The height of the caption strip in logical pixels. Defaults to 32.
+      * `dwm_buttons`: If the system draws the minimize, maximize and close buttons. Defaults to `false`.
+
+    **Control events:**
+      * `OnPaint`: Sent to the window. The caption buttons must be redrawn.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn build_title_bar(title_bar: &mut nwg::TitleBar, window: &nwg::Window) {
+        nwg::TitleBar::builder()
+            .height(40)
+            .parent(window)
+            .build(title_bar)
+            .expect("Failed to build the title bar");
+
+        let (width, _) = window.size();
+        title_bar.add_region(nwg::TitleBarHit::Close, (width as i32 - 46, 0), (46, 40));
+        title_bar.add_region(nwg::TitleBarHit::Maximize, (width as i32 - 92, 0), (46, 40));
+        title_bar.add_region(nwg::TitleBarHit::Minimize, (width as i32 - 138, 0), (46, 40));
+    }
+    ```
+*/
+use winapi::shared::windef::{HWND, POINT, RECT};
+use winapi::um::winuser::{HTCAPTION, HTCLIENT, HTMINBUTTON, HTMAXBUTTON, HTCLOSE};
+use crate::win32::window_helper as wh;
+use crate::win32::base_helper::check_hwnd;
+use crate::win32::high_dpi;
+use crate::{NwgError, RawEventHandler, unbind_raw_event_handler};
+use super::ControlHandle;
+use std::{rc::Rc, cell::RefCell, ptr, mem};
+
+const NOT_BOUND: &'static str = "TitleBar is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: TitleBar handle is not HWND!";
+
+
+/// The role of a region of the caption strip. See `TitleBar::add_region`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TitleBarHit {
+    /// The region drags the window
+    Caption,
+
+    /// The region minimizes the window when clicked
+    Minimize,
+
+    /// The region maximizes or restores the window when clicked
+    Maximize,
+
+    /// The region closes the window when clicked
+    Close,
+
+    /// The region is part of the client area. The window receives the mouse events as usual.
+    Client,
+}
+
+impl TitleBarHit {
+
+    fn hit_code(&self) -> isize {
+        match self {
+            TitleBarHit::Caption => HTCAPTION,
+            TitleBarHit::Minimize => HTMINBUTTON,
+            TitleBarHit::Maximize => HTMAXBUTTON,
+            TitleBarHit::Close => HTCLOSE,
+            TitleBarHit::Client => HTCLIENT,
+        }
+    }
+
+    fn from_hit_code(code: isize) -> Option<TitleBarHit> {
+        match code {
+            HTMINBUTTON => Some(TitleBarHit::Minimize),
+            HTMAXBUTTON => Some(TitleBarHit::Maximize),
+            HTCLOSE => Some(TitleBarHit::Close),
+            _ => None
+        }
+    }
+
+}
+
+#[derive(Default)]
+struct TitleBarState {
+    /// Height of the caption strip in logical pixels
+    height: u32,
+    dwm_buttons: bool,
+    /// Regions registered by the application: kind, position and size in logical pixels
+    regions: Vec<(TitleBarHit, (i32, i32), (u32, u32))>,
+    hovered: Option<TitleBarHit>,
+    pressed: Option<TitleBarHit>,
+    tracking: bool,
+}
+
+/**
+    An application drawn title bar. See the module documentation.
+*/
+#[derive(Default)]
+pub struct TitleBar {
+    /// The handle of the window that owns the title bar
+    pub handle: ControlHandle,
+    state: Rc<RefCell<TitleBarState>>,
+    handler0: RefCell<Option<RawEventHandler>>,
+}
+
+impl TitleBar {
+
+    pub fn builder() -> TitleBarBuilder {
+        TitleBarBuilder {
+            height: 32,
+            dwm_buttons: false,
+            parent: None,
+        }
+    }
+
+    /// Returns the height of the caption strip in logical pixels
+    pub fn height(&self) -> u32 {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow().height
+    }
+
+    /// Sets the height of the caption strip in logical pixels
+    pub fn set_height(&self, height: u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let dwm_buttons = {
+            let mut state = self.state.borrow_mut();
+            state.height = height;
+            state.dwm_buttons
+        };
+
+        unsafe { extend_frame(handle, height, dwm_buttons); }
+    }
+
+    /// Returns `true` if the system draws the caption buttons
+    pub fn dwm_buttons(&self) -> bool {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow().dwm_buttons
+    }
+
+    /**
+        Registers a region of the caption strip. The position is relative to the client area of the window, in logical pixels.
+        The regions are tested in the order they were added, before the default caption behaviour.
+
+        The regions do not follow the window: reposition them in the `OnResize` event of the window.
+    */
+    pub fn add_region(&self, kind: TitleBarHit, position: (i32, i32), size: (u32, u32)) {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow_mut().regions.push((kind, position, size));
+    }
+
+    /// Removes all the regions registered with `add_region`
+    pub fn clear_regions(&self) {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let mut state = self.state.borrow_mut();
+        state.regions.clear();
+        state.hovered = None;
+        state.pressed = None;
+    }
+
+    /// Returns the regions registered with `add_region`
+    pub fn regions(&self) -> Vec<(TitleBarHit, (i32, i32), (u32, u32))> {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow().regions.clone()
+    }
+
+    /// Returns what is under a point of the client area of the window, in logical pixels.
+    /// Returns `None` if the point is outside the caption strip.
+    pub fn hit_test(&self, position: (i32, i32)) -> Option<TitleBarHit> {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        hit_test(&self.state.borrow(), position)
+    }
+
+    /// Returns the caption button under the mouse, if any
+    pub fn hovered(&self) -> Option<TitleBarHit> {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow().hovered
+    }
+
+    /// Returns the caption button being pressed, if any
+    pub fn pressed(&self) -> Option<TitleBarHit> {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        self.state.borrow().pressed
+    }
+
+    /// Returns `true` if the window is active. Inactive title bars are usually drawn with a lighter text.
+    pub fn active(&self) -> bool {
+        use winapi::um::winuser::GetActiveWindow;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { GetActiveWindow() == handle }
+    }
+
+    /// Handles the non client messages of the window
+    fn hook_events(&self) {
+        use crate::bind_raw_event_handler_inner;
+        use winapi::um::winuser::{WM_NCCALCSIZE, WM_NCHITTEST, WM_NCMOUSEMOVE, WM_NCMOUSELEAVE, WM_MOUSEMOVE, WM_NCLBUTTONDOWN,
+            WM_NCLBUTTONUP, WM_NCLBUTTONDBLCLK, WM_NCACTIVATE, WM_SYSCOMMAND, SC_MINIMIZE, SC_MAXIMIZE, SC_RESTORE, SC_CLOSE,
+            NCCALCSIZE_PARAMS, HTTOP, HTTOPLEFT, HTTOPRIGHT};
+        use winapi::um::winuser::{DefWindowProcW, IsZoomed, ScreenToClient, InvalidateRect};
+        use winapi::um::dwmapi::DwmDefWindowProc;
+        use winapi::shared::minwindef::{LOWORD, HIWORD};
+
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        let state = self.state.clone();
+
+        let handler = bind_raw_event_handler_inner(&self.handle, 0, move |hwnd, msg, w, l| {
+            unsafe {
+                if state.borrow().dwm_buttons {
+                    let mut result = 0;
+                    if DwmDefWindowProc(hwnd, msg, w, l, &mut result) != 0 {
+                        return Some(result);
+                    }
+                }
+
+                match msg {
+                    WM_NCCALCSIZE if w == 1 => {
+                        // Keeps the left, right and bottom borders. The top border becomes part of the client area.
+                        let params = &mut *(l as *mut NCCALCSIZE_PARAMS);
+                        let top = params.rgrc[0].top;
+                        DefWindowProcW(hwnd, msg, w, l);
+
+                        // A maximized window overflows the monitor by the size of its borders
+                        params.rgrc[0].top = match IsZoomed(hwnd) != 0 {
+                            true => top + frame_thickness(),
+                            false => top
+                        };
+
+                        return Some(0);
+                    },
+                    WM_NCACTIVATE => {
+                        // Prevents the system from painting an inactive caption over the client area
+                        InvalidateRect(hwnd, ptr::null(), 0);
+                        return Some(DefWindowProcW(hwnd, msg, w, -1));
+                    },
+                    WM_NCHITTEST => {
+                        let mut point = POINT { x: LOWORD(l as u32) as i16 as i32, y: HIWORD(l as u32) as i16 as i32 };
+                        ScreenToClient(hwnd, &mut point);
+
+                        // The top resize border is in the client area
+                        let border = frame_thickness();
+                        if IsZoomed(hwnd) == 0 && point.y >= 0 && point.y < border {
+                            let (width, _) = client_size(hwnd);
+                            return match point.x {
+                                x if x < border * 2 => Some(HTTOPLEFT),
+                                x if x >= width - border * 2 => Some(HTTOPRIGHT),
+                                _ => Some(HTTOP)
+                            };
+                        }
+
+                        // The side borders keep their default behaviour
+                        let (width, _) = client_size(hwnd);
+                        if point.x < 0 || point.x >= width {
+                            return None;
+                        }
+
+                        let position = high_dpi::physical_to_logical(point.x, point.y);
+                        return hit_test(&state.borrow(), position).map(|hit| hit.hit_code());
+                    },
+                    WM_NCMOUSEMOVE | WM_MOUSEMOVE => {
+                        let hovered = match msg {
+                            WM_NCMOUSEMOVE => TitleBarHit::from_hit_code(w as isize),
+                            _ => None
+                        };
+
+                        let track = {
+                            let mut state = state.borrow_mut();
+                            let changed = state.hovered != hovered;
+                            state.hovered = hovered;
+
+                            if msg == WM_NCMOUSEMOVE && !state.tracking {
+                                state.tracking = true;
+                                true
+                            } else {
+                                if changed { InvalidateRect(hwnd, ptr::null(), 0); }
+                                false
+                            }
+                        };
+
+                        if track {
+                            track_mouse_leave(hwnd);
+                            InvalidateRect(hwnd, ptr::null(), 0);
+                        }
+                    },
+                    WM_NCMOUSELEAVE => {
+                        let mut state = state.borrow_mut();
+                        state.tracking = false;
+                        if state.hovered.is_some() || state.pressed.is_some() {
+                            state.hovered = None;
+                            state.pressed = None;
+                            InvalidateRect(hwnd, ptr::null(), 0);
+                        }
+                    },
+                    WM_NCLBUTTONDOWN | WM_NCLBUTTONDBLCLK => {
+                        // The default handling paints the legacy caption buttons
+                        if let Some(button) = TitleBarHit::from_hit_code(w as isize) {
+                            state.borrow_mut().pressed = Some(button);
+                            InvalidateRect(hwnd, ptr::null(), 0);
+                            return Some(0);
+                        }
+                    },
+                    WM_NCLBUTTONUP => {
+                        let released = TitleBarHit::from_hit_code(w as isize);
+                        let pressed = state.borrow_mut().pressed.take();
+                        if pressed.is_some() {
+                            InvalidateRect(hwnd, ptr::null(), 0);
+                        }
+
+                        if let Some(button) = released {
+                            // The command is sent after the state is released, because it can destroy the window
+                            if pressed == Some(button) {
+                                let command = match button {
+                                    TitleBarHit::Minimize => SC_MINIMIZE,
+                                    TitleBarHit::Maximize if IsZoomed(hwnd) != 0 => SC_RESTORE,
+                                    TitleBarHit::Maximize => SC_MAXIMIZE,
+                                    _ => SC_CLOSE,
+                                };
+
+                                wh::post_message(hwnd, WM_SYSCOMMAND, command, 0);
+                            }
+
+                            return Some(0);
+                        }
+                    },
+                    _ => {}
+                }
+            }
+
+            None
+        });
+
+        *self.handler0.borrow_mut() = Some(handler.unwrap());
+    }
+
+}
+
+impl Drop for TitleBar {
+    fn drop(&mut self) {
+        let handler = self.handler0.borrow();
+        if let Some(h) = handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
+        // Restores the caption if the window outlives the title bar
+        if let Some(handle) = self.handle.hwnd() {
+            unsafe {
+                if winapi::um::winuser::IsWindow(handle) != 0 {
+                    extend_frame(handle, 0, false);
+                }
+            }
+        }
+    }
+}
+
+impl PartialEq for TitleBar {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for TitleBar {}
+
+pub struct TitleBarBuilder {
+    height: u32,
+    dwm_buttons: bool,
+    parent: Option<ControlHandle>
+}
+
+impl TitleBarBuilder {
+
+    pub fn height(mut self, height: u32) -> TitleBarBuilder {
+        self.height = height;
+        self
+    }
+
+    pub fn dwm_buttons(mut self, dwm_buttons: bool) -> TitleBarBuilder {
+        self.dwm_buttons = dwm_buttons;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> TitleBarBuilder {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut TitleBar) -> Result<(), NwgError> {
+        let parent = match self.parent {
+            Some(p) => Ok(p),
+            None => Err(NwgError::no_parent("TitleBar"))
+        }?;
+
+        let handle = match parent.hwnd() {
+            Some(h) => Ok(h),
+            None => Err(NwgError::control_create("TitleBar parent must be a window"))
+        }?;
+
+        crate::win32::ui_thread::check_thread(handle)?;
+
+        *out = Default::default();
+        out.handle = parent;
+
+        {
+            let mut state = out.state.borrow_mut();
+            state.height = self.height;
+            state.dwm_buttons = self.dwm_buttons;
+        }
+
+        out.hook_events();
+
+        unsafe { extend_frame(handle, self.height, self.dwm_buttons); }
+
+        Ok(())
+    }
+
+}
+
+/// Tests the registered regions, then the caption strip
+fn hit_test(state: &TitleBarState, position: (i32, i32)) -> Option<TitleBarHit> {
+    let (x, y) = position;
+
+    let region = state.regions.iter().find(|(_, (rx, ry), (rw, rh))| {
+        x >= *rx && y >= *ry && x < *rx + (*rw as i32) && y < *ry + (*rh as i32)
+    });
+
+    match region {
+        Some((kind, _, _)) => Some(*kind),
+        None if y >= 0 && y < state.height as i32 => Some(TitleBarHit::Caption),
+        None => None
+    }
+}
+
+/// Extends the frame of the window into its client area and recomputes the non client area.
+/// A `height` of 0 restores the default frame.
+unsafe fn extend_frame(handle: HWND, height: u32, dwm_buttons: bool) {
+    use winapi::um::dwmapi::DwmExtendFrameIntoClientArea;
+    use winapi::um::uxtheme::MARGINS;
+    use winapi::um::winuser::{SetWindowPos, SWP_FRAMECHANGED, SWP_NOMOVE, SWP_NOSIZE, SWP_NOZORDER, SWP_NOACTIVATE};
+
+    // One pixel keeps the shadow of the window. The system draws its buttons over the extended frame.
+    let top = match (height, dwm_buttons) {
+        (0, _) => 0,
+        (h, true) => high_dpi::logical_to_physical(0, h as i32).1,
+        (_, false) => 1,
+    };
+
+    let margins = MARGINS { cxLeftWidth: 0, cxRightWidth: 0, cyTopHeight: top, cyBottomHeight: 0 };
+    DwmExtendFrameIntoClientArea(handle, &margins);
+
+    SetWindowPos(handle, ptr::null_mut(), 0, 0, 0, 0, SWP_FRAMECHANGED | SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE);
+}
+
+/// Size of the resize border of a window, in physical pixels
+unsafe fn frame_thickness() -> i32 {
+    use winapi::um::winuser::{GetSystemMetrics, SM_CYFRAME, SM_CXPADDEDBORDER};
+    GetSystemMetrics(SM_CYFRAME) + GetSystemMetrics(SM_CXPADDEDBORDER)
+}
+
+unsafe fn client_size(handle: HWND) -> (i32, i32) {
+    use winapi::um::winuser::GetClientRect;
+
+    let mut rect: RECT = mem::zeroed();
+    GetClientRect(handle, &mut rect);
+    (rect.right - rect.left, rect.bottom - rect.top)
+}
+
+/// Requests a `WM_NCMOUSELEAVE` when the mouse leaves the non client area
+unsafe fn track_mouse_leave(handle: HWND) {
+    use winapi::um::winuser::{TrackMouseEvent, TRACKMOUSEEVENT, TME_LEAVE, TME_NONCLIENT};
+
+    let mut track = TRACKMOUSEEVENT {
+        cbSize: mem::size_of::<TRACKMOUSEEVENT>() as u32,
+        dwFlags: TME_LEAVE | TME_NONCLIENT,
+        hwndTrack: handle,
+        dwHoverTime: 0,
+    };
+
+    TrackMouseEvent(&mut track);
+}
@@ -0,0 +1,762 @@
+use winapi::shared::windef::{HWND, HMENU, RECT};
+use winapi::shared::minwindef::{WPARAM, LPARAM};
+use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED};
+use winapi::um::commctrl::{TBSTYLE_FLAT, TBSTYLE_LIST, TBSTYLE_WRAPABLE, TBSTYLE_TOOLTIPS, CCS_BOTTOM, CCS_NODIVIDER,
+  BTNS_BUTTON, BTNS_SEP, BTNS_CHECK, BTNS_DROPDOWN, BTNS_WHOLEDROPDOWN, BTNS_AUTOSIZE, BTNS_SHOWTEXT,
+  TBSTATE_ENABLED, TBSTATE_CHECKED, TBBUTTON};
+use crate::win32::window_helper as wh;
+use crate::win32::base_helper::{check_hwnd, to_utf16, from_utf16};
+use crate::{NwgError, ImageList, Menu, RawEventHandler, unbind_raw_event_handler};
+use super::{ControlBase, ControlHandle};
+use std::{mem, ptr, rc::Rc, cell::RefCell};
+
+const NOT_BOUND: &'static str = "Toolbar is not yet bound to a winapi object";
+const BAD_HANDLE: &'static str = "INTERNAL ERROR: Toolbar handle is not HWND!";
+
+/// Command id of the chevron button. Button ids must be lower than this value.
+const CHEVRON_ID: i32 = 0xFFFE;
+
+
+bitflags! {
+
+    /**
+        The toolbar flags
+
+        * NONE:       No flags. Equivalent to a invisible toolbar.
+        * VISIBLE:    The toolbar is immediatly visible after creation
+        * DISABLED:   The toolbar cannot be interacted with by the user. It also has a grayed out look.
+        * FLAT:       Transparent buttons. The button borders are only shown when the mouse hovers them.
+        * LIST:       The button text is displayed on the right of the button image instead of under it.
+        * WRAPABLE:   The buttons wrap on multiple lines when the toolbar is too narrow. Do not use with a chevron.
+        * TOOLTIPS:   Display the button text in a tooltip
+        * BOTTOM:     The toolbar is placed at the bottom of the parent instead of the top.
+        * NO_DIVIDER: Do not draw the two pixels highlight at the top of the toolbar.
+    */
+    pub struct ToolbarFlags: u32 {
+        const NONE = 0;
+        const VISIBLE = WS_VISIBLE;
+        const DISABLED = WS_DISABLED;
+        const FLAT = TBSTYLE_FLAT;
+        const LIST = TBSTYLE_LIST;
+        const WRAPABLE = TBSTYLE_WRAPABLE;
+        const TOOLTIPS = TBSTYLE_TOOLTIPS;
+        const BOTTOM = CCS_BOTTOM;
+        const NO_DIVIDER = CCS_NODIVIDER;
+    }
+}
+
+/// The kind of a toolbar button
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ToolbarButtonStyle {
+    /// A push button
+    Button,
+
+    /// A button that toggles between the checked and unchecked states when clicked
+    Check,
+
+    /// A button with a dropdown arrow. Clicking the button raises `OnToolbarClick` and
+    /// clicking the arrow raises `OnToolbarDropdown` (or shows the attached menu).
+    Dropdown,
+
+    /// A button that only acts as a dropdown. Clicking it raises `OnToolbarDropdown` (or shows the attached menu).
+    WholeDropdown,
+
+    /// A separator between two group of buttons
+    Separator,
+}
+
+/**
+    Parameters of a button added to a toolbar.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    let save = nwg::ToolbarButton::new(10, "Save").image(0);
+    let bold = nwg::ToolbarButton::check(11, "Bold").image(1).checked(true);
+    let sep = nwg::ToolbarButton::separator();
+    ```
+*/
+#[derive(Clone, Debug)]
+pub struct ToolbarButton {
+    /// The command id of the button. Returned by `EventData::on_toolbar_button`.
+    pub id: u32,
+    pub text: Option<String>,
+
+    /// Index of the image in the toolbar image list
+    pub image: Option<i32>,
+    pub style: ToolbarButtonStyle,
+    pub checked: bool,
+    pub enabled: bool,
+}
+
+impl ToolbarButton {
+
+    pub fn new<'a>(id: u32, text: &'a str) -> ToolbarButton {
+        ToolbarButton {
+            id,
+            text: Some(text.to_string()),
+            image: None,
+            style: ToolbarButtonStyle::Button,
+            checked: false,
+            enabled: true,
+        }
+    }
+
+    pub fn check<'a>(id: u32, text: &'a str) -> ToolbarButton {
+        ToolbarButton { style: ToolbarButtonStyle::Check, ..ToolbarButton::new(id, text) }
+    }
+
+    pub fn dropdown<'a>(id: u32, text: &'a str) -> ToolbarButton {
+        ToolbarButton { style: ToolbarButtonStyle::Dropdown, ..ToolbarButton::new(id, text) }
+    }
+
+    pub fn separator() -> ToolbarButton {
+        ToolbarButton {
+            id: 0,
+            text: None,
+            image: None,
+            style: ToolbarButtonStyle::Separator,
+            checked: false,
+            enabled: true,
+        }
+    }
+
+    pub fn image(mut self, image: i32) -> ToolbarButton {
+        self.image = Some(image);
+        self
+    }
+
+    pub fn style(mut self, style: ToolbarButtonStyle) -> ToolbarButton {
+        self.style = style;
+        self
+    }
+
+    pub fn checked(mut self, checked: bool) -> ToolbarButton {
+        self.checked = checked;
+        self
+    }
+
+    pub fn enabled(mut self, enabled: bool) -> ToolbarButton {
+        self.enabled = enabled;
+        self
+    }
+
+}
+
+/// Internal state shared with the parent hook
+#[derive(Default)]
+struct ToolbarState {
+    /// Popup menus attached to dropdown buttons: (button id, menu owner, menu)
+    menus: Vec<(u32, HWND, HMENU)>,
+
+    /// Buttons hidden because they do not fit in the toolbar
+    overflow: Vec<u32>,
+
+    /// If the toolbar has a chevron button
+    chevron: bool,
+}
+
+/**
+A toolbar is a row of buttons displayed at the top (or bottom) of a window. Each button can have an image,
+a text, a checked state or a dropdown menu.
+
+The toolbar resize itself to fit the width of its parent. When the `chevron` builder parameter is set, the buttons that
+do not fit in the toolbar are replaced by a chevron button that lists them in a popup menu.
+
+Requires the `toolbar` feature.
+
+**Builder parameters:**
+  * `parent`:     **Required.** The toolbar parent container.
+  * `flags`:      A combination of the ToolbarFlags values.
+  * `ex_flags`:   A combination of win32 window extended flags. Unlike `flags`, ex_flags must be used straight from winapi
+  * `image_list`: The image list used by the toolbar buttons
+  * `buttons`:    The buttons of the toolbar
+  * `chevron`:    Show the buttons that do not fit in the toolbar in a chevron menu
+
+**Control events:**
+  * `OnToolbarClick`: When a toolbar button is clicked. Use `EventData::on_toolbar_button` to get the button id.
+  * `OnToolbarDropdown`: When the dropdown arrow of a button without an attached menu is clicked.
+  * `MousePress(_)`: Generic mouse press events on the toolbar
+  * `OnMouseMove`: Generic mouse mouse event
+  * `OnMouseWheel`: Generic mouse wheel event
+
+```rust
+use native_windows_gui as nwg;
+fn build_toolbar(toolbar: &mut nwg::Toolbar, window: &nwg::Window, icons: &nwg::ImageList) {
+    nwg::Toolbar::builder()
+        .image_list(Some(icons))
+        .buttons(vec![
+            nwg::ToolbarButton::new(1, "New").image(0),
+            nwg::ToolbarButton::new(2, "Save").image(1),
+            nwg::ToolbarButton::separator(),
+            nwg::ToolbarButton::dropdown(3, "Export").image(2),
+        ])
+        .chevron(true)
+        .parent(window)
+        .build(toolbar);
+}
+```
+*/
+#[derive(Default)]
+pub struct Toolbar {
+    pub handle: ControlHandle,
+    state: Rc<RefCell<ToolbarState>>,
+    handler0: RefCell<Option<RawEventHandler>>,
+}
+
+impl Toolbar {
+
+    pub fn builder<'a>() -> ToolbarBuilder<'a> {
+        ToolbarBuilder {
+            flags: None,
+            ex_flags: 0,
+            image_list: None,
+            buttons: Vec::new(),
+            chevron: false,
+            parent: None
+        }
+    }
+
+    /// Add a button at the end of the toolbar (before the chevron, if any)
+    pub fn add_button(&self, button: &ToolbarButton) {
+        self.insert_button(usize::max_value(), button);
+    }
+
+    /// Insert a button at `index`. If `index` is out of bounds, the button is added at the end of the toolbar (before the chevron, if any).
+    pub fn insert_button(&self, index: usize, button: &ToolbarButton) {
+        use winapi::um::commctrl::TB_INSERTBUTTONW;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+
+        let text = button.text.as_ref().map(|t| to_utf16(t));
+        let info = button_struct(button, text.as_ref());
+        let index = index.min(self.len()) as WPARAM;
+
+        wh::send_message(handle, TB_INSERTBUTTONW, index, &info as *const TBBUTTON as LPARAM);
+        self.update_overflow();
+    }
+
+    /// Remove the button with the command `id`. Returns `false` if there was no button with this id.
+    pub fn remove_button(&self, id: u32) -> bool {
+        use winapi::um::commctrl::TB_DELETEBUTTON;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let removed = match button_index(handle, id) {
+            Some(index) => wh::send_message(handle, TB_DELETEBUTTON, index as WPARAM, 0) != 0,
+            None => false
+        };
+
+        if removed {
+            let mut state = self.state.borrow_mut();
+            state.menus.retain(|&(i, _, _)| i != id);
+            state.overflow.retain(|&i| i != id);
+        }
+
+        removed
+    }
+
+    /// Return the number of buttons in the toolbar, excluding the chevron button
+    pub fn len(&self) -> usize {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let count = self.button_count(handle);
+        match self.state.borrow().chevron {
+            true => count.saturating_sub(1),
+            false => count
+        }
+    }
+
+    /// Return the checked state of the button with the command `id`
+    pub fn checked(&self, id: u32) -> bool {
+        use winapi::um::commctrl::TB_ISBUTTONCHECKED;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::send_message(handle, TB_ISBUTTONCHECKED, id as WPARAM, 0) != 0
+    }
+
+    /// Set the checked state of the button with the command `id`
+    pub fn set_checked(&self, id: u32, checked: bool) {
+        use winapi::um::commctrl::TB_CHECKBUTTON;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::send_message(handle, TB_CHECKBUTTON, id as WPARAM, checked as LPARAM);
+    }
+
+    /// Return `true` if the button with the command `id` is enabled
+    pub fn button_enabled(&self, id: u32) -> bool {
+        use winapi::um::commctrl::TB_ISBUTTONENABLED;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::send_message(handle, TB_ISBUTTONENABLED, id as WPARAM, 0) != 0
+    }
+
+    /// Enable or disable the button with the command `id`
+    pub fn set_button_enabled(&self, id: u32, enabled: bool) {
+        use winapi::um::commctrl::TB_ENABLEBUTTON;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::send_message(handle, TB_ENABLEBUTTON, id as WPARAM, enabled as LPARAM);
+    }
+
+    /// Return the text of the button with the command `id`
+    pub fn button_text(&self, id: u32) -> String {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        button_text(handle, id)
+    }
+
+    /// Set the text of the button with the command `id`
+    pub fn set_button_text<'a>(&self, id: u32, text: &'a str) {
+        use winapi::um::commctrl::{TB_SETBUTTONINFOW, TBBUTTONINFOW, TBIF_TEXT};
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let mut text = to_utf16(text);
+
+        let mut info: TBBUTTONINFOW = unsafe { mem::zeroed() };
+        info.cbSize = mem::size_of::<TBBUTTONINFOW>() as u32;
+        info.dwMask = TBIF_TEXT;
+        info.pszText = text.as_mut_ptr();
+
+        wh::send_message(handle, TB_SETBUTTONINFOW, id as WPARAM, &info as *const TBBUTTONINFOW as LPARAM);
+        self.update_overflow();
+    }
+
+    /// Set the image of the button with the command `id`. `image` is an index in the toolbar image list.
+    pub fn set_button_image(&self, id: u32, image: i32) {
+        use winapi::um::commctrl::TB_CHANGEBITMAP;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::send_message(handle, TB_CHANGEBITMAP, id as WPARAM, image as LPARAM);
+    }
+
+    /// Attach a popup menu to the dropdown button with the command `id`. The menu is shown under the button
+    /// when its dropdown arrow is clicked. The menu must be created with `popup(true)`.
+    pub fn attach_menu(&self, id: u32, menu: &Menu) {
+        let (owner, hmenu) = match menu.handle.pop_hmenu() {
+            Some(v) => v,
+            None => panic!("The menu attached to a toolbar button must be a popup menu")
+        };
+
+        let mut state = self.state.borrow_mut();
+        state.menus.retain(|&(i, _, _)| i != id);
+        state.menus.push((id, owner, hmenu));
+    }
+
+    /// Remove the popup menu attached to the button with the command `id`
+    pub fn detach_menu(&self, id: u32) {
+        self.state.borrow_mut().menus.retain(|&(i, _, _)| i != id);
+    }
+
+    /// Set the image list of the toolbar buttons
+    pub fn set_image_list(&self, list: Option<&ImageList>) {
+        use winapi::um::commctrl::TB_SETIMAGELIST;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let list_handle = list.map(|l| l.handle).unwrap_or(ptr::null_mut());
+
+        wh::send_message(handle, TB_SETIMAGELIST, 0, list_handle as LPARAM);
+        self.autosize();
+    }
+
+    /// Return the command id of the buttons hidden in the chevron menu
+    pub fn overflow(&self) -> Vec<u32> {
+        self.state.borrow().overflow.clone()
+    }
+
+    /// Resize the toolbar to fit its parent and update the buttons shown in the chevron menu.
+    /// This is done automatically when the parent is resized.
+    pub fn autosize(&self) {
+        use winapi::um::commctrl::TB_AUTOSIZE;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        wh::send_message(handle, TB_AUTOSIZE, 0, 0);
+        self.update_overflow();
+    }
+
+    /// Return true if the control user can interact with the control, return false otherwise
+    pub fn enabled(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_enabled(handle) }
+    }
+
+    /// Enable or disable the control
+    pub fn set_enabled(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_enabled(handle, v) }
+    }
+
+    /// Return true if the control is visible to the user. Will return true even if the
+    /// control is outside of the parent client view (ex: at the position (10000, 10000))
+    pub fn visible(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_visibility(handle) }
+    }
+
+    /// Show or hide the control to the user
+    pub fn set_visible(&self, v: bool) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::set_window_visibility(handle, v) }
+    }
+
+    /// Return the size of the toolbar in the parent window
+    pub fn size(&self) -> (u32, u32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_size(handle) }
+    }
+
+    /// Return the position of the toolbar in the parent window
+    pub fn position(&self) -> (i32, i32) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        unsafe { wh::get_window_position(handle) }
+    }
+
+    /// Winapi class name used during control creation
+    pub fn class_name(&self) -> &'static str {
+        "ToolbarWindow32"
+    }
+
+    /// Winapi base flags used during window creation
+    pub fn flags(&self) -> u32 {
+        WS_VISIBLE | TBSTYLE_FLAT | TBSTYLE_LIST
+    }
+
+    /// Winapi flags required by the control
+    pub fn forced_flags(&self) -> u32 {
+        use winapi::um::winuser::WS_CHILD;
+
+        WS_CHILD
+    }
+
+    fn button_count(&self, handle: HWND) -> usize {
+        use winapi::um::commctrl::TB_BUTTONCOUNT;
+        wh::send_message(handle, TB_BUTTONCOUNT, 0, 0) as usize
+    }
+
+    fn update_overflow(&self) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        update_overflow(handle, &mut self.state.borrow_mut());
+    }
+
+    /// Resize the toolbar with its parent and show the attached menus / chevron menu
+    fn hook_parent(&self) {
+        use crate::bind_raw_event_handler_inner;
+        use winapi::um::winuser::{WM_NOTIFY, WM_SIZE, NMHDR};
+        use winapi::um::commctrl::{TBN_DROPDOWN, NMTOOLBARW, TB_AUTOSIZE, TBDDRET_DEFAULT};
+        use winapi::shared::basetsd::UINT_PTR;
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let parent_handle = ControlHandle::Hwnd(wh::get_window_parent(handle));
+        let state = self.state.clone();
+
+        let handler = bind_raw_event_handler_inner(&parent_handle, handle as UINT_PTR, move |hwnd, msg, _w, l| {
+            match msg {
+                WM_SIZE => {
+                    wh::send_message(handle, TB_AUTOSIZE, 0, 0);
+                    update_overflow(handle, &mut state.borrow_mut());
+                },
+                WM_NOTIFY => {
+                    let nmhdr = unsafe { &*(l as *const NMHDR) };
+                    if nmhdr.hwndFrom == handle && nmhdr.code == TBN_DROPDOWN {
+                        let info = unsafe { &*(l as *const NMTOOLBARW) };
+                        let id = info.iItem as u32;
+
+                        if info.iItem == CHEVRON_ID {
+                            let overflow = state.borrow().overflow.clone();
+                            show_overflow_menu(handle, hwnd, &overflow, info.rcButton);
+                            return Some(TBDDRET_DEFAULT);
+                        }
+
+                        let menu = state.borrow().menus.iter().find(|&&(i, _, _)| i == id).map(|&(_, owner, hmenu)| (owner, hmenu));
+                        if let Some((owner, hmenu)) = menu {
+                            show_menu(handle, owner, hmenu, info.rcButton);
+                            return Some(TBDDRET_DEFAULT);
+                        }
+                    }
+                },
+                _ => {}
+            }
+
+            None
+        });
+
+        *self.handler0.borrow_mut() = Some(handler.unwrap());
+    }
+
+}
+
+impl Drop for Toolbar {
+    fn drop(&mut self) {
+        let handler = self.handler0.borrow();
+        if let Some(h) = handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
+        self.handle.destroy();
+    }
+}
+
+impl PartialEq for Toolbar {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for Toolbar {}
+
+pub struct ToolbarBuilder<'a> {
+    flags: Option<ToolbarFlags>,
+    ex_flags: u32,
+    image_list: Option<&'a ImageList>,
+    buttons: Vec<ToolbarButton>,
+    chevron: bool,
+    parent: Option<ControlHandle>
+}
+
+impl<'a> ToolbarBuilder<'a> {
+
+    pub fn flags(mut self, flags: ToolbarFlags) -> ToolbarBuilder<'a> {
+        self.flags = Some(flags);
+        self
+    }
+
+    pub fn ex_flags(mut self, flags: u32) -> ToolbarBuilder<'a> {
+        self.ex_flags = flags;
+        self
+    }
+
+    pub fn image_list(mut self, list: Option<&'a ImageList>) -> ToolbarBuilder<'a> {
+        self.image_list = list;
+        self
+    }
+
+    pub fn buttons(mut self, buttons: Vec<ToolbarButton>) -> ToolbarBuilder<'a> {
+        self.buttons = buttons;
+        self
+    }
+
+    pub fn chevron(mut self, chevron: bool) -> ToolbarBuilder<'a> {
+        self.chevron = chevron;
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> ToolbarBuilder<'a> {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut Toolbar) -> Result<(), NwgError> {
+        use winapi::um::commctrl::{TB_BUTTONSTRUCTSIZE, TB_SETEXTENDEDSTYLE, TBSTYLE_EX_DRAWDDARROWS, TBSTYLE_EX_MIXEDBUTTONS};
+
+        let flags = self.flags.map(|f| f.bits()).unwrap_or(out.flags());
+
+        let parent = match self.parent {
+            Some(p) => Ok(p),
+            None => Err(NwgError::no_parent("Toolbar"))
+        }?;
+
+        *out = Default::default();
+
+        out.handle = ControlBase::build_hwnd()
+            .class_name(out.class_name())
+            .forced_flags(out.forced_flags())
+            .flags(flags)
+            .ex_flags(self.ex_flags)
+            .parent(Some(parent))
+            .build()?;
+
+        let handle = out.handle.hwnd().unwrap();
+        wh::send_message(handle, TB_BUTTONSTRUCTSIZE, mem::size_of::<TBBUTTON>() as WPARAM, 0);
+        wh::send_message(handle, TB_SETEXTENDEDSTYLE, 0, (TBSTYLE_EX_DRAWDDARROWS | TBSTYLE_EX_MIXEDBUTTONS) as LPARAM);
+
+        if self.image_list.is_some() {
+            out.set_image_list(self.image_list);
+        }
+
+        for button in self.buttons.iter() {
+            out.add_button(button);
+        }
+
+        if self.chevron {
+            let chevron = ToolbarButton {
+                id: CHEVRON_ID as u32,
+                text: Some("\u{00BB}".to_string()),
+                image: None,
+                style: ToolbarButtonStyle::WholeDropdown,
+                checked: false,
+                enabled: true,
+            };
+
+            out.add_button(&chevron);
+            out.state.borrow_mut().chevron = true;
+        }
+
+        out.hook_parent();
+        out.autosize();
+
+        Ok(())
+    }
+
+}
+
+fn button_struct(button: &ToolbarButton, text: Option<&Vec<u16>>) -> TBBUTTON {
+    use winapi::um::commctrl::I_IMAGENONE;
+
+    let style = match button.style {
+        ToolbarButtonStyle::Button => BTNS_BUTTON,
+        ToolbarButtonStyle::Check => BTNS_CHECK,
+        ToolbarButtonStyle::Dropdown => BTNS_DROPDOWN,
+        ToolbarButtonStyle::WholeDropdown => BTNS_WHOLEDROPDOWN,
+        ToolbarButtonStyle::Separator => BTNS_SEP,
+    };
+
+    let mut state = 0;
+    if button.enabled { state |= TBSTATE_ENABLED; }
+    if button.checked { state |= TBSTATE_CHECKED; }
+
+    let mut info: TBBUTTON = unsafe { mem::zeroed() };
+    info.idCommand = button.id as i32;
+    info.iBitmap = button.image.unwrap_or(I_IMAGENONE);
+    info.fsState = state;
+
+    match (button.style, text) {
+        (ToolbarButtonStyle::Separator, _) | (_, None) => {
+            info.fsStyle = style as u8;
+            info.iString = -1;
+        },
+        (_, Some(text)) => {
+            info.fsStyle = (style | BTNS_AUTOSIZE | BTNS_SHOWTEXT) as u8;
+            info.iString = text.as_ptr() as _;
+        }
+    }
+
+    info
+}
+
+fn button_index(handle: HWND, id: u32) -> Option<usize> {
+    use winapi::um::commctrl::TB_COMMANDTOINDEX;
+
+    match wh::send_message(handle, TB_COMMANDTOINDEX, id as WPARAM, 0) {
+        -1 => None,
+        i => Some(i as usize)
+    }
+}
+
+fn button_text(handle: HWND, id: u32) -> String {
+    use winapi::um::commctrl::TB_GETBUTTONTEXTW;
+
+    let length = wh::send_message(handle, TB_GETBUTTONTEXTW, id as WPARAM, 0);
+    if length <= 0 {
+        return String::new();
+    }
+
+    let mut buffer: Vec<u16> = vec![0; length as usize + 1];
+    wh::send_message(handle, TB_GETBUTTONTEXTW, id as WPARAM, buffer.as_mut_ptr() as LPARAM);
+    from_utf16(&buffer)
+}
+
+/// Hide the buttons that do not fit in the toolbar and show the chevron if needed
+fn update_overflow(handle: HWND, state: &mut ToolbarState) {
+    use winapi::um::commctrl::{TB_HIDEBUTTON, TB_GETITEMRECT, TB_BUTTONCOUNT, TB_GETBUTTON, TB_ISBUTTONHIDDEN};
+
+    if !state.chevron {
+        return;
+    }
+
+    for &id in state.overflow.iter() {
+        wh::send_message(handle, TB_HIDEBUTTON, id as WPARAM, 0);
+    }
+    state.overflow.clear();
+    wh::send_message(handle, TB_HIDEBUTTON, CHEVRON_ID as WPARAM, 0);
+
+    let count = wh::send_message(handle, TB_BUTTONCOUNT, 0, 0) as usize;
+    let (width, _) = unsafe { wh::get_window_physical_size(handle) };
+    let width = width as i32;
+
+    // (id, right edge) of every visible button except the chevron. Separators share the same id and are never hidden.
+    let mut buttons = Vec::with_capacity(count);
+    let mut chevron_width = 0;
+    for index in 0..count {
+        let mut info: TBBUTTON = unsafe { mem::zeroed() };
+        let mut rect: RECT = unsafe { mem::zeroed() };
+        wh::send_message(handle, TB_GETBUTTON, index as WPARAM, &mut info as *mut TBBUTTON as LPARAM);
+        wh::send_message(handle, TB_GETITEMRECT, index as WPARAM, &mut rect as *mut RECT as LPARAM);
+
+        if info.idCommand == CHEVRON_ID {
+            chevron_width = rect.right - rect.left;
+        } else if info.fsStyle as u32 & BTNS_SEP == BTNS_SEP {
+            continue;
+        } else if wh::send_message(handle, TB_ISBUTTONHIDDEN, info.idCommand as WPARAM, 0) == 0 {
+            buttons.push((info.idCommand as u32, rect.right));
+        }
+    }
+
+    if buttons.iter().all(|&(_, right)| right <= width) {
+        wh::send_message(handle, TB_HIDEBUTTON, CHEVRON_ID as WPARAM, 1);
+        return;
+    }
+
+    let available = width - chevron_width;
+    for &(id, right) in buttons.iter() {
+        if right > available {
+            wh::send_message(handle, TB_HIDEBUTTON, id as WPARAM, 1);
+            state.overflow.push(id);
+        }
+    }
+}
+
+/// Show the buttons hidden by the chevron in a popup menu and forward the selected button as a click
+fn show_overflow_menu(handle: HWND, parent: HWND, overflow: &[u32], button: RECT) {
+    use winapi::um::winuser::{CreatePopupMenu, DestroyMenu, AppendMenuW, TrackPopupMenu, MF_STRING, MF_CHECKED, MF_GRAYED,
+      TPM_RETURNCMD, TPM_NONOTIFY, TPM_LEFTALIGN, TPM_TOPALIGN, WM_COMMAND};
+    use winapi::um::commctrl::{TB_ISBUTTONCHECKED, TB_ISBUTTONENABLED, TB_CHECKBUTTON, TB_GETBUTTON};
+
+    if overflow.is_empty() {
+        return;
+    }
+
+    unsafe {
+        let menu = CreatePopupMenu();
+        for &id in overflow.iter() {
+            let mut flags = MF_STRING;
+            if wh::send_message(handle, TB_ISBUTTONCHECKED, id as WPARAM, 0) != 0 { flags |= MF_CHECKED; }
+            if wh::send_message(handle, TB_ISBUTTONENABLED, id as WPARAM, 0) == 0 { flags |= MF_GRAYED; }
+
+            let text = to_utf16(&button_text(handle, id));
+            AppendMenuW(menu, flags, id as usize, text.as_ptr());
+        }
+
+        let (x, y) = menu_position(handle, button);
+        let selected = TrackPopupMenu(menu, TPM_RETURNCMD | TPM_NONOTIFY | TPM_LEFTALIGN | TPM_TOPALIGN, x, y, 0, parent, ptr::null());
+        DestroyMenu(menu);
+
+        if selected > 0 {
+            let id = selected as u32;
+            if let Some(index) = button_index(handle, id) {
+                let mut info: TBBUTTON = mem::zeroed();
+                wh::send_message(handle, TB_GETBUTTON, index as WPARAM, &mut info as *mut TBBUTTON as LPARAM);
+                if info.fsStyle as u32 & BTNS_CHECK == BTNS_CHECK {
+                    let checked = wh::send_message(handle, TB_ISBUTTONCHECKED, id as WPARAM, 0) != 0;
+                    wh::send_message(handle, TB_CHECKBUTTON, id as WPARAM, (!checked) as LPARAM);
+                }
+            }
+
+            wh::send_message(parent, WM_COMMAND, id as WPARAM, handle as LPARAM);
+        }
+    }
+}
+
+/// Show a popup menu under a toolbar button
+fn show_menu(handle: HWND, owner: HWND, menu: HMENU, button: RECT) {
+    use winapi::um::winuser::{TrackPopupMenu, TPM_LEFTALIGN, TPM_TOPALIGN};
+
+    let (x, y) = menu_position(handle, button);
+    unsafe { TrackPopupMenu(menu, TPM_LEFTALIGN | TPM_TOPALIGN, x, y, 0, owner, ptr::null()); }
+}
+
+/// Return the screen position of the bottom left corner of a toolbar button
+fn menu_position(handle: HWND, button: RECT) -> (i32, i32) {
+    use winapi::um::winuser::ClientToScreen;
+    use winapi::shared::windef::POINT;
+
+    let mut point = POINT { x: button.left, y: button.bottom };
+    unsafe { ClientToScreen(handle, &mut point); }
+    (point.x, point.y)
+}
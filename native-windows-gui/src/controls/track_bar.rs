@@ -8,8 +8,10 @@ use winapi::um::{
 };
 use winapi::um::commctrl::{TBS_AUTOTICKS, TBS_VERT, TBS_HORZ, TBS_TOP, TBS_BOTTOM, TBS_LEFT, TBS_RIGHT, TBS_NOTICKS, TBS_ENABLESELRANGE};
 use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
 use crate::win32::base_helper::check_hwnd;
 use crate::{NwgError, RawEventHandler};
+use crate::win32::resource_tracker::{self as rt, ResourceKind};
 use super::{ControlBase, ControlHandle};
 use std::cell::RefCell;
 use std::ops::Range;
@@ -87,8 +89,8 @@ impl TrackBar {
 
     pub fn builder() -> TrackBarBuilder {
         TrackBarBuilder {
-            size: (100, 20),
-            position: (0, 0),
+            size: (100, 20).into(),
+            position: (0, 0).into(),
             focus: false,
             range: None,
             selected_range: None,
@@ -284,6 +286,7 @@ impl TrackBar {
 
         let parent_handle = ControlHandle::Hwnd(wh::get_window_parent(handle));
         let brush = unsafe { CreateSolidBrush(RGB(c[0], c[1], c[2])) };
+        rt::track(ResourceKind::Brush, brush);
         self.background_brush = Some(brush);
 
         let handler = bind_raw_event_handler_inner(&parent_handle, handle as UINT_PTR, move |_hwnd, msg, _w, l| {
@@ -315,6 +318,7 @@ impl Drop for TrackBar {
         }
 
         if let Some(bg) = self.background_brush {
+            rt::untrack(bg);
             unsafe { DeleteObject(bg as _); }
         }
     
@@ -324,8 +328,8 @@ impl Drop for TrackBar {
 
 
 pub struct TrackBarBuilder {
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     focus: bool,
     range: Option<Range<usize>>,
     selected_range: Option<Range<usize>>,
@@ -348,13 +352,13 @@ impl TrackBarBuilder {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> TrackBarBuilder {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> TrackBarBuilder {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> TrackBarBuilder {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> TrackBarBuilder {
+        self.position = pos.into();
         self
     }
 
@@ -1,14 +1,23 @@
 use winapi::um::shellapi::{NIIF_NONE, NIIF_INFO, NIIF_WARNING, NIIF_ERROR, NIIF_USER, NIIF_NOSOUND, NIIF_LARGE_ICON, NIIF_RESPECT_QUIET_TIME};
 use winapi::um::shellapi::{Shell_NotifyIconW, NOTIFYICONDATAW};
+use winapi::shared::windef::HWND;
+use winapi::shared::guiddef::GUID;
+use winapi::um::winnt::WCHAR;
 use super::{ControlBase, ControlHandle};
 use crate::win32::base_helper::to_utf16;
 use crate::win32::window_helper as wh;
-use crate::{Icon, NwgError};
-use std::{mem, ptr};
+use crate::{Icon, NwgError, RawEventHandler, unbind_raw_event_handler};
+use std::{mem, ptr, rc::Rc, cell::RefCell};
 
 const NOT_BOUND: &'static str = "TrayNotification is not yet bound to a winapi object";
 const BAD_HANDLE: &'static str = "INTERNAL ERROR: TrayNotification handle is not HWND!";
 
+/// Raw handler id of the "TaskbarCreated" hook on the tray parent
+const TASKBAR_HANDLER_ID: usize = 0x7A4E;
+
+/// Not defined in winapi
+const MSGFLT_ALLOW: u32 = 1;
+
 
 bitflags! {
     pub struct TrayNotificationFlags: u32 {
@@ -24,6 +33,30 @@ bitflags! {
 }
 
 
+/**
+    A balloon notification displayed over the system tray. See `TrayNotification::show_balloon`.
+
+    The default value is an empty balloon without a title that uses the `NO_ICON | SILENT` flags.
+*/
+#[derive(Clone, Copy, Default)]
+pub struct TrayBalloon<'a> {
+    /// The text of the balloon. Truncated to 255 characters.
+    pub text: &'a str,
+
+    /// The title of the balloon. Truncated to 63 characters.
+    pub title: Option<&'a str>,
+
+    /// Flags that specify how the balloon is shown. Default is `NO_ICON | SILENT`.
+    pub flags: Option<TrayNotificationFlags>,
+
+    /// Icon displayed in the balloon. Only used if `USER_ICON` is set in flags.
+    pub icon: Option<&'a Icon>,
+
+    /// If the balloon cannot be displayed immediately (ex: a full screen application is running), discard it.
+    pub realtime: bool,
+}
+
+
 /**
     A control that handle system tray notification.
     A TrayNotification wraps a single icon in the Windows system tray.
@@ -33,7 +66,14 @@ bitflags! {
 
     A system tray will receive events if `callback` is set to true in the builder (the default behaviour).
     The control will generate mouse events such as `OnMouseMove` when the user interact with the tray icon or the message popup.
-    A system tray will also receive a `OnContextMenu` when the user right click the icon. It is highly recommended handle this message and display a popup menu
+    A system tray will also receive a `OnContextMenu` when the user right click the icon or uses the keyboard (shift+F10 or the menu key).
+    It is highly recommended handle this message and display a popup menu at the position returned by `EventData::on_tray_notification`.
+
+    If a `guid` is set, the icon is identified by this value instead of its parent window. This lets Windows remember the user settings
+    (such as "always show") between sessions. Windows ties a guid to the path of the executable: if the executable is moved, the registration will fail.
+
+    When explorer restarts, every tray icon is lost. The tray notification listens to the `TaskbarCreated` message on its parent
+    and adds itself back with its current icon, tip and visibility. Message only windows do not receive this message.
 
     You can't get information on the state of a tray notification (such as visibility) because Windows don't want you to.
 
@@ -47,9 +87,12 @@ bitflags! {
         * `info`:         Display a fancy tooltip when the system tray icon is hovered (replaces tip) 
         * `balloon_icon`: The icon to display in the fancy tooltip  
         * `info_title`:   The title of the fancy tooltip  
+        * `guid`:         A unique identifier for the icon. Keeps the user settings of the icon between sessions.
 
     **Control events:**
-        * `OnContextMenu`: When the user right clicks on the system tray icon
+        * `OnContextMenu`: When the user right clicks on the system tray icon or opens its context menu with the keyboard
+        * `OnTrayNotificationSelect`: When the user selects the system tray icon with the mouse
+        * `OnTrayNotificationKeySelect`: When the user selects the system tray icon with the keyboard
         * `MousePressLeftUp`: When the user left click the system tray icon
         * `OnTrayNotificationShow`: When a TrayNotification info popup (not the tooltip) is shown 
         * `OnTrayNotificationHide`: When a TrayNotification info popup (not the tooltip) is hidden 
@@ -67,6 +110,19 @@ bitflags! {
     }
     ```
 
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn notice_user_later(tray: &nwg::TrayNotification) {
+        tray.show_balloon(&nwg::TrayBalloon {
+            text: "Your download is complete",
+            title: Some("Downloads"),
+            flags: Some(nwg::TrayNotificationFlags::INFO_ICON),
+            ..Default::default()
+        });
+    }
+    ```
+
     ```rust
     use native_windows_gui as nwg;
     fn build_tray(tray: &mut nwg::TrayNotification, window: &nwg::Window, icon: &nwg::Icon) {
@@ -74,15 +130,19 @@ bitflags! {
             .parent(window)
             .icon(Some(icon))
             .tip(Some("Hello"))
+            .guid(Some(0x5E0D6C2A_93F1_4B7E_8D21_6A0F3C9B7E14))
             .build(tray);
     }
     ```
 
     Winapi docs: https://docs.microsoft.com/en-us/windows/win32/shell/notification-area
 */
-#[derive(Default, PartialEq, Eq)]
+#[derive(Default)]
 pub struct TrayNotification {
     pub handle: ControlHandle,
+    guid: Option<u128>,
+    registration: Rc<RefCell<Option<NOTIFYICONDATAW>>>,
+    handler0: RefCell<Option<RawEventHandler>>,
 }
 
 impl TrayNotification {
@@ -99,9 +159,15 @@ impl TrayNotification {
             realtime: false,
             callback: true,
             visible: true,
+            guid: None,
         }
     }
 
+    /// Return the guid that identifies the icon, if any
+    pub fn guid(&self) -> Option<u128> {
+        self.guid
+    }
+
     /// Set the visibility of the icon in the system tray
     pub fn set_visibility(&self, v: bool) {
         use winapi::um::shellapi::{NIF_STATE, NIM_MODIFY, NIS_HIDDEN};  
@@ -116,6 +182,12 @@ impl TrayNotification {
             data.dwStateMask = NIS_HIDDEN;
             Shell_NotifyIconW(NIM_MODIFY, &mut data);
         }
+
+        if let Some(reg) = self.registration.borrow_mut().as_mut() {
+            reg.uFlags |= NIF_STATE;
+            reg.dwState = if v { 0 } else { NIS_HIDDEN };
+            reg.dwStateMask = NIS_HIDDEN;
+        }
     }
 
     /// Set the tooltip for the tray notification.
@@ -128,17 +200,16 @@ impl TrayNotification {
 
         unsafe {
             let mut data = self.notify_default();
-            
-            data.uFlags = NIF_TIP | NIF_SHOWTIP;
-            
-            let tip_v = to_utf16(tip);
-            let length = if tip_v.len() >= 128 { 127 } else { tip_v.len() };
-            for i in 0..length {
-                data.szTip[i] = tip_v[i];
-            }
-
+            data.uFlags |= NIF_TIP | NIF_SHOWTIP;
+            copy_text(&mut data.szTip, tip);
             Shell_NotifyIconW(NIM_MODIFY, &mut data);
         }
+
+        if let Some(reg) = self.registration.borrow_mut().as_mut() {
+            reg.uFlags |= NIF_TIP | NIF_SHOWTIP;
+            reg.szTip = [0; 128];
+            copy_text(&mut reg.szTip, tip);
+        }
     }
 
     /// Set the focus to the tray icon
@@ -164,11 +235,14 @@ impl TrayNotification {
 
         unsafe {
             let mut data = self.notify_default();
-            
-            data.uFlags = NIF_ICON;
+            data.uFlags |= NIF_ICON;
             data.hIcon = icon.handle as HICON;
             Shell_NotifyIconW(NIM_MODIFY, &mut data);
         }
+
+        if let Some(reg) = self.registration.borrow_mut().as_mut() {
+            reg.hIcon = icon.handle as HICON;
+        }
     }
 
     /// Shows a popup message on top of the system tray
@@ -182,7 +256,13 @@ impl TrayNotification {
     /// Note 1: text will be truncated to 255 characters
     /// Note 2: title will be truncated to 63 characters
     pub fn show<'a>(&self, text: &'a str, title: Option<&'a str>, flags: Option<TrayNotificationFlags>, icon: Option<&'a Icon>) {
-        use winapi::um::shellapi::{NIF_INFO, NIM_MODIFY};
+        self.show_balloon(&TrayBalloon { text, title, flags, icon, realtime: false });
+    }
+
+    /// Shows a balloon notification on top of the system tray. Replaces the balloon currently displayed, if any.
+    /// See `TrayBalloon` for the available options.
+    pub fn show_balloon<'a>(&self, balloon: &TrayBalloon<'a>) {
+        use winapi::um::shellapi::{NIF_INFO, NIF_REALTIME, NIM_MODIFY};
         use winapi::shared::windef::HICON;
 
         if self.handle.blank() { panic!("{}", NOT_BOUND); }
@@ -192,32 +272,65 @@ impl TrayNotification {
 
         unsafe {
             let mut data = self.notify_default();
-            data.uFlags = NIF_INFO;
-            data.dwInfoFlags = flags.unwrap_or(default_flags).bits();
-            data.hBalloonIcon = icon.map(|i| i.handle as HICON).unwrap_or(ptr::null_mut());
-            
-            let info_v = to_utf16(text);
-            let length = if info_v.len() >= 256 { 255 } else { info_v.len() };
-            for i in 0..length {
-                data.szInfo[i] = info_v[i];
-            }
-        
-            let info_title_v = match title {
-                Some(t) => to_utf16(t),
-                None => vec![]
-            };
+            data.uFlags |= NIF_INFO;
+            if balloon.realtime { data.uFlags |= NIF_REALTIME; }
 
-            let length = if info_title_v.len() >= 256 { 255 } else { info_title_v.len() };
-            for i in 0..length {
-                data.szInfoTitle[i] = info_title_v[i];
-            }
+            data.dwInfoFlags = balloon.flags.unwrap_or(default_flags).bits();
+            data.hBalloonIcon = balloon.icon.map(|i| i.handle as HICON).unwrap_or(ptr::null_mut());
+            copy_text(&mut data.szInfo, balloon.text);
+            copy_text(&mut data.szInfoTitle, balloon.title.unwrap_or(""));
 
             Shell_NotifyIconW(NIM_MODIFY, &mut data);
         }
     }
 
-    fn notify_default(&self) -> NOTIFYICONDATAW {
+    /// Removes the balloon notification currently displayed, if any
+    pub fn hide_balloon(&self) {
+        use winapi::um::shellapi::{NIF_INFO, NIM_MODIFY};
+
+        if self.handle.blank() { panic!("{}", NOT_BOUND); }
+        self.handle.tray().expect(BAD_HANDLE);
+
         unsafe {
+            let mut data = self.notify_default();
+            data.uFlags |= NIF_INFO;
+            Shell_NotifyIconW(NIM_MODIFY, &mut data);
+        }
+    }
+
+    /// Adds the icon back to the system tray when explorer sends "TaskbarCreated" to the parent
+    fn hook_taskbar_created(&self, parent: HWND) -> Result<(), NwgError> {
+        use crate::bind_raw_event_handler_inner;
+        use winapi::um::winuser::{RegisterWindowMessageW, ChangeWindowMessageFilterEx};
+
+        let taskbar_created = unsafe { RegisterWindowMessageW(to_utf16("TaskbarCreated").as_ptr()) };
+        if taskbar_created == 0 {
+            return Ok(());
+        }
+
+        // Explorer may run at a lower integrity level than the application
+        unsafe { ChangeWindowMessageFilterEx(parent, taskbar_created, MSGFLT_ALLOW, ptr::null_mut()); }
+
+        let registration = self.registration.clone();
+        let handler = bind_raw_event_handler_inner(&ControlHandle::Hwnd(parent), TASKBAR_HANDLER_ID, move |_hwnd, msg, _w, _l| {
+            if msg == taskbar_created {
+                if let Some(mut data) = *registration.borrow() {
+                    unsafe { add_icon(&mut data); }
+                }
+            }
+
+            None
+        })?;
+
+        *self.handler0.borrow_mut() = Some(handler);
+
+        Ok(())
+    }
+
+    fn notify_default(&self) -> NOTIFYICONDATAW {
+        use winapi::um::shellapi::NIF_GUID;
+
+        let mut data = unsafe {
             let parent = self.handle.tray().unwrap();
             NOTIFYICONDATAW {
                 cbSize: mem::size_of::<NOTIFYICONDATAW>() as u32,
@@ -236,15 +349,35 @@ impl TrayNotification {
                 guidItem: mem::zeroed(),
                 hBalloonIcon: ptr::null_mut()
             }
+        };
+
+        if let Some(guid) = self.guid {
+            data.uFlags = NIF_GUID;
+            data.guidItem = guid_from_u128(guid);
         }
+
+        data
     }
 
 }
 
+impl PartialEq for TrayNotification {
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+}
+
+impl Eq for TrayNotification {}
+
 impl Drop for TrayNotification {
     fn drop(&mut self) {
         use winapi::um::shellapi::NIM_DELETE;
 
+        let handler = self.handler0.borrow();
+        if let Some(h) = handler.as_ref() {
+            drop(unbind_raw_event_handler(h));
+        }
+
         if self.handle.tray().is_some() {
             let mut data = self.notify_default();
             unsafe {
@@ -270,6 +403,7 @@ pub struct TrayNotificationBuilder<'a> {
     realtime: bool,
     callback: bool,
     visible: bool,
+    guid: Option<u128>,
 }
 
 impl<'a> TrayNotificationBuilder<'a> {
@@ -330,11 +464,16 @@ impl<'a> TrayNotificationBuilder<'a> {
         self
     }
 
+    /// A unique identifier for the icon. Written as a u128 (ex: `0x5E0D6C2A_93F1_4B7E_8D21_6A0F3C9B7E14`).
+    pub fn guid(mut self, guid: Option<u128>) -> TrayNotificationBuilder<'a> {
+        self.guid = guid;
+        self
+    }
+
     pub fn build(self, out: &mut TrayNotification) -> Result<(), NwgError> {
-        use winapi::um::shellapi::{NIM_ADD, NIF_ICON, NIF_TIP, NIF_SHOWTIP, NIF_INFO, NOTIFYICONDATAW_u, NOTIFYICON_VERSION_4,
-         NIF_REALTIME, NIF_MESSAGE, NIS_HIDDEN, NIF_STATE};
+        use winapi::um::shellapi::{NIF_ICON, NIF_TIP, NIF_SHOWTIP, NIF_INFO, NOTIFYICONDATAW_u, NOTIFYICON_VERSION_4,
+         NIF_REALTIME, NIF_MESSAGE, NIS_HIDDEN, NIF_STATE, NIF_GUID};
         use winapi::shared::windef::HICON;
-        use winapi::um::winnt::WCHAR;
 
         // Flags
         let version = NOTIFYICON_VERSION_4;
@@ -354,6 +493,7 @@ impl<'a> TrayNotificationBuilder<'a> {
         if self.realtime { flags |= NIF_REALTIME; }
         if self.callback { flags |= NIF_MESSAGE; }
         if !self.visible { state |= NIS_HIDDEN; flags |= NIF_STATE; }
+        if self.guid.is_some() { flags |= NIF_GUID; }
 
         // Resource handles
 
@@ -375,6 +515,9 @@ impl<'a> TrayNotificationBuilder<'a> {
             (true, Some(i)) => i.handle as HICON
         };
 
+        // Dropping a previous tray notification on the same parent would remove the new icon
+        *out = Default::default();
+
         // UID
         let handle = ControlBase::build_tray_notification()
             .parent(parent)
@@ -382,34 +525,19 @@ impl<'a> TrayNotificationBuilder<'a> {
         
         // Tips or infos
         let mut tip: [WCHAR; 128] = [0; 128];
-        if self.tip.is_some() {
-            let tip_v = to_utf16(self.tip.unwrap());
-            let length = if tip_v.len() >= 128 { 127 } else { tip_v.len() };
-            for i in 0..length {
-                tip[i] = tip_v[i];
-            }
+        if let Some(t) = self.tip {
+            copy_text(&mut tip, t);
         }
 
         let mut info: [WCHAR; 256] = [0; 256];
-        if self.info.is_some() {
-            let info_v = to_utf16(self.info.unwrap());
-            let length = if info_v.len() >= 256 { 255 } else { info_v.len() };
-            for i in 0..length {
-                info[i] = info_v[i];
-            }
-        }
-        
         let mut title: [WCHAR; 64] = [0; 64];
-        if self.info.is_some() && self.info_title.is_some() {
-            let info_title_v = to_utf16(self.info_title.unwrap());
-            let length = if info_title_v.len() >= 256 { 255 } else { info_title_v.len() };
-            for i in 0..length {
-                title[i] = info_title_v[i];
-            }
+        if let Some(i) = self.info {
+            copy_text(&mut info, i);
+            copy_text(&mut title, self.info_title.unwrap_or(""));
         }
 
         // Creation
-        unsafe {
+        let mut data = unsafe {
             let mut u: NOTIFYICONDATAW_u = mem::zeroed();
             *u.uVersion_mut() = version;
 
@@ -431,15 +559,61 @@ impl<'a> TrayNotificationBuilder<'a> {
                 hBalloonIcon: balloon_icon
             };
 
-            Shell_NotifyIconW(NIM_ADD, &mut data);
-        }
+            if let Some(guid) = self.guid {
+                data.guidItem = guid_from_u128(guid);
+            }
 
+            add_icon(&mut data);
+            data
+        };
 
         // Finish
-        *out = Default::default();
         out.handle = handle;
+        out.guid = self.guid;
+
+        // The initial balloon is not shown again if the icon is added back
+        data.uFlags &= !(NIF_INFO | NIF_REALTIME);
+        data.szInfo = [0; 256];
+        data.szInfoTitle = [0; 64];
+        data.dwInfoFlags = 0;
+        data.hBalloonIcon = ptr::null_mut();
+        *out.registration.borrow_mut() = Some(data);
+
+        out.hook_taskbar_created(parent)?;
 
         Ok(())
     }
 
 }
+
+/// Adds an icon to the system tray and opt in the NOTIFYICON_VERSION_4 behaviour
+unsafe fn add_icon(data: &mut NOTIFYICONDATAW) {
+    use winapi::um::shellapi::{NIM_ADD, NIM_DELETE, NIM_SETVERSION, NIF_GUID, NOTIFYICON_VERSION_4};
+
+    // An icon with the same guid may be left over by a previous instance that did not exit cleanly
+    if data.uFlags & NIF_GUID == NIF_GUID {
+        Shell_NotifyIconW(NIM_DELETE, data);
+    }
+
+    if Shell_NotifyIconW(NIM_ADD, data) != 0 {
+        *data.u.uVersion_mut() = NOTIFYICON_VERSION_4;
+        Shell_NotifyIconW(NIM_SETVERSION, data);
+    }
+}
+
+/// Copy `text` into a fixed size wide string buffer. The text is truncated to keep the null terminator.
+fn copy_text(buffer: &mut [WCHAR], text: &str) {
+    let text = to_utf16(text);
+    let length = text.len().min(buffer.len() - 1);
+    buffer[..length].copy_from_slice(&text[..length]);
+    buffer[length] = 0;
+}
+
+fn guid_from_u128(v: u128) -> GUID {
+    GUID {
+        Data1: (v >> 96) as u32,
+        Data2: (v >> 80) as u16,
+        Data3: (v >> 64) as u16,
+        Data4: (v as u64).to_be_bytes(),
+    }
+}
@@ -6,6 +6,7 @@ use winapi::shared::minwindef::{WPARAM, LPARAM};
 use winapi::um::winuser::{WS_VISIBLE, WS_DISABLED, WS_TABSTOP};
 use winapi::um::commctrl::{HTREEITEM, TVIS_EXPANDED, TVIS_SELECTED, TVS_SHOWSELALWAYS, TVITEMW};
 use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
 use crate::win32::base_helper::{check_hwnd, to_utf16, from_utf16};
 use crate::{Font, NwgError};
 use super::{ControlBase, ControlHandle};
@@ -152,8 +153,8 @@ impl TreeView {
 
     pub fn builder<'a>() -> TreeViewBuilder<'a> {
         TreeViewBuilder {
-            size: (100, 200),
-            position: (0, 0),
+            size: (100, 200).into(),
+            position: (0, 0).into(),
             enabled: true,
             focus: false,
             flags: None,
@@ -787,8 +788,8 @@ impl Drop for TreeView {
 
 /// Builder for a TreeView
 pub struct TreeViewBuilder<'a> {
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     enabled: bool,
     focus: bool,
     flags: Option<TreeViewFlags>,
@@ -813,13 +814,13 @@ impl<'a> TreeViewBuilder<'a> {
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> TreeViewBuilder<'a> {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> TreeViewBuilder<'a> {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> TreeViewBuilder<'a> {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> TreeViewBuilder<'a> {
+        self.position = pos.into();
         self
     }
 
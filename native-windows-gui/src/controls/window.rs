@@ -1,14 +1,25 @@
 use winapi::um::winuser::{WS_OVERLAPPEDWINDOW, WS_CLIPCHILDREN, WS_VISIBLE, WS_DISABLED, WS_MAXIMIZE, WS_MINIMIZE, WS_CAPTION,
-WS_MINIMIZEBOX, WS_MAXIMIZEBOX, WS_SYSMENU, WS_THICKFRAME, WS_POPUP, WS_EX_TOPMOST, WS_EX_ACCEPTFILES};
+WS_MINIMIZEBOX, WS_MAXIMIZEBOX, WS_SYSMENU, WS_THICKFRAME, WS_POPUP, WS_EX_TOPMOST, WS_EX_ACCEPTFILES, WS_EX_TOOLWINDOW,
+WS_EX_NOACTIVATE, WS_EX_TRANSPARENT, WS_EX_LAYERED, WS_EX_COMPOSITED};
 
 use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Extent;
 use crate::win32::base_helper::check_hwnd;
 use crate::{NwgError, Icon};
 use super::{ControlBase, ControlHandle};
 
+#[cfg(feature = "mdi")] use crate::win32::mdi;
+#[cfg(feature = "mdi")] use winapi::shared::windef::HWND;
+#[cfg(feature = "backdrop")] use crate::win32::backdrop::{self, Backdrop};
+#[cfg(feature = "taskbar")] use crate::win32::taskbar::{self, ProgressState, ThumbnailButton};
+#[cfg(feature = "touch")] use crate::win32::touch::{self, TouchMode};
+
 const NOT_BOUND: &'static str = "Window is not yet bound to a winapi object";
 const BAD_HANDLE: &'static str = "INTERNAL ERROR: Window handle is not HWND!";
 
+#[cfg(feature = "mdi")]
+const NOT_MDI_FRAME: &'static str = "Window is not a MDI frame";
+
 
 bitflags! {
 
@@ -43,6 +54,32 @@ bitflags! {
     }
 }
 
+bitflags! {
+
+    /**
+        The window extended flags. Unlike the raw `ex_flags`, those can be changed after the window is created
+        with `Window::set_ex_style`.
+
+        Example: `WindowExFlags::TOOL_WINDOW | WindowExFlags::TOPMOST`
+
+        Window extended flags:
+        * TOOL_WINDOW: A floating toolbar window. It has a smaller title bar and is not shown in the taskbar or in ALT+TAB
+        * NO_ACTIVATE: The window does not become the foreground window when the user clicks it
+        * ACCEPT_FILES: The window accepts the files dropped by drag & drop (see `OnFileDrop`)
+        * TRANSPARENT: The mouse clicks go through the window to the windows below it
+        * TOPMOST: The window stays on top of the windows that are not topmost
+        * COMPOSITED: The window and its children are painted in a single buffer, which removes the flickering of overlapping children
+    */
+    pub struct WindowExFlags: u32 {
+        const TOOL_WINDOW = WS_EX_TOOLWINDOW;
+        const NO_ACTIVATE = WS_EX_NOACTIVATE;
+        const ACCEPT_FILES = WS_EX_ACCEPTFILES;
+        const TRANSPARENT = WS_EX_TRANSPARENT | WS_EX_LAYERED;
+        const TOPMOST = WS_EX_TOPMOST;
+        const COMPOSITED = WS_EX_COMPOSITED;
+    }
+}
+
 
 /**
     A basic top level window. At least one top level window is required to make a NWG application.
@@ -53,6 +90,7 @@ bitflags! {
     **Builder parameters:**
       * `flags`:       The window flags. See `WindowFlags`
       * `ex_flags`:    A combination of win32 window extended flags. Unlike `flags`, ex_flags must be used straight from winapi
+      * `ex_style`:    The window extended flags. See `WindowExFlags`
       * `title`:       The text in the window title bar
       * `size`:        The default size of the window
       * `position`:    The default position of the window in the desktop
@@ -63,6 +101,9 @@ bitflags! {
       * `center`:      Center the window in the current monitor based on its size. If `true`, this overrides `position`
       * `topmost`:     If the window should always be on top of other system window
       * `parent`:      Logical parent of the window, unlike children controls, this is NOT required.
+      * `mdi_frame`:   If the window is a MDI frame that contains `MdiChild` windows. Requires the `mdi` feature.
+      * `backdrop`:    The material drawn behind the window on Windows 11. See `Backdrop`. Requires the `backdrop` feature.
+      * `touch_mode`:  How the window receives the touch input. See `TouchMode`. Requires the `touch` feature.
 
     **Control events:**
       * `OnInit`: The window was created
@@ -80,6 +121,15 @@ bitflags! {
       * `OnMove`: When the window is moved by the user
       * `OnFileDrop`: When a file is dropped in the window (only raised if accept_file is set)
       * `OnMinMaxInfo`: When the size or position of the window is about to change and the size of the windows must be restricted
+      * `OnGesture`: When the user makes a gesture on a touch screen (requires the `touch` feature)
+      * `OnTouch`: When the user touches the window, if the window was built with `TouchMode::Touch` (requires the `touch` feature)
+
+    **MDI frames**
+
+    With the `mdi` feature, a window built with `mdi_frame(true)` hosts multiple document windows (see `MdiChild`).
+    The children are placed in a MDI client window that fills the client area of the frame. The `mdi_*` methods
+    arrange the children, and `set_mdi_window_menu` lists the children in a menu of the menubar.
+    The standard MDI keyboard shortcuts (CTRL+F4, CTRL+F6) are handled by `dispatch_thread_events`.
 
 */
 #[derive(Default, PartialEq, Eq)]
@@ -92,8 +142,8 @@ impl Window {
     pub fn builder<'a>() -> WindowBuilder<'a> {
         WindowBuilder {
             title: "New Window",
-            size: (500, 500),
-            position: (300, 300),
+            size: (500, 500).into(),
+            position: (300, 300).into(),
             accept_files: false,
             topmost: false,
             center: false,
@@ -101,8 +151,15 @@ impl Window {
             minimized: false,
             flags: None,
             ex_flags: 0,
+            ex_style: WindowExFlags::empty(),
             icon: None,
-            parent: None
+            parent: None,
+            #[cfg(feature = "mdi")]
+            mdi_frame: false,
+            #[cfg(feature = "backdrop")]
+            backdrop: Backdrop::None,
+            #[cfg(feature = "touch")]
+            touch_mode: TouchMode::Default,
         }
     }
 
@@ -241,6 +298,238 @@ impl Window {
         unsafe { wh::set_window_text(handle, v) }
     }
 
+    /// Return true if the window was built with `mdi_frame(true)`
+    #[cfg(feature = "mdi")]
+    pub fn is_mdi_frame(&self) -> bool {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        mdi::mdi_client(handle).is_some()
+    }
+
+    /// Return the MDI child that is currently active, or `None` if the frame has no children
+    ///
+    /// Panics if the window is not a MDI frame
+    #[cfg(feature = "mdi")]
+    pub fn mdi_active_child(&self) -> Option<ControlHandle> {
+        use winapi::um::winuser::WM_MDIGETACTIVE;
+
+        let client = self.mdi_client_handle();
+        match wh::send_message(client, WM_MDIGETACTIVE, 0, 0) {
+            0 => None,
+            child => Some(ControlHandle::Hwnd(child as _))
+        }
+    }
+
+    /// Arrange the MDI children in a cascade
+    ///
+    /// Panics if the window is not a MDI frame
+    #[cfg(feature = "mdi")]
+    pub fn mdi_cascade(&self) {
+        use winapi::um::winuser::{WM_MDICASCADE, MDITILE_SKIPDISABLED};
+
+        let client = self.mdi_client_handle();
+        wh::send_message(client, WM_MDICASCADE, MDITILE_SKIPDISABLED as _, 0);
+    }
+
+    /// Arrange the MDI children side by side. If `horizontal` is true, the children are stacked from top to bottom.
+    ///
+    /// Panics if the window is not a MDI frame
+    #[cfg(feature = "mdi")]
+    pub fn mdi_tile(&self, horizontal: bool) {
+        use winapi::um::winuser::{WM_MDITILE, MDITILE_HORIZONTAL, MDITILE_VERTICAL, MDITILE_SKIPDISABLED};
+
+        let client = self.mdi_client_handle();
+        let mode = match horizontal {
+            true => MDITILE_HORIZONTAL,
+            false => MDITILE_VERTICAL
+        };
+
+        wh::send_message(client, WM_MDITILE, (mode | MDITILE_SKIPDISABLED) as _, 0);
+    }
+
+    /// Arrange the icons of the minimized MDI children
+    ///
+    /// Panics if the window is not a MDI frame
+    #[cfg(feature = "mdi")]
+    pub fn mdi_arrange_icons(&self) {
+        use winapi::um::winuser::WM_MDIICONARRANGE;
+
+        let client = self.mdi_client_handle();
+        wh::send_message(client, WM_MDIICONARRANGE, 0, 0);
+    }
+
+    /// Activate the next MDI child. If `previous` is true, activate the previous child instead.
+    ///
+    /// Panics if the window is not a MDI frame
+    #[cfg(feature = "mdi")]
+    pub fn mdi_next(&self, previous: bool) {
+        use winapi::um::winuser::WM_MDINEXT;
+
+        let client = self.mdi_client_handle();
+        wh::send_message(client, WM_MDINEXT, 0, previous as _);
+    }
+
+    /**
+        Set the menu of the menubar that lists the MDI children. The children are appended to the menu
+        and selecting one of them activates it. Use `None` to remove the list.
+
+        Panics if the window is not a MDI frame
+    */
+    #[cfg(feature = "mdi")]
+    pub fn set_mdi_window_menu(&self, menu: Option<&crate::Menu>) {
+        use winapi::um::winuser::{WM_MDISETMENU, WM_MDIREFRESHMENU, DrawMenuBar};
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let client = self.mdi_client_handle();
+        let menu = match menu.map(|m| m.handle) {
+            Some(ControlHandle::Menu(_, m)) => m,
+            Some(_) => panic!("Menu handle is not HMENU!"),
+            None => ::std::ptr::null_mut(),
+        };
+
+        wh::send_message(client, WM_MDISETMENU, 0, menu as _);
+        wh::send_message(client, WM_MDIREFRESHMENU, 0, 0);
+        unsafe { DrawMenuBar(handle); }
+    }
+
+    #[cfg(feature = "mdi")]
+    fn mdi_client_handle(&self) -> HWND {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        mdi::mdi_client(handle).expect(NOT_MDI_FRAME)
+    }
+
+    /// Returns the backdrop of the window. See `set_backdrop`
+    #[cfg(feature = "backdrop")]
+    pub fn backdrop(&self) -> Backdrop {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        backdrop::window_backdrop(&self.handle)
+    }
+
+    /// Sets the material drawn behind the window. Returns the backdrop that was applied:
+    /// the systems older than Windows 11 22H2 fall back to a simpler backdrop, see `nwg::supported_backdrop`
+    #[cfg(feature = "backdrop")]
+    pub fn set_backdrop(&self, backdrop: Backdrop) -> Backdrop {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        backdrop::set_window_backdrop(&self.handle, backdrop)
+    }
+
+    /// Returns the progress drawn in the taskbar button of the window as `(state, value, total)`
+    #[cfg(feature = "taskbar")]
+    pub fn taskbar_progress(&self) -> (ProgressState, u64, u64) {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        taskbar::taskbar_progress(&self.handle)
+    }
+
+    /// Draws a progress bar in the taskbar button of the window. `ProgressState::None` removes the progress bar.
+    /// The value is ignored with `ProgressState::None` and `ProgressState::Indeterminate`.
+    #[cfg(feature = "taskbar")]
+    pub fn set_taskbar_progress(&self, state: ProgressState, value: u64, total: u64) {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        taskbar::set_taskbar_progress(&self.handle, state, value, total)
+    }
+
+    /// Sets the toolbar (at most seven buttons) shown in the thumbnail preview of the window. See `nwg::set_thumbnail_buttons`
+    #[cfg(feature = "taskbar")]
+    pub fn set_thumbnail_buttons(&self, buttons: &[ThumbnailButton]) -> Result<(), NwgError> {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        taskbar::set_thumbnail_buttons(&self.handle, buttons)
+    }
+
+    /// Enables or disables a button of the thumbnail toolbar of the window
+    #[cfg(feature = "taskbar")]
+    pub fn set_thumbnail_button_enabled(&self, id: u32, enabled: bool) {
+        check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        taskbar::set_thumbnail_button_enabled(&self.handle, id, enabled)
+    }
+
+    /// Sets how the window receives the touch input. See `TouchMode`
+    #[cfg(feature = "touch")]
+    pub fn set_touch_mode(&self, mode: TouchMode) {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        touch::set_touch_mode(handle, mode)
+    }
+
+    /// Returns the extended flags of the window. See `WindowExFlags`
+    pub fn ex_style(&self) -> WindowExFlags {
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let style = wh::get_ex_style(handle);
+
+        // `TRANSPARENT` is only set if both of its bits are
+        let mut flags = WindowExFlags::from_bits_truncate(style & !WindowExFlags::TRANSPARENT.bits());
+        if style & WindowExFlags::TRANSPARENT.bits() == WindowExFlags::TRANSPARENT.bits() {
+            flags |= WindowExFlags::TRANSPARENT;
+        }
+
+        flags
+    }
+
+    /**
+        Adds (`enabled = true`) or removes (`enabled = false`) extended flags of the window. See `WindowExFlags`.
+
+        Removing `TRANSPARENT` also removes the layered style of the window. Changing `TOOL_WINDOW` on a visible
+        window only updates the taskbar after the window is hidden and shown again.
+    */
+    pub fn set_ex_style(&self, flags: WindowExFlags, enabled: bool) {
+        use winapi::um::winuser::{SetWindowPos, SetLayeredWindowAttributes, HWND_TOPMOST, HWND_NOTOPMOST, SWP_NOMOVE, SWP_NOSIZE, SWP_NOACTIVATE, LWA_ALPHA};
+
+        let handle = check_hwnd(&self.handle, NOT_BOUND, BAD_HANDLE);
+        let old_style = wh::get_ex_style(handle);
+
+        // Topmost windows are moved in the z-order, setting the style has no effect
+        let style_flags = flags.bits() & !WS_EX_TOPMOST;
+        let style = match enabled {
+            true => old_style | style_flags,
+            false => old_style & !style_flags
+        };
+
+        if style != old_style {
+            wh::set_ex_style(handle, style);
+
+            // A layered window is not drawn until its attributes are set
+            if style & WS_EX_LAYERED != 0 && old_style & WS_EX_LAYERED == 0 {
+                unsafe { SetLayeredWindowAttributes(handle, 0, 255, LWA_ALPHA); }
+            }
+        }
+
+        if flags.contains(WindowExFlags::TOPMOST) {
+            let after = match enabled {
+                true => HWND_TOPMOST,
+                false => HWND_NOTOPMOST
+            };
+
+            unsafe { SetWindowPos(handle, after, 0, 0, 0, 0, SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE); }
+        }
+    }
+
+    /// Returns `true` if the window stays on top of the other windows
+    pub fn topmost(&self) -> bool {
+        self.ex_style().contains(WindowExFlags::TOPMOST)
+    }
+
+    /// Sets if the window stays on top of the other windows
+    pub fn set_topmost(&self, topmost: bool) {
+        self.set_ex_style(WindowExFlags::TOPMOST, topmost);
+    }
+
+    /// Returns `true` if the mouse clicks go through the window
+    pub fn click_through(&self) -> bool {
+        self.ex_style().contains(WindowExFlags::TRANSPARENT)
+    }
+
+    /// Sets if the mouse clicks go through the window to the windows below it
+    pub fn set_click_through(&self, click_through: bool) {
+        self.set_ex_style(WindowExFlags::TRANSPARENT, click_through);
+    }
+
+    /// Returns `true` if the window accepts the files dropped by drag & drop
+    pub fn accept_files(&self) -> bool {
+        self.ex_style().contains(WindowExFlags::ACCEPT_FILES)
+    }
+
+    /// Sets if the window accepts the files dropped by drag & drop
+    pub fn set_accept_files(&self, accept_files: bool) {
+        self.set_ex_style(WindowExFlags::ACCEPT_FILES, accept_files);
+    }
+
     /// Winapi class name used during control creation
     pub fn class_name(&self) -> &'static str {
         "NativeWindowsGuiWindow"
@@ -259,6 +548,12 @@ impl Window {
 
 impl Drop for Window {
     fn drop(&mut self) {
+        // The MDI client is destroyed first so that the MDI children are not reparented to the desktop
+        #[cfg(feature = "mdi")]
+        if let Some(client) = self.handle.hwnd().and_then(mdi::mdi_client) {
+            wh::destroy_window(client);
+        }
+
         self.handle.destroy();
     }
 }
@@ -288,8 +583,8 @@ unsafe impl HasRawWindowHandle for Window {
 
 pub struct WindowBuilder<'a> {
     title: &'a str,
-    size: (i32, i32),
-    position: (i32, i32),
+    size: Extent,
+    position: Extent,
     accept_files: bool,
     center: bool,
     topmost: bool,
@@ -297,8 +592,15 @@ pub struct WindowBuilder<'a> {
     minimized: bool,
     flags: Option<WindowFlags>,
     ex_flags: u32,
+    ex_style: WindowExFlags,
     icon: Option<&'a Icon>,
-    parent: Option<ControlHandle>
+    parent: Option<ControlHandle>,
+    #[cfg(feature = "mdi")]
+    mdi_frame: bool,
+    #[cfg(feature = "backdrop")]
+    backdrop: Backdrop,
+    #[cfg(feature = "touch")]
+    touch_mode: TouchMode,
 }
 
 impl<'a> WindowBuilder<'a> {
@@ -313,18 +615,23 @@ impl<'a> WindowBuilder<'a> {
         self
     }
 
+    pub fn ex_style(mut self, style: WindowExFlags) -> WindowBuilder<'a> {
+        self.ex_style = style;
+        self
+    }
+
     pub fn title(mut self, text: &'a str) -> WindowBuilder<'a> {
         self.title = text;
         self
     }
 
-    pub fn size(mut self, size: (i32, i32)) -> WindowBuilder<'a> {
-        self.size = size;
+    pub fn size<S: Into<Extent>>(mut self, size: S) -> WindowBuilder<'a> {
+        self.size = size.into();
         self
     }
 
-    pub fn position(mut self, pos: (i32, i32)) -> WindowBuilder<'a> {
-        self.position = pos;
+    pub fn position<P: Into<Extent>>(mut self, pos: P) -> WindowBuilder<'a> {
+        self.position = pos.into();
         self
     }
 
@@ -363,19 +670,46 @@ impl<'a> WindowBuilder<'a> {
         self
     }
 
+    #[cfg(feature = "mdi")]
+    pub fn mdi_frame(mut self, mdi_frame: bool) -> WindowBuilder<'a> {
+        self.mdi_frame = mdi_frame;
+        self
+    }
+
+    #[cfg(feature = "backdrop")]
+    pub fn backdrop(mut self, backdrop: Backdrop) -> WindowBuilder<'a> {
+        self.backdrop = backdrop;
+        self
+    }
+
+    #[cfg(feature = "touch")]
+    pub fn touch_mode(mut self, mode: TouchMode) -> WindowBuilder<'a> {
+        self.touch_mode = mode;
+        self
+    }
+
     pub fn build(self, out: &mut Window) -> Result<(), NwgError> {
         use crate::win32::high_dpi::physical_to_logical;
 
         let flags = self.flags.map(|f| f.bits()).unwrap_or(out.flags());
 
-        let mut ex_flags = self.ex_flags;
+        let mut ex_flags = self.ex_flags | self.ex_style.bits();
         if self.topmost { ex_flags |= WS_EX_TOPMOST; }
         if self.accept_files { ex_flags |= WS_EX_ACCEPTFILES; }
 
+        #[cfg(feature = "mdi")]
+        let class_name = match self.mdi_frame {
+            true => "NWG_MDI_FRAME",
+            false => out.class_name()
+        };
+
+        #[cfg(not(feature = "mdi"))]
+        let class_name = out.class_name();
+
         *out = Default::default();
 
         out.handle = ControlBase::build_hwnd()
-            .class_name(out.class_name())
+            .class_name(class_name)
             .forced_flags(out.forced_flags())
             .ex_flags(ex_flags)
             .flags(flags)
@@ -385,14 +719,36 @@ impl<'a> WindowBuilder<'a> {
             .parent(self.parent)
             .build()?;
 
+        #[cfg(feature = "mdi")]
+        if self.mdi_frame {
+            unsafe { mdi::build_mdi_client(out.handle.hwnd().unwrap())?; }
+        }
+
         if self.icon.is_some() {
             out.set_icon(self.icon);
         }
 
+        // A layered window is not drawn until its attributes are set
+        if ex_flags & WS_EX_LAYERED != 0 {
+            use winapi::um::winuser::{SetLayeredWindowAttributes, LWA_ALPHA};
+            unsafe { SetLayeredWindowAttributes(out.handle.hwnd().unwrap(), 0, 255, LWA_ALPHA); }
+        }
+
+        #[cfg(feature = "backdrop")]
+        if self.backdrop != Backdrop::None {
+            out.set_backdrop(self.backdrop);
+        }
+
+        #[cfg(feature = "touch")]
+        if self.touch_mode != TouchMode::Default {
+            out.set_touch_mode(self.touch_mode);
+        }
+
         if self.center {
             let [left, top, right, bottom] = crate::Monitor::monitor_rect_from_window(out as &Window);
             let (m_width, m_height) = unsafe { physical_to_logical(right-left, bottom-top) };
-            let (width, height) = self.size;
+            let (width, height) = out.size();
+            let (width, height) = (width as i32, height as i32);
 
             let x = left + ((m_width-width)/2);
             let y = top + ((m_height-height)/2);
@@ -4,6 +4,9 @@ use std::error::Error;
 #[cfg(feature = "plotting")]
 use crate::win32::plotters_d2d::PlottersError;
 
+#[cfg(feature = "shell")]
+use crate::win32::shell::ShellErrorKind;
+
 /**
     Error enums used in the native window gui crate
 */
@@ -29,6 +32,9 @@ pub enum NwgError {
     /// Error raised when an event handler could not be bound
     EventsBinding(String),
 
+    /// Error raised when a window is used from a thread that does not own it
+    WrongThread(String),
+
     /// Error raised by the FileDialog object
     #[cfg(feature = "file-dialog")]
     FileDialogError(String),
@@ -41,6 +47,50 @@ pub enum NwgError {
     #[cfg(feature = "winnls")]
     BadLocale(String),
 
+    /// Error raised when the settings could not be read or saved
+    #[cfg(feature = "settings")]
+    SettingsError(String),
+
+    /// Error raised when a file type could not be registered
+    #[cfg(feature = "file-association")]
+    FileAssociationError(String),
+
+    /// Error raised when a child process could not be started or stopped
+    #[cfg(feature = "process")]
+    ProcessError(String),
+
+    /// Error raised when the jump list of the application could not be updated
+    #[cfg(feature = "jump-list")]
+    JumpListError(String),
+
+    /// Error raised when the taskbar button of a window could not be updated
+    #[cfg(feature = "taskbar")]
+    TaskbarError(String),
+
+    /// Error raised when the raw input devices could not be registered or listed
+    #[cfg(feature = "raw-input")]
+    RawInputError(String),
+
+    /// Error raised when the shell could not open a file
+    #[cfg(feature = "shell")]
+    ShellError(ShellErrorKind, String),
+
+    /// Error raised when a drop target could not be registered or when a drag and drop could not be started
+    #[cfg(feature = "drag-drop")]
+    DragDropError(String),
+
+    /// Error raised when the path of a known folder could not be found or when an application folder could not be created
+    #[cfg(feature = "known-folders")]
+    KnownFolderError(String),
+
+    /// Error raised when a local drag could not be started
+    #[cfg(feature = "local-drag")]
+    LocalDragError(String),
+
+    /// Error raised by a GDI+ function
+    #[cfg(feature = "gdiplus")]
+    GdiPlusError(String),
+
     /// Error raised by one of the locale functions
     #[cfg(feature = "plotting")]
     Plotters(PlottersError),
@@ -72,6 +122,10 @@ impl NwgError {
         NwgError::EventsBinding(e.into())
     }
 
+    pub fn wrong_thread<S: Into<String>>(e: S) -> NwgError {
+        NwgError::WrongThread(e.into())
+    }
+
     #[cfg(feature = "file-dialog")]
     pub fn file_dialog<S: Into<String>>(e: S) -> NwgError {
         NwgError::FileDialogError(e.into())
@@ -82,6 +136,61 @@ impl NwgError {
         NwgError::BadLocale(e.into())
     }
 
+    #[cfg(feature = "settings")]
+    pub fn settings<S: Into<String>>(e: S) -> NwgError {
+        NwgError::SettingsError(e.into())
+    }
+
+    #[cfg(feature = "file-association")]
+    pub fn file_association<S: Into<String>>(e: S) -> NwgError {
+        NwgError::FileAssociationError(e.into())
+    }
+
+    #[cfg(feature = "process")]
+    pub fn process<S: Into<String>>(e: S) -> NwgError {
+        NwgError::ProcessError(e.into())
+    }
+
+    #[cfg(feature = "jump-list")]
+    pub fn jump_list<S: Into<String>>(e: S) -> NwgError {
+        NwgError::JumpListError(e.into())
+    }
+
+    #[cfg(feature = "taskbar")]
+    pub fn taskbar<S: Into<String>>(e: S) -> NwgError {
+        NwgError::TaskbarError(e.into())
+    }
+
+    #[cfg(feature = "raw-input")]
+    pub fn raw_input<S: Into<String>>(e: S) -> NwgError {
+        NwgError::RawInputError(e.into())
+    }
+
+    #[cfg(feature = "shell")]
+    pub fn shell<S: Into<String>>(kind: ShellErrorKind, e: S) -> NwgError {
+        NwgError::ShellError(kind, e.into())
+    }
+
+    #[cfg(feature = "drag-drop")]
+    pub fn drag_drop<S: Into<String>>(e: S) -> NwgError {
+        NwgError::DragDropError(e.into())
+    }
+
+    #[cfg(feature = "known-folders")]
+    pub fn known_folder<S: Into<String>>(e: S) -> NwgError {
+        NwgError::KnownFolderError(e.into())
+    }
+
+    #[cfg(feature = "local-drag")]
+    pub fn local_drag<S: Into<String>>(e: S) -> NwgError {
+        NwgError::LocalDragError(e.into())
+    }
+
+    #[cfg(feature = "gdiplus")]
+    pub fn gdiplus<S: Into<String>>(e: S) -> NwgError {
+        NwgError::GdiPlusError(e.into())
+    }
+
     #[cfg(feature = "image-decoder")]
     pub fn image_decoder<S: Into<String>>(code: i32, e: S) -> NwgError {
         NwgError::ImageDecoderError(code, e.into())
@@ -109,6 +218,7 @@ impl fmt::Display for NwgError {
             ResourceCreationError(reason) => write!(f, "Failed to create a resource: {:?}", reason),
             LayoutCreationError(reason) => write!(f, "Failed to create a layout: {:?}", reason),
             EventsBinding(reason) => write!(f, "Failed to bind events: {:?}", reason),
+            WrongThread(reason) => write!(f, "A window was used from a thread that does not own it: {:?}", reason),
             
             #[cfg(feature = "file-dialog")]
             FileDialogError(reason) => write!(f, "File dialog actions failed: {:?}", reason),
@@ -119,6 +229,39 @@ impl fmt::Display for NwgError {
             #[cfg(feature = "winnls")]
             BadLocale(reason) => write!(f, "Windows locale functions failed: {:?}", reason),
 
+            #[cfg(feature = "settings")]
+            SettingsError(reason) => write!(f, "Settings actions failed: {:?}", reason),
+
+            #[cfg(feature = "file-association")]
+            FileAssociationError(reason) => write!(f, "File association failed: {:?}", reason),
+
+            #[cfg(feature = "process")]
+            ProcessError(reason) => write!(f, "Child process failed: {:?}", reason),
+
+            #[cfg(feature = "jump-list")]
+            JumpListError(reason) => write!(f, "Jump list actions failed: {:?}", reason),
+
+            #[cfg(feature = "taskbar")]
+            TaskbarError(reason) => write!(f, "Taskbar actions failed: {:?}", reason),
+
+            #[cfg(feature = "raw-input")]
+            RawInputError(reason) => write!(f, "Raw input actions failed: {:?}", reason),
+
+            #[cfg(feature = "shell")]
+            ShellError(_, reason) => write!(f, "Shell actions failed: {:?}", reason),
+
+            #[cfg(feature = "drag-drop")]
+            DragDropError(reason) => write!(f, "Drag and drop failed: {:?}", reason),
+
+            #[cfg(feature = "known-folders")]
+            KnownFolderError(reason) => write!(f, "Known folder failed: {:?}", reason),
+
+            #[cfg(feature = "local-drag")]
+            LocalDragError(reason) => write!(f, "Local drag failed: {:?}", reason),
+
+            #[cfg(feature = "gdiplus")]
+            GdiPlusError(reason) => write!(f, "GDI+ function failed: {:?}", reason),
+
             #[cfg(feature = "plotting")]
             Plotters(reason) => write!(f, "Plotting canvas function failed: {}", reason),
         }
@@ -0,0 +1,176 @@
+/*!
+    Asynchronous streams of events.
+
+    An `EventStream` implements `futures::Stream` over the events of a window or of a control, so that the logic of the
+    interface can be written with `async`/`await`. The events are queued by the events dispatcher of the thread and the
+    task that polls the stream is woken. The stream ends when its windows are destroyed.
+
+    The streams must be polled by an executor that runs on the thread of the windows (ex: `nwg::spawn_local` with the
+    `async-runtime` feature). Dropping the stream unbinds its handler.
+
+    The data of some events is only valid while the message is processed (`OnPaint`, `OnWindowClose`, `OnMinMaxInfo`,
+    `OnTooltipText`, `OnFileDrop`, `OnDeviceChange` and the drag and drop events). Those events are delivered with `EventData::NoData`.
+    Use a regular event handler to paint, to cancel the closing of a window, or to read the dropped files.
+
+    Requires the `async-events` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+    use futures::StreamExt;
+
+    async fn count_clicks(button: &nwg::Button, label: &nwg::Label) {
+        let mut clicks = nwg::EventStream::event(&button.handle, nwg::Event::OnButtonClick);
+        let mut count = 0;
+
+        while let Some(_click) = clicks.next().await {
+            count += 1;
+            label.set_text(&format!("{} clicks", count));
+        }
+    }
+    ```
+*/
+use futures_core::Stream;
+use crate::win32::window::{EventHandlerGuard, bind_event_handler, full_bind_event_handler};
+use crate::{ControlHandle, Event, EventData};
+use std::{cell::RefCell, collections::VecDeque, pin::Pin, rc::Rc};
+use std::task::{Context, Poll, Waker};
+
+
+/// An event received by an `EventStream`
+#[derive(Debug)]
+pub struct StreamEvent {
+    pub event: Event,
+    pub data: EventData,
+    pub control: ControlHandle,
+}
+
+struct StreamState {
+    queue: VecDeque<StreamEvent>,
+    waker: Option<Waker>,
+    closed: bool,
+}
+
+/// Owned by the callback of the handler. Ends the stream when the callback is freed (the windows were destroyed or the handler was unbound).
+struct StreamSender {
+    state: Rc<RefCell<StreamState>>,
+}
+
+impl StreamSender {
+
+    fn send(&self, event: Event, data: EventData, control: ControlHandle) {
+        let waker = {
+            let mut state = self.state.borrow_mut();
+            state.queue.push_back(StreamEvent { event, data: detach(data), control });
+            state.waker.take()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+}
+
+impl Drop for StreamSender {
+    fn drop(&mut self) {
+        let waker = {
+            let mut state = self.state.borrow_mut();
+            state.closed = true;
+            state.waker.take()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/**
+    A stream of the events of a window or of a control. See the module documentation.
+*/
+pub struct EventStream {
+    state: Rc<RefCell<StreamState>>,
+    _handler: EventHandlerGuard,
+}
+
+impl EventStream {
+
+    /// Returns a stream of the events of a window and of all its children, like `full_bind_event_handler`
+    pub fn window(window: &ControlHandle) -> EventStream {
+        let (state, sender) = EventStream::channel();
+        let handler = full_bind_event_handler(window, move |evt, data, handle| sender.send(evt, data, handle));
+
+        EventStream { state, _handler: handler.guard() }
+    }
+
+    /// Returns a stream of the events of a single control. The window that receives the events is found like `ControlHandle::on`.
+    pub fn control(control: &ControlHandle) -> EventStream {
+        EventStream::filtered(control, None)
+    }
+
+    /// Returns a stream of a single event of a control
+    pub fn event(control: &ControlHandle, event: Event) -> EventStream {
+        EventStream::filtered(control, Some(event))
+    }
+
+    /// Returns the number of events waiting in the stream
+    pub fn len(&self) -> usize {
+        self.state.borrow().queue.len()
+    }
+
+    fn filtered(control: &ControlHandle, event: Option<Event>) -> EventStream {
+        let (state, sender) = EventStream::channel();
+        let (hooked, parent) = control.event_windows();
+
+        let control = *control;
+        let handler = bind_event_handler(&ControlHandle::Hwnd(hooked), &ControlHandle::Hwnd(parent), move |evt, data, handle| {
+            if handle == control && event.map(|e| e == evt).unwrap_or(true) {
+                sender.send(evt, data, handle);
+            }
+        });
+
+        EventStream { state, _handler: handler.guard() }
+    }
+
+    fn channel() -> (Rc<RefCell<StreamState>>, StreamSender) {
+        let state = Rc::new(RefCell::new(StreamState {
+            queue: VecDeque::new(),
+            waker: None,
+            closed: false,
+        }));
+
+        (state.clone(), StreamSender { state })
+    }
+
+}
+
+impl Stream for EventStream {
+    type Item = StreamEvent;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<StreamEvent>> {
+        let mut state = self.state.borrow_mut();
+
+        if let Some(event) = state.queue.pop_front() {
+            return Poll::Ready(Some(event));
+        }
+
+        match state.closed {
+            true => Poll::Ready(None),
+            false => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Replaces the data that is only valid while the message is processed
+fn detach(data: EventData) -> EventData {
+    match data {
+        EventData::OnWindowClose(_) | EventData::OnMinMaxInfo(_) | EventData::OnTooltipText(_) |
+        EventData::OnPaint(_) | EventData::OnFileDrop(_) | EventData::OnDeviceChange(_) => EventData::NoData,
+        #[cfg(feature="drag-drop")]
+        EventData::OnDragDrop(_) => EventData::NoData,
+        data => data
+    }
+}
@@ -28,6 +28,11 @@ pub enum Event {
     /// Read the delta value with `EventData::OnMouseWheel` to check which key.
     OnMouseWheel,
 
+    /// When the user scrolls a control with the mouse wheel (vertical or horizontal), a precision touchpad or a scroll bar.
+    /// Unlike OnMouseWheel, the delta is given in lines and can be a fraction of a line on touchpads.
+    /// Read the deltas with `EventData::OnScroll`.
+    OnScroll,
+
     /// Generic window event when the user right clicks a window
     OnContextMenu,
 
@@ -38,8 +43,17 @@ pub enum Event {
     OnPaint,
 
     /// When a key is pressed on a keyboard. Unlike OnKeyDown, this returns a char (ex: 'c') in a EventData::OnChar.
+    /// The character is composed with the keyboard layout of the user (AltGr, dead keys, characters outside of the BMP).
     OnChar,
 
+    /// When a dead key is pressed on a keyboard (ex: `^` on a French keyboard). The character is combined with the next key
+    /// and delivered by `OnChar`. The dead key is returned in a EventData::OnChar.
+    OnDeadChar,
+
+    /// When the user switches to another keyboard layout. Only raised on the top level windows.
+    /// See `nwg::keyboard` for the layout information.
+    OnKeyboardLayoutChanged,
+
     /// When a key is pressed on a keyboard. Use `EventData::OnKey` to check which key.
     OnKeyPress,
 
@@ -132,6 +146,28 @@ pub enum Event {
     /// When the value of the date select is changed
     OnDatePickerChanged,
 
+    /// When the selected date (or range of dates) of a month calendar is changed. This includes the user scrolling the months.
+    /// Generates a `EventData::OnMonthCalendarSelection`
+    OnMonthCalendarSelectionChanged,
+
+    /// When the user explicitly selects a date in a month calendar
+    /// Generates a `EventData::OnMonthCalendarSelection`
+    OnMonthCalendarSelect,
+
+    /// When the user changes the key chord of a hotkey control
+    OnHotKeyChanged,
+
+    /// When a toolbar button is clicked
+    /// Generates a `EventData::OnToolbarButton`
+    OnToolbarClick,
+
+    /// When the dropdown arrow of a toolbar button without an attached menu is clicked
+    /// Generates a `EventData::OnToolbarButton`
+    OnToolbarDropdown,
+
+    /// When the user has finished dragging a splitter. The panes of the splitter are already resized.
+    OnSplitterMoved,
+
     /// When an item on a list box is clicked twice
     OnListBoxDoubleClick,
 
@@ -162,6 +198,10 @@ pub enum Event {
     /// When the user selects on a menu item
     OnMenuItemSelected,
 
+    /// When the user checks another item of a `MenuRadioGroup`
+    /// Generates a `EventData::OnMenuRadioSelection` with the index of the checked item
+    OnMenuRadioSelectionChanged,
+
     /// When the user hovers over a callback tooltip
     /// The callback will also receive a `EventData::OnTooltipText`
     OnTooltipText,
@@ -243,6 +283,11 @@ pub enum Event {
     /// When the control has lost the input focus
     OnListViewFocusLost,
 
+    /// When the user starts to drag an item with the left mouse button. See `nwg::start_drag` (requires the `drag-drop` feature)
+    /// or `nwg::start_local_drag` (requires the `local-drag` feature).
+    /// Generates an `EventData::ListViewItemIndex`
+    OnListViewBeginDrag,
+
     /// When a TrayNotification info popup (not the tooltip) is shown 
     OnTrayNotificationShow,
 
@@ -255,17 +300,177 @@ pub enum Event {
     /// When a TrayNotification is closed due to a user click
     OnTrayNotificationUserClose,
 
+    /// When the user selects a TrayNotification icon with the mouse
+    /// Generates a `EventData::OnTrayNotification` with the position of the icon in screen coordinates
+    OnTrayNotificationSelect,
+
+    /// When the user selects a TrayNotification icon with the keyboard (space or enter)
+    /// Generates a `EventData::OnTrayNotification` with the position of the icon in screen coordinates
+    OnTrayNotificationKeySelect,
+
     /// When a timer delay is elapsed
+    /// The ticks of an `AnimationTimer` generate a `EventData::OnTimerTick` with the time elapsed since the previous tick
     OnTimerTick,
 
     /// When a timer end condition is reached
     OnTimerStop,
 
+    /// When an animation of an Animator reaches its target value
+    /// Generates a `EventData::OnAnimationEnd` with the id of the animation
+    OnAnimationEnd,
+
     /// When a notice is... noticed
     OnNotice,
 
     /// When a user clicks on the X button of a window
     OnWindowClose,
+
+    /// When a MDI child becomes the active child of its frame
+    OnMdiChildActivate,
+
+    /// When a MDI child is no longer the active child of its frame
+    OnMdiChildDeactivate,
+
+    /// When the mouse moves over a data point of a chart
+    /// Generates a `EventData::OnChartPoint`
+    OnChartPointHover,
+
+    /// When the mouse leaves the data point of a chart that was hovered
+    OnChartPointLeave,
+
+    /// When the user selects a tab of a `DocumentTabs`
+    /// Generates a `EventData::OnDocumentTab` with the index of the selected tab and the index of the tab that was selected before
+    OnDocumentTabSelect,
+
+    /// When the user clicks the close button of a document tab or middle-clicks the tab. The tab is not removed by the control.
+    /// Generates a `EventData::OnDocumentTab`
+    OnDocumentTabClose,
+
+    /// When the user moves a document tab by dragging it
+    /// Generates a `EventData::OnDocumentTab` with the new index of the tab and its previous index
+    OnDocumentTabReorder,
+
+    /// When the user right-clicks a document tab
+    /// Generates a `EventData::OnDocumentTab`
+    OnDocumentTabContextMenu,
+
+    /// When the user changes the selection of a hex view
+    /// Generates a `EventData::OnHexView` with the first selected byte and the length of the selection
+    OnHexViewSelectionChanged,
+
+    /// When the user modifies a byte in a hex view
+    /// Generates a `EventData::OnHexView` with the offset and the length of the modified bytes
+    OnHexViewEdit,
+
+    /// When the user clicks a link of a markdown view. Not raised for the links to the headings of the document (ex: `#install`).
+    /// Generates a `EventData::OnMarkdownLink` with the url of the link
+    OnMarkdownLinkClick,
+
+    /// When a window receives a jump list task with `JumpList::dispatch`
+    /// Generates a `EventData::OnJumpListTask` with the id of the task
+    OnJumpListTask,
+
+    /// When the user clicks on a button of the thumbnail toolbar of a window. See `nwg::set_thumbnail_buttons`
+    /// Generates a `EventData::OnThumbnailButtonClick` with the id of the button
+    OnThumbnailButtonClick,
+
+    /// When the user presses a shortcut of an `AcceleratorTable` bound to a command id.
+    /// Generates a `EventData::OnAccelerator` with the id
+    OnAccelerator,
+
+    /// When the user changes the value of a `NumberSlider` with its trackbar or its text input.
+    /// Generates a `EventData::OnValueChanged` with the new value
+    OnValueChanged,
+
+    /// When the user drops a panel of a `PanelStack` at a new place.
+    /// Generates a `EventData::OnPanelReorder` with the new index of the panel and its previous index
+    OnPanelReorder,
+
+    /// When a window registered with `nwg::raw_input::register` receives an input from a device.
+    /// Generates a `EventData::OnRawInput` with the parsed input
+    OnRawInput,
+
+    /// When the user touches a window built with `TouchMode::Touch`.
+    /// Generates a `EventData::OnTouch` with the contacts that changed
+    OnTouch,
+
+    /// When the user makes a gesture (pan, zoom, rotate, two finger tap, press and tap) on a touch screen.
+    /// Generates a `EventData::OnGesture` with the gesture and its deltas
+    OnGesture,
+
+    /// When a pen or a finger touches a control, or when a button of the pointer is pressed.
+    /// Generates a `EventData::OnPointer` with the pressure and the tilt of the pointer
+    OnPointerDown,
+
+    /// When a pen or a finger moves over a control, or when its pressure changes.
+    /// Generates a `EventData::OnPointer` with the pressure and the tilt of the pointer
+    OnPointerUpdate,
+
+    /// When a pen or a finger is lifted from a control.
+    /// Generates a `EventData::OnPointer` with the pressure and the tilt of the pointer
+    OnPointerUp,
+
+    /// When a device is added to or removed from the system.
+    /// Top level windows are notified when the device tree changes. A `MessageWindow` built with `device_notifications(true)`
+    /// is also notified of the arrival and the removal of each device interface.
+    /// Generates a `EventData::OnDeviceChange`
+    OnDeviceChange,
+
+    /// When the user changes the color theme of the applications (light or dark) in the Windows settings.
+    /// Only sent to the top level windows. See `nwg::system_theme` (requires the `dark-mode` feature).
+    OnSystemThemeChanged,
+
+    /// When the events loop of the thread exits (after `nwg::quit` or `stop_thread_dispatch`). Sent to the top level windows
+    /// and message windows of the thread while they are still alive, so that the application can save its state and free its resources.
+    /// Generates a `EventData::OnAppExit`
+    OnAppExit,
+
+    /// When data is dragged into a control registered with `nwg::register_drop_target`.
+    /// Generates a `EventData::OnDragDrop`. Set an effect to accept the drop.
+    OnDragEnter,
+
+    /// When data is dragged over a drop target.
+    /// Generates a `EventData::OnDragDrop` with the effect set by the previous event.
+    OnDragOver,
+
+    /// When the dragged data leaves a drop target, or when the user cancels the drag and drop
+    OnDragLeave,
+
+    /// When data is dropped on a drop target. Only raised if the drop was accepted during `OnDragEnter` or `OnDragOver`.
+    /// Generates a `EventData::OnDragDrop`
+    OnDragDrop,
+
+    /// When a file or a folder watched by a `nwg::shell::ShellWatcher` changes, or when a media is inserted in a watched drive.
+    /// Generates a `EventData::OnShellChange` (requires the `shell` feature)
+    OnShellChange,
+
+    /// When `nwg::start_local_drag` starts to drag a payload accepted by a local drop target. Sent to every target that accepts the payload.
+    /// Generates a `EventData::OnLocalDrag`
+    OnLocalDragStart,
+
+    /// When a payload accepted by a local drop target is dragged into the target.
+    /// Generates a `EventData::OnLocalDrag`
+    OnLocalDragEnter,
+
+    /// When a payload accepted by a local drop target moves over the target.
+    /// Generates a `EventData::OnLocalDrag`
+    OnLocalDragOver,
+
+    /// When a dragged payload leaves a local drop target, or when the drag is cancelled over the target.
+    /// Generates a `EventData::OnLocalDrag`
+    OnLocalDragLeave,
+
+    /// When a payload is dropped on a local drop target that accepts it.
+    /// Generates a `EventData::OnLocalDrag`
+    OnLocalDrop,
+
+    /// When a local drag ends, after the drop or after the user cancelled it. Sent to every target that received `OnLocalDragStart`.
+    /// Generates a `EventData::OnLocalDrag`
+    OnLocalDragEnd,
+
+    /// When an image decoded on a worker thread by `Bitmap::decode_async` is ready. Sent to the target window of the call.
+    /// Generates a `EventData::OnImageDecoded` (requires the `async-decode` feature)
+    OnImageDecoded,
 }
 
 
@@ -299,9 +504,18 @@ pub enum EventData {
     /// a negative value indicates that the wheel was rotated to the left.
     OnMouseWheel(i32),
 
+    /// The scroll deltas in lines
+    OnScroll(ScrollData),
+
     /// The path to one or more files that were dropped in the application
     OnFileDrop(DropFiles),
 
+    /// The kind of device change and the device that was added or removed
+    OnDeviceChange(DeviceChangeData),
+
+    /// The exit code passed to `nwg::quit`
+    OnAppExit(i32),
+
     /// The handle to the item being deleted. The item is still valid.
     #[cfg(feature="tree-view")]
     OnTreeItemDelete(crate::TreeItem),
@@ -318,6 +532,14 @@ pub enum EventData {
     #[cfg(feature="tree-view")]
     OnTreeItemSelectionChanged{ old: crate::TreeItem, new: crate::TreeItem },
 
+    /// The first and the last date of the selection of a month calendar. The range is inclusive.
+    #[cfg(feature="month-calendar")]
+    OnMonthCalendarSelection { start: crate::DatePickerValue, end: crate::DatePickerValue },
+
+    /// The command id of the toolbar button that raised the event
+    #[cfg(feature="toolbar")]
+    OnToolbarButton { id: u32 },
+
     /// Row index and column index of the list view item that raised the event
     /// `row_index` `0xFFF...` means the absence of an item
     #[cfg(feature="list-view")]
@@ -326,10 +548,115 @@ pub enum EventData {
     /// Row index, column index, and selected state of the list view item that raised the event
     #[cfg(feature="list-view")]
     OnListViewItemChanged { row_index: usize, column_index: usize, selected: bool },
+
+    /// The data point of a chart hovered by the mouse
+    #[cfg(feature="chart")]
+    OnChartPoint(crate::ChartPoint),
+
+    /// The index of the document tab that raised the event, and its previous index (previously selected tab or index before a move)
+    #[cfg(feature="document-tabs")]
+    OnDocumentTab { index: usize, previous: Option<usize> },
+
+    /// A range of bytes in a hex view
+    #[cfg(feature="hex-view")]
+    OnHexView { offset: u64, length: u64 },
+
+    /// The index of the checked item of a menu radio group
+    #[cfg(feature="menu")]
+    OnMenuRadioSelection(usize),
+
+    /// The url of the link clicked in a markdown view
+    #[cfg(feature="markdown-view")]
+    OnMarkdownLink(String),
+
+    /// The anchor point of a tray notification event in screen coordinates
+    OnTrayNotification { x: i32, y: i32 },
+
+    /// The id of the animation that raised the event
+    OnAnimationEnd(u32),
+
+    /// The time elapsed since the previous tick of an animation timer
+    OnTimerTick(std::time::Duration),
+
+    /// The id of the jump list task that raised the event
+    #[cfg(feature="jump-list")]
+    OnJumpListTask(String),
+
+    /// The id of the thumbnail toolbar button that was clicked
+    #[cfg(feature="taskbar")]
+    OnThumbnailButtonClick(u32),
+
+    /// The command id of the accelerator that was pressed
+    #[cfg(feature="accelerator-table")]
+    OnAccelerator(u16),
+
+    /// The new value of a number slider
+    #[cfg(feature="number-slider")]
+    OnValueChanged(f64),
+
+    /// The new index and the previous index of a panel
+    #[cfg(feature="panel-stack")]
+    OnPanelReorder { index: usize, previous: usize },
+
+    /// The input sent by a keyboard, a mouse or a HID device
+    #[cfg(feature="raw-input")]
+    OnRawInput(crate::raw_input::RawInput),
+
+    /// The touch contacts that changed
+    #[cfg(feature="touch")]
+    OnTouch(Vec<crate::TouchPoint>),
+
+    /// A step of a gesture
+    #[cfg(feature="touch")]
+    OnGesture(crate::GestureData),
+
+    /// The state of a pen, touch or mouse pointer
+    #[cfg(feature="pointer")]
+    OnPointer(crate::PointerData),
+
+    /// The data dragged over a drop target
+    #[cfg(feature="drag-drop")]
+    OnDragDrop(crate::DragDropData),
+
+    /// The kind of change and the paths of the item that changed
+    #[cfg(feature="shell")]
+    OnShellChange(crate::shell::ShellChange),
+
+    /// The payload dragged between the controls of the application
+    #[cfg(feature="local-drag")]
+    OnLocalDrag(crate::LocalDragData),
+
+    /// The bitmap decoded by `Bitmap::decode_async`
+    #[cfg(feature="async-decode")]
+    OnImageDecoded(Box<crate::DecodedImage>),
 }
 
 impl EventData {
 
+    /// Unwraps event data into the exit code of the application. Panics if it's not the right type.
+    pub fn on_app_exit(&self) -> i32 {
+        match self {
+            &EventData::OnAppExit(code) => code,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// Unwraps event data into a `ScrollData`. Panics if it's not the right type.
+    pub fn on_scroll(&self) -> ScrollData {
+        match self {
+            &EventData::OnScroll(s) => s,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// Unwraps event data into a `&DeviceChangeData`. Panics if it's not the right type.
+    pub fn on_device_change(&self) -> &DeviceChangeData {
+        match self {
+            EventData::OnDeviceChange(d) => d,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
     /// Unwraps event data into a `&PaintData`. Panics if it's not the right type.
     pub fn on_paint(&self) -> &PaintData {
         match self {
@@ -416,6 +743,24 @@ impl EventData {
         }
     }
     
+    /// unwraps event data into the first and the last date of a month calendar selection (start, end)
+    #[cfg(feature="month-calendar")]
+    pub fn on_month_calendar_selection(&self) -> (crate::DatePickerValue, crate::DatePickerValue) {
+        match self {
+            &EventData::OnMonthCalendarSelection { start, end } => (start, end),
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into the command id of a toolbar button
+    #[cfg(feature="toolbar")]
+    pub fn on_toolbar_button(&self) -> u32 {
+        match self {
+            &EventData::OnToolbarButton { id } => id,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
     /// unwraps event data into the indices of a list view index (row_index, column_index)
     #[cfg(feature="list-view")]
     pub fn on_list_view_item_index(&self) -> (usize, usize) {
@@ -434,6 +779,192 @@ impl EventData {
         }
     }
 
+    /// unwraps event data into the data point of a chart
+    #[cfg(feature="chart")]
+    pub fn on_chart_point(&self) -> crate::ChartPoint {
+        match self {
+            &EventData::OnChartPoint(point) => point,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into the index of a document tab and its previous index (index, previous)
+    #[cfg(feature="document-tabs")]
+    pub fn on_document_tab(&self) -> (usize, Option<usize>) {
+        match self {
+            &EventData::OnDocumentTab { index, previous } => (index, previous),
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into a range of bytes of a hex view (offset, length)
+    #[cfg(feature="hex-view")]
+    pub fn on_hex_view(&self) -> (u64, u64) {
+        match self {
+            &EventData::OnHexView { offset, length } => (offset, length),
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into the index of the checked item of a menu radio group
+    #[cfg(feature="menu")]
+    pub fn on_menu_radio_selection(&self) -> usize {
+        match self {
+            &EventData::OnMenuRadioSelection(index) => index,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into the url of the link clicked in a markdown view
+    #[cfg(feature="markdown-view")]
+    pub fn on_markdown_link(&self) -> &str {
+        match self {
+            EventData::OnMarkdownLink(url) => url,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into the anchor point of a tray notification event in screen coordinates (x, y)
+    pub fn on_tray_notification(&self) -> (i32, i32) {
+        match self {
+            &EventData::OnTrayNotification { x, y } => (x, y),
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into the id of the animation that raised the event
+    pub fn on_animation_end(&self) -> u32 {
+        match self {
+            &EventData::OnAnimationEnd(id) => id,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into the time elapsed since the previous tick of an animation timer
+    pub fn on_timer_tick(&self) -> std::time::Duration {
+        match self {
+            &EventData::OnTimerTick(delta) => delta,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into the id of a jump list task
+    #[cfg(feature="jump-list")]
+    pub fn on_jump_list_task(&self) -> &str {
+        match self {
+            EventData::OnJumpListTask(id) => id,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into the id of a thumbnail toolbar button
+    #[cfg(feature="taskbar")]
+    pub fn on_thumbnail_button_click(&self) -> u32 {
+        match self {
+            &EventData::OnThumbnailButtonClick(id) => id,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into the command id of an accelerator
+    #[cfg(feature="accelerator-table")]
+    pub fn on_accelerator(&self) -> u16 {
+        match self {
+            &EventData::OnAccelerator(id) => id,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into the value of a number slider
+    #[cfg(feature="number-slider")]
+    pub fn on_value_changed(&self) -> f64 {
+        match self {
+            &EventData::OnValueChanged(value) => value,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into the new index and the previous index of a panel
+    #[cfg(feature="panel-stack")]
+    pub fn on_panel_reorder(&self) -> (usize, usize) {
+        match self {
+            &EventData::OnPanelReorder { index, previous } => (index, previous),
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into the raw input of a device
+    #[cfg(feature="raw-input")]
+    pub fn on_raw_input(&self) -> &crate::raw_input::RawInput {
+        match self {
+            EventData::OnRawInput(i) => i,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into the touch contacts that changed
+    #[cfg(feature="touch")]
+    pub fn on_touch(&self) -> &[crate::TouchPoint] {
+        match self {
+            EventData::OnTouch(points) => points,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into a step of a gesture
+    #[cfg(feature="touch")]
+    pub fn on_gesture(&self) -> crate::GestureData {
+        match self {
+            &EventData::OnGesture(g) => g,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into the state of a pointer
+    #[cfg(feature="pointer")]
+    pub fn on_pointer(&self) -> crate::PointerData {
+        match self {
+            &EventData::OnPointer(p) => p,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into the data dragged over a drop target
+    #[cfg(feature="drag-drop")]
+    pub fn on_drag_drop(&self) -> &crate::DragDropData {
+        match self {
+            EventData::OnDragDrop(d) => d,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into a change reported by a shell watcher
+    #[cfg(feature="shell")]
+    pub fn on_shell_change(&self) -> &crate::shell::ShellChange {
+        match self {
+            EventData::OnShellChange(d) => d,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into the payload of a local drag
+    #[cfg(feature="local-drag")]
+    pub fn on_local_drag(&self) -> &crate::LocalDragData {
+        match self {
+            EventData::OnLocalDrag(d) => d,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
+    /// unwraps event data into an image decoded on a worker thread
+    #[cfg(feature="async-decode")]
+    pub fn on_image_decoded(&self) -> &crate::DecodedImage {
+        match self {
+            EventData::OnImageDecoded(d) => d,
+            d => panic!("Wrong data type: {:?}", d)
+        }
+    }
+
 }
 
 //
@@ -443,7 +974,9 @@ impl EventData {
 use winapi::um::commctrl::NMTTDISPINFOW;
 use winapi::um::winuser::{PAINTSTRUCT, MINMAXINFO, BeginPaint, EndPaint};
 use winapi::um::shellapi::{HDROP, DragFinish};
+use winapi::um::dbt::DEV_BROADCAST_HDR;
 use winapi::shared::windef::{HWND, POINT};
+use winapi::shared::minwindef::WPARAM;
 use std::fmt;
 
 /// A wrapper structure that sets the tooltip text on an `OnTooltipText` callback
@@ -629,6 +1162,116 @@ impl Drop for DropFiles {
 }
 
 
+/// The kind of a `DeviceChangeData`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeviceChange {
+    /// A device was inserted and is now available
+    Arrival,
+
+    /// A device was removed
+    RemoveComplete,
+
+    /// A device was added to or removed from the device tree. No device information is available.
+    NodesChanged,
+
+    /// Another `DBT_*` device event
+    Other(usize),
+}
+
+/// Information about a device added to or removed from the system. See `Event::OnDeviceChange`.
+pub struct DeviceChangeData {
+    pub(crate) event: WPARAM,
+    pub(crate) header: *const DEV_BROADCAST_HDR,
+}
+
+impl DeviceChangeData {
+
+    /// Returns the kind of device change
+    pub fn kind(&self) -> DeviceChange {
+        use winapi::um::dbt::{DBT_DEVICEARRIVAL, DBT_DEVICEREMOVECOMPLETE, DBT_DEVNODES_CHANGED};
+
+        match self.event {
+            DBT_DEVICEARRIVAL => DeviceChange::Arrival,
+            DBT_DEVICEREMOVECOMPLETE => DeviceChange::RemoveComplete,
+            DBT_DEVNODES_CHANGED => DeviceChange::NodesChanged,
+            e => DeviceChange::Other(e)
+        }
+    }
+
+    /// Returns the symbolic link name of the device interface that was added or removed.
+    /// Only available when the event was sent to a `MessageWindow` that receives the device notifications.
+    pub fn device_name(&self) -> Option<String> {
+        use winapi::um::dbt::{DEV_BROADCAST_DEVICEINTERFACE_W, DBT_DEVTYP_DEVICEINTERFACE};
+        use crate::win32::base_helper::from_utf16;
+        use std::slice;
+
+        if self.header.is_null() {
+            return None;
+        }
+
+        unsafe {
+            let header = &*self.header;
+            if header.dbch_devicetype != DBT_DEVTYP_DEVICEINTERFACE {
+                return None;
+            }
+
+            // `dbcc_name` is a variable length, null terminated string that starts at the end of the structure header
+            let data = &*(self.header as *const DEV_BROADCAST_DEVICEINTERFACE_W);
+            let name_offset = data.dbcc_name.as_ptr() as usize - self.header as usize;
+            let name_len = (header.dbch_size as usize).saturating_sub(name_offset) / 2;
+            let name = slice::from_raw_parts(data.dbcc_name.as_ptr(), name_len);
+
+            Some(from_utf16(name))
+        }
+    }
+
+}
+
+impl fmt::Debug for DeviceChangeData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DeviceChangeData {{ kind: {:?}, device_name: {:?} }}", self.kind(), self.device_name())
+    }
+}
+
+
+/// The source of a `ScrollData`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrollSource {
+    /// A mouse wheel that scrolls by notches
+    Wheel,
+
+    /// A precision touchpad or a free spinning wheel that scrolls by fractions of a notch
+    Precise,
+
+    /// A scroll bar of the control or a scroll bar control
+    ScrollBar,
+}
+
+/**
+    The deltas of an `OnScroll` event, in lines. A positive `dy` scrolls toward the end of the content (down) and
+    a positive `dx` scrolls right.
+
+    The wheel deltas are scaled by the number of lines (or characters horizontally) that the user selected in the mouse settings,
+    so a control only needs to know the size of a line to scroll like the system controls.
+    The scroll bar deltas are in the units of the scroll bar.
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScrollData {
+    pub dx: f64,
+    pub dy: f64,
+    pub source: ScrollSource,
+}
+
+impl ScrollData {
+
+    /// Returns the deltas in pixels, using `line_width` and `line_height` as the size of a line
+    pub fn pixels(&self, line_width: f64, line_height: f64) -> (f64, f64) {
+        (self.dx * line_width, self.dy * line_height)
+    }
+
+}
+
+
 pub struct MinMaxInfo {
     pub(crate) inner: *mut MINMAXINFO,
 }
@@ -0,0 +1,407 @@
+use crate::controls::ControlHandle;
+use crate::win32::window_helper as wh;
+use crate::win32::window::{RawEventHandler, unbind_raw_event_handler};
+use crate::win32::resize::bind_resize_handler;
+use crate::NwgError;
+use winapi::shared::windef::HWND;
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::ptr;
+
+
+bitflags! {
+    /**
+        The edges of the parent a child of an AnchorLayout keeps its distance to.
+
+        * NONE:   The child keeps its size and its relative position at the center of the parent
+        * LEFT:   The child keeps its distance to the left edge
+        * TOP:    The child keeps its distance to the top edge
+        * RIGHT:  The child keeps its distance to the right edge
+        * BOTTOM: The child keeps its distance to the bottom edge
+        * ALL:    The child keeps its distance to all the edges, growing and shrinking with the parent
+
+        If a child is anchored to two opposite edges, it is stretched. If it is anchored to one of them, it is moved
+        with this edge. The default anchor of a child is `LEFT | TOP`, which means the child does not move.
+    */
+    pub struct Anchor: u8 {
+        const NONE = 0;
+        const LEFT = 0b0001;
+        const TOP = 0b0010;
+        const RIGHT = 0b0100;
+        const BOTTOM = 0b1000;
+        const ALL = 0b1111;
+    }
+}
+
+impl Default for Anchor {
+    fn default() -> Anchor {
+        Anchor::LEFT | Anchor::TOP
+    }
+}
+
+
+/// A control item in an AnchorLayout
+#[derive(Debug)]
+pub struct AnchorLayoutItem {
+    /// The handle to the control in the item
+    control: HWND,
+
+    /// The position and the size of the control when the parent has the reference size
+    rect: (i32, i32, i32, i32),
+
+    /// The size of the parent used to compute the distances to the anchored edges
+    reference: Option<(u32, u32)>,
+
+    /// The edges the control is anchored to
+    pub anchor: Anchor,
+}
+
+impl AnchorLayoutItem {
+
+    /// Initialize a new layout item. The control keeps its current distances to the anchored edges.
+    pub fn new<W: Into<ControlHandle>>(c: W, anchor: Anchor) -> AnchorLayoutItem {
+        use winapi::um::winuser::GetParent;
+
+        let control = c.into().hwnd().expect("Child must be a window-like control (HWND handle)");
+        let (x, y, w, h) = unsafe { wh::get_window_rect_in_parent(control) };
+
+        // The position of the child is relative to the current size of its parent
+        let reference = unsafe {
+            let parent = GetParent(control);
+            match parent.is_null() {
+                true => None,
+                false => Some(wh::get_window_size(parent))
+            }
+        };
+
+        AnchorLayoutItem { control, rect: (x, y, w as i32, h as i32), reference, anchor }
+    }
+
+    /**
+        Initialize a new layout item from a position and a size in logical units, for example generated by a designer.
+        The position is relative to the reference size of the layout (see `AnchorLayoutBuilder::reference_size`).
+    */
+    pub fn with_rect<W: Into<ControlHandle>>(c: W, anchor: Anchor, pos: (i32, i32), size: (u32, u32)) -> AnchorLayoutItem {
+        let control = c.into().hwnd().expect("Child must be a window-like control (HWND handle)");
+        AnchorLayoutItem { control, rect: (pos.0, pos.1, size.0 as i32, size.1 as i32), reference: None, anchor }
+    }
+
+    /// Computes the position and size of the item along one axis
+    fn place(pos: i32, size: i32, delta: i32, start: bool, end: bool) -> (i32, i32) {
+        match (start, end) {
+            (true, true) => (pos, (size + delta).max(0)),
+            (false, true) => (pos + delta, size),
+            (true, false) => (pos, size),
+            (false, false) => (pos + delta / 2, size),
+        }
+    }
+
+}
+
+
+/// A layout that keeps the distance of widgets to the edges of their parent
+/// This is the inner data shared between the callback and the application
+pub struct AnchorLayoutInner {
+    /// The control that holds the layout
+    base: HWND,
+
+    /// The resize handler of the layout
+    handler: Option<RawEventHandler>,
+
+    /// The children of the control that fit in the layout
+    children: Vec<AnchorLayoutItem>,
+
+    /// The size of the parent the children positions are relative to. If None, use the size of the parent when the layout is built.
+    reference_size: Option<(u32, u32)>,
+}
+
+/**
+A layout where each child is anchored to some edges of the parent control, and keeps its distance to these edges
+when the parent is resized. This is the model used by most dialog designers: the children are placed at absolute
+positions and the anchors only describe how they follow the parent.
+
+By default, the children positions are taken from their current position when they are added to the layout.
+To place them from a design (ex: generated code), use `AnchorLayoutItem::with_rect` with the `reference_size`
+of the design.
+
+Requires the `anchor-layout` feature.
+
+```rust
+    use native_windows_gui as nwg;
+    use nwg::Anchor;
+
+    fn layout(layout: &nwg::AnchorLayout, window: &nwg::Window, text: &nwg::TextBox, ok: &nwg::Button, cancel: &nwg::Button) {
+        nwg::AnchorLayout::builder()
+            .parent(window)
+            .reference_size((400, 300))
+            .child_item(nwg::AnchorLayoutItem::with_rect(text, Anchor::ALL, (10, 10), (380, 240)))
+            .child_item(nwg::AnchorLayoutItem::with_rect(ok, Anchor::RIGHT | Anchor::BOTTOM, (210, 260), (85, 30)))
+            .child_item(nwg::AnchorLayoutItem::with_rect(cancel, Anchor::RIGHT | Anchor::BOTTOM, (305, 260), (85, 30)))
+            .build(&layout);
+    }
+```
+*/
+#[derive(Clone)]
+pub struct AnchorLayout {
+    inner: Rc<RefCell<AnchorLayoutInner>>
+}
+
+impl AnchorLayout {
+
+    pub fn builder() -> AnchorLayoutBuilder {
+        let layout = AnchorLayoutInner {
+            base: ptr::null_mut(),
+            handler: None,
+            children: Vec::new(),
+            reference_size: None,
+        };
+
+        AnchorLayoutBuilder { layout }
+    }
+
+    /**
+        Add a children control to the layout. The control keeps its current distances to the anchored edges.
+        This is a simplified interface over `add_child_item`
+
+        Panic:
+        - If the layout is not initialized
+        - If the control is not window-like (HWND handle)
+    */
+    pub fn add_child<W: Into<ControlHandle>>(&self, anchor: Anchor, c: W) {
+        self.add_child_item(AnchorLayoutItem::new(c, anchor));
+    }
+
+    /**
+        Add a children control to the layout. If the item was created with `AnchorLayoutItem::with_rect`, the position
+        is relative to the reference size of the layout.
+
+        Panic:
+        - If the layout is not initialized
+    */
+    pub fn add_child_item(&self, i: AnchorLayoutItem) {
+        {
+            let mut inner = self.inner.borrow_mut();
+            if inner.base.is_null() {
+                panic!("AnchorLayout is not initialized");
+            }
+
+            inner.children.push(i);
+        }
+
+        self.fit();
+    }
+
+    /**
+        Remove the children control from the layout.
+        Note that the child control won't be hidden after being removed from the control.
+
+        This method won't do anything if the control is not in the layout.
+
+        Panic:
+        - If the layout is not initialized
+    */
+    pub fn remove_child<W: Into<ControlHandle>>(&self, c: W) {
+        let mut inner = self.inner.borrow_mut();
+        if inner.base.is_null() {
+            panic!("AnchorLayout is not initialized");
+        }
+
+        let handle = c.into().hwnd().expect("Control must be window-like (HWND handle)");
+        if let Some(i) = inner.children.iter().position(|item| item.control == handle) {
+            inner.children.remove(i);
+        }
+    }
+
+    /**
+        Change the anchors of a children control. The control keeps its current distances to the new anchored edges.
+
+        This method won't do anything if the control is not in the layout.
+
+        Panic:
+        - If the layout is not initialized
+    */
+    pub fn set_child_anchor<W: Into<ControlHandle>>(&self, c: W, anchor: Anchor) {
+        let mut inner = self.inner.borrow_mut();
+        if inner.base.is_null() {
+            panic!("AnchorLayout is not initialized");
+        }
+
+        let handle = c.into().hwnd().expect("Control must be window-like (HWND handle)");
+        let size = unsafe { wh::get_window_size(inner.base) };
+        if let Some(item) = inner.children.iter_mut().find(|item| item.control == handle) {
+            let (x, y, w, h) = unsafe { wh::get_window_rect_in_parent(handle) };
+            item.rect = (x, y, w as i32, h as i32);
+            item.reference = Some(size);
+            item.anchor = anchor;
+        }
+    }
+
+    /**
+        Check if a window control is a children of the layout
+
+        Panic:
+        - If the layout is not initialized
+        - If the child is not a window-like control
+    */
+    pub fn has_child<W: Into<ControlHandle>>(&self, c: W) -> bool {
+        let inner = self.inner.borrow();
+        if inner.base.is_null() {
+            panic!("AnchorLayout is not initialized");
+        }
+
+        let handle = c.into().hwnd().expect("Children is not a window-like control (HWND handle)");
+        inner.children.iter().any(|c| c.control == handle )
+    }
+
+    /// Resize the layout as if the parent window had the specified size.
+    ///
+    /// Arguments:
+    ///   w: New width of the layout
+    ///   h: New height of the layout
+    ///
+    ///  Panic:
+    ///   - The layout must have been successfully built otherwise this function will panic.
+    pub fn resize(&self, w: u32, h: u32) {
+        if self.inner.borrow().base.is_null() {
+            panic!("Anchor layout is not bound to a parent control.")
+        }
+
+        self.update_layout(w, h);
+    }
+
+    /// Resize the layout to fit the parent window size
+    ///
+    /// Panic:
+    ///   - The layout must have been successfully built otherwise this function will panic.
+    pub fn fit(&self) {
+        let base = self.inner.borrow().base;
+        if base.is_null() {
+            panic!("Anchor layout is not bound to a parent control.")
+        }
+
+        let (w, h) = unsafe { wh::get_window_size(base) };
+        self.update_layout(w, h);
+    }
+
+    fn update_layout(&self, width: u32, height: u32) {
+        let mut inner = self.inner.borrow_mut();
+        if inner.base.is_null() || inner.children.len() == 0 {
+            return;
+        }
+
+        let reference = inner.reference_size.unwrap_or((width, height));
+        let mut batch = wh::WindowPositionBatch::new(inner.children.len());
+        for item in inner.children.iter_mut() {
+            let (rw, rh) = *item.reference.get_or_insert(reference);
+            let (dx, dy) = (width as i32 - rw as i32, height as i32 - rh as i32);
+            let (x, y, w, h) = item.rect;
+
+            let anchor = item.anchor;
+            let (x, w) = AnchorLayoutItem::place(x, w, dx, anchor.contains(Anchor::LEFT), anchor.contains(Anchor::RIGHT));
+            let (y, h) = AnchorLayoutItem::place(y, h, dy, anchor.contains(Anchor::TOP), anchor.contains(Anchor::BOTTOM));
+
+            batch.move_window(item.control, x, y, w as u32, h as u32);
+        }
+
+        batch.apply();
+    }
+}
+
+impl Default for AnchorLayout {
+
+    fn default() -> AnchorLayout {
+        let inner = AnchorLayoutInner {
+            base: ptr::null_mut(),
+            handler: None,
+            children: Vec::new(),
+            reference_size: None,
+        };
+
+        AnchorLayout {
+            inner: Rc::new(RefCell::new(inner))
+        }
+    }
+
+}
+
+
+/// Builder for an `AnchorLayout` struct
+pub struct AnchorLayoutBuilder {
+    layout: AnchorLayoutInner
+}
+
+impl AnchorLayoutBuilder {
+
+    /// Set the layout parent. The handle must be a window object otherwise the function will panic
+    pub fn parent<W: Into<ControlHandle>>(mut self, p: W) -> AnchorLayoutBuilder {
+        self.layout.base = p.into().hwnd().expect("Parent must be HWND");
+        self
+    }
+
+    /// Add a children to the layout. The control keeps its current distances to the anchored edges.
+    /// This is a shortcut over `child_item`.
+    /// The handle must be a window object otherwise the function will panic
+    pub fn child<W: Into<ControlHandle>>(mut self, anchor: Anchor, c: W) -> AnchorLayoutBuilder {
+        self.layout.children.push(AnchorLayoutItem::new(c, anchor));
+        self
+    }
+
+    /// Add a children to the layout
+    pub fn child_item(mut self, item: AnchorLayoutItem) -> AnchorLayoutBuilder {
+        self.layout.children.push(item);
+        self
+    }
+
+    /// Set the size of the parent (in logical units) the positions of the children are relative to.
+    /// If not set, use the size of the parent when the layout is built.
+    pub fn reference_size(mut self, size: (u32, u32)) -> AnchorLayoutBuilder {
+        self.layout.reference_size = Some(size);
+        self
+    }
+
+    /// Build the layout object and bind the callback.
+    pub fn build(mut self, layout: &AnchorLayout) -> Result<(), NwgError> {
+        if self.layout.base.is_null() {
+            return Err(NwgError::layout_create("AnchorLayout does not have a parent."));
+        }
+
+        let (w, h) = unsafe { wh::get_window_size(self.layout.base) };
+        let base_handle = ControlHandle::Hwnd(self.layout.base);
+
+        // Items created with `with_rect` are relative to the reference size
+        let reference = self.layout.reference_size.unwrap_or((w, h));
+        for item in self.layout.children.iter_mut() {
+            item.reference.get_or_insert(reference);
+        }
+
+        // Saves the new layout. Free the old layout (if there is one)
+        {
+            let mut layout_inner = layout.inner.borrow_mut();
+            if let Some(handler) = layout_inner.handler.as_ref() {
+                drop(unbind_raw_event_handler(handler));
+            }
+
+            *layout_inner = self.layout;
+        }
+
+        // Initial layout update
+        layout.update_layout(w, h);
+
+        // Keep generating ids so that multiple layouts can be applied to the same parent
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static ANCHOR_LAYOUT_ID: AtomicUsize = AtomicUsize::new(0x3FFF);
+        let handler_id = ANCHOR_LAYOUT_ID.fetch_add(1, Ordering::SeqCst);
+
+        // Bind the event handler
+        let event_layout = layout.clone();
+        let cb = move |w, h| AnchorLayout::update_layout(&event_layout, w, h);
+
+        {
+            let mut layout_inner = layout.inner.borrow_mut();
+            layout_inner.handler = Some(bind_resize_handler(&base_handle, handler_id, cb)?);
+        }
+
+        Ok(())
+    }
+
+}
@@ -0,0 +1,452 @@
+use crate::controls::ControlHandle;
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::{Length, Margins};
+use crate::win32::window::{RawEventHandler, unbind_raw_event_handler};
+use crate::win32::resize::bind_resize_handler;
+use crate::NwgError;
+use winapi::shared::windef::HWND;
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::ptr;
+
+
+/// The edge of the remaining space a child of a DockLayout is attached to
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DockStyle {
+    /// The child takes the full width of the remaining space at the top
+    Top,
+
+    /// The child takes the full width of the remaining space at the bottom
+    Bottom,
+
+    /// The child takes the full height of the remaining space on the left
+    Left,
+
+    /// The child takes the full height of the remaining space on the right
+    Right,
+
+    /// The child takes all the remaining space
+    Fill,
+}
+
+/// The size of a docked child, across the edge it is docked to. Ignored by `DockStyle::Fill`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DockSize {
+    /// A size in logical units
+    Fixed(u32),
+
+    /// A fraction (0.0 to 1.0) of the size of the layout, without the margins
+    Percent(f32),
+}
+
+
+/// A control item in a DockLayout
+#[derive(Debug)]
+pub struct DockLayoutItem {
+    /// The handle to the control in the item
+    control: HWND,
+
+    /// The edge the control is docked to
+    pub dock: DockStyle,
+
+    /// The size of the control across the docked edge
+    pub size: DockSize,
+}
+
+impl DockLayoutItem {
+
+    /// Initialize a new dock layout item
+    pub fn new<W: Into<ControlHandle>>(c: W, dock: DockStyle, size: DockSize) -> DockLayoutItem {
+        let control = c.into().hwnd().expect("Child must be a window-like control (HWND handle)");
+        DockLayoutItem { control, dock, size }
+    }
+
+}
+
+
+/// A layout that docks widgets to the edges of their parent
+/// This is the inner data shared between the callback and the application
+pub struct DockLayoutInner {
+    /// The control that holds the layout
+    base: HWND,
+
+    /// The resize handler of the layout
+    handler: Option<RawEventHandler>,
+
+    /// The children of the control that fit in the layout, in docking order
+    children: Vec<DockLayoutItem>,
+
+    /// The top, right, bottom, left space around the layout
+    margins: Margins,
+
+    /// The spacing between controls
+    spacing: Length
+}
+
+/**
+A layout that docks its children to the edges of the parent control, like the WinForms docking.
+
+The children are docked in the order they were added. Each child takes its size from the remaining space
+on the edge it is docked to, so the first children are the outermost ones. A `Fill` child takes all the space
+left by the previous children and should be the last one. Hidden children are skipped; call `fit` after
+showing or hiding a child to update the layout.
+
+A DockLayout has the following properties:
+* margin - The top, right, bottom, left margins of the layout - (default: [0, 0, 0, 0])
+* spacing - The spacing between children controls - (default: 0)
+
+Requires the `dock-layout` feature.
+
+```rust
+    use native_windows_gui as nwg;
+    use nwg::{DockStyle, DockSize};
+
+    fn layout(layout: &nwg::DockLayout, window: &nwg::Window, toolbar: &nwg::Frame, status: &nwg::StatusBar, tree: &nwg::TreeView, editor: &nwg::TextBox) {
+        nwg::DockLayout::builder()
+            .parent(window)
+            .child(DockStyle::Top, DockSize::Fixed(32), toolbar)
+            .child(DockStyle::Bottom, DockSize::Fixed(22), status)
+            .child(DockStyle::Left, DockSize::Percent(0.25), tree)
+            .child(DockStyle::Fill, DockSize::Fixed(0), editor)
+            .build(&layout);
+    }
+```
+*/
+#[derive(Clone)]
+pub struct DockLayout {
+    inner: Rc<RefCell<DockLayoutInner>>
+}
+
+impl DockLayout {
+
+    pub fn builder() -> DockLayoutBuilder {
+        let layout = DockLayoutInner {
+            base: ptr::null_mut(),
+            handler: None,
+            children: Vec::new(),
+            margins: Margins::Units([0, 0, 0, 0]),
+            spacing: Length::Units(0),
+        };
+
+        DockLayoutBuilder { layout }
+    }
+
+    /**
+        Dock a children control at the end of the layout.
+        This is a simplified interface over `add_child_item`
+
+        Panic:
+        - If the layout is not initialized
+        - If the control is not window-like (HWND handle)
+    */
+    pub fn add_child<W: Into<ControlHandle>>(&self, dock: DockStyle, size: DockSize, c: W) {
+        self.add_child_item(DockLayoutItem::new(c, dock, size));
+    }
+
+    /**
+        Dock a children control at the end of the layout.
+
+        Panic:
+        - If the layout is not initialized
+    */
+    pub fn add_child_item(&self, i: DockLayoutItem) {
+        {
+            let mut inner = self.inner.borrow_mut();
+            if inner.base.is_null() {
+                panic!("DockLayout is not initialized");
+            }
+
+            inner.children.push(i);
+        }
+
+        self.fit();
+    }
+
+    /**
+        Remove the children control from the layout.
+        Note that the child control won't be hidden after being removed from the control.
+
+        This method won't do anything if the control is not in the layout.
+
+        Panic:
+        - If the layout is not initialized
+    */
+    pub fn remove_child<W: Into<ControlHandle>>(&self, c: W) {
+        {
+            let mut inner = self.inner.borrow_mut();
+            if inner.base.is_null() {
+                panic!("DockLayout is not initialized");
+            }
+
+            let handle = c.into().hwnd().expect("Control must be window-like (HWND handle)");
+            match inner.children.iter().position(|item| item.control == handle) {
+                Some(i) => { inner.children.remove(i); },
+                None => { return; }
+            }
+        }
+
+        self.fit();
+    }
+
+    /**
+        Change the docking of a children control
+
+        This method won't do anything if the control is not in the layout.
+
+        Panic:
+        - If the layout is not initialized
+    */
+    pub fn set_child_dock<W: Into<ControlHandle>>(&self, c: W, dock: DockStyle, size: DockSize) {
+        {
+            let mut inner = self.inner.borrow_mut();
+            if inner.base.is_null() {
+                panic!("DockLayout is not initialized");
+            }
+
+            let handle = c.into().hwnd().expect("Control must be window-like (HWND handle)");
+            match inner.children.iter_mut().find(|item| item.control == handle) {
+                Some(item) => {
+                    item.dock = dock;
+                    item.size = size;
+                },
+                None => { return; }
+            }
+        }
+
+        self.fit();
+    }
+
+    /**
+        Check if a window control is a children of the layout
+
+        Panic:
+        - If the layout is not initialized
+        - If the child is not a window-like control
+    */
+    pub fn has_child<W: Into<ControlHandle>>(&self, c: W) -> bool {
+        let inner = self.inner.borrow();
+        if inner.base.is_null() {
+            panic!("DockLayout is not initialized");
+        }
+
+        let handle = c.into().hwnd().expect("Children is not a window-like control (HWND handle)");
+        inner.children.iter().any(|c| c.control == handle )
+    }
+
+    /// Resize the layout as if the parent window had the specified size.
+    ///
+    /// Arguments:
+    ///   w: New width of the layout
+    ///   h: New height of the layout
+    ///
+    ///  Panic:
+    ///   - The layout must have been successfully built otherwise this function will panic.
+    pub fn resize(&self, w: u32, h: u32) {
+        if self.inner.borrow().base.is_null() {
+            panic!("Dock layout is not bound to a parent control.")
+        }
+
+        self.update_layout(w, h);
+    }
+
+    /// Resize the layout to fit the parent window size
+    ///
+    /// Panic:
+    ///   - The layout must have been successfully built otherwise this function will panic.
+    pub fn fit(&self) {
+        let base = self.inner.borrow().base;
+        if base.is_null() {
+            panic!("Dock layout is not bound to a parent control.")
+        }
+
+        let (w, h) = unsafe { wh::get_window_size(base) };
+        self.update_layout(w, h);
+    }
+
+    /// Set the margins of the layout. The four values are in this order: top, right, bottom, left.
+    pub fn margin<M: Into<Margins>>(&self, m: M) {
+        let mut inner = self.inner.borrow_mut();
+        inner.margins = m.into();
+    }
+
+    /// Set the size of the space between the children in the layout. Default value is 0.
+    pub fn spacing<L: Into<Length>>(&self, sp: L) {
+        let mut inner = self.inner.borrow_mut();
+        inner.spacing = sp.into();
+    }
+
+    fn update_layout(&self, width: u32, height: u32) {
+        use winapi::um::winuser::{GWL_STYLE, WS_VISIBLE};
+
+        let inner = self.inner.borrow();
+        if inner.base.is_null() || inner.children.len() == 0 {
+            return;
+        }
+
+        let [m_top, m_right, m_bottom, m_left] = unsafe { inner.margins.to_units(inner.base) };
+        let sp = unsafe { inner.spacing.to_units(inner.base) } as i32;
+
+        // The remaining space, as left, top, right, bottom
+        let mut left = m_left as i32;
+        let mut top = m_top as i32;
+        let mut right = (width as i32 - m_right as i32).max(left);
+        let mut bottom = (height as i32 - m_bottom as i32).max(top);
+
+        let (total_width, total_height) = (right - left, bottom - top);
+        let resolve = |size: DockSize, total: i32, available: i32| {
+            let size = match size {
+                DockSize::Fixed(s) => s as i32,
+                DockSize::Percent(p) => (total as f32 * p.max(0.0)).round() as i32,
+            };
+
+            size.min(available).max(0)
+        };
+
+        let mut batch = wh::WindowPositionBatch::new(inner.children.len());
+        let mut last_handle = None;
+        for item in inner.children.iter() {
+            // Checks the style of the child, because IsWindowVisible is false for every child while the parent is hidden
+            let style = wh::get_window_long(item.control, GWL_STYLE) as u32;
+            if style & WS_VISIBLE == 0 {
+                continue;
+            }
+
+            let (x, y, w, h) = match item.dock {
+                DockStyle::Top => {
+                    let h = resolve(item.size, total_height, bottom - top);
+                    let rect = (left, top, right - left, h);
+                    top = (top + h + sp).min(bottom);
+                    rect
+                },
+                DockStyle::Bottom => {
+                    let h = resolve(item.size, total_height, bottom - top);
+                    let rect = (left, bottom - h, right - left, h);
+                    bottom = (bottom - h - sp).max(top);
+                    rect
+                },
+                DockStyle::Left => {
+                    let w = resolve(item.size, total_width, right - left);
+                    let rect = (left, top, w, bottom - top);
+                    left = (left + w + sp).min(right);
+                    rect
+                },
+                DockStyle::Right => {
+                    let w = resolve(item.size, total_width, right - left);
+                    let rect = (right - w, top, w, bottom - top);
+                    right = (right - w - sp).max(left);
+                    rect
+                },
+                DockStyle::Fill => {
+                    let rect = (left, top, right - left, bottom - top);
+                    left = right;
+                    top = bottom;
+                    rect
+                }
+            };
+
+            batch.move_window_after(item.control, last_handle, x, y, w as u32, h as u32);
+            last_handle = Some(item.control);
+        }
+
+        batch.apply();
+    }
+}
+
+impl Default for DockLayout {
+
+    fn default() -> DockLayout {
+        let inner = DockLayoutInner {
+            base: ptr::null_mut(),
+            handler: None,
+            children: Vec::new(),
+            margins: Margins::Units([0, 0, 0, 0]),
+            spacing: Length::Units(0),
+        };
+
+        DockLayout {
+            inner: Rc::new(RefCell::new(inner))
+        }
+    }
+
+}
+
+
+/// Builder for a `DockLayout` struct
+pub struct DockLayoutBuilder {
+    layout: DockLayoutInner
+}
+
+impl DockLayoutBuilder {
+
+    /// Set the layout parent. The handle must be a window object otherwise the function will panic
+    pub fn parent<W: Into<ControlHandle>>(mut self, p: W) -> DockLayoutBuilder {
+        self.layout.base = p.into().hwnd().expect("Parent must be HWND");
+        self
+    }
+
+    /// Dock a children to the layout. Children are docked in the order they are added.
+    /// This is a shortcut over `child_item`.
+    /// The handle must be a window object otherwise the function will panic
+    pub fn child<W: Into<ControlHandle>>(mut self, dock: DockStyle, size: DockSize, c: W) -> DockLayoutBuilder {
+        self.layout.children.push(DockLayoutItem::new(c, dock, size));
+        self
+    }
+
+    /// Dock a children to the layout. Children are docked in the order they are added.
+    pub fn child_item(mut self, item: DockLayoutItem) -> DockLayoutBuilder {
+        self.layout.children.push(item);
+        self
+    }
+
+    /// Set the margins of the layout. The four values are in this order: top, right, bottom, left.
+    pub fn margin<M: Into<Margins>>(mut self, m: M) -> DockLayoutBuilder {
+        self.layout.margins = m.into();
+        self
+    }
+
+    /// Set the size of the space between the children in the layout. Default value is 0.
+    pub fn spacing<L: Into<Length>>(mut self, sp: L) -> DockLayoutBuilder {
+        self.layout.spacing = sp.into();
+        self
+    }
+
+    /// Build the layout object and bind the callback.
+    pub fn build(self, layout: &DockLayout) -> Result<(), NwgError> {
+        if self.layout.base.is_null() {
+            return Err(NwgError::layout_create("DockLayout does not have a parent."));
+        }
+
+        let (w, h) = unsafe { wh::get_window_size(self.layout.base) };
+        let base_handle = ControlHandle::Hwnd(self.layout.base);
+
+        // Saves the new layout. Free the old layout (if there is one)
+        {
+            let mut layout_inner = layout.inner.borrow_mut();
+            if let Some(handler) = layout_inner.handler.as_ref() {
+                drop(unbind_raw_event_handler(handler));
+            }
+
+            *layout_inner = self.layout;
+        }
+
+        // Initial layout update
+        layout.update_layout(w, h);
+
+        // Keep generating ids so that multiple layouts can be applied to the same parent
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static DOCK_LAYOUT_ID: AtomicUsize = AtomicUsize::new(0x2FFF);
+        let handler_id = DOCK_LAYOUT_ID.fetch_add(1, Ordering::SeqCst);
+
+        // Bind the event handler
+        let event_layout = layout.clone();
+        let cb = move |w, h| DockLayout::update_layout(&event_layout, w, h);
+
+        {
+            let mut layout_inner = layout.inner.borrow_mut();
+            layout_inner.handler = Some(bind_resize_handler(&base_handle, handler_id, cb)?);
+        }
+
+        Ok(())
+    }
+
+}
@@ -1,5 +1,5 @@
 use crate::controls::ControlHandle;
-use crate::win32::window::bind_raw_event_handler_inner;
+use crate::win32::resize::bind_resize_handler;
 use crate::win32::window_helper as wh;
 use crate::NwgError;
 use winapi::shared::windef::{HWND};
@@ -208,10 +208,6 @@ impl DynLayout {
     }
 
     fn update_layout(&self, width: u32, height: u32) -> () {
-        use winapi::um::winuser::{BeginDeferWindowPos, DeferWindowPos, EndDeferWindowPos};
-        use winapi::um::winuser::{HWND_TOP, SWP_NOZORDER, SWP_NOREPOSITION, SWP_NOACTIVATE, SWP_NOCOPYBITS};
-        use winapi::ctypes::c_int;
-
         let inner = self.inner.borrow();
         if inner.base.is_null() || inner.children.len() == 0 {
             return;
@@ -220,31 +216,26 @@ impl DynLayout {
         let xdelta = 0.01 * width as f32;
         let ydelta = 0.01 * height as f32;
 
-        unsafe {
-            let hdwp = BeginDeferWindowPos(inner.children.len() as c_int);
-
-            let mut last_handle = None;
-            for item in inner.children.iter() {
-                let mut x = item.pos_init.0;
-                if item.mv.0 > 0 { x += (xdelta * item.mv.0 as f32) as i32; }
-
-                let mut y = item.pos_init.1;
-                if item.mv.1 > 0 { y += (ydelta * item.mv.1 as f32) as i32; }
+        let mut batch = wh::WindowPositionBatch::new(inner.children.len());
+        let mut last_handle = None;
+        for item in inner.children.iter() {
+            let mut x = item.pos_init.0;
+            if item.mv.0 > 0 { x += (xdelta * item.mv.0 as f32) as i32; }
 
-                let mut w = item.size_init.0;
-                if item.sz.0 > 0 { w += (xdelta * item.sz.0 as f32) as i32; }
+            let mut y = item.pos_init.1;
+            if item.mv.1 > 0 { y += (ydelta * item.mv.1 as f32) as i32; }
 
-                let mut h = item.size_init.1;
-                if item.sz.1 > 0 { h += (ydelta * item.sz.1 as f32) as i32; }
+            let mut w = item.size_init.0;
+            if item.sz.0 > 0 { w += (xdelta * item.sz.0 as f32) as i32; }
 
-                DeferWindowPos(hdwp, item.control, HWND_TOP, x, y, w, h, SWP_NOZORDER | SWP_NOREPOSITION | SWP_NOACTIVATE | SWP_NOCOPYBITS);
-
-                wh::set_window_after(item.control, last_handle);
-                last_handle = Some(item.control);
-            }
+            let mut h = item.size_init.1;
+            if item.sz.1 > 0 { h += (ydelta * item.sz.1 as f32) as i32; }
 
-            EndDeferWindowPos(hdwp);
+            batch.move_window_after(item.control, last_handle, x, y, w.max(0) as u32, h.max(0) as u32);
+            last_handle = Some(item.control);
         }
+
+        batch.apply();
     }
 }
 
@@ -306,9 +297,6 @@ impl DynLayoutBuilder {
     /// Build the layout object and bind the callback.
     /// Children must only contains window object otherwise this method will panic.
     pub fn build(self, layout: &DynLayout) -> Result<(), NwgError> {
-        use winapi::um::winuser::WM_SIZE;
-        use winapi::shared::minwindef::{HIWORD, LOWORD};
-
         if self.layout.base.is_null() {
             return Err(NwgError::layout_create("DynLayout does not have a parent."));
         }
@@ -327,21 +315,12 @@ impl DynLayoutBuilder {
 
         // Bind the event handler
         let event_layout = layout.clone();
-        let cb = move |_h, msg, _w, l| {
-            if msg == WM_SIZE {
-                let size = l as u32;
-                let width = LOWORD(size) as i32;
-                let height = HIWORD(size) as i32;
-                let (w, h) = unsafe { crate::win32::high_dpi::physical_to_logical(width, height) };
-                DynLayout::update_layout(&event_layout, w as u32, h as u32);
-            }
-            None
-        };
+        let cb = move |w, h| DynLayout::update_layout(&event_layout, w, h);
 
         /// Keep generating ids so that multiple layouts can be applied to the same parent
         use std::sync::atomic::{AtomicUsize, Ordering};
         static BOX_LAYOUT_ID: AtomicUsize = AtomicUsize::new(0x8FFF);
-        bind_raw_event_handler_inner(&base_handle, BOX_LAYOUT_ID.fetch_add(1, Ordering::SeqCst), cb).unwrap();
+        bind_resize_handler(&base_handle, BOX_LAYOUT_ID.fetch_add(1, Ordering::SeqCst), cb).unwrap();
 
         Ok(())
     }
@@ -1,9 +1,11 @@
 use crate::controls::ControlHandle;
 use crate::win32::window_helper as wh;
-use crate::win32::window::{RawEventHandler, unbind_raw_event_handler, bind_raw_event_handler_inner};
+use crate::win32::window::{RawEventHandler, unbind_raw_event_handler};
+use crate::win32::resize::bind_resize_handler;
+use crate::win32::high_dpi::{Length, Margins};
 use crate::NwgError;
 use winapi::shared::windef::HWND;
-use std::{ptr, rc::Rc, cell::{RefCell, RefMut, Ref} };
+use std::{ptr, rc::Rc, cell::{Cell, RefCell, RefMut, Ref} };
 
 use stretch::{
     number::Number,
@@ -18,6 +20,15 @@ pub struct FlexboxLayoutItem {
     /// The handle to the control in the item
     control: HWND,
     style: Style,
+
+    /// The position and size computed for the control during the last update of the layout
+    rect: Cell<Option<(i32, i32, u32, u32)>>,
+}
+
+impl FlexboxLayoutItem {
+    fn new(control: HWND, style: Style) -> FlexboxLayoutItem {
+        FlexboxLayoutItem { control, style, rect: Cell::new(None) }
+    }
 }
 
 pub enum FlexboxLayoutChild {
@@ -39,15 +50,45 @@ struct FlexboxLayoutInner {
     base: HWND,
     handler: Option<RawEventHandler>,
     style: Style,
+
+    /// The vertical and horizontal space between the children (row gap, column gap)
+    gap: (Length, Length),
+
+    /// The padding set by `FlexboxLayoutBuilder::padding_dip`. Replaces the padding of `style` when the layout is updated.
+    padding: Option<Margins>,
     children: Vec<FlexboxLayoutChild>,
     parent_layout: Option<FlexboxLayout>,
 }
 
+impl FlexboxLayoutInner {
+
+    /// Returns the gap in units, resolved with the DPI of the parent
+    fn gap_units(&self) -> (u32, u32) {
+        unsafe { (self.gap.0.to_units(self.base), self.gap.1.to_units(self.base)) }
+    }
+
+    /// Returns the style of the layout with the padding resolved with the DPI of the parent
+    fn resolved_style(&self) -> Style {
+        let mut style = self.style.clone();
+        if let Some(padding) = self.padding {
+            let [top, right, bottom, left] = unsafe { padding.to_units(self.base) };
+            let points = |v: u32| Dimension::Points(v as f32);
+            style.padding = Rect { start: points(left), end: points(right), top: points(top), bottom: points(bottom) };
+        }
+
+        style
+    }
+
+}
+
 
 /**
     A flexbox layout that organizes the children control in a parent control.
     Flexbox uses the stretch library internally ( https://github.com/vislyhq/stretch ).
 
+    Children can be added, removed or reordered after the layout is built (see `insert_child`, `move_child`, `remove_child`),
+    and the layout is updated automatically. The space between the children can be set with `row_gap` and `column_gap`.
+
     FlexboxLayout requires the `flexbox` feature.
 */
 #[derive(Clone)]
@@ -62,6 +103,8 @@ impl FlexboxLayout {
             base: ptr::null_mut(),
             handler: None,
             style: Default::default(),
+            gap: (Length::Units(0), Length::Units(0)),
+            padding: None,
             children: Vec::new(),
             parent_layout: None,
         };
@@ -81,11 +124,11 @@ impl FlexboxLayout {
             panic!("Flexbox layout is not yet initialized!");
         }
 
-        inner.style.clone()
+        inner.resolved_style()
     }
 
     /**
-        Sets the style of the layout parent control
+        Sets the style of the layout parent control. The padding of `style` replaces the padding set by the builder.
 
         Panic:
         - The layout must have been successfully built otherwise this function will panic.
@@ -97,6 +140,7 @@ impl FlexboxLayout {
         }
 
         inner.style = style;
+        inner.padding = None;
     }
 
     /**
@@ -113,11 +157,7 @@ impl FlexboxLayout {
                 panic!("Flexbox layout is not yet initialized!");
             }
     
-            let item = FlexboxLayoutItem {
-                control: c.into().hwnd().expect("Control must be window like (HWND handle)"),
-                style
-            };
-    
+            let item = FlexboxLayoutItem::new(c.into().hwnd().expect("Control must be window like (HWND handle)"), style);
             inner.children.push(FlexboxLayoutChild::Item(item));
         }
 
@@ -125,27 +165,181 @@ impl FlexboxLayout {
     }
 
     /**
-        Remove a children from the layout
-        
+        Insert a new children at `index` in the layout with the stretch style. If `index` is greater than
+        the number of children, the control is added at the end.
+
+        Panic:
+        * If the control is not a window-like control
+        * If the layout was not initialized
+    */
+    pub fn insert_child<W: Into<ControlHandle>>(&self, index: usize, c: W, style: Style) -> Result<(), stretch::Error> {
+        {
+            let mut inner = self.inner.borrow_mut();
+            if inner.base.is_null() {
+                panic!("Flexbox layout is not yet initialized!");
+            }
+
+            let item = FlexboxLayoutItem::new(c.into().hwnd().expect("Control must be window like (HWND handle)"), style);
+            let index = index.min(inner.children.len());
+            inner.children.insert(index, FlexboxLayoutChild::Item(item));
+        }
+
+        self.fit()
+    }
+
+    /**
+        Move a children of the layout to `index`. If `index` is greater than the number of children,
+        the control is moved at the end.
+
         Panic:
         * If the control is not a window-like control
         * If the control is not in the layout (see `has_child`)
         * If the layout was not initialized
     */
-    pub fn remove_child<W: Into<ControlHandle>>(&self, c: W) {
-        let mut inner = self.inner.borrow_mut();
+    pub fn move_child<W: Into<ControlHandle>>(&self, c: W, index: usize) -> Result<(), stretch::Error> {
+        {
+            let mut inner = self.inner.borrow_mut();
+            if inner.base.is_null() {
+                panic!("Flexbox layout is not yet initialized!");
+            }
+
+            let handle = c.into().hwnd().expect("Control must be window like (HWND handle)");
+            let old_index = inner.children.iter()
+                .position(|child| child.is_item() && child.as_item().control == handle)
+                .expect("Control was not found in layout");
+
+            let child = inner.children.remove(old_index);
+            let index = index.min(inner.children.len());
+            inner.children.insert(index, child);
+        }
+
+        self.fit()
+    }
+
+    /**
+        Sets the style of a children of the layout and update the layout.
+
+        Panic:
+        * If the control is not a window-like control
+        * If the control is not in the layout (see `has_child`)
+        * If the layout was not initialized
+    */
+    pub fn set_child_style<W: Into<ControlHandle>>(&self, c: W, style: Style) -> Result<(), stretch::Error> {
+        {
+            let mut inner = self.inner.borrow_mut();
+            if inner.base.is_null() {
+                panic!("Flexbox layout is not yet initialized!");
+            }
+
+            let handle = c.into().hwnd().expect("Control must be window like (HWND handle)");
+            let item = inner.children.iter_mut()
+                .filter(|child| child.is_item())
+                .map(|child| child.as_item_mut())
+                .find(|item| item.control == handle)
+                .expect("Control was not found in layout");
+
+            item.style = style;
+        }
+
+        self.fit()
+    }
+
+    /**
+        Returns the index of a children in the layout, or `None` if the control is not a direct children of the layout.
+
+        Panic:
+        * If the control is not a window-like control
+        * If the layout was not initialized
+    */
+    pub fn child_index<W: Into<ControlHandle>>(&self, c: W) -> Option<usize> {
+        let inner = self.inner.borrow();
         if inner.base.is_null() {
             panic!("Flexbox layout is not yet initialized!");
         }
 
         let handle = c.into().hwnd().expect("Control must be window like (HWND handle)");
-        let index = inner.children.iter()
-            .position(|child| child.is_item() && child.as_item().control == handle);
+        inner.children.iter().position(|child| child.is_item() && child.as_item().control == handle)
+    }
 
-        match index {
-            Some(i) => { inner.children.remove(i); },
-            None => { panic!("Control was not found in layout"); }
+    /**
+        Returns the position and the size (x, y, width, height) computed for a children during the last update of the layout.
+        Returns `None` if the control is not a direct children of the layout or if the layout was not updated since the control was added.
+
+        Panic:
+        * If the control is not a window-like control
+        * If the layout was not initialized
+    */
+    pub fn child_rect<W: Into<ControlHandle>>(&self, c: W) -> Option<(i32, i32, u32, u32)> {
+        let inner = self.inner.borrow();
+        if inner.base.is_null() {
+            panic!("Flexbox layout is not yet initialized!");
         }
+
+        let handle = c.into().hwnd().expect("Control must be window like (HWND handle)");
+        inner.children.iter()
+            .filter(|child| child.is_item())
+            .map(|child| child.as_item())
+            .find(|item| item.control == handle)
+            .and_then(|item| item.rect.get())
+    }
+
+    /**
+        Returns the vertical and horizontal space between the children of the layout in units (row gap, column gap)
+
+        Panic:
+        - The layout must have been successfully built otherwise this function will panic.
+    */
+    pub fn gap(&self) -> (u32, u32) {
+        let inner = self.inner.borrow();
+        if inner.base.is_null() {
+            panic!("Flexbox layout is not yet initialized!");
+        }
+
+        inner.gap_units()
+    }
+
+    /**
+        Sets the vertical and horizontal space between the children of the layout. Call `fit` to update the layout.
+
+        Panic:
+        - The layout must have been successfully built otherwise this function will panic.
+    */
+    pub fn set_gap<R: Into<Length>, C: Into<Length>>(&self, row_gap: R, column_gap: C) {
+        let mut inner = self.inner.borrow_mut();
+        if inner.base.is_null() {
+            panic!("Flexbox layout is not yet initialized!");
+        }
+
+        inner.gap = (row_gap.into(), column_gap.into());
+    }
+
+    /**
+        Remove a children from the layout and update the layout.
+        Returns an error if the layout cannot be computed, in which case the control is still removed.
+        
+        Panic:
+        * If the control is not a window-like control
+        * If the control is not in the layout (see `has_child`)
+        * If the layout was not initialized
+    */
+    pub fn remove_child<W: Into<ControlHandle>>(&self, c: W) -> Result<(), stretch::Error> {
+        {
+            let mut inner = self.inner.borrow_mut();
+            if inner.base.is_null() {
+                panic!("Flexbox layout is not yet initialized!");
+            }
+
+            let handle = c.into().hwnd().expect("Control must be window like (HWND handle)");
+            let index = inner.children.iter()
+                .position(|child| child.is_item() && child.as_item().control == handle);
+
+            match index {
+                Some(i) => { inner.children.remove(i); },
+                None => { panic!("Control was not found in layout"); }
+            }
+        }
+
+        self.fit()
     }
 
     /**
@@ -227,19 +421,28 @@ impl FlexboxLayout {
 
     // Utility function to compile tree of children nodes for layout purposes
     // Also returns the total number of children items to allow cleaner deferred positioning
-    fn build_child_nodes(children: &Vec<FlexboxLayoutChild>, stretch: &mut Stretch) -> Result<(usize, Vec<Node>), stretch::Error> {
+    fn build_child_nodes(children: &Vec<FlexboxLayoutChild>, parent_style: &Style, gap: (u32, u32), stretch: &mut Stretch) -> Result<(usize, Vec<Node>), stretch::Error> {
         let mut nodes = Vec::new();
         let mut item_count = 0;
+        let count = children.len();
+
+        for (index, child) in children.iter().enumerate() {
+            let position = (index == 0, index + 1 == count);
 
-        for child in children.iter() {
             match child {
                 FlexboxLayoutChild::Item(child) =>{
-                    nodes.push(stretch.new_node(child.style, Vec::new())?);
+                    nodes.push(stretch.new_node(gap_margin(child.style, parent_style, gap, position), Vec::new())?);
                     item_count += 1;
                 },
                 FlexboxLayoutChild::Flexbox(child) => {
-                    let (child_count, child_nodes) = FlexboxLayout::build_child_nodes(child.children().children(), stretch)?;
-                    nodes.push(stretch.new_node(child.style(), child_nodes)?);
+                    let (child_gap, child_style) = {
+                        let child_inner = child.inner.borrow();
+                        (child_inner.gap_units(), child_inner.resolved_style())
+                    };
+
+                    let (child_count, child_nodes) = FlexboxLayout::build_child_nodes(child.children().children(), &child_style, child_gap, stretch)?;
+                    let style = gap_margin(gap_padding(child_style, child_gap), parent_style, gap, position);
+                    nodes.push(stretch.new_node(style, child_nodes)?);
                     item_count += child_count;
                 },
             };
@@ -249,8 +452,8 @@ impl FlexboxLayout {
     }
 
     // Applies the calculated item positions for this layout
-    // Uses deferred window positioning to prevent rendering artefacts
-    fn apply_layout_deferred(positioner: &mut wh::DeferredWindowPositioner, stretch: &mut Stretch, nodes: Vec<Node>, children: &Vec<FlexboxLayoutChild>, last_handle: &mut Option<HWND>, offset: (i32, i32)) -> Result<(), stretch::Error> {
+    // The moves are collected in a batch to prevent rendering artefacts
+    fn apply_layout(batch: &mut wh::WindowPositionBatch, stretch: &mut Stretch, nodes: Vec<Node>, children: &Vec<FlexboxLayoutChild>, last_handle: &mut Option<HWND>, offset: (i32, i32)) -> Result<(), stretch::Error> {
         use FlexboxLayoutChild as Child;
 
         for (node, child) in nodes.into_iter().zip(children.iter()) {
@@ -260,40 +463,14 @@ impl FlexboxLayout {
 
             match child {
                 Child::Item(child) => {
-                    positioner.defer_pos(child.control, last_handle.unwrap_or(std::ptr::null_mut()), x as i32 + offset.0, y as i32 + offset.1, width as i32, height as i32).ok();
-                    last_handle.replace(child.control);                    
+                    let (x, y) = (x as i32 + offset.0, y as i32 + offset.1);
+                    child.rect.set(Some((x, y, width as u32, height as u32)));
+                    batch.move_window_after(child.control, *last_handle, x, y, width as u32, height as u32);
+                    last_handle.replace(child.control);
                 },
                 Child::Flexbox(child) => {
                     let children_nodes = stretch.children(node)?;
-                    FlexboxLayout::apply_layout_deferred(positioner, stretch, children_nodes, child.children().children(), last_handle, (x as i32, y as i32))?;
-                }
-            }
-            
-        }
-
-        Ok(())
-    }
-
-    // Applies the calculated item positions for this layout
-    // Uses immediate window positioning, which might cause visual artefacts in some cases
-    fn apply_layout_immediate(stretch: &mut Stretch, nodes: Vec<Node>, children: &Vec<FlexboxLayoutChild>, last_handle: &mut Option<HWND>, offset: (i32, i32)) -> Result<(), stretch::Error> {
-        use FlexboxLayoutChild as Child;
-
-        for (node, child) in nodes.into_iter().zip(children.iter()) {
-            let layout = stretch.layout(node)?;
-            let Point { x, y } = layout.location;
-            let Size { width, height } = layout.size;
-
-            match child {
-                Child::Item(child) => unsafe {
-                    wh::set_window_position(child.control, x as i32 + offset.0, y as i32 + offset.1);
-                    wh::set_window_size(child.control, width as u32, height as u32, false);
-                    wh::set_window_after(child.control, *last_handle);
-                    last_handle.replace(child.control);                    
-                },
-                Child::Flexbox(child) => {
-                    let children_nodes = stretch.children(node)?;
-                    FlexboxLayout::apply_layout_immediate(stretch, children_nodes, child.children().children(), last_handle, (x as i32, y as i32))?;
+                    FlexboxLayout::apply_layout(batch, stretch, children_nodes, child.children().children(), last_handle, (x as i32, y as i32))?;
                 }
             }
             
@@ -309,24 +486,21 @@ impl FlexboxLayout {
         }
 
         let mut stretch = Stretch::new();
-        let (item_count, nodes) = FlexboxLayout::build_child_nodes(&inner.children, &mut stretch)?;
+        let gap = inner.gap_units();
+        let style = inner.resolved_style();
+        let (item_count, nodes) = FlexboxLayout::build_child_nodes(&inner.children, &style, gap, &mut stretch)?;
 
-        let mut style = inner.style.clone();
+        let mut style = gap_padding(style, gap);
         style.size = Size { width: Dimension::Points(width as f32), height: Dimension::Points(height as f32) };
         let node = stretch.new_node(style, nodes.clone())?;
 
         stretch.compute_layout(node, Size::undefined())?;
 
-        // Keep a fallback case to prevent panics if the layout is too large to be deferred
-        if let Ok(mut positioner) = wh::DeferredWindowPositioner::new(item_count as i32) {
-            let layout_result = FlexboxLayout::apply_layout_deferred(&mut positioner, &mut stretch, nodes, self.children().children(), &mut None, offset);
-            positioner.end();
-    
-            layout_result
-        }
-        else {
-            FlexboxLayout::apply_layout_immediate(&mut stretch, nodes, self.children().children(), &mut None, offset)
-        }
+        let mut batch = wh::WindowPositionBatch::new(item_count);
+        let layout_result = FlexboxLayout::apply_layout(&mut batch, &mut stretch, nodes, self.children().children(), &mut None, offset);
+        batch.apply();
+
+        layout_result
     }
 }
 
@@ -350,10 +524,7 @@ impl FlexboxLayoutBuilder {
     pub fn child<W: Into<ControlHandle>>(mut self, child: W) -> FlexboxLayoutBuilder {
         self.current_index = Some(self.layout.children.len());
         
-        let item = FlexboxLayoutItem {
-            control: child.into().hwnd().unwrap(),
-            style: Style::default()
-        };
+        let item = FlexboxLayoutItem::new(child.into().hwnd().unwrap(), Style::default());
 
         self.layout.children.push(FlexboxLayoutChild::Item(item));
 
@@ -424,6 +595,15 @@ impl FlexboxLayoutBuilder {
 
     pub fn padding(mut self, value: Rect<Dimension>) -> FlexboxLayoutBuilder {
         self.layout.style.padding = value;
+        self.layout.padding = None;
+        self.auto_spacing = None;
+        self
+    }
+
+    /// Set the padding of the layout in units or DIPs. The four values are in this order: top, right, bottom, left.
+    /// `Dip` values are converted with the DPI of the parent each time the layout is updated. Replaces `padding`.
+    pub fn padding_dip<M: Into<Margins>>(mut self, value: M) -> FlexboxLayoutBuilder {
+        self.layout.padding = Some(value.into());
         self.auto_spacing = None;
         self
     }
@@ -448,6 +628,26 @@ impl FlexboxLayoutBuilder {
         self
     }
 
+    /// Set the vertical space between the children of the layout (or between the lines if the layout wraps)
+    pub fn row_gap<L: Into<Length>>(mut self, value: L) -> FlexboxLayoutBuilder {
+        self.layout.gap.0 = value.into();
+        self
+    }
+
+    /// Set the horizontal space between the children of the layout (or between the columns if the layout wraps)
+    pub fn column_gap<L: Into<Length>>(mut self, value: L) -> FlexboxLayoutBuilder {
+        self.layout.gap.1 = value.into();
+        self
+    }
+
+    /// Set both the row gap and the column gap of the layout
+    /// The gaps accept units or `Dip` values. `Dip` values are converted with the DPI of the parent each time the layout is updated.
+    pub fn gap<L: Into<Length>>(mut self, value: L) -> FlexboxLayoutBuilder {
+        let value = value.into();
+        self.layout.gap = (value, value);
+        self
+    }
+
     //
     // Child layout style
     //
@@ -539,9 +739,6 @@ impl FlexboxLayoutBuilder {
 
     /// Build the layout object and bind the callback.
     pub fn build(mut self, layout: &FlexboxLayout) -> Result<(), NwgError> {
-        use winapi::um::winuser::WM_SIZE;
-        use winapi::shared::minwindef::{HIWORD, LOWORD};
-
         if self.layout.base.is_null() {
             return Err(NwgError::layout_create("Flexboxlayout does not have a parent."));
         }
@@ -605,22 +802,15 @@ impl FlexboxLayoutBuilder {
         static FLEX_LAYOUT_ID: AtomicUsize = AtomicUsize::new(0x9FFF); 
         let handler_id = FLEX_LAYOUT_ID.fetch_add(1, Ordering::SeqCst);
  
-        // Bind the event handler
+        // Bind the event handler. The resizes are coalesced while the user drags the border of the window.
         let event_layout = layout.clone();
-        let cb = move |_h, msg, _w, l| {
-            if msg == WM_SIZE {
-                let size = l as u32;
-                let width = LOWORD(size) as i32;
-                let height = HIWORD(size) as i32;
-                let (w, h) = unsafe { crate::win32::high_dpi::physical_to_logical(width, height) };
-                FlexboxLayout::update_layout(&event_layout, w as u32, h as u32, (0, 0)).expect("Failed to compute layout!");
-            }
-            None
+        let cb = move |w, h| {
+            FlexboxLayout::update_layout(&event_layout, w, h, (0, 0)).expect("Failed to compute layout!");
         };
 
         {
             let mut layout_inner = layout.inner.borrow_mut();
-            layout_inner.handler = Some(bind_raw_event_handler_inner(&base_handle, handler_id, cb).unwrap());
+            layout_inner.handler = Some(bind_resize_handler(&base_handle, handler_id, cb).unwrap());
         }
 
         Ok(())
@@ -682,6 +872,8 @@ impl Default for FlexboxLayout {
             handler: None,
             children: Vec::new(),
             style: Default::default(),
+            gap: (Length::Units(0), Length::Units(0)),
+            padding: None,
             parent_layout: None,
         };
 
@@ -748,4 +940,73 @@ impl<'a> FlexboxLayoutChildren<'a> {
     pub fn children<'b>(&'b self) -> &'b Vec<FlexboxLayoutChild> {
         &self.inner.children
     }
-}
\ No newline at end of file
+}
+
+
+/*
+    Gaps are not supported by stretch, so they are emulated with the margins of the children.
+
+    Without wrapping, there is a single line of children: the gap is added to the margins between two children,
+    half of it on each child. The first child does not get the half on its leading side and the last child
+    does not get the half on its trailing side, so nothing is added against the padding of the parent.
+
+    With wrapping, any child can start a line. Every child gets half of the gap on each side, and the padding of the
+    parent is reduced by the same amount. Stretch does not clamp the padding, so when the padding is smaller than half of
+    the gap the negative padding cancels the margins of the outer children.
+*/
+
+fn gap_margin(mut style: Style, parent: &Style, (row_gap, column_gap): (u32, u32), (first, last): (bool, bool)) -> Style {
+    let (h, v) = (column_gap as f32 / 2.0, row_gap as f32 / 2.0);
+    let m = style.margin;
+
+    style.margin = match parent.flex_wrap {
+        FlexWrap::NoWrap => {
+            // The half of the gap before and after the child, in the order of the children
+            let before = if first { 0.0 } else { 1.0 };
+            let after = if last { 0.0 } else { 1.0 };
+            match parent.flex_direction {
+                FlexDirection::Row => Rect { start: grow_dimension(m.start, h * before), end: grow_dimension(m.end, h * after), ..m },
+                FlexDirection::RowReverse => Rect { start: grow_dimension(m.start, h * after), end: grow_dimension(m.end, h * before), ..m },
+                FlexDirection::Column => Rect { top: grow_dimension(m.top, v * before), bottom: grow_dimension(m.bottom, v * after), ..m },
+                FlexDirection::ColumnReverse => Rect { top: grow_dimension(m.top, v * after), bottom: grow_dimension(m.bottom, v * before), ..m },
+            }
+        },
+        FlexWrap::Wrap | FlexWrap::WrapReverse => Rect {
+            start: grow_dimension(m.start, h),
+            end: grow_dimension(m.end, h),
+            top: grow_dimension(m.top, v),
+            bottom: grow_dimension(m.bottom, v),
+        }
+    };
+
+    style
+}
+
+fn gap_padding(mut style: Style, (row_gap, column_gap): (u32, u32)) -> Style {
+    if style.flex_wrap == FlexWrap::NoWrap {
+        return style;
+    }
+
+    let (h, v) = (column_gap as f32 / 2.0, row_gap as f32 / 2.0);
+    style.padding = Rect {
+        start: grow_dimension(style.padding.start, -h),
+        end: grow_dimension(style.padding.end, -h),
+        top: grow_dimension(style.padding.top, -v),
+        bottom: grow_dimension(style.padding.bottom, -v),
+    };
+
+    style
+}
+
+/// Add `value` to a dimension in points. Auto and percent dimensions are not modified.
+fn grow_dimension(dimension: Dimension, value: f32) -> Dimension {
+    if value == 0.0 {
+        return dimension;
+    }
+
+    match dimension {
+        Dimension::Points(p) => Dimension::Points(p + value),
+        Dimension::Undefined => Dimension::Points(value),
+        d => d
+    }
+}
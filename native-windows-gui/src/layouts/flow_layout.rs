@@ -0,0 +1,493 @@
+use crate::controls::ControlHandle;
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::{Length, Margins};
+use crate::win32::window::{RawEventHandler, unbind_raw_event_handler};
+use crate::win32::resize::bind_resize_handler;
+use crate::NwgError;
+use winapi::shared::windef::HWND;
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::ptr;
+
+
+/// The alignment of the children of a FlowLayout
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FlowAlignment {
+    /// Rows are aligned to the left edge and children are aligned to the top of their row
+    Start,
+
+    /// Rows are centered and children are centered vertically in their row
+    Center,
+
+    /// Rows are aligned to the right edge and children are aligned to the bottom of their row
+    End,
+}
+
+
+/// A control item in a FlowLayout
+#[derive(Debug)]
+pub struct FlowLayoutItem {
+    /// The handle to the control in the item
+    control: HWND,
+
+    /// The size of the control. If None, the control keeps its current size.
+    pub size: Option<(u32, u32)>,
+}
+
+impl FlowLayoutItem {
+
+    /// Initialize a new layout item. If `size` is None, the control keeps its current size.
+    pub fn new<W: Into<ControlHandle>>(c: W, size: Option<(u32, u32)>) -> FlowLayoutItem {
+        let control = c.into().hwnd().expect("Child must be a window-like control (HWND handle)");
+        FlowLayoutItem { control, size }
+    }
+
+}
+
+
+/// A layout that lays out widgets in rows
+/// This is the inner data shared between the callback and the application
+pub struct FlowLayoutInner {
+    /// The control that holds the layout
+    base: HWND,
+
+    /// The resize handler of the layout
+    handler: Option<RawEventHandler>,
+
+    /// The children of the control that fit in the layout
+    children: Vec<FlowLayoutItem>,
+
+    /// The top, right, bottom, left space around the layout
+    margins: Margins,
+
+    /// The horizontal space between the children of a row
+    column_gap: Length,
+
+    /// The vertical space between the rows
+    row_gap: Length,
+
+    /// The alignment of the rows and of the children in the rows
+    alignment: FlowAlignment,
+
+    /// The height used by the rows during the last update, including the margins
+    content_height: u32,
+}
+
+/**
+A layout that lays out its children from left to right, in the order they were added, and wraps to a new row
+when there is not enough width left. The children keep their size unless the item defines one.
+Hidden children are skipped; call `fit` after showing, hiding or resizing a child to update the layout.
+
+A FlowLayout has the following properties:
+* margin - The top, right, bottom, left margins of the layout - (default: [5, 5, 5, 5])
+* column_gap - The horizontal space between the children of a row - (default: 5)
+* row_gap - The vertical space between the rows - (default: 5)
+* alignment - The alignment of the rows - (default: FlowAlignment::Start)
+
+Requires the `flow-layout` feature.
+
+```rust
+    use native_windows_gui as nwg;
+
+    fn layout(layout: &nwg::FlowLayout, window: &nwg::Window, tags: &[nwg::Button]) {
+        let mut builder = nwg::FlowLayout::builder()
+            .parent(window)
+            .column_gap(4)
+            .row_gap(4)
+            .alignment(nwg::FlowAlignment::Center);
+
+        for tag in tags {
+            builder = builder.child(tag);
+        }
+
+        builder.build(&layout);
+    }
+```
+*/
+#[derive(Clone)]
+pub struct FlowLayout {
+    inner: Rc<RefCell<FlowLayoutInner>>
+}
+
+impl FlowLayout {
+
+    pub fn builder() -> FlowLayoutBuilder {
+        let layout = FlowLayoutInner {
+            base: ptr::null_mut(),
+            handler: None,
+            children: Vec::new(),
+            margins: Margins::Units([5, 5, 5, 5]),
+            column_gap: Length::Units(5),
+            row_gap: Length::Units(5),
+            alignment: FlowAlignment::Start,
+            content_height: 0,
+        };
+
+        FlowLayoutBuilder { layout }
+    }
+
+    /**
+        Add a children control at the end of the layout. The control keeps its current size.
+        This is a simplified interface over `add_child_item`
+
+        Panic:
+        - If the layout is not initialized
+        - If the control is not window-like (HWND handle)
+    */
+    pub fn add_child<W: Into<ControlHandle>>(&self, c: W) {
+        self.add_child_item(FlowLayoutItem::new(c, None));
+    }
+
+    /**
+        Add a children control at the end of the layout.
+
+        Panic:
+        - If the layout is not initialized
+    */
+    pub fn add_child_item(&self, i: FlowLayoutItem) {
+        {
+            let mut inner = self.inner.borrow_mut();
+            if inner.base.is_null() {
+                panic!("FlowLayout is not initialized");
+            }
+
+            inner.children.push(i);
+        }
+
+        self.fit();
+    }
+
+    /**
+        Insert a children control at `index` in the layout. If `index` is greater than the number of children,
+        the control is added at the end.
+
+        Panic:
+        - If the layout is not initialized
+    */
+    pub fn insert_child_item(&self, index: usize, i: FlowLayoutItem) {
+        {
+            let mut inner = self.inner.borrow_mut();
+            if inner.base.is_null() {
+                panic!("FlowLayout is not initialized");
+            }
+
+            let index = index.min(inner.children.len());
+            inner.children.insert(index, i);
+        }
+
+        self.fit();
+    }
+
+    /**
+        Remove the children control from the layout.
+        Note that the child control won't be hidden after being removed from the control.
+
+        This method won't do anything if the control is not in the layout.
+
+        Panic:
+        - If the layout is not initialized
+    */
+    pub fn remove_child<W: Into<ControlHandle>>(&self, c: W) {
+        {
+            let mut inner = self.inner.borrow_mut();
+            if inner.base.is_null() {
+                panic!("FlowLayout is not initialized");
+            }
+
+            let handle = c.into().hwnd().expect("Control must be window-like (HWND handle)");
+            match inner.children.iter().position(|item| item.control == handle) {
+                Some(i) => { inner.children.remove(i); },
+                None => { return; }
+            }
+        }
+
+        self.fit();
+    }
+
+    /**
+        Check if a window control is a children of the layout
+
+        Panic:
+        - If the layout is not initialized
+        - If the child is not a window-like control
+    */
+    pub fn has_child<W: Into<ControlHandle>>(&self, c: W) -> bool {
+        let inner = self.inner.borrow();
+        if inner.base.is_null() {
+            panic!("FlowLayout is not initialized");
+        }
+
+        let handle = c.into().hwnd().expect("Children is not a window-like control (HWND handle)");
+        inner.children.iter().any(|c| c.control == handle )
+    }
+
+    /// Returns the number of children in the layout
+    pub fn len(&self) -> usize {
+        self.inner.borrow().children.len()
+    }
+
+    /// Returns true if the layout has no children
+    pub fn is_empty(&self) -> bool {
+        self.inner.borrow().children.is_empty()
+    }
+
+    /**
+        Returns the height used by the rows during the last update of the layout, including the margins.
+        Useful to size a scrollable container that holds the layout.
+    */
+    pub fn content_height(&self) -> u32 {
+        self.inner.borrow().content_height
+    }
+
+    /// Resize the layout as if the parent window had the specified size.
+    ///
+    /// Arguments:
+    ///   w: New width of the layout
+    ///   h: New height of the layout
+    ///
+    ///  Panic:
+    ///   - The layout must have been successfully built otherwise this function will panic.
+    pub fn resize(&self, w: u32, h: u32) {
+        if self.inner.borrow().base.is_null() {
+            panic!("Flow layout is not bound to a parent control.")
+        }
+
+        self.update_layout(w, h);
+    }
+
+    /// Resize the layout to fit the parent window size
+    ///
+    /// Panic:
+    ///   - The layout must have been successfully built otherwise this function will panic.
+    pub fn fit(&self) {
+        let base = self.inner.borrow().base;
+        if base.is_null() {
+            panic!("Flow layout is not bound to a parent control.")
+        }
+
+        let (w, h) = unsafe { wh::get_window_size(base) };
+        self.update_layout(w, h);
+    }
+
+    /// Set the margins of the layout. The four values are in this order: top, right, bottom, left.
+    pub fn margin<M: Into<Margins>>(&self, m: M) {
+        let mut inner = self.inner.borrow_mut();
+        inner.margins = m.into();
+    }
+
+    /// Set the horizontal space between the children of a row. Default value is 5.
+    pub fn column_gap<L: Into<Length>>(&self, gap: L) {
+        let mut inner = self.inner.borrow_mut();
+        inner.column_gap = gap.into();
+    }
+
+    /// Set the vertical space between the rows. Default value is 5.
+    pub fn row_gap<L: Into<Length>>(&self, gap: L) {
+        let mut inner = self.inner.borrow_mut();
+        inner.row_gap = gap.into();
+    }
+
+    /// Set the alignment of the rows and of the children in their row
+    pub fn alignment(&self, align: FlowAlignment) {
+        let mut inner = self.inner.borrow_mut();
+        inner.alignment = align;
+    }
+
+    fn update_layout(&self, width: u32, _height: u32) {
+        use winapi::um::winuser::{GWL_STYLE, WS_VISIBLE};
+
+        let mut inner = self.inner.borrow_mut();
+        if inner.base.is_null() || inner.children.len() == 0 {
+            return;
+        }
+
+        let [m_top, m_right, m_bottom, m_left] = unsafe { inner.margins.to_units(inner.base) };
+        let (col_gap, row_gap) = unsafe { (inner.column_gap.to_units(inner.base) as i32, inner.row_gap.to_units(inner.base) as i32) };
+        let available = (width as i32 - m_left as i32 - m_right as i32).max(0);
+
+        // Collect the visible children with their size.
+        // Checks the style of the child, because IsWindowVisible is false for every child while the parent is hidden
+        let items: Vec<(HWND, i32, i32, bool)> = inner.children.iter()
+            .filter(|item| wh::get_window_long(item.control, GWL_STYLE) as u32 & WS_VISIBLE != 0)
+            .map(|item| match item.size {
+                Some((w, h)) => (item.control, w as i32, h as i32, true),
+                None => {
+                    let (_, _, w, h) = unsafe { wh::get_window_rect_in_parent(item.control) };
+                    (item.control, w as i32, h as i32, false)
+                }
+            })
+            .collect();
+
+        // Split the children in rows. A child wider than the layout takes a row by itself.
+        let mut rows: Vec<&[(HWND, i32, i32, bool)]> = Vec::new();
+        let mut start = 0;
+        let mut row_width = 0;
+        for (index, &(_, w, _, _)) in items.iter().enumerate() {
+            if index > start && row_width + col_gap + w > available {
+                rows.push(&items[start..index]);
+                start = index;
+                row_width = 0;
+            }
+
+            row_width += if index > start { col_gap + w } else { w };
+        }
+
+        if start < items.len() {
+            rows.push(&items[start..]);
+        }
+
+        let mut y = m_top as i32;
+        let mut batch = wh::WindowPositionBatch::new(items.len());
+        let mut last_handle = None;
+        for row in rows.iter() {
+            let row_width: i32 = row.iter().map(|&(_, w, _, _)| w).sum::<i32>() + col_gap * (row.len() as i32 - 1);
+            let row_height: i32 = row.iter().map(|&(_, _, h, _)| h).max().unwrap_or(0);
+
+            let mut x = m_left as i32 + match inner.alignment {
+                FlowAlignment::Start => 0,
+                FlowAlignment::Center => ((available - row_width) / 2).max(0),
+                FlowAlignment::End => (available - row_width).max(0),
+            };
+
+            for &(control, w, h, resize) in row.iter() {
+                let item_y = y + match inner.alignment {
+                    FlowAlignment::Start => 0,
+                    FlowAlignment::Center => (row_height - h) / 2,
+                    FlowAlignment::End => row_height - h,
+                };
+
+                match resize {
+                    true => batch.move_window_after(control, last_handle, x, item_y, w as u32, h as u32),
+                    false => batch.set_position_after(control, last_handle, x, item_y)
+                }
+
+                last_handle = Some(control);
+                x += w + col_gap;
+            }
+
+            y += row_height + row_gap;
+        }
+
+        batch.apply();
+
+        if !rows.is_empty() {
+            y -= row_gap;
+        }
+
+        inner.content_height = (y + m_bottom as i32).max(0) as u32;
+    }
+}
+
+impl Default for FlowLayout {
+
+    fn default() -> FlowLayout {
+        let inner = FlowLayoutInner {
+            base: ptr::null_mut(),
+            handler: None,
+            children: Vec::new(),
+            margins: Margins::Units([5, 5, 5, 5]),
+            column_gap: Length::Units(5),
+            row_gap: Length::Units(5),
+            alignment: FlowAlignment::Start,
+            content_height: 0,
+        };
+
+        FlowLayout {
+            inner: Rc::new(RefCell::new(inner))
+        }
+    }
+
+}
+
+
+/// Builder for a `FlowLayout` struct
+pub struct FlowLayoutBuilder {
+    layout: FlowLayoutInner
+}
+
+impl FlowLayoutBuilder {
+
+    /// Set the layout parent. The handle must be a window object otherwise the function will panic
+    pub fn parent<W: Into<ControlHandle>>(mut self, p: W) -> FlowLayoutBuilder {
+        self.layout.base = p.into().hwnd().expect("Parent must be HWND");
+        self
+    }
+
+    /// Add a children at the end of the layout. The control keeps its current size.
+    /// This is a shortcut over `child_item`.
+    /// The handle must be a window object otherwise the function will panic
+    pub fn child<W: Into<ControlHandle>>(mut self, c: W) -> FlowLayoutBuilder {
+        self.layout.children.push(FlowLayoutItem::new(c, None));
+        self
+    }
+
+    /// Add a children at the end of the layout
+    pub fn child_item(mut self, item: FlowLayoutItem) -> FlowLayoutBuilder {
+        self.layout.children.push(item);
+        self
+    }
+
+    /// Set the margins of the layout. The four values are in this order: top, right, bottom, left.
+    pub fn margin<M: Into<Margins>>(mut self, m: M) -> FlowLayoutBuilder {
+        self.layout.margins = m.into();
+        self
+    }
+
+    /// Set the horizontal space between the children of a row. Default value is 5.
+    pub fn column_gap<L: Into<Length>>(mut self, gap: L) -> FlowLayoutBuilder {
+        self.layout.column_gap = gap.into();
+        self
+    }
+
+    /// Set the vertical space between the rows. Default value is 5.
+    pub fn row_gap<L: Into<Length>>(mut self, gap: L) -> FlowLayoutBuilder {
+        self.layout.row_gap = gap.into();
+        self
+    }
+
+    /// Set the alignment of the rows and of the children in their row. Default value is `FlowAlignment::Start`.
+    pub fn alignment(mut self, align: FlowAlignment) -> FlowLayoutBuilder {
+        self.layout.alignment = align;
+        self
+    }
+
+    /// Build the layout object and bind the callback.
+    pub fn build(self, layout: &FlowLayout) -> Result<(), NwgError> {
+        if self.layout.base.is_null() {
+            return Err(NwgError::layout_create("FlowLayout does not have a parent."));
+        }
+
+        let (w, h) = unsafe { wh::get_window_size(self.layout.base) };
+        let base_handle = ControlHandle::Hwnd(self.layout.base);
+
+        // Saves the new layout. Free the old layout (if there is one)
+        {
+            let mut layout_inner = layout.inner.borrow_mut();
+            if let Some(handler) = layout_inner.handler.as_ref() {
+                drop(unbind_raw_event_handler(handler));
+            }
+
+            *layout_inner = self.layout;
+        }
+
+        // Initial layout update
+        layout.update_layout(w, h);
+
+        // Keep generating ids so that multiple layouts can be applied to the same parent
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static FLOW_LAYOUT_ID: AtomicUsize = AtomicUsize::new(0x6FFF);
+        let handler_id = FLOW_LAYOUT_ID.fetch_add(1, Ordering::SeqCst);
+
+        // Bind the event handler
+        let event_layout = layout.clone();
+        let cb = move |w, h| FlowLayout::update_layout(&event_layout, w, h);
+
+        {
+            let mut layout_inner = layout.inner.borrow_mut();
+            layout_inner.handler = Some(bind_resize_handler(&base_handle, handler_id, cb)?);
+        }
+
+        Ok(())
+    }
+
+}
@@ -1,6 +1,7 @@
 use crate::controls::ControlHandle;
-use crate::win32::window::bind_raw_event_handler_inner;
+use crate::win32::resize::bind_resize_handler;
 use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::{Length, Margins};
 use crate::NwgError;
 use winapi::shared::windef::{HWND};
 use std::rc::Rc;
@@ -8,6 +9,90 @@ use std::cell::RefCell;
 use std::ptr;
 
 
+/// How the size of a row or a column of a GridLayout is computed
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GridTrackSize {
+    /// A size in logical units
+    Fixed(u32),
+
+    /// A share of the space left by the fixed and auto tracks, proportional to the weight
+    Star(f32),
+
+    /// The largest preferred size of the children in the track. Children that span multiple tracks are ignored.
+    Auto,
+}
+
+/// The sizing of a row or a column of a GridLayout
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GridTrack {
+    /// How the size of the track is computed
+    pub size: GridTrackSize,
+
+    /// The minimum size of the track
+    pub min: u32,
+
+    /// The maximum size of the track
+    pub max: u32,
+}
+
+impl GridTrack {
+
+    /// A track with a fixed size
+    pub fn fixed(size: u32) -> GridTrack {
+        GridTrack { size: GridTrackSize::Fixed(size), min: 0, max: u32::max_value() }
+    }
+
+    /// A track that takes a share of the remaining space proportional to `weight`
+    pub fn star(weight: f32) -> GridTrack {
+        GridTrack { size: GridTrackSize::Star(weight), min: 0, max: u32::max_value() }
+    }
+
+    /// A track sized from the preferred size of its children
+    pub fn auto() -> GridTrack {
+        GridTrack { size: GridTrackSize::Auto, min: 0, max: u32::max_value() }
+    }
+
+    /// Sets the minimum size of the track
+    pub fn min(mut self, min: u32) -> GridTrack {
+        self.min = min;
+        self
+    }
+
+    /// Sets the maximum size of the track
+    pub fn max(mut self, max: u32) -> GridTrack {
+        self.max = max;
+        self
+    }
+
+    fn clamp(&self, size: u32) -> u32 {
+        size.min(self.max).max(self.min)
+    }
+
+}
+
+impl Default for GridTrack {
+    fn default() -> GridTrack {
+        GridTrack::star(1.0)
+    }
+}
+
+/// The alignment of a child of a GridLayout in its cell, on one axis
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GridAlignment {
+    /// The child fills the cell
+    Stretch,
+
+    /// The child keeps its preferred size and is placed at the start (left or top) of the cell
+    Start,
+
+    /// The child keeps its preferred size and is centered in the cell
+    Center,
+
+    /// The child keeps its preferred size and is placed at the end (right or bottom) of the cell
+    End,
+}
+
+
 /// A control item in a GridLayout
 #[derive(Debug)]
 pub struct GridLayoutItem {
@@ -24,7 +109,17 @@ pub struct GridLayoutItem {
     pub col_span: u32,
 
     /// The number row this item should span. Should be 1 for single row item.
-    pub row_span: u32
+    pub row_span: u32,
+
+    /// The size of the control used by auto tracks and by the alignments other than `Stretch`.
+    /// By default, the size of the control when the item was created.
+    pub preferred_size: (u32, u32),
+
+    /// The horizontal alignment of the control in its cell
+    pub h_align: GridAlignment,
+
+    /// The vertical alignment of the control in its cell
+    pub v_align: GridAlignment,
 }
 
 impl GridLayoutItem {
@@ -32,16 +127,33 @@ impl GridLayoutItem {
     /// Initialize a new grid layout item
     pub fn new<W: Into<ControlHandle>>(c: W, col: u32, row: u32, col_span: u32, row_span: u32) -> GridLayoutItem {
         let control = c.into().hwnd().expect("Child must be a window-like control (HWND handle)");
+        let (_, _, w, h) = unsafe { wh::get_window_rect_in_parent(control) };
 
         GridLayoutItem {
             control,
             col,
             row,
             col_span,
-            row_span
+            row_span,
+            preferred_size: (w, h),
+            h_align: GridAlignment::Stretch,
+            v_align: GridAlignment::Stretch,
         }
     }
 
+    /// Sets the horizontal and vertical alignment of the item in its cell
+    pub fn align(mut self, h_align: GridAlignment, v_align: GridAlignment) -> GridLayoutItem {
+        self.h_align = h_align;
+        self.v_align = v_align;
+        self
+    }
+
+    /// Sets the size of the item used by auto tracks and by the alignments other than `Stretch`
+    pub fn preferred_size(mut self, size: (u32, u32)) -> GridLayoutItem {
+        self.preferred_size = size;
+        self
+    }
+
 }
 
 
@@ -55,7 +167,7 @@ pub struct GridLayoutInner {
     children: Vec<GridLayoutItem>,
 
     /// The top, right, bottom, left space around the layout
-    margins: [u32; 4],
+    margins: Margins,
 
     /// The minimum size of the layout. Used if `base` is smaller than `min_size`.
     min_size: [u32; 2],
@@ -69,8 +181,14 @@ pub struct GridLayoutInner {
     /// The number of row. If None, compute the value from children.
     row_count: Option<u32>, 
 
+    /// The sizing of the columns. Columns without a value use `GridTrack::star(1.0)`.
+    columns: Vec<GridTrack>,
+
+    /// The sizing of the rows. Rows without a value use `GridTrack::star(1.0)`.
+    rows: Vec<GridTrack>,
+
     /// The spacing between controls
-    spacing: u32
+    spacing: Length
 }
 
 /** 
@@ -84,6 +202,12 @@ A GridLayouts has the following properties:
 * max_size - The maximum size of the layout - (default: [u32::max_value(), u32::max_value()])
 * max_column - Number of columns - (default: None),
 * max_row - Number of rows - (default: None),
+* column - The sizing of a column, see `GridTrack` - (default: GridTrack::star(1.0))
+* row - The sizing of a row, see `GridTrack` - (default: GridTrack::star(1.0))
+
+By default, the space is divided evenly between the rows and the columns. Each row and column can instead have
+a fixed size, a weighted share of the remaining space (star), or the preferred size of its children (auto),
+with an optional minimum and maximum size. Children fill their cell unless they have an alignment (see `GridLayoutItem::align`).
 
 ```rust
     use native_windows_gui as nwg;
@@ -95,6 +219,7 @@ A GridLayouts has the following properties:
             .margin([0,0,0,0])
             .child(0, 0, item1)
             .child_item(nwg::GridLayoutItem::new(item2, 1, 0, 2, 1))
+            .column(0, nwg::GridTrack::auto().min(80))
             .build(&layout);
     }
 ```
@@ -110,12 +235,14 @@ impl GridLayout {
         let layout = GridLayoutInner {
             base: ptr::null_mut(),
             children: Vec::new(),
-            margins: [5, 5, 5, 5],
-            spacing: 5,
+            margins: Margins::Units([5, 5, 5, 5]),
+            spacing: Length::Units(5),
             min_size: [0, 0],
             max_size: [u32::max_value(), u32::max_value()],
             column_count: None,
-            row_count: None
+            row_count: None,
+            columns: Vec::new(),
+            rows: Vec::new(),
         };
 
         GridLayoutBuilder { layout }
@@ -130,16 +257,7 @@ impl GridLayout {
         - If the control is not window-like (HWND handle)
     */
     pub fn add_child<W: Into<ControlHandle>>(&self, col: u32, row: u32, c: W) {
-        let h = c.into().hwnd().expect("Child must be a window-like control (HWND handle)");
-        let item = GridLayoutItem {
-            control: h,
-            col,
-            row,
-            col_span: 1,
-            row_span: 1,
-        };
-
-        self.add_child_item(item);
+        self.add_child_item(GridLayoutItem::new(c, col, row, 1, 1));
     }
     
     /** 
@@ -350,15 +468,15 @@ impl GridLayout {
     }
 
     /// Set the margins of the layout. The four values are in this order: top, right, bottom, left.
-    pub fn margin(&self, m: [u32; 4]) {
+    pub fn margin<M: Into<Margins>>(&self, m: M) {
         let mut inner = self.inner.borrow_mut();
-        inner.margins = m;
+        inner.margins = m.into();
     }
 
     /// Set the size of the space between the children in the layout. Default value is 5.
-    pub fn spacing(&self, sp: u32) {
+    pub fn spacing<L: Into<Length>>(&self, sp: L) {
         let mut inner = self.inner.borrow_mut();
-        inner.spacing = sp;
+        inner.spacing = sp.into();
     }
 
     /// Sets the minimum size of the layout
@@ -385,14 +503,26 @@ impl GridLayout {
         inner.row_count = count;
     }
 
+    /// Set the sizing of the column at `index`. Call `fit` to update the layout.
+    pub fn column(&self, index: u32, track: GridTrack) {
+        let mut inner = self.inner.borrow_mut();
+        set_track(&mut inner.columns, index, track);
+    }
+
+    /// Set the sizing of the row at `index`. Call `fit` to update the layout.
+    pub fn row(&self, index: u32, track: GridTrack) {
+        let mut inner = self.inner.borrow_mut();
+        set_track(&mut inner.rows, index, track);
+    }
+
     fn update_layout(&self, mut width: u32, mut height: u32) -> () {
         let inner = self.inner.borrow();
         if inner.base.is_null() || inner.children.len() == 0 {
             return;
         }
 
-        let [m_top, m_right, m_bottom, m_left] = inner.margins;
-        let sp = inner.spacing;
+        let [m_top, m_right, m_bottom, m_left] = unsafe { inner.margins.to_units(inner.base) };
+        let sp = unsafe { inner.spacing.to_units(inner.base) };
 
         let children = &inner.children;
 
@@ -430,25 +560,28 @@ impl GridLayout {
         width = width - ((sp * 2) * column_count);
         height = height - ((sp * 2) * row_count);
 
-        let item_width = width / column_count;
-        let item_height = height / row_count;
         let sp2 = sp * 2;
 
-        let mut columns = vec![item_width; column_count as usize];
-        let mut rows = vec![item_height; row_count as usize];
-        let extra_width = width - item_width * column_count;
-        if extra_width > 0 {
-            for x in &mut columns[0..(extra_width as usize)] {
-                *x += 1;
+        // Preferred size of the auto tracks
+        let mut auto_columns = vec![0; column_count as usize];
+        let mut auto_rows = vec![0; row_count as usize];
+        for item in children.iter() {
+            let (w, h) = item.preferred_size;
+            if item.col_span == 1 && item.col < column_count {
+                let c = &mut auto_columns[item.col as usize];
+                *c = (*c).max(w);
             }
-        }
-        let extra_height = height - item_height * row_count;
-        if extra_height > 0 {
-            for x in &mut rows[0..(extra_height as usize)] {
-                *x += 1;
+
+            if item.row_span == 1 && item.row < row_count {
+                let r = &mut auto_rows[item.row as usize];
+                *r = (*r).max(h);
             }
         }
 
+        let columns = compute_tracks(&inner.columns, &auto_columns, width);
+        let rows = compute_tracks(&inner.rows, &auto_rows, height);
+
+        let mut batch = wh::WindowPositionBatch::new(inner.children.len());
         let mut last_handle = None;
         for item in inner.children.iter() {
             let x: u32 = m_left + (sp + (sp2 * item.col)) + columns[0..(item.col as usize)].iter().sum::<u32>();
@@ -457,14 +590,101 @@ impl GridLayout {
             let local_width: u32 = &columns[(item.col as usize)..((item.col + item.col_span) as usize)].iter().sum::<u32>() + (sp2 * (item.col_span - 1));
             let local_height: u32 = &rows[(item.row as usize)..((item.row + item.row_span) as usize)].iter().sum::<u32>() + (sp2 * (item.row_span - 1));
 
-            unsafe {
-                wh::set_window_position(item.control, x as i32, y as i32);
-                wh::set_window_size(item.control, local_width, local_height, false);
-                wh::set_window_after(item.control, last_handle)
-            }
+            let (x, local_width) = align_in_cell(item.h_align, x, local_width, item.preferred_size.0);
+            let (y, local_height) = align_in_cell(item.v_align, y, local_height, item.preferred_size.1);
 
+            batch.move_window_after(item.control, last_handle, x as i32, y as i32, local_width, local_height);
             last_handle = Some(item.control);
         }
+
+        batch.apply();
+    }
+}
+
+/// Sets the track at `index`, filling the missing tracks with the default track
+fn set_track(tracks: &mut Vec<GridTrack>, index: u32, track: GridTrack) {
+    let index = index as usize;
+    if tracks.len() <= index {
+        tracks.resize(index + 1, GridTrack::default());
+    }
+
+    tracks[index] = track;
+}
+
+/// Computes the size of `auto_sizes.len()` tracks sharing `available` units.
+/// Fixed and auto tracks are sized first, then the remaining space is shared between the star tracks.
+fn compute_tracks(tracks: &[GridTrack], auto_sizes: &[u32], available: u32) -> Vec<u32> {
+    let count = auto_sizes.len();
+    let track = |i: usize| tracks.get(i).copied().unwrap_or_default();
+
+    let mut sizes = vec![0u32; count];
+    let mut stars = Vec::with_capacity(count);
+    let mut remaining = available as i64;
+    for i in 0..count {
+        let t = track(i);
+        match t.size {
+            GridTrackSize::Fixed(size) => sizes[i] = t.clamp(size),
+            GridTrackSize::Auto => sizes[i] = t.clamp(auto_sizes[i]),
+            GridTrackSize::Star(weight) => { stars.push((i, weight.max(0.0))); continue; }
+        }
+
+        remaining -= sizes[i] as i64;
+    }
+
+    // Star tracks whose share is out of their bounds are fixed to the bound, and the space is shared again between the others
+    let mut remaining = remaining.max(0);
+    loop {
+        let total_weight: f32 = stars.iter().map(|&(_, w)| w).sum();
+        let share = |w: f32| match total_weight > 0.0 {
+            true => (remaining as f64 * w as f64 / total_weight as f64) as i64,
+            false => 0
+        };
+
+        let bounded = stars.iter().position(|&(i, w)| {
+            let t = track(i);
+            let s = share(w);
+            s < t.min as i64 || s > t.max as i64
+        });
+
+        match bounded {
+            Some(index) => {
+                let (i, w) = stars.remove(index);
+                sizes[i] = track(i).clamp(share(w).max(0).min(u32::max_value() as i64) as u32);
+                remaining = (remaining - sizes[i] as i64).max(0);
+            },
+            None => {
+                let mut used = 0;
+                for &(i, w) in stars.iter() {
+                    sizes[i] = share(w) as u32;
+                    used += sizes[i] as i64;
+                }
+
+                // Give the pixels lost by the rounding to the first star tracks that can grow
+                let mut extra = remaining - used;
+                for &(i, w) in stars.iter() {
+                    if extra <= 0 { break; }
+                    if w > 0.0 && sizes[i] < track(i).max {
+                        sizes[i] += 1;
+                        extra -= 1;
+                    }
+                }
+
+                break;
+            }
+        }
+    }
+
+    sizes
+}
+
+/// Computes the position and the size of a child in a cell along one axis
+fn align_in_cell(align: GridAlignment, pos: u32, cell: u32, preferred: u32) -> (u32, u32) {
+    let size = preferred.min(cell);
+    match align {
+        GridAlignment::Stretch => (pos, cell),
+        GridAlignment::Start => (pos, size),
+        GridAlignment::Center => (pos + (cell - size) / 2, size),
+        GridAlignment::End => (pos + cell - size, size),
     }
 }
 
@@ -474,12 +694,14 @@ impl Default for GridLayout {
         let inner = GridLayoutInner {
             base: ptr::null_mut(),
             children: Vec::new(),
-            margins: [5, 5, 5, 5],
+            margins: Margins::Units([5, 5, 5, 5]),
             min_size: [0, 0],
             max_size: [u32::max_value(), u32::max_value()],
             column_count: None,
             row_count: None,
-            spacing: 5,
+            columns: Vec::new(),
+            rows: Vec::new(),
+            spacing: Length::Units(5),
         };
 
         GridLayout {
@@ -507,15 +729,7 @@ impl GridLayoutBuilder {
     /// This is a shortcut over `child_item` for item with default span.
     /// The handle must be a window object otherwise the function will panic
     pub fn child<W: Into<ControlHandle>>(mut self, col: u32, row: u32, c: W) -> GridLayoutBuilder {
-        let h = c.into().hwnd().expect("Child must be HWND");
-        self.layout.children.push(GridLayoutItem {
-            control: h,
-            col,
-            row,
-            col_span: 1,
-            row_span: 1,
-        });
-
+        self.layout.children.push(GridLayoutItem::new(c, col, row, 1, 1));
         self
     }
 
@@ -527,14 +741,14 @@ impl GridLayoutBuilder {
     }
 
     /// Set the margins of the layout. The four values are in this order: top, right, bottom, left.
-    pub fn margin(mut self, m: [u32; 4]) -> GridLayoutBuilder {
-        self.layout.margins = m;
+    pub fn margin<M: Into<Margins>>(mut self, m: M) -> GridLayoutBuilder {
+        self.layout.margins = m.into();
         self
     }
 
     /// Set the size of the space between the children in the layout. Default value is 5.
-    pub fn spacing(mut self, sp: u32) -> GridLayoutBuilder {
-        self.layout.spacing = sp;
+    pub fn spacing<L: Into<Length>>(mut self, sp: L) -> GridLayoutBuilder {
+        self.layout.spacing = sp.into();
         self
     }
 
@@ -562,12 +776,21 @@ impl GridLayoutBuilder {
         self
     }
 
+    /// Set the sizing of the column at `index`
+    pub fn column(mut self, index: u32, track: GridTrack) -> GridLayoutBuilder {
+        set_track(&mut self.layout.columns, index, track);
+        self
+    }
+
+    /// Set the sizing of the row at `index`
+    pub fn row(mut self, index: u32, track: GridTrack) -> GridLayoutBuilder {
+        set_track(&mut self.layout.rows, index, track);
+        self
+    }
+
     /// Build the layout object and bind the callback.
     /// Children must only contains window object otherwise this method will panic.
     pub fn build(self, layout: &GridLayout) -> Result<(), NwgError> {
-        use winapi::um::winuser::WM_SIZE;
-        use winapi::shared::minwindef::{HIWORD, LOWORD};
-
         if self.layout.base.is_null() {
             return Err(NwgError::layout_create("Gridlayout does not have a parent."));
         }
@@ -599,21 +822,12 @@ impl GridLayoutBuilder {
        
         // Bind the event handler
         let event_layout = layout.clone();
-        let cb = move |_h, msg, _w, l| {
-            if msg == WM_SIZE {
-                let size = l as u32;
-                let width = LOWORD(size) as i32;
-                let height = HIWORD(size) as i32;
-                let (w, h) = unsafe { crate::win32::high_dpi::physical_to_logical(width, height) };
-                GridLayout::update_layout(&event_layout, w as u32, h as u32);
-            }
-            None
-        };
+        let cb = move |w, h| GridLayout::update_layout(&event_layout, w, h);
 
         /// Keep generating ids so that multiple layouts can be applied to the same parent
         use std::sync::atomic::{AtomicUsize, Ordering};
         static BOX_LAYOUT_ID: AtomicUsize = AtomicUsize::new(0x8FFF); 
-        bind_raw_event_handler_inner(&base_handle, BOX_LAYOUT_ID.fetch_add(1, Ordering::SeqCst), cb).unwrap();
+        bind_resize_handler(&base_handle, BOX_LAYOUT_ID.fetch_add(1, Ordering::SeqCst), cb).unwrap();
 
         Ok(())
     }
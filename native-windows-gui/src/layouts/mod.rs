@@ -6,10 +6,34 @@ mod flexbox_layout;
 #[cfg(feature = "dynamic_layout")]
 mod dyn_layout;
 
-pub use self::grid_layout::{GridLayout, GridLayoutInner, GridLayoutBuilder, GridLayoutItem};
+#[cfg(feature = "dock-layout")]
+mod dock_layout;
+
+#[cfg(feature = "anchor-layout")]
+mod anchor_layout;
+
+#[cfg(feature = "flow-layout")]
+mod flow_layout;
+
+#[cfg(feature = "stack-layout")]
+mod stack_layout;
+
+pub use self::grid_layout::{GridLayout, GridLayoutInner, GridLayoutBuilder, GridLayoutItem, GridTrack, GridTrackSize, GridAlignment};
 
 #[cfg(feature = "flexbox")]
 pub use self::flexbox_layout::{FlexboxLayout, FlexboxLayoutBuilder, FlexboxLayoutItem, FlexboxLayoutChildrenMut, FlexboxLayoutChildren};
 
 #[cfg(feature = "dynamic_layout")]
 pub use self::dyn_layout::{DynLayout, DynLayoutInner, DynLayoutBuilder, DynLayoutItem };
+
+#[cfg(feature = "dock-layout")]
+pub use self::dock_layout::{DockLayout, DockLayoutInner, DockLayoutBuilder, DockLayoutItem, DockStyle, DockSize};
+
+#[cfg(feature = "anchor-layout")]
+pub use self::anchor_layout::{AnchorLayout, AnchorLayoutInner, AnchorLayoutBuilder, AnchorLayoutItem, Anchor};
+
+#[cfg(feature = "flow-layout")]
+pub use self::flow_layout::{FlowLayout, FlowLayoutInner, FlowLayoutBuilder, FlowLayoutItem, FlowAlignment};
+
+#[cfg(feature = "stack-layout")]
+pub use self::stack_layout::{StackLayout, StackLayoutInner, StackLayoutBuilder};
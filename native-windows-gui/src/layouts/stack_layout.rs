@@ -0,0 +1,434 @@
+use crate::controls::ControlHandle;
+use crate::win32::window_helper as wh;
+use crate::win32::high_dpi::Margins;
+use crate::win32::window::{RawEventHandler, unbind_raw_event_handler};
+use crate::win32::resize::bind_resize_handler;
+use crate::NwgError;
+use winapi::shared::windef::HWND;
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::ptr;
+
+
+/// A layout where the children share the same area and only one is visible at a time
+/// This is the inner data shared between the callback and the application
+pub struct StackLayoutInner {
+    /// The control that holds the layout
+    base: HWND,
+
+    /// The resize handler of the layout
+    handler: Option<RawEventHandler>,
+
+    /// The pages of the layout
+    children: Vec<HWND>,
+
+    /// The index of the visible page
+    active: Option<usize>,
+
+    /// The top, right, bottom, left space around the layout
+    margins: Margins,
+}
+
+/**
+A layout where the children (the pages) occupy the same area of the parent, and only one of them is visible at a time.
+Use it to switch between the pages of a wizard or the categories of a settings window without the tabs of a `TabsContainer`.
+
+The pages are usually `Frame` controls that hold the actual controls of the page. They are stretched to the size of the parent
+(minus the margins). The layout shows the active page and hides the other pages; if the focus was in the page that was hidden,
+it is given to the new active page.
+
+A StackLayout has the following properties:
+* margin - The top, right, bottom, left margins of the layout - (default: [0, 0, 0, 0])
+* active - The index of the page visible when the layout is built - (default: 0)
+
+Requires the `stack-layout` feature.
+
+```rust
+    use native_windows_gui as nwg;
+
+    fn layout(layout: &nwg::StackLayout, window: &nwg::Window, welcome: &nwg::Frame, options: &nwg::Frame, finish: &nwg::Frame) {
+        nwg::StackLayout::builder()
+            .parent(window)
+            .margin([0, 0, 40, 0])
+            .child(welcome)
+            .child(options)
+            .child(finish)
+            .build(&layout);
+    }
+
+    fn next_page(layout: &nwg::StackLayout) {
+        layout.next();
+    }
+```
+*/
+#[derive(Clone)]
+pub struct StackLayout {
+    inner: Rc<RefCell<StackLayoutInner>>
+}
+
+impl StackLayout {
+
+    pub fn builder() -> StackLayoutBuilder {
+        let layout = StackLayoutInner {
+            base: ptr::null_mut(),
+            handler: None,
+            children: Vec::new(),
+            active: None,
+            margins: Margins::Units([0, 0, 0, 0]),
+        };
+
+        StackLayoutBuilder { layout, active: 0 }
+    }
+
+    /**
+        Add a page at the end of the layout and returns its index. The page is hidden, unless it is the only page of the layout.
+
+        Panic:
+        - If the layout is not initialized
+        - If the control is not window-like (HWND handle)
+    */
+    pub fn add_child<W: Into<ControlHandle>>(&self, c: W) -> usize {
+        let handle = c.into().hwnd().expect("Child must be a window-like control (HWND handle)");
+        let index = {
+            let mut inner = self.inner.borrow_mut();
+            if inner.base.is_null() {
+                panic!("StackLayout is not initialized");
+            }
+
+            inner.children.push(handle);
+            inner.children.len() - 1
+        };
+
+        match self.active() {
+            Some(_) => unsafe { wh::set_window_visibility(handle, false); },
+            None => self.set_active(index),
+        }
+
+        self.fit();
+
+        index
+    }
+
+    /**
+        Remove the page from the layout. If the page was the active page, the previous page (or the next one if
+        it was the first page) becomes active. The control of the page is not hidden.
+
+        This method won't do anything if the control is not in the layout.
+
+        Panic:
+        - If the layout is not initialized
+    */
+    pub fn remove_child<W: Into<ControlHandle>>(&self, c: W) {
+        let handle = c.into().hwnd().expect("Control must be window-like (HWND handle)");
+        let new_active = {
+            let mut inner = self.inner.borrow_mut();
+            if inner.base.is_null() {
+                panic!("StackLayout is not initialized");
+            }
+
+            let index = match inner.children.iter().position(|&child| child == handle) {
+                Some(i) => i,
+                None => { return; }
+            };
+
+            inner.children.remove(index);
+
+            match inner.active {
+                Some(active) if active == index => {
+                    inner.active = None;
+                    match inner.children.is_empty() {
+                        true => None,
+                        false => Some(index.saturating_sub(1))
+                    }
+                },
+                Some(active) if active > index => {
+                    inner.active = Some(active - 1);
+                    None
+                },
+                _ => None
+            }
+        };
+
+        if let Some(index) = new_active {
+            self.set_active(index);
+        }
+    }
+
+    /**
+        Check if a window control is a page of the layout
+
+        Panic:
+        - If the layout is not initialized
+        - If the child is not a window-like control
+    */
+    pub fn has_child<W: Into<ControlHandle>>(&self, c: W) -> bool {
+        let inner = self.inner.borrow();
+        if inner.base.is_null() {
+            panic!("StackLayout is not initialized");
+        }
+
+        let handle = c.into().hwnd().expect("Children is not a window-like control (HWND handle)");
+        inner.children.iter().any(|&child| child == handle)
+    }
+
+    /// Returns the number of pages in the layout
+    pub fn len(&self) -> usize {
+        self.inner.borrow().children.len()
+    }
+
+    /// Returns true if the layout has no pages
+    pub fn is_empty(&self) -> bool {
+        self.inner.borrow().children.is_empty()
+    }
+
+    /// Returns the index of the visible page, or `None` if the layout has no pages
+    pub fn active(&self) -> Option<usize> {
+        self.inner.borrow().active
+    }
+
+    /**
+        Shows the page at `index` and hides the other pages. This method won't do anything if `index` is out of bounds.
+
+        Panic:
+        - If the layout is not initialized
+    */
+    pub fn set_active(&self, index: usize) {
+        use winapi::um::winuser::{GetFocus, IsChild, SetFocus};
+
+        let (old, new) = {
+            let mut inner = self.inner.borrow_mut();
+            if inner.base.is_null() {
+                panic!("StackLayout is not initialized");
+            }
+
+            if index >= inner.children.len() || inner.active == Some(index) {
+                return;
+            }
+
+            let old = inner.active.and_then(|i| inner.children.get(i).copied());
+            inner.active = Some(index);
+            (old, inner.children[index])
+        };
+
+        self.fit();
+
+        unsafe {
+            wh::set_window_visibility(new, true);
+
+            if let Some(old) = old {
+                let focus = GetFocus();
+                let had_focus = !focus.is_null() && (focus == old || IsChild(old, focus) != 0);
+
+                wh::set_window_visibility(old, false);
+
+                if had_focus {
+                    SetFocus(new);
+                }
+            }
+        }
+    }
+
+    /**
+        Shows the page that holds the control `c`. This method won't do anything if the control is not in the layout.
+
+        Panic:
+        - If the layout is not initialized
+    */
+    pub fn set_active_child<W: Into<ControlHandle>>(&self, c: W) {
+        let handle = c.into().hwnd().expect("Control must be window-like (HWND handle)");
+        let index = self.inner.borrow().children.iter().position(|&child| child == handle);
+        if let Some(index) = index {
+            self.set_active(index);
+        }
+    }
+
+    /// Shows the next page. Returns `false` if the active page is the last page.
+    pub fn next(&self) -> bool {
+        let (active, len) = {
+            let inner = self.inner.borrow();
+            (inner.active, inner.children.len())
+        };
+
+        match active {
+            Some(i) if i + 1 < len => {
+                self.set_active(i + 1);
+                true
+            },
+            _ => false
+        }
+    }
+
+    /// Shows the previous page. Returns `false` if the active page is the first page.
+    pub fn previous(&self) -> bool {
+        match self.active() {
+            Some(i) if i > 0 => {
+                self.set_active(i - 1);
+                true
+            },
+            _ => false
+        }
+    }
+
+    /// Resize the layout as if the parent window had the specified size.
+    ///
+    /// Arguments:
+    ///   w: New width of the layout
+    ///   h: New height of the layout
+    ///
+    ///  Panic:
+    ///   - The layout must have been successfully built otherwise this function will panic.
+    pub fn resize(&self, w: u32, h: u32) {
+        if self.inner.borrow().base.is_null() {
+            panic!("Stack layout is not bound to a parent control.")
+        }
+
+        self.update_layout(w, h);
+    }
+
+    /// Resize the layout to fit the parent window size
+    ///
+    /// Panic:
+    ///   - The layout must have been successfully built otherwise this function will panic.
+    pub fn fit(&self) {
+        let base = self.inner.borrow().base;
+        if base.is_null() {
+            panic!("Stack layout is not bound to a parent control.")
+        }
+
+        let (w, h) = unsafe { wh::get_window_size(base) };
+        self.update_layout(w, h);
+    }
+
+    /// Set the margins of the layout. The four values are in this order: top, right, bottom, left.
+    pub fn margin<M: Into<Margins>>(&self, m: M) {
+        let mut inner = self.inner.borrow_mut();
+        inner.margins = m.into();
+    }
+
+    fn update_layout(&self, width: u32, height: u32) {
+        let inner = self.inner.borrow();
+        if inner.base.is_null() || inner.children.len() == 0 {
+            return;
+        }
+
+        let [m_top, m_right, m_bottom, m_left] = unsafe { inner.margins.to_units(inner.base) };
+        let w = width.saturating_sub(m_left + m_right);
+        let h = height.saturating_sub(m_top + m_bottom);
+
+        // Hidden pages are also resized so that they have the right size when they are shown
+        let mut batch = wh::WindowPositionBatch::new(inner.children.len());
+        for &child in inner.children.iter() {
+            batch.move_window(child, m_left as i32, m_top as i32, w, h);
+        }
+
+        batch.apply();
+    }
+}
+
+impl Default for StackLayout {
+
+    fn default() -> StackLayout {
+        let inner = StackLayoutInner {
+            base: ptr::null_mut(),
+            handler: None,
+            children: Vec::new(),
+            active: None,
+            margins: Margins::Units([0, 0, 0, 0]),
+        };
+
+        StackLayout {
+            inner: Rc::new(RefCell::new(inner))
+        }
+    }
+
+}
+
+
+/// Builder for a `StackLayout` struct
+pub struct StackLayoutBuilder {
+    layout: StackLayoutInner,
+    active: usize,
+}
+
+impl StackLayoutBuilder {
+
+    /// Set the layout parent. The handle must be a window object otherwise the function will panic
+    pub fn parent<W: Into<ControlHandle>>(mut self, p: W) -> StackLayoutBuilder {
+        self.layout.base = p.into().hwnd().expect("Parent must be HWND");
+        self
+    }
+
+    /// Add a page at the end of the layout.
+    /// The handle must be a window object otherwise the function will panic
+    pub fn child<W: Into<ControlHandle>>(mut self, c: W) -> StackLayoutBuilder {
+        let handle = c.into().hwnd().expect("Child must be HWND");
+        self.layout.children.push(handle);
+        self
+    }
+
+    /// Set the margins of the layout. The four values are in this order: top, right, bottom, left.
+    pub fn margin<M: Into<Margins>>(mut self, m: M) -> StackLayoutBuilder {
+        self.layout.margins = m.into();
+        self
+    }
+
+    /// Set the index of the page visible when the layout is built. Default value is 0.
+    pub fn active(mut self, index: usize) -> StackLayoutBuilder {
+        self.active = index;
+        self
+    }
+
+    /// Build the layout object and bind the callback.
+    pub fn build(self, layout: &StackLayout) -> Result<(), NwgError> {
+        if self.layout.base.is_null() {
+            return Err(NwgError::layout_create("StackLayout does not have a parent."));
+        }
+
+        if !self.layout.children.is_empty() && self.active >= self.layout.children.len() {
+            return Err(NwgError::layout_create(format!("The active page index is out of bounds. {} >= {}", self.active, self.layout.children.len())));
+        }
+
+        let (w, h) = unsafe { wh::get_window_size(self.layout.base) };
+        let base_handle = ControlHandle::Hwnd(self.layout.base);
+
+        // Only the active page is visible
+        let mut inner = self.layout;
+        for (index, &child) in inner.children.iter().enumerate() {
+            unsafe { wh::set_window_visibility(child, index == self.active); }
+        }
+
+        if !inner.children.is_empty() {
+            inner.active = Some(self.active);
+        }
+
+        // Saves the new layout. Free the old layout (if there is one)
+        {
+            let mut layout_inner = layout.inner.borrow_mut();
+            if let Some(handler) = layout_inner.handler.as_ref() {
+                drop(unbind_raw_event_handler(handler));
+            }
+
+            *layout_inner = inner;
+        }
+
+        // Initial layout update
+        layout.update_layout(w, h);
+
+        // Keep generating ids so that multiple layouts can be applied to the same parent
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static STACK_LAYOUT_ID: AtomicUsize = AtomicUsize::new(0x1FFF);
+        let handler_id = STACK_LAYOUT_ID.fetch_add(1, Ordering::SeqCst);
+
+        // Bind the event handler
+        let event_layout = layout.clone();
+        let cb = move |w, h| StackLayout::update_layout(&event_layout, w, h);
+
+        {
+            let mut layout_inner = layout.inner.borrow_mut();
+            layout_inner.handler = Some(bind_resize_handler(&base_handle, handler_id, cb)?);
+        }
+
+        Ok(())
+    }
+
+}
@@ -24,21 +24,31 @@ pub use common_types::*;
 
 pub(crate) mod win32;
 pub use win32::{
- dispatch_thread_events, dispatch_thread_events_with_callback, stop_thread_dispatch, enable_visual_styles, init_common_controls, 
+ dispatch_thread_events, dispatch_thread_events_with_callback, dispatch_thread_events_with_exit_code, stop_thread_dispatch, quit,
+ enable_visual_styles, init_common_controls, 
  window::{
-     EventHandler, RawEventHandler,
-     full_bind_event_handler, bind_event_handler, unbind_event_handler,
-     bind_raw_event_handler, has_raw_handler, unbind_raw_event_handler
+     EventHandler, RawEventHandler, EventHandlerGuard, RawEventHandlerGuard,
+     full_bind_event_handler, bind_event_handler, bind_event_handler_with_priority, unbind_event_handler, consume_event,
+     bind_event_handler_guarded, try_full_bind_event_handler, try_bind_event_handler, bind_raw_event_handler, bind_raw_event_handler_guarded, has_raw_handler, unbind_raw_event_handler
  },
  message_box::*
 };
 
 pub(crate) use win32::window::bind_raw_event_handler_inner;
 
+#[doc(hidden)]
+pub use win32::base_helper::{utf16_len as __utf16_len, encode_utf16 as __encode_utf16};
+
 #[allow(deprecated)]
-pub use win32::high_dpi::{set_dpi_awareness, scale_factor, dpi};
+pub use win32::high_dpi::{set_dpi_awareness, scale_factor, dpi, Dip, Extent, Length, Margins};
+
+pub use win32::monitor::{Monitor, MonitorInfo};
+
+pub use win32::ui_thread::{init_thread, spawn_ui_thread, UiThread};
 
-pub use win32::monitor::Monitor;
+pub use win32::command_line::{CommandLine, LaunchKind, command_line, register_application_restart};
+
+pub use win32::keyboard;
 
 #[cfg(feature="cursor")]
 pub use win32::cursor::GlobalCursor;
@@ -46,6 +56,89 @@ pub use win32::cursor::GlobalCursor;
 #[cfg(feature="clipboard")]
 pub use win32::clipboard::{Clipboard, ClipboardFormat, ClipboardData};
 
+#[cfg(feature="resource-tracking")]
+pub use win32::resource_tracker::{ResourceKind, TrackedResource, tracked_resources, report_resource_leaks};
+
+pub use win32::build_profiler::BuildProfile;
+
+#[cfg(feature="dark-mode")]
+pub use win32::dark_mode::{Theme, system_theme, enable_dark_mode, set_window_theme, window_theme};
+
+#[cfg(feature="dirty-tracker")]
+pub use win32::dirty_tracker::DirtyTracker;
+
+#[cfg(feature="backdrop")]
+pub use win32::backdrop::{Backdrop, supported_backdrop, set_window_backdrop, window_backdrop};
+
+#[cfg(feature="file-association")]
+pub use win32::file_assoc;
+
+#[cfg(feature="single-instance")]
+pub use win32::single_instance::SingleInstance;
+
+#[cfg(feature="window-placement")]
+pub use win32::window_placement::WindowPlacement;
+
+#[cfg(feature="window-capture")]
+pub use win32::capture::{WindowCapture, capture_window};
+
+#[cfg(feature="window-snap")]
+pub use win32::window_snap::{WindowSnapper, SnapSide};
+
+#[cfg(feature="process")]
+pub use win32::process;
+
+#[cfg(feature="raw-input")]
+pub use win32::raw_input;
+
+#[cfg(feature="shell")]
+pub use win32::shell;
+
+#[cfg(feature="known-folders")]
+pub use win32::known_folders;
+
+#[cfg(feature="gdiplus")]
+pub use win32::gdiplus;
+
+#[cfg(feature="async-runtime")]
+pub use win32::async_runtime::{spawn_local, spawn_blocking, TaskHandle, BlockingTask};
+
+#[cfg(feature="drag-drop")]
+pub use win32::drag_drop::{DropEffect, DragDropData, DragData, register_drop_target, unregister_drop_target, start_drag};
+
+#[cfg(feature="local-drag")]
+pub use win32::local_drag::{LocalDragData, register_local_drop_target, unregister_local_drop_target, start_local_drag};
+
+#[cfg(feature="touch")]
+pub use win32::touch::{TouchMode, TouchPhase, TouchPoint, Gesture, GestureData};
+
+#[cfg(feature="pointer")]
+pub use win32::pointer::{PointerType, PointerData};
+
+#[cfg(feature="animator")]
+pub use win32::animation::{Easing, Property};
+
+#[cfg(feature="jump-list")]
+pub use win32::jump_list::{JumpList, JumpTask, KnownCategory};
+
+#[cfg(feature="kinetic-scroll")]
+pub use win32::kinetic_scroll::KineticScroll;
+
+#[cfg(feature="taskbar")]
+pub use win32::taskbar::{ProgressState, ThumbnailButton, set_taskbar_progress, taskbar_progress, set_thumbnail_buttons, set_thumbnail_button_enabled, thumbnail_buttons};
+
+#[cfg(feature="tab-order")]
+pub use win32::tab_order::{enable_tab_navigation, set_tab_order, clear_tab_order, tab_order, focus_next, focus_prev};
+
+/// Helpers to test the applications built with NWG
+#[cfg(feature="monkey-test")]
+pub mod test {
+    pub use crate::win32::monkey::{monkey, MonkeyAction, MonkeyReport};
+}
+
+#[cfg(feature="build-profiling")]
+pub use win32::build_profiler::{BuildTiming, build_timings, clear_build_timings, print_build_report};
+
 mod resources;
 pub use resources::*;
 
@@ -61,6 +154,21 @@ mod winnls;
 #[cfg(feature = "winnls")]
 pub use winnls::*;
 
+#[cfg(feature = "settings")]
+mod settings;
+
+#[cfg(feature = "settings")]
+pub use settings::*;
+
+#[cfg(feature = "typed-events")]
+pub mod typed_events;
+
+#[cfg(feature = "async-events")]
+mod event_stream;
+
+#[cfg(feature = "async-events")]
+pub use event_stream::{EventStream, StreamEvent};
+
 /**
     A structure that implements this trait is considered a GUI structure. The structure will hold GUI components and possibly user data.
 
@@ -0,0 +1,219 @@
+use winapi::shared::windef::{HWND, HACCEL, HMENU};
+use winapi::shared::minwindef::WORD;
+use winapi::um::winuser::ACCEL;
+use crate::win32::accelerator;
+use crate::{ControlHandle, HotKeyModifiers, KeyChord, NwgError};
+use std::ptr;
+
+#[cfg(feature = "menu")]
+use crate::MenuItem;
+
+#[cfg(feature = "shortcut-editor")]
+use crate::Shortcut;
+
+
+/**
+An accelerator table binds keyboard shortcuts (Ctrl+S, F5, ...) to the commands of a window.
+
+When a shortcut is pressed while the keyboard focus is in the window (or in one of its children), the events loop
+raises a `OnAccelerator` event on the window with the id of the command. A shortcut bound to a menu item raises the
+`OnMenuItemSelected` event of the item instead, as if the item was clicked, unless the item is disabled.
+
+The accelerators are translated by `dispatch_thread_events` and `dispatch_thread_events_with_callback`, before the
+keyboard navigation of the dialogs. The table is active until it is dropped. An accelerator table cannot be modified:
+build a new table to change the shortcuts (for example with the shortcuts returned by a `ShortcutEditor`).
+
+Requires the `accelerator-table` feature.
+
+**Builder parameters:**
+  * `parent`:       **Required.** The window that receives the commands of the table.
+  * `accelerator`:  Binds a key chord to a command id.
+  * `menu_item`:    Binds a key chord to a menu item. Requires the `menu` feature.
+  * `shortcuts`:    Binds the key chords of a list of shortcuts to their id. Requires the `shortcut-editor` feature.
+
+**Events (on the parent window):**
+  * `OnAccelerator`: When a shortcut bound to a command id is pressed. The event data is the id of the command.
+
+```rust
+use native_windows_gui as nwg;
+use nwg::{KeyChord, HotKeyModifiers, keys};
+
+const SAVE: u16 = 1;
+const REFRESH: u16 = 2;
+
+fn build_accelerators(table: &mut nwg::AcceleratorTable, window: &nwg::Window) -> Result<(), nwg::NwgError> {
+    nwg::AcceleratorTable::builder()
+        .accelerator(SAVE, KeyChord::new(keys::_S, HotKeyModifiers::CONTROL))
+        .accelerator(REFRESH, KeyChord::new(keys::F5, HotKeyModifiers::NONE))
+        .parent(window)
+        .build(table)
+}
+
+fn on_accelerator(evt_data: &nwg::EventData) {
+    match evt_data.on_accelerator() {
+        SAVE => println!("Save"),
+        REFRESH => println!("Refresh"),
+        _ => {}
+    }
+}
+```
+*/
+pub struct AcceleratorTable {
+    pub handle: HACCEL,
+    window: HWND,
+}
+
+impl AcceleratorTable {
+
+    pub fn builder() -> AcceleratorTableBuilder {
+        AcceleratorTableBuilder {
+            accelerators: Vec::new(),
+            menu_items: Vec::new(),
+            parent: None,
+            error: None,
+        }
+    }
+
+    /// Returns the handle of the window that receives the commands of the table
+    pub fn window(&self) -> ControlHandle {
+        ControlHandle::Hwnd(self.window)
+    }
+
+}
+
+impl Default for AcceleratorTable {
+
+    fn default() -> AcceleratorTable {
+        AcceleratorTable {
+            handle: ptr::null_mut(),
+            window: ptr::null_mut(),
+        }
+    }
+
+}
+
+impl PartialEq for AcceleratorTable {
+
+    fn eq(&self, other: &Self) -> bool {
+        self.handle == other.handle
+    }
+
+}
+
+impl Drop for AcceleratorTable {
+
+    fn drop(&mut self) {
+        use winapi::um::winuser::DestroyAcceleratorTable;
+
+        if !self.handle.is_null() {
+            accelerator::unregister(self.handle);
+            unsafe { DestroyAcceleratorTable(self.handle); }
+        }
+    }
+
+}
+
+pub struct AcceleratorTableBuilder {
+    accelerators: Vec<(u16, KeyChord)>,
+    menu_items: Vec<(u16, KeyChord, HMENU)>,
+    parent: Option<ControlHandle>,
+    /// A menu item or a shortcut id that cannot be used as a command id
+    error: Option<String>,
+}
+
+impl AcceleratorTableBuilder {
+
+    /// Binds `chord` to the command `id`. Pressing the chord raises `OnAccelerator` with `id`.
+    pub fn accelerator(mut self, id: u16, chord: KeyChord) -> AcceleratorTableBuilder {
+        self.accelerators.push((id, chord));
+        self
+    }
+
+    /// Binds `chord` to a menu item. Pressing the chord raises `OnMenuItemSelected` on the item.
+    #[cfg(feature = "menu")]
+    pub fn menu_item(mut self, item: &MenuItem, chord: KeyChord) -> AcceleratorTableBuilder {
+        match item.handle.hmenu_item() {
+            Some((menu, id)) if id <= u16::MAX as u32 => self.menu_items.push((id as u16, chord, menu)),
+            Some((_, id)) => { self.error = Some(format!("Menu item id {} cannot be used in an accelerator table", id)); },
+            None => { self.error = Some("Menu item is not bound to a menu".to_string()); }
+        }
+
+        self
+    }
+
+    /// Binds the key chord of each shortcut to the shortcut id. The shortcuts without a key chord are ignored.
+    #[cfg(feature = "shortcut-editor")]
+    pub fn shortcuts(mut self, shortcuts: &[Shortcut]) -> AcceleratorTableBuilder {
+        for shortcut in shortcuts {
+            match (shortcut.chord, shortcut.id <= u16::MAX as usize) {
+                (Some(chord), true) => self.accelerators.push((shortcut.id as u16, chord)),
+                (Some(_), false) => { self.error = Some(format!("Shortcut id {} cannot be used in an accelerator table", shortcut.id)); },
+                (None, _) => {}
+            }
+        }
+
+        self
+    }
+
+    pub fn parent<C: Into<ControlHandle>>(mut self, p: C) -> AcceleratorTableBuilder {
+        self.parent = Some(p.into());
+        self
+    }
+
+    pub fn build(self, out: &mut AcceleratorTable) -> Result<(), NwgError> {
+        use winapi::um::winuser::CreateAcceleratorTableW;
+
+        let window = match self.parent {
+            Some(p) => match p.hwnd() {
+                Some(handle) => Ok(handle),
+                None => Err(NwgError::resource_create("Wrong parent type"))
+            },
+            None => Err(NwgError::no_parent("AcceleratorTable"))
+        }?;
+
+        if let Some(error) = self.error {
+            return Err(NwgError::resource_create(error));
+        }
+
+        let menu_items = &self.menu_items;
+        if let Some((id, _)) = self.accelerators.iter().find(|(id, _)| menu_items.iter().any(|(menu_id, _, _)| menu_id == id)) {
+            return Err(NwgError::resource_create(format!("Accelerator id {} is already used by a menu item", id)));
+        }
+
+        let mut accels: Vec<ACCEL> = self.accelerators.iter()
+            .map(|&(id, chord)| accel(id, chord))
+            .chain(self.menu_items.iter().map(|&(id, chord, _)| accel(id, chord)))
+            .collect();
+
+        *out = Default::default();
+
+        if accels.is_empty() {
+            return Ok(());
+        }
+
+        let handle = unsafe { CreateAcceleratorTableW(accels.as_mut_ptr(), accels.len() as i32) };
+        if handle.is_null() {
+            return Err(NwgError::resource_create("Failed to create the accelerator table"));
+        }
+
+        let menu_items = self.menu_items.iter().map(|&(id, _, menu)| (id, menu)).collect();
+        accelerator::register(window, handle, menu_items);
+
+        out.handle = handle;
+        out.window = window;
+
+        Ok(())
+    }
+
+}
+
+fn accel(id: u16, chord: KeyChord) -> ACCEL {
+    use winapi::um::winuser::{FVIRTKEY, FCONTROL, FSHIFT, FALT};
+
+    let mut flags = FVIRTKEY;
+    if chord.modifiers.contains(HotKeyModifiers::CONTROL) { flags |= FCONTROL; }
+    if chord.modifiers.contains(HotKeyModifiers::SHIFT) { flags |= FSHIFT; }
+    if chord.modifiers.contains(HotKeyModifiers::ALT) { flags |= FALT; }
+
+    ACCEL { fVirt: flags, key: chord.key as WORD, cmd: id }
+}
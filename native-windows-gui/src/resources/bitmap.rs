@@ -1,6 +1,9 @@
 use winapi::um::winnt::HANDLE;
 use winapi::um::winuser::IMAGE_BITMAP;
 use crate::win32::resources_helper as rh;
+#[cfg(feature = "bitmap-pixels")]
+use crate::win32::image_decoder as img;
+use crate::win32::resource_tracker::{self as rt, ResourceKind};
 use crate::{OemBitmap, OemImage, NwgError};
 use std::ptr;
 
@@ -10,8 +13,8 @@ use super::EmbedResource;
 /** 
 A wrapper over a bitmap file (*.bmp)
 
-Note that Bitmap object are only used as display resources (ie: it's impossible to read pixels or resized it).
-If those features are needed, see the `image-decoder` feature.
+Note that Bitmap object are mostly used as display resources. To read or modify the pixels of a bitmap, resize it,
+or save it to a file, see the `bitmap-pixels` feature.
 
 To display a bitmap in an application, see the `ImageFrame` control.
 
@@ -28,7 +31,9 @@ Bitmaps can be converted to icons using the "copy_as_icon" function.
   * `source_embed`:     The source of the bitmap if it is stored in an embedded file
   * `source_embed_id`:  The number identifier of the icon in the embedded file
   * `source_embed_str`: The string identifier of the icon in the embedded file
-  * `size`:             Optional. Resize the image to this size.
+  * `source_svg_file`:  The source of the bitmap if it is a svg file. Requires the `svg` feature.
+  * `source_svg_bin`:   The source of the bitmap if it is a svg document in memory. Requires the `svg` feature.
+  * `size`:             Optional. Resize the image to this size. Required for svg sources, in which case the size is in logical units and scaled with the DPI.
   * `strict`:           Use a system placeholder instead of panicking if the image source do no exists.
 
 Example:
@@ -78,6 +83,12 @@ impl Bitmap {
             #[cfg(feature = "embed-resource")]
             source_embed_str: None,
 
+            #[cfg(feature = "svg")]
+            source_svg_file: None,
+
+            #[cfg(feature = "svg")]
+            source_svg_bin: None,
+
             size: None,
             strict: false
         }
@@ -131,6 +142,50 @@ impl Bitmap {
         Ok(bitmap)
     }
 
+    /**
+        Single line helper function over the bitmap builder api.
+
+        Rasterize a svg document at `size` (in logical units). Because the image is rendered
+        at the final size, it stays sharp at any DPI.
+
+        Requires the `svg` feature and Windows 10 (Creators update) or later.
+    */
+    #[cfg(feature = "svg")]
+    pub fn from_svg(bin: &[u8], size: (u32, u32)) -> Result<Bitmap, NwgError> {
+        let mut bitmap = Bitmap::default();
+
+        Bitmap::builder()
+            .source_svg_bin(Some(bin))
+            .size(Some(size))
+            .build(&mut bitmap)?;
+
+        Ok(bitmap)
+    }
+
+    /**
+        Rasterize a single glyph of an icon font, such as `Segoe MDL2 Assets` (Windows 10) or `Segoe Fluent Icons` (Windows 11),
+        in a square bitmap of `size` (in logical units). The glyph is centered, antialiased and drawn with `color` ([r, g, b])
+        over a transparent background. Because the glyph is rendered at the final size, it stays sharp at any DPI.
+
+        Returns an error if the font is not installed or if it does not contain the glyph.
+
+        ```rust
+        use native_windows_gui as nwg;
+        fn settings_bitmap() -> Result<nwg::Bitmap, nwg::NwgError> {
+            nwg::Bitmap::from_glyph("Segoe MDL2 Assets", '\u{E713}', 16, [0, 0, 0])
+        }
+        ```
+    */
+    pub fn from_glyph(font_family: &str, glyph: char, size: u32, color: [u8; 3]) -> Result<Bitmap, NwgError> {
+        use crate::win32::glyph;
+
+        let size = glyph::physical_size(size);
+        let handle = unsafe { glyph::glyph_bitmap(font_family, glyph, size, color)? };
+        rt::track(ResourceKind::Bitmap, handle);
+
+        Ok(Bitmap { handle, owned: true })
+    }
+
     /**
         Single line helper function over the bitmap builder api.
 
@@ -151,6 +206,52 @@ impl Bitmap {
         Ok(bitmap)
     }
 
+    /**
+        Decodes an image on a worker thread, so that large images do not freeze the events loop.
+        `source` is a file path or the content of a file (see `ImageDecodeSource`), and `size` optionally resizes the image.
+
+        When the image is decoded, `target` (a window) receives a `OnImageDecoded` event. Call `take_bitmap` on the
+        event data to get the bitmap or the decoding error. The returned identifier is the `id` of the event data.
+
+        Returns an error if `target` is not a window.
+
+        Requires the `async-decode` feature.
+
+        ```rust
+        use native_windows_gui as nwg;
+
+        fn load_photo(window: &nwg::Window) -> Result<usize, nwg::NwgError> {
+            nwg::Bitmap::decode_async("photo.jpg", Some((1920, 1080)), window)
+        }
+
+        fn on_event(evt: nwg::Event, data: &nwg::EventData, viewer: &nwg::ImageFrame, photo: &mut nwg::Bitmap) {
+            if evt == nwg::Event::OnImageDecoded {
+                match data.on_image_decoded().take_bitmap() {
+                    Ok(bitmap) => { *photo = bitmap; viewer.set_bitmap(Some(photo)); },
+                    Err(e) => println!("{}", e),
+                }
+            }
+        }
+        ```
+    */
+    #[cfg(feature = "async-decode")]
+    pub fn decode_async<S, C>(source: S, size: Option<(u32, u32)>, target: C) -> Result<usize, NwgError>
+        where S: Into<crate::ImageDecodeSource>,
+              C: Into<crate::ControlHandle>
+    {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(1);
+
+        let target = target.into().hwnd()
+            .ok_or_else(|| NwgError::resource_create("The target of decode_async must be a window"))?;
+
+        let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+        crate::win32::image_decoder::decode_in_background(source.into(), size, target, id);
+
+        Ok(id)
+    }
+
     /**
         Creates a new icon from the bitmap data.
         
@@ -180,6 +281,267 @@ impl Bitmap {
         }
     }
 
+    /**
+        Creates a bitmap from RGBA pixels (4 bytes per pixel, rows from top to bottom) with a straight alpha.
+        The length of `pixels` must be `width * height * 4`.
+
+        Requires the `bitmap-pixels` feature.
+    */
+    #[cfg(feature = "bitmap-pixels")]
+    pub fn from_rgba(width: u32, height: u32, pixels: &[u8]) -> Result<Bitmap, NwgError> {
+        let handle = unsafe { img::bitmap_from_rgba(width, height, pixels)? };
+        rt::track(ResourceKind::Bitmap, handle as HANDLE);
+        Ok(Bitmap { handle: handle as HANDLE, owned: true })
+    }
+
+    /**
+        Returns the size of the bitmap in pixels.
+
+        Panics if the bitmap is not initialized
+
+        Requires the `bitmap-pixels` feature.
+    */
+    #[cfg(feature = "bitmap-pixels")]
+    pub fn size(&self) -> (u32, u32) {
+        if self.handle.is_null() {
+            panic!("Bitmap was not initialized");
+        }
+
+        unsafe { img::bitmap_info(self.handle as _) }
+            .map(|(width, height, _)| (width, height))
+            .unwrap_or((0, 0))
+    }
+
+    /**
+        Returns a copy of the pixels of the bitmap as RGBA values (4 bytes per pixel, rows from top to bottom) with a straight alpha.
+        Bitmaps without an alpha channel are returned as opaque.
+
+        Panics if the bitmap is not initialized
+
+        Requires the `bitmap-pixels` feature.
+    */
+    #[cfg(feature = "bitmap-pixels")]
+    pub fn rgba_pixels(&self) -> Result<Vec<u8>, NwgError> {
+        self.read_rgba().map(|(_, _, pixels)| pixels)
+    }
+
+    /**
+        Replaces the pixels of the bitmap with RGBA values (4 bytes per pixel, rows from top to bottom) with a straight alpha.
+        The length of `pixels` must be `width * height * 4`. The alpha channel is lost if the bitmap has less than 32 bits per pixel.
+
+        Panics if the bitmap is not initialized
+
+        Requires the `bitmap-pixels` feature.
+    */
+    #[cfg(feature = "bitmap-pixels")]
+    pub fn set_rgba_pixels(&self, pixels: &[u8]) -> Result<(), NwgError> {
+        if self.handle.is_null() {
+            panic!("Bitmap was not initialized");
+        }
+
+        unsafe { img::write_rgba(self.handle as _, pixels) }
+    }
+
+    /**
+        Returns a copy of the bitmap resized to `width` x `height` with the selected filter.
+
+        Panics if the bitmap is not initialized
+
+        Requires the `bitmap-pixels` feature.
+    */
+    #[cfg(feature = "bitmap-pixels")]
+    pub fn resize(&self, width: u32, height: u32, filter: ResizeFilter) -> Result<Bitmap, NwgError> {
+        let (w, h, pixels) = self.read_rgba()?;
+        let pixels = unsafe { img::resize_rgba((w, h), &pixels, (width, height), filter.mode())? };
+        Bitmap::from_rgba(width, height, &pixels)
+    }
+
+    /**
+        Returns a copy of the area of the bitmap at `(x, y)` of size `width` x `height`. The area must be inside the bitmap.
+
+        Panics if the bitmap is not initialized
+
+        Requires the `bitmap-pixels` feature.
+    */
+    #[cfg(feature = "bitmap-pixels")]
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Result<Bitmap, NwgError> {
+        let (w, h, pixels) = self.read_rgba()?;
+        if width == 0 || height == 0 || x + width > w || y + height > h {
+            return Err(NwgError::resource_create("The crop area must be inside the bitmap"));
+        }
+
+        let mut cropped = Vec::with_capacity((width * height * 4) as usize);
+        for row in y..(y + height) {
+            let start = ((row * w + x) * 4) as usize;
+            cropped.extend_from_slice(&pixels[start..start + (width * 4) as usize]);
+        }
+
+        Bitmap::from_rgba(width, height, &cropped)
+    }
+
+    /**
+        Returns a copy of the bitmap rotated clockwise.
+
+        Panics if the bitmap is not initialized
+
+        Requires the `bitmap-pixels` feature.
+    */
+    #[cfg(feature = "bitmap-pixels")]
+    pub fn rotate(&self, rotation: BitmapRotation) -> Result<Bitmap, NwgError> {
+        let (w, h, pixels) = self.read_rgba()?;
+        let (nw, nh) = match rotation {
+            BitmapRotation::Rotate180 => (w, h),
+            BitmapRotation::Rotate90 | BitmapRotation::Rotate270 => (h, w),
+        };
+
+        let rotated = map_pixels(&pixels, (w, h), (nw, nh), |x, y| match rotation {
+            BitmapRotation::Rotate90 => (y, h - 1 - x),
+            BitmapRotation::Rotate180 => (w - 1 - x, h - 1 - y),
+            BitmapRotation::Rotate270 => (w - 1 - y, x),
+        });
+
+        Bitmap::from_rgba(nw, nh, &rotated)
+    }
+
+    /**
+        Returns a copy of the bitmap mirrored left to right (`horizontal`) or top to bottom.
+
+        Panics if the bitmap is not initialized
+
+        Requires the `bitmap-pixels` feature.
+    */
+    #[cfg(feature = "bitmap-pixels")]
+    pub fn flip(&self, horizontal: bool) -> Result<Bitmap, NwgError> {
+        let (w, h, pixels) = self.read_rgba()?;
+        let flipped = map_pixels(&pixels, (w, h), (w, h), |x, y| match horizontal {
+            true => (w - 1 - x, y),
+            false => (x, h - 1 - y),
+        });
+
+        Bitmap::from_rgba(w, h, &flipped)
+    }
+
+    /**
+        Encodes the bitmap into the content of an image file.
+
+        Panics if the bitmap is not initialized
+
+        Requires the `bitmap-pixels` feature.
+    */
+    #[cfg(feature = "bitmap-pixels")]
+    pub fn encode(&self, format: ImageEncoding) -> Result<Vec<u8>, NwgError> {
+        use winapi::um::wincodec::{GUID_ContainerFormatPng, GUID_ContainerFormatJpeg, GUID_ContainerFormatBmp};
+
+        let (w, h, pixels) = self.read_rgba()?;
+        unsafe {
+            match format {
+                ImageEncoding::Png => img::encode_rgba((w, h), &pixels, &GUID_ContainerFormatPng, None),
+                ImageEncoding::Jpeg(quality) => img::encode_rgba((w, h), &pixels, &GUID_ContainerFormatJpeg, Some(quality.min(100) as f32 / 100.0)),
+                ImageEncoding::Bmp => img::encode_rgba((w, h), &pixels, &GUID_ContainerFormatBmp, None),
+            }
+        }
+    }
+
+    /**
+        Encodes the bitmap and writes the image in the file at `path`.
+
+        Panics if the bitmap is not initialized
+
+        Requires the `bitmap-pixels` feature.
+    */
+    #[cfg(feature = "bitmap-pixels")]
+    pub fn save(&self, path: &str, format: ImageEncoding) -> Result<(), NwgError> {
+        let data = self.encode(format)?;
+        std::fs::write(path, data)
+            .map_err(|e| NwgError::resource_create(format!("Failed to write {:?}: {}", path, e)))
+    }
+
+    #[cfg(feature = "bitmap-pixels")]
+    fn read_rgba(&self) -> Result<(u32, u32, Vec<u8>), NwgError> {
+        if self.handle.is_null() {
+            panic!("Bitmap was not initialized");
+        }
+
+        unsafe { img::read_rgba(self.handle as _) }
+    }
+
+}
+
+/// The filter used by `Bitmap::resize`. Requires the `bitmap-pixels` feature.
+#[cfg(feature = "bitmap-pixels")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ResizeFilter {
+    /// Copies the nearest pixel. Keeps hard edges (ex: pixel art).
+    Nearest,
+
+    /// Bilinear interpolation
+    Linear,
+
+    /// Bicubic interpolation
+    Cubic,
+
+    /// Averages the covered pixels. Best for downscaling.
+    Fant,
+
+    /// High quality bicubic interpolation. Requires Windows 10.
+    HighQualityCubic,
+}
+
+#[cfg(feature = "bitmap-pixels")]
+impl ResizeFilter {
+    fn mode(self) -> winapi::um::wincodec::WICBitmapInterpolationMode {
+        use winapi::um::wincodec::*;
+
+        match self {
+            ResizeFilter::Nearest => WICBitmapInterpolationModeNearestNeighbor,
+            ResizeFilter::Linear => WICBitmapInterpolationModeLinear,
+            ResizeFilter::Cubic => WICBitmapInterpolationModeCubic,
+            ResizeFilter::Fant => WICBitmapInterpolationModeFant,
+            ResizeFilter::HighQualityCubic => WICBitmapInterpolationModeHighQualityCubic,
+        }
+    }
+}
+
+/// A clockwise rotation used by `Bitmap::rotate`. Requires the `bitmap-pixels` feature.
+#[cfg(feature = "bitmap-pixels")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BitmapRotation {
+    Rotate90,
+    Rotate180,
+    Rotate270,
+}
+
+/// The file format used by `Bitmap::encode` and `Bitmap::save`. Requires the `bitmap-pixels` feature.
+#[cfg(feature = "bitmap-pixels")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ImageEncoding {
+    Png,
+
+    /// A JPEG image with a quality between 0 and 100. JPEG does not support transparency.
+    Jpeg(u8),
+
+    Bmp,
+}
+
+/// Builds a `new_size` image where each pixel is copied from the pixel of `pixels` at the position returned by `source`
+#[cfg(feature = "bitmap-pixels")]
+fn map_pixels<F>(pixels: &[u8], size: (u32, u32), new_size: (u32, u32), source: F) -> Vec<u8>
+    where F: Fn(u32, u32) -> (u32, u32)
+{
+    let (w, _) = size;
+    let (nw, nh) = new_size;
+    let mut out = vec![0u8; (nw * nh * 4) as usize];
+
+    for y in 0..nh {
+        for x in 0..nw {
+            let (sx, sy) = source(x, y);
+            let src = ((sy * w + sx) * 4) as usize;
+            let dst = ((y * nw + x) * 4) as usize;
+            out[dst..dst+4].copy_from_slice(&pixels[src..src+4]);
+        }
+    }
+
+    out
 }
 
 pub struct BitmapBuilder<'a> {
@@ -195,6 +557,12 @@ pub struct BitmapBuilder<'a> {
 
     #[cfg(feature = "embed-resource")]
     source_embed_str: Option<&'a str>,
+
+    #[cfg(feature = "svg")]
+    source_svg_file: Option<&'a str>,
+
+    #[cfg(feature = "svg")]
+    source_svg_bin: Option<&'a [u8]>,
     
     size: Option<(u32, u32)>,
     strict: bool,
@@ -235,6 +603,18 @@ impl<'a> BitmapBuilder<'a> {
         self
     }
 
+    #[cfg(feature = "svg")]
+    pub fn source_svg_file(mut self, t: Option<&'a str>) -> BitmapBuilder<'a> {
+        self.source_svg_file = t;
+        self
+    }
+
+    #[cfg(feature = "svg")]
+    pub fn source_svg_bin(mut self, t: Option<&'a [u8]>) -> BitmapBuilder<'a> {
+        self.source_svg_bin = t;
+        self
+    }
+
     pub fn size(mut self, s: Option<(u32, u32)>) -> BitmapBuilder<'a> {
         self.size = s;
         self
@@ -246,6 +626,29 @@ impl<'a> BitmapBuilder<'a> {
     }
 
     pub fn build(self, b: &mut Bitmap) -> Result<(), NwgError> {
+        #[cfg(feature = "svg")]
+        {
+            use crate::win32::svg;
+
+            if self.source_svg_file.is_some() || self.source_svg_bin.is_some() {
+                let size = self.size
+                    .map(svg::physical_size)
+                    .ok_or_else(|| NwgError::resource_create("A size is required to load a svg bitmap"))?;
+
+                let handle = unsafe {
+                    match (self.source_svg_file, self.source_svg_bin) {
+                        (Some(path), _) => svg::svg_file_bitmap(path, size)?,
+                        (None, Some(src)) => svg::svg_bitmap(src, size)?,
+                        (None, None) => unreachable!(),
+                    }
+                };
+
+                *b = Bitmap { handle, owned: true };
+                rt::track(ResourceKind::Bitmap, b.handle);
+                return Ok(());
+            }
+        }
+
         if let Some(src) = self.source_text {
             let handle = unsafe { 
                 #[cfg(feature="image-decoder")]
@@ -305,6 +708,10 @@ impl<'a> BitmapBuilder<'a> {
             *b = build_embed(self)?;
         }
     
+        if b.owned {
+            rt::track(ResourceKind::Bitmap, b.handle);
+        }
+
         Ok(())
     }
 
@@ -1,6 +1,7 @@
 use winapi::um::winnt::HANDLE;
 use winapi::um::winuser::IMAGE_CURSOR;
 use crate::win32::resources_helper as rh;
+use crate::win32::resource_tracker::{self as rt, ResourceKind};
 use crate::{OemCursor, OemImage, NwgError};
 use std::ptr;
 
@@ -199,6 +200,10 @@ impl<'a> CursorBuilder<'a> {
             *b = build_embed(self)?;
         }
 
+        if b.owned {
+            rt::track(ResourceKind::Cursor, b.handle);
+        }
+
         Ok(())
     }
 
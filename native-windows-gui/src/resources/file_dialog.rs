@@ -1,9 +1,14 @@
 use winapi::um::shobjidl::IFileDialog;
+use winapi::shared::minwindef::BOOL;
 use crate::win32::resources_helper as rh;
 
 use crate::win32::base_helper::to_utf16;
 use crate::{ControlHandle, NwgError};
-use std::{fmt, ptr, mem, ffi::OsString};
+use std::{fmt, ptr, mem, ffi::OsString, path::PathBuf};
+use std::{cell::RefCell, rc::Rc};
+
+/// Added to the id of a custom control to get the id of its visual group
+const GROUP_ID_OFFSET: u32 = 0x8000_0000;
 
 
 /**
@@ -29,8 +34,13 @@ pub enum FileDialogAction {
     * multiselect: Whether the user can select more than one file. Only supported with the Open action
     * default_folder: Default folder to show in the dialog.
     * filters: If defined, filter the files that the user can select (In a Open dialog) or which extension to add to the saved file (in a Save dialog)
+    * places: Folders to add to the sidebar of the dialog
+    * default_extension: Extension added to the saved file if the user does not type one. By default, the extension of the selected filter.
     The `filters` value must be a '|' separated string having this format: "Test(*.txt;*.rs)|Any(*.*)"  
 
+    The dialog can also call back the application while it runs: `on_selection_changed` when the user selects another item,
+    and `on_file_ok` before the dialog is accepted (the callback can refuse the selection and keep the dialog open).
+
     ```rust
         use native_windows_gui as nwg;
         fn layout(dialog: &mut nwg::FileDialog) {
@@ -38,13 +48,46 @@ pub enum FileDialogAction {
                 .title("Hello")
                 .action(nwg::FileDialogAction::Open)
                 .multiselect(true)
+                .place("C:\\Projects")
                 .build(dialog);
+
+            dialog.on_file_ok(|paths| paths.iter().all(|p| p.extension().is_some()));
+        }
+    ```
+
+    Custom controls (check boxes, combo boxes, text boxes) can be added to the dialog before it runs, and read after it closes:
+
+    ```rust
+        use native_windows_gui as nwg;
+        const ENCODING: u32 = 1;
+
+        fn save_as(dialog: &nwg::FileDialog, window: &nwg::Window) -> Result<Option<(std::path::PathBuf, usize)>, nwg::NwgError> {
+            dialog.add_combo_box(ENCODING, "Encoding", &["UTF-8", "UTF-16", "ANSI"])?;
+
+            if !dialog.run(Some(window)) {
+                return Ok(None);
+            }
+
+            let path = dialog.get_selected_paths()?.remove(0);
+            let encoding = dialog.combo_box_selection(ENCODING)?.unwrap_or(0);
+            Ok(Some((path, encoding)))
         }
     ```
 */
 pub struct FileDialog {
     handle: *mut IFileDialog,
-    action: FileDialogAction
+    action: FileDialogAction,
+    callbacks: Rc<RefCell<FileDialogCallbacks>>,
+    cookie: Option<u32>,
+}
+
+/// The callbacks of a file dialog, called by its `IFileDialogEvents` implementation
+#[derive(Default)]
+pub(crate) struct FileDialogCallbacks {
+    pub selection_changed: Option<Rc<dyn Fn(Option<PathBuf>)>>,
+    pub file_ok: Option<Rc<dyn Fn(&[PathBuf]) -> bool>>,
+    /// The default extension of each filter, set when the user selects another filter
+    pub extensions: Vec<Option<String>>,
 }
 
 impl FileDialog {
@@ -55,7 +98,9 @@ impl FileDialog {
             action: FileDialogAction::Save,
             multiselect: false,
             default_folder: None,
-            filters: None
+            filters: None,
+            places: Vec::new(),
+            default_extension: None,
         }
     }
 
@@ -119,6 +164,20 @@ impl FileDialog {
         }
     }
 
+    /**
+        Return the paths selected by the user. Works with every action: a save dialog or a dialog without
+        the `multiselect` flag returns a single path.
+
+        Failures:  
+        • if the dialog was not called  
+        • if there was a system error while reading the selected items  
+    */
+    pub fn get_selected_paths(&self) -> Result<Vec<PathBuf>, NwgError> {
+        unsafe {
+            rh::file_dialog_paths(&mut *self.handle)
+        }
+    }
+
     /// Return `true` if the dialog accepts multiple values or `false` otherwise
     pub fn multiselect(&self) -> bool {
         use winapi::um::shobjidl::FOS_ALLOWMULTISELECT;
@@ -173,8 +232,183 @@ impl FileDialog {
     pub fn set_filters<'a>(&self, filters: &'a str) -> Result<(), NwgError> {
         unsafe{ 
             let handle = &mut *self.handle;
-            rh::file_dialog_set_filters(handle, &filters) 
+            rh::file_dialog_set_filters(handle, &filters)?;
         }
+
+        self.callbacks.borrow_mut().extensions = rh::file_dialog_filter_extensions(filters);
+
+        Ok(())
+    }
+
+    /**
+        Add a folder to the sidebar of the dialog. If `top` is true, the folder is added before the default places.
+        Failures:
+        • if the folder do not exists  
+    */
+    pub fn add_place<'a>(&self, folder: &'a str, top: bool) -> Result<(), NwgError> {
+        unsafe {
+            rh::file_dialog_add_place(&mut *self.handle, folder, top)
+        }
+    }
+
+    /**
+        Set the extension added to the saved file if the user does not type one (ex: "txt", without the dot).
+        By default, the dialog uses the extension of the selected filter. `None` removes the default extension.
+        The extension is replaced when the user selects another filter.
+    */
+    pub fn set_default_extension<'a>(&self, extension: Option<&'a str>) -> Result<(), NwgError> {
+        unsafe {
+            rh::file_dialog_set_default_extension(&mut *self.handle, extension)
+        }
+    }
+
+    /**
+        Set a callback called when the user selects another item in the dialog, with the path of the item.
+        The path is `None` if the item is not in the file system or if the selection is empty.
+        Replaces the previous callback.
+    */
+    pub fn on_selection_changed<F: Fn(Option<PathBuf>) + 'static>(&self, callback: F) {
+        self.callbacks.borrow_mut().selection_changed = Some(Rc::new(callback));
+    }
+
+    /**
+        Set a callback called when the user accepts the dialog, with the selected paths.
+        If the callback returns `false`, the dialog stays open and `run` does not return. 
+        The callback should explain to the user why the selection was refused (ex: with a message box).
+        Replaces the previous callback.
+    */
+    pub fn on_file_ok<F: Fn(&[PathBuf]) -> bool + 'static>(&self, callback: F) {
+        self.callbacks.borrow_mut().file_ok = Some(Rc::new(callback));
+    }
+
+    /**
+        Add a check box to the dialog. `id` identifies the control in the other methods and must be unique in the dialog.
+        The ids starting at `0x80000000` are reserved by NWG.
+        The custom controls must be added before calling `run`. Their values can be read after `run` returns.
+    */
+    pub fn add_check_box<'a>(&self, id: u32, label: &'a str, checked: bool) -> Result<(), NwgError> {
+        let label = to_utf16(label);
+        unsafe { rh::file_dialog_customize(&mut *self.handle, |c| c.AddCheckButton(id, label.as_ptr(), checked as BOOL)) }
+    }
+
+    /**
+        Add a combo box to the dialog. The items are identified by their index. The first item is selected.
+        If `label` is not empty, the combo box is placed in a group with the label.
+    */
+    pub fn add_combo_box<'a>(&self, id: u32, label: &'a str, items: &[&'a str]) -> Result<(), NwgError> {
+        self.start_group(id, label, |dialog| unsafe {
+            rh::file_dialog_customize(&mut *dialog.handle, |c| c.AddComboBox(id))?;
+
+            for (index, item) in items.iter().enumerate() {
+                let item = to_utf16(item);
+                rh::file_dialog_customize(&mut *dialog.handle, |c| c.AddControlItem(id, index as u32, item.as_ptr()))?;
+            }
+
+            match items.is_empty() {
+                true => Ok(()),
+                false => dialog.set_combo_box_selection(id, 0)
+            }
+        })
+    }
+
+    /**
+        Add a text box to the dialog.
+        If `label` is not empty, the text box is placed in a group with the label.
+    */
+    pub fn add_text_box<'a>(&self, id: u32, label: &'a str, text: &'a str) -> Result<(), NwgError> {
+        let text = to_utf16(text);
+        self.start_group(id, label, |dialog| unsafe {
+            rh::file_dialog_customize(&mut *dialog.handle, |c| c.AddEditBox(id, text.as_ptr()))
+        })
+    }
+
+    /// Add a static text to the dialog
+    pub fn add_label<'a>(&self, id: u32, text: &'a str) -> Result<(), NwgError> {
+        let text = to_utf16(text);
+        unsafe { rh::file_dialog_customize(&mut *self.handle, |c| c.AddText(id, text.as_ptr())) }
+    }
+
+    /// Add a separator to the dialog
+    pub fn add_separator(&self, id: u32) -> Result<(), NwgError> {
+        unsafe { rh::file_dialog_customize(&mut *self.handle, |c| c.AddSeparator(id)) }
+    }
+
+    /// Enable or disable a custom control. A disabled control is still visible.
+    pub fn set_control_enabled(&self, id: u32, enabled: bool) -> Result<(), NwgError> {
+        use winapi::um::shobjidl::{CDCS_ENABLEDVISIBLE, CDCS_VISIBLE};
+
+        let state = match enabled {
+            true => CDCS_ENABLEDVISIBLE,
+            false => CDCS_VISIBLE
+        };
+
+        unsafe { rh::file_dialog_customize(&mut *self.handle, |c| c.SetControlState(id, state)) }
+    }
+
+    /// Return the state of a check box added with `add_check_box`
+    pub fn check_box_checked(&self, id: u32) -> Result<bool, NwgError> {
+        let mut checked: BOOL = 0;
+        unsafe { rh::file_dialog_customize(&mut *self.handle, |c| c.GetCheckButtonState(id, &mut checked))?; }
+        Ok(checked != 0)
+    }
+
+    /// Set the state of a check box added with `add_check_box`
+    pub fn set_check_box_checked(&self, id: u32, checked: bool) -> Result<(), NwgError> {
+        unsafe { rh::file_dialog_customize(&mut *self.handle, |c| c.SetCheckButtonState(id, checked as BOOL)) }
+    }
+
+    /// Return the index of the selected item of a combo box added with `add_combo_box`, or `None` if no item is selected
+    pub fn combo_box_selection(&self, id: u32) -> Result<Option<usize>, NwgError> {
+        use winapi::shared::winerror::S_OK;
+
+        let mut index = 0;
+        let mut selected = false;
+        unsafe {
+            // `GetSelectedControlItem` fails if there is no selection
+            rh::file_dialog_customize(&mut *self.handle, |c| {
+                selected = c.GetSelectedControlItem(id, &mut index) == S_OK;
+                S_OK
+            })?;
+        }
+
+        Ok(match selected {
+            true => Some(index as usize),
+            false => None
+        })
+    }
+
+    /// Select an item of a combo box added with `add_combo_box`
+    pub fn set_combo_box_selection(&self, id: u32, index: usize) -> Result<(), NwgError> {
+        unsafe { rh::file_dialog_customize(&mut *self.handle, |c| c.SetSelectedControlItem(id, index as u32)) }
+    }
+
+    /// Return the text of a text box added with `add_text_box`
+    pub fn text_box_text(&self, id: u32) -> Result<String, NwgError> {
+        unsafe { rh::file_dialog_edit_box_text(&mut *self.handle, id) }
+    }
+
+    /// Set the text of a text box added with `add_text_box`
+    pub fn set_text_box_text<'a>(&self, id: u32, text: &'a str) -> Result<(), NwgError> {
+        let text = to_utf16(text);
+        unsafe { rh::file_dialog_customize(&mut *self.handle, |c| c.SetEditBoxText(id, text.as_ptr())) }
+    }
+
+    /// Places the control added by `f` in a visual group if `label` is not empty. The group uses the id of the control plus `GROUP_ID_OFFSET`.
+    fn start_group<'a, F>(&self, id: u32, label: &'a str, f: F) -> Result<(), NwgError> 
+        where F: FnOnce(&FileDialog) -> Result<(), NwgError>
+    {
+        if label.is_empty() {
+            return f(self);
+        }
+
+        let label = to_utf16(label);
+        unsafe { rh::file_dialog_customize(&mut *self.handle, |c| c.StartVisualGroup(id.wrapping_add(GROUP_ID_OFFSET), label.as_ptr()))?; }
+
+        let result = f(self);
+
+        unsafe { rh::file_dialog_customize(&mut *self.handle, |c| c.EndVisualGroup())?; }
+
+        result
     }
 
     /// Change the dialog title
@@ -209,7 +443,26 @@ impl Default for FileDialog {
     fn default() -> FileDialog {
         FileDialog {
             handle: ptr::null_mut(),
-            action: FileDialogAction::Open
+            action: FileDialogAction::Open,
+            callbacks: Default::default(),
+            cookie: None,
+        }
+    }
+}
+
+impl Drop for FileDialog {
+    fn drop(&mut self) {
+        if self.handle.is_null() {
+            return;
+        }
+
+        unsafe {
+            let handle = &mut *self.handle;
+            if let Some(cookie) = self.cookie {
+                handle.Unadvise(cookie);
+            }
+
+            handle.Release();
         }
     }
 }
@@ -230,7 +483,9 @@ pub struct FileDialogBuilder {
     pub action: FileDialogAction,
     pub multiselect: bool,
     pub default_folder: Option<String>,
-    pub filters: Option<String>
+    pub filters: Option<String>,
+    pub places: Vec<String>,
+    pub default_extension: Option<String>,
 }
 
 impl FileDialogBuilder {
@@ -250,6 +505,17 @@ impl FileDialogBuilder {
         self
     }
 
+    /// Add a folder at the top of the sidebar of the dialog. Can be called more than once.
+    pub fn place<S: Into<String>>(mut self, folder: S) -> FileDialogBuilder {
+        self.places.push(folder.into());
+        self
+    }
+
+    pub fn default_extension<S: Into<String>>(mut self, ext: S) -> FileDialogBuilder {
+        self.default_extension = Some(ext.into());
+        self
+    }
+
     pub fn action(mut self, a: FileDialogAction) -> FileDialogBuilder {
         self.action = a;
         self
@@ -261,16 +527,44 @@ impl FileDialogBuilder {
     }
 
     pub fn build(self, out: &mut FileDialog) -> Result<(), NwgError> {
-        unsafe {
-            out.handle = rh::create_file_dialog(
+        let extensions = match (&self.filters, self.action) {
+            (Some(filters), FileDialogAction::Open) | (Some(filters), FileDialogAction::Save) => rh::file_dialog_filter_extensions(filters),
+            _ => Vec::new()
+        };
+
+        let handle = unsafe {
+            rh::create_file_dialog(
                 self.action,
                 self.multiselect,
                 self.default_folder,
                 self.filters
-            )?;
+            )?
+        };
+
+        let callbacks = Rc::new(RefCell::new(FileDialogCallbacks { extensions, ..Default::default() }));
+
+        // Replacing `out` releases the previous dialog
+        *out = FileDialog {
+            handle,
+            action: self.action,
+            callbacks: callbacks.clone(),
+            cookie: None,
+        };
+
+        out.cookie = Some(unsafe { rh::file_dialog_advise(&mut *out.handle, callbacks)? });
+
+        for place in self.places.iter().rev() {
+            out.add_place(place, true)?;
         }
 
-        out.action = self.action;
+        let default_extension = match self.default_extension {
+            Some(ext) => Some(ext),
+            None => out.callbacks.borrow().extensions.first().cloned().flatten()
+        };
+
+        if default_extension.is_some() {
+            out.set_default_extension(default_extension.as_ref().map(|e| e.as_str()))?;
+        }
         
         if let Some(title) = self.title {
             out.set_title(&title);
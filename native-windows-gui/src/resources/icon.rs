@@ -1,6 +1,7 @@
 use winapi::um::winnt::HANDLE;
 use winapi::um::winuser::IMAGE_ICON;
 use crate::win32::resources_helper as rh;
+use crate::win32::resource_tracker::{self as rt, ResourceKind};
 use crate::{OemImage, OemIcon, NwgError};
 use std::ptr;
 
@@ -26,7 +27,9 @@ Note: Loading an icon from binary source (source_bin) REQUIRES the "image-decode
   * `source_embed`:     The source of the icon if it is stored in an embedded file
   * `source_embed_id`:  The number identifier of the icon in the embedded file
   * `source_embed_str`: The string identifier of the icon in the embedded file
-  * `size`:             Optional. Resize the image to this size.
+  * `source_svg_file`:  The source of the icon if it is a svg file. Requires the `svg` feature.
+  * `source_svg_bin`:   The source of the icon if it is a svg document in memory. Requires the `svg` feature.
+  * `size`:             Optional. Resize the image to this size. Required for svg sources, in which case the size is in logical units and scaled with the DPI.
   * `strict`:           Use a system placeholder instead of panicking if the image source do no exists.
 
 Example:
@@ -73,6 +76,12 @@ impl Icon {
             #[cfg(feature = "embed-resource")]
             source_embed_str: None,
 
+            #[cfg(feature = "svg")]
+            source_svg_file: None,
+
+            #[cfg(feature = "svg")]
+            source_svg_bin: None,
+
             size: None,
             strict: false
         }
@@ -126,6 +135,50 @@ impl Icon {
         Ok(icon)
     }
 
+    /**
+        Single line helper function over the icon builder api.
+
+        Rasterize a svg document at `size` (in logical units). Because the image is rendered
+        at the final size, it stays sharp at any DPI.
+
+        Requires the `svg` feature and Windows 10 (Creators update) or later.
+    */
+    #[cfg(feature = "svg")]
+    pub fn from_svg(bin: &[u8], size: (u32, u32)) -> Result<Icon, NwgError> {
+        let mut icon = Icon::default();
+
+        Icon::builder()
+            .source_svg_bin(Some(bin))
+            .size(Some(size))
+            .build(&mut icon)?;
+
+        Ok(icon)
+    }
+
+    /**
+        Rasterize a single glyph of an icon font, such as `Segoe MDL2 Assets` (Windows 10) or `Segoe Fluent Icons` (Windows 11),
+        in a square icon of `size` (in logical units). The glyph is centered, antialiased and drawn with `color` ([r, g, b])
+        over a transparent background. Because the glyph is rendered at the final size, it stays sharp at any DPI.
+
+        Returns an error if the font is not installed or if it does not contain the glyph.
+
+        ```rust
+        use native_windows_gui as nwg;
+        fn settings_icon() -> Result<nwg::Icon, nwg::NwgError> {
+            nwg::Icon::from_glyph("Segoe MDL2 Assets", '\u{E713}', 16, [0, 0, 0])
+        }
+        ```
+    */
+    pub fn from_glyph(font_family: &str, glyph: char, size: u32, color: [u8; 3]) -> Result<Icon, NwgError> {
+        use crate::win32::glyph;
+
+        let size = glyph::physical_size(size);
+        let handle = unsafe { glyph::glyph_icon(font_family, glyph, size, color)? };
+        rt::track(ResourceKind::Icon, handle);
+
+        Ok(Icon { handle, owned: true })
+    }
+
     /**
         Single line helper function over the icon builder api.
 
@@ -162,6 +215,12 @@ pub struct IconBuilder<'a> {
     #[cfg(feature = "embed-resource")]
     source_embed_str: Option<&'a str>,
 
+    #[cfg(feature = "svg")]
+    source_svg_file: Option<&'a str>,
+
+    #[cfg(feature = "svg")]
+    source_svg_bin: Option<&'a [u8]>,
+
     size: Option<(u32, u32)>,
     strict: bool,
 }
@@ -201,6 +260,18 @@ impl<'a> IconBuilder<'a> {
         self
     }
 
+    #[cfg(feature = "svg")]
+    pub fn source_svg_file(mut self, t: Option<&'a str>) -> IconBuilder<'a> {
+        self.source_svg_file = t;
+        self
+    }
+
+    #[cfg(feature = "svg")]
+    pub fn source_svg_bin(mut self, t: Option<&'a [u8]>) -> IconBuilder<'a> {
+        self.source_svg_bin = t;
+        self
+    }
+
     pub fn size(mut self, s: Option<(u32, u32)>) -> IconBuilder<'a> {
         self.size = s;
         self
@@ -212,6 +283,29 @@ impl<'a> IconBuilder<'a> {
     }
 
     pub fn build(self, b: &mut Icon) -> Result<(), NwgError> {
+        #[cfg(feature = "svg")]
+        {
+            use crate::win32::svg;
+
+            if self.source_svg_file.is_some() || self.source_svg_bin.is_some() {
+                let size = self.size
+                    .map(svg::physical_size)
+                    .ok_or_else(|| NwgError::resource_create("A size is required to load a svg icon"))?;
+
+                let handle = unsafe {
+                    match (self.source_svg_file, self.source_svg_bin) {
+                        (Some(path), _) => svg::svg_file_icon(path, size)?,
+                        (None, Some(src)) => svg::svg_icon(src, size)?,
+                        (None, None) => unreachable!(),
+                    }
+                };
+
+                *b = Icon { handle, owned: true };
+                rt::track(ResourceKind::Icon, b.handle);
+                return Ok(());
+            }
+        }
+
         if let Some(src) = self.source_text {
             let handle = unsafe { rh::build_image(src, self.size, self.strict, IMAGE_ICON)? };
             *b = Icon { handle, owned: true };
@@ -245,6 +339,10 @@ impl<'a> IconBuilder<'a> {
             *b = build_embed(self)?;
         }
     
+        if b.owned {
+            rt::track(ResourceKind::Icon, b.handle);
+        }
+
         Ok(())
     }
 
@@ -0,0 +1,348 @@
+use winapi::shared::windef::{HWND, HICON};
+use winapi::um::commctrl::ImageList_ReplaceIcon;
+use winapi::um::winuser::{DestroyIcon, PostMessageW, WM_APP};
+use crate::win32::window::{create_message_window, bind_raw_event_handler_inner, unbind_raw_event_handler, RawEventHandler};
+use crate::win32::base_helper::to_utf16;
+use crate::{ControlHandle, Icon, ImageList, NwgError};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, mpsc, atomic::{AtomicBool, Ordering}};
+use std::{cell::RefCell, rc::Rc, mem, ptr, thread};
+
+/// Id of the raw event handler that receives the loaded icons
+const ICON_CACHE_HANDLER_ID: usize = 0x1C0C;
+
+/// Message posted to the message window when new icons are loaded
+const WM_ICONS_LOADED: u32 = WM_APP + 0x1C0;
+
+/// The extensions of the files that have their own icon. Those icons are cached by path instead of by extension.
+const PER_FILE_EXTENSIONS: &[&str] = &["exe", "ico", "lnk", "cur", "ani", "url", "msc", "scr"];
+
+const FILE_ATTRIBUTE_NORMAL: u32 = 0x80;
+const FILE_ATTRIBUTE_DIRECTORY: u32 = 0x10;
+
+
+/// The size of the icons of an `IconCache`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IconCacheSize {
+    /// The size of the small icons of the system (usually 16x16)
+    Small,
+
+    /// The size of the large icons of the system (usually 32x32)
+    Large,
+}
+
+/// An icon to load on the worker thread
+struct IconRequest {
+    key: String,
+    path: String,
+    /// Loads the icon of the file type from the extension, without accessing the file
+    by_extension: bool,
+}
+
+/// The data shared with the worker thread
+struct Shared {
+    /// The loaded icons, by key. The icons are sent as integers because a HICON cannot be sent between threads.
+    loaded: Mutex<Vec<(String, usize)>>,
+    /// Set when a `WM_ICONS_LOADED` message is waiting in the queue
+    posted: AtomicBool,
+    window: usize,
+}
+
+struct IconCacheInner {
+    indices: HashMap<String, i32>,
+    placeholder: HICON,
+    callback: Option<Rc<dyn Fn(i32)>>,
+}
+
+/**
+An icon cache loads the shell icons of files (the icon of the file type, or the icon of the file itself for the
+executables and the shortcuts) into a single image list, and keeps one image per file type. File listings can use it
+instead of creating an icon for each item.
+
+`IconCache::icon` returns the index of an icon in `image_list` immediately. If the icon was not loaded yet, the index
+shows a placeholder (the generic file icon) while the icon is loaded on a worker thread. When the icon is ready,
+the image is replaced and the `on_icon_loaded` callback is called with the index so that the controls can be redrawn.
+
+A single cache can be shared by all the list views of the application. The list views must then be built with
+`ListViewFlags::SHARE_IMAGE_LISTS`, otherwise the first list view destroyed also destroys the image list.
+
+Requires the `icon-cache` feature.
+
+**Builder parameters:**
+  * `size`:        The size of the icons. `IconCacheSize::Small` by default.
+  * `placeholder`: The icon shown while an icon is loaded. The generic file icon by default.
+
+```rust
+use native_windows_gui as nwg;
+use std::rc::Rc;
+
+fn list_files(list: &Rc<nwg::ListView>, cache: &nwg::IconCache, files: &[String]) {
+    list.set_image_list(Some(&cache.image_list), nwg::ListViewImageListType::Small);
+
+    for (i, file) in files.iter().enumerate() {
+        let image = cache.icon(file);
+        list.insert_item(nwg::InsertListViewItem { index: Some(i as i32), text: Some(file.clone()), image: Some(image), column_index: 0 });
+    }
+
+    let list = list.clone();
+    cache.on_icon_loaded(move |_index| list.invalidate());
+}
+```
+*/
+pub struct IconCache {
+    /// The image list that contains the icons. Owned by the cache.
+    pub image_list: ImageList,
+    inner: Rc<RefCell<IconCacheInner>>,
+    sender: Option<mpsc::Sender<IconRequest>>,
+    window: Option<ControlHandle>,
+    handler: Option<RawEventHandler>,
+}
+
+impl IconCache {
+
+    pub fn builder<'a>() -> IconCacheBuilder<'a> {
+        IconCacheBuilder {
+            size: IconCacheSize::Small,
+            placeholder: None,
+        }
+    }
+
+    /**
+        Returns the index of the icon of the file at `path` in `image_list`. The file does not have to exist,
+        except for the executables, the icons and the shortcuts, which have their own icon.
+
+        If the icon is not cached yet, the index shows the placeholder until the icon is loaded.
+    */
+    pub fn icon(&self, path: &str) -> i32 {
+        let extension = Path::new(path).extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .unwrap_or_default();
+
+        match PER_FILE_EXTENSIONS.contains(&extension.as_str()) {
+            true => self.request(path.to_lowercase(), path.to_string(), false),
+            false => self.request(format!(".{}", extension), format!("file.{}", extension), true),
+        }
+    }
+
+    /// Returns the index of the icon of the folders in `image_list`
+    pub fn folder_icon(&self) -> i32 {
+        self.request("<folder>".to_string(), "folder".to_string(), true)
+    }
+
+    /// Returns the number of icons in the cache
+    pub fn len(&self) -> usize {
+        self.inner.borrow().indices.len()
+    }
+
+    /// Sets the callback called with the index of an icon when it is loaded. Replaces the previous callback.
+    pub fn on_icon_loaded<F>(&self, callback: F)
+        where F: Fn(i32) + 'static
+    {
+        self.inner.borrow_mut().callback = Some(Rc::new(callback));
+    }
+
+    fn request(&self, key: String, path: String, by_extension: bool) -> i32 {
+        let mut inner = self.inner.borrow_mut();
+        if let Some(&index) = inner.indices.get(&key) {
+            return index;
+        }
+
+        // Reserve the index of the icon with a copy of the placeholder
+        let index = unsafe { ImageList_ReplaceIcon(self.image_list.handle, -1, inner.placeholder) };
+        inner.indices.insert(key.clone(), index);
+
+        if let Some(sender) = self.sender.as_ref() {
+            drop(sender.send(IconRequest { key, path, by_extension }));
+        }
+
+        index
+    }
+
+}
+
+impl Drop for IconCache {
+    fn drop(&mut self) {
+        // Closing the channel stops the worker thread
+        self.sender = None;
+
+        if let Some(handler) = self.handler.take() {
+            drop(unbind_raw_event_handler(&handler));
+        }
+
+        if let Some(mut window) = self.window.take() {
+            window.destroy();
+        }
+
+        let inner = self.inner.borrow();
+        if !inner.placeholder.is_null() {
+            unsafe { DestroyIcon(inner.placeholder); }
+        }
+    }
+}
+
+pub struct IconCacheBuilder<'a> {
+    size: IconCacheSize,
+    placeholder: Option<&'a Icon>,
+}
+
+impl<'a> IconCacheBuilder<'a> {
+
+    pub fn size(mut self, size: IconCacheSize) -> IconCacheBuilder<'a> {
+        self.size = size;
+        self
+    }
+
+    pub fn placeholder(mut self, placeholder: Option<&'a Icon>) -> IconCacheBuilder<'a> {
+        self.placeholder = placeholder;
+        self
+    }
+
+    pub fn build(self, cache: &mut IconCache) -> Result<(), NwgError> {
+        use winapi::um::winuser::{GetSystemMetrics, CopyIcon, SM_CXSMICON, SM_CYSMICON, SM_CXICON, SM_CYICON};
+        use winapi::um::shellapi::{SHGFI_SMALLICON, SHGFI_LARGEICON};
+
+        let (size, shell_flag) = unsafe {
+            match self.size {
+                IconCacheSize::Small => ((GetSystemMetrics(SM_CXSMICON), GetSystemMetrics(SM_CYSMICON)), SHGFI_SMALLICON),
+                IconCacheSize::Large => ((GetSystemMetrics(SM_CXICON), GetSystemMetrics(SM_CYICON)), SHGFI_LARGEICON),
+            }
+        };
+
+        let mut image_list = ImageList::default();
+        ImageList::builder()
+            .size(size)
+            .initial(32)
+            .grow(32)
+            .build(&mut image_list)?;
+
+        let placeholder = match self.placeholder {
+            Some(icon) => unsafe { CopyIcon(icon.handle as HICON) },
+            None => load_shell_icon("file", FILE_ATTRIBUTE_NORMAL, shell_flag, true),
+        };
+
+        let window = create_message_window("")?;
+        let hwnd = window.hwnd().unwrap();
+
+        let shared = Arc::new(Shared {
+            loaded: Mutex::new(Vec::new()),
+            posted: AtomicBool::new(false),
+            window: hwnd as usize,
+        });
+
+        let (sender, receiver) = mpsc::channel();
+        spawn_loader(receiver, shared.clone(), shell_flag);
+
+        let inner = Rc::new(RefCell::new(IconCacheInner {
+            indices: HashMap::new(),
+            placeholder,
+            callback: None,
+        }));
+
+        let handler_inner = inner.clone();
+        let list_handle = image_list.handle as usize;
+        let handler = bind_raw_event_handler_inner(&window, ICON_CACHE_HANDLER_ID, move |_hwnd, msg, _w, _l| {
+            if msg != WM_ICONS_LOADED {
+                return None;
+            }
+
+            shared.posted.store(false, Ordering::SeqCst);
+            let loaded: Vec<(String, usize)> = shared.loaded.lock().unwrap().drain(..).collect();
+
+            for (key, icon) in loaded {
+                let index = handler_inner.borrow().indices.get(&key).copied();
+                let icon = icon as HICON;
+
+                if let Some(index) = index {
+                    unsafe { ImageList_ReplaceIcon(list_handle as _, index, icon); }
+
+                    // The callback is cloned so that it can replace the callback of the cache
+                    let callback = handler_inner.borrow().callback.clone();
+                    if let Some(callback) = callback {
+                        callback(index);
+                    }
+                }
+
+                unsafe { DestroyIcon(icon); }
+            }
+
+            Some(0)
+        })?;
+
+        *cache = IconCache {
+            image_list,
+            inner,
+            sender: Some(sender),
+            window: Some(window),
+            handler: Some(handler),
+        };
+
+        Ok(())
+    }
+
+}
+
+impl Default for IconCache {
+
+    fn default() -> IconCache {
+        IconCache {
+            image_list: ImageList::default(),
+            inner: Rc::new(RefCell::new(IconCacheInner { indices: HashMap::new(), placeholder: ptr::null_mut(), callback: None })),
+            sender: None,
+            window: None,
+            handler: None,
+        }
+    }
+
+}
+
+
+/// Loads the icons requested by the cache until the cache is dropped
+fn spawn_loader(receiver: mpsc::Receiver<IconRequest>, shared: Arc<Shared>, shell_flag: u32) {
+    use winapi::um::objbase::CoInitialize;
+    use winapi::um::combaseapi::CoUninitialize;
+
+    thread::spawn(move || {
+        // The shell icon handlers are COM objects
+        unsafe { CoInitialize(ptr::null_mut()); }
+
+        for request in receiver {
+            let attributes = match request.key.as_str() {
+                "<folder>" => FILE_ATTRIBUTE_DIRECTORY,
+                _ => FILE_ATTRIBUTE_NORMAL,
+            };
+
+            let icon = load_shell_icon(&request.path, attributes, shell_flag, request.by_extension);
+            if icon.is_null() {
+                continue;
+            }
+
+            shared.loaded.lock().unwrap().push((request.key, icon as usize));
+            if !shared.posted.swap(true, Ordering::SeqCst) {
+                unsafe { PostMessageW(shared.window as HWND, WM_ICONS_LOADED, 0, 0); }
+            }
+        }
+
+        unsafe { CoUninitialize(); }
+    });
+}
+
+/// Loads the shell icon of a file. With `by_extension`, the file is not accessed and only its name and its attributes are used.
+fn load_shell_icon(path: &str, attributes: u32, shell_flag: u32, by_extension: bool) -> HICON {
+    use winapi::um::shellapi::{SHGetFileInfoW, SHFILEINFOW, SHGFI_ICON, SHGFI_USEFILEATTRIBUTES};
+
+    let path = to_utf16(path);
+    let mut flags = SHGFI_ICON | shell_flag;
+    if by_extension {
+        flags |= SHGFI_USEFILEATTRIBUTES;
+    }
+
+    unsafe {
+        let mut info: SHFILEINFOW = mem::zeroed();
+        match SHGetFileInfoW(path.as_ptr(), attributes, &mut info, mem::size_of::<SHFILEINFOW>() as u32, flags) {
+            0 => ptr::null_mut(),
+            _ => info.hIcon
+        }
+    }
+}
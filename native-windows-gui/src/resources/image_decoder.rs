@@ -269,10 +269,111 @@ pub enum ContainerFormat {
     Wmp,
 }
 
+/// The source of an image decoded by `Bitmap::decode_async`
+#[cfg(feature = "async-decode")]
+#[derive(Clone, Debug)]
+pub enum ImageDecodeSource {
+    /// The path of an image file
+    File(String),
+
+    /// The content of an image file
+    Bytes(Vec<u8>),
+}
+
+#[cfg(feature = "async-decode")]
+impl<'a> From<&'a str> for ImageDecodeSource {
+    fn from(path: &'a str) -> ImageDecodeSource {
+        ImageDecodeSource::File(path.to_string())
+    }
+}
+
+#[cfg(feature = "async-decode")]
+impl From<String> for ImageDecodeSource {
+    fn from(path: String) -> ImageDecodeSource {
+        ImageDecodeSource::File(path)
+    }
+}
+
+#[cfg(feature = "async-decode")]
+impl<'a> From<&'a [u8]> for ImageDecodeSource {
+    fn from(data: &'a [u8]) -> ImageDecodeSource {
+        ImageDecodeSource::Bytes(data.to_vec())
+    }
+}
+
+#[cfg(feature = "async-decode")]
+impl From<Vec<u8>> for ImageDecodeSource {
+    fn from(data: Vec<u8>) -> ImageDecodeSource {
+        ImageDecodeSource::Bytes(data)
+    }
+}
+
+/**
+    The result of a `Bitmap::decode_async` call, sent with the `OnImageDecoded` event.
+
+    The bitmap must be taken with `take_bitmap` during the event. If it is not taken, the bitmap is freed after the event.
+*/
+#[cfg(feature = "async-decode")]
+#[derive(Debug)]
+pub struct DecodedImage {
+    id: usize,
+    result: std::cell::RefCell<Option<Result<winapi::um::winnt::HANDLE, NwgError>>>,
+}
+
+#[cfg(feature = "async-decode")]
+impl DecodedImage {
+
+    pub(crate) fn new(id: usize, result: Result<winapi::um::winnt::HANDLE, NwgError>) -> DecodedImage {
+        DecodedImage { id, result: std::cell::RefCell::new(Some(result)) }
+    }
+
+    /// The identifier returned by the `Bitmap::decode_async` call that decoded this image
+    pub fn id(&self) -> usize {
+        self.id
+    }
+
+    /// Returns `true` if the image was decoded successfully and the bitmap was not taken yet
+    pub fn is_ok(&self) -> bool {
+        match *self.result.borrow() {
+            Some(Ok(_)) => true,
+            _ => false
+        }
+    }
+
+    /**
+        Takes the decoded bitmap, or the error that happened while decoding the image.
+        Returns an error if the result was already taken.
+    */
+    pub fn take_bitmap(&self) -> Result<Bitmap, NwgError> {
+        use crate::win32::resource_tracker::{self as rt, ResourceKind};
+
+        match self.result.borrow_mut().take() {
+            Some(Ok(handle)) => {
+                rt::track(ResourceKind::Bitmap, handle);
+                Ok(Bitmap { handle, owned: true })
+            },
+            Some(Err(e)) => Err(e),
+            None => Err(NwgError::resource_create("The decoded image was already taken"))
+        }
+    }
+
+}
+
 //
 // IMPL
 //
 
+#[cfg(feature = "async-decode")]
+impl Drop for DecodedImage {
+    fn drop(&mut self) {
+        use winapi::um::wingdi::DeleteObject;
+
+        if let Some(Ok(handle)) = self.result.borrow_mut().take() {
+            unsafe { DeleteObject(handle as _); }
+        }
+    }
+}
+
 impl Default for ImageDecoder {
     fn default() -> ImageDecoder {
         ImageDecoder {
@@ -1,7 +1,9 @@
 use winapi::um::commctrl::{HIMAGELIST, ImageList_AddMasked};
 use winapi::shared::windef::{HICON, HBITMAP};
+use crate::win32::base_helper::to_utf16;
+use crate::win32::resource_tracker::{self as rt, ResourceKind};
 use crate::{Bitmap, Icon, NwgError};
-use std::ptr;
+use std::{mem, ptr};
 
 
 const NOT_BOUND: &'static str = "ImageList is not yet bound to a winapi object";
@@ -30,6 +32,35 @@ fn build_image_list(list: &mut nwg::ImageList) {
 }
 ```
 
+An image list can also be built from a sprite sheet: a single bitmap that holds all the images in a grid.
+
+```rust
+use native_windows_gui as nwg;
+fn build_toolbar_icons() -> Result<nwg::ImageList, nwg::NwgError> {
+    let sheet = nwg::Bitmap::from_file("toolbar.png", true)?;
+    nwg::ImageList::from_sprite_sheet(&sheet, (24, 24), None)
+}
+```
+
+The system image list, which holds the shell icons of the files, can be used with `ImageList::from_shell_icons`.
+`ImageList::shell_icon_index` returns the index of the icon of a file in this list. Unlike the other image lists,
+the system image list is shared by the whole process and is never destroyed.
+
+```rust
+use native_windows_gui as nwg;
+fn list_files(list: &nwg::ListView, files: &[&str]) -> Result<(), nwg::NwgError> {
+    let icons = nwg::ImageList::from_shell_icons(nwg::ShellIconSize::Small)?;
+    list.set_image_list(Some(&icons), nwg::ListViewImageListType::Small);
+
+    for (i, file) in files.iter().enumerate() {
+        let image = nwg::ImageList::shell_icon_index(file)?;
+        list.insert_item(nwg::InsertListViewItem { index: Some(i as i32), text: Some(file.to_string()), image: Some(image), column_index: 0 });
+    }
+
+    Ok(())
+}
+```
+
 */
 pub struct ImageList {
     pub handle: HIMAGELIST,
@@ -46,6 +77,144 @@ impl ImageList {
         }
     }
 
+    /**
+        Returns the system image list of the shell icons at a given size. The icons are added to the list
+        by `ImageList::shell_icon_index`. The returned list is not owned and is never destroyed.
+
+        Controls that display the list must not destroy it (ex: a list view must use `ListViewFlags::SHARE_IMAGE_LISTS`).
+    */
+    pub fn from_shell_icons(size: ShellIconSize) -> Result<ImageList, NwgError> {
+        use winapi::um::shellapi::SHGetImageList;
+        use winapi::um::commoncontrols::IImageList;
+        use winapi::shared::winerror::S_OK;
+        use winapi::Interface;
+
+        // The system image list lives as long as the process. The reference is never released.
+        let mut list: *mut IImageList = ptr::null_mut();
+        let result = unsafe { SHGetImageList(size.shell_list() as _, &IImageList::uuidof(), &mut list as *mut *mut IImageList as _) };
+        match result {
+            S_OK => Ok(ImageList { handle: list as HIMAGELIST, owned: false }),
+            e => Err(NwgError::resource_create(format!("Failed to get the system image list (error {:#X})", e)))
+        }
+    }
+
+    /**
+        Returns the index of the shell icon of a file in the system image lists (see `ImageList::from_shell_icons`).
+        The index is the same for every size. The icon depends on the extension of the file, or on the file itself
+        for the executables and the shortcuts.
+
+        Returns an error if the file does not exist.
+    */
+    pub fn shell_icon_index(path: &str) -> Result<i32, NwgError> {
+        use winapi::um::shellapi::{SHGetFileInfoW, SHFILEINFOW, SHGFI_SYSICONINDEX};
+
+        let path_w = to_utf16(path);
+        unsafe {
+            let mut info: SHFILEINFOW = mem::zeroed();
+            match SHGetFileInfoW(path_w.as_ptr(), 0, &mut info, mem::size_of::<SHFILEINFOW>() as u32, SHGFI_SYSICONINDEX) {
+                0 => Err(NwgError::resource_create(format!("Failed to get the icon of {:?}", path))),
+                _ => Ok(info.iIcon)
+            }
+        }
+    }
+
+    /**
+        Creates an image list with images of `cell_size` by slicing a sprite sheet. See `add_sprite_sheet`.
+    */
+    pub fn from_sprite_sheet(sheet: &Bitmap, cell_size: (i32, i32), mask_color: Option<[u8; 3]>) -> Result<ImageList, NwgError> {
+        let mut list = ImageList::default();
+        ImageList::builder()
+            .size(cell_size)
+            .build(&mut list)?;
+
+        list.add_sprite_sheet(sheet, mask_color)?;
+
+        Ok(list)
+    }
+
+    /**
+        Slices a sprite sheet (a single bitmap containing a grid of images) in cells of the size of the image list
+        and adds the cells to the list, from left to right then from top to bottom. The incomplete cells on the right and
+        bottom edges of the sheet are ignored.
+
+        If `mask_color` is set, the pixels of this color are transparent. Otherwise the alpha channel of the sheet is kept
+        (ex: a PNG loaded with the `image-decoder` feature).
+
+        Returns the index of the first added image. Panics if the bitmap was not initialized
+    */
+    pub fn add_sprite_sheet(&self, sheet: &Bitmap, mask_color: Option<[u8; 3]>) -> Result<i32, NwgError> {
+        use winapi::um::commctrl::ImageList_Add;
+        use winapi::um::wingdi::{GetObjectW, CreateCompatibleDC, CreateDIBSection, SelectObject, DeleteDC, DeleteObject, BitBlt, RGB,
+            BITMAP, BITMAPINFO, BITMAPINFOHEADER, RGBQUAD, BI_RGB, DIB_RGB_COLORS, SRCCOPY};
+
+        if self.handle.is_null() { panic!("{}", NOT_BOUND); }
+        if sheet.handle.is_null() { panic!("Bitmap was not initialized"); }
+
+        let (cw, ch) = self.size();
+
+        unsafe {
+            let mut info: BITMAP = mem::zeroed();
+            let info_size = mem::size_of::<BITMAP>() as i32;
+            if GetObjectW(sheet.handle, info_size, &mut info as *mut BITMAP as _) != info_size {
+                return Err(NwgError::resource_create("The sprite sheet is not a bitmap"));
+            }
+
+            let (columns, rows) = (info.bmWidth / cw, info.bmHeight.abs() / ch);
+            if columns == 0 || rows == 0 {
+                return Err(NwgError::resource_create("The sprite sheet is smaller than the image list size"));
+            }
+
+            // Each row of the sheet is copied in a strip. A strip wider than the image list size is added as multiple images.
+            let strip_info = BITMAPINFO {
+                bmiHeader: BITMAPINFOHEADER {
+                    biSize: mem::size_of::<BITMAPINFOHEADER>() as u32,
+                    biWidth: columns * cw, biHeight: -ch,
+                    biPlanes: 1, biBitCount: 32, biCompression: BI_RGB,
+                    biSizeImage: 0,
+                    biXPelsPerMeter: 0, biYPelsPerMeter: 0,
+                    biClrUsed: 0, biClrImportant: 0
+                },
+                bmiColors: [RGBQUAD { rgbBlue: 0, rgbGreen: 0, rgbRed: 0, rgbReserved: 0 }],
+            };
+
+            let sheet_dc = CreateCompatibleDC(ptr::null_mut());
+            let strip_dc = CreateCompatibleDC(ptr::null_mut());
+            let old_sheet = SelectObject(sheet_dc, sheet.handle as _);
+
+            let mut first = None;
+            for row in 0..rows {
+                let mut bits = ptr::null_mut();
+                let strip = CreateDIBSection(strip_dc, &strip_info, DIB_RGB_COLORS, &mut bits, ptr::null_mut(), 0);
+                if strip.is_null() {
+                    break;
+                }
+
+                let old_strip = SelectObject(strip_dc, strip as _);
+                BitBlt(strip_dc, 0, 0, columns * cw, ch, sheet_dc, 0, row * ch, SRCCOPY);
+                SelectObject(strip_dc, old_strip);
+
+                let index = match mask_color {
+                    Some([r, g, b]) => ImageList_AddMasked(self.handle, strip, RGB(r, g, b)),
+                    None => ImageList_Add(self.handle, strip, ptr::null_mut())
+                };
+
+                DeleteObject(strip as _);
+
+                if index < 0 {
+                    break;
+                }
+
+                first = first.or(Some(index));
+            }
+
+            SelectObject(sheet_dc, old_sheet);
+            DeleteDC(strip_dc);
+            DeleteDC(sheet_dc);
+
+            first.ok_or_else(|| NwgError::resource_create("Failed to add the sprite sheet to the image list"))
+        }
+    }
+
     /// Returns the size of the images in the image list
     pub fn size(&self) -> (i32, i32) {
         use winapi::um::commctrl::ImageList_GetIconSize;
@@ -181,6 +350,56 @@ impl ImageList {
 
 }
 
+/// The sizes of the shell icons. The sizes in pixels are the sizes at 100% scaling.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShellIconSize {
+    /// 16x16
+    Small,
+    /// 32x32
+    Large,
+    /// 48x48
+    ExtraLarge,
+    /// 256x256. The file types without a jumbo icon show their large icon in the middle of the image.
+    Jumbo,
+}
+
+impl ShellIconSize {
+
+    fn shell_list(&self) -> u32 {
+        use winapi::um::shellapi::{SHIL_SMALL, SHIL_LARGE, SHIL_EXTRALARGE, SHIL_JUMBO};
+
+        match self {
+            ShellIconSize::Small => SHIL_SMALL,
+            ShellIconSize::Large => SHIL_LARGE,
+            ShellIconSize::ExtraLarge => SHIL_EXTRALARGE,
+            ShellIconSize::Jumbo => SHIL_JUMBO,
+        }
+    }
+
+}
+
+/**
+    Returns the shell icon of a file at a given size, like the explorer shows it.
+    The icon depends on the extension of the file, or on the file itself for the executables and the shortcuts.
+
+    Returns an error if the file does not exist.
+*/
+pub fn shell_icon_for_path(path: &str, size: ShellIconSize) -> Result<Icon, NwgError> {
+    use winapi::um::commctrl::{ImageList_GetIcon, ILD_TRANSPARENT};
+
+    let list = ImageList::from_shell_icons(size)?;
+    let index = ImageList::shell_icon_index(path)?;
+
+    let handle = unsafe { ImageList_GetIcon(list.handle, index, ILD_TRANSPARENT) };
+    if handle.is_null() {
+        return Err(NwgError::resource_create(format!("Failed to get the icon of {:?}", path)));
+    }
+
+    rt::track(ResourceKind::Icon, handle);
+
+    Ok(Icon { handle: handle as _, owned: true })
+}
+
 impl Drop for ImageList {
     fn drop(&mut self) {
         use winapi::um::commctrl::ImageList_Destroy;
@@ -22,17 +22,31 @@ mod image_list;
 #[cfg(feature = "embed-resource")]
 mod embed;
 
+#[cfg(feature = "accelerator-table")]
+mod accelerator_table;
+
+#[cfg(feature = "icon-cache")]
+mod icon_cache;
+
 pub use font::{Font, MemFont, FontInfo, FontBuilder};
 pub use system_images::*;
 pub use icon::{Icon, IconBuilder};
 pub use cursor::{Cursor, CursorBuilder};
 pub use bitmap::{Bitmap, BitmapBuilder};
 
+#[cfg(feature = "bitmap-pixels")]
+pub use bitmap::{ResizeFilter, BitmapRotation, ImageEncoding};
+
 #[cfg(feature = "image-decoder")]
 pub use image_decoder::{ImageDecoder, ImageSource, ImageData, ImageDecoderBuilder, ContainerFormat};
 
+#[cfg(feature = "async-decode")]
+pub use image_decoder::{ImageDecodeSource, DecodedImage};
+
 #[cfg(feature = "file-dialog")]
 pub use file_dialog::{FileDialog, FileDialogAction, FileDialogBuilder};
+#[cfg(feature = "file-dialog")]
+pub(crate) use file_dialog::FileDialogCallbacks;
 
 #[cfg(feature = "color-dialog")]
 pub use color_dialog::{ColorDialog, ColorDialogBuilder};
@@ -41,8 +55,14 @@ pub use color_dialog::{ColorDialog, ColorDialogBuilder};
 pub use font_dialog::{FontDialog, FontDialogBuilder};
 
 #[cfg(feature = "image-list")]
-pub use image_list::{ImageList, ImageListBuilder};
+pub use image_list::{ImageList, ImageListBuilder, ShellIconSize, shell_icon_for_path};
 
 #[cfg(feature = "embed-resource")]
 pub use embed::*;
 
+#[cfg(feature = "accelerator-table")]
+pub use accelerator_table::{AcceleratorTable, AcceleratorTableBuilder};
+
+#[cfg(feature = "icon-cache")]
+pub use icon_cache::{IconCache, IconCacheBuilder, IconCacheSize};
+
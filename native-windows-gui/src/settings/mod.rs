@@ -0,0 +1,91 @@
+mod registry;
+pub use registry::*;
+
+mod settings_dialog;
+pub use settings_dialog::*;
+
+#[cfg(feature = "shortcut-editor")]
+mod shortcut_editor;
+
+#[cfg(feature = "shortcut-editor")]
+pub use shortcut_editor::*;
+
+
+/**
+    A value that can be stored by the settings module
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SettingValue {
+    Bool(bool),
+    Int(i32),
+    Text(String),
+}
+
+/**
+    Describe a single setting. `key` is the name of the value in the settings store
+    and `label` is the text displayed next to its input in the settings dialog.
+*/
+#[derive(Clone, Debug)]
+pub struct SettingEntry {
+    pub key: String,
+    pub label: String,
+    pub default: SettingValue,
+}
+
+impl SettingEntry {
+    pub fn new<'a>(key: &'a str, label: &'a str, default: SettingValue) -> SettingEntry {
+        SettingEntry { key: key.to_string(), label: label.to_string(), default }
+    }
+}
+
+/**
+    A named group of settings. Each page is displayed on its own in the settings dialog.
+*/
+#[derive(Clone, Debug)]
+pub struct SettingsPage {
+    pub name: String,
+    pub entries: Vec<SettingEntry>,
+}
+
+impl SettingsPage {
+    pub fn new<'a>(name: &'a str, entries: Vec<SettingEntry>) -> SettingsPage {
+        SettingsPage { name: name.to_string(), entries }
+    }
+}
+
+/**
+    Describe the settings of an application.
+
+    `pages` is called once when a `SettingsDialog` is built. `applied` is called each time
+    the values of the dialog are saved to the settings store.
+
+    ```rust
+    use native_windows_gui as nwg;
+    use nwg::{Settings, SettingsPage, SettingEntry, SettingValue};
+
+    struct AppSettings;
+
+    impl Settings for AppSettings {
+        fn pages(&self) -> Vec<SettingsPage> {
+            vec![
+                SettingsPage::new("General", vec![
+                    SettingEntry::new("show_toolbar", "Show toolbar", SettingValue::Bool(true)),
+                    SettingEntry::new("user_name", "User name", SettingValue::Text(String::new())),
+                ]),
+                SettingsPage::new("Editor", vec![
+                    SettingEntry::new("tab_size", "Tab size", SettingValue::Int(4)),
+                ]),
+            ]
+        }
+    }
+    ```
+*/
+pub trait Settings {
+
+    /// Return the settings pages of the application
+    fn pages(&self) -> Vec<SettingsPage>;
+
+    /// Called after the values were written to the settings store
+    fn applied(&self, _values: &[(String, SettingValue)]) {}
+
+}
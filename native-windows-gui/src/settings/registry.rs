@@ -0,0 +1,160 @@
+use winapi::shared::minwindef::{HKEY, DWORD};
+use winapi::shared::winerror::ERROR_SUCCESS;
+use winapi::um::winnt::{KEY_READ, KEY_WRITE, REG_OPTION_NON_VOLATILE, REG_SZ, REG_DWORD};
+use winapi::um::winreg::{HKEY_CURRENT_USER, RegCreateKeyExW, RegQueryValueExW, RegSetValueExW, RegDeleteValueW, RegCloseKey};
+use crate::win32::base_helper::{to_utf16, from_utf16};
+use crate::NwgError;
+use super::SettingValue;
+use std::{mem, ptr};
+
+
+/**
+    A registry key under `HKEY_CURRENT_USER` used to persist application settings.
+    The key is created if it does not exist. The handle is closed when the value is dropped.
+
+    Booleans and integers are stored as `REG_DWORD` and text as `REG_SZ`.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn settings() -> Result<(), nwg::NwgError> {
+        let store = nwg::RegistrySettings::open("Software\\MyCompany\\MyApp")?;
+        store.write("show_toolbar", &nwg::SettingValue::Bool(true))?;
+
+        let show = store.read_as("show_toolbar", nwg::SettingValue::Bool(false));
+        Ok(())
+    }
+    ```
+*/
+pub struct RegistrySettings {
+    path: String,
+    key: HKEY,
+}
+
+impl RegistrySettings {
+
+    /// Open (or create) the key `path` under `HKEY_CURRENT_USER`.
+    pub fn open<'a>(path: &'a str) -> Result<RegistrySettings, NwgError> {
+        let path_w = to_utf16(path);
+        let mut key: HKEY = ptr::null_mut();
+
+        let result = unsafe {
+            RegCreateKeyExW(
+                HKEY_CURRENT_USER,
+                path_w.as_ptr(),
+                0,
+                ptr::null_mut(),
+                REG_OPTION_NON_VOLATILE,
+                KEY_READ | KEY_WRITE,
+                ptr::null_mut(),
+                &mut key,
+                ptr::null_mut()
+            )
+        };
+
+        match result as DWORD {
+            ERROR_SUCCESS => Ok(RegistrySettings { path: path.to_string(), key }),
+            code => Err(NwgError::settings(format!("Failed to open registry key {:?} (error {})", path, code)))
+        }
+    }
+
+    /// Return the path of the key relative to `HKEY_CURRENT_USER`
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Read the value `name`. Returns `None` if the value does not exist or if it is not a `REG_DWORD` or a `REG_SZ`.
+    /// `REG_DWORD` values are always returned as `SettingValue::Int`. Use `read_as` to get the value in the expected type.
+    pub fn read<'a>(&self, name: &'a str) -> Option<SettingValue> {
+        let name = to_utf16(name);
+        let mut value_type: DWORD = 0;
+        let mut size: DWORD = 0;
+
+        unsafe {
+            let result = RegQueryValueExW(self.key, name.as_ptr(), ptr::null_mut(), &mut value_type, ptr::null_mut(), &mut size);
+            if result as DWORD != ERROR_SUCCESS {
+                return None;
+            }
+
+            match value_type {
+                REG_DWORD => {
+                    let mut value: DWORD = 0;
+                    let mut size = mem::size_of::<DWORD>() as DWORD;
+                    let result = RegQueryValueExW(self.key, name.as_ptr(), ptr::null_mut(), ptr::null_mut(), &mut value as *mut DWORD as _, &mut size);
+                    match result as DWORD {
+                        ERROR_SUCCESS => Some(SettingValue::Int(value as i32)),
+                        _ => None
+                    }
+                },
+                REG_SZ => {
+                    let mut buffer: Vec<u16> = vec![0; (size as usize / 2) + 1];
+                    let mut size = (buffer.len() * 2) as DWORD;
+                    let result = RegQueryValueExW(self.key, name.as_ptr(), ptr::null_mut(), ptr::null_mut(), buffer.as_mut_ptr() as _, &mut size);
+                    match result as DWORD {
+                        ERROR_SUCCESS => Some(SettingValue::Text(from_utf16(&buffer))),
+                        _ => None
+                    }
+                },
+                _ => None
+            }
+        }
+    }
+
+    /// Read the value `name` and convert it to the type of `default`.
+    /// Returns `default` if the value does not exist or cannot be converted.
+    pub fn read_as<'a>(&self, name: &'a str, default: SettingValue) -> SettingValue {
+        match (self.read(name), default) {
+            (Some(SettingValue::Int(v)), SettingValue::Bool(_)) => SettingValue::Bool(v != 0),
+            (Some(SettingValue::Int(v)), SettingValue::Int(_)) => SettingValue::Int(v),
+            (Some(SettingValue::Text(v)), SettingValue::Text(_)) => SettingValue::Text(v),
+            (_, default) => default
+        }
+    }
+
+    /// Write `value` under `name`, replacing any existing value.
+    pub fn write<'a>(&self, name: &'a str, value: &SettingValue) -> Result<(), NwgError> {
+        let name_w = to_utf16(name);
+
+        let result = unsafe {
+            match value {
+                SettingValue::Bool(v) => {
+                    let v = *v as DWORD;
+                    RegSetValueExW(self.key, name_w.as_ptr(), 0, REG_DWORD, &v as *const DWORD as _, mem::size_of::<DWORD>() as DWORD)
+                },
+                SettingValue::Int(v) => {
+                    let v = *v as DWORD;
+                    RegSetValueExW(self.key, name_w.as_ptr(), 0, REG_DWORD, &v as *const DWORD as _, mem::size_of::<DWORD>() as DWORD)
+                },
+                SettingValue::Text(v) => {
+                    let v = to_utf16(v);
+                    RegSetValueExW(self.key, name_w.as_ptr(), 0, REG_SZ, v.as_ptr() as _, (v.len() * 2) as DWORD)
+                }
+            }
+        };
+
+        match result as DWORD {
+            ERROR_SUCCESS => Ok(()),
+            code => Err(NwgError::settings(format!("Failed to write registry value {:?} (error {})", name, code)))
+        }
+    }
+
+    /// Remove the value `name` from the key. Removing a value that does not exist is not an error.
+    pub fn delete<'a>(&self, name: &'a str) -> Result<(), NwgError> {
+        use winapi::shared::winerror::ERROR_FILE_NOT_FOUND;
+
+        let name_w = to_utf16(name);
+        let result = unsafe { RegDeleteValueW(self.key, name_w.as_ptr()) };
+
+        match result as DWORD {
+            ERROR_SUCCESS | ERROR_FILE_NOT_FOUND => Ok(()),
+            code => Err(NwgError::settings(format!("Failed to delete registry value {:?} (error {})", name, code)))
+        }
+    }
+
+}
+
+impl Drop for RegistrySettings {
+    fn drop(&mut self) {
+        unsafe { RegCloseKey(self.key); }
+    }
+}
@@ -0,0 +1,309 @@
+use crate::win32::window_helper as wh;
+use crate::{PartialUi, NwgError, ControlHandle, Event, EventData};
+use crate::{ListBox, Frame, FrameFlags, Label, CheckBox, CheckBoxState, TextInput, Button, HTextAlign};
+use super::{Settings, SettingEntry, SettingValue, RegistrySettings};
+use std::cell::RefCell;
+
+
+/// The input used to edit a setting in a `SettingsDialog`
+pub enum SettingInput {
+    Check(CheckBox),
+    Text(TextInput),
+}
+
+/**
+    A partial that displays the settings of an application grouped by pages.
+
+    The page names are listed on the left side of the parent. Selecting a name shows the matching page.
+    Boolean settings are edited with a check box and integer/text settings with a text input.
+
+    The values are loaded from a `RegistrySettings` store when the partial is built. "Apply" writes
+    the modified values back to the store and "Revert" restores the last saved values.
+    Both buttons are only enabled when the dialog holds unsaved changes.
+
+    The controls are positioned using the size of the parent at build time.
+
+    ```rust
+    use native_windows_gui as nwg;
+    use nwg::PartialUi;
+
+    fn build_settings<S: nwg::Settings + 'static>(window: &nwg::Window, settings: S) -> Result<nwg::SettingsDialog, nwg::NwgError> {
+        let store = nwg::RegistrySettings::open("Software\\MyCompany\\MyApp")?;
+        let mut dialog = nwg::SettingsDialog::new(settings, store);
+        nwg::SettingsDialog::build_partial(&mut dialog, Some(window))?;
+        Ok(dialog)
+    }
+    ```
+*/
+#[derive(Default)]
+pub struct SettingsDialog {
+    settings: Option<Box<dyn Settings>>,
+    store: Option<RegistrySettings>,
+    entries: Vec<SettingEntry>,
+    saved: RefCell<Vec<SettingValue>>,
+
+    pub pages_list: ListBox<String>,
+    pub pages: Vec<Frame>,
+    pub labels: Vec<Label>,
+    pub inputs: Vec<SettingInput>,
+    pub apply_button: Button,
+    pub revert_button: Button,
+}
+
+impl SettingsDialog {
+
+    pub fn new<S: Settings + 'static>(settings: S, store: RegistrySettings) -> SettingsDialog {
+        SettingsDialog {
+            settings: Some(Box::new(settings)),
+            store: Some(store),
+            ..Default::default()
+        }
+    }
+
+    /// Return the last saved value of the setting `key`
+    pub fn value<'a>(&self, key: &'a str) -> Option<SettingValue> {
+        let saved = self.saved.borrow();
+        self.entries.iter()
+            .position(|e| e.key == key)
+            .map(|i| saved[i].clone())
+    }
+
+    /// Return `true` if the values in the inputs differ from the saved values
+    pub fn is_dirty(&self) -> bool {
+        let saved = self.saved.borrow();
+        (0..self.entries.len()).any(|i| self.input_value(i).as_ref() != Some(&saved[i]))
+    }
+
+    /// Write the values of the inputs to the settings store.
+    /// Returns an error and saves nothing if an integer input does not hold a valid number.
+    pub fn apply(&self) -> Result<(), NwgError> {
+        let mut values = Vec::with_capacity(self.entries.len());
+        for (i, entry) in self.entries.iter().enumerate() {
+            match self.input_value(i) {
+                Some(v) => values.push((entry.key.clone(), v)),
+                None => { return Err(NwgError::settings(format!("{:?} is not a valid number", entry.label))); }
+            }
+        }
+
+        if let Some(store) = self.store.as_ref() {
+            for (key, value) in values.iter() {
+                store.write(key, value)?;
+            }
+        }
+
+        *self.saved.borrow_mut() = values.iter().map(|(_, v)| v.clone()).collect();
+
+        if let Some(settings) = self.settings.as_ref() {
+            settings.applied(&values);
+        }
+
+        self.update_buttons();
+
+        Ok(())
+    }
+
+    /// Restore the last saved values in the inputs
+    pub fn revert(&self) {
+        let saved = self.saved.borrow();
+        for (input, value) in self.inputs.iter().zip(saved.iter()) {
+            match (input, value) {
+                (SettingInput::Check(c), SettingValue::Bool(v)) => c.set_check_state(check_state(*v)),
+                (SettingInput::Text(t), SettingValue::Int(v)) => t.set_text(&v.to_string()),
+                (SettingInput::Text(t), SettingValue::Text(v)) => t.set_text(v),
+                _ => {}
+            }
+        }
+
+        drop(saved);
+        self.update_buttons();
+    }
+
+    /// Show the page at `index` and hide the others
+    pub fn show_page(&self, index: usize) {
+        for (i, page) in self.pages.iter().enumerate() {
+            page.set_visible(i == index);
+        }
+    }
+
+    fn input_value(&self, index: usize) -> Option<SettingValue> {
+        match (&self.inputs[index], &self.entries[index].default) {
+            (SettingInput::Check(c), _) => Some(SettingValue::Bool(c.check_state() == CheckBoxState::Checked)),
+            (SettingInput::Text(t), SettingValue::Int(_)) => t.text().trim().parse::<i32>().ok().map(SettingValue::Int),
+            (SettingInput::Text(t), _) => Some(SettingValue::Text(t.text())),
+        }
+    }
+
+    fn update_buttons(&self) {
+        let dirty = self.is_dirty();
+        self.apply_button.set_enabled(dirty);
+        self.revert_button.set_enabled(dirty);
+    }
+
+}
+
+impl PartialUi for SettingsDialog {
+
+    fn build_partial<W: Into<ControlHandle>>(data: &mut SettingsDialog, parent: Option<W>) -> Result<(), NwgError> {
+        let parent = match parent {
+            Some(p) => p.into(),
+            None => { return Err(NwgError::no_parent("SettingsDialog")); }
+        };
+
+        let (width, height) = match parent.hwnd() {
+            Some(hwnd) => unsafe { wh::get_window_size(hwnd) },
+            None => { return Err(NwgError::control_create("SettingsDialog parent must be a window")); }
+        };
+        let (width, height) = (width as i32, height as i32);
+
+        let pages = match data.settings.as_ref() {
+            Some(settings) => settings.pages(),
+            None => Vec::new(),
+        };
+
+        let page_size = (width - 180, height - 60);
+
+        ListBox::builder()
+            .position((10, 10))
+            .size((150, height - 60))
+            .collection(pages.iter().map(|p| p.name.clone()).collect())
+            .selected_index(if pages.is_empty() { None } else { Some(0) })
+            .parent(&parent)
+            .build(&mut data.pages_list)?;
+
+        data.entries.clear();
+        data.pages.clear();
+        data.labels.clear();
+        data.inputs.clear();
+
+        let mut saved = Vec::new();
+
+        for (page_index, page) in pages.into_iter().enumerate() {
+            let mut frame = Frame::default();
+            let flags = match page_index == 0 {
+                true => FrameFlags::VISIBLE,
+                false => FrameFlags::NONE,
+            };
+
+            Frame::builder()
+                .flags(flags)
+                .position((170, 10))
+                .size(page_size)
+                .parent(&parent)
+                .build(&mut frame)?;
+
+            for (row, entry) in page.entries.into_iter().enumerate() {
+                let y = 10 + (row as i32) * 30;
+                let value = match data.store.as_ref() {
+                    Some(store) => store.read_as(&entry.key, entry.default.clone()),
+                    None => entry.default.clone(),
+                };
+
+                let input = match &value {
+                    SettingValue::Bool(v) => {
+                        let mut check = CheckBox::default();
+                        CheckBox::builder()
+                            .text(&entry.label)
+                            .check_state(check_state(*v))
+                            .position((10, y))
+                            .size((page_size.0 - 20, 25))
+                            .parent(&frame)
+                            .build(&mut check)?;
+
+                        SettingInput::Check(check)
+                    },
+                    SettingValue::Int(_) | SettingValue::Text(_) => {
+                        let mut label = Label::default();
+                        Label::builder()
+                            .text(&entry.label)
+                            .h_align(HTextAlign::Right)
+                            .position((10, y))
+                            .size((150, 25))
+                            .parent(&frame)
+                            .build(&mut label)?;
+
+                        let text = match &value {
+                            SettingValue::Int(v) => v.to_string(),
+                            SettingValue::Text(v) => v.clone(),
+                            SettingValue::Bool(_) => unreachable!(),
+                        };
+
+                        let mut input = TextInput::default();
+                        TextInput::builder()
+                            .text(&text)
+                            .position((170, y))
+                            .size((page_size.0 - 180, 25))
+                            .parent(&frame)
+                            .build(&mut input)?;
+
+                        data.labels.push(label);
+                        SettingInput::Text(input)
+                    }
+                };
+
+                data.inputs.push(input);
+                data.entries.push(entry);
+                saved.push(value);
+            }
+
+            data.pages.push(frame);
+        }
+
+        *data.saved.borrow_mut() = saved;
+
+        Button::builder()
+            .text("Apply")
+            .enabled(false)
+            .position((width - 210, height - 40))
+            .size((95, 30))
+            .parent(&parent)
+            .build(&mut data.apply_button)?;
+
+        Button::builder()
+            .text("Revert")
+            .enabled(false)
+            .position((width - 105, height - 40))
+            .size((95, 30))
+            .parent(&parent)
+            .build(&mut data.revert_button)?;
+
+        Ok(())
+    }
+
+    fn process_event<'a>(&self, evt: Event, _evt_data: &EventData, handle: ControlHandle) {
+        match evt {
+            Event::OnListBoxSelect =>
+                if &handle == &self.pages_list {
+                    if let Some(index) = self.pages_list.selection() {
+                        self.show_page(index);
+                    }
+                },
+            Event::OnButtonClick =>
+                if &handle == &self.apply_button {
+                    if let Err(e) = self.apply() {
+                        crate::error_message("Settings", &e.to_string());
+                    }
+                } else if &handle == &self.revert_button {
+                    self.revert();
+                } else if self.inputs.iter().any(|i| match i { SettingInput::Check(c) => &handle == &c.handle, _ => false }) {
+                    self.update_buttons();
+                },
+            Event::OnTextInput =>
+                if self.inputs.iter().any(|i| match i { SettingInput::Text(t) => &handle == &t.handle, _ => false }) {
+                    self.update_buttons();
+                },
+            _ => {}
+        }
+    }
+
+    fn handles(&self) -> Vec<&ControlHandle> {
+        Vec::new()
+    }
+
+}
+
+fn check_state(checked: bool) -> CheckBoxState {
+    match checked {
+        true => CheckBoxState::Checked,
+        false => CheckBoxState::Unchecked,
+    }
+}
@@ -0,0 +1,296 @@
+use crate::win32::window_helper as wh;
+use crate::{PartialUi, NwgError, ControlHandle, Event, EventData};
+use crate::{ListView, ListViewStyle, ListViewFlags, ListViewExFlags, InsertListViewItem, InsertListViewColumn};
+use crate::{HotKey, HotKeyModifiers, KeyChord, Label, Button};
+use super::{RegistrySettings, SettingValue};
+use std::cell::RefCell;
+
+
+/**
+    A keyboard shortcut of an application. `id` identifies the command triggered by the shortcut
+    and `name` is the text displayed in the shortcut editor.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shortcut {
+    pub id: usize,
+    pub name: String,
+    pub chord: Option<KeyChord>,
+}
+
+impl Shortcut {
+    pub fn new<'a>(id: usize, name: &'a str, chord: Option<KeyChord>) -> Shortcut {
+        Shortcut { id, name: name.to_string(), chord }
+    }
+}
+
+/**
+    A partial that lists the keyboard shortcuts of an application and lets the user rebind them.
+
+    Selecting a shortcut in the list loads its key chord in a hotkey control. While the user enters
+    a new key chord, the shortcut already using it (if any) is displayed under the control.
+    "Assign" binds the key chord to the selected shortcut and unbinds it from the conflicting shortcut.
+    "Clear" removes the key chord of the selected shortcut.
+
+    The controls are positioned using the size of the parent at build time.
+
+    ```rust
+    use native_windows_gui as nwg;
+    use nwg::{PartialUi, Shortcut, KeyChord, HotKeyModifiers, keys};
+
+    fn build_editor(window: &nwg::Window) -> Result<nwg::ShortcutEditor, nwg::NwgError> {
+        let mut editor = nwg::ShortcutEditor::new(vec![
+            Shortcut::new(0, "Save", Some(KeyChord::new(keys::_S, HotKeyModifiers::CONTROL))),
+            Shortcut::new(1, "Refresh", Some(KeyChord::new(keys::F5, HotKeyModifiers::NONE))),
+        ]);
+
+        nwg::ShortcutEditor::build_partial(&mut editor, Some(window))?;
+        Ok(editor)
+    }
+    ```
+*/
+#[derive(Default)]
+pub struct ShortcutEditor {
+    shortcuts: RefCell<Vec<Shortcut>>,
+
+    pub list: ListView,
+    pub hotkey: HotKey,
+    pub conflict_label: Label,
+    pub assign_button: Button,
+    pub clear_button: Button,
+}
+
+impl ShortcutEditor {
+
+    pub fn new(shortcuts: Vec<Shortcut>) -> ShortcutEditor {
+        ShortcutEditor {
+            shortcuts: RefCell::new(shortcuts),
+            ..Default::default()
+        }
+    }
+
+    /// Return a copy of the shortcuts with the key chords set by the user
+    pub fn shortcuts(&self) -> Vec<Shortcut> {
+        self.shortcuts.borrow().clone()
+    }
+
+    /// Replace the shortcuts of the editor
+    pub fn set_shortcuts(&self, shortcuts: Vec<Shortcut>) {
+        *self.shortcuts.borrow_mut() = shortcuts;
+        self.reload_list();
+        self.update_conflict();
+    }
+
+    /// Return the index of the pairs of shortcuts that share the same key chord
+    pub fn conflicts(&self) -> Vec<(usize, usize)> {
+        let shortcuts = self.shortcuts.borrow();
+        let mut conflicts = Vec::new();
+
+        for (i, a) in shortcuts.iter().enumerate() {
+            for (j, b) in shortcuts.iter().enumerate().skip(i + 1) {
+                if a.chord.is_some() && a.chord == b.chord {
+                    conflicts.push((i, j));
+                }
+            }
+        }
+
+        conflicts
+    }
+
+    /// Return the index of the shortcut using `chord`, ignoring the shortcut at `except`
+    pub fn find_chord(&self, chord: KeyChord, except: Option<usize>) -> Option<usize> {
+        self.shortcuts.borrow().iter()
+            .enumerate()
+            .position(|(i, s)| Some(i) != except && s.chord == Some(chord))
+    }
+
+    /// Bind `chord` to the shortcut at `index`. Any other shortcut using the same key chord is unbound.
+    pub fn assign(&self, index: usize, chord: Option<KeyChord>) {
+        {
+            let mut shortcuts = self.shortcuts.borrow_mut();
+            if index >= shortcuts.len() {
+                return;
+            }
+
+            if chord.is_some() {
+                for (i, s) in shortcuts.iter_mut().enumerate() {
+                    if i != index && s.chord == chord {
+                        s.chord = None;
+                    }
+                }
+            }
+
+            shortcuts[index].chord = chord;
+        }
+
+        self.reload_list();
+        self.list.select_item(index, true);
+        self.update_conflict();
+    }
+
+    /// Load the key chords saved with `save` from the settings store.
+    /// Shortcuts without a saved value keep their current key chord.
+    pub fn load(&self, store: &RegistrySettings) {
+        {
+            let mut shortcuts = self.shortcuts.borrow_mut();
+            for s in shortcuts.iter_mut() {
+                if let Some(SettingValue::Int(v)) = store.read(&shortcut_key(s.id)) {
+                    s.chord = match v {
+                        0 => None,
+                        v => Some(KeyChord::new((v & 0xFF) as u32, HotKeyModifiers::from_bits_truncate((v >> 8) as u8)))
+                    };
+                }
+            }
+        }
+
+        self.reload_list();
+        self.update_conflict();
+    }
+
+    /// Save the key chords of the shortcuts in the settings store
+    pub fn save(&self, store: &RegistrySettings) -> Result<(), NwgError> {
+        for s in self.shortcuts.borrow().iter() {
+            let value = match s.chord {
+                Some(c) => ((c.modifiers.bits() as i32) << 8) | (c.key as i32 & 0xFF),
+                None => 0
+            };
+
+            store.write(&shortcut_key(s.id), &SettingValue::Int(value))?;
+        }
+
+        Ok(())
+    }
+
+    fn reload_list(&self) {
+        let conflicts = self.conflicts();
+        let shortcuts = self.shortcuts.borrow();
+
+        self.list.set_redraw(false);
+        self.list.clear();
+
+        for (i, s) in shortcuts.iter().enumerate() {
+            let mut chord = s.chord.map(|c| c.to_string()).unwrap_or_default();
+            if conflicts.iter().any(|&(a, b)| a == i || b == i) {
+                chord.push_str(" (conflict)");
+            }
+
+            self.list.insert_item(InsertListViewItem { index: Some(i as i32), column_index: 0, text: Some(s.name.clone()), ..Default::default() });
+            self.list.insert_item(InsertListViewItem { index: Some(i as i32), column_index: 1, text: Some(chord), ..Default::default() });
+        }
+
+        self.list.set_redraw(true);
+    }
+
+    fn update_conflict(&self) {
+        let selected = self.list.selected_item();
+        let text = match self.hotkey.chord() {
+            Some(chord) => match self.find_chord(chord, selected) {
+                Some(i) => format!("{} is already used by {:?}", chord, self.shortcuts.borrow()[i].name),
+                None => String::new()
+            },
+            None => String::new()
+        };
+
+        self.conflict_label.set_text(&text);
+        self.assign_button.set_enabled(selected.is_some());
+        self.clear_button.set_enabled(selected.is_some());
+    }
+
+}
+
+impl PartialUi for ShortcutEditor {
+
+    fn build_partial<W: Into<ControlHandle>>(data: &mut ShortcutEditor, parent: Option<W>) -> Result<(), NwgError> {
+        let parent = match parent {
+            Some(p) => p.into(),
+            None => { return Err(NwgError::no_parent("ShortcutEditor")); }
+        };
+
+        let (width, height) = match parent.hwnd() {
+            Some(hwnd) => unsafe { wh::get_window_size(hwnd) },
+            None => { return Err(NwgError::control_create("ShortcutEditor parent must be a window")); }
+        };
+        let (width, height) = (width as i32, height as i32);
+
+        ListView::builder()
+            .list_style(ListViewStyle::Detailed)
+            .flags(ListViewFlags::VISIBLE | ListViewFlags::TAB_STOP | ListViewFlags::SINGLE_SELECTION | ListViewFlags::ALWAYS_SHOW_SELECTION)
+            .ex_flags(ListViewExFlags::FULL_ROW_SELECT)
+            .position((10, 10))
+            .size((width - 20, height - 90))
+            .parent(&parent)
+            .build(&mut data.list)?;
+
+        data.list.insert_column(InsertListViewColumn { index: Some(0), fmt: None, width: Some((width - 40) / 2), text: Some("Command".to_string()) });
+        data.list.insert_column(InsertListViewColumn { index: Some(1), fmt: None, width: Some((width - 40) / 2), text: Some("Shortcut".to_string()) });
+        data.list.set_headers_enabled(true);
+
+        HotKey::builder()
+            .position((10, height - 70))
+            .size((width - 230, 25))
+            .parent(&parent)
+            .build(&mut data.hotkey)?;
+
+        Button::builder()
+            .text("Assign")
+            .enabled(false)
+            .position((width - 210, height - 72))
+            .size((95, 28))
+            .parent(&parent)
+            .build(&mut data.assign_button)?;
+
+        Button::builder()
+            .text("Clear")
+            .enabled(false)
+            .position((width - 105, height - 72))
+            .size((95, 28))
+            .parent(&parent)
+            .build(&mut data.clear_button)?;
+
+        Label::builder()
+            .text("")
+            .position((10, height - 38))
+            .size((width - 20, 25))
+            .parent(&parent)
+            .build(&mut data.conflict_label)?;
+
+        data.reload_list();
+
+        Ok(())
+    }
+
+    fn process_event<'a>(&self, evt: Event, _evt_data: &EventData, handle: ControlHandle) {
+        match evt {
+            Event::OnListViewItemChanged =>
+                if &handle == &self.list {
+                    let chord = self.list.selected_item().and_then(|i| self.shortcuts.borrow()[i].chord);
+                    self.hotkey.set_chord(chord);
+                    self.update_conflict();
+                },
+            Event::OnHotKeyChanged =>
+                if &handle == &self.hotkey {
+                    self.update_conflict();
+                },
+            Event::OnButtonClick =>
+                if &handle == &self.assign_button {
+                    if let Some(index) = self.list.selected_item() {
+                        self.assign(index, self.hotkey.chord());
+                    }
+                } else if &handle == &self.clear_button {
+                    if let Some(index) = self.list.selected_item() {
+                        self.hotkey.set_chord(None);
+                        self.assign(index, None);
+                    }
+                },
+            _ => {}
+        }
+    }
+
+    fn handles(&self) -> Vec<&ControlHandle> {
+        Vec::new()
+    }
+
+}
+
+fn shortcut_key(id: usize) -> String {
+    format!("shortcut_{}", id)
+}
@@ -0,0 +1,88 @@
+/*!
+    Layout tests. The layouts are computed when they are built, so the windows do not have to be shown.
+*/
+use crate::*;
+use stretch::{geometry::Size, style::{Dimension as D, FlexDirection, FlexWrap, AlignContent}};
+
+const BUTTON_SIZE: Size<D> = Size { width: D::Points(50.0), height: D::Points(20.0) };
+
+
+fn build_window(window: &mut Window, buttons: &mut [Button]) {
+    Window::builder()
+        .flags(WindowFlags::WINDOW)
+        .size((200, 200))
+        .position((300, 300))
+        .title("Layout Test")
+        .build(window)
+        .expect("Failed to build the window");
+
+    for button in buttons.iter_mut() {
+        Button::builder()
+            .parent(&*window)
+            .build(button)
+            .expect("Failed to build the button");
+    }
+}
+
+fn build_layout(layout: &FlexboxLayout, window: &Window, buttons: &[Button], direction: FlexDirection, wrap: FlexWrap) {
+    let mut builder = FlexboxLayout::builder()
+        .parent(window)
+        .auto_spacing(None)
+        .flex_direction(direction)
+        .flex_wrap(wrap)
+        .align_content(AlignContent::FlexStart)
+        .gap(10);
+
+    for button in buttons.iter() {
+        builder = builder.child(button).child_size(BUTTON_SIZE);
+    }
+
+    builder.build(layout).expect("Failed to build the layout");
+}
+
+fn rects(layout: &FlexboxLayout, buttons: &[Button]) -> Vec<(i32, i32, u32, u32)> {
+    buttons.iter()
+        .map(|b| layout.child_rect(b).expect("The layout was not computed"))
+        .collect()
+}
+
+
+/// Without padding, the gap is only added between the children
+#[test]
+fn flexbox_gap_without_padding() {
+    init().expect("Failed to init Native Windows GUI");
+
+    let mut window = Window::default();
+    let mut buttons: Vec<Button> = (0..3).map(|_| Button::default()).collect();
+    build_window(&mut window, &mut buttons);
+
+    let layout = FlexboxLayout::default();
+    build_layout(&layout, &window, &buttons, FlexDirection::Row, FlexWrap::NoWrap);
+    assert_eq!(rects(&layout, &buttons), vec![(0, 0, 50, 20), (60, 0, 50, 20), (120, 0, 50, 20)]);
+
+    // Building the layout again replaces the first one
+    build_layout(&layout, &window, &buttons, FlexDirection::Column, FlexWrap::NoWrap);
+    assert_eq!(rects(&layout, &buttons), vec![(0, 0, 50, 20), (0, 30, 50, 20), (0, 60, 50, 20)]);
+}
+
+/// When the children wrap, the lines are also separated by the gap and the first line touches the edges
+#[test]
+fn flexbox_wrapped_gap_without_padding() {
+    init().expect("Failed to init Native Windows GUI");
+
+    let mut window = Window::default();
+    let mut buttons: Vec<Button> = (0..8).map(|_| Button::default()).collect();
+    build_window(&mut window, &mut buttons);
+
+    let layout = FlexboxLayout::default();
+    build_layout(&layout, &window, &buttons, FlexDirection::Row, FlexWrap::Wrap);
+
+    // Number of buttons that fit in the first line: n * 50 + (n - 1) * 10 <= width
+    let (width, _) = window.size();
+    let per_line = ((width as usize + 10) / 60).max(1);
+
+    for (i, rect) in rects(&layout, &buttons).into_iter().enumerate() {
+        let (column, line) = ((i % per_line) as i32, (i / per_line) as i32);
+        assert_eq!(rect, (column * 60, line * 30, 50, 20), "button {}", i);
+    }
+}
@@ -12,6 +12,10 @@ use freeing_test::*;
 
 mod other;
 
+mod layout_test;
+
+mod visual;
+
 
 #[derive(Default)]
 pub struct TestControlPanel {
@@ -164,3 +164,34 @@ mod partial_canvas_test_ui {
     }
 
 }
+
+
+/// Using a window from a thread that does not own it returns a `WrongThread` error
+#[test]
+fn wrong_thread_errors() {
+    init().expect("Failed to init Native Windows GUI");
+
+    let mut window = Window::default();
+    Window::builder()
+        .flags(WindowFlags::WINDOW)
+        .title("Wrong Thread")
+        .build(&mut window)
+        .expect("Failed to build the window");
+
+    assert!(window.handle.check_thread().is_ok());
+
+    // Window handles are not `Send`
+    let hwnd = window.handle.hwnd().unwrap() as usize;
+    let result = thread::spawn(move || {
+        let handle = ControlHandle::Hwnd(hwnd as _);
+        let checked = handle.check_thread();
+        let bound = try_bind_event_handler(&handle, &handle, |_evt, _data, _handle| {});
+        let full_bound = try_full_bind_event_handler(&handle, |_evt, _data, _handle| {});
+
+        matches!(checked, Err(NwgError::WrongThread(_))) &&
+        matches!(bound, Err(NwgError::WrongThread(_))) &&
+        matches!(full_bound, Err(NwgError::WrongThread(_)))
+    }).join().expect("The thread panicked");
+
+    assert!(result);
+}
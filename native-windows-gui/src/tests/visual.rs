@@ -0,0 +1,122 @@
+/*!
+    Visual regression tests. A capture of a window is compared to a golden image stored in `test_rc/golden`.
+
+    Set the `NWG_UPDATE_GOLDENS` environment variable to write the captures as the new golden images.
+    Without it, a missing golden image fails the test.
+*/
+use crate::*;
+use std::path::{Path, PathBuf};
+use std::env;
+
+/// Directory of the golden images, relative to the crate root
+const GOLDEN_DIR: &'static str = "./test_rc/golden";
+
+/// Environment variable that replaces the golden images with the captures
+const UPDATE_GOLDENS_VAR: &'static str = "NWG_UPDATE_GOLDENS";
+
+/// Maximum difference between two channels of a pixel that is considered identical (antialiasing, ClearType)
+const CHANNEL_THRESHOLD: u8 = 2;
+
+
+/**
+    Captures the client area of `window` and compares it to the golden image `golden` (ex: "chart.png").
+
+    `tolerance` is the fraction of pixels that can differ, from 0.0 (identical images) to 1.0.
+
+    Panics if the golden image does not exist, if the images have different sizes or if too many pixels differ.
+    In that case, the capture is written next to the golden image with the `.actual.png` extension.
+*/
+pub fn assert_visual_match<C: Into<ControlHandle>>(window: C, golden: &str, tolerance: f64) {
+    let capture = capture_window(window).expect("Failed to capture the window");
+    let golden_path = Path::new(GOLDEN_DIR).join(golden);
+
+    if env::var_os(UPDATE_GOLDENS_VAR).is_some() {
+        std::fs::create_dir_all(GOLDEN_DIR).expect("Failed to create the golden directory");
+        capture.save_png(&golden_path).expect("Failed to write the golden image");
+        return;
+    }
+
+    let actual_path = actual_path(&golden_path);
+    if !golden_path.exists() {
+        capture.save_png(&actual_path).ok();
+        panic!("{}: the golden image {:?} does not exist. Run the tests with {} set to create it. See {:?}", golden, golden_path, UPDATE_GOLDENS_VAR, actual_path);
+    }
+
+    let expected = load_golden(&golden_path);
+
+    if expected.size() != capture.size() {
+        capture.save_png(&actual_path).ok();
+        panic!("{}: expected a {:?} image, captured {:?}. See {:?}", golden, expected.size(), capture.size(), actual_path);
+    }
+
+    let different = expected.pixels().chunks(4)
+        .zip(capture.pixels().chunks(4))
+        .filter(|(e, a)| e.iter().zip(a.iter()).take(3).any(|(e, a)| (*e as i16 - *a as i16).abs() > CHANNEL_THRESHOLD as i16))
+        .count();
+
+    let (width, height) = capture.size();
+    let ratio = different as f64 / (width * height) as f64;
+
+    if ratio > tolerance {
+        capture.save_png(&actual_path).ok();
+        panic!("{}: {} pixels differ ({:.2}%, tolerance {:.2}%). See {:?}", golden, different, ratio * 100.0, tolerance * 100.0, actual_path);
+    }
+
+    std::fs::remove_file(&actual_path).ok();
+}
+
+/// Decodes a golden image to BGRA pixels
+fn load_golden(path: &Path) -> WindowCapture {
+    use winapi::um::wincodec::{WICConvertBitmapSource, GUID_WICPixelFormat32bppBGRA};
+    use winapi::shared::winerror::S_OK;
+    use std::ptr;
+
+    let decoder = ImageDecoder::new().expect("Failed to create the image decoder");
+    let source = decoder.from_filename(path.to_str().unwrap()).expect("Failed to read the golden image");
+    let frame = source.frame(0).expect("Failed to read the golden image");
+
+    let mut converted = ptr::null_mut();
+    let hr = unsafe { WICConvertBitmapSource(&GUID_WICPixelFormat32bppBGRA, frame.frame, &mut converted) };
+    assert!(hr == S_OK, "Failed to convert the golden image");
+
+    let bgra = ImageData { frame: converted };
+    let pixels = bgra.pixels(4).expect("Failed to read the golden image");
+
+    WindowCapture::from_pixels(bgra.size(), pixels).expect("Invalid golden image")
+}
+
+fn actual_path(golden: &Path) -> PathBuf {
+    golden.with_extension("actual.png")
+}
+
+
+/// A fixed size window with a button. PrintWindow paints the window, so it does not have to be shown
+///
+/// Ignored until `basic_window.png` is recorded: run `cargo test visual -- --ignored` with `NWG_UPDATE_GOLDENS` set on Windows,
+/// commit the image and remove the `ignore` attribute.
+#[test]
+#[ignore]
+fn visual_basic_window() {
+    init().expect("Failed to init Native Windows GUI");
+
+    let mut window = Window::default();
+    let mut button = Button::default();
+
+    Window::builder()
+        .flags(WindowFlags::WINDOW)
+        .size((200, 100))
+        .position((300, 300))
+        .title("Visual Test")
+        .build(&mut window)
+        .expect("Failed to build the window");
+
+    Button::builder()
+        .text("Visual")
+        .size((100, 30))
+        .position((50, 35))
+        .parent(&window)
+        .build(&mut button)
+        .expect("Failed to build the button");
+
+    assert_visual_match(&window, "basic_window.png", 0.01);
+}
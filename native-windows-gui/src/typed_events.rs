@@ -0,0 +1,452 @@
+/*!
+    Strongly typed event handlers.
+
+    Instead of matching the `(Event, EventData, ControlHandle)` triple of `full_bind_event_handler`, a typed handler
+    subscribes to a single event of a single control and receives the arguments of the event in a struct.
+    Each event is a type that implements `TypedEvent` (ex: `OnMouseClick` gives a `MouseClickArgs`).
+
+    The typed handlers are built on top of the usual event handlers: `bind_typed_event_handler` returns an `EventHandler`
+    that is unbound with `unbind_event_handler`, and both kinds of handlers can be mixed in the same application.
+
+    Requires the `typed-events` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+    use nwg::typed_events::{self as te, MouseButton};
+
+    fn bind(window: &nwg::Window, canvas: &nwg::ExternCanvas) -> Vec<nwg::EventHandler> {
+        vec![
+            te::bind_typed_event_handler::<te::OnMouseClick, _>(&window.handle, &canvas.handle, |args| {
+                if args.button == MouseButton::Right && args.modifiers.control {
+                    println!("Ctrl+Right click at {:?}", args.position);
+                }
+            }),
+            te::bind_typed_event_handler::<te::OnWindowClose, _>(&window.handle, &window.handle, |args| {
+                args.cancel();
+            }),
+        ]
+    }
+    ```
+*/
+use winapi::shared::windef::POINT;
+use crate::win32::window::{EventHandler, bind_event_handler};
+use crate::{ControlHandle, Event, EventData, MousePressEvent, WindowCloseData, ScrollData};
+
+
+/// A event with typed arguments. See `bind_typed_event_handler`.
+pub trait TypedEvent {
+    /// The arguments given to the handlers of the event
+    type Args;
+
+    /// Builds the arguments of the event from the raw event. Returns `None` if the raw event is another event.
+    fn args(evt: Event, data: &EventData, control: ControlHandle) -> Option<Self::Args>;
+}
+
+/**
+    Binds `f` to the event `E` of `control`. `parent` is the window that contains the control: most controls send their
+    events to their parent. To bind an event of a window, use the window as both `control` and `parent`.
+
+    The handler is removed with `unbind_event_handler`.
+*/
+pub fn bind_typed_event_handler<E, F>(parent: &ControlHandle, control: &ControlHandle, f: F) -> EventHandler
+    where E: TypedEvent + 'static,
+          F: Fn(E::Args) + 'static
+{
+    // The menu items, the timers and the notices do not have a window. Their events are sent to the parent.
+    let hooked = match control.hwnd() {
+        Some(_) => control,
+        None => parent
+    };
+
+    let control = *control;
+    bind_event_handler(hooked, parent, move |evt, data, handle| {
+        if handle != control {
+            return;
+        }
+
+        if let Some(args) = E::args(evt, &data, handle) {
+            f(args);
+        }
+    })
+}
+
+
+/// The state of the modifier keys when an event was raised
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyModifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+}
+
+impl KeyModifiers {
+
+    /// Reads the state of the modifier keys for the message being processed
+    pub fn current() -> KeyModifiers {
+        use winapi::um::winuser::{GetKeyState, VK_SHIFT, VK_CONTROL, VK_MENU};
+
+        let down = |key| unsafe { GetKeyState(key) < 0 };
+        KeyModifiers {
+            shift: down(VK_SHIFT),
+            control: down(VK_CONTROL),
+            alt: down(VK_MENU),
+        }
+    }
+
+}
+
+/// A mouse button
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MouseButton {
+    Left,
+    Right,
+}
+
+/// The arguments of the events raised by a control without data (ex: `OnButtonClick`)
+#[derive(Copy, Clone, Debug)]
+pub struct ControlArgs {
+    /// The control that raised the event
+    pub control: ControlHandle,
+}
+
+/// The arguments of `OnMouseDown` and `OnMouseClick`
+#[derive(Copy, Clone, Debug)]
+pub struct MouseClickArgs {
+    pub control: ControlHandle,
+
+    /// The position of the cursor, in physical pixels, relative to the client area of the control
+    pub position: (i32, i32),
+
+    pub button: MouseButton,
+    pub modifiers: KeyModifiers,
+}
+
+/// The arguments of `OnMouseMove`
+#[derive(Copy, Clone, Debug)]
+pub struct MouseMoveArgs {
+    pub control: ControlHandle,
+
+    /// The position of the cursor, in physical pixels, relative to the client area of the control
+    pub position: (i32, i32),
+
+    pub modifiers: KeyModifiers,
+}
+
+/// The arguments of `OnMouseWheel`
+#[derive(Copy, Clone, Debug)]
+pub struct MouseWheelArgs {
+    pub control: ControlHandle,
+
+    /// The rotation of the wheel, in multiple of `WHEEL_DELTA` (120). Positive values rotate the wheel away from the user.
+    pub delta: i32,
+
+    /// The position of the cursor, in physical pixels, relative to the client area of the control
+    pub position: (i32, i32),
+
+    pub modifiers: KeyModifiers,
+}
+
+/// The arguments of `OnScroll`
+#[derive(Copy, Clone, Debug)]
+pub struct ScrollArgs {
+    pub control: ControlHandle,
+    pub scroll: ScrollData,
+}
+
+/// The arguments of `OnKeyPress` and `OnKeyRelease`
+#[derive(Copy, Clone, Debug)]
+pub struct KeyArgs {
+    pub control: ControlHandle,
+
+    /// The virtual key code of the key. See `nwg::keys`.
+    pub key: u32,
+
+    pub modifiers: KeyModifiers,
+}
+
+/// The arguments of `OnChar`
+#[derive(Copy, Clone, Debug)]
+pub struct CharArgs {
+    pub control: ControlHandle,
+    pub character: char,
+}
+
+/// The arguments of `OnResize`
+#[derive(Copy, Clone, Debug)]
+pub struct ResizeArgs {
+    pub control: ControlHandle,
+
+    /// The new size of the control
+    pub size: (u32, u32),
+}
+
+/// The arguments of `OnWindowClose`
+pub struct WindowCloseArgs {
+    pub control: ControlHandle,
+    data: WindowCloseData,
+}
+
+impl WindowCloseArgs {
+
+    /// Keeps the window open
+    pub fn cancel(&self) {
+        self.data.close(false);
+    }
+
+}
+
+
+/// Returns the position of the cursor for the message being processed, relative to `control`
+fn message_position(control: ControlHandle) -> (i32, i32) {
+    use winapi::um::winuser::{GetMessagePos, ScreenToClient};
+    use winapi::shared::minwindef::{LOWORD, HIWORD};
+
+    let pos = unsafe { GetMessagePos() };
+    let mut point = POINT { x: LOWORD(pos) as i16 as i32, y: HIWORD(pos) as i16 as i32 };
+    if let Some(hwnd) = control.hwnd() {
+        unsafe { ScreenToClient(hwnd, &mut point); }
+    }
+
+    (point.x, point.y)
+}
+
+/// Declares the events that are raised without data
+macro_rules! control_events {
+    ($($(#[$meta:meta])* $name:ident),* $(,)?) => {
+        $(
+            $(#[$meta])*
+            pub struct $name;
+
+            impl TypedEvent for $name {
+                type Args = ControlArgs;
+
+                fn args(evt: Event, _data: &EventData, control: ControlHandle) -> Option<ControlArgs> {
+                    match evt {
+                        Event::$name => Some(ControlArgs { control }),
+                        _ => None
+                    }
+                }
+            }
+        )*
+    };
+}
+
+control_events!(
+    /// When a window is created
+    OnInit,
+    /// When a button is clicked
+    OnButtonClick,
+    /// When a button is double clicked
+    OnButtonDoubleClick,
+    /// When the text of a text input or of a text box is changed by the user
+    OnTextInput,
+    /// When the selection of a combobox changes
+    OnComboxBoxSelection,
+    /// When the selection of a list box changes
+    OnListBoxSelect,
+    /// When a menu item is selected
+    OnMenuItemSelected,
+    /// When a timer ticks
+    OnTimerTick,
+    /// When a notice is received
+    OnNotice,
+    /// When a window was moved by the user
+    OnMove,
+    /// When the user starts to resize a window
+    OnResizeBegin,
+    /// When the user stops resizing a window
+    OnResizeEnd,
+    /// When a window is maximized
+    OnWindowMaximize,
+    /// When a window is minimized
+    OnWindowMinimize,
+);
+
+/// When a mouse button is pressed over a control
+pub struct OnMouseDown;
+
+impl TypedEvent for OnMouseDown {
+    type Args = MouseClickArgs;
+
+    fn args(evt: Event, _data: &EventData, control: ControlHandle) -> Option<MouseClickArgs> {
+        let button = match evt {
+            Event::OnMousePress(MousePressEvent::MousePressLeftDown) => MouseButton::Left,
+            Event::OnMousePress(MousePressEvent::MousePressRightDown) => MouseButton::Right,
+            _ => { return None; }
+        };
+
+        Some(MouseClickArgs { control, position: message_position(control), button, modifiers: KeyModifiers::current() })
+    }
+}
+
+/// When a mouse button is released over a control
+pub struct OnMouseClick;
+
+impl TypedEvent for OnMouseClick {
+    type Args = MouseClickArgs;
+
+    fn args(evt: Event, _data: &EventData, control: ControlHandle) -> Option<MouseClickArgs> {
+        let button = match evt {
+            Event::OnMousePress(MousePressEvent::MousePressLeftUp) => MouseButton::Left,
+            Event::OnMousePress(MousePressEvent::MousePressRightUp) => MouseButton::Right,
+            _ => { return None; }
+        };
+
+        Some(MouseClickArgs { control, position: message_position(control), button, modifiers: KeyModifiers::current() })
+    }
+}
+
+/// When the mouse moves over a control
+pub struct OnMouseMove;
+
+impl TypedEvent for OnMouseMove {
+    type Args = MouseMoveArgs;
+
+    fn args(evt: Event, _data: &EventData, control: ControlHandle) -> Option<MouseMoveArgs> {
+        match evt {
+            Event::OnMouseMove => Some(MouseMoveArgs { control, position: message_position(control), modifiers: KeyModifiers::current() }),
+            _ => None
+        }
+    }
+}
+
+/// When the mouse wheel is rotated over a control
+pub struct OnMouseWheel;
+
+impl TypedEvent for OnMouseWheel {
+    type Args = MouseWheelArgs;
+
+    fn args(evt: Event, data: &EventData, control: ControlHandle) -> Option<MouseWheelArgs> {
+        match (evt, data) {
+            (Event::OnMouseWheel, &EventData::OnMouseWheel(delta)) => Some(MouseWheelArgs {
+                control,
+                delta,
+                position: message_position(control),
+                modifiers: KeyModifiers::current()
+            }),
+            _ => None
+        }
+    }
+}
+
+/// When a control is scrolled. See `Event::OnScroll`
+pub struct OnScroll;
+
+impl TypedEvent for OnScroll {
+    type Args = ScrollArgs;
+
+    fn args(evt: Event, data: &EventData, control: ControlHandle) -> Option<ScrollArgs> {
+        match (evt, data) {
+            (Event::OnScroll, &EventData::OnScroll(scroll)) => Some(ScrollArgs { control, scroll }),
+            _ => None
+        }
+    }
+}
+
+/// When a key is pressed
+pub struct OnKeyPress;
+
+impl TypedEvent for OnKeyPress {
+    type Args = KeyArgs;
+
+    fn args(evt: Event, data: &EventData, control: ControlHandle) -> Option<KeyArgs> {
+        match (evt, data) {
+            (Event::OnKeyPress, &EventData::OnKey(key)) => Some(KeyArgs { control, key, modifiers: KeyModifiers::current() }),
+            _ => None
+        }
+    }
+}
+
+/// When a key is released
+pub struct OnKeyRelease;
+
+impl TypedEvent for OnKeyRelease {
+    type Args = KeyArgs;
+
+    fn args(evt: Event, data: &EventData, control: ControlHandle) -> Option<KeyArgs> {
+        match (evt, data) {
+            (Event::OnKeyRelease, &EventData::OnKey(key)) => Some(KeyArgs { control, key, modifiers: KeyModifiers::current() }),
+            _ => None
+        }
+    }
+}
+
+/// When a character is typed. See `Event::OnChar`
+pub struct OnChar;
+
+impl TypedEvent for OnChar {
+    type Args = CharArgs;
+
+    fn args(evt: Event, data: &EventData, control: ControlHandle) -> Option<CharArgs> {
+        match (evt, data) {
+            (Event::OnChar, &EventData::OnChar(character)) => Some(CharArgs { control, character }),
+            _ => None
+        }
+    }
+}
+
+/// When a control is resized
+pub struct OnResize;
+
+impl TypedEvent for OnResize {
+    type Args = ResizeArgs;
+
+    fn args(evt: Event, _data: &EventData, control: ControlHandle) -> Option<ResizeArgs> {
+        use crate::win32::window_helper::get_window_size;
+
+        match (evt, control.hwnd()) {
+            (Event::OnResize, Some(hwnd)) => Some(ResizeArgs { control, size: unsafe { get_window_size(hwnd) } }),
+            _ => None
+        }
+    }
+}
+
+/// When the user closes a window
+pub struct OnWindowClose;
+
+impl TypedEvent for OnWindowClose {
+    type Args = WindowCloseArgs;
+
+    fn args(evt: Event, data: &EventData, control: ControlHandle) -> Option<WindowCloseArgs> {
+        match (evt, data) {
+            (Event::OnWindowClose, EventData::OnWindowClose(close)) => Some(WindowCloseArgs {
+                control,
+                data: WindowCloseData { data: close.data }
+            }),
+            _ => None
+        }
+    }
+}
+
+/// The arguments of `OnImageDecoded`
+#[cfg(feature = "async-decode")]
+pub struct ImageDecodedArgs {
+    /// The window that received the decoded image
+    pub control: ControlHandle,
+
+    /// The identifier returned by `Bitmap::decode_async`
+    pub id: usize,
+
+    /// The decoded bitmap, or the error that happened while decoding the image
+    pub bitmap: Result<crate::Bitmap, crate::NwgError>,
+}
+
+/// When an image decoded by `Bitmap::decode_async` is ready. The handler takes the ownership of the bitmap.
+#[cfg(feature = "async-decode")]
+pub struct OnImageDecoded;
+
+#[cfg(feature = "async-decode")]
+impl TypedEvent for OnImageDecoded {
+    type Args = ImageDecodedArgs;
+
+    fn args(evt: Event, data: &EventData, control: ControlHandle) -> Option<ImageDecodedArgs> {
+        match (evt, data) {
+            (Event::OnImageDecoded, EventData::OnImageDecoded(image)) => Some(ImageDecodedArgs {
+                control,
+                id: image.id(),
+                bitmap: image.take_bitmap()
+            }),
+            _ => None
+        }
+    }
+}
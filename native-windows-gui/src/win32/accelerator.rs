@@ -0,0 +1,70 @@
+/*!
+    Translation of the keyboard shortcuts of the accelerator tables in the events loop.
+*/
+use winapi::shared::windef::{HWND, HACCEL, HMENU};
+use winapi::um::winuser::MSG;
+use std::cell::RefCell;
+
+/// An accelerator table and the window that receives its commands
+struct Registration {
+    window: HWND,
+    table: HACCEL,
+    /// Commands that select a menu item: (command id, menu of the item)
+    menu_items: Vec<(u16, HMENU)>,
+}
+
+thread_local! {
+    static TABLES: RefCell<Vec<Registration>> = RefCell::new(Vec::new());
+}
+
+/// Translates the shortcuts of `table` for the messages sent to `window` or to its children
+pub(crate) fn register(window: HWND, table: HACCEL, menu_items: Vec<(u16, HMENU)>) {
+    TABLES.with(|tables| tables.borrow_mut().push(Registration { window, table, menu_items }));
+}
+
+pub(crate) fn unregister(table: HACCEL) {
+    TABLES.with(|tables| tables.borrow_mut().retain(|r| r.table != table));
+}
+
+/**
+    Translates a key press into the command of an accelerator table. The command is sent to the window of the table.
+    Returns `true` if the message was translated and must not be dispatched.
+*/
+pub(crate) unsafe fn translate_accelerator(msg: &mut MSG) -> bool {
+    use winapi::um::winuser::{TranslateAcceleratorW, IsWindow, IsChild, WM_KEYDOWN, WM_SYSKEYDOWN, WM_CHAR, WM_SYSCHAR};
+
+    match msg.message {
+        WM_KEYDOWN | WM_SYSKEYDOWN | WM_CHAR | WM_SYSCHAR => {},
+        _ => { return false; }
+    }
+
+    let target = msg.hwnd;
+    let candidates: Vec<(HWND, HACCEL)> = TABLES.with(|tables| {
+        let mut tables = tables.borrow_mut();
+        tables.retain(|r| IsWindow(r.window) != 0);
+        tables.iter()
+            .filter(|r| r.window == target || IsChild(r.window, target) != 0)
+            .map(|r| (r.window, r.table))
+            .collect()
+    });
+
+    // The tables are released first because the commands are sent synchronously and the event handlers may build new tables
+    candidates.into_iter().any(|(window, table)| TranslateAcceleratorW(window, table, msg) != 0)
+}
+
+/**
+    Returns the menu of the item selected by an accelerator command and if the item is enabled,
+    or `None` if the command is not bound to a menu item. Disabled menu items are not selected by their shortcut.
+*/
+pub(crate) fn accelerator_menu_item(window: HWND, id: u16) -> Option<(HMENU, bool)> {
+    use winapi::um::winuser::{GetMenuState, MF_BYCOMMAND, MF_DISABLED, MF_GRAYED};
+
+    let menu = TABLES.with(|tables| {
+        tables.borrow().iter()
+            .filter(|r| r.window == window)
+            .find_map(|r| r.menu_items.iter().find(|(cmd, _)| *cmd == id).map(|(_, menu)| *menu))
+    })?;
+
+    let state = unsafe { GetMenuState(menu, id as u32, MF_BYCOMMAND) };
+    Some((menu, state != u32::MAX && state & (MF_DISABLED | MF_GRAYED) == 0))
+}
@@ -0,0 +1,333 @@
+/*!
+    Frame scheduler of the property animations.
+
+    All the animations of a thread are driven by a single timer on a hidden message window. The timer only runs while
+    at least one animation is active. The scheduler is used by the `Animator` control and by the other features
+    that move or resize the controls over time.
+*/
+use winapi::shared::windef::HWND;
+use super::window::{create_message_window, bind_raw_event_handler_inner, RawEventHandler};
+use super::window_helper as wh;
+use crate::NwgError;
+use std::{cell::RefCell, time::{Duration, Instant}};
+
+/// Id of the raw handler bound to the scheduler window
+const SCHEDULER_HANDLER_ID: usize = 0xA213;
+
+/// Id of the frame timer of the scheduler window
+const FRAME_TIMER_ID: u32 = 1;
+
+/// Interval between two frames, in milliseconds
+const FRAME_INTERVAL: u32 = 15;
+
+
+/// Easing curves of an animation. See https://easings.net for a preview of the curves.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    InQuad,
+    OutQuad,
+    InOutQuad,
+    InCubic,
+    OutCubic,
+    InOutCubic,
+    /// Overshoots the target a little before settling on it
+    OutBack,
+    /// Bounces on the target value
+    OutBounce,
+}
+
+impl Easing {
+
+    /// Maps the progress of an animation (from 0.0 to 1.0) to the progress of the animated value
+    pub fn ease(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::InQuad => t * t,
+            Easing::OutQuad => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::InOutQuad => match t < 0.5 {
+                true => 2.0 * t * t,
+                false => 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+            },
+            Easing::InCubic => t * t * t,
+            Easing::OutCubic => 1.0 - (1.0 - t).powi(3),
+            Easing::InOutCubic => match t < 0.5 {
+                true => 4.0 * t * t * t,
+                false => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+            },
+            Easing::OutBack => {
+                let c1 = 1.70158;
+                let c3 = c1 + 1.0;
+                1.0 + c3 * (t - 1.0).powi(3) + c1 * (t - 1.0).powi(2)
+            },
+            Easing::OutBounce => {
+                let (n1, d1) = (7.5625, 2.75);
+                if t < 1.0 / d1 {
+                    n1 * t * t
+                } else if t < 2.0 / d1 {
+                    let t = t - 1.5 / d1;
+                    n1 * t * t + 0.75
+                } else if t < 2.5 / d1 {
+                    let t = t - 2.25 / d1;
+                    n1 * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / d1;
+                    n1 * t * t + 0.984375
+                }
+            }
+        }
+    }
+
+}
+
+/**
+    A property of a control that can be animated.
+
+    The position and the size use the same units as `set_position` and `set_size` on the controls.
+    `Opacity` goes from 0 (invisible) to 255 (opaque) and only works on top level windows.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Property {
+    X,
+    Y,
+    Width,
+    Height,
+    Opacity,
+}
+
+struct Animation {
+    id: u32,
+    target: HWND,
+    property: Property,
+    from: i32,
+    to: i32,
+    start: Instant,
+    duration: Duration,
+    easing: Easing,
+    on_end: Option<Box<dyn FnOnce(u32)>>,
+}
+
+impl Animation {
+
+    /// Returns the value of the property at this frame and if the animation is over
+    fn frame(&self) -> (i32, bool) {
+        let elapsed = self.start.elapsed();
+        if elapsed >= self.duration {
+            return (self.to, true);
+        }
+
+        let progress = self.easing.ease(elapsed.as_secs_f64() / self.duration.as_secs_f64());
+        let value = self.from as f64 + (self.to as f64 - self.from as f64) * progress;
+        (value.round() as i32, false)
+    }
+
+}
+
+#[derive(Default)]
+struct Scheduler {
+    window: Option<HWND>,
+    handler: Option<RawEventHandler>,
+    animations: Vec<Animation>,
+    next_id: u32,
+}
+
+thread_local! {
+    static SCHEDULER: RefCell<Scheduler> = RefCell::new(Scheduler::default());
+}
+
+/**
+    Starts animating `property` of `target` from `from` to `to`. Replaces the animation running on the same property
+    of the same window, if any. `on_end` is called with the animation id once the animation is over. It is not called
+    if the animation is stopped or replaced.
+
+    Returns the id of the animation.
+*/
+pub(crate) fn animate(target: HWND, property: Property, from: i32, to: i32, duration: Duration, easing: Easing, on_end: Option<Box<dyn FnOnce(u32)>>) -> Result<u32, NwgError> {
+    let window = scheduler_window()?;
+
+    if property == Property::Opacity {
+        prepare_opacity(target);
+    }
+
+    apply(target, property, from);
+
+    let id = SCHEDULER.with(|s| {
+        let mut s = s.borrow_mut();
+        s.animations.retain(|a| !(a.target == target && a.property == property));
+
+        s.next_id = s.next_id.wrapping_add(1).max(1);
+        let id = s.next_id;
+        s.animations.push(Animation { id, target, property, from, to, start: Instant::now(), duration, easing, on_end });
+
+        id
+    });
+
+    wh::start_timer(window, FRAME_TIMER_ID, FRAME_INTERVAL);
+
+    Ok(id)
+}
+
+/// Creates the scheduler of the current thread if it does not exist yet
+pub(crate) fn init() -> Result<(), NwgError> {
+    scheduler_window().map(|_| ())
+}
+
+/// Stops an animation. The property keeps its current value. Returns `false` if the animation was not running.
+pub(crate) fn stop(id: u32) -> bool {
+    SCHEDULER.with(|s| {
+        let mut s = s.borrow_mut();
+        let count = s.animations.len();
+        s.animations.retain(|a| a.id != id);
+        count != s.animations.len()
+    })
+}
+
+/// Stops all the animations of a window. The properties keep their current value.
+pub(crate) fn stop_window(target: HWND) {
+    SCHEDULER.with(|s| s.borrow_mut().animations.retain(|a| a.target != target));
+}
+
+/// Returns `true` if the animation is running
+pub(crate) fn running(id: u32) -> bool {
+    SCHEDULER.with(|s| s.borrow().animations.iter().any(|a| a.id == id))
+}
+
+/// Returns the window that receives the frame timer. The window is created by the first animation of the thread.
+fn scheduler_window() -> Result<HWND, NwgError> {
+    use winapi::um::winuser::WM_TIMER;
+
+    if let Some(window) = SCHEDULER.with(|s| s.borrow().window) {
+        return Ok(window);
+    }
+
+    let window = create_message_window("")?;
+    let hwnd = window.hwnd().unwrap();
+
+    let handler = bind_raw_event_handler_inner(&window, SCHEDULER_HANDLER_ID, |hwnd, msg, w, _l| {
+        if msg != WM_TIMER || w != FRAME_TIMER_ID as usize {
+            return None;
+        }
+
+        next_frame(hwnd);
+
+        Some(0)
+    });
+
+    let handler = match handler {
+        Ok(h) => h,
+        Err(e) => {
+            let mut window = window;
+            window.destroy();
+            return Err(e);
+        }
+    };
+
+    SCHEDULER.with(|s| {
+        let mut s = s.borrow_mut();
+        s.window = Some(hwnd);
+        s.handler = Some(handler);
+    });
+
+    Ok(hwnd)
+}
+
+fn next_frame(window: HWND) {
+    use winapi::um::winuser::IsWindow;
+
+    let mut values = Vec::new();
+    let mut ended = Vec::new();
+
+    SCHEDULER.with(|s| {
+        let mut s = s.borrow_mut();
+
+        let mut i = 0;
+        while i < s.animations.len() {
+            let animation = &s.animations[i];
+            if unsafe { IsWindow(animation.target) == 0 } {
+                s.animations.remove(i);
+                continue;
+            }
+
+            let (value, done) = animation.frame();
+            values.push((animation.target, animation.property, value));
+
+            if done {
+                let animation = s.animations.remove(i);
+                if let Some(on_end) = animation.on_end {
+                    ended.push((animation.id, on_end));
+                }
+            } else {
+                i += 1;
+            }
+        }
+
+        if s.animations.is_empty() {
+            wh::kill_timer(window, FRAME_TIMER_ID);
+        }
+    });
+
+    // The scheduler is released first because moving a control raises events that may start new animations
+    for (target, property, value) in values {
+        apply(target, property, value);
+    }
+
+    for (id, on_end) in ended {
+        on_end(id);
+    }
+}
+
+/// Top level windows must be layered to use `SetLayeredWindowAttributes`
+fn prepare_opacity(target: HWND) {
+    use winapi::um::winuser::{GWL_EXSTYLE, WS_EX_LAYERED};
+
+    let ex_style = wh::get_window_long(target, GWL_EXSTYLE) as u32;
+    if ex_style & WS_EX_LAYERED == 0 {
+        wh::set_window_long(target, GWL_EXSTYLE, (ex_style | WS_EX_LAYERED) as usize);
+    }
+}
+
+fn apply(target: HWND, property: Property, value: i32) {
+    use winapi::um::winuser::{SetLayeredWindowAttributes, GetWindowRect, GWL_STYLE, WS_CHILD, LWA_ALPHA};
+    use winapi::shared::windef::RECT;
+    use super::high_dpi::physical_to_logical;
+    use std::mem;
+
+    unsafe {
+        match property {
+            Property::X => {
+                let (_, y) = wh::get_window_position(target);
+                wh::set_window_position(target, value, y);
+            },
+            Property::Y => {
+                let (x, _) = wh::get_window_position(target);
+                wh::set_window_position(target, x, value);
+            },
+            Property::Width | Property::Height => {
+                let value = value.max(0) as u32;
+
+                // Same size semantic as `set_size`: the client area for top level windows, the whole window for the children
+                let top_level = wh::get_window_long(target, GWL_STYLE) as u32 & WS_CHILD == 0;
+                let (w, h) = match top_level {
+                    true => wh::get_window_size(target),
+                    false => {
+                        let mut r: RECT = mem::zeroed();
+                        GetWindowRect(target, &mut r);
+                        let (w, h) = physical_to_logical(r.right - r.left, r.bottom - r.top);
+                        (w as u32, h as u32)
+                    }
+                };
+
+                match property {
+                    Property::Width => wh::set_window_size(target, value, h, top_level),
+                    _ => wh::set_window_size(target, w, value, top_level),
+                }
+            },
+            Property::Opacity => {
+                let alpha = value.clamp(0, 255) as u8;
+                SetLayeredWindowAttributes(target, 0, alpha, LWA_ALPHA);
+            }
+        }
+    }
+}
@@ -0,0 +1,257 @@
+/*!
+    A single thread executor that runs futures on the UI thread, between the messages of the events loop.
+
+    `spawn_local` starts a future on the current thread. When the future is woken, from any thread, a message is posted to
+    a hidden window of the thread and the future is polled by the events loop like any other message. The futures can
+    update the controls directly, without a `Notice`. `spawn_blocking` runs a blocking function on a worker thread and
+    resumes the awaiting future on the UI thread once the function returns.
+
+    The futures of other runtimes can be awaited if their reactor runs on another thread (ex: a `JoinHandle` of a tokio runtime
+    started with `tokio::runtime::Runtime::new`), because their wakers only need to call `wake`.
+
+    Requires the `async-runtime` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+    use std::rc::Rc;
+
+    fn load_file(label: &Rc<nwg::Label>, path: String) -> Result<nwg::TaskHandle, nwg::NwgError> {
+        let label = label.clone();
+        nwg::spawn_local(async move {
+            label.set_text("Loading...");
+
+            match nwg::spawn_blocking(move || std::fs::read_to_string(path)).await {
+                Ok(text) => label.set_text(&format!("{} characters", text.len())),
+                Err(e) => label.set_text(&e.to_string()),
+            }
+        })
+    }
+    ```
+*/
+use winapi::shared::windef::HWND;
+use winapi::um::winuser::{PostMessageW, WM_APP};
+use super::window::{create_message_window, bind_raw_event_handler_inner, RawEventHandler};
+use crate::{ControlHandle, NwgError};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
+use std::task::{Context, Poll, Wake, Waker};
+use std::{cell::RefCell, thread};
+
+/// Id of the raw event handler that polls the tasks
+const RUNTIME_HANDLER_ID: usize = 0xA5C0;
+
+/// Message posted to the message window of the runtime when tasks are woken
+const WM_TASKS_READY: u32 = WM_APP + 0xA5C;
+
+type Task = Pin<Box<dyn Future<Output=()>>>;
+
+thread_local! {
+    static RUNTIME: RefCell<Option<Runtime>> = RefCell::new(None);
+}
+
+
+/// The data shared with the wakers, which can be called from any thread
+struct Shared {
+    /// The ids of the woken tasks
+    ready: Mutex<VecDeque<usize>>,
+    /// Set when a `WM_TASKS_READY` message is waiting in the queue
+    posted: AtomicBool,
+    window: usize,
+}
+
+struct TaskWaker {
+    id: usize,
+    shared: Arc<Shared>,
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.shared.ready.lock().unwrap().push_back(self.id);
+        if !self.shared.posted.swap(true, Ordering::SeqCst) {
+            unsafe { PostMessageW(self.shared.window as HWND, WM_TASKS_READY, 0, 0); }
+        }
+    }
+}
+
+/// The executor of a UI thread
+struct Runtime {
+    tasks: HashMap<usize, Task>,
+    next_id: usize,
+    shared: Arc<Shared>,
+    _window: ControlHandle,
+    _handler: RawEventHandler,
+}
+
+impl Runtime {
+
+    fn new() -> Result<Runtime, NwgError> {
+        let window = create_message_window("")?;
+        let shared = Arc::new(Shared {
+            ready: Mutex::new(VecDeque::new()),
+            posted: AtomicBool::new(false),
+            window: window.hwnd().unwrap() as usize,
+        });
+
+        let handler_shared = shared.clone();
+        let handler = bind_raw_event_handler_inner(&window, RUNTIME_HANDLER_ID, move |_hwnd, msg, _w, _l| {
+            if msg != WM_TASKS_READY {
+                return None;
+            }
+
+            handler_shared.posted.store(false, Ordering::SeqCst);
+            let ready: Vec<usize> = handler_shared.ready.lock().unwrap().drain(..).collect();
+            for id in ready {
+                poll_task(id, &handler_shared);
+            }
+
+            Some(0)
+        })?;
+
+        Ok(Runtime {
+            tasks: HashMap::new(),
+            next_id: 1,
+            shared,
+            _window: window,
+            _handler: handler,
+        })
+    }
+
+}
+
+/// Polls a task. The task is removed from the runtime while it is polled, so that it can spawn or abort other tasks.
+fn poll_task(id: usize, shared: &Arc<Shared>) {
+    let task = RUNTIME.with(|runtime| runtime.borrow_mut().as_mut().and_then(|r| r.tasks.remove(&id)));
+
+    // The task was aborted, already completed, or is being polled (woken from its own poll)
+    let mut task = match task {
+        Some(task) => task,
+        None => { return; }
+    };
+
+    let waker = Waker::from(Arc::new(TaskWaker { id, shared: shared.clone() }));
+    let mut context = Context::from_waker(&waker);
+
+    if task.as_mut().poll(&mut context).is_pending() {
+        RUNTIME.with(|runtime| {
+            if let Some(runtime) = runtime.borrow_mut().as_mut() {
+                runtime.tasks.insert(id, task);
+            }
+        });
+    }
+}
+
+
+/// A task started by `spawn_local`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TaskHandle {
+    id: usize,
+}
+
+impl TaskHandle {
+
+    /// Returns `true` if the task is still running. Must be called from the thread that spawned the task.
+    pub fn is_running(&self) -> bool {
+        RUNTIME.with(|runtime| runtime.borrow().as_ref().map(|r| r.tasks.contains_key(&self.id)).unwrap_or(false))
+    }
+
+    /**
+        Stops the task. The future is dropped and will not be polled again.
+        Must be called from the thread that spawned the task. Does nothing if the task is completed.
+    */
+    pub fn abort(&self) {
+        let task = RUNTIME.with(|runtime| runtime.borrow_mut().as_mut().and_then(|r| r.tasks.remove(&self.id)));
+
+        // The future is dropped outside of the borrow of the runtime, because it may own other tasks
+        drop(task);
+    }
+
+}
+
+/**
+    Runs a future on the current thread. The future is first polled by the events loop, after the current event handler returns.
+    The futures run as long as the events loop of the thread runs (`dispatch_thread_events` or any modal loop).
+
+    Fails if the hidden window of the runtime cannot be created.
+*/
+pub fn spawn_local<F>(future: F) -> Result<TaskHandle, NwgError>
+    where F: Future<Output=()> + 'static
+{
+    let (id, shared) = RUNTIME.with(|runtime| -> Result<(usize, Arc<Shared>), NwgError> {
+        let mut runtime = runtime.borrow_mut();
+        if runtime.is_none() {
+            *runtime = Some(Runtime::new()?);
+        }
+
+        let runtime = runtime.as_mut().unwrap();
+        let id = runtime.next_id;
+        runtime.next_id += 1;
+        runtime.tasks.insert(id, Box::pin(future));
+
+        Ok((id, runtime.shared.clone()))
+    })?;
+
+    Waker::from(Arc::new(TaskWaker { id, shared })).wake();
+
+    Ok(TaskHandle { id })
+}
+
+
+struct BlockingState<T> {
+    result: Option<thread::Result<T>>,
+    waker: Option<Waker>,
+}
+
+/// The future returned by `spawn_blocking`
+pub struct BlockingTask<T> {
+    state: Arc<Mutex<BlockingState<T>>>,
+}
+
+impl<T> Future for BlockingTask<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.state.lock().unwrap();
+        match state.result.take() {
+            Some(Ok(value)) => Poll::Ready(value),
+            Some(Err(panic)) => std::panic::resume_unwind(panic),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/**
+    Runs a blocking function on a new worker thread. The returned future completes with the result of the function.
+    If the function panics, the panic is resumed in the awaiting task.
+*/
+pub fn spawn_blocking<F, T>(f: F) -> BlockingTask<T>
+    where F: FnOnce() -> T + Send + 'static,
+          T: Send + 'static
+{
+    let state = Arc::new(Mutex::new(BlockingState { result: None, waker: None }));
+
+    let worker_state = state.clone();
+    thread::spawn(move || {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+
+        let waker = {
+            let mut state = worker_state.lock().unwrap();
+            state.result = Some(result);
+            state.waker.take()
+        };
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    });
+
+    BlockingTask { state }
+}
@@ -0,0 +1,188 @@
+/*!
+    System backdrop materials (Mica, Acrylic) of Windows 11.
+
+    The backdrop is drawn by the system behind the window, where the window does not paint. When a backdrop is set,
+    the frame of the window is extended over the whole client area and the background of the window is cleared. Text and
+    controls painted with GDI over the backdrop are best used with the dark theme (see `set_window_theme`).
+
+    The documented `DWMWA_SYSTEMBACKDROP_TYPE` attribute requires Windows 11 22H2 (build 22621). The first release of Windows 11
+    (build 22000) only supports Mica with an undocumented attribute. Windows 10 does not support the backdrops.
+*/
+use winapi::shared::windef::HWND;
+use winapi::shared::minwindef::{BOOL, DWORD};
+use super::base_helper::windows_build;
+use super::window::{bind_raw_event_handler_inner, unbind_raw_event_handler, RawEventHandler};
+use crate::ControlHandle;
+use std::{mem, ptr, cell::RefCell, collections::HashMap};
+
+/// Id of the raw event handlers that clear the background of the windows
+const BACKDROP_HANDLER_ID: usize = 0xBAC0;
+
+/// First build of Windows 11
+const MICA_MIN_BUILD: u32 = 22000;
+
+/// First build of Windows 11 that supports `DWMWA_SYSTEMBACKDROP_TYPE`
+const SYSTEM_BACKDROP_MIN_BUILD: u32 = 22621;
+
+const DWMWA_SYSTEMBACKDROP_TYPE: DWORD = 38;
+const DWMWA_MICA_EFFECT: DWORD = 1029;
+
+const DWMSBT_AUTO: i32 = 0;
+const DWMSBT_NONE: i32 = 1;
+const DWMSBT_MAINWINDOW: i32 = 2;
+const DWMSBT_TRANSIENTWINDOW: i32 = 3;
+const DWMSBT_TABBEDWINDOW: i32 = 4;
+
+thread_local! {
+    static BACKDROPS: RefCell<HashMap<usize, (Backdrop, RawEventHandler)>> = RefCell::new(HashMap::new());
+}
+
+
+/// The material drawn behind a top level window
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Backdrop {
+    /// No backdrop. The window paints its background.
+    None,
+
+    /// The system chooses the backdrop of the window
+    Auto,
+
+    /// The material of the long lived windows. Tinted with the desktop wallpaper.
+    Mica,
+
+    /// The material of the transient windows (menus, flyouts). Blurs the windows below.
+    Acrylic,
+
+    /// The material of the windows with tabs in their title bar. A stronger tint of the desktop wallpaper.
+    Tabbed,
+}
+
+impl Default for Backdrop {
+    fn default() -> Backdrop { Backdrop::None }
+}
+
+/**
+    Returns the backdrop that the system can draw instead of `backdrop`:
+      * Windows 11 22H2 or later supports all the backdrops
+      * Windows 11 21H2 only supports Mica. `Acrylic`, `Tabbed` and `Auto` fall back to `Mica`.
+      * Older systems do not support the backdrops. Every backdrop falls back to `None`.
+*/
+pub fn supported_backdrop(backdrop: Backdrop) -> Backdrop {
+    let build = windows_build();
+
+    if backdrop == Backdrop::None || build >= SYSTEM_BACKDROP_MIN_BUILD {
+        backdrop
+    } else if build >= MICA_MIN_BUILD {
+        Backdrop::Mica
+    } else {
+        Backdrop::None
+    }
+}
+
+/**
+    Sets the backdrop of a top level window. Returns the backdrop that was applied, see `supported_backdrop`.
+    If the system does not support the backdrops, the window is not modified.
+
+    This function panics if `window` is not a window-like control.
+*/
+pub fn set_window_backdrop<C: Into<ControlHandle>>(window: C, backdrop: Backdrop) -> Backdrop {
+    let handle = window.into().hwnd().expect("Backdrops can only be set on window-like controls");
+    let backdrop = supported_backdrop(backdrop);
+
+    BACKDROPS.with(|backdrops| {
+        let mut backdrops = backdrops.borrow_mut();
+        let key = handle as usize;
+
+        let previous = match backdrops.remove(&key) {
+            Some((previous, handler)) => {
+                drop(unbind_raw_event_handler(&handler));
+                previous
+            },
+            None => Backdrop::None
+        };
+
+        if backdrop == Backdrop::None && previous == Backdrop::None {
+            return;
+        }
+
+        unsafe {
+            apply_backdrop(handle, backdrop);
+            extend_frame(handle, backdrop != Backdrop::None);
+        }
+
+        if backdrop != Backdrop::None {
+            if let Ok(handler) = bind_raw_event_handler_inner(&ControlHandle::Hwnd(handle), BACKDROP_HANDLER_ID, clear_background_proc) {
+                backdrops.insert(key, (backdrop, handler));
+            }
+        }
+    });
+
+    unsafe { redraw(handle); }
+
+    backdrop
+}
+
+/**
+    Returns the backdrop of a window set with `set_window_backdrop`
+*/
+pub fn window_backdrop<C: Into<ControlHandle>>(window: C) -> Backdrop {
+    let handle = window.into().hwnd().expect("Backdrops can only be set on window-like controls");
+    BACKDROPS.with(|backdrops| {
+        backdrops.borrow().get(&(handle as usize)).map(|(b, _)| *b).unwrap_or(Backdrop::None)
+    })
+}
+
+unsafe fn apply_backdrop(handle: HWND, backdrop: Backdrop) {
+    use winapi::um::dwmapi::DwmSetWindowAttribute;
+
+    if windows_build() >= SYSTEM_BACKDROP_MIN_BUILD {
+        let value = match backdrop {
+            Backdrop::None => DWMSBT_NONE,
+            Backdrop::Auto => DWMSBT_AUTO,
+            Backdrop::Mica => DWMSBT_MAINWINDOW,
+            Backdrop::Acrylic => DWMSBT_TRANSIENTWINDOW,
+            Backdrop::Tabbed => DWMSBT_TABBEDWINDOW,
+        };
+
+        DwmSetWindowAttribute(handle, DWMWA_SYSTEMBACKDROP_TYPE, &value as *const i32 as _, mem::size_of::<i32>() as DWORD);
+    } else {
+        let value: BOOL = (backdrop != Backdrop::None) as BOOL;
+        DwmSetWindowAttribute(handle, DWMWA_MICA_EFFECT, &value as *const BOOL as _, mem::size_of::<BOOL>() as DWORD);
+    }
+}
+
+/// The backdrop is only visible in the frame of the window
+unsafe fn extend_frame(handle: HWND, extend: bool) {
+    use winapi::um::dwmapi::DwmExtendFrameIntoClientArea;
+    use winapi::um::uxtheme::MARGINS;
+
+    let size = match extend {
+        true => -1,
+        false => 0
+    };
+
+    let margins = MARGINS { cxLeftWidth: size, cxRightWidth: size, cyTopHeight: size, cyBottomHeight: size };
+    DwmExtendFrameIntoClientArea(handle, &margins);
+}
+
+unsafe fn redraw(handle: HWND) {
+    use winapi::um::winuser::{RedrawWindow, RDW_ERASE, RDW_FRAME, RDW_INVALIDATE, RDW_ALLCHILDREN};
+    RedrawWindow(handle, ptr::null(), ptr::null_mut(), RDW_ERASE | RDW_FRAME | RDW_INVALIDATE | RDW_ALLCHILDREN);
+}
+
+/// Raw event handler of the windows with a backdrop. Black pixels are transparent in the extended frame.
+fn clear_background_proc(hwnd: HWND, msg: u32, w: usize, _l: isize) -> Option<isize> {
+    use winapi::um::winuser::{WM_ERASEBKGND, GetClientRect, FillRect};
+    use winapi::um::wingdi::{GetStockObject, BLACK_BRUSH};
+    use winapi::shared::windef::{HDC, RECT};
+
+    match msg {
+        WM_ERASEBKGND => unsafe {
+            let mut rect: RECT = mem::zeroed();
+            GetClientRect(hwnd, &mut rect);
+            FillRect(w as HDC, &rect, GetStockObject(BLACK_BRUSH as i32) as _);
+            Some(1)
+        },
+        _ => None
+    }
+}
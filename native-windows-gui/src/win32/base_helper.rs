@@ -7,6 +7,13 @@ use std::ffi::OsString;
 pub const CUSTOM_ID_BEGIN: u32 = 10000;
 
 
+/**
+    Returns the window handle of a control. Used by the methods of the controls (ex: the setters) before sending a message.
+
+    Panics if the control was not built, if the window was destroyed, if the handle is not a window handle (`bad_handle`),
+    or if the control is used from a thread that does not own it. Unlike the builders, the methods do not return a
+    `NwgError::WrongThread` error: `ControlHandle::check_thread` returns it before the methods are called.
+*/
 pub fn check_hwnd(handle: &ControlHandle, not_bound: &str, bad_handle: &str) -> HWND {
     use winapi::um::winuser::IsWindow;
 
@@ -14,6 +21,7 @@ pub fn check_hwnd(handle: &ControlHandle, not_bound: &str, bad_handle: &str) ->
     match handle.hwnd() {
         Some(hwnd) => match unsafe { IsWindow(hwnd) } {
             0 => { panic!("The window handle is no longer valid. This usually means the control was freed by the OS"); },
+            _ if !super::ui_thread::is_window_thread(hwnd) => { panic!("The control is used from a thread that does not own it. See `nwg::spawn_ui_thread`"); },
             _ => hwnd
         },
         None => { panic!("{}", bad_handle); }
@@ -30,6 +38,122 @@ pub fn to_utf16<'a>(s: &'a str) -> Vec<u16> {
       .collect()
 }
 
+/// Conversion buffers above this size are freed after use
+const UTF16_BUFFER_MAX: usize = 4096;
+
+thread_local! {
+    static UTF16_BUFFER: std::cell::RefCell<Vec<u16>> = std::cell::RefCell::new(Vec::with_capacity(256));
+}
+
+/**
+    Encode a string into a null terminated utf16 string and pass it to `f`.
+
+    Unlike `to_utf16`, the string is encoded in a buffer reused by every call on the same thread, so
+    setters called in loops (ex: populating a list) do not allocate. If the buffer is already in use
+    (ex: `f` sends a message whose handler sets another text), a new vector is allocated instead.
+*/
+pub fn with_utf16<'a, R, F: FnOnce(&[u16]) -> R>(s: &'a str, f: F) -> R {
+    let mut f = Some(f);
+
+    let result = UTF16_BUFFER.with(|buffer| {
+        let mut buffer = buffer.try_borrow_mut().ok()?;
+        buffer.clear();
+        buffer.extend(s.encode_utf16());
+        buffer.push(0);
+
+        let f = f.take().unwrap();
+        let result = f(&buffer);
+
+        if buffer.capacity() > UTF16_BUFFER_MAX {
+            buffer.clear();
+            buffer.shrink_to_fit();
+        }
+
+        Some(result)
+    });
+
+    match (result, f) {
+        (Some(result), _) => result,
+        (None, Some(f)) => f(&to_utf16(s)),
+        (None, None) => unreachable!(),
+    }
+}
+
+/// Returns the length of `s` encoded in utf16. Used by the `wstr!` macro.
+#[doc(hidden)]
+pub const fn utf16_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    let mut len = 0;
+
+    while i < bytes.len() {
+        let b = bytes[i];
+        if b < 0x80 { i += 1; len += 1; }
+        else if b < 0xE0 { i += 2; len += 1; }
+        else if b < 0xF0 { i += 3; len += 1; }
+        else { i += 4; len += 2; }
+    }
+
+    len
+}
+
+/// Encodes `s` in utf16 at compile time. `N` must be greater than `utf16_len(s)`. Used by the `wstr!` macro.
+#[doc(hidden)]
+pub const fn encode_utf16<const N: usize>(s: &str) -> [u16; N] {
+    let bytes = s.as_bytes();
+    let mut out = [0u16; N];
+    let mut i = 0;
+    let mut o = 0;
+
+    while i < bytes.len() {
+        let b = bytes[i] as u32;
+        let (c, size) = if b < 0x80 {
+            (b, 1)
+        } else if b < 0xE0 {
+            (((b & 0x1F) << 6) | (bytes[i+1] as u32 & 0x3F), 2)
+        } else if b < 0xF0 {
+            (((b & 0x0F) << 12) | ((bytes[i+1] as u32 & 0x3F) << 6) | (bytes[i+2] as u32 & 0x3F), 3)
+        } else {
+            (((b & 0x07) << 18) | ((bytes[i+1] as u32 & 0x3F) << 12) | ((bytes[i+2] as u32 & 0x3F) << 6) | (bytes[i+3] as u32 & 0x3F), 4)
+        };
+
+        if c >= 0x10000 {
+            let c = c - 0x10000;
+            out[o] = (0xD800 + (c >> 10)) as u16;
+            out[o+1] = (0xDC00 + (c & 0x3FF)) as u16;
+            o += 2;
+        } else {
+            out[o] = c as u16;
+            o += 1;
+        }
+
+        i += size;
+    }
+
+    out
+}
+
+/**
+    Encodes a string literal into a null terminated utf16 string at compile time.
+    Returns a `&'static [u16]`, including the terminating null character.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn set_status(status: &nwg::StatusBar) {
+        status.set_text_wide(0, nwg::wstr!("Ready"));
+    }
+    ```
+*/
+#[macro_export]
+macro_rules! wstr {
+    ($s:expr) => {{
+        const WSTR_LEN: usize = $crate::__utf16_len($s) + 1;
+        static WSTR: [u16; WSTR_LEN] = $crate::__encode_utf16::<WSTR_LEN>($s);
+        &WSTR as &'static [u16]
+    }};
+}
+
 /**
     Decode a raw utf16 string. Should be null terminated.
 */
@@ -112,3 +236,48 @@ pub unsafe fn get_system_error() -> (DWORD, String) {
 
     (code, error_message)
 }
+
+/// Returns the build number of Windows. `GetVersionEx` cannot be used because it lies to the applications without a manifest.
+#[cfg(any(feature = "dark-mode", feature = "backdrop"))]
+pub fn windows_build() -> u32 {
+    use std::{mem, cell::Cell};
+    use winapi::um::libloaderapi::{GetModuleHandleW, GetProcAddress};
+    use winapi::um::winnt::OSVERSIONINFOW;
+
+    type RtlGetVersion = unsafe extern "system" fn(*mut OSVERSIONINFOW) -> i32;
+
+    thread_local! {
+        static BUILD: Cell<Option<u32>> = Cell::new(None);
+    }
+
+    BUILD.with(|build| {
+        if let Some(b) = build.get() {
+            return b;
+        }
+
+        let b = unsafe {
+            let name = to_utf16("ntdll.dll");
+            let ntdll = GetModuleHandleW(name.as_ptr());
+            let f = match ntdll.is_null() {
+                true => ptr::null_mut(),
+                false => GetProcAddress(ntdll, "RtlGetVersion\0".as_ptr() as *const i8)
+            };
+
+            match f.is_null() {
+                true => 0,
+                false => {
+                    let rtl_get_version: RtlGetVersion = mem::transmute(f);
+                    let mut info: OSVERSIONINFOW = mem::zeroed();
+                    info.dwOSVersionInfoSize = mem::size_of::<OSVERSIONINFOW>() as DWORD;
+                    match rtl_get_version(&mut info) {
+                        0 => info.dwBuildNumber,
+                        _ => 0
+                    }
+                }
+            }
+        };
+
+        build.set(Some(b));
+        b
+    })
+}
@@ -0,0 +1,110 @@
+/*!
+    Measures the time spent building each control, resource and partial of a UI.
+
+    Requires the `build-profiling` feature. Without the feature, `BuildProfile` compiles to nothing.
+
+    native-windows-derive only measures the builders of the structs that have the `#[nwg_profile]` attribute.
+
+    The timings are stored per thread. The time of a partial (or of a whole UI) includes the time of the controls it contains.
+*/
+
+#[cfg(feature = "build-profiling")]
+use std::{cell::RefCell, time::{Duration, Instant}};
+
+
+/// The build time of a single control, resource or partial. Returned by `build_timings`.
+#[cfg(feature = "build-profiling")]
+#[derive(Clone, Debug)]
+pub struct BuildTiming {
+    /// The name of the built object. With native-windows-derive, this is the name of the struct field.
+    pub name: &'static str,
+
+    /// The type of the built object
+    pub kind: &'static str,
+
+    /// The time spent in the builder
+    pub duration: Duration,
+}
+
+#[cfg(feature = "build-profiling")]
+thread_local! {
+    static TIMINGS: RefCell<Vec<BuildTiming>> = RefCell::new(Vec::new());
+}
+
+/**
+    Measures the time between its creation and its drop. Used by native-windows-derive to measure the builders.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn build_font(font: &mut nwg::Font) -> Result<(), nwg::NwgError> {
+        let _profile = nwg::BuildProfile::start("font", "Font");
+        nwg::Font::builder().family("Segoe UI").build(font)
+    }
+    ```
+*/
+pub struct BuildProfile {
+    #[cfg(feature = "build-profiling")]
+    name: &'static str,
+
+    #[cfg(feature = "build-profiling")]
+    kind: &'static str,
+
+    #[cfg(feature = "build-profiling")]
+    start: Instant,
+}
+
+impl BuildProfile {
+
+    #[cfg(feature = "build-profiling")]
+    pub fn start(name: &'static str, kind: &'static str) -> BuildProfile {
+        BuildProfile { name, kind, start: Instant::now() }
+    }
+
+    #[cfg(not(feature = "build-profiling"))]
+    #[inline(always)]
+    pub fn start(_name: &'static str, _kind: &'static str) -> BuildProfile {
+        BuildProfile {}
+    }
+
+}
+
+#[cfg(feature = "build-profiling")]
+impl Drop for BuildProfile {
+    fn drop(&mut self) {
+        let timing = BuildTiming { name: self.name, kind: self.kind, duration: self.start.elapsed() };
+        TIMINGS.with(|t| t.borrow_mut().push(timing));
+    }
+}
+
+/// Return the build timings recorded on the current thread, in the order the builds finished
+#[cfg(feature = "build-profiling")]
+pub fn build_timings() -> Vec<BuildTiming> {
+    TIMINGS.with(|t| t.borrow().clone())
+}
+
+/// Remove the build timings recorded on the current thread
+#[cfg(feature = "build-profiling")]
+pub fn clear_build_timings() {
+    TIMINGS.with(|t| t.borrow_mut().clear());
+}
+
+/**
+    Print the build timings recorded on the current thread on stderr, slowest first.
+    Only the `count` slowest builds are printed.
+*/
+#[cfg(feature = "build-profiling")]
+pub fn print_build_report(count: usize) {
+    let mut timings = build_timings();
+    if timings.is_empty() {
+        return;
+    }
+
+    timings.sort_by(|a, b| b.duration.cmp(&a.duration));
+
+    eprintln!("[NWG] {} build(s) recorded, slowest first:", timings.len());
+    for t in timings.iter().take(count) {
+        let ms = t.duration.as_secs_f64() * 1000.0;
+        eprintln!("[NWG] {:>10.3} ms  {} ({})", ms, t.name, t.kind);
+    }
+}
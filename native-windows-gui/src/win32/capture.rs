@@ -0,0 +1,219 @@
+/*!
+    Capture of the pixels of a window, and a minimal PNG writer to save the captures.
+*/
+use winapi::shared::windef::RECT;
+use crate::{ControlHandle, NwgError};
+use std::{fs, io, mem, ptr, slice, path::Path};
+
+/// Largest block of a deflate stream stored without compression
+const MAX_STORED_BLOCK: usize = 0xFFFF;
+
+
+/**
+    The pixels of the client area of a window, captured with `capture_window`.
+
+    The pixels are stored row by row, from the top left corner, with 4 bytes per pixel in the BGRA order.
+    The alpha channel is always 255.
+
+    Requires the `window-capture` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn screenshot(window: &nwg::Window) {
+        let capture = nwg::capture_window(window).expect("Failed to capture the window");
+        capture.save_png("screenshot.png").expect("Failed to save the capture");
+    }
+    ```
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WindowCapture {
+    size: (u32, u32),
+    pixels: Vec<u8>,
+}
+
+impl WindowCapture {
+
+    /// Creates a capture from BGRA pixels. Returns `None` if the length of `pixels` is not `width * height * 4`.
+    pub fn from_pixels(size: (u32, u32), pixels: Vec<u8>) -> Option<WindowCapture> {
+        match pixels.len() == (size.0 as usize) * (size.1 as usize) * 4 {
+            true => Some(WindowCapture { size, pixels }),
+            false => None
+        }
+    }
+
+    /// Returns the size of the capture in pixels
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Returns the pixels of the capture in the BGRA format
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Returns the [red, green, blue, alpha] color of a pixel. Panics if the pixel is outside the capture.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        assert!(x < self.size.0 && y < self.size.1, "Pixel outside of the capture");
+
+        let i = ((y * self.size.0 + x) * 4) as usize;
+        let p = &self.pixels[i..i+4];
+        [p[2], p[1], p[0], p[3]]
+    }
+
+    /// Writes the capture to a PNG file. The image is not compressed.
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, encode_png(self))
+    }
+
+}
+
+/**
+    Captures the client area of a window. The window is asked to paint itself in a bitmap, so the capture
+    works even if the window is covered by other windows. The window must be visible.
+
+    Requires the `window-capture` feature.
+
+    Panics if `window` is not a window-like control.
+*/
+pub fn capture_window<C: Into<ControlHandle>>(window: C) -> Result<WindowCapture, NwgError> {
+    use winapi::um::winuser::{GetClientRect, GetDC, ReleaseDC, PrintWindow, PW_CLIENTONLY, PW_RENDERFULLCONTENT};
+    use winapi::um::wingdi::{CreateCompatibleDC, CreateDIBSection, SelectObject, DeleteObject, DeleteDC, GdiFlush,
+        BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS};
+
+    let handle = window.into().hwnd().expect("Window to be a window-like control");
+
+    unsafe {
+        let mut rect: RECT = mem::zeroed();
+        GetClientRect(handle, &mut rect);
+
+        let (width, height) = (rect.right - rect.left, rect.bottom - rect.top);
+        if width <= 0 || height <= 0 {
+            return Err(NwgError::resource_create("The window to capture has an empty client area"));
+        }
+
+        let mut info: BITMAPINFO = mem::zeroed();
+        info.bmiHeader = BITMAPINFOHEADER {
+            biSize: mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: width,
+            biHeight: -height,  // Top-down rows
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB,
+            biSizeImage: 0,
+            biXPelsPerMeter: 0,
+            biYPelsPerMeter: 0,
+            biClrUsed: 0,
+            biClrImportant: 0,
+        };
+
+        let screen = GetDC(ptr::null_mut());
+        let dc = CreateCompatibleDC(screen);
+        ReleaseDC(ptr::null_mut(), screen);
+
+        let mut bits = ptr::null_mut();
+        let bitmap = CreateDIBSection(dc, &info, DIB_RGB_COLORS, &mut bits, ptr::null_mut(), 0);
+        if bitmap.is_null() {
+            DeleteDC(dc);
+            return Err(NwgError::resource_create("Failed to create the capture bitmap"));
+        }
+
+        let old = SelectObject(dc, bitmap as _);
+        let printed = PrintWindow(handle, dc, PW_CLIENTONLY | PW_RENDERFULLCONTENT) != 0;
+        GdiFlush();
+
+        let mut pixels = slice::from_raw_parts(bits as *const u8, (width * height * 4) as usize).to_vec();
+
+        SelectObject(dc, old);
+        DeleteObject(bitmap as _);
+        DeleteDC(dc);
+
+        if !printed {
+            return Err(NwgError::resource_create("PrintWindow failed on the window to capture"));
+        }
+
+        // GDI does not write the alpha channel
+        for alpha in pixels.iter_mut().skip(3).step_by(4) {
+            *alpha = 255;
+        }
+
+        Ok(WindowCapture { size: (width as u32, height as u32), pixels })
+    }
+}
+
+/// Encodes a capture as a RGBA PNG. The image data is stored in uncompressed deflate blocks.
+fn encode_png(capture: &WindowCapture) -> Vec<u8> {
+    let (width, height) = capture.size;
+
+    // Each row starts with the filter type (0: none)
+    let mut raw = Vec::with_capacity(((width * 4 + 1) * height) as usize);
+    for row in capture.pixels.chunks((width * 4) as usize) {
+        raw.push(0);
+        for p in row.chunks(4) {
+            raw.extend_from_slice(&[p[2], p[1], p[0], p[3]]);
+        }
+    }
+
+    let mut zlib = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(MAX_STORED_BLOCK).peekable();
+    if blocks.peek().is_none() {
+        zlib.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none() as u8;
+        let length = block.len() as u16;
+        zlib.push(last);
+        zlib.extend_from_slice(&length.to_le_bytes());
+        zlib.extend_from_slice(&(!length).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);  // 8 bits per channel, RGBA, deflate, no filter, no interlace
+
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib);
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xEDB8_8320,
+                _ => crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+
+    (b << 16) | a
+}
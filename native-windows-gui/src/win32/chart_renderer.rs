@@ -0,0 +1,412 @@
+/*!
+    Direct2D renderer used by the chart control.
+
+    All coordinates are in device independent pixels (DIPs). The render target is created on the first draw and
+    recreated if the device is lost.
+*/
+use winapi::shared::windef::HWND;
+use winapi::um::winuser::NMHDR;
+use winapi::um::d2d1::*;
+use winapi::um::dcommon::DWRITE_MEASURING_MODE_NATURAL;
+use winapi::um::dwrite::{IDWriteFactory, IDWriteTextFormat, DWriteCreateFactory, DWRITE_FACTORY_TYPE_SHARED};
+use winapi::shared::winerror::{S_OK, D2DERR_RECREATE_TARGET};
+use super::base_helper::to_utf16;
+use super::window_helper;
+use crate::NwgError;
+use std::{mem, ptr};
+
+
+/// Notification code sent by a chart when the mouse moves over a data point
+pub(crate) const CHART_POINT_HOVER: u32 = 1;
+
+/// Notification code sent by a chart when the mouse leaves the hovered data point
+pub(crate) const CHART_POINT_LEAVE: u32 = 2;
+
+/// The WM_NOTIFY data sent by a chart to its parent
+#[repr(C)]
+pub(crate) struct NMCHART {
+    pub hdr: NMHDR,
+    pub series: usize,
+    pub index: usize,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A rectangle in DIPs: left, top, right, bottom
+pub(crate) type Rect = [f32; 4];
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) enum TextStyle {
+    Normal,
+    Title,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub(crate) enum TextAlign {
+    Left,
+    Center,
+    Right,
+}
+
+pub(crate) struct ChartRenderer {
+    factory: *mut ID2D1Factory,
+    write_factory: *mut IDWriteFactory,
+    normal_format: *mut IDWriteTextFormat,
+    title_format: *mut IDWriteTextFormat,
+
+    // Device dependent resources. Null until the first draw or after the device was lost.
+    target: *mut ID2D1HwndRenderTarget,
+    brush: *mut ID2D1SolidColorBrush,
+}
+
+impl ChartRenderer {
+
+    pub(crate) fn new() -> Result<ChartRenderer, NwgError> {
+        use winapi::ctypes::c_void;
+        use winapi::Interface;
+        use winapi::um::dwrite::{DWRITE_FONT_WEIGHT_NORMAL, DWRITE_FONT_WEIGHT_SEMI_BOLD};
+
+        let mut renderer = ChartRenderer {
+            factory: ptr::null_mut(),
+            write_factory: ptr::null_mut(),
+            normal_format: ptr::null_mut(),
+            title_format: ptr::null_mut(),
+            target: ptr::null_mut(),
+            brush: ptr::null_mut(),
+        };
+
+        unsafe {
+            let result = D2D1CreateFactory(
+                D2D1_FACTORY_TYPE_SINGLE_THREADED,
+                &ID2D1Factory::uuidof(),
+                ptr::null(),
+                (&mut renderer.factory as *mut *mut ID2D1Factory) as *mut *mut c_void
+            );
+
+            if result != S_OK {
+                return Err(NwgError::control_create("Failed to create the direct2D factory"));
+            }
+
+            let result = DWriteCreateFactory(
+                DWRITE_FACTORY_TYPE_SHARED,
+                &IDWriteFactory::uuidof(),
+                (&mut renderer.write_factory as *mut *mut IDWriteFactory) as _
+            );
+
+            if result != S_OK {
+                return Err(NwgError::control_create("Failed to create the directwrite factory"));
+            }
+
+            renderer.normal_format = renderer.create_text_format(12.0, DWRITE_FONT_WEIGHT_NORMAL)?;
+            renderer.title_format = renderer.create_text_format(15.0, DWRITE_FONT_WEIGHT_SEMI_BOLD)?;
+        }
+
+        Ok(renderer)
+    }
+
+    /// Starts drawing in the window. Returns `false` if the render target could not be created.
+    pub(crate) fn begin_draw(&mut self, hwnd: HWND) -> bool {
+        if self.target.is_null() && unsafe { self.create_target(hwnd) }.is_err() {
+            return false;
+        }
+
+        unsafe {
+            let target = &*self.target;
+            target.BeginDraw();
+            target.SetTransform(&identity());
+        }
+
+        true
+    }
+
+    /// Ends the drawing. If the device was lost, the render target is recreated on the next draw.
+    pub(crate) fn end_draw(&mut self) {
+        let result = unsafe { (&*self.target).EndDraw(ptr::null_mut(), ptr::null_mut()) };
+        if result == D2DERR_RECREATE_TARGET {
+            self.release_target();
+        }
+    }
+
+    /// Resizes the render target to the size of the window
+    pub(crate) fn resize(&mut self, hwnd: HWND) {
+        if self.target.is_null() {
+            return;
+        }
+
+        unsafe {
+            let (width, height) = window_helper::get_window_physical_size(hwnd);
+            (&*self.target).Resize(&D2D1_SIZE_U { width, height });
+        }
+    }
+
+    /// Returns the size of the render target in DIPs
+    pub(crate) fn size(&self) -> (f32, f32) {
+        if self.target.is_null() {
+            return (0.0, 0.0);
+        }
+
+        let size = unsafe { (&*self.target).GetSize() };
+        (size.width, size.height)
+    }
+
+    /// Converts a position in physical pixels into DIPs
+    pub(crate) fn to_dips(&self, x: i32, y: i32) -> (f32, f32) {
+        if self.target.is_null() {
+            return (x as f32, y as f32);
+        }
+
+        let (mut dpi_x, mut dpi_y) = (96.0, 96.0);
+        unsafe { (&*self.target).GetDpi(&mut dpi_x, &mut dpi_y); }
+        (x as f32 * 96.0 / dpi_x, y as f32 * 96.0 / dpi_y)
+    }
+
+    pub(crate) fn clear(&self, color: [u8; 3]) {
+        unsafe { (&*self.target).Clear(&d2d_color(color, 1.0)); }
+    }
+
+    pub(crate) fn fill_rect(&self, rect: Rect, color: [u8; 3], alpha: f32) {
+        let brush = self.brush(color, alpha);
+        unsafe { (&*self.target).FillRectangle(&d2d_rect(rect), brush); }
+    }
+
+    pub(crate) fn draw_rect(&self, rect: Rect, color: [u8; 3], width: f32) {
+        let brush = self.brush(color, 1.0);
+        unsafe { (&*self.target).DrawRectangle(&d2d_rect(rect), brush, width, ptr::null_mut()); }
+    }
+
+    pub(crate) fn line(&self, from: (f32, f32), to: (f32, f32), color: [u8; 3], width: f32) {
+        let brush = self.brush(color, 1.0);
+        unsafe {
+            (&*self.target).DrawLine(
+                D2D1_POINT_2F { x: from.0, y: from.1 },
+                D2D1_POINT_2F { x: to.0, y: to.1 },
+                brush,
+                width,
+                ptr::null_mut()
+            );
+        }
+    }
+
+    pub(crate) fn fill_circle(&self, center: (f32, f32), radius: f32, color: [u8; 3]) {
+        let brush = self.brush(color, 1.0);
+        let ellipse = D2D1_ELLIPSE { point: D2D1_POINT_2F { x: center.0, y: center.1 }, radiusX: radius, radiusY: radius };
+        unsafe { (&*self.target).FillEllipse(&ellipse, brush); }
+    }
+
+    pub(crate) fn draw_circle(&self, center: (f32, f32), radius: f32, color: [u8; 3], width: f32) {
+        let brush = self.brush(color, 1.0);
+        let ellipse = D2D1_ELLIPSE { point: D2D1_POINT_2F { x: center.0, y: center.1 }, radiusX: radius, radiusY: radius };
+        unsafe { (&*self.target).DrawEllipse(&ellipse, brush, width, ptr::null_mut()); }
+    }
+
+    /// Draws a single line of text in `rect`. The text is centered vertically.
+    pub(crate) fn text(&self, text: &str, rect: Rect, style: TextStyle, align: TextAlign, color: [u8; 3]) {
+        use winapi::um::dwrite::{DWRITE_TEXT_ALIGNMENT_LEADING, DWRITE_TEXT_ALIGNMENT_CENTER, DWRITE_TEXT_ALIGNMENT_TRAILING};
+
+        let format = self.format(style);
+        let text = to_utf16(text);
+        let brush = self.brush(color, 1.0);
+
+        let alignment = match align {
+            TextAlign::Left => DWRITE_TEXT_ALIGNMENT_LEADING,
+            TextAlign::Center => DWRITE_TEXT_ALIGNMENT_CENTER,
+            TextAlign::Right => DWRITE_TEXT_ALIGNMENT_TRAILING,
+        };
+
+        unsafe {
+            (&*format).SetTextAlignment(alignment);
+            (&*self.target).DrawText(
+                text.as_ptr(),
+                (text.len() - 1) as _,
+                format,
+                &d2d_rect(rect),
+                brush,
+                D2D1_DRAW_TEXT_OPTIONS_CLIP,
+                DWRITE_MEASURING_MODE_NATURAL,
+            );
+        }
+    }
+
+    /// Draws a single line of text rotated by 90 degrees counter clockwise, centered in `rect`
+    pub(crate) fn vertical_text(&self, text: &str, rect: Rect, style: TextStyle, color: [u8; 3]) {
+        let [left, top, right, bottom] = rect;
+
+        // Rotates the local coordinates so that the text goes from the bottom to the top of `rect`
+        let rotation = D2D1_MATRIX_3X2_F { matrix: [[0.0, -1.0], [1.0, 0.0], [left, bottom]] };
+        unsafe { (&*self.target).SetTransform(&rotation); }
+        self.text(text, [0.0, 0.0, bottom - top, right - left], style, TextAlign::Center, color);
+        unsafe { (&*self.target).SetTransform(&identity()); }
+    }
+
+    /// Returns the width and the height of `text` in DIPs
+    pub(crate) fn measure_text(&self, text: &str, style: TextStyle) -> (f32, f32) {
+        use winapi::um::dwrite::DWRITE_TEXT_METRICS;
+
+        let text = to_utf16(text);
+        let format = self.format(style);
+
+        unsafe {
+            let mut layout = ptr::null_mut();
+            let result = (&*self.write_factory).CreateTextLayout(
+                text.as_ptr(),
+                (text.len() - 1) as _,
+                format,
+                10000.0,
+                10000.0,
+                &mut layout,
+            );
+
+            if result != S_OK {
+                return (0.0, 0.0);
+            }
+
+            let layout = &*layout;
+            let mut metrics: DWRITE_TEXT_METRICS = mem::zeroed();
+            layout.GetMetrics(&mut metrics);
+            layout.Release();
+
+            (metrics.widthIncludingTrailingWhitespace, metrics.height)
+        }
+    }
+
+    pub(crate) fn push_clip(&self, rect: Rect) {
+        unsafe { (&*self.target).PushAxisAlignedClip(&d2d_rect(rect), D2D1_ANTIALIAS_MODE_ALIASED); }
+    }
+
+    pub(crate) fn pop_clip(&self) {
+        unsafe { (&*self.target).PopAxisAlignedClip(); }
+    }
+
+    fn brush(&self, color: [u8; 3], alpha: f32) -> *mut ID2D1Brush {
+        unsafe {
+            (&*self.brush).SetColor(&d2d_color(color, alpha));
+        }
+
+        self.brush as _
+    }
+
+    fn format(&self, style: TextStyle) -> *mut IDWriteTextFormat {
+        match style {
+            TextStyle::Normal => self.normal_format,
+            TextStyle::Title => self.title_format,
+        }
+    }
+
+    unsafe fn create_text_format(&self, size: f32, weight: u32) -> Result<*mut IDWriteTextFormat, NwgError> {
+        use winapi::um::dwrite::{DWRITE_FONT_STYLE_NORMAL, DWRITE_FONT_STRETCH_NORMAL, DWRITE_PARAGRAPH_ALIGNMENT_CENTER, DWRITE_WORD_WRAPPING_NO_WRAP};
+
+        let family = to_utf16("Segoe UI");
+        let locale = to_utf16("");
+        let mut format = ptr::null_mut();
+
+        let result = (&*self.write_factory).CreateTextFormat(
+            family.as_ptr(),
+            ptr::null_mut(),
+            weight,
+            DWRITE_FONT_STYLE_NORMAL,
+            DWRITE_FONT_STRETCH_NORMAL,
+            size,
+            locale.as_ptr(),
+            &mut format
+        );
+
+        if result != S_OK {
+            return Err(NwgError::control_create("Failed to create the chart text format"));
+        }
+
+        (&*format).SetParagraphAlignment(DWRITE_PARAGRAPH_ALIGNMENT_CENTER);
+        (&*format).SetWordWrapping(DWRITE_WORD_WRAPPING_NO_WRAP);
+
+        Ok(format)
+    }
+
+    unsafe fn create_target(&mut self, hwnd: HWND) -> Result<(), NwgError> {
+        use winapi::shared::dxgiformat::DXGI_FORMAT_B8G8R8A8_UNORM;
+        use winapi::um::dcommon::{D2D1_PIXEL_FORMAT, D2D1_ALPHA_MODE_PREMULTIPLIED};
+
+        let (width, height) = window_helper::get_window_physical_size(hwnd);
+
+        let render_props = D2D1_RENDER_TARGET_PROPERTIES {
+            _type: D2D1_RENDER_TARGET_TYPE_DEFAULT,
+            pixelFormat: D2D1_PIXEL_FORMAT { format: DXGI_FORMAT_B8G8R8A8_UNORM, alphaMode: D2D1_ALPHA_MODE_PREMULTIPLIED },
+            dpiX: 0.0, dpiY: 0.0,
+            usage: D2D1_RENDER_TARGET_USAGE_NONE,
+            minLevel: D2D1_FEATURE_LEVEL_DEFAULT
+        };
+
+        let hwnd_render_props = D2D1_HWND_RENDER_TARGET_PROPERTIES {
+            hwnd,
+            pixelSize: D2D1_SIZE_U { width, height },
+            presentOptions: D2D1_PRESENT_OPTIONS_NONE
+        };
+
+        if (&*self.factory).CreateHwndRenderTarget(&render_props, &hwnd_render_props, &mut self.target) != S_OK {
+            self.target = ptr::null_mut();
+            return Err(NwgError::control_create("Failed to create the direct2D render target"));
+        }
+
+        let props = D2D1_BRUSH_PROPERTIES { opacity: 1.0, transform: identity() };
+        if (&*self.target).CreateSolidColorBrush(&d2d_color([0, 0, 0], 1.0), &props, &mut self.brush) != S_OK {
+            self.release_target();
+            return Err(NwgError::control_create("Failed to create the direct2D brush"));
+        }
+
+        Ok(())
+    }
+
+    fn release_target(&mut self) {
+        unsafe {
+            if !self.brush.is_null() {
+                (&*self.brush).Release();
+                self.brush = ptr::null_mut();
+            }
+
+            if !self.target.is_null() {
+                (&*self.target).Release();
+                self.target = ptr::null_mut();
+            }
+        }
+    }
+
+}
+
+impl Drop for ChartRenderer {
+
+    fn drop(&mut self) {
+        self.release_target();
+
+        unsafe {
+            for &format in [self.normal_format, self.title_format].iter() {
+                if !format.is_null() {
+                    (&*format).Release();
+                }
+            }
+
+            if !self.write_factory.is_null() {
+                (&*self.write_factory).Release();
+            }
+
+            if !self.factory.is_null() {
+                (&*self.factory).Release();
+            }
+        }
+    }
+
+}
+
+fn identity() -> D2D1_MATRIX_3X2_F {
+    D2D1_MATRIX_3X2_F { matrix: [[1.0, 0.0], [0.0, 1.0], [0.0, 0.0]] }
+}
+
+fn d2d_rect(rect: Rect) -> D2D1_RECT_F {
+    D2D1_RECT_F { left: rect[0], top: rect[1], right: rect[2], bottom: rect[3] }
+}
+
+fn d2d_color(color: [u8; 3], alpha: f32) -> D2D1_COLOR_F {
+    D2D1_COLOR_F {
+        r: color[0] as f32 / 255.0,
+        g: color[1] as f32 / 255.0,
+        b: color[2] as f32 / 255.0,
+        a: alpha,
+    }
+}
@@ -1,10 +1,27 @@
 use crate::controls::ControlHandle;
+use crate::DropFiles;
 use super::base_helper::{to_utf16};
-use winapi::um::winuser::{CF_BITMAP, CF_TEXT, CF_UNICODETEXT};
+use winapi::um::winuser::{CF_BITMAP, CF_TEXT, CF_UNICODETEXT, CF_DIB, CF_HDROP};
 use winapi::um::winnt::HANDLE;
+use winapi::shared::minwindef::{BOOL, DWORD};
+use winapi::shared::windef::POINT;
 
+const HTML_FORMAT: &'static str = "HTML Format";
+const RTF_FORMAT: &'static str = "Rich Text Format";
+const PNG_FORMAT: &'static str = "PNG";
 
-#[derive(Copy, Clone)]
+/// Not declared by winapi
+#[repr(C)]
+#[allow(non_snake_case)]
+struct DROPFILES {
+    pFiles: DWORD,
+    pt: POINT,
+    fNC: BOOL,
+    fWide: BOOL,
+}
+
+
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub enum ClipboardFormat {
     /// ANSI text. You probably want to use `UnicodeText`.
     Text,
@@ -15,15 +32,33 @@ pub enum ClipboardFormat {
     /// A bitmap file
     Bitmap,
 
+    /// An HTML fragment, with the `CF_HTML` header. See `set_data_html`.
+    Html,
+
+    /// A rich text document
+    Rtf,
+
+    /// A device independent bitmap: a `BITMAPINFOHEADER` followed by the pixels. See `set_data_bitmap`.
+    Dib,
+
+    /// The content of a PNG file. Used by the browsers and the office applications to keep the transparency of the images.
+    Png,
+
+    /// A list of files copied in the explorer. See `set_data_files`.
+    Files,
+
     /// Global clipboard format to share data between applications
     /// The format name comparison is case-insensitive.
-    Global(&'static str)
+    Global(&'static str),
+
+    /// A format identified by its raw value, as returned by `available_formats`
+    Raw(u32),
 }
 
 
 impl ClipboardFormat {
 
-    fn into_raw(&self) -> u32 {
+    pub(crate) fn into_raw(&self) -> u32 {
         use ClipboardFormat::*;
         use winapi::um::winuser::RegisterClipboardFormatW;
 
@@ -31,6 +66,12 @@ impl ClipboardFormat {
             Text => CF_TEXT,
             UnicodeText => CF_UNICODETEXT,
             Bitmap => CF_BITMAP,
+            Dib => CF_DIB,
+            Files => CF_HDROP,
+            Html => Global(HTML_FORMAT).into_raw(),
+            Rtf => Global(RTF_FORMAT).into_raw(),
+            Png => Global(PNG_FORMAT).into_raw(),
+            Raw(v) => *v,
             Global(v) => unsafe {
                 let v = to_utf16(v);
                 RegisterClipboardFormatW(v.as_ptr())
@@ -38,6 +79,22 @@ impl ClipboardFormat {
         }
     }
 
+    fn from_raw(format: u32) -> ClipboardFormat {
+        use ClipboardFormat::*;
+
+        match format {
+            CF_TEXT => Text,
+            CF_UNICODETEXT => UnicodeText,
+            CF_BITMAP => Bitmap,
+            CF_DIB => Dib,
+            CF_HDROP => Files,
+            f if f == Html.into_raw() => Html,
+            f if f == Rtf.into_raw() => Rtf,
+            f if f == Png.into_raw() => Png,
+            f => Raw(f)
+        }
+    }
+
 }
 
 /// Wrapper over a clipboard global allocation handle.
@@ -81,6 +138,25 @@ fn clipboard_text(window: &nwg::Window) {
 ```
 
 
+Writing / Reading rich formats
+
+```rust
+use native_windows_gui as nwg;
+
+fn copy_link(window: &nwg::Window) {
+    nwg::Clipboard::set_data_html(window, "<a href=\"https://example.com\">Example</a>", Some("https://example.com"));
+}
+
+fn paste_files(window: &nwg::Window) -> Vec<String> {
+    if nwg::Clipboard::available_formats(window).contains(&nwg::ClipboardFormat::Files) {
+        nwg::Clipboard::data_files(window).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+```
+
+
 Writing / Reading custom data
 
 ```rust
@@ -220,6 +296,215 @@ impl Clipboard {
         data
     }
 
+    /**
+        Fill the clipboard with an HTML fragment (ex: `<b>Hello</b>`). The `CF_HTML` header expected by the other
+        applications is generated around the fragment. `text` is the plain text version of the fragment,
+        used by the applications that cannot read HTML.
+
+        This is a high level function that handles `open` and `close`
+    */
+    pub fn set_data_html<C: Into<ControlHandle>>(handle: C, html: &str, text: Option<&str>) {
+        Clipboard::open(handle);
+        Clipboard::empty();
+
+        Clipboard::set_data_bytes(ClipboardFormat::Html, &html_clipboard_data(html));
+        if let Some(text) = text {
+            Clipboard::set_data_bytes(ClipboardFormat::UnicodeText, &utf16_bytes(text));
+        }
+
+        Clipboard::close();
+    }
+
+    /**
+        Return the HTML fragment in the clipboard (if there is one), without the `CF_HTML` header.
+        The fragment may not be a complete document (ex: a part of a table copied in a browser).
+    */
+    pub fn data_html<C: Into<ControlHandle>>(handle: C) -> Option<String> {
+        Clipboard::open(handle);
+        let data = Clipboard::data_bytes(ClipboardFormat::Html);
+        Clipboard::close();
+
+        data.map(|data| html_fragment(&data))
+    }
+
+    /**
+        Fill the clipboard with a rich text document (ex: `{\rtf1 {\b Hello}}`). `text` is the plain text version of the document,
+        used by the applications that cannot read rich text.
+
+        This is a high level function that handles `open` and `close`
+    */
+    pub fn set_data_rtf<C: Into<ControlHandle>>(handle: C, rtf: &str, text: Option<&str>) {
+        let mut data = rtf.as_bytes().to_vec();
+        data.push(0);
+
+        Clipboard::open(handle);
+        Clipboard::empty();
+
+        Clipboard::set_data_bytes(ClipboardFormat::Rtf, &data);
+        if let Some(text) = text {
+            Clipboard::set_data_bytes(ClipboardFormat::UnicodeText, &utf16_bytes(text));
+        }
+
+        Clipboard::close();
+    }
+
+    /**
+        Return the rich text document in the clipboard (if there is one)
+    */
+    pub fn data_rtf<C: Into<ControlHandle>>(handle: C) -> Option<String> {
+        Clipboard::open(handle);
+        let data = Clipboard::data_bytes(ClipboardFormat::Rtf);
+        Clipboard::close();
+
+        data.map(|data| {
+            let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+            String::from_utf8_lossy(&data[..end]).into_owned()
+        })
+    }
+
+    /**
+        Fill the clipboard with an image. The image is copied as a device independent bitmap. With the `image-decoder` feature,
+        the image is also copied as a PNG file.
+
+        Panics if the bitmap is not initialized.
+
+        This is a high level function that handles `open` and `close`
+    */
+    pub fn set_data_bitmap<C: Into<ControlHandle>>(handle: C, bitmap: &crate::Bitmap) {
+        if bitmap.handle.is_null() {
+            panic!("Bitmap was not initialized");
+        }
+
+        let dib = unsafe { dib_from_bitmap(bitmap.handle as _) };
+
+        #[cfg(feature = "image-decoder")]
+        let png = unsafe { super::image_decoder::encode_png(bitmap.handle as _).ok() };
+
+        Clipboard::open(handle);
+        Clipboard::empty();
+
+        if let Some(dib) = dib {
+            Clipboard::set_data_bytes(ClipboardFormat::Dib, &dib);
+        }
+
+        #[cfg(feature = "image-decoder")]
+        {
+            if let Some(png) = png {
+                Clipboard::set_data_bytes(ClipboardFormat::Png, &png);
+            }
+        }
+
+        Clipboard::close();
+    }
+
+    /**
+        Return the image in the clipboard (if there is one). With the `image-decoder` feature, the PNG image is read first.
+        Otherwise, the image is read from the device independent bitmap (the system converts the other bitmap formats to a DIB).
+    */
+    pub fn data_bitmap<C: Into<ControlHandle>>(handle: C) -> Option<crate::Bitmap> {
+        use super::resource_tracker::{self as rt, ResourceKind};
+
+        Clipboard::open(handle);
+
+        #[cfg(feature = "image-decoder")]
+        let png = Clipboard::data_bytes(ClipboardFormat::Png)
+            .and_then(|png| unsafe { super::resources_helper::bitmap_from_memory(&png).ok() });
+
+        #[cfg(not(feature = "image-decoder"))]
+        let png: Option<HANDLE> = None;
+
+        let bitmap = match png {
+            Some(bitmap) => Some(bitmap),
+            None => Clipboard::data_bytes(ClipboardFormat::Dib).and_then(|dib| unsafe { bitmap_from_dib(&dib) })
+        };
+
+        Clipboard::close();
+
+        bitmap.map(|handle| {
+            rt::track(ResourceKind::Bitmap, handle);
+            crate::Bitmap { handle, owned: true }
+        })
+    }
+
+    /**
+        Fill the clipboard with a list of files, like the "Copy" command of the explorer.
+        The files can then be pasted in a folder of the explorer.
+
+        This is a high level function that handles `open` and `close`
+    */
+    pub fn set_data_files<C: Into<ControlHandle>, S: AsRef<str>>(handle: C, files: &[S]) {
+        Clipboard::open(handle);
+        Clipboard::empty();
+        Clipboard::set_data_bytes(ClipboardFormat::Files, &drop_files_data(files));
+        Clipboard::close();
+    }
+
+    /**
+        Return the paths of the files in the clipboard (if there are files), like the files copied in the explorer.
+    */
+    pub fn data_files<C: Into<ControlHandle>>(handle: C) -> Option<Vec<String>> {
+        use winapi::um::winuser::GetClipboardData;
+        use std::mem::ManuallyDrop;
+
+        Clipboard::open(handle);
+
+        let files = unsafe {
+            let drop = GetClipboardData(CF_HDROP);
+            match drop.is_null() {
+                true => None,
+                // The handle is owned by the clipboard and must not be released with `DragFinish`
+                false => Some(ManuallyDrop::new(DropFiles { drop: drop as _ }).files())
+            }
+        };
+
+        Clipboard::close();
+
+        files
+    }
+
+    /**
+        Return the formats of the data in the clipboard, in the order they were placed in the clipboard.
+        This includes the formats that the system can convert the data to (ex: `Text` for `UnicodeText`).
+        The formats that are not known by NWG are returned as `ClipboardFormat::Raw`. See `format_name`.
+    */
+    pub fn available_formats<C: Into<ControlHandle>>(handle: C) -> Vec<ClipboardFormat> {
+        use winapi::um::winuser::EnumClipboardFormats;
+
+        let mut formats = Vec::new();
+
+        Clipboard::open(handle);
+
+        let mut format = 0;
+        loop {
+            format = unsafe { EnumClipboardFormats(format) };
+            if format == 0 {
+                break;
+            }
+
+            formats.push(ClipboardFormat::from_raw(format));
+        }
+
+        Clipboard::close();
+
+        formats
+    }
+
+    /**
+        Return the name of a registered clipboard format (ex: "HTML Format").
+        Return `None` for the standard formats of the system, which do not have a name.
+    */
+    pub fn format_name(fmt: ClipboardFormat) -> Option<String> {
+        use winapi::um::winuser::GetClipboardFormatNameW;
+        use super::base_helper::from_utf16;
+
+        let mut buffer = [0u16; 256];
+        let length = unsafe { GetClipboardFormatNameW(fmt.into_raw(), buffer.as_mut_ptr(), buffer.len() as _) };
+        match length {
+            0 => None,
+            length => Some(from_utf16(&buffer[..length as usize]))
+        }
+    }
+
     /**
         Remove the current data in the clipboard
     */
@@ -279,6 +564,46 @@ impl Clipboard {
         }
     }
 
+    /**
+        Places a copy of `data` on the clipboard in a specified clipboard format.
+
+        The clipboard must be open when calling this function.
+    */
+    pub fn set_data_bytes(fmt: ClipboardFormat, data: &[u8]) {
+        unsafe { Clipboard::set_data(fmt, data.as_ptr(), data.len()); }
+    }
+
+    /**
+        Returns a copy of the data of the clipboard in the selected format.
+        The size of the global allocations is rounded up by the system, so the data may end with extra bytes.
+
+        The clipboard must be open when calling this function.
+
+        If no data is found with the selected clipboard format, `None` is returned.
+    */
+    pub fn data_bytes(fmt: ClipboardFormat) -> Option<Vec<u8>> {
+        use winapi::um::winuser::GetClipboardData;
+        use winapi::um::winbase::{GlobalLock, GlobalUnlock, GlobalSize};
+        use std::slice;
+
+        unsafe {
+            let handle = GetClipboardData(fmt.into_raw());
+            if handle.is_null() {
+                return None;
+            }
+
+            let data = GlobalLock(handle) as *const u8;
+            if data.is_null() {
+                return None;
+            }
+
+            let bytes = slice::from_raw_parts(data, GlobalSize(handle)).to_vec();
+            GlobalUnlock(handle);
+
+            Some(bytes)
+        }
+    }
+
     /**
         Check if the selected format is available in the clipboard.
     */
@@ -405,3 +730,157 @@ unsafe fn from_ptr(ptr: *const u8) -> Option<String> {
         .map(|s| s.into())
         .ok()
 }
+
+fn utf16_bytes(text: &str) -> Vec<u8> {
+    to_utf16(text).iter().flat_map(|c| c.to_le_bytes().to_vec()).collect()
+}
+
+/// Wraps an HTML fragment in a document with the `CF_HTML` header. The offsets of the header are in bytes.
+fn html_clipboard_data(fragment: &str) -> Vec<u8> {
+    const PREFIX: &'static str = "<html>\r\n<body>\r\n<!--StartFragment-->";
+    const SUFFIX: &'static str = "<!--EndFragment-->\r\n</body>\r\n</html>";
+
+    let header = |start_html: usize, end_html: usize, start_fragment: usize, end_fragment: usize| {
+        format!(
+            "Version:0.9\r\nStartHTML:{:010}\r\nEndHTML:{:010}\r\nStartFragment:{:010}\r\nEndFragment:{:010}\r\n",
+            start_html, end_html, start_fragment, end_fragment
+        )
+    };
+
+    // The offsets are padded, so the size of the header does not depend on their values
+    let start_html = header(0, 0, 0, 0).len();
+    let start_fragment = start_html + PREFIX.len();
+    let end_fragment = start_fragment + fragment.len();
+    let end_html = end_fragment + SUFFIX.len();
+
+    let mut data = header(start_html, end_html, start_fragment, end_fragment).into_bytes();
+    data.extend(PREFIX.as_bytes());
+    data.extend(fragment.as_bytes());
+    data.extend(SUFFIX.as_bytes());
+    data.push(0);
+
+    data
+}
+
+/// Returns the fragment of a `CF_HTML` document, or the whole document if the header does not have the fragment offsets
+fn html_fragment(data: &[u8]) -> String {
+    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    let data = &data[..end];
+    let text = String::from_utf8_lossy(data);
+
+    let offset = |name: &str| -> Option<usize> {
+        let start = text.find(name)? + name.len();
+        let digits: String = text[start..].chars().take_while(|c| c.is_ascii_digit()).collect();
+        digits.parse().ok()
+    };
+
+    let range = match (offset("StartFragment:"), offset("EndFragment:")) {
+        (Some(start), Some(end)) if start <= end && end <= data.len() => start..end,
+        _ => match offset("StartHTML:") {
+            Some(start) if start <= data.len() => start..data.len(),
+            _ => 0..data.len()
+        }
+    };
+
+    String::from_utf8_lossy(&data[range]).into_owned()
+}
+
+/// Builds a `DROPFILES` structure followed by the list of paths, as used by `CF_HDROP`
+pub(crate) fn drop_files_data<S: AsRef<str>>(files: &[S]) -> Vec<u8> {
+    use std::{mem, slice};
+
+    let header = DROPFILES {
+        pFiles: mem::size_of::<DROPFILES>() as DWORD,
+        pt: POINT { x: 0, y: 0 },
+        fNC: 0,
+        fWide: 1,
+    };
+
+    let mut data: Vec<u8> = unsafe {
+        slice::from_raw_parts(&header as *const DROPFILES as *const u8, mem::size_of::<DROPFILES>()).to_vec()
+    };
+
+    // A list of null terminated paths, terminated by an empty path
+    for file in files {
+        data.extend(utf16_bytes(file.as_ref()));
+    }
+    data.extend(&[0, 0]);
+
+    data
+}
+
+/// Copies the pixels of a bitmap into a 32 bits device independent bitmap
+unsafe fn dib_from_bitmap(bitmap: winapi::shared::windef::HBITMAP) -> Option<Vec<u8>> {
+    use winapi::um::wingdi::{GetObjectW, GetDIBits, BITMAP, BITMAPINFO, BITMAPINFOHEADER, DIB_RGB_COLORS, BI_RGB};
+    use winapi::um::winuser::{GetDC, ReleaseDC};
+    use std::{mem, ptr};
+
+    let mut info: BITMAP = mem::zeroed();
+    if GetObjectW(bitmap as _, mem::size_of::<BITMAP>() as _, &mut info as *mut BITMAP as _) == 0 {
+        return None;
+    }
+
+    let (width, height) = (info.bmWidth, info.bmHeight.abs());
+    let header_size = mem::size_of::<BITMAPINFOHEADER>();
+    let pixels_size = (width * height * 4) as usize;
+
+    let header = BITMAPINFOHEADER {
+        biSize: header_size as DWORD,
+        biWidth: width, biHeight: height,
+        biPlanes: 1, biBitCount: 32, biCompression: BI_RGB,
+        biSizeImage: pixels_size as DWORD,
+        biXPelsPerMeter: 0, biYPelsPerMeter: 0,
+        biClrUsed: 0, biClrImportant: 0
+    };
+
+    let mut data = vec![0u8; header_size + pixels_size];
+    ptr::copy_nonoverlapping(&header as *const BITMAPINFOHEADER as *const u8, data.as_mut_ptr(), header_size);
+
+    let screen_dc = GetDC(ptr::null_mut());
+    let pixels = data.as_mut_ptr().offset(header_size as isize);
+    let lines = GetDIBits(screen_dc, bitmap, 0, height as u32, pixels as _, data.as_mut_ptr() as *mut BITMAPINFO, DIB_RGB_COLORS);
+    ReleaseDC(ptr::null_mut(), screen_dc);
+
+    match lines {
+        0 => None,
+        _ => Some(data)
+    }
+}
+
+/// Creates a bitmap from a device independent bitmap (a `BITMAPINFO` followed by the pixels)
+unsafe fn bitmap_from_dib(dib: &[u8]) -> Option<HANDLE> {
+    use winapi::um::wingdi::{CreateDIBitmap, BITMAPINFO, BITMAPINFOHEADER, CBM_INIT, DIB_RGB_COLORS, BI_BITFIELDS};
+    use winapi::um::winuser::{GetDC, ReleaseDC};
+    use std::{mem, ptr};
+
+    if dib.len() < mem::size_of::<BITMAPINFOHEADER>() {
+        return None;
+    }
+
+    let header: BITMAPINFOHEADER = ptr::read_unaligned(dib.as_ptr() as *const BITMAPINFOHEADER);
+
+    // The pixels follow the header, the color masks and the color table
+    let masks = match header.biCompression == BI_BITFIELDS && header.biSize as usize == mem::size_of::<BITMAPINFOHEADER>() {
+        true => 3 * mem::size_of::<DWORD>(),
+        false => 0
+    };
+
+    let colors = match (header.biClrUsed, header.biBitCount) {
+        (0, bits) if bits <= 8 => 1 << bits,
+        (used, _) => used as usize
+    };
+
+    let offset = header.biSize as usize + masks + colors * 4;
+    if offset >= dib.len() {
+        return None;
+    }
+
+    let screen_dc = GetDC(ptr::null_mut());
+    let bitmap = CreateDIBitmap(screen_dc, &header, CBM_INIT, dib.as_ptr().offset(offset as isize) as _, dib.as_ptr() as *const BITMAPINFO, DIB_RGB_COLORS);
+    ReleaseDC(ptr::null_mut(), screen_dc);
+
+    match bitmap.is_null() {
+        true => None,
+        false => Some(bitmap as HANDLE)
+    }
+}
@@ -0,0 +1,349 @@
+/*!
+    Command line arguments of the application, parsed with the rules of the Windows shell.
+*/
+use winapi::shared::winerror::S_OK;
+use super::base_helper::to_utf16;
+use crate::NwgError;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::os::windows::ffi::OsStringExt;
+use std::{mem, slice};
+
+/// Argument added to the command line registered by `register_application_restart`
+const RESTART_FLAG: &'static str = "/nwg-restarted";
+
+/// Option added to the command of the verbs registered by `file_assoc::register_verb`
+pub(crate) const VERB_OPTION: &'static str = "nwg-verb";
+
+/// Option added to the command of the jump list tasks
+pub(crate) const JUMP_TASK_OPTION: &'static str = "nwg-task";
+
+/// Maximum length of the restart command line, in characters (`RESTART_MAX_CMD_LINE`)
+const RESTART_MAX_CMD_LINE: usize = 1024;
+
+
+/// How the application was started. See `CommandLine::launch`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LaunchKind {
+    /// Started from a shortcut. Holds the path of the `.lnk` file.
+    Shortcut(PathBuf),
+
+    /// Started with a document: opened from the shell with a file association or "Open with",
+    /// or dropped on the executable. Holds the path of the first document.
+    Document(PathBuf),
+
+    /// Restarted by the system after an update or a crash. See `register_application_restart`.
+    Restarted,
+
+    /// Started from a console, another program or the executable itself
+    Direct,
+}
+
+/**
+    The command line of the application, split with `CommandLineToArgvW`.
+
+    Unlike `std::env::args`, the arguments that are not valid unicode (unpaired surrogates in file names) are kept
+    as `OsString`, and the first argument is always the program, even if the process was started with an empty command line.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn open_documents() {
+        let cmd = nwg::command_line();
+        if cmd.has_flag("safe-mode") {
+            // ...
+        }
+
+        for path in cmd.files() {
+            // open the document
+        }
+    }
+    ```
+*/
+#[derive(Clone, Debug)]
+pub struct CommandLine {
+    raw: OsString,
+    args: Vec<OsString>,
+    link: Option<PathBuf>,
+}
+
+impl CommandLine {
+
+    /// Splits a raw command line with `CommandLineToArgvW`. Used for the command lines forwarded by other instances.
+    pub(crate) fn parse(raw: OsString) -> CommandLine {
+        use winapi::um::shellapi::CommandLineToArgvW;
+        use winapi::um::winbase::LocalFree;
+        use std::os::windows::ffi::OsStrExt;
+
+        let raw_w: Vec<u16> = raw.encode_wide().chain(Some(0)).collect();
+
+        let mut args = Vec::new();
+        unsafe {
+            let mut count = 0;
+            let argv = CommandLineToArgvW(raw_w.as_ptr(), &mut count);
+            if !argv.is_null() {
+                for &arg in slice::from_raw_parts(argv, count as usize) {
+                    args.push(OsString::from_wide(wide_slice(arg)));
+                }
+
+                LocalFree(argv as _);
+            }
+        }
+
+        CommandLine { raw, args, link: None }
+    }
+
+    /// Returns the command line as it was passed to the process
+    pub fn raw(&self) -> &OsString {
+        &self.raw
+    }
+
+    /// Returns all the arguments, including the program
+    pub fn args_os(&self) -> &[OsString] {
+        &self.args
+    }
+
+    /// Returns all the arguments, including the program. Invalid unicode is replaced by `U+FFFD`.
+    pub fn args(&self) -> Vec<String> {
+        self.args.iter().map(|a| a.to_string_lossy().into_owned()).collect()
+    }
+
+    /// Returns the path of the program, as written in the command line
+    pub fn program(&self) -> PathBuf {
+        self.args.first().map(PathBuf::from).unwrap_or_default()
+    }
+
+    /**
+        Returns `true` if the flag `name` was passed. The flag can start with `/`, `-` or `--`, and the name
+        is not case sensitive: `has_flag("verbose")` matches `/verbose`, `-Verbose` and `--verbose`.
+    */
+    pub fn has_flag(&self, name: &str) -> bool {
+        self.options().any(|(flag, value)| value.is_none() && flag.eq_ignore_ascii_case(name))
+    }
+
+    /**
+        Returns the value of the option `name`. The value must be joined to the option with `:` or `=`
+        (`/out:file.txt`, `--out=file.txt`). In `--out file.txt`, `--out` is a flag and `file.txt` is returned by `files`.
+    */
+    pub fn value(&self, name: &str) -> Option<String> {
+        self.options()
+            .find(|(flag, _)| flag.eq_ignore_ascii_case(name))
+            .and_then(|(_, value)| value)
+    }
+
+    /**
+        Returns the arguments that are not options, excluding the program. Those are usually the documents to open.
+        An argument starting with `/`, `-` or `--` is always an option.
+    */
+    pub fn files(&self) -> Vec<PathBuf> {
+        self.args.iter()
+            .skip(1)
+            .filter(|a| !is_option(a))
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    /**
+        Returns how the application was started.
+
+        A shortcut is detected with the startup information of the process. A document is detected when the first
+        non option argument is an existing file. A restart is detected with the flag added by `register_application_restart`.
+    */
+    pub fn launch(&self) -> LaunchKind {
+        if let Some(link) = self.link.as_ref() {
+            return LaunchKind::Shortcut(link.clone());
+        }
+
+        if self.args.iter().skip(1).any(|a| a.to_str().map(|a| a.eq_ignore_ascii_case(RESTART_FLAG)).unwrap_or(false)) {
+            return LaunchKind::Restarted;
+        }
+
+        match self.files().into_iter().next() {
+            Some(path) if path.is_file() => LaunchKind::Document(path),
+            _ => LaunchKind::Direct
+        }
+    }
+
+    /// Returns the shell verb that started the application, if it was registered with `file_assoc::register_verb`
+    pub fn verb(&self) -> Option<String> {
+        self.value(VERB_OPTION)
+    }
+
+    /// Returns the id of the jump list task that started the application. See `JumpList`.
+    pub fn jump_task(&self) -> Option<String> {
+        self.value(JUMP_TASK_OPTION)
+    }
+
+    fn options<'a>(&'a self) -> impl Iterator<Item=(String, Option<String>)> + 'a {
+        self.args.iter()
+            .skip(1)
+            .filter_map(|a| a.to_str())
+            .filter_map(|a| split_option(a).map(|(f, v)| (f.to_string(), v.map(|v| v.to_string()))))
+    }
+
+}
+
+/**
+    Returns the command line of the application. See `CommandLine`.
+*/
+pub fn command_line() -> CommandLine {
+    use winapi::um::processenv::GetCommandLineW;
+
+    unsafe {
+        let raw = OsString::from_wide(wide_slice(GetCommandLineW()));
+        let mut cmd = CommandLine::parse(raw);
+        cmd.link = startup_link();
+        cmd
+    }
+}
+
+/**
+    Asks the system to restart the application if it crashes, hangs, or is closed by an update.
+    `args` are the arguments of the restarted process (without the program). `CommandLine::launch` returns
+    `LaunchKind::Restarted` in the restarted process.
+
+    The process must run at least 60 seconds before it can be restarted.
+*/
+pub fn register_application_restart(args: &str) -> Result<(), NwgError> {
+    use winapi::um::winbase::RegisterApplicationRestart;
+
+    let command = match args.is_empty() {
+        true => RESTART_FLAG.to_string(),
+        false => format!("{} {}", RESTART_FLAG, args)
+    };
+
+    if command.len() >= RESTART_MAX_CMD_LINE {
+        return Err(NwgError::initialization("The restart command line is too long"));
+    }
+
+    let command = to_utf16(&command);
+    match unsafe { RegisterApplicationRestart(command.as_ptr(), 0) } {
+        S_OK => Ok(()),
+        _ => Err(NwgError::initialization("RegisterApplicationRestart failed"))
+    }
+}
+
+/// Splits `/name:value`, `-name=value` or `--name` into the name and the value. Returns `None` if `arg` is not an option.
+fn split_option(arg: &str) -> Option<(&str, Option<&str>)> {
+    let name = if arg.starts_with("--") {
+        &arg[2..]
+    } else if arg.starts_with('-') || arg.starts_with('/') {
+        &arg[1..]
+    } else {
+        return None;
+    };
+
+    if name.is_empty() {
+        return None;
+    }
+
+    match name.find(|c| c == ':' || c == '=') {
+        Some(i) => Some((&name[..i], Some(&name[i+1..]))),
+        None => Some((name, None))
+    }
+}
+
+fn is_option(arg: &OsString) -> bool {
+    match arg.to_str() {
+        Some(a) => split_option(a).is_some(),
+        None => false
+    }
+}
+
+/// Returns the path of the shortcut that started the process, if any
+unsafe fn startup_link() -> Option<PathBuf> {
+    use winapi::um::processthreadsapi::{GetStartupInfoW, STARTUPINFOW};
+    use winapi::um::winbase::STARTF_TITLEISLINKNAME;
+
+    let mut info: STARTUPINFOW = mem::zeroed();
+    info.cb = mem::size_of::<STARTUPINFOW>() as u32;
+    GetStartupInfoW(&mut info);
+
+    if info.dwFlags & STARTF_TITLEISLINKNAME == 0 || info.lpTitle.is_null() {
+        return None;
+    }
+
+    Some(PathBuf::from(OsString::from_wide(wide_slice(info.lpTitle))))
+}
+
+/// Borrows a null terminated wide string
+unsafe fn wide_slice<'a>(ptr: *const u16) -> &'a [u16] {
+    if ptr.is_null() {
+        return &[];
+    }
+
+    let mut length = 0;
+    while *ptr.offset(length) != 0 {
+        length += 1;
+    }
+
+    slice::from_raw_parts(ptr, length as usize)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(args: &[&str]) -> CommandLine {
+        CommandLine {
+            raw: OsString::from(args.join(" ")),
+            args: args.iter().map(OsString::from).collect(),
+            link: None,
+        }
+    }
+
+    #[test]
+    fn split_option_prefixes() {
+        assert_eq!(split_option("--verbose"), Some(("verbose", None)));
+        assert_eq!(split_option("-v"), Some(("v", None)));
+        assert_eq!(split_option("/verbose"), Some(("verbose", None)));
+        assert_eq!(split_option("/out:file.txt"), Some(("out", Some("file.txt"))));
+        assert_eq!(split_option("--out=C:\\file.txt"), Some(("out", Some("C:\\file.txt"))));
+        assert_eq!(split_option("--out="), Some(("out", Some(""))));
+    }
+
+    #[test]
+    fn split_option_not_options() {
+        assert_eq!(split_option("file.txt"), None);
+        assert_eq!(split_option("C:\\file.txt"), None);
+        assert_eq!(split_option("-"), None);
+        assert_eq!(split_option("--"), None);
+        assert_eq!(split_option("/"), None);
+    }
+
+    #[test]
+    fn has_flag() {
+        let cmd = command(&["app.exe", "/Verbose", "--safe-mode", "--out:file.txt", "doc.txt"]);
+        assert!(cmd.has_flag("verbose"));
+        assert!(cmd.has_flag("SAFE-MODE"));
+        assert!(!cmd.has_flag("out"));
+        assert!(!cmd.has_flag("doc.txt"));
+        assert!(!cmd.has_flag("app.exe"));
+    }
+
+    #[test]
+    fn value() {
+        let cmd = command(&["app.exe", "/out:file.txt", "--level=3", "--name", "doc.txt"]);
+        assert_eq!(cmd.value("out"), Some("file.txt".to_string()));
+        assert_eq!(cmd.value("LEVEL"), Some("3".to_string()));
+        assert_eq!(cmd.value("name"), None);
+        assert_eq!(cmd.value("missing"), None);
+    }
+
+    #[test]
+    fn files() {
+        let cmd = command(&["app.exe", "/nwg-restarted", "first.txt", "--out", "second.txt", "-v", "/out:third.txt"]);
+        assert_eq!(cmd.files(), vec![PathBuf::from("first.txt"), PathBuf::from("second.txt")]);
+        assert_eq!(cmd.value("out"), None);
+        assert!(cmd.has_flag("out"));
+    }
+
+    #[test]
+    fn files_without_arguments() {
+        assert!(command(&["app.exe"]).files().is_empty());
+        assert!(command(&[]).files().is_empty());
+        assert_eq!(command(&[]).program(), PathBuf::new());
+    }
+
+}
@@ -0,0 +1,355 @@
+/*!
+    Dark theme for the windows and the controls.
+
+    Windows does not provide a public API to theme the common controls in dark colors. This module uses:
+      * `DwmSetWindowAttribute(DWMWA_USE_IMMERSIVE_DARK_MODE)` for the title bar of the top level windows
+      * the undocumented uxtheme functions used by the Windows Explorer (exported by ordinal) for the menus and the scrollbars
+      * the "DarkMode_Explorer" and "DarkMode_CFD" visual styles for the common controls
+      * the `WM_CTLCOLOR*` messages for the background and the text of the controls that do not have a dark visual style
+
+    The undocumented functions are only called on Windows 10 1809 (build 17763) or later. On older systems, only the colors of
+    the controls are changed.
+*/
+use winapi::shared::windef::{HWND, HBRUSH};
+use winapi::shared::minwindef::{BOOL, DWORD, LPARAM};
+use super::base_helper::{to_utf16, windows_build};
+use super::window::{bind_raw_event_handler_inner, unbind_raw_event_handler, RawEventHandler};
+use super::resource_tracker::{self as rt, ResourceKind};
+use crate::ControlHandle;
+use std::{mem, ptr, cell::{Cell, RefCell}, collections::HashMap};
+
+/// Id of the raw event handlers that paint the background of the controls
+const DARK_MODE_HANDLER_ID: usize = 0xDA4C;
+
+const DARK_BACKGROUND: [u8; 3] = [32, 32, 32];
+const DARK_CONTROL_BACKGROUND: [u8; 3] = [45, 45, 45];
+const DARK_TEXT: [u8; 3] = [240, 240, 240];
+
+/// First build of Windows 10 that exports the dark mode functions of uxtheme
+const DARK_MODE_MIN_BUILD: u32 = 17763;
+
+/// First build of Windows 10 that uses the documented value of `DWMWA_USE_IMMERSIVE_DARK_MODE`
+const DWMWA_USE_IMMERSIVE_DARK_MODE_BUILD: u32 = 18985;
+const DWMWA_USE_IMMERSIVE_DARK_MODE: DWORD = 20;
+const DWMWA_USE_IMMERSIVE_DARK_MODE_BEFORE_20H1: DWORD = 19;
+
+// Undocumented uxtheme functions, by ordinal
+const ORD_REFRESH_IMMERSIVE_COLOR_POLICY_STATE: u16 = 104;
+const ORD_ALLOW_DARK_MODE_FOR_WINDOW: u16 = 133;
+const ORD_SET_PREFERRED_APP_MODE: u16 = 135;
+const ORD_FLUSH_MENU_THEMES: u16 = 136;
+
+type AllowDarkModeForWindow = unsafe extern "system" fn(HWND, BOOL) -> BOOL;
+type SetPreferredAppMode = unsafe extern "system" fn(i32) -> i32;
+type VoidFn = unsafe extern "system" fn();
+
+thread_local! {
+    /// The raw event handlers bound by `set_window_theme`, by window
+    static DARK_HANDLERS: RefCell<HashMap<usize, RawEventHandler>> = RefCell::new(HashMap::new());
+
+    /// The brushes of the background and of the controls. Created on first use and freed when no container uses the dark theme.
+    static DARK_BRUSHES: Cell<Option<(HBRUSH, HBRUSH)>> = Cell::new(None);
+}
+
+
+/// The color theme of a window
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+/**
+    Returns the theme selected by the user for the applications in the Windows settings.
+    Returns `Theme::Light` on systems without a dark mode.
+
+    The `OnSystemThemeChanged` event is sent to the top level windows when this value changes.
+
+    Requires the `dark-mode` feature.
+*/
+pub fn system_theme() -> Theme {
+    use winapi::um::winreg::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+    use winapi::shared::winerror::ERROR_SUCCESS;
+
+    let key = to_utf16("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize");
+    let value = to_utf16("AppsUseLightTheme");
+
+    let mut light: DWORD = 1;
+    let mut size = mem::size_of::<DWORD>() as DWORD;
+    let result = unsafe {
+        RegGetValueW(HKEY_CURRENT_USER, key.as_ptr(), value.as_ptr(), RRF_RT_REG_DWORD, ptr::null_mut(), &mut light as *mut DWORD as _, &mut size)
+    };
+
+    match result as u32 == ERROR_SUCCESS && light == 0 {
+        true => Theme::Dark,
+        false => Theme::Light
+    }
+}
+
+/**
+    Allows the application to use the dark menus and the dark scrollbars. Should be called once, before the windows are created.
+    `set_window_theme` calls this function automatically.
+
+    Requires the `dark-mode` feature.
+*/
+pub fn enable_dark_mode() {
+    unsafe {
+        if let Some(set_preferred_app_mode) = uxtheme_fn::<SetPreferredAppMode>(ORD_SET_PREFERRED_APP_MODE) {
+            // 1 is `AllowDark` for `SetPreferredAppMode` and `TRUE` for `AllowDarkModeForApp` (its name in build 17763)
+            set_preferred_app_mode(1);
+        }
+
+        if let Some(refresh) = uxtheme_fn::<VoidFn>(ORD_REFRESH_IMMERSIVE_COLOR_POLICY_STATE) {
+            refresh();
+        }
+
+        if let Some(flush_menu_themes) = uxtheme_fn::<VoidFn>(ORD_FLUSH_MENU_THEMES) {
+            flush_menu_themes();
+        }
+    }
+}
+
+/**
+    Applies a color theme to a window and to all its children. If the window is a top level window, its title bar is also themed.
+
+    Controls created after this call are not themed. Call `set_window_theme` again after creating them.
+    Controls with a custom background color (ex: a `Label` with `background_color`) keep their colors.
+
+    To follow the system setting, call `set_window_theme(&window, nwg::system_theme())` when the window is created and
+    in the `OnSystemThemeChanged` event.
+
+    Requires the `dark-mode` feature.
+*/
+pub fn set_window_theme<C: Into<ControlHandle>>(window: C, theme: Theme) {
+    use winapi::um::winuser::{EnumChildWindows, GetAncestor, SetWindowPos, RedrawWindow, GA_ROOT,
+        SWP_FRAMECHANGED, SWP_NOMOVE, SWP_NOSIZE, SWP_NOZORDER, SWP_NOACTIVATE, RDW_ERASE, RDW_FRAME, RDW_INVALIDATE, RDW_ALLCHILDREN};
+
+    let handle = window.into().hwnd().expect("set_window_theme requires a window control");
+    let dark = theme == Theme::Dark;
+
+    if dark {
+        enable_dark_mode();
+    }
+
+    unsafe {
+        if GetAncestor(handle, GA_ROOT) == handle {
+            set_title_bar_theme(handle, dark);
+        }
+
+        theme_window(handle, dark);
+
+        let mut children: Vec<HWND> = Vec::new();
+        EnumChildWindows(handle, Some(collect_children), &mut children as *mut Vec<HWND> as LPARAM);
+        for &child in children.iter() {
+            theme_window(child, dark);
+        }
+
+        SetWindowPos(handle, ptr::null_mut(), 0, 0, 0, 0, SWP_FRAMECHANGED | SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE);
+        RedrawWindow(handle, ptr::null(), ptr::null_mut(), RDW_ERASE | RDW_FRAME | RDW_INVALIDATE | RDW_ALLCHILDREN);
+    }
+}
+
+/**
+    Returns the theme applied to a window by `set_window_theme`
+
+    Requires the `dark-mode` feature.
+*/
+pub fn window_theme<C: Into<ControlHandle>>(window: C) -> Theme {
+    let handle = window.into().hwnd().expect("window_theme requires a window control");
+    let dark = DARK_HANDLERS.with(|handlers| handlers.borrow().contains_key(&(handle as usize)));
+    match dark {
+        true => Theme::Dark,
+        false => Theme::Light
+    }
+}
+
+unsafe extern "system" fn collect_children(hwnd: HWND, children: LPARAM) -> BOOL {
+    let children = &mut *(children as *mut Vec<HWND>);
+    children.push(hwnd);
+    1
+}
+
+unsafe fn set_title_bar_theme(handle: HWND, dark: bool) {
+    use winapi::um::dwmapi::DwmSetWindowAttribute;
+
+    let value: BOOL = dark as BOOL;
+    let attribute = match windows_build() >= DWMWA_USE_IMMERSIVE_DARK_MODE_BUILD {
+        true => DWMWA_USE_IMMERSIVE_DARK_MODE,
+        false => DWMWA_USE_IMMERSIVE_DARK_MODE_BEFORE_20H1
+    };
+
+    DwmSetWindowAttribute(handle, attribute, &value as *const BOOL as _, mem::size_of::<BOOL>() as DWORD);
+}
+
+/// Applies the theme to a single window
+unsafe fn theme_window(handle: HWND, dark: bool) {
+    use winapi::um::uxtheme::SetWindowTheme;
+    use winapi::um::winuser::{GetWindow, SendMessageW, GW_CHILD, WM_THEMECHANGED};
+    use winapi::um::commctrl::{LVM_SETBKCOLOR, LVM_SETTEXTCOLOR, LVM_SETTEXTBKCOLOR, TVM_SETBKCOLOR, TVM_SETTEXTCOLOR, CLR_DEFAULT};
+
+    if let Some(allow_dark_mode_for_window) = uxtheme_fn::<AllowDarkModeForWindow>(ORD_ALLOW_DARK_MODE_FOR_WINDOW) {
+        allow_dark_mode_for_window(handle, dark as BOOL);
+    }
+
+    let class_name = class_name(handle);
+    let visual_style = match class_name.as_str() {
+        "Edit" | "ComboBox" => "DarkMode_CFD",
+        _ => "DarkMode_Explorer"
+    };
+
+    match dark {
+        true => {
+            let style = to_utf16(visual_style);
+            SetWindowTheme(handle, style.as_ptr(), ptr::null());
+        },
+        false => {
+            SetWindowTheme(handle, ptr::null(), ptr::null());
+        }
+    }
+
+    // The list view and the tree view do not use the colors of their visual style
+    let (background, text) = match dark {
+        true => (rgb(DARK_CONTROL_BACKGROUND) as LPARAM, rgb(DARK_TEXT) as LPARAM),
+        false => (CLR_DEFAULT as LPARAM, CLR_DEFAULT as LPARAM)
+    };
+
+    match class_name.as_str() {
+        "SysListView32" => {
+            SendMessageW(handle, LVM_SETBKCOLOR, 0, background);
+            SendMessageW(handle, LVM_SETTEXTBKCOLOR, 0, background);
+            SendMessageW(handle, LVM_SETTEXTCOLOR, 0, text);
+        },
+        "SysTreeView32" => {
+            let (background, text) = match dark {
+                true => (background, text),
+                false => (-1, -1)
+            };
+
+            SendMessageW(handle, TVM_SETBKCOLOR, 0, background);
+            SendMessageW(handle, TVM_SETTEXTCOLOR, 0, text);
+        },
+        _ => {}
+    }
+
+    // Containers paint the background of their children
+    let is_container = !GetWindow(handle, GW_CHILD).is_null();
+    DARK_HANDLERS.with(|handlers| {
+        let mut handlers = handlers.borrow_mut();
+        let key = handle as usize;
+
+        if let Some(handler) = handlers.remove(&key) {
+            drop(unbind_raw_event_handler(&handler));
+        }
+
+        if dark && is_container {
+            if let Ok(handler) = bind_raw_event_handler_inner(&ControlHandle::Hwnd(handle), DARK_MODE_HANDLER_ID, dark_colors_proc) {
+                handlers.insert(key, handler);
+            }
+        }
+
+        if handlers.is_empty() {
+            free_brushes();
+        }
+    });
+
+    SendMessageW(handle, WM_THEMECHANGED, 0, 0);
+}
+
+/// Raw event handler of the containers themed in dark colors
+fn dark_colors_proc(hwnd: HWND, msg: u32, w: usize, l: isize) -> Option<isize> {
+    use winapi::um::winuser::{WM_CTLCOLORSTATIC, WM_CTLCOLORBTN, WM_CTLCOLOREDIT, WM_CTLCOLORLISTBOX, WM_CTLCOLORDLG, WM_ERASEBKGND, GetClientRect, FillRect};
+    use winapi::um::wingdi::{SetTextColor, SetBkColor};
+    use winapi::shared::windef::{HDC, RECT};
+    use super::window::has_raw_handler;
+
+    let (background, control_background) = brushes();
+
+    match msg {
+        WM_ERASEBKGND => unsafe {
+            let mut rect: RECT = mem::zeroed();
+            GetClientRect(hwnd, &mut rect);
+            FillRect(w as HDC, &rect, background);
+            Some(1)
+        },
+        WM_CTLCOLORSTATIC | WM_CTLCOLORBTN | WM_CTLCOLOREDIT | WM_CTLCOLORLISTBOX | WM_CTLCOLORDLG => unsafe {
+            // Controls with a custom background color bind their own handler on the parent, with the handle of the control as id
+            if has_raw_handler(&ControlHandle::Hwnd(hwnd), l as usize) {
+                return None;
+            }
+
+            let is_input = msg == WM_CTLCOLOREDIT || msg == WM_CTLCOLORLISTBOX;
+            let (brush, color) = match is_input {
+                true => (control_background, DARK_CONTROL_BACKGROUND),
+                false => (background, DARK_BACKGROUND)
+            };
+
+            SetTextColor(w as HDC, rgb(DARK_TEXT));
+            SetBkColor(w as HDC, rgb(color));
+            Some(brush as isize)
+        },
+        _ => None
+    }
+}
+
+fn brushes() -> (HBRUSH, HBRUSH) {
+    use winapi::um::wingdi::CreateSolidBrush;
+
+    DARK_BRUSHES.with(|brushes| {
+        match brushes.get() {
+            Some(b) => b,
+            None => {
+                let b = unsafe { (CreateSolidBrush(rgb(DARK_BACKGROUND)), CreateSolidBrush(rgb(DARK_CONTROL_BACKGROUND))) };
+                rt::track(ResourceKind::Brush, b.0);
+                rt::track(ResourceKind::Brush, b.1);
+                brushes.set(Some(b));
+                b
+            }
+        }
+    })
+}
+
+fn free_brushes() {
+    use winapi::um::wingdi::DeleteObject;
+
+    if let Some((background, control_background)) = DARK_BRUSHES.with(|brushes| brushes.take()) {
+        for &brush in [background, control_background].iter() {
+            rt::untrack(brush);
+            unsafe { DeleteObject(brush as _); }
+        }
+    }
+}
+
+fn rgb(c: [u8; 3]) -> u32 {
+    use winapi::um::wingdi::RGB;
+    RGB(c[0], c[1], c[2])
+}
+
+fn class_name(handle: HWND) -> String {
+    use winapi::um::winuser::GetClassNameW;
+    use super::base_helper::from_utf16;
+
+    let mut buffer = [0u16; 64];
+    let count = unsafe { GetClassNameW(handle, buffer.as_mut_ptr(), buffer.len() as i32) } as usize;
+    from_utf16(&buffer[..count])
+}
+
+/// Returns an undocumented function of uxtheme by ordinal, or `None` if the system does not support the dark mode
+unsafe fn uxtheme_fn<T: Copy>(ordinal: u16) -> Option<T> {
+    use winapi::um::libloaderapi::{LoadLibraryW, GetProcAddress};
+
+    if windows_build() < DARK_MODE_MIN_BUILD {
+        return None;
+    }
+
+    let name = to_utf16("uxtheme.dll");
+    let module = LoadLibraryW(name.as_ptr());
+    if module.is_null() {
+        return None;
+    }
+
+    let f = GetProcAddress(module, ordinal as usize as *const i8);
+    match f.is_null() {
+        true => None,
+        false => Some(mem::transmute_copy(&f))
+    }
+}
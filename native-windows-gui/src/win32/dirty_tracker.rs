@@ -0,0 +1,214 @@
+/*!
+    Tracking of the unsaved changes of a document, and confirmation before closing its window.
+*/
+use winapi::shared::windef::HWND;
+use super::window::{bind_event_handler, unbind_event_handler, bind_raw_event_handler_inner, unbind_raw_event_handler, EventHandler, RawEventHandler};
+use super::window_helper as wh;
+use super::message_box::{modal_message, MessageParams, MessageButtons, MessageIcons, MessageChoice};
+use crate::controls::ControlHandle;
+use crate::{Event, NwgError};
+use std::{rc::Rc, cell::RefCell};
+
+/// Id of the raw handler that intercepts `WM_CLOSE`. Ids smaller than 0xFFFF are reserved by NWG.
+const CONFIRM_CLOSE_HANDLER_ID: usize = 0xC105;
+
+
+#[derive(Default)]
+struct DirtyState {
+    dirty: bool,
+    on_change: Option<Box<dyn Fn(bool)>>,
+}
+
+impl DirtyState {
+
+    /// Updates the flag. Returns `true` if the value changed.
+    fn set(&mut self, dirty: bool) -> bool {
+        let changed = self.dirty != dirty;
+        self.dirty = dirty;
+        changed
+    }
+
+}
+
+/**
+    Tracks if a document has unsaved changes.
+
+    The controls registered with `watch` mark the tracker as dirty when the user edits them (text typed in a `TextInput`,
+    new selection in a `ComboBox`, click on a `CheckBox`, ...). Other changes can be registered with `mark_dirty`.
+
+    Note that setting the value of a watched control from the code (ex: `set_text` when a document is loaded) also
+    counts as a change. Call `mark_clean` after loading or saving the document.
+
+    `confirm_close` asks the user to save the changes when the window is closed.
+
+    Requires the `dirty-tracker` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn track_changes(tracker: &nwg::DirtyTracker, window: &nwg::Window, title: &nwg::TextInput, body: &nwg::TextBox) {
+        tracker.watch(title);
+        tracker.watch(body);
+
+        tracker.confirm_close(window, "Editor", "Do you want to save the changes to the document?", || {
+            // Write the document. Returning `false` keeps the window open.
+            true
+        }).expect("Failed to hook the window");
+    }
+    ```
+*/
+#[derive(Default)]
+pub struct DirtyTracker {
+    state: Rc<RefCell<DirtyState>>,
+    handlers: RefCell<Vec<(EventHandler, [HWND; 2])>>,
+    close_handlers: RefCell<Vec<RawEventHandler>>,
+}
+
+impl DirtyTracker {
+
+    /// Returns `true` if the document has unsaved changes
+    pub fn is_dirty(&self) -> bool {
+        self.state.borrow().dirty
+    }
+
+    /// Registers a change
+    pub fn mark_dirty(&self) {
+        set_dirty(&self.state, true);
+    }
+
+    /// Clears the changes. Call this after saving or loading the document.
+    pub fn mark_clean(&self) {
+        set_dirty(&self.state, false);
+    }
+
+    /**
+        Sets a callback called when the tracker becomes dirty or clean (ex: to add a `*` to the title of the window).
+        Replaces the previous callback.
+    */
+    pub fn on_change<F: Fn(bool) + 'static>(&self, callback: F) {
+        self.state.borrow_mut().on_change = Some(Box::new(callback));
+    }
+
+    /**
+        Marks the tracker as dirty when the user edits a control. The control must have a parent.
+
+        The tracked events are: `OnTextInput`, `OnButtonClick`, `OnComboxBoxSelection`, `OnListBoxSelect`, `OnDatePickerChanged`,
+        `OnMonthCalendarSelectionChanged`, `OnHotKeyChanged`, `OnTreeViewEndItemEdit`, `OnHorizontalScroll` and `OnVerticalScroll`.
+
+        Panics if the control is not a window-like control.
+    */
+    pub fn watch<C: Into<ControlHandle>>(&self, control: C) {
+        let control = control.into();
+        let handle = control.hwnd().expect("DirtyTracker can only watch window-like controls");
+        let parent = wh::get_window_parent(handle);
+        if parent.is_null() {
+            panic!("DirtyTracker can only watch controls with a parent");
+        }
+
+        let state = self.state.clone();
+        let handler = bind_event_handler(&control, &ControlHandle::Hwnd(parent), move |evt, _evt_data, evt_handle| {
+            if evt_handle != control {
+                return;
+            }
+
+            match evt {
+                Event::OnTextInput | Event::OnButtonClick | Event::OnComboxBoxSelection | Event::OnListBoxSelect |
+                Event::OnDatePickerChanged | Event::OnMonthCalendarSelectionChanged | Event::OnHotKeyChanged |
+                Event::OnTreeViewEndItemEdit | Event::OnHorizontalScroll | Event::OnVerticalScroll => {
+                    set_dirty(&state, true);
+                },
+                _ => {}
+            }
+        });
+
+        self.handlers.borrow_mut().push((handler, [handle, parent]));
+    }
+
+    /**
+        Asks the user to save the changes when the window is closed while the tracker is dirty.
+        The message box shows `content` with the buttons "Yes" (save), "No" (discard the changes) and "Cancel".
+
+        On "Yes", `save` is called. If it returns `true`, the tracker is marked as clean and the window closes.
+        If it returns `false` (the save failed or was cancelled), the window stays open.
+
+        Call this after binding the events handler of the window (ex: after `build_ui`), so that `OnWindowClose`
+        is only sent if the window closes.
+    */
+    pub fn confirm_close<W, F>(&self, window: W, title: &str, content: &str, save: F) -> Result<(), NwgError>
+        where W: Into<ControlHandle>,
+              F: Fn() -> bool + 'static
+    {
+        use winapi::um::winuser::WM_CLOSE;
+
+        let window = window.into();
+        let title = title.to_string();
+        let content = content.to_string();
+        let state = self.state.clone();
+
+        let handler = bind_raw_event_handler_inner(&window, CONFIRM_CLOSE_HANDLER_ID, move |hwnd, msg, _w, _l| {
+            if msg != WM_CLOSE || !state.borrow().dirty {
+                return None;
+            }
+
+            let params = MessageParams {
+                title: &title,
+                content: &content,
+                buttons: MessageButtons::YesNoCancel,
+                icons: MessageIcons::Warning,
+            };
+
+            match modal_message(ControlHandle::Hwnd(hwnd), &params) {
+                MessageChoice::Yes => match save() {
+                    true => {
+                        set_dirty(&state, false);
+                        None
+                    },
+                    false => Some(0)
+                },
+                MessageChoice::No => None,
+                _ => Some(0)
+            }
+        })?;
+
+        self.close_handlers.borrow_mut().push(handler);
+
+        Ok(())
+    }
+
+}
+
+impl Drop for DirtyTracker {
+    fn drop(&mut self) {
+        use winapi::um::winuser::IsWindow;
+
+        // The controls may have been destroyed before the tracker
+        for (handler, handles) in self.handlers.borrow().iter() {
+            if handles.iter().all(|&h| unsafe { IsWindow(h) != 0 }) {
+                unbind_event_handler(handler);
+            }
+        }
+
+        for handler in self.close_handlers.borrow().iter() {
+            drop(unbind_raw_event_handler(handler));
+        }
+    }
+}
+
+/// Sets the flag and calls the change callback, after the state is released
+fn set_dirty(state: &Rc<RefCell<DirtyState>>, dirty: bool) {
+    let changed = state.borrow_mut().set(dirty);
+    if !changed {
+        return;
+    }
+
+    // The callback is taken out of the state so that it can use the tracker
+    let callback = state.borrow_mut().on_change.take();
+    if let Some(callback) = callback {
+        callback(dirty);
+
+        let mut state = state.borrow_mut();
+        if state.on_change.is_none() {
+            state.on_change = Some(callback);
+        }
+    }
+}
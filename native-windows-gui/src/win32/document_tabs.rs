@@ -0,0 +1,27 @@
+/*!
+    Notifications sent by the document tabs control to its parent.
+*/
+use winapi::um::winuser::NMHDR;
+
+/// Notification code sent when the user selects a tab
+pub(crate) const DOCUMENT_TAB_SELECT: u32 = 1;
+
+/// Notification code sent when the user clicks the close button of a tab, or middle-clicks a tab
+pub(crate) const DOCUMENT_TAB_CLOSE: u32 = 2;
+
+/// Notification code sent when the user moves a tab by dragging it
+pub(crate) const DOCUMENT_TAB_REORDER: u32 = 3;
+
+/// Notification code sent when the user right-clicks a tab
+pub(crate) const DOCUMENT_TAB_CONTEXT_MENU: u32 = 4;
+
+/// Value of `NMDOCUMENTTAB::previous` when there is no previous index
+pub(crate) const NO_TAB: usize = usize::max_value();
+
+/// The WM_NOTIFY data sent by the document tabs to their parent
+#[repr(C)]
+pub(crate) struct NMDOCUMENTTAB {
+    pub hdr: NMHDR,
+    pub index: usize,
+    pub previous: usize,
+}
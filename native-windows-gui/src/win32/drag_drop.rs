@@ -0,0 +1,590 @@
+/*!
+    OLE drag and drop between the controls and the other applications.
+
+    `register_drop_target` makes a control accept the data dragged from any application (files from the explorer, text
+    from an editor, or the custom formats of another application). The control then receives `OnDragEnter`, `OnDragOver`,
+    `OnDragLeave` and `OnDragDrop`. The handlers read the dragged data with `EventData::on_drag_drop` and accept the drop
+    by setting the effect shown to the user. A drop is refused unless a handler sets an effect.
+
+    `start_drag` starts dragging data out of a control (ex: a row of a list view dragged into the explorer). It should be
+    called when the user starts to drag, for example from `OnListViewBeginDrag`, and returns once the data is dropped.
+
+    Unlike `OnFileDrop`, the drop target receives any kind of data and can show if a drop is accepted while the data is dragged.
+
+    Requires the `drag-drop` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn accept_text(editor: &nwg::TextBox) -> Result<(), nwg::NwgError> {
+        nwg::register_drop_target(&editor.handle)?;
+
+        let handle = editor.handle;
+        nwg::bind_event_handler(&editor.handle, &editor.handle, move |evt, data, control| {
+            if control != handle { return; }
+
+            match evt {
+                nwg::Event::OnDragEnter => {
+                    let drag = data.on_drag_drop();
+                    if drag.has_format(nwg::ClipboardFormat::UnicodeText) {
+                        drag.set_effect(nwg::DropEffect::COPY);
+                    }
+                },
+                nwg::Event::OnDragDrop => {
+                    println!("Dropped {:?}", data.on_drag_drop().text());
+                },
+                _ => {}
+            }
+        });
+
+        Ok(())
+    }
+
+    fn drag_file(list: &nwg::ListView, path: &str) -> Result<(), nwg::NwgError> {
+        let data = nwg::DragData::new().files(&[path]);
+        nwg::start_drag(&list.handle, &data, nwg::DropEffect::COPY)?;
+        Ok(())
+    }
+    ```
+*/
+use winapi::shared::guiddef::{REFIID, IsEqualGUID};
+use winapi::shared::minwindef::{DWORD, ULONG, WPARAM, LPARAM};
+use winapi::shared::windef::{HWND, POINT, POINTL};
+use winapi::shared::winerror::{S_OK, E_NOINTERFACE, SUCCEEDED};
+use winapi::um::objidl::{IDataObject, FORMATETC, TYMED_HGLOBAL};
+use winapi::um::oleidl::{IDropTarget, DROPEFFECT_NONE, DROPEFFECT_COPY, DROPEFFECT_MOVE, DROPEFFECT_LINK};
+use winapi::um::unknwnbase::IUnknown;
+use winapi::um::winnt::{HRESULT, HANDLE};
+use winapi::um::winuser::{SendMessageW, WM_DESTROY, MK_SHIFT, MK_CONTROL, CF_HDROP, CF_UNICODETEXT};
+use winapi::Interface;
+use super::window::{bind_raw_event_handler_inner, unbind_raw_event_handler, RawEventHandler};
+use super::window_helper::NWG_DRAG_DROP;
+use super::clipboard::drop_files_data;
+use crate::{ControlHandle, ClipboardFormat, DropFiles, Event, NwgError};
+use std::{cell::{Cell, RefCell}, collections::HashMap, fmt, mem, ptr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::ffi::c_void;
+
+/// Id of the raw event handler that revokes a drop target when its window is destroyed
+const DROP_TARGET_HANDLER_ID: usize = 0xD0D0;
+
+const DRAG_ENTER: WPARAM = 0;
+const DRAG_OVER: WPARAM = 1;
+const DRAG_LEAVE: WPARAM = 2;
+const DRAG_DROP: WPARAM = 3;
+
+const DRAGDROP_S_DROP: HRESULT = 0x00040100;
+const DRAGDROP_S_CANCEL: HRESULT = 0x00040101;
+const DRAGDROP_E_ALREADYREGISTERED: HRESULT = 0x80040101u32 as HRESULT;
+
+/// Declared with a wrong value by winapi (`20` instead of `0x20`)
+const MK_ALT: DWORD = 0x20;
+
+/// Not declared correctly by winapi (the union is a pointer in winapi). Only the `HGLOBAL` medium is used by NWG.
+#[repr(C)]
+#[allow(non_snake_case)]
+struct STGMEDIUM {
+    tymed: DWORD,
+    hGlobal: HANDLE,
+    pUnkForRelease: *mut IUnknown,
+}
+
+// Not declared by winapi, but exported by ole32 and shell32
+extern "system" {
+    fn ReleaseStgMedium(pmedium: *mut STGMEDIUM);
+    fn SHCreateDataObject(pidlFolder: *const c_void, cidl: u32, apidl: *const c_void, pdtInner: *mut IDataObject, riid: REFIID, ppv: *mut *mut c_void) -> HRESULT;
+    fn SHDoDragDrop(hwnd: HWND, pdata: *mut IDataObject, pdsrc: *mut IUnknown, dwEffect: DWORD, pdwEffect: *mut DWORD) -> HRESULT;
+}
+
+thread_local! {
+    static OLE_INITIALIZED: Cell<bool> = Cell::new(false);
+    static DROP_TARGETS: RefCell<HashMap<usize, RawEventHandler>> = RefCell::new(HashMap::new());
+}
+
+
+bitflags! {
+    /**
+        The effect of a drag and drop operation, as shown by the cursor
+
+        * NONE: The drop is refused
+        * COPY: The data is copied
+        * MOVE: The data is moved. The source deletes its copy after the drop.
+        * LINK: A link to the data is created
+    */
+    pub struct DropEffect: u32 {
+        const NONE = DROPEFFECT_NONE;
+        const COPY = DROPEFFECT_COPY;
+        const MOVE = DROPEFFECT_MOVE;
+        const LINK = DROPEFFECT_LINK;
+    }
+}
+
+/// The state of a drag and drop operation, owned by the drop target while the event is processed
+struct DropState {
+    data: *mut IDataObject,
+    keys: DWORD,
+    position: (i32, i32),
+    allowed: DWORD,
+    effect: Cell<DWORD>,
+}
+
+/**
+    The data dragged over a drop target. See `Event::OnDragEnter`, `Event::OnDragOver` and `Event::OnDragDrop`.
+    Only valid while the event is processed.
+*/
+pub struct DragDropData {
+    state: *const DropState,
+}
+
+impl DragDropData {
+
+    /// Returns the position of the mouse, relative to the client area of the control
+    pub fn position(&self) -> (i32, i32) {
+        self.state().position
+    }
+
+    /// Returns `true` if the shift key is pressed. By convention, the data should be moved.
+    pub fn shift(&self) -> bool {
+        self.state().keys & (MK_SHIFT as DWORD) != 0
+    }
+
+    /// Returns `true` if the control key is pressed. By convention, the data should be copied.
+    pub fn control(&self) -> bool {
+        self.state().keys & (MK_CONTROL as DWORD) != 0
+    }
+
+    /// Returns `true` if the alt key is pressed. By convention, a link to the data should be created.
+    pub fn alt(&self) -> bool {
+        self.state().keys & MK_ALT != 0
+    }
+
+    /// Returns the effects allowed by the source of the data
+    pub fn allowed_effects(&self) -> DropEffect {
+        DropEffect::from_bits_truncate(self.state().allowed)
+    }
+
+    /// Returns the effect of the drop. By default, the effect set during the previous event of the operation.
+    pub fn effect(&self) -> DropEffect {
+        DropEffect::from_bits_truncate(self.state().effect.get())
+    }
+
+    /**
+        Sets the effect of the drop, which is shown by the cursor and returned to the source of the data.
+        The effects that are not allowed by the source are ignored. `DropEffect::NONE` refuses the drop.
+    */
+    pub fn set_effect(&self, effect: DropEffect) {
+        let state = self.state();
+        state.effect.set(effect.bits() & state.allowed);
+    }
+
+    /// Returns `true` if the data can be read in `format`
+    pub fn has_format(&self, format: ClipboardFormat) -> bool {
+        let format = format_etc(format.into_raw());
+        unsafe { (*self.state().data).QueryGetData(&format) == S_OK }
+    }
+
+    /// Returns the paths of the dragged files. `None` if the data does not contain files.
+    pub fn files(&self) -> Option<Vec<String>> {
+        let format = format_etc(CF_HDROP);
+
+        unsafe {
+            let mut medium: STGMEDIUM = mem::zeroed();
+            if !SUCCEEDED((*self.state().data).GetData(&format, &mut medium as *mut STGMEDIUM as _)) {
+                return None;
+            }
+
+            // The medium is released by `ReleaseStgMedium`, not by `DragFinish`
+            let files = mem::ManuallyDrop::new(DropFiles { drop: medium.hGlobal as _ }).files();
+            ReleaseStgMedium(&mut medium);
+
+            Some(files)
+        }
+    }
+
+    /// Returns the dragged text. `None` if the data does not contain text.
+    pub fn text(&self) -> Option<String> {
+        use super::base_helper::from_utf16;
+
+        self.data(ClipboardFormat::UnicodeText).map(|bytes| {
+            let text: Vec<u16> = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+            from_utf16(&text)
+        })
+    }
+
+    /// Returns a copy of the dragged data in `format`. `None` if the data cannot be read in this format.
+    pub fn data(&self, format: ClipboardFormat) -> Option<Vec<u8>> {
+        use winapi::um::winbase::{GlobalLock, GlobalUnlock, GlobalSize};
+
+        let format = format_etc(format.into_raw());
+
+        unsafe {
+            let mut medium: STGMEDIUM = mem::zeroed();
+            if !SUCCEEDED((*self.state().data).GetData(&format, &mut medium as *mut STGMEDIUM as _)) {
+                return None;
+            }
+
+            let size = GlobalSize(medium.hGlobal);
+            let source = GlobalLock(medium.hGlobal) as *const u8;
+            let data = match source.is_null() {
+                true => None,
+                false => Some(std::slice::from_raw_parts(source, size).to_vec())
+            };
+
+            GlobalUnlock(medium.hGlobal);
+            ReleaseStgMedium(&mut medium);
+
+            data
+        }
+    }
+
+    /// Returns the raw `IDataObject` of the operation, to read the data in a medium that is not a global memory handle
+    pub unsafe fn data_object(&self) -> *mut IDataObject {
+        self.state().data
+    }
+
+    fn state(&self) -> &DropState {
+        unsafe { &*self.state }
+    }
+
+}
+
+impl fmt::Debug for DragDropData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DragDropData {{ position: {:?}, effect: {:?} }}", self.position(), self.effect())
+    }
+}
+
+
+/**
+    The data dragged out of a control with `start_drag`. The data can be offered in several formats at once
+    (ex: the files and their names as text), and the drop target picks the format it understands.
+*/
+#[derive(Clone, Default)]
+pub struct DragData {
+    formats: Vec<(u32, Vec<u8>)>,
+}
+
+impl DragData {
+
+    pub fn new() -> DragData {
+        DragData { formats: Vec::new() }
+    }
+
+    /// Adds a list of files. The files are copied or moved by the explorer when they are dropped in a folder.
+    pub fn files<S: AsRef<str>>(mut self, files: &[S]) -> DragData {
+        self.formats.push((CF_HDROP, drop_files_data(files)));
+        self
+    }
+
+    /// Adds a text
+    pub fn text(mut self, text: &str) -> DragData {
+        use super::base_helper::to_utf16;
+
+        let data = to_utf16(text).iter().flat_map(|c| c.to_le_bytes().to_vec()).collect();
+        self.formats.push((CF_UNICODETEXT, data));
+        self
+    }
+
+    /// Adds the data of a custom format (ex: `ClipboardFormat::Global("MyApp.Item")`)
+    pub fn format(mut self, format: ClipboardFormat, data: &[u8]) -> DragData {
+        self.formats.push((format.into_raw(), data.to_vec()));
+        self
+    }
+
+}
+
+
+/// The `IDropTarget` implementation of NWG. Each event of the operation is sent to the window as a `NWG_DRAG_DROP` message.
+#[repr(C)]
+struct DropTarget {
+    vtbl: *const DropTargetVtbl,
+    refs: AtomicUsize,
+    hwnd: HWND,
+    data: Cell<*mut IDataObject>,
+    effect: Cell<DWORD>,
+}
+
+/// `IDropTargetVtbl` of winapi passes the `POINTL` by pointer instead of by value
+#[repr(C)]
+struct DropTargetVtbl {
+    query_interface: unsafe extern "system" fn(*mut DropTarget, REFIID, *mut *mut c_void) -> HRESULT,
+    add_ref: unsafe extern "system" fn(*mut DropTarget) -> ULONG,
+    release: unsafe extern "system" fn(*mut DropTarget) -> ULONG,
+    drag_enter: unsafe extern "system" fn(*mut DropTarget, *mut IDataObject, DWORD, POINTL, *mut DWORD) -> HRESULT,
+    drag_over: unsafe extern "system" fn(*mut DropTarget, DWORD, POINTL, *mut DWORD) -> HRESULT,
+    drag_leave: unsafe extern "system" fn(*mut DropTarget) -> HRESULT,
+    drop: unsafe extern "system" fn(*mut DropTarget, *mut IDataObject, DWORD, POINTL, *mut DWORD) -> HRESULT,
+}
+
+static DROP_TARGET_VTBL: DropTargetVtbl = DropTargetVtbl {
+    query_interface: drop_target_query_interface,
+    add_ref: drop_target_add_ref,
+    release: drop_target_release,
+    drag_enter: drop_target_drag_enter,
+    drag_over: drop_target_drag_over,
+    drag_leave: drop_target_drag_leave,
+    drop: drop_target_drop,
+};
+
+impl DropTarget {
+
+    /// Sends an event of the operation to the window and returns the effect chosen by the handlers
+    unsafe fn send(&self, event: WPARAM, keys: DWORD, pt: POINTL, allowed: DWORD) -> DWORD {
+        use winapi::um::winuser::ScreenToClient;
+
+        let mut point = POINT { x: pt.x, y: pt.y };
+        ScreenToClient(self.hwnd, &mut point);
+
+        let state = DropState {
+            data: self.data.get(),
+            keys,
+            position: (point.x, point.y),
+            allowed,
+            effect: Cell::new(self.effect.get() & allowed),
+        };
+
+        SendMessageW(self.hwnd, NWG_DRAG_DROP, event, &state as *const DropState as LPARAM);
+
+        let effect = state.effect.get() & allowed;
+        self.effect.set(effect);
+        effect
+    }
+
+    unsafe fn release_data(&self) {
+        let data = self.data.replace(ptr::null_mut());
+        if !data.is_null() {
+            (*data).Release();
+        }
+    }
+
+}
+
+unsafe extern "system" fn drop_target_query_interface(this: *mut DropTarget, riid: REFIID, out: *mut *mut c_void) -> HRESULT {
+    if IsEqualGUID(&*riid, &IUnknown::uuidof()) || IsEqualGUID(&*riid, &IDropTarget::uuidof()) {
+        drop_target_add_ref(this);
+        *out = this as *mut c_void;
+        S_OK
+    } else {
+        *out = ptr::null_mut();
+        E_NOINTERFACE
+    }
+}
+
+unsafe extern "system" fn drop_target_add_ref(this: *mut DropTarget) -> ULONG {
+    ((*this).refs.fetch_add(1, Ordering::SeqCst) + 1) as ULONG
+}
+
+unsafe extern "system" fn drop_target_release(this: *mut DropTarget) -> ULONG {
+    let refs = (*this).refs.fetch_sub(1, Ordering::SeqCst) - 1;
+    if refs == 0 {
+        let target = Box::from_raw(this);
+        target.release_data();
+    }
+
+    refs as ULONG
+}
+
+unsafe extern "system" fn drop_target_drag_enter(this: *mut DropTarget, data: *mut IDataObject, keys: DWORD, pt: POINTL, effect: *mut DWORD) -> HRESULT {
+    let target = &*this;
+    target.release_data();
+
+    (*data).AddRef();
+    target.data.set(data);
+    target.effect.set(DROPEFFECT_NONE);
+
+    *effect = target.send(DRAG_ENTER, keys, pt, *effect);
+    S_OK
+}
+
+unsafe extern "system" fn drop_target_drag_over(this: *mut DropTarget, keys: DWORD, pt: POINTL, effect: *mut DWORD) -> HRESULT {
+    let target = &*this;
+    *effect = match target.data.get().is_null() {
+        true => DROPEFFECT_NONE,
+        false => target.send(DRAG_OVER, keys, pt, *effect)
+    };
+
+    S_OK
+}
+
+unsafe extern "system" fn drop_target_drag_leave(this: *mut DropTarget) -> HRESULT {
+    let target = &*this;
+    SendMessageW(target.hwnd, NWG_DRAG_DROP, DRAG_LEAVE, 0);
+    target.release_data();
+
+    S_OK
+}
+
+unsafe extern "system" fn drop_target_drop(this: *mut DropTarget, data: *mut IDataObject, keys: DWORD, pt: POINTL, effect: *mut DWORD) -> HRESULT {
+    let target = &*this;
+    if target.data.get() != data {
+        target.release_data();
+        (*data).AddRef();
+        target.data.set(data);
+    }
+
+    *effect = match target.effect.get() {
+        DROPEFFECT_NONE => DROPEFFECT_NONE,
+        _ => target.send(DRAG_DROP, keys, pt, *effect)
+    };
+
+    target.release_data();
+    S_OK
+}
+
+
+/// Returns the event of a `NWG_DRAG_DROP` message
+pub(crate) fn drag_event(w: WPARAM) -> Event {
+    match w {
+        DRAG_ENTER => Event::OnDragEnter,
+        DRAG_OVER => Event::OnDragOver,
+        DRAG_LEAVE => Event::OnDragLeave,
+        _ => Event::OnDragDrop,
+    }
+}
+
+/// Wraps the state sent in a `NWG_DRAG_DROP` message
+pub(crate) fn drag_data(l: LPARAM) -> DragDropData {
+    DragDropData { state: l as *const DropState }
+}
+
+fn format_etc(format: u32) -> FORMATETC {
+    use winapi::shared::wtypes::DVASPECT_CONTENT;
+
+    FORMATETC {
+        cfFormat: format as _,
+        ptd: ptr::null(),
+        dwAspect: DVASPECT_CONTENT,
+        lindex: -1,
+        tymed: TYMED_HGLOBAL,
+    }
+}
+
+/// OLE must be initialized on the thread of the windows before a drag and drop
+fn ole_initialize() -> Result<(), NwgError> {
+    use winapi::um::ole2::OleInitialize;
+
+    if OLE_INITIALIZED.with(|i| i.get()) {
+        return Ok(());
+    }
+
+    match SUCCEEDED(unsafe { OleInitialize(ptr::null_mut()) }) {
+        true => {
+            OLE_INITIALIZED.with(|i| i.set(true));
+            Ok(())
+        },
+        false => Err(NwgError::drag_drop("OleInitialize failed. The thread must be a single threaded apartment."))
+    }
+}
+
+
+/**
+    Makes a control accept the data dragged by the user. The control then receives `OnDragEnter`, `OnDragOver`,
+    `OnDragLeave` and `OnDragDrop`. The drop target is removed when the control is destroyed.
+
+    Fails if the control is already a drop target.
+*/
+pub fn register_drop_target<C: Into<ControlHandle>>(control: C) -> Result<(), NwgError> {
+    use winapi::um::ole2::{RegisterDragDrop, RevokeDragDrop};
+
+    let handle = control.into();
+    let hwnd = handle.hwnd().expect("Only window-like controls can be drop targets");
+
+    ole_initialize()?;
+
+    let target = Box::into_raw(Box::new(DropTarget {
+        vtbl: &DROP_TARGET_VTBL,
+        refs: AtomicUsize::new(1),
+        hwnd,
+        data: Cell::new(ptr::null_mut()),
+        effect: Cell::new(DROPEFFECT_NONE),
+    }));
+
+    // `RegisterDragDrop` keeps its own reference to the target
+    let result = unsafe { RegisterDragDrop(hwnd, target as *mut IDropTarget) };
+    unsafe { drop_target_release(target); }
+
+    match result {
+        S_OK => {},
+        DRAGDROP_E_ALREADYREGISTERED => { return Err(NwgError::drag_drop("The control is already a drop target")); },
+        e => { return Err(NwgError::drag_drop(format!("RegisterDragDrop failed (0x{:X})", e))); }
+    }
+
+    let handler = bind_raw_event_handler_inner(&handle, DROP_TARGET_HANDLER_ID, move |hwnd, msg, _w, _l| {
+        if msg == WM_DESTROY {
+            unsafe { RevokeDragDrop(hwnd); }
+            DROP_TARGETS.with(|targets| targets.borrow_mut().remove(&(hwnd as usize)));
+        }
+
+        None
+    })?;
+
+    DROP_TARGETS.with(|targets| targets.borrow_mut().insert(hwnd as usize, handler));
+
+    Ok(())
+}
+
+/// Stops a control from accepting the dragged data. Does nothing if the control is not a drop target.
+pub fn unregister_drop_target<C: Into<ControlHandle>>(control: C) {
+    use winapi::um::ole2::RevokeDragDrop;
+
+    let hwnd = control.into().hwnd().expect("Only window-like controls can be drop targets");
+    let handler = DROP_TARGETS.with(|targets| targets.borrow_mut().remove(&(hwnd as usize)));
+
+    if let Some(handler) = handler {
+        unsafe { RevokeDragDrop(hwnd); }
+        drop(unbind_raw_event_handler(&handler));
+    }
+}
+
+/**
+    Starts dragging data out of a control. The function returns once the data is dropped, with the effect chosen by the
+    drop target, or `DropEffect::NONE` if the user cancelled the operation (with the escape key or by dropping the
+    data where it is not accepted). If the effect is `DropEffect::MOVE`, the source should delete the data.
+
+    Must be called while the left mouse button is pressed, usually when the user starts to drag (ex: `OnListViewBeginDrag`).
+*/
+pub fn start_drag<C: Into<ControlHandle>>(source: C, data: &DragData, allowed: DropEffect) -> Result<DropEffect, NwgError> {
+    use winapi::um::winbase::{GlobalAlloc, GlobalLock, GlobalUnlock, GlobalFree, GMEM_MOVEABLE};
+
+    let hwnd = source.into().hwnd().expect("Only window-like controls can start a drag and drop");
+
+    ole_initialize()?;
+
+    unsafe {
+        let mut object: *mut IDataObject = ptr::null_mut();
+        let hr = SHCreateDataObject(ptr::null(), 0, ptr::null(), ptr::null_mut(), &IDataObject::uuidof(), &mut object as *mut _ as *mut *mut c_void);
+        if !SUCCEEDED(hr) {
+            return Err(NwgError::drag_drop(format!("Failed to create the data object (0x{:X})", hr)));
+        }
+
+        for (format, bytes) in data.formats.iter() {
+            let memory = GlobalAlloc(GMEM_MOVEABLE, bytes.len().max(1));
+            if memory.is_null() {
+                (*object).Release();
+                return Err(NwgError::drag_drop("Failed to allocate the dragged data"));
+            }
+
+            let dest = GlobalLock(memory) as *mut u8;
+            ptr::copy_nonoverlapping(bytes.as_ptr(), dest, bytes.len());
+            GlobalUnlock(memory);
+
+            // With `fRelease`, the data object owns the memory if the call succeeds
+            let format = format_etc(*format);
+            let mut medium = STGMEDIUM { tymed: TYMED_HGLOBAL, hGlobal: memory, pUnkForRelease: ptr::null_mut() };
+            if !SUCCEEDED((*object).SetData(&format, &mut medium as *mut STGMEDIUM as _, 1)) {
+                GlobalFree(memory);
+            }
+        }
+
+        // Without a drop source, the shell uses its default drop source and shows the drag image of the data
+        let mut effect = DROPEFFECT_NONE;
+        let hr = SHDoDragDrop(hwnd, object, ptr::null_mut(), allowed.bits(), &mut effect);
+        (*object).Release();
+
+        match hr {
+            DRAGDROP_S_DROP => Ok(DropEffect::from_bits_truncate(effect)),
+            DRAGDROP_S_CANCEL => Ok(DropEffect::NONE),
+            e => Err(NwgError::drag_drop(format!("SHDoDragDrop failed (0x{:X})", e)))
+        }
+    }
+}
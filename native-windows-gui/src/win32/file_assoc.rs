@@ -0,0 +1,336 @@
+/*!
+    Registration of the file types opened by the application.
+
+    The file types are registered for the current user only (under `HKEY_CURRENT_USER\Software\Classes`), so the
+    application does not need to run as administrator. Since Windows 8, an application cannot make itself the default program
+    of a file type: `register` adds the application to the "Open with" list, and `prompt_default` asks the user to choose it.
+
+    Requires the `file-association` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn register_file_type(window: &nwg::Window) -> Result<(), nwg::NwgError> {
+        nwg::file_assoc::register(".mydoc", "MyCompany.MyApp.Document", None, None)?;
+
+        if !nwg::file_assoc::is_default(".mydoc", "MyCompany.MyApp.Document") {
+            nwg::file_assoc::prompt_default(Some(&window.handle), ".mydoc")?;
+        }
+
+        Ok(())
+    }
+    ```
+*/
+use winapi::shared::minwindef::{HKEY, DWORD, UINT, LPCVOID};
+use winapi::shared::windef::HWND;
+use winapi::shared::winerror::{ERROR_SUCCESS, ERROR_FILE_NOT_FOUND, ERROR_CANCELLED, S_OK, HRESULT_FROM_WIN32};
+use winapi::um::winnt::{HRESULT, LONG, LPCWSTR, REG_SZ, REG_NONE, KEY_READ, KEY_WRITE, REG_OPTION_NON_VOLATILE};
+use winapi::um::winreg::{HKEY_CURRENT_USER, RegCreateKeyExW, RegSetValueExW, RegQueryValueExW, RegDeleteValueW, RegCloseKey};
+use super::base_helper::{to_utf16, from_utf16};
+use crate::{ControlHandle, NwgError};
+use std::{mem, ptr};
+
+const CLASSES: &'static str = "Software\\Classes";
+const USER_CHOICE: &'static str = "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\FileExts";
+
+const SHCNE_ASSOCCHANGED: LONG = 0x08000000;
+const SHCNF_IDLIST: UINT = 0x0000;
+
+const OAIF_ALLOW_REGISTRATION: u32 = 0x01;
+const OAIF_REGISTER_EXT: u32 = 0x02;
+const OAIF_FORCE_REGISTRATION: u32 = 0x08;
+
+/// Not declared by winapi
+#[repr(C)]
+#[allow(non_snake_case)]
+struct OPENASINFO {
+    pcszFile: LPCWSTR,
+    pcszClass: LPCWSTR,
+    oaifInFlags: u32,
+}
+
+// Not declared by winapi, but exported by shell32
+extern "system" {
+    fn SHChangeNotify(wEventId: LONG, uFlags: UINT, dwItem1: LPCVOID, dwItem2: LPCVOID);
+    fn SHOpenWithDialog(hwndParent: HWND, poainfo: *const OPENASINFO) -> HRESULT;
+}
+
+
+/**
+    Registers the file type `ext` (ex: ".txt") for the current user and associates it with the program identifier `progid`
+    (ex: "MyCompany.MyApp.Document"). The application is added to the "Open with" list of the file type, and becomes the
+    default program if the file type has none.
+
+    Parameters:
+      * `icon`: The icon of the files, as `"path,index"`. Defaults to the first icon of the executable.
+      * `open_command`: The command used to open a file. `%1` is replaced by the path of the file. Defaults to `"<current exe>" "%1"`.
+*/
+pub fn register(ext: &str, progid: &str, icon: Option<&str>, open_command: Option<&str>) -> Result<(), NwgError> {
+    let ext = normalize_extension(ext);
+    let exe = current_exe()?;
+
+    let icon = match icon {
+        Some(icon) => icon.to_string(),
+        None => format!("{},0", exe)
+    };
+
+    let open_command = match open_command {
+        Some(cmd) => cmd.to_string(),
+        None => format!("\"{}\" \"%1\"", exe)
+    };
+
+    let progid_key = format!("{}\\{}", CLASSES, progid);
+    write_string(&format!("{}\\DefaultIcon", progid_key), "", &icon)?;
+    write_string(&format!("{}\\shell\\open\\command", progid_key), "", &open_command)?;
+
+    let ext_key = format!("{}\\{}", CLASSES, ext);
+    write_none(&format!("{}\\OpenWithProgids", ext_key), progid)?;
+
+    if read_string(&ext_key, "").map(|v| v.is_empty()).unwrap_or(true) {
+        write_string(&ext_key, "", progid)?;
+    }
+
+    notify_change();
+
+    Ok(())
+}
+
+/**
+    Removes the registration of `register`. The registration of the other applications is not modified.
+*/
+pub fn unregister(ext: &str, progid: &str) -> Result<(), NwgError> {
+    use winapi::um::winreg::RegDeleteTreeW;
+
+    let ext = normalize_extension(ext);
+    let ext_key = format!("{}\\{}", CLASSES, ext);
+
+    delete_value(&format!("{}\\OpenWithProgids", ext_key), progid)?;
+
+    if read_string(&ext_key, "").as_ref().map(|v| v.as_str()) == Some(progid) {
+        delete_value(&ext_key, "")?;
+    }
+
+    let progid_key = to_utf16(&format!("{}\\{}", CLASSES, progid));
+    match unsafe { RegDeleteTreeW(HKEY_CURRENT_USER, progid_key.as_ptr()) } as DWORD {
+        ERROR_SUCCESS | ERROR_FILE_NOT_FOUND => {},
+        code => { return Err(NwgError::file_association(format!("Failed to delete the program identifier {:?} (error {})", progid, code))); }
+    }
+
+    notify_change();
+
+    Ok(())
+}
+
+/**
+    Adds a command to the context menu of the files of `progid`. `progid` can be a program identifier registered with `register`,
+    `"*"` for all the files, or `"SystemFileAssociations\.ext"` for a file type owned by another application.
+
+    Parameters:
+      * `verb`: The identifier of the command (ex: "print", "compress")
+      * `label`: The text of the menu item. A `&` marks the access key.
+      * `command`: The command used to open a file. `%1` is replaced by the path of the file.
+         Defaults to `"<current exe>" /nwg-verb:<verb> "%1"`, and `CommandLine::verb` returns the verb in the started process.
+
+    When the command is used on several files, the shell starts one process per file. Use a `SingleInstance` to forward the files
+    to the first process, so that they open in the same window.
+*/
+pub fn register_verb(progid: &str, verb: &str, label: &str, command: Option<&str>) -> Result<(), NwgError> {
+    use super::command_line::VERB_OPTION;
+
+    let command = match command {
+        Some(cmd) => cmd.to_string(),
+        None => format!("\"{}\" /{}:{} \"%1\"", current_exe()?, VERB_OPTION, verb)
+    };
+
+    let verb_key = format!("{}\\{}\\shell\\{}", CLASSES, progid, verb);
+    write_string(&verb_key, "", label)?;
+    write_string(&format!("{}\\command", verb_key), "", &command)?;
+
+    notify_change();
+
+    Ok(())
+}
+
+/// Removes a command added with `register_verb`
+pub fn unregister_verb(progid: &str, verb: &str) -> Result<(), NwgError> {
+    use winapi::um::winreg::RegDeleteTreeW;
+
+    let verb_key = to_utf16(&format!("{}\\{}\\shell\\{}", CLASSES, progid, verb));
+    match unsafe { RegDeleteTreeW(HKEY_CURRENT_USER, verb_key.as_ptr()) } as DWORD {
+        ERROR_SUCCESS | ERROR_FILE_NOT_FOUND => {},
+        code => { return Err(NwgError::file_association(format!("Failed to delete the verb {:?} of {:?} (error {})", verb, progid, code))); }
+    }
+
+    notify_change();
+
+    Ok(())
+}
+
+/// Returns `true` if `progid` is registered in the "Open with" list of the file type `ext` for the current user
+pub fn is_registered(ext: &str, progid: &str) -> bool {
+    let ext = normalize_extension(ext);
+    let key = format!("{}\\{}\\OpenWithProgids", CLASSES, ext);
+    value_exists(&key, progid)
+}
+
+/// Returns `true` if the user chose `progid` as the default program of the file type `ext`,
+/// or if the file type only has the default set by `register`
+pub fn is_default(ext: &str, progid: &str) -> bool {
+    let ext = normalize_extension(ext);
+
+    match read_string(&format!("{}\\{}\\UserChoice", USER_CHOICE, ext), "ProgId") {
+        Some(choice) => choice == progid,
+        None => read_string(&format!("{}\\{}", CLASSES, ext), "").as_ref().map(|v| v.as_str()) == Some(progid)
+    }
+}
+
+/**
+    Shows the system dialog that lets the user choose the default program of the file type `ext`.
+    Returns once the dialog is closed. The application must be registered with `register` first.
+*/
+pub fn prompt_default(parent: Option<&ControlHandle>, ext: &str) -> Result<(), NwgError> {
+    let ext = normalize_extension(ext);
+    let parent = parent.and_then(|p| p.hwnd()).unwrap_or(ptr::null_mut());
+
+    let file = to_utf16(&ext);
+    let info = OPENASINFO {
+        pcszFile: file.as_ptr(),
+        pcszClass: ptr::null(),
+        oaifInFlags: OAIF_ALLOW_REGISTRATION | OAIF_REGISTER_EXT | OAIF_FORCE_REGISTRATION,
+    };
+
+    match unsafe { SHOpenWithDialog(parent, &info) } {
+        S_OK => Ok(()),
+        // The user cancelled the dialog
+        e if e == HRESULT_FROM_WIN32(ERROR_CANCELLED) => Ok(()),
+        e => Err(NwgError::file_association(format!("Failed to show the default program dialog (error {:#X})", e)))
+    }
+}
+
+fn normalize_extension(ext: &str) -> String {
+    match ext.starts_with('.') {
+        true => ext.to_lowercase(),
+        false => format!(".{}", ext.to_lowercase())
+    }
+}
+
+fn current_exe() -> Result<String, NwgError> {
+    match std::env::current_exe() {
+        Ok(path) => Ok(path.to_string_lossy().into_owned()),
+        Err(e) => Err(NwgError::file_association(format!("Failed to get the path of the executable: {}", e)))
+    }
+}
+
+/// Tells the shell to refresh the icons and the associations
+fn notify_change() {
+    unsafe { SHChangeNotify(SHCNE_ASSOCCHANGED, SHCNF_IDLIST, ptr::null(), ptr::null()); }
+}
+
+/// Opens (or creates) a key under `HKEY_CURRENT_USER`. The key must be closed with `RegCloseKey`.
+unsafe fn open_key(path: &str, create: bool) -> Result<HKEY, NwgError> {
+    use winapi::um::winreg::RegOpenKeyExW;
+
+    let path_w = to_utf16(path);
+    let mut key: HKEY = ptr::null_mut();
+
+    let result = match create {
+        true => RegCreateKeyExW(HKEY_CURRENT_USER, path_w.as_ptr(), 0, ptr::null_mut(), REG_OPTION_NON_VOLATILE, KEY_READ | KEY_WRITE, ptr::null_mut(), &mut key, ptr::null_mut()),
+        false => RegOpenKeyExW(HKEY_CURRENT_USER, path_w.as_ptr(), 0, KEY_READ | KEY_WRITE, &mut key)
+    };
+
+    match result as DWORD {
+        ERROR_SUCCESS => Ok(key),
+        code => Err(NwgError::file_association(format!("Failed to open registry key {:?} (error {})", path, code)))
+    }
+}
+
+fn write_string(path: &str, name: &str, value: &str) -> Result<(), NwgError> {
+    let name_w = to_utf16(name);
+    let value_w = to_utf16(value);
+
+    unsafe {
+        let key = open_key(path, true)?;
+        let result = RegSetValueExW(key, name_w.as_ptr(), 0, REG_SZ, value_w.as_ptr() as _, (value_w.len() * mem::size_of::<u16>()) as DWORD);
+        RegCloseKey(key);
+
+        match result as DWORD {
+            ERROR_SUCCESS => Ok(()),
+            code => Err(NwgError::file_association(format!("Failed to write registry value {:?} in {:?} (error {})", name, path, code)))
+        }
+    }
+}
+
+/// Writes an empty `REG_NONE` value. Used by the `OpenWithProgids` keys.
+fn write_none(path: &str, name: &str) -> Result<(), NwgError> {
+    let name_w = to_utf16(name);
+
+    unsafe {
+        let key = open_key(path, true)?;
+        let result = RegSetValueExW(key, name_w.as_ptr(), 0, REG_NONE, ptr::null(), 0);
+        RegCloseKey(key);
+
+        match result as DWORD {
+            ERROR_SUCCESS => Ok(()),
+            code => Err(NwgError::file_association(format!("Failed to write registry value {:?} in {:?} (error {})", name, path, code)))
+        }
+    }
+}
+
+fn read_string(path: &str, name: &str) -> Option<String> {
+    let name_w = to_utf16(name);
+
+    unsafe {
+        let key = open_key(path, false).ok()?;
+
+        let mut value_type: DWORD = 0;
+        let mut size: DWORD = 0;
+        let mut value = None;
+
+        let result = RegQueryValueExW(key, name_w.as_ptr(), ptr::null_mut(), &mut value_type, ptr::null_mut(), &mut size);
+        if result as DWORD == ERROR_SUCCESS && value_type == REG_SZ {
+            let mut buffer: Vec<u16> = vec![0; (size as usize / 2) + 1];
+            let result = RegQueryValueExW(key, name_w.as_ptr(), ptr::null_mut(), ptr::null_mut(), buffer.as_mut_ptr() as _, &mut size);
+            if result as DWORD == ERROR_SUCCESS {
+                value = Some(from_utf16(&buffer));
+            }
+        }
+
+        RegCloseKey(key);
+        value
+    }
+}
+
+fn value_exists(path: &str, name: &str) -> bool {
+    let name_w = to_utf16(name);
+
+    unsafe {
+        let key = match open_key(path, false) {
+            Ok(key) => key,
+            Err(_) => { return false; }
+        };
+
+        let result = RegQueryValueExW(key, name_w.as_ptr(), ptr::null_mut(), ptr::null_mut(), ptr::null_mut(), ptr::null_mut());
+        RegCloseKey(key);
+
+        result as DWORD == ERROR_SUCCESS
+    }
+}
+
+fn delete_value(path: &str, name: &str) -> Result<(), NwgError> {
+    let name_w = to_utf16(name);
+
+    unsafe {
+        let key = match open_key(path, false) {
+            Ok(key) => key,
+            Err(_) => { return Ok(()); }
+        };
+
+        let result = RegDeleteValueW(key, name_w.as_ptr());
+        RegCloseKey(key);
+
+        match result as DWORD {
+            ERROR_SUCCESS | ERROR_FILE_NOT_FOUND => Ok(()),
+            code => Err(NwgError::file_association(format!("Failed to delete registry value {:?} in {:?} (error {})", name, path, code)))
+        }
+    }
+}
@@ -0,0 +1,452 @@
+/*!
+    A wrapper over GDI+, the drawing library of Windows with anti-aliasing, alpha blending, gradients and image scaling.
+
+    GDI+ is started by `GdiPlus::startup`. The returned guard must be kept alive as long as the GDI+ objects are used
+    (usually as a field of the application) and the objects must be dropped before the guard.
+
+    A `Graphics` draws on a device context, usually the one returned by `PaintData::begin_paint` in a `OnPaint` event.
+    The coordinates are in pixels, as floats. The colors are `[r, g, b, a]`.
+
+    Requires the `gdiplus` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+    use nwg::gdiplus::{Graphics, Pen, Brush, SmoothingMode};
+
+    // `_gdiplus` is a `GdiPlus` guard kept by the application
+    fn paint(data: &nwg::EventData) -> Result<(), nwg::NwgError> {
+        let paint = data.on_paint();
+        let ps = paint.begin_paint();
+
+        {
+            let graphics = Graphics::from_hdc(ps.hdc)?;
+            graphics.set_smoothing_mode(SmoothingMode::AntiAlias);
+            graphics.clear([255, 255, 255, 255]);
+
+            let brush = Brush::linear_gradient((0.0, 0.0), (200.0, 0.0), [30, 120, 220, 255], [30, 120, 220, 0])?;
+            graphics.fill_ellipse(&brush, [10.0, 10.0, 200.0, 100.0]);
+
+            let pen = Pen::new([0, 0, 0, 255], 2.0)?;
+            graphics.draw_curve(&pen, &[(10.0, 150.0), (60.0, 120.0), (110.0, 180.0), (160.0, 130.0)], 0.5);
+        }
+
+        paint.end_paint(&ps);
+        Ok(())
+    }
+    ```
+*/
+use winapi::shared::basetsd::ULONG_PTR;
+use winapi::shared::minwindef::{BOOL, UINT};
+use winapi::shared::windef::{HDC, HBITMAP, HPALETTE};
+use winapi::ctypes::{c_int, c_void};
+use super::base_helper::to_utf16;
+use crate::NwgError;
+use std::sync::Mutex;
+use std::ptr;
+
+type GpStatus = c_int;
+type ARGB = u32;
+
+const OK: GpStatus = 0;
+
+/// Not declared by winapi
+#[repr(C)]
+#[allow(non_snake_case)]
+struct GdiplusStartupInput {
+    GdiplusVersion: UINT,
+    DebugEventCallback: *mut c_void,
+    SuppressBackgroundThread: BOOL,
+    SuppressExternalCodecs: BOOL,
+}
+
+#[repr(C)]
+struct GpPointF {
+    x: f32,
+    y: f32,
+}
+
+enum GpGraphics {}
+enum GpPen {}
+enum GpBrush {}
+enum GpImage {}
+
+const UNIT_WORLD: c_int = 0;
+const FILL_MODE_ALTERNATE: c_int = 0;
+const WRAP_MODE_TILE: c_int = 0;
+
+// Not declared by winapi, but exported by gdiplus
+#[link(name = "gdiplus")]
+extern "system" {
+    fn GdiplusStartup(token: *mut ULONG_PTR, input: *const GdiplusStartupInput, output: *mut c_void) -> GpStatus;
+    fn GdiplusShutdown(token: ULONG_PTR);
+
+    fn GdipCreateFromHDC(hdc: HDC, graphics: *mut *mut GpGraphics) -> GpStatus;
+    fn GdipDeleteGraphics(graphics: *mut GpGraphics) -> GpStatus;
+    fn GdipSetSmoothingMode(graphics: *mut GpGraphics, mode: c_int) -> GpStatus;
+    fn GdipSetInterpolationMode(graphics: *mut GpGraphics, mode: c_int) -> GpStatus;
+    fn GdipGraphicsClear(graphics: *mut GpGraphics, color: ARGB) -> GpStatus;
+
+    fn GdipCreatePen1(color: ARGB, width: f32, unit: c_int, pen: *mut *mut GpPen) -> GpStatus;
+    fn GdipDeletePen(pen: *mut GpPen) -> GpStatus;
+    fn GdipSetPenDashStyle(pen: *mut GpPen, style: c_int) -> GpStatus;
+    fn GdipSetPenLineCap197819(pen: *mut GpPen, start: c_int, end: c_int, dash: c_int) -> GpStatus;
+
+    fn GdipCreateSolidFill(color: ARGB, brush: *mut *mut GpBrush) -> GpStatus;
+    fn GdipCreateLineBrush(point1: *const GpPointF, point2: *const GpPointF, color1: ARGB, color2: ARGB, wrap: c_int, brush: *mut *mut GpBrush) -> GpStatus;
+    fn GdipDeleteBrush(brush: *mut GpBrush) -> GpStatus;
+
+    fn GdipDrawLine(graphics: *mut GpGraphics, pen: *mut GpPen, x1: f32, y1: f32, x2: f32, y2: f32) -> GpStatus;
+    fn GdipDrawLines(graphics: *mut GpGraphics, pen: *mut GpPen, points: *const GpPointF, count: c_int) -> GpStatus;
+    fn GdipDrawBezier(graphics: *mut GpGraphics, pen: *mut GpPen, x1: f32, y1: f32, x2: f32, y2: f32, x3: f32, y3: f32, x4: f32, y4: f32) -> GpStatus;
+    fn GdipDrawCurve2(graphics: *mut GpGraphics, pen: *mut GpPen, points: *const GpPointF, count: c_int, tension: f32) -> GpStatus;
+    fn GdipDrawRectangle(graphics: *mut GpGraphics, pen: *mut GpPen, x: f32, y: f32, width: f32, height: f32) -> GpStatus;
+    fn GdipFillRectangle(graphics: *mut GpGraphics, brush: *mut GpBrush, x: f32, y: f32, width: f32, height: f32) -> GpStatus;
+    fn GdipDrawEllipse(graphics: *mut GpGraphics, pen: *mut GpPen, x: f32, y: f32, width: f32, height: f32) -> GpStatus;
+    fn GdipFillEllipse(graphics: *mut GpGraphics, brush: *mut GpBrush, x: f32, y: f32, width: f32, height: f32) -> GpStatus;
+    fn GdipDrawPolygon(graphics: *mut GpGraphics, pen: *mut GpPen, points: *const GpPointF, count: c_int) -> GpStatus;
+    fn GdipFillPolygon(graphics: *mut GpGraphics, brush: *mut GpBrush, points: *const GpPointF, count: c_int, fill_mode: c_int) -> GpStatus;
+
+    fn GdipLoadImageFromFile(filename: *const u16, image: *mut *mut GpImage) -> GpStatus;
+    fn GdipCreateBitmapFromHBITMAP(bitmap: HBITMAP, palette: HPALETTE, image: *mut *mut GpImage) -> GpStatus;
+    fn GdipDisposeImage(image: *mut GpImage) -> GpStatus;
+    fn GdipGetImageWidth(image: *mut GpImage, width: *mut UINT) -> GpStatus;
+    fn GdipGetImageHeight(image: *mut GpImage, height: *mut UINT) -> GpStatus;
+    fn GdipDrawImageRect(graphics: *mut GpGraphics, image: *mut GpImage, x: f32, y: f32, width: f32, height: f32) -> GpStatus;
+}
+
+lazy_static! {
+    /// The number of `GdiPlus` guards alive and the token of GDI+
+    static ref STARTUP: Mutex<(usize, usize)> = Mutex::new((0, 0));
+}
+
+fn check(status: GpStatus, what: &str) -> Result<(), NwgError> {
+    match status {
+        OK => Ok(()),
+        s => Err(NwgError::gdiplus(format!("{} (status {})", what, s)))
+    }
+}
+
+fn argb(color: [u8; 4]) -> ARGB {
+    let [r, g, b, a] = color;
+    ((a as u32) << 24) | ((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
+}
+
+fn points(points: &[(f32, f32)]) -> Vec<GpPointF> {
+    points.iter().map(|&(x, y)| GpPointF { x, y }).collect()
+}
+
+
+/**
+    Keeps GDI+ started. GDI+ is shut down when the last guard is dropped.
+*/
+#[derive(Debug)]
+pub struct GdiPlus {
+    _private: (),
+}
+
+impl GdiPlus {
+
+    /// Starts GDI+, or returns a new guard if GDI+ is already started
+    pub fn startup() -> Result<GdiPlus, NwgError> {
+        let mut state = STARTUP.lock().unwrap();
+        if state.0 == 0 {
+            let input = GdiplusStartupInput {
+                GdiplusVersion: 1,
+                DebugEventCallback: ptr::null_mut(),
+                SuppressBackgroundThread: 0,
+                SuppressExternalCodecs: 0,
+            };
+
+            let mut token: ULONG_PTR = 0;
+            check(unsafe { GdiplusStartup(&mut token, &input, ptr::null_mut()) }, "Failed to start GDI+")?;
+            state.1 = token;
+        }
+
+        state.0 += 1;
+
+        Ok(GdiPlus { _private: () })
+    }
+
+}
+
+impl Drop for GdiPlus {
+    fn drop(&mut self) {
+        let mut state = STARTUP.lock().unwrap();
+        state.0 -= 1;
+        if state.0 == 0 {
+            unsafe { GdiplusShutdown(state.1); }
+            state.1 = 0;
+        }
+    }
+}
+
+
+/// How the lines and the edges of the shapes are smoothed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SmoothingMode {
+    /// No anti-aliasing. The default.
+    None,
+    /// Anti-aliasing with a 8x4 box filter
+    AntiAlias,
+    /// Anti-aliasing with a 8x8 box filter. Slower, but better for the nearly horizontal lines.
+    AntiAlias8x8,
+}
+
+/// How the images are scaled by `Graphics::draw_image`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InterpolationMode {
+    /// The closest pixel. Keeps the hard edges of the pixel art.
+    NearestNeighbor,
+    Bilinear,
+    Bicubic,
+    /// Bilinear with prefiltering. Good for the large reductions.
+    HighQualityBilinear,
+    /// Bicubic with prefiltering. The best quality and the slowest.
+    HighQualityBicubic,
+}
+
+/// The dashes of a `Pen`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DashStyle {
+    Solid,
+    Dash,
+    Dot,
+    DashDot,
+    DashDotDot,
+}
+
+
+/**
+    Draws on a device context with GDI+. The device context must stay valid while the graphics is used.
+*/
+pub struct Graphics {
+    handle: *mut GpGraphics,
+}
+
+impl Graphics {
+
+    /// Creates a graphics that draws on a device context (ex: `PAINTSTRUCT::hdc`). GDI+ must be started.
+    pub fn from_hdc(hdc: HDC) -> Result<Graphics, NwgError> {
+        let mut handle = ptr::null_mut();
+        check(unsafe { GdipCreateFromHDC(hdc, &mut handle) }, "Failed to create the graphics")?;
+        Ok(Graphics { handle })
+    }
+
+    /// Sets how the lines and the edges of the shapes are smoothed
+    pub fn set_smoothing_mode(&self, mode: SmoothingMode) {
+        let mode = match mode {
+            SmoothingMode::None => 3,
+            SmoothingMode::AntiAlias => 4,
+            SmoothingMode::AntiAlias8x8 => 5,
+        };
+
+        unsafe { GdipSetSmoothingMode(self.handle, mode); }
+    }
+
+    /// Sets how the images are scaled
+    pub fn set_interpolation_mode(&self, mode: InterpolationMode) {
+        let mode = match mode {
+            InterpolationMode::Bilinear => 3,
+            InterpolationMode::Bicubic => 4,
+            InterpolationMode::NearestNeighbor => 5,
+            InterpolationMode::HighQualityBilinear => 6,
+            InterpolationMode::HighQualityBicubic => 7,
+        };
+
+        unsafe { GdipSetInterpolationMode(self.handle, mode); }
+    }
+
+    /// Fills the whole device context with a color
+    pub fn clear(&self, color: [u8; 4]) {
+        unsafe { GdipGraphicsClear(self.handle, argb(color)); }
+    }
+
+    pub fn draw_line(&self, pen: &Pen, from: (f32, f32), to: (f32, f32)) {
+        unsafe { GdipDrawLine(self.handle, pen.handle, from.0, from.1, to.0, to.1); }
+    }
+
+    /// Draws a series of connected lines
+    pub fn draw_lines(&self, pen: &Pen, line_points: &[(f32, f32)]) {
+        let line_points = points(line_points);
+        unsafe { GdipDrawLines(self.handle, pen.handle, line_points.as_ptr(), line_points.len() as c_int); }
+    }
+
+    /// Draws a cubic bezier curve from `p1` to `p4`, with the control points `p2` and `p3`
+    pub fn draw_bezier(&self, pen: &Pen, p1: (f32, f32), p2: (f32, f32), p3: (f32, f32), p4: (f32, f32)) {
+        unsafe { GdipDrawBezier(self.handle, pen.handle, p1.0, p1.1, p2.0, p2.1, p3.0, p3.1, p4.0, p4.1); }
+    }
+
+    /// Draws a smooth curve that passes through the points. `tension` is usually between `0.0` (straight lines) and `1.0`.
+    pub fn draw_curve(&self, pen: &Pen, curve_points: &[(f32, f32)], tension: f32) {
+        let curve_points = points(curve_points);
+        unsafe { GdipDrawCurve2(self.handle, pen.handle, curve_points.as_ptr(), curve_points.len() as c_int, tension); }
+    }
+
+    /// Draws the outline of a rectangle. `rect` is `[x, y, width, height]`.
+    pub fn draw_rectangle(&self, pen: &Pen, rect: [f32; 4]) {
+        let [x, y, w, h] = rect;
+        unsafe { GdipDrawRectangle(self.handle, pen.handle, x, y, w, h); }
+    }
+
+    /// Fills a rectangle. `rect` is `[x, y, width, height]`.
+    pub fn fill_rectangle(&self, brush: &Brush, rect: [f32; 4]) {
+        let [x, y, w, h] = rect;
+        unsafe { GdipFillRectangle(self.handle, brush.handle, x, y, w, h); }
+    }
+
+    /// Draws the outline of the ellipse that fits in a rectangle. `rect` is `[x, y, width, height]`.
+    pub fn draw_ellipse(&self, pen: &Pen, rect: [f32; 4]) {
+        let [x, y, w, h] = rect;
+        unsafe { GdipDrawEllipse(self.handle, pen.handle, x, y, w, h); }
+    }
+
+    /// Fills the ellipse that fits in a rectangle. `rect` is `[x, y, width, height]`.
+    pub fn fill_ellipse(&self, brush: &Brush, rect: [f32; 4]) {
+        let [x, y, w, h] = rect;
+        unsafe { GdipFillEllipse(self.handle, brush.handle, x, y, w, h); }
+    }
+
+    /// Draws the outline of a polygon. The last point is joined to the first point.
+    pub fn draw_polygon(&self, pen: &Pen, polygon_points: &[(f32, f32)]) {
+        let polygon_points = points(polygon_points);
+        unsafe { GdipDrawPolygon(self.handle, pen.handle, polygon_points.as_ptr(), polygon_points.len() as c_int); }
+    }
+
+    pub fn fill_polygon(&self, brush: &Brush, polygon_points: &[(f32, f32)]) {
+        let polygon_points = points(polygon_points);
+        unsafe { GdipFillPolygon(self.handle, brush.handle, polygon_points.as_ptr(), polygon_points.len() as c_int, FILL_MODE_ALTERNATE); }
+    }
+
+    /// Draws an image scaled to a rectangle, with the interpolation mode of the graphics. `rect` is `[x, y, width, height]`.
+    pub fn draw_image(&self, image: &Image, rect: [f32; 4]) {
+        let [x, y, w, h] = rect;
+        unsafe { GdipDrawImageRect(self.handle, image.handle, x, y, w, h); }
+    }
+
+}
+
+impl Drop for Graphics {
+    fn drop(&mut self) {
+        unsafe { GdipDeleteGraphics(self.handle); }
+    }
+}
+
+
+/// Draws the lines and the outlines of the shapes
+pub struct Pen {
+    handle: *mut GpPen,
+}
+
+impl Pen {
+
+    /// Creates a solid pen. `width` is in pixels.
+    pub fn new(color: [u8; 4], width: f32) -> Result<Pen, NwgError> {
+        let mut handle = ptr::null_mut();
+        check(unsafe { GdipCreatePen1(argb(color), width, UNIT_WORLD, &mut handle) }, "Failed to create the pen")?;
+        Ok(Pen { handle })
+    }
+
+    pub fn set_dash_style(&self, style: DashStyle) {
+        let style = match style {
+            DashStyle::Solid => 0,
+            DashStyle::Dash => 1,
+            DashStyle::Dot => 2,
+            DashStyle::DashDot => 3,
+            DashStyle::DashDotDot => 4,
+        };
+
+        unsafe { GdipSetPenDashStyle(self.handle, style); }
+    }
+
+    /// Rounds the ends of the lines and of the dashes instead of cutting them square
+    pub fn set_round_caps(&self, round: bool) {
+        let (cap, dash_cap) = match round {
+            true => (2, 2),
+            false => (0, 0)
+        };
+
+        unsafe { GdipSetPenLineCap197819(self.handle, cap, cap, dash_cap); }
+    }
+
+}
+
+impl Drop for Pen {
+    fn drop(&mut self) {
+        unsafe { GdipDeletePen(self.handle); }
+    }
+}
+
+
+/// Fills the shapes
+pub struct Brush {
+    handle: *mut GpBrush,
+}
+
+impl Brush {
+
+    /// Creates a brush that fills with a single color
+    pub fn solid(color: [u8; 4]) -> Result<Brush, NwgError> {
+        let mut handle = ptr::null_mut();
+        check(unsafe { GdipCreateSolidFill(argb(color), &mut handle) }, "Failed to create the brush")?;
+        Ok(Brush { handle })
+    }
+
+    /// Creates a brush that fills with a gradient from `color1` at `point1` to `color2` at `point2`. The gradient is repeated after `point2`.
+    pub fn linear_gradient(point1: (f32, f32), point2: (f32, f32), color1: [u8; 4], color2: [u8; 4]) -> Result<Brush, NwgError> {
+        let p1 = GpPointF { x: point1.0, y: point1.1 };
+        let p2 = GpPointF { x: point2.0, y: point2.1 };
+
+        let mut handle = ptr::null_mut();
+        check(unsafe { GdipCreateLineBrush(&p1, &p2, argb(color1), argb(color2), WRAP_MODE_TILE, &mut handle) }, "Failed to create the brush")?;
+        Ok(Brush { handle })
+    }
+
+}
+
+impl Drop for Brush {
+    fn drop(&mut self) {
+        unsafe { GdipDeleteBrush(self.handle); }
+    }
+}
+
+
+/// An image drawn by `Graphics::draw_image`
+pub struct Image {
+    handle: *mut GpImage,
+}
+
+impl Image {
+
+    /// Loads an image from a file (bmp, png, jpeg, gif, tiff, ico)
+    pub fn from_file(path: &str) -> Result<Image, NwgError> {
+        let path_w = to_utf16(path);
+        let mut handle = ptr::null_mut();
+        check(unsafe { GdipLoadImageFromFile(path_w.as_ptr(), &mut handle) }, &format!("Failed to load {:?}", path))?;
+        Ok(Image { handle })
+    }
+
+    /// Copies a bitmap. The alpha channel of the bitmap is ignored by GDI+.
+    pub fn from_bitmap(bitmap: &crate::Bitmap) -> Result<Image, NwgError> {
+        let mut handle = ptr::null_mut();
+        check(unsafe { GdipCreateBitmapFromHBITMAP(bitmap.handle as HBITMAP, ptr::null_mut(), &mut handle) }, "Failed to copy the bitmap")?;
+        Ok(Image { handle })
+    }
+
+    /// Returns the size of the image in pixels
+    pub fn size(&self) -> (u32, u32) {
+        let (mut width, mut height) = (0, 0);
+        unsafe {
+            GdipGetImageWidth(self.handle, &mut width);
+            GdipGetImageHeight(self.handle, &mut height);
+        }
+
+        (width, height)
+    }
+
+}
+
+impl Drop for Image {
+    fn drop(&mut self) {
+        unsafe { GdipDisposeImage(self.handle); }
+    }
+}
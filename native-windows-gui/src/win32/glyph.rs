@@ -0,0 +1,176 @@
+/*!
+    Rasterize a single glyph of an icon font (ex: `Segoe MDL2 Assets` or `Segoe Fluent Icons`) into GDI bitmaps and icons.
+
+    The glyph is drawn with grayscale antialiasing. The coverage of each pixel is then used as the alpha channel
+    of a 32 bits premultiplied alpha bitmap filled with the requested color.
+*/
+use winapi::shared::windef::{HBITMAP, HDC, HFONT, RECT};
+use winapi::um::winnt::HANDLE;
+use winapi::ctypes::c_void;
+use crate::win32::base_helper::to_utf16;
+use crate::NwgError;
+use std::{mem, ptr, slice};
+
+
+/// Render `glyph` from the font `family` as a 32 bits premultiplied alpha bitmap of `size` x `size` physical pixels
+pub(crate) unsafe fn glyph_bitmap(family: &str, glyph: char, size: u32, color: [u8; 3]) -> Result<HANDLE, NwgError> {
+    use winapi::um::wingdi::{CreateCompatibleDC, DeleteDC, DeleteObject, SelectObject};
+
+    if size == 0 {
+        return Err(NwgError::resource_create("Glyph size must be greater than 0"));
+    }
+
+    let dc = CreateCompatibleDC(ptr::null_mut());
+    if dc.is_null() {
+        return Err(NwgError::resource_create("Failed to create a memory device context"));
+    }
+
+    let font = match create_font(family, size) {
+        Ok(font) => font,
+        Err(e) => {
+            DeleteDC(dc);
+            return Err(e);
+        }
+    };
+
+    let (bitmap, bits) = match create_dib(dc, size) {
+        Ok(dib) => dib,
+        Err(e) => {
+            DeleteObject(font as _);
+            DeleteDC(dc);
+            return Err(e);
+        }
+    };
+
+    let old_bitmap = SelectObject(dc, bitmap as _);
+    let old_font = SelectObject(dc, font as _);
+    let result = draw_glyph(dc, glyph, size);
+    SelectObject(dc, old_font);
+    SelectObject(dc, old_bitmap);
+    DeleteObject(font as _);
+    DeleteDC(dc);
+
+    match result {
+        Ok(()) => {
+            colorize(bits, size, color);
+            Ok(bitmap as HANDLE)
+        },
+        Err(e) => {
+            DeleteObject(bitmap as _);
+            Err(e)
+        }
+    }
+}
+
+/// Render `glyph` from the font `family` as an icon of `size` x `size` physical pixels
+pub(crate) unsafe fn glyph_icon(family: &str, glyph: char, size: u32, color: [u8; 3]) -> Result<HANDLE, NwgError> {
+    use winapi::um::wingdi::DeleteObject;
+    use winapi::um::winuser::{CreateIconIndirect, ICONINFO};
+
+    let color_bmp = glyph_bitmap(family, glyph, size, color)?;
+    let mut icon_info = ICONINFO {
+        fIcon: 1,
+        xHotspot: 0,
+        yHotspot: 0,
+        hbmMask: color_bmp as _,
+        hbmColor: color_bmp as _
+    };
+
+    let icon = CreateIconIndirect(&mut icon_info);
+    DeleteObject(color_bmp as _);
+
+    match icon.is_null() {
+        true => Err(NwgError::resource_create("Failed to create icon from glyph")),
+        false => Ok(icon as _)
+    }
+}
+
+/// Converts a size in logical units into the physical size used to rasterize a glyph
+pub(crate) fn physical_size(size: u32) -> u32 {
+    let (size, _) = unsafe { crate::win32::high_dpi::logical_to_physical(size as i32, size as i32) };
+    size.max(1) as u32
+}
+
+unsafe fn create_font(family: &str, size: u32) -> Result<HFONT, NwgError> {
+    use winapi::um::wingdi::{CreateFontW, FW_NORMAL, DEFAULT_CHARSET, OUT_TT_PRECIS, CLIP_DEFAULT_PRECIS, ANTIALIASED_QUALITY, DEFAULT_PITCH};
+
+    let family = to_utf16(family);
+    let font = CreateFontW(
+        -(size as i32), 0, 0, 0,
+        FW_NORMAL, 0, 0, 0,
+        DEFAULT_CHARSET, OUT_TT_PRECIS, CLIP_DEFAULT_PRECIS, ANTIALIASED_QUALITY, DEFAULT_PITCH,
+        family.as_ptr()
+    );
+
+    match font.is_null() {
+        true => Err(NwgError::resource_create("Failed to create the glyph font")),
+        false => Ok(font)
+    }
+}
+
+unsafe fn create_dib(dc: HDC, size: u32) -> Result<(HBITMAP, *mut u8), NwgError> {
+    use winapi::um::wingdi::{CreateDIBSection, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, RGBQUAD};
+
+    let mut info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: size as i32,
+            biHeight: -(size as i32),
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB,
+            biSizeImage: 0,
+            biXPelsPerMeter: 0,
+            biYPelsPerMeter: 0,
+            biClrUsed: 0,
+            biClrImportant: 0,
+        },
+        bmiColors: [RGBQUAD { rgbBlue: 0, rgbGreen: 0, rgbRed: 0, rgbReserved: 0 }],
+    };
+
+    let mut bits: *mut c_void = ptr::null_mut();
+    let bitmap = CreateDIBSection(dc, &mut info, DIB_RGB_COLORS, &mut bits, ptr::null_mut(), 0);
+    match bitmap.is_null() || bits.is_null() {
+        true => Err(NwgError::resource_create("Failed to allocate the glyph bitmap")),
+        false => Ok((bitmap, bits as *mut u8))
+    }
+}
+
+unsafe fn draw_glyph(dc: HDC, glyph: char, size: u32) -> Result<(), NwgError> {
+    use winapi::um::wingdi::{GetGlyphIndicesW, SetBkMode, SetTextColor, GdiFlush, RGB, TRANSPARENT, GDI_ERROR, GGI_MARK_NONEXISTING_GLYPHS};
+    use winapi::um::winuser::{DrawTextW, DT_CENTER, DT_VCENTER, DT_SINGLELINE, DT_NOPREFIX};
+
+    let mut text = [0u16; 2];
+    let text = glyph.encode_utf16(&mut text);
+
+    // GDI silently substitutes a fallback font if the family is not installed, so the glyph must be checked explicitly
+    let mut indices = [0u16; 2];
+    let found = GetGlyphIndicesW(dc, text.as_ptr(), text.len() as i32, indices.as_mut_ptr(), GGI_MARK_NONEXISTING_GLYPHS);
+    if found == GDI_ERROR || indices[..text.len()].iter().any(|&i| i == 0xFFFF) {
+        return Err(NwgError::resource_create(format!("The glyph {:?} does not exist in the font", glyph)));
+    }
+
+    // White on black, so that any color channel holds the coverage of the pixel
+    SetBkMode(dc, TRANSPARENT as _);
+    SetTextColor(dc, RGB(255, 255, 255));
+
+    let mut rect = RECT { left: 0, top: 0, right: size as i32, bottom: size as i32 };
+    DrawTextW(dc, text.as_ptr(), text.len() as i32, &mut rect, DT_CENTER | DT_VCENTER | DT_SINGLELINE | DT_NOPREFIX);
+    GdiFlush();
+
+    Ok(())
+}
+
+unsafe fn colorize(bits: *mut u8, size: u32, color: [u8; 3]) {
+    let [r, g, b] = color;
+    let premultiply = |c: u8, a: u8| ((c as u32 * a as u32 + 127) / 255) as u8;
+
+    let pixels = slice::from_raw_parts_mut(bits, (size * size * 4) as usize);
+    for px in pixels.chunks_exact_mut(4) {
+        let a = px[0].max(px[1]).max(px[2]);
+        px[0] = premultiply(b, a);
+        px[1] = premultiply(g, a);
+        px[2] = premultiply(r, a);
+        px[3] = a;
+    }
+}
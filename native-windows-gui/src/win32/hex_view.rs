@@ -0,0 +1,18 @@
+/*!
+    Notifications sent by the hex view control to its parent.
+*/
+use winapi::um::winuser::NMHDR;
+
+/// Notification code sent when the user changes the selection
+pub(crate) const HEX_VIEW_SELECTION_CHANGED: u32 = 1;
+
+/// Notification code sent when the user modifies a byte
+pub(crate) const HEX_VIEW_EDIT: u32 = 2;
+
+/// The WM_NOTIFY data sent by the hex view to its parent
+#[repr(C)]
+pub(crate) struct NMHEXVIEW {
+    pub hdr: NMHDR,
+    pub offset: u64,
+    pub length: u64,
+}
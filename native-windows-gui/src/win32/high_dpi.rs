@@ -1,3 +1,7 @@
+use winapi::shared::windef::HWND;
+use crate::controls::ControlHandle;
+use crate::NwgError;
+
 #[cfg(not(feature = "high-dpi"))]
 #[deprecated(note = "Specifying the default process DPI awareness via API is not recommended. Use the '<dpiAware>true</dpiAware>' setting in the application manifest. https://docs.microsoft.com/ru-ru/windows/win32/hidpi/setting-the-default-dpi-awareness-for-a-process")]
 pub unsafe fn set_dpi_awareness() {
@@ -60,3 +64,185 @@ pub unsafe fn dpi() -> i32 {
     let dpi = GetDeviceCaps(screen, LOGPIXELSX);
     dpi
 }
+
+/// Returns the current DPI of a window. `GetDpiForWindow` is loaded dynamically because it does not exist before Windows 10 (1607).
+/// On older systems, or if `handle` is null, the DPI of the screen is returned.
+pub unsafe fn window_dpi(handle: HWND) -> i32 {
+    use winapi::um::libloaderapi::{GetModuleHandleW, GetProcAddress};
+    use crate::win32::base_helper::to_utf16;
+    use std::mem;
+
+    type GetDpiForWindow = unsafe extern "system" fn(HWND) -> u32;
+
+    if handle.is_null() {
+        return dpi();
+    }
+
+    let name = to_utf16("user32.dll");
+    let module = GetModuleHandleW(name.as_ptr());
+    let f = match module.is_null() {
+        true => std::ptr::null_mut(),
+        false => GetProcAddress(module, "GetDpiForWindow\0".as_ptr() as *const i8)
+    };
+
+    if !f.is_null() {
+        let get_dpi_for_window: GetDpiForWindow = mem::transmute(f);
+        match get_dpi_for_window(handle) {
+            0 => {},
+            dpi => { return dpi as i32; }
+        }
+    }
+
+    dpi()
+}
+
+
+/**
+    A length in device independent pixels (DIPs). A DIP is 1/96th of an inch: 100 DIPs are 100 pixels at 100% scaling,
+    150 pixels at 150% scaling and 200 pixels at 200% scaling.
+
+    DIPs are converted using the current DPI of the window that holds the control, and are scaled even without the `high-dpi` feature.
+    They are accepted (through `Extent`, `Length` and `Margins`) by the `size` and `position` methods of the control builders,
+    and by the margins and the spacing of the layouts.
+
+    ```rust
+    use native_windows_gui as nwg;
+    use nwg::Dip;
+
+    fn build_button(button: &mut nwg::Button, window: &nwg::Window) {
+        nwg::Button::builder()
+            .size((Dip(120.0), Dip(32.0)))
+            .position((Dip(10.0), Dip(10.0)))
+            .parent(window)
+            .build(button);
+    }
+    ```
+*/
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct Dip(pub f32);
+
+impl Dip {
+
+    /// Converts the length to physical pixels at `dpi`
+    pub fn to_physical(self, dpi: u32) -> i32 {
+        use winapi::um::winuser::USER_DEFAULT_SCREEN_DPI;
+        (self.0 * (dpi as f32) / (USER_DEFAULT_SCREEN_DPI as f32)).round() as i32
+    }
+
+    /// Converts a length in physical pixels at `dpi` to DIPs
+    pub fn from_physical(pixels: i32, dpi: u32) -> Dip {
+        use winapi::um::winuser::USER_DEFAULT_SCREEN_DPI;
+        match dpi {
+            0 => Dip(pixels as f32),
+            dpi => Dip((pixels as f32) * (USER_DEFAULT_SCREEN_DPI as f32) / (dpi as f32))
+        }
+    }
+
+    /**
+        Converts the length to physical pixels at the current DPI of `window`.
+        Returns an error if `window` is not a window-like control (ex: a menu or a timer).
+    */
+    pub fn for_window<C: Into<ControlHandle>>(self, window: C) -> Result<i32, NwgError> {
+        match window.into().hwnd() {
+            Some(handle) => Ok(self.to_physical(unsafe { window_dpi(handle) } as u32)),
+            None => Err(NwgError::control_create("Dip conversion requires a window-like control"))
+        }
+    }
+
+    /// Converts the length to the units used by the functions of NWG (logical pixels with the `high-dpi` feature, physical pixels otherwise)
+    pub(crate) unsafe fn to_units(self, handle: HWND) -> i32 {
+        let pixels = self.to_physical(window_dpi(handle) as u32);
+        physical_to_logical(pixels, 0).0
+    }
+
+}
+
+/**
+    A size or a position accepted by the `size` and `position` methods of the control builders.
+    Either a pair of NWG units (`(i32, i32)`, as before) or a pair of `Dip`.
+*/
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Extent {
+    Units(i32, i32),
+    Dips(Dip, Dip),
+}
+
+impl Extent {
+
+    /// Returns the extent in NWG units, converting the DIPs at the DPI of `handle` (the parent of the control)
+    pub(crate) unsafe fn to_units(self, handle: HWND) -> (i32, i32) {
+        match self {
+            Extent::Units(x, y) => (x, y),
+            Extent::Dips(x, y) => (x.to_units(handle), y.to_units(handle)),
+        }
+    }
+
+}
+
+impl From<(i32, i32)> for Extent {
+    fn from(v: (i32, i32)) -> Extent { Extent::Units(v.0, v.1) }
+}
+
+impl From<(Dip, Dip)> for Extent {
+    fn from(v: (Dip, Dip)) -> Extent { Extent::Dips(v.0, v.1) }
+}
+
+/// A length accepted by the spacing and the gaps of the layouts. Either NWG units (`u32`, as before) or a `Dip`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Length {
+    Units(u32),
+    Dips(Dip),
+}
+
+impl Length {
+
+    /// Returns the length in NWG units, converting the DIPs at the DPI of `handle` (the parent of the layout)
+    pub(crate) unsafe fn to_units(self, handle: HWND) -> u32 {
+        match self {
+            Length::Units(v) => v,
+            Length::Dips(v) => v.to_units(handle).max(0) as u32,
+        }
+    }
+
+}
+
+impl From<u32> for Length {
+    fn from(v: u32) -> Length { Length::Units(v) }
+}
+
+impl From<Dip> for Length {
+    fn from(v: Dip) -> Length { Length::Dips(v) }
+}
+
+/// The top, right, bottom, left margins of a layout. Either NWG units (`[u32; 4]`, as before) or `Dip`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Margins {
+    Units([u32; 4]),
+    Dips([Dip; 4]),
+}
+
+impl Margins {
+
+    /// Returns the margins in NWG units, converting the DIPs at the DPI of `handle` (the parent of the layout)
+    pub(crate) unsafe fn to_units(self, handle: HWND) -> [u32; 4] {
+        match self {
+            Margins::Units(m) => m,
+            Margins::Dips(m) => {
+                let mut out = [0; 4];
+                for (o, v) in out.iter_mut().zip(m.iter()) {
+                    *o = v.to_units(handle).max(0) as u32;
+                }
+                out
+            }
+        }
+    }
+
+}
+
+impl From<[u32; 4]> for Margins {
+    fn from(v: [u32; 4]) -> Margins { Margins::Units(v) }
+}
+
+impl From<[Dip; 4]> for Margins {
+    fn from(v: [Dip; 4]) -> Margins { Margins::Dips(v) }
+}
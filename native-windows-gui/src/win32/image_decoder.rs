@@ -170,3 +170,677 @@ pub unsafe fn resize_bitmap(fact: &IWICImagingFactory, image: &ImageData, new_si
 
     Ok(ImageData { frame: scaler as *mut IWICBitmapSource })
 }
+
+/// Reads the content written by an encoder in a memory stream. The encoder leaves the stream at the end of the file.
+unsafe fn read_encoded_stream(stream: &IStream, data: &mut Vec<u8>) -> winapi::shared::winerror::HRESULT {
+    use winapi::um::objidlbase::{STREAM_SEEK_CUR, STREAM_SEEK_SET};
+    use winapi::shared::ntdef::{LARGE_INTEGER, ULARGE_INTEGER};
+    use std::mem;
+
+    let zero: LARGE_INTEGER = mem::zeroed();
+    let mut size: ULARGE_INTEGER = mem::zeroed();
+    stream.Seek(zero, STREAM_SEEK_CUR, &mut size);
+    stream.Seek(zero, STREAM_SEEK_SET, ptr::null_mut());
+
+    data.resize(*size.QuadPart() as usize, 0u8);
+    let mut read = 0;
+    stream.Read(data.as_mut_ptr() as *mut c_void, data.len() as u32, &mut read)
+}
+
+/// Encodes a bitmap into the content of a PNG file
+pub unsafe fn encode_png(bitmap: winapi::shared::windef::HBITMAP) -> Result<Vec<u8>, NwgError> {
+    use winapi::um::wincodec::{IWICBitmap, IWICBitmapEncoder, IWICBitmapFrameEncode, IWICBitmapSource, GUID_ContainerFormatPng,
+        WICBitmapIgnoreAlpha, WICBitmapEncoderNoCache};
+    use winapi::um::unknwnbase::IUnknown;
+
+    let factory = create_image_factory()?;
+    let stream = SHCreateMemStream(ptr::null(), 0);
+    if stream.is_null() {
+        (&*factory).Release();
+        return Err(NwgError::resource_create("Failed to create memory stream, allocation failure"));
+    }
+
+    let mut source: *mut IWICBitmap = ptr::null_mut();
+    let mut encoder: *mut IWICBitmapEncoder = ptr::null_mut();
+    let mut frame: *mut IWICBitmapFrameEncode = ptr::null_mut();
+    let mut options = ptr::null_mut();
+
+    // GDI bitmaps do not have a meaningful alpha channel
+    let mut hr = (&*factory).CreateBitmapFromHBITMAP(bitmap, ptr::null_mut(), WICBitmapIgnoreAlpha, &mut source);
+    if hr == S_OK { hr = (&*factory).CreateEncoder(&GUID_ContainerFormatPng, ptr::null(), &mut encoder); }
+    if hr == S_OK { hr = (&*encoder).Initialize(stream, WICBitmapEncoderNoCache); }
+    if hr == S_OK { hr = (&*encoder).CreateNewFrame(&mut frame, &mut options); }
+    if hr == S_OK { hr = (&*frame).Initialize(options); }
+    if hr == S_OK { hr = (&*frame).WriteSource(source as *const IWICBitmapSource, ptr::null()); }
+    if hr == S_OK { hr = (&*frame).Commit(); }
+    if hr == S_OK { hr = (&*encoder).Commit(); }
+
+    let mut data = Vec::new();
+    if hr == S_OK {
+        hr = read_encoded_stream(&*stream, &mut data);
+    }
+
+    let objects = [options as *mut IUnknown, frame as _, encoder as _, source as _, stream as _, factory as _];
+    for &object in objects.iter() {
+        if !object.is_null() {
+            (&*object).Release();
+        }
+    }
+
+    match hr {
+        S_OK => Ok(data),
+        e => Err(NwgError::image_decoder(e, "Could not encode the bitmap"))
+    }
+}
+
+/// An animation decoded from a multi-frame image. Each frame is a fully composed 32 bits premultiplied alpha
+/// bitmap with the size of the animation, paired with its delay in milliseconds.
+#[cfg(feature = "animated-image")]
+pub struct Animation {
+    pub size: (u32, u32),
+    pub frames: Vec<(winapi::shared::windef::HBITMAP, u32)>,
+
+    /// Number of times the animation is played. `0` loops forever.
+    pub loop_count: u32,
+}
+
+/// Delay used by browsers for the frames that do not specify one
+#[cfg(feature = "animated-image")]
+const DEFAULT_FRAME_DELAY: u32 = 100;
+
+#[cfg(feature = "animated-image")]
+pub unsafe fn animation_from_file(path: &str) -> Result<Animation, NwgError> {
+    let factory = create_image_factory()?;
+    let decoder = create_decoder_from_file(&*factory, path);
+    (&*factory).Release();
+
+    let decoder = decoder?;
+    let animation = decode_animation(&*decoder);
+    (&*decoder).Release();
+
+    animation
+}
+
+#[cfg(feature = "animated-image")]
+pub unsafe fn animation_from_memory(data: &[u8]) -> Result<Animation, NwgError> {
+    let factory = create_image_factory()?;
+    let decoder = create_decoder_from_stream(&*factory, data);
+    (&*factory).Release();
+
+    let decoder = decoder?;
+    let animation = decode_animation(&*decoder);
+    (&*decoder).Release();
+
+    animation
+}
+
+/**
+    Decodes and composes all the frames of an image. The frames position, delay and disposal are read
+    from the GIF metadata. Other formats are composed as full frames with the default delay.
+*/
+#[cfg(feature = "animated-image")]
+pub unsafe fn decode_animation(decoder: &IWICBitmapDecoder) -> Result<Animation, NwgError> {
+    use winapi::um::wincodec::{IWICBitmapFrameDecode, IWICBitmapSource, IWICMetadataQueryReader, WICConvertBitmapSource, GUID_WICPixelFormat32bppPBGRA};
+    use winapi::um::wingdi::DeleteObject;
+
+    let mut frame_count = 0;
+    decoder.GetFrameCount(&mut frame_count);
+    if frame_count == 0 {
+        return Err(NwgError::resource_create("The image does not have any frame"));
+    }
+
+    let mut reader: *mut IWICMetadataQueryReader = ptr::null_mut();
+    decoder.GetMetadataQueryReader(&mut reader);
+
+    let screen_size = match reader.is_null() {
+        true => None,
+        false => match (metadata_u16(&*reader, "/logscrdesc/Width"), metadata_u16(&*reader, "/logscrdesc/Height")) {
+            (Some(w), Some(h)) if w > 0 && h > 0 => Some((w as u32, h as u32)),
+            _ => None
+        }
+    };
+
+    // A GIF without the NETSCAPE2.0 extension is played once
+    let loop_count = match reader.is_null() {
+        true => 1,
+        false => metadata_loop_count(&*reader).unwrap_or(1)
+    };
+
+    if !reader.is_null() {
+        (&*reader).Release();
+    }
+
+    let mut animation = Animation { size: (0, 0), frames: Vec::with_capacity(frame_count as usize), loop_count };
+    let mut canvas: Vec<u8> = Vec::new();
+
+    let free_frames = |animation: &Animation| {
+        for &(bitmap, _) in animation.frames.iter() {
+            DeleteObject(bitmap as _);
+        }
+    };
+
+    for index in 0..frame_count {
+        let mut frame: *mut IWICBitmapFrameDecode = ptr::null_mut();
+        let hr = decoder.GetFrame(index, &mut frame);
+        if hr != S_OK {
+            free_frames(&animation);
+            return Err(NwgError::image_decoder(hr, "Could not read image frame"));
+        }
+
+        let (mut fw, mut fh) = (0, 0);
+        (&*frame).GetSize(&mut fw, &mut fh);
+
+        if index == 0 {
+            animation.size = screen_size.unwrap_or((fw, fh));
+            canvas = vec![0u8; (animation.size.0 * animation.size.1 * 4) as usize];
+        }
+
+        let (mut left, mut top, mut delay, mut disposal) = (0, 0, None, 0);
+        let mut frame_reader: *mut IWICMetadataQueryReader = ptr::null_mut();
+        if (&*frame).GetMetadataQueryReader(&mut frame_reader) == S_OK && !frame_reader.is_null() {
+            left = metadata_u16(&*frame_reader, "/imgdesc/Left").unwrap_or(0) as u32;
+            top = metadata_u16(&*frame_reader, "/imgdesc/Top").unwrap_or(0) as u32;
+            delay = metadata_u16(&*frame_reader, "/grctlext/Delay");
+            disposal = metadata_u8(&*frame_reader, "/grctlext/Disposal").unwrap_or(0);
+            (&*frame_reader).Release();
+        }
+
+        let mut converted: *mut IWICBitmapSource = ptr::null_mut();
+        let hr = WICConvertBitmapSource(&GUID_WICPixelFormat32bppPBGRA, frame as *mut IWICBitmapSource, &mut converted);
+        (&*frame).Release();
+
+        if hr != S_OK {
+            free_frames(&animation);
+            return Err(NwgError::image_decoder(hr, "Could not convert image pixels"));
+        }
+
+        let mut pixels = vec![0u8; (fw * fh * 4) as usize];
+        let hr = (&*converted).CopyPixels(ptr::null(), fw * 4, pixels.len() as u32, pixels.as_mut_ptr());
+        (&*converted).Release();
+
+        if hr != S_OK {
+            free_frames(&animation);
+            return Err(NwgError::image_decoder(hr, "Could not read image pixels"));
+        }
+
+        // Disposal 3: the canvas is restored to its previous state after the frame is displayed
+        let previous = match disposal == 3 {
+            true => Some(canvas.clone()),
+            false => None
+        };
+
+        let region = (left, top, fw, fh);
+        compose_frame(&mut canvas, animation.size, &pixels, region);
+
+        let bitmap = match dib_from_pixels(animation.size, &canvas) {
+            Ok(bitmap) => bitmap,
+            Err(e) => {
+                free_frames(&animation);
+                return Err(e);
+            }
+        };
+
+        // GIF delays are in hundredths of a second
+        let delay = match delay {
+            Some(d) if d > 1 => d as u32 * 10,
+            _ => DEFAULT_FRAME_DELAY
+        };
+
+        animation.frames.push((bitmap, delay));
+
+        match (disposal, previous) {
+            (2, _) => clear_region(&mut canvas, animation.size, region),
+            (3, Some(previous)) => { canvas = previous; },
+            _ => {}
+        }
+    }
+
+    Ok(animation)
+}
+
+/// Draws the premultiplied `pixels` of a frame over the canvas at `region`
+#[cfg(feature = "animated-image")]
+fn compose_frame(canvas: &mut [u8], size: (u32, u32), pixels: &[u8], region: (u32, u32, u32, u32)) {
+    let (left, top, fw, fh) = region;
+    let (width, height) = size;
+
+    for y in 0..fh {
+        let cy = top + y;
+        if cy >= height { break; }
+
+        for x in 0..fw {
+            let cx = left + x;
+            if cx >= width { break; }
+
+            let src = ((y * fw + x) * 4) as usize;
+            let dst = ((cy * width + cx) * 4) as usize;
+            let alpha = pixels[src + 3] as u32;
+
+            match alpha {
+                0 => {},
+                255 => canvas[dst..dst+4].copy_from_slice(&pixels[src..src+4]),
+                a => for c in 0..4 {
+                    let value = pixels[src + c] as u32 + (canvas[dst + c] as u32 * (255 - a) + 127) / 255;
+                    canvas[dst + c] = value.min(255) as u8;
+                }
+            }
+        }
+    }
+}
+
+/// Disposal 2: the area of the frame is cleared to transparent after the frame is displayed
+#[cfg(feature = "animated-image")]
+fn clear_region(canvas: &mut [u8], size: (u32, u32), region: (u32, u32, u32, u32)) {
+    let (left, top, fw, fh) = region;
+    let (width, height) = size;
+    let right = (left + fw).min(width);
+
+    for y in top..(top + fh).min(height) {
+        if left >= right { break; }
+        let start = ((y * width + left) * 4) as usize;
+        let end = ((y * width + right) * 4) as usize;
+        for value in canvas[start..end].iter_mut() {
+            *value = 0;
+        }
+    }
+}
+
+#[cfg(any(feature = "animated-image", feature = "bitmap-pixels"))]
+unsafe fn dib_from_pixels(size: (u32, u32), pixels: &[u8]) -> Result<winapi::shared::windef::HBITMAP, NwgError> {
+    use winapi::um::wingdi::{CreateDIBSection, BITMAPINFO, BITMAPINFOHEADER, DIB_RGB_COLORS, BI_RGB, RGBQUAD};
+    use winapi::shared::{ntdef::LONG, minwindef::DWORD};
+    use std::mem;
+
+    let (width, height) = size;
+    let header = BITMAPINFOHEADER {
+        biSize: mem::size_of::<BITMAPINFOHEADER>() as DWORD,
+        biWidth: width as LONG, biHeight: -(height as LONG),
+        biPlanes: 1, biBitCount: 32, biCompression: BI_RGB,
+        biSizeImage: 0,
+        biXPelsPerMeter: 0, biYPelsPerMeter: 0,
+        biClrUsed: 0, biClrImportant: 0
+    };
+
+    let quad = RGBQUAD { rgbBlue: 0, rgbGreen: 0, rgbRed: 0, rgbReserved: 0 };
+    let bitmap_info = BITMAPINFO { bmiHeader: header, bmiColors: [quad] };
+
+    let mut bits: *mut c_void = ptr::null_mut();
+    let bitmap = CreateDIBSection(ptr::null_mut(), &bitmap_info, DIB_RGB_COLORS, &mut bits, ptr::null_mut(), 0);
+    if bitmap.is_null() || bits.is_null() {
+        return Err(NwgError::resource_create("Could not create a bitmap"));
+    }
+
+    ptr::copy_nonoverlapping(pixels.as_ptr(), bits as *mut u8, pixels.len());
+
+    Ok(bitmap)
+}
+
+#[cfg(feature = "animated-image")]
+unsafe fn metadata_value<T, F>(reader: &winapi::um::wincodec::IWICMetadataQueryReader, name: &str, read: F) -> Option<T>
+    where F: FnOnce(&winapi::um::propidl::PROPVARIANT) -> Option<T>
+{
+    use winapi::um::propidl::PropVariantClear;
+    use crate::win32::base_helper::to_utf16;
+    use std::mem;
+
+    let name = to_utf16(name);
+    let mut value = mem::zeroed();
+    if reader.GetMetadataByName(name.as_ptr(), &mut value) != S_OK {
+        return None;
+    }
+
+    let result = read(&value);
+    PropVariantClear(&mut value);
+
+    result
+}
+
+#[cfg(feature = "animated-image")]
+unsafe fn metadata_u16(reader: &winapi::um::wincodec::IWICMetadataQueryReader, name: &str) -> Option<u16> {
+    use winapi::shared::wtypes::VT_UI2;
+    metadata_value(reader, name, |v| match v.vt == VT_UI2 as u16 {
+        true => Some(*v.data.uiVal()),
+        false => None
+    })
+}
+
+#[cfg(feature = "animated-image")]
+unsafe fn metadata_u8(reader: &winapi::um::wincodec::IWICMetadataQueryReader, name: &str) -> Option<u8> {
+    use winapi::shared::wtypes::VT_UI1;
+    metadata_value(reader, name, |v| match v.vt == VT_UI1 as u16 {
+        true => Some(*v.data.bVal()),
+        false => None
+    })
+}
+
+/// Reads the loop count of the NETSCAPE2.0 application extension of a GIF
+#[cfg(feature = "animated-image")]
+unsafe fn metadata_loop_count(reader: &winapi::um::wincodec::IWICMetadataQueryReader) -> Option<u32> {
+    use winapi::shared::wtypes::{VT_UI1, VT_VECTOR};
+    use std::slice;
+
+    let app = metadata_value(reader, "/appext/Application", |v| match v.vt == (VT_UI1 | VT_VECTOR) as u16 {
+        true => {
+            let data = v.data.caub();
+            Some(slice::from_raw_parts(data.pElems, data.cElems as usize).to_vec())
+        },
+        false => None
+    })?;
+
+    if app != b"NETSCAPE2.0" && app != b"ANIMEXTS1.0" {
+        return None;
+    }
+
+    // The data sub-block is [size, 1, count low, count high]. The count is the number of repetitions
+    // after the first play, and `0` repeats forever.
+    metadata_value(reader, "/appext/Data", |v| match v.vt == (VT_UI1 | VT_VECTOR) as u16 {
+        true => {
+            let data = v.data.caub();
+            let data = slice::from_raw_parts(data.pElems, data.cElems as usize);
+            match data.len() >= 4 && data[1] == 1 {
+                true => match u16::from_le_bytes([data[2], data[3]]) as u32 {
+                    0 => Some(0),
+                    repeat => Some(repeat + 1)
+                },
+                false => None
+            }
+        },
+        false => None
+    })
+}
+
+/**
+    Decodes `source` on a new worker thread and posts the result to `target` with `NWG_IMAGE_DECODED`.
+    If the message cannot be posted (ex: `target` was destroyed), the bitmap is freed by the worker.
+*/
+#[cfg(feature = "async-decode")]
+pub fn decode_in_background(source: crate::ImageDecodeSource, size: Option<(u32, u32)>, target: winapi::shared::windef::HWND, id: usize) {
+    use winapi::um::combaseapi::{CoInitializeEx, CoUninitialize};
+    use winapi::um::objbase::COINIT_MULTITHREADED;
+    use winapi::um::winuser::{PostMessageW, IMAGE_BITMAP};
+    use winapi::shared::windef::HWND;
+    use crate::win32::window_helper::NWG_IMAGE_DECODED;
+    use crate::win32::resources_helper as rh;
+    use crate::{ImageDecodeSource, DecodedImage};
+    use std::thread;
+
+    // Window handles can be used from any thread
+    let target = target as usize;
+
+    thread::spawn(move || unsafe {
+        CoInitializeEx(ptr::null_mut(), COINIT_MULTITHREADED);
+
+        let result = match &source {
+            ImageDecodeSource::File(path) => rh::build_image_decoder(path, size, true, IMAGE_BITMAP),
+            ImageDecodeSource::Bytes(data) => rh::build_image_decoder_from_memory(data, size),
+        };
+
+        CoUninitialize();
+
+        let image = Box::into_raw(Box::new(DecodedImage::new(id, result)));
+        if PostMessageW(target as HWND, NWG_IMAGE_DECODED, 0, image as _) == 0 {
+            drop(Box::from_raw(image));
+        }
+    });
+}
+
+/// Returns the size of a bitmap and its number of bits per pixel
+#[cfg(feature = "bitmap-pixels")]
+pub unsafe fn bitmap_info(bitmap: winapi::shared::windef::HBITMAP) -> Result<(u32, u32, u16), NwgError> {
+    use winapi::um::wingdi::{GetObjectW, BITMAP};
+    use std::mem;
+
+    let mut info: BITMAP = mem::zeroed();
+    let size = mem::size_of::<BITMAP>() as i32;
+    match GetObjectW(bitmap as _, size, &mut info as *mut BITMAP as _) == size {
+        true => Ok((info.bmWidth as u32, info.bmHeight.abs() as u32, info.bmBitsPixel)),
+        false => Err(NwgError::resource_create("The handle is not a bitmap"))
+    }
+}
+
+/// Builds the header used to read and write the pixels of a bitmap as 32 bits top-down rows
+#[cfg(feature = "bitmap-pixels")]
+fn rgba_bitmap_info(width: u32, height: u32) -> winapi::um::wingdi::BITMAPINFO {
+    use winapi::um::wingdi::{BITMAPINFO, BITMAPINFOHEADER, BI_RGB, RGBQUAD};
+    use std::mem;
+
+    BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: width as i32, biHeight: -(height as i32),
+            biPlanes: 1, biBitCount: 32, biCompression: BI_RGB,
+            biSizeImage: 0,
+            biXPelsPerMeter: 0, biYPelsPerMeter: 0,
+            biClrUsed: 0, biClrImportant: 0
+        },
+        bmiColors: [RGBQUAD { rgbBlue: 0, rgbGreen: 0, rgbRed: 0, rgbReserved: 0 }],
+    }
+}
+
+/**
+    Reads the pixels of a bitmap as RGBA rows with a straight alpha. Returns the size of the bitmap and the pixels.
+
+    Bitmaps with less than 32 bits per pixel, and 32 bits bitmaps where every alpha value is 0 (the usual GDI bitmaps),
+    are considered opaque. The other 32 bits bitmaps are considered premultiplied, like the bitmaps of the image decoder.
+*/
+#[cfg(feature = "bitmap-pixels")]
+pub unsafe fn read_rgba(bitmap: winapi::shared::windef::HBITMAP) -> Result<(u32, u32, Vec<u8>), NwgError> {
+    use winapi::um::wingdi::{GetDIBits, DIB_RGB_COLORS};
+    use winapi::um::winuser::{GetDC, ReleaseDC};
+
+    let (width, height, bits) = bitmap_info(bitmap)?;
+    let mut info = rgba_bitmap_info(width, height);
+    let mut pixels = vec![0u8; (width * height * 4) as usize];
+
+    let dc = GetDC(ptr::null_mut());
+    let lines = GetDIBits(dc, bitmap, 0, height, pixels.as_mut_ptr() as _, &mut info, DIB_RGB_COLORS);
+    ReleaseDC(ptr::null_mut(), dc);
+
+    if lines as u32 != height {
+        return Err(NwgError::resource_create("Could not read the bitmap pixels"));
+    }
+
+    let opaque = bits < 32 || pixels.chunks(4).all(|p| p[3] == 0);
+    for p in pixels.chunks_mut(4) {
+        p.swap(0, 2);
+
+        match (opaque, p[3]) {
+            (true, _) => { p[3] = 255; },
+            (false, 0) => { p[0] = 0; p[1] = 0; p[2] = 0; },
+            (false, 255) => {},
+            (false, a) => for c in 0..3 {
+                p[c] = ((p[c] as u32 * 255 + (a as u32 / 2)) / a as u32).min(255) as u8;
+            }
+        }
+    }
+
+    Ok((width, height, pixels))
+}
+
+/// Converts RGBA pixels with a straight alpha into the premultiplied BGRA pixels of a GDI bitmap
+#[cfg(feature = "bitmap-pixels")]
+fn premultiplied_bgra(rgba: &[u8]) -> Vec<u8> {
+    let mut pixels = rgba.to_vec();
+    for p in pixels.chunks_mut(4) {
+        p.swap(0, 2);
+
+        let a = p[3] as u32;
+        if a != 255 {
+            for c in 0..3 {
+                p[c] = ((p[c] as u32 * a + 127) / 255) as u8;
+            }
+        }
+    }
+
+    pixels
+}
+
+/// Creates a 32 bits premultiplied bitmap from RGBA pixels with a straight alpha
+#[cfg(feature = "bitmap-pixels")]
+pub unsafe fn bitmap_from_rgba(width: u32, height: u32, rgba: &[u8]) -> Result<winapi::shared::windef::HBITMAP, NwgError> {
+    if width == 0 || height == 0 || rgba.len() != (width * height * 4) as usize {
+        return Err(NwgError::resource_create("The pixel buffer does not match the bitmap size"));
+    }
+
+    dib_from_pixels((width, height), &premultiplied_bgra(rgba))
+}
+
+/// Replaces the pixels of a bitmap with RGBA pixels with a straight alpha. The buffer must match the size of the bitmap.
+#[cfg(feature = "bitmap-pixels")]
+pub unsafe fn write_rgba(bitmap: winapi::shared::windef::HBITMAP, rgba: &[u8]) -> Result<(), NwgError> {
+    use winapi::um::wingdi::{SetDIBits, DIB_RGB_COLORS};
+    use winapi::um::winuser::{GetDC, ReleaseDC};
+
+    let (width, height, _) = bitmap_info(bitmap)?;
+    if rgba.len() != (width * height * 4) as usize {
+        return Err(NwgError::resource_create("The pixel buffer does not match the bitmap size"));
+    }
+
+    let info = rgba_bitmap_info(width, height);
+    let pixels = premultiplied_bgra(rgba);
+
+    let dc = GetDC(ptr::null_mut());
+    let lines = SetDIBits(dc, bitmap, 0, height, pixels.as_ptr() as _, &info, DIB_RGB_COLORS);
+    ReleaseDC(ptr::null_mut(), dc);
+
+    match lines as u32 == height {
+        true => Ok(()),
+        false => Err(NwgError::resource_create("Could not write the bitmap pixels"))
+    }
+}
+
+/// Creates a WIC bitmap over RGBA pixels with a straight alpha
+#[cfg(feature = "bitmap-pixels")]
+unsafe fn wic_from_rgba(fact: &IWICImagingFactory, width: u32, height: u32, rgba: &[u8]) -> Result<*mut winapi::um::wincodec::IWICBitmap, NwgError> {
+    use winapi::um::wincodec::GUID_WICPixelFormat32bppRGBA;
+
+    let mut bitmap = ptr::null_mut();
+    let hr = fact.CreateBitmapFromMemory(width, height, &GUID_WICPixelFormat32bppRGBA, width * 4, rgba.len() as u32, rgba.as_ptr(), &mut bitmap);
+    match hr {
+        S_OK => Ok(bitmap),
+        e => Err(NwgError::image_decoder(e, "Could not create a bitmap from the pixels"))
+    }
+}
+
+/// Resizes RGBA pixels with a straight alpha. The pixels are premultiplied while they are filtered.
+#[cfg(feature = "bitmap-pixels")]
+pub unsafe fn resize_rgba(size: (u32, u32), rgba: &[u8], new_size: (u32, u32), mode: winapi::um::wincodec::WICBitmapInterpolationMode) -> Result<Vec<u8>, NwgError> {
+    use winapi::um::wincodec::{IWICBitmapScaler, IWICBitmapSource, WICConvertBitmapSource, GUID_WICPixelFormat32bppPRGBA, GUID_WICPixelFormat32bppRGBA};
+    use winapi::um::unknwnbase::IUnknown;
+
+    let (width, height) = new_size;
+    if width == 0 || height == 0 {
+        return Err(NwgError::resource_create("The new size of the bitmap must be greater than 0"));
+    }
+
+    let factory = create_image_factory()?;
+    let source = match wic_from_rgba(&*factory, size.0, size.1, rgba) {
+        Ok(source) => source,
+        Err(e) => {
+            (&*factory).Release();
+            return Err(e);
+        }
+    };
+
+    let mut premultiplied: *mut IWICBitmapSource = ptr::null_mut();
+    let mut scaler: *mut IWICBitmapScaler = ptr::null_mut();
+    let mut straight: *mut IWICBitmapSource = ptr::null_mut();
+    let mut pixels = vec![0u8; (width * height * 4) as usize];
+
+    let mut hr = WICConvertBitmapSource(&GUID_WICPixelFormat32bppPRGBA, source as *const IWICBitmapSource, &mut premultiplied);
+    if hr == S_OK { hr = (&*factory).CreateBitmapScaler(&mut scaler); }
+    if hr == S_OK { hr = (&*scaler).Initialize(premultiplied, width, height, mode); }
+    if hr == S_OK { hr = WICConvertBitmapSource(&GUID_WICPixelFormat32bppRGBA, scaler as *const IWICBitmapSource, &mut straight); }
+    if hr == S_OK { hr = (&*straight).CopyPixels(ptr::null(), width * 4, pixels.len() as u32, pixels.as_mut_ptr()); }
+
+    let objects = [straight as *mut IUnknown, scaler as _, premultiplied as _, source as _, factory as _];
+    for &object in objects.iter() {
+        if !object.is_null() {
+            (&*object).Release();
+        }
+    }
+
+    match hr {
+        S_OK => Ok(pixels),
+        e => Err(NwgError::image_decoder(e, "Could not resize the bitmap"))
+    }
+}
+
+/**
+    Encodes RGBA pixels with a straight alpha into the content of an image file.
+    `container` is the WIC container format, and `quality` (0.0 to 1.0) is the quality of lossy formats.
+*/
+#[cfg(feature = "bitmap-pixels")]
+pub unsafe fn encode_rgba(size: (u32, u32), rgba: &[u8], container: &winapi::shared::guiddef::GUID, quality: Option<f32>) -> Result<Vec<u8>, NwgError> {
+    use winapi::um::wincodec::{IWICBitmapEncoder, IWICBitmapFrameEncode, IWICBitmapSource, WICConvertBitmapSource, WICBitmapEncoderNoCache};
+    use winapi::um::ocidl::{IPropertyBag2, PROPBAG2};
+    use winapi::um::oaidl::VARIANT;
+    use winapi::shared::wtypes::VT_R4;
+    use winapi::um::unknwnbase::IUnknown;
+    use crate::win32::base_helper::to_utf16;
+    use std::mem;
+
+    let factory = create_image_factory()?;
+    let stream = SHCreateMemStream(ptr::null(), 0);
+    if stream.is_null() {
+        (&*factory).Release();
+        return Err(NwgError::resource_create("Failed to create memory stream, allocation failure"));
+    }
+
+    let source = match wic_from_rgba(&*factory, size.0, size.1, rgba) {
+        Ok(source) => source,
+        Err(e) => {
+            (&*stream).Release();
+            (&*factory).Release();
+            return Err(e);
+        }
+    };
+
+    let mut encoder: *mut IWICBitmapEncoder = ptr::null_mut();
+    let mut frame: *mut IWICBitmapFrameEncode = ptr::null_mut();
+    let mut options: *mut IPropertyBag2 = ptr::null_mut();
+    let mut converted: *mut IWICBitmapSource = ptr::null_mut();
+
+    let mut hr = (&*factory).CreateEncoder(container, ptr::null(), &mut encoder);
+    if hr == S_OK { hr = (&*encoder).Initialize(stream, WICBitmapEncoderNoCache); }
+    if hr == S_OK { hr = (&*encoder).CreateNewFrame(&mut frame, &mut options); }
+
+    if let (S_OK, Some(quality)) = (hr, quality) {
+        let mut name = to_utf16("ImageQuality");
+        let mut bag: PROPBAG2 = mem::zeroed();
+        bag.pstrName = name.as_mut_ptr();
+
+        let mut value: VARIANT = mem::zeroed();
+        value.n1.n2_mut().vt = VT_R4 as u16;
+        *value.n1.n2_mut().n3.fltVal_mut() = quality.max(0.0).min(1.0);
+
+        hr = (&*options).Write(1, &bag, &value);
+    }
+
+    // The encoder picks the pixel format closest to the requested one
+    let mut format = winapi::um::wincodec::GUID_WICPixelFormat32bppRGBA;
+    if hr == S_OK { hr = (&*frame).Initialize(options); }
+    if hr == S_OK { hr = (&*frame).SetSize(size.0, size.1); }
+    if hr == S_OK { hr = (&*frame).SetPixelFormat(&mut format); }
+    if hr == S_OK { hr = WICConvertBitmapSource(&format, source as *const IWICBitmapSource, &mut converted); }
+    if hr == S_OK { hr = (&*frame).WriteSource(converted, ptr::null()); }
+    if hr == S_OK { hr = (&*frame).Commit(); }
+    if hr == S_OK { hr = (&*encoder).Commit(); }
+
+    let mut data = Vec::new();
+    if hr == S_OK {
+        hr = read_encoded_stream(&*stream, &mut data);
+    }
+
+    let objects = [converted as *mut IUnknown, options as _, frame as _, encoder as _, source as _, stream as _, factory as _];
+    for &object in objects.iter() {
+        if !object.is_null() {
+            (&*object).Release();
+        }
+    }
+
+    match hr {
+        S_OK => Ok(data),
+        e => Err(NwgError::image_decoder(e, "Could not encode the bitmap"))
+    }
+}
@@ -0,0 +1,476 @@
+/*!
+    Jump lists: the menu shown when the user right clicks the taskbar button of the application.
+
+    A jump list holds user tasks (shortcuts to the application with a task id), custom categories of tasks, and the
+    recent or frequent documents tracked by the shell. Activating a task starts a new process of the application with
+    the task id on its command line. `JumpList::dispatch` turns that command line into a `OnJumpListTask` event, in the
+    first process or in a `SingleInstance` that received the forwarded command line.
+
+    Requires the `jump-list` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn build_jump_list(window: &nwg::Window) -> Result<(), nwg::NwgError> {
+        let mut list = nwg::JumpList::new(Some("MyCompany.MyApp"));
+        list.add_task(nwg::JumpTask::new("new-window", "New window"));
+        list.add_task(nwg::JumpTask::new("new-note", "New note").description("Creates an empty note"));
+        list.add_known_category(nwg::KnownCategory::Recent);
+        list.commit()?;
+
+        // The application was started from a task
+        nwg::JumpList::dispatch(&window.handle, &nwg::command_line());
+
+        Ok(())
+    }
+    ```
+*/
+use winapi::shared::minwindef::UINT;
+use winapi::shared::winerror::{S_OK, SUCCEEDED};
+use winapi::shared::wtypes::{PROPERTYKEY, VT_LPWSTR, VT_BOOL, VARIANT_TRUE};
+use winapi::um::unknwnbase::IUnknown;
+use winapi::um::shobjidl_core::IShellLinkW;
+use winapi::um::propsys::IPropertyStore;
+use winapi::um::propidl::PROPVARIANT;
+use winapi::um::winnt::{HRESULT, LPCWSTR};
+use winapi::ctypes::{c_int, c_void};
+use winapi::Interface;
+use super::base_helper::{to_utf16, from_utf16};
+use super::command_line::{CommandLine, JUMP_TASK_OPTION};
+use super::window_helper as wh;
+use crate::{ControlHandle, NwgError};
+use std::ffi::OsString;
+use std::path::Path;
+use std::{mem, ptr};
+
+/// COM classes and interfaces of the jump lists that are not defined in winapi
+#[allow(non_snake_case, non_upper_case_globals, dead_code)]
+mod com {
+    use winapi::shared::guiddef::REFIID;
+    use winapi::shared::minwindef::UINT;
+    use winapi::um::unknwnbase::{IUnknown, IUnknownVtbl};
+    use winapi::um::winnt::{HRESULT, LPCWSTR};
+    use winapi::ctypes::{c_int, c_void};
+    use winapi::{RIDL, DEFINE_GUID};
+
+    DEFINE_GUID!{CLSID_DestinationList,
+        0x77f10cf0, 0x3db5, 0x4966, 0xb5, 0x20, 0xb7, 0xc5, 0x4f, 0xd3, 0x5e, 0xd6}
+    DEFINE_GUID!{CLSID_EnumerableObjectCollection,
+        0x2d3468c1, 0x36a7, 0x43b6, 0xac, 0x24, 0xd3, 0xf0, 0x2f, 0xd9, 0x60, 0x7a}
+    DEFINE_GUID!{CLSID_ShellLink,
+        0x00021401, 0x0000, 0x0000, 0xc0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46}
+
+    RIDL!{#[uuid(0x92ca9dcd, 0x5622, 0x4bba, 0xa8, 0x05, 0x5e, 0x9f, 0x54, 0x1b, 0xd8, 0xc9)]
+    interface IObjectArray(IObjectArrayVtbl): IUnknown(IUnknownVtbl) {
+        fn GetCount(
+            pcObjects: *mut UINT,
+        ) -> HRESULT,
+        fn GetAt(
+            uiIndex: UINT,
+            riid: REFIID,
+            ppv: *mut *mut c_void,
+        ) -> HRESULT,
+    }}
+
+    RIDL!{#[uuid(0x5632b1a4, 0xe38a, 0x400a, 0x92, 0x8a, 0xd4, 0xcd, 0x63, 0x23, 0x02, 0x95)]
+    interface IObjectCollection(IObjectCollectionVtbl): IObjectArray(IObjectArrayVtbl) {
+        fn AddObject(
+            punk: *mut IUnknown,
+        ) -> HRESULT,
+        fn AddFromArray(
+            poaSource: *mut IObjectArray,
+        ) -> HRESULT,
+        fn RemoveObjectAt(
+            uiIndex: UINT,
+        ) -> HRESULT,
+        fn Clear() -> HRESULT,
+    }}
+
+    RIDL!{#[uuid(0x6332debf, 0x87b5, 0x4670, 0x90, 0xc0, 0x5e, 0x57, 0xb4, 0x08, 0xa4, 0x9e)]
+    interface ICustomDestinationList(ICustomDestinationListVtbl): IUnknown(IUnknownVtbl) {
+        fn SetAppID(
+            pszAppID: LPCWSTR,
+        ) -> HRESULT,
+        fn BeginList(
+            pcMinSlots: *mut UINT,
+            riid: REFIID,
+            ppv: *mut *mut c_void,
+        ) -> HRESULT,
+        fn AppendCategory(
+            pszCategory: LPCWSTR,
+            poa: *mut IObjectArray,
+        ) -> HRESULT,
+        fn AppendKnownCategory(
+            category: c_int,
+        ) -> HRESULT,
+        fn AddUserTasks(
+            poa: *mut IObjectArray,
+        ) -> HRESULT,
+        fn CommitList() -> HRESULT,
+        fn GetRemovedDestinations(
+            riid: REFIID,
+            ppv: *mut *mut c_void,
+        ) -> HRESULT,
+        fn DeleteList(
+            pszAppID: LPCWSTR,
+        ) -> HRESULT,
+        fn AbortList() -> HRESULT,
+    }}
+}
+
+use self::com::{CLSID_DestinationList, CLSID_EnumerableObjectCollection, CLSID_ShellLink, IObjectArray, IObjectCollection, ICustomDestinationList};
+
+const KDC_FREQUENT: c_int = 1;
+const KDC_RECENT: c_int = 2;
+
+const SHARD_PATHW: UINT = 3;
+
+extern "system" {
+    fn SHAddToRecentDocs(uFlags: UINT, pv: *const c_void);
+    fn SetCurrentProcessExplicitAppUserModelID(AppID: LPCWSTR) -> HRESULT;
+}
+
+
+/// A list of documents maintained by the shell. See `JumpList::add_known_category`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KnownCategory {
+    /// The documents recently opened with the application
+    Recent,
+    /// The documents frequently opened with the application
+    Frequent,
+}
+
+/**
+    A task of a jump list. When the user activates the task, the application is started with the task id on its command line.
+    See `JumpList::dispatch` and `CommandLine::jump_task`.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JumpTask {
+    /// The id sent with the `OnJumpListTask` event
+    pub id: String,
+    /// The text displayed in the jump list
+    pub title: String,
+    /// The tooltip of the task
+    pub description: Option<String>,
+    /// The icon of the task, as `"path,index"`. Defaults to the first icon of the executable.
+    pub icon: Option<String>,
+}
+
+impl JumpTask {
+
+    pub fn new(id: &str, title: &str) -> JumpTask {
+        JumpTask {
+            id: id.to_string(),
+            title: title.to_string(),
+            description: None,
+            icon: None,
+        }
+    }
+
+    /// Sets the tooltip of the task
+    pub fn description(mut self, description: &str) -> JumpTask {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Sets the icon of the task, as `"path,index"`
+    pub fn icon(mut self, icon: &str) -> JumpTask {
+        self.icon = Some(icon.to_string());
+        self
+    }
+
+}
+
+/**
+    The content of the jump list of the application. The jump list is only updated by `commit`.
+
+    `app_id` is the application user model id of the taskbar button. It must be the same as the id of the process
+    (see `JumpList::set_process_app_id`), or `None` to use the id computed by the system from the executable path.
+
+    Only the tasks in custom categories can be removed by the user. The removed tasks are not added back by `commit`.
+*/
+#[derive(Clone, Debug, Default)]
+pub struct JumpList {
+    app_id: Option<String>,
+    /// The user tasks. `None` is a separator.
+    tasks: Vec<Option<JumpTask>>,
+    categories: Vec<(String, Vec<JumpTask>)>,
+    known_categories: Vec<KnownCategory>,
+}
+
+impl JumpList {
+
+    pub fn new(app_id: Option<&str>) -> JumpList {
+        JumpList {
+            app_id: app_id.map(|id| id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Adds a task to the "Tasks" category
+    pub fn add_task(&mut self, task: JumpTask) {
+        self.tasks.push(Some(task));
+    }
+
+    /// Adds a separator after the last task of the "Tasks" category
+    pub fn add_separator(&mut self) {
+        self.tasks.push(None);
+    }
+
+    /// Adds a category of tasks. The categories are displayed above the "Tasks" category.
+    pub fn add_category(&mut self, title: &str, tasks: Vec<JumpTask>) {
+        self.categories.push((title.to_string(), tasks));
+    }
+
+    /**
+        Adds a category of documents maintained by the shell. The documents are added with `JumpList::add_recent_document`,
+        or by the shell when a document is opened with a file association. The application must be registered
+        as a handler of the file types (see `file_assoc::register`), otherwise the category stays empty.
+    */
+    pub fn add_known_category(&mut self, category: KnownCategory) {
+        if !self.known_categories.contains(&category) {
+            self.known_categories.push(category);
+        }
+    }
+
+    /// Removes the tasks and the categories. The jump list of the taskbar is not modified until `commit` is called.
+    pub fn clear(&mut self) {
+        self.tasks.clear();
+        self.categories.clear();
+        self.known_categories.clear();
+    }
+
+    /// Replaces the jump list of the taskbar button with the content of this jump list
+    pub fn commit(&self) -> Result<(), NwgError> {
+        unsafe {
+            let list: ComPtr<ICustomDestinationList> = create_instance(&CLSID_DestinationList)?;
+
+            if let Some(app_id) = self.app_id.as_ref() {
+                let app_id = to_utf16(app_id);
+                check(list.SetAppID(app_id.as_ptr()), "Failed to set the application id")?;
+            }
+
+            let mut min_slots: UINT = 0;
+            let mut removed: *mut IObjectArray = ptr::null_mut();
+            check(list.BeginList(&mut min_slots, &IObjectArray::uuidof(), &mut removed as *mut _ as *mut *mut c_void), "Failed to begin the list")?;
+            let removed = removed_tasks(ComPtr(removed));
+
+            match self.append_content(&list, &removed) {
+                Ok(()) => check(list.CommitList(), "Failed to commit the list"),
+                Err(e) => {
+                    list.AbortList();
+                    Err(e)
+                }
+            }
+        }
+    }
+
+    /// Removes the jump list of the application
+    pub fn delete(&self) -> Result<(), NwgError> {
+        unsafe {
+            let list: ComPtr<ICustomDestinationList> = create_instance(&CLSID_DestinationList)?;
+            let app_id = self.app_id.as_ref().map(|id| to_utf16(id));
+            let app_id = app_id.as_ref().map(|id| id.as_ptr()).unwrap_or(ptr::null());
+            check(list.DeleteList(app_id), "Failed to delete the list")
+        }
+    }
+
+    /**
+        Raises a `OnJumpListTask` event on `window` if `cmd` was created by a jump list task. Returns `true` if an event was raised.
+
+        Call it with `nwg::command_line()` once the window is created, and with the command lines received by `SingleInstance::on_forward`.
+    */
+    pub fn dispatch(window: &ControlHandle, cmd: &CommandLine) -> bool {
+        let hwnd = window.hwnd().expect("Window to be a window-like control");
+
+        match cmd.jump_task() {
+            Some(id) => {
+                wh::send_message(hwnd, wh::NWG_JUMP_TASK, &id as *const String as usize, 0);
+                true
+            },
+            None => false
+        }
+    }
+
+    /// Adds a document to the recent and frequent documents of the application
+    pub fn add_recent_document<P: AsRef<Path>>(path: P) {
+        use std::os::windows::ffi::OsStrExt;
+
+        let path: Vec<u16> = path.as_ref().as_os_str().encode_wide().chain(Some(0)).collect();
+        unsafe { SHAddToRecentDocs(SHARD_PATHW, path.as_ptr() as *const c_void); }
+    }
+
+    /**
+        Sets the application user model id of the process. Must be called before any window is created.
+        The windows of the process are grouped under one taskbar button with this id.
+    */
+    pub fn set_process_app_id(app_id: &str) -> Result<(), NwgError> {
+        let app_id = to_utf16(app_id);
+        check(unsafe { SetCurrentProcessExplicitAppUserModelID(app_id.as_ptr()) }, "Failed to set the application id")
+    }
+
+    unsafe fn append_content(&self, list: &ICustomDestinationList, removed: &[String]) -> Result<(), NwgError> {
+        let exe = current_exe()?;
+
+        for (title, tasks) in self.categories.iter() {
+            let collection: ComPtr<IObjectCollection> = create_instance(&CLSID_EnumerableObjectCollection)?;
+            for task in tasks.iter().filter(|t| !removed.contains(&t.id)) {
+                let link = create_link(&exe, Some(task))?;
+                collection.AddObject(link.0 as *mut IUnknown);
+            }
+
+            let title = to_utf16(title);
+            check(list.AppendCategory(title.as_ptr(), collection.0 as *mut IObjectArray), "Failed to add a category")?;
+        }
+
+        for category in self.known_categories.iter() {
+            let category = match category {
+                KnownCategory::Recent => KDC_RECENT,
+                KnownCategory::Frequent => KDC_FREQUENT,
+            };
+
+            check(list.AppendKnownCategory(category), "Failed to add a known category. Is the application registered for a file type?")?;
+        }
+
+        if !self.tasks.is_empty() {
+            let collection: ComPtr<IObjectCollection> = create_instance(&CLSID_EnumerableObjectCollection)?;
+            for task in self.tasks.iter() {
+                let link = create_link(&exe, task.as_ref())?;
+                collection.AddObject(link.0 as *mut IUnknown);
+            }
+
+            check(list.AddUserTasks(collection.0 as *mut IObjectArray), "Failed to add the tasks")?;
+        }
+
+        Ok(())
+    }
+
+}
+
+/// Releases a COM object when dropped
+struct ComPtr<T: Interface>(*mut T);
+
+impl<T: Interface> std::ops::Deref for ComPtr<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.0 }
+    }
+}
+
+impl<T: Interface> Drop for ComPtr<T> {
+    fn drop(&mut self) {
+        if !self.0.is_null() {
+            unsafe { (*(self.0 as *mut IUnknown)).Release(); }
+        }
+    }
+}
+
+unsafe fn create_instance<T: Interface>(clsid: &winapi::shared::guiddef::GUID) -> Result<ComPtr<T>, NwgError> {
+    use winapi::um::combaseapi::CoCreateInstance;
+    use winapi::shared::wtypesbase::CLSCTX_INPROC_SERVER;
+
+    let mut instance: *mut T = ptr::null_mut();
+    let hr = CoCreateInstance(clsid, ptr::null_mut(), CLSCTX_INPROC_SERVER, &T::uuidof(), &mut instance as *mut _ as *mut *mut c_void);
+    check(hr, "Failed to create a COM object. Was NWG initialized?")?;
+
+    Ok(ComPtr(instance))
+}
+
+/// Creates a link that starts the application with the task id. `None` creates a separator.
+unsafe fn create_link(exe: &str, task: Option<&JumpTask>) -> Result<ComPtr<IShellLinkW>, NwgError> {
+    use winapi::um::propkey::{PKEY_Title, PKEY_AppUserModel_IsDestListSeparator};
+
+    let link: ComPtr<IShellLinkW> = create_instance(&CLSID_ShellLink)?;
+
+    let mut store: *mut IPropertyStore = ptr::null_mut();
+    check(link.QueryInterface(&IPropertyStore::uuidof(), &mut store as *mut _ as *mut *mut c_void), "Failed to get the link properties")?;
+    let store = ComPtr(store);
+
+    match task {
+        Some(task) => {
+            let path = to_utf16(exe);
+            let args = to_utf16(&format!("/{}:\"{}\"", JUMP_TASK_OPTION, task.id));
+            link.SetPath(path.as_ptr());
+            link.SetArguments(args.as_ptr());
+
+            if let Some(description) = task.description.as_ref() {
+                let description = to_utf16(description);
+                link.SetDescription(description.as_ptr());
+            }
+
+            let (icon_path, icon_index) = match task.icon.as_ref().and_then(|i| i.rsplit_once(',')) {
+                Some((path, index)) => (path.to_string(), index.trim().parse().unwrap_or(0)),
+                None => (task.icon.clone().unwrap_or_else(|| exe.to_string()), 0)
+            };
+
+            let icon_path = to_utf16(&icon_path);
+            link.SetIconLocation(icon_path.as_ptr(), icon_index);
+
+            check(set_string_property(&store, &PKEY_Title, &task.title), "Failed to set the title of a task")?;
+        },
+        None => {
+            let mut value: PROPVARIANT = mem::zeroed();
+            value.vt = VT_BOOL as u16;
+            *value.data.boolVal_mut() = VARIANT_TRUE;
+            check(store.SetValue(&PKEY_AppUserModel_IsDestListSeparator, &value), "Failed to create a separator")?;
+        }
+    }
+
+    check(store.Commit(), "Failed to save the link properties")?;
+
+    Ok(link)
+}
+
+unsafe fn set_string_property(store: &IPropertyStore, key: &PROPERTYKEY, value: &str) -> HRESULT {
+    // The property store copies the value, so the string is not allocated with `CoTaskMemAlloc`
+    let mut text = to_utf16(value);
+    let mut value: PROPVARIANT = mem::zeroed();
+    value.vt = VT_LPWSTR as u16;
+    *value.data.pwszVal_mut() = text.as_mut_ptr();
+
+    store.SetValue(key, &value)
+}
+
+/// Returns the id of the tasks removed from the jump list by the user
+unsafe fn removed_tasks(removed: ComPtr<IObjectArray>) -> Vec<String> {
+    let mut tasks = Vec::new();
+    if removed.0.is_null() {
+        return tasks;
+    }
+
+    let mut count: UINT = 0;
+    removed.GetCount(&mut count);
+
+    for i in 0..count {
+        let mut link: *mut IShellLinkW = ptr::null_mut();
+        if removed.GetAt(i, &IShellLinkW::uuidof(), &mut link as *mut _ as *mut *mut c_void) != S_OK {
+            continue;
+        }
+
+        let link = ComPtr(link);
+        let mut args = [0u16; 1024];
+        if link.GetArguments(args.as_mut_ptr(), args.len() as c_int) == S_OK {
+            // `CommandLine` expects the program as the first argument
+            let cmd = CommandLine::parse(OsString::from(format!("nwg {}", from_utf16(&args))));
+            if let Some(id) = cmd.jump_task() {
+                tasks.push(id);
+            }
+        }
+    }
+
+    tasks
+}
+
+fn current_exe() -> Result<String, NwgError> {
+    std::env::current_exe()
+        .map(|p| p.to_string_lossy().into_owned())
+        .map_err(|e| NwgError::jump_list(format!("Failed to get the path of the executable: {}", e)))
+}
+
+fn check(hr: HRESULT, message: &str) -> Result<(), NwgError> {
+    match SUCCEEDED(hr) {
+        true => Ok(()),
+        false => Err(NwgError::jump_list(format!("{} (0x{:X})", message, hr)))
+    }
+}
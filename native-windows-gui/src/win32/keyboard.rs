@@ -0,0 +1,87 @@
+/*!
+    Information about the keyboard layout of the current thread.
+
+    The characters typed by the user are delivered by the `OnChar` event. They are already composed with the keyboard layout
+    of the user: AltGr combinations and dead keys (ex: `^` then `e` gives `ê`) produce a single `OnChar` with the final character,
+    and the characters outside of the basic multilingual plane (ex: emojis) are delivered as a single `char`.
+    Use `OnKeyPress` for the physical keys (shortcuts, arrows, ...) and `OnChar` for the text.
+
+    `OnDeadChar` is raised when a dead key is pressed, to display the pending accent, and `OnKeyboardLayoutChanged`
+    is raised on the top level windows when the user switches to another keyboard layout.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn print_layout() {
+        println!("Keyboard layout {} ({})", nwg::keyboard::layout_name(), nwg::keyboard::layout_locale());
+    }
+    ```
+*/
+use winapi::shared::windef::HWND;
+use super::base_helper::from_utf16;
+use std::cell::Cell;
+
+thread_local! {
+    /// A high surrogate received by a window, waiting for the low surrogate of the character
+    static PENDING_SURROGATE: Cell<Option<(HWND, u16)>> = Cell::new(None);
+}
+
+/// Returns the identifier of the keyboard layout of the current thread (ex: "00000409" for the US layout)
+pub fn layout_name() -> String {
+    use winapi::um::winuser::{GetKeyboardLayoutNameW, KL_NAMELENGTH};
+
+    let mut name = [0u16; KL_NAMELENGTH];
+    match unsafe { GetKeyboardLayoutNameW(name.as_mut_ptr()) } {
+        0 => String::new(),
+        _ => from_utf16(&name)
+    }
+}
+
+/// Returns the language of the keyboard layout of the current thread, as a language identifier (ex: 0x040C for French)
+pub fn layout_language() -> u16 {
+    use winapi::um::winuser::GetKeyboardLayout;
+    use winapi::shared::minwindef::LOWORD;
+
+    let layout = unsafe { GetKeyboardLayout(0) };
+    LOWORD(layout as usize as u32)
+}
+
+/// Returns the name of the language of the keyboard layout of the current thread (ex: "fr-FR")
+pub fn layout_locale() -> String {
+    use winapi::um::winnls::LCIDToLocaleName;
+    use winapi::um::winnt::LOCALE_NAME_MAX_LENGTH;
+
+    let mut name = [0u16; LOCALE_NAME_MAX_LENGTH];
+    let lcid = layout_language() as u32;
+    match unsafe { LCIDToLocaleName(lcid, name.as_mut_ptr(), name.len() as i32, 0) } {
+        0 => String::new(),
+        _ => from_utf16(&name)
+    }
+}
+
+/**
+    Composes the UTF-16 code units of the `WM_CHAR` messages into characters. The high surrogate of a character
+    is kept until the low surrogate is received. An unpaired surrogate gives the replacement character.
+*/
+pub(crate) fn compose_char(hwnd: HWND, code: u32) -> Option<char> {
+    if code > 0xFFFF {
+        return std::char::from_u32(code);
+    }
+
+    let unit = code as u16;
+    let pending = PENDING_SURROGATE.with(|p| p.take());
+
+    match unit {
+        0xD800..=0xDBFF => {
+            PENDING_SURROGATE.with(|p| p.set(Some((hwnd, unit))));
+            None
+        },
+        0xDC00..=0xDFFF => match pending {
+            Some((window, high)) if window == hwnd => std::char::decode_utf16([high, unit].iter().copied())
+                .next()
+                .and_then(|c| c.ok()),
+            _ => Some(std::char::REPLACEMENT_CHARACTER)
+        },
+        _ => std::char::from_u32(code)
+    }
+}
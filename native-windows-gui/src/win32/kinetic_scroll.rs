@@ -0,0 +1,605 @@
+/*!
+    Kinetic scrolling of the list controls on touch screens.
+*/
+use winapi::shared::windef::{HWND, RECT, POINT};
+use winapi::shared::minwindef::{WPARAM, LPARAM, LOWORD, HIWORD};
+use winapi::ctypes::c_int;
+use super::window::{bind_raw_event_handler_inner, unbind_raw_event_handler, RawEventHandler};
+use super::window_helper as wh;
+use super::high_dpi::logical_to_physical;
+use crate::controls::ControlHandle;
+use crate::NwgError;
+use std::{mem, rc::Rc, cell::{Cell, RefCell}, time::Instant};
+
+/// Id of the raw handlers bound to the scrolled controls. Ids smaller than 0xFFFF are reserved by NWG.
+const KINETIC_HANDLER_ID: usize = 0x4B1E;
+
+/// Id of the inertia timer of the scrolled controls
+const INERTIA_TIMER_ID: u32 = 0x4B1E;
+
+/// Interval between two inertia frames, in milliseconds
+const INERTIA_INTERVAL: u32 = 15;
+
+/// Distance in logical pixels a touch must move before it pans the control. Shorter touches are taps.
+const PAN_THRESHOLD: i32 = 8;
+
+/// Default fraction of the velocity kept after one millisecond of inertia
+const DEFAULT_DECELERATION: f64 = 0.997;
+
+/// Fraction of the velocity kept after one inertia frame once the content hit a boundary
+const BOUNDARY_DECELERATION: f64 = 0.5;
+
+/// Inertia stops under this speed, in physical pixels per millisecond
+const MIN_VELOCITY: f64 = 0.05;
+
+/// A finger that rests this long (in milliseconds) before being lifted does not throw the content
+const REST_DELAY: u128 = 60;
+
+/// Stiffness of the rubber band past the boundaries of the content. Smaller values are stiffer.
+const RUBBER_BAND: f64 = 0.55;
+
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Scrolled {
+    ListView,
+    TreeView,
+    ScrollPanel,
+}
+
+struct Touch {
+    pointer: u32,
+    start: (i32, i32),
+    last: (i32, i32),
+    time: Instant,
+    panning: bool,
+    /// Smoothed speed of the finger in physical pixels per millisecond
+    velocity: (f64, f64),
+    /// The touch stopped a running inertia, so it does not click if it is a tap
+    stopped_inertia: bool,
+}
+
+struct KineticState {
+    kind: Scrolled,
+    /// The window that owns the scrollbars. Different from the hooked window for the scroll panels.
+    scrolled: HWND,
+    deceleration: Rc<Cell<f64>>,
+    touch: Option<Touch>,
+    inertia: Option<(f64, f64)>,
+    last_frame: Instant,
+    /// Parts of the movement too small to be scrolled yet (less than a pixel, or less than a line)
+    remainder: (f64, f64),
+    /// Movement past the boundaries of the content, before the rubber band is applied
+    overscroll: (f64, f64),
+    feedback: bool,
+}
+
+struct Attached {
+    handle: HWND,
+    handler: RawEventHandler,
+    state: Rc<RefCell<KineticState>>,
+}
+
+/**
+    Adds touch kinetic scrolling to list views, tree views and scroll panels.
+
+    Once attached, a control follows the finger when it is panned with a touch screen and keeps scrolling with
+    a decreasing speed when the finger is lifted. Pulling the content past its boundaries stretches the window
+    like a rubber band, using the boundary feedback of Windows, and it springs back when the finger is lifted.
+    A short touch that does not move is sent to the control as a click. Mouse and pen input are not changed.
+
+    Tree views only pan vertically. The list views in report mode scroll by whole lines. The scroll panels are only
+    panned when the touch starts on the panel itself, not on one of its children.
+
+    Requires the `kinetic-scroll` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn enable_touch(kinetic: &nwg::KineticScroll, list: &nwg::ListView, tree: &nwg::TreeView) {
+        kinetic.attach(list).expect("Failed to hook the list view");
+        kinetic.attach(tree).expect("Failed to hook the tree view");
+    }
+    ```
+*/
+pub struct KineticScroll {
+    deceleration: Rc<Cell<f64>>,
+    attached: RefCell<Vec<Attached>>,
+}
+
+impl KineticScroll {
+
+    /**
+        Enables kinetic scrolling on a control. `control` can be a `ListView`, a `TreeView` or a `ScrollPanel`.
+
+        Returns an error if the control is of another type or if it is already attached.
+        Panics if `control` is not a window-like control.
+    */
+    pub fn attach<C: Into<ControlHandle>>(&self, control: C) -> Result<(), NwgError> {
+        use winapi::um::winuser::{WM_POINTERDOWN, WM_POINTERUPDATE, WM_POINTERUP, WM_POINTERCAPTURECHANGED, WM_TIMER};
+
+        let handle = control.into().hwnd().expect("Control to be a window-like control");
+        let (handle, scrolled, kind) = match resolve(handle) {
+            Some(r) => r,
+            None => { return Err(NwgError::control_create("KineticScroll only supports ListView, TreeView and ScrollPanel")); }
+        };
+
+        let state = Rc::new(RefCell::new(KineticState {
+            kind,
+            scrolled,
+            deceleration: self.deceleration.clone(),
+            touch: None,
+            inertia: None,
+            last_frame: Instant::now(),
+            remainder: (0.0, 0.0),
+            overscroll: (0.0, 0.0),
+            feedback: false,
+        }));
+
+        // Only the pointer messages and the inertia timer borrow the state, because scrolling sends messages to the control
+        let handler_state = state.clone();
+        let handler = bind_raw_event_handler_inner(&ControlHandle::Hwnd(handle), KINETIC_HANDLER_ID, move |hwnd, msg, w, l| {
+            match msg {
+                WM_POINTERDOWN => pointer_down(&handler_state, hwnd, w, l),
+                WM_POINTERUPDATE => pointer_update(&handler_state, w, l),
+                WM_POINTERUP => pointer_up(&handler_state, hwnd, w),
+                WM_POINTERCAPTURECHANGED => {
+                    let mut state = handler_state.borrow_mut();
+                    if state.touch.take().is_some() {
+                        end_feedback(&mut state);
+                    }
+                    None
+                },
+                WM_TIMER if w == INERTIA_TIMER_ID as WPARAM => {
+                    inertia_frame(&handler_state, hwnd);
+                    Some(0)
+                },
+                _ => None
+            }
+        })?;
+
+        self.attached.borrow_mut().push(Attached { handle, handler, state });
+
+        Ok(())
+    }
+
+    /**
+        Disables kinetic scrolling on a control. Stops the inertia if the control is still moving.
+    */
+    pub fn detach<C: Into<ControlHandle>>(&self, control: C) {
+        let handle = match control.into().hwnd().and_then(resolve) {
+            Some((handle, _, _)) => handle,
+            None => { return; }
+        };
+
+        let mut attached = self.attached.borrow_mut();
+        if let Some(index) = attached.iter().position(|a| a.handle == handle) {
+            release(attached.remove(index));
+        }
+    }
+
+    /// Returns the fraction of the scrolling speed kept after one millisecond of inertia
+    pub fn deceleration(&self) -> f64 {
+        self.deceleration.get()
+    }
+
+    /**
+        Sets the fraction of the scrolling speed kept after one millisecond of inertia.
+        Values closer to `1.0` make the content slide further. `0.0` disables the inertia.
+        The default value is `0.997`.
+    */
+    pub fn set_deceleration(&self, deceleration: f64) {
+        self.deceleration.set(deceleration.clamp(0.0, 0.9999));
+    }
+
+}
+
+impl Default for KineticScroll {
+    fn default() -> KineticScroll {
+        KineticScroll {
+            deceleration: Rc::new(Cell::new(DEFAULT_DECELERATION)),
+            attached: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl Drop for KineticScroll {
+    fn drop(&mut self) {
+        for attached in self.attached.borrow_mut().drain(..) {
+            release(attached);
+        }
+    }
+}
+
+/// Unbinds the handler of an attached control and stops its inertia. The control may have been destroyed before the helper.
+fn release(attached: Attached) {
+    drop(unbind_raw_event_handler(&attached.handler));
+
+    if wh::window_valid(attached.handle) {
+        wh::kill_timer(attached.handle, INERTIA_TIMER_ID);
+        end_feedback(&mut attached.state.borrow_mut());
+    }
+}
+
+/// Returns the window that receives the touch input, the window that scrolls and the type of a supported control
+fn resolve(handle: HWND) -> Option<(HWND, HWND, Scrolled)> {
+    use winapi::um::winuser::{GetParent, GetWindow, GW_CHILD, GWL_STYLE, WS_VSCROLL};
+    use winapi::um::commctrl::{WC_LISTVIEW, WC_TREEVIEW};
+
+    let class_name = unsafe { wh::get_window_class_name(handle) };
+    match class_name.as_str() {
+        WC_LISTVIEW => Some((handle, handle, Scrolled::ListView)),
+        WC_TREEVIEW => Some((handle, handle, Scrolled::TreeView)),
+        "NWG_SCROLL_PANEL" => unsafe {
+            // The panel owns the scrollbars and its content window covers its client area
+            match wh::get_window_long(handle, GWL_STYLE) as u32 & WS_VSCROLL == 0 {
+                true => Some((handle, GetParent(handle), Scrolled::ScrollPanel)),
+                false => match GetWindow(handle, GW_CHILD) {
+                    content if content.is_null() => None,
+                    content => Some((content, handle, Scrolled::ScrollPanel))
+                }
+            }
+        },
+        _ => None
+    }
+}
+
+/// Converts a distance in logical pixels to physical pixels
+fn px(v: i32) -> i32 {
+    unsafe { logical_to_physical(v, 0).0 }
+}
+
+/// Returns the pointer id of a pointer message if the pointer is a finger
+fn touch_pointer(w: WPARAM) -> Option<u32> {
+    use winapi::um::winuser::{GetPointerType, PT_TOUCH};
+
+    let pointer = LOWORD(w as u32) as u32;
+    let mut pointer_type = 0;
+    match unsafe { GetPointerType(pointer, &mut pointer_type) != 0 && pointer_type == PT_TOUCH } {
+        true => Some(pointer),
+        false => None
+    }
+}
+
+/// Returns the position of a pointer message in screen coordinates (physical pixels)
+fn pointer_position(l: LPARAM) -> (i32, i32) {
+    (LOWORD(l as u32) as i16 as i32, HIWORD(l as u32) as i16 as i32)
+}
+
+fn pointer_down(state: &RefCell<KineticState>, hwnd: HWND, w: WPARAM, l: LPARAM) -> Option<isize> {
+    let pointer = touch_pointer(w)?;
+    let mut state = state.borrow_mut();
+
+    // A second finger is ignored, so that it does not steal the pan of the first one
+    if state.touch.is_some() {
+        return Some(0);
+    }
+
+    // Touching a moving list stops it
+    let stopped_inertia = state.inertia.take().is_some();
+    if stopped_inertia {
+        wh::kill_timer(hwnd, INERTIA_TIMER_ID);
+        end_feedback(&mut state);
+    }
+
+    let position = pointer_position(l);
+    state.touch = Some(Touch {
+        pointer,
+        start: position,
+        last: position,
+        time: Instant::now(),
+        panning: false,
+        velocity: (0.0, 0.0),
+        stopped_inertia,
+    });
+
+    Some(0)
+}
+
+fn pointer_update(state: &RefCell<KineticState>, w: WPARAM, l: LPARAM) -> Option<isize> {
+    let pointer = LOWORD(w as u32) as u32;
+    let position = pointer_position(l);
+    let mut state = state.borrow_mut();
+
+    let (dx, dy) = {
+        let touch = match state.touch.as_mut() {
+            Some(t) if t.pointer == pointer => t,
+            _ => { return None; }
+        };
+
+        if !touch.panning {
+            let threshold = px(PAN_THRESHOLD);
+            if (position.0 - touch.start.0).abs() < threshold && (position.1 - touch.start.1).abs() < threshold {
+                return Some(0);
+            }
+
+            touch.panning = true;
+        }
+
+        // Moving the finger down scrolls the content up
+        let (dx, dy) = ((touch.last.0 - position.0) as f64, (touch.last.1 - position.1) as f64);
+
+        let elapsed = (touch.time.elapsed().as_secs_f64() * 1000.0).max(1.0);
+        touch.velocity = (
+            touch.velocity.0 * 0.3 + (dx / elapsed) * 0.7,
+            touch.velocity.1 * 0.3 + (dy / elapsed) * 0.7,
+        );
+
+        touch.last = position;
+        touch.time = Instant::now();
+
+        (dx, dy)
+    };
+
+    pan(&mut state, dx, dy, false);
+
+    Some(0)
+}
+
+fn pointer_up(state: &RefCell<KineticState>, hwnd: HWND, w: WPARAM) -> Option<isize> {
+    let pointer = LOWORD(w as u32) as u32;
+    let mut state = state.borrow_mut();
+
+    let touch = match state.touch.take() {
+        Some(t) if t.pointer == pointer => t,
+        other => {
+            state.touch = other;
+            return None;
+        }
+    };
+
+    if !touch.panning {
+        drop(state);
+        if !touch.stopped_inertia {
+            click(hwnd, touch.last);
+        }
+        return Some(0);
+    }
+
+    // The content does not slide on an axis pulled past its boundary, it springs back
+    let resting = touch.time.elapsed().as_millis() > REST_DELAY;
+    let (mut vx, mut vy) = match resting {
+        true => (0.0, 0.0),
+        false => touch.velocity
+    };
+
+    if state.overscroll.0 != 0.0 { vx = 0.0; }
+    if state.overscroll.1 != 0.0 { vy = 0.0; }
+
+    end_feedback(&mut state);
+
+    if state.deceleration.get() > 0.0 && vx.abs().max(vy.abs()) >= MIN_VELOCITY {
+        state.inertia = Some((vx, vy));
+        state.last_frame = Instant::now();
+        wh::start_timer(hwnd, INERTIA_TIMER_ID, INERTIA_INTERVAL);
+    }
+
+    Some(0)
+}
+
+fn inertia_frame(state: &RefCell<KineticState>, hwnd: HWND) {
+    let mut state = state.borrow_mut();
+
+    let (vx, vy) = match state.inertia {
+        Some(v) => v,
+        None => {
+            wh::kill_timer(hwnd, INERTIA_TIMER_ID);
+            return;
+        }
+    };
+
+    let elapsed = (state.last_frame.elapsed().as_secs_f64() * 1000.0).max(1.0);
+    state.last_frame = Instant::now();
+
+    let (blocked_x, blocked_y) = pan(&mut state, vx * elapsed, vy * elapsed, true);
+
+    // The content bumps into the boundaries: it stops quickly, then springs back when the inertia ends
+    let decay = state.deceleration.get().powf(elapsed);
+    let vx = vx * if blocked_x { BOUNDARY_DECELERATION } else { decay };
+    let vy = vy * if blocked_y { BOUNDARY_DECELERATION } else { decay };
+
+    match vx.abs().max(vy.abs()) < MIN_VELOCITY {
+        true => {
+            state.inertia = None;
+            end_feedback(&mut state);
+            wh::kill_timer(hwnd, INERTIA_TIMER_ID);
+        },
+        false => {
+            state.inertia = Some((vx, vy));
+        }
+    }
+}
+
+/// Gives back the overscroll of an axis when the finger moves in the other direction.
+/// Returns the new overscroll and the part of the movement left to scroll the content.
+fn unwind(overscroll: f64, delta: f64) -> (f64, f64) {
+    if overscroll == 0.0 || overscroll * delta > 0.0 {
+        return (overscroll, delta);
+    }
+
+    match delta.abs() >= overscroll.abs() {
+        true => (0.0, delta + overscroll),
+        false => (overscroll + delta, 0.0)
+    }
+}
+
+/// Scrolls the content, or stretches it when it is at a boundary. Returns the axes that hit a boundary.
+fn pan(state: &mut KineticState, dx: f64, dy: f64, inertia: bool) -> (bool, bool) {
+    use winapi::um::winuser::{SB_HORZ, SB_VERT};
+
+    // Tree views cannot be scrolled by pixels horizontally
+    let dx = match state.kind {
+        Scrolled::TreeView => 0.0,
+        _ => dx
+    };
+
+    let (ox, dx) = unwind(state.overscroll.0, dx);
+    let (oy, dy) = unwind(state.overscroll.1, dy);
+
+    let blocked_x = dx != 0.0 && at_boundary(state.scrolled, SB_HORZ, dx);
+    let blocked_y = dy != 0.0 && at_boundary(state.scrolled, SB_VERT, dy);
+
+    scroll(state, if blocked_x { 0.0 } else { dx }, if blocked_y { 0.0 } else { dy });
+
+    state.overscroll = (
+        if blocked_x { ox + dx } else { ox },
+        if blocked_y { oy + dy } else { oy },
+    );
+
+    update_feedback(state, inertia);
+
+    (blocked_x, blocked_y)
+}
+
+/// Returns `true` if the content cannot be scrolled further in the direction of `delta`
+fn at_boundary(hwnd: HWND, bar: c_int, delta: f64) -> bool {
+    use winapi::um::winuser::{GetScrollInfo, SCROLLINFO, SIF_ALL};
+
+    unsafe {
+        let mut info: SCROLLINFO = mem::zeroed();
+        info.cbSize = mem::size_of::<SCROLLINFO>() as u32;
+        info.fMask = SIF_ALL;
+
+        // Controls hide their scrollbars when the content fits
+        if GetScrollInfo(hwnd, bar, &mut info) == 0 {
+            return true;
+        }
+
+        let max = info.nMax - info.nPage.max(1) as i32 + 1;
+        match delta < 0.0 {
+            true => info.nPos <= info.nMin,
+            false => info.nPos >= max
+        }
+    }
+}
+
+/// Scrolls the content by a distance in physical pixels. Keeps the parts of the distance that cannot be scrolled yet.
+fn scroll(state: &mut KineticState, dx: f64, dy: f64) {
+    use winapi::um::winuser::{SendMessageW, WM_VSCROLL, SB_LINEUP, SB_LINEDOWN};
+    use winapi::um::commctrl::{LVM_SCROLL, TVM_GETITEMHEIGHT};
+
+    let hwnd = state.scrolled;
+    let (rx, ry) = (state.remainder.0 + dx, state.remainder.1 + dy);
+
+    unsafe {
+        match state.kind {
+            Scrolled::ListView => {
+                // Report views scroll by whole lines and round the other distances
+                let line = list_line_height(hwnd);
+                let x = rx.trunc() as i32;
+                let y = (ry / line as f64).trunc() as i32 * line;
+                if x != 0 || y != 0 {
+                    SendMessageW(hwnd, LVM_SCROLL, x as isize as WPARAM, y as LPARAM);
+                }
+
+                state.remainder = (rx - x as f64, ry - y as f64);
+            },
+            Scrolled::TreeView => {
+                let line = (SendMessageW(hwnd, TVM_GETITEMHEIGHT, 0, 0) as i32).max(1);
+                let lines = (ry / line as f64).trunc() as i32;
+                let code = match lines < 0 {
+                    true => SB_LINEUP,
+                    false => SB_LINEDOWN
+                };
+
+                for _ in 0..lines.abs() {
+                    SendMessageW(hwnd, WM_VSCROLL, code as WPARAM, 0);
+                }
+
+                state.remainder = (0.0, ry - (lines * line) as f64);
+            },
+            Scrolled::ScrollPanel => {
+                let (x, y) = (rx.trunc() as i32, ry.trunc() as i32);
+                if x != 0 || y != 0 {
+                    SendMessageW(hwnd, wh::NWG_SCROLL_BY, x as isize as WPARAM, y as LPARAM);
+                }
+
+                state.remainder = (rx - x as f64, ry - y as f64);
+            }
+        }
+    }
+}
+
+/// Returns the height of a line of a list view in report mode, or `1` for the other views
+fn list_line_height(hwnd: HWND) -> i32 {
+    use winapi::um::winuser::SendMessageW;
+    use winapi::um::commctrl::{LVM_GETVIEW, LVM_GETITEMRECT, LV_VIEW_DETAILS, LVIR_BOUNDS};
+    use winapi::shared::minwindef::DWORD;
+
+    unsafe {
+        if SendMessageW(hwnd, LVM_GETVIEW, 0, 0) as DWORD != LV_VIEW_DETAILS {
+            return 1;
+        }
+
+        let mut rect: RECT = mem::zeroed();
+        rect.left = LVIR_BOUNDS;
+        match SendMessageW(hwnd, LVM_GETITEMRECT, 0, &mut rect as *mut RECT as LPARAM) {
+            0 => 1,
+            _ => (rect.bottom - rect.top).max(1)
+        }
+    }
+}
+
+/// Maps an overscroll to the distance the window is stretched. The stretch slows down as it grows and never reaches `dimension`.
+fn rubber_band(overscroll: f64, dimension: f64) -> f64 {
+    if dimension <= 0.0 {
+        return 0.0;
+    }
+
+    let stretch = (1.0 - 1.0 / (overscroll.abs() * RUBBER_BAND / dimension + 1.0)) * dimension;
+    stretch.copysign(overscroll)
+}
+
+/// The boundary feedback moves the top level window, like the other touch applications of Windows
+fn update_feedback(state: &mut KineticState, inertia: bool) {
+    use winapi::um::uxtheme::{BeginPanningFeedback, UpdatePanningFeedback};
+    use winapi::um::winuser::{GetAncestor, GetClientRect, GA_ROOT};
+
+    if !state.feedback && state.overscroll == (0.0, 0.0) {
+        return;
+    }
+
+    unsafe {
+        let root = GetAncestor(state.scrolled, GA_ROOT);
+
+        let mut client: RECT = mem::zeroed();
+        GetClientRect(state.scrolled, &mut client);
+
+        if !state.feedback {
+            BeginPanningFeedback(root);
+            state.feedback = true;
+        }
+
+        // The window moves in the direction of the finger, the opposite of the scrolling
+        let x = -rubber_band(state.overscroll.0, client.right as f64);
+        let y = -rubber_band(state.overscroll.1, client.bottom as f64);
+        UpdatePanningFeedback(root, x.round() as i32, y.round() as i32, inertia as i32);
+    }
+}
+
+/// Releases the boundary feedback. The window springs back to its position.
+fn end_feedback(state: &mut KineticState) {
+    use winapi::um::uxtheme::EndPanningFeedback;
+    use winapi::um::winuser::{GetAncestor, GA_ROOT};
+
+    state.overscroll = (0.0, 0.0);
+    state.remainder = (0.0, 0.0);
+
+    if state.feedback {
+        state.feedback = false;
+        unsafe { EndPanningFeedback(GetAncestor(state.scrolled, GA_ROOT), 1); }
+    }
+}
+
+/// Sends a tap to the control as a left click, because the pointer messages of the touch were not turned into mouse messages
+fn click(hwnd: HWND, position: (i32, i32)) {
+    use winapi::um::winuser::{SendMessageW, ScreenToClient, WM_LBUTTONDOWN, WM_LBUTTONUP, MK_LBUTTON};
+
+    unsafe {
+        let mut point = POINT { x: position.0, y: position.1 };
+        ScreenToClient(hwnd, &mut point);
+
+        let l = ((point.y as u16 as u32) << 16 | point.x as u16 as u32) as LPARAM;
+        SendMessageW(hwnd, WM_LBUTTONDOWN, MK_LBUTTON, l);
+        SendMessageW(hwnd, WM_LBUTTONUP, 0, l);
+    }
+}
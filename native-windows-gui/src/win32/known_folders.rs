@@ -0,0 +1,154 @@
+/*!
+    The paths of the known folders of the user (ex: Documents, Downloads, AppData) and of the folders of the application.
+
+    The known folders can be moved by the user or by a policy (ex: a Documents folder redirected to a network share),
+    and their names are translated. Always use these functions instead of building the paths from the environment variables.
+
+    Requires the `known-folders` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+    use nwg::known_folders;
+
+    fn settings_path() -> Result<std::path::PathBuf, nwg::NwgError> {
+        let folder = known_folders::app_config_dir("MyCompany\\MyApp", true)?;
+        Ok(folder.join("settings.ini"))
+    }
+    ```
+*/
+use winapi::shared::winerror::S_OK;
+use winapi::shared::guiddef::GUID;
+use winapi::um::shlobj::{SHGetKnownFolderPath, KF_FLAG_DEFAULT, KF_FLAG_CREATE};
+use winapi::um::combaseapi::CoTaskMemFree;
+use winapi::um::knownfolders::{FOLDERID_Documents, FOLDERID_Downloads, FOLDERID_Desktop, FOLDERID_Pictures, FOLDERID_Music, FOLDERID_Videos,
+    FOLDERID_Profile, FOLDERID_RoamingAppData, FOLDERID_LocalAppData, FOLDERID_LocalAppDataLow, FOLDERID_ProgramData};
+use super::base_helper::os_string_from_wide_ptr;
+use crate::NwgError;
+use std::path::{Path, PathBuf, Component};
+use std::{fs, ptr};
+
+
+/// A folder of the user or of the system. See `known_folders::path`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KnownFolder {
+    /// The documents of the user
+    Documents,
+    /// The files downloaded by the user
+    Downloads,
+    /// The desktop of the user
+    Desktop,
+    /// The pictures of the user
+    Pictures,
+    /// The music of the user
+    Music,
+    /// The videos of the user
+    Videos,
+    /// The folder of the user (ex: `C:\Users\name`)
+    Profile,
+    /// The data of the applications that follow the user on the computers of a domain (`AppData\Roaming`)
+    RoamingAppData,
+    /// The data of the applications that stays on the computer (`AppData\Local`), ex: the caches
+    LocalAppData,
+    /// Like `LocalAppData`, for the applications that run with a low integrity level (`AppData\LocalLow`)
+    LocalAppDataLow,
+    /// The data of the applications shared by all the users (`C:\ProgramData`)
+    ProgramData,
+}
+
+impl KnownFolder {
+
+    fn id(&self) -> &'static GUID {
+        match self {
+            KnownFolder::Documents => &FOLDERID_Documents,
+            KnownFolder::Downloads => &FOLDERID_Downloads,
+            KnownFolder::Desktop => &FOLDERID_Desktop,
+            KnownFolder::Pictures => &FOLDERID_Pictures,
+            KnownFolder::Music => &FOLDERID_Music,
+            KnownFolder::Videos => &FOLDERID_Videos,
+            KnownFolder::Profile => &FOLDERID_Profile,
+            KnownFolder::RoamingAppData => &FOLDERID_RoamingAppData,
+            KnownFolder::LocalAppData => &FOLDERID_LocalAppData,
+            KnownFolder::LocalAppDataLow => &FOLDERID_LocalAppDataLow,
+            KnownFolder::ProgramData => &FOLDERID_ProgramData,
+        }
+    }
+
+}
+
+/**
+    Returns the path of a known folder.
+    If `create` is true, the folder is created if it does not exist (ex: a Downloads folder deleted by the user).
+*/
+pub fn path(folder: KnownFolder, create: bool) -> Result<PathBuf, NwgError> {
+    let flags = match create {
+        true => KF_FLAG_CREATE,
+        false => KF_FLAG_DEFAULT
+    };
+
+    unsafe {
+        let mut raw_path = ptr::null_mut();
+        let result = SHGetKnownFolderPath(folder.id(), flags, ptr::null_mut(), &mut raw_path);
+
+        // The buffer must be freed even if the function fails
+        let path = match result {
+            S_OK => Ok(PathBuf::from(os_string_from_wide_ptr(raw_path, None))),
+            e => Err(NwgError::known_folder(format!("Failed to get the path of {:?} (error {:#X})", folder, e)))
+        };
+
+        CoTaskMemFree(raw_path as _);
+
+        path
+    }
+}
+
+/// Returns the path of the documents folder of the user
+pub fn documents() -> Result<PathBuf, NwgError> {
+    path(KnownFolder::Documents, false)
+}
+
+/// Returns the path of the downloads folder of the user
+pub fn downloads() -> Result<PathBuf, NwgError> {
+    path(KnownFolder::Downloads, false)
+}
+
+/// Returns the path of the desktop of the user
+pub fn desktop() -> Result<PathBuf, NwgError> {
+    path(KnownFolder::Desktop, false)
+}
+
+/// Returns the path of `AppData\Roaming`
+pub fn roaming_app_data() -> Result<PathBuf, NwgError> {
+    path(KnownFolder::RoamingAppData, false)
+}
+
+/// Returns the path of `AppData\Local`
+pub fn local_app_data() -> Result<PathBuf, NwgError> {
+    path(KnownFolder::LocalAppData, false)
+}
+
+/**
+    Returns the configuration folder of the application and creates it if it does not exist.
+
+    `app` is a relative path, usually `"Company\\Application"`. The folder is created in `AppData\Roaming` if `roaming` is true
+    (the settings that follow the user), or in `AppData\Local` otherwise (the data that is specific to the computer, ex: a cache).
+
+    Fails if `app` is empty, is an absolute path or contains `..`.
+*/
+pub fn app_config_dir(app: &str, roaming: bool) -> Result<PathBuf, NwgError> {
+    let app_path = Path::new(app);
+    let relative = app_path.components().all(|c| match c { Component::Normal(_) => true, _ => false });
+    if app.is_empty() || !relative {
+        return Err(NwgError::known_folder(format!("{:?} is not a valid application folder", app)));
+    }
+
+    let base = match roaming {
+        true => path(KnownFolder::RoamingAppData, true)?,
+        false => path(KnownFolder::LocalAppData, true)?
+    };
+
+    let folder = base.join(app_path);
+    match fs::create_dir_all(&folder) {
+        Ok(()) => Ok(folder),
+        Err(e) => Err(NwgError::known_folder(format!("Failed to create {:?}: {}", folder, e)))
+    }
+}
@@ -0,0 +1,424 @@
+/*!
+    Drag and drop between the controls of the application, without OLE.
+
+    `start_local_drag` drags any rust value (the payload) out of a control, optionally with an image that follows the cursor.
+    `register_local_drop_target` makes a control accept the payloads chosen by a callback. While a payload is dragged,
+    the targets that accept it receive `OnLocalDragStart` and `OnLocalDragEnd` (ex: to highlight themselves), the target under
+    the cursor receives `OnLocalDragEnter`, `OnLocalDragOver` and `OnLocalDragLeave`, and the target where the payload is
+    dropped receives `OnLocalDrop`. The handlers read the payload with `EventData::on_local_drag`.
+
+    The payload never leaves the application: use the `drag-drop` feature to exchange data with the other applications.
+
+    Requires the `local-drag` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    struct PaletteItem { kind: &'static str }
+
+    fn setup_canvas(canvas: &nwg::Frame) -> Result<(), nwg::NwgError> {
+        nwg::register_local_drop_target(&canvas.handle, |drag| drag.is::<PaletteItem>())?;
+
+        let handle = canvas.handle;
+        nwg::bind_event_handler(&canvas.handle, &canvas.handle, move |evt, data, control| {
+            if control != handle || evt != nwg::Event::OnLocalDrop { return; }
+
+            let drag = data.on_local_drag();
+            if let Some(item) = drag.payload::<PaletteItem>() {
+                println!("Create a {} at {:?}", item.kind, drag.position());
+            }
+        });
+
+        Ok(())
+    }
+
+    fn drag_from_palette(palette: &nwg::ListView, icon: &nwg::Bitmap) -> Result<(), nwg::NwgError> {
+        nwg::start_local_drag(&palette.handle, Box::new(PaletteItem { kind: "button" }), Some(icon))?;
+        Ok(())
+    }
+    ```
+*/
+use winapi::shared::minwindef::{WPARAM, LPARAM};
+use winapi::shared::windef::{HWND, POINT};
+use winapi::um::commctrl::HIMAGELIST;
+use winapi::um::winuser::{SendMessageW, WM_DESTROY};
+use super::window::{bind_raw_event_handler_inner, unbind_raw_event_handler, RawEventHandler};
+use super::window_helper::NWG_LOCAL_DRAG;
+use crate::{ControlHandle, Event, NwgError};
+use std::{any::Any, cell::{Cell, RefCell}, collections::HashMap, fmt, mem, ptr, rc::Rc};
+
+/// Id of the raw event handler that removes a local drop target when its window is destroyed
+const LOCAL_DROP_TARGET_HANDLER_ID: usize = 0xD0D1;
+
+const DRAG_START: WPARAM = 0;
+const DRAG_ENTER: WPARAM = 1;
+const DRAG_OVER: WPARAM = 2;
+const DRAG_LEAVE: WPARAM = 3;
+const DRAG_DROP: WPARAM = 4;
+const DRAG_END: WPARAM = 5;
+
+struct LocalDropTarget {
+    accepts: Rc<dyn Fn(&LocalDragData) -> bool>,
+    handler: RawEventHandler,
+}
+
+thread_local! {
+    static LOCAL_DROP_TARGETS: RefCell<HashMap<usize, LocalDropTarget>> = RefCell::new(HashMap::new());
+    static DRAGGING: Cell<bool> = Cell::new(false);
+}
+
+
+/**
+    The payload dragged by `start_local_drag`. Sent with the local drag events.
+*/
+#[derive(Clone)]
+pub struct LocalDragData {
+    payload: Rc<dyn Any>,
+    source: ControlHandle,
+    position: (i32, i32),
+}
+
+impl LocalDragData {
+
+    /// Returns the payload if it has the type `T`
+    pub fn payload<T: Any>(&self) -> Option<&T> {
+        self.payload.downcast_ref::<T>()
+    }
+
+    /// Returns `true` if the payload has the type `T`
+    pub fn is<T: Any>(&self) -> bool {
+        self.payload.is::<T>()
+    }
+
+    /// Returns the control that started the drag
+    pub fn source(&self) -> ControlHandle {
+        self.source
+    }
+
+    /// Returns the position of the cursor in the client area of the control that receives the event
+    pub fn position(&self) -> (i32, i32) {
+        self.position
+    }
+
+}
+
+impl fmt::Debug for LocalDragData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "LocalDragData {{ source: {:?}, position: {:?} }}", self.source, self.position)
+    }
+}
+
+
+/**
+    Makes a control accept the payloads dragged by `start_local_drag`. `accepts` is called when a drag starts and
+    returns `true` if the control accepts the payload. Calling the function again replaces the callback.
+
+    The control is removed from the targets when it is destroyed.
+*/
+pub fn register_local_drop_target<C, F>(control: C, accepts: F) -> Result<(), NwgError>
+    where C: Into<ControlHandle>,
+          F: Fn(&LocalDragData) -> bool + 'static
+{
+    let handle = control.into();
+    let hwnd = handle.hwnd().expect("Only window-like controls can be drop targets");
+    let accepts: Rc<dyn Fn(&LocalDragData) -> bool> = Rc::new(accepts);
+
+    let replaced = LOCAL_DROP_TARGETS.with(|targets| {
+        match targets.borrow_mut().get_mut(&(hwnd as usize)) {
+            Some(target) => { target.accepts = accepts.clone(); true },
+            None => false
+        }
+    });
+
+    if replaced {
+        return Ok(());
+    }
+
+    let handler = bind_raw_event_handler_inner(&handle, LOCAL_DROP_TARGET_HANDLER_ID, move |hwnd, msg, _w, _l| {
+        if msg == WM_DESTROY {
+            LOCAL_DROP_TARGETS.with(|targets| targets.borrow_mut().remove(&(hwnd as usize)));
+        }
+
+        None
+    })?;
+
+    LOCAL_DROP_TARGETS.with(|targets| targets.borrow_mut().insert(hwnd as usize, LocalDropTarget { accepts, handler }));
+
+    Ok(())
+}
+
+/// Stops a control from accepting the local drags. Does nothing if the control is not a local drop target.
+pub fn unregister_local_drop_target<C: Into<ControlHandle>>(control: C) {
+    let hwnd = control.into().hwnd().expect("Only window-like controls can be drop targets");
+    let target = LOCAL_DROP_TARGETS.with(|targets| targets.borrow_mut().remove(&(hwnd as usize)));
+
+    if let Some(target) = target {
+        drop(unbind_raw_event_handler(&target.handler));
+    }
+}
+
+/**
+    Starts dragging a payload out of a control. `image` follows the cursor during the drag, centered on the cursor.
+    The function returns once the payload is dropped, with the target that received `OnLocalDrop`, or `None` if the user
+    cancelled the drag (with the escape key, the right mouse button, or by dropping the payload where it is not accepted).
+
+    Must be called while the left mouse button is pressed, usually when the user starts to drag (ex: `OnListViewBeginDrag`).
+    The events of the application are dispatched during the drag.
+
+    Fails if a local drag is already running or if the drag image could not be created.
+*/
+pub fn start_local_drag<C: Into<ControlHandle>>(source: C, payload: Box<dyn Any>, image: Option<&crate::Bitmap>) -> Result<Option<ControlHandle>, NwgError> {
+    let source = source.into();
+    let source_hwnd = source.hwnd().expect("Only window-like controls can start a drag");
+
+    if DRAGGING.with(|dragging| dragging.replace(true)) {
+        return Err(NwgError::local_drag("A local drag is already running"));
+    }
+
+    let data = LocalDragData { payload: Rc::from(payload), source, position: (0, 0) };
+
+    // The callbacks are called outside of the borrow of the targets, because they can register other targets
+    let callbacks: Vec<(HWND, Rc<dyn Fn(&LocalDragData) -> bool>)> = LOCAL_DROP_TARGETS.with(|targets| {
+        targets.borrow().iter().map(|(&hwnd, t)| (hwnd as HWND, t.accepts.clone())).collect()
+    });
+
+    let targets = callbacks.into_iter()
+        .filter(|(_, accepts)| accepts(&data))
+        .map(|(hwnd, _)| hwnd)
+        .collect();
+
+    let mut drag = LocalDrag { data, targets, current: None, image: ptr::null_mut() };
+
+    let result = unsafe {
+        match image {
+            Some(image) => drag.begin_image(image),
+            None => Ok(())
+        }.map(|_| drag.run(source_hwnd))
+    };
+
+    DRAGGING.with(|dragging| dragging.set(false));
+
+    result
+}
+
+/// The state of a running local drag
+struct LocalDrag {
+    data: LocalDragData,
+    /// The targets that accept the payload
+    targets: Vec<HWND>,
+    /// The target under the cursor
+    current: Option<HWND>,
+    image: HIMAGELIST,
+}
+
+impl LocalDrag {
+
+    unsafe fn run(&mut self, source: HWND) -> Option<ControlHandle> {
+        use winapi::um::winuser::{GetMessageW, TranslateMessage, DispatchMessageW, PostQuitMessage, SetCapture, ReleaseCapture, GetCapture,
+            MSG, WM_MOUSEMOVE, WM_LBUTTONUP, WM_RBUTTONDOWN, WM_KEYDOWN, WM_CANCELMODE, VK_ESCAPE};
+
+        for &target in self.targets.clone().iter() {
+            self.send(target, DRAG_START);
+        }
+
+        SetCapture(source);
+        self.update();
+
+        let mut result = None;
+        let mut msg: MSG = mem::zeroed();
+        loop {
+            match GetMessageW(&mut msg, ptr::null_mut(), 0, 0) {
+                // The quit message is posted again for the events loop of the application
+                0 => { PostQuitMessage(msg.wParam as i32); break; },
+                -1 => { break; },
+                _ => {}
+            }
+
+            match msg.message {
+                WM_MOUSEMOVE => self.update(),
+                WM_LBUTTONUP => {
+                    result = self.drop_payload();
+                    break;
+                },
+                WM_KEYDOWN if msg.wParam == VK_ESCAPE as WPARAM => { break; },
+                WM_RBUTTONDOWN | WM_CANCELMODE => { break; },
+                _ => {
+                    TranslateMessage(&msg);
+                    DispatchMessageW(&msg);
+                }
+            }
+
+            // Another window took the mouse capture (ex: a message box)
+            if GetCapture() != source {
+                break;
+            }
+        }
+
+        ReleaseCapture();
+        self.end_image();
+
+        if let Some(current) = self.current.take() {
+            self.send(current, DRAG_LEAVE);
+        }
+
+        for &target in self.targets.clone().iter() {
+            self.send(target, DRAG_END);
+        }
+
+        result
+    }
+
+    /// Finds the target under the cursor and moves the drag image
+    unsafe fn update(&mut self) {
+        use winapi::um::winuser::{GetCursorPos, SetCursor, LoadCursorW, IDC_ARROW, IDC_NO};
+        use winapi::um::commctrl::ImageList_DragMove;
+
+        let mut point = POINT { x: 0, y: 0 };
+        GetCursorPos(&mut point);
+
+        if !self.image.is_null() {
+            ImageList_DragMove(point.x, point.y);
+        }
+
+        let target = self.target_at(point);
+        if target != self.current {
+            if let Some(current) = self.current {
+                self.send(current, DRAG_LEAVE);
+            }
+
+            if let Some(target) = target {
+                self.send(target, DRAG_ENTER);
+            }
+
+            self.current = target;
+        }
+
+        if let Some(target) = target {
+            self.send(target, DRAG_OVER);
+        }
+
+        let cursor = match target.is_some() {
+            true => IDC_ARROW,
+            false => IDC_NO
+        };
+
+        SetCursor(LoadCursorW(ptr::null_mut(), cursor));
+    }
+
+    unsafe fn drop_payload(&mut self) -> Option<ControlHandle> {
+        self.update();
+
+        match self.current.take() {
+            Some(target) => {
+                self.send(target, DRAG_DROP);
+                Some(ControlHandle::Hwnd(target))
+            },
+            None => None
+        }
+    }
+
+    /// Returns the target under a point in screen coordinates. The target can be a parent of the window under the point.
+    unsafe fn target_at(&self, point: POINT) -> Option<HWND> {
+        use winapi::um::winuser::{WindowFromPoint, GetAncestor, GA_PARENT};
+
+        let mut hwnd = WindowFromPoint(point);
+        while !hwnd.is_null() {
+            if self.targets.contains(&hwnd) {
+                return Some(hwnd);
+            }
+
+            hwnd = GetAncestor(hwnd, GA_PARENT);
+        }
+
+        None
+    }
+
+    /// Sends a local drag event to a target, with the position of the cursor in its client area
+    unsafe fn send(&mut self, target: HWND, event: WPARAM) {
+        use winapi::um::winuser::{GetCursorPos, ScreenToClient, IsWindow};
+        use winapi::um::commctrl::ImageList_DragShowNolock;
+
+        // The target may have been destroyed by a previous event
+        if IsWindow(target) == 0 {
+            self.targets.retain(|&t| t != target);
+            return;
+        }
+
+        let mut point = POINT { x: 0, y: 0 };
+        GetCursorPos(&mut point);
+        ScreenToClient(target, &mut point);
+        self.data.position = (point.x, point.y);
+
+        // The image is hidden while the target repaints itself
+        if !self.image.is_null() {
+            ImageList_DragShowNolock(0);
+        }
+
+        SendMessageW(target, NWG_LOCAL_DRAG, event, &self.data as *const LocalDragData as LPARAM);
+
+        if !self.image.is_null() {
+            ImageList_DragShowNolock(1);
+        }
+    }
+
+    unsafe fn begin_image(&mut self, image: &crate::Bitmap) -> Result<(), NwgError> {
+        use winapi::um::commctrl::{ImageList_Create, ImageList_Add, ImageList_Destroy, ImageList_BeginDrag, ImageList_DragEnter, ILC_COLOR32};
+        use winapi::um::wingdi::{GetObjectW, BITMAP};
+        use winapi::um::winuser::GetCursorPos;
+
+        let mut bitmap: BITMAP = mem::zeroed();
+        if GetObjectW(image.handle as _, mem::size_of::<BITMAP>() as _, &mut bitmap as *mut BITMAP as _) == 0 {
+            return Err(NwgError::local_drag("The drag image is not a bitmap"));
+        }
+
+        let (width, height) = (bitmap.bmWidth, bitmap.bmHeight);
+        let list = ImageList_Create(width, height, ILC_COLOR32, 1, 0);
+        if list.is_null() || ImageList_Add(list, image.handle as _, ptr::null_mut()) < 0 || ImageList_BeginDrag(list, 0, width / 2, height / 2) == 0 {
+            if !list.is_null() {
+                ImageList_Destroy(list);
+            }
+
+            return Err(NwgError::local_drag("Failed to create the drag image"));
+        }
+
+        let mut point = POINT { x: 0, y: 0 };
+        GetCursorPos(&mut point);
+        ImageList_DragEnter(ptr::null_mut(), point.x, point.y);
+
+        self.image = list;
+
+        Ok(())
+    }
+
+    unsafe fn end_image(&mut self) {
+        use winapi::um::commctrl::{ImageList_DragLeave, ImageList_EndDrag, ImageList_Destroy};
+
+        if self.image.is_null() {
+            return;
+        }
+
+        ImageList_DragLeave(ptr::null_mut());
+        ImageList_EndDrag();
+        ImageList_Destroy(self.image);
+        self.image = ptr::null_mut();
+    }
+
+}
+
+
+/// Returns the event of a `NWG_LOCAL_DRAG` message
+pub(crate) fn drag_event(w: WPARAM) -> Event {
+    match w {
+        DRAG_START => Event::OnLocalDragStart,
+        DRAG_ENTER => Event::OnLocalDragEnter,
+        DRAG_OVER => Event::OnLocalDragOver,
+        DRAG_LEAVE => Event::OnLocalDragLeave,
+        DRAG_DROP => Event::OnLocalDrop,
+        _ => Event::OnLocalDragEnd,
+    }
+}
+
+/// Returns the data of a `NWG_LOCAL_DRAG` message
+pub(crate) unsafe fn drag_data(l: LPARAM) -> LocalDragData {
+    (*(l as *const LocalDragData)).clone()
+}
@@ -0,0 +1,672 @@
+/*!
+    A small Markdown parser used by the markdown view control.
+
+    Supports ATX and setext headings, paragraphs, hard line breaks, ordered and unordered (nested) lists, block quotes,
+    fenced code blocks, thematic breaks, and the inline emphasis, strong emphasis, strikethrough, code spans, links,
+    autolinks, images and backslash escapes. HTML and reference links are displayed as text.
+*/
+use winapi::um::winuser::NMHDR;
+
+/// Notification code sent when the user clicks a link
+pub(crate) const MARKDOWN_LINK_CLICK: u32 = 1;
+
+/// The WM_NOTIFY data sent by the markdown view to its parent. `url` points to `url_len` UTF-8 bytes.
+#[repr(C)]
+pub(crate) struct NMMARKDOWNLINK {
+    pub hdr: NMHDR,
+    pub url: *const u8,
+    pub url_len: usize,
+}
+
+/// The style of a run of text
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct SpanStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub strike: bool,
+    pub code: bool,
+    pub link: Option<String>,
+}
+
+/// A run of text with the same style
+#[derive(Clone, Debug)]
+pub(crate) struct Span {
+    pub text: String,
+    pub style: SpanStyle,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum BlockKind {
+    /// A heading, from 1 to 6
+    Heading(u8),
+    Paragraph,
+    /// A list item. `marker` is empty for the paragraphs that follow the first one in an item.
+    ListItem { marker: String, depth: usize },
+    /// A fenced code block. The text is in a single code span.
+    Code,
+    Rule,
+    /// An image, displayed on its own line. `link` is set when the image is inside a link.
+    Image { src: String, alt: String, link: Option<String> },
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Block {
+    pub kind: BlockKind,
+    /// The number of block quotes around the block
+    pub quote: usize,
+    pub spans: Vec<Span>,
+}
+
+impl Block {
+
+    /// Returns the text of the block without the formatting
+    pub fn plain_text(&self) -> String {
+        self.spans.iter().map(|s| s.text.as_str()).collect()
+    }
+
+}
+
+/// Parses a markdown document into a list of blocks
+pub(crate) fn parse(text: &str) -> Vec<Block> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut blocks = Vec::new();
+    parse_lines(&lines, 0, &mut blocks);
+    blocks
+}
+
+/// Returns the anchor of a heading, used by the links that start with `#`. Ex: "Getting started" => "getting-started"
+pub(crate) fn anchor(text: &str) -> String {
+    text.trim()
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_alphanumeric() => Some(c.to_lowercase().next().unwrap_or(c)),
+            ' ' | '-' => Some('-'),
+            '_' => Some('_'),
+            _ => None
+        })
+        .collect()
+}
+
+/// A paragraph or a list item being read
+struct Pending {
+    kind: BlockKind,
+    text: String,
+}
+
+struct BlockParser<'a> {
+    out: &'a mut Vec<Block>,
+    quote: usize,
+    pending: Option<Pending>,
+    /// The indentation of the content of the open lists, from the outer list to the inner list
+    lists: Vec<usize>,
+    after_blank: bool,
+}
+
+impl<'a> BlockParser<'a> {
+
+    fn flush(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            push_inline_block(self.out, pending.kind, self.quote, &pending.text);
+        }
+    }
+
+    fn push(&mut self, kind: BlockKind, spans: Vec<Span>) {
+        self.out.push(Block { kind, quote: self.quote, spans });
+    }
+
+    /// Adds a line to the paragraph being read, or starts a new paragraph
+    fn add_line(&mut self, kind: BlockKind, line: &str) {
+        match self.pending.as_mut() {
+            Some(pending) => {
+                // Two trailing spaces or a trailing backslash is a hard line break
+                let hard_break = pending.text.ends_with("  ") || pending.text.ends_with('\\');
+                let len = pending.text.trim_end_matches(|c| c == ' ' || c == '\\').len();
+                pending.text.truncate(len);
+                pending.text.push(if hard_break { '\n' } else { ' ' });
+                pending.text.push_str(line.trim());
+            },
+            None => {
+                self.pending = Some(Pending { kind, text: line.trim().to_string() });
+            }
+        }
+    }
+
+    /// Returns the depth of a list item with the given indentation and closes the lists nested deeper
+    fn list_depth(&mut self, indent: usize, content_indent: usize) -> usize {
+        // The item is nested in the deepest item whose content is not more indented than the marker
+        let depth = self.lists.iter().rposition(|&c| indent >= c).map(|d| d + 1).unwrap_or(0);
+        self.lists.truncate(depth);
+        self.lists.push(content_indent);
+        depth
+    }
+
+}
+
+fn parse_lines(lines: &[&str], quote: usize, out: &mut Vec<Block>) {
+    let mut p = BlockParser { out, quote, pending: None, lists: Vec::new(), after_blank: false };
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let indent = indentation(line);
+        let content = line.trim_start();
+        i += 1;
+
+        if content.is_empty() {
+            p.flush();
+            p.after_blank = true;
+            continue;
+        }
+
+        if let Some((fence, fence_len)) = code_fence(content) {
+            p.flush();
+            let mut code = Vec::new();
+            while i < lines.len() {
+                let l = lines[i];
+                i += 1;
+
+                // The closing fence uses the same character and is at least as long as the opening fence
+                let t = l.trim();
+                if t.len() >= fence_len && t.chars().all(|c| c == fence) {
+                    break;
+                }
+
+                // Removes the indentation of the fence from the code
+                let skip = indentation(l).min(indent);
+                code.push(l.chars().skip(skip).collect::<String>());
+            }
+
+            let span = Span { text: code.join("\n"), style: SpanStyle { code: true, ..Default::default() } };
+            p.push(BlockKind::Code, vec![span]);
+            p.after_blank = false;
+            continue;
+        }
+
+        if content.starts_with('>') {
+            p.flush();
+            p.lists.clear();
+
+            let mut inner = vec![strip_quote(content)];
+            while i < lines.len() && lines[i].trim_start().starts_with('>') {
+                inner.push(strip_quote(lines[i].trim_start()));
+                i += 1;
+            }
+
+            parse_lines(&inner, quote + 1, p.out);
+            p.after_blank = false;
+            continue;
+        }
+
+        if let Some((level, text)) = atx_heading(content) {
+            p.flush();
+            p.lists.clear();
+            let spans = parse_inline(text);
+            p.push(BlockKind::Heading(level), spans);
+            p.after_blank = false;
+            continue;
+        }
+
+        // Setext heading: a paragraph underlined with `=` or `-`
+        let is_paragraph = p.pending.as_ref().map(|pending| pending.kind == BlockKind::Paragraph).unwrap_or(false);
+        if is_paragraph && indent < 4 {
+            let underline = content.trim_end();
+            let level = match underline.chars().next() {
+                Some('=') if underline.chars().all(|c| c == '=') => Some(1),
+                Some('-') if underline.chars().all(|c| c == '-') => Some(2),
+                _ => None
+            };
+
+            if let Some(level) = level {
+                let pending = p.pending.take().unwrap();
+                push_inline_block(p.out, BlockKind::Heading(level), quote, &pending.text);
+                continue;
+            }
+        }
+
+        if is_rule(content) {
+            p.flush();
+            p.lists.clear();
+            p.push(BlockKind::Rule, Vec::new());
+            p.after_blank = false;
+            continue;
+        }
+
+        if let Some((marker, marker_len)) = list_marker(content) {
+            p.flush();
+            let text = &content[marker_len..];
+            let content_indent = indent + marker_len + (text.len() - text.trim_start().len()).max(1);
+            let depth = p.list_depth(indent, content_indent);
+            let marker = match marker {
+                Some(number) => number,
+                None => ["\u{2022}", "\u{25E6}", "\u{25AA}"][depth % 3].to_string()
+            };
+
+            p.add_line(BlockKind::ListItem { marker, depth }, text);
+            p.after_blank = false;
+            continue;
+        }
+
+        if p.pending.is_none() {
+            // After a blank line, an indented paragraph continues the current list item
+            let item_depth = match p.after_blank {
+                true => p.lists.iter().rposition(|&content_indent| indent >= content_indent),
+                false => None
+            };
+
+            match item_depth {
+                Some(depth) => {
+                    p.lists.truncate(depth + 1);
+                    p.add_line(BlockKind::ListItem { marker: String::new(), depth }, content);
+                },
+                None => {
+                    if p.after_blank || indent == 0 {
+                        p.lists.clear();
+                    }
+                    p.add_line(BlockKind::Paragraph, content);
+                }
+            }
+        } else {
+            // Continuation of the current paragraph or list item
+            p.add_line(BlockKind::Paragraph, content);
+        }
+
+        p.after_blank = false;
+    }
+
+    p.flush();
+}
+
+/// Parses the inline content of a paragraph and adds the blocks. Images are moved in their own block.
+fn push_inline_block(out: &mut Vec<Block>, kind: BlockKind, quote: usize, text: &str) {
+    let mut items = Vec::new();
+    parse_inline_items(text, &SpanStyle::default(), &mut items);
+
+    // The marker of a list item is only displayed on its first block
+    let next_kind = match &kind {
+        BlockKind::ListItem { depth, .. } => BlockKind::ListItem { marker: String::new(), depth: *depth },
+        _ => BlockKind::Paragraph
+    };
+
+    let mut kind = Some(kind);
+    let mut spans = Vec::new();
+
+    let flush_spans = |spans: &mut Vec<Span>, kind: &mut Option<BlockKind>, out: &mut Vec<Block>| {
+        trim_spans(spans);
+        if !spans.is_empty() {
+            let block_kind = kind.take().unwrap_or_else(|| next_kind.clone());
+            out.push(Block { kind: block_kind, quote, spans: std::mem::replace(spans, Vec::new()) });
+        }
+    };
+
+    for item in items {
+        match item {
+            Inline::Span(span) => spans.push(span),
+            Inline::Image { src, alt, link } => {
+                flush_spans(&mut spans, &mut kind, out);
+                out.push(Block { kind: BlockKind::Image { src, alt, link }, quote, spans: Vec::new() });
+            }
+        }
+    }
+
+    flush_spans(&mut spans, &mut kind, out);
+}
+
+/// Removes the whitespace around the text of a block
+fn trim_spans(spans: &mut Vec<Span>) {
+    if let Some(first) = spans.first_mut() {
+        first.text = first.text.trim_start().to_string();
+    }
+
+    if let Some(last) = spans.last_mut() {
+        last.text = last.text.trim_end().to_string();
+    }
+
+    spans.retain(|s| !s.text.is_empty());
+}
+
+/// Returns the width of the leading whitespace of a line. Tabs count as 4 spaces.
+fn indentation(line: &str) -> usize {
+    let mut width = 0;
+    for c in line.chars() {
+        match c {
+            ' ' => width += 1,
+            '\t' => width += 4 - width % 4,
+            _ => break
+        }
+    }
+    width
+}
+
+fn strip_quote(line: &str) -> &str {
+    let line = &line[1..];
+    line.strip_prefix(' ').unwrap_or(line)
+}
+
+/// Returns the character of the fence of a fenced code block (``` or ~~~) and the length of the fence
+fn code_fence(line: &str) -> Option<(char, usize)> {
+    for &fence in ['`', '~'].iter() {
+        let len = line.chars().take_while(|&c| c == fence).count();
+        if len >= 3 {
+            return Some((fence, len));
+        }
+    }
+
+    None
+}
+
+fn atx_heading(line: &str) -> Option<(u8, &str)> {
+    let level = line.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') && !rest.starts_with('\t') {
+        return None;
+    }
+
+    // The closing sequence of `#` is optional
+    let text = rest.trim();
+    let without_closing = text.trim_end_matches('#');
+    let text = match without_closing.is_empty() || without_closing.ends_with(' ') {
+        true => without_closing.trim_end(),
+        false => text
+    };
+
+    Some((level as u8, text))
+}
+
+fn is_rule(line: &str) -> bool {
+    let chars: Vec<char> = line.chars().filter(|c| !c.is_whitespace()).collect();
+    match chars.first() {
+        Some(&c) if c == '-' || c == '*' || c == '_' => chars.len() >= 3 && chars.iter().all(|&x| x == c),
+        _ => false
+    }
+}
+
+/// Returns the number of an ordered list item (`None` for an unordered item) and the length of the marker
+fn list_marker(line: &str) -> Option<(Option<String>, usize)> {
+    let followed_by_space = |rest: &str| rest.is_empty() || rest.starts_with(' ') || rest.starts_with('\t');
+
+    let first = line.chars().next()?;
+    if (first == '-' || first == '*' || first == '+') && followed_by_space(&line[1..]) {
+        return Some((None, 1));
+    }
+
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 && digits <= 9 {
+        let rest = &line[digits..];
+        if (rest.starts_with('.') || rest.starts_with(')')) && followed_by_space(&rest[1..]) {
+            return Some((Some(format!("{}.", &line[..digits])), digits + 1));
+        }
+    }
+
+    None
+}
+
+enum Inline {
+    Span(Span),
+    Image { src: String, alt: String, link: Option<String> },
+}
+
+/// Parses the inline content of a heading. Images are replaced by their text.
+fn parse_inline(text: &str) -> Vec<Span> {
+    let mut items = Vec::new();
+    parse_inline_items(text, &SpanStyle::default(), &mut items);
+
+    let mut spans: Vec<Span> = items.into_iter()
+        .map(|item| match item {
+            Inline::Span(span) => span,
+            Inline::Image { alt, link, .. } => Span { text: alt, style: SpanStyle { link, ..Default::default() } }
+        })
+        .collect();
+
+    trim_spans(&mut spans);
+    spans
+}
+
+fn parse_inline_items(text: &str, style: &SpanStyle, out: &mut Vec<Inline>) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut buffer = String::new();
+    let mut i = 0;
+
+    let flush = |buffer: &mut String, out: &mut Vec<Inline>| {
+        if !buffer.is_empty() {
+            out.push(Inline::Span(Span { text: std::mem::replace(buffer, String::new()), style: style.clone() }));
+        }
+    };
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        match c {
+            '\\' if i + 1 < chars.len() && chars[i + 1].is_ascii_punctuation() => {
+                buffer.push(chars[i + 1]);
+                i += 2;
+                continue;
+            },
+            '`' => {
+                let run = count_run(&chars, i, '`');
+                if let Some(end) = find_code_end(&chars, i + run, run) {
+                    flush(&mut buffer, out);
+                    let code: String = chars[i + run..end].iter().collect();
+                    let code = code.replace('\n', " ");
+                    let code = match code.len() > 2 && code.starts_with(' ') && code.ends_with(' ') {
+                        true => code[1..code.len() - 1].to_string(),
+                        false => code
+                    };
+
+                    out.push(Inline::Span(Span { text: code, style: SpanStyle { code: true, ..style.clone() } }));
+                    i = end + run;
+                } else {
+                    buffer.extend(&chars[i..i + run]);
+                    i += run;
+                }
+                continue;
+            },
+            '!' if chars.get(i + 1) == Some(&'[') => {
+                if let Some((label, dest, end)) = parse_link(&chars, i + 1) {
+                    flush(&mut buffer, out);
+                    let alt: String = plain_text(&label);
+                    out.push(Inline::Image { src: dest, alt, link: style.link.clone() });
+                    i = end;
+                    continue;
+                }
+            },
+            '[' if style.link.is_none() => {
+                if let Some((label, dest, end)) = parse_link(&chars, i) {
+                    flush(&mut buffer, out);
+                    let link_style = SpanStyle { link: Some(dest), ..style.clone() };
+                    parse_inline_items(&label, &link_style, out);
+                    i = end;
+                    continue;
+                }
+            },
+            '<' if style.link.is_none() => {
+                if let Some(end) = chars[i + 1..].iter().position(|&c| c == '>' || c == '<' || c.is_whitespace()).map(|p| p + i + 1) {
+                    let url: String = chars[i + 1..end].iter().collect();
+                    if chars[end] == '>' && (url.contains("://") || url.starts_with("mailto:")) {
+                        flush(&mut buffer, out);
+                        let link_style = SpanStyle { link: Some(url.clone()), ..style.clone() };
+                        out.push(Inline::Span(Span { text: url.trim_start_matches("mailto:").to_string(), style: link_style }));
+                        i = end + 1;
+                        continue;
+                    }
+                }
+            },
+            '*' | '_' | '~' => {
+                let run = count_run(&chars, i, c);
+                let previous = match i { 0 => None, _ => Some(chars[i - 1]) };
+
+                // `_` inside a word is not an emphasis (ex: snake_case)
+                let intraword = c == '_' && previous.map(|p| p.is_alphanumeric()).unwrap_or(false);
+                let opens = chars.get(i + run).map(|n| !n.is_whitespace()).unwrap_or(false);
+
+                if !intraword && opens {
+                    let size = match (c, run) {
+                        ('~', r) if r >= 2 => 2,
+                        ('~', _) => 0,
+                        (_, r) if r >= 2 => 2,
+                        _ => 1,
+                    };
+
+                    if size > 0 {
+                        if let Some(end) = find_emphasis_end(&chars, i + size, c, size) {
+                            flush(&mut buffer, out);
+                            let inner: String = chars[i + size..end].iter().collect();
+                            let inner_style = match (c, size) {
+                                ('~', _) => SpanStyle { strike: true, ..style.clone() },
+                                (_, 2) => SpanStyle { bold: true, ..style.clone() },
+                                _ => SpanStyle { italic: true, ..style.clone() },
+                            };
+
+                            parse_inline_items(&inner, &inner_style, out);
+                            i = end + size;
+                            continue;
+                        }
+                    }
+                }
+
+                buffer.extend(&chars[i..i + run]);
+                i += run;
+                continue;
+            },
+            _ => {}
+        }
+
+        buffer.push(c);
+        i += 1;
+    }
+
+    flush(&mut buffer, out);
+}
+
+fn count_run(chars: &[char], start: usize, c: char) -> usize {
+    chars[start..].iter().take_while(|&&x| x == c).count()
+}
+
+/// Finds a run of exactly `run` backticks
+fn find_code_end(chars: &[char], start: usize, run: usize) -> Option<usize> {
+    let mut i = start;
+    while i < chars.len() {
+        if chars[i] == '`' {
+            let len = count_run(chars, i, '`');
+            if len == run {
+                return Some(i);
+            }
+            i += len;
+        } else {
+            i += 1;
+        }
+    }
+
+    None
+}
+
+/// Finds the closing delimiter of an emphasis. The delimiter must follow a non whitespace character.
+fn find_emphasis_end(chars: &[char], start: usize, c: char, size: usize) -> Option<usize> {
+    let mut i = start + 1;
+    while i + size <= chars.len() {
+        match chars[i] {
+            '\\' => { i += 2; continue; },
+            '`' => {
+                // Delimiters inside code spans do not count
+                let run = count_run(chars, i, '`');
+                i = find_code_end(chars, i + run, run).map(|end| end + run).unwrap_or(i + run);
+                continue;
+            },
+            x if x == c => {
+                let run = count_run(chars, i, c);
+                let closes = !chars[i - 1].is_whitespace();
+                let word_follows = c == '_' && chars.get(i + run).map(|n| n.is_alphanumeric()).unwrap_or(false);
+
+                // A single delimiter does not close on a double delimiter, which is a nested strong emphasis.
+                // Three delimiters close both an emphasis and a strong emphasis: the last ones close the outer one.
+                if closes && !word_follows && (run == size || run == 3) {
+                    return Some(i + run - size);
+                }
+
+                i += run;
+                continue;
+            },
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+/// Parses `[label](destination "title")` at `start`. Returns the label, the destination and the index after the link.
+fn parse_link(chars: &[char], start: usize) -> Option<(String, String, usize)> {
+    // Matching closing bracket, brackets can be nested
+    let mut depth = 0;
+    let mut label_end = None;
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => { i += 1; },
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    label_end = Some(i);
+                    break;
+                }
+            },
+            _ => {}
+        }
+        i += 1;
+    }
+
+    let label_end = label_end?;
+    if chars.get(label_end + 1) != Some(&'(') {
+        return None;
+    }
+
+    let mut depth = 0;
+    let mut end = None;
+    let mut i = label_end + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => { i += 1; },
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(i);
+                    break;
+                }
+            },
+            _ => {}
+        }
+        i += 1;
+    }
+
+    let end = end?;
+    let label: String = chars[start + 1..label_end].iter().collect();
+    let inside: String = chars[label_end + 2..end].iter().collect();
+    let inside = inside.trim();
+
+    // The title after the destination is ignored
+    let destination = match inside.starts_with('<') {
+        true => inside[1..].split('>').next().unwrap_or(""),
+        false => inside.split_whitespace().next().unwrap_or("")
+    };
+
+    Some((label, destination.to_string(), end + 1))
+}
+
+/// Returns the text of inline markdown without the formatting
+fn plain_text(text: &str) -> String {
+    let mut items = Vec::new();
+    parse_inline_items(text, &SpanStyle::default(), &mut items);
+
+    items.into_iter()
+        .map(|item| match item {
+            Inline::Span(span) => span.text,
+            Inline::Image { alt, .. } => alt
+        })
+        .collect()
+}
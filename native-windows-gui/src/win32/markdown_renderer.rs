@@ -0,0 +1,557 @@
+/*!
+    Direct2D renderer used by the markdown view control.
+
+    All coordinates are in device independent pixels (DIPs). The text is laid out with DirectWrite text layouts that do
+    not depend on the render target, so that the document can be measured before the first draw. The render target is
+    created on the first draw and recreated if the device is lost.
+*/
+use winapi::shared::windef::HWND;
+use winapi::um::d2d1::*;
+use winapi::um::dcommon::DWRITE_MEASURING_MODE_NATURAL;
+use winapi::um::dwrite::{IDWriteFactory, IDWriteTextFormat, IDWriteTextLayout, DWriteCreateFactory, DWRITE_FACTORY_TYPE_SHARED, DWRITE_TEXT_RANGE};
+use winapi::um::wincodec::IWICBitmapSource;
+use winapi::shared::winerror::{S_OK, D2DERR_RECREATE_TARGET};
+use super::base_helper::to_utf16;
+use super::window_helper;
+use crate::{NwgError, ImageData};
+use std::{cell::Cell, collections::HashMap, mem, ptr};
+
+
+/// A rectangle in DIPs: left, top, right, bottom
+pub(crate) type Rect = [f32; 4];
+
+const BODY_FONT: &'static str = "Segoe UI";
+const CODE_FONT: &'static str = "Consolas";
+
+/// A paragraph of text laid out for a maximum width
+pub(crate) struct TextLayout {
+    layout: *mut IDWriteTextLayout,
+    /// The ranges drawn with the link brush
+    links: Vec<DWRITE_TEXT_RANGE>,
+    /// The render target generation of the link brush set on the links
+    links_generation: Cell<u32>,
+}
+
+impl TextLayout {
+
+    fn range(start: u32, length: u32) -> DWRITE_TEXT_RANGE {
+        DWRITE_TEXT_RANGE { startPosition: start, length }
+    }
+
+    pub(crate) fn set_font_size(&self, size: f32, start: u32, length: u32) {
+        unsafe { (&*self.layout).SetFontSize(size, Self::range(start, length)); }
+    }
+
+    pub(crate) fn set_weight(&self, weight: u32, start: u32, length: u32) {
+        unsafe { (&*self.layout).SetFontWeight(weight, Self::range(start, length)); }
+    }
+
+    pub(crate) fn set_italic(&self, start: u32, length: u32) {
+        use winapi::um::dwrite::DWRITE_FONT_STYLE_ITALIC;
+        unsafe { (&*self.layout).SetFontStyle(DWRITE_FONT_STYLE_ITALIC, Self::range(start, length)); }
+    }
+
+    pub(crate) fn set_strikethrough(&self, start: u32, length: u32) {
+        unsafe { (&*self.layout).SetStrikethrough(1, Self::range(start, length)); }
+    }
+
+    /// Uses the code font for a range of text
+    pub(crate) fn set_code(&self, start: u32, length: u32) {
+        let family = to_utf16(CODE_FONT);
+        unsafe { (&*self.layout).SetFontFamilyName(family.as_ptr(), Self::range(start, length)); }
+    }
+
+    /// Underlines a range of text and draws it with the link color
+    pub(crate) fn set_link(&mut self, start: u32, length: u32) {
+        unsafe { (&*self.layout).SetUnderline(1, Self::range(start, length)); }
+        self.links.push(Self::range(start, length));
+        self.links_generation.set(0);
+    }
+
+    /// Disables the word wrapping. Used for the code blocks.
+    pub(crate) fn set_no_wrap(&self) {
+        use winapi::um::dwrite::DWRITE_WORD_WRAPPING_NO_WRAP;
+        unsafe { (&*self.layout).SetWordWrapping(DWRITE_WORD_WRAPPING_NO_WRAP); }
+    }
+
+    /// Returns the width and the height of the text
+    pub(crate) fn size(&self) -> (f32, f32) {
+        use winapi::um::dwrite::DWRITE_TEXT_METRICS;
+
+        unsafe {
+            let mut metrics: DWRITE_TEXT_METRICS = mem::zeroed();
+            (&*self.layout).GetMetrics(&mut metrics);
+            (metrics.widthIncludingTrailingWhitespace, metrics.height)
+        }
+    }
+
+    /// Returns the position (in UTF-16 units) of the character at a point relative to the layout, if the point is over the text
+    pub(crate) fn hit_test(&self, x: f32, y: f32) -> Option<u32> {
+        use winapi::um::dwrite::DWRITE_HIT_TEST_METRICS;
+
+        unsafe {
+            let mut trailing = 0;
+            let mut inside = 0;
+            let mut metrics: DWRITE_HIT_TEST_METRICS = mem::zeroed();
+            (&*self.layout).HitTestPoint(x, y, &mut trailing, &mut inside, &mut metrics);
+
+            match inside != 0 {
+                true => Some(metrics.textPosition),
+                false => None
+            }
+        }
+    }
+
+    /// Returns the rectangles covered by a range of text, relative to the layout
+    pub(crate) fn range_rects(&self, start: u32, length: u32) -> Vec<Rect> {
+        use winapi::um::dwrite::DWRITE_HIT_TEST_METRICS;
+
+        unsafe {
+            let mut count = 0;
+            let mut metrics: Vec<DWRITE_HIT_TEST_METRICS> = vec![mem::zeroed(); 8];
+            let mut result = (&*self.layout).HitTestTextRange(start, length, 0.0, 0.0, metrics.as_mut_ptr(), metrics.len() as u32, &mut count);
+
+            // The buffer is too small if the range spans many lines
+            if result != S_OK && count as usize > metrics.len() {
+                metrics = vec![mem::zeroed(); count as usize];
+                result = (&*self.layout).HitTestTextRange(start, length, 0.0, 0.0, metrics.as_mut_ptr(), metrics.len() as u32, &mut count);
+            }
+
+            if result != S_OK {
+                return Vec::new();
+            }
+
+            metrics[..count as usize].iter()
+                .map(|m| [m.left, m.top, m.left + m.width, m.top + m.height])
+                .collect()
+        }
+    }
+
+}
+
+impl Drop for TextLayout {
+    fn drop(&mut self) {
+        unsafe { (&*self.layout).Release(); }
+    }
+}
+
+/// An image registered with `MarkdownRenderer::set_image`
+struct MarkdownImage {
+    /// The pixels converted to the format of the render target
+    source: *mut IWICBitmapSource,
+    size: (u32, u32),
+    /// Device dependent bitmap. Created on the first draw.
+    bitmap: *mut ID2D1Bitmap,
+}
+
+impl Drop for MarkdownImage {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.bitmap.is_null() {
+                (&*self.bitmap).Release();
+            }
+
+            (&*self.source).Release();
+        }
+    }
+}
+
+pub(crate) struct MarkdownRenderer {
+    factory: *mut ID2D1Factory,
+    write_factory: *mut IDWriteFactory,
+    body_format: *mut IDWriteTextFormat,
+    code_format: *mut IDWriteTextFormat,
+    font_size: f32,
+    images: HashMap<String, MarkdownImage>,
+
+    // Device dependent resources. Null until the first draw or after the device was lost.
+    target: *mut ID2D1HwndRenderTarget,
+    brush: *mut ID2D1SolidColorBrush,
+    link_brush: *mut ID2D1SolidColorBrush,
+    /// Incremented each time the render target is created. The text layouts use it to refresh the brush of their links.
+    generation: u32,
+}
+
+impl MarkdownRenderer {
+
+    pub(crate) fn new(font_size: f32) -> Result<MarkdownRenderer, NwgError> {
+        use winapi::ctypes::c_void;
+        use winapi::Interface;
+
+        let mut renderer = MarkdownRenderer {
+            factory: ptr::null_mut(),
+            write_factory: ptr::null_mut(),
+            body_format: ptr::null_mut(),
+            code_format: ptr::null_mut(),
+            font_size,
+            images: HashMap::new(),
+            target: ptr::null_mut(),
+            brush: ptr::null_mut(),
+            link_brush: ptr::null_mut(),
+            generation: 0,
+        };
+
+        unsafe {
+            let result = D2D1CreateFactory(
+                D2D1_FACTORY_TYPE_SINGLE_THREADED,
+                &ID2D1Factory::uuidof(),
+                ptr::null(),
+                (&mut renderer.factory as *mut *mut ID2D1Factory) as *mut *mut c_void
+            );
+
+            if result != S_OK {
+                return Err(NwgError::control_create("Failed to create the direct2D factory"));
+            }
+
+            let result = DWriteCreateFactory(
+                DWRITE_FACTORY_TYPE_SHARED,
+                &IDWriteFactory::uuidof(),
+                (&mut renderer.write_factory as *mut *mut IDWriteFactory) as _
+            );
+
+            if result != S_OK {
+                return Err(NwgError::control_create("Failed to create the directwrite factory"));
+            }
+
+            renderer.create_formats()?;
+        }
+
+        Ok(renderer)
+    }
+
+    /// The size of the body text in DIPs
+    pub(crate) fn font_size(&self) -> f32 {
+        self.font_size
+    }
+
+    /// Changes the size of the body text. The existing text layouts keep the previous size.
+    pub(crate) fn set_font_size(&mut self, size: f32) -> Result<(), NwgError> {
+        self.font_size = size;
+        unsafe {
+            self.release_formats();
+            self.create_formats()
+        }
+    }
+
+    /// Lays out `text` for a maximum width. `code` selects the monospaced font.
+    pub(crate) fn create_layout(&self, text: &str, code: bool, width: f32) -> Option<TextLayout> {
+        let format = match code {
+            true => self.code_format,
+            false => self.body_format
+        };
+
+        let text = to_utf16(text);
+        let mut layout = ptr::null_mut();
+
+        let result = unsafe {
+            (&*self.write_factory).CreateTextLayout(
+                text.as_ptr(),
+                (text.len() - 1) as _,
+                format,
+                width.max(1.0),
+                f32::MAX,
+                &mut layout,
+            )
+        };
+
+        match result == S_OK {
+            true => Some(TextLayout { layout, links: Vec::new(), links_generation: Cell::new(0) }),
+            false => None
+        }
+    }
+
+    /// Registers the pixels of an image under a name
+    pub(crate) fn set_image(&mut self, name: &str, image: &ImageData) -> Result<(), NwgError> {
+        use winapi::um::wincodec::{WICConvertBitmapSource, GUID_WICPixelFormat32bppPBGRA};
+
+        let mut source = ptr::null_mut();
+        let (mut width, mut height) = (0, 0);
+
+        unsafe {
+            let hr = WICConvertBitmapSource(&GUID_WICPixelFormat32bppPBGRA, image.frame, &mut source);
+            if hr != S_OK {
+                return Err(NwgError::image_decoder(hr, "Could not convert image pixels"));
+            }
+
+            (&*source).GetSize(&mut width, &mut height);
+        }
+
+        self.images.insert(name.to_string(), MarkdownImage { source, size: (width, height), bitmap: ptr::null_mut() });
+
+        Ok(())
+    }
+
+    pub(crate) fn remove_image(&mut self, name: &str) -> bool {
+        self.images.remove(name).is_some()
+    }
+
+    pub(crate) fn clear_images(&mut self) {
+        self.images.clear();
+    }
+
+    /// Returns the size in pixels of a registered image
+    pub(crate) fn image_size(&self, name: &str) -> Option<(u32, u32)> {
+        self.images.get(name).map(|i| i.size)
+    }
+
+    /// Starts drawing in the window. Returns `false` if the render target could not be created.
+    pub(crate) fn begin_draw(&mut self, hwnd: HWND) -> bool {
+        if self.target.is_null() && unsafe { self.create_target(hwnd) }.is_err() {
+            return false;
+        }
+
+        unsafe {
+            let target = &*self.target;
+            target.BeginDraw();
+            target.SetTransform(&identity());
+        }
+
+        true
+    }
+
+    /// Ends the drawing. If the device was lost, the render target is recreated on the next draw.
+    pub(crate) fn end_draw(&mut self) {
+        let result = unsafe { (&*self.target).EndDraw(ptr::null_mut(), ptr::null_mut()) };
+        if result == D2DERR_RECREATE_TARGET {
+            self.release_target();
+        }
+    }
+
+    /// Resizes the render target to the size of the window
+    pub(crate) fn resize(&mut self, hwnd: HWND) {
+        if self.target.is_null() {
+            return;
+        }
+
+        unsafe {
+            let (width, height) = window_helper::get_window_physical_size(hwnd);
+            (&*self.target).Resize(&D2D1_SIZE_U { width, height });
+        }
+    }
+
+    pub(crate) fn clear(&self, color: [u8; 3]) {
+        unsafe { (&*self.target).Clear(&d2d_color(color)); }
+    }
+
+    pub(crate) fn fill_rect(&self, rect: Rect, color: [u8; 3]) {
+        let brush = self.brush(color);
+        unsafe { (&*self.target).FillRectangle(&d2d_rect(rect), brush); }
+    }
+
+    pub(crate) fn fill_rounded_rect(&self, rect: Rect, radius: f32, color: [u8; 3]) {
+        let brush = self.brush(color);
+        let rounded = D2D1_ROUNDED_RECT { rect: d2d_rect(rect), radiusX: radius, radiusY: radius };
+        unsafe { (&*self.target).FillRoundedRectangle(&rounded, brush); }
+    }
+
+    pub(crate) fn draw_rect(&self, rect: Rect, color: [u8; 3], width: f32) {
+        let brush = self.brush(color);
+        unsafe { (&*self.target).DrawRectangle(&d2d_rect(rect), brush, width, ptr::null_mut()); }
+    }
+
+    /// Draws a text layout with its top left corner at `origin`. The links use `link_color`.
+    pub(crate) fn draw_layout(&self, layout: &TextLayout, origin: (f32, f32), color: [u8; 3], link_color: [u8; 3]) {
+        use winapi::um::unknwnbase::IUnknown;
+
+        unsafe {
+            (&*self.link_brush).SetColor(&d2d_color(link_color));
+
+            // The links keep a reference to the brush of the previous render target
+            if layout.links_generation.get() != self.generation {
+                for &range in layout.links.iter() {
+                    (&*layout.layout).SetDrawingEffect(self.link_brush as *mut IUnknown, range);
+                }
+                layout.links_generation.set(self.generation);
+            }
+
+            let brush = self.brush(color);
+            (&*self.target).DrawTextLayout(
+                D2D1_POINT_2F { x: origin.0, y: origin.1 },
+                layout.layout,
+                brush,
+                D2D1_DRAW_TEXT_OPTIONS_CLIP,
+            );
+        }
+    }
+
+    /// Draws a registered image, scaled to fit in `rect`
+    pub(crate) fn draw_image(&mut self, name: &str, rect: Rect) {
+        let target = self.target;
+        let image = match self.images.get_mut(name) {
+            Some(i) => i,
+            None => { return; }
+        };
+
+        unsafe {
+            if image.bitmap.is_null() {
+                let result = (&*target).CreateBitmapFromWicBitmap(image.source, ptr::null(), &mut image.bitmap);
+                if result != S_OK {
+                    image.bitmap = ptr::null_mut();
+                    return;
+                }
+            }
+
+            (&*target).DrawBitmap(image.bitmap, &d2d_rect(rect), 1.0, D2D1_BITMAP_INTERPOLATION_MODE_LINEAR, ptr::null());
+        }
+    }
+
+    pub(crate) fn push_clip(&self, rect: Rect) {
+        unsafe { (&*self.target).PushAxisAlignedClip(&d2d_rect(rect), D2D1_ANTIALIAS_MODE_ALIASED); }
+    }
+
+    pub(crate) fn pop_clip(&self) {
+        unsafe { (&*self.target).PopAxisAlignedClip(); }
+    }
+
+    fn brush(&self, color: [u8; 3]) -> *mut ID2D1Brush {
+        unsafe {
+            (&*self.brush).SetColor(&d2d_color(color));
+        }
+
+        self.brush as _
+    }
+
+    unsafe fn create_formats(&mut self) -> Result<(), NwgError> {
+        self.body_format = self.create_text_format(BODY_FONT, self.font_size)?;
+        self.code_format = self.create_text_format(CODE_FONT, self.font_size * 0.9)?;
+        Ok(())
+    }
+
+    unsafe fn release_formats(&mut self) {
+        for format in [&mut self.body_format, &mut self.code_format].iter_mut() {
+            if !format.is_null() {
+                (&***format).Release();
+                **format = ptr::null_mut();
+            }
+        }
+    }
+
+    unsafe fn create_text_format(&self, family: &str, size: f32) -> Result<*mut IDWriteTextFormat, NwgError> {
+        use winapi::um::dwrite::{DWRITE_FONT_WEIGHT_NORMAL, DWRITE_FONT_STYLE_NORMAL, DWRITE_FONT_STRETCH_NORMAL, DWRITE_WORD_WRAPPING_WRAP};
+
+        let family = to_utf16(family);
+        let locale = to_utf16("");
+        let mut format = ptr::null_mut();
+
+        let result = (&*self.write_factory).CreateTextFormat(
+            family.as_ptr(),
+            ptr::null_mut(),
+            DWRITE_FONT_WEIGHT_NORMAL,
+            DWRITE_FONT_STYLE_NORMAL,
+            DWRITE_FONT_STRETCH_NORMAL,
+            size,
+            locale.as_ptr(),
+            &mut format
+        );
+
+        if result != S_OK {
+            return Err(NwgError::control_create("Failed to create the markdown text format"));
+        }
+
+        (&*format).SetWordWrapping(DWRITE_WORD_WRAPPING_WRAP);
+
+        Ok(format)
+    }
+
+    unsafe fn create_target(&mut self, hwnd: HWND) -> Result<(), NwgError> {
+        use winapi::shared::dxgiformat::DXGI_FORMAT_B8G8R8A8_UNORM;
+        use winapi::um::dcommon::{D2D1_PIXEL_FORMAT, D2D1_ALPHA_MODE_PREMULTIPLIED};
+
+        let (width, height) = window_helper::get_window_physical_size(hwnd);
+
+        let render_props = D2D1_RENDER_TARGET_PROPERTIES {
+            _type: D2D1_RENDER_TARGET_TYPE_DEFAULT,
+            pixelFormat: D2D1_PIXEL_FORMAT { format: DXGI_FORMAT_B8G8R8A8_UNORM, alphaMode: D2D1_ALPHA_MODE_PREMULTIPLIED },
+            dpiX: 0.0, dpiY: 0.0,
+            usage: D2D1_RENDER_TARGET_USAGE_NONE,
+            minLevel: D2D1_FEATURE_LEVEL_DEFAULT
+        };
+
+        let hwnd_render_props = D2D1_HWND_RENDER_TARGET_PROPERTIES {
+            hwnd,
+            pixelSize: D2D1_SIZE_U { width, height },
+            presentOptions: D2D1_PRESENT_OPTIONS_NONE
+        };
+
+        if (&*self.factory).CreateHwndRenderTarget(&render_props, &hwnd_render_props, &mut self.target) != S_OK {
+            self.target = ptr::null_mut();
+            return Err(NwgError::control_create("Failed to create the direct2D render target"));
+        }
+
+        let props = D2D1_BRUSH_PROPERTIES { opacity: 1.0, transform: identity() };
+        let target = &*self.target;
+        if target.CreateSolidColorBrush(&d2d_color([0, 0, 0]), &props, &mut self.brush) != S_OK
+            || target.CreateSolidColorBrush(&d2d_color([0, 0, 0]), &props, &mut self.link_brush) != S_OK
+        {
+            self.release_target();
+            return Err(NwgError::control_create("Failed to create the direct2D brush"));
+        }
+
+        self.generation = self.generation.wrapping_add(1).max(1);
+
+        Ok(())
+    }
+
+    fn release_target(&mut self) {
+        unsafe {
+            // The image bitmaps belong to the render target
+            for image in self.images.values_mut() {
+                if !image.bitmap.is_null() {
+                    (&*image.bitmap).Release();
+                    image.bitmap = ptr::null_mut();
+                }
+            }
+
+            for brush in [&mut self.brush, &mut self.link_brush].iter_mut() {
+                if !brush.is_null() {
+                    (&***brush).Release();
+                    **brush = ptr::null_mut();
+                }
+            }
+
+            if !self.target.is_null() {
+                (&*self.target).Release();
+                self.target = ptr::null_mut();
+            }
+        }
+    }
+
+}
+
+impl Drop for MarkdownRenderer {
+
+    fn drop(&mut self) {
+        self.images.clear();
+        self.release_target();
+
+        unsafe {
+            self.release_formats();
+
+            if !self.write_factory.is_null() {
+                (&*self.write_factory).Release();
+            }
+
+            if !self.factory.is_null() {
+                (&*self.factory).Release();
+            }
+        }
+    }
+
+}
+
+fn identity() -> D2D1_MATRIX_3X2_F {
+    D2D1_MATRIX_3X2_F { matrix: [[1.0, 0.0], [0.0, 1.0], [0.0, 0.0]] }
+}
+
+fn d2d_rect(rect: Rect) -> D2D1_RECT_F {
+    D2D1_RECT_F { left: rect[0], top: rect[1], right: rect[2], bottom: rect[3] }
+}
+
+fn d2d_color(color: [u8; 3]) -> D2D1_COLOR_F {
+    D2D1_COLOR_F {
+        r: color[0] as f32 / 255.0,
+        g: color[1] as f32 / 255.0,
+        b: color[2] as f32 / 255.0,
+        a: 1.0,
+    }
+}
@@ -0,0 +1,227 @@
+/*!
+    Multiple document interface (MDI) helpers.
+
+    A MDI frame is a top level window that uses `mdi_frame_proc` as its window procedure. The frame owns a "MDICLIENT"
+    window that fills its client area and that contains the MDI children. The handle of the client is stored in the
+    user data of the frame.
+*/
+use winapi::shared::windef::{HWND, HMENU};
+use winapi::shared::minwindef::{UINT, WPARAM, LPARAM, LRESULT, BOOL};
+use winapi::um::winuser::{MSG, GWL_USERDATA};
+use super::base_helper::{CUSTOM_ID_BEGIN, to_utf16};
+use super::window_helper::{self as wh, NWG_INIT};
+use super::window::build_sysclass;
+use super::high_dpi;
+use crate::NwgError;
+use std::{ptr, cell::RefCell};
+
+/// The id of the first MDI child in the window menu. Must be lower than the ids of the menu items created by NWG.
+pub(crate) const MDI_FIRST_CHILD_ID: u32 = 5000;
+
+/// Not declared by winapi
+#[repr(C)]
+#[allow(non_snake_case)]
+struct CLIENTCREATESTRUCT {
+    hWindowMenu: HMENU,
+    idFirstChild: UINT,
+}
+
+// Not declared by winapi, but exported by user32
+extern "system" {
+    fn TranslateMDISysAccel(hWndClient: HWND, lpMsg: *mut MSG) -> BOOL;
+}
+
+thread_local! {
+    /// The MDI clients created in this thread. Used to translate the MDI keyboard shortcuts in the events loop.
+    static MDI_CLIENTS: RefCell<Vec<HWND>> = RefCell::new(Vec::new());
+}
+
+
+/// Register the window classes of the MDI frames and of the MDI children
+pub(crate) fn create_mdi_classes() -> Result<(), NwgError> {
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    use winapi::um::winuser::COLOR_APPWORKSPACE;
+    use winapi::shared::windef::HBRUSH;
+
+    unsafe {
+        let hmod = GetModuleHandleW(ptr::null_mut());
+        if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
+
+        let background = (COLOR_APPWORKSPACE + 1) as usize as HBRUSH;
+        build_sysclass(hmod, "NWG_MDI_FRAME", Some(mdi_frame_proc), Some(background), None)?;
+        build_sysclass(hmod, "NWG_MDI_CHILD", Some(mdi_child_proc), None, None)?;
+    }
+
+    Ok(())
+}
+
+/// Create the MDI client of a MDI frame
+pub(crate) unsafe fn build_mdi_client(frame: HWND) -> Result<HWND, NwgError> {
+    use winapi::um::winuser::{CreateWindowExW, GetClientRect, WS_CHILD, WS_VISIBLE, WS_CLIPCHILDREN, WS_VSCROLL, WS_HSCROLL, WS_EX_CLIENTEDGE};
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    use winapi::shared::windef::RECT;
+    use std::mem;
+
+    let hmod = GetModuleHandleW(ptr::null_mut());
+    if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
+
+    let mut rect: RECT = mem::zeroed();
+    GetClientRect(frame, &mut rect);
+
+    let mut params = CLIENTCREATESTRUCT { hWindowMenu: ptr::null_mut(), idFirstChild: MDI_FIRST_CHILD_ID };
+    let class_name = to_utf16("MDICLIENT");
+    let flags = WS_CHILD | WS_VISIBLE | WS_CLIPCHILDREN | WS_VSCROLL | WS_HSCROLL;
+
+    let client = CreateWindowExW(
+        WS_EX_CLIENTEDGE,
+        class_name.as_ptr(), ptr::null(),
+        flags,
+        0, 0,
+        rect.right, rect.bottom,
+        frame,
+        ptr::null_mut(),
+        hmod,
+        &mut params as *mut CLIENTCREATESTRUCT as _
+    );
+
+    if client.is_null() {
+        return Err(NwgError::control_create("Failed to create the MDI client"));
+    }
+
+    super::resource_tracker::track(super::resource_tracker::ResourceKind::Window, client);
+    wh::set_window_long(frame, GWL_USERDATA, client as usize);
+    MDI_CLIENTS.with(|clients| clients.borrow_mut().push(client));
+
+    Ok(client)
+}
+
+/// Create a MDI child in `client`. If `pos` is `None`, the system picks the position of the child.
+pub(crate) unsafe fn build_mdi_child(client: HWND, title: &str, size: (i32, i32), pos: Option<(i32, i32)>, flags: u32) -> Result<HWND, NwgError> {
+    use winapi::um::winuser::{CreateWindowExW, WS_EX_MDICHILD, CW_USEDEFAULT};
+    use winapi::um::libloaderapi::GetModuleHandleW;
+
+    let hmod = GetModuleHandleW(ptr::null_mut());
+    if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
+
+    let class_name = to_utf16("NWG_MDI_CHILD");
+    let title = to_utf16(title);
+    let (sx, sy) = high_dpi::logical_to_physical(size.0, size.1);
+    let (px, py) = match pos {
+        Some((x, y)) => high_dpi::logical_to_physical(x, y),
+        None => (CW_USEDEFAULT, CW_USEDEFAULT)
+    };
+
+    let handle = CreateWindowExW(
+        WS_EX_MDICHILD,
+        class_name.as_ptr(), title.as_ptr(),
+        flags,
+        px, py,
+        sx, sy,
+        client,
+        ptr::null_mut(),
+        hmod,
+        ptr::null_mut()
+    );
+
+    if handle.is_null() {
+        Err(NwgError::control_create("Failed to create the MDI child"))
+    } else {
+        super::resource_tracker::track(super::resource_tracker::ResourceKind::Window, handle);
+        Ok(handle)
+    }
+}
+
+/// Returns the MDI client of a MDI frame, or `None` if the window is not a MDI frame
+pub(crate) fn mdi_client(frame: HWND) -> Option<HWND> {
+    match wh::get_window_long(frame, GWL_USERDATA) as usize {
+        0 => None,
+        client => Some(client as HWND)
+    }
+}
+
+/// Returns `true` if `handle` is a MDI client window
+pub(crate) fn is_mdi_client(handle: HWND) -> bool {
+    use winapi::um::winuser::GetClassNameW;
+
+    let mut class_name = [0u16; 16];
+    let count = unsafe { GetClassNameW(handle, class_name.as_mut_ptr(), class_name.len() as i32) } as usize;
+    String::from_utf16_lossy(&class_name[..count]).eq_ignore_ascii_case("MDICLIENT")
+}
+
+/**
+    Translate the MDI keyboard shortcuts (CTRL+F4, CTRL+F6, ...) of `msg`.
+    Returns `true` if the message was translated and must not be dispatched.
+*/
+pub(crate) unsafe fn translate_mdi_accelerator(msg: &mut MSG) -> bool {
+    use winapi::um::winuser::{IsWindow, GetAncestor, GA_ROOT, WM_KEYDOWN, WM_SYSKEYDOWN};
+
+    if msg.message != WM_KEYDOWN && msg.message != WM_SYSKEYDOWN {
+        return false;
+    }
+
+    let root = GetAncestor(msg.hwnd, GA_ROOT);
+
+    MDI_CLIENTS.with(|clients| {
+        let mut clients = clients.borrow_mut();
+        clients.retain(|&c| IsWindow(c) != 0);
+
+        // Only translate the shortcuts of the frame that has the keyboard focus
+        clients.iter()
+            .filter(|&&c| GetAncestor(c, GA_ROOT) == root)
+            .any(|&c| TranslateMDISysAccel(c, msg) != 0)
+    })
+}
+
+/**
+    The window procedure of the MDI frames. Like `blank_window_proc`, closing the window hides it.
+*/
+unsafe extern "system" fn mdi_frame_proc(hwnd: HWND, msg: UINT, w: WPARAM, l: LPARAM) -> LRESULT {
+    use winapi::um::winuser::{WM_CREATE, WM_CLOSE, WM_COMMAND, WM_MENUCOMMAND, SW_HIDE};
+    use winapi::um::winuser::{DefFrameProcW, PostMessageW, ShowWindow, GetMenuItemID};
+
+    let client = mdi_client(hwnd).unwrap_or(ptr::null_mut());
+
+    match msg {
+        WM_CREATE => {
+            PostMessageW(hwnd, NWG_INIT, 0, 0);
+            0
+        },
+        WM_CLOSE => {
+            ShowWindow(hwnd, SW_HIDE);
+            0
+        },
+        WM_MENUCOMMAND => {
+            // NWG menus send their commands by position. The MDI client only understands WM_COMMAND,
+            // so the items of the window menu that activate a child must be forwarded.
+            let id = GetMenuItemID(l as HMENU, w as i32);
+            if id != u32::max_value() && id >= MDI_FIRST_CHILD_ID && id < CUSTOM_ID_BEGIN {
+                DefFrameProcW(hwnd, client, WM_COMMAND, id as WPARAM, 0)
+            } else {
+                DefFrameProcW(hwnd, client, msg, w, l)
+            }
+        },
+        _ => DefFrameProcW(hwnd, client, msg, w, l)
+    }
+}
+
+/**
+    The window procedure of the MDI children. Closing a child hides it and activates the next child.
+    The child is only destroyed when the `MdiChild` is dropped.
+*/
+unsafe extern "system" fn mdi_child_proc(hwnd: HWND, msg: UINT, w: WPARAM, l: LPARAM) -> LRESULT {
+    use winapi::um::winuser::{WM_CREATE, WM_CLOSE, WM_MDINEXT, SW_HIDE};
+    use winapi::um::winuser::{DefMDIChildProcW, PostMessageW, ShowWindow};
+
+    match msg {
+        WM_CREATE => {
+            PostMessageW(hwnd, NWG_INIT, 0, 0);
+            DefMDIChildProcW(hwnd, msg, w, l)
+        },
+        WM_CLOSE => {
+            wh::send_message(wh::get_window_parent(hwnd), WM_MDINEXT, hwnd as WPARAM, 0);
+            ShowWindow(hwnd, SW_HIDE);
+            0
+        },
+        _ => DefMDIChildProcW(hwnd, msg, w, l)
+    }
+}
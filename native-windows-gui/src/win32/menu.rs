@@ -1,8 +1,9 @@
 /*!
 Native Windows GUI menu base.
 */
-use winapi::shared::windef::{HMENU, HWND};
-use winapi::shared::minwindef::UINT;
+use winapi::shared::windef::{HMENU, HWND, HICON, HBITMAP, HFONT, HDC, RECT};
+use winapi::shared::minwindef::{UINT, LRESULT};
+use winapi::um::winuser::{MEASUREITEMSTRUCT, DRAWITEMSTRUCT};
 use super::base_helper::{CUSTOM_ID_BEGIN, to_utf16};
 use crate::controls::ControlHandle;
 use crate::{NwgError};
@@ -18,6 +19,10 @@ pub unsafe fn build_hmenu_control(text: Option<String>, item: bool, separator: b
     use winapi::um::winuser::{CreateMenu, CreatePopupMenu, GetMenu, SetMenu, DrawMenuBar, AppendMenuW};
     use winapi::um::winuser::{MF_STRING, MF_POPUP};
 
+    if let Some(hwnd) = hwnd {
+        super::ui_thread::check_thread(hwnd)?;
+    }
+
     if separator {
         if hmenu.is_none() {
             return Err(NwgError::menu_create("Separator without parent"));
@@ -35,7 +40,7 @@ pub unsafe fn build_hmenu_control(text: Option<String>, item: bool, separator: b
             return Err(NwgError::menu_create("Popup menu creation failed"));
         }
 
-        use_menu_command(menu);
+        use_menu_command(menu, hwnd.unwrap());
 
         return Ok(ControlHandle::PopMenu(hwnd.unwrap(), menu));
     }
@@ -55,7 +60,7 @@ pub unsafe fn build_hmenu_control(text: Option<String>, item: bool, separator: b
         if menubar.is_null() {
             // If the window do not have a menu bar, create one
             menubar = CreateMenu();
-            use_menu_command(menubar);
+            use_menu_command(menubar, hwnd);
             SetMenu(hwnd, menubar);
         }
 
@@ -69,7 +74,7 @@ pub unsafe fn build_hmenu_control(text: Option<String>, item: bool, separator: b
             if menu.is_null() {
                 return Err(NwgError::menu_create("Menu without parent"));
             }
-            use_menu_command(menu);
+            use_menu_command(menu, hwnd);
             AppendMenuW(menubar, flags, mem::transmute(menu), text.as_ptr());
         }
 
@@ -88,7 +93,7 @@ pub unsafe fn build_hmenu_control(text: Option<String>, item: bool, separator: b
             if menu.is_null() {
                 return Err(NwgError::menu_create("Menu without parent"));
             }
-            use_menu_command(menu);
+            use_menu_command(menu, menu_window(parent));
             AppendMenuW(parent, flags, mem::transmute(menu), text.as_ptr());
         }
     }
@@ -220,24 +225,44 @@ unsafe fn build_hmenu_separator(menu: HMENU) -> ControlHandle {
 /**
     Configure the menu to use a WM_MENUCOMMAND instead of a WM_COMMAND when its action are triggered.
     Required in order to allow nwg to dispatch the events correctly
+
+    The window that receives the events of the menu is saved in the menu data. See `menu_window`.
 */
-unsafe fn use_menu_command(h: HMENU) {
-    use winapi::um::winuser::{MENUINFO, MNS_NOTIFYBYPOS, MIM_STYLE, SetMenuInfo};
+unsafe fn use_menu_command(h: HMENU, window: HWND) {
+    use winapi::um::winuser::{MENUINFO, MNS_NOTIFYBYPOS, MIM_STYLE, MIM_MENUDATA, SetMenuInfo};
     use winapi::shared::minwindef::DWORD;
 
     let mut info = MENUINFO {
         cbSize: mem::size_of::<MENUINFO>() as DWORD,
-        fMask: MIM_STYLE,
+        fMask: MIM_STYLE | MIM_MENUDATA,
         dwStyle: MNS_NOTIFYBYPOS,
         cyMax: 0,
         hbrBack: ptr::null_mut(),
         dwContextHelpID: 0,
-        dwMenuData: 0
+        dwMenuData: window as usize
     };
 
     SetMenuInfo(h, &mut info);
 }
 
+/**
+    Return the window that receives the events of a menu created by nwg (the window of the menu bar or of the popup menu).
+    Return a null handle if the menu was not created by nwg.
+*/
+pub unsafe fn menu_window(h: HMENU) -> HWND {
+    use winapi::um::winuser::{MENUINFO, MIM_MENUDATA, GetMenuInfo};
+    use winapi::shared::minwindef::DWORD;
+
+    let mut info: MENUINFO = mem::zeroed();
+    info.cbSize = mem::size_of::<MENUINFO>() as DWORD;
+    info.fMask = MIM_MENUDATA;
+
+    match GetMenuInfo(h, &mut info) {
+        0 => ptr::null_mut(),
+        _ => info.dwMenuData as HWND
+    }
+}
+
 /**
     Return the index of a children menu/menuitem in a parent menu.
     Panic if the menu is not found in the parent.
@@ -256,3 +281,518 @@ pub unsafe fn menu_index_in_parent(parent: HMENU, menu: HMENU) -> UINT {
 
     panic!("Menu/MenuItem not found in parent!")
 }
+
+
+/// `DrawIconEx` flag to draw the icon with its mask
+const DI_NORMAL: u32 = 0x0003;
+
+/// Identifies the owner draw data created by nwg in the `dwItemData` of a menu item
+const STYLE_MAGIC: u32 = 0x4E57_474D;
+
+/// Padding around the content of an owner drawn menu item, in pixels
+const ITEM_PADDING: i32 = 4;
+
+
+/**
+    The appearance of an owner drawn menu item. A pointer to a boxed style is saved in the `dwItemData` of the item
+    and freed by `free_menu_item_style`.
+
+    The icon, the bitmaps and the font are not owned by the style. They must outlive the menu item.
+*/
+pub(crate) struct MenuItemStyle {
+    magic: u32,
+    pub text: Vec<u16>,
+    pub icon: HICON,
+    pub check_bitmap: HBITMAP,
+    pub uncheck_bitmap: HBITMAP,
+    pub font: HFONT,
+    pub text_color: Option<[u8; 3]>,
+    pub background_color: Option<[u8; 3]>,
+
+    /// If the item is in a menu bar. The check mark column is not drawn in the menu bar.
+    pub menubar: bool,
+}
+
+impl MenuItemStyle {
+
+    pub fn new(text: &str, menubar: bool) -> MenuItemStyle {
+        MenuItemStyle {
+            magic: STYLE_MAGIC,
+            text: text.encode_utf16().collect(),
+            icon: ptr::null_mut(),
+            check_bitmap: ptr::null_mut(),
+            uncheck_bitmap: ptr::null_mut(),
+            font: ptr::null_mut(),
+            text_color: None,
+            background_color: None,
+            menubar,
+        }
+    }
+
+    /// The text before the tab character
+    fn label(&self) -> &[u16] {
+        match self.text.iter().position(|&c| c == '\t' as u16) {
+            Some(i) => &self.text[..i],
+            None => &self.text
+        }
+    }
+
+    /// The shortcut label after the tab character
+    fn shortcut(&self) -> &[u16] {
+        match self.text.iter().position(|&c| c == '\t' as u16) {
+            Some(i) => &self.text[i+1..],
+            None => &[]
+        }
+    }
+
+    /// The lowercase access key of the item (the character after a single `&`)
+    fn access_key(&self) -> Option<u16> {
+        let label = self.label();
+        let mut i = 0;
+        while i + 1 < label.len() {
+            match (label[i] == '&' as u16, label[i+1] == '&' as u16) {
+                (true, true) => { i += 2; },
+                (true, false) => { return Some(to_lower(label[i+1])); },
+                _ => { i += 1; }
+            }
+        }
+
+        None
+    }
+
+}
+
+fn to_lower(c: u16) -> u16 {
+    match std::char::from_u32(c as u32) {
+        Some(c) => c.to_lowercase().next().map(|c| c as u32 as u16).unwrap_or(c as u32 as u16),
+        None => c
+    }
+}
+
+/// Returns the nwg style of an owner drawn menu item from its item data
+unsafe fn style_from_data<'a>(data: usize) -> Option<&'a MenuItemStyle> {
+    let style = data as *const MenuItemStyle;
+    match style.is_null() || (*style).magic != STYLE_MAGIC {
+        true => None,
+        false => Some(&*style)
+    }
+}
+
+/// Reads the item data of a menu item by id
+unsafe fn menu_item_data(parent_menu: HMENU, id: u32, by_position: bool) -> (UINT, usize) {
+    use winapi::um::winuser::{MENUITEMINFOW, MIIM_FTYPE, MIIM_DATA, GetMenuItemInfoW};
+    use winapi::shared::minwindef::BOOL;
+
+    let mut info = MENUITEMINFOW { 
+        cbSize: mem::size_of::<MENUITEMINFOW>() as UINT,
+        fMask: MIIM_FTYPE | MIIM_DATA, fType: 0, fState: 0,
+        wID: 0, hSubMenu: ptr::null_mut(), hbmpChecked: ptr::null_mut(),
+        hbmpUnchecked: ptr::null_mut(), dwItemData: 0, dwTypeData: ptr::null_mut(),
+        cch: 0, hbmpItem: ptr::null_mut()
+    };
+
+    GetMenuItemInfoW(parent_menu, id, by_position as BOOL, &mut info);
+
+    (info.fType, info.dwItemData)
+}
+
+/// Makes a menu item owner drawn using `style`. Replaces the previous style of the item.
+pub unsafe fn set_menu_item_style(parent_menu: HMENU, id: u32, style: MenuItemStyle) {
+    use winapi::um::winuser::{MENUITEMINFOW, MIIM_FTYPE, MIIM_DATA, MFT_OWNERDRAW, SetMenuItemInfoW};
+
+    free_menu_item_style(parent_menu, id);
+
+    let style = Box::into_raw(Box::new(style));
+    let mut info = MENUITEMINFOW { 
+        cbSize: mem::size_of::<MENUITEMINFOW>() as UINT,
+        fMask: MIIM_FTYPE | MIIM_DATA, fType: MFT_OWNERDRAW, fState: 0,
+        wID: 0, hSubMenu: ptr::null_mut(), hbmpChecked: ptr::null_mut(),
+        hbmpUnchecked: ptr::null_mut(), dwItemData: style as usize, dwTypeData: ptr::null_mut(),
+        cch: 0, hbmpItem: ptr::null_mut()
+    };
+
+    SetMenuItemInfoW(parent_menu, id, 0, &mut info);
+}
+
+/// Frees the owner draw style of a menu item, if the item has one. Must be called before the item is destroyed.
+pub unsafe fn free_menu_item_style(parent_menu: HMENU, id: u32) {
+    use winapi::um::winuser::{MENUITEMINFOW, MIIM_DATA, MFT_OWNERDRAW, SetMenuItemInfoW};
+
+    let (ty, data) = menu_item_data(parent_menu, id, false);
+    if ty & MFT_OWNERDRAW != MFT_OWNERDRAW || style_from_data(data).is_none() {
+        return;
+    }
+
+    let mut info = MENUITEMINFOW { 
+        cbSize: mem::size_of::<MENUITEMINFOW>() as UINT,
+        fMask: MIIM_DATA, fType: 0, fState: 0,
+        wID: 0, hSubMenu: ptr::null_mut(), hbmpChecked: ptr::null_mut(),
+        hbmpUnchecked: ptr::null_mut(), dwItemData: 0, dwTypeData: ptr::null_mut(),
+        cch: 0, hbmpItem: ptr::null_mut()
+    };
+
+    SetMenuItemInfoW(parent_menu, id, 0, &mut info);
+
+    drop(Box::from_raw(data as *mut MenuItemStyle));
+}
+
+/// Sets the bitmap displayed next to the text of a menu item
+pub unsafe fn set_menu_item_bitmap(parent_menu: HMENU, id: u32, bitmap: HBITMAP) {
+    use winapi::um::winuser::{MENUITEMINFOW, MIIM_BITMAP, SetMenuItemInfoW};
+
+    let mut info = MENUITEMINFOW { 
+        cbSize: mem::size_of::<MENUITEMINFOW>() as UINT,
+        fMask: MIIM_BITMAP, fType: 0, fState: 0,
+        wID: 0, hSubMenu: ptr::null_mut(), hbmpChecked: ptr::null_mut(),
+        hbmpUnchecked: ptr::null_mut(), dwItemData: 0, dwTypeData: ptr::null_mut(),
+        cch: 0, hbmpItem: bitmap
+    };
+
+    SetMenuItemInfoW(parent_menu, id, 0, &mut info);
+}
+
+/// Sets the bitmaps displayed in the check mark column of a menu item. A null bitmap uses the default check mark.
+pub unsafe fn set_menu_item_checkmarks(parent_menu: HMENU, id: u32, checked: HBITMAP, unchecked: HBITMAP) {
+    use winapi::um::winuser::{MENUITEMINFOW, MIIM_CHECKMARKS, SetMenuItemInfoW};
+
+    let mut info = MENUITEMINFOW { 
+        cbSize: mem::size_of::<MENUITEMINFOW>() as UINT,
+        fMask: MIIM_CHECKMARKS, fType: 0, fState: 0,
+        wID: 0, hSubMenu: ptr::null_mut(), hbmpChecked: checked,
+        hbmpUnchecked: unchecked, dwItemData: 0, dwTypeData: ptr::null_mut(),
+        cch: 0, hbmpItem: ptr::null_mut()
+    };
+
+    SetMenuItemInfoW(parent_menu, id, 0, &mut info);
+}
+
+/// Creates the font used by the system to draw the menus. Must be deleted by the caller.
+unsafe fn create_menu_font() -> HFONT {
+    use winapi::um::winuser::{SystemParametersInfoW, NONCLIENTMETRICSW, SPI_GETNONCLIENTMETRICS};
+    use winapi::um::wingdi::CreateFontIndirectW;
+
+    let mut metrics: NONCLIENTMETRICSW = mem::zeroed();
+    metrics.cbSize = mem::size_of::<NONCLIENTMETRICSW>() as UINT;
+    SystemParametersInfoW(SPI_GETNONCLIENTMETRICS, metrics.cbSize, &mut metrics as *mut NONCLIENTMETRICSW as _, 0);
+
+    CreateFontIndirectW(&metrics.lfMenuFont)
+}
+
+/// Returns the size of a text drawn in `dc`, without the access key prefix
+unsafe fn text_size(dc: HDC, text: &[u16]) -> (i32, i32) {
+    use winapi::um::winuser::{DrawTextW, DT_CALCRECT, DT_SINGLELINE};
+
+    let mut rect: RECT = mem::zeroed();
+    if !text.is_empty() {
+        DrawTextW(dc, text.as_ptr(), text.len() as i32, &mut rect, DT_CALCRECT | DT_SINGLELINE);
+    }
+
+    (rect.right - rect.left, rect.bottom - rect.top)
+}
+
+/**
+    Handles the WM_MEASUREITEM message of an owner drawn menu item created by nwg.
+    Returns `false` if the message was not sent for a nwg menu item.
+*/
+pub unsafe fn measure_menu_item(hwnd: HWND, measure: *mut MEASUREITEMSTRUCT) -> bool {
+    use winapi::um::winuser::{GetDC, ReleaseDC, GetSystemMetrics, ODT_MENU, SM_CXSMICON};
+    use winapi::um::wingdi::{SelectObject, DeleteObject};
+
+    let measure = &mut *measure;
+    if measure.CtlType != ODT_MENU {
+        return false;
+    }
+
+    let style = match style_from_data(measure.itemData) {
+        Some(s) => s,
+        None => { return false; }
+    };
+
+    let menu_font = create_menu_font();
+    let font = match style.font.is_null() {
+        true => menu_font,
+        false => style.font
+    };
+
+    let dc = GetDC(hwnd);
+    let old_font = SelectObject(dc, font as _);
+    let (label_width, text_height) = text_size(dc, style.label());
+    let (shortcut_width, _) = text_size(dc, style.shortcut());
+    SelectObject(dc, old_font);
+    ReleaseDC(hwnd, dc);
+    DeleteObject(menu_font as _);
+
+    let icon_size = GetSystemMetrics(SM_CXSMICON);
+    let (_, min_height) = menu_metrics();
+    let mut width = ITEM_PADDING * 2 + label_width;
+    if !style.icon.is_null() {
+        width += icon_size + ITEM_PADDING;
+    }
+    if shortcut_width > 0 {
+        width += ITEM_PADDING * 6 + shortcut_width;
+    }
+
+    // The system adds the width of the check mark column to the width of the popup menu items
+    measure.itemWidth = width.max(0) as UINT;
+    measure.itemHeight = (text_height.max(icon_size) + ITEM_PADDING).max(min_height) as UINT;
+
+    true
+}
+
+/// Returns the width of the check mark column and the height of a standard menu item
+unsafe fn menu_metrics() -> (i32, i32) {
+    use winapi::um::winuser::{GetSystemMetrics, SM_CXMENUCHECK, SM_CYMENUSIZE};
+    (GetSystemMetrics(SM_CXMENUCHECK), GetSystemMetrics(SM_CYMENUSIZE))
+}
+
+/**
+    Handles the WM_DRAWITEM message of an owner drawn menu item created by nwg.
+    Returns `false` if the message was not sent for a nwg menu item.
+*/
+pub unsafe fn draw_menu_item(draw: *const DRAWITEMSTRUCT) -> bool {
+    use winapi::um::winuser::{FillRect, DrawTextW, DrawIconEx, GetSysColor, GetSysColorBrush, GetSystemMetrics, ODT_MENU, ODS_SELECTED,
+        ODS_GRAYED, ODS_DISABLED, ODS_CHECKED, ODS_NOACCEL, COLOR_MENU, COLOR_MENUTEXT, COLOR_HIGHLIGHT, COLOR_HIGHLIGHTTEXT,
+        COLOR_GRAYTEXT, SM_CXSMICON, DT_SINGLELINE, DT_VCENTER, DT_LEFT, DT_RIGHT, DT_CENTER, DT_HIDEPREFIX, DT_END_ELLIPSIS};
+    use winapi::um::wingdi::{SelectObject, DeleteObject, CreateSolidBrush, SetTextColor, SetBkMode, RGB, TRANSPARENT};
+
+    let draw = &*draw;
+    if draw.CtlType != ODT_MENU {
+        return false;
+    }
+
+    let style = match style_from_data(draw.itemData) {
+        Some(s) => s,
+        None => { return false; }
+    };
+
+    let dc = draw.hDC;
+    let rect = draw.rcItem;
+    let selected = draw.itemState & ODS_SELECTED == ODS_SELECTED;
+    let disabled = draw.itemState & (ODS_GRAYED | ODS_DISABLED) != 0;
+    let checked = draw.itemState & ODS_CHECKED == ODS_CHECKED;
+
+    // Background
+    match (selected && !disabled, style.background_color) {
+        (true, _) => { FillRect(dc, &rect, GetSysColorBrush(COLOR_HIGHLIGHT)); },
+        (false, Some([r, g, b])) => {
+            let brush = CreateSolidBrush(RGB(r, g, b));
+            FillRect(dc, &rect, brush);
+            DeleteObject(brush as _);
+        },
+        (false, None) => { FillRect(dc, &rect, GetSysColorBrush(COLOR_MENU)); }
+    }
+
+    let text_color = match (disabled, selected, style.text_color) {
+        (true, _, _) => GetSysColor(COLOR_GRAYTEXT),
+        (false, true, _) => GetSysColor(COLOR_HIGHLIGHTTEXT),
+        (false, false, Some([r, g, b])) => RGB(r, g, b),
+        (false, false, None) => GetSysColor(COLOR_MENUTEXT),
+    };
+
+    SetTextColor(dc, text_color);
+    SetBkMode(dc, TRANSPARENT as i32);
+
+    let mut x = rect.left + ITEM_PADDING;
+
+    // Check mark column
+    if !style.menubar {
+        let (check_width, _) = menu_metrics();
+        let bitmap = match checked {
+            true => style.check_bitmap,
+            false => style.uncheck_bitmap
+        };
+
+        if !bitmap.is_null() {
+            draw_bitmap_centered(dc, bitmap, x, rect.top, check_width, rect.bottom - rect.top);
+        } else if checked {
+            // The "a" of the Marlett font is the check mark used by the system
+            let marlett = create_marlett_font(check_width);
+            let old_font = SelectObject(dc, marlett as _);
+            let mut check_rect = RECT { left: x, top: rect.top, right: x + check_width, bottom: rect.bottom };
+            let mark = ['a' as u16];
+            DrawTextW(dc, mark.as_ptr(), 1, &mut check_rect, DT_SINGLELINE | DT_VCENTER | DT_CENTER);
+            SelectObject(dc, old_font);
+            DeleteObject(marlett as _);
+        }
+
+        x += check_width + ITEM_PADDING;
+    }
+
+    // Icon
+    if !style.icon.is_null() {
+        let size = GetSystemMetrics(SM_CXSMICON);
+        let y = rect.top + ((rect.bottom - rect.top) - size) / 2;
+        DrawIconEx(dc, x, y, style.icon, size, size, 0, ptr::null_mut(), DI_NORMAL);
+        x += size + ITEM_PADDING;
+    }
+
+    // Text and shortcut
+    let menu_font = create_menu_font();
+    let font = match style.font.is_null() {
+        true => menu_font,
+        false => style.font
+    };
+    let old_font = SelectObject(dc, font as _);
+
+    let mut flags = DT_SINGLELINE | DT_VCENTER;
+    if draw.itemState & ODS_NOACCEL == ODS_NOACCEL {
+        flags |= DT_HIDEPREFIX;
+    }
+
+    let label = style.label();
+    let mut text_rect = RECT { left: x, top: rect.top, right: rect.right - ITEM_PADDING, bottom: rect.bottom };
+    DrawTextW(dc, label.as_ptr(), label.len() as i32, &mut text_rect, flags | DT_LEFT | DT_END_ELLIPSIS);
+
+    let shortcut = style.shortcut();
+    if !shortcut.is_empty() {
+        let mut shortcut_rect = RECT { left: x, top: rect.top, right: rect.right - ITEM_PADDING * 2, bottom: rect.bottom };
+        DrawTextW(dc, shortcut.as_ptr(), shortcut.len() as i32, &mut shortcut_rect, flags | DT_RIGHT);
+    }
+
+    SelectObject(dc, old_font);
+    DeleteObject(menu_font as _);
+
+    true
+}
+
+/**
+    Handles the WM_MENUCHAR message. The system does not handle the access keys of owner drawn items.
+    Returns the value of WM_MENUCHAR if an item of `menu` has the access key `key`.
+*/
+pub unsafe fn menu_char(menu: HMENU, key: u16) -> Option<LRESULT> {
+    use winapi::um::winuser::{GetMenuItemCount, MFT_OWNERDRAW, MNC_EXECUTE};
+
+    let key = to_lower(key);
+    let count = GetMenuItemCount(menu);
+    for index in 0..count.max(0) {
+        let (ty, data) = menu_item_data(menu, index as u32, true);
+        if ty & MFT_OWNERDRAW != MFT_OWNERDRAW {
+            continue;
+        }
+
+        if let Some(style) = style_from_data(data) {
+            if style.access_key() == Some(key) {
+                return Some(((MNC_EXECUTE << 16) | index as u32) as LRESULT);
+            }
+        }
+    }
+
+    None
+}
+
+unsafe fn create_marlett_font(height: i32) -> HFONT {
+    use winapi::um::wingdi::{CreateFontW, FW_NORMAL, SYMBOL_CHARSET, OUT_DEFAULT_PRECIS, CLIP_DEFAULT_PRECIS, DEFAULT_QUALITY, DEFAULT_PITCH};
+
+    let family = to_utf16("Marlett");
+    CreateFontW(height, 0, 0, 0, FW_NORMAL, 0, 0, 0, SYMBOL_CHARSET, OUT_DEFAULT_PRECIS, CLIP_DEFAULT_PRECIS,
+        DEFAULT_QUALITY, DEFAULT_PITCH, family.as_ptr())
+}
+
+unsafe fn draw_bitmap_centered(dc: HDC, bitmap: HBITMAP, x: i32, y: i32, width: i32, height: i32) {
+    use winapi::um::wingdi::{CreateCompatibleDC, DeleteDC, SelectObject, GetObjectW, BitBlt, BITMAP, SRCCOPY};
+
+    let mut info: BITMAP = mem::zeroed();
+    GetObjectW(bitmap as _, mem::size_of::<BITMAP>() as i32, &mut info as *mut BITMAP as _);
+
+    let w = info.bmWidth.min(width);
+    let h = info.bmHeight.min(height);
+    let memory_dc = CreateCompatibleDC(dc);
+    let old = SelectObject(memory_dc, bitmap as _);
+    BitBlt(dc, x + (width - w) / 2, y + (height - h) / 2, w, h, memory_dc, 0, 0, SRCCOPY);
+    SelectObject(memory_dc, old);
+    DeleteDC(memory_dc);
+}
+
+/**
+    Appends a group of radio items to a menu. The items have consecutive ids and their item data is the id of the first item,
+    so that the group can be found when one of the items is selected. Returns the id of the first item.
+*/
+pub unsafe fn build_radio_group(menu: HMENU, items: &[&str]) -> u32 {
+    use winapi::um::winuser::{AppendMenuW, SetMenuItemInfoW, MENUITEMINFOW, MIIM_FTYPE, MIIM_DATA, MF_STRING, MFT_RADIOCHECK};
+
+    let first_id = MENU_ITEMS_ID.fetch_add(items.len() as u32, Ordering::SeqCst);
+
+    for (i, text) in items.iter().enumerate() {
+        let id = first_id + i as u32;
+        let text = to_utf16(text);
+        AppendMenuW(menu, MF_STRING, id as usize, text.as_ptr());
+
+        let mut info = MENUITEMINFOW { 
+            cbSize: mem::size_of::<MENUITEMINFOW>() as UINT,
+            fMask: MIIM_FTYPE | MIIM_DATA, fType: MFT_RADIOCHECK, fState: 0,
+            wID: 0, hSubMenu: ptr::null_mut(), hbmpChecked: ptr::null_mut(),
+            hbmpUnchecked: ptr::null_mut(), dwItemData: first_id as usize, dwTypeData: ptr::null_mut(),
+            cch: 0, hbmpItem: ptr::null_mut()
+        };
+
+        SetMenuItemInfoW(menu, id, 0, &mut info);
+    }
+
+    first_id
+}
+
+/// Returns the id of the first item of the radio group of a menu item, or `None` if the item is not in a radio group
+unsafe fn radio_group_id(menu: HMENU, id: u32) -> Option<u32> {
+    use winapi::um::winuser::{MENUITEMINFOW, MIIM_FTYPE, MIIM_DATA, MFT_RADIOCHECK, MFT_OWNERDRAW, GetMenuItemInfoW};
+
+    let mut info = MENUITEMINFOW { 
+        cbSize: mem::size_of::<MENUITEMINFOW>() as UINT,
+        fMask: MIIM_FTYPE | MIIM_DATA, fType: 0, fState: 0,
+        wID: 0, hSubMenu: ptr::null_mut(), hbmpChecked: ptr::null_mut(),
+        hbmpUnchecked: ptr::null_mut(), dwItemData: 0, dwTypeData: ptr::null_mut(),
+        cch: 0, hbmpItem: ptr::null_mut()
+    };
+
+    if GetMenuItemInfoW(menu, id, 0, &mut info) == 0 {
+        return None;
+    }
+
+    let first_id = info.dwItemData;
+    let radio = info.fType & (MFT_RADIOCHECK | MFT_OWNERDRAW) == MFT_RADIOCHECK;
+    match radio && first_id >= CUSTOM_ID_BEGIN as usize && first_id <= id as usize {
+        true => Some(first_id as u32),
+        false => None
+    }
+}
+
+/// Returns the number of items of a radio group
+pub unsafe fn radio_group_len(menu: HMENU, first_id: u32) -> u32 {
+    let mut count = 0;
+    while radio_group_id(menu, first_id + count) == Some(first_id) {
+        count += 1;
+    }
+
+    count
+}
+
+/// Returns the index of the checked item of a radio group
+pub unsafe fn radio_group_selected(menu: HMENU, first_id: u32) -> Option<u32> {
+    (0..radio_group_len(menu, first_id)).find(|&i| menu_item_checked(menu, first_id + i))
+}
+
+/// Checks an item of a radio group and unchecks the others
+pub unsafe fn radio_group_select(menu: HMENU, first_id: u32, index: u32) {
+    use winapi::um::winuser::{CheckMenuRadioItem, MF_BYCOMMAND};
+
+    let count = radio_group_len(menu, first_id);
+    if index < count {
+        CheckMenuRadioItem(menu, first_id, first_id + count - 1, first_id + index, MF_BYCOMMAND);
+    }
+}
+
+/**
+    Checks the selected item if it belongs to a radio group. Returns the id of the first item of the group
+    and the index of the item if the checked item changed.
+*/
+pub unsafe fn select_radio_item(menu: HMENU, id: u32) -> Option<(u32, u32)> {
+    let first_id = radio_group_id(menu, id)?;
+    let index = id - first_id;
+
+    match radio_group_selected(menu, first_id) == Some(index) {
+        true => None,
+        false => {
+            radio_group_select(menu, first_id, index);
+            Some((first_id, index))
+        }
+    }
+}
@@ -5,6 +5,16 @@ pub(crate) mod window;
 pub(crate) mod message_box;
 pub(crate) mod high_dpi;
 pub(crate) mod monitor;
+pub(crate) mod resource_tracker;
+pub(crate) mod build_profiler;
+pub(crate) mod ui_thread;
+pub(crate) mod command_line;
+pub mod keyboard;
+pub(crate) mod resize;
+pub(crate) mod glyph;
+
+#[cfg(any(feature = "hex-view", feature = "diff-view", feature = "terminal-view", feature = "document-tabs", feature = "panel-stack"))]
+pub(crate) mod paint_helper;
 
 #[cfg(feature = "menu")]
 pub(crate) mod menu;
@@ -30,6 +40,108 @@ pub(crate) mod richedit;
 #[cfg(feature = "plotting")]
 pub(crate) mod plotters_d2d;
 
+#[cfg(feature = "mdi")]
+pub(crate) mod mdi;
+
+#[cfg(feature = "chart")]
+pub(crate) mod chart_renderer;
+
+#[cfg(feature = "markdown-view")]
+pub(crate) mod markdown;
+
+#[cfg(feature = "markdown-view")]
+pub(crate) mod markdown_renderer;
+
+#[cfg(feature = "dark-mode")]
+pub(crate) mod dark_mode;
+
+#[cfg(feature = "dirty-tracker")]
+pub(crate) mod dirty_tracker;
+
+#[cfg(feature = "backdrop")]
+pub(crate) mod backdrop;
+
+#[cfg(feature = "file-association")]
+pub mod file_assoc;
+
+#[cfg(feature = "single-instance")]
+pub(crate) mod single_instance;
+
+#[cfg(feature = "window-placement")]
+pub(crate) mod window_placement;
+
+#[cfg(feature = "window-capture")]
+pub(crate) mod capture;
+
+#[cfg(feature = "monkey-test")]
+pub(crate) mod monkey;
+
+#[cfg(feature = "document-tabs")]
+pub(crate) mod document_tabs;
+
+#[cfg(feature = "window-snap")]
+pub(crate) mod window_snap;
+
+#[cfg(feature = "hex-view")]
+pub(crate) mod hex_view;
+
+#[cfg(feature = "process")]
+pub mod process;
+
+#[cfg(feature = "raw-input")]
+pub mod raw_input;
+
+#[cfg(feature = "shell")]
+pub mod shell;
+
+#[cfg(feature = "known-folders")]
+pub mod known_folders;
+
+#[cfg(feature = "gdiplus")]
+pub mod gdiplus;
+
+#[cfg(feature = "async-runtime")]
+pub(crate) mod async_runtime;
+
+#[cfg(feature = "drag-drop")]
+pub(crate) mod drag_drop;
+
+#[cfg(feature = "local-drag")]
+pub(crate) mod local_drag;
+
+#[cfg(feature = "svg")]
+pub(crate) mod svg;
+
+#[cfg(feature = "touch")]
+pub(crate) mod touch;
+
+#[cfg(feature = "pointer")]
+pub(crate) mod pointer;
+
+#[cfg(feature = "animator")]
+pub(crate) mod animation;
+
+#[cfg(feature = "jump-list")]
+pub(crate) mod jump_list;
+
+#[cfg(feature = "kinetic-scroll")]
+pub(crate) mod kinetic_scroll;
+
+#[cfg(feature = "taskbar")]
+pub(crate) mod taskbar;
+
+#[cfg(feature = "accelerator-table")]
+pub(crate) mod accelerator;
+
+#[cfg(feature = "number-slider")]
+pub(crate) mod number_slider;
+
+#[cfg(feature = "panel-stack")]
+pub(crate) mod panel_stack;
+
+#[cfg(feature = "tab-order")]
+pub(crate) mod tab_order;
+
 use std::{fs, mem, ptr};
 use crate::errors::NwgError;
 
@@ -38,19 +150,52 @@ use winapi::um::winuser::{IsDialogMessageW, GetAncestor, TranslateMessage, Dispa
 
 /**
     Dispatch system events in the current thread. This method will pause the thread until there are events to process.
+
+    With the `resource-tracking` feature, the handles created while dispatching the events that are still alive
+    when this function returns are reported on stderr.
 */
 pub fn dispatch_thread_events() {
+    dispatch_thread_events_with_exit_code();
+}
+
+/**
+    Dispatch system events in the current thread until `nwg::quit` or `stop_thread_dispatch` is called, and returns the exit code
+    passed to `nwg::quit` (`0` for `stop_thread_dispatch`).
+
+    Before returning, the top level windows of the thread receive the `OnAppExit` event.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn run() {
+        // ... build the UI ...
+        let code = nwg::dispatch_thread_events_with_exit_code();
+        std::process::exit(code);
+    }
+    ```
+*/
+pub fn dispatch_thread_events_with_exit_code() -> i32 {
     use winapi::um::winuser::MSG;
     use winapi::um::winuser::GetMessageW;
 
+    let _leaks = resource_tracker::LeakReport::start();
+
     unsafe {
         let mut msg: MSG = mem::zeroed();
         while GetMessageW(&mut msg, ptr::null_mut(), 0, 0) != 0 {
+            if translate_accelerator(&mut msg) || translate_mdi_accelerator(&mut msg) || translate_tab_navigation(&mut msg) {
+                continue;
+            }
+
             if IsDialogMessageW(GetAncestor(msg.hwnd, GA_ROOT), &mut msg) == 0 {
                 TranslateMessage(&msg); 
                 DispatchMessageW(&msg); 
             }
         }
+
+        let exit_code = msg.wParam as i32;
+        notify_app_exit(exit_code);
+        exit_code
     }
 }
 
@@ -65,11 +210,13 @@ pub fn dispatch_thread_events_with_callback<F>(mut cb: F)
     use winapi::um::winuser::MSG;
     use winapi::um::winuser::{PeekMessageW, PM_REMOVE, WM_QUIT};
 
+    let _leaks = resource_tracker::LeakReport::start();
+
     unsafe {
         let mut msg: MSG = mem::zeroed();
         while msg.message != WM_QUIT {
             let has_message = PeekMessageW(&mut msg, ptr::null_mut(), 0, 0, PM_REMOVE) != 0;
-            if has_message {
+            if has_message && !(translate_accelerator(&mut msg) || translate_mdi_accelerator(&mut msg) || translate_tab_navigation(&mut msg)) {
                 if IsDialogMessageW(GetAncestor(msg.hwnd, GA_ROOT), &mut msg) == 0 {
                     TranslateMessage(&msg); 
                     DispatchMessageW(&msg); 
@@ -78,6 +225,8 @@ pub fn dispatch_thread_events_with_callback<F>(mut cb: F)
 
             cb();
         }
+
+        notify_app_exit(msg.wParam as i32);
     }
 }
 
@@ -91,6 +240,53 @@ pub fn stop_thread_dispatch() {
   unsafe { PostMessageW(ptr::null_mut(), WM_QUIT, 0, 0) };
 }
 
+/**
+    Ask the application to exit. Breaks the events loop of the main UI thread (the thread that called `nwg::init`),
+    even if this function is called from another thread.
+
+    `dispatch_thread_events_with_exit_code` then returns `exit_code`, after sending the `OnAppExit` event to the top level windows.
+    Secondary UI threads started with `spawn_ui_thread` must be stopped with `UiThread::stop`.
+*/
+pub fn quit(exit_code: i32) {
+    use winapi::um::winuser::{PostQuitMessage, PostThreadMessageW, WM_QUIT};
+    use winapi::um::processthreadsapi::GetCurrentThreadId;
+
+    let main_thread = ui_thread::main_thread();
+
+    unsafe {
+        if main_thread == 0 || main_thread == GetCurrentThreadId() {
+            PostQuitMessage(exit_code);
+        } else {
+            PostThreadMessageW(main_thread, WM_QUIT, exit_code as usize, 0);
+        }
+    }
+}
+
+/// Sends `NWG_APP_EXIT` to the top level windows and to the message windows of the current thread
+unsafe fn notify_app_exit(exit_code: i32) {
+    use winapi::um::winuser::{EnumThreadWindows, FindWindowExW, HWND_MESSAGE};
+    use winapi::um::processthreadsapi::GetCurrentThreadId;
+    use winapi::shared::windef::HWND;
+    use winapi::shared::minwindef::{BOOL, LPARAM};
+
+    unsafe extern "system" fn send_app_exit(hwnd: HWND, exit_code: LPARAM) -> BOOL {
+        window_helper::send_message(hwnd, window_helper::NWG_APP_EXIT, exit_code as usize, 0);
+        1
+    }
+
+    let thread = GetCurrentThreadId();
+    EnumThreadWindows(thread, Some(send_app_exit), exit_code as LPARAM);
+
+    // Message only windows are not enumerated by `EnumThreadWindows`
+    let mut message_window = FindWindowExW(HWND_MESSAGE, ptr::null_mut(), ptr::null(), ptr::null());
+    while !message_window.is_null() {
+        if ui_thread::window_thread(message_window) == thread {
+            send_app_exit(message_window, exit_code as LPARAM);
+        }
+        message_window = FindWindowExW(HWND_MESSAGE, message_window, ptr::null(), ptr::null());
+    }
+}
+
 
 /**
   Enable the Windows visual style in the application without having to use a manifest
@@ -158,13 +354,13 @@ pub fn init_common_controls() -> Result<(), NwgError> {
     use winapi::um::libloaderapi::LoadLibraryW;
     use winapi::um::commctrl::{InitCommonControlsEx, INITCOMMONCONTROLSEX};
     use winapi::um::commctrl::{ICC_BAR_CLASSES, ICC_STANDARD_CLASSES, ICC_DATE_CLASSES, ICC_PROGRESS_CLASS,
-     ICC_TAB_CLASSES, ICC_TREEVIEW_CLASSES, ICC_LISTVIEW_CLASSES};
+     ICC_TAB_CLASSES, ICC_TREEVIEW_CLASSES, ICC_LISTVIEW_CLASSES, ICC_HOTKEY_CLASS};
     use winapi::shared::winerror::{S_OK, S_FALSE};
 
     unsafe {
         let mut classes = ICC_BAR_CLASSES | ICC_STANDARD_CLASSES;
 
-        if cfg!(feature = "datetime-picker") {
+        if cfg!(any(feature = "datetime-picker", feature = "month-calendar")) {
             classes |= ICC_DATE_CLASSES;
         }
 
@@ -172,6 +368,10 @@ pub fn init_common_controls() -> Result<(), NwgError> {
             classes |= ICC_PROGRESS_CLASS;
         }
 
+        if cfg!(feature = "hotkey") {
+            classes |= ICC_HOTKEY_CLASS;
+        }
+
         if cfg!(feature = "tabs") {
             classes |= ICC_TAB_CLASSES;
         }
@@ -201,9 +401,25 @@ pub fn init_common_controls() -> Result<(), NwgError> {
     tabs_init()?;
     extern_canvas_init()?;
     frame_init()?;
+    splitter_init()?;
+    scroll_panel_init()?;
+    image_viewer_init()?;
+    mdi_init()?;
+    chart_init()?;
+    document_tabs_init()?;
+    hex_view_init()?;
+    terminal_view_init()?;
+    diff_view_init()?;
+    markdown_view_init()?;
+    number_slider_init()?;
+    panel_stack_init()?;
+    animated_image_init()?;
     
     match unsafe { CoInitialize(ptr::null_mut()) } {
-        S_OK | S_FALSE => Ok(()),
+        S_OK | S_FALSE => {
+            ui_thread::set_process_initialized();
+            Ok(())
+        },
         _ => Err(NwgError::initialization("CoInitialize failed"))
     }
 }
@@ -226,3 +442,99 @@ fn frame_init() -> Result<(), NwgError> { window::create_frame_classes() }
 #[cfg(not(feature = "frame"))]
 fn frame_init() -> Result<(), NwgError> { Ok(()) }
 
+#[cfg(feature = "splitter")]
+fn splitter_init() -> Result<(), NwgError> { window::create_splitter_classes() }
+
+#[cfg(not(feature = "splitter"))]
+fn splitter_init() -> Result<(), NwgError> { Ok(()) }
+
+#[cfg(feature = "scroll-panel")]
+fn scroll_panel_init() -> Result<(), NwgError> { window::create_scroll_panel_classes() }
+
+#[cfg(not(feature = "scroll-panel"))]
+fn scroll_panel_init() -> Result<(), NwgError> { Ok(()) }
+
+#[cfg(feature = "image-viewer")]
+fn image_viewer_init() -> Result<(), NwgError> { window::create_image_viewer_classes() }
+
+#[cfg(not(feature = "image-viewer"))]
+fn image_viewer_init() -> Result<(), NwgError> { Ok(()) }
+
+#[cfg(feature = "mdi")]
+fn mdi_init() -> Result<(), NwgError> { mdi::create_mdi_classes() }
+
+#[cfg(not(feature = "mdi"))]
+fn mdi_init() -> Result<(), NwgError> { Ok(()) }
+
+#[cfg(feature = "chart")]
+fn chart_init() -> Result<(), NwgError> { window::create_chart_classes() }
+
+#[cfg(not(feature = "chart"))]
+fn chart_init() -> Result<(), NwgError> { Ok(()) }
+
+#[cfg(feature = "document-tabs")]
+fn document_tabs_init() -> Result<(), NwgError> { window::create_document_tabs_classes() }
+
+#[cfg(not(feature = "document-tabs"))]
+fn document_tabs_init() -> Result<(), NwgError> { Ok(()) }
+
+#[cfg(feature = "hex-view")]
+fn hex_view_init() -> Result<(), NwgError> { window::create_hex_view_classes() }
+
+#[cfg(not(feature = "hex-view"))]
+fn hex_view_init() -> Result<(), NwgError> { Ok(()) }
+
+#[cfg(feature = "terminal-view")]
+fn terminal_view_init() -> Result<(), NwgError> { window::create_terminal_view_classes() }
+
+#[cfg(not(feature = "terminal-view"))]
+fn terminal_view_init() -> Result<(), NwgError> { Ok(()) }
+
+#[cfg(feature = "diff-view")]
+fn diff_view_init() -> Result<(), NwgError> { window::create_diff_view_classes() }
+
+#[cfg(not(feature = "diff-view"))]
+fn diff_view_init() -> Result<(), NwgError> { Ok(()) }
+
+#[cfg(feature = "markdown-view")]
+fn markdown_view_init() -> Result<(), NwgError> { window::create_markdown_view_classes() }
+
+#[cfg(not(feature = "markdown-view"))]
+fn markdown_view_init() -> Result<(), NwgError> { Ok(()) }
+
+#[cfg(feature = "number-slider")]
+fn number_slider_init() -> Result<(), NwgError> { window::create_number_slider_classes() }
+
+#[cfg(not(feature = "number-slider"))]
+fn number_slider_init() -> Result<(), NwgError> { Ok(()) }
+
+#[cfg(feature = "panel-stack")]
+fn panel_stack_init() -> Result<(), NwgError> { window::create_panel_stack_classes() }
+
+#[cfg(not(feature = "panel-stack"))]
+fn panel_stack_init() -> Result<(), NwgError> { Ok(()) }
+
+#[cfg(feature = "animated-image")]
+fn animated_image_init() -> Result<(), NwgError> { window::create_animated_image_classes() }
+
+#[cfg(not(feature = "animated-image"))]
+fn animated_image_init() -> Result<(), NwgError> { Ok(()) }
+
+#[cfg(feature = "mdi")]
+unsafe fn translate_mdi_accelerator(msg: &mut winapi::um::winuser::MSG) -> bool { mdi::translate_mdi_accelerator(msg) }
+
+#[cfg(not(feature = "mdi"))]
+unsafe fn translate_mdi_accelerator(_msg: &mut winapi::um::winuser::MSG) -> bool { false }
+
+#[cfg(feature = "accelerator-table")]
+unsafe fn translate_accelerator(msg: &mut winapi::um::winuser::MSG) -> bool { accelerator::translate_accelerator(msg) }
+
+#[cfg(not(feature = "accelerator-table"))]
+unsafe fn translate_accelerator(_msg: &mut winapi::um::winuser::MSG) -> bool { false }
+
+#[cfg(feature = "tab-order")]
+unsafe fn translate_tab_navigation(msg: &mut winapi::um::winuser::MSG) -> bool { tab_order::translate_tab_navigation(msg) }
+
+#[cfg(not(feature = "tab-order"))]
+unsafe fn translate_tab_navigation(_msg: &mut winapi::um::winuser::MSG) -> bool { false }
+
@@ -1,8 +1,57 @@
-use winapi::shared::windef::HWND;
-use winapi::um::winuser::{GetSystemMetrics, MonitorFromWindow, GetMonitorInfoW, MONITORINFO,
-    SM_CXSCREEN, SM_CYSCREEN, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN, MONITOR_DEFAULTTONEAREST};
+use winapi::shared::windef::{HWND, HMONITOR, RECT, POINT};
+use winapi::um::winuser::{GetSystemMetrics, MonitorFromWindow, GetMonitorInfoW, MONITORINFO, MONITORINFOEXW,
+    SM_CXSCREEN, SM_CYSCREEN, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN, MONITOR_DEFAULTTONEAREST, MONITORINFOF_PRIMARY};
+use super::base_helper::to_utf16;
 use crate::ControlHandle;
-use std::mem;
+use std::ffi::OsString;
+use std::os::windows::ffi::OsStringExt;
+use std::{mem, ptr};
+
+
+/**
+    A display monitor. Returned by `Monitor::all`, `Monitor::from_window` and `Monitor::from_point`.
+
+    All the rectangles are [left, top, right, bottom] in virtual-screen coordinates. The monitors that are not the
+    primary monitor can have negative coordinates.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MonitorInfo {
+    /// The device name of the monitor (ex: `\\.\DISPLAY1`). Stays the same while the monitor is connected.
+    pub name: String,
+
+    /// The bounds of the monitor
+    pub rect: [i32; 4],
+
+    /// The bounds of the monitor, excluding the taskbar and the docked toolbars
+    pub work_area: [i32; 4],
+
+    /// The effective DPI of the monitor. On systems older than Windows 8.1, all the monitors have the DPI of the primary monitor.
+    pub dpi: u32,
+
+    /// If the monitor is the primary monitor
+    pub primary: bool,
+}
+
+impl MonitorInfo {
+
+    /// Returns the size of the monitor, in pixels
+    pub fn size(&self) -> (u32, u32) {
+        let [left, top, right, bottom] = self.rect;
+        ((right - left) as u32, (bottom - top) as u32)
+    }
+
+    /// Returns the size of the work area of the monitor, in pixels
+    pub fn work_size(&self) -> (u32, u32) {
+        let [left, top, right, bottom] = self.work_area;
+        ((right - left) as u32, (bottom - top) as u32)
+    }
+
+    /// Returns the scale factor of the monitor (1.0 at 96 DPI)
+    pub fn scale_factor(&self) -> f64 {
+        self.dpi as f64 / 96.0
+    }
+
+}
 
 /**
     Expose basic properties of the monitor(s) on the system and the virtual screen.
@@ -82,6 +131,82 @@ impl Monitor {
         ]
     }
 
+    /// Returns all the monitors connected to the system. The primary monitor is not always the first one.
+    pub fn all() -> Vec<MonitorInfo> {
+        use winapi::um::winuser::EnumDisplayMonitors;
+        use winapi::shared::windef::{HDC, LPRECT};
+        use winapi::shared::minwindef::{BOOL, LPARAM};
+
+        unsafe extern "system" fn enum_monitor(monitor: HMONITOR, _hdc: HDC, _rect: LPRECT, data: LPARAM) -> BOOL {
+            let monitors = &mut *(data as *mut Vec<HMONITOR>);
+            monitors.push(monitor);
+            1
+        }
+
+        let mut monitors: Vec<HMONITOR> = Vec::new();
+        unsafe {
+            EnumDisplayMonitors(ptr::null_mut(), ptr::null(), Some(enum_monitor), &mut monitors as *mut Vec<HMONITOR> as LPARAM);
+        }
+
+        monitors.into_iter().filter_map(|m| monitor_info(m)).collect()
+    }
+
+    /// Returns the primary monitor
+    pub fn primary() -> MonitorInfo {
+        use winapi::um::winuser::{MonitorFromPoint, MONITOR_DEFAULTTOPRIMARY};
+
+        let monitor = unsafe { MonitorFromPoint(POINT { x: 0, y: 0 }, MONITOR_DEFAULTTOPRIMARY) };
+        monitor_info(monitor).expect("Failed to read the primary monitor")
+    }
+
+    /// Returns the monitor that has the largest area of intersection with a window, or the nearest monitor if the window is off screen.
+    /// Panics if `window` is not a window like control.
+    pub fn from_window<H: Into<ControlHandle>>(window: H) -> MonitorInfo {
+        let handle = window.into().hwnd().expect("Window to be a window-like control");
+        let monitor = unsafe { MonitorFromWindow(handle, MONITOR_DEFAULTTONEAREST) };
+        monitor_info(monitor).expect("Failed to read the monitor of the window")
+    }
+
+    /// Returns the monitor that contains a point in virtual-screen coordinates, or the nearest monitor if the point is off screen.
+    pub fn from_point(x: i32, y: i32) -> MonitorInfo {
+        use winapi::um::winuser::MonitorFromPoint;
+
+        let monitor = unsafe { MonitorFromPoint(POINT { x, y }, MONITOR_DEFAULTTONEAREST) };
+        monitor_info(monitor).expect("Failed to read the monitor of the point")
+    }
+
+    /// Centers a window in the work area of its monitor, so that the taskbar does not cover it.
+    /// Panics if `window` is not a window like control.
+    pub fn center_window<H: Into<ControlHandle>>(window: H) {
+        let handle = window.into().hwnd().expect("Window to be a window-like control");
+        let work = Self::from_window(handle).work_area;
+
+        unsafe {
+            let rect = window_rect(handle);
+            let (width, height) = (rect.right - rect.left, rect.bottom - rect.top);
+            let x = work[0] + ((work[2] - work[0]) - width) / 2;
+            let y = work[1] + ((work[3] - work[1]) - height) / 2;
+
+            move_window(handle, fit_rect(RECT { left: x, top: y, right: x + width, bottom: y + height }, work));
+        }
+    }
+
+    /// Moves a window inside the work area of its monitor. If the window is bigger than the work area, it is shrunk.
+    /// Panics if `window` is not a window like control.
+    pub fn snap_to_work_area<H: Into<ControlHandle>>(window: H) {
+        let handle = window.into().hwnd().expect("Window to be a window-like control");
+        let work = Self::from_window(handle).work_area;
+
+        unsafe {
+            let rect = window_rect(handle);
+            let fitted = fit_rect(rect, work);
+            let moved = fitted.left != rect.left || fitted.top != rect.top || fitted.right != rect.right || fitted.bottom != rect.bottom;
+            if moved {
+                move_window(handle, fitted);
+            }
+        }
+    }
+
     /// Returns the primary monitor width in pixel
     /// Use `Monitor::virtual_width` to get the dimensions of the virtual screen
     pub fn width() -> i32 {
@@ -115,3 +240,81 @@ impl Monitor {
     }
 
 }
+
+/// Reads the information of a monitor. Returns `None` if the monitor was disconnected.
+pub(crate) fn monitor_info(monitor: HMONITOR) -> Option<MonitorInfo> {
+    if monitor.is_null() {
+        return None;
+    }
+
+    unsafe {
+        let mut info: MONITORINFOEXW = mem::zeroed();
+        info.cbSize = mem::size_of::<MONITORINFOEXW>() as u32;
+        if GetMonitorInfoW(monitor, &mut info as *mut MONITORINFOEXW as _) == 0 {
+            return None;
+        }
+
+        let length = info.szDevice.iter().position(|&c| c == 0).unwrap_or(info.szDevice.len());
+        let m = info.rcMonitor;
+        let w = info.rcWork;
+
+        Some(MonitorInfo {
+            name: OsString::from_wide(&info.szDevice[..length]).to_string_lossy().into_owned(),
+            rect: [m.left, m.top, m.right, m.bottom],
+            work_area: [w.left, w.top, w.right, w.bottom],
+            dpi: monitor_dpi(monitor),
+            primary: info.dwFlags & MONITORINFOF_PRIMARY == MONITORINFOF_PRIMARY,
+        })
+    }
+}
+
+/// Shrinks `rect` to the size of the area `work`, then moves it inside
+pub(crate) fn fit_rect(rect: RECT, work: [i32; 4]) -> RECT {
+    let [left, top, right, bottom] = work;
+    let width = (rect.right - rect.left).min(right - left);
+    let height = (rect.bottom - rect.top).min(bottom - top);
+
+    let x = rect.left.max(left).min(right - width);
+    let y = rect.top.max(top).min(bottom - height);
+
+    RECT { left: x, top: y, right: x + width, bottom: y + height }
+}
+
+/// `GetDpiForMonitor` is loaded dynamically because shcore.dll does not exist before Windows 8.1
+unsafe fn monitor_dpi(monitor: HMONITOR) -> u32 {
+    use winapi::um::libloaderapi::{LoadLibraryW, GetProcAddress};
+    use winapi::shared::winerror::S_OK;
+
+    type GetDpiForMonitor = unsafe extern "system" fn(HMONITOR, u32, *mut u32, *mut u32) -> i32;
+    const MDT_EFFECTIVE_DPI: u32 = 0;
+
+    let name = to_utf16("shcore.dll");
+    let module = LoadLibraryW(name.as_ptr());
+    let f = match module.is_null() {
+        true => ptr::null_mut(),
+        false => GetProcAddress(module, "GetDpiForMonitor\0".as_ptr() as *const i8)
+    };
+
+    if !f.is_null() {
+        let get_dpi_for_monitor: GetDpiForMonitor = mem::transmute(f);
+        let (mut x, mut y) = (0, 0);
+        if get_dpi_for_monitor(monitor, MDT_EFFECTIVE_DPI, &mut x, &mut y) == S_OK {
+            return x;
+        }
+    }
+
+    super::high_dpi::dpi() as u32
+}
+
+unsafe fn window_rect(handle: HWND) -> RECT {
+    use winapi::um::winuser::GetWindowRect;
+
+    let mut rect: RECT = mem::zeroed();
+    GetWindowRect(handle, &mut rect);
+    rect
+}
+
+unsafe fn move_window(handle: HWND, rect: RECT) {
+    use winapi::um::winuser::{SetWindowPos, SWP_NOZORDER, SWP_NOACTIVATE};
+    SetWindowPos(handle, ptr::null_mut(), rect.left, rect.top, rect.right - rect.left, rect.bottom - rect.top, SWP_NOZORDER | SWP_NOACTIVATE);
+}
@@ -0,0 +1,272 @@
+/*!
+    Random input stress testing ("monkey testing") of a window.
+*/
+use winapi::shared::windef::{HWND, RECT};
+use winapi::shared::minwindef::{LPARAM, BOOL};
+use super::window_helper as wh;
+use crate::ControlHandle;
+use std::{mem, panic, sync::{Arc, Mutex}};
+use std::time::{Duration, Instant};
+
+/// Keys sent by the monkey. The keys that usually close a window (ESCAPE, ALT+F4) are not included.
+const MONKEY_KEYS: &'static [i32] = &[
+    0x08, 0x09, 0x0D, 0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28, 0x2E,  // Backspace, tab, enter, space, navigation, delete
+    0x30, 0x31, 0x32, 0x39,  // Digits
+    0x41, 0x42, 0x43, 0x5A,  // Letters
+];
+
+/// Smallest size of the window when it is resized by the monkey
+const MIN_SIZE: i32 = 50;
+
+
+/// An input sent by `monkey`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MonkeyAction {
+    /// A click at a position in the client area of a control. `control` is the index of the control in the children of the window (0 is the window itself).
+    Click { control: usize, x: i32, y: i32, right: bool },
+
+    /// A key pressed and released in the control with the keyboard focus
+    Key(i32),
+
+    /// The window was resized
+    Resize(i32, i32),
+
+    /// The keyboard focus was moved to a control. `control` is the index of the control in the children of the window (0 is the window itself).
+    Focus(usize),
+}
+
+/// The result of `monkey`
+#[derive(Clone, Debug)]
+pub struct MonkeyReport {
+    /// The seed used to generate the inputs
+    pub seed: u64,
+
+    /// The inputs sent to the window, in order
+    pub actions: Vec<MonkeyAction>,
+
+    /// If the window was destroyed before the end of the test (ex: a button closed it)
+    pub window_destroyed: bool,
+}
+
+/**
+    Sends random clicks, key presses, resizes and focus changes to `window` and its children for `duration`.
+    The events are processed by the event handlers of the application as if a user had generated them.
+
+    The inputs are generated from `seed`, so a failure can be reproduced by running the test again with the same seed.
+    If an event handler panics (including the panics of NWG when a control is misused), the seed and the last inputs are
+    printed before the panic message.
+
+    The function must be called from the thread of the window, after the UI was built. The window should be visible.
+
+    Requires the `monkey-test` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+    use std::time::Duration;
+
+    fn stress(app: &nwg::Window) {
+        let report = nwg::test::monkey(app, Duration::from_secs(10), 42);
+        println!("{} inputs sent", report.actions.len());
+    }
+    ```
+*/
+pub fn monkey<C: Into<ControlHandle>>(window: C, duration: Duration, seed: u64) -> MonkeyReport {
+    let handle = window.into().hwnd().expect("Window to be a window-like control");
+
+    let actions = Arc::new(Mutex::new(Vec::new()));
+    let previous_hook = Arc::new(panic::take_hook());
+
+    // Prints the seed and the last actions when a handler panics
+    let hook_actions = actions.clone();
+    let hook_previous = previous_hook.clone();
+    panic::set_hook(Box::new(move |info| {
+        if let Ok(actions) = hook_actions.try_lock() {
+            let last: Vec<&MonkeyAction> = actions.iter().rev().take(10).collect();
+            eprintln!("monkey: panic after {} inputs (seed {}). Last inputs, most recent first: {:#?}", actions.len(), seed, last);
+        }
+
+        (*hook_previous)(info);
+    }));
+
+    let mut rng = XorShift::new(seed);
+    let start = Instant::now();
+    let mut window_destroyed = false;
+
+    while start.elapsed() < duration {
+        if !wh::window_valid(handle) {
+            window_destroyed = true;
+            break;
+        }
+
+        let controls = children(handle);
+        let action = random_action(&mut rng, handle, &controls);
+        actions.lock().unwrap().push(action);
+
+        let quit = unsafe {
+            send_action(handle, &controls, action);
+            pump_messages()
+        };
+
+        if quit {
+            break;
+        }
+    }
+
+    drop(panic::take_hook());
+    panic::set_hook(Box::new(move |info| (*previous_hook)(info)));
+
+    let actions = actions.lock().unwrap().clone();
+    MonkeyReport { seed, actions, window_destroyed }
+}
+
+fn random_action(rng: &mut XorShift, window: HWND, controls: &[HWND]) -> MonkeyAction {
+    match rng.below(10) {
+        0..=4 => {
+            let control = rng.below(controls.len() as u32) as usize;
+            let (w, h) = unsafe { client_size(controls[control]) };
+            MonkeyAction::Click {
+                control,
+                x: rng.below(w.max(1) as u32) as i32,
+                y: rng.below(h.max(1) as u32) as i32,
+                right: rng.below(4) == 0,
+            }
+        },
+        5..=7 => MonkeyAction::Key(MONKEY_KEYS[rng.below(MONKEY_KEYS.len() as u32) as usize]),
+        8 => {
+            let (max_w, max_h) = unsafe { work_area_size(window) };
+            MonkeyAction::Resize(
+                MIN_SIZE + rng.below((max_w - MIN_SIZE).max(1) as u32) as i32,
+                MIN_SIZE + rng.below((max_h - MIN_SIZE).max(1) as u32) as i32,
+            )
+        },
+        _ => MonkeyAction::Focus(rng.below(controls.len() as u32) as usize),
+    }
+}
+
+unsafe fn send_action(window: HWND, controls: &[HWND], action: MonkeyAction) {
+    use winapi::um::winuser::{SendMessageW, GetFocus, SetFocus, SetWindowPos, MapVirtualKeyW, MAPVK_VK_TO_VSC,
+        WM_LBUTTONDOWN, WM_LBUTTONUP, WM_RBUTTONDOWN, WM_RBUTTONUP, WM_KEYDOWN, WM_KEYUP, WM_CHAR, MK_LBUTTON, MK_RBUTTON,
+        SWP_NOMOVE, SWP_NOZORDER, SWP_NOACTIVATE};
+
+    match action {
+        MonkeyAction::Click { control, x, y, right } => {
+            let target = controls[control];
+            let pos = (((y as u16) as u32) << 16 | (x as u16) as u32) as LPARAM;
+            let (down, up, key) = match right {
+                true => (WM_RBUTTONDOWN, WM_RBUTTONUP, MK_RBUTTON),
+                false => (WM_LBUTTONDOWN, WM_LBUTTONUP, MK_LBUTTON)
+            };
+
+            SendMessageW(target, down, key, pos);
+            if wh::window_valid(target) {
+                SendMessageW(target, up, 0, pos);
+            }
+        },
+        MonkeyAction::Key(key) => {
+            let focus = GetFocus();
+            let target = match focus.is_null() {
+                true => window,
+                false => focus
+            };
+
+            let scan = MapVirtualKeyW(key as u32, MAPVK_VK_TO_VSC) as LPARAM;
+            SendMessageW(target, WM_KEYDOWN, key as usize, 1 | (scan << 16));
+
+            let is_char = (0x30..=0x5A).contains(&key) || key == 0x20 || key == 0x0D || key == 0x08 || key == 0x09;
+            if is_char && wh::window_valid(target) {
+                SendMessageW(target, WM_CHAR, key as usize, 1 | (scan << 16));
+            }
+
+            if wh::window_valid(target) {
+                SendMessageW(target, WM_KEYUP, key as usize, 1 | (scan << 16) | (0xC000_0000u32 as LPARAM));
+            }
+        },
+        MonkeyAction::Resize(w, h) => {
+            SetWindowPos(window, std::ptr::null_mut(), 0, 0, w, h, SWP_NOMOVE | SWP_NOZORDER | SWP_NOACTIVATE);
+        },
+        MonkeyAction::Focus(control) => {
+            SetFocus(controls[control]);
+        }
+    }
+}
+
+/// Dispatches the messages posted by the inputs (notifications, timers, redraws) without blocking.
+/// Returns `true` if the application asked to quit.
+unsafe fn pump_messages() -> bool {
+    use winapi::um::winuser::{PeekMessageW, TranslateMessage, DispatchMessageW, MSG, PM_REMOVE, WM_QUIT, PostQuitMessage};
+
+    let mut msg: MSG = mem::zeroed();
+    while PeekMessageW(&mut msg, std::ptr::null_mut(), 0, 0, PM_REMOVE) != 0 {
+        if msg.message == WM_QUIT {
+            // Keep the quit request for the application event loop
+            PostQuitMessage(msg.wParam as i32);
+            return true;
+        }
+
+        TranslateMessage(&msg);
+        DispatchMessageW(&msg);
+    }
+
+    false
+}
+
+/// The window followed by all its visible and enabled children
+fn children(window: HWND) -> Vec<HWND> {
+    use winapi::um::winuser::{EnumChildWindows, IsWindowVisible, IsWindowEnabled};
+
+    unsafe extern "system" fn enum_child(hwnd: HWND, data: LPARAM) -> BOOL {
+        if IsWindowVisible(hwnd) != 0 && IsWindowEnabled(hwnd) != 0 {
+            let controls = &mut *(data as *mut Vec<HWND>);
+            controls.push(hwnd);
+        }
+        1
+    }
+
+    let mut controls = vec![window];
+    unsafe { EnumChildWindows(window, Some(enum_child), &mut controls as *mut Vec<HWND> as LPARAM); }
+    controls
+}
+
+unsafe fn client_size(handle: HWND) -> (i32, i32) {
+    use winapi::um::winuser::GetClientRect;
+
+    let mut rect: RECT = mem::zeroed();
+    GetClientRect(handle, &mut rect);
+    (rect.right - rect.left, rect.bottom - rect.top)
+}
+
+unsafe fn work_area_size(handle: HWND) -> (i32, i32) {
+    let [left, top, right, bottom] = crate::Monitor::from_window(ControlHandle::Hwnd(handle)).work_area;
+    (right - left, bottom - top)
+}
+
+/// Small deterministic generator, so that a seed produces the same inputs on every system
+struct XorShift(u64);
+
+impl XorShift {
+
+    fn new(seed: u64) -> XorShift {
+        // The state of xorshift must not be 0
+        match seed ^ 0x9E37_79B9_7F4A_7C15 {
+            0 => XorShift(0x9E37_79B9_7F4A_7C15),
+            state => XorShift(state)
+        }
+    }
+
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    fn below(&mut self, max: u32) -> u32 {
+        match max {
+            0 => 0,
+            _ => (self.next() % max as u64) as u32
+        }
+    }
+
+}
@@ -0,0 +1,14 @@
+/*!
+    Notifications sent by the number slider control to its parent.
+*/
+use winapi::um::winuser::NMHDR;
+
+/// Notification code sent when the value of the slider changes
+pub(crate) const NUMBER_SLIDER_VALUE_CHANGED: u32 = 1;
+
+/// The WM_NOTIFY data sent by the number slider to its parent
+#[repr(C)]
+pub(crate) struct NMNUMBERSLIDER {
+    pub hdr: NMHDR,
+    pub value: f64,
+}
@@ -0,0 +1,119 @@
+/*!
+    Helpers shared by the custom drawn controls (hex view, diff view, terminal view, document tabs, panel stack).
+*/
+use winapi::shared::windef::{HWND, HFONT, HDC, RECT};
+use crate::win32::high_dpi::logical_to_physical;
+use crate::win32::resource_tracker::{self as rt, ResourceKind};
+use std::{mem, ptr};
+
+
+/// Converts a size in logical pixels to physical pixels
+pub(crate) fn px(v: i32) -> i32 {
+    unsafe { logical_to_physical(v, 0).0 }
+}
+
+/// Invalidates the whole client area of a control without erasing the background
+pub(crate) fn redraw(handle: HWND) {
+    use winapi::um::winuser::InvalidateRect;
+    unsafe { InvalidateRect(handle, ptr::null(), 0); }
+}
+
+/// Returns the size of the client area of a control in physical pixels
+pub(crate) fn client_size(handle: HWND) -> (i32, i32) {
+    use winapi::um::winuser::GetClientRect;
+
+    unsafe {
+        let mut rect: RECT = mem::zeroed();
+        GetClientRect(handle, &mut rect);
+        (rect.right - rect.left, rect.bottom - rect.top)
+    }
+}
+
+/// Creates the default monospaced font of the text views. The caller must delete it with `delete_font`.
+pub(crate) fn monospace_font() -> HFONT {
+    use winapi::um::wingdi::{CreateFontW, FW_NORMAL, DEFAULT_CHARSET, OUT_DEFAULT_PRECIS, CLIP_DEFAULT_PRECIS, CLEARTYPE_QUALITY, FIXED_PITCH, FF_MODERN};
+    use crate::win32::base_helper::to_utf16;
+
+    let family = to_utf16("Consolas");
+    let font = unsafe {
+        CreateFontW(-px(13), 0, 0, 0, FW_NORMAL, 0, 0, 0, DEFAULT_CHARSET, OUT_DEFAULT_PRECIS, CLIP_DEFAULT_PRECIS,
+            CLEARTYPE_QUALITY, FIXED_PITCH | FF_MODERN, family.as_ptr())
+    };
+
+    rt::track(ResourceKind::Font, font);
+    font
+}
+
+/// Deletes a font created by the control. Does nothing if `font` is null.
+pub(crate) fn delete_font(font: HFONT) {
+    use winapi::um::wingdi::DeleteObject;
+
+    if !font.is_null() {
+        rt::untrack(font);
+        unsafe { DeleteObject(font as _); }
+    }
+}
+
+/// Returns the stock GUI font. Stock objects must not be deleted.
+pub(crate) fn gui_font() -> HFONT {
+    use winapi::um::wingdi::{GetStockObject, DEFAULT_GUI_FONT};
+    unsafe { GetStockObject(DEFAULT_GUI_FONT as _) as HFONT }
+}
+
+/// Returns the font set by the user, or `default` if no font was set
+pub(crate) fn font_or(font: HFONT, default: HFONT) -> HFONT {
+    match font.is_null() {
+        true => default,
+        false => font
+    }
+}
+
+pub(crate) fn rgb(color: [u8; 3]) -> u32 {
+    use winapi::um::wingdi::RGB;
+    RGB(color[0], color[1], color[2])
+}
+
+/// Fills `rect` with a solid color
+pub(crate) unsafe fn fill(dc: HDC, rect: &RECT, color: u32) {
+    use winapi::um::winuser::FillRect;
+    use winapi::um::wingdi::{CreateSolidBrush, DeleteObject};
+
+    let brush = CreateSolidBrush(color);
+    FillRect(dc, rect, brush);
+    DeleteObject(brush as _);
+}
+
+/**
+    Handles a `WM_PAINT` message: `paint` draws the control in a memory bitmap the size of the client area,
+    with `font` selected, and the result is copied on the screen. This avoids the flickering of the controls that are repainted often.
+
+    The parameters of `paint` are the memory device context and the width and height of the client area.
+*/
+pub(crate) fn paint_buffered<F>(handle: HWND, font: HFONT, paint: F)
+    where F: FnOnce(HDC, i32, i32)
+{
+    use winapi::um::winuser::{BeginPaint, EndPaint, PAINTSTRUCT};
+    use winapi::um::wingdi::{CreateCompatibleDC, CreateCompatibleBitmap, SelectObject, DeleteDC, DeleteObject, BitBlt, SRCCOPY};
+
+    unsafe {
+        let mut ps: PAINTSTRUCT = mem::zeroed();
+        let hdc = BeginPaint(handle, &mut ps);
+        let (width, height) = client_size(handle);
+
+        let buffer_dc = CreateCompatibleDC(hdc);
+        let buffer = CreateCompatibleBitmap(hdc, width.max(1), height.max(1));
+        let old_buffer = SelectObject(buffer_dc, buffer as _);
+        let old_font = SelectObject(buffer_dc, font as _);
+
+        paint(buffer_dc, width, height);
+
+        BitBlt(hdc, 0, 0, width, height, buffer_dc, 0, 0, SRCCOPY);
+
+        SelectObject(buffer_dc, old_font);
+        SelectObject(buffer_dc, old_buffer);
+        DeleteObject(buffer as _);
+        DeleteDC(buffer_dc);
+
+        EndPaint(handle, &ps);
+    }
+}
@@ -0,0 +1,15 @@
+/*!
+    Notifications sent by the panel stack control to its parent.
+*/
+use winapi::um::winuser::NMHDR;
+
+/// Notification code sent when the user drops a panel at a new index
+pub(crate) const PANEL_STACK_REORDER: u32 = 1;
+
+/// The WM_NOTIFY data sent by the panel stack to its parent
+#[repr(C)]
+pub(crate) struct NMPANELSTACK {
+    pub hdr: NMHDR,
+    pub index: usize,
+    pub previous: usize,
+}
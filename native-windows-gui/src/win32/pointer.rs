@@ -0,0 +1,167 @@
+/*!
+    Pointer input (pen, touch, touchpad) with the pressure and the tilt of the pen.
+
+    Since Windows 8, the pens and the touch screens send `WM_POINTER*` messages to the windows. NWG raises `OnPointerDown`,
+    `OnPointerUpdate` and `OnPointerUp` for them, then lets the system translate them into the usual mouse events,
+    so the controls that do not handle the pointer events keep working. The mouse only sends pointer messages if
+    the application calls `EnableMouseInPointer`.
+
+    Requires the `pointer` feature.
+*/
+use winapi::shared::minwindef::{WPARAM, LOWORD};
+use winapi::shared::windef::HWND;
+use winapi::um::winuser::{ScreenToClient, POINTER_INFO, POINTER_PEN_INFO, POINTER_TOUCH_INFO};
+use std::mem;
+
+/// The maximum pressure reported by the pens and the touch screens
+const MAX_PRESSURE: f32 = 1024.0;
+
+
+/// The device that controls a pointer
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PointerType {
+    Touch,
+    Pen,
+    Mouse,
+    Touchpad,
+    Other,
+}
+
+/// A pointer event. See `Event::OnPointerDown`, `Event::OnPointerUpdate` and `Event::OnPointerUp`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PointerData {
+    /// Identifies the pointer. A touch contact has a new id each time it touches the screen, a pen keeps the same id.
+    pub id: u32,
+
+    /// The device that controls the pointer
+    pub kind: PointerType,
+
+    /// The position of the pointer, in physical pixels, relative to the client area of the window
+    pub position: (i32, i32),
+
+    /// The pressure of the pen or of the touch contact, from `0.0` to `1.0`. `None` if the device does not report the pressure.
+    pub pressure: Option<f32>,
+
+    /// The tilt of the pen along the X and the Y axes, in degrees (from -90 to 90). `(0, 0)` if the device does not report the tilt.
+    pub tilt: (i32, i32),
+
+    /// The clockwise rotation of the pen, in degrees (from 0 to 359). `None` if the device does not report the rotation.
+    pub rotation: Option<u32>,
+
+    /// If the pointer touches the surface (ex: the tip of the pen is pressed)
+    pub in_contact: bool,
+
+    /// If the pointer is the primary pointer (ex: the first finger on the screen)
+    pub primary: bool,
+
+    /// If the barrel button of the pen is pressed
+    pub barrel: bool,
+
+    /// If the pen is inverted (the eraser end is near the screen)
+    pub inverted: bool,
+
+    /// If the eraser of the pen is pressed
+    pub eraser: bool,
+
+    /// If the pointer input was canceled by the system (ex: a palm was detected)
+    pub canceled: bool,
+}
+
+
+/// Reads the pointer information of a `WM_POINTER*` message
+pub(crate) unsafe fn read_pointer(hwnd: HWND, w: WPARAM) -> Option<PointerData> {
+    use winapi::um::winuser::{GetPointerType, GetPointerInfo, GetPointerPenInfo, GetPointerTouchInfo, PT_TOUCH, PT_PEN, PT_MOUSE, PT_TOUCHPAD,
+        POINTER_FLAG_INCONTACT, POINTER_FLAG_PRIMARY, POINTER_FLAG_CANCELED, PEN_FLAG_BARREL, PEN_FLAG_INVERTED, PEN_FLAG_ERASER,
+        PEN_MASK_PRESSURE, PEN_MASK_ROTATION, PEN_MASK_TILT_X, PEN_MASK_TILT_Y, TOUCH_MASK_PRESSURE};
+
+    let id = LOWORD(w as u32) as u32;
+
+    let mut pointer_type = 0;
+    if GetPointerType(id, &mut pointer_type) == 0 {
+        return None;
+    }
+
+    let kind = match pointer_type {
+        PT_TOUCH => PointerType::Touch,
+        PT_PEN => PointerType::Pen,
+        PT_MOUSE => PointerType::Mouse,
+        PT_TOUCHPAD => PointerType::Touchpad,
+        _ => PointerType::Other,
+    };
+
+    let mut data = PointerData {
+        id,
+        kind,
+        position: (0, 0),
+        pressure: None,
+        tilt: (0, 0),
+        rotation: None,
+        in_contact: false,
+        primary: false,
+        barrel: false,
+        inverted: false,
+        eraser: false,
+        canceled: false,
+    };
+
+    // The pen and the touch information start with the generic pointer information
+    let info: POINTER_INFO = match kind {
+        PointerType::Pen => {
+            let mut pen: POINTER_PEN_INFO = mem::zeroed();
+            if GetPointerPenInfo(id, &mut pen) == 0 {
+                return None;
+            }
+
+            if pen.penMask & PEN_MASK_PRESSURE != 0 {
+                data.pressure = Some(pen.pressure as f32 / MAX_PRESSURE);
+            }
+
+            if pen.penMask & PEN_MASK_ROTATION != 0 {
+                data.rotation = Some(pen.rotation);
+            }
+
+            if pen.penMask & PEN_MASK_TILT_X != 0 {
+                data.tilt.0 = pen.tiltX;
+            }
+
+            if pen.penMask & PEN_MASK_TILT_Y != 0 {
+                data.tilt.1 = pen.tiltY;
+            }
+
+            data.barrel = pen.penFlags & PEN_FLAG_BARREL != 0;
+            data.inverted = pen.penFlags & PEN_FLAG_INVERTED != 0;
+            data.eraser = pen.penFlags & PEN_FLAG_ERASER != 0;
+            pen.pointerInfo
+        },
+        PointerType::Touch => {
+            let mut touch: POINTER_TOUCH_INFO = mem::zeroed();
+            if GetPointerTouchInfo(id, &mut touch) == 0 {
+                return None;
+            }
+
+            if touch.touchMask & TOUCH_MASK_PRESSURE != 0 {
+                data.pressure = Some(touch.pressure as f32 / MAX_PRESSURE);
+            }
+
+            touch.pointerInfo
+        },
+        _ => {
+            let mut info: POINTER_INFO = mem::zeroed();
+            if GetPointerInfo(id, &mut info) == 0 {
+                return None;
+            }
+
+            info
+        }
+    };
+
+    let mut point = info.ptPixelLocation;
+    ScreenToClient(hwnd, &mut point);
+    data.position = (point.x, point.y);
+
+    data.in_contact = info.pointerFlags & POINTER_FLAG_INCONTACT != 0;
+    data.primary = info.pointerFlags & POINTER_FLAG_PRIMARY != 0;
+    data.canceled = info.pointerFlags & POINTER_FLAG_CANCELED != 0;
+
+    Some(data)
+}
@@ -0,0 +1,329 @@
+/*!
+    Runs a child process and streams its output to the GUI thread.
+
+    The standard output and the standard error of the process are read line by line on worker threads. The lines are
+    sent to the thread that spawned the process and delivered to the callbacks of the `StreamedProcess`, so that the
+    callbacks can update the controls directly.
+
+    Requires the `process` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+    use std::process::Command;
+    use std::rc::Rc;
+
+    fn build(output: Rc<nwg::TerminalView>, status: Rc<nwg::Label>) -> Result<nwg::process::StreamedProcess, nwg::NwgError> {
+        let mut cmd = Command::new("cargo");
+        cmd.args(&["build", "--color", "always"]);
+
+        let process = nwg::process::spawn_streamed(cmd)?;
+        process.on_line(move |line| {
+            output.write(&line.text);
+            output.write("\r\n");
+        });
+        process.on_exit(move |exit| match exit.code {
+            Some(0) => status.set_text("Build succeeded"),
+            Some(code) => status.set_text(&format!("Build failed ({})", code)),
+            None => status.set_text("Build cancelled"),
+        });
+
+        Ok(process)
+    }
+    ```
+*/
+use winapi::shared::windef::HWND;
+use super::window::{create_message_window, bind_raw_event_handler_inner, unbind_raw_event_handler, RawEventHandler};
+use crate::{ControlHandle, NwgError};
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
+use std::{cell::RefCell, rc::Rc, thread, time::Duration};
+
+/// Id of the raw event handler that receives the output of the process
+const PROCESS_HANDLER_ID: usize = 0x9D0C;
+
+/// Message posted to the message window when new events are queued
+const WM_PROCESS_EVENTS: u32 = winapi::um::winuser::WM_APP + 0x9D0;
+
+/// How often the exit of the process is checked
+const EXIT_POLL_DELAY: Duration = Duration::from_millis(50);
+
+/// Hides the console window of console programs
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+
+/// The stream of a line of output
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProcessStream {
+    Stdout,
+    Stderr,
+}
+
+/// A line written by the process
+#[derive(Clone, Debug)]
+pub struct ProcessLine {
+    /// The stream that received the line
+    pub stream: ProcessStream,
+
+    /// The text of the line, without the line ending. Invalid UTF-8 is replaced by `U+FFFD`.
+    /// Escape sequences (ex: colors) are kept.
+    pub text: String,
+}
+
+/// The end of the process
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ProcessExit {
+    /// The exit code of the process. `None` if the process was stopped with `StreamedProcess::kill`.
+    pub code: Option<i32>,
+}
+
+enum ProcessEvent {
+    Line(ProcessLine),
+    Exit(ProcessExit),
+}
+
+/// The data shared with the worker threads
+struct Shared {
+    events: Mutex<VecDeque<ProcessEvent>>,
+    /// Set when a `WM_PROCESS_EVENTS` message is waiting in the queue, so that a process writing many lines
+    /// does not flood the message queue
+    posted: AtomicBool,
+    killed: AtomicBool,
+    exit_code: Mutex<Option<Option<i32>>>,
+    /// The message window, as an integer because a HWND cannot be sent between threads
+    window: usize,
+}
+
+impl Shared {
+
+    fn push(&self, event: ProcessEvent) {
+        use winapi::um::winuser::PostMessageW;
+
+        self.events.lock().unwrap().push_back(event);
+
+        if !self.posted.swap(true, Ordering::SeqCst) {
+            unsafe { PostMessageW(self.window as HWND, WM_PROCESS_EVENTS, 0, 0); }
+        }
+    }
+
+}
+
+#[derive(Default)]
+struct Callbacks {
+    line: Option<Rc<dyn Fn(&ProcessLine)>>,
+    exit: Option<Rc<dyn Fn(ProcessExit)>>,
+}
+
+/**
+    Spawns `cmd` with its standard output and standard error redirected, and returns a `StreamedProcess` that delivers
+    the lines written by the process on the calling thread. The standard input of the process is closed, and console
+    programs are started without a console window.
+
+    The calling thread must be an UI thread running an events loop (ex: `nwg::dispatch_thread_events`).
+    Returns an error if the process cannot be started.
+*/
+pub fn spawn_streamed(mut cmd: Command) -> Result<StreamedProcess, NwgError> {
+    use std::os::windows::process::CommandExt;
+
+    let window = create_message_window("")?;
+    let hwnd = window.hwnd().unwrap();
+
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .creation_flags(CREATE_NO_WINDOW);
+
+    let mut child = match cmd.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            let mut window = window;
+            window.destroy();
+            return Err(NwgError::process(format!("Failed to start {:?}: {}", cmd, e)));
+        }
+    };
+
+    let shared = Arc::new(Shared {
+        events: Mutex::new(VecDeque::new()),
+        posted: AtomicBool::new(false),
+        killed: AtomicBool::new(false),
+        exit_code: Mutex::new(None),
+        window: hwnd as usize,
+    });
+
+    let readers = vec![
+        spawn_reader(child.stdout.take().unwrap(), ProcessStream::Stdout, shared.clone()),
+        spawn_reader(child.stderr.take().unwrap(), ProcessStream::Stderr, shared.clone()),
+    ];
+
+    let id = child.id();
+    let child = Arc::new(Mutex::new(child));
+    spawn_waiter(child.clone(), readers, shared.clone());
+
+    let callbacks: Rc<RefCell<Callbacks>> = Default::default();
+    let handler_callbacks = callbacks.clone();
+    let handler_shared = shared.clone();
+    let handler = bind_raw_event_handler_inner(&window, PROCESS_HANDLER_ID, move |_hwnd, msg, _w, _l| {
+        if msg != WM_PROCESS_EVENTS {
+            return None;
+        }
+
+        handler_shared.posted.store(false, Ordering::SeqCst);
+        let events: Vec<ProcessEvent> = handler_shared.events.lock().unwrap().drain(..).collect();
+
+        // The callbacks are cloned so that a callback can replace the callbacks of the process
+        for event in events {
+            match event {
+                ProcessEvent::Line(line) => {
+                    let callback = handler_callbacks.borrow().line.clone();
+                    if let Some(callback) = callback {
+                        callback(&line);
+                    }
+                },
+                ProcessEvent::Exit(exit) => {
+                    let callback = handler_callbacks.borrow().exit.clone();
+                    if let Some(callback) = callback {
+                        callback(exit);
+                    }
+                }
+            }
+        }
+
+        Some(0)
+    })?;
+
+    Ok(StreamedProcess {
+        id,
+        child,
+        shared,
+        callbacks,
+        window: Some(window),
+        handler: Some(handler),
+    })
+}
+
+/// Reads the lines of a stream until the process closes it
+fn spawn_reader<R: Read + Send + 'static>(stream: R, kind: ProcessStream, shared: Arc<Shared>) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(stream);
+        let mut buffer = Vec::new();
+
+        loop {
+            buffer.clear();
+            match reader.read_until(b'\n', &mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    while buffer.last() == Some(&b'\n') || buffer.last() == Some(&b'\r') {
+                        buffer.pop();
+                    }
+
+                    let text = String::from_utf8_lossy(&buffer).into_owned();
+                    shared.push(ProcessEvent::Line(ProcessLine { stream: kind, text }));
+                }
+            }
+        }
+    })
+}
+
+/// Waits for the end of the process, then sends the exit event after the last lines
+fn spawn_waiter(child: Arc<Mutex<Child>>, readers: Vec<thread::JoinHandle<()>>, shared: Arc<Shared>) {
+    thread::spawn(move || {
+        // `Child::wait` would keep the lock and prevent `kill`
+        let status = loop {
+            match child.lock().unwrap().try_wait() {
+                Ok(Some(status)) => break status.code(),
+                Ok(None) => {},
+                Err(_) => break None,
+            }
+
+            thread::sleep(EXIT_POLL_DELAY);
+        };
+
+        for reader in readers {
+            drop(reader.join());
+        }
+
+        let code = match shared.killed.load(Ordering::SeqCst) {
+            true => None,
+            false => status
+        };
+
+        *shared.exit_code.lock().unwrap() = Some(code);
+        shared.push(ProcessEvent::Exit(ProcessExit { code }));
+    });
+}
+
+/**
+    A child process started with `spawn_streamed`. The callbacks are called on the thread that spawned the process.
+
+    Dropping the `StreamedProcess` does not stop the process, but the remaining output is discarded.
+*/
+pub struct StreamedProcess {
+    id: u32,
+    child: Arc<Mutex<Child>>,
+    shared: Arc<Shared>,
+    callbacks: Rc<RefCell<Callbacks>>,
+    window: Option<ControlHandle>,
+    handler: Option<RawEventHandler>,
+}
+
+impl StreamedProcess {
+
+    /// Returns the id of the process
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Sets the callback called for each line written by the process. Replaces the previous callback.
+    pub fn on_line<F>(&self, callback: F)
+        where F: Fn(&ProcessLine) + 'static
+    {
+        self.callbacks.borrow_mut().line = Some(Rc::new(callback));
+    }
+
+    /// Sets the callback called when the process ends, after the last line. Replaces the previous callback.
+    pub fn on_exit<F>(&self, callback: F)
+        where F: Fn(ProcessExit) + 'static
+    {
+        self.callbacks.borrow_mut().exit = Some(Rc::new(callback));
+    }
+
+    /**
+        Stops the process. The exit callback is called with a `None` exit code once the remaining output is read.
+
+        Only the process itself is stopped. If the process started other processes that keep its output open,
+        the exit callback is called when they end.
+    */
+    pub fn kill(&self) -> Result<(), NwgError> {
+        let mut child = self.child.lock().unwrap();
+        if self.running() {
+            self.shared.killed.store(true, Ordering::SeqCst);
+            child.kill().map_err(|e| NwgError::process(format!("Failed to stop the process: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` until the process ended and its output was read
+    pub fn running(&self) -> bool {
+        self.shared.exit_code.lock().unwrap().is_none()
+    }
+
+    /// Returns the exit code of the process, once the process ended. The inner value is `None` if the process was killed.
+    pub fn exit_code(&self) -> Option<Option<i32>> {
+        *self.shared.exit_code.lock().unwrap()
+    }
+
+}
+
+impl Drop for StreamedProcess {
+    fn drop(&mut self) {
+        if let Some(handler) = self.handler.take() {
+            drop(unbind_raw_event_handler(&handler));
+        }
+
+        if let Some(mut window) = self.window.take() {
+            window.destroy();
+        }
+    }
+}
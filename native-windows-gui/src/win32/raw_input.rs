@@ -0,0 +1,321 @@
+/*!
+    Raw input from the keyboards, the mice and the other HID devices.
+
+    Once a window is registered for a kind of device with `register`, the window receives an `OnRawInput` event for
+    each input of the devices. Unlike the mouse events, the mouse movements are not accelerated nor clipped to the screen,
+    and each input carries the handle of the device that sent it, so that two mice or two keyboards can be told apart.
+    `devices` lists the devices connected to the system.
+
+    Requires the `raw-input` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+    use nwg::raw_input::{RawDevice, RawInput};
+
+    fn setup(window: &nwg::Window) -> Result<(), nwg::NwgError> {
+        for device in nwg::raw_input::devices()? {
+            println!("{:?} {}", device.kind, device.name);
+        }
+
+        nwg::raw_input::register(window, &[RawDevice::Mouse, RawDevice::Keyboard], false)
+    }
+
+    fn events(evt: nwg::Event, data: &nwg::EventData) {
+        if evt == nwg::Event::OnRawInput {
+            match data.on_raw_input() {
+                RawInput::Mouse(mouse) => println!("Mouse moved by ({}, {})", mouse.dx, mouse.dy),
+                RawInput::Keyboard(key) => println!("Key {} pressed: {}", key.virtual_key, key.pressed),
+                RawInput::Hid(hid) => println!("HID report of {} bytes", hid.data.len()),
+            }
+        }
+    }
+    ```
+*/
+use winapi::shared::minwindef::LPARAM;
+use winapi::shared::windef::HWND;
+use winapi::um::winnt::HANDLE;
+use winapi::um::winuser::{RAWINPUTHEADER, RAWINPUTDEVICE, RIM_TYPEMOUSE, RIM_TYPEKEYBOARD, RIM_TYPEHID};
+use super::base_helper::from_utf16;
+use crate::{ControlHandle, NwgError};
+use std::{mem, ptr};
+
+/// The generic desktop usage page of the HID specification
+const USAGE_PAGE_GENERIC: u16 = 0x01;
+const USAGE_MOUSE: u16 = 0x02;
+const USAGE_KEYBOARD: u16 = 0x06;
+
+
+/// A kind of device that sends raw input
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RawDevice {
+    Mouse,
+    Keyboard,
+
+    /// Another HID device (ex: a gamepad is `Hid { usage_page: 0x01, usage: 0x05 }`)
+    Hid { usage_page: u16, usage: u16 },
+}
+
+impl RawDevice {
+
+    fn usage(&self) -> (u16, u16) {
+        match *self {
+            RawDevice::Mouse => (USAGE_PAGE_GENERIC, USAGE_MOUSE),
+            RawDevice::Keyboard => (USAGE_PAGE_GENERIC, USAGE_KEYBOARD),
+            RawDevice::Hid { usage_page, usage } => (usage_page, usage),
+        }
+    }
+
+}
+
+/// A device connected to the system. See `devices`.
+#[derive(Clone, Debug)]
+pub struct RawDeviceInfo {
+    /// The handle of the device. Matches the `device` field of the raw input sent by the device.
+    pub handle: HANDLE,
+
+    /// The kind of device
+    pub kind: RawDevice,
+
+    /// The interface name of the device (ex: `\\?\HID#VID_046D&PID_C52B...`)
+    pub name: String,
+
+    /// The vendor id of a HID device. `0` for the mice and the keyboards.
+    pub vendor_id: u32,
+
+    /// The product id of a HID device. `0` for the mice and the keyboards.
+    pub product_id: u32,
+}
+
+/// A mouse input
+#[derive(Copy, Clone, Debug)]
+pub struct RawMouse {
+    /// The handle of the mouse that sent the input
+    pub device: HANDLE,
+
+    /// The movement of the mouse since the last input, or the absolute position if `absolute` is true
+    pub dx: i32,
+    pub dy: i32,
+
+    /// If the device reports absolute positions (ex: a tablet or a remote desktop session). The positions range from 0 to 65535.
+    pub absolute: bool,
+
+    /// The transitions of the mouse buttons, as a combination of the `RI_MOUSE_*` flags
+    pub buttons: u16,
+
+    /// The rotation of the mouse wheel, in multiple of `WHEEL_DELTA`. `0` if the wheel did not move.
+    pub wheel: i16,
+}
+
+/// A keyboard input
+#[derive(Copy, Clone, Debug)]
+pub struct RawKeyboard {
+    /// The handle of the keyboard that sent the input
+    pub device: HANDLE,
+
+    /// The scan code of the key
+    pub scan_code: u16,
+
+    /// The virtual key code of the key. See `nwg::keys`.
+    pub virtual_key: u32,
+
+    /// `true` if the key was pressed, `false` if it was released
+    pub pressed: bool,
+
+    /// If the scan code has the `E0` prefix (ex: the right control key)
+    pub extended: bool,
+}
+
+/// An input of a HID device
+#[derive(Clone, Debug)]
+pub struct RawHid {
+    /// The handle of the device that sent the input
+    pub device: HANDLE,
+
+    /// The size of each report in `data`
+    pub report_size: usize,
+
+    /// The reports sent by the device, one after the other
+    pub data: Vec<u8>,
+}
+
+/// A raw input received by a window. See `Event::OnRawInput`.
+#[derive(Clone, Debug)]
+pub enum RawInput {
+    Mouse(RawMouse),
+    Keyboard(RawKeyboard),
+    Hid(RawHid),
+}
+
+impl RawInput {
+
+    /// Returns the handle of the device that sent the input
+    pub fn device(&self) -> HANDLE {
+        match self {
+            RawInput::Mouse(m) => m.device,
+            RawInput::Keyboard(k) => k.device,
+            RawInput::Hid(h) => h.device,
+        }
+    }
+
+}
+
+
+/**
+    Registers `window` to receive the raw input of `devices` in `OnRawInput` events.
+    If `background` is true, the window also receives the input when it is not in the foreground.
+
+    Only one window of the application receives the raw input of a kind of device:
+    registering another window for the same kind of device replaces the previous one.
+*/
+pub fn register<W: Into<ControlHandle>>(window: W, devices: &[RawDevice], background: bool) -> Result<(), NwgError> {
+    use winapi::um::winuser::RIDEV_INPUTSINK;
+
+    let hwnd = window.into().hwnd().ok_or_else(|| NwgError::raw_input("The raw input target must be a window"))?;
+    let flags = match background {
+        true => RIDEV_INPUTSINK,
+        false => 0
+    };
+
+    register_devices(devices, flags, hwnd)
+}
+
+/// Stops the raw input of `devices`
+pub fn unregister(devices: &[RawDevice]) -> Result<(), NwgError> {
+    use winapi::um::winuser::RIDEV_REMOVE;
+
+    register_devices(devices, RIDEV_REMOVE, ptr::null_mut())
+}
+
+fn register_devices(devices: &[RawDevice], flags: u32, hwnd: HWND) -> Result<(), NwgError> {
+    use winapi::um::winuser::RegisterRawInputDevices;
+
+    let devices: Vec<RAWINPUTDEVICE> = devices.iter()
+        .map(|d| {
+            let (page, usage) = d.usage();
+            RAWINPUTDEVICE { usUsagePage: page, usUsage: usage, dwFlags: flags, hwndTarget: hwnd }
+        })
+        .collect();
+
+    let size = mem::size_of::<RAWINPUTDEVICE>() as u32;
+    match unsafe { RegisterRawInputDevices(devices.as_ptr(), devices.len() as u32, size) } {
+        0 => Err(NwgError::raw_input("Failed to register the raw input devices")),
+        _ => Ok(())
+    }
+}
+
+/// Returns the keyboards, the mice and the other HID devices connected to the system
+pub fn devices() -> Result<Vec<RawDeviceInfo>, NwgError> {
+    use winapi::um::winuser::{GetRawInputDeviceList, RAWINPUTDEVICELIST};
+
+    let item_size = mem::size_of::<RAWINPUTDEVICELIST>() as u32;
+    let mut list = Vec::new();
+
+    // A device may be plugged in between the two calls
+    loop {
+        let mut count = 0;
+        if unsafe { GetRawInputDeviceList(ptr::null_mut(), &mut count, item_size) } == u32::max_value() {
+            return Err(NwgError::raw_input("Failed to list the raw input devices"));
+        }
+
+        list.resize(count as usize, RAWINPUTDEVICELIST { hDevice: ptr::null_mut(), dwType: 0 });
+        match unsafe { GetRawInputDeviceList(list.as_mut_ptr(), &mut count, item_size) } {
+            e if e == u32::max_value() => continue,
+            found => {
+                list.truncate(found as usize);
+                break;
+            }
+        }
+    }
+
+    Ok(list.iter().filter_map(|d| device_info(d.hDevice)).collect())
+}
+
+fn device_info(handle: HANDLE) -> Option<RawDeviceInfo> {
+    use winapi::um::winuser::{GetRawInputDeviceInfoW, RID_DEVICE_INFO, RIDI_DEVICEINFO, RIDI_DEVICENAME};
+
+    unsafe {
+        let mut info: RID_DEVICE_INFO = mem::zeroed();
+        let mut size = mem::size_of::<RID_DEVICE_INFO>() as u32;
+        info.cbSize = size;
+        if GetRawInputDeviceInfoW(handle, RIDI_DEVICEINFO, &mut info as *mut RID_DEVICE_INFO as _, &mut size) == u32::max_value() {
+            return None;
+        }
+
+        // The size of the name is returned in characters
+        let mut name_len = 0;
+        GetRawInputDeviceInfoW(handle, RIDI_DEVICENAME, ptr::null_mut(), &mut name_len);
+        let mut name = vec![0u16; name_len as usize + 1];
+        GetRawInputDeviceInfoW(handle, RIDI_DEVICENAME, name.as_mut_ptr() as _, &mut name_len);
+
+        let (kind, vendor_id, product_id) = match info.dwType {
+            RIM_TYPEMOUSE => (RawDevice::Mouse, 0, 0),
+            RIM_TYPEKEYBOARD => (RawDevice::Keyboard, 0, 0),
+            _ => {
+                let hid = info.u.hid();
+                (RawDevice::Hid { usage_page: hid.usUsagePage, usage: hid.usUsage }, hid.dwVendorId, hid.dwProductId)
+            }
+        };
+
+        Some(RawDeviceInfo { handle, kind, name: from_utf16(&name), vendor_id, product_id })
+    }
+}
+
+/// Reads the raw input of a `WM_INPUT` message
+pub(crate) fn read_input(l: LPARAM) -> Option<RawInput> {
+    use winapi::um::winuser::{GetRawInputData, RAWINPUT, RID_INPUT, MOUSE_MOVE_ABSOLUTE, RI_MOUSE_WHEEL, RI_KEY_BREAK, RI_KEY_E0};
+
+    let header_size = mem::size_of::<RAWINPUTHEADER>() as u32;
+
+    unsafe {
+        let mut size = 0;
+        if GetRawInputData(l as _, RID_INPUT, ptr::null_mut(), &mut size, header_size) != 0 {
+            return None;
+        }
+
+        // The buffer is made of u64 to keep the alignment of the handles in the structure
+        let mut buffer = vec![0u64; (size as usize + 7) / 8];
+        if GetRawInputData(l as _, RID_INPUT, buffer.as_mut_ptr() as _, &mut size, header_size) == u32::max_value() {
+            return None;
+        }
+
+        let input = &*(buffer.as_ptr() as *const RAWINPUT);
+        let device = input.header.hDevice;
+
+        match input.header.dwType {
+            RIM_TYPEMOUSE => {
+                let mouse = input.data.mouse();
+                let wheel = match mouse.usButtonFlags & RI_MOUSE_WHEEL == RI_MOUSE_WHEEL {
+                    true => mouse.usButtonData as i16,
+                    false => 0
+                };
+
+                Some(RawInput::Mouse(RawMouse {
+                    device,
+                    dx: mouse.lLastX,
+                    dy: mouse.lLastY,
+                    absolute: mouse.usFlags & MOUSE_MOVE_ABSOLUTE == MOUSE_MOVE_ABSOLUTE,
+                    buttons: mouse.usButtonFlags,
+                    wheel,
+                }))
+            },
+            RIM_TYPEKEYBOARD => {
+                let keyboard = input.data.keyboard();
+                let flags = keyboard.Flags as u32;
+                Some(RawInput::Keyboard(RawKeyboard {
+                    device,
+                    scan_code: keyboard.MakeCode,
+                    virtual_key: keyboard.VKey as u32,
+                    pressed: flags & RI_KEY_BREAK == 0,
+                    extended: flags & RI_KEY_E0 == RI_KEY_E0,
+                }))
+            },
+            RIM_TYPEHID => {
+                let hid = input.data.hid();
+                let len = (hid.dwSizeHid * hid.dwCount) as usize;
+                let data = std::slice::from_raw_parts(hid.bRawData.as_ptr(), len).to_vec();
+                Some(RawInput::Hid(RawHid { device, report_size: hid.dwSizeHid as usize, data }))
+            },
+            _ => None
+        }
+    }
+}
@@ -0,0 +1,69 @@
+/*!
+    Coalescing of the resize messages received by the layouts.
+
+    When the user drags the border of a window, the system sends a `WM_SIZE` message for each movement of the mouse.
+    Computing a big layout for each of them makes the children lag behind the border. While the window is being resized,
+    the layouts only keep the last size and update the children at most once per frame. The final size is applied
+    when the user releases the border, before the `OnResizeEnd` event.
+*/
+use winapi::shared::minwindef::{HIWORD, LOWORD, UINT, WPARAM};
+use winapi::shared::basetsd::UINT_PTR;
+use winapi::um::winuser::{SetTimer, KillTimer, WM_SIZE, WM_TIMER, WM_ENTERSIZEMOVE, WM_EXITSIZEMOVE};
+use super::window::{RawEventHandler, bind_raw_event_handler_inner};
+use super::high_dpi::physical_to_logical;
+use crate::{ControlHandle, NwgError};
+use std::cell::Cell;
+
+/// The delay between two layout updates while the window is being resized (about one frame at 60 fps)
+const FRAME_DELAY: UINT = 16;
+
+
+/**
+    Binds a raw event handler that calls `update` with the logical size of the client area of `base` when it changes.
+    While the user resizes the window, the updates are coalesced. The handler id is also used as the id of the timer of the handler.
+*/
+pub(crate) fn bind_resize_handler<F>(base: &ControlHandle, handler_id: usize, update: F) -> Result<RawEventHandler, NwgError>
+    where F: Fn(u32, u32) + 'static
+{
+    let sizing = Cell::new(false);
+    let pending: Cell<Option<(u32, u32)>> = Cell::new(None);
+    let timer_id = handler_id as UINT_PTR;
+
+    let cb = move |hwnd, msg, w: WPARAM, l| {
+        match msg {
+            WM_ENTERSIZEMOVE => sizing.set(true),
+            WM_SIZE => {
+                let size = l as u32;
+                let (width, height) = unsafe { physical_to_logical(LOWORD(size) as i32, HIWORD(size) as i32) };
+                let size = (width as u32, height as u32);
+
+                match sizing.get() {
+                    true => if pending.replace(Some(size)).is_none() {
+                        unsafe { SetTimer(hwnd, timer_id, FRAME_DELAY, None); }
+                    },
+                    false => update(size.0, size.1)
+                }
+            },
+            WM_TIMER if w == timer_id => {
+                unsafe { KillTimer(hwnd, timer_id); }
+                if let Some((width, height)) = pending.take() {
+                    update(width, height);
+                }
+
+                return Some(0);
+            },
+            WM_EXITSIZEMOVE => {
+                sizing.set(false);
+                unsafe { KillTimer(hwnd, timer_id); }
+                if let Some((width, height)) = pending.take() {
+                    update(width, height);
+                }
+            },
+            _ => {}
+        }
+
+        None
+    };
+
+    bind_raw_event_handler_inner(base, handler_id, cb)
+}
@@ -0,0 +1,195 @@
+/*!
+    Debug tracking of the GDI/USER handles (windows, fonts, brushes, icons, cursors, bitmaps) created by NWG.
+
+    Requires the `resource-tracking` feature. Without the feature, `track` and `untrack` compile to nothing.
+
+    Every handle created by the crate is registered with the backtrace of its creation and removed when the crate
+    destroys it. When `dispatch_thread_events` exits (normally or by unwinding), the handles that were created
+    while the events were dispatched and that are still alive are reported on stderr. Those are the handles
+    that accumulate in long running applications.
+*/
+
+#[cfg(feature = "resource-tracking")]
+use std::{collections::HashMap, sync::Mutex, thread::{self, ThreadId}, backtrace::Backtrace};
+
+
+/// The kind of a tracked handle
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    Window,
+    Font,
+    Brush,
+    Icon,
+    Cursor,
+    Bitmap,
+}
+
+/// A handle that is currently alive. Returned by `tracked_resources`.
+#[cfg(feature = "resource-tracking")]
+#[derive(Clone, Debug)]
+pub struct TrackedResource {
+    pub kind: ResourceKind,
+    pub handle: usize,
+
+    /// The thread that created the handle
+    pub thread: ThreadId,
+
+    /// Creation order of the handle. Increases by one each time a handle is tracked.
+    pub sequence: u64,
+
+    /// The backtrace of the handle creation
+    pub backtrace: String,
+}
+
+#[cfg(feature = "resource-tracking")]
+struct Entry {
+    kind: ResourceKind,
+    thread: ThreadId,
+    sequence: u64,
+    backtrace: Backtrace,
+}
+
+#[cfg(feature = "resource-tracking")]
+#[derive(Default)]
+struct Tracker {
+    sequence: u64,
+    entries: HashMap<usize, Entry>,
+}
+
+#[cfg(feature = "resource-tracking")]
+lazy_static! {
+    static ref TRACKER: Mutex<Tracker> = Mutex::new(Default::default());
+}
+
+/// Register a handle created by NWG
+#[cfg(feature = "resource-tracking")]
+pub(crate) fn track<T>(kind: ResourceKind, handle: *mut T) {
+    if handle.is_null() {
+        return;
+    }
+
+    let mut tracker = match TRACKER.lock() {
+        Ok(t) => t,
+        Err(e) => e.into_inner()
+    };
+
+    tracker.sequence += 1;
+    let entry = Entry {
+        kind,
+        thread: thread::current().id(),
+        sequence: tracker.sequence,
+        backtrace: Backtrace::force_capture(),
+    };
+
+    tracker.entries.insert(handle as usize, entry);
+}
+
+/// Remove a handle destroyed by NWG
+#[cfg(feature = "resource-tracking")]
+pub(crate) fn untrack<T>(handle: *mut T) {
+    let mut tracker = match TRACKER.lock() {
+        Ok(t) => t,
+        Err(e) => e.into_inner()
+    };
+
+    tracker.entries.remove(&(handle as usize));
+}
+
+#[cfg(not(feature = "resource-tracking"))]
+#[inline(always)]
+pub(crate) fn track<T>(_kind: ResourceKind, _handle: *mut T) {}
+
+#[cfg(not(feature = "resource-tracking"))]
+#[inline(always)]
+pub(crate) fn untrack<T>(_handle: *mut T) {}
+
+/**
+    Return the handles created by NWG that are still alive, sorted by creation order.
+    Windows that were destroyed by the system (ex: when their parent was destroyed) are not returned.
+*/
+#[cfg(feature = "resource-tracking")]
+pub fn tracked_resources() -> Vec<TrackedResource> {
+    use winapi::um::winuser::IsWindow;
+
+    let mut tracker = match TRACKER.lock() {
+        Ok(t) => t,
+        Err(e) => e.into_inner()
+    };
+
+    tracker.entries.retain(|&handle, entry| {
+        entry.kind != ResourceKind::Window || unsafe { IsWindow(handle as _) != 0 }
+    });
+
+    let mut resources: Vec<TrackedResource> = tracker.entries.iter()
+        .map(|(&handle, entry)| TrackedResource {
+            kind: entry.kind,
+            handle,
+            thread: entry.thread,
+            sequence: entry.sequence,
+            backtrace: entry.backtrace.to_string(),
+        })
+        .collect();
+
+    resources.sort_by_key(|r| r.sequence);
+    resources
+}
+
+/**
+    Print the handles created by NWG that are still alive on stderr.
+    Call this after the UI was dropped to list the handles that were never freed.
+*/
+#[cfg(feature = "resource-tracking")]
+pub fn report_resource_leaks() {
+    report(tracked_resources().iter());
+}
+
+#[cfg(feature = "resource-tracking")]
+fn report<'a, I: Iterator<Item=&'a TrackedResource>>(resources: I) {
+    let resources: Vec<&TrackedResource> = resources.collect();
+    if resources.is_empty() {
+        return;
+    }
+
+    eprintln!("[NWG] {} handle(s) still alive:", resources.len());
+    for r in resources {
+        eprintln!("[NWG] {:?} 0x{:X} (#{}) created at:\n{}", r.kind, r.handle, r.sequence, r.backtrace);
+    }
+}
+
+/**
+    Reports the handles created by the current thread since the guard creation when dropped.
+    Used by the events dispatch functions so that the report is also printed if the events loop unwinds.
+*/
+pub(crate) struct LeakReport {
+    #[cfg(feature = "resource-tracking")]
+    start: u64,
+}
+
+impl LeakReport {
+
+    #[cfg(feature = "resource-tracking")]
+    pub fn start() -> LeakReport {
+        let tracker = match TRACKER.lock() {
+            Ok(t) => t,
+            Err(e) => e.into_inner()
+        };
+
+        LeakReport { start: tracker.sequence }
+    }
+
+    #[cfg(not(feature = "resource-tracking"))]
+    #[inline(always)]
+    pub fn start() -> LeakReport {
+        LeakReport {}
+    }
+
+}
+
+#[cfg(feature = "resource-tracking")]
+impl Drop for LeakReport {
+    fn drop(&mut self) {
+        let current = thread::current().id();
+        let resources = tracked_resources();
+        report(resources.iter().filter(|r| r.sequence > self.start && r.thread == current));
+    }
+}
@@ -4,6 +4,7 @@ use winapi::um::winnt::HANDLE;
 
 use crate::resources::OemImage;
 use super::base_helper::{get_system_error, to_utf16};
+use super::resource_tracker::{self as rt, ResourceKind};
 
 #[allow(unused_imports)] use std::{ptr, mem};
 #[allow(unused_imports)] use crate::NwgError;
@@ -11,8 +12,10 @@ use super::base_helper::{get_system_error, to_utf16};
 #[cfg(feature = "file-dialog")] use winapi::Interface;
 #[cfg(feature = "file-dialog")] use winapi::um::shobjidl_core::IShellItem;
 #[cfg(feature = "file-dialog")] use crate::resources::FileDialogAction;
-#[cfg(feature = "file-dialog")] use winapi::um::shobjidl::{IFileDialog, IFileOpenDialog};
+#[cfg(feature = "file-dialog")] use winapi::um::shobjidl::{IFileDialog, IFileOpenDialog, IFileDialogCustomize};
 #[cfg(feature = "file-dialog")] use std::ffi::OsString;
+#[cfg(feature = "file-dialog")] use std::{cell::{Cell, RefCell}, rc::Rc, path::PathBuf};
+#[cfg(feature = "file-dialog")] use crate::resources::FileDialogCallbacks;
 
 
 pub fn is_bitmap(handle: HBITMAP) -> bool {
@@ -24,14 +27,17 @@ pub fn is_bitmap(handle: HBITMAP) -> bool {
 }
 
 pub fn destroy_icon(icon: HANDLE) {
+    rt::untrack(icon);
     unsafe { winapi::um::winuser::DestroyIcon(icon as _); }
 } 
 
 pub fn destroy_cursor(cursor: HANDLE) {
+    rt::untrack(cursor);
     unsafe { winapi::um::winuser::DestroyCursor(cursor as _); }
 } 
 
 pub fn destroy_obj(obj: HANDLE) {
+    rt::untrack(obj);
     unsafe { winapi::um::wingdi::DeleteObject(obj as _); }
 } 
 
@@ -78,6 +84,7 @@ pub unsafe fn build_font(
     if handle.is_null() {
         Err( NwgError::resource_create("Failed to create font") )
     } else {
+        rt::track(ResourceKind::Font, handle);
         Ok( handle )
     }
 }
@@ -400,19 +407,18 @@ pub unsafe fn create_file_dialog<'a, 'b>(
 }
 
 
+#[cfg(feature = "file-dialog")]
+extern "system" {
+    fn SHCreateItemFromParsingName(pszPath: winapi::shared::ntdef::PCWSTR, pbc: *mut winapi::um::objidl::IBindCtx, riid: winapi::shared::guiddef::REFIID, ppv: *mut *mut winapi::ctypes::c_void) -> winapi::shared::ntdef::HRESULT;
+}
+
 #[cfg(feature = "file-dialog")]
 pub unsafe fn file_dialog_set_default_folder<'a>(dialog: &mut IFileDialog, folder_name: &'a str) -> Result<(), NwgError> {
     use winapi::um::shobjidl_core::{SFGAOF};
-    use winapi::um::objidl::IBindCtx;
-    use winapi::shared::{winerror::{S_OK, S_FALSE}, guiddef::REFIID, ntdef::{HRESULT, PCWSTR}};
-    use winapi::ctypes::c_void;
+    use winapi::shared::winerror::{S_OK, S_FALSE};
 
     const SFGAO_FOLDER: u32 = 0x20000000;
 
-    extern "system" {
-        pub fn SHCreateItemFromParsingName(pszPath: PCWSTR, pbc: *mut IBindCtx, riid: REFIID, ppv: *mut *mut c_void) -> HRESULT;
-    }
-
     // Code starts here :)
 
     let mut shellitem: *mut IShellItem = ptr::null_mut();
@@ -448,6 +454,21 @@ pub unsafe fn file_dialog_set_default_folder<'a>(dialog: &mut IFileDialog, folde
 }
 
 
+/// Returns the default extension of each filter: the extension of its first pattern, or `None` if the pattern matches any extension
+#[cfg(feature = "file-dialog")]
+pub fn file_dialog_filter_extensions(filters: &str) -> Vec<Option<String>> {
+    filters.split('|')
+        .map(|f| {
+            let start = f.rfind('(').map(|i| i + 1).unwrap_or(0);
+            let pattern = f[start..].trim_end_matches(')').split(';').next().unwrap_or("");
+            match pattern.rfind('.') {
+                Some(i) if !pattern[i+1..].is_empty() && !pattern[i+1..].contains('*') => Some(pattern[i+1..].to_string()),
+                _ => None
+            }
+        })
+        .collect()
+}
+
 #[cfg(feature = "file-dialog")]
 pub unsafe fn file_dialog_set_filters<'a>(dialog: &mut IFileDialog, filters: &'a str) -> Result<(), NwgError> {
     use winapi::shared::minwindef::UINT;
@@ -519,6 +540,7 @@ pub unsafe fn filedialog_get_items(dialog: &mut IFileOpenDialog) -> Result<Vec<O
             Ok(s) => item_names.push(s),
             Err(_) => {}
         }
+        (&mut *_item).Release();
     }
 
     items.Release();
@@ -573,3 +595,261 @@ pub unsafe fn toggle_dialog_flags(dialog: &mut IFileDialog, flag: u32, enabled:
         Ok(())
     }
 }
+
+#[cfg(feature = "file-dialog")]
+pub unsafe fn file_dialog_add_place(dialog: &mut IFileDialog, folder_name: &str, top: bool) -> Result<(), NwgError> {
+    use winapi::um::shobjidl::{FDAP_TOP, FDAP_BOTTOM};
+    use winapi::shared::winerror::S_OK;
+
+    let mut shellitem: *mut IShellItem = ptr::null_mut();
+    let path = to_utf16(folder_name);
+
+    if SHCreateItemFromParsingName(path.as_ptr(), ptr::null_mut(), &IShellItem::uuidof(), mem::transmute(&mut shellitem) ) != S_OK {
+        return Err(NwgError::file_dialog(format!("Failed to add the place {:?}", folder_name)));
+    }
+
+    let position = match top {
+        true => FDAP_TOP,
+        false => FDAP_BOTTOM
+    };
+
+    let result = dialog.AddPlace(shellitem, position);
+    (&mut *shellitem).Release();
+
+    match result {
+        S_OK => Ok(()),
+        _ => Err(NwgError::file_dialog(format!("Failed to add the place {:?}", folder_name)))
+    }
+}
+
+#[cfg(feature = "file-dialog")]
+pub unsafe fn file_dialog_set_default_extension(dialog: &mut IFileDialog, extension: Option<&str>) -> Result<(), NwgError> {
+    use winapi::shared::winerror::S_OK;
+
+    let extension = extension.map(to_utf16);
+    let extension_ptr = extension.as_ref().map(|e| e.as_ptr()).unwrap_or(ptr::null());
+
+    match dialog.SetDefaultExtension(extension_ptr) {
+        S_OK => Ok(()),
+        _ => Err(NwgError::file_dialog("Failed to set the default extension"))
+    }
+}
+
+/// Returns the paths selected in a dialog. Works with the open dialogs (one or more items) and the save dialogs (one item).
+#[cfg(feature = "file-dialog")]
+pub unsafe fn file_dialog_paths(dialog: &mut IFileDialog) -> Result<Vec<PathBuf>, NwgError> {
+    use winapi::shared::winerror::S_OK;
+
+    let mut open_dialog: *mut IFileOpenDialog = ptr::null_mut();
+    if dialog.QueryInterface(&IFileOpenDialog::uuidof(), mem::transmute(&mut open_dialog)) == S_OK {
+        let items = filedialog_get_items(&mut *open_dialog);
+        (&mut *open_dialog).Release();
+        return items.map(|items| items.into_iter().map(PathBuf::from).collect());
+    }
+
+    filedialog_get_item(dialog).map(|item| vec![PathBuf::from(item)])
+}
+
+#[cfg(feature = "file-dialog")]
+unsafe fn file_dialog_current_selection(dialog: &mut IFileDialog) -> Option<PathBuf> {
+    use winapi::shared::winerror::S_OK;
+
+    let mut item: *mut IShellItem = ptr::null_mut();
+    if dialog.GetCurrentSelection(&mut item) != S_OK {
+        return None;
+    }
+
+    let path = get_ishellitem_path(&mut *item).ok().map(PathBuf::from);
+    (&mut *item).Release();
+
+    path
+}
+
+
+/// The `IFileDialogEvents` implementation of NWG. Calls the callbacks of a `FileDialog`.
+#[cfg(feature = "file-dialog")]
+#[repr(C)]
+struct FileDialogEvents {
+    vtbl: *const winapi::um::shobjidl::IFileDialogEventsVtbl,
+    refs: Cell<u32>,
+    callbacks: Rc<RefCell<FileDialogCallbacks>>,
+}
+
+#[cfg(feature = "file-dialog")]
+mod file_dialog_events {
+    use winapi::um::shobjidl::{IFileDialog, IFileDialogEvents, IFileDialogEventsVtbl, FDE_SHAREVIOLATION_RESPONSE, FDE_OVERWRITE_RESPONSE};
+    use winapi::um::shobjidl_core::IShellItem;
+    use winapi::um::unknwnbase::{IUnknown, IUnknownVtbl};
+    use winapi::shared::guiddef::{REFIID, IsEqualGUID};
+    use winapi::shared::minwindef::ULONG;
+    use winapi::shared::winerror::{S_OK, S_FALSE, E_NOINTERFACE, E_NOTIMPL};
+    use winapi::um::winnt::HRESULT;
+    use winapi::ctypes::c_void;
+    use winapi::Interface;
+    use super::FileDialogEvents;
+    use std::ptr;
+
+    pub static VTBL: IFileDialogEventsVtbl = IFileDialogEventsVtbl {
+        parent: IUnknownVtbl {
+            QueryInterface: query_interface,
+            AddRef: add_ref,
+            Release: release,
+        },
+        OnFileOk: on_file_ok,
+        OnFolderChanging: on_folder_changing,
+        OnFolderChange: on_folder_change,
+        OnSelectionChange: on_selection_change,
+        OnShareViolation: on_share_violation,
+        OnTypeChange: on_type_change,
+        OnOverwrite: on_overwrite,
+    };
+
+    unsafe fn events<'a>(this: *mut IUnknown) -> &'a FileDialogEvents {
+        &*(this as *const FileDialogEvents)
+    }
+
+    unsafe extern "system" fn query_interface(this: *mut IUnknown, riid: REFIID, out: *mut *mut c_void) -> HRESULT {
+        if IsEqualGUID(&*riid, &IUnknown::uuidof()) || IsEqualGUID(&*riid, &IFileDialogEvents::uuidof()) {
+            add_ref(this);
+            *out = this as *mut c_void;
+            S_OK
+        } else {
+            *out = ptr::null_mut();
+            E_NOINTERFACE
+        }
+    }
+
+    pub unsafe extern "system" fn add_ref(this: *mut IUnknown) -> ULONG {
+        let events = events(this);
+        events.refs.set(events.refs.get() + 1);
+        events.refs.get()
+    }
+
+    pub unsafe extern "system" fn release(this: *mut IUnknown) -> ULONG {
+        let refs = {
+            let events = events(this);
+            events.refs.set(events.refs.get() - 1);
+            events.refs.get()
+        };
+
+        if refs == 0 {
+            drop(Box::from_raw(this as *mut FileDialogEvents));
+        }
+
+        refs
+    }
+
+    unsafe extern "system" fn on_file_ok(this: *mut IFileDialogEvents, dialog: *mut IFileDialog) -> HRESULT {
+        // The callback is cloned so that it can replace the callbacks of the dialog
+        let callback = events(this as _).callbacks.borrow().file_ok.clone();
+        match callback {
+            Some(callback) => {
+                let paths = super::file_dialog_paths(&mut *dialog).unwrap_or_default();
+                match callback(&paths) {
+                    true => S_OK,
+                    false => S_FALSE
+                }
+            },
+            None => S_OK
+        }
+    }
+
+    unsafe extern "system" fn on_folder_changing(_this: *mut IFileDialogEvents, _dialog: *mut IFileDialog, _folder: *mut IShellItem) -> HRESULT {
+        S_OK
+    }
+
+    unsafe extern "system" fn on_folder_change(_this: *mut IFileDialogEvents, _dialog: *mut IFileDialog) -> HRESULT {
+        S_OK
+    }
+
+    unsafe extern "system" fn on_selection_change(this: *mut IFileDialogEvents, dialog: *mut IFileDialog) -> HRESULT {
+        let callback = events(this as _).callbacks.borrow().selection_changed.clone();
+        if let Some(callback) = callback {
+            callback(super::file_dialog_current_selection(&mut *dialog));
+        }
+
+        S_OK
+    }
+
+    unsafe extern "system" fn on_share_violation(_this: *mut IFileDialogEvents, _dialog: *mut IFileDialog, _item: *mut IShellItem, _response: *mut FDE_SHAREVIOLATION_RESPONSE) -> HRESULT {
+        E_NOTIMPL
+    }
+
+    unsafe extern "system" fn on_type_change(this: *mut IFileDialogEvents, dialog: *mut IFileDialog) -> HRESULT {
+        let mut index = 0;
+        if (&mut *dialog).GetFileTypeIndex(&mut index) != S_OK || index == 0 {
+            return S_OK;
+        }
+
+        // The type index starts at 1
+        let extension = events(this as _).callbacks.borrow().extensions.get(index as usize - 1).cloned();
+        if let Some(extension) = extension {
+            let _ = super::file_dialog_set_default_extension(&mut *dialog, extension.as_ref().map(|e| e.as_str()));
+        }
+
+        S_OK
+    }
+
+    unsafe extern "system" fn on_overwrite(_this: *mut IFileDialogEvents, _dialog: *mut IFileDialog, _item: *mut IShellItem, _response: *mut FDE_OVERWRITE_RESPONSE) -> HRESULT {
+        E_NOTIMPL
+    }
+
+}
+
+/// Registers the callbacks of a dialog. Returns the cookie used to unregister them.
+#[cfg(feature = "file-dialog")]
+pub unsafe fn file_dialog_advise(dialog: &mut IFileDialog, callbacks: Rc<RefCell<FileDialogCallbacks>>) -> Result<u32, NwgError> {
+    use winapi::um::shobjidl::IFileDialogEvents;
+    use winapi::shared::winerror::S_OK;
+
+    let events = Box::into_raw(Box::new(FileDialogEvents {
+        vtbl: &file_dialog_events::VTBL,
+        refs: Cell::new(1),
+        callbacks,
+    }));
+
+    // The dialog keeps its own reference to the events
+    let mut cookie = 0;
+    let result = dialog.Advise(events as *mut IFileDialogEvents, &mut cookie);
+    file_dialog_events::release(events as _);
+
+    match result {
+        S_OK => Ok(cookie),
+        _ => Err(NwgError::file_dialog("Failed to register the file dialog events"))
+    }
+}
+
+/// Calls `f` with the `IFileDialogCustomize` interface of a dialog. Fails if `f` does not return `S_OK`.
+#[cfg(feature = "file-dialog")]
+pub unsafe fn file_dialog_customize<F>(dialog: &mut IFileDialog, f: F) -> Result<(), NwgError> 
+    where F: FnOnce(&mut IFileDialogCustomize) -> winapi::um::winnt::HRESULT
+{
+    use winapi::shared::winerror::S_OK;
+
+    let mut customize: *mut IFileDialogCustomize = ptr::null_mut();
+    if dialog.QueryInterface(&IFileDialogCustomize::uuidof(), mem::transmute(&mut customize)) != S_OK {
+        return Err(NwgError::file_dialog("The file dialog cannot be customized"));
+    }
+
+    let result = f(&mut *customize);
+    (&mut *customize).Release();
+
+    match result {
+        S_OK => Ok(()),
+        e => Err(NwgError::file_dialog(format!("Failed to customize the file dialog (error {:#X})", e)))
+    }
+}
+
+#[cfg(feature = "file-dialog")]
+pub unsafe fn file_dialog_edit_box_text(dialog: &mut IFileDialog, id: u32) -> Result<String, NwgError> {
+    use winapi::um::combaseapi::CoTaskMemFree;
+    use super::base_helper::from_wide_ptr;
+
+    let mut text: *mut u16 = ptr::null_mut();
+    file_dialog_customize(dialog, |c| c.GetEditBoxText(id, &mut text))?;
+
+    let value = from_wide_ptr(text, None);
+    CoTaskMemFree(text as _);
+
+    Ok(value)
+}
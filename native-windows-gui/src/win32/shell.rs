@@ -0,0 +1,638 @@
+/*!
+    Integration with the Windows shell: opening files with their default program or with a program chosen by the user,
+    showing the context menu of the explorer for files, and watching the changes of folders with a `ShellWatcher`.
+
+    The shell functions report the usual failures with a `ShellErrorKind` (ex: a file type without a program, or a program
+    that must run as administrator), so that the application can react to them instead of showing a generic error.
+
+    Requires the `shell` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+    use nwg::shell::{self, ShellErrorKind};
+
+    fn open_document(path: &str) -> Result<(), nwg::NwgError> {
+        match shell::launch_default(path) {
+            Err(nwg::NwgError::ShellError(ShellErrorKind::NoAssociation, _)) => shell::open_with_dialog(path).map(|_| ()),
+            Err(nwg::NwgError::ShellError(ShellErrorKind::ElevationRequired, _)) => shell::launch_verb(path, "runas"),
+            result => result
+        }
+    }
+    ```
+*/
+use winapi::shared::minwindef::{DWORD, UINT, BOOL, ULONG, WPARAM, LPARAM};
+use winapi::shared::windef::{HWND, POINT};
+use winapi::ctypes::c_int;
+use winapi::shared::winerror::{S_OK, ERROR_FILE_NOT_FOUND, ERROR_PATH_NOT_FOUND, ERROR_ACCESS_DENIED, ERROR_ELEVATION_REQUIRED,
+    ERROR_NO_ASSOCIATION, ERROR_CANCELLED, HRESULT_FROM_WIN32};
+use winapi::um::winnt::{HRESULT, HANDLE, LONG, LPCSTR, LPCWSTR};
+use winapi::um::shobjidl_core::IShellItemArray;
+use winapi::um::shtypes::ITEMIDLIST;
+use winapi::Interface;
+use super::base_helper::to_utf16;
+use super::window::bind_raw_event_handler_inner;
+use crate::{ControlHandle, NwgError};
+use std::{mem, ptr, path::{Path, PathBuf}};
+
+const OAIF_ALLOW_REGISTRATION: u32 = 0x01;
+const OAIF_EXEC: u32 = 0x04;
+
+/// Not declared by winapi
+#[repr(C)]
+#[allow(non_snake_case)]
+struct OPENASINFO {
+    pcszFile: LPCWSTR,
+    pcszClass: LPCWSTR,
+    oaifInFlags: u32,
+}
+
+// Not declared by winapi, but exported by shell32
+extern "system" {
+    fn SHOpenWithDialog(hwndParent: HWND, poainfo: *const OPENASINFO) -> HRESULT;
+    fn ILCreateFromPathW(pszPath: LPCWSTR) -> *mut ITEMIDLIST;
+    fn ILFree(pidl: *mut ITEMIDLIST);
+    fn SHCreateShellItemArrayFromIDLists(cidl: UINT, rgpidl: *const *mut ITEMIDLIST, ppsiItemArray: *mut *mut IShellItemArray) -> HRESULT;
+    fn SHChangeNotifyRegister(hwnd: HWND, fSources: c_int, fEvents: LONG, wMsg: UINT, cEntries: c_int, pshcne: *const SHChangeNotifyEntry) -> ULONG;
+    fn SHChangeNotifyDeregister(ulID: ULONG) -> BOOL;
+    fn SHChangeNotification_Lock(hChange: HANDLE, dwProcId: DWORD, pppidl: *mut *mut *mut ITEMIDLIST, plEvent: *mut LONG) -> HANDLE;
+    fn SHChangeNotification_Unlock(hLock: HANDLE) -> BOOL;
+}
+
+/// Not declared by winapi
+#[repr(C, packed)]
+#[allow(non_snake_case)]
+struct SHChangeNotifyEntry {
+    pidl: *const ITEMIDLIST,
+    fRecursive: BOOL,
+}
+
+const SHCNRF_INTERRUPT_LEVEL: c_int = 0x0001;
+const SHCNRF_SHELL_LEVEL: c_int = 0x0002;
+const SHCNRF_RECURSIVE_INTERRUPT: c_int = 0x1000;
+const SHCNRF_NEW_DELIVERY: c_int = 0x8000;
+
+const SHCNE_RENAMEITEM: LONG = 0x00000001;
+const SHCNE_CREATE: LONG = 0x00000002;
+const SHCNE_DELETE: LONG = 0x00000004;
+const SHCNE_MKDIR: LONG = 0x00000008;
+const SHCNE_RMDIR: LONG = 0x00000010;
+const SHCNE_MEDIAINSERTED: LONG = 0x00000020;
+const SHCNE_MEDIAREMOVED: LONG = 0x00000040;
+const SHCNE_DRIVEREMOVED: LONG = 0x00000080;
+const SHCNE_DRIVEADD: LONG = 0x00000100;
+const SHCNE_ATTRIBUTES: LONG = 0x00000800;
+const SHCNE_UPDATEDIR: LONG = 0x00001000;
+const SHCNE_UPDATEITEM: LONG = 0x00002000;
+const SHCNE_RENAMEFOLDER: LONG = 0x00020000;
+const SHCNE_ALLEVENTS: LONG = 0x7FFFFFFF;
+const SHCNE_INTERRUPT: LONG = 0x80000000u32 as LONG;
+
+/// COM interfaces and structures of the context menus that are not defined in winapi
+#[allow(non_snake_case, non_upper_case_globals, dead_code)]
+mod com {
+    use winapi::shared::basetsd::UINT_PTR;
+    use winapi::shared::minwindef::{DWORD, UINT, WPARAM, LPARAM, LRESULT};
+    use winapi::shared::windef::HMENU;
+    use winapi::um::unknwnbase::{IUnknown, IUnknownVtbl};
+    use winapi::shared::windef::{HWND, POINT};
+    use winapi::um::winnt::{HRESULT, HANDLE, CHAR, LPCSTR, LPCWSTR};
+    use winapi::ctypes::c_int;
+    use winapi::{RIDL, DEFINE_GUID};
+
+    #[repr(C)]
+    pub struct CMINVOKECOMMANDINFOEX {
+        pub cbSize: DWORD,
+        pub fMask: DWORD,
+        pub hwnd: HWND,
+        pub lpVerb: LPCSTR,
+        pub lpParameters: LPCSTR,
+        pub lpDirectory: LPCSTR,
+        pub nShow: c_int,
+        pub dwHotKey: DWORD,
+        pub hIcon: HANDLE,
+        pub lpTitle: LPCSTR,
+        pub lpVerbW: LPCWSTR,
+        pub lpParametersW: LPCWSTR,
+        pub lpDirectoryW: LPCWSTR,
+        pub lpTitleW: LPCWSTR,
+        pub ptInvoke: POINT,
+    }
+
+    DEFINE_GUID!{BHID_SFUIObject,
+        0x3981e225, 0xf559, 0x11d3, 0x8e, 0x3a, 0x00, 0xc0, 0x4f, 0x68, 0x37, 0xd5}
+
+    // `InvokeCommand` takes a `CMINVOKECOMMANDINFO`. NWG always sends the extended version of the structure.
+    RIDL!{#[uuid(0x000214e4, 0x0000, 0x0000, 0xc0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46)]
+    interface IContextMenu(IContextMenuVtbl): IUnknown(IUnknownVtbl) {
+        fn QueryContextMenu(
+            hmenu: HMENU,
+            indexMenu: UINT,
+            idCmdFirst: UINT,
+            idCmdLast: UINT,
+            uFlags: UINT,
+        ) -> HRESULT,
+        fn InvokeCommand(
+            pici: *mut CMINVOKECOMMANDINFOEX,
+        ) -> HRESULT,
+        fn GetCommandString(
+            idCmd: UINT_PTR,
+            uType: UINT,
+            pReserved: *mut UINT,
+            pszName: *mut CHAR,
+            cchMax: UINT,
+        ) -> HRESULT,
+    }}
+
+    RIDL!{#[uuid(0x000214f4, 0x0000, 0x0000, 0xc0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46)]
+    interface IContextMenu2(IContextMenu2Vtbl): IContextMenu(IContextMenuVtbl) {
+        fn HandleMenuMsg(
+            uMsg: UINT,
+            wParam: WPARAM,
+            lParam: LPARAM,
+        ) -> HRESULT,
+    }}
+
+    RIDL!{#[uuid(0xbcfce0a0, 0xec17, 0x11d0, 0x8d, 0x10, 0x00, 0xa0, 0xc9, 0x0f, 0x27, 0x19)]
+    interface IContextMenu3(IContextMenu3Vtbl): IContextMenu2(IContextMenu2Vtbl) {
+        fn HandleMenuMsg2(
+            uMsg: UINT,
+            wParam: WPARAM,
+            lParam: LPARAM,
+            plResult: *mut LRESULT,
+        ) -> HRESULT,
+    }}
+}
+
+use self::com::{BHID_SFUIObject, IContextMenu, IContextMenu2, IContextMenu3, CMINVOKECOMMANDINFOEX};
+
+/// Id of the raw event handler that forwards the menu messages to a context menu
+const CONTEXT_MENU_HANDLER_ID: usize = 0xC3E0;
+
+/// The first and the last command ids of a context menu
+const CONTEXT_MENU_FIRST: UINT = 1;
+const CONTEXT_MENU_LAST: UINT = 0x7FFF;
+
+const CMF_NORMAL: UINT = 0x0;
+const CMF_EXTENDEDVERBS: UINT = 0x100;
+const CMIC_MASK_UNICODE: DWORD = 0x4000;
+const CMIC_MASK_PTINVOKE: DWORD = 0x20000000;
+
+
+/// The reason a shell function failed. See `NwgError::ShellError`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShellErrorKind {
+    /// The file or its folder does not exist
+    NotFound,
+
+    /// No program is associated with the file type or with the verb
+    NoAssociation,
+
+    /// The program must run as administrator. Launch it again with the `"runas"` verb.
+    ElevationRequired,
+
+    /// The user does not have the permission to open the file
+    AccessDenied,
+
+    /// The user cancelled the operation (ex: refused the elevation prompt)
+    Cancelled,
+
+    /// Any other failure
+    Other,
+}
+
+impl ShellErrorKind {
+
+    fn from_win32(code: DWORD) -> ShellErrorKind {
+        match code {
+            ERROR_FILE_NOT_FOUND | ERROR_PATH_NOT_FOUND => ShellErrorKind::NotFound,
+            ERROR_NO_ASSOCIATION => ShellErrorKind::NoAssociation,
+            ERROR_ELEVATION_REQUIRED => ShellErrorKind::ElevationRequired,
+            ERROR_ACCESS_DENIED => ShellErrorKind::AccessDenied,
+            ERROR_CANCELLED => ShellErrorKind::Cancelled,
+            _ => ShellErrorKind::Other,
+        }
+    }
+
+}
+
+
+/**
+    Opens a file, a folder or an url with its default program, like a double click in the explorer.
+    Returns once the program is started.
+
+    Errors:
+      * `ShellErrorKind::NoAssociation` if the file type does not have a default program. See `open_with_dialog`.
+      * `ShellErrorKind::ElevationRequired` if the program must run as administrator. See `launch_verb`.
+*/
+pub fn launch_default(path: &str) -> Result<(), NwgError> {
+    shell_execute(path, None)
+}
+
+/**
+    Runs a verb of the context menu of a file (ex: "open", "edit", "print", or "runas" to open the file as administrator).
+
+    Errors:
+      * `ShellErrorKind::NoAssociation` if the file type does not have the verb
+      * `ShellErrorKind::Cancelled` if the user refused the elevation prompt of "runas"
+*/
+pub fn launch_verb(path: &str, verb: &str) -> Result<(), NwgError> {
+    shell_execute(path, Some(verb))
+}
+
+/**
+    Shows the "Open with" dialog of the system for a file, then opens the file with the program chosen by the user.
+    The user can also make the program the default program of the file type.
+
+    Returns `Ok(false)` if the user closed the dialog without choosing a program.
+*/
+pub fn open_with_dialog(path: &str) -> Result<bool, NwgError> {
+    if !Path::new(path).exists() {
+        return Err(NwgError::shell(ShellErrorKind::NotFound, format!("{:?} does not exist", path)));
+    }
+
+    let file = to_utf16(path);
+    let info = OPENASINFO {
+        pcszFile: file.as_ptr(),
+        pcszClass: ptr::null(),
+        oaifInFlags: OAIF_ALLOW_REGISTRATION | OAIF_EXEC,
+    };
+
+    match unsafe { SHOpenWithDialog(ptr::null_mut(), &info) } {
+        S_OK => Ok(true),
+        e if e == HRESULT_FROM_WIN32(ERROR_CANCELLED) => Ok(false),
+        e => Err(NwgError::shell(ShellErrorKind::Other, format!("Failed to show the \"Open with\" dialog for {:?} (error {:#X})", path, e)))
+    }
+}
+
+/**
+    Shows the context menu of the explorer for one or more files or folders, and runs the command selected by the user
+    (ex: "Open with", "Send to", the commands added by other programs). Returns once the menu is closed.
+    Returns `Ok(true)` if the user selected a command.
+
+    The paths must be in the same folder. `position` is in screen coordinates (ex: `GlobalCursor::position()`).
+    The extended commands are shown if the shift key is pressed, like in the explorer.
+
+    The parent must be a window control. NWG forwards the menu messages of the parent to the context menu while it is
+    shown (the submenus and the icons of some commands are drawn by their program).
+
+    Some commands (ex: "Delete", "Rename") change the files. The application should reload them after the menu closes.
+*/
+pub fn show_context_menu<C: Into<ControlHandle>>(parent: C, paths: &[&str], position: (i32, i32)) -> Result<bool, NwgError> {
+    use winapi::um::winuser::{CreatePopupMenu, DestroyMenu, TrackPopupMenuEx, GetKeyState, VK_SHIFT, TPM_RETURNCMD, TPM_RIGHTBUTTON, SW_SHOWNORMAL,
+        WM_INITMENUPOPUP, WM_DRAWITEM, WM_MEASUREITEM, WM_MENUCHAR};
+
+    let parent = parent.into();
+    let hwnd = parent.hwnd().expect("Context menu parent must be a window control");
+
+    if paths.is_empty() {
+        return Err(NwgError::shell(ShellErrorKind::Other, "The context menu requires at least one path"));
+    }
+
+    for &path in paths {
+        if !Path::new(path).exists() {
+            return Err(NwgError::shell(ShellErrorKind::NotFound, format!("{:?} does not exist", path)));
+        }
+    }
+
+    unsafe {
+        let menu = context_menu(paths)?;
+        let menu_ref = &mut *menu;
+
+        let mut flags = CMF_NORMAL;
+        if GetKeyState(VK_SHIFT) < 0 {
+            flags |= CMF_EXTENDEDVERBS;
+        }
+
+        let hmenu = CreatePopupMenu();
+        if menu_ref.QueryContextMenu(hmenu, 0, CONTEXT_MENU_FIRST, CONTEXT_MENU_LAST, flags) < 0 {
+            DestroyMenu(hmenu);
+            menu_ref.Release();
+            return Err(NwgError::shell(ShellErrorKind::Other, format!("Failed to build the context menu of {:?}", paths)));
+        }
+
+        // The newer interfaces handle the messages of the owner drawn items and of the submenus filled on demand
+        let mut menu3: *mut IContextMenu3 = ptr::null_mut();
+        let mut menu2: *mut IContextMenu2 = ptr::null_mut();
+        if menu_ref.QueryInterface(&IContextMenu3::uuidof(), &mut menu3 as *mut *mut IContextMenu3 as _) != S_OK {
+            menu3 = ptr::null_mut();
+            if menu_ref.QueryInterface(&IContextMenu2::uuidof(), &mut menu2 as *mut *mut IContextMenu2 as _) != S_OK {
+                menu2 = ptr::null_mut();
+            }
+        }
+
+        let handler = match menu3.is_null() && menu2.is_null() {
+            true => None,
+            false => {
+                let (menu2, menu3) = (menu2 as usize, menu3 as usize);
+                bind_raw_event_handler_inner(&parent, CONTEXT_MENU_HANDLER_ID, move |_hwnd, msg, w, l| {
+                    match msg {
+                        WM_INITMENUPOPUP | WM_DRAWITEM | WM_MEASUREITEM | WM_MENUCHAR => {
+                            let mut result = 0;
+                            let handled = match menu3 {
+                                0 => (&mut *(menu2 as *mut IContextMenu2)).HandleMenuMsg(msg, w, l) == S_OK,
+                                menu3 => (&mut *(menu3 as *mut IContextMenu3)).HandleMenuMsg2(msg, w, l, &mut result) == S_OK,
+                            };
+
+                            match (handled, msg) {
+                                (true, WM_MENUCHAR) => Some(result),
+                                (true, WM_DRAWITEM) | (true, WM_MEASUREITEM) => Some(1),
+                                (true, _) => Some(0),
+                                (false, _) => None
+                            }
+                        },
+                        _ => None
+                    }
+                }).ok().map(|h| h.guard())
+            }
+        };
+
+        let (x, y) = position;
+        let command = TrackPopupMenuEx(hmenu, TPM_RETURNCMD | TPM_RIGHTBUTTON, x, y, hwnd, ptr::null_mut()) as UINT;
+        drop(handler);
+
+        let mut result = Ok(false);
+        if command >= CONTEXT_MENU_FIRST {
+            let mut info: CMINVOKECOMMANDINFOEX = mem::zeroed();
+            info.cbSize = mem::size_of::<CMINVOKECOMMANDINFOEX>() as DWORD;
+            info.fMask = CMIC_MASK_UNICODE | CMIC_MASK_PTINVOKE;
+            info.hwnd = hwnd;
+
+            // The verb is the offset of the command, passed like `MAKEINTRESOURCE`
+            info.lpVerb = (command - CONTEXT_MENU_FIRST) as usize as LPCSTR;
+            info.lpVerbW = (command - CONTEXT_MENU_FIRST) as usize as LPCWSTR;
+            info.nShow = SW_SHOWNORMAL;
+            info.ptInvoke = POINT { x, y };
+
+            result = match menu_ref.InvokeCommand(&mut info) {
+                S_OK => Ok(true),
+                e if e == HRESULT_FROM_WIN32(ERROR_CANCELLED) => Ok(false),
+                e => Err(NwgError::shell(ShellErrorKind::Other, format!("Failed to run the command of the context menu (error {:#X})", e)))
+            };
+        }
+
+        DestroyMenu(hmenu);
+        if !menu3.is_null() { (&mut *menu3).Release(); }
+        if !menu2.is_null() { (&mut *menu2).Release(); }
+        menu_ref.Release();
+
+        result
+    }
+}
+
+/// Returns the `IContextMenu` of a group of files
+unsafe fn context_menu(paths: &[&str]) -> Result<*mut IContextMenu, NwgError> {
+    let pidls: Vec<*mut ITEMIDLIST> = paths.iter().map(|p| ILCreateFromPathW(to_utf16(p).as_ptr())).collect();
+
+    let mut array: *mut IShellItemArray = ptr::null_mut();
+    let mut menu: *mut IContextMenu = ptr::null_mut();
+    let mut result = match pidls.iter().any(|p| p.is_null()) {
+        true => -1,
+        false => SHCreateShellItemArrayFromIDLists(pidls.len() as UINT, pidls.as_ptr(), &mut array)
+    };
+
+    if result == S_OK {
+        result = (&mut *array).BindToHandler(ptr::null_mut(), &BHID_SFUIObject, &IContextMenu::uuidof(), &mut menu as *mut *mut IContextMenu as _);
+        (&mut *array).Release();
+    }
+
+    for pidl in pidls.into_iter().filter(|p| !p.is_null()) {
+        ILFree(pidl);
+    }
+
+    match result {
+        S_OK => Ok(menu),
+        e => Err(NwgError::shell(ShellErrorKind::Other, format!("Failed to get the context menu of {:?} (error {:#X})", paths, e)))
+    }
+}
+
+
+/// The kind of a `ShellChange`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ShellChangeKind {
+    /// A file was created
+    Created,
+    /// A file was deleted
+    Deleted,
+    /// A file was renamed or moved. `ShellChange::new_path` holds the new path.
+    Renamed,
+    /// The content of a file changed
+    Updated,
+    /// The attributes of a file or a folder changed
+    Attributes,
+    /// A folder was created
+    FolderCreated,
+    /// A folder was deleted
+    FolderDeleted,
+    /// A folder was renamed or moved. `ShellChange::new_path` holds the new path.
+    FolderRenamed,
+    /// The content of a folder changed. The shell sends this instead of the individual changes when many items change at once.
+    FolderUpdated,
+    /// A media (ex: a CD or a USB key) was inserted in a drive
+    MediaInserted,
+    /// A media was removed from a drive
+    MediaRemoved,
+    /// A drive was added
+    DriveAdded,
+    /// A drive was removed
+    DriveRemoved,
+    /// Any other change. Holds the `SHCNE_*` value.
+    Other(u32),
+}
+
+impl ShellChangeKind {
+
+    fn from_event(event: LONG) -> ShellChangeKind {
+        match event & !SHCNE_INTERRUPT {
+            SHCNE_CREATE => ShellChangeKind::Created,
+            SHCNE_DELETE => ShellChangeKind::Deleted,
+            SHCNE_RENAMEITEM => ShellChangeKind::Renamed,
+            SHCNE_UPDATEITEM => ShellChangeKind::Updated,
+            SHCNE_ATTRIBUTES => ShellChangeKind::Attributes,
+            SHCNE_MKDIR => ShellChangeKind::FolderCreated,
+            SHCNE_RMDIR => ShellChangeKind::FolderDeleted,
+            SHCNE_RENAMEFOLDER => ShellChangeKind::FolderRenamed,
+            SHCNE_UPDATEDIR => ShellChangeKind::FolderUpdated,
+            SHCNE_MEDIAINSERTED => ShellChangeKind::MediaInserted,
+            SHCNE_MEDIAREMOVED => ShellChangeKind::MediaRemoved,
+            SHCNE_DRIVEADD => ShellChangeKind::DriveAdded,
+            SHCNE_DRIVEREMOVED => ShellChangeKind::DriveRemoved,
+            e => ShellChangeKind::Other(e as u32),
+        }
+    }
+
+}
+
+/// A change reported by a `ShellWatcher`. See `Event::OnShellChange`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShellChange {
+    pub kind: ShellChangeKind,
+
+    /// The item that changed. `None` if the item is not in the file system (ex: a printer).
+    pub path: Option<PathBuf>,
+
+    /// The new path of a renamed item
+    pub new_path: Option<PathBuf>,
+}
+
+/**
+    Watches the changes of a folder through the shell and sends them to a window as `OnShellChange` events.
+    Unlike `ReadDirectoryChangesW`, the shell also reports the media insertions and the changes of the drives, and merges the
+    changes made by the explorer. The notifications are sent asynchronously, a short time after the change.
+
+    The watcher stops when it is dropped.
+
+    ```rust
+    use native_windows_gui as nwg;
+    use nwg::shell::{ShellWatcher, ShellChangeKind};
+
+    fn watch(window: &nwg::Window) -> Result<ShellWatcher, nwg::NwgError> {
+        let watcher = ShellWatcher::new(window, Some("C:\\Projects"), true)?;
+
+        nwg::bind_event_handler(&window.handle, &window.handle, |evt, data, _handle| {
+            if evt == nwg::Event::OnShellChange {
+                let change = data.on_shell_change();
+                if change.kind == ShellChangeKind::Renamed {
+                    println!("{:?} -> {:?}", change.path, change.new_path);
+                }
+            }
+        });
+
+        Ok(watcher)
+    }
+    ```
+*/
+#[derive(Debug)]
+pub struct ShellWatcher {
+    id: ULONG,
+}
+
+impl ShellWatcher {
+
+    /**
+        Starts to watch a folder and, if `recursive` is true, its subfolders. If `folder` is `None`, watches the whole
+        shell namespace (ex: to receive the media insertions of every drive).
+        The events are sent to `window`, which must be a window control.
+    */
+    pub fn new<C: Into<ControlHandle>>(window: C, folder: Option<&str>, recursive: bool) -> Result<ShellWatcher, NwgError> {
+        use super::window_helper::NWG_SHELL_CHANGE;
+
+        let hwnd = window.into().hwnd().expect("ShellWatcher window must be a window control");
+
+        let pidl = match folder {
+            Some(folder) => {
+                if !Path::new(folder).exists() {
+                    return Err(NwgError::shell(ShellErrorKind::NotFound, format!("{:?} does not exist", folder)));
+                }
+
+                let pidl = unsafe { ILCreateFromPathW(to_utf16(folder).as_ptr()) };
+                if pidl.is_null() {
+                    return Err(NwgError::shell(ShellErrorKind::Other, format!("Failed to watch {:?}", folder)));
+                }
+
+                pidl
+            },
+            None => ptr::null_mut()
+        };
+
+        let entry = SHChangeNotifyEntry { pidl, fRecursive: recursive as BOOL };
+
+        let mut sources = SHCNRF_INTERRUPT_LEVEL | SHCNRF_SHELL_LEVEL | SHCNRF_NEW_DELIVERY;
+        if recursive {
+            sources |= SHCNRF_RECURSIVE_INTERRUPT;
+        }
+
+        let id = unsafe { 
+            let id = SHChangeNotifyRegister(hwnd, sources, SHCNE_ALLEVENTS, NWG_SHELL_CHANGE, 1, &entry);
+            if !pidl.is_null() {
+                ILFree(pidl);
+            }
+
+            id
+        };
+
+        match id {
+            0 => Err(NwgError::shell(ShellErrorKind::Other, format!("Failed to watch {:?}", folder))),
+            id => Ok(ShellWatcher { id })
+        }
+    }
+
+}
+
+impl Drop for ShellWatcher {
+    fn drop(&mut self) {
+        unsafe { SHChangeNotifyDeregister(self.id); }
+    }
+}
+
+/// Reads a `NWG_SHELL_CHANGE` message sent by the shell to the window of a `ShellWatcher`
+pub(crate) unsafe fn read_change(w: WPARAM, l: LPARAM) -> Option<ShellChange> {
+    let mut pidls: *mut *mut ITEMIDLIST = ptr::null_mut();
+    let mut event: LONG = 0;
+
+    let lock = SHChangeNotification_Lock(w as HANDLE, l as DWORD, &mut pidls, &mut event);
+    if lock.is_null() {
+        return None;
+    }
+
+    let change = ShellChange {
+        kind: ShellChangeKind::from_event(event),
+        path: pidl_path(*pidls),
+        new_path: pidl_path(*pidls.add(1)),
+    };
+
+    SHChangeNotification_Unlock(lock);
+
+    Some(change)
+}
+
+unsafe fn pidl_path(pidl: *mut ITEMIDLIST) -> Option<PathBuf> {
+    use winapi::um::shlobj::{SHGetPathFromIDListEx, GPFIDL_DEFAULT};
+    use super::base_helper::os_string_from_wide_ptr;
+
+    if pidl.is_null() {
+        return None;
+    }
+
+    // Long enough for the extended paths
+    let mut buffer: Vec<u16> = vec![0; 0x8000];
+    match SHGetPathFromIDListEx(pidl, buffer.as_mut_ptr(), buffer.len() as DWORD, GPFIDL_DEFAULT) {
+        0 => None,
+        _ => Some(PathBuf::from(os_string_from_wide_ptr(buffer.as_mut_ptr(), None)))
+    }
+}
+
+fn shell_execute(path: &str, verb: Option<&str>) -> Result<(), NwgError> {
+    use winapi::um::shellapi::{ShellExecuteExW, SHELLEXECUTEINFOW, SEE_MASK_NOASYNC, SEE_MASK_FLAG_NO_UI};
+    use winapi::um::winuser::SW_SHOWNORMAL;
+    use winapi::um::errhandlingapi::GetLastError;
+
+    let file = to_utf16(path);
+    let verb_w = verb.map(to_utf16);
+
+    unsafe {
+        let mut info: SHELLEXECUTEINFOW = mem::zeroed();
+        info.cbSize = mem::size_of::<SHELLEXECUTEINFOW>() as DWORD;
+
+        // Without `SEE_MASK_FLAG_NO_UI`, the shell shows its own error dialogs instead of returning the error
+        info.fMask = SEE_MASK_NOASYNC | SEE_MASK_FLAG_NO_UI;
+        info.lpVerb = verb_w.as_ref().map(|v| v.as_ptr()).unwrap_or(ptr::null());
+        info.lpFile = file.as_ptr();
+        info.nShow = SW_SHOWNORMAL;
+
+        if ShellExecuteExW(&mut info) != 0 {
+            return Ok(());
+        }
+
+        let code = GetLastError();
+        let kind = ShellErrorKind::from_win32(code);
+        let message = match (kind, verb) {
+            (ShellErrorKind::NoAssociation, Some(verb)) => format!("No program is associated with the verb {:?} of {:?}", verb, path),
+            (ShellErrorKind::NoAssociation, None) => format!("No program is associated with {:?}", path),
+            (ShellErrorKind::ElevationRequired, _) => format!("The program that opens {:?} must run as administrator", path),
+            (ShellErrorKind::NotFound, _) => format!("{:?} does not exist", path),
+            _ => format!("Failed to open {:?} (error {})", path, code),
+        };
+
+        Err(NwgError::shell(kind, message))
+    }
+}
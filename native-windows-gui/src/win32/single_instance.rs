@@ -0,0 +1,233 @@
+/*!
+    Single instance applications. The command line of the instances started after the first one is forwarded to the first instance.
+*/
+use winapi::shared::windef::HWND;
+use winapi::um::winnt::HANDLE;
+use winapi::um::winuser::COPYDATASTRUCT;
+use super::base_helper::to_utf16;
+use super::command_line::CommandLine;
+use super::window::{create_message_window, bind_raw_event_handler_inner, unbind_raw_event_handler, RawEventHandler};
+use crate::{ControlHandle, NwgError};
+use std::ffi::OsString;
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
+use std::{mem, ptr, slice, thread, time::Duration};
+
+/// Id of the raw event handler that receives the forwarded command lines
+const FORWARD_HANDLER_ID: usize = 0x51A1;
+
+/// Identifies the `WM_COPYDATA` messages sent by `SingleInstance::forward`
+const FORWARD_MAGIC: usize = 0x4E57_4731;
+
+/// How long a new instance waits for the first instance to create its window
+const FORWARD_RETRIES: u32 = 40;
+const FORWARD_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// How long a new instance waits for the first instance to process the command line
+const FORWARD_TIMEOUT: u32 = 5000;
+
+
+/**
+    Makes sure that only one instance of the application runs for the current user session.
+
+    The first instance owns a named mutex and a message window. The next instances detect the mutex, forward their command
+    line to the first instance with `forward`, then exit. The first instance receives the command lines in the callback
+    set with `on_forward`, with the current directory of the instance that forwarded it.
+
+    Combined with `file_assoc::register_verb`, the files selected in the shell are opened in a single window: the shell
+    starts one process per file, and every process forwards its file to the first one.
+
+    Requires the `single-instance` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn start() -> Option<nwg::SingleInstance> {
+        let mut instance = nwg::SingleInstance::acquire("MyCompany.MyApp").expect("Failed to check the running instances");
+        if !instance.is_primary() {
+            instance.forward(&nwg::command_line());
+            return None;
+        }
+
+        instance.on_forward(|cmd, current_dir| {
+            for file in cmd.files() {
+                let path = current_dir.join(file);
+                // open the document in the existing window
+            }
+        });
+
+        Some(instance)
+    }
+    ```
+*/
+pub struct SingleInstance {
+    name: String,
+    mutex: HANDLE,
+    primary: bool,
+    window: Option<ControlHandle>,
+    handler: Option<RawEventHandler>,
+}
+
+impl SingleInstance {
+
+    /**
+        Checks if another instance of the application is running. `app_id` must be unique to the application (ex: "MyCompany.MyApp").
+
+        If no other instance is running, the message window that receives the forwarded command lines is created.
+        NWG must be initialized first.
+    */
+    pub fn acquire(app_id: &str) -> Result<SingleInstance, NwgError> {
+        use winapi::um::synchapi::CreateMutexW;
+        use winapi::um::errhandlingapi::GetLastError;
+        use winapi::shared::winerror::ERROR_ALREADY_EXISTS;
+
+        let name = window_name(app_id);
+        let mutex_name = to_utf16(&format!("Local\\{}", name));
+
+        let mutex = unsafe { CreateMutexW(ptr::null_mut(), 0, mutex_name.as_ptr()) };
+        if mutex.is_null() {
+            return Err(NwgError::initialization(format!("Failed to create the mutex of {:?}", app_id)));
+        }
+
+        let primary = unsafe { GetLastError() } != ERROR_ALREADY_EXISTS;
+
+        let mut instance = SingleInstance { name, mutex, primary, window: None, handler: None };
+        if primary {
+            instance.window = Some(create_message_window(&instance.name)?);
+        }
+
+        Ok(instance)
+    }
+
+    /// Returns `true` if this process is the first instance of the application
+    pub fn is_primary(&self) -> bool {
+        self.primary
+    }
+
+    /**
+        Sets the callback that receives the command lines forwarded by the other instances. Replaces the previous callback.
+        Does nothing if this process is not the first instance.
+    */
+    pub fn on_forward<F>(&mut self, callback: F)
+        where F: Fn(&CommandLine, &Path) + 'static
+    {
+        use winapi::um::winuser::WM_COPYDATA;
+
+        let window = match self.window {
+            Some(w) => w,
+            None => { return; }
+        };
+
+        if let Some(handler) = self.handler.take() {
+            drop(unbind_raw_event_handler(&handler));
+        }
+
+        let handler = bind_raw_event_handler_inner(&window, FORWARD_HANDLER_ID, move |_hwnd, msg, _w, l| {
+            if msg != WM_COPYDATA {
+                return None;
+            }
+
+            let data = unsafe { &*(l as *const COPYDATASTRUCT) };
+            if data.dwData != FORWARD_MAGIC {
+                return None;
+            }
+
+            let (current_dir, cmd) = unsafe { decode(data) };
+            callback(&cmd, &current_dir);
+
+            Some(1)
+        });
+
+        self.handler = handler.ok();
+    }
+
+    /**
+        Sends a command line to the first instance. The first instance can bring its window to the foreground.
+        Returns `false` if the first instance did not answer (ex: it is closing).
+    */
+    pub fn forward(&self, cmd: &CommandLine) -> bool {
+        use winapi::um::winuser::{AllowSetForegroundWindow, SendMessageTimeoutW, FindWindowExW, HWND_MESSAGE, WM_COPYDATA, SMTO_ABORTIFHUNG, ASFW_ANY};
+
+        if self.primary {
+            return false;
+        }
+
+        let name = to_utf16(&self.name);
+        let class = to_utf16("NativeWindowsGuiWindow");
+
+        let mut target: HWND = ptr::null_mut();
+        for _ in 0..FORWARD_RETRIES {
+            target = unsafe { FindWindowExW(HWND_MESSAGE, ptr::null_mut(), class.as_ptr(), name.as_ptr()) };
+            if !target.is_null() {
+                break;
+            }
+
+            thread::sleep(FORWARD_RETRY_DELAY);
+        }
+
+        if target.is_null() {
+            return false;
+        }
+
+        let payload = encode(cmd);
+        let data = COPYDATASTRUCT {
+            dwData: FORWARD_MAGIC,
+            cbData: (payload.len() * mem::size_of::<u16>()) as u32,
+            lpData: payload.as_ptr() as _,
+        };
+
+        unsafe {
+            AllowSetForegroundWindow(ASFW_ANY);
+
+            let mut result = 0;
+            let sent = SendMessageTimeoutW(target, WM_COPYDATA, 0, &data as *const COPYDATASTRUCT as _, SMTO_ABORTIFHUNG, FORWARD_TIMEOUT, &mut result);
+            sent != 0 && result == 1
+        }
+    }
+
+}
+
+impl Drop for SingleInstance {
+    fn drop(&mut self) {
+        use winapi::um::handleapi::CloseHandle;
+
+        if let Some(handler) = self.handler.take() {
+            drop(unbind_raw_event_handler(&handler));
+        }
+
+        if let Some(mut window) = self.window.take() {
+            window.destroy();
+        }
+
+        unsafe { CloseHandle(self.mutex); }
+    }
+}
+
+/// Name of the mutex and of the message window of an application
+fn window_name(app_id: &str) -> String {
+    // `\` is not allowed in the name of a kernel object
+    format!("NWG.SingleInstance.{}", app_id.replace('\\', "_"))
+}
+
+/// Current directory and raw command line, separated by a null character
+fn encode(cmd: &CommandLine) -> Vec<u16> {
+    let current_dir = std::env::current_dir().unwrap_or_default();
+
+    let mut payload: Vec<u16> = current_dir.as_os_str().encode_wide().collect();
+    payload.push(0);
+    payload.extend(cmd.raw().encode_wide());
+    payload
+}
+
+unsafe fn decode(data: &COPYDATASTRUCT) -> (PathBuf, CommandLine) {
+    let payload = match data.lpData.is_null() {
+        true => &[],
+        false => slice::from_raw_parts(data.lpData as *const u16, data.cbData as usize / mem::size_of::<u16>())
+    };
+
+    let split = payload.iter().position(|&c| c == 0).unwrap_or(payload.len());
+    let current_dir = PathBuf::from(OsString::from_wide(&payload[..split]));
+    let raw = OsString::from_wide(payload.get(split+1..).unwrap_or(&[]));
+
+    (current_dir, CommandLine::parse(raw))
+}
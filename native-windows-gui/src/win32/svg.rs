@@ -0,0 +1,220 @@
+/*!
+    Rasterize SVG documents into GDI bitmaps and icons using the Direct2D SVG renderer.
+
+    Direct2D SVG support requires Windows 10 (Creators update) or later.
+*/
+use winapi::shared::windef::{HBITMAP, HDC, RECT};
+use winapi::shared::winerror::S_OK;
+use winapi::um::d2d1::*;
+use winapi::um::d2d1_3::ID2D1DeviceContext5;
+use winapi::um::d2d1svg::ID2D1SvgDocument;
+use winapi::um::dcommon::{D2D1_PIXEL_FORMAT, D2D1_ALPHA_MODE_PREMULTIPLIED};
+use winapi::shared::dxgiformat::DXGI_FORMAT_B8G8R8A8_UNORM;
+use winapi::um::objidlbase::IStream;
+use winapi::um::winnt::HANDLE;
+use winapi::ctypes::{c_void, c_uint};
+use winapi::Interface;
+use crate::NwgError;
+use std::convert::TryInto;
+use std::ptr;
+
+
+// Not declared by winapi, but exported by shlwapi
+extern "system" {
+    fn SHCreateMemStream(p_init: *const u8, cb_init: c_uint) -> *mut IStream;
+}
+
+/// Read a svg file and render it as a 32 bits premultiplied alpha bitmap of `size` physical pixels
+pub(crate) unsafe fn svg_file_bitmap(path: &str, size: (u32, u32)) -> Result<HANDLE, NwgError> {
+    let data = std::fs::read(path)
+        .map_err(|e| NwgError::resource_create(format!("Failed to read svg file {:?}: {}", path, e)))?;
+
+    svg_bitmap(&data, size)
+}
+
+/// Render the svg document in `data` as a 32 bits premultiplied alpha bitmap of `size` physical pixels
+pub(crate) unsafe fn svg_bitmap(data: &[u8], size: (u32, u32)) -> Result<HANDLE, NwgError> {
+    use winapi::um::wingdi::{CreateCompatibleDC, DeleteDC, DeleteObject, SelectObject};
+
+    let (width, height) = size;
+    if width == 0 || height == 0 {
+        return Err(NwgError::resource_create("Svg image size must be greater than 0"));
+    }
+
+    let dc = CreateCompatibleDC(ptr::null_mut());
+    if dc.is_null() {
+        return Err(NwgError::resource_create("Failed to create a memory device context"));
+    }
+
+    let bitmap = match create_dib(dc, width, height) {
+        Ok(bitmap) => bitmap,
+        Err(e) => {
+            DeleteDC(dc);
+            return Err(e);
+        }
+    };
+
+    let old = SelectObject(dc, bitmap as _);
+    let result = render(dc, data, width, height);
+    SelectObject(dc, old);
+    DeleteDC(dc);
+
+    match result {
+        Ok(()) => Ok(bitmap as HANDLE),
+        Err(e) => {
+            DeleteObject(bitmap as _);
+            Err(e)
+        }
+    }
+}
+
+/// Render the svg document in `data` as an icon of `size` physical pixels
+pub(crate) unsafe fn svg_icon(data: &[u8], size: (u32, u32)) -> Result<HANDLE, NwgError> {
+    use winapi::um::wingdi::DeleteObject;
+    use winapi::um::winuser::{CreateIconIndirect, ICONINFO};
+
+    let color_bmp = svg_bitmap(data, size)?;
+    let mut icon_info = ICONINFO {
+        fIcon: 1,
+        xHotspot: 0,
+        yHotspot: 0,
+        hbmMask: color_bmp as _,
+        hbmColor: color_bmp as _
+    };
+
+    let icon = CreateIconIndirect(&mut icon_info);
+    DeleteObject(color_bmp as _);
+
+    match icon.is_null() {
+        true => Err(NwgError::resource_create("Failed to create icon from svg source")),
+        false => Ok(icon as _)
+    }
+}
+
+/// Read a svg file and render it as an icon of `size` physical pixels
+pub(crate) unsafe fn svg_file_icon(path: &str, size: (u32, u32)) -> Result<HANDLE, NwgError> {
+    let data = std::fs::read(path)
+        .map_err(|e| NwgError::resource_create(format!("Failed to read svg file {:?}: {}", path, e)))?;
+
+    svg_icon(&data, size)
+}
+
+/// Converts a size in logical units into the physical size used to rasterize a svg source
+pub(crate) fn physical_size(size: (u32, u32)) -> (u32, u32) {
+    let (w, h) = unsafe { crate::win32::high_dpi::logical_to_physical(size.0 as i32, size.1 as i32) };
+    (w.max(1) as u32, h.max(1) as u32)
+}
+
+unsafe fn create_dib(dc: HDC, width: u32, height: u32) -> Result<HBITMAP, NwgError> {
+    use winapi::um::wingdi::{CreateDIBSection, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, RGBQUAD};
+    use std::mem;
+
+    let mut info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: width as i32,
+            biHeight: -(height as i32),
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB,
+            biSizeImage: 0,
+            biXPelsPerMeter: 0,
+            biYPelsPerMeter: 0,
+            biClrUsed: 0,
+            biClrImportant: 0,
+        },
+        bmiColors: [RGBQUAD { rgbBlue: 0, rgbGreen: 0, rgbRed: 0, rgbReserved: 0 }],
+    };
+
+    let mut bits: *mut c_void = ptr::null_mut();
+    let bitmap = CreateDIBSection(dc, &mut info, DIB_RGB_COLORS, &mut bits, ptr::null_mut(), 0);
+    match bitmap.is_null() {
+        true => Err(NwgError::resource_create("Failed to allocate the svg bitmap")),
+        false => Ok(bitmap)
+    }
+}
+
+unsafe fn render(dc: HDC, data: &[u8], width: u32, height: u32) -> Result<(), NwgError> {
+    let stream = SHCreateMemStream(data.as_ptr(), data.len().try_into().map_err(|_| {
+        NwgError::resource_create("Failed to create memory stream, stream is too long")
+    })?);
+    if stream.is_null() {
+        return Err(NwgError::resource_create("Failed to create memory stream, allocation failure"));
+    }
+
+    let mut factory: *mut ID2D1Factory = ptr::null_mut();
+    let result = D2D1CreateFactory(
+        D2D1_FACTORY_TYPE_SINGLE_THREADED,
+        &ID2D1Factory::uuidof(),
+        ptr::null(),
+        (&mut factory as *mut *mut ID2D1Factory) as *mut *mut c_void
+    );
+
+    if result != S_OK {
+        (&*stream).Release();
+        return Err(NwgError::resource_create("Failed to create the direct2D factory"));
+    }
+
+    let result = render_with_factory(&*factory, stream, dc, width, height);
+
+    (&*factory).Release();
+    (&*stream).Release();
+
+    result
+}
+
+unsafe fn render_with_factory(factory: &ID2D1Factory, stream: *mut IStream, dc: HDC, width: u32, height: u32) -> Result<(), NwgError> {
+    let props = D2D1_RENDER_TARGET_PROPERTIES {
+        _type: D2D1_RENDER_TARGET_TYPE_DEFAULT,
+        pixelFormat: D2D1_PIXEL_FORMAT { format: DXGI_FORMAT_B8G8R8A8_UNORM, alphaMode: D2D1_ALPHA_MODE_PREMULTIPLIED },
+        dpiX: 96.0, dpiY: 96.0,
+        usage: D2D1_RENDER_TARGET_USAGE_NONE,
+        minLevel: D2D1_FEATURE_LEVEL_DEFAULT
+    };
+
+    let mut target: *mut ID2D1DCRenderTarget = ptr::null_mut();
+    if factory.CreateDCRenderTarget(&props, &mut target) != S_OK {
+        return Err(NwgError::resource_create("Failed to create the direct2D render target"));
+    }
+
+    let target = &*target;
+    let rect = RECT { left: 0, top: 0, right: width as i32, bottom: height as i32 };
+    if target.BindDC(dc, &rect) != S_OK {
+        target.Release();
+        return Err(NwgError::resource_create("Failed to bind the direct2D render target"));
+    }
+
+    let mut context: *mut ID2D1DeviceContext5 = ptr::null_mut();
+    let result = target.QueryInterface(
+        &ID2D1DeviceContext5::uuidof(),
+        (&mut context as *mut *mut ID2D1DeviceContext5) as *mut *mut c_void
+    );
+
+    if result != S_OK {
+        target.Release();
+        return Err(NwgError::resource_create("Svg rendering is not supported on this system"));
+    }
+
+    let context = &*context;
+    let viewport = D2D1_SIZE_F { width: width as f32, height: height as f32 };
+    let mut document: *mut ID2D1SvgDocument = ptr::null_mut();
+    if context.CreateSvgDocument(stream, viewport, &mut document) != S_OK {
+        context.Release();
+        target.Release();
+        return Err(NwgError::resource_create("Failed to parse the svg document"));
+    }
+
+    target.BeginDraw();
+    target.Clear(&D2D1_COLOR_F { r: 0.0, g: 0.0, b: 0.0, a: 0.0 });
+    context.DrawSvgDocument(document);
+    let result = target.EndDraw(ptr::null_mut(), ptr::null_mut());
+
+    (&*document).Release();
+    context.Release();
+    target.Release();
+
+    match result == S_OK {
+        true => Ok(()),
+        false => Err(NwgError::resource_create("Failed to render the svg document"))
+    }
+}
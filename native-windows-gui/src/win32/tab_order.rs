@@ -0,0 +1,306 @@
+/*!
+    Keyboard navigation between the controls of a window with Tab, Shift+Tab and the arrow keys.
+
+    The events loop gives the key presses to `IsDialogMessage`, which follows the z-order of the controls and does not
+    enter the containers without the `WS_EX_CONTROLPARENT` style. Windows registered here are navigated by NWG instead:
+    either in an explicit order, or in the order of the controls with the `TAB_STOP` flag, including the controls in containers.
+*/
+use winapi::shared::windef::HWND;
+use winapi::shared::minwindef::{BOOL, LPARAM};
+use winapi::um::winuser::MSG;
+use crate::ControlHandle;
+use std::cell::RefCell;
+
+
+/// A window navigated by NWG and its explicit tab order (or `None` to use the order of the controls)
+struct Registration {
+    window: HWND,
+    order: Option<Vec<HWND>>,
+}
+
+thread_local! {
+    static WINDOWS: RefCell<Vec<Registration>> = RefCell::new(Vec::new());
+}
+
+/**
+    Enables the keyboard navigation of a window: Tab and Shift+Tab move the focus to the next and the previous control
+    with the `TAB_STOP` flag, in the order of the controls (the order of creation, or the order of the layouts that place them).
+    Unlike the default navigation, the controls inside containers (ex: a `Frame`) are included.
+
+    The arrow keys move the focus between the controls of a group (a group starts at a control with the `WS_GROUP` style),
+    unless the focused control uses the arrow keys itself (ex: a text input or a list).
+
+    `window` can be any control of the window, the navigation applies to its top level window.
+
+    Requires the `tab-order` feature.
+*/
+pub fn enable_tab_navigation<C: Into<ControlHandle>>(window: C) {
+    let root = root_window(window.into());
+    register(root, None);
+}
+
+/**
+    Sets an explicit tab order for a window and enables its keyboard navigation (see `enable_tab_navigation`).
+    The controls receive the focus in the order of `controls`. Hidden or disabled controls are skipped.
+    Controls that are not in `controls` are not reached with the Tab key.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn tab_order(window: &nwg::Window, name: &nwg::TextInput, email: &nwg::TextInput, ok: &nwg::Button) {
+        nwg::set_tab_order(window, &[name.handle, email.handle, ok.handle]);
+    }
+    ```
+
+    Requires the `tab-order` feature.
+*/
+pub fn set_tab_order<C: Into<ControlHandle>>(window: C, controls: &[ControlHandle]) {
+    let root = root_window(window.into());
+    let order = controls.iter()
+        .map(|c| c.hwnd().expect("Tab order controls must be window-like controls (HWND handle)"))
+        .collect();
+
+    register(root, Some(order));
+}
+
+/**
+    Disables the keyboard navigation of NWG for a window. The window goes back to the default navigation of the system.
+
+    Requires the `tab-order` feature.
+*/
+pub fn clear_tab_order<C: Into<ControlHandle>>(window: C) {
+    let root = root_window(window.into());
+    WINDOWS.with(|windows| windows.borrow_mut().retain(|r| r.window != root));
+}
+
+/**
+    Returns the controls of a window in tab order: the explicit order if `set_tab_order` was called, or the controls
+    with the `TAB_STOP` flag otherwise. Hidden and disabled controls are included.
+
+    Requires the `tab-order` feature.
+*/
+pub fn tab_order<C: Into<ControlHandle>>(window: C) -> Vec<ControlHandle> {
+    let root = root_window(window.into());
+    unsafe { navigation_order(root) }.into_iter().map(ControlHandle::Hwnd).collect()
+}
+
+/**
+    Moves the focus to the next control in the tab order of the window that has the focus.
+    Returns `false` if no control could receive the focus.
+
+    Requires the `tab-order` feature.
+*/
+pub fn focus_next() -> bool {
+    unsafe { move_focus_from_current(true) }
+}
+
+/**
+    Moves the focus to the previous control in the tab order of the window that has the focus.
+    Returns `false` if no control could receive the focus.
+
+    Requires the `tab-order` feature.
+*/
+pub fn focus_prev() -> bool {
+    unsafe { move_focus_from_current(false) }
+}
+
+/**
+    Translates the navigation keys for the windows registered by `enable_tab_navigation` or `set_tab_order`.
+    Returns `true` if the message was handled and must not be dispatched.
+*/
+pub(crate) unsafe fn translate_tab_navigation(msg: &mut MSG) -> bool {
+    use winapi::um::winuser::{GetAncestor, GetKeyState, SendMessageW, GA_ROOT, WM_KEYDOWN, WM_GETDLGCODE,
+        VK_TAB, VK_LEFT, VK_UP, VK_RIGHT, VK_DOWN, VK_CONTROL, VK_MENU, VK_SHIFT, DLGC_WANTTAB, DLGC_WANTARROWS, DLGC_WANTALLKEYS};
+
+    if msg.message != WM_KEYDOWN || msg.hwnd.is_null() {
+        return false;
+    }
+
+    let key = msg.wParam as i32;
+    match key {
+        VK_TAB | VK_LEFT | VK_UP | VK_RIGHT | VK_DOWN => {},
+        _ => { return false; }
+    }
+
+    // Ctrl+Tab and Alt combinations are used by other controls (ex: the tabs)
+    if GetKeyState(VK_CONTROL) < 0 || GetKeyState(VK_MENU) < 0 {
+        return false;
+    }
+
+    let root = GetAncestor(msg.hwnd, GA_ROOT);
+    if !is_registered(root) {
+        return false;
+    }
+
+    let code = SendMessageW(msg.hwnd, WM_GETDLGCODE, msg.wParam, &*msg as *const MSG as LPARAM);
+    let order = navigation_order(root);
+
+    match key {
+        VK_TAB => {
+            if code & (DLGC_WANTTAB | DLGC_WANTALLKEYS) != 0 {
+                return false;
+            }
+
+            let forward = GetKeyState(VK_SHIFT) >= 0;
+            move_focus(&order, msg.hwnd, forward, false);
+            true
+        },
+        _ => {
+            if code & (DLGC_WANTARROWS | DLGC_WANTALLKEYS) != 0 {
+                return false;
+            }
+
+            let forward = key == VK_RIGHT || key == VK_DOWN;
+            move_focus(&order, msg.hwnd, forward, true)
+        }
+    }
+}
+
+fn root_window(handle: ControlHandle) -> HWND {
+    use winapi::um::winuser::{GetAncestor, GA_ROOT};
+
+    let handle = handle.hwnd().expect("Tab navigation requires a window-like control (HWND handle)");
+    let root = unsafe { GetAncestor(handle, GA_ROOT) };
+    match root.is_null() {
+        true => handle,
+        false => root
+    }
+}
+
+fn register(window: HWND, order: Option<Vec<HWND>>) {
+    WINDOWS.with(|windows| {
+        let mut windows = windows.borrow_mut();
+        windows.retain(|r| r.window != window);
+        windows.push(Registration { window, order });
+    });
+}
+
+fn is_registered(window: HWND) -> bool {
+    use winapi::um::winuser::IsWindow;
+
+    WINDOWS.with(|windows| {
+        let mut windows = windows.borrow_mut();
+        windows.retain(|r| unsafe { IsWindow(r.window) != 0 });
+        windows.iter().any(|r| r.window == window)
+    })
+}
+
+/// Returns the explicit tab order of a window, or its controls with the `WS_TABSTOP` style in z-order
+unsafe fn navigation_order(root: HWND) -> Vec<HWND> {
+    use winapi::um::winuser::{EnumChildWindows, GWL_STYLE, WS_TABSTOP};
+
+    let order = WINDOWS.with(|windows| {
+        windows.borrow().iter()
+            .find(|r| r.window == root)
+            .and_then(|r| r.order.clone())
+    });
+
+    if let Some(order) = order {
+        return order;
+    }
+
+    let mut children: Vec<HWND> = Vec::new();
+    EnumChildWindows(root, Some(collect_children), &mut children as *mut Vec<HWND> as LPARAM);
+    children.retain(|&child| super::window_helper::get_window_long(child, GWL_STYLE) as u32 & WS_TABSTOP != 0);
+    children
+}
+
+unsafe extern "system" fn collect_children(hwnd: HWND, children: LPARAM) -> BOOL {
+    let children = &mut *(children as *mut Vec<HWND>);
+    children.push(hwnd);
+    1
+}
+
+unsafe fn move_focus_from_current(forward: bool) -> bool {
+    use winapi::um::winuser::{GetFocus, GetAncestor, GA_ROOT};
+
+    let focus = GetFocus();
+    if focus.is_null() {
+        return false;
+    }
+
+    let order = navigation_order(GetAncestor(focus, GA_ROOT));
+    move_focus(&order, focus, forward, false)
+}
+
+/**
+    Moves the focus from the control `focus` to the next (or previous) control of `order` that can receive it.
+    If `in_group` is true, the focus stays in the group of `focus` (as with the arrow keys in a dialog).
+*/
+unsafe fn move_focus(order: &[HWND], focus: HWND, forward: bool, in_group: bool) -> bool {
+    use winapi::um::winuser::{IsChild, GWL_STYLE, WS_GROUP};
+
+    let len = order.len();
+    if len == 0 {
+        return false;
+    }
+
+    let current = order.iter().position(|&c| c == focus || IsChild(c, focus) != 0);
+    let (start, end) = match (in_group, current) {
+        (true, Some(index)) => {
+            let is_group = |&c: &HWND| super::window_helper::get_window_long(c, GWL_STYLE) as u32 & WS_GROUP != 0;
+            let start = (0..=index).rev().find(|&i| is_group(&order[i])).unwrap_or(0);
+            let end = (index+1..len).find(|&i| is_group(&order[i])).unwrap_or(len);
+            (start, end)
+        },
+        (true, None) => { return false; },
+        (false, _) => (0, len)
+    };
+
+    let count = end - start;
+    for step in 1..=count {
+        let index = match (current, forward) {
+            (Some(i), true) => start + (i - start + step) % count,
+            (Some(i), false) => start + (i - start + count - (step % count)) % count,
+            (None, true) => start + step - 1,
+            (None, false) => end - step,
+        };
+
+        let target = order[index];
+        if Some(index) == current {
+            break;
+        }
+
+        if can_focus(target) {
+            focus_control(target, in_group);
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Checks that a control and all its parents are visible and enabled
+unsafe fn can_focus(handle: HWND) -> bool {
+    use winapi::um::winuser::{IsWindowVisible, IsWindowEnabled, GetParent};
+
+    if IsWindowVisible(handle) == 0 {
+        return false;
+    }
+
+    let mut current = handle;
+    while !current.is_null() {
+        if IsWindowEnabled(current) == 0 {
+            return false;
+        }
+
+        current = GetParent(current);
+    }
+
+    true
+}
+
+unsafe fn focus_control(handle: HWND, from_arrow: bool) {
+    use winapi::um::winuser::{SetFocus, SendMessageW, WM_GETDLGCODE, EM_SETSEL, BM_CLICK, DLGC_HASSETSEL, DLGC_RADIOBUTTON};
+
+    SetFocus(handle);
+
+    // Same behaviour as the dialogs: the text is selected when a text input receives the focus with Tab,
+    // and a radio button is checked when it receives the focus with an arrow key
+    let code = SendMessageW(handle, WM_GETDLGCODE, 0, 0);
+    if !from_arrow && code & DLGC_HASSETSEL != 0 {
+        SendMessageW(handle, EM_SETSEL as u32, 0, -1);
+    } else if from_arrow && code & DLGC_RADIOBUTTON != 0 {
+        SendMessageW(handle, BM_CLICK, 0, 0);
+    }
+}
@@ -0,0 +1,409 @@
+/*!
+    The taskbar button of the top level windows: the progress bar drawn in the button and the toolbar of the thumbnail preview.
+
+    The state of a button is kept for each window and applied again when explorer sends `TaskbarButtonCreated`: the button
+    of a window is only created once the window is shown, and every button is recreated when explorer restarts.
+*/
+use winapi::shared::windef::{HWND, HICON};
+use winapi::shared::minwindef::UINT;
+use winapi::shared::winerror::SUCCEEDED;
+use winapi::um::shobjidl_core::{ITaskbarList3, CLSID_TaskbarList, THUMBBUTTON};
+use winapi::um::unknwnbase::IUnknown;
+use winapi::ctypes::c_void;
+use winapi::Interface;
+use super::base_helper::to_utf16;
+use super::window::bind_raw_event_handler_inner;
+use crate::{ControlHandle, Icon, NwgError};
+use std::{mem, ptr, cell::RefCell, collections::HashMap};
+
+/// Id of the raw event handlers that listen to `TaskbarButtonCreated`
+const TASKBAR_HANDLER_ID: usize = 0x7BB7;
+
+/// Not defined in winapi
+const MSGFLT_ALLOW: u32 = 1;
+
+/// Maximum number of buttons in the toolbar of a thumbnail preview
+const MAX_THUMBNAIL_BUTTONS: usize = 7;
+
+/// Ids of the hidden buttons that fill the unused slots of a toolbar. The user ids must be smaller.
+const PLACEHOLDER_ID: u32 = 0xFFF0;
+
+
+/// The state of the progress bar drawn in a taskbar button
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProgressState {
+    /// No progress bar
+    None,
+
+    /// A progress bar that cycles without a value, for operations of unknown length
+    Indeterminate,
+
+    /// A green progress bar
+    Normal,
+
+    /// A red progress bar
+    Error,
+
+    /// A yellow progress bar
+    Paused,
+}
+
+impl Default for ProgressState {
+    fn default() -> ProgressState { ProgressState::None }
+}
+
+/**
+    A button of the toolbar shown in the thumbnail preview of a window. See `set_thumbnail_buttons`.
+
+    Clicking the button raises a `OnThumbnailButtonClick` event on the window, with the id of the button.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThumbnailButton {
+    /// The id sent with the `OnThumbnailButtonClick` event. Must be smaller than `0xFFF0`.
+    pub id: u32,
+    /// The tooltip of the button. Truncated to 259 characters.
+    pub tooltip: String,
+    /// The icon of the button. The icon must outlive its use by the taskbar.
+    pub icon: HICON,
+    /// A disabled button is grayed and cannot be clicked
+    pub enabled: bool,
+    /// Closes the thumbnail preview when the button is clicked
+    pub dismiss_on_click: bool,
+}
+
+impl ThumbnailButton {
+
+    pub fn new(id: u32, tooltip: &str) -> ThumbnailButton {
+        ThumbnailButton {
+            id,
+            tooltip: tooltip.to_string(),
+            icon: ptr::null_mut(),
+            enabled: true,
+            dismiss_on_click: false,
+        }
+    }
+
+    /// Sets the icon of the button
+    pub fn icon(mut self, icon: &Icon) -> ThumbnailButton {
+        self.icon = icon.handle;
+        self
+    }
+
+    /// Sets if the button can be clicked
+    pub fn enabled(mut self, enabled: bool) -> ThumbnailButton {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Sets if clicking the button closes the thumbnail preview
+    pub fn dismiss_on_click(mut self, dismiss: bool) -> ThumbnailButton {
+        self.dismiss_on_click = dismiss;
+        self
+    }
+
+}
+
+/// The state of the taskbar button of a window. The hook of the window lives until the window is destroyed.
+#[derive(Default)]
+struct TaskbarButton {
+    progress: (ProgressState, u64, u64),
+    thumbnail_buttons: Vec<ThumbnailButton>,
+    /// The toolbar buttons can only be added once to a taskbar button, then they are updated
+    thumbnail_buttons_added: bool,
+}
+
+/// The `ITaskbarList3` object of the thread. Released when explorer restarts.
+struct TaskbarList(*mut ITaskbarList3);
+
+impl Drop for TaskbarList {
+    fn drop(&mut self) {
+        unsafe { (*(self.0 as *mut IUnknown)).Release(); }
+    }
+}
+
+thread_local! {
+    static BUTTONS: RefCell<HashMap<usize, TaskbarButton>> = RefCell::new(HashMap::new());
+    static TASKBAR_LIST: RefCell<Option<TaskbarList>> = RefCell::new(None);
+}
+
+/**
+    Sets the progress bar drawn in the taskbar button of a top level window. `value` is clamped to `total`.
+    The value is ignored with `ProgressState::None` and `ProgressState::Indeterminate`.
+
+    The progress is kept if the window is not shown yet or if explorer restarts.
+
+    This function panics if `window` is not a window-like control.
+*/
+pub fn set_taskbar_progress<C: Into<ControlHandle>>(window: C, state: ProgressState, value: u64, total: u64) {
+    let handle = window.into().hwnd().expect("Taskbar progress can only be set on window-like controls");
+    let progress = (state, value.min(total), total);
+
+    if with_button(handle, |button| button.progress = progress).is_some() {
+        apply_progress(handle, progress);
+    }
+}
+
+/**
+    Returns the progress drawn in the taskbar button of a window as `(state, value, total)`.
+*/
+pub fn taskbar_progress<C: Into<ControlHandle>>(window: C) -> (ProgressState, u64, u64) {
+    let handle = window.into().hwnd().expect("Taskbar progress can only be set on window-like controls");
+    BUTTONS.with(|buttons| {
+        buttons.borrow().get(&(handle as usize)).map(|b| b.progress).unwrap_or((ProgressState::None, 0, 0))
+    })
+}
+
+/**
+    Sets the toolbar shown in the thumbnail preview of a top level window. A toolbar has at most seven buttons.
+    Setting an empty list hides the toolbar.
+
+    Clicking a button raises a `OnThumbnailButtonClick` event on the window, with the id of the button.
+    The toolbar is kept if the window is not shown yet or if explorer restarts.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn player_buttons(window: &nwg::Window, previous: &nwg::Icon, play: &nwg::Icon, next: &nwg::Icon) -> Result<(), nwg::NwgError> {
+        nwg::set_thumbnail_buttons(window, &[
+            nwg::ThumbnailButton::new(1, "Previous").icon(previous),
+            nwg::ThumbnailButton::new(2, "Play").icon(play),
+            nwg::ThumbnailButton::new(3, "Next").icon(next).enabled(false),
+        ])
+    }
+    ```
+
+    This function panics if `window` is not a window-like control.
+*/
+pub fn set_thumbnail_buttons<C: Into<ControlHandle>>(window: C, buttons: &[ThumbnailButton]) -> Result<(), NwgError> {
+    let handle = window.into().hwnd().expect("Thumbnail buttons can only be set on window-like controls");
+
+    if buttons.len() > MAX_THUMBNAIL_BUTTONS {
+        return Err(NwgError::taskbar(format!("A thumbnail toolbar has at most {} buttons", MAX_THUMBNAIL_BUTTONS)));
+    }
+
+    if let Some(button) = buttons.iter().find(|b| b.id >= PLACEHOLDER_ID) {
+        return Err(NwgError::taskbar(format!("Thumbnail button id {} is too large", button.id)));
+    }
+
+    match with_button(handle, |button| button.thumbnail_buttons = buttons.to_vec()) {
+        Some(_) => {
+            apply_thumbnail_buttons(handle);
+            Ok(())
+        },
+        None => Err(NwgError::taskbar("Failed to hook the window"))
+    }
+}
+
+/**
+    Enables or disables a button of the thumbnail toolbar of a window. Does nothing if the window does not have a button with this id.
+*/
+pub fn set_thumbnail_button_enabled<C: Into<ControlHandle>>(window: C, id: u32, enabled: bool) {
+    let handle = window.into().hwnd().expect("Thumbnail buttons can only be set on window-like controls");
+
+    let found = BUTTONS.with(|buttons| {
+        let mut buttons = buttons.borrow_mut();
+        let button = buttons.get_mut(&(handle as usize)).and_then(|b| b.thumbnail_buttons.iter_mut().find(|b| b.id == id));
+        match button {
+            Some(button) => {
+                button.enabled = enabled;
+                true
+            },
+            None => false
+        }
+    });
+
+    if found {
+        apply_thumbnail_buttons(handle);
+    }
+}
+
+/// Returns the buttons of the thumbnail toolbar of a window
+pub fn thumbnail_buttons<C: Into<ControlHandle>>(window: C) -> Vec<ThumbnailButton> {
+    let handle = window.into().hwnd().expect("Thumbnail buttons can only be set on window-like controls");
+    BUTTONS.with(|buttons| {
+        buttons.borrow().get(&(handle as usize)).map(|b| b.thumbnail_buttons.clone()).unwrap_or_default()
+    })
+}
+
+/// Calls `f` with the state of the taskbar button of a window. Hooks the window the first time.
+/// Returns `None` if the window cannot be hooked.
+fn with_button<T, F: FnOnce(&mut TaskbarButton) -> T>(handle: HWND, f: F) -> Option<T> {
+    let known = BUTTONS.with(|buttons| buttons.borrow().contains_key(&(handle as usize)));
+    if !known {
+        if !hook_button_created(handle) {
+            return None;
+        }
+
+        BUTTONS.with(|buttons| {
+            buttons.borrow_mut().insert(handle as usize, TaskbarButton::default());
+        });
+    }
+
+    BUTTONS.with(|buttons| {
+        buttons.borrow_mut().get_mut(&(handle as usize)).map(f)
+    })
+}
+
+/// Calls `f` with the `ITaskbarList3` object of the thread. Returns `None` if the object cannot be created.
+fn with_taskbar_list<T, F: FnOnce(&ITaskbarList3) -> T>(f: F) -> Option<T> {
+    use winapi::um::combaseapi::CoCreateInstance;
+    use winapi::shared::wtypesbase::CLSCTX_INPROC_SERVER;
+
+    let list = TASKBAR_LIST.with(|list| {
+        let mut list = list.borrow_mut();
+        if list.is_none() {
+            unsafe {
+                let mut instance: *mut ITaskbarList3 = ptr::null_mut();
+                let hr = CoCreateInstance(&CLSID_TaskbarList, ptr::null_mut(), CLSCTX_INPROC_SERVER, &ITaskbarList3::uuidof(), &mut instance as *mut _ as *mut *mut c_void);
+                if !SUCCEEDED(hr) {
+                    return None;
+                }
+
+                let instance = TaskbarList(instance);
+                if !SUCCEEDED((*instance.0).HrInit()) {
+                    return None;
+                }
+
+                *list = Some(instance);
+            }
+        }
+
+        list.as_ref().map(|l| l.0)
+    })?;
+
+    // The list is not borrowed while calling into the taskbar, because the taskbar may send messages to the window
+    Some(f(unsafe { &*list }))
+}
+
+fn apply_progress(handle: HWND, progress: (ProgressState, u64, u64)) {
+    use winapi::um::shobjidl_core::{TBPF_NOPROGRESS, TBPF_INDETERMINATE, TBPF_NORMAL, TBPF_ERROR, TBPF_PAUSED};
+
+    let (state, value, total) = progress;
+    let flag = match state {
+        ProgressState::None => TBPF_NOPROGRESS,
+        ProgressState::Indeterminate => TBPF_INDETERMINATE,
+        ProgressState::Normal => TBPF_NORMAL,
+        ProgressState::Error => TBPF_ERROR,
+        ProgressState::Paused => TBPF_PAUSED,
+    };
+
+    // Setting a value switches the button to the normal state, so the value is set before the state
+    with_taskbar_list(|list| unsafe {
+        match state {
+            ProgressState::Normal | ProgressState::Error | ProgressState::Paused => {
+                list.SetProgressValue(handle, value, total.max(1));
+            },
+            _ => {}
+        }
+
+        list.SetProgressState(handle, flag);
+    });
+}
+
+/// Adds the toolbar of the thumbnail preview the first time, then updates it. The unused slots are hidden buttons.
+fn apply_thumbnail_buttons(handle: HWND) {
+    use winapi::um::shobjidl_core::{THB_ICON, THB_TOOLTIP, THB_FLAGS, THBF_ENABLED, THBF_DISABLED, THBF_DISMISSONCLICK, THBF_HIDDEN};
+
+    let (buttons, added) = match BUTTONS.with(|b| b.borrow().get(&(handle as usize)).map(|b| (b.thumbnail_buttons.clone(), b.thumbnail_buttons_added))) {
+        Some(state) => state,
+        None => { return; }
+    };
+
+    // Nothing to hide if the toolbar was never added
+    if buttons.is_empty() && !added {
+        return;
+    }
+
+    let mut thumb_buttons: Vec<THUMBBUTTON> = (0..MAX_THUMBNAIL_BUTTONS).map(|i| {
+        let mut thumb: THUMBBUTTON = unsafe { mem::zeroed() };
+        thumb.dwMask = THB_ICON | THB_TOOLTIP | THB_FLAGS;
+
+        match buttons.get(i) {
+            Some(button) => {
+                thumb.iId = button.id;
+                thumb.hIcon = button.icon;
+                thumb.dwFlags = match button.enabled {
+                    true => THBF_ENABLED,
+                    false => THBF_DISABLED
+                };
+
+                if button.dismiss_on_click {
+                    thumb.dwFlags |= THBF_DISMISSONCLICK;
+                }
+
+                let tooltip = to_utf16(&button.tooltip);
+                let length = tooltip.len().min(thumb.szTip.len()) - 1;
+                thumb.szTip[..length].copy_from_slice(&tooltip[..length]);
+            },
+            None => {
+                thumb.iId = PLACEHOLDER_ID + i as u32;
+                thumb.dwFlags = THBF_HIDDEN;
+            }
+        }
+
+        thumb
+    }).collect();
+
+    let count = thumb_buttons.len() as UINT;
+    let added = with_taskbar_list(|list| unsafe {
+        match added {
+            true => list.ThumbBarUpdateButtons(handle, count, thumb_buttons.as_mut_ptr()),
+            false => list.ThumbBarAddButtons(handle, count, thumb_buttons.as_mut_ptr())
+        }
+    });
+
+    // The buttons cannot be added before the taskbar button exists. They are added when explorer sends `TaskbarButtonCreated`.
+    if added.map(SUCCEEDED).unwrap_or(false) {
+        BUTTONS.with(|b| {
+            if let Some(button) = b.borrow_mut().get_mut(&(handle as usize)) {
+                button.thumbnail_buttons_added = true;
+            }
+        });
+    }
+}
+
+/// Applies the state of the button again when explorer creates it, and forgets the window when it is destroyed
+fn hook_button_created(handle: HWND) -> bool {
+    use winapi::um::winuser::{RegisterWindowMessageW, ChangeWindowMessageFilterEx, WM_NCDESTROY, WM_COMMAND};
+
+    let button_created: UINT = unsafe { RegisterWindowMessageW(to_utf16("TaskbarButtonCreated").as_ptr()) };
+    if button_created == 0 {
+        return false;
+    }
+
+    // Explorer may run at a lower integrity level than the application. The clicks on the thumbnail buttons are sent with `WM_COMMAND`.
+    unsafe {
+        ChangeWindowMessageFilterEx(handle, button_created, MSGFLT_ALLOW, ptr::null_mut());
+        ChangeWindowMessageFilterEx(handle, WM_COMMAND, MSGFLT_ALLOW, ptr::null_mut());
+    }
+
+    let handler = bind_raw_event_handler_inner(&ControlHandle::Hwnd(handle), TASKBAR_HANDLER_ID, move |hwnd, msg, _w, _l| {
+        if msg == button_created {
+            // The object of the previous explorer process cannot be used anymore
+            TASKBAR_LIST.with(|list| list.borrow_mut().take());
+
+            // The new taskbar button does not have the toolbar of the previous one
+            let progress = BUTTONS.with(|buttons| {
+                buttons.borrow_mut().get_mut(&(hwnd as usize)).map(|b| {
+                    b.thumbnail_buttons_added = false;
+                    b.progress
+                })
+            });
+
+            if let Some(progress) = progress {
+                apply_progress(hwnd, progress);
+                apply_thumbnail_buttons(hwnd);
+            }
+        } else if msg == WM_NCDESTROY {
+            BUTTONS.with(|buttons| {
+                if let Ok(mut buttons) = buttons.try_borrow_mut() {
+                    buttons.remove(&(hwnd as usize));
+                }
+            });
+        }
+
+        None
+    });
+
+    handler.is_ok()
+}
@@ -0,0 +1,267 @@
+/*!
+    Touch and gesture input.
+
+    By default, the windows receive the gestures of the system (pan, zoom, two finger tap, press and tap) in `OnGesture` events,
+    and the taps are delivered as mouse clicks. A window built with `touch_mode(TouchMode::Gestures)` also receives
+    the rotation gesture and the pan gesture in all directions. A window built with `touch_mode(TouchMode::Touch)` receives
+    the raw touch contacts in `OnTouch` events instead of the gestures.
+
+    The gestures made over a child control are also reported to its parent windows.
+
+    Requires the `touch` feature.
+*/
+use winapi::shared::minwindef::{BOOL, DWORD, UINT, LPARAM};
+use winapi::shared::windef::{HWND, POINT, POINTS};
+use winapi::um::winuser::ScreenToClient;
+use std::{cell::Cell, mem};
+
+/// Not declared by winapi
+#[repr(C)]
+#[allow(non_snake_case)]
+struct GESTUREINFO {
+    cbSize: UINT,
+    dwFlags: DWORD,
+    dwID: DWORD,
+    hwndTarget: HWND,
+    ptsLocation: POINTS,
+    dwInstanceID: DWORD,
+    dwSequenceID: DWORD,
+    ullArguments: u64,
+    cbExtraArgs: UINT,
+}
+
+/// Not declared by winapi
+#[repr(C)]
+#[allow(non_snake_case)]
+struct GESTURECONFIG {
+    dwID: DWORD,
+    dwWant: DWORD,
+    dwBlock: DWORD,
+}
+
+// Not declared by winapi, but exported by user32
+extern "system" {
+    fn GetGestureInfo(hGestureInfo: LPARAM, pGestureInfo: *mut GESTUREINFO) -> BOOL;
+    fn SetGestureConfig(hwnd: HWND, dwReserved: DWORD, cIDs: UINT, pGestureConfig: *mut GESTURECONFIG, cbSize: UINT) -> BOOL;
+}
+
+const GID_ZOOM: DWORD = 3;
+const GID_PAN: DWORD = 4;
+const GID_ROTATE: DWORD = 5;
+const GID_TWOFINGERTAP: DWORD = 6;
+const GID_PRESSANDTAP: DWORD = 7;
+
+const GF_BEGIN: DWORD = 1;
+const GF_INERTIA: DWORD = 2;
+const GF_END: DWORD = 4;
+
+const GC_ALLGESTURES: DWORD = 1;
+
+thread_local! {
+    /// The previous step of the gesture in progress: the window, the gesture id, the location and the argument.
+    /// Used to compute the deltas of the pan, zoom and rotate gestures.
+    static LAST_GESTURE: Cell<Option<(HWND, DWORD, (i32, i32), u64)>> = Cell::new(None);
+}
+
+
+/// How a window receives the touch input. See `WindowBuilder::touch_mode`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TouchMode {
+    /// The default gestures of the system. The taps are delivered as mouse clicks.
+    Default,
+
+    /// All the gestures, including the rotation and the pan in all directions
+    Gestures,
+
+    /// The raw touch contacts, in `OnTouch` events. No gesture is recognized.
+    Touch,
+}
+
+/// The kind of change of a touch contact
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TouchPhase {
+    Down,
+    Move,
+    Up,
+}
+
+/// A touch contact. See `Event::OnTouch`.
+#[derive(Copy, Clone, Debug)]
+pub struct TouchPoint {
+    /// Identifies the contact from the moment it touches the screen until it is lifted
+    pub id: u32,
+
+    /// The position of the contact, in physical pixels, relative to the client area of the window
+    pub position: (i32, i32),
+
+    /// The change of the contact
+    pub phase: TouchPhase,
+
+    /// If the contact is the first one placed on the screen
+    pub primary: bool,
+
+    /// If the contact is made by a pen
+    pub pen: bool,
+}
+
+/// A gesture recognized by the system. See `GestureData`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Gesture {
+    /// The content is dragged. `dx` and `dy` are the movement since the previous step, in physical pixels.
+    /// `inertia` is the velocity of the content once the fingers are lifted, while the system simulates the inertia.
+    Pan { dx: i32, dy: i32, inertia: Option<(i32, i32)> },
+
+    /// Two fingers are moved apart or together. `factor` is the change of scale since the previous step (ex: `1.1` zooms in by 10%).
+    Zoom { factor: f64 },
+
+    /// Two fingers are rotated. `angle` is the rotation since the previous step, in radians. Positive values rotate counterclockwise.
+    Rotate { angle: f64 },
+
+    /// The screen is tapped with two fingers
+    TwoFingerTap,
+
+    /// A finger is held while another finger taps the screen
+    PressAndTap,
+}
+
+/// A step of a gesture. See `Event::OnGesture`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GestureData {
+    /// The gesture and its deltas
+    pub gesture: Gesture,
+
+    /// The center of the gesture, in physical pixels, relative to the client area of the window
+    pub position: (i32, i32),
+
+    /// If this is the first step of the gesture
+    pub begin: bool,
+
+    /// If this is the last step of the gesture
+    pub end: bool,
+}
+
+
+/// Sets how a window receives the touch input
+pub(crate) fn set_touch_mode(hwnd: HWND, mode: TouchMode) {
+    use winapi::um::winuser::{RegisterTouchWindow, UnregisterTouchWindow};
+
+    unsafe {
+        match mode {
+            TouchMode::Touch => { RegisterTouchWindow(hwnd, 0); },
+            TouchMode::Default | TouchMode::Gestures => { UnregisterTouchWindow(hwnd); }
+        }
+
+        // An id of 0 configures all the gestures at once
+        if mode == TouchMode::Gestures {
+            let mut config = GESTURECONFIG { dwID: 0, dwWant: GC_ALLGESTURES, dwBlock: 0 };
+            SetGestureConfig(hwnd, 0, 1, &mut config, mem::size_of::<GESTURECONFIG>() as UINT);
+        }
+    }
+}
+
+/// Reads the touch contacts of a `WM_TOUCH` message. The handle is closed by `DefWindowProc`.
+pub(crate) unsafe fn read_touch(hwnd: HWND, w: usize, l: LPARAM) -> Vec<TouchPoint> {
+    use winapi::um::winuser::{GetTouchInputInfo, TOUCHINPUT, TOUCHEVENTF_DOWN, TOUCHEVENTF_UP, TOUCHEVENTF_PRIMARY, TOUCHEVENTF_PEN};
+
+    let count = (w & 0xFFFF) as usize;
+    let mut inputs: Vec<TOUCHINPUT> = vec![mem::zeroed(); count];
+    if GetTouchInputInfo(l as _, count as UINT, inputs.as_mut_ptr(), mem::size_of::<TOUCHINPUT>() as i32) == 0 {
+        return Vec::new();
+    }
+
+    inputs.iter()
+        .map(|input| {
+            // The touch coordinates are in hundredths of a pixel
+            let mut point = POINT { x: input.x / 100, y: input.y / 100 };
+            ScreenToClient(hwnd, &mut point);
+
+            let phase = match input.dwFlags {
+                f if f & TOUCHEVENTF_DOWN != 0 => TouchPhase::Down,
+                f if f & TOUCHEVENTF_UP != 0 => TouchPhase::Up,
+                _ => TouchPhase::Move,
+            };
+
+            TouchPoint {
+                id: input.dwID,
+                position: (point.x, point.y),
+                phase,
+                primary: input.dwFlags & TOUCHEVENTF_PRIMARY != 0,
+                pen: input.dwFlags & TOUCHEVENTF_PEN != 0,
+            }
+        })
+        .collect()
+}
+
+/**
+    Reads the gesture of a `WM_GESTURE` message. The handle is closed by `DefWindowProc`.
+    Returns `None` for the begin and end notifications of the system, and for the unknown gestures.
+*/
+pub(crate) unsafe fn read_gesture(hwnd: HWND, l: LPARAM) -> Option<GestureData> {
+    use std::f64::consts::PI;
+
+    let mut info: GESTUREINFO = mem::zeroed();
+    info.cbSize = mem::size_of::<GESTUREINFO>() as UINT;
+    if GetGestureInfo(l, &mut info) == 0 {
+        return None;
+    }
+
+    let mut point = POINT { x: info.ptsLocation.x as i32, y: info.ptsLocation.y as i32 };
+    ScreenToClient(hwnd, &mut point);
+    let position = (point.x, point.y);
+
+    let begin = info.dwFlags & GF_BEGIN != 0;
+    let end = info.dwFlags & GF_END != 0;
+    let args = info.ullArguments;
+
+    // The first step of a gesture has no delta
+    let last = LAST_GESTURE.with(|last| last.replace(Some((hwnd, info.dwID, position, args))));
+    let previous = match last {
+        Some((last_hwnd, id, position, args)) if !begin && last_hwnd == hwnd && id == info.dwID => Some((position, args)),
+        _ => None
+    };
+
+    if end {
+        LAST_GESTURE.with(|last| last.set(None));
+    }
+
+    let gesture = match info.dwID {
+        GID_PAN => {
+            let (dx, dy) = match previous {
+                Some(((x, y), _)) => (position.0 - x, position.1 - y),
+                None => (0, 0)
+            };
+
+            // The inertia vector is packed in the high dword of the arguments
+            let inertia = match info.dwFlags & GF_INERTIA != 0 {
+                true => Some((((args >> 32) & 0xFFFF) as i16 as i32, ((args >> 48) & 0xFFFF) as i16 as i32)),
+                false => None
+            };
+
+            Gesture::Pan { dx, dy, inertia }
+        },
+        GID_ZOOM => {
+            // The argument is the distance between the two fingers
+            let factor = match previous {
+                Some((_, distance)) if distance != 0 => args as f64 / distance as f64,
+                _ => 1.0
+            };
+
+            Gesture::Zoom { factor }
+        },
+        GID_ROTATE => {
+            // The argument is the angle since the start of the gesture, mapped from [-2π, 2π] to [0, 65535]
+            let angle = |arg: u64| ((arg & 0xFFFF) as f64 / 65535.0) * 4.0 * PI - 2.0 * PI;
+            let delta = match previous {
+                Some((_, last)) => angle(args) - angle(last),
+                None => 0.0
+            };
+
+            Gesture::Rotate { angle: delta }
+        },
+        GID_TWOFINGERTAP => Gesture::TwoFingerTap,
+        GID_PRESSANDTAP => Gesture::PressAndTap,
+        _ => { return None; }
+    };
+
+    Some(GestureData { gesture, position, begin, end })
+}
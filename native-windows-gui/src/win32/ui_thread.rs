@@ -0,0 +1,166 @@
+/*!
+    Support for the applications that run more than one UI thread.
+
+    A window belongs to the thread that created it: its messages are only dispatched by the events loop of this thread,
+    and the controls of NWG keep their state in thread local, non thread safe, structures. A control must be created,
+    used and destroyed by the thread that owns its parent window. NWG returns a `NwgError::WrongThread` error when a
+    control is created in a parent owned by another thread, when a raw events handler is bound from another thread,
+    and from `try_full_bind_event_handler`, `try_bind_event_handler` and `ControlHandle::check_thread`.
+
+    The other functions panic with a clear message instead of returning an error: the methods of the controls (ex: the setters),
+    `full_bind_event_handler`, `bind_event_handler` and `bind_event_handler_with_priority`. Call `check_thread` on the handle
+    of a control before using it from code that may run on another thread.
+
+    To communicate between the UI threads, use a `Notice` or send messages with the `SendMessage` family of functions.
+*/
+use winapi::shared::windef::HWND;
+use crate::NwgError;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::{ptr, thread};
+
+/// Set when `init_common_controls` succeeded. The window classes are registered for the whole process.
+static PROCESS_INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+/// The id of the thread that initialized NWG first. `nwg::quit` breaks the events loop of this thread.
+static MAIN_THREAD: AtomicU32 = AtomicU32::new(0);
+
+
+/**
+    Initializes NWG in a secondary UI thread. Must be called in each thread that creates windows,
+    except the thread that called `nwg::init`.
+
+    If `nwg::init` was never called, this function calls it.
+*/
+pub fn init_thread() -> Result<(), NwgError> {
+    use winapi::um::objbase::CoInitialize;
+    use winapi::shared::winerror::{S_OK, S_FALSE};
+
+    if !PROCESS_INITIALIZED.load(Ordering::SeqCst) {
+        return crate::init();
+    }
+
+    // COM (used by the dialogs and the image decoder) is initialized per thread
+    match unsafe { CoInitialize(ptr::null_mut()) } {
+        S_OK | S_FALSE => Ok(()),
+        _ => Err(NwgError::initialization("CoInitialize failed"))
+    }
+}
+
+/**
+    A UI thread started with `spawn_ui_thread`
+*/
+pub struct UiThread {
+    thread_id: u32,
+    handle: thread::JoinHandle<Result<(), NwgError>>,
+}
+
+impl UiThread {
+
+    /// Returns the system identifier of the thread
+    pub fn thread_id(&self) -> u32 {
+        self.thread_id
+    }
+
+    /// Breaks the events loop of the thread. The UI built by the thread is dropped once the loop exits.
+    pub fn stop(&self) {
+        use winapi::um::winuser::{PostThreadMessageW, WM_QUIT};
+        unsafe { PostThreadMessageW(self.thread_id, WM_QUIT, 0, 0); }
+    }
+
+    /// Waits for the thread to exit. Returns the error of `init_thread` or of the `build` function, if any.
+    /// Panics if the thread panicked.
+    pub fn join(self) -> Result<(), NwgError> {
+        self.handle.join().expect("The UI thread panicked")
+    }
+
+}
+
+/**
+    Starts a new UI thread. The thread calls `init_thread`, then `build` to create its windows, then runs its own events loop
+    until `stop_thread_dispatch` is called from the thread or `UiThread::stop` is called from another thread.
+    `nwg::quit` only stops the main UI thread.
+
+    The value returned by `build` (usually the UI structure) is kept alive while the events loop runs, and is dropped in the thread.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn open_tool_window() -> nwg::UiThread {
+        nwg::spawn_ui_thread(|| {
+            let mut window = nwg::Window::default();
+            nwg::Window::builder().title("Tool window").build(&mut window)?;
+            Ok(window)
+        })
+    }
+    ```
+*/
+pub fn spawn_ui_thread<F, T>(build: F) -> UiThread
+    where F: FnOnce() -> Result<T, NwgError> + Send + 'static,
+          T: 'static
+{
+    use winapi::um::processthreadsapi::GetCurrentThreadId;
+    use winapi::um::winuser::{PeekMessageW, MSG, PM_NOREMOVE};
+    use std::sync::mpsc;
+    use std::mem;
+
+    let (sender, receiver) = mpsc::channel();
+
+    let handle = thread::spawn(move || {
+        unsafe {
+            // Creates the message queue of the thread before its id is published, so that `UiThread::stop` cannot be lost
+            let mut msg: MSG = mem::zeroed();
+            PeekMessageW(&mut msg, ptr::null_mut(), 0, 0, PM_NOREMOVE);
+            sender.send(GetCurrentThreadId()).ok();
+        }
+
+        init_thread()?;
+
+        let ui = build()?;
+        crate::dispatch_thread_events();
+        drop(ui);
+
+        Ok(())
+    });
+
+    let thread_id = receiver.recv().unwrap_or(0);
+
+    UiThread { thread_id, handle }
+}
+
+/// Marks NWG as initialized. The first thread to initialize NWG becomes the main UI thread.
+pub(crate) fn set_process_initialized() {
+    use winapi::um::processthreadsapi::GetCurrentThreadId;
+
+    let thread = unsafe { GetCurrentThreadId() };
+    MAIN_THREAD.compare_exchange(0, thread, Ordering::SeqCst, Ordering::SeqCst).ok();
+    PROCESS_INITIALIZED.store(true, Ordering::SeqCst);
+}
+
+/// Returns the id of the main UI thread, or 0 if NWG is not initialized
+pub(crate) fn main_thread() -> u32 {
+    MAIN_THREAD.load(Ordering::SeqCst)
+}
+
+/// Returns the id of the thread that owns a window
+pub(crate) fn window_thread(hwnd: HWND) -> u32 {
+    use winapi::um::winuser::GetWindowThreadProcessId;
+    unsafe { GetWindowThreadProcessId(hwnd, ptr::null_mut()) }
+}
+
+/// Returns `true` if the window belongs to the calling thread. Returns `true` if the window was destroyed.
+pub(crate) fn is_window_thread(hwnd: HWND) -> bool {
+    use winapi::um::processthreadsapi::GetCurrentThreadId;
+
+    match window_thread(hwnd) {
+        0 => true,
+        id => id == unsafe { GetCurrentThreadId() }
+    }
+}
+
+/// Returns a `NwgError::WrongThread` error if the window does not belong to the calling thread
+pub(crate) fn check_thread(hwnd: HWND) -> Result<(), NwgError> {
+    match is_window_thread(hwnd) {
+        true => Ok(()),
+        false => Err(NwgError::wrong_thread(format!("The window {:?} belongs to the thread {}", hwnd, window_thread(hwnd))))
+    }
+}
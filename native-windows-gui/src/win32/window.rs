@@ -9,12 +9,15 @@ use winapi::shared::basetsd::{DWORD_PTR, UINT_PTR};
 use winapi::um::winuser::{WNDPROC, NMHDR, IDCANCEL, IDOK};
 use winapi::um::commctrl::{NMTTDISPINFOW, SUBCLASSPROC};
 use super::base_helper::{CUSTOM_ID_BEGIN, to_utf16};
-use super::window_helper::{NOTICE_MESSAGE, NWG_INIT, NWG_TRAY, NWG_TIMER_TICK, NWG_TIMER_STOP};
+use super::window_helper::{NOTICE_MESSAGE, NWG_INIT, NWG_TRAY, NWG_TIMER_TICK, NWG_TIMER_STOP, NWG_APP_EXIT, NWG_ANIMATION_END};
 use super::high_dpi;
 use crate::controls::ControlHandle;
 use crate::{Event, EventData, NwgError};
 use std::{ptr, mem};
 use std::rc::Rc;
+use std::cell::{Cell, RefCell};
+use std::ops::Deref;
+use std::collections::HashMap;
 use std::ffi::OsString;
 use std::os::windows::prelude::OsStringExt;
 use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
@@ -29,13 +32,60 @@ const NO_DATA: EventData = EventData::NoData;
 type RawCallback = dyn Fn(HWND, UINT, WPARAM, LPARAM) -> Option<LRESULT>;
 type Callback = dyn Fn(Event, EventData, ControlHandle) -> ();
 
+thread_local! {
+    /// The event handlers bound to each window, in the order of their subclasses (the last one is called first).
+    /// Each entry is the priority of the handler, its subclass id and the pointer to its callback.
+    static HANDLER_ORDER: RefCell<HashMap<usize, Vec<(i32, UINT_PTR, UINT_PTR)>>> = RefCell::new(HashMap::new());
+
+    /// Set by `consume_event` while an event is dispatched
+    static EVENT_CONSUMED: Cell<bool> = Cell::new(false);
+
+    /// The handlers bound by `full_bind_event_handler`, by subclass id, and the windows created after the handler was bound
+    /// that were subclassed by `bind_late_child`. `unbind_event_handler` also removes the subclasses of those windows.
+    static FULL_HANDLERS: RefCell<HashMap<UINT_PTR, Vec<HWND>>> = RefCell::new(HashMap::new());
+}
+
 /**
     An opaque structure that represent a window subclass hook. 
 */
 pub struct EventHandler {
     handles: Vec<HWND>,
     id: SUBCLASSPROC,
-    subclass_id: UINT_PTR
+    subclass_id: UINT_PTR,
+    callback_ptr: *mut *const Callback,
+    bound: Cell<bool>,
+}
+
+impl EventHandler {
+
+    /// Returns `false` once the handler was unbound
+    pub fn is_bound(&self) -> bool {
+        self.bound.get()
+    }
+
+    /**
+        Wraps the handler in a guard that unbinds it when the guard is dropped.
+
+        ```rust
+        use native_windows_gui as nwg;
+
+        struct Preview {
+            window: nwg::Window,
+            handler: Option<nwg::EventHandlerGuard>,
+        }
+
+        fn bind(preview: &mut Preview) {
+            let handler = nwg::full_bind_event_handler(&preview.window.handle, |_evt, _data, _handle| {});
+
+            // Unbound when `preview` is dropped, even if the window was destroyed first
+            preview.handler = Some(handler.guard());
+        }
+        ```
+    */
+    pub fn guard(self) -> EventHandlerGuard {
+        EventHandlerGuard { handler: Some(self) }
+    }
+
 }
 
 /**
@@ -44,7 +94,100 @@ pub struct EventHandler {
 pub struct RawEventHandler {
     handle: HWND,
     subclass_proc: SUBCLASSPROC,
-    handler_id: UINT_PTR
+    handler_id: UINT_PTR,
+    bound: Cell<bool>,
+}
+
+impl RawEventHandler {
+
+    /// Returns `false` once the handler was unbound by `unbind_raw_event_handler`
+    pub fn is_bound(&self) -> bool {
+        self.bound.get()
+    }
+
+    /// Wraps the handler in a guard that unbinds it when the guard is dropped
+    pub fn guard(self) -> RawEventHandlerGuard {
+        RawEventHandlerGuard { handler: Some(self) }
+    }
+
+}
+
+/**
+    Unbinds an event handler when it is dropped. Returned by `bind_event_handler_guarded` and `ControlHandle::on_guarded`,
+    or created with `EventHandler::guard`. Unlike `unbind_event_handler`, dropping the guard never panics.
+
+    The guard derefs to the handler, so it can also be passed to `unbind_event_handler`.
+    Use `release` to keep the handler bound after the guard is dropped.
+*/
+#[must_use = "the handler is unbound when the guard is dropped"]
+pub struct EventHandlerGuard {
+    handler: Option<EventHandler>,
+}
+
+impl EventHandlerGuard {
+
+    /// Returns the handler without unbinding it
+    pub fn release(mut self) -> EventHandler {
+        self.handler.take().unwrap()
+    }
+
+}
+
+impl Deref for EventHandlerGuard {
+    type Target = EventHandler;
+
+    fn deref(&self) -> &EventHandler {
+        self.handler.as_ref().unwrap()
+    }
+}
+
+impl Drop for EventHandlerGuard {
+    fn drop(&mut self) {
+        if let Some(handler) = self.handler.take() {
+            if handler.is_bound() {
+                unbind_event_handler(&handler);
+            }
+        }
+    }
+}
+
+/**
+    Unbinds a raw event handler when it is dropped. Returned by `bind_raw_event_handler_guarded`, or created with `RawEventHandler::guard`.
+    The error returned when the window was already destroyed is ignored.
+
+    The guard derefs to the handler, so it can also be passed to `unbind_raw_event_handler`.
+    Use `release` to keep the handler bound after the guard is dropped.
+*/
+#[must_use = "the handler is unbound when the guard is dropped"]
+pub struct RawEventHandlerGuard {
+    handler: Option<RawEventHandler>,
+}
+
+impl RawEventHandlerGuard {
+
+    /// Returns the handler without unbinding it
+    pub fn release(mut self) -> RawEventHandler {
+        self.handler.take().unwrap()
+    }
+
+}
+
+impl Deref for RawEventHandlerGuard {
+    type Target = RawEventHandler;
+
+    fn deref(&self) -> &RawEventHandler {
+        self.handler.as_ref().unwrap()
+    }
+}
+
+impl Drop for RawEventHandlerGuard {
+    fn drop(&mut self) {
+        if let Some(handler) = self.handler.take() {
+            if handler.is_bound() {
+                drop(unbind_raw_event_handler(&handler));
+            }
+        }
+    }
 }
 
 
@@ -76,7 +219,8 @@ pub unsafe fn build_timer(parent: HWND, interval: u32, stopped: bool) -> Control
 
     Returns a `EventHandler` that can be passed to `unbind_event_handler` to remove the callbacks.
 
-    This function will panic if `handle` is not a window handle.
+    This function will panic if `handle` is not a window handle, or if it is called from a thread that does not own the window.
+    See `try_full_bind_event_handler` for a version that returns an error instead.
 */
 pub fn full_bind_event_handler<F>(handle: &ControlHandle, f: F) -> EventHandler
     where F: Fn(Event, EventData, ControlHandle) -> () + 'static
@@ -86,6 +230,7 @@ pub fn full_bind_event_handler<F>(handle: &ControlHandle, f: F) -> EventHandler
     struct SetSubclassParam {
         callback_ptr: *mut *const Callback,
         subclass_id: UINT_PTR,
+        priority: i32,
     }
 
     /**
@@ -100,7 +245,7 @@ pub fn full_bind_event_handler<F>(handle: &ControlHandle, f: F) -> EventHandler
         // Simply increase the rc count because the callback
         // will also be stored into the current children window. 
         mem::forget(cb.clone());
-        SetWindowSubclass(h, Some(process_events), params.subclass_id, params.callback_ptr as UINT_PTR);
+        subclass_events(h, params.priority, params.subclass_id, params.callback_ptr as UINT_PTR);
 
         // Do not decrease the refcount
         mem::forget(cb);
@@ -119,6 +264,9 @@ pub fn full_bind_event_handler<F>(handle: &ControlHandle, f: F) -> EventHandler
     }
 
     let hwnd = handle.hwnd().expect("Cannot bind control with an handle of type");
+    if !super::ui_thread::is_window_thread(hwnd) {
+        panic!("Events handlers must be bound from the thread that owns the window. See `nwg::spawn_ui_thread`");
+    }
 
     // The callback function must be passed to each children of the control
     // To do so, we must RC the callback
@@ -132,22 +280,80 @@ pub fn full_bind_event_handler<F>(handle: &ControlHandle, f: F) -> EventHandler
         handles: vec![hwnd],
         id: callback_fn,
         subclass_id,
+        callback_ptr,
+        bound: Cell::new(true),
     };
 
 
-    let params = Box::new(SetSubclassParam { callback_ptr, subclass_id });
+    let params = Box::new(SetSubclassParam { callback_ptr, subclass_id, priority: 0 });
     let params_ptr: *mut SetSubclassParam = Box::into_raw(params);
 
     unsafe {
         EnumChildWindows(hwnd, Some(handler_children), (&mut handler.handles as *mut Vec<HWND>) as LPARAM);
         EnumChildWindows(hwnd, Some(set_children_subclass), params_ptr as LPARAM);
-        SetWindowSubclass(hwnd, callback_fn, subclass_id, callback_ptr as UINT_PTR);
+        subclass_events(hwnd, 0, subclass_id, callback_ptr as UINT_PTR);
         Box::from_raw(params_ptr);
     }
 
+    FULL_HANDLERS.with(|handlers| handlers.borrow_mut().insert(subclass_id, Vec::new()));
+
     handler
 }
 
+/**
+    Subclasses a control created after the handlers of its parent were bound by `full_bind_event_handler` (ex: a `LazyControl`),
+    and its children, with the same handlers. Without this, only the events sent to the parent (ex: `OnButtonClick`) would be received.
+*/
+pub(crate) fn bind_late_child(child: HWND) {
+    use winapi::um::winuser::{EnumChildWindows, GetParent};
+
+    unsafe extern "system" fn collect_children(hwnd: HWND, children: LPARAM) -> i32 {
+        let children = &mut *(children as *mut Vec<HWND>);
+        children.push(hwnd);
+        1
+    }
+
+    let parent = unsafe { GetParent(child) };
+    if parent.is_null() {
+        return;
+    }
+
+    let parent_handlers: Vec<(i32, UINT_PTR, UINT_PTR)> = HANDLER_ORDER.with(|order| {
+        order.borrow().get(&(parent as usize)).cloned().unwrap_or_default()
+    });
+
+    let mut windows = vec![child];
+    unsafe { EnumChildWindows(child, Some(collect_children), &mut windows as *mut Vec<HWND> as LPARAM); }
+
+    FULL_HANDLERS.with(|handlers| {
+        let mut handlers = handlers.borrow_mut();
+        for &(priority, subclass_id, callback_ptr) in parent_handlers.iter() {
+            let late_windows = match handlers.get_mut(&subclass_id) {
+                Some(w) => w,
+                None => { continue; }
+            };
+
+            for &hwnd in windows.iter() {
+                unsafe {
+                    let mut callback_value: UINT_PTR = 0;
+                    if GetWindowSubclass(hwnd, Some(process_events), subclass_id, &mut callback_value) != 0 {
+                        continue;
+                    }
+
+                    // Each subclass holds a reference to the callback. See `full_bind_event_handler`
+                    let cb: Rc<Callback> = Rc::from_raw(*(callback_ptr as *mut *const Callback));
+                    mem::forget(cb.clone());
+                    mem::forget(cb);
+
+                    subclass_events(hwnd, priority, subclass_id, callback_ptr);
+                }
+
+                late_windows.push(hwnd);
+            }
+        }
+    });
+}
+
 
 /**
 Hook the window subclass with the default event dispatcher.
@@ -159,65 +365,178 @@ Arguments:
     - f: User event callback
 
 Returns a `EventHandler` that can be passed to `unbind_event_handler` to remove the callbacks.
+The handler is not unbound when it is dropped. See `bind_event_handler_guarded` for a handler that is.
 
+This function will panic if the handles are not window handles, or if it is called from a thread that does not own the windows.
+See `try_bind_event_handler` for a version that returns an error instead.
 */
 pub fn bind_event_handler<F>(handle: &ControlHandle, parent_handle: &ControlHandle, f: F) -> EventHandler
     where F: Fn(Event, EventData, ControlHandle) -> () + 'static
+{
+    bind_event_handler_with_priority(handle, parent_handle, 0, f)
+}
+
+/**
+Same as `full_bind_event_handler`, but returns an error instead of panicking:
+- `NwgError::EventsBinding` if `handle` is not a window handle
+- `NwgError::WrongThread` if the window is owned by another thread
+*/
+pub fn try_full_bind_event_handler<F>(handle: &ControlHandle, f: F) -> Result<EventHandler, NwgError>
+    where F: Fn(Event, EventData, ControlHandle) -> () + 'static
+{
+    let hwnd = bind_target(handle)?;
+    super::ui_thread::check_thread(hwnd)?;
+
+    Ok(full_bind_event_handler(handle, f))
+}
+
+/**
+Same as `bind_event_handler`, but returns an error instead of panicking:
+- `NwgError::EventsBinding` if the handles are not window handles
+- `NwgError::WrongThread` if one of the windows is owned by another thread
+
+```rust
+use native_windows_gui as nwg;
+
+fn bind(window: &nwg::Window, button: &nwg::Button) -> Result<nwg::EventHandler, nwg::NwgError> {
+    nwg::try_bind_event_handler(&button.handle, &window.handle, |_evt, _data, _handle| {})
+}
+```
+*/
+pub fn try_bind_event_handler<F>(handle: &ControlHandle, parent_handle: &ControlHandle, f: F) -> Result<EventHandler, NwgError>
+    where F: Fn(Event, EventData, ControlHandle) -> () + 'static
+{
+    super::ui_thread::check_thread(bind_target(handle)?)?;
+    super::ui_thread::check_thread(bind_target(parent_handle)?)?;
+
+    Ok(bind_event_handler(handle, parent_handle, f))
+}
+
+/// Returns the window of a control that receives an events handler
+fn bind_target(handle: &ControlHandle) -> Result<HWND, NwgError> {
+    handle.hwnd().ok_or_else(|| NwgError::events_binding("Events handlers can only be bound to window handles"))
+}
+
+/**
+Same as `bind_event_handler`, but returns a `EventHandlerGuard` that removes the callbacks when it is dropped.
+Keep the guard as long as the callback is needed, or call `release` on it to keep the callback until the windows are destroyed.
+
+```rust
+use native_windows_gui as nwg;
+
+struct Preview {
+    window: nwg::Window,
+    button: nwg::Button,
+    handler: Option<nwg::EventHandlerGuard>,
+}
+
+fn bind(preview: &mut Preview) {
+    // Unbound when `preview` is dropped
+    preview.handler = Some(nwg::bind_event_handler_guarded(&preview.button.handle, &preview.window.handle, |_evt, _data, _handle| {}));
+}
+```
+*/
+pub fn bind_event_handler_guarded<F>(handle: &ControlHandle, parent_handle: &ControlHandle, f: F) -> EventHandlerGuard
+    where F: Fn(Event, EventData, ControlHandle) -> () + 'static
+{
+    bind_event_handler(handle, parent_handle, f).guard()
+}
+
+/**
+Same as `bind_event_handler`, but the handler is called before the handlers with a lower priority, whatever the order
+in which they were bound. The handlers bound by the other functions have a priority of 0. The handlers with the same
+priority are called from the last bound to the first bound.
+
+A handler can call `consume_event` to skip the handlers with a lower priority and the default processing of the event
+by the control. For example, a handler of `OnKeyPress` with a priority of 1 can consume a key so that an embedded text input does not receive it.
+
+```rust
+use native_windows_gui as nwg;
+
+fn bind_tab_completion(window: &nwg::Window, input: &nwg::TextInput) -> nwg::EventHandler {
+    nwg::bind_event_handler_with_priority(&input.handle, &window.handle, 1, |evt, data, _handle| {
+        if evt == nwg::Event::OnKeyPress && data.on_key() == nwg::keys::TAB {
+            // complete the text here...
+            nwg::consume_event();
+        }
+    })
+}
+```
+*/
+pub fn bind_event_handler_with_priority<F>(handle: &ControlHandle, parent_handle: &ControlHandle, priority: i32, f: F) -> EventHandler
+    where F: Fn(Event, EventData, ControlHandle) -> () + 'static
 {
     let hwnd = handle.hwnd().expect("Cannot bind control with an handle of type");
     let parent_hwnd = parent_handle.hwnd().expect("Cannot bind control with an handle of type");
-    
+    if !super::ui_thread::is_window_thread(hwnd) || !super::ui_thread::is_window_thread(parent_hwnd) {
+        panic!("Events handlers must be bound from the thread that owns the window. See `nwg::spawn_ui_thread`");
+    }
+
     let callback: Rc<Callback> = Rc::new(f);
-    let parent_callback = callback.clone();
+    let callback_fn: SUBCLASSPROC = Some(process_events);
+    let subclass_id = EVENT_HANDLER_ID.fetch_add(1, Ordering::SeqCst);
 
-    let callback_box: Box<*const Callback> = Box::new(Rc::into_raw(callback));
-    let callback_box_parent: Box<*const Callback> = Box::new(Rc::into_raw(parent_callback));
+    // A window bound to itself is only subclassed once. Subclassing it twice with the same id would leak the first callback.
+    let handles = match hwnd == parent_hwnd {
+        true => vec![hwnd],
+        false => vec![hwnd, parent_hwnd]
+    };
+
+    // Like `full_bind_event_handler`, the subclasses share the same box and each of them holds a reference
+    // to the callback. `unbind_event_handler` releases one reference per handle, then frees the box.
+    for _ in 1..handles.len() {
+        mem::forget(callback.clone());
+    }
 
+    let callback_box: Box<*const Callback> = Box::new(Rc::into_raw(callback));
     let callback_ptr: *mut *const Callback = Box::into_raw(callback_box);
-    let callback_ptr_parent: *mut *const Callback = Box::into_raw(callback_box_parent);
 
-    let callback_fn: SUBCLASSPROC = Some(process_events);
-    let subclass_id = EVENT_HANDLER_ID.fetch_add(1, Ordering::SeqCst);
-    let handler = EventHandler {
-        handles: vec![hwnd, parent_hwnd],
+    for &h in handles.iter() {
+        unsafe { subclass_events(h, priority, subclass_id, callback_ptr as UINT_PTR); }
+    }
+
+    EventHandler {
+        handles,
         id: callback_fn,
         subclass_id,
-    };
-
-    unsafe {
-        SetWindowSubclass(hwnd, callback_fn, subclass_id, callback_ptr as UINT_PTR);
-        SetWindowSubclass(parent_hwnd, callback_fn, subclass_id, callback_ptr_parent as UINT_PTR);
+        callback_ptr,
+        bound: Cell::new(true),
     }
-
-    handler
 }
 
 
 /**
     Free all associated callbacks with the event handler.
+    The windows destroyed since the handler was bound are skipped: their reference to the callback was released with the window.
 
-    This function will panic if the handler was already freed.
+    This function will panic if the handler was already unbound.
 */
 pub fn unbind_event_handler(handler: &EventHandler)
 {
+    if !handler.bound.replace(false) {
+        panic!("Event handler is already unbound");
+    }
+
     let id = handler.id;
     let subclass_id = handler.subclass_id;
-    let mut callback_ptr: *mut *const Callback = ptr::null_mut();
+    let callback_ptr = handler.callback_ptr;
 
-    for &handle in handler.handles.iter() {
+    let late_windows = FULL_HANDLERS.with(|handlers| handlers.borrow_mut().remove(&subclass_id)).unwrap_or_default();
+
+    for &handle in handler.handles.iter().chain(late_windows.iter()) {
         unsafe { 
             let mut callback_value: UINT_PTR = 0;
             let result = GetWindowSubclass(handle, id, subclass_id, &mut callback_value);
             if result == 0 {
-                panic!("Parent of hander was either freed or is already unbound");
+                continue;
             }
 
-            callback_ptr = callback_value as *mut *const Callback;
             let callback: Rc<Callback> = Rc::from_raw(*callback_ptr);
 
             // Remove the window subclass before dropping the callback to prevent the
             // subclass window procedure from being called during the drop.
             RemoveWindowSubclass(handle, id, subclass_id);
+            forget_handler_order(handle, Some(subclass_id));
 
             mem::drop(callback);
         };
@@ -229,6 +548,76 @@ pub fn unbind_event_handler(handler: &EventHandler)
     }
 }
 
+/**
+    Marks the event being dispatched as handled. The event handlers with a lower priority are not called,
+    and the event is not passed to the default procedure of the window (ex: a key is not typed in a text input).
+    See `bind_event_handler_with_priority`.
+
+    Some events are raised by a parent window on behalf of a control (ex: `OnButtonClick`). For those events,
+    the control already processed the message, and only the other handlers are skipped.
+
+    Does nothing if it is called outside of an event handler.
+*/
+pub fn consume_event() {
+    EVENT_CONSUMED.with(|consumed| consumed.set(true));
+}
+
+/**
+    Subclasses `hwnd` with the event dispatcher, below the handlers that have a higher priority. The subclasses of the handlers
+    with a higher priority are removed and set again on top of the new one, because the last subclass of a window is called first.
+*/
+unsafe fn subclass_events(hwnd: HWND, priority: i32, subclass_id: UINT_PTR, callback_ptr: UINT_PTR) {
+    let callback_fn: SUBCLASSPROC = Some(process_events);
+
+    let above: Vec<(i32, UINT_PTR, UINT_PTR)> = HANDLER_ORDER.with(|order| {
+        let mut order = order.borrow_mut();
+        let handlers = order.entry(hwnd as usize).or_insert_with(Vec::new);
+        let index = handlers.iter().position(|&(p, _, _)| p > priority).unwrap_or(handlers.len());
+        let above = handlers.split_off(index);
+        handlers.push((priority, subclass_id, callback_ptr));
+        handlers.extend(above.iter().cloned());
+        above
+    });
+
+    for &(_, id, _) in above.iter() {
+        RemoveWindowSubclass(hwnd, callback_fn, id);
+    }
+
+    SetWindowSubclass(hwnd, callback_fn, subclass_id, callback_ptr);
+
+    for &(_, id, ptr) in above.iter() {
+        SetWindowSubclass(hwnd, callback_fn, id, ptr);
+    }
+}
+
+/// Removes a handler from the order of the handlers of a window, or all the handlers if `subclass_id` is `None`
+fn forget_handler_order(hwnd: HWND, subclass_id: Option<UINT_PTR>) {
+    HANDLER_ORDER.with(|order| {
+        let mut order = order.borrow_mut();
+        let empty = match (order.get_mut(&(hwnd as usize)), subclass_id) {
+            (Some(handlers), Some(id)) => {
+                handlers.retain(|&(_, i, _)| i != id);
+                handlers.is_empty()
+            },
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        if empty {
+            order.remove(&(hwnd as usize));
+        }
+    });
+}
+
+/// Restores the consumed state of the event that was dispatched when a message is sent from an event handler
+struct ConsumeScope(bool);
+
+impl Drop for ConsumeScope {
+    fn drop(&mut self) {
+        EVENT_CONSUMED.with(|consumed| consumed.set(self.0));
+    }
+}
+
 pub(crate) fn bind_raw_event_handler_inner<F>(handle: &ControlHandle, handler_id: UINT_PTR, f: F) -> Result<RawEventHandler, NwgError>
     where F: Fn(HWND, UINT, WPARAM, LPARAM) -> Option<LRESULT> + 'static
 {
@@ -237,6 +626,9 @@ pub(crate) fn bind_raw_event_handler_inner<F>(handle: &ControlHandle, handler_id
     
     let handle = match handle {
         &ControlHandle::Hwnd(h) => unsafe {
+            // A window can only be subclassed by the thread that owns it
+            super::ui_thread::check_thread(h)?;
+
             // Check if the handler is already bound to the control
             let mut tmp_value = 0;
             let result = GetWindowSubclass(h, subclass_proc, handler_id, &mut tmp_value);
@@ -258,7 +650,8 @@ pub(crate) fn bind_raw_event_handler_inner<F>(handle: &ControlHandle, handler_id
     Ok(RawEventHandler {
         handle,
         subclass_proc,
-        handler_id
+        handler_id,
+        bound: Cell::new(true),
     })
 }
 
@@ -273,6 +666,8 @@ or otherwise, the old callback will be replaced by the new one. See `Label::hook
 Error:
 - If the event handler with the same ID is already bound, this function will return an Error. The `has_raw_handler` method can be used to check this.
 
+The handler is not unbound when it is dropped. See `bind_raw_event_handler_guarded` for a handler that is.
+
 Panic:
 - If the `handle` parameter is not a window-like control
 - If the `handler_id` parameter is <= 0xFFFF
@@ -305,6 +700,16 @@ where F: Fn(HWND, UINT, WPARAM, LPARAM) -> Option<LRESULT> + 'static
     bind_raw_event_handler_inner(handle, handler_id, f)
 }
 
+/**
+Same as `bind_raw_event_handler`, but returns a `RawEventHandlerGuard` that removes the handler when it is dropped.
+Call `release` on the guard to keep the handler until the window is destroyed.
+*/
+pub fn bind_raw_event_handler_guarded<F>(handle: &ControlHandle, handler_id: UINT_PTR, f: F) -> Result<RawEventHandlerGuard, NwgError>
+where F: Fn(HWND, UINT, WPARAM, LPARAM) -> Option<LRESULT> + 'static
+{
+    bind_raw_event_handler(handle, handler_id, f).map(RawEventHandler::guard)
+}
+
 
 /** 
     Check if a raw handler with the specified handler_id is currently bound on the control.
@@ -346,6 +751,7 @@ pub fn unbind_raw_event_handler(handler: &RawEventHandler) -> Result<(), NwgErro
         // Remove the window subclass before dropping the callback to prevent the
         // subclass window procedure from being called during the drop.
         RemoveWindowSubclass(handle, subclass_proc, handler_id);
+        handler.bound.set(false);
 
         mem::drop(callback);
 
@@ -372,6 +778,10 @@ pub(crate) unsafe fn build_hwnd_control<'a>(
     use winapi::shared::windef::RECT;
     use winapi::um::libloaderapi::GetModuleHandleW;
 
+    if let Some(parent) = parent {
+        super::ui_thread::check_thread(parent)?;
+    }
+
     let hmod = GetModuleHandleW(ptr::null_mut());
     if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
 
@@ -412,6 +822,7 @@ pub(crate) unsafe fn build_hwnd_control<'a>(
     if handle.is_null() {
         Err(NwgError::initialization("Window creation failed"))
     } else {
+        super::resource_tracker::track(super::resource_tracker::ResourceKind::Window, handle);
         Ok(ControlHandle::Hwnd(handle))
     }
 }
@@ -457,31 +868,215 @@ pub(crate) unsafe fn build_sysclass<'a>(
     }
 }
 
-/// Create the window class for the base nwg window
-pub(crate) fn init_window_class() -> Result<(), NwgError> {
+/// Create the window class for the base nwg window
+pub(crate) fn init_window_class() -> Result<(), NwgError> {
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    
+    unsafe {
+        let hmod = GetModuleHandleW(ptr::null_mut());
+        if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
+
+        build_sysclass(hmod, "NativeWindowsGuiWindow", Some(blank_window_proc), None, None)?;
+    }
+    
+    Ok(())
+}
+
+
+#[cfg(feature = "frame")]
+/// Create the window class for the frame control
+pub(crate) fn create_frame_classes() -> Result<(), NwgError> {
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    
+    unsafe {
+        let hmod = GetModuleHandleW(ptr::null_mut());
+        if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
+
+        build_sysclass(hmod, "NWG_FRAME", Some(blank_window_proc), None, None)?;
+    }
+    
+    Ok(())
+}
+
+#[cfg(feature = "splitter")]
+/// Create the window class for the splitter control
+pub(crate) fn create_splitter_classes() -> Result<(), NwgError> {
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    use winapi::um::winuser::COLOR_BTNFACE;
+    
+    unsafe {
+        let hmod = GetModuleHandleW(ptr::null_mut());
+        if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
+
+        let background = (COLOR_BTNFACE + 1) as usize as HBRUSH;
+        build_sysclass(hmod, "NWG_SPLITTER", Some(blank_window_proc), Some(background), None)?;
+    }
+    
+    Ok(())
+}
+
+#[cfg(feature = "scroll-panel")]
+/// Create the window class for the scroll panel control
+pub(crate) fn create_scroll_panel_classes() -> Result<(), NwgError> {
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    
+    unsafe {
+        let hmod = GetModuleHandleW(ptr::null_mut());
+        if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
+
+        build_sysclass(hmod, "NWG_SCROLL_PANEL", Some(blank_window_proc), None, None)?;
+    }
+    
+    Ok(())
+}
+
+#[cfg(feature = "image-viewer")]
+/// Create the window class for the image viewer control
+pub(crate) fn create_image_viewer_classes() -> Result<(), NwgError> {
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    
+    unsafe {
+        let hmod = GetModuleHandleW(ptr::null_mut());
+        if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
+
+        build_sysclass(hmod, "NWG_IMAGE_VIEWER", Some(blank_window_proc), None, None)?;
+    }
+    
+    Ok(())
+}
+
+#[cfg(feature = "chart")]
+/// Create the window class for the chart control
+pub(crate) fn create_chart_classes() -> Result<(), NwgError> {
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    
+    unsafe {
+        let hmod = GetModuleHandleW(ptr::null_mut());
+        if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
+
+        build_sysclass(hmod, "NWG_CHART", Some(blank_window_proc), None, None)?;
+    }
+    
+    Ok(())
+}
+
+#[cfg(feature = "document-tabs")]
+/// Create the window class for the document tabs control
+pub(crate) fn create_document_tabs_classes() -> Result<(), NwgError> {
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    
+    unsafe {
+        let hmod = GetModuleHandleW(ptr::null_mut());
+        if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
+
+        build_sysclass(hmod, "NWG_DOCUMENT_TABS", Some(blank_window_proc), None, None)?;
+    }
+    
+    Ok(())
+}
+
+#[cfg(feature = "hex-view")]
+/// Create the window class for the hex view control
+pub(crate) fn create_hex_view_classes() -> Result<(), NwgError> {
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    
+    unsafe {
+        let hmod = GetModuleHandleW(ptr::null_mut());
+        if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
+
+        build_sysclass(hmod, "NWG_HEX_VIEW", Some(blank_window_proc), None, None)?;
+    }
+    
+    Ok(())
+}
+
+#[cfg(feature = "terminal-view")]
+/// Create the window class for the terminal view control
+pub(crate) fn create_terminal_view_classes() -> Result<(), NwgError> {
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    
+    unsafe {
+        let hmod = GetModuleHandleW(ptr::null_mut());
+        if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
+
+        build_sysclass(hmod, "NWG_TERMINAL_VIEW", Some(blank_window_proc), None, None)?;
+    }
+    
+    Ok(())
+}
+
+#[cfg(feature = "diff-view")]
+/// Create the window class for the diff view control
+pub(crate) fn create_diff_view_classes() -> Result<(), NwgError> {
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    
+    unsafe {
+        let hmod = GetModuleHandleW(ptr::null_mut());
+        if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
+
+        build_sysclass(hmod, "NWG_DIFF_VIEW", Some(blank_window_proc), None, None)?;
+    }
+    
+    Ok(())
+}
+
+#[cfg(feature = "markdown-view")]
+/// Create the window class for the markdown view control
+pub(crate) fn create_markdown_view_classes() -> Result<(), NwgError> {
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    
+    unsafe {
+        let hmod = GetModuleHandleW(ptr::null_mut());
+        if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
+
+        build_sysclass(hmod, "NWG_MARKDOWN_VIEW", Some(blank_window_proc), None, None)?;
+    }
+    
+    Ok(())
+}
+
+#[cfg(feature = "number-slider")]
+/// Create the window class for the number slider control
+pub(crate) fn create_number_slider_classes() -> Result<(), NwgError> {
     use winapi::um::libloaderapi::GetModuleHandleW;
+    use winapi::um::winuser::COLOR_BTNFACE;
     
     unsafe {
         let hmod = GetModuleHandleW(ptr::null_mut());
         if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
 
-        build_sysclass(hmod, "NativeWindowsGuiWindow", Some(blank_window_proc), None, None)?;
+        let background = (COLOR_BTNFACE + 1) as usize as HBRUSH;
+        build_sysclass(hmod, "NWG_NUMBER_SLIDER", Some(blank_window_proc), Some(background), None)?;
     }
     
     Ok(())
 }
 
+#[cfg(feature = "panel-stack")]
+/// Create the window class for the panel stack control
+pub(crate) fn create_panel_stack_classes() -> Result<(), NwgError> {
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    
+    unsafe {
+        let hmod = GetModuleHandleW(ptr::null_mut());
+        if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
 
-#[cfg(feature = "frame")]
-/// Create the window class for the frame control
-pub(crate) fn create_frame_classes() -> Result<(), NwgError> {
+        build_sysclass(hmod, "NWG_PANEL_STACK", Some(blank_window_proc), None, None)?;
+    }
+    
+    Ok(())
+}
+
+#[cfg(feature = "animated-image")]
+/// Create the window class for the animated image control
+pub(crate) fn create_animated_image_classes() -> Result<(), NwgError> {
     use winapi::um::libloaderapi::GetModuleHandleW;
     
     unsafe {
         let hmod = GetModuleHandleW(ptr::null_mut());
         if hmod.is_null() { return Err(NwgError::initialization("GetModuleHandleW failed")); }
 
-        build_sysclass(hmod, "NWG_FRAME", Some(blank_window_proc), None, None)?;
+        build_sysclass(hmod, "NWG_ANIMATED_IMAGE", Some(blank_window_proc), None, None)?;
     }
     
     Ok(())
@@ -489,14 +1084,14 @@ pub(crate) fn create_frame_classes() -> Result<(), NwgError> {
 
 #[cfg(feature = "message-window")]
 /// Create a message only window. Used with the `MessageWindow` control
-pub(crate) fn create_message_window() -> Result<ControlHandle, NwgError> {
+pub(crate) fn create_message_window(title: &str) -> Result<ControlHandle, NwgError> {
     use winapi::um::winuser::HWND_MESSAGE;
     use winapi::um::winuser::CreateWindowExW;
     use winapi::um::libloaderapi::GetModuleHandleW;
 
 
     let class_name = to_utf16("NativeWindowsGuiWindow");
-    let window_title = vec![0];
+    let window_title = to_utf16(title);
 
     unsafe {
         let hmod = GetModuleHandleW(ptr::null_mut());
@@ -518,11 +1113,34 @@ pub(crate) fn create_message_window() -> Result<ControlHandle, NwgError> {
         if handle.is_null() {
             Err(NwgError::initialization("Message only window creation failed"))
         } else {
+            super::resource_tracker::track(super::resource_tracker::ResourceKind::Window, handle);
             Ok(ControlHandle::Hwnd(handle))
         }
     }
 }
 
+#[cfg(feature = "message-window")]
+/// Register a window to receive the arrival and the removal of all the device interfaces.
+/// Message only windows do not receive the `WM_DEVICECHANGE` broadcasts otherwise.
+pub(crate) fn register_device_notifications(handle: HWND) -> Result<winapi::um::winuser::HDEVNOTIFY, NwgError> {
+    use winapi::um::winuser::{RegisterDeviceNotificationW, DEVICE_NOTIFY_WINDOW_HANDLE, DEVICE_NOTIFY_ALL_INTERFACE_CLASSES};
+    use winapi::um::dbt::{DEV_BROADCAST_DEVICEINTERFACE_W, DBT_DEVTYP_DEVICEINTERFACE};
+
+    unsafe {
+        let mut filter: DEV_BROADCAST_DEVICEINTERFACE_W = mem::zeroed();
+        filter.dbcc_size = mem::size_of::<DEV_BROADCAST_DEVICEINTERFACE_W>() as u32;
+        filter.dbcc_devicetype = DBT_DEVTYP_DEVICEINTERFACE;
+
+        let flags = DEVICE_NOTIFY_WINDOW_HANDLE | DEVICE_NOTIFY_ALL_INTERFACE_CLASSES;
+        let notify = RegisterDeviceNotificationW(handle as _, &mut filter as *mut DEV_BROADCAST_DEVICEINTERFACE_W as _, flags);
+
+        match notify.is_null() {
+            true => Err(NwgError::control_create("Failed to register the device notifications")),
+            false => Ok(notify)
+        }
+    }
+}
+
 
 /**
     A blank system procedure used when creating new window class. Actual system event handling is done in the subclass procedure `process_events`.
@@ -559,13 +1177,33 @@ unsafe extern "system" fn process_events(hwnd: HWND, msg: UINT, w: WPARAM, l: LP
     use crate::events::*;
 
     use winapi::um::commctrl::{DefSubclassProc, TTN_GETDISPINFOW};
-    use winapi::um::winuser::{GetClassNameW, GetMenuItemID, GetSubMenu};
+    use winapi::um::winuser::{GetMenuItemID, GetSubMenu};
     use winapi::um::winuser::{WM_CLOSE, WM_COMMAND, WM_MENUCOMMAND, WM_TIMER, WM_NOTIFY, WM_HSCROLL, WM_VSCROLL, WM_LBUTTONDOWN, WM_LBUTTONUP,
       WM_RBUTTONDOWN, WM_RBUTTONUP, WM_SIZE, WM_MOVE, WM_PAINT, WM_MOUSEMOVE, WM_CONTEXTMENU, WM_INITMENUPOPUP, WM_MENUSELECT, WM_EXITSIZEMOVE,
       WM_ENTERSIZEMOVE, SIZE_MAXIMIZED, SIZE_MINIMIZED, WM_KEYDOWN, WM_KEYUP, WM_CHAR, WM_MOUSEWHEEL, WM_DROPFILES, GET_WHEEL_DELTA_WPARAM,
-      WM_GETMINMAXINFO, WM_ENTERMENULOOP, WM_EXITMENULOOP, WM_SYSKEYDOWN, WM_SYSKEYUP};
-    use winapi::um::shellapi::{NIN_BALLOONSHOW, NIN_BALLOONHIDE, NIN_BALLOONTIMEOUT, NIN_BALLOONUSERCLICK};
-    use winapi::um::winnt::WCHAR;
+      WM_GETMINMAXINFO, WM_ENTERMENULOOP, WM_EXITMENULOOP, WM_SYSKEYDOWN, WM_SYSKEYUP, WM_MDIACTIVATE, WM_DEVICECHANGE, WM_SETTINGCHANGE,
+      WM_UNICHAR, UNICODE_NOCHAR, WM_DEADCHAR, WM_INPUTLANGCHANGE, GWL_STYLE, WS_CHILD, WM_MOUSEHWHEEL, SB_HORZ, SB_VERT, SB_CTL, WM_NCDESTROY};
+    #[cfg(feature = "menu")]
+    use winapi::um::winuser::{WM_MEASUREITEM, WM_DRAWITEM, WM_MENUCHAR};
+    #[cfg(feature = "raw-input")]
+    use winapi::um::winuser::WM_INPUT;
+    #[cfg(feature = "touch")]
+    use winapi::um::winuser::{WM_TOUCH, WM_GESTURE};
+    #[cfg(feature = "pointer")]
+    use winapi::um::winuser::{WM_POINTERDOWN, WM_POINTERUPDATE, WM_POINTERUP};
+    #[cfg(feature = "jump-list")]
+    use super::window_helper::NWG_JUMP_TASK;
+    #[cfg(feature = "drag-drop")]
+    use super::window_helper::NWG_DRAG_DROP;
+    #[cfg(feature = "shell")]
+    use super::window_helper::NWG_SHELL_CHANGE;
+    #[cfg(feature = "local-drag")]
+    use super::window_helper::NWG_LOCAL_DRAG;
+    #[cfg(feature = "async-decode")]
+    use super::window_helper::NWG_IMAGE_DECODED;
+    #[cfg(feature = "taskbar")]
+    use winapi::um::shobjidl_core::THBN_CLICKED;
+    use winapi::um::shellapi::{NIN_BALLOONSHOW, NIN_BALLOONHIDE, NIN_BALLOONTIMEOUT, NIN_BALLOONUSERCLICK, NIN_SELECT, NIN_KEYSELECT};
     use winapi::shared::minwindef::{HIWORD, LOWORD};
 
     let callback_ptr = data as *mut *const Callback;
@@ -575,6 +1213,9 @@ unsafe extern "system" fn process_events(hwnd: HWND, msg: UINT, w: WPARAM, l: LP
 
     let base_handle = ControlHandle::Hwnd(hwnd);
 
+    // A handler can consume the event. The handlers of lower priority and the default window procedure are then skipped.
+    let _consume_scope = ConsumeScope(EVENT_CONSUMED.with(|consumed| consumed.replace(false)));
+
     match msg {
         WM_KEYDOWN | WM_KEYUP | WM_SYSKEYDOWN | WM_SYSKEYUP => {
             let evt = match msg {
@@ -610,7 +1251,18 @@ unsafe extern "system" fn process_events(hwnd: HWND, msg: UINT, w: WPARAM, l: LP
             let parent_handle: HMENU = mem::transmute(l);
             let item_id = GetMenuItemID(parent_handle, w as i32);
             let handle = ControlHandle::MenuItem(parent_handle, item_id);
+
+            #[cfg(feature = "menu")]
+            let radio_change = super::menu::select_radio_item(parent_handle, item_id);
+
             callback(Event::OnMenuItemSelected, NO_DATA, handle);
+
+            // The handle of a radio group is the handle of its first item
+            #[cfg(feature = "menu")]
+            if let Some((first_id, index)) = radio_change {
+                let group_handle = ControlHandle::MenuItem(parent_handle, first_id);
+                callback(Event::OnMenuRadioSelectionChanged, EventData::OnMenuRadioSelection(index as usize), group_handle);
+            }
         },
         WM_INITMENUPOPUP => {
             callback(Event::OnMenuOpen, NO_DATA, ControlHandle::Menu(ptr::null_mut(), w as HMENU));
@@ -622,7 +1274,13 @@ unsafe extern "system" fn process_events(hwnd: HWND, msg: UINT, w: WPARAM, l: LP
             callback(Event::OnMenuExit, NO_DATA, ControlHandle::Menu(ptr::null_mut(), w as HMENU));
         },
         WM_MOUSEWHEEL => {
-            callback(Event::OnMouseWheel, EventData::OnMouseWheel(GET_WHEEL_DELTA_WPARAM(w) as i32), base_handle);
+            let delta = GET_WHEEL_DELTA_WPARAM(w);
+            callback(Event::OnMouseWheel, EventData::OnMouseWheel(delta as i32), base_handle);
+            callback(Event::OnScroll, EventData::OnScroll(wheel_scroll(delta, false)), base_handle);
+        },
+        WM_MOUSEHWHEEL => {
+            let delta = GET_WHEEL_DELTA_WPARAM(w);
+            callback(Event::OnScroll, EventData::OnScroll(wheel_scroll(delta, true)), base_handle);
         },
         WM_MENUSELECT => {
             let index = LOWORD(w as u32) as u32;
@@ -639,45 +1297,76 @@ unsafe extern "system" fn process_events(hwnd: HWND, msg: UINT, w: WPARAM, l: LP
             let child_handle: HWND = l as HWND;
             let message = HIWORD(w as u32) as u16;
             let handle = ControlHandle::Hwnd(child_handle);
-            
-            // Converting the class name into rust string might not be the most efficient way to do this
-            // It might be a good idea to just compare the class_name_raw
-            let mut class_name_raw: [WCHAR; 100] = [0; 100];
-            let count = GetClassNameW(child_handle, class_name_raw.as_mut_ptr(), 100) as usize;
-            let class_name = OsString::from_wide(&class_name_raw[..count]).into_string().unwrap_or("".to_string());
-
-            match &class_name as &str {
-                "Button" => callback(button_commands(message), NO_DATA, handle),
-                "Edit" => callback(edit_commands(message), NO_DATA, handle),
-                "ComboBox" => callback(combo_commands(message), NO_DATA, handle),
-                "Static" => callback(static_commands(child_handle, message), NO_DATA, handle),
-                "ListBox" => callback(listbox_commands(message), NO_DATA, handle),
+
+            match control_class(child_handle) {
+                ControlClass::Button => callback(button_commands(message), NO_DATA, handle),
+                ControlClass::Edit => callback(edit_commands(message), NO_DATA, handle),
+                ControlClass::ComboBox => callback(combo_commands(message), NO_DATA, handle),
+                ControlClass::Static => callback(static_commands(child_handle, message), NO_DATA, handle),
+                ControlClass::ListBox => callback(listbox_commands(message), NO_DATA, handle),
+                ControlClass::HotKey => callback(hotkey_commands(message), NO_DATA, handle),
+                ControlClass::Toolbar => callback(Event::OnToolbarClick, toolbar_data(LOWORD(w as u32) as u32), handle),
+                ControlClass::Splitter => callback(Event::OnSplitterMoved, NO_DATA, handle),
+                #[cfg(feature = "taskbar")]
+                _ if child_handle.is_null() && message == THBN_CLICKED => {
+                    callback(Event::OnThumbnailButtonClick, EventData::OnThumbnailButtonClick(LOWORD(w as u32) as u32), base_handle)
+                },
+                #[cfg(feature = "accelerator-table")]
+                _ if child_handle.is_null() && message == 1 => {
+                    // Command sent by an accelerator table
+                    let id = LOWORD(w as u32);
+                    match super::accelerator::accelerator_menu_item(hwnd, id) {
+                        Some((menu, true)) => callback(Event::OnMenuItemSelected, NO_DATA, ControlHandle::MenuItem(menu, id as u32)),
+                        Some((_, false)) => {},
+                        None => callback(Event::OnAccelerator, EventData::OnAccelerator(id), base_handle),
+                    }
+                },
                 _ => match w as i32 {
                     IDOK | IDCANCEL => callback(no_class_name_commands(w), NO_DATA, base_handle),
                     _ => {}
                 },
             }
         },
+        #[cfg(feature = "menu")]
+        WM_MEASUREITEM => {
+            if super::menu::measure_menu_item(hwnd, l as _) {
+                return 1;
+            }
+        },
+        #[cfg(feature = "menu")]
+        WM_DRAWITEM => {
+            if super::menu::draw_menu_item(l as _) {
+                return 1;
+            }
+        },
+        #[cfg(feature = "menu")]
+        WM_MENUCHAR => {
+            if let Some(result) = super::menu::menu_char(l as HMENU, LOWORD(w as u32)) {
+                return result;
+            }
+        },
         WM_CONTEXTMENU => {
             let target_handle = w as HWND;
             let handle = ControlHandle::Hwnd(target_handle);
             callback(Event::OnContextMenu, NO_DATA, handle);
         },
         NWG_TRAY => {
+            // With NOTIFYICON_VERSION_4, the low word of `l` is the notification and `w` holds the anchor point of the event in screen coordinates
             let msg = LOWORD(l as u32) as u32;
             let handle = ControlHandle::SystemTray(hwnd);
+            let point = EventData::OnTrayNotification { x: LOWORD(w as u32) as i16 as i32, y: HIWORD(w as u32) as i16 as i32 };
 
             match msg {
+                NIN_SELECT => callback(Event::OnTrayNotificationSelect, point, handle),
+                NIN_KEYSELECT => callback(Event::OnTrayNotificationKeySelect, point, handle),
+                WM_CONTEXTMENU => callback(Event::OnContextMenu, point, handle),
                 NIN_BALLOONSHOW => callback(Event::OnTrayNotificationShow, NO_DATA, handle),
                 NIN_BALLOONHIDE => callback(Event::OnTrayNotificationHide, NO_DATA, handle),
                 NIN_BALLOONTIMEOUT => callback(Event::OnTrayNotificationTimeout, NO_DATA, handle),
                 NIN_BALLOONUSERCLICK => callback(Event::OnTrayNotificationUserClose, NO_DATA, handle),
                 WM_LBUTTONUP => callback(Event::OnMousePress(MousePressEvent::MousePressLeftUp), NO_DATA,  handle), 
                 WM_LBUTTONDOWN => callback(Event::OnMousePress(MousePressEvent::MousePressLeftDown), NO_DATA, handle), 
-                WM_RBUTTONUP => {
-                    callback(Event::OnMousePress(MousePressEvent::MousePressRightUp), NO_DATA, handle);
-                    callback(Event::OnContextMenu, NO_DATA, handle);
-                }, 
+                WM_RBUTTONUP => callback(Event::OnMousePress(MousePressEvent::MousePressRightUp), NO_DATA, handle),
                 WM_RBUTTONDOWN => callback(Event::OnMousePress(MousePressEvent::MousePressRightDown), NO_DATA, handle),
                 WM_MOUSEMOVE => callback(Event::OnMouseMove, NO_DATA, handle),
                 _ => {}
@@ -702,13 +1391,47 @@ unsafe extern "system" fn process_events(hwnd: HWND, msg: UINT, w: WPARAM, l: LP
             let data = EventData::OnMinMaxInfo(MinMaxInfo { inner: l as _ });
             callback(Event::OnMinMaxInfo, data, base_handle)
         },
-        WM_CHAR => callback(Event::OnChar, EventData::OnChar(char::from_u32(w as u32).unwrap_or('?')), base_handle),
+        WM_CHAR => {
+            // The characters outside of the BMP are sent as two UTF-16 surrogates
+            if let Some(c) = super::keyboard::compose_char(hwnd, w as u32) {
+                callback(Event::OnChar, EventData::OnChar(c), base_handle);
+            }
+        },
+        WM_UNICHAR if w != UNICODE_NOCHAR => {
+            if let Some(c) = char::from_u32(w as u32) {
+                callback(Event::OnChar, EventData::OnChar(c), base_handle);
+            }
+        },
+        WM_DEADCHAR => callback(Event::OnDeadChar, EventData::OnChar(char::from_u32(w as u32).unwrap_or('?')), base_handle),
+        WM_INPUTLANGCHANGE => {
+            // The message is forwarded to the children by `DefWindowProc`
+            if super::window_helper::get_window_long(hwnd, GWL_STYLE) as u32 & WS_CHILD == 0 {
+                callback(Event::OnKeyboardLayoutChanged, NO_DATA, base_handle);
+            }
+        },
         WM_EXITSIZEMOVE => callback(Event::OnResizeEnd, NO_DATA, base_handle),
         WM_ENTERSIZEMOVE => callback(Event::OnResizeBegin, NO_DATA, base_handle),
         WM_TIMER => callback(Event::OnTimerTick, NO_DATA, ControlHandle::Timer(hwnd, w as u32)),
         WM_MOVE => callback(Event::OnMove, NO_DATA, base_handle),
-        WM_HSCROLL => callback(Event::OnHorizontalScroll, NO_DATA, ControlHandle::Hwnd(l as HWND)),
-        WM_VSCROLL => callback(Event::OnVerticalScroll, NO_DATA, ControlHandle::Hwnd(l as HWND)),
+        WM_HSCROLL | WM_VSCROLL => {
+            let horizontal = msg == WM_HSCROLL;
+            match horizontal {
+                true => callback(Event::OnHorizontalScroll, NO_DATA, ControlHandle::Hwnd(l as HWND)),
+                false => callback(Event::OnVerticalScroll, NO_DATA, ControlHandle::Hwnd(l as HWND)),
+            }
+
+            // `l` is the scroll bar control, or null for the scroll bars of the window
+            let (target, bar, handle) = match (l as HWND).is_null() {
+                true => (hwnd, if horizontal { SB_HORZ } else { SB_VERT }, base_handle),
+                false => (l as HWND, SB_CTL, ControlHandle::Hwnd(l as HWND)),
+            };
+
+            if let Some(delta) = scroll_bar_delta(target, bar as i32, LOWORD(w as u32) as isize) {
+                let (dx, dy) = if horizontal { (delta, 0.0) } else { (0.0, delta) };
+                let data = ScrollData { dx, dy, source: ScrollSource::ScrollBar };
+                callback(Event::OnScroll, EventData::OnScroll(data), handle);
+            }
+        },
         WM_MOUSEMOVE => callback(Event::OnMouseMove, NO_DATA, base_handle), 
         WM_LBUTTONUP => callback(Event::OnMousePress(MousePressEvent::MousePressLeftUp), NO_DATA,  base_handle), 
         WM_LBUTTONDOWN => callback(Event::OnMousePress(MousePressEvent::MousePressLeftDown), NO_DATA, base_handle), 
@@ -716,8 +1439,99 @@ unsafe extern "system" fn process_events(hwnd: HWND, msg: UINT, w: WPARAM, l: LP
         WM_RBUTTONDOWN => callback(Event::OnMousePress(MousePressEvent::MousePressRightDown), NO_DATA, base_handle),
         NOTICE_MESSAGE => callback(Event::OnNotice, NO_DATA, ControlHandle::Notice(hwnd, w as u32)),
         NWG_TIMER_STOP => callback(Event::OnTimerStop, NO_DATA, ControlHandle::Timer(hwnd, w as u32)),
-        NWG_TIMER_TICK => callback(Event::OnTimerTick, NO_DATA, ControlHandle::Timer(hwnd, w as u32)),
+        NWG_TIMER_TICK => {
+            // The animation timers send the time elapsed since their previous tick, in microseconds
+            let delta = std::time::Duration::from_micros(l as u32 as u64);
+            callback(Event::OnTimerTick, EventData::OnTimerTick(delta), ControlHandle::Timer(hwnd, w as u32));
+        },
+        NWG_ANIMATION_END => callback(Event::OnAnimationEnd, EventData::OnAnimationEnd(l as u32), ControlHandle::Timer(hwnd, w as u32)),
         NWG_INIT => callback(Event::OnInit, NO_DATA, base_handle),
+        NWG_APP_EXIT => callback(Event::OnAppExit, EventData::OnAppExit(w as i32), base_handle),
+        #[cfg(feature = "jump-list")]
+        NWG_JUMP_TASK => {
+            // Sent by `JumpList::dispatch` with a pointer to the task id
+            let id = &*(w as *const String);
+            callback(Event::OnJumpListTask, EventData::OnJumpListTask(id.clone()), base_handle);
+        },
+        WM_SETTINGCHANGE => {
+            if is_color_set_change(l) {
+                callback(Event::OnSystemThemeChanged, NO_DATA, base_handle);
+            }
+        },
+        #[cfg(feature = "raw-input")]
+        WM_INPUT => {
+            if let Some(input) = super::raw_input::read_input(l) {
+                callback(Event::OnRawInput, EventData::OnRawInput(input), base_handle);
+            }
+        },
+        #[cfg(feature = "touch")]
+        WM_TOUCH => {
+            let points = super::touch::read_touch(hwnd, w, l);
+            if !points.is_empty() {
+                callback(Event::OnTouch, EventData::OnTouch(points), base_handle);
+            }
+        },
+        #[cfg(feature = "touch")]
+        WM_GESTURE => {
+            if let Some(gesture) = super::touch::read_gesture(hwnd, l) {
+                callback(Event::OnGesture, EventData::OnGesture(gesture), base_handle);
+            }
+        },
+        #[cfg(feature = "pointer")]
+        WM_POINTERDOWN | WM_POINTERUPDATE | WM_POINTERUP => {
+            // The message is passed to `DefWindowProc`, which translates it into the mouse messages
+            if let Some(pointer) = super::pointer::read_pointer(hwnd, w) {
+                let event = match msg {
+                    WM_POINTERDOWN => Event::OnPointerDown,
+                    WM_POINTERUP => Event::OnPointerUp,
+                    _ => Event::OnPointerUpdate,
+                };
+
+                callback(event, EventData::OnPointer(pointer), base_handle);
+            }
+        },
+        #[cfg(feature = "drag-drop")]
+        NWG_DRAG_DROP => {
+            // Sent by the drop target of the window with a pointer to the state of the operation
+            let event = super::drag_drop::drag_event(w);
+            let data = match event {
+                Event::OnDragLeave => NO_DATA,
+                _ => EventData::OnDragDrop(super::drag_drop::drag_data(l))
+            };
+
+            callback(event, data, base_handle);
+        },
+        #[cfg(feature = "local-drag")]
+        NWG_LOCAL_DRAG => {
+            // Sent by `start_local_drag` to the drop targets with a pointer to the dragged payload
+            let data = EventData::OnLocalDrag(super::local_drag::drag_data(l));
+            callback(super::local_drag::drag_event(w), data, base_handle);
+        },
+        #[cfg(feature = "shell")]
+        NWG_SHELL_CHANGE => {
+            // Sent by the shell to the windows of a `ShellWatcher`
+            if let Some(change) = super::shell::read_change(w, l) {
+                callback(Event::OnShellChange, EventData::OnShellChange(change), base_handle);
+            }
+        },
+        #[cfg(feature = "async-decode")]
+        NWG_IMAGE_DECODED => {
+            // Posted by the worker thread of `Bitmap::decode_async`. The bitmap is freed after the event if the handlers did not take it.
+            let image = Box::from_raw(l as *mut crate::DecodedImage);
+            callback(Event::OnImageDecoded, EventData::OnImageDecoded(image), base_handle);
+        },
+        WM_DEVICECHANGE => {
+            let data = EventData::OnDeviceChange(DeviceChangeData { event: w, header: l as _ });
+            callback(Event::OnDeviceChange, data, base_handle);
+        },
+        WM_MDIACTIVATE => {
+            // Sent to the child being deactivated (in `w`) and to the child being activated (in `l`)
+            if l as HWND == hwnd {
+                callback(Event::OnMdiChildActivate, NO_DATA, base_handle);
+            } else if w as HWND == hwnd {
+                callback(Event::OnMdiChildDeactivate, NO_DATA, base_handle);
+            }
+        },
         WM_CLOSE => {
             let mut should_exit = true;
             let data = EventData::OnWindowClose(WindowCloseData { data: &mut should_exit as *mut bool });
@@ -727,9 +1541,20 @@ unsafe extern "system" fn process_events(hwnd: HWND, msg: UINT, w: WPARAM, l: LP
                 return 0;
             }
         },
+        WM_NCDESTROY => {
+            // The subclasses are removed with the window. Release the reference of the window to the callback,
+            // so that the callback is freed even if the handler is never unbound. See `unbind_event_handler`.
+            forget_handler_order(hwnd, None);
+            RemoveWindowSubclass(hwnd, Some(process_events), id);
+            mem::drop(Rc::from_raw(*callback_ptr));
+        },
         _ => {}
     }
 
+    if EVENT_CONSUMED.with(|consumed| consumed.get()) {
+        return 0;
+    }
+
     DefSubclassProc(hwnd, msg, w, l)
 }
 
@@ -750,6 +1575,67 @@ unsafe extern "system" fn process_raw_events(hwnd: HWND, msg: UINT, w: WPARAM, l
     }
 }
 
+/// Converts a wheel delta into lines (or characters for the horizontal wheel), using the mouse settings of the user
+fn wheel_scroll(delta: i16, horizontal: bool) -> ScrollData {
+    use winapi::um::winuser::{SystemParametersInfoW, SPI_GETWHEELSCROLLLINES, SPI_GETWHEELSCROLLCHARS, WHEEL_DELTA, WHEEL_PAGESCROLL};
+
+    let setting = match horizontal {
+        true => SPI_GETWHEELSCROLLCHARS,
+        false => SPI_GETWHEELSCROLLLINES,
+    };
+
+    let mut lines: u32 = 3;
+    unsafe { SystemParametersInfoW(setting, 0, &mut lines as *mut u32 as _, 0); }
+
+    // There is no page size for the generic event. Scroll by the default amount instead.
+    if lines == WHEEL_PAGESCROLL {
+        lines = 3;
+    }
+
+    let notches = delta as f64 / WHEEL_DELTA as f64;
+    let source = match delta % WHEEL_DELTA == 0 {
+        true => ScrollSource::Wheel,
+        false => ScrollSource::Precise,
+    };
+
+    // A positive vertical delta is the wheel rotating away from the user, which scrolls up
+    match horizontal {
+        true => ScrollData { dx: notches * lines as f64, dy: 0.0, source },
+        false => ScrollData { dx: 0.0, dy: -notches * lines as f64, source },
+    }
+}
+
+/// Computes the delta of a scroll bar request, in the units of the scroll bar.
+/// Returns `None` if the request does not move the scroll bar or if `bar` is not a scroll bar (ex: a track bar).
+unsafe fn scroll_bar_delta(hwnd: HWND, bar: i32, request: isize) -> Option<f64> {
+    use winapi::um::winuser::{GetScrollInfo, SCROLLINFO, SIF_ALL, SB_LINEUP, SB_LINEDOWN, SB_PAGEUP, SB_PAGEDOWN,
+        SB_THUMBTRACK, SB_TOP, SB_BOTTOM};
+
+    let mut info: SCROLLINFO = mem::zeroed();
+    info.cbSize = mem::size_of::<SCROLLINFO>() as u32;
+    info.fMask = SIF_ALL;
+    if GetScrollInfo(hwnd, bar, &mut info) == 0 {
+        return None;
+    }
+
+    let page = info.nPage.max(1) as i32;
+    let delta = match request {
+        SB_LINEUP => -1,
+        SB_LINEDOWN => 1,
+        SB_PAGEUP => -page,
+        SB_PAGEDOWN => page,
+        SB_THUMBTRACK => info.nTrackPos - info.nPos,
+        SB_TOP => info.nMin - info.nPos,
+        SB_BOTTOM => (info.nMax - page + 1).max(info.nMin) - info.nPos,
+        _ => 0
+    };
+
+    match delta {
+        0 => None,
+        d => Some(d as f64)
+    }
+}
+
 fn button_commands(m: u16) -> Event {
     use winapi::um::winuser::{BN_CLICKED, BN_DBLCLK};
     match m {
@@ -768,6 +1654,15 @@ fn edit_commands(m: u16) -> Event {
     }
 }
 
+fn hotkey_commands(m: u16) -> Event {
+    use winapi::um::winuser::{EN_CHANGE};
+
+    match m {
+        EN_CHANGE => Event::OnHotKeyChanged,
+        _ => Event::Unknown
+    }
+}
+
 fn combo_commands(m: u16) -> Event {
     use winapi::um::winuser::{CBN_CLOSEUP, CBN_DROPDOWN, CBN_SELCHANGE};
     match m {
@@ -788,6 +1683,24 @@ fn datetimepick_commands(m: u32) -> Event {
     }
 }
 
+fn month_calendar_commands(m: u32) -> Event {
+    use winapi::um::commctrl::{MCN_SELCHANGE, MCN_SELECT};
+    match m {
+        MCN_SELCHANGE => Event::OnMonthCalendarSelectionChanged,
+        MCN_SELECT => Event::OnMonthCalendarSelect,
+        _ => Event::Unknown
+    }
+}
+
+fn toolbar_commands(m: u32) -> Event {
+    use winapi::um::commctrl::TBN_DROPDOWN;
+
+    match m {
+        TBN_DROPDOWN => Event::OnToolbarDropdown,
+        _ => Event::Unknown
+    }
+}
+
 fn tabs_commands(m: u32) -> Event {
     use winapi::um::commctrl::{TCN_SELCHANGE, TCN_SELCHANGING};
     match m {
@@ -831,7 +1744,7 @@ fn tree_commands(m: u32) -> Event {
 fn list_view_commands(m: u32) -> Event {
     use winapi::um::commctrl::{NM_KILLFOCUS, NM_SETFOCUS, LVN_DELETEALLITEMS,
         LVN_DELETEITEM, LVN_INSERTITEM, LVN_ITEMACTIVATE, LVN_ITEMCHANGED,
-        NM_CLICK, NM_DBLCLK, NM_RCLICK, LVN_COLUMNCLICK};
+        NM_CLICK, NM_DBLCLK, NM_RCLICK, LVN_COLUMNCLICK, LVN_BEGINDRAG};
 
     match m {
         NM_CLICK => Event::OnListViewClick,
@@ -845,6 +1758,7 @@ fn list_view_commands(m: u32) -> Event {
         LVN_ITEMCHANGED => Event::OnListViewItemChanged,
         NM_KILLFOCUS => Event::OnListViewFocusLost,
         NM_SETFOCUS => Event::OnListViewFocus,
+        LVN_BEGINDRAG => Event::OnListViewBeginDrag,
         _ => Event::Unknown
     }
 }
@@ -938,14 +1852,67 @@ fn tree_data(_m: u32, _notif_raw: *const NMHDR) -> EventData {
     NO_DATA
 }
 
+#[cfg(feature="month-calendar")]
+fn month_calendar_data(m: u32, notif_raw: *const NMHDR) -> EventData {
+    use crate::DatePickerValue;
+    use winapi::um::commctrl::{NMSELCHANGE, MCN_SELCHANGE, MCN_SELECT};
+
+    match m {
+        MCN_SELCHANGE | MCN_SELECT => {
+            let data = unsafe { &*(notif_raw as *const NMSELCHANGE) };
+            let (st, en) = (&data.stSelStart, &data.stSelEnd);
+            EventData::OnMonthCalendarSelection {
+                start: DatePickerValue { year: st.wYear, month: st.wMonth, day: st.wDay },
+                end: DatePickerValue { year: en.wYear, month: en.wMonth, day: en.wDay },
+            }
+        },
+        _ => NO_DATA
+    }
+}
+
+#[cfg(not(feature="month-calendar"))]
+fn month_calendar_data(_m: u32, _notif_raw: *const NMHDR) -> EventData {
+    // If month-calendar is not enabled, the data type won't be available so we return NO_DATA
+    NO_DATA
+}
+
+#[cfg(feature="toolbar")]
+fn toolbar_data(id: u32) -> EventData {
+    EventData::OnToolbarButton { id }
+}
+
+#[cfg(not(feature="toolbar"))]
+fn toolbar_data(_id: u32) -> EventData {
+    NO_DATA
+}
+
+#[cfg(feature="toolbar")]
+fn toolbar_notify_data(m: u32, notif_raw: *const NMHDR) -> EventData {
+    use winapi::um::commctrl::{NMTOOLBARW, TBN_DROPDOWN};
+
+    match m {
+        TBN_DROPDOWN => {
+            let data = unsafe { &*(notif_raw as *const NMTOOLBARW) };
+            EventData::OnToolbarButton { id: data.iItem as u32 }
+        },
+        _ => NO_DATA
+    }
+}
+
+#[cfg(not(feature="toolbar"))]
+fn toolbar_notify_data(_m: u32, _notif_raw: *const NMHDR) -> EventData {
+    // If toolbar is not enabled, the data type won't be available so we return NO_DATA
+    NO_DATA
+}
+
 #[cfg(feature="list-view")]
 fn list_view_data(m: u32, notif_raw: *const NMHDR) -> EventData {
     use winapi::um::commctrl::{NMLISTVIEW, NMITEMACTIVATE, LVN_DELETEITEM, LVN_ITEMACTIVATE,
-        LVN_INSERTITEM, LVN_ITEMCHANGED, LVIS_SELECTED, LVN_COLUMNCLICK,
+        LVN_INSERTITEM, LVN_ITEMCHANGED, LVIS_SELECTED, LVN_COLUMNCLICK, LVN_BEGINDRAG,
         NM_CLICK, NM_RCLICK, NM_DBLCLK};
 
     match m {
-        LVN_DELETEITEM | LVN_INSERTITEM | LVN_COLUMNCLICK => {
+        LVN_DELETEITEM | LVN_INSERTITEM | LVN_COLUMNCLICK | LVN_BEGINDRAG => {
             let data: &NMLISTVIEW = unsafe { &*(notif_raw as *const NMLISTVIEW) };
             EventData::OnListViewItemIndex { 
                 row_index: data.iItem as _,
@@ -977,6 +1944,196 @@ fn list_view_data(_m: u32, _notif_raw: *const NMHDR) -> EventData {
     NO_DATA
 }
 
+#[cfg(feature="chart")]
+fn chart_commands(m: u32) -> Event {
+    use super::chart_renderer::{CHART_POINT_HOVER, CHART_POINT_LEAVE};
+
+    match m {
+        CHART_POINT_HOVER => Event::OnChartPointHover,
+        CHART_POINT_LEAVE => Event::OnChartPointLeave,
+        _ => Event::Unknown
+    }
+}
+
+#[cfg(not(feature="chart"))]
+fn chart_commands(_m: u32) -> Event {
+    Event::Unknown
+}
+
+#[cfg(feature="chart")]
+fn chart_data(m: u32, notif_raw: *const NMHDR) -> EventData {
+    use super::chart_renderer::{NMCHART, CHART_POINT_HOVER};
+    use crate::ChartPoint;
+
+    match m {
+        CHART_POINT_HOVER => {
+            let data = unsafe { &*(notif_raw as *const NMCHART) };
+            EventData::OnChartPoint(ChartPoint { series: data.series, index: data.index, x: data.x, y: data.y })
+        },
+        _ => NO_DATA
+    }
+}
+
+#[cfg(not(feature="chart"))]
+fn chart_data(_m: u32, _notif_raw: *const NMHDR) -> EventData {
+    // If chart is not enabled, the data type won't be available so we return NO_DATA
+    NO_DATA
+}
+
+#[cfg(feature="document-tabs")]
+fn document_tabs_commands(m: u32) -> Event {
+    use super::document_tabs::{DOCUMENT_TAB_SELECT, DOCUMENT_TAB_CLOSE, DOCUMENT_TAB_REORDER, DOCUMENT_TAB_CONTEXT_MENU};
+
+    match m {
+        DOCUMENT_TAB_SELECT => Event::OnDocumentTabSelect,
+        DOCUMENT_TAB_CLOSE => Event::OnDocumentTabClose,
+        DOCUMENT_TAB_REORDER => Event::OnDocumentTabReorder,
+        DOCUMENT_TAB_CONTEXT_MENU => Event::OnDocumentTabContextMenu,
+        _ => Event::Unknown
+    }
+}
+
+#[cfg(not(feature="document-tabs"))]
+fn document_tabs_commands(_m: u32) -> Event {
+    Event::Unknown
+}
+
+#[cfg(feature="document-tabs")]
+fn document_tabs_data(_m: u32, notif_raw: *const NMHDR) -> EventData {
+    use super::document_tabs::{NMDOCUMENTTAB, NO_TAB};
+
+    let data = unsafe { &*(notif_raw as *const NMDOCUMENTTAB) };
+    let previous = match data.previous {
+        NO_TAB => None,
+        i => Some(i)
+    };
+
+    EventData::OnDocumentTab { index: data.index, previous }
+}
+
+#[cfg(not(feature="document-tabs"))]
+fn document_tabs_data(_m: u32, _notif_raw: *const NMHDR) -> EventData {
+    // If document-tabs is not enabled, the data type won't be available so we return NO_DATA
+    NO_DATA
+}
+
+#[cfg(feature="hex-view")]
+fn hex_view_commands(m: u32) -> Event {
+    use super::hex_view::{HEX_VIEW_SELECTION_CHANGED, HEX_VIEW_EDIT};
+
+    match m {
+        HEX_VIEW_SELECTION_CHANGED => Event::OnHexViewSelectionChanged,
+        HEX_VIEW_EDIT => Event::OnHexViewEdit,
+        _ => Event::Unknown
+    }
+}
+
+#[cfg(not(feature="hex-view"))]
+fn hex_view_commands(_m: u32) -> Event {
+    Event::Unknown
+}
+
+#[cfg(feature="hex-view")]
+fn hex_view_data(_m: u32, notif_raw: *const NMHDR) -> EventData {
+    use super::hex_view::NMHEXVIEW;
+
+    let data = unsafe { &*(notif_raw as *const NMHEXVIEW) };
+    EventData::OnHexView { offset: data.offset, length: data.length }
+}
+
+#[cfg(not(feature="hex-view"))]
+fn hex_view_data(_m: u32, _notif_raw: *const NMHDR) -> EventData {
+    // If hex-view is not enabled, the data type won't be available so we return NO_DATA
+    NO_DATA
+}
+
+#[cfg(feature="markdown-view")]
+fn markdown_view_commands(m: u32) -> Event {
+    use super::markdown::MARKDOWN_LINK_CLICK;
+
+    match m {
+        MARKDOWN_LINK_CLICK => Event::OnMarkdownLinkClick,
+        _ => Event::Unknown
+    }
+}
+
+#[cfg(not(feature="markdown-view"))]
+fn markdown_view_commands(_m: u32) -> Event {
+    Event::Unknown
+}
+
+#[cfg(feature="markdown-view")]
+fn markdown_view_data(_m: u32, notif_raw: *const NMHDR) -> EventData {
+    use super::markdown::NMMARKDOWNLINK;
+
+    let data = unsafe { &*(notif_raw as *const NMMARKDOWNLINK) };
+    let url = unsafe { std::slice::from_raw_parts(data.url, data.url_len) };
+    EventData::OnMarkdownLink(String::from_utf8_lossy(url).into_owned())
+}
+
+#[cfg(not(feature="markdown-view"))]
+fn markdown_view_data(_m: u32, _notif_raw: *const NMHDR) -> EventData {
+    // If markdown-view is not enabled, the data type won't be available so we return NO_DATA
+    NO_DATA
+}
+
+#[cfg(feature="number-slider")]
+fn number_slider_commands(m: u32) -> Event {
+    use super::number_slider::NUMBER_SLIDER_VALUE_CHANGED;
+
+    match m {
+        NUMBER_SLIDER_VALUE_CHANGED => Event::OnValueChanged,
+        _ => Event::Unknown
+    }
+}
+
+#[cfg(not(feature="number-slider"))]
+fn number_slider_commands(_m: u32) -> Event {
+    Event::Unknown
+}
+
+#[cfg(feature="number-slider")]
+fn number_slider_data(_m: u32, notif_raw: *const NMHDR) -> EventData {
+    use super::number_slider::NMNUMBERSLIDER;
+
+    let data = unsafe { &*(notif_raw as *const NMNUMBERSLIDER) };
+    EventData::OnValueChanged(data.value)
+}
+
+#[cfg(not(feature="number-slider"))]
+fn number_slider_data(_m: u32, _notif_raw: *const NMHDR) -> EventData {
+    // If number-slider is not enabled, the data type won't be available so we return NO_DATA
+    NO_DATA
+}
+
+#[cfg(feature="panel-stack")]
+fn panel_stack_commands(m: u32) -> Event {
+    use super::panel_stack::PANEL_STACK_REORDER;
+
+    match m {
+        PANEL_STACK_REORDER => Event::OnPanelReorder,
+        _ => Event::Unknown
+    }
+}
+
+#[cfg(not(feature="panel-stack"))]
+fn panel_stack_commands(_m: u32) -> Event {
+    Event::Unknown
+}
+
+#[cfg(feature="panel-stack")]
+fn panel_stack_data(_m: u32, notif_raw: *const NMHDR) -> EventData {
+    use super::panel_stack::NMPANELSTACK;
+
+    let data = unsafe { &*(notif_raw as *const NMPANELSTACK) };
+    EventData::OnPanelReorder { index: data.index, previous: data.previous }
+}
+
+#[cfg(not(feature="panel-stack"))]
+fn panel_stack_data(_m: u32, _notif_raw: *const NMHDR) -> EventData {
+    // If panel-stack is not enabled, the data type won't be available so we return NO_DATA
+    NO_DATA
+}
 
 unsafe fn static_commands(handle: HWND, m: u16) -> Event {
     use winapi::um::winuser::{STN_CLICKED, STN_DBLCLK, STM_GETIMAGE, IMAGE_BITMAP, IMAGE_ICON, IMAGE_CURSOR};
@@ -1021,44 +2178,125 @@ unsafe fn handle_tooltip_callback<'a>(notif: *mut NMTTDISPINFOW, callback: &Call
 }
 
 unsafe fn handle_default_notify_callback<'a>(notif_raw: *const NMHDR, callback: &Callback){
-    use winapi::um::winnt::WCHAR;
-    use winapi::um::winuser::GetClassNameW;
-
     let notif = &*notif_raw;
     let handle = ControlHandle::Hwnd(notif.hwndFrom);
-
-    let mut class_name_raw: [WCHAR; 100] = mem::zeroed();
-    let count = GetClassNameW(notif.hwndFrom, class_name_raw.as_mut_ptr(), 100) as usize;
-    let class_name = OsString::from_wide(&class_name_raw[..count]).into_string().unwrap_or("".to_string());
-
     let code = notif.code;
 
-    match &class_name as &str {
-        "SysDateTimePick32" => callback(datetimepick_commands(code), NO_DATA, handle),
-        "SysTabControl32" => callback(tabs_commands(code), NO_DATA, handle),
-        "msctls_trackbar32" => callback(track_commands(code), NO_DATA, handle),
-        winapi::um::commctrl::WC_TREEVIEW => callback(tree_commands(code), tree_data(code, notif_raw), handle),
-        winapi::um::commctrl::WC_LISTVIEW => callback(list_view_commands(code), list_view_data(code, notif_raw), handle),
+    match control_class(notif.hwndFrom) {
+        ControlClass::DateTimePicker => callback(datetimepick_commands(code), NO_DATA, handle),
+        ControlClass::MonthCalendar => callback(month_calendar_commands(code), month_calendar_data(code, notif_raw), handle),
+        ControlClass::Tabs => callback(tabs_commands(code), NO_DATA, handle),
+        ControlClass::TrackBar => callback(track_commands(code), NO_DATA, handle),
+        ControlClass::Toolbar => callback(toolbar_commands(code), toolbar_notify_data(code, notif_raw), handle),
+        ControlClass::TreeView => callback(tree_commands(code), tree_data(code, notif_raw), handle),
+        ControlClass::ListView => callback(list_view_commands(code), list_view_data(code, notif_raw), handle),
+        ControlClass::Chart => callback(chart_commands(code), chart_data(code, notif_raw), handle),
+        ControlClass::DocumentTabs => callback(document_tabs_commands(code), document_tabs_data(code, notif_raw), handle),
+        ControlClass::HexView => callback(hex_view_commands(code), hex_view_data(code, notif_raw), handle),
+        ControlClass::MarkdownView => callback(markdown_view_commands(code), markdown_view_data(code, notif_raw), handle),
+        ControlClass::NumberSlider => callback(number_slider_commands(code), number_slider_data(code, notif_raw), handle),
+        ControlClass::PanelStack => callback(panel_stack_commands(code), panel_stack_data(code, notif_raw), handle),
         _ => {}
     }
 }
 
+/// Returns `true` if the `WM_SETTINGCHANGE` parameter is "ImmersiveColorSet", the setting sent when the color theme of the system changes
+unsafe fn is_color_set_change(l: LPARAM) -> bool {
+    use winapi::um::winnt::WCHAR;
+
+    if l == 0 {
+        return false;
+    }
+
+    let expected = "ImmersiveColorSet";
+    let setting = l as *const WCHAR;
+    let len = (0..).take_while(|&i| *setting.add(i) != 0).take(expected.len() + 1).count();
+    let setting = std::slice::from_raw_parts(setting, len);
+
+    expected.encode_utf16().eq(setting.iter().copied())
+}
+
 unsafe fn is_textbox_control(hwnd: HWND) -> bool {
+    match control_class(hwnd) {
+        ControlClass::Edit | ControlClass::RichEdit => true,
+        _ => false
+    }
+}
+
+/// The window classes that the events dispatcher needs to identify
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ControlClass {
+    Button,
+    Edit,
+    RichEdit,
+    ComboBox,
+    Static,
+    ListBox,
+    HotKey,
+    Toolbar,
+    Splitter,
+    DateTimePicker,
+    MonthCalendar,
+    Tabs,
+    TrackBar,
+    TreeView,
+    ListView,
+    Chart,
+    DocumentTabs,
+    HexView,
+    MarkdownView,
+    NumberSlider,
+    PanelStack,
+    Other,
+}
+
+const CONTROL_CLASSES: &[(&str, ControlClass)] = &[
+    ("Button", ControlClass::Button),
+    ("Edit", ControlClass::Edit),
+    ("RICHEDIT50W", ControlClass::RichEdit),
+    ("ComboBox", ControlClass::ComboBox),
+    ("Static", ControlClass::Static),
+    ("ListBox", ControlClass::ListBox),
+    ("msctls_hotkey32", ControlClass::HotKey),
+    ("ToolbarWindow32", ControlClass::Toolbar),
+    ("NWG_SPLITTER", ControlClass::Splitter),
+    ("SysDateTimePick32", ControlClass::DateTimePicker),
+    ("SysMonthCal32", ControlClass::MonthCalendar),
+    ("SysTabControl32", ControlClass::Tabs),
+    ("msctls_trackbar32", ControlClass::TrackBar),
+    (winapi::um::commctrl::WC_TREEVIEW, ControlClass::TreeView),
+    (winapi::um::commctrl::WC_LISTVIEW, ControlClass::ListView),
+    ("NWG_CHART", ControlClass::Chart),
+    ("NWG_DOCUMENT_TABS", ControlClass::DocumentTabs),
+    ("NWG_HEX_VIEW", ControlClass::HexView),
+    ("NWG_MARKDOWN_VIEW", ControlClass::MarkdownView),
+    ("NWG_NUMBER_SLIDER", ControlClass::NumberSlider),
+    ("NWG_PANEL_STACK", ControlClass::PanelStack),
+];
+
+/**
+    Identify the class of a window. The class name is compared in its UTF-16 form, on the stack,
+    because this is called for every WM_COMMAND and WM_NOTIFY message.
+*/
+unsafe fn control_class(hwnd: HWND) -> ControlClass {
     use winapi::um::winnt::WCHAR;
     use winapi::um::winuser::GetClassNameW;
 
     let mut class_name_raw: [WCHAR; 100] = [0; 100];
     let count = GetClassNameW(hwnd, class_name_raw.as_mut_ptr(), 100) as usize;
-    let class_name = OsString::from_wide(&class_name_raw[..count]).into_string().unwrap_or("".to_string());
-    
-    class_name == "Edit" || class_name == "RICHEDIT50W"
+    let class_name = &class_name_raw[..count];
+
+    CONTROL_CLASSES.iter()
+        .find(|(name, _)| name.encode_utf16().eq(class_name.iter().copied()))
+        .map(|&(_, class)| class)
+        .unwrap_or(ControlClass::Other)
 }
 
 //
 // Hack to make `GetWindowSubclass` work on GNU
 //
 
-#[cfg(target_env="gnu")] use std::{sync::Mutex, collections::HashMap};
+#[cfg(target_env="gnu")] use std::sync::Mutex;
 
 #[cfg(target_env="gnu")]
 type SubclassId = (usize, usize, UINT_PTR);
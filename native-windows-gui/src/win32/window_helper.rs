@@ -1,4 +1,4 @@
-use super::base_helper::{to_utf16, from_utf16};
+use super::base_helper::{with_utf16, from_utf16};
 use super::high_dpi;
 use winapi::shared::windef::{HFONT, HWND, HMENU};
 use winapi::shared::minwindef::{UINT, WPARAM, LPARAM, LRESULT};
@@ -14,6 +14,15 @@ pub const NWG_INIT: UINT = WM_USER + 101;
 pub const NWG_TRAY: UINT = WM_USER + 102;
 pub const NWG_TIMER_TICK: UINT = WM_USER + 103;
 pub const NWG_TIMER_STOP: UINT = WM_USER + 104;
+pub const NWG_APP_EXIT: UINT = WM_USER + 105;
+pub const NWG_ANIMATION_END: UINT = WM_USER + 106;
+pub const NWG_JUMP_TASK: UINT = WM_USER + 107;
+pub const NWG_SCROLL_BY: UINT = WM_USER + 108;
+pub const NWG_JOB_PROGRESS: UINT = WM_USER + 109;
+pub const NWG_DRAG_DROP: UINT = WM_USER + 110;
+pub const NWG_SHELL_CHANGE: UINT = WM_USER + 111;
+pub const NWG_LOCAL_DRAG: UINT = WM_USER + 112;
+pub const NWG_IMAGE_DECODED: UINT = WM_USER + 113;
 
 
 /// Returns the class info of a hwnd handle
@@ -88,6 +97,7 @@ pub fn destroy_window(hwnd: HWND) {
         }
     });
 
+    super::resource_tracker::untrack(hwnd);
     unsafe { DestroyWindow(hwnd); }
 }
 
@@ -232,12 +242,27 @@ pub fn get_style(handle: HWND) -> UINT {
     get_window_long(handle, GWL_STYLE) as UINT
 }
 
-#[cfg(any(feature = "list-view", feature = "progress-bar"))]
+#[cfg(any(feature = "list-view", feature = "progress-bar", feature = "month-calendar", feature = "status-bar"))]
 pub fn set_style(handle: HWND, style: u32) {
     use ::winapi::um::winuser::GWL_STYLE;
     set_window_long(handle, GWL_STYLE, style as usize);
 }
 
+pub fn get_ex_style(handle: HWND) -> UINT {
+    use ::winapi::um::winuser::GWL_EXSTYLE;
+    get_window_long(handle, GWL_EXSTYLE) as UINT
+}
+
+/// Sets the extended style of a window and refreshes its frame
+pub fn set_ex_style(handle: HWND, style: u32) {
+    use ::winapi::um::winuser::{GWL_EXSTYLE, SetWindowPos, SWP_NOMOVE, SWP_NOSIZE, SWP_NOZORDER, SWP_NOACTIVATE, SWP_FRAMECHANGED};
+
+    set_window_long(handle, GWL_EXSTYLE, style as usize);
+    unsafe {
+        SetWindowPos(handle, ptr::null_mut(), 0, 0, 0, 0, SWP_NOMOVE | SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE | SWP_FRAMECHANGED);
+    }
+}
+
 pub fn send_message(hwnd: HWND, msg: UINT, w: WPARAM, l: LPARAM) -> LRESULT {
     unsafe { ::winapi::um::winuser::SendMessageW(hwnd, msg, w, l) }
 }
@@ -272,8 +297,7 @@ pub unsafe fn get_window_text(handle: HWND) -> String {
 pub unsafe fn set_window_text<'a>(handle: HWND, text: &'a str) {
     use winapi::um::winuser::SetWindowTextW;
 
-    let text = to_utf16(text);
-    SetWindowTextW(handle, text.as_ptr());
+    with_utf16(text, |text| { SetWindowTextW(handle, text.as_ptr()); });
 }
 
 pub unsafe fn set_window_position(handle: HWND, x: i32, y: i32) {
@@ -316,6 +340,24 @@ pub unsafe fn get_window_position(handle: HWND) -> (i32, i32) {
     high_dpi::physical_to_logical(x, y)
 }
 
+/// Returns the position and the size of the whole window (including the borders) in its parent client area, in logical units
+pub unsafe fn get_window_rect_in_parent(handle: HWND) -> (i32, i32, u32, u32) {
+    use winapi::um::winuser::{GetWindowRect, GetParent, MapWindowPoints};
+    use winapi::shared::windef::{RECT, POINT};
+
+    let mut r: RECT = mem::zeroed();
+    GetWindowRect(handle, &mut r);
+
+    let parent = GetParent(handle);
+    if !parent.is_null() {
+        MapWindowPoints(ptr::null_mut(), parent, &mut r as *mut RECT as *mut POINT, 2);
+    }
+
+    let (x, y) = high_dpi::physical_to_logical(r.left, r.top);
+    let (w, h) = high_dpi::physical_to_logical(r.right - r.left, r.bottom - r.top);
+    (x, y, w.max(0) as u32, h.max(0) as u32)
+}
+
 pub unsafe fn set_window_size(handle: HWND, w: u32, h: u32, fix: bool) {
     use winapi::um::winuser::{SetWindowPos, AdjustWindowRectEx, GetWindowLongW};
     use winapi::um::winuser::{SWP_NOZORDER, SWP_NOMOVE, SWP_NOACTIVATE, SWP_NOCOPYBITS, GWL_STYLE, GWL_EXSTYLE, SWP_NOOWNERZORDER};
@@ -439,69 +481,80 @@ pub fn set_window_long(handle: HWND, index: c_int, v: usize) {
     unsafe { ::winapi::um::winuser::SetWindowLongW(handle, index, v as LONG); }
 }
 
-// Helper to wrap DeferWindowPos somewhat safely
-// Mostly to streamline the usage of the returned HWDP from DeferWindowPos
-pub struct DeferredWindowPositioner {
-    handle: winapi::um::winuser::HDWP,
+/**
+    Collects the moves of sibling windows during a layout update and applies them in a single
+    `BeginDeferWindowPos`/`EndDeferWindowPos` batch, so that the children are repainted once.
+
+    Positions and sizes are in logical units. If the batch cannot be allocated, the windows are moved one by one.
+*/
+pub struct WindowPositionBatch {
+    moves: Vec<(HWND, Option<HWND>, c_int, c_int, c_int, c_int, UINT)>,
 }
 
-impl DeferredWindowPositioner {
-    const MEM_FAIL: &'static str = "Insufficient system resources";
-    
-    /// Initialises a new DeferredWindowPositioner with memory for `item_count` windows
-    pub fn new(item_count: i32) -> Result<DeferredWindowPositioner, &'static str> {
-        use ::winapi::um::winuser::BeginDeferWindowPos;
+impl WindowPositionBatch {
+
+    pub fn new(capacity: usize) -> WindowPositionBatch {
+        WindowPositionBatch { moves: Vec::with_capacity(capacity) }
+    }
 
-        let handle = unsafe { BeginDeferWindowPos(item_count) };
+    /// Moves and resizes a window without changing its z-order
+    pub fn move_window(&mut self, handle: HWND, x: i32, y: i32, w: u32, h: u32) {
+        use winapi::um::winuser::SWP_NOZORDER;
+        self.push(handle, None, (x, y), Some((w, h)), SWP_NOZORDER);
+    }
 
-        if handle.is_null() {
-            Err(DeferredWindowPositioner::MEM_FAIL)
-        }
-        else {
-            Ok(DeferredWindowPositioner {
-                handle
-            })
-        }
+    /// Moves and resizes a window, and places it after `after` in the z-order (or at the top if `after` is None)
+    pub fn move_window_after(&mut self, handle: HWND, after: Option<HWND>, x: i32, y: i32, w: u32, h: u32) {
+        self.push(handle, after, (x, y), Some((w, h)), 0);
+    }
+
+    /// Moves a window without changing its size, and places it after `after` in the z-order (or at the top if `after` is None)
+    pub fn set_position_after(&mut self, handle: HWND, after: Option<HWND>, x: i32, y: i32) {
+        self.push(handle, after, (x, y), None, 0);
     }
 
-    /// Defers a window positioning
-    pub fn defer_pos(
-        &mut self, 
-        hwnd: HWND,
-        hwnd_insertafter: HWND,
-        x: i32,
-        y: i32,
-        cx: i32,
-        cy: i32,
-    ) -> Result<(), &'static str> {
-        use ::winapi::um::winuser::DeferWindowPos;
-
-        let handle = unsafe {
-            DeferWindowPos(self.handle, hwnd, hwnd_insertafter, x, y, cx, cy, 0)
+    fn push(&mut self, handle: HWND, after: Option<HWND>, pos: (i32, i32), size: Option<(u32, u32)>, flags: UINT) {
+        use winapi::um::winuser::{SWP_NOSIZE, SWP_NOACTIVATE, SWP_NOOWNERZORDER, SWP_NOCOPYBITS};
+
+        let mut flags = flags | SWP_NOACTIVATE | SWP_NOOWNERZORDER | SWP_NOCOPYBITS;
+        let (x, y) = unsafe { high_dpi::logical_to_physical(pos.0, pos.1) };
+        let (w, h) = match size {
+            Some((w, h)) => unsafe { high_dpi::logical_to_physical(w as i32, h as i32) },
+            None => {
+                flags |= SWP_NOSIZE;
+                (0, 0)
+            }
         };
 
-        self.handle = handle;
+        self.moves.push((handle, after, x, y, w, h, flags));
+    }
 
-        if handle.is_null() {
-            Err(DeferredWindowPositioner::MEM_FAIL)
-        }
-        else {
-            Ok(())
+    /// Applies all the moves of the batch
+    pub fn apply(self) {
+        use winapi::um::winuser::{BeginDeferWindowPos, DeferWindowPos, EndDeferWindowPos, SetWindowPos, HWND_TOP};
+
+        if self.moves.is_empty() {
+            return;
         }
-    }
 
-    /// Ends the deferred operation list
-    /// This will apply the batched changes, unless an internal error occured in a defer_pos call
-    pub fn end(self) {} // Handled by drop impl
-}
+        unsafe {
+            let mut hdwp = BeginDeferWindowPos(self.moves.len() as c_int);
+            for &(handle, after, x, y, w, h, flags) in self.moves.iter() {
+                if hdwp.is_null() {
+                    break;
+                }
 
-impl std::ops::Drop for DeferredWindowPositioner {
-    fn drop(&mut self) {
-        use ::winapi::um::winuser::EndDeferWindowPos;
+                hdwp = DeferWindowPos(hdwp, handle, after.unwrap_or(HWND_TOP), x, y, w, h, flags);
+            }
 
-        // In case of previous error, no-op
-        if !self.handle.is_null() {
-            unsafe { EndDeferWindowPos(self.handle) };
+            // If DeferWindowPos fails, the batch is discarded by the system. Move the windows one by one instead.
+            match hdwp.is_null() {
+                false => { EndDeferWindowPos(hdwp); },
+                true => for &(handle, after, x, y, w, h, flags) in self.moves.iter() {
+                    SetWindowPos(handle, after.unwrap_or(HWND_TOP), x, y, w, h, flags);
+                }
+            }
         }
     }
-}
\ No newline at end of file
+
+}
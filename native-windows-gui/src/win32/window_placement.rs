@@ -0,0 +1,232 @@
+/*!
+    Saving and restoring the size, position and state of a window between two launches of the application.
+*/
+use winapi::shared::windef::{HWND, RECT};
+use winapi::um::winuser::WINDOWPLACEMENT;
+use super::monitor::{Monitor, monitor_info, fit_rect};
+use crate::ControlHandle;
+use std::{fmt, fs, io, mem, path::Path};
+
+/// Height of the part of the window that must stay on screen so that the user can move it
+const MIN_VISIBLE_HEIGHT: i32 = 32;
+
+/// Prefix of the serialized placements. Changed if the format changes.
+const FORMAT_VERSION: &'static str = "nwg-placement-1";
+
+
+/**
+    The size, position and state of a top level window, in screen coordinates.
+
+    `WindowPlacement::from_window` saves the placement of a window, and `apply` restores it. The placement can be
+    serialized with `to_string` and read back with `parse`, or written to a file with `save` and `load`.
+
+    When the placement is restored, the window is moved to the monitor where it was saved. If the monitor was
+    unplugged or if the monitors were rearranged so that the window would be out of reach, the window is moved
+    to the nearest monitor and shrunk to fit its work area.
+
+    Requires the `window-placement` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    const PLACEMENT_FILE: &str = "window.cfg";
+
+    fn restore(window: &nwg::Window) {
+        // Build the window with `visible(false)`. `apply` shows the window.
+        match nwg::WindowPlacement::load(PLACEMENT_FILE) {
+            Some(placement) => placement.apply(window),
+            None => window.set_visible(true)
+        }
+    }
+
+    fn save(window: &nwg::Window) {
+        // Called in `OnWindowClose`
+        nwg::WindowPlacement::from_window(window).save(PLACEMENT_FILE).ok();
+    }
+    ```
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WindowPlacement {
+    /// Position of the restored (not maximized) window
+    pub position: (i32, i32),
+
+    /// Size of the restored (not maximized) window, including the frame
+    pub size: (u32, u32),
+
+    /// If the window was maximized. A minimized window is saved in the state it had before it was minimized.
+    pub maximized: bool,
+
+    /// Device name of the monitor of the window (ex: `\\.\DISPLAY1`)
+    pub monitor: String,
+
+    /// The [left, top, right, bottom] rectangle of the monitor when the placement was saved
+    pub monitor_rect: [i32; 4],
+}
+
+impl WindowPlacement {
+
+    /**
+        Returns the placement of a window. If the window is maximized or minimized, the returned position and size
+        are the ones of the restored window.
+
+        Panics if `window` is not a window-like control.
+    */
+    pub fn from_window<C: Into<ControlHandle>>(window: C) -> WindowPlacement {
+        use winapi::um::winuser::{GetWindowPlacement, IsZoomed, MonitorFromRect, SW_SHOWMINIMIZED, WPF_RESTORETOMAXIMIZED, MONITOR_DEFAULTTONEAREST};
+
+        let handle = window.into().hwnd().expect("Window to be a window-like control");
+
+        unsafe {
+            let mut placement: WINDOWPLACEMENT = mem::zeroed();
+            placement.length = mem::size_of::<WINDOWPLACEMENT>() as u32;
+            GetWindowPlacement(handle, &mut placement);
+
+            let (dx, dy) = workspace_offset(handle);
+            let r = placement.rcNormalPosition;
+            let rect = RECT { left: r.left + dx, top: r.top + dy, right: r.right + dx, bottom: r.bottom + dy };
+
+            let maximized = match placement.showCmd as i32 == SW_SHOWMINIMIZED {
+                true => placement.flags & WPF_RESTORETOMAXIMIZED == WPF_RESTORETOMAXIMIZED,
+                false => IsZoomed(handle) != 0
+            };
+
+            let (monitor, monitor_rect) = monitor_info(MonitorFromRect(&rect, MONITOR_DEFAULTTONEAREST))
+                .map(|m| (m.name, m.rect))
+                .unwrap_or_default();
+
+            WindowPlacement {
+                position: (rect.left, rect.top),
+                size: ((rect.right - rect.left).max(0) as u32, (rect.bottom - rect.top).max(0) as u32),
+                maximized,
+                monitor,
+                monitor_rect,
+            }
+        }
+    }
+
+    /**
+        Moves and resizes a window to the placement, then maximizes it if the placement is maximized.
+        The position is corrected if the window would be off screen.
+
+        Because a window cannot be maximized without being shown, this function shows the window.
+        Build the window hidden and call `apply` instead of `set_visible(true)` to avoid flickering.
+
+        Panics if `window` is not a window-like control.
+    */
+    pub fn apply<C: Into<ControlHandle>>(&self, window: C) {
+        use winapi::um::winuser::{SetWindowPlacement, SW_SHOWMAXIMIZED, SW_SHOWNORMAL};
+
+        let handle = window.into().hwnd().expect("Window to be a window-like control");
+        let rect = self.visible_rect();
+
+        unsafe {
+            let (dx, dy) = workspace_offset(handle);
+
+            let mut placement: WINDOWPLACEMENT = mem::zeroed();
+            placement.length = mem::size_of::<WINDOWPLACEMENT>() as u32;
+            placement.showCmd = match self.maximized {
+                true => SW_SHOWMAXIMIZED as u32,
+                false => SW_SHOWNORMAL as u32
+            };
+            placement.rcNormalPosition = RECT { left: rect.left - dx, top: rect.top - dy, right: rect.right - dx, bottom: rect.bottom - dy };
+
+            SetWindowPlacement(handle, &placement);
+        }
+    }
+
+    /**
+        Reads a placement serialized with `to_string`. Returns `None` if the text is not a valid placement.
+    */
+    pub fn parse(text: &str) -> Option<WindowPlacement> {
+        let mut parts = text.trim().splitn(11, ',');
+        if parts.next()? != FORMAT_VERSION {
+            return None;
+        }
+
+        let mut number = || parts.next().and_then(|v| v.trim().parse::<i32>().ok());
+        let position = (number()?, number()?);
+        let size = (number()?.max(0) as u32, number()?.max(0) as u32);
+        let maximized = number()? != 0;
+        let monitor_rect = [number()?, number()?, number()?, number()?];
+        let monitor = parts.next().unwrap_or("").to_string();
+
+        Some(WindowPlacement { position, size, maximized, monitor, monitor_rect })
+    }
+
+    /**
+        Reads a placement written with `save`. Returns `None` if the file does not exist or is not a valid placement.
+    */
+    pub fn load<P: AsRef<Path>>(path: P) -> Option<WindowPlacement> {
+        let text = fs::read_to_string(path).ok()?;
+        WindowPlacement::parse(&text)
+    }
+
+    /**
+        Writes the placement to a file
+    */
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    /// Returns the rectangle of the restored window, moved on screen if needed
+    fn visible_rect(&self) -> RECT {
+        use winapi::um::winuser::{MonitorFromRect, MONITOR_DEFAULTTONULL, MONITOR_DEFAULTTONEAREST};
+
+        let (x, y) = self.position;
+        let (w, h) = (self.size.0 as i32, self.size.1 as i32);
+        let mut rect = RECT { left: x, top: y, right: x + w, bottom: y + h };
+
+        // The monitor still exists but was moved in the virtual screen: keep the window at the same place on the monitor
+        if let Some(current) = Monitor::all().into_iter().find(|m| !self.monitor.is_empty() && m.name.eq_ignore_ascii_case(&self.monitor)) {
+            let dx = current.rect[0] - self.monitor_rect[0];
+            let dy = current.rect[1] - self.monitor_rect[1];
+            rect = offset_rect(rect, dx, dy);
+        }
+
+        // The title bar must be on a monitor, otherwise the user cannot move the window
+        let title = RECT { left: rect.left, top: rect.top, right: rect.right, bottom: rect.top + MIN_VISIBLE_HEIGHT };
+        let on_screen = unsafe { !MonitorFromRect(&title, MONITOR_DEFAULTTONULL).is_null() };
+        if on_screen {
+            return rect;
+        }
+
+        match monitor_info(unsafe { MonitorFromRect(&rect, MONITOR_DEFAULTTONEAREST) }) {
+            Some(monitor) => fit_rect(rect, monitor.work_area),
+            None => rect
+        }
+    }
+
+}
+
+impl fmt::Display for WindowPlacement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let m = self.monitor_rect;
+        write!(f, "{},{},{},{},{},{},{},{},{},{},{}",
+            FORMAT_VERSION,
+            self.position.0, self.position.1,
+            self.size.0, self.size.1,
+            self.maximized as i32,
+            m[0], m[1], m[2], m[3],
+            self.monitor
+        )
+    }
+}
+
+/**
+    `GetWindowPlacement` uses workspace coordinates, relative to the work area of the primary monitor,
+    except for the tool windows. Returns the offset from the workspace coordinates to the screen coordinates.
+*/
+unsafe fn workspace_offset(handle: HWND) -> (i32, i32) {
+    use winapi::um::winuser::{GetWindowLongW, GWL_EXSTYLE, WS_EX_TOOLWINDOW};
+
+    if GetWindowLongW(handle, GWL_EXSTYLE) as u32 & WS_EX_TOOLWINDOW == WS_EX_TOOLWINDOW {
+        return (0, 0);
+    }
+
+    let primary = Monitor::primary();
+    (primary.work_area[0] - primary.rect[0], primary.work_area[1] - primary.rect[1])
+}
+
+fn offset_rect(rect: RECT, dx: i32, dy: i32) -> RECT {
+    RECT { left: rect.left + dx, top: rect.top + dy, right: rect.right + dx, bottom: rect.bottom + dy }
+}
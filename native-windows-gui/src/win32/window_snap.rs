@@ -0,0 +1,408 @@
+/*!
+    Magnetic snapping of tool windows to the edges of a main window.
+*/
+use winapi::shared::windef::{HWND, RECT};
+use super::window::{bind_raw_event_handler_inner, unbind_raw_event_handler, RawEventHandler};
+use super::high_dpi::logical_to_physical;
+use crate::controls::ControlHandle;
+use crate::NwgError;
+use std::{mem, ptr, rc::Rc, cell::RefCell};
+
+/// Id of the raw handlers bound to the main window and to the tool windows. Ids smaller than 0xFFFF are reserved by NWG.
+const SNAP_HANDLER_ID: usize = 0x5AA9;
+
+/// Default snap distance in logical pixels
+const DEFAULT_SNAP_DISTANCE: u32 = 12;
+
+
+/// The edge of the main window a tool window is docked to
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SnapSide {
+    Left,
+    Top,
+    Right,
+    Bottom,
+}
+
+struct ToolWindow {
+    handle: HWND,
+    /// The docked side, and the offset of the tool window along the edge (from the left or top of the main window)
+    dock: Option<(SnapSide, i32)>,
+}
+
+struct SnapState {
+    main: HWND,
+    distance: u32,
+    follow: bool,
+    tools: Vec<ToolWindow>,
+}
+
+impl Default for SnapState {
+    fn default() -> SnapState {
+        SnapState {
+            main: ptr::null_mut(),
+            distance: DEFAULT_SNAP_DISTANCE,
+            follow: true,
+            tools: Vec::new(),
+        }
+    }
+}
+
+/**
+    Makes tool windows (palettes, inspectors, ...) snap to the edges of a main window when the user moves them.
+
+    When a tool window is dropped against an edge of the main window, it is docked to that edge: if `follow` is enabled
+    (the default), the tool window moves with the main window, and stays against the edge when the main window is resized.
+    Moving the tool window away undocks it.
+
+    The snapping uses the visible bounds of the windows, so the invisible resize borders of Windows 10 and later do not
+    leave a gap between the windows.
+
+    Requires the `window-snap` feature.
+
+    ```rust
+    use native_windows_gui as nwg;
+
+    fn snap_palettes(snapper: &nwg::WindowSnapper, main: &nwg::Window, palette: &nwg::Window, inspector: &nwg::Window) {
+        snapper.set_main_window(main).expect("Failed to hook the main window");
+        snapper.add_tool_window(palette).expect("Failed to hook the palette");
+        snapper.add_tool_window(inspector).expect("Failed to hook the inspector");
+
+        snapper.dock(palette, nwg::SnapSide::Left);
+        snapper.dock(inspector, nwg::SnapSide::Right);
+    }
+    ```
+*/
+#[derive(Default)]
+pub struct WindowSnapper {
+    state: Rc<RefCell<SnapState>>,
+    main_handler: RefCell<Option<RawEventHandler>>,
+    tool_handlers: RefCell<Vec<(HWND, RawEventHandler)>>,
+}
+
+impl WindowSnapper {
+
+    /**
+        Sets the window that the tool windows snap to. Replaces the previous main window.
+
+        Panics if `window` is not a window-like control.
+    */
+    pub fn set_main_window<W: Into<ControlHandle>>(&self, window: W) -> Result<(), NwgError> {
+        use winapi::um::winuser::{WM_WINDOWPOSCHANGED, IsIconic};
+
+        let window = window.into();
+        let handle = window.hwnd().expect("Window to be a window-like control");
+
+        if let Some(handler) = self.main_handler.borrow_mut().take() {
+            drop(unbind_raw_event_handler(&handler));
+        }
+
+        let state = self.state.clone();
+        let handler = bind_raw_event_handler_inner(&window, SNAP_HANDLER_ID, move |hwnd, msg, _w, _l| {
+            if msg == WM_WINDOWPOSCHANGED && unsafe { IsIconic(hwnd) == 0 } {
+                let moves = match state.borrow().follow {
+                    true => docked_positions(&state.borrow()),
+                    false => Vec::new()
+                };
+
+                // The state is released before moving the windows because the move events may use the snapper
+                move_windows(&moves);
+            }
+
+            None
+        })?;
+
+        *self.main_handler.borrow_mut() = Some(handler);
+
+        let mut state = self.state.borrow_mut();
+        state.main = handle;
+        for i in 0..state.tools.len() {
+            let tool = state.tools[i].handle;
+            state.tools[i].dock = find_dock(handle, tool);
+        }
+
+        Ok(())
+    }
+
+    /**
+        Makes a window snap to the main window. If the window already touches an edge of the main window, it is docked.
+
+        Panics if `window` is not a window-like control.
+    */
+    pub fn add_tool_window<W: Into<ControlHandle>>(&self, window: W) -> Result<(), NwgError> {
+        use winapi::um::winuser::{WM_MOVING, WM_EXITSIZEMOVE};
+
+        let window = window.into();
+        let handle = window.hwnd().expect("Window to be a window-like control");
+
+        let state = self.state.clone();
+        let handler = bind_raw_event_handler_inner(&window, SNAP_HANDLER_ID, move |hwnd, msg, _w, l| {
+            match msg {
+                WM_MOVING => {
+                    let state = state.borrow();
+                    if !state.main.is_null() {
+                        let proposed = unsafe { &mut *(l as *mut RECT) };
+                        snap_rect(state.main, hwnd, proposed, px(state.distance as i32));
+                    }
+                },
+                WM_EXITSIZEMOVE => {
+                    let mut state = state.borrow_mut();
+                    let main = state.main;
+                    if let Some(tool) = state.tools.iter_mut().find(|t| t.handle == hwnd) {
+                        tool.dock = find_dock(main, hwnd);
+                    }
+                },
+                _ => {}
+            }
+
+            None
+        })?;
+
+        self.tool_handlers.borrow_mut().push((handle, handler));
+
+        let mut state = self.state.borrow_mut();
+        let dock = find_dock(state.main, handle);
+        state.tools.push(ToolWindow { handle, dock });
+
+        Ok(())
+    }
+
+    /**
+        Stops snapping a tool window. The window is not moved.
+    */
+    pub fn remove_tool_window<W: Into<ControlHandle>>(&self, window: W) {
+        let handle = match window.into().hwnd() {
+            Some(h) => h,
+            None => return
+        };
+
+        let mut handlers = self.tool_handlers.borrow_mut();
+        if let Some(index) = handlers.iter().position(|(h, _)| *h == handle) {
+            drop(unbind_raw_event_handler(&handlers.remove(index).1));
+        }
+
+        self.state.borrow_mut().tools.retain(|t| t.handle != handle);
+    }
+
+    /**
+        Moves a tool window against an edge of the main window, aligned with the top (or the left) of the main window, and docks it.
+        Does nothing if the main window is not set or if the window was not added with `add_tool_window`.
+    */
+    pub fn dock<W: Into<ControlHandle>>(&self, window: W, side: SnapSide) {
+        let handle = match window.into().hwnd() {
+            Some(h) => h,
+            None => return
+        };
+
+        let moves = {
+            let mut state = self.state.borrow_mut();
+            if state.main.is_null() {
+                return;
+            }
+
+            match state.tools.iter_mut().find(|t| t.handle == handle) {
+                Some(tool) => tool.dock = Some((side, 0)),
+                None => return
+            }
+
+            docked_positions(&state)
+        };
+
+        move_windows(&moves);
+    }
+
+    /**
+        Undocks a tool window: it stops following the main window until the user drops it against an edge again.
+    */
+    pub fn undock<W: Into<ControlHandle>>(&self, window: W) {
+        let handle = window.into().hwnd();
+        let mut state = self.state.borrow_mut();
+        if let Some(tool) = state.tools.iter_mut().find(|t| Some(t.handle) == handle) {
+            tool.dock = None;
+        }
+    }
+
+    /// Returns the edge of the main window a tool window is docked to, or `None` if the window is not docked
+    pub fn docked_side<W: Into<ControlHandle>>(&self, window: W) -> Option<SnapSide> {
+        let handle = window.into().hwnd()?;
+        self.state.borrow().tools.iter()
+            .find(|t| t.handle == handle)
+            .and_then(|t| t.dock)
+            .map(|(side, _)| side)
+    }
+
+    /// Returns the distance (in logical pixels) under which a tool window snaps to an edge
+    pub fn snap_distance(&self) -> u32 {
+        self.state.borrow().distance
+    }
+
+    /// Sets the distance (in logical pixels) under which a tool window snaps to an edge. `0` disables the snapping.
+    pub fn set_snap_distance(&self, distance: u32) {
+        self.state.borrow_mut().distance = distance;
+    }
+
+    /// Returns `true` if the docked tool windows follow the main window
+    pub fn follow(&self) -> bool {
+        self.state.borrow().follow
+    }
+
+    /// Sets if the docked tool windows follow the main window when it is moved or resized
+    pub fn set_follow(&self, follow: bool) {
+        self.state.borrow_mut().follow = follow;
+    }
+
+}
+
+impl Drop for WindowSnapper {
+    fn drop(&mut self) {
+        // The windows may have been destroyed before the snapper
+        if let Some(handler) = self.main_handler.borrow().as_ref() {
+            drop(unbind_raw_event_handler(handler));
+        }
+
+        for (_, handler) in self.tool_handlers.borrow().iter() {
+            drop(unbind_raw_event_handler(handler));
+        }
+    }
+}
+
+/// Converts a distance in logical pixels to physical pixels
+fn px(v: i32) -> i32 {
+    unsafe { logical_to_physical(v, 0).0 }
+}
+
+/// Returns the visible bounds of a window and its window rectangle.
+/// The visible bounds exclude the invisible resize borders added by Windows 10.
+fn window_bounds(handle: HWND) -> (RECT, RECT) {
+    use winapi::um::winuser::GetWindowRect;
+    use winapi::um::dwmapi::{DwmGetWindowAttribute, DWMWA_EXTENDED_FRAME_BOUNDS};
+    use winapi::shared::winerror::S_OK;
+
+    unsafe {
+        let mut window: RECT = mem::zeroed();
+        GetWindowRect(handle, &mut window);
+
+        let mut visible: RECT = mem::zeroed();
+        let hr = DwmGetWindowAttribute(handle, DWMWA_EXTENDED_FRAME_BOUNDS, &mut visible as *mut RECT as _, mem::size_of::<RECT>() as u32);
+        match hr == S_OK {
+            true => (visible, window),
+            false => (window, window)
+        }
+    }
+}
+
+/// Returns the `(left, top, right, bottom)` size of the invisible borders of a window
+fn invisible_borders(handle: HWND) -> (i32, i32, i32, i32) {
+    let (visible, window) = window_bounds(handle);
+    (visible.left - window.left, visible.top - window.top, window.right - visible.right, window.bottom - visible.bottom)
+}
+
+/// Returns the candidate offset with the smallest absolute value that is not greater than `distance`
+fn nearest(candidates: &[i32], distance: i32) -> Option<i32> {
+    candidates.iter()
+        .copied()
+        .filter(|d| d.abs() <= distance)
+        .min_by_key(|d| d.abs())
+}
+
+/// Moves the proposed window rectangle of a tool window (from `WM_MOVING`) so that it snaps to the edges of the main window
+fn snap_rect(main: HWND, tool: HWND, proposed: &mut RECT, distance: i32) {
+    if distance <= 0 {
+        return;
+    }
+
+    let (l, t, r, b) = invisible_borders(tool);
+    let v = RECT { left: proposed.left + l, top: proposed.top + t, right: proposed.right - r, bottom: proposed.bottom - b };
+    let (m, _) = window_bounds(main);
+
+    let facing_horizontally = v.top < m.bottom + distance && v.bottom > m.top - distance;
+    let facing_vertically = v.left < m.right + distance && v.right > m.left - distance;
+
+    // Outer edges: the tool window is put against the main window
+    let mut dx = match facing_horizontally {
+        true => nearest(&[m.right - v.left, m.left - v.right], distance),
+        false => None
+    };
+
+    let mut dy = match facing_vertically {
+        true => nearest(&[m.bottom - v.top, m.top - v.bottom], distance),
+        false => None
+    };
+
+    // Aligned edges: a tool window against an edge is aligned with the corners of the main window
+    if dx.is_some() && dy.is_none() {
+        dy = nearest(&[m.top - v.top, m.bottom - v.bottom], distance);
+    } else if dy.is_some() && dx.is_none() {
+        dx = nearest(&[m.left - v.left, m.right - v.right], distance);
+    }
+
+    let (dx, dy) = (dx.unwrap_or(0), dy.unwrap_or(0));
+    proposed.left += dx;
+    proposed.right += dx;
+    proposed.top += dy;
+    proposed.bottom += dy;
+}
+
+/// Returns the edge of the main window that a tool window touches, if any
+fn find_dock(main: HWND, tool: HWND) -> Option<(SnapSide, i32)> {
+    if main.is_null() {
+        return None;
+    }
+
+    let (m, _) = window_bounds(main);
+    let (v, _) = window_bounds(tool);
+
+    let overlap_vertically = v.top < m.bottom && v.bottom > m.top;
+    let overlap_horizontally = v.left < m.right && v.right > m.left;
+
+    if overlap_vertically && v.left == m.right {
+        Some((SnapSide::Right, v.top - m.top))
+    } else if overlap_vertically && v.right == m.left {
+        Some((SnapSide::Left, v.top - m.top))
+    } else if overlap_horizontally && v.top == m.bottom {
+        Some((SnapSide::Bottom, v.left - m.left))
+    } else if overlap_horizontally && v.bottom == m.top {
+        Some((SnapSide::Top, v.left - m.left))
+    } else {
+        None
+    }
+}
+
+/// Returns the window positions of the docked tool windows that must move to stay against the main window
+fn docked_positions(state: &SnapState) -> Vec<(HWND, i32, i32)> {
+    if state.main.is_null() {
+        return Vec::new();
+    }
+
+    let (m, _) = window_bounds(state.main);
+
+    state.tools.iter()
+        .filter_map(|tool| tool.dock.map(|dock| (tool.handle, dock)))
+        .filter_map(|(handle, (side, offset))| {
+            let (v, window) = window_bounds(handle);
+            let (width, height) = (v.right - v.left, v.bottom - v.top);
+
+            let (x, y) = match side {
+                SnapSide::Left => (m.left - width, m.top + offset),
+                SnapSide::Right => (m.right, m.top + offset),
+                SnapSide::Top => (m.left + offset, m.top - height),
+                SnapSide::Bottom => (m.left + offset, m.bottom),
+            };
+
+            // From the visible bounds to the window position
+            let (x, y) = (x - (v.left - window.left), y - (v.top - window.top));
+            match (x, y) != (window.left, window.top) {
+                true => Some((handle, x, y)),
+                false => None
+            }
+        })
+        .collect()
+}
+
+fn move_windows(moves: &[(HWND, i32, i32)]) {
+    use winapi::um::winuser::{SetWindowPos, SWP_NOSIZE, SWP_NOZORDER, SWP_NOACTIVATE};
+
+    for &(handle, x, y) in moves {
+        unsafe { SetWindowPos(handle, ptr::null_mut(), x, y, 0, 0, SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE); }
+    }
+}
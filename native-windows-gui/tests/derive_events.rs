@@ -0,0 +1,75 @@
+/*!
+    Tests the callbacks generated by the `nwg_events` attribute of the derive macro.
+    The derive output refers to `native_windows_gui` by name, so this test cannot live in `src/tests`.
+*/
+extern crate native_windows_gui as nwg;
+extern crate native_windows_derive as nwd;
+
+use nwd::NwgUi;
+use nwg::NativeUi;
+use std::cell::{Cell, RefCell};
+
+thread_local! {
+    static NO_PARAMS_CALLS: Cell<u32> = Cell::new(0);
+}
+
+
+#[derive(Default, NwgUi)]
+pub struct EventsApp {
+    #[nwg_control(size: (300, 200), position: (300, 300), title: "Derive events", flags: "WINDOW|VISIBLE")]
+    window: nwg::Window,
+
+    #[nwg_control(text: "Args", size: (280, 30), position: (10, 10))]
+    #[nwg_events( OnButtonClick: [EventsApp::with_args(SELF, CTRL, 2)] )]
+    args_button: nwg::Button,
+
+    #[nwg_control(text: "Path", size: (280, 30), position: (10, 50))]
+    #[nwg_events( OnButtonClick: EventsApp::without_brackets )]
+    path_button: nwg::Button,
+
+    #[nwg_control(text: "Closures", size: (280, 30), position: (10, 90))]
+    #[nwg_events( OnButtonClick: [
+        || NO_PARAMS_CALLS.with(|c| c.set(c.get() + 1)),
+        |app| app.record("one param".into()),
+        |app, data| app.record(format!("two params {}", matches!(data, nwg::EventData::NoData)))
+    ] )]
+    closures_button: nwg::Button,
+
+    calls: RefCell<Vec<String>>,
+}
+
+impl EventsApp {
+
+    fn with_args(&self, button: &nwg::Button, count: u32) {
+        self.record(format!("{} {}", button.text(), count));
+    }
+
+    fn without_brackets(&self) {
+        self.record("without brackets".into());
+    }
+
+    fn record(&self, call: String) {
+        self.calls.borrow_mut().push(call);
+    }
+
+}
+
+
+#[test]
+fn derive_event_callbacks() {
+    nwg::init().expect("Failed to init Native Windows GUI");
+
+    let app = EventsApp::build_ui(Default::default()).expect("Failed to build UI");
+
+    app.args_button.click();
+    app.path_button.click();
+    app.closures_button.click();
+
+    assert_eq!(NO_PARAMS_CALLS.with(|c| c.get()), 1);
+    assert_eq!(*app.calls.borrow(), vec![
+        "Args 2".to_string(),
+        "without brackets".to_string(),
+        "one param".to_string(),
+        "two params true".to_string(),
+    ]);
+}